@@ -7,7 +7,8 @@
 //! ## How it works:
 //! 1. At build time: Hash all cache-critical files and store as CACHE_SCHEMA_HASH env var
 //! 2. At runtime: Compare stored hash in meta.db with current CACHE_SCHEMA_HASH
-//! 3. On mismatch: Warn user and suggest `rfx index` to rebuild cache
+//! 3. On mismatch: `rfx index` rebuilds the cache even if no file changed, and queries
+//!    ask for that re-index (see src/migration.rs for explicit format versions)
 //!
 //! ## Cache-critical files:
 //! - src/cache.rs: SQLite schema definitions (files, statistics, config tables)
@@ -17,13 +18,60 @@
 //! - src/symbol_cache.rs: Symbol storage format
 //! - src/models.rs: Core data structures (Span, SymbolKind, SearchResult)
 //! - src/dependency.rs: Dependency extraction and storage
+//! - src/flags.rs: Feature flag usage storage
 //!
 //! Changes to these files may break compatibility with existing cache files.
+//!
+//! ## Parser versions:
+//! Each language also gets a parser version: a hash of its parser source files plus
+//! the tree-sitter grammar versions resolved in Cargo.lock. These are written to
+//! `$OUT_DIR/parser_versions.rs` and stored with every cached symbol set, so a
+//! grammar upgrade only invalidates the symbols of the affected languages.
+//!
+//! ## Test corpus:
+//! With the `testkit` feature enabled, every file under `tests/corpus` is embedded
+//! via `$OUT_DIR/testkit_corpus.rs` so `reflex::testkit` can materialize it anywhere.
 
 use std::collections::BTreeSet;
 use std::fs;
 use std::path::Path;
 
+/// Parser sources shared by every language (symbol post-processing lives here)
+const SHARED_PARSER_SOURCES: &[&str] = &[
+    "src/parsers/mod.rs",
+    "src/parsers/signature.rs",
+    "src/parsers/docs.rs",
+    "src/parsers/qualify.rs",
+    "src/parsers/visibility.rs",
+    "src/routes.rs",
+];
+
+/// Per-language parser inputs: (language key, parser source files, grammar crates)
+///
+/// Language keys match `ParserFactory::parser_version`.
+const PARSER_INPUTS: &[(&str, &[&str], &[&str])] = &[
+    ("rust", &["src/parsers/rust.rs"], &["tree-sitter-rust"]),
+    ("python", &["src/parsers/python.rs"], &["tree-sitter-python"]),
+    ("javascript", &["src/parsers/typescript.rs"], &["tree-sitter-typescript"]),
+    ("typescript", &["src/parsers/typescript.rs"], &["tree-sitter-typescript"]),
+    ("vue", &["src/parsers/vue.rs"], &["tree-sitter-typescript"]),
+    ("svelte", &["src/parsers/svelte.rs"], &["tree-sitter-typescript"]),
+    ("go", &["src/parsers/go.rs"], &["tree-sitter-go"]),
+    ("java", &["src/parsers/java.rs"], &["tree-sitter-java"]),
+    ("php", &["src/parsers/php.rs"], &["tree-sitter-php"]),
+    ("c", &["src/parsers/c.rs"], &["tree-sitter-c"]),
+    ("cpp", &["src/parsers/cpp.rs"], &["tree-sitter-cpp"]),
+    ("csharp", &["src/parsers/csharp.rs"], &["tree-sitter-c-sharp"]),
+    ("ruby", &["src/parsers/ruby.rs"], &["tree-sitter-ruby"]),
+    ("kotlin", &["src/parsers/kotlin.rs"], &["tree-sitter-kotlin-ng"]),
+    ("zig", &["src/parsers/zig.rs"], &["tree-sitter-zig"]),
+    ("shell", &["src/parsers/shell.rs"], &[]),
+    ("sql", &["src/parsers/sql.rs"], &[]),
+    ("make", &["src/parsers/make.rs"], &[]),
+    ("cmake", &["src/parsers/cmake.rs"], &[]),
+    ("bazel", &["src/parsers/bazel.rs"], &[]),
+];
+
 /// Cache-critical source files that affect binary format compatibility
 const CACHE_CRITICAL_FILES: &[&str] = &[
     "src/cache.rs",
@@ -33,6 +81,7 @@ const CACHE_CRITICAL_FILES: &[&str] = &[
     "src/symbol_cache.rs",
     "src/models.rs",
     "src/dependency.rs",
+    "src/flags.rs",
 ];
 
 fn main() {
@@ -48,6 +97,123 @@ fn main() {
     }
 
     println!("cargo:warning=Cache schema hash: {}", schema_hash);
+
+    // Per-language parser versions for symbol cache invalidation
+    write_parser_versions();
+
+    // Embedded test corpus for `reflex::testkit`
+    if std::env::var_os("CARGO_FEATURE_TESTKIT").is_some() {
+        write_testkit_corpus();
+    }
+
+    // Target triple used by `rfx self-update` to pick the matching release artifact
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=RFX_BUILD_TARGET={}", target);
+}
+
+/// Write `$OUT_DIR/parser_versions.rs` with one version hash per language
+fn write_parser_versions() {
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let core_version = locked_version(&lock, "tree-sitter");
+
+    let mut entries = String::new();
+    for (language, sources, grammars) in PARSER_INPUTS {
+        let mut hasher = blake3::Hasher::new();
+
+        // Shared parser code and the tree-sitter runtime affect every language
+        for shared in SHARED_PARSER_SOURCES {
+            hasher.update(&fs::read(shared).unwrap_or_default());
+        }
+        hasher.update(core_version.as_bytes());
+
+        for source in *sources {
+            hasher.update(source.as_bytes());
+            hasher.update(&fs::read(source).unwrap_or_default());
+        }
+        for grammar in *grammars {
+            hasher.update(grammar.as_bytes());
+            hasher.update(locked_version(&lock, grammar).as_bytes());
+        }
+
+        let hash = hasher.finalize();
+        let version: String = hash.as_bytes()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        entries.push_str(&format!("    (\"{}\", \"{}\"),\n", language, version));
+    }
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let contents = format!(
+        "/// Parser version per language key (generated by build.rs)\n\
+         const PARSER_VERSIONS: &[(&str, &str)] = &[\n{}];\n",
+        entries
+    );
+    fs::write(Path::new(&out_dir).join("parser_versions.rs"), contents)
+        .expect("Failed to write parser_versions.rs");
+
+    println!("cargo:rerun-if-changed=src/parsers");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
+
+/// Write `$OUT_DIR/testkit_corpus.rs` embedding every file under `tests/corpus`
+fn write_testkit_corpus() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    let corpus = Path::new(&manifest_dir).join("tests/corpus");
+
+    let mut files = BTreeSet::new();
+    collect_files(&corpus, &corpus, &mut files);
+
+    let mut entries = String::new();
+    for relative in &files {
+        let absolute = corpus.join(relative);
+        entries.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            relative,
+            absolute.to_string_lossy()
+        ));
+    }
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let contents = format!(
+        "/// Test corpus files as (relative path, contents) (generated by build.rs)\n\
+         const CORPUS_FILES: &[(&str, &[u8])] = &[\n{}];\n",
+        entries
+    );
+    fs::write(Path::new(&out_dir).join("testkit_corpus.rs"), contents)
+        .expect("Failed to write testkit_corpus.rs");
+
+    println!("cargo:rerun-if-changed=tests/corpus");
+}
+
+/// Recursively collect file paths under `dir`, relative to `base` with `/` separators
+fn collect_files(base: &Path, dir: &Path, files: &mut BTreeSet<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(base, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            files.insert(parts.join("/"));
+        }
+    }
+}
+
+/// Version of `package` resolved in Cargo.lock (empty if not locked)
+fn locked_version(lock: &str, package: &str) -> String {
+    let name_line = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line
+            && let Some(version) = lines.next().and_then(|l| l.trim().strip_prefix("version = "))
+        {
+            return version.trim_matches('"').to_string();
+        }
+    }
+    String::new()
 }
 
 /// Compute a deterministic hash of all cache-critical source files
@@ -0,0 +1,14 @@
+//! Fuzz the Go parser entry point (symbols, signatures, docs)
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use reflex::models::Language;
+use reflex::parsers::ParserFactory;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(source) = std::str::from_utf8(data) {
+        // Errors are fine; panics are what we're looking for
+        let _ = ParserFactory::parse("fuzz.go", source, Language::Go);
+    }
+});
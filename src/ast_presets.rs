@@ -0,0 +1,449 @@
+//! Named AST query presets
+//!
+//! Writing Tree-sitter S-expressions by hand is the main barrier to `--ast`, so
+//! common structural searches ship as named presets:
+//!
+//! ```bash
+//! rfx query --ast-preset async-functions --lang rust
+//! rfx query --ast-preset unused-params --lang python --glob "src/**"
+//! ```
+//!
+//! Projects add their own (or override a built-in of the same name) with one
+//! query file per preset in `.reflex/ast_presets/<language>/<name>.scm`, where
+//! `<language>` is the lowercase language name (`rust`, `typescript`, `cpp`,
+//! `csharp`, ...). Leading `;` comment lines are the preset's description:
+//!
+//! ```scheme
+//! ; Handlers registered on the router
+//! (call_expression
+//!   function: (field_expression field: (field_identifier) @method)
+//!   (#eq? @method "route")) @call
+//! ```
+//!
+//! Besides the standard `#eq?`/`#match?`/`#any-of?` predicates, patterns can use
+//! `(#unused? @name @scope)`, which keeps a match only when the identifier
+//! captured as `@name` is not referenced anywhere in `@scope` (see `ast_query`).
+
+use anyhow::{Context, Result};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::models::Language;
+
+/// Directory under `.reflex/` holding project presets
+pub const AST_PRESETS_DIR: &str = "ast_presets";
+
+// JavaScript is parsed with the TSX grammar, so it shares TypeScript's patterns
+const ECMASCRIPT_ASYNC_FUNCTIONS: &str =
+    r#"[(function_declaration "async") (function_expression "async") (method_definition "async") (arrow_function "async")] @fn"#;
+const ECMASCRIPT_UNUSED_PARAMS: &str = r#"([(function_declaration
+    parameters: (formal_parameters [(required_parameter pattern: (identifier) @param) (optional_parameter pattern: (identifier) @param)])
+    body: (statement_block) @body)
+  (method_definition
+    parameters: (formal_parameters [(required_parameter pattern: (identifier) @param) (optional_parameter pattern: (identifier) @param)])
+    body: (statement_block) @body)] @fn
+  (#not-match? @param "^_")
+  (#unused? @param @body))"#;
+const ECMASCRIPT_EMPTY_CATCH: &str = r#"(catch_clause body: (statement_block) @body (#match? @body "^\\{\\s*\\}$")) @catch"#;
+const ECMASCRIPT_CONSOLE_CALLS: &str = r#"(call_expression
+  function: (member_expression object: (identifier) @object)
+  (#eq? @object "console")) @call"#;
+
+/// Built-in presets: (name, language, description, pattern)
+const BUILTIN_PRESETS: &[(&str, Language, &str, &str)] = &[
+    // async-functions
+    (
+        "async-functions",
+        Language::Rust,
+        "Async functions and methods",
+        r#"(function_item (function_modifiers "async")) @fn"#,
+    ),
+    (
+        "async-functions",
+        Language::Python,
+        "Async functions and methods",
+        r#"(function_definition "async") @fn"#,
+    ),
+    (
+        "async-functions",
+        Language::JavaScript,
+        "Async functions, methods and arrow functions",
+        ECMASCRIPT_ASYNC_FUNCTIONS,
+    ),
+    (
+        "async-functions",
+        Language::TypeScript,
+        "Async functions, methods and arrow functions",
+        ECMASCRIPT_ASYNC_FUNCTIONS,
+    ),
+    // unused-params
+    (
+        "unused-params",
+        Language::Rust,
+        "Function parameters never referenced in the body (`_`-prefixed ones excluded)",
+        r#"(function_item
+  parameters: (parameters (parameter pattern: (identifier) @param))
+  body: (block) @body
+  (#not-match? @param "^_")
+  (#unused? @param @body)) @fn"#,
+    ),
+    (
+        "unused-params",
+        Language::Python,
+        "Function parameters never referenced in the body (`self`, `cls` and `_`-prefixed ones excluded)",
+        r#"(function_definition
+  parameters: (parameters [(identifier) @param (typed_parameter . (identifier) @param) (default_parameter name: (identifier) @param) (typed_default_parameter name: (identifier) @param)])
+  body: (block) @body
+  (#not-match? @param "^(_|self$|cls$)")
+  (#unused? @param @body)) @fn"#,
+    ),
+    (
+        "unused-params",
+        Language::JavaScript,
+        "Function parameters never referenced in the body (`_`-prefixed ones excluded)",
+        ECMASCRIPT_UNUSED_PARAMS,
+    ),
+    (
+        "unused-params",
+        Language::TypeScript,
+        "Function parameters never referenced in the body (`_`-prefixed ones excluded)",
+        ECMASCRIPT_UNUSED_PARAMS,
+    ),
+    (
+        "unused-params",
+        Language::Go,
+        "Function and method parameters never referenced in the body (`_` excluded)",
+        r#"([(function_declaration
+    parameters: (parameter_list (parameter_declaration name: (identifier) @param))
+    body: (block) @body)
+  (method_declaration
+    parameters: (parameter_list (parameter_declaration name: (identifier) @param))
+    body: (block) @body)] @fn
+  (#not-eq? @param "_")
+  (#unused? @param @body))"#,
+    ),
+    // empty-catch
+    (
+        "empty-catch",
+        Language::Java,
+        "Catch blocks with an empty body",
+        r#"(catch_clause body: (block) @body (#match? @body "^\\{\\s*\\}$")) @catch"#,
+    ),
+    (
+        "empty-catch",
+        Language::JavaScript,
+        "Catch blocks with an empty body",
+        ECMASCRIPT_EMPTY_CATCH,
+    ),
+    (
+        "empty-catch",
+        Language::TypeScript,
+        "Catch blocks with an empty body",
+        ECMASCRIPT_EMPTY_CATCH,
+    ),
+    (
+        "empty-catch",
+        Language::PHP,
+        "Catch blocks with an empty body",
+        r#"(catch_clause body: (compound_statement) @body (#match? @body "^\\{\\s*\\}$")) @catch"#,
+    ),
+    (
+        "empty-catch",
+        Language::Python,
+        "Except clauses whose body is only `pass`",
+        r#"(except_clause (block . (pass_statement) .)) @catch"#,
+    ),
+    // Language-specific
+    (
+        "unsafe-blocks",
+        Language::Rust,
+        "`unsafe` blocks",
+        r#"(unsafe_block) @unsafe"#,
+    ),
+    (
+        "unwrap-calls",
+        Language::Rust,
+        "`.unwrap()` and `.expect(..)` calls",
+        r#"(call_expression
+  function: (field_expression field: (field_identifier) @method)
+  (#any-of? @method "unwrap" "expect")) @call"#,
+    ),
+    (
+        "bare-except",
+        Language::Python,
+        "`except:` clauses that catch everything",
+        r#"(except_clause . ":") @catch"#,
+    ),
+    (
+        "console-log",
+        Language::JavaScript,
+        "`console.*` calls",
+        ECMASCRIPT_CONSOLE_CALLS,
+    ),
+    (
+        "console-log",
+        Language::TypeScript,
+        "`console.*` calls",
+        ECMASCRIPT_CONSOLE_CALLS,
+    ),
+];
+
+/// A named AST pattern for one language
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstPreset {
+    pub name: String,
+    pub language: Language,
+    pub description: String,
+    /// Tree-sitter query S-expression
+    pub pattern: String,
+    /// Shipped with reflex (false for `.reflex/ast_presets/` files)
+    pub builtin: bool,
+}
+
+/// Built-in presets
+pub fn builtin_presets() -> Vec<AstPreset> {
+    BUILTIN_PRESETS
+        .iter()
+        .map(|&(name, language, description, pattern)| AstPreset {
+            name: name.to_string(),
+            language,
+            description: description.to_string(),
+            pattern: pattern.to_string(),
+            builtin: true,
+        })
+        .collect()
+}
+
+/// Built-in presets followed by the project's
+///
+/// A project preset replaces the built-in of the same name and language.
+pub fn load_presets(cache_path: &Path) -> Result<Vec<AstPreset>> {
+    let mut presets = builtin_presets();
+
+    for preset in load_project_presets(&cache_path.join(AST_PRESETS_DIR))? {
+        presets.retain(|p| p.name != preset.name || p.language != preset.language);
+        presets.push(preset);
+    }
+
+    Ok(presets)
+}
+
+/// Look up a preset by name for `language`
+///
+/// The error lists the presets available for the language.
+pub fn find_preset(cache_path: &Path, name: &str, language: Language) -> Result<AstPreset> {
+    let presets = load_presets(cache_path)?;
+    let available: Vec<&str> = presets
+        .iter()
+        .filter(|p| p.language == language)
+        .map(|p| p.name.as_str())
+        .collect();
+
+    if let Some(preset) = presets.iter().find(|p| p.name == name && p.language == language) {
+        return Ok(preset.clone());
+    }
+
+    let other_languages: Vec<String> = presets
+        .iter()
+        .filter(|p| p.name == name)
+        .map(|p| format!("{:?}", p.language).to_lowercase())
+        .collect();
+
+    let mut message = format!("Unknown AST preset '{}' for {:?}", name, language);
+    if !other_languages.is_empty() {
+        message.push_str(&format!(" (available for: {})", other_languages.join(", ")));
+    }
+    if available.is_empty() {
+        message.push_str(&format!(
+            "\n\nNo presets for {:?} yet; add one in .reflex/{}/",
+            language, AST_PRESETS_DIR
+        ));
+    } else {
+        message.push_str(&format!("\n\nPresets for {:?}: {}", language, available.join(", ")));
+    }
+    anyhow::bail!(message)
+}
+
+/// Read `<dir>/<language>/<name>.scm` files
+fn load_project_presets(dir: &Path) -> Result<Vec<AstPreset>> {
+    let mut presets = Vec::new();
+    if !dir.is_dir() {
+        return Ok(presets);
+    }
+
+    for lang_entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let lang_dir = lang_entry?.path();
+        if !lang_dir.is_dir() {
+            continue;
+        }
+        let Some(language) = lang_dir.file_name().and_then(|n| n.to_str()).and_then(parse_language_dir) else {
+            log::warn!("Ignoring AST presets in {}: unknown language", lang_dir.display());
+            continue;
+        };
+
+        for entry in std::fs::read_dir(&lang_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("scm") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let pattern = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read AST preset {}", path.display()))?;
+            let description = pattern
+                .lines()
+                .map(str::trim)
+                .take_while(|line| line.starts_with(';'))
+                .map(|line| line.trim_start_matches(';').trim())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            presets.push(AstPreset {
+                name: name.to_string(),
+                language,
+                description,
+                pattern,
+                builtin: false,
+            });
+        }
+    }
+
+    Ok(presets)
+}
+
+/// Language of a preset directory name (its serialized name, e.g. `typescript`)
+fn parse_language_dir(name: &str) -> Option<Language> {
+    let deserializer: serde::de::value::StrDeserializer<'_, serde::de::value::Error> = name.into_deserializer();
+    Language::deserialize(deserializer).ok().filter(|lang| *lang != Language::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_query::execute_ast_query;
+    use crate::models::{SearchResult, Span, SymbolKind};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn run(preset: &str, language: Language, path: &str, content: &str) -> Vec<SearchResult> {
+        let preset = find_preset(Path::new("/nonexistent"), preset, language).unwrap();
+
+        let mut file_contents = HashMap::new();
+        file_contents.insert(path.to_string(), content.to_string());
+        let candidates = vec![SearchResult {
+            path: path.to_string(),
+            lang: language,
+            span: Span { start_line: 1, end_line: 1 },
+            symbol: None,
+            parent: None,
+            qualified_name: None,
+            kind: SymbolKind::Unknown("ast_query".to_string()),
+            preview: String::new(),
+            dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
+        }];
+        execute_ast_query(candidates, &preset.pattern, language, &file_contents).unwrap()
+    }
+
+    fn captured(results: &[SearchResult], capture: &str) -> Vec<String> {
+        results
+            .iter()
+            .map(|r| r.captures.as_ref().unwrap()[capture][0].text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_presets_compile() {
+        for preset in builtin_presets() {
+            let grammar = crate::parsers::ParserFactory::get_language_grammar(preset.language).unwrap();
+            if let Err(e) = tree_sitter::Query::new(&grammar, &preset.pattern) {
+                panic!("preset {} for {:?} does not compile: {}", preset.name, preset.language, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_async_functions() {
+        let results = run(
+            "async-functions",
+            Language::Rust,
+            "lib.rs",
+            "async fn fetch() {}\nfn sync() {}\npub async fn load() {}\n",
+        );
+        let names: Vec<_> = results.iter().filter_map(|r| r.symbol.as_deref()).collect();
+        assert_eq!(names, vec!["fetch", "load"]);
+    }
+
+    #[test]
+    fn test_unused_params() {
+        let results = run(
+            "unused-params",
+            Language::Rust,
+            "lib.rs",
+            "fn add(a: i32, b: i32, _c: i32) -> i32 {\n    a + a\n}\n",
+        );
+        assert_eq!(captured(&results, "param"), vec!["b"]);
+
+        let results = run(
+            "unused-params",
+            Language::Python,
+            "app.py",
+            "def handler(self, request, extra=None):\n    return request.body\n",
+        );
+        assert_eq!(captured(&results, "param"), vec!["extra"]);
+
+        let results = run(
+            "unused-params",
+            Language::JavaScript,
+            "app.js",
+            "function render(props, context) {\n  return props.title;\n}\n",
+        );
+        assert_eq!(captured(&results, "param"), vec!["context"]);
+    }
+
+    #[test]
+    fn test_empty_catch() {
+        let results = run(
+            "empty-catch",
+            Language::JavaScript,
+            "app.js",
+            "try { a() } catch (e) {}\ntry { b() } catch (e) { log(e) }\n",
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].span.start_line, 1);
+
+        let source = "try:\n    a()\nexcept ValueError:\n    pass\ntry:\n    b()\nexcept:\n    log()\n";
+        let results = run("empty-catch", Language::Python, "app.py", source);
+        assert_eq!(results.iter().map(|r| r.span.start_line).collect::<Vec<_>>(), vec![3]);
+        let results = run("bare-except", Language::Python, "app.py", source);
+        assert_eq!(results.iter().map(|r| r.span.start_line).collect::<Vec<_>>(), vec![7]);
+    }
+
+    #[test]
+    fn test_project_presets_override_builtins() {
+        let temp = TempDir::new().unwrap();
+        let rust_dir = temp.path().join(AST_PRESETS_DIR).join("rust");
+        std::fs::create_dir_all(&rust_dir).unwrap();
+        std::fs::write(rust_dir.join("structs.scm"), "; Struct definitions\n(struct_item) @struct\n").unwrap();
+        std::fs::write(rust_dir.join("unsafe-blocks.scm"), "(unsafe_block) @block\n").unwrap();
+
+        let preset = find_preset(temp.path(), "structs", Language::Rust).unwrap();
+        assert_eq!(preset.description, "Struct definitions");
+        assert!(!preset.builtin);
+
+        let preset = find_preset(temp.path(), "unsafe-blocks", Language::Rust).unwrap();
+        assert_eq!(preset.pattern, "(unsafe_block) @block\n");
+        assert_eq!(load_presets(temp.path()).unwrap().iter().filter(|p| p.name == "unsafe-blocks").count(), 1);
+
+        let err = find_preset(temp.path(), "structs", Language::Python).unwrap_err().to_string();
+        assert!(err.contains("available for: rust"), "{}", err);
+    }
+}
@@ -30,9 +30,17 @@
 //!     lang: Language::Rust,
 //!     span: Span { start_line: 1, end_line: 1 },
 //!     symbol: None,
+//!     parent: None,
+//!     qualified_name: None,
 //!     kind: SymbolKind::Unknown("text_match".to_string()),
 //!     preview: String::new(),
 //!     dependencies: None,
+//!     repo: None,
+//!     signature: None,
+//!     doc: None,
+//!     exported: None,
+//!     captures: None,
+//!     score: None,
 //! }];
 //!
 //! // File contents map
@@ -46,11 +54,11 @@
 //! # }
 //! ```
 
-use crate::models::{Language, SearchResult, Span, SymbolKind};
+use crate::models::{AstCapture, AstCaptures, Language, SearchResult, Span, SymbolKind};
 use anyhow::{anyhow, Context, Result};
 use std::collections::HashMap;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{Query, QueryCursor, QueryPredicateArg};
 
 /// Execute an AST query pattern against candidate files
 ///
@@ -66,7 +74,13 @@ use tree_sitter::{Parser, Query, QueryCursor};
 ///
 /// # Returns
 ///
-/// Filtered list of search results matching the AST pattern
+/// Filtered list of search results matching the AST pattern: one per match,
+/// spanning its outermost captured node, with every named capture in `captures`
+///
+/// # Cancellation
+///
+/// Under an entered [`crate::cancel::CancelToken`], parsing and matching stop
+/// at its deadline and the matches found so far are returned.
 ///
 /// # Errors
 ///
@@ -82,7 +96,7 @@ pub fn execute_ast_query(
 ) -> Result<Vec<SearchResult>> {
 
     // Get Tree-sitter grammar for the language
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let ts_language = get_tree_sitter_language(language)?;
     parser
         .set_language(&ts_language)
@@ -91,6 +105,7 @@ pub fn execute_ast_query(
     // Compile the AST query pattern
     let query = Query::new(&ts_language, ast_pattern)
         .map_err(|e| anyhow!("Invalid AST query pattern: {}", e))?;
+    let capture_names = query.capture_names();
 
     // Group candidates by file for efficient parsing
     let mut files_to_parse: HashMap<String, Vec<SearchResult>> = HashMap::new();
@@ -106,6 +121,11 @@ pub fn execute_ast_query(
 
     // Parse each file and execute query
     for (file_path, _candidates_in_file) in files_to_parse {
+        // Out of time: keep the matches found so far
+        if crate::cancel::cancelled() {
+            break;
+        }
+
         // Get file content
         let content = match file_contents.get(&file_path) {
             Some(c) => c,
@@ -117,8 +137,10 @@ pub fn execute_ast_query(
         };
 
         // Parse file with Tree-sitter
+        parser.set_timeout_micros(crate::cancel::timeout_micros());
         let tree = match parser.parse(content, None) {
             Some(t) => t,
+            None if crate::cancel::cancelled() => break,
             None => {
                 log::warn!("Failed to parse file: {}", file_path);
                 continue;
@@ -127,6 +149,7 @@ pub fn execute_ast_query(
 
         // Execute query on the AST
         let mut cursor = QueryCursor::new();
+        cursor.set_timeout_micros(crate::cancel::timeout_micros());
         let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
 
         // Process each match from the cursor
@@ -137,45 +160,110 @@ pub fn execute_ast_query(
                 continue;
             }
 
+            if !general_predicates_hold(&query, m, content) {
+                continue;
+            }
+
+            // One result per match, spanning its outermost captured node; the
+            // other captures (e.g. `@name`, `@params`) are exposed by name
+            let mut captures = AstCaptures::new();
             for capture in m.captures {
+                let name = capture_names[capture.index as usize];
+                // Bookkeeping of compiled structural patterns
+                if name.starts_with(crate::structural::INTERNAL_CAPTURE_PREFIX) {
+                    continue;
+                }
                 let node = capture.node;
-                let start_byte = node.start_byte();
-                let end_byte = node.end_byte();
-                let start_pos = node.start_position();
-                let end_pos = node.end_position();
-
-                // Extract matched text
-                let matched_text = &content[start_byte..end_byte];
-
-                // Try to determine symbol name and kind
-                let (symbol_name, symbol_kind) = extract_symbol_info(&node, content);
-
-                // Detect language from file extension
-                let ext = std::path::Path::new(&file_path)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-                let detected_lang = Language::from_extension(ext);
-
-                matched_results.push(SearchResult {
-                    path: file_path.clone(),
-                    lang: detected_lang,
-                    span: Span {
-                        start_line: start_pos.row + 1, // Tree-sitter uses 0-indexed lines
-                        end_line: end_pos.row + 1,
-                    },
-                    symbol: symbol_name,
-                    kind: symbol_kind.unwrap_or_else(|| SymbolKind::Unknown("ast_match".to_string())),
-                    preview: matched_text.to_string(),
-                    dependencies: None,
-                });
+                captures
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(AstCapture {
+                        kind: node.kind().to_string(),
+                        text: content[node.byte_range()].to_string(),
+                        span: node_span(&node),
+                    });
             }
+
+            let node = m
+                .captures
+                .iter()
+                .map(|capture| capture.node)
+                .min_by_key(|node| (node.start_byte(), std::cmp::Reverse(node.end_byte())))
+                .expect("match has captures");
+
+            // Try to determine symbol name and kind
+            let (symbol_name, symbol_kind) = extract_symbol_info(&node, content);
+
+            // Detect language from the file name
+            let detected_lang = Language::from_path(std::path::Path::new(&file_path));
+
+            matched_results.push(SearchResult {
+                path: file_path.clone(),
+                lang: detected_lang,
+                span: node_span(&node),
+                symbol: symbol_name,
+                parent: None,
+                qualified_name: None,
+                kind: symbol_kind.unwrap_or_else(|| SymbolKind::Unknown("ast_match".to_string())),
+                preview: content[node.byte_range()].to_string(),
+                dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: Some(captures),
+                score: None,
+            });
         }
     }
 
     Ok(matched_results)
 }
 
+/// Check the predicates Tree-sitter leaves to the caller
+///
+/// Supports `(#unused? @name @scope)`: the identifier captured as `@name` must
+/// not occur (as a whole word) anywhere in `@scope` other than at `@name` itself.
+/// Unknown predicates are ignored.
+fn general_predicates_hold(query: &Query, m: &tree_sitter::QueryMatch, content: &str) -> bool {
+    query.general_predicates(m.pattern_index).iter().all(|predicate| {
+        match (predicate.operator.as_ref(), predicate.args.as_ref()) {
+            ("unused?", [QueryPredicateArg::Capture(name), QueryPredicateArg::Capture(scope)]) => {
+                let nodes = |index: u32| m.captures.iter().filter(move |c| c.index == index).map(|c| c.node);
+                nodes(*name).all(|name_node| {
+                    nodes(*scope).all(|scope_node| !is_referenced(content, name_node.byte_range(), scope_node.byte_range()))
+                })
+            }
+            _ => true,
+        }
+    })
+}
+
+/// Whether the identifier at `name` occurs as a whole word in `scope`, outside `name`
+fn is_referenced(content: &str, name: std::ops::Range<usize>, scope: std::ops::Range<usize>) -> bool {
+    let ident = &content[name.clone()];
+    if ident.is_empty() {
+        return false;
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    content[scope.clone()].match_indices(ident).any(|(offset, _)| {
+        let start = scope.start + offset;
+        let end = start + ident.len();
+        start != name.start
+            && !content[..start].chars().next_back().is_some_and(is_word)
+            && !content[end..].chars().next().is_some_and(is_word)
+    })
+}
+
+/// Line span of a node (Tree-sitter rows are 0-indexed)
+fn node_span(node: &tree_sitter::Node) -> Span {
+    Span {
+        start_line: node.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+    }
+}
+
 /// Get Tree-sitter language grammar for a given language
 ///
 /// Delegates to ParserFactory::get_language_grammar() for centralized grammar loading.
@@ -310,9 +398,17 @@ fn sync_helper() {
                 end_line: 1,
             },
             symbol: None,
+            parent: None,
+            qualified_name: None,
             kind: SymbolKind::Unknown("text_match".to_string()),
             preview: String::new(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }];
 
         // Query for all functions - using capture syntax @fn
@@ -350,9 +446,17 @@ struct Config {
                 end_line: 1,
             },
             symbol: None,
+            parent: None,
+            qualified_name: None,
             kind: SymbolKind::Unknown("text_match".to_string()),
             preview: String::new(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }];
 
         // Query for all structs - using capture syntax @struct
@@ -366,6 +470,55 @@ struct Config {
         assert!(results.iter().any(|r| r.symbol == Some("Config".to_string())));
     }
 
+    #[test]
+    fn test_named_captures() {
+        let content = "fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+
+        let mut file_contents = HashMap::new();
+        file_contents.insert("test.rs".to_string(), content.to_string());
+
+        let candidates = vec![SearchResult {
+            path: "test.rs".to_string(),
+            lang: Language::Rust,
+            span: Span {
+                start_line: 1,
+                end_line: 1,
+            },
+            symbol: None,
+            parent: None,
+            qualified_name: None,
+            kind: SymbolKind::Unknown("text_match".to_string()),
+            preview: String::new(),
+            dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
+        }];
+
+        let ast_pattern = "(function_item name: (identifier) @name parameters: (parameters) @params) @fn";
+        let results = execute_ast_query(candidates, ast_pattern, Language::Rust, &file_contents)
+            .expect("AST query failed");
+
+        // One result per match, spanning the whole function
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.symbol.as_deref(), Some("add"));
+        assert_eq!(result.span, Span { start_line: 1, end_line: 3 });
+
+        let captures = result.captures.as_ref().unwrap();
+        assert_eq!(captures.keys().collect::<Vec<_>>(), vec!["fn", "name", "params"]);
+        assert_eq!(captures["name"][0].text, "add");
+        assert_eq!(captures["name"][0].kind, "identifier");
+        assert_eq!(captures["params"][0].text, "(a: i32, b: i32)");
+
+        let json = serde_json::to_value(result).unwrap();
+        assert_eq!(json["captures"]["name"][0]["text"], "add");
+        assert_eq!(json["captures"]["fn"][0]["span"]["end_line"], 3);
+    }
+
     #[test]
     fn test_invalid_ast_pattern() {
         let mut file_contents = HashMap::new();
@@ -379,9 +532,17 @@ struct Config {
                 end_line: 1,
             },
             symbol: None,
+            parent: None,
+            qualified_name: None,
             kind: SymbolKind::Unknown("text_match".to_string()),
             preview: String::new(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }];
 
         // Invalid S-expression syntax (missing closing paren)
@@ -405,9 +566,17 @@ struct Config {
                 end_line: 1,
             },
             symbol: None,
+            parent: None,
+            qualified_name: None,
             kind: SymbolKind::Unknown("text_match".to_string()),
             preview: String::new(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }];
 
         // Vue uses line-based parsing, not tree-sitter, so AST queries should fail
@@ -442,9 +611,17 @@ def process(x):
                 end_line: 1,
             },
             symbol: None,
+            parent: None,
+            qualified_name: None,
             kind: SymbolKind::Unknown("text_match".to_string()),
             preview: String::new(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }];
 
         // Query for all Python functions
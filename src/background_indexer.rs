@@ -3,6 +3,11 @@
 //! This module provides background processing to parse symbols from all indexed
 //! files and populate the symbol cache. It runs as a separate process spawned by
 //! `rfx index`, allowing users to continue working while symbols are being indexed.
+//!
+//! Files in directories that recent queries found results in are parsed first,
+//! so the code being worked on is cached before vendored or generated code.
+//! `rfx index symbols` runs the indexer for a subset of files ([`SymbolScope`]),
+//! optionally in the foreground (`--wait`).
 
 use anyhow::{Context, Result};
 use rayon::prelude::*;
@@ -15,7 +20,8 @@ use std::time::Instant;
 
 use crate::cache::CacheManager;
 use crate::content_store::ContentReader;
-use crate::parsers::ParserFactory;
+use crate::models::Language;
+use crate::quarantine::Quarantine;
 use crate::symbol_cache::SymbolCache;
 
 /// Lock file name to prevent concurrent indexing
@@ -24,6 +30,71 @@ const LOCK_FILE: &str = "indexing.lock";
 /// Status file name for progress tracking
 const STATUS_FILE: &str = "indexing.status";
 
+/// Recent queries whose result directories are indexed first
+const PRIORITY_QUERIES: usize = 50;
+
+/// Files a symbol indexing run is limited to (`rfx index symbols --lang --glob`)
+#[derive(Debug, Clone, Default)]
+pub struct SymbolScope {
+    /// Languages to parse (empty = all)
+    pub languages: Vec<Language>,
+    /// Glob patterns a path must match (empty = all files)
+    pub globs: Vec<String>,
+}
+
+impl SymbolScope {
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty() && self.globs.is_empty()
+    }
+
+    /// Check that every glob pattern is valid
+    pub fn validate(&self) -> Result<()> {
+        self.matcher().map(|_| ())
+    }
+
+    /// Matcher over the scope's path and language restrictions
+    fn matcher(&self) -> Result<impl Fn(&str) -> bool + '_> {
+        let globs = if self.globs.is_empty() {
+            None
+        } else {
+            let mut builder = globset::GlobSetBuilder::new();
+            for pattern in &self.globs {
+                let normalized = crate::query::QueryEngine::normalize_glob_pattern(pattern);
+                let glob = globset::Glob::new(&normalized).map_err(|e| {
+                    crate::error_code::ReflexError::InvalidArgument(format!("Invalid glob pattern '{}': {}", pattern, e))
+                })?;
+                builder.add(glob);
+            }
+            Some(builder.build().context("Failed to build glob matcher")?)
+        };
+
+        Ok(move |path: &str| {
+            let in_language = self.languages.is_empty() || self.languages.contains(&Language::from_path(Path::new(path)));
+            // Normalized globs start with "./" while index paths don't
+            let in_globs = globs.as_ref().is_none_or(|g| g.is_match(path) || g.is_match(format!("./{}", path)));
+            in_language && in_globs
+        })
+    }
+}
+
+/// Order files so those in `priority_dirs` come first, in the order of the directories
+///
+/// A file belongs to a directory if it is inside it or one of its subdirectories
+/// (`.` holds top-level files only). Files keep their relative order otherwise.
+fn prioritize(files: &mut [(u32, String)], priority_dirs: &[String]) {
+    if priority_dirs.is_empty() {
+        return;
+    }
+
+    files.sort_by_cached_key(|(_, path)| {
+        let dir = path.rsplit_once('/').map_or(".", |(dir, _)| dir);
+        priority_dirs
+            .iter()
+            .position(|p| dir == p || (p != "." && dir.starts_with(p.as_str()) && dir[p.len()..].starts_with('/')))
+            .unwrap_or(usize::MAX)
+    });
+}
+
 /// Indexing progress status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexingStatus {
@@ -67,6 +138,9 @@ pub struct BackgroundIndexer {
     cache_path: PathBuf,
     status: IndexingStatus,
     batch_size: usize,
+    scope: SymbolScope,
+    /// Foreground runs (`rfx index symbols --wait`) neither lock nor write the status file
+    foreground: bool,
 }
 
 impl BackgroundIndexer {
@@ -80,6 +154,8 @@ impl BackgroundIndexer {
         // Create CacheManager to get the cache directory path
         let cache_mgr = CacheManager::new(workspace_path);
         let cache_path = cache_mgr.path().to_path_buf();
+        crate::indexer::install_extension_overrides(&cache_path, &std::collections::HashMap::new());
+        crate::parsers::sql::install_dialect(&cache_path);
 
         Ok(Self {
             workspace_path: workspace_path.to_path_buf(),
@@ -97,9 +173,29 @@ impl BackgroundIndexer {
                 error: None,
             },
             batch_size: 500, // Batch symbol writes for performance (increased for better throughput)
+            scope: SymbolScope::default(),
+            foreground: false,
         })
     }
 
+    /// Only parse files in `scope`
+    pub fn with_scope(mut self, scope: SymbolScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Index symbols in the calling process and return the final counts
+    ///
+    /// Unlike [`run`](Self::run) this takes no lock and leaves the status file of a
+    /// background run alone, so it can warm part of the cache while one is running.
+    pub fn run_foreground(&mut self) -> Result<IndexingStatus> {
+        self.foreground = true;
+        self.run_internal()?;
+        self.status.state = IndexerState::Completed;
+        self.status.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        Ok(self.status.clone())
+    }
+
     /// Check if an indexing process is already running
     pub fn is_running(cache_dir: &Path) -> bool {
         cache_dir.join(LOCK_FILE).exists()
@@ -157,6 +253,9 @@ impl BackgroundIndexer {
     /// Write current status to status file
     fn write_status(&mut self) -> Result<()> {
         self.status.updated_at = chrono::Utc::now().to_rfc3339();
+        if self.foreground {
+            return Ok(());
+        }
 
         let status_path = self.cache_path.join(STATUS_FILE);
         let status_json = serde_json::to_string_pretty(&self.status)
@@ -243,6 +342,8 @@ impl BackgroundIndexer {
         let cache_mgr = CacheManager::new(&self.workspace_path);
         let symbol_cache = SymbolCache::open(&self.cache_path)
             .context("Failed to open symbol cache")?;
+        let quarantine = Quarantine::open(&self.cache_path)
+            .context("Failed to open parser quarantine")?;
 
         // Load content reader to iterate through all indexed files
         let content_path = self.cache_path.join("content.bin");
@@ -263,16 +364,14 @@ impl BackgroundIndexer {
         let file_hashes = cache_mgr.load_all_hashes()
             .context("Failed to load file hashes")?;
 
-        let total_files = content_reader.file_count();
-        self.status.total_files = total_files;
-        log::info!("Found {} indexed files to process", total_files);
+        let indexed_files = content_reader.file_count();
         log::debug!("Loaded {} file hashes from file_branches table", file_hashes.len());
 
         // DEFENSIVE CHECK: If file_hashes is empty but we have files, this indicates a problem
-        if file_hashes.is_empty() && total_files > 0 {
+        if file_hashes.is_empty() && indexed_files > 0 {
             log::error!(
                 "CRITICAL: No file hashes found in file_branches table, but {} files exist in content.bin!",
-                total_files
+                indexed_files
             );
             log::error!("This likely means:");
             log::error!("  1. The main indexer failed to populate file_branches table");
@@ -288,6 +387,35 @@ impl BackgroundIndexer {
             );
         }
 
+        // Files in content.bin within the scope
+        // NORMALIZE: content.bin stores paths like "./src/main.rs" (or absolute paths
+        // when indexed from one), but the database stores workspace-relative paths
+        // like "src/main.rs"
+        let mut files: Vec<(u32, String)> = {
+            let in_scope = self.scope.matcher()?;
+            (0..indexed_files as u32)
+                .filter_map(|file_id| {
+                    let path = content_reader.get_file_path(file_id)?;
+                    let path = crate::paths::relative_path(path, &self.workspace_path);
+                    in_scope(&path).then_some((file_id, path))
+                })
+                .collect()
+        };
+
+        // Directories recent queries found results in go first
+        let priority_dirs = crate::history::QueryHistoryLog::new(cache_mgr.clone())
+            .recent_directories(PRIORITY_QUERIES)
+            .unwrap_or_else(|e| {
+                log::debug!("No query history to prioritize symbol indexing: {}", e);
+                Vec::new()
+            });
+        log::debug!("Prioritizing {} recently queried directories", priority_dirs.len());
+        prioritize(&mut files, &priority_dirs);
+
+        let total_files = files.len();
+        self.status.total_files = total_files;
+        log::info!("Found {} indexed files to process", total_files);
+
         // Write initial status
         self.write_status()?;
 
@@ -298,17 +426,12 @@ impl BackgroundIndexer {
         let batch_size = self.batch_size;
         let mut processed = 0;
 
-        // Iterate through all files in content.bin
-        let file_ids: Vec<u32> = (0..total_files as u32).collect();
-
         // DIAGNOSTIC: Log sample paths to debug hash lookup failures
-        if !file_ids.is_empty() && !file_hashes.is_empty() {
+        if !files.is_empty() && !file_hashes.is_empty() {
             // Log first 3 paths from content.bin
             log::debug!("=== Path Comparison Diagnostic ===");
-            for sample_id in file_ids.iter().take(3) {
-                if let Some(path) = content_reader.get_file_path(*sample_id) {
-                    log::debug!("  content.bin path[{}]: '{}'", sample_id, path.to_string_lossy());
-                }
+            for (sample_id, path) in files.iter().take(3) {
+                log::debug!("  content.bin path[{}]: '{}'", sample_id, path);
             }
             // Log first 3 keys from file_hashes HashMap
             let sample_keys: Vec<_> = file_hashes.keys().take(3).collect();
@@ -318,31 +441,27 @@ impl BackgroundIndexer {
             log::debug!("=================================");
         }
 
-        for chunk in file_ids.chunks(batch_size) {
+        for chunk in files.chunks(batch_size) {
             // Build list of files to parse (with cache check)
             let files_to_parse: Vec<_> = chunk
                 .iter()
-                .filter_map(|&file_id| {
-                    let path = content_reader.get_file_path(file_id)?;
-                    let mut path_str = path.to_string_lossy().to_string();
+                .filter_map(|(file_id, path_str)| {
+                    let file_hash = file_hashes.get(path_str)?;
 
-                    // NORMALIZE: Strip "./" prefix to match database paths
-                    // content.bin stores paths like "./src/main.rs"
-                    // but database stores paths like "src/main.rs"
-                    if path_str.starts_with("./") {
-                        path_str = path_str[2..].to_string();
+                    // Skip files that crashed a parser at this content hash
+                    if quarantine.contains(path_str, file_hash) {
+                        log::debug!("Skipping quarantined file: {}", path_str);
+                        return None;
                     }
 
-                    let file_hash = file_hashes.get(&path_str)?;
-
                     // Check if already cached
-                    if symbol_cache.get(&path_str, file_hash).ok().flatten().is_some() {
+                    if symbol_cache.get(path_str, file_hash).ok().flatten().is_some() {
                         // Update cached count
                         let mut status = status_mutex.lock().unwrap();
                         status.0 += 1;
                         None
                     } else {
-                        Some((file_id, path_str, file_hash.clone()))
+                        Some((*file_id, path_str.clone(), file_hash.clone()))
                     }
                 })
                 .collect();
@@ -352,7 +471,7 @@ impl BackgroundIndexer {
                 files_to_parse
                     .par_iter()
                     .map(|(file_id, path_str, file_hash)| {
-                        match self.parse_symbols(&content_reader, *file_id, path_str) {
+                        match self.parse_symbols(&content_reader, &quarantine, *file_id, path_str, file_hash) {
                             Ok(symbols) => {
                                 // Update parsed count
                                 let mut status = status_mutex.lock().unwrap();
@@ -411,6 +530,20 @@ impl BackgroundIndexer {
             log::info!("Cleaned up {} stale symbol entries", removed);
         }
 
+        // Forget quarantined files that changed or were removed since they crashed
+        let released = quarantine.prune(&file_hashes)
+            .context("Failed to prune parser quarantine")?;
+
+        if released > 0 {
+            log::info!("Released {} changed files from parser quarantine", released);
+        }
+
+        // Symbols invalidated by a parser upgrade have now been rebuilt (unless the run was scoped)
+        if self.scope.is_empty() {
+            symbol_cache.clear_invalidation()
+                .context("Failed to clear parser invalidation record")?;
+        }
+
         Ok(())
     }
 
@@ -418,23 +551,20 @@ impl BackgroundIndexer {
     fn parse_symbols(
         &self,
         content_reader: &ContentReader,
+        quarantine: &Quarantine,
         file_id: u32,
         path: &str,
+        file_hash: &str,
     ) -> Result<Vec<crate::models::SearchResult>> {
         // Read file contents from content.bin (memory-mapped, zero-copy)
         let source = content_reader.get_file_content(file_id)
             .with_context(|| format!("Failed to read file from content.bin: {}", path))?;
 
-        // Detect language from file extension
-        let extension = std::path::Path::new(path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
+        // Detect language from the file name
+        let language = crate::models::Language::from_path(std::path::Path::new(path));
 
-        let language = crate::models::Language::from_extension(extension);
-
-        // Parse with appropriate parser
-        let symbols = ParserFactory::parse(path, source, language)
+        // Parse with appropriate parser (quarantining the file if the parser panics)
+        let symbols = quarantine.parse(path, Some(file_hash), source, language)
             .with_context(|| format!("Failed to parse symbols from: {}", path))?;
 
         Ok(symbols)
@@ -511,6 +641,44 @@ mod tests {
         assert!(status.is_none());
     }
 
+    #[test]
+    fn test_prioritize_recent_directories() {
+        let mut files: Vec<(u32, String)> = ["vendor/x.rs", "main.rs", "src/a.rs", "src/api/b.rs", "srcx/c.rs"]
+            .iter()
+            .enumerate()
+            .map(|(id, path)| (id as u32, path.to_string()))
+            .collect();
+
+        prioritize(&mut files, &["src".to_string(), ".".to_string()]);
+        let order: Vec<&str> = files.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(order, vec!["src/a.rs", "src/api/b.rs", "main.rs", "vendor/x.rs", "srcx/c.rs"]);
+    }
+
+    #[test]
+    fn test_scoped_foreground_run() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/lib.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(temp.path().join("src/app.py"), "def b():\n    pass\n").unwrap();
+        std::fs::write(temp.path().join("build.rs"), "fn main() {}\n").unwrap();
+
+        let cache_mgr = CacheManager::new(temp.path());
+        let indexer = crate::indexer::Indexer::new(cache_mgr.clone(), crate::models::IndexConfig::default());
+        indexer.index(temp.path(), false).unwrap();
+
+        let scope = SymbolScope { languages: vec![Language::Rust], globs: vec!["src/**".to_string()] };
+        let status = BackgroundIndexer::new(temp.path()).unwrap().with_scope(scope).run_foreground().unwrap();
+        assert_eq!(status.total_files, 1);
+        assert_eq!(status.cached_files + status.parsed_files, 1);
+
+        // Foreground runs leave the background lock and status file alone
+        assert!(!BackgroundIndexer::is_running(cache_mgr.path()));
+        assert!(BackgroundIndexer::get_status(cache_mgr.path()).unwrap().is_none());
+
+        let invalid = SymbolScope { globs: vec!["[".to_string()], ..Default::default() };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_indexer_run_empty_index() {
         let temp = TempDir::new().unwrap();
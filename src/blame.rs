@@ -0,0 +1,316 @@
+//! Blame annotations for query results (`--blame`)
+//!
+//! `rfx query --blame` attaches the last commit touching each match's span:
+//! the newest commit among the span's lines, with its author and age. Blame
+//! comes from `git blame --porcelain` and is cached per file in meta.db,
+//! keyed by the file's indexed content hash, so a file is blamed once until
+//! its content changes. Files with uncommitted lines are not cached, since
+//! committing them changes the blame without changing the content.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{BlameInfo, FileGroupedResult};
+
+/// Commit git reports for lines that aren't committed yet
+const UNCOMMITTED: &str = "0000000000000000000000000000000000000000";
+
+/// A commit referenced by a file's blame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BlameCommit {
+    commit: String,
+    author: String,
+    author_email: String,
+    /// Unix timestamp of the authored change
+    timestamp: i64,
+    summary: String,
+}
+
+/// Blame of one file: the commit of each line
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FileBlame {
+    commits: Vec<BlameCommit>,
+    /// Index into `commits` for each line (line 1 first)
+    lines: Vec<u32>,
+}
+
+impl FileBlame {
+    /// Parse `git blame --porcelain` output
+    fn parse(porcelain: &str) -> Self {
+        let mut blame = FileBlame::default();
+        let mut by_sha: HashMap<String, u32> = HashMap::new();
+        let mut current: Option<u32> = None;
+        let mut line_no = 0usize;
+
+        for line in porcelain.lines() {
+            if line.starts_with('\t') {
+                // The blamed line itself follows its header and commit info
+                if let Some(index) = current {
+                    if blame.lines.len() < line_no {
+                        blame.lines.resize(line_no, index);
+                    }
+                    blame.lines[line_no - 1] = index;
+                }
+                continue;
+            }
+
+            let mut fields = line.split(' ');
+            let first = fields.next().unwrap_or_default();
+            if first.len() == 40 && first.bytes().all(|b| b.is_ascii_hexdigit()) {
+                // <sha> <orig line> <final line> [<group size>]
+                line_no = fields.nth(1).and_then(|n| n.parse().ok()).unwrap_or(line_no + 1);
+                let next = blame.commits.len() as u32;
+                let index = *by_sha.entry(first.to_string()).or_insert_with(|| {
+                    blame.commits.push(BlameCommit {
+                        commit: first.to_string(),
+                        author: String::new(),
+                        author_email: String::new(),
+                        timestamp: 0,
+                        summary: String::new(),
+                    });
+                    next
+                });
+                current = Some(index);
+                continue;
+            }
+
+            let Some(commit) = current.map(|i| &mut blame.commits[i as usize]) else {
+                continue;
+            };
+            let value = line.split_once(' ').map_or("", |(_, value)| value);
+            match first {
+                "author" => commit.author = value.to_string(),
+                "author-mail" => commit.author_email = value.trim_start_matches('<').trim_end_matches('>').to_string(),
+                "author-time" => commit.timestamp = value.parse().unwrap_or(0),
+                "summary" => commit.summary = value.to_string(),
+                _ => {}
+            }
+        }
+
+        blame
+    }
+
+    fn has_uncommitted(&self) -> bool {
+        self.commits.iter().any(|c| c.commit == UNCOMMITTED)
+    }
+
+    /// The newest commit among lines `start..=end`
+    fn last_change(&self, start: usize, end: usize) -> Option<&BlameCommit> {
+        let end = end.min(self.lines.len());
+        if start == 0 || start > end {
+            return None;
+        }
+        self.lines[start - 1..end]
+            .iter()
+            .map(|&i| &self.commits[i as usize])
+            .max_by_key(|c| c.timestamp)
+    }
+}
+
+/// Per-file blame of an index, cached in its meta.db
+pub struct BlameCache {
+    conn: Connection,
+    root: PathBuf,
+    /// Commit of a revision segment (blame that commit instead of the indexed text)
+    revision: Option<String>,
+    hashes: HashMap<String, String>,
+    content: Option<ContentReader>,
+    now: i64,
+}
+
+impl BlameCache {
+    /// Open the blame cache of `cache`
+    pub fn open(cache: &CacheManager) -> Result<Self> {
+        let db_path = cache.path().join("meta.db");
+        if !db_path.exists() {
+            anyhow::bail!("Cache not initialized - run 'rfx index' first");
+        }
+
+        let conn = Connection::open(&db_path).context("Failed to open meta.db")?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS blame_cache (
+                path TEXT PRIMARY KEY,
+                file_hash TEXT NOT NULL,
+                blame TEXT NOT NULL
+            )",
+            [],
+        )
+        .context("Failed to create blame_cache table")?;
+
+        let hashes = cache.load_hashes_for_branch(&cache.query_branch())
+            .context("Failed to load file hashes")?;
+
+        Ok(Self {
+            conn,
+            root: cache.workspace_root(),
+            revision: cache.revision().map(str::to_string),
+            hashes,
+            content: ContentReader::open(cache.path().join("content.bin")).ok(),
+            now: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Attach blame to every match of `group`
+    ///
+    /// Files git can't blame (untracked, outside a repository) are left
+    /// without annotations.
+    pub fn annotate(&self, group: &mut FileGroupedResult) {
+        let blame = match self.file_blame(&group.path) {
+            Ok(Some(blame)) => blame,
+            Ok(None) => return,
+            Err(e) => {
+                log::debug!("No blame for {}: {:#}", group.path, e);
+                return;
+            }
+        };
+
+        for m in &mut group.matches {
+            m.blame = blame.last_change(m.span.start_line, m.span.end_line).map(|c| BlameInfo {
+                commit: c.commit.clone(),
+                author: c.author.clone(),
+                author_email: c.author_email.clone(),
+                summary: c.summary.clone(),
+                timestamp: c.timestamp,
+                age_days: (self.now - c.timestamp).max(0) / 86_400,
+            });
+        }
+    }
+
+    fn file_blame(&self, path: &str) -> Result<Option<FileBlame>> {
+        let normalized = path.strip_prefix("./").unwrap_or(path);
+        let relative = crate::paths::relative_path(Path::new(normalized), &self.root);
+        let Some(hash) = self.hashes.get(&relative) else {
+            return Ok(None);
+        };
+
+        let cached: Option<(String, String)> = self.conn
+            .query_row(
+                "SELECT file_hash, blame FROM blame_cache WHERE path = ?",
+                [&relative],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some((_, json)) = cached.filter(|(cached_hash, _)| cached_hash == hash) {
+            match serde_json::from_str(&json) {
+                Ok(blame) => return Ok(Some(blame)),
+                Err(e) => log::debug!("Ignoring unreadable cached blame for {}: {}", relative, e),
+            }
+        }
+
+        let contents = match self.revision {
+            Some(_) => "",
+            None => {
+                let reader = self.content.as_ref().context("Content store unavailable")?;
+                let file_id = reader.get_file_id_by_path(normalized)
+                    .with_context(|| format!("{} is not in the content store", normalized))?;
+                reader.get_file_content(file_id)?
+            }
+        };
+        let porcelain = crate::git::blame_porcelain(&self.root, &relative, self.revision.as_deref(), contents.as_bytes())?;
+        let blame = FileBlame::parse(&porcelain);
+
+        if blame.has_uncommitted() {
+            self.conn.execute("DELETE FROM blame_cache WHERE path = ?", [&relative])
+                .context("Failed to clear cached blame")?;
+        } else {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO blame_cache (path, file_hash, blame) VALUES (?, ?, ?)",
+                rusqlite::params![relative, hash, serde_json::to_string(&blame)?],
+            )
+            .context("Failed to cache blame")?;
+        }
+
+        Ok(Some(blame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use crate::query::{QueryEngine, QueryFilter};
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(root: &Path, author: &str, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", &format!("user.name={}", author), "-c", "user.email=dev@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_parse_porcelain_repeated_commits() {
+        let porcelain = "\
+1111111111111111111111111111111111111111 1 1 2
+author Alice
+author-mail <alice@example.com>
+author-time 1700000000
+summary Initial
+\tfn a() {}
+1111111111111111111111111111111111111111 2 2
+\tfn b() {}
+2222222222222222222222222222222222222222 3 3 1
+author Bob
+author-mail <bob@example.com>
+author-time 1710000000
+summary Tweak
+\tfn c() {}
+";
+        let blame = FileBlame::parse(porcelain);
+        assert_eq!(blame.commits.len(), 2);
+        assert_eq!(blame.lines, vec![0, 0, 1]);
+        assert_eq!(blame.commits[0].author_email, "alice@example.com");
+        assert_eq!(blame.last_change(1, 2).unwrap().author, "Alice");
+        assert_eq!(blame.last_change(2, 3).unwrap().summary, "Tweak");
+        assert!(blame.last_change(4, 5).is_none());
+    }
+
+    #[test]
+    fn test_blame_annotations_follow_file_content() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        git(root, "Alice", &["init", "-q"]);
+        fs::write(root.join("lib.rs"), "fn parse_config() {}\n\nfn load_config() {}\n").unwrap();
+        git(root, "Alice", &["add", "."]);
+        git(root, "Alice", &["commit", "-q", "-m", "Add config"]);
+        fs::write(root.join("lib.rs"), "fn parse_config() {}\n\nfn load_config() { todo!() }\n").unwrap();
+        git(root, "Bob", &["commit", "-q", "-am", "Load config"]);
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let filter = QueryFilter { suppress_output: true, use_contains: true, blame: true, ..Default::default() };
+        let engine = QueryEngine::new(cache.clone());
+        let response = engine.search_with_metadata("_config", filter.clone()).unwrap();
+        let matches = &response.results[0].matches;
+        assert_eq!(matches.len(), 2);
+        let first = matches[0].blame.as_ref().unwrap();
+        assert_eq!(first.author, "Alice");
+        assert_eq!(first.summary, "Add config");
+        assert_eq!(matches[1].blame.as_ref().unwrap().author, "Bob");
+
+        // Uncommitted edits are attributed to the all-zero commit and not cached
+        fs::write(root.join("lib.rs"), "fn parse_config() { 1 }\n\nfn load_config() { todo!() }\n").unwrap();
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+        let response = engine.search_with_metadata("parse_config", filter).unwrap();
+        let blame = response.results[0].matches[0].blame.as_ref().unwrap();
+        assert_eq!(blame.commit, UNCOMMITTED);
+
+        let conn = Connection::open(cache.path().join("meta.db")).unwrap();
+        let cached: i64 = conn.query_row("SELECT COUNT(*) FROM blame_cache", [], |row| row.get(0)).unwrap();
+        assert_eq!(cached, 0);
+    }
+}
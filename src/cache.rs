@@ -2,7 +2,7 @@
 //!
 //! The cache module handles the `.reflex/` directory structure:
 //! - `meta.db`: Metadata, file hashes, and configuration (SQLite)
-//! - `tokens.bin`: Compressed lexical tokens (binary)
+//! - `tokens.bin`: Identifier word-part index for `--tokens` queries (binary)
 //! - `content.bin`: Memory-mapped file contents (binary)
 //! - `trigrams.bin`: Trigram inverted index (bincode binary)
 //! - `config.toml`: Index settings (TOML text)
@@ -13,7 +13,10 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 
-use crate::models::IndexedFile;
+use crate::models::{
+    DirectoryStats, IndexComponentSizes, IndexWarning, IndexWarningDetails, IndexedFile, SkipReason, SkippedFile,
+    WorkspaceRoot,
+};
 
 /// Default cache directory name
 pub const CACHE_DIR: &str = ".reflex";
@@ -24,17 +27,67 @@ pub const TOKENS_BIN: &str = "tokens.bin";
 pub const HASHES_JSON: &str = "hashes.json";
 pub const CONFIG_TOML: &str = "config.toml";
 
+/// Format version of meta.db, stored as the `cache_version` statistic
+///
+/// Bump it together with a migration in `migration.rs` when the schema changes.
+pub const CACHE_FORMAT_VERSION: u32 = 7;
+
 /// Manages the Reflex cache directory
 #[derive(Clone)]
 pub struct CacheManager {
     cache_path: PathBuf,
+    workspace_root: PathBuf,
+    /// Commit of a historical revision segment (`None` = working-tree index)
+    revision: Option<String>,
 }
 
 impl CacheManager {
     /// Create a new cache manager for the given root directory
     pub fn new(root: impl AsRef<Path>) -> Self {
-        let cache_path = root.as_ref().join(CACHE_DIR);
-        Self { cache_path }
+        let workspace_root = root.as_ref().to_path_buf();
+        let cache_path = workspace_root.join(CACHE_DIR);
+        Self { cache_path, workspace_root, revision: None }
+    }
+
+    /// Cache manager for the dirty working-tree overlay (`.reflex/overlay/`)
+    ///
+    /// The overlay is a regular cache for the same workspace, so its paths
+    /// resolve against the same root as the main index.
+    pub fn overlay(&self) -> Self {
+        Self {
+            cache_path: self.cache_path.join(crate::overlay::OVERLAY_DIR),
+            workspace_root: self.workspace_root.clone(),
+            revision: self.revision.clone(),
+        }
+    }
+
+    /// Cache manager for the segment of a historical revision (`.reflex/revs/<commit>/`)
+    ///
+    /// Like the overlay, a segment is a regular cache for the same workspace;
+    /// its files were read from the git object database at `commit`.
+    pub fn at_revision(&self, commit: &str) -> Self {
+        Self {
+            cache_path: self.cache_path.join(crate::revision::REVS_DIR).join(commit),
+            workspace_root: self.workspace_root.clone(),
+            revision: Some(commit.to_string()),
+        }
+    }
+
+    /// Commit this cache was built from, for revision segments
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// Branch whose file hashes answer queries against this cache
+    ///
+    /// Revision segments are keyed by their commit; otherwise this is the
+    /// current git branch (`_default` outside git repos).
+    pub fn query_branch(&self) -> String {
+        match &self.revision {
+            Some(commit) => commit.clone(),
+            None => crate::git::get_current_branch(&self.workspace_root)
+                .unwrap_or_else(|_| "_default".to_string()),
+        }
     }
 
     /// Initialize the cache directory structure if it doesn't exist
@@ -51,7 +104,7 @@ impl CacheManager {
         // Create default config.toml
         self.init_config_toml()?;
 
-        // Note: tokens.bin removed - was never used
+        // Note: tokens.bin is written by the indexer alongside trigrams.bin
         // Note: hashes.json is deprecated - hashes are now stored in meta.db
 
         log::info!("Cache initialized successfully");
@@ -103,7 +156,7 @@ impl CacheManager {
         )?;
         conn.execute(
             "INSERT OR REPLACE INTO statistics (key, value, updated_at) VALUES (?, ?, ?)",
-            ["cache_version", "1", &now.to_string()],
+            ["cache_version", &CACHE_FORMAT_VERSION.to_string(), &now.to_string()],
         )?;
 
         // Store cache schema hash for automatic invalidation detection
@@ -227,6 +280,36 @@ impl CacheManager {
             [],
         )?;
 
+        // Create workspace roots table for multi-root (monorepo) indexing
+        Self::ensure_roots_table(&conn)?;
+
+        // Create table of files skipped by the size/line length/binary limits
+        Self::ensure_skipped_files_table(&conn)?;
+
+        // Create table of canonical paths for files reached through symlinks
+        Self::ensure_linked_files_table(&conn)?;
+
+        // Create feature flag usage table
+        crate::flags::ensure_schema(&conn)?;
+
+        // Create environment variable / config key usage table
+        crate::config_keys::ensure_schema(&conn)?;
+
+        // Create database table / ORM model reference table
+        crate::tables::ensure_schema(&conn)?;
+
+        // Create content transform table
+        crate::transforms::ensure_schema(&conn)?;
+
+        // Create tombstone table for files removed by compaction
+        crate::tombstones::ensure_schema(&conn)?;
+
+        // Create embedded sub-language region table
+        crate::embedded::ensure_schema(&conn)?;
+
+        // Create query history table (`rfx history`)
+        crate::history::ensure_schema(&conn)?;
+
         log::debug!("Created meta.db with schema");
         Ok(())
     }
@@ -242,7 +325,10 @@ impl CacheManager {
         let default_config = r#"[index]
 languages = []  # Empty = all supported languages
 max_file_size = 10485760  # 10 MB
-follow_symlinks = false
+max_line_length = 10000  # Skip files with longer lines (minified bundles), 0 = no limit
+binary_detection = true  # Skip files that look binary
+follow_symlinks = false  # Index files in symlinked directories (loops are skipped)
+dedupe_hardlinks = false  # Index a file with several hard links once (Unix)
 
 [index.include]
 patterns = []
@@ -250,14 +336,67 @@ patterns = []
 [index.exclude]
 patterns = []
 
+[index.extensions]
+# Index extra extensions as a language, e.g. phtml = "php", pyi = "python", h = "cpp"
+
+[sql]
+# Dialect of .sql files: auto, postgres, mysql, sqlite, tsql
+dialect = "auto"
+
 [search]
-default_limit = 100
-fuzzy_threshold = 0.8
+# Defaults are tuned to repo size on every `rfx index` (shown in its output)
+auto_tune = true
+# default_limit = 100  # Uncomment to pin the result limit (0 = unlimited)
+# timeout = 30  # Query timeout in seconds (0 = no timeout)
+# max_preview_length = 100  # Preview truncation length in characters
+fuzzy_threshold = 0.8  # Minimum symbol name similarity for --fuzzy (0.0-1.0)
+# max_memory_mb = 1024  # Memory ceiling per query; larger queries parse in smaller batches (0 = unlimited)
 
 [performance]
 parallel_threads = 0  # 0 = auto (80% of available cores), or set a specific number
+memory_budget_mb = 512  # File content held in memory while indexing (larger = fewer, bigger batches)
 compression_level = 3  # zstd level
 
+[flags]
+# Feature flag checks recorded by `rfx index` (see `rfx flags --help`)
+# Extra regex patterns; the first capture group is the flag name
+patterns = []
+disable_defaults = false  # true = ignore built-in LaunchDarkly/Unleash patterns
+
+[tombstones]
+# Keep path, last hash and symbols of files removed by compaction
+# (see `rfx deleted list` and `rfx query --include-deleted`)
+enabled = false
+retention_days = 90  # 0 = keep forever
+
+[todos]
+# Comment markers reported by `rfx todos`
+markers = ["TODO", "FIXME", "HACK", "XXX"]
+
+[history]
+# Queries recorded for `rfx history` (list, replay, clear)
+enabled = true
+max_entries = 1000
+
+# Content transforms: pipe matching files through a command (stdin -> stdout)
+# before indexing, e.g. to strip license headers or redact secrets
+# [[transforms.rules]]
+# name = "strip-license"
+# command = "scripts/strip-license.sh"
+# globs = ["**/*.rs"]
+
+[mcp]
+# Tool surface of `rfx mcp` (the effective manifest is logged at startup)
+# enabled_tools = []  # Tools to expose (empty = all)
+# disabled_tools = ["index_project"]  # e.g. on shared read-only deployments
+#
+# Per-tool defaults (limit = default when the client passes none,
+# max_limit = hard cap, timeout = seconds for search tools)
+# [mcp.tools.search_code]
+# limit = 50
+# max_limit = 200
+# timeout = 10
+
 [semantic]
 # Semantic query generation using LLMs
 # Translate natural language questions into rfx query commands
@@ -396,6 +535,14 @@ provider = "groq"  # Options: openai, anthropic, groq
             }
         }
 
+        // Check format versions (older caches are upgraded by migrate())
+        if let Some(outdated) = crate::migration::versions(self)?.iter().find(|v| !v.is_current()) {
+            anyhow::bail!(
+                "Cache format version mismatch: {}. Run 'rfx index' to upgrade it.",
+                outdated.describe()
+            );
+        }
+
         // Check schema hash for automatic invalidation
         let current_schema_hash = env!("CACHE_SCHEMA_HASH");
 
@@ -441,6 +588,14 @@ provider = "groq"  # Options: openai, anthropic, groq
         Ok(())
     }
 
+    /// Upgrade a cache written by an older Reflex to the current formats
+    ///
+    /// See [`crate::migration`]. Parts that can't be upgraded in place are
+    /// reported in `rebuild_required`.
+    pub fn migrate(&self) -> Result<crate::migration::MigrationReport> {
+        crate::migration::migrate(self)
+    }
+
     /// Get the path to the cache directory
     pub fn path(&self) -> &Path {
         &self.cache_path
@@ -448,10 +603,7 @@ provider = "groq"  # Options: openai, anthropic, groq
 
     /// Get the workspace root directory (parent of .reflex/)
     pub fn workspace_root(&self) -> PathBuf {
-        self.cache_path
-            .parent()
-            .expect(".reflex directory should have a parent")
-            .to_path_buf()
+        self.workspace_root.clone()
     }
 
     /// Clear the entire cache
@@ -792,8 +944,13 @@ provider = "groq"  # Options: openai, anthropic, groq
         let conn = Connection::open(&db_path)
             .context("Failed to open meta.db")?;
 
+        Self::ensure_linked_files_table(&conn)?;
+
         let mut stmt = conn.prepare(
-            "SELECT path, language, last_indexed FROM files ORDER BY path"
+            "SELECT f.path, f.language, f.last_indexed, l.canonical_path
+             FROM files f
+             LEFT JOIN linked_files l ON l.path = f.path
+             ORDER BY f.path"
         )?;
 
         let files = stmt.query_map([], |row| {
@@ -807,6 +964,7 @@ provider = "groq"  # Options: openai, anthropic, groq
                 last_indexed: chrono::DateTime::from_timestamp(last_indexed, 0)
                     .unwrap_or_else(chrono::Utc::now)
                     .to_rfc3339(),
+                canonical_path: row.get(3)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -829,6 +987,11 @@ provider = "groq"  # Options: openai, anthropic, groq
                 last_updated: chrono::Utc::now().to_rfc3339(),
                 files_by_language: std::collections::HashMap::new(),
                 lines_by_language: std::collections::HashMap::new(),
+                symbols_by_language: std::collections::HashMap::new(),
+                component_sizes: IndexComponentSizes::default(),
+                directories: Vec::new(),
+                degraded_languages: crate::models::DegradedLanguage::detect(),
+                warning: None,
             });
         }
 
@@ -836,14 +999,7 @@ provider = "groq"  # Options: openai, anthropic, groq
             .context("Failed to open meta.db")?;
 
         // Determine current branch for branch-aware statistics
-        let workspace_root = self.workspace_root();
-        let current_branch = if crate::git::is_git_repo(&workspace_root) {
-            crate::git::get_git_state(&workspace_root)
-                .ok()
-                .map(|state| state.branch)
-        } else {
-            Some("_default".to_string())
-        };
+        let current_branch = self.current_branch();
 
         log::debug!("stats(): current_branch = {:?}", current_branch);
 
@@ -923,6 +1079,20 @@ provider = "groq"  # Options: openai, anthropic, groq
             }
         }
 
+        let file_size = |name: &str| std::fs::metadata(self.cache_path.join(name)).map(|m| m.len()).unwrap_or(0);
+        let symbols_size: i64 = if Self::has_symbols_table(&conn) {
+            conn.query_row("SELECT COALESCE(SUM(LENGTH(symbols_json)), 0) FROM symbols", [], |row| row.get(0))?
+        } else {
+            0
+        };
+        let component_sizes = IndexComponentSizes {
+            trigrams: file_size("trigrams.bin"),
+            content: file_size("content.bin"),
+            tokens: file_size(TOKENS_BIN),
+            symbols: symbols_size as u64,
+            metadata: file_size(META_DB),
+        };
+
         // Get file count breakdown by language (branch-aware if possible)
         let mut files_by_language = std::collections::HashMap::new();
         if let Some(ref branch) = current_branch {
@@ -997,15 +1167,141 @@ provider = "groq"  # Options: openai, anthropic, groq
             }
         }
 
+        // Symbol counts come from the symbol cache (files parsed so far)
+        let mut symbols_by_language = std::collections::HashMap::new();
+        if let Some(ref branch) = current_branch.as_ref().filter(|_| Self::has_symbols_table(&conn)) {
+            let mut stmt = conn.prepare(
+                "SELECT f.language, SUM(json_array_length(s.symbols_json))
+                 FROM files f
+                 JOIN file_branches fb ON f.id = fb.file_id
+                 JOIN branches b ON fb.branch_id = b.id
+                 JOIN symbols s ON s.file_id = f.id AND s.file_hash = fb.hash
+                 WHERE b.name = ?
+                 GROUP BY f.language"
+            )?;
+            let symbol_counts = stmt.query_map([branch], |row| {
+                let language: String = row.get(0)?;
+                let count: i64 = row.get(1)?;
+                Ok((language, count as usize))
+            })?;
+
+            for result in symbol_counts {
+                let (language, count) = result?;
+                symbols_by_language.insert(language, count);
+            }
+        }
+
+        let skipped_files = self.list_skipped_files()?;
+        let warning = (!skipped_files.is_empty()).then(|| IndexWarning {
+            reason: format!(
+                "{} files were skipped by the index limits (too large, long lines or binary)",
+                skipped_files.len()
+            ),
+            action_required: "Raise max_file_size or max_line_length, or set binary_detection = false, \
+                              under [index] in .reflex/config.toml, then run 'rfx index'".to_string(),
+            details: Some(IndexWarningDetails {
+                current_branch: None,
+                indexed_branch: None,
+                current_commit: None,
+                indexed_commit: None,
+                skipped_files,
+            }),
+        });
+
         Ok(crate::models::IndexStats {
             total_files,
             index_size_bytes,
             last_updated,
             files_by_language,
             lines_by_language,
+            symbols_by_language,
+            component_sizes,
+            directories: Vec::new(),
+            degraded_languages: crate::models::DegradedLanguage::detect(),
+            warning,
         })
     }
 
+    /// Files, lines and symbols per directory for the current branch
+    ///
+    /// Directories are cut to their first `depth` components, so `depth = 1`
+    /// groups by top-level directory. Sorted by file count, largest first.
+    pub fn directory_stats(&self, depth: usize) -> Result<Vec<DirectoryStats>> {
+        let db_path = self.cache_path.join(META_DB);
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db")?;
+
+        let Some(branch) = self.current_branch() else {
+            return Ok(Vec::new());
+        };
+        let symbol_count = if Self::has_symbols_table(&conn) {
+            "(SELECT json_array_length(s.symbols_json) FROM symbols s WHERE s.file_id = f.id AND s.file_hash = fb.hash)"
+        } else {
+            "0"
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT f.path, f.line_count, COALESCE({}, 0)
+             FROM files f
+             JOIN file_branches fb ON f.id = fb.file_id
+             JOIN branches b ON fb.branch_id = b.id
+             WHERE b.name = ?",
+            symbol_count
+        ))?;
+        let rows = stmt.query_map([&branch], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+        })?;
+
+        let mut by_dir: HashMap<String, DirectoryStats> = HashMap::new();
+        for row in rows {
+            let (path, lines, symbols) = row?;
+            let components: Vec<&str> = path.split('/').collect();
+            let dir_components = &components[..components.len() - 1];
+            let directory = if dir_components.is_empty() {
+                ".".to_string()
+            } else {
+                dir_components[..dir_components.len().min(depth.max(1))].join("/")
+            };
+
+            let entry = by_dir.entry(directory.clone()).or_insert_with(|| DirectoryStats {
+                directory,
+                files: 0,
+                lines: 0,
+                symbols: 0,
+            });
+            entry.files += 1;
+            entry.lines += lines as usize;
+            entry.symbols += symbols as usize;
+        }
+
+        let mut dirs: Vec<DirectoryStats> = by_dir.into_values().collect();
+        dirs.sort_by(|a, b| b.files.cmp(&a.files).then(a.directory.cmp(&b.directory)));
+        Ok(dirs)
+    }
+
+    /// Branch the statistics are reported for (`_default` outside git)
+    fn current_branch(&self) -> Option<String> {
+        if let Some(commit) = &self.revision {
+            return Some(commit.clone());
+        }
+        let workspace_root = self.workspace_root();
+        if crate::git::is_git_repo(&workspace_root) {
+            crate::git::get_git_state(&workspace_root)
+                .ok()
+                .map(|state| state.branch)
+        } else {
+            Some("_default".to_string())
+        }
+    }
+
+    /// Whether the symbol cache table exists (created on first symbol query or background indexing)
+    fn has_symbols_table(conn: &Connection) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'symbols'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ).is_ok_and(|count| count > 0)
+    }
+
     // ===== Branch-aware indexing methods =====
 
     /// Get or create a branch ID by name
@@ -1268,6 +1564,219 @@ provider = "groq"  # Options: openai, anthropic, groq
         Ok(())
     }
 
+    /// Create the workspace roots table if it doesn't exist
+    ///
+    /// Called from `init_meta_db()` for new caches and lazily by the root
+    /// accessors so caches created before multi-root support keep working.
+    pub(crate) fn ensure_roots_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS roots (
+                name TEXT PRIMARY KEY,
+                path TEXT NOT NULL UNIQUE,
+                file_count INTEGER DEFAULT 0,
+                last_indexed INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the set of indexed workspace roots and their file counts
+    ///
+    /// Passing an empty slice clears all roots (the whole workspace is indexed).
+    pub fn update_roots(&self, roots: &[(WorkspaceRoot, usize)]) -> Result<()> {
+        let db_path = self.cache_path.join(META_DB);
+        let mut conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for roots update")?;
+
+        Self::ensure_roots_table(&conn)?;
+
+        let now = chrono::Utc::now().timestamp();
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM roots", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO roots (name, path, file_count, last_indexed) VALUES (?, ?, ?, ?)"
+            )?;
+            for (root, file_count) in roots {
+                stmt.execute(rusqlite::params![root.name, root.path, file_count, now])?;
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Updated {} workspace roots", roots.len());
+        Ok(())
+    }
+
+    /// Get all indexed workspace roots, sorted by name
+    ///
+    /// Returns an empty list if the cache indexes the whole workspace.
+    pub fn list_roots(&self) -> Result<Vec<RootInfo>> {
+        let db_path = self.cache_path.join(META_DB);
+
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db")?;
+
+        Self::ensure_roots_table(&conn)?;
+
+        let mut stmt = conn.prepare(
+            "SELECT name, path, file_count, last_indexed FROM roots ORDER BY name"
+        )?;
+
+        let roots = stmt.query_map([], |row| {
+            Ok(RootInfo {
+                name: row.get(0)?,
+                path: row.get(1)?,
+                file_count: row.get(2)?,
+                last_indexed: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(roots)
+    }
+
+    /// Create the skipped files table if it doesn't exist
+    ///
+    /// Called from `init_meta_db()`, the format 2 -> 3 migration and the
+    /// skipped file accessors (which may run before a query migrates the cache).
+    pub(crate) fn ensure_skipped_files_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS skipped_files (
+                path TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                detail TEXT NOT NULL,
+                hash TEXT
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the files skipped by the last index run, and the limits it used
+    ///
+    /// `hash` is the content hash for files skipped after reading them (line
+    /// length, binary), so an unchanged skipped file doesn't force a re-index.
+    pub fn record_skipped_files(&self, limits: &str, skipped: &[(SkippedFile, Option<String>)]) -> Result<()> {
+        let db_path = self.cache_path.join(META_DB);
+        let mut conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for skipped files update")?;
+
+        Self::ensure_skipped_files_table(&conn)?;
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM skipped_files", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO skipped_files (path, reason, detail, hash) VALUES (?, ?, ?, ?)"
+            )?;
+            for (file, hash) in skipped {
+                stmt.execute(rusqlite::params![file.path, file.reason.as_str(), file.detail, hash])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO statistics (key, value, updated_at) VALUES ('index_limits', ?, ?)",
+            rusqlite::params![limits, chrono::Utc::now().timestamp()],
+        )?;
+        tx.commit()?;
+
+        log::debug!("Recorded {} skipped files", skipped.len());
+        Ok(())
+    }
+
+    /// Files skipped by the last index run, sorted by path
+    pub fn list_skipped_files(&self) -> Result<Vec<SkippedFile>> {
+        let db_path = self.cache_path.join(META_DB);
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db")?;
+        Self::ensure_skipped_files_table(&conn)?;
+
+        let mut stmt = conn.prepare("SELECT path, reason, detail FROM skipped_files ORDER BY path")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            let (path, reason, detail) = row?;
+            if let Some(reason) = SkipReason::parse(&reason) {
+                files.push(SkippedFile { path, reason, detail });
+            }
+        }
+        Ok(files)
+    }
+
+    /// Limits recorded by the last index run (see `record_skipped_files`)
+    pub fn recorded_index_limits(&self) -> Result<Option<String>> {
+        let db_path = self.cache_path.join(META_DB);
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db")?;
+
+        let limits = conn
+            .query_row("SELECT value FROM statistics WHERE key = 'index_limits'", [], |row| row.get(0))
+            .optional()?;
+        Ok(limits)
+    }
+
+    /// Create the linked files table if it doesn't exist
+    ///
+    /// Called from `init_meta_db()`, the format 4 -> 5 migration and the
+    /// linked file accessors (which may run before a query migrates the cache).
+    pub(crate) fn ensure_linked_files_table(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS linked_files (
+                path TEXT PRIMARY KEY,
+                canonical_path TEXT NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the canonical paths of files the last index run reached through symlinks
+    ///
+    /// Each entry is `(path, canonical_path)`; the canonical path is relative to
+    /// the workspace when the link target is inside it.
+    pub fn record_linked_files(&self, linked: &[(String, String)]) -> Result<()> {
+        let db_path = self.cache_path.join(META_DB);
+        let mut conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for linked files update")?;
+
+        Self::ensure_linked_files_table(&conn)?;
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM linked_files", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT OR REPLACE INTO linked_files (path, canonical_path) VALUES (?, ?)")?;
+            for (path, canonical) in linked {
+                stmt.execute([path, canonical])?;
+            }
+        }
+        tx.commit()?;
+
+        log::debug!("Recorded {} linked files", linked.len());
+        Ok(())
+    }
+
+    /// Content hashes of skipped files that were read before being skipped
+    pub fn load_skipped_hashes(&self) -> Result<HashMap<String, String>> {
+        let db_path = self.cache_path.join(META_DB);
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db")?;
+        Self::ensure_skipped_files_table(&conn)?;
+
+        let mut stmt = conn.prepare("SELECT path, hash FROM skipped_files WHERE hash IS NOT NULL")?;
+        let hashes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<String, String>, _>>()?;
+        Ok(hashes)
+    }
+
     /// Find a file with a specific hash (for symbol reuse optimization)
     ///
     /// Returns the path and branch where this hash was first seen,
@@ -1458,6 +1967,9 @@ provider = "groq"  # Options: openai, anthropic, groq
         let deleted_files = self.identify_deleted_files()?;
         log::info!("Found {} deleted files to remove from cache", deleted_files.len());
 
+        // Step 1.5: Keep tombstones for the removed files (opt-in via [tombstones])
+        self.record_tombstones(&deleted_files);
+
         if deleted_files.is_empty() {
             log::info!("No deleted files to compact - cache is clean");
             // Update timestamp anyway to prevent running compaction too frequently
@@ -1502,6 +2014,68 @@ provider = "groq"  # Options: openai, anthropic, groq
         })
     }
 
+    /// Remove deleted files from meta.db without waiting for compaction
+    ///
+    /// `paths` are workspace-relative files or directories (a directory covers
+    /// every file below it). Only files that no longer exist on disk are
+    /// removed. Returns the number of files removed.
+    pub fn remove_deleted_files(&self, paths: &[String]) -> Result<usize> {
+        let db_path = self.cache_path.join(META_DB);
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for deleted file removal")?;
+
+        let workspace_root = self.workspace_root();
+        let mut stmt = conn.prepare(
+            "SELECT id, path FROM files WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/'",
+        )?;
+
+        let mut deleted_file_ids = Vec::new();
+        for path in paths {
+            let files = stmt
+                .query_map([path], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (file_id, file_path) in files {
+                if !workspace_root.join(&file_path).exists() {
+                    deleted_file_ids.push(file_id);
+                }
+            }
+        }
+        drop(stmt);
+        drop(conn);
+
+        deleted_file_ids.sort_unstable();
+        deleted_file_ids.dedup();
+
+        self.record_tombstones(&deleted_file_ids);
+        self.delete_files_from_db(&deleted_file_ids)?;
+        Ok(deleted_file_ids.len())
+    }
+
+    /// Record tombstones for files about to be removed and prune expired ones
+    ///
+    /// Only runs when `[tombstones] enabled = true`. Best-effort: a failure is
+    /// logged and never blocks compaction.
+    fn record_tombstones(&self, file_ids: &[i64]) {
+        let config = match crate::tombstones::load_tombstone_config(&self.cache_path) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("Skipping tombstones: {}", e);
+                return;
+            }
+        };
+        if !config.enabled {
+            return;
+        }
+
+        let tombstones = crate::tombstones::TombstoneIndex::new(self.clone());
+        if let Err(e) = tombstones.record(file_ids) {
+            log::warn!("Failed to record tombstones: {}", e);
+        }
+        if let Err(e) = tombstones.prune(config.retention_days) {
+            log::warn!("Failed to prune tombstones: {}", e);
+        }
+    }
+
     /// Identify files in database that no longer exist on filesystem
     ///
     /// Returns a Vec of file IDs for files that should be removed from the cache.
@@ -1593,6 +2167,7 @@ provider = "groq"  # Options: openai, anthropic, groq
     /// Sums up the size of all cache files:
     /// - meta.db (SQLite database)
     /// - trigrams.bin (inverted index)
+    /// - tokens.bin (identifier-token index)
     /// - content.bin (file contents)
     /// - config.toml (configuration)
     fn calculate_cache_size(&self) -> Result<u64> {
@@ -1619,9 +2194,27 @@ pub struct BranchInfo {
     pub is_dirty: bool,
 }
 
+/// Workspace root metadata information
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RootInfo {
+    pub name: String,
+    pub path: String,
+    pub file_count: usize,
+    pub last_indexed: i64,
+}
+
+impl RootInfo {
+    /// Convert back into the root definition used by the indexer
+    pub fn to_workspace_root(&self) -> WorkspaceRoot {
+        WorkspaceRoot {
+            name: self.name.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
+
 // TODO: Implement memory-mapped readers for:
 // - SymbolReader (reads from symbols.bin)
-// - TokenReader (reads from tokens.bin)
 // - MetaReader (reads from meta.db)
 
 #[cfg(test)]
@@ -1839,6 +2432,47 @@ mod tests {
         assert_eq!(stats.lines_by_language.get("Python"), Some(&130)); // 50 + 80
     }
 
+    #[test]
+    fn test_stats_symbols_and_directories() {
+        use crate::models::{Language, SearchResult, Span, SymbolKind};
+
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+
+        cache.init().unwrap();
+        for (path, lines, hash) in [("src/main.rs", 10, "h1"), ("src/a/b.rs", 20, "h2"), ("build.rs", 5, "h3")] {
+            cache.update_file(path, "Rust", lines).unwrap();
+            cache.record_branch_file(path, "_default", hash, None).unwrap();
+        }
+        cache.update_stats("_default").unwrap();
+
+        let symbol = |name: &str| SearchResult::new(
+            String::new(),
+            Language::Rust,
+            SymbolKind::Function,
+            Some(name.to_string()),
+            Span::new(1, 0, 1, 0),
+            None,
+            format!("fn {}() {{}}", name),
+        );
+        let symbol_cache = crate::symbol_cache::SymbolCache::open(&cache.cache_path).unwrap();
+        symbol_cache.set("src/main.rs", "h1", &[symbol("main"), symbol("run")]).unwrap();
+        // Symbols cached for an older version of a file don't count
+        symbol_cache.set("src/a/b.rs", "stale", &[symbol("old")]).unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.symbols_by_language.get("Rust"), Some(&2));
+        assert!(stats.component_sizes.symbols > 0);
+        assert!(stats.component_sizes.metadata > 0);
+
+        let dirs = cache.directory_stats(1).unwrap();
+        let summary: Vec<_> = dirs.iter().map(|d| (d.directory.as_str(), d.files, d.lines, d.symbols)).collect();
+        assert_eq!(summary, vec![("src", 2, 30, 2), (".", 1, 5, 0)]);
+
+        let nested = cache.directory_stats(2).unwrap();
+        assert!(nested.iter().any(|d| d.directory == "src/a" && d.files == 1));
+    }
+
     #[test]
     fn test_list_files_empty() {
         let temp = TempDir::new().unwrap();
@@ -1868,6 +2502,28 @@ mod tests {
         assert_eq!(files[0].language, "rust");
     }
 
+    #[test]
+    fn test_remove_deleted_files() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+
+        cache.init().unwrap();
+        std::fs::create_dir(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        for path in ["src/main.rs", "src/gone.rs", "old/a.rs", "old/b.rs", "older.rs"] {
+            cache.update_file(path, "rust", 100).unwrap();
+        }
+
+        // Files still on disk are kept; a directory covers only the files below it
+        let removed = cache
+            .remove_deleted_files(&["src/main.rs".to_string(), "src/gone.rs".to_string(), "old".to_string()])
+            .unwrap();
+        assert_eq!(removed, 3);
+
+        let paths: Vec<String> = cache.list_files().unwrap().into_iter().map(|f| f.path).collect();
+        assert_eq!(paths, vec!["older.rs", "src/main.rs"]);
+    }
+
     #[test]
     fn test_list_files_before_init() {
         let temp = TempDir::new().unwrap();
@@ -2168,4 +2824,53 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("files") && err.contains("missing"));
     }
+
+    #[test]
+    fn test_list_roots_empty() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+
+        cache.init().unwrap();
+        let roots = cache.list_roots().unwrap();
+        assert!(roots.is_empty());
+    }
+
+    #[test]
+    fn test_update_roots_replaces_previous() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+
+        cache.init().unwrap();
+
+        let api = WorkspaceRoot { name: "api".to_string(), path: "packages/api".to_string() };
+        let web = WorkspaceRoot { name: "web".to_string(), path: "packages/web".to_string() };
+
+        cache.update_roots(&[(api.clone(), 10), (web, 5)]).unwrap();
+        let roots = cache.list_roots().unwrap();
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].name, "api");
+        assert_eq!(roots[0].file_count, 10);
+        assert_eq!(roots[1].path, "packages/web");
+
+        cache.update_roots(&[(api, 12)]).unwrap();
+        let roots = cache.list_roots().unwrap();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].file_count, 12);
+    }
+
+    #[test]
+    fn test_list_roots_legacy_database() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+
+        cache.init().unwrap();
+
+        // Simulate a cache created before the roots table existed
+        let db_path = cache.path().join(META_DB);
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute("DROP TABLE roots", []).unwrap();
+
+        let roots = cache.list_roots().unwrap();
+        assert!(roots.is_empty());
+    }
 }
@@ -0,0 +1,141 @@
+//! Cooperative cancellation for query timeouts
+//!
+//! `--timeout` is checked between query phases, but a single pathological file
+//! or AST pattern can keep one phase busy far past it. A [`CancelToken`]
+//! carries the query's deadline into the parsing and AST phases: files not yet
+//! started are skipped once it has passed, and tree-sitter work started while
+//! the token is [entered](CancelToken::enter) on a thread (including rayon
+//! workers) gets the remaining time as its own timeout, so a parse already in
+//! progress gives up too.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    /// Token entered on this thread, seen by [`parser`] and [`timeout_micros`]
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// Deadline shared by every phase and worker thread of one query
+///
+/// Clones share the cancelled state. The default token never cancels.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Token that cancels `timeout` from now (never, for `None`)
+    pub fn with_timeout(timeout: Option<Duration>) -> Self {
+        Self {
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether work should stop because the deadline has passed
+    ///
+    /// The first check that finds the deadline passed marks the token as
+    /// cancelled, so [`was_cancelled`](Self::was_cancelled) tells afterwards
+    /// whether any work was cut short.
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.cancelled.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// Whether a check found the deadline passed
+    pub fn was_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Time left before the deadline (None without one)
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Run `f` with this token entered on the current thread
+    pub fn enter<T>(&self, f: impl FnOnce() -> T) -> T {
+        /// Restores the previously entered token, even if `f` panics
+        struct Restore(Option<CancelToken>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(self.clone()))));
+        f()
+    }
+}
+
+/// Whether the token entered on this thread has been cancelled (false without one)
+pub fn cancelled() -> bool {
+    CURRENT.with(|current| current.borrow().as_ref().is_some_and(CancelToken::is_cancelled))
+}
+
+/// Timeout for tree-sitter work started now on this thread, in microseconds
+///
+/// 0 (no timeout) unless a token with a deadline is entered. A passed deadline
+/// gives the smallest timeout, so the work stops almost immediately.
+pub fn timeout_micros() -> u64 {
+    CURRENT.with(|current| match current.borrow().as_ref().and_then(CancelToken::remaining) {
+        Some(remaining) => u64::try_from(remaining.as_micros()).unwrap_or(u64::MAX).max(1),
+        None => 0,
+    })
+}
+
+/// New tree-sitter parser that gives up at the deadline of the token entered on this thread
+pub fn parser() -> tree_sitter::Parser {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_timeout_micros(timeout_micros());
+    parser
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_token_aborts_parse() {
+        let source = "fn f(x: i32) -> i32 { x + 1 }\n".repeat(20_000);
+        let parse = || {
+            let mut parser = parser();
+            parser.set_language(&tree_sitter_rust::LANGUAGE.into()).unwrap();
+            parser.parse(&source, None)
+        };
+
+        assert!(parse().is_some());
+
+        let token = CancelToken::with_timeout(Some(Duration::ZERO));
+        assert!(!token.was_cancelled());
+        assert!(token.enter(|| parse()).is_none());
+        assert!(token.enter(cancelled));
+        assert!(token.was_cancelled());
+
+        // Leaving the token restores the unbounded default
+        assert_eq!(timeout_micros(), 0);
+        assert!(!cancelled());
+    }
+
+    #[test]
+    fn test_token_without_deadline_never_cancels() {
+        let token = CancelToken::with_timeout(None);
+        assert!(!token.is_cancelled());
+        assert_eq!(token.enter(timeout_micros), 0);
+
+        let token = CancelToken::with_timeout(Some(Duration::from_secs(60)));
+        assert!(token.enter(timeout_micros) > 1_000_000);
+        assert!(!token.clone().is_cancelled());
+    }
+}
@@ -9,9 +9,12 @@ use indicatif::{ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
 
 use crate::cache::CacheManager;
+use crate::error_code::ReflexError;
 use crate::indexer::Indexer;
-use crate::models::{IndexConfig, Language};
+use crate::models::{IndexConfig, Language, WorkspaceRoot};
 use crate::output;
+use crate::parsers::span::SpanPolicy;
+use crate::parsers::visibility::Visibility;
 use crate::query::{QueryEngine, QueryFilter};
 
 /// Reflex: Local-first, structure-aware code search for AI agents
@@ -56,6 +59,211 @@ pub enum IndexSubcommand {
         #[arg(long)]
         pretty: bool,
     },
+
+    /// Warm the symbol cache for all files or a subset
+    ///
+    /// `rfx index` already caches symbols in the background, starting with the
+    /// directories recent queries found results in. Use this to cache the files
+    /// you are about to search first, or to wait until they are cached.
+    ///
+    /// Examples:
+    ///   rfx index symbols --wait --lang rust            # Cache Rust symbols now
+    ///   rfx index symbols --wait --glob 'src/**'        # Cache symbols under src/
+    ///   rfx index symbols --glob 'packages/api/**'      # Same, in the background
+    Symbols {
+        /// Parse in the foreground and return once the symbols are cached
+        #[arg(long)]
+        wait: bool,
+
+        /// Only files of these languages (can be repeated)
+        #[arg(long, value_name = "LANG")]
+        lang: Vec<String>,
+
+        /// Only files matching these globs (can be repeated)
+        #[arg(long, value_name = "GLOB")]
+        glob: Vec<String>,
+
+        /// Output format as JSON (only with --wait)
+        #[arg(long, requires = "wait")]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FlagsSubcommand {
+    /// List all feature flags found in the index with usage counts
+    ///
+    /// Examples:
+    ///   rfx flags list                   # Table of flags
+    ///   rfx flags list --json            # JSON output
+    List {
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Show every usage of a feature flag
+    ///
+    /// Examples:
+    ///   rfx flags where new-checkout           # All checks of "new-checkout"
+    ///   rfx flags where new-checkout --paths   # Unique file paths only
+    Where {
+        /// Flag key as written in source (exact match)
+        flag: String,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+
+        /// Only show unique file paths
+        #[arg(short, long)]
+        paths: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DeletedSubcommand {
+    /// List tombstones of removed files, newest first
+    ///
+    /// Examples:
+    ///   rfx deleted list                     # All tombstones
+    ///   rfx deleted list --path src/legacy   # Only paths containing "src/legacy"
+    ///   rfx deleted list --json              # JSON output
+    List {
+        /// Only show tombstones whose path contains this text
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistorySubcommand {
+    /// List recorded queries, newest first
+    ///
+    /// Examples:
+    ///   rfx history list                 # Last 20 queries
+    ///   rfx history list -n 100 --json   # JSON output
+    List {
+        /// Number of entries to show (0 = all)
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Run a recorded query again with its original arguments
+    ///
+    /// Examples:
+    ///   rfx history replay       # Most recent query
+    ///   rfx history replay 42    # Entry 42 from `rfx history list`
+    Replay {
+        /// Entry id (defaults to the most recent query)
+        id: Option<i64>,
+    },
+
+    /// Delete all recorded queries
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotSubcommand {
+    /// Pack the index into a single archive
+    ///
+    /// Examples:
+    ///   rfx snapshot save                        # Writes reflex.rfxsnap
+    ///   rfx snapshot save /tmp/index.rfxsnap     # Custom output path
+    Save {
+        /// Archive to write
+        #[arg(value_name = "FILE", default_value = "reflex.rfxsnap")]
+        output: PathBuf,
+
+        /// Output the manifest as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Restore an index from an archive, replacing the current index
+    ///
+    /// The archive must come from the same Reflex version (cache schema) on a
+    /// platform with the same byte order, built at the commit checked out here.
+    ///
+    /// Examples:
+    ///   rfx snapshot load reflex.rfxsnap
+    ///   rfx snapshot load reflex.rfxsnap --force && rfx index   # Older snapshot, then catch up
+    Load {
+        /// Archive to restore
+        #[arg(value_name = "FILE")]
+        archive: PathBuf,
+
+        /// Accept a snapshot built at a different commit than HEAD
+        #[arg(long)]
+        force: bool,
+
+        /// Output the manifest as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HooksSubcommand {
+    /// Install git hooks that re-index the files each commit, merge or checkout changed
+    ///
+    /// Adds a marked block to post-commit, post-merge and post-checkout that
+    /// runs `rfx index --only -` in the background, keeping existing hook
+    /// content. With husky the hooks go into .husky/; with lefthook nothing is
+    /// written and the lefthook.yml entries to add are printed instead.
+    ///
+    /// Examples:
+    ///   rfx hooks install
+    ///   rfx hooks install --uninstall
+    Install {
+        /// Remove the Reflex hooks instead
+        #[arg(long)]
+        uninstall: bool,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -78,7 +286,85 @@ pub enum Command {
         #[arg(short, long)]
         quiet: bool,
 
-        /// Subcommand (status, compact)
+        /// Index only these sub-directories as named roots (can be repeated)
+        ///
+        /// Roots share a single cache and are remembered for later runs.
+        /// The name defaults to the last path component; use name=path to override.
+        /// Query filters address roots by name with an @ prefix.
+        ///
+        /// Examples:
+        ///   rfx index --root packages/api --root packages/web
+        ///   rfx index --root backend=services/api
+        ///   rfx query "handler" --file @api
+        #[arg(long = "root", value_name = "[NAME=]PATH")]
+        roots: Vec<String>,
+
+        /// Forget the roots remembered from earlier `--root` runs and index the whole workspace
+        ///
+        /// Examples:
+        ///   rfx index --reset-roots
+        #[arg(long, conflicts_with = "roots")]
+        reset_roots: bool,
+
+        /// Leave matching files out of the index (gitignore syntax, can be repeated)
+        ///
+        /// Applied on top of .gitignore and .reflexignore files. Use a
+        /// .reflexignore file in the repository to make exclusions permanent.
+        ///
+        /// Examples:
+        ///   rfx index --exclude vendor/ --exclude '*.generated.ts'
+        ///   rfx index --exclude '/tests/fixtures/'
+        #[arg(long = "exclude", value_name = "GLOB")]
+        excludes: Vec<String>,
+
+        /// Only index files changed in the working tree (modified or untracked)
+        ///
+        /// Changed files go into a small overlay that shadows the full index,
+        /// so queries see uncommitted edits without a full rebuild. Requires
+        /// an existing index; the next full `rfx index` replaces the overlay.
+        ///
+        /// Examples:
+        ///   rfx index --dirty-only
+        #[arg(long, conflicts_with = "force")]
+        dirty_only: bool,
+
+        /// Only re-index files matching these globs or paths (can be repeated)
+        ///
+        /// Use `-` to read paths from stdin, one per line. Changed files go
+        /// into the overlay used by --dirty-only, next to what it already
+        /// holds, so a handful of files refresh in milliseconds. Requires an
+        /// existing index; the next full `rfx index` folds them in.
+        ///
+        /// Examples:
+        ///   rfx index --only src/query.rs --only 'src/parsers/**'
+        ///   git diff --name-only | rfx index --only -
+        #[arg(long, value_name = "GLOB", conflicts_with_all = ["force", "dirty_only"])]
+        only: Vec<String>,
+
+        /// Index a git revision (branch, tag or commit) instead of the working tree
+        ///
+        /// Files are read from the git object database into a separate cache
+        /// segment; nothing is checked out. Query it with `rfx query --rev`,
+        /// which also indexes the revision on first use. Re-running rebuilds it.
+        ///
+        /// Examples:
+        ///   rfx index --rev v1.2.0
+        ///   rfx query "parse_config" --rev HEAD~20
+        #[arg(long, value_name = "REV", conflicts_with_all = ["force", "dirty_only", "only", "roots", "reset_roots"])]
+        rev: Option<String>,
+
+        /// Memory budget in MB for file content held while indexing
+        ///
+        /// Files are read, hashed and parsed in parallel batches sized to fit
+        /// this budget while the previous batch is written to the index.
+        /// Overrides memory_budget_mb under [performance] in .reflex/config.toml.
+        ///
+        /// Examples:
+        ///   rfx index --memory-budget 2048
+        #[arg(long, value_name = "MB")]
+        memory_budget: Option<usize>,
+
+        /// Subcommand (status, compact, symbols)
         #[command(subcommand)]
         command: Option<IndexSubcommand>,
     },
@@ -115,15 +401,24 @@ pub enum Command {
         symbols: bool,
 
         /// Filter by language
-        /// Supported: rust, python, javascript, typescript, vue, svelte, go, java, php, c, c++, c#, ruby, kotlin, zig
+        /// Supported: rust, python, javascript, typescript, vue, svelte, go, java, php, c, c++, c#, ruby, kotlin, zig, shell, sql
+        /// Build files: make, cmake, bazel
+        /// Embedded regions (requires [embedded] enabled): sql, graphql, html
         #[arg(short, long)]
         lang: Option<String>,
 
-        /// Filter by symbol kind (implies --symbols)
+        /// Filter by symbol kind, or a comma-separated list of kinds (implies --symbols)
         /// Supported: function, class, struct, enum, interface, trait, constant, variable, method, module, namespace, type, macro, property, event, import, export, attribute
+        /// (decorator and annotation are aliases of attribute)
+        /// Example: --kind struct,enum,trait lists every type definition in one query
         #[arg(short, long)]
         kind: Option<String>,
 
+        /// Leave out symbols of these kinds (comma-separated, implies --symbols)
+        /// Example: rfx query handle --kind-not import,variable
+        #[arg(long)]
+        kind_not: Option<String>,
+
         /// Use AST pattern matching (SLOW: 500ms-2s+, scans all files)
         ///
         /// WARNING: AST queries bypass trigram optimization and scan the entire codebase.
@@ -141,6 +436,31 @@ pub enum Command {
         #[arg(long)]
         ast: bool,
 
+        /// Run a named AST pattern instead of an S-expression (implies --ast, requires --lang)
+        ///
+        /// Built-in presets: async-functions, unused-params, empty-catch, unsafe-blocks,
+        /// unwrap-calls, bare-except, console-log (availability varies by language).
+        /// Add project presets as .reflex/ast_presets/<lang>/<name>.scm
+        ///
+        /// Examples:
+        ///   rfx query --ast-preset async-functions --lang rust
+        ///   rfx query --ast-preset unused-params --lang python --glob "src/**"
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["pattern", "ast"])]
+        ast_preset: Option<String>,
+
+        /// Interpret the pattern as code with metavariables (structural search, requires --lang)
+        ///
+        /// $NAME matches any single node and is returned in `captures` (--json);
+        /// repeating it requires the same text. $_ matches without capturing,
+        /// $$$ matches any number of arguments/statements.
+        ///
+        /// Examples:
+        ///   rfx query 'if ($COND) { return $X; }' --pattern --lang typescript
+        ///   rfx query 'foo($A, $$$)' --pattern --lang rust
+        ///   rfx query '$X == $X' --pattern --lang python
+        #[arg(long = "pattern", id = "structural", conflicts_with_all = ["ast", "ast_preset", "regex", "symbols"])]
+        structural: bool,
+
         /// Use regex pattern matching
         ///
         /// Enables standard regex syntax in the search pattern:
@@ -168,6 +488,18 @@ pub enum Command {
         #[arg(long)]
         pretty: bool,
 
+        /// Output format: json (same as --json) or ndjson
+        ///
+        /// ndjson streams one JSON object per line as results are produced:
+        ///   {"type":"header",...}    Index status, emitted first
+        ///   {"type":"file",...}      One per matching file, sorted by path
+        ///   {"type":"summary",...}   Pagination info, emitted last
+        ///
+        /// Useful for piping broad queries into other tools without waiting
+        /// for the full response. Not compatible with --ast, --count or --paths.
+        #[arg(long, value_parser = ["json", "ndjson"])]
+        format: Option<String>,
+
         /// AI-optimized mode: returns JSON with ai_instruction field
         /// Implies --json (minified by default, use --pretty for formatted output)
         /// Provides context-aware guidance to AI agents on response format and next actions
@@ -175,6 +507,7 @@ pub enum Command {
         ai: bool,
 
         /// Maximum number of results
+        /// Default is tuned to repo size by `rfx index` (100 for mid-sized repos)
         #[arg(short = 'n', long)]
         limit: Option<usize>,
 
@@ -190,6 +523,7 @@ pub enum Command {
 
         /// Filter by file path (supports substring matching)
         /// Example: --file math.rs or --file helpers/
+        /// Use @name to match a workspace root from `rfx index --root` (e.g., --file @api)
         #[arg(short = 'f', long)]
         file: Option<String>,
 
@@ -215,13 +549,93 @@ pub enum Command {
         #[arg(long)]
         contains: bool,
 
+        /// Rank symbols by name similarity instead of matching literally (implies --symbols)
+        ///
+        /// Tolerates typos, other case conventions and abbreviations, like an
+        /// editor's Ctrl-P:
+        ///   "getUserByID" finds get_user_by_id, getUserById
+        ///   "gubi"        finds getUserById
+        ///
+        /// Results are ordered best match first (ties by path and line), so
+        /// --limit keeps the closest names.
+        #[arg(long, conflicts_with_all = ["regex", "exact", "contains", "ast"])]
+        fuzzy: bool,
+
+        /// Minimum similarity of a --fuzzy match, 0.0-1.0 (default: [search] fuzzy_threshold, 0.8)
+        #[arg(long, value_name = "SCORE", requires = "fuzzy")]
+        fuzzy_threshold: Option<f64>,
+
+        /// Match identifiers by word parts, across naming conventions
+        ///
+        /// The pattern is split like an identifier and every part must appear in
+        /// one identifier on the line, in any order and any case convention:
+        ///   "user config"  finds parseUserConfig, load_user_config, USER_CONFIG_PATH
+        ///   "userConfig"   same query
+        ///
+        /// Combine with --symbols to match definition names only.
+        #[arg(long, conflicts_with_all = ["regex", "exact", "contains", "fuzzy", "ast"])]
+        tokens: bool,
+
+        /// Rank code by meaning: the pattern is a natural-language description
+        ///
+        /// Blends embedding similarity with keyword hits over the chunks embedded
+        /// by `rfx embed` (run it first). Results are ranked best first.
+        ///
+        /// Examples:
+        ///   rfx query "retry logic for http client" --semantic
+        ///   rfx query "where are sessions invalidated" --semantic --lang go --json
+        #[arg(long, conflicts_with_all = ["regex", "exact", "contains", "fuzzy", "tokens", "ast", "ast_preset", "structural", "symbols", "kind", "kind_not"])]
+        semantic: bool,
+
         /// Only show count and timing, not the actual results
         #[arg(short, long)]
         count: bool,
 
-        /// Query timeout in seconds (0 = no timeout, default: 30)
-        #[arg(short = 't', long, default_value = "30")]
-        timeout: u64,
+        /// Query timeout in seconds (0 = no timeout)
+        /// Default is tuned to repo size by `rfx index` (30s for mid-sized repos)
+        ///
+        /// Timing out while candidates are found is an error; timing out while
+        /// symbols are parsed or AST patterns matched returns the results so far
+        /// (JSON output reports `complete: false` and `partial: "TIMEOUT_PARTIAL"`).
+        #[arg(short = 't', long)]
+        timeout: Option<u64>,
+
+        /// Best-effort time budget in milliseconds
+        ///
+        /// When the budget expires, return the results verified so far instead
+        /// of failing like --timeout. JSON output reports `complete: false` and
+        /// the number of unscanned candidate files.
+        ///
+        /// Examples:
+        ///   rfx query "handler" --symbols --budget-ms 150 --json
+        #[arg(long, value_name = "MS")]
+        budget_ms: Option<u64>,
+
+        /// Approximate token budget for JSON output (~4 characters per token)
+        ///
+        /// Responses over budget lose context lines first, then get shorter
+        /// previews, then drop matches (plain text matches before symbol
+        /// definitions). Trimmed responses carry `truncated: true`, and --ai
+        /// output explains how to narrow the query.
+        ///
+        /// Examples:
+        ///   rfx query "unwrap" --ai --max-tokens 4000
+        #[arg(long, value_name = "N")]
+        max_tokens: Option<usize>,
+
+        /// Report how the query was executed
+        ///
+        /// Shows the candidate strategy (trigram, regex, tokens, keyword, fuzzy
+        /// or semantic), how many candidates the trigram index found, how many were
+        /// pruned by the overlay, --lang and --glob, symbol cache hits vs
+        /// parsed files, and the time spent per phase. JSON output carries
+        /// this as `plan` and `timings`.
+        ///
+        /// Examples:
+        ///   rfx query "handler" --symbols --explain
+        ///   rfx query "class" --lang php --json --explain
+        #[arg(long, conflicts_with = "ast")]
+        explain: bool,
 
         /// Use plain text output (disable colors and syntax highlighting)
         #[arg(long)]
@@ -238,6 +652,9 @@ pub enum Command {
         ///   --glob app/Models/*.php     PHP files directly in Models/ (not subdirs)
         ///   --glob tests/**/*_test.go   All test files under tests/
         ///
+        /// Workspace roots (from `rfx index --root`) can be addressed by name:
+        ///   --glob @api/**/*.ts         All .ts files under the "api" root
+        ///
         /// Tip: Use --file for simple substring matching instead:
         ///   --file User.php             Simpler than --glob **/User.php
         #[arg(short = 'g', long)]
@@ -254,11 +671,117 @@ pub enum Command {
         #[arg(short = 'x', long)]
         exclude: Vec<String>,
 
+        /// Drop matching lines that also contain this pattern (can be repeated)
+        ///
+        /// A substring, or a regex with --regex. Applied to candidate lines before
+        /// symbols are parsed.
+        ///
+        /// Examples:
+        ///   rfx query client --not test --not mock
+        ///   rfx query "fn \w+_client" --regex --not "fn (test|mock)_"
+        #[arg(long = "not", value_name = "PATTERN")]
+        not: Vec<String>,
+
+        /// Only search files changed since a git ref (branch, tag or commit)
+        ///
+        /// Compares against the merge base with HEAD, so only files touched on
+        /// the current branch count. Uncommitted and untracked files are included.
+        ///
+        /// Examples:
+        ///   rfx query "unwrap" --changed-since main
+        ///   rfx query "TODO" --changed-since origin/main --paths
+        #[arg(long, value_name = "REF")]
+        changed_since: Option<String>,
+
+        /// Search a git revision (branch, tag or commit) instead of the working tree
+        ///
+        /// The revision is read from the git object database into its own cache
+        /// segment the first time it is queried (see `rfx index --rev`); the
+        /// working tree is never touched.
+        ///
+        /// Examples:
+        ///   rfx query "LegacyParser" --rev v1.0.0 --symbols
+        ///   rfx query "retry_policy" --rev HEAD~50
+        #[arg(long, value_name = "REV", conflicts_with_all = ["changed_since", "indexes"])]
+        rev: Option<String>,
+
+        /// Only keep matches inside the body of a named symbol
+        ///
+        /// The symbol is resolved per file via the symbol cache. Qualify it to
+        /// pick a method (Engine.search or Engine::search) or prefix a path
+        /// substring to pick a file (src/query.rs:search).
+        ///
+        /// Examples:
+        ///   rfx query "unwrap" --in parse_config
+        ///   rfx query "self.cache" --in src/query.rs:QueryEngine::search
+        #[arg(long = "in", visible_alias = "scope", value_name = "SYMBOL", conflicts_with = "ast")]
+        scope: Option<String>,
+
+        /// Only keep symbols whose qualified name ends with this path (implies --symbols)
+        ///
+        /// Segments are the enclosing modules, classes and impl targets, separated
+        /// by :: or . in any language.
+        ///
+        /// Examples:
+        ///   rfx query "run" --qualified Worker::run
+        ///   rfx query "save" --qualified models.User.save
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["ast", "regex"])]
+        qualified: Option<String>,
+
+        /// Only keep exported (public) or local (private) symbols (implies --symbols)
+        ///
+        /// Exported means `pub` in Rust and Zig, `export` in JavaScript/TypeScript,
+        /// `public` in Java and C#, an uppercase name in Go and no leading
+        /// underscore in Python. Members also need an exported parent.
+        ///
+        /// Examples:
+        ///   rfx query "parse" --visibility public --lang rust
+        ///   rfx query "helper" --visibility private --kind function
+        #[arg(long, value_name = "VISIBILITY", conflicts_with_all = ["ast", "regex"])]
+        visibility: Option<String>,
+
+        /// Only search files owned by this CODEOWNERS team or user
+        ///
+        /// Uses the CODEOWNERS file (.github/, root, docs/ or .gitlab/) recorded
+        /// by `rfx index`. Matching is case-insensitive, the `@` is optional and
+        /// a bare team name matches its organisation (`payments` = `@acme/payments`).
+        ///
+        /// Examples:
+        ///   rfx query "charge" --owner @acme/team-payments
+        ///   rfx query "Invoice" --symbols --owner team-payments
+        #[arg(long, value_name = "OWNER")]
+        owner: Option<String>,
+
+        /// Only search files touching this database table or ORM model
+        ///
+        /// Uses the table registry built by `rfx index` from ORM models (Eloquent,
+        /// SQLAlchemy, Django, Diesel, Prisma) and raw SQL. A file touches the table
+        /// if it defines or queries it or mentions one of its models. Without a
+        /// pattern, finds every mention of the table and its models.
+        ///
+        /// Examples:
+        ///   rfx query --table users                 # All code touching users
+        ///   rfx query "delete" --table User         # Deletes in files using User
+        #[arg(long, value_name = "TABLE")]
+        table: Option<String>,
+
         /// Return only unique file paths (no line numbers or content)
         /// Compatible with --json to output ["path1", "path2", ...]
         #[arg(short = 'p', long)]
         paths: bool,
 
+        /// Aggregate results into groups with match and file counts
+        ///
+        /// symbol = innermost symbol containing each match (path:Outer.inner),
+        /// dir = directory, lang = language, kind = symbol kind (text for
+        /// full-text matches). Groups cover every result unless --limit is given.
+        ///
+        /// Examples:
+        ///   rfx query "unwrap" --group-by dir          # Which modules mention unwrap
+        ///   rfx query "retry" --group-by symbol --json
+        #[arg(long, value_name = "GROUP", conflicts_with_all = ["count", "paths"])]
+        group_by: Option<String>,
+
         /// Disable smart preview truncation (show full lines)
         /// By default, previews are truncated to ~100 chars to reduce token usage
         #[arg(long)]
@@ -281,40 +804,247 @@ pub enum Command {
         /// Currently only available for Rust files
         #[arg(long)]
         dependencies: bool,
-    },
-
-    /// Start a local HTTP API server
-    Serve {
-        /// Port to listen on
-        #[arg(short, long, default_value = "7878")]
-        port: u16,
 
-        /// Host to bind to
-        #[arg(long, default_value = "127.0.0.1")]
-        host: String,
-    },
+        /// Attach related files to each result file (JSON output)
+        ///
+        /// Lists the file's tests, header/implementation counterpart, and its
+        /// strongest importers and imports, up to N per relation (default: 5).
+        ///
+        /// Examples:
+        ///   rfx query "parse_config" --symbols --json --related
+        ///   rfx query "Router" --json --related 3
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "5")]
+        related: Option<usize>,
 
-    /// Show index statistics and cache information
-    Stats {
-        /// Output format as JSON
+        /// Attach each result file's CODEOWNERS owners (JSON output)
         #[arg(long)]
-        json: bool,
+        with_owners: bool,
 
-        /// Pretty-print JSON output (only with --json)
+        /// Attach the last commit, author and age of each match's lines (JSON output)
+        ///
+        /// Reports the newest commit among the lines of the match span. Blame is
+        /// cached per file until its content changes.
+        ///
+        /// Examples:
+        ///   rfx query "retry_payment" --symbols --json --blame
         #[arg(long)]
-        pretty: bool,
-    },
+        blame: bool,
 
-    /// Clear the local cache
-    Clear {
-        /// Skip confirmation prompt
-        #[arg(short, long)]
-        yes: bool,
-    },
+        /// Also report removed files whose path or last symbols match the pattern
+        ///
+        /// Requires tombstones ([tombstones] enabled = true in .reflex/config.toml),
+        /// recorded when compaction drops deleted files. See `rfx deleted list`.
+        #[arg(long, conflicts_with = "ast")]
+        include_deleted: bool,
 
-    /// List all indexed files
-    ListFiles {
-        /// Output format as JSON
+        /// Include each symbol's doc comment or docstring (///, JSDoc, javadoc, Python docstrings)
+        /// Only applicable to symbol searches
+        #[arg(long)]
+        with_docs: bool,
+
+        /// Span policy for symbol results: body (default), with-attrs, with-docs
+        ///
+        /// with-attrs extends spans (and --expand bodies) over preceding attributes,
+        /// annotations and decorators; with-docs also covers the doc comment above them.
+        ///
+        /// Examples:
+        ///   rfx query Config --kind struct --expand --span with-attrs
+        ///   rfx query handler --symbols --expand --span with-docs
+        #[arg(long, value_name = "POLICY")]
+        span: Option<String>,
+
+        /// Show N lines of context before each match (like grep -B)
+        #[arg(short = 'B', long, value_name = "N")]
+        before: Option<usize>,
+
+        /// Show N lines of context after each match (like grep -A)
+        #[arg(short = 'A', long, value_name = "N")]
+        after: Option<usize>,
+
+        /// Show N lines of context before and after each match (like grep -C)
+        /// --before/--after take precedence for their side
+        /// In JSON output, this sets context_before/context_after (default: 3)
+        #[arg(short = 'C', long = "context", value_name = "N")]
+        context_lines: Option<usize>,
+
+        /// Query the index of another project directory instead of the current one
+        ///
+        /// Result paths stay relative to that project's root.
+        ///
+        /// Example:
+        ///   rfx query "handler" --dir ../other-service
+        #[arg(long, value_name = "PATH", default_value = ".")]
+        dir: PathBuf,
+
+        /// Also search the index of another project directory (can be repeated)
+        ///
+        /// Results from every index are merged and labelled with a `repo` field
+        /// (the project's directory name). Plain output shows paths as repo:path.
+        /// --limit and --offset apply to each index separately.
+        /// Not compatible with --ast or --format ndjson.
+        ///
+        /// Examples:
+        ///   rfx query "UserCreated" --index ../billing --index ../notifications
+        ///   rfx query "handler" --symbols --index ../auth --json
+        #[arg(long = "index", value_name = "PATH")]
+        indexes: Vec<PathBuf>,
+    },
+
+    /// Query with a compact filter expression
+    ///
+    /// The pattern and all filters go in one argument, translated to the same
+    /// options as `rfx query` (which remains the stable interface):
+    ///   word / "two words"   full-text pattern
+    ///   sym:NAME             symbol pattern (--symbols)
+    ///   re:REGEX             regex pattern (--regex)
+    ///   kind:KIND lang:LANG  --kind / --lang
+    ///   path:GLOB !path:GLOB --glob / --exclude (repeatable)
+    ///   file:TEXT since:REF  --file / --changed-since
+    ///   match:exact|contains --exact / --contains
+    ///   limit:N offset:N     --limit / --offset
+    ///
+    /// Examples:
+    ///   rfx q 'sym:parse kind:function lang:rust path:src/** !path:tests/**'
+    ///   rfx q 'kind:struct lang:go' --count
+    ///   rfx q 're:"fn \w+_test" since:main' --json
+    Q {
+        /// Query expression
+        expr: String,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+
+        /// Only show count of results
+        #[arg(long)]
+        count: bool,
+
+        /// Return only unique file paths
+        #[arg(short = 'p', long)]
+        paths: bool,
+
+        /// Return all results (no limit)
+        #[arg(short = 'a', long)]
+        all: bool,
+
+        /// Query the index of another project directory instead of the current one
+        #[arg(long, value_name = "PATH", default_value = ".")]
+        dir: PathBuf,
+    },
+
+    /// Start a local HTTP API server
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value = "7878")]
+        port: u16,
+
+        /// Host to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Require `Authorization: Bearer <TOKEN>` on every endpoint except /health
+        ///
+        /// Falls back to the REFLEX_TOKEN environment variable. Without a token,
+        /// POST /index is only accepted from localhost.
+        ///
+        /// Example:
+        ///   REFLEX_TOKEN=$(openssl rand -hex 32) rfx serve --host 0.0.0.0
+        #[arg(long, value_name = "TOKEN")]
+        auth_token: Option<String>,
+
+        /// Serve HTTPS with this PEM certificate (requires --tls-key)
+        #[arg(long, value_name = "PATH", requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key for --tls-cert
+        #[arg(long, value_name = "PATH", requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+
+        /// Maximum requests per minute per client IP (default: unlimited)
+        #[arg(long, value_name = "N")]
+        rate_limit: Option<u32>,
+
+        /// Watch the directory and reindex on changes (events on GET /events)
+        #[arg(long)]
+        watch: bool,
+
+        /// Debounce for --watch in milliseconds (5000-30000)
+        #[arg(long, default_value = "15000", requires = "watch")]
+        debounce: u64,
+    },
+
+    /// Show index statistics and cache information
+    ///
+    /// Reports files, lines and cached symbols per language, and how much
+    /// disk each index component uses.
+    ///
+    /// Examples:
+    ///   rfx stats                      # Overview
+    ///   rfx stats --by-dir             # Breakdown by top-level directory
+    ///   rfx stats --by-dir --depth 2   # Breakdown by two directory levels
+    ///   rfx stats --json               # Machine-readable output
+    Stats {
+        /// Break down files, lines and symbols per directory
+        #[arg(long)]
+        by_dir: bool,
+
+        /// Directory levels to group by (only with --by-dir)
+        #[arg(long, default_value = "1", requires = "by_dir")]
+        depth: usize,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Diagnose index problems
+    ///
+    /// Checks that meta.db, content.bin, trigrams.bin and tokens.bin are
+    /// readable and agree with each other (orphaned or missing files, content
+    /// hash mismatches, truncated files, schema drift). Also lists files
+    /// quarantined after crashing a parser. Quarantined files are skipped by
+    /// symbol search and background symbol indexing until their content
+    /// changes, so one malformed file can't break every query.
+    ///
+    /// Examples:
+    ///   rfx doctor           # Show diagnostics
+    ///   rfx doctor --json    # Machine-readable report
+    ///   rfx doctor --repair  # Rebuild only the broken components
+    Doctor {
+        /// Rebuild the components with integrity issues
+        ///
+        /// Search indexes are rebuilt from content.bin; a broken content store
+        /// or schema drift triggers a re-index, keeping the rest of .reflex/.
+        #[arg(long)]
+        repair: bool,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Clear the local cache
+    Clear {
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List all indexed files
+    ListFiles {
+        /// Output format as JSON
         #[arg(long)]
         json: bool,
 
@@ -323,6 +1053,47 @@ pub enum Command {
         pretty: bool,
     },
 
+    /// Export every symbol as JSON Lines
+    ///
+    /// Streams one record per symbol (path, name, qualified name, kind, span,
+    /// signature) in path order, for search UIs, embeddings pipelines and
+    /// inventory dashboards. Files not yet in the symbol cache are parsed on the fly.
+    ///
+    /// Large exports can be split into pages of files with --limit/--after, and
+    /// an interrupted export to --out continues with --resume.
+    ///
+    /// Examples:
+    ///   rfx dump-symbols --out symbols.jsonl
+    ///   rfx dump-symbols --lang rust --kind function | jq -r .qualified_name
+    ///   rfx dump-symbols --out symbols.jsonl --resume
+    ///   rfx dump-symbols --limit 1000 --after src/foo.rs
+    #[command(name = "dump-symbols")]
+    DumpSymbols {
+        /// Only export symbols of this language
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Only export symbols of this kind (function, class, struct, ...)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Export at most N files (the next cursor is printed to stderr)
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Start after this file path (cursor from a previous page)
+        #[arg(long, value_name = "PATH", conflicts_with = "resume")]
+        after: Option<String>,
+
+        /// Continue an interrupted export, appending to --out
+        #[arg(long, requires = "out")]
+        resume: bool,
+    },
+
     /// Watch for file changes and auto-reindex
     ///
     /// Continuously monitors the workspace for changes and automatically
@@ -377,9 +1148,12 @@ pub enum Command {
     ///   rfx analyze --hotspots                     # Most-imported files
     ///   rfx analyze --hotspots --min-dependents 5  # Filter by minimum
     ///   rfx analyze --unused                       # Orphaned files
+    ///   rfx analyze --dead-symbols                 # Exported symbols nothing uses
     ///   rfx analyze --islands                      # Disconnected components
     ///   rfx analyze --hotspots --count             # Just show count
     ///   rfx analyze --circular --glob "src/**"     # Limit to src/
+    ///   rfx analyze --format dot | dot -Tsvg > deps.svg   # Export the import graph
+    ///   rfx analyze --hotspots --resolve-barrels   # Count barrel imports at their source
     Analyze {
         /// Show circular dependencies
         #[arg(long)]
@@ -397,6 +1171,19 @@ pub enum Command {
         #[arg(long)]
         unused: bool,
 
+        /// Show exported symbols that are never referenced outside their own file
+        ///
+        /// Entry points (main, lib.rs, index.ts, __init__.py) and test files are
+        /// skipped. Each symbol has a confidence: high (no references at all),
+        /// medium (only used in its own file) or low (only used by tests);
+        /// methods are one level lower since they may be called dynamically.
+        #[arg(long)]
+        dead_symbols: bool,
+
+        /// Minimum confidence for --dead-symbols: low (default), medium or high
+        #[arg(long, value_name = "LEVEL", requires = "dead_symbols")]
+        min_confidence: Option<String>,
+
         /// Show disconnected components (islands)
         #[arg(long)]
         islands: bool,
@@ -409,7 +1196,16 @@ pub enum Command {
         #[arg(long, requires = "islands")]
         max_island_size: Option<usize>,
 
-        /// Output format: tree (default), table, dot
+        /// Attribute imports of barrel files (`export ... from` re-exports) to the
+        /// files that define the imported symbols
+        #[arg(long)]
+        resolve_barrels: bool,
+
+        /// Output format: tree (default), table, dot, mermaid, graph (node/edge JSON),
+        /// sarif (SARIF 2.1.0 for GitHub code scanning)
+        ///
+        /// sarif reports --circular, --unused and --dead-symbols findings (all
+        /// three when none is given) without a limit.
         #[arg(short = 'f', long, default_value = "tree")]
         format: String,
 
@@ -473,6 +1269,8 @@ pub enum Command {
     ///   rfx deps src/main.rs                  # Show dependencies
     ///   rfx deps src/config.rs --reverse      # Show dependents
     ///   rfx deps src/api.rs --depth 3         # Transitive deps
+    ///   rfx deps src/api.rs --depth 2 -f mermaid   # Subgraph as Mermaid
+    ///   rfx deps src/app.ts --resolve-barrels     # Look through index.ts re-exports
     Deps {
         /// File path to analyze
         file: PathBuf,
@@ -485,7 +1283,12 @@ pub enum Command {
         #[arg(short, long, default_value = "1")]
         depth: usize,
 
-        /// Output format: tree (default), table, dot
+        /// Attribute imports of barrel files (`export ... from` re-exports) to the
+        /// files that define the imported symbols
+        #[arg(long)]
+        resolve_barrels: bool,
+
+        /// Output format: tree (default), table, dot, mermaid, graph (node/edge JSON)
         #[arg(short = 'f', long, default_value = "tree")]
         format: String,
 
@@ -498,1560 +1301,5479 @@ pub enum Command {
         pretty: bool,
     },
 
-    /// Ask a natural language question and generate search queries
+    /// Show every file and line affected by renaming or changing a symbol
     ///
-    /// Uses an LLM to translate natural language questions into `rfx query` commands.
-    /// Requires API key configuration for one of: OpenAI, Anthropic, or Groq.
-    ///
-    /// If no question is provided, launches interactive chat mode by default.
-    ///
-    /// Configuration:
-    ///   1. Run interactive setup wizard (recommended):
-    ///      rfx ask --configure
-    ///
-    ///   2. OR set API key via environment variable:
-    ///      - OPENAI_API_KEY, ANTHROPIC_API_KEY, or GROQ_API_KEY
-    ///
-    ///   3. Optional: Configure provider in .reflex/config.toml:
-    ///      [semantic]
-    ///      provider = "groq"  # or openai, anthropic
-    ///      model = "llama-3.3-70b-versatile"  # optional, defaults to provider default
+    /// Finds the symbol's definitions, every line mentioning its name, and walks
+    /// the dependency graph backwards from each defining file. References are
+    /// grouped by how their file reaches a definition:
+    ///   direct      the defining file or a file importing it
+    ///   transitive  a file importing it through other files (re-exports)
+    ///   unlinked    no import path found (name collision or dynamic use)
     ///
     /// Examples:
-    ///   rfx ask --configure                           # Interactive setup wizard
-    ///   rfx ask                                       # Launch interactive chat (default)
-    ///   rfx ask "Find all TODOs in Rust files"
-    ///   rfx ask "Where is the main function defined?" --execute
-    ///   rfx ask "Show me error handling code" --provider groq
-    Ask {
-        /// Natural language question
-        question: Option<String>,
-
-        /// Execute queries immediately without confirmation
+    ///   rfx impact verify_token                  # Direct and transitive usages
+    ///   rfx impact Config --kind struct --json   # Only the Config struct
+    ///   rfx impact parse --lang python --depth 1 # Direct importers only
+    Impact {
+        /// Symbol name (exact match)
+        symbol: String,
+
+        /// Only consider definitions of this kind (function, struct, class, ...)
         #[arg(short, long)]
-        execute: bool,
+        kind: Option<String>,
 
-        /// Override configured LLM provider (openai, anthropic, groq)
+        /// Filter by language
         #[arg(short, long)]
-        provider: Option<String>,
+        lang: Option<String>,
 
-        /// Output format as JSON
+        /// Maximum import distance from a defining file
+        #[arg(short, long, default_value = "3")]
+        depth: usize,
+
+        /// Output as JSON
         #[arg(long)]
         json: bool,
 
-        /// Pretty-print JSON output (only with --json)
+        /// Pretty-print JSON output
         #[arg(long)]
         pretty: bool,
+    },
 
-        /// Additional context to inject into prompt (e.g., from `rfx context`)
-        #[arg(long)]
-        additional_context: Option<String>,
+    /// Show a type's supertypes and subtypes across files
+    ///
+    /// Reads extends/implements declarations (class base lists, interface and
+    /// trait supertypes, Rust `impl Trait for Type`) from every indexed file and
+    /// walks them from TYPE in both directions. Types are matched by their
+    /// unqualified name.
+    ///
+    /// Examples:
+    ///   rfx hierarchy BaseModel                  # Supertypes and subtypes
+    ///   rfx hierarchy Handler --depth 1          # Direct edges only
+    ///   rfx hierarchy Repository --lang ts --json
+    Hierarchy {
+        /// Type name (class, interface, trait or struct)
+        #[arg(value_name = "TYPE")]
+        name: String,
+
+        /// Maximum number of levels in each direction (0 = unlimited)
+        #[arg(short, long, default_value = "0")]
+        depth: usize,
 
-        /// Launch interactive configuration wizard to set up AI provider and API key
-        #[arg(long)]
-        configure: bool,
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
 
-        /// Enable agentic mode (multi-step reasoning with context gathering)
-        #[arg(long)]
-        agentic: bool,
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
 
-        /// Maximum iterations for query refinement in agentic mode (default: 2)
-        #[arg(long, default_value = "2")]
-        max_iterations: usize,
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
 
-        /// Skip result evaluation in agentic mode
+        /// Output as JSON
         #[arg(long)]
-        no_eval: bool,
+        json: bool,
 
-        /// Show LLM reasoning blocks at each phase (agentic mode only)
+        /// Pretty-print JSON output
         #[arg(long)]
-        show_reasoning: bool,
+        pretty: bool,
+    },
 
-        /// Verbose output: show tool results and details (agentic mode only)
-        #[arg(long)]
-        verbose: bool,
+    /// Count how often a term occurs and which identifiers appear next to it
+    ///
+    /// Reads the trigram index and content store directly without building
+    /// search results, so it's cheap to compare candidate terms and pick the
+    /// most selective one before running a query. Matching is word-boundary,
+    /// like the default `rfx query`.
+    ///
+    /// Examples:
+    ///   rfx freq token                      # Totals and top co-occurring identifiers
+    ///   rfx freq token --by dir             # Broken down by directory
+    ///   rfx freq handler --by lang --json   # Per-language counts as JSON
+    Freq {
+        /// Term to count (whole-word match)
+        term: String,
 
-        /// Quiet mode: suppress progress output (agentic mode only)
-        #[arg(long)]
-        quiet: bool,
+        /// Break counts down by directory or language (dir, lang)
+        #[arg(long, value_name = "GROUP")]
+        by: Option<String>,
 
-        /// Generate a conversational answer based on search results
-        #[arg(long)]
-        answer: bool,
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
 
-        /// Launch interactive chat mode (TUI) with conversation history
-        #[arg(short = 'i', long)]
-        interactive: bool,
+        /// Number of co-occurring identifiers to show
+        #[arg(long, default_value = "10")]
+        top: usize,
 
-        /// Debug mode: output full LLM prompts and retain terminal history
+        /// Output as JSON
         #[arg(long)]
-        debug: bool,
+        json: bool,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
     },
 
-    /// Generate codebase context for AI prompts
+    /// Find feature flag checks (LaunchDarkly, Unleash, custom patterns)
     ///
-    /// Provides structural and organizational context about the project to help
-    /// LLMs understand project layout. Use with `rfx ask --additional-context`.
+    /// Flag usages are recorded during `rfx index`. Built-in patterns match common
+    /// SDK calls such as variation("flag"), isEnabled("flag") and is_enabled("flag").
     ///
-    /// By default (no flags), shows all context types. Use individual flags to
-    /// select specific context types.
+    /// Add custom patterns in .reflex/config.toml (first capture group = flag name):
+    ///   [flags]
+    ///   patterns = ['Flags\.on\(\s*"([^"]+)"']
     ///
     /// Examples:
-    ///   rfx context                                    # Full context (all types)
-    ///   rfx context --path services/backend            # Full context for monorepo subdirectory
-    ///   rfx context --framework --entry-points         # Specific context types only
-    ///   rfx context --structure --depth 5              # Deep directory tree
-    ///
-    ///   # Use with semantic queries
-    ///   rfx ask "find auth" --additional-context "$(rfx context --framework)"
-    Context {
-        /// Show directory structure (enabled by default)
-        #[arg(long)]
-        structure: bool,
+    ///   rfx flags list                     # All flags with usage counts
+    ///   rfx flags where new-checkout       # Every check of a retired flag
+    Flags {
+        #[command(subcommand)]
+        command: FlagsSubcommand,
+    },
 
-        /// Focus on specific directory path
+    /// Show where environment variables and config keys are read or written
+    ///
+    /// Usages are recorded during `rfx index`: process.env.X, os.environ["X"],
+    /// std::env::var("X"), os.Getenv("X"), ENV["X"], config("app.name"),
+    /// viper.GetString("db.host"), @Value("${db.host}") and similar.
+    ///
+    /// Examples:
+    ///   rfx config-keys                        # All keys with read/write counts
+    ///   rfx config-keys --kind env             # Environment variables only
+    ///   rfx config-keys --key DATABASE_URL     # Every read and write of one key
+    ConfigKeys {
+        /// Show every usage of this key (exact match)
         #[arg(short, long)]
-        path: Option<String>,
+        key: Option<String>,
 
-        /// Show file type distribution (enabled by default)
+        /// Only environment variables (env) or config keys (config)
         #[arg(long)]
-        file_types: bool,
+        kind: Option<String>,
 
-        /// Detect project type (CLI/library/webapp/monorepo)
+        /// Output format as JSON
         #[arg(long)]
-        project_type: bool,
+        json: bool,
 
-        /// Detect frameworks and conventions
+        /// Pretty-print JSON output (only with --json)
         #[arg(long)]
-        framework: bool,
+        pretty: bool,
+    },
 
-        /// Show entry point files
+    /// Inspect tombstones of files removed from the index
+    ///
+    /// With tombstones enabled, compaction records the path, last hash, deletion
+    /// time and last symbols of every file it removes:
+    ///   [tombstones]
+    ///   enabled = true
+    ///
+    /// Examples:
+    ///   rfx deleted list                   # Every removed file
+    ///   rfx query legacy_parser --include-deleted   # Live matches plus tombstones
+    Deleted {
+        #[command(subcommand)]
+        command: DeletedSubcommand,
+    },
+
+    /// List, replay or clear previously executed queries
+    ///
+    /// Every `rfx query` and `rfx q` run is recorded in the index with its
+    /// arguments, result counts and timing. Disable with:
+    ///   [history]
+    ///   enabled = false
+    ///
+    /// Examples:
+    ///   rfx history list                  # Recent queries with ids
+    ///   rfx history replay 42             # Re-run entry 42
+    ///   rfx history clear                 # Forget everything
+    History {
+        #[command(subcommand)]
+        command: HistorySubcommand,
+    },
+
+    /// Save or load the whole index as a single archive
+    ///
+    /// Build the index once (e.g. in CI) and ship it instead of re-indexing
+    /// large repositories on every machine. Loading validates the archive
+    /// format, cache schema, platform endianness, file checksums and commit.
+    ///
+    /// Examples:
+    ///   rfx snapshot save reflex.rfxsnap   # In CI, after rfx index
+    ///   rfx snapshot load reflex.rfxsnap   # Locally, at the same commit
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotSubcommand,
+    },
+
+    /// Keep the index fresh from git hooks instead of `rfx watch`
+    ///
+    /// Examples:
+    ///   rfx hooks install               # post-commit, post-merge, post-checkout
+    ///   rfx hooks install --uninstall
+    Hooks {
+        #[command(subcommand)]
+        command: HooksSubcommand,
+    },
+
+    /// List error/exception types and the sites that construct or raise them
+    ///
+    /// Error types are found with the symbol parsers: names ending in Error/Exception,
+    /// classes inheriting an error base (Exception, Error, StandardError, ...), Rust
+    /// types deriving or implementing Error, and Go types with an Error() method.
+    /// Each type is listed with every raise/construct site in the index.
+    ///
+    /// Examples:
+    ///   rfx errors                         # Full inventory
+    ///   rfx errors ParseError              # One type (substring match)
+    ///   rfx errors --lang python --json    # Python exceptions as JSON
+    ///   rfx errors --defs-only             # Only the type definitions
+    Errors {
+        /// Only show error types whose name contains this text (case-insensitive)
+        name: Option<String>,
+
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
+
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
+
+        /// List type definitions without their sites
         #[arg(long)]
-        entry_points: bool,
+        defs_only: bool,
 
-        /// Show test organization pattern
+        /// Output as JSON
         #[arg(long)]
-        test_layout: bool,
+        json: bool,
 
-        /// List important configuration files
+        /// Pretty-print JSON output
         #[arg(long)]
-        config_files: bool,
+        pretty: bool,
+    },
 
-        /// Tree depth for --structure (default: 1)
-        #[arg(long, default_value = "1")]
-        depth: usize,
+    /// List TODO/FIXME/HACK/XXX annotations in comments
+    ///
+    /// Scans the comments of indexed files for annotation markers and attributes
+    /// each one to the innermost enclosing symbol. Markers in code or string
+    /// literals are ignored. The default markers come from .reflex/config.toml:
+    ///   [todos]
+    ///   markers = ["TODO", "FIXME", "HACK", "XXX"]
+    ///
+    /// Examples:
+    ///   rfx todos                          # All annotations
+    ///   rfx todos -m FIXME -m HACK         # Only these markers
+    ///   rfx todos --lang rust --json       # Rust annotations as JSON
+    Todos {
+        /// Marker to report (can be repeated; default: configured markers)
+        #[arg(short, long)]
+        marker: Vec<String>,
+
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
+
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
 
         /// Output as JSON
         #[arg(long)]
         json: bool,
-    },
 
-    /// Internal command: Run background symbol indexing (hidden from help)
-    #[command(hide = true)]
-    IndexSymbolsInternal {
-        /// Cache directory path
-        cache_dir: PathBuf,
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
     },
-}
 
-/// Try to run background cache compaction if needed
-///
-/// Checks if 24+ hours have passed since last compaction.
-/// If yes, spawns a non-blocking background thread to compact the cache.
-/// Main command continues immediately without waiting for compaction.
-///
-/// Compaction is skipped for commands that don't need it:
-/// - Clear (will delete the cache anyway)
-/// - Mcp (long-running server process)
-/// - Watch (long-running watcher process)
-/// - Serve (long-running HTTP server)
-fn try_background_compact(cache: &CacheManager, command: &Command) {
-    // Skip compaction for certain commands
-    match command {
-        Command::Clear { .. } => {
-            log::debug!("Skipping compaction for Clear command");
-            return;
-        }
-        Command::Mcp => {
-            log::debug!("Skipping compaction for Mcp command");
-            return;
-        }
-        Command::Watch { .. } => {
-            log::debug!("Skipping compaction for Watch command");
-            return;
-        }
-        Command::Serve { .. } => {
-            log::debug!("Skipping compaction for Serve command");
-            return;
-        }
-        _ => {}
-    }
+    /// List HTTP routes registered with web frameworks
+    ///
+    /// Recognizes axum, actix-web and Rocket routes, Express and Fastify routes,
+    /// Laravel routes, Spring @RequestMapping/@GetMapping handlers and Django
+    /// urls.py patterns, with method, path and handler. The same routes are
+    /// symbols of kind `route`, named by path (`rfx query /users --kind route`).
+    ///
+    /// A path pattern matches routes containing it or matching it segment by
+    /// segment, so `/users/42` finds `/users/:id`. A leading method narrows the
+    /// results to routes reachable with it.
+    ///
+    /// Examples:
+    ///   rfx routes                         # All routes
+    ///   rfx routes "GET /users"            # Where is GET /users handled?
+    ///   rfx routes --method post --json    # POST routes as JSON
+    Routes {
+        /// Route to look for: a path, optionally preceded by an HTTP method
+        pattern: Option<String>,
 
-    // Check if compaction should run
-    let should_compact = match cache.should_compact() {
-        Ok(true) => true,
-        Ok(false) => {
-            log::debug!("Compaction not needed yet (last run <24h ago)");
-            return;
-        }
-        Err(e) => {
-            log::warn!("Failed to check compaction status: {}", e);
-            return;
-        }
-    };
+        /// Only routes reachable with this HTTP method
+        #[arg(short, long)]
+        method: Option<String>,
 
-    if !should_compact {
-        return;
-    }
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
 
-    log::info!("Starting background cache compaction...");
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
 
-    // Clone cache path for background thread
-    let cache_path = cache.path().to_path_buf();
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
 
-    // Spawn background thread for compaction
-    std::thread::spawn(move || {
-        let cache = CacheManager::new(cache_path.parent().expect("Cache should have parent directory"));
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
 
-        match cache.compact() {
-            Ok(report) => {
-                log::info!(
-                    "Background compaction completed: {} files removed, {:.2} MB saved, took {}ms",
-                    report.files_removed,
-                    report.space_saved_bytes as f64 / 1_048_576.0,
-                    report.duration_ms
-                );
-            }
-            Err(e) => {
-                log::warn!("Background compaction failed: {}", e);
-            }
-        }
-    });
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-    log::debug!("Background compaction thread spawned - main command continuing");
-}
+    /// Search and replace across the index with a diff preview
+    ///
+    /// Candidate files come from the same trigram + regex pipeline as `rfx query`;
+    /// edits are computed against the current file contents. By default only a
+    /// unified diff is printed - nothing is modified until --write is given.
+    ///
+    /// Matching follows `rfx query`: word-boundary by default, substring with
+    /// --contains, regex with --regex (replacement may use $1 / ${name}).
+    ///
+    /// Examples:
+    ///   rfx replace old_name new_name                     # Preview a rename
+    ///   rfx replace old_name new_name --write             # Apply it
+    ///   rfx replace 'get_(\w+)\(\)' '$1()' --regex --lang rust
+    ///   rfx replace Foo Bar --glob 'src/**/*.ts' --json   # Structured edits for agents
+    Replace {
+        /// Pattern to search for
+        pattern: String,
+
+        /// Replacement text
+        replacement: String,
+
+        /// Treat the pattern as a regex (enables $1 / ${name} in the replacement)
+        #[arg(short = 'r', long)]
+        regex: bool,
 
-impl Cli {
-    /// Execute the CLI command
-    pub fn execute(self) -> Result<()> {
-        // Setup logging based on verbosity
-        let log_level = match self.verbose {
-            0 => "warn",   // Default: only warnings and errors
-            1 => "info",   // -v: show info messages
-            2 => "debug",  // -vv: show debug messages
-            _ => "trace",  // -vvv: show trace messages
-        };
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
-            .init();
+        /// Use substring matching instead of word-boundary matching
+        #[arg(long, conflicts_with = "regex")]
+        contains: bool,
 
-        // Try background compaction (non-blocking) before command execution
-        if let Some(ref command) = self.command {
-            // Use current directory as default cache location
-            let cache = CacheManager::new(".");
-            try_background_compact(&cache, command);
-        }
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
 
-        // Execute the subcommand, or show help if no command provided
-        match self.command {
-            None => {
-                // No subcommand: show help
-                Cli::command().print_help()?;
-                println!();  // Add newline after help
-                Ok(())
-            }
-            Some(Command::Index { path, force, languages, quiet, command }) => {
-                match command {
-                    None => {
-                        // Default: run index build
-                        handle_index_build(&path, &force, &languages, &quiet)
-                    }
-                    Some(IndexSubcommand::Status) => {
-                        handle_index_status()
-                    }
-                    Some(IndexSubcommand::Compact { json, pretty }) => {
-                        handle_index_compact(&json, &pretty)
-                    }
-                }
-            }
-            Some(Command::Query { pattern, symbols, lang, kind, ast, regex, json, pretty, ai, limit, offset, expand, file, exact, contains, count, timeout, plain, glob, exclude, paths, no_truncate, all, force, dependencies }) => {
-                // If no pattern provided, launch interactive mode
-                match pattern {
-                    None => handle_interactive(),
-                    Some(pattern) => handle_query(pattern, symbols, lang, kind, ast, regex, json, pretty, ai, limit, offset, expand, file, exact, contains, count, timeout, plain, glob, exclude, paths, no_truncate, all, force, dependencies)
-                }
-            }
-            Some(Command::Serve { port, host }) => {
-                handle_serve(port, host)
-            }
-            Some(Command::Stats { json, pretty }) => {
-                handle_stats(json, pretty)
-            }
-            Some(Command::Clear { yes }) => {
-                handle_clear(yes)
-            }
-            Some(Command::ListFiles { json, pretty }) => {
-                handle_list_files(json, pretty)
-            }
-            Some(Command::Watch { path, debounce, quiet }) => {
-                handle_watch(path, debounce, quiet)
-            }
-            Some(Command::Mcp) => {
-                handle_mcp()
-            }
-            Some(Command::Analyze { circular, hotspots, min_dependents, unused, islands, min_island_size, max_island_size, format, json, pretty, count, all, plain, glob, exclude, force, limit, offset, sort }) => {
-                handle_analyze(circular, hotspots, min_dependents, unused, islands, min_island_size, max_island_size, format, json, pretty, count, all, plain, glob, exclude, force, limit, offset, sort)
-            }
-            Some(Command::Deps { file, reverse, depth, format, json, pretty }) => {
-                handle_deps(file, reverse, depth, format, json, pretty)
-            }
-            Some(Command::Ask { question, execute, provider, json, pretty, additional_context, configure, agentic, max_iterations, no_eval, show_reasoning, verbose, quiet, answer, interactive, debug }) => {
-                handle_ask(question, execute, provider, json, pretty, additional_context, configure, agentic, max_iterations, no_eval, show_reasoning, verbose, quiet, answer, interactive, debug)
-            }
-            Some(Command::Context { structure, path, file_types, project_type, framework, entry_points, test_layout, config_files, depth, json }) => {
-                handle_context(structure, path, file_types, project_type, framework, entry_points, test_layout, config_files, depth, json)
-            }
-            Some(Command::IndexSymbolsInternal { cache_dir }) => {
-                handle_index_symbols_internal(cache_dir)
-            }
-        }
-    }
-}
+        /// Filter by file path (supports substring matching)
+        #[arg(short = 'f', long)]
+        file: Option<String>,
 
-/// Handle the `index status` subcommand
-fn handle_index_status() -> Result<()> {
-    log::info!("Checking background symbol indexing status");
-
-    let cache = CacheManager::new(".");
-    let cache_path = cache.path().to_path_buf();
-
-    match crate::background_indexer::BackgroundIndexer::get_status(&cache_path) {
-            Ok(Some(status)) => {
-                println!("Background Symbol Indexing Status");
-                println!("==================================");
-                println!("State:           {:?}", status.state);
-                println!("Total files:     {}", status.total_files);
-                println!("Processed:       {}", status.processed_files);
-                println!("Cached:          {}", status.cached_files);
-                println!("Parsed:          {}", status.parsed_files);
-                println!("Failed:          {}", status.failed_files);
-                println!("Started:         {}", status.started_at);
-                println!("Last updated:    {}", status.updated_at);
-
-                if let Some(completed_at) = &status.completed_at {
-                    println!("Completed:       {}", completed_at);
-                }
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
 
-                if let Some(error) = &status.error {
-                    println!("Error:           {}", error);
-                }
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
 
-                // Show progress percentage if running
-                if status.state == crate::background_indexer::IndexerState::Running && status.total_files > 0 {
-                    let progress = (status.processed_files as f64 / status.total_files as f64) * 100.0;
-                    println!("\nProgress:        {:.1}%", progress);
-                }
+        /// Apply the edits (default is preview only)
+        #[arg(long)]
+        write: bool,
 
-                Ok(())
-            }
-            Ok(None) => {
-                println!("No background symbol indexing in progress.");
-                println!("\nRun 'rfx index' to start background symbol indexing.");
-                Ok(())
-            }
-            Err(e) => {
-                anyhow::bail!("Failed to get indexing status: {}", e);
-            }
-        }
-    }
+        /// Bypass broad query detection
+        #[arg(long)]
+        force: bool,
 
-/// Handle the `index compact` subcommand
-fn handle_index_compact(json: &bool, pretty: &bool) -> Result<()> {
-    log::info!("Running cache compaction");
+        /// Output the edit plan as JSON (includes a unified diff per file)
+        #[arg(long)]
+        json: bool,
 
-    let cache = CacheManager::new(".");
-    let report = cache.compact()?;
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-    // Output results in requested format
-    if *json {
-        let json_str = if *pretty {
-            serde_json::to_string_pretty(&report)?
-        } else {
-            serde_json::to_string(&report)?
-        };
-        println!("{}", json_str);
-    } else {
-        println!("Cache Compaction Complete");
-        println!("=========================");
-        println!("Files removed:    {}", report.files_removed);
-        println!("Space saved:      {:.2} MB", report.space_saved_bytes as f64 / 1_048_576.0);
-        println!("Duration:         {}ms", report.duration_ms);
-    }
+    /// Turn query findings into review comments on a pull request diff
+    ///
+    /// Runs the query (or reads `rfx query --json` output with --findings) and keeps
+    /// only findings on lines the PR adds or modifies. Each comment carries
+    /// path / line / side (and start_line / start_side when it spans several lines),
+    /// ready to post through the GitHub or GitLab review APIs.
+    ///
+    /// --pr accepts a unified diff file, '-' for stdin, or a git range.
+    ///
+    /// Examples:
+    ///   rfx review unwrap --pr main...HEAD --lang rust
+    ///   gh pr diff 123 | rfx review TODO --pr - --json
+    ///   rfx review --pr pr.diff --findings findings.json --message "Use the new API"
+    Review {
+        /// Pattern to search for (omit when using --findings)
+        pattern: Option<String>,
 
-    Ok(())
-}
+        /// Pull request diff: unified diff file, '-' for stdin, or git range (e.g. main...HEAD)
+        #[arg(long, value_name = "DIFF|RANGE")]
+        pr: String,
 
-fn handle_index_build(path: &PathBuf, force: &bool, languages: &[String], quiet: &bool) -> Result<()> {
-    log::info!("Starting index build");
+        /// Read findings from `rfx query --json` output instead of running a query
+        #[arg(long, value_name = "FILE", conflicts_with = "pattern")]
+        findings: Option<PathBuf>,
 
-    let cache = CacheManager::new(path);
-    let cache_path = cache.path().to_path_buf();
+        /// Search symbol definitions only
+        #[arg(short, long)]
+        symbols: bool,
 
-    if *force {
-        log::info!("Force rebuild requested, clearing existing cache");
-        cache.clear()?;
-    }
+        /// Filter by symbol kind (implies --symbols)
+        #[arg(short, long)]
+        kind: Option<String>,
 
-    // Parse language filters
-    let lang_filters: Vec<Language> = languages
-        .iter()
-        .filter_map(|s| match s.to_lowercase().as_str() {
-            "rust" | "rs" => Some(Language::Rust),
-            "python" | "py" => Some(Language::Python),
-            "javascript" | "js" => Some(Language::JavaScript),
-            "typescript" | "ts" => Some(Language::TypeScript),
-            "go" => Some(Language::Go),
-            "java" => Some(Language::Java),
-            "php" => Some(Language::PHP),
-            "c" => Some(Language::C),
-            "cpp" | "c++" => Some(Language::Cpp),
-            _ => {
-                output::warn(&format!("Unknown language: {}", s));
-                None
-            }
-        })
-        .collect();
+        /// Treat the pattern as a regex
+        #[arg(short = 'r', long)]
+        regex: bool,
 
-    let config = IndexConfig {
-        languages: lang_filters,
-        ..Default::default()
-    };
+        /// Filter by language
+        #[arg(short, long)]
+        lang: Option<String>,
 
-    let indexer = Indexer::new(cache, config);
-    // Show progress by default, unless quiet mode is enabled
-    let show_progress = !quiet;
-    let stats = indexer.index(path, show_progress)?;
+        /// Include files matching glob pattern (can be repeated)
+        #[arg(short = 'g', long)]
+        glob: Vec<String>,
 
-    // In quiet mode, suppress all output
-    if !quiet {
-        println!("Indexing complete!");
-        println!("  Files indexed: {}", stats.total_files);
-        println!("  Cache size: {}", format_bytes(stats.index_size_bytes));
-        println!("  Last updated: {}", stats.last_updated);
+        /// Exclude files matching glob pattern (can be repeated)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
 
-        // Display language breakdown if we have indexed files
-        if !stats.files_by_language.is_empty() {
-            println!("\nFiles by language:");
+        /// Comment body to use instead of the generated one
+        #[arg(short, long)]
+        message: Option<String>,
 
-            // Sort languages by count (descending) for consistent output
-            let mut lang_vec: Vec<_> = stats.files_by_language.iter().collect();
-            lang_vec.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        /// Bypass broad query detection
+        #[arg(long)]
+        force: bool,
 
-            // Calculate column widths
-            let max_lang_len = lang_vec.iter().map(|(lang, _)| lang.len()).max().unwrap_or(8);
-            let lang_width = max_lang_len.max(8); // At least "Language" header width
+        /// Output comments as JSON
+        #[arg(long)]
+        json: bool,
 
-            // Print table header
-            println!("  {:<width$}  Files  Lines", "Language", width = lang_width);
-            println!("  {}  -----  -------", "-".repeat(lang_width));
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-            // Print rows
-            for (language, file_count) in lang_vec {
-                let line_count = stats.lines_by_language.get(language).copied().unwrap_or(0);
-                println!("  {:<width$}  {:5}  {:7}",
-                    language, file_count, line_count,
-                    width = lang_width);
-            }
-        }
-    }
+    /// Check the project against its lint rules in .reflex/rules/
+    ///
+    /// Rule packs are TOML files of [[rule]] tables, each pairing one matcher
+    /// (ast, preset, pattern or regex) with an id, message and severity
+    /// (error, warning or note). Every rule runs over the index and its matches
+    /// are reported as diagnostics. Exits with status 1 when a rule with
+    /// severity "error" matches.
+    ///
+    /// Example rule (.reflex/rules/style.toml):
+    ///   [[rule]]
+    ///   id = "no-unwrap"
+    ///   message = "Handle the error instead of calling unwrap()"
+    ///   severity = "error"
+    ///   lang = "rust"
+    ///   preset = "unwrap-calls"
+    ///   exclude = ["tests/**"]
+    ///
+    /// Examples:
+    ///   rfx lint                          # Run every rule
+    ///   rfx lint --rule no-unwrap         # Run one rule
+    ///   rfx lint --format sarif > lint.sarif
+    ///   rfx lint --list                   # Show the loaded rules
+    Lint {
+        /// Only run the rule with this id (can be repeated)
+        #[arg(long = "rule", value_name = "ID")]
+        rules: Vec<String>,
+
+        /// List the loaded rules instead of running them
+        #[arg(long)]
+        list: bool,
 
-    // Start background symbol indexing (if not already running)
-    if !crate::background_indexer::BackgroundIndexer::is_running(&cache_path) {
-        if !quiet {
-            println!("\nStarting background symbol indexing...");
-            println!("  Symbols will be cached for faster queries");
-            println!("  Check status with: rfx index status");
-        }
+        /// Output format: text (default) or sarif (SARIF 2.1.0)
+        #[arg(short = 'f', long, default_value = "text")]
+        format: String,
 
-        // Spawn detached background process for symbol indexing
-        // Pass the workspace root, not the .reflex directory
-        let current_exe = std::env::current_exe()
-            .context("Failed to get current executable path")?;
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
 
-        #[cfg(unix)]
-        {
-            std::process::Command::new(&current_exe)
-                .arg("index-symbols-internal")
-                .arg(path)
-                .stdin(std::process::Stdio::null())
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .spawn()
-                .context("Failed to spawn background indexing process")?;
-        }
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
+    /// Show the symbol enclosing a file location
+    ///
+    /// Parses the file with tree-sitter (no index needed) and reports the innermost
+    /// symbol whose span contains the line, the symbols around it, and its span.
+    /// Handy for turning stack traces, compiler errors or diff hunks into structure.
+    ///
+    /// The column only breaks ties between symbols on the same lines.
+    ///
+    /// Examples:
+    ///   rfx at src/query.rs:120
+    ///   rfx at src/main.rs:42:17 --json
+    At {
+        /// Location as <path>:<line> or <path>:<line>:<col>
+        location: String,
 
-            std::process::Command::new(&current_exe)
-                .arg("index-symbols-internal")
-                .arg(&path)
-                .creation_flags(CREATE_NO_WINDOW)
-                .stdin(std::process::Stdio::null())
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .spawn()
-                .context("Failed to spawn background indexing process")?;
-        }
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
 
-        log::debug!("Spawned background symbol indexing process");
-    } else if !quiet {
-        println!("\n⚠️  Background symbol indexing already in progress");
-        println!("  Check status with: rfx index status");
-    }
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-    Ok(())
-}
+    /// Map a stack trace onto the indexed code
+    ///
+    /// Reads a stack trace from stdin (Python, Java, Node or Rust panic/backtrace)
+    /// and resolves each frame to an indexed file, matching paths by suffix so
+    /// build and deploy prefixes (/app/, /home/ci/build/, webpack:///) don't matter.
+    /// Resolved frames include the enclosing symbol and the source line.
+    ///
+    /// Examples:
+    ///   pbpaste | rfx trace-resolve
+    ///   kubectl logs api-7d9f | rfx trace-resolve --json
+    TraceResolve {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
 
-/// Format bytes into human-readable size (KB, MB, GB, etc.)
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-    if bytes >= TB {
-        format!("{:.2} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} bytes", bytes)
-    }
-}
+    /// Embed indexed code for `rfx query --semantic`
+    ///
+    /// Splits every indexed file into chunks (one per function, method or type;
+    /// line windows otherwise), embeds them and stores the vectors in the cache.
+    /// Only files that changed since the last run are embedded again.
+    ///
+    /// The embedding model comes from .reflex/config.toml:
+    ///   [embeddings]
+    ///   provider = "openai"   # local (default, offline), openai or ollama
+    ///   model = "text-embedding-3-small"
+    ///
+    /// Examples:
+    ///   rfx embed
+    ///   rfx embed --force     # Re-embed every file
+    Embed {
+        /// Re-embed every file, not just changed ones
+        #[arg(short, long)]
+        force: bool,
 
-/// Smart truncate preview to reduce token usage
-/// Truncates at word boundary if possible, adds ellipsis if truncated
-pub fn truncate_preview(preview: &str, max_length: usize) -> String {
-    if preview.len() <= max_length {
-        return preview.to_string();
-    }
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
 
-    // Find a good break point (prefer word boundary)
-    let truncate_at = preview.char_indices()
-        .take(max_length)
-        .filter(|(_, c)| c.is_whitespace())
-        .last()
-        .map(|(i, _)| i)
-        .unwrap_or(max_length.min(preview.len()));
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
 
-    let mut truncated = preview[..truncate_at].to_string();
-    truncated.push('…');
-    truncated
-}
+    /// Ask a natural language question and generate search queries
+    ///
+    /// Uses an LLM to translate natural language questions into `rfx query` commands.
+    /// Requires API key configuration for one of: OpenAI, Anthropic, or Groq.
+    ///
+    /// If no question is provided, launches interactive chat mode by default.
+    ///
+    /// Configuration:
+    ///   1. Run interactive setup wizard (recommended):
+    ///      rfx ask --configure
+    ///
+    ///   2. OR set API key via environment variable:
+    ///      - OPENAI_API_KEY, ANTHROPIC_API_KEY, or GROQ_API_KEY
+    ///
+    ///   3. Optional: Configure provider in .reflex/config.toml:
+    ///      [semantic]
+    ///      provider = "groq"  # or openai, anthropic
+    ///      model = "llama-3.3-70b-versatile"  # optional, defaults to provider default
+    ///
+    /// Examples:
+    ///   rfx ask --configure                           # Interactive setup wizard
+    ///   rfx ask                                       # Launch interactive chat (default)
+    ///   rfx ask "Find all TODOs in Rust files"
+    ///   rfx ask "Where is the main function defined?" --execute
+    ///   rfx ask "Show me error handling code" --provider groq
+    ///   rfx ask --chat                                # Line-based chat; follow-ups refine earlier queries
+    Ask {
+        /// Natural language question
+        question: Option<String>,
+
+        /// Execute queries immediately without confirmation
+        #[arg(short, long)]
+        execute: bool,
+
+        /// Override configured LLM provider (openai, anthropic, groq)
+        #[arg(short, long)]
+        provider: Option<String>,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output (only with --json)
+        #[arg(long)]
+        pretty: bool,
+
+        /// Additional context to inject into prompt (e.g., from `rfx context`)
+        #[arg(long)]
+        additional_context: Option<String>,
+
+        /// Launch interactive configuration wizard to set up AI provider and API key
+        #[arg(long)]
+        configure: bool,
+
+        /// Enable agentic mode (multi-step reasoning with context gathering)
+        #[arg(long)]
+        agentic: bool,
+
+        /// Maximum iterations for query refinement in agentic mode (default: 2)
+        #[arg(long, default_value = "2")]
+        max_iterations: usize,
+
+        /// Skip result evaluation in agentic mode
+        #[arg(long)]
+        no_eval: bool,
+
+        /// Show LLM reasoning blocks at each phase (agentic mode only)
+        #[arg(long)]
+        show_reasoning: bool,
+
+        /// Verbose output: show tool results and details (agentic mode only)
+        #[arg(long)]
+        verbose: bool,
+
+        /// Quiet mode: suppress progress output (agentic mode only)
+        #[arg(long)]
+        quiet: bool,
+
+        /// Generate a conversational answer based on search results
+        #[arg(long)]
+        answer: bool,
+
+        /// Launch interactive chat mode (TUI) with conversation history
+        #[arg(short = 'i', long)]
+        interactive: bool,
+
+        /// Line-based chat in the terminal: each question sees earlier questions,
+        /// their queries and a summary of their results (combine with --answer)
+        #[arg(long, conflicts_with_all = ["question", "interactive", "agentic", "json"])]
+        chat: bool,
+
+        /// Debug mode: output full LLM prompts and retain terminal history
+        #[arg(long)]
+        debug: bool,
+    },
+
+    /// Generate codebase context for AI prompts
+    ///
+    /// Provides structural and organizational context about the project to help
+    /// LLMs understand project layout. Use with `rfx ask --additional-context`.
+    ///
+    /// By default (no flags), shows all context types. Use individual flags to
+    /// select specific context types.
+    ///
+    /// Examples:
+    ///   rfx context                                    # Full context (all types)
+    ///   rfx context --path services/backend            # Full context for monorepo subdirectory
+    ///   rfx context --framework --entry-points         # Specific context types only
+    ///   rfx context --structure --depth 5              # Deep directory tree
+    ///   rfx context --conventions --json               # Naming, test, lint and error-handling conventions
+    ///   rfx context --summary                          # LLM-written architecture brief
+    ///
+    ///   # Use with semantic queries
+    ///   rfx ask "find auth" --additional-context "$(rfx context --framework)"
+    Context {
+        /// Show directory structure (enabled by default)
+        #[arg(long)]
+        structure: bool,
+
+        /// Focus on specific directory path
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Show file type distribution (enabled by default)
+        #[arg(long)]
+        file_types: bool,
+
+        /// Detect project type (CLI/library/webapp/monorepo)
+        #[arg(long)]
+        project_type: bool,
+
+        /// Detect frameworks and conventions
+        #[arg(long)]
+        framework: bool,
+
+        /// Show entry point files
+        #[arg(long)]
+        entry_points: bool,
+
+        /// Show test organization pattern
+        #[arg(long)]
+        test_layout: bool,
+
+        /// List important configuration files
+        #[arg(long)]
+        config_files: bool,
+
+        /// Infer conventions: naming styles per language, test frameworks,
+        /// lint/format configs and error-handling idioms (not included by default)
+        #[arg(long)]
+        conventions: bool,
+
+        /// Tree depth for --structure (default: 1)
+        #[arg(long, default_value = "1")]
+        depth: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Write a concise architecture brief with the configured LLM provider
+        ///
+        /// The brief cites paths and is cached per commit; the other flags
+        /// choose which context the provider sees.
+        #[arg(long)]
+        summary: bool,
+
+        /// Override configured LLM provider for --summary (openai, anthropic, groq)
+        #[arg(long, requires = "summary")]
+        provider: Option<String>,
+
+        /// Regenerate the --summary brief instead of using the cached one
+        #[arg(long, requires = "summary")]
+        refresh: bool,
+    },
+
+    /// Update rfx to the latest GitHub release
+    ///
+    /// Downloads the release archive for this platform, verifies its SHA-256
+    /// checksum, and atomically replaces the running binary.
+    ///
+    /// Channels:
+    ///   stable   - Latest non-prerelease release (default)
+    ///   nightly  - Newest release, including prereleases
+    ///
+    /// Configure in ~/.reflex/config.toml:
+    ///   [update]
+    ///   enabled = false      # Disable self-update (e.g., managed installs)
+    ///   channel = "nightly"  # Default channel
+    ///
+    /// Examples:
+    ///   rfx self-update                    # Update to latest stable
+    ///   rfx self-update --check            # Only check for a newer version
+    ///   rfx self-update --channel nightly  # Follow prereleases
+    SelfUpdate {
+        /// Release channel (overrides [update] channel in config)
+        #[arg(long, value_parser = ["stable", "nightly"])]
+        channel: Option<String>,
+
+        /// Only check whether an update is available (don't install)
+        #[arg(long)]
+        check: bool,
+
+        /// Reinstall even if already on the latest version
+        #[arg(long)]
+        force: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
+
+    /// Internal command: Run background symbol indexing (hidden from help)
+    #[command(hide = true)]
+    IndexSymbolsInternal {
+        /// Cache directory path
+        cache_dir: PathBuf,
+
+        /// Only files of these languages
+        #[arg(long)]
+        lang: Vec<String>,
+
+        /// Only files matching these globs
+        #[arg(long)]
+        glob: Vec<String>,
+    },
+}
+
+/// Try to run background cache compaction if needed
+///
+/// Checks if 24+ hours have passed since last compaction.
+/// If yes, spawns a non-blocking background thread to compact the cache.
+/// Main command continues immediately without waiting for compaction.
+///
+/// Compaction is skipped for commands that don't need it:
+/// - Clear (will delete the cache anyway)
+/// - Mcp (long-running server process)
+/// - Watch (long-running watcher process)
+/// - Serve (long-running HTTP server)
+fn try_background_compact(cache: &CacheManager, command: &Command) {
+    // Skip compaction for certain commands
+    match command {
+        Command::Clear { .. } => {
+            log::debug!("Skipping compaction for Clear command");
+            return;
+        }
+        Command::Mcp => {
+            log::debug!("Skipping compaction for Mcp command");
+            return;
+        }
+        Command::Watch { .. } => {
+            log::debug!("Skipping compaction for Watch command");
+            return;
+        }
+        Command::Serve { .. } => {
+            log::debug!("Skipping compaction for Serve command");
+            return;
+        }
+        _ => {}
+    }
+
+    // Check if compaction should run
+    let should_compact = match cache.should_compact() {
+        Ok(true) => true,
+        Ok(false) => {
+            log::debug!("Compaction not needed yet (last run <24h ago)");
+            return;
+        }
+        Err(e) => {
+            log::warn!("Failed to check compaction status: {}", e);
+            return;
+        }
+    };
+
+    if !should_compact {
+        return;
+    }
+
+    log::info!("Starting background cache compaction...");
+
+    // Clone cache path for background thread
+    let cache_path = cache.path().to_path_buf();
+
+    // Spawn background thread for compaction
+    std::thread::spawn(move || {
+        let cache = CacheManager::new(cache_path.parent().expect("Cache should have parent directory"));
+
+        match cache.compact() {
+            Ok(report) => {
+                log::info!(
+                    "Background compaction completed: {} files removed, {:.2} MB saved, took {}ms",
+                    report.files_removed,
+                    report.space_saved_bytes as f64 / 1_048_576.0,
+                    report.duration_ms
+                );
+            }
+            Err(e) => {
+                log::warn!("Background compaction failed: {}", e);
+            }
+        }
+    });
+
+    log::debug!("Background compaction thread spawned - main command continuing");
+}
+
+impl Cli {
+    /// Execute the CLI command
+    pub fn execute(self) -> Result<()> {
+        // Setup logging based on verbosity
+        let log_level = match self.verbose {
+            0 => "warn",   // Default: only warnings and errors
+            1 => "info",   // -v: show info messages
+            2 => "debug",  // -vv: show debug messages
+            _ => "trace",  // -vvv: show trace messages
+        };
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
+            .init();
+
+        // Try background compaction (non-blocking) before command execution
+        if let Some(ref command) = self.command {
+            // Use current directory as default cache location
+            let cache = CacheManager::new(".");
+            try_background_compact(&cache, command);
+        }
+
+        // Flag notices from the deprecation registry (`query` reports its own, including in JSON)
+        if self.command.is_some() && !matches!(self.command, Some(Command::Query { .. } | Command::Q { .. })) {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            crate::deprecations::print_warnings(&crate::deprecations::flag_warnings(&args));
+        }
+
+        // Execute the subcommand, or show help if no command provided
+        match self.command {
+            None => {
+                // No subcommand: show help
+                Cli::command().print_help()?;
+                println!();  // Add newline after help
+                Ok(())
+            }
+            Some(Command::Index { path, force, languages, quiet, roots, reset_roots, excludes, dirty_only, only, rev, memory_budget, command }) => {
+                match command {
+                    None => {
+                        // Default: run index build
+                        handle_index_build(&path, &force, &languages, &quiet, &roots, reset_roots, &excludes, dirty_only, &only, rev.as_deref(), memory_budget)
+                    }
+                    Some(IndexSubcommand::Status) => {
+                        handle_index_status()
+                    }
+                    Some(IndexSubcommand::Compact { json, pretty }) => {
+                        handle_index_compact(&json, &pretty)
+                    }
+                    Some(IndexSubcommand::Symbols { wait, lang, glob, json, pretty }) => {
+                        handle_index_symbols(&path, wait, lang, glob, json, pretty)
+                    }
+                }
+            }
+            Some(Command::Query { pattern, symbols, lang, kind, kind_not, ast, ast_preset, structural, regex, json, pretty, format, ai, limit, offset, expand, file, exact, contains, fuzzy, fuzzy_threshold, tokens, semantic, count, timeout, budget_ms, max_tokens, explain, plain, glob, exclude, not, changed_since, rev, scope, qualified, visibility, owner, table, paths, group_by, no_truncate, all, force, dependencies, related, with_owners, blame, include_deleted, with_docs, span, before, after, context_lines, dir, indexes }) => {
+                // --ast-preset and --pattern stand in for the S-expression pattern
+                let (pattern, ast) = match ast_preset {
+                    Some(name) => (Some(resolve_ast_preset(&name, lang.as_deref(), &dir)?), true),
+                    None if structural => {
+                        (pattern.map(|p| compile_structural_pattern(&p, lang.as_deref())).transpose()?, true)
+                    }
+                    None => (pattern, ast),
+                };
+
+                // --table without a pattern searches for the table and its model names
+                let (pattern, regex) = match (pattern, &table) {
+                    (None, Some(table)) => (Some(crate::tables::usage_pattern(&CacheManager::new(&dir), table)?), true),
+                    (pattern, _) => (pattern, regex),
+                };
+
+                // If no pattern provided, launch interactive mode
+                match pattern {
+                    None => handle_interactive(),
+                    Some(pattern) => handle_query(QueryArgs { pattern, symbols_flag: symbols, lang, kind_str: kind, kind_not_str: kind_not, use_ast: ast, use_regex: regex, as_json: json, pretty_json: pretty, format, ai_mode: ai, limit, offset, expand, file_pattern: file, exact, use_contains: contains, fuzzy, fuzzy_threshold, tokens, semantic, count_only: count, timeout, budget_ms, max_tokens, explain, plain, glob_patterns: glob, exclude_patterns: exclude, not_patterns: not, changed_since, rev, scope, qualified, visibility, owner, table, paths_only: paths, group_by, no_truncate, all, force, include_dependencies: dependencies, related_files: related, with_owners, blame, include_deleted, with_docs, span, before, after, context_lines, dir, indexes }),
+                }
+            }
+            Some(Command::Q { expr, json, pretty, count, paths, all, dir }) => {
+                handle_q(expr, json, pretty, count, paths, all, dir)
+            }
+            Some(Command::Serve { port, host, auth_token, tls_cert, tls_key, rate_limit, watch, debounce }) => {
+                let tls = tls_cert.zip(tls_key);
+                handle_serve(port, host, auth_token, tls, rate_limit, watch.then_some(debounce))
+            }
+            Some(Command::Stats { by_dir, depth, json, pretty }) => {
+                handle_stats(by_dir, depth, json, pretty)
+            }
+            Some(Command::Doctor { repair, json, pretty }) => {
+                handle_doctor(repair, json, pretty)
+            }
+            Some(Command::Clear { yes }) => {
+                handle_clear(yes)
+            }
+            Some(Command::ListFiles { json, pretty }) => {
+                handle_list_files(json, pretty)
+            }
+            Some(Command::DumpSymbols { lang, kind, out, limit, after, resume }) => {
+                handle_dump_symbols(lang, kind, out, limit, after, resume)
+            }
+            Some(Command::Watch { path, debounce, quiet }) => {
+                handle_watch(path, debounce, quiet)
+            }
+            Some(Command::Mcp) => {
+                handle_mcp()
+            }
+            Some(Command::Analyze { circular, hotspots, min_dependents, unused, dead_symbols, min_confidence, islands, min_island_size, max_island_size, resolve_barrels, format, json, pretty, count, all, plain, glob, exclude, force, limit, offset, sort }) => {
+                handle_analyze(circular, hotspots, min_dependents, unused, dead_symbols, min_confidence, islands, min_island_size, max_island_size, resolve_barrels, format, json, pretty, count, all, plain, glob, exclude, force, limit, offset, sort)
+            }
+            Some(Command::Deps { file, reverse, depth, resolve_barrels, format, json, pretty }) => {
+                handle_deps(file, reverse, depth, resolve_barrels, format, json, pretty)
+            }
+            Some(Command::Flags { command }) => {
+                match command {
+                    FlagsSubcommand::List { json, pretty } => {
+                        handle_flags_list(json, pretty)
+                    }
+                    FlagsSubcommand::Where { flag, json, pretty, paths } => {
+                        handle_flags_where(flag, json, pretty, paths)
+                    }
+                }
+            }
+            Some(Command::ConfigKeys { key, kind, json, pretty }) => {
+                handle_config_keys(key, kind, json, pretty)
+            }
+            Some(Command::Deleted { command }) => {
+                match command {
+                    DeletedSubcommand::List { path, json, pretty } => {
+                        handle_deleted_list(path, json, pretty)
+                    }
+                }
+            }
+            Some(Command::History { command }) => {
+                match command {
+                    HistorySubcommand::List { limit, json, pretty } => {
+                        handle_history_list(limit, json, pretty)
+                    }
+                    HistorySubcommand::Replay { id } => {
+                        handle_history_replay(id)
+                    }
+                    HistorySubcommand::Clear => {
+                        handle_history_clear()
+                    }
+                }
+            }
+            Some(Command::Snapshot { command }) => {
+                match command {
+                    SnapshotSubcommand::Save { output, json, pretty } => {
+                        handle_snapshot_save(output, json, pretty)
+                    }
+                    SnapshotSubcommand::Load { archive, force, json, pretty } => {
+                        handle_snapshot_load(archive, force, json, pretty)
+                    }
+                }
+            }
+            Some(Command::Hooks { command }) => {
+                match command {
+                    HooksSubcommand::Install { uninstall, json, pretty } => {
+                        handle_hooks_install(uninstall, json, pretty)
+                    }
+                }
+            }
+            Some(Command::Impact { symbol, kind, lang, depth, json, pretty }) => {
+                handle_impact(symbol, kind, lang, depth, json, pretty)
+            }
+            Some(Command::Hierarchy { name, depth, lang, glob, exclude, json, pretty }) => {
+                handle_hierarchy(name, depth, lang, glob, exclude, json, pretty)
+            }
+            Some(Command::Freq { term, by, lang, top, json, pretty }) => {
+                handle_freq(term, by, lang, top, json, pretty)
+            }
+            Some(Command::Errors { name, lang, glob, exclude, defs_only, json, pretty }) => {
+                handle_errors(name, lang, glob, exclude, defs_only, json, pretty)
+            }
+            Some(Command::Routes { pattern, method, lang, glob, exclude, json, pretty }) => {
+                handle_routes(pattern, method, lang, glob, exclude, json, pretty)
+            }
+            Some(Command::Todos { marker, lang, glob, exclude, json, pretty }) => {
+                handle_todos(marker, lang, glob, exclude, json, pretty)
+            }
+            Some(Command::Replace { pattern, replacement, regex, contains, lang, file, glob, exclude, write, force, json, pretty }) => {
+                handle_replace(pattern, replacement, regex, contains, lang, file, glob, exclude, write, force, json, pretty)
+            }
+            Some(Command::Review { pattern, pr, findings, symbols, kind, regex, lang, glob, exclude, message, force, json, pretty }) => {
+                handle_review(pattern, pr, findings, symbols, kind, regex, lang, glob, exclude, message, force, json, pretty)
+            }
+            Some(Command::Lint { rules, list, format, json, pretty }) => {
+                handle_lint(rules, list, format, json, pretty)
+            }
+            Some(Command::At { location, json, pretty }) => {
+                handle_at(location, json, pretty)
+            }
+            Some(Command::Embed { force, json, pretty }) => {
+                handle_embed(force, json, pretty)
+            }
+            Some(Command::TraceResolve { json, pretty }) => {
+                handle_trace_resolve(json, pretty)
+            }
+            Some(Command::Ask { question, execute, provider, json, pretty, additional_context, configure, agentic, max_iterations, no_eval, show_reasoning, verbose, quiet, answer, interactive, chat, debug }) => {
+                handle_ask(question, execute, provider, json, pretty, additional_context, configure, agentic, max_iterations, no_eval, show_reasoning, verbose, quiet, answer, interactive, chat, debug)
+            }
+            Some(Command::Context { structure, path, file_types, project_type, framework, entry_points, test_layout, config_files, conventions, depth, json, summary, provider, refresh }) => {
+                handle_context(structure, path, file_types, project_type, framework, entry_points, test_layout, config_files, conventions, depth, json, summary, provider, refresh)
+            }
+            Some(Command::SelfUpdate { channel, check, force, json, pretty }) => {
+                handle_self_update(channel, check, force, json, pretty)
+            }
+            Some(Command::IndexSymbolsInternal { cache_dir, lang, glob }) => {
+                handle_index_symbols_internal(cache_dir, lang, glob)
+            }
+        }
+    }
+}
+
+/// Handle the `index status` subcommand
+fn handle_index_status() -> Result<()> {
+    log::info!("Checking background symbol indexing status");
+
+    let cache = CacheManager::new(".");
+    let cache_path = cache.path().to_path_buf();
+
+    // Opening the symbol cache drops symbols produced by an older parser/grammar
+    if cache.exists()
+        && let Ok(symbol_cache) = crate::symbol_cache::SymbolCache::open(&cache_path)
+        && let Ok(Some(invalidation)) = symbol_cache.last_invalidation()
+    {
+        let when = chrono::DateTime::from_timestamp(invalidation.invalidated_at, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| invalidation.invalidated_at.to_string());
+        println!("Notice: parser upgrade invalidated {} cached symbol set(s) ({}) at {}",
+            invalidation.invalidated_entries, invalidation.languages.join(", "), when);
+        println!("  Affected files are re-parsed on demand until 'rfx index' rebuilds the symbol cache");
+        println!();
+    }
+
+    match crate::background_indexer::BackgroundIndexer::get_status(&cache_path) {
+            Ok(Some(status)) => {
+                println!("Background Symbol Indexing Status");
+                println!("==================================");
+                println!("State:           {:?}", status.state);
+                println!("Total files:     {}", status.total_files);
+                println!("Processed:       {}", status.processed_files);
+                println!("Cached:          {}", status.cached_files);
+                println!("Parsed:          {}", status.parsed_files);
+                println!("Failed:          {}", status.failed_files);
+                println!("Started:         {}", status.started_at);
+                println!("Last updated:    {}", status.updated_at);
+
+                if let Some(completed_at) = &status.completed_at {
+                    println!("Completed:       {}", completed_at);
+                }
+
+                if let Some(error) = &status.error {
+                    println!("Error:           {}", error);
+                }
+
+                // Show progress percentage if running
+                if status.state == crate::background_indexer::IndexerState::Running && status.total_files > 0 {
+                    let progress = (status.processed_files as f64 / status.total_files as f64) * 100.0;
+                    println!("\nProgress:        {:.1}%", progress);
+                }
+
+                Ok(())
+            }
+            Ok(None) => {
+                println!("No background symbol indexing in progress.");
+                println!("\nRun 'rfx index' to start background symbol indexing.");
+                Ok(())
+            }
+            Err(e) => {
+                anyhow::bail!("Failed to get indexing status: {}", e);
+            }
+        }
+    }
+
+/// Handle the `index compact` subcommand
+fn handle_index_compact(json: &bool, pretty: &bool) -> Result<()> {
+    log::info!("Running cache compaction");
+
+    let cache = CacheManager::new(".");
+    let report = cache.compact()?;
+
+    // Output results in requested format
+    if *json {
+        let json_str = if *pretty {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{}", json_str);
+    } else {
+        println!("Cache Compaction Complete");
+        println!("=========================");
+        println!("Files removed:    {}", report.files_removed);
+        println!("Space saved:      {:.2} MB", report.space_saved_bytes as f64 / 1_048_576.0);
+        println!("Duration:         {}ms", report.duration_ms);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_index_build(path: &PathBuf, force: &bool, languages: &[String], quiet: &bool, roots: &[String], reset_roots: bool, excludes: &[String], dirty_only: bool, only: &[String], rev: Option<&str>, memory_budget: Option<usize>) -> Result<()> {
+    log::info!("Starting index build");
+
+    let cache = CacheManager::new(path);
+    let cache_path = cache.path().to_path_buf();
+
+    if *force {
+        log::info!("Force rebuild requested, clearing existing cache");
+        cache.clear()?;
+    }
+
+    if reset_roots && cache.exists() {
+        log::info!("Forgetting remembered workspace roots");
+        cache.update_roots(&[])?;
+    }
+
+    // Parse language filters
+    let lang_filters: Vec<Language> = languages
+        .iter()
+        .filter_map(|s| {
+            let language = Language::from_name(s);
+            if language.is_none() {
+                output::warn(&format!("Unknown language: {}", s));
+            }
+            language
+        })
+        .collect();
+
+    // Parse workspace roots (monorepo packages)
+    let workspace_roots = roots
+        .iter()
+        .map(|spec| WorkspaceRoot::parse(spec))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = IndexConfig {
+        languages: lang_filters,
+        roots: workspace_roots,
+        ignore_patterns: excludes.to_vec(),
+        ..Default::default()
+    };
+    crate::indexer::load_performance_config(&cache_path)?.apply(&mut config);
+    crate::indexer::load_index_limits_config(&cache_path)?.apply(&mut config);
+    if let Some(budget) = memory_budget {
+        config.memory_budget_mb = budget;
+    }
+
+    if let Some(rev) = rev {
+        let start = std::time::Instant::now();
+        let segment = crate::revision::index_revision(path, &cache, rev, config)?;
+        if !quiet {
+            println!("Indexed revision {} ({}) in {}ms", rev, &segment.commit[..12], start.elapsed().as_millis());
+            println!("  Files indexed: {}", segment.files);
+        }
+        return Ok(());
+    }
+
+    if dirty_only {
+        let start = std::time::Instant::now();
+        let overlay = crate::overlay::build_overlay(path, &cache, config)?;
+        if !quiet {
+            println!("Dirty overlay updated in {}ms", start.elapsed().as_millis());
+            println!("  Modified files: {}", overlay.modified.len());
+            println!("  Deleted files: {}", overlay.deleted.len());
+        }
+        return Ok(());
+    }
+
+    if !only.is_empty() {
+        let start = std::time::Instant::now();
+        let selectors = read_only_selectors(only)?;
+        if selectors.is_empty() {
+            // e.g. `git diff --name-only | rfx index --only -` with nothing changed
+            if !quiet {
+                println!("No paths to re-index");
+            }
+            return Ok(());
+        }
+        let overlay = crate::overlay::build_partial_overlay(path, &cache, config, &selectors)?;
+        if !quiet {
+            println!("Re-indexed {} selected paths in {}ms", selectors.len(), start.elapsed().as_millis());
+            println!("  Modified files in overlay: {}", overlay.modified.len());
+            println!("  Deleted files in overlay: {}", overlay.deleted.len());
+        }
+        return Ok(());
+    }
+
+    let indexer = Indexer::new(cache, config);
+    // Show progress by default, unless quiet mode is enabled
+    let show_progress = !quiet;
+    let stats = indexer.index(path, show_progress)?;
+
+    // Record repo scale so queries can derive size-appropriate defaults
+    let profile = crate::tuning::RepoProfile::from_stats(&stats);
+    let tuning_cache = CacheManager::new(path);
+    if let Err(e) = crate::tuning::record_profile(&tuning_cache, &profile) {
+        log::warn!("Failed to record repo profile: {}", e);
+    }
+
+    // In quiet mode, suppress all output
+    if !quiet {
+        println!("Indexing complete!");
+        println!("  Files indexed: {}", stats.total_files);
+        println!("  Cache size: {}", format_bytes(stats.index_size_bytes));
+        println!("  Last updated: {}", stats.last_updated);
+        if let Some(details) = stats.warning.as_ref().and_then(|w| w.details.as_ref()) {
+            println!("  Files skipped: {} (over the index limits, see 'rfx stats')", details.skipped_files.len());
+        }
+
+        // Display per-root breakdown for multi-root workspaces
+        let indexed_roots = CacheManager::new(path).list_roots()?;
+        if !indexed_roots.is_empty() {
+            println!("\nWorkspace roots:");
+            let name_width = indexed_roots.iter().map(|r| r.name.len()).max().unwrap_or(4).max(4);
+            for root in &indexed_roots {
+                println!("  @{:<width$}  {:5} files  {}",
+                    root.name, root.file_count, root.path,
+                    width = name_width);
+            }
+        }
+
+        // Display language breakdown if we have indexed files
+        if !stats.files_by_language.is_empty() {
+            println!("\nFiles by language:");
+
+            // Sort languages by count (descending) for consistent output
+            let mut lang_vec: Vec<_> = stats.files_by_language.iter().collect();
+            lang_vec.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+            // Calculate column widths
+            let max_lang_len = lang_vec.iter().map(|(lang, _)| lang.len()).max().unwrap_or(8);
+            let lang_width = max_lang_len.max(8); // At least "Language" header width
+
+            // Print table header
+            println!("  {:<width$}  Files  Lines", "Language", width = lang_width);
+            println!("  {}  -----  -------", "-".repeat(lang_width));
+
+            // Print rows
+            for (language, file_count) in lang_vec {
+                let line_count = stats.lines_by_language.get(language).copied().unwrap_or(0);
+                println!("  {:<width$}  {:5}  {:7}",
+                    language, file_count, line_count,
+                    width = lang_width);
+            }
+        }
+
+        // Display the query defaults derived from the repo profile (after config overrides)
+        let defaults = crate::tuning::search_defaults(&tuning_cache);
+        println!("\nQuery defaults ({} repo, {} lines):", profile.scale.as_str(), profile.total_lines);
+        println!("  Limit:    {}", defaults.limit.map_or("unlimited".to_string(), |n| n.to_string()));
+        println!("  Timeout:  {}s", defaults.timeout_secs);
+        println!("  Preview:  {} chars", defaults.max_preview_length);
+        println!("  Override in .reflex/config.toml under [search]");
+    }
+
+    // Start background symbol indexing (if not already running)
+    if !crate::background_indexer::BackgroundIndexer::is_running(&cache_path) {
+        if !quiet {
+            println!("\nStarting background symbol indexing...");
+            println!("  Symbols will be cached for faster queries");
+            println!("  Check status with: rfx index status");
+        }
+
+        spawn_symbol_indexer(path, &[])?;
+    } else if !quiet {
+        println!("\n⚠️  Background symbol indexing already in progress");
+        println!("  Check status with: rfx index status");
+    }
+
+    Ok(())
+}
+
+/// Spawn a detached background symbol indexing process for the workspace at `path`
+///
+/// `scope_args` are passed on to `index-symbols-internal` (`--lang`/`--glob`).
+fn spawn_symbol_indexer(path: &std::path::Path, scope_args: &[String]) -> Result<()> {
+    // Pass the workspace root, not the .reflex directory
+    let current_exe = std::env::current_exe()
+        .context("Failed to get current executable path")?;
+
+    #[cfg(unix)]
+    {
+        std::process::Command::new(&current_exe)
+            .arg("index-symbols-internal")
+            .arg(path)
+            .args(scope_args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .context("Failed to spawn background indexing process")?;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        std::process::Command::new(&current_exe)
+            .arg("index-symbols-internal")
+            .arg(path)
+            .args(scope_args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .context("Failed to spawn background indexing process")?;
+    }
+
+    log::debug!("Spawned background symbol indexing process");
+    Ok(())
+}
+
+/// Handle the `index symbols` subcommand
+fn handle_index_symbols(path: &std::path::Path, wait: bool, lang: Vec<String>, glob: Vec<String>, as_json: bool, pretty_json: bool) -> Result<()> {
+    use crate::background_indexer::{BackgroundIndexer, SymbolScope};
+
+    let cache = CacheManager::new(path);
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             It also starts caching symbols in the background.\n\
+             \n\
+             Example:\n\
+             $ rfx index                          # Index current directory\n\
+             $ rfx index symbols --wait --lang go # Cache Go symbols now".to_string()
+        ).into());
+    }
+    cache.migrate()?;
+
+    let mut languages = Vec::new();
+    for name in &lang {
+        languages.extend(parse_language_arg(Some(name))?);
+    }
+    let scope = SymbolScope { languages, globs: glob.clone() };
+    scope.validate()?;
+
+    if !wait {
+        if BackgroundIndexer::is_running(cache.path()) {
+            println!("⚠️  Background symbol indexing already in progress");
+            println!("  Check status with: rfx index status");
+            println!("  Use --wait to cache these files now");
+            return Ok(());
+        }
+
+        let scope_args: Vec<String> = lang.iter().map(|l| format!("--lang={}", l))
+            .chain(glob.iter().map(|g| format!("--glob={}", g)))
+            .collect();
+        spawn_symbol_indexer(path, &scope_args)?;
+        println!("Started background symbol indexing");
+        println!("  Check status with: rfx index status");
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    let status = BackgroundIndexer::new(path)?
+        .with_scope(scope)
+        .run_foreground()?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&status)?
+        } else {
+            serde_json::to_string(&status)?
+        };
+        println!("{}", json_output);
+    } else {
+        println!("Symbol cache warmed for {} files in {:.2}s", status.total_files, start.elapsed().as_secs_f64());
+        println!("  Already cached: {}", status.cached_files);
+        println!("  Parsed:         {}", status.parsed_files);
+        if status.failed_files > 0 {
+            println!("  Failed:         {}", status.failed_files);
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand `--only` arguments, reading paths from stdin for `-`
+fn read_only_selectors(only: &[String]) -> Result<Vec<String>> {
+    let mut selectors = Vec::new();
+    for selector in only {
+        if selector == "-" {
+            for line in std::io::stdin().lines() {
+                let line = line.context("Failed to read paths from stdin")?;
+                if !line.trim().is_empty() {
+                    selectors.push(line.trim().to_string());
+                }
+            }
+        } else {
+            selectors.push(selector.clone());
+        }
+    }
+    Ok(selectors)
+}
+
+/// Format bytes into human-readable size (KB, MB, GB, etc.)
+fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    const TB: u64 = GB * 1024;
+
+    if bytes >= TB {
+        format!("{:.2} TB", bytes as f64 / TB as f64)
+    } else if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} bytes", bytes)
+    }
+}
+
+/// Smart truncate preview to reduce token usage
+/// Truncates at word boundary if possible, adds ellipsis if truncated
+pub fn truncate_preview(preview: &str, max_length: usize) -> String {
+    if preview.len() <= max_length {
+        return preview.to_string();
+    }
+
+    // Find a good break point (prefer word boundary)
+    let truncate_at = preview.char_indices()
+        .take(max_length)
+        .filter(|(_, c)| c.is_whitespace())
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(max_length.min(preview.len()));
+
+    let mut truncated = preview[..truncate_at].to_string();
+    truncated.push('…');
+    truncated
+}
+
+/// Record a query for `rfx history` (best-effort: failures are logged, never returned)
+#[allow(clippy::too_many_arguments)]
+fn record_query_history(
+    dir: &std::path::Path,
+    args: &[String],
+    pattern: &str,
+    filters: crate::history::HistoryFilters,
+    result_count: usize,
+    total_results: usize,
+    directories: Vec<String>,
+    elapsed: std::time::Duration,
+) {
+    let cache = CacheManager::new(dir);
+    let config = match crate::history::load_history_config(cache.path()) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Query not recorded in history: {}", e);
+            return;
+        }
+    };
+    if !config.enabled {
+        return;
+    }
+
+    let entry = crate::history::HistoryEntry {
+        id: 0,
+        pattern: pattern.to_string(),
+        args: args.to_vec(),
+        filters,
+        result_count,
+        total_results,
+        timing_ms: elapsed.as_millis() as u64,
+        executed_at: 0,
+        directories,
+    };
+    if let Err(e) = crate::history::QueryHistoryLog::new(cache).record(&entry, config.max_entries) {
+        log::warn!("Query not recorded in history: {}", e);
+    }
+}
+
+/// Run a query against the `--dir` index and every `--index`, merging results labelled by repo
+fn search_indexes(
+    dir: &std::path::Path,
+    indexes: &[PathBuf],
+    pattern: &str,
+    filter: &QueryFilter,
+) -> Result<crate::models::QueryResponse> {
+    let roots: Vec<&std::path::Path> = std::iter::once(dir)
+        .chain(indexes.iter().map(PathBuf::as_path))
+        .collect();
+
+    let mut responses: Vec<(String, crate::models::QueryResponse)> = Vec::with_capacity(roots.len());
+    for root in roots {
+        // Label by directory name; fall back to the path as given when two names collide
+        let name = std::fs::canonicalize(root)
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| root.display().to_string());
+        let label = if responses.iter().any(|(existing, _)| *existing == name) {
+            root.display().to_string()
+        } else {
+            name
+        };
+
+        let engine = QueryEngine::new(CacheManager::new(root));
+        let response = engine
+            .search_with_metadata(pattern, filter.clone())
+            .with_context(|| format!("Failed to query index '{}' ({})", label, root.display()))?;
+        responses.push((label, response));
+    }
+
+    Ok(crate::query::merge_responses(responses))
+}
+
+/// Write query results to stdout as NDJSON, one record per line, flushing as each is produced
+///
+/// Errors are reported in-band as a `{"type":"error",...}` record so consumers reading the
+/// stream never have to parse stderr.
+fn stream_query_ndjson(
+    engine: &QueryEngine,
+    pattern: &str,
+    filter: QueryFilter,
+    max_preview: Option<usize>,
+    mut warnings: Vec<crate::models::CliWarning>,
+    tuned_limit_in_effect: bool,
+) -> Result<()> {
+    use std::io::Write;
+    use crate::query::QueryStreamRecord;
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    let result = engine.search_streaming(pattern, filter, |mut record| {
+        match &mut record {
+            QueryStreamRecord::File(group) => {
+                if let Some(max_length) = max_preview {
+                    for m in group.matches.iter_mut() {
+                        m.preview = truncate_preview(&m.preview, max_length);
+                    }
+                }
+            }
+            QueryStreamRecord::Summary { pagination, warnings: summary_warnings, .. } => {
+                warnings.extend(query_behavior_warnings(tuned_limit_in_effect, pagination.has_more));
+                *summary_warnings = std::mem::take(&mut warnings);
+            }
+            QueryStreamRecord::Header { .. } => {}
+        }
+        serde_json::to_writer(&mut out, &record)?;
+        out.write_all(b"\n")?;
+        out.flush()?;
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        let error_record = serde_json::json!({
+            "type": "error",
+            "error": e.to_string(),
+            "code": crate::error_code::error_code(&e),
+            "query_too_broad": e.to_string().contains("Query too broad")
+        });
+        // Ignore write failures here: if stdout is gone there is nobody left to tell
+        let _ = writeln!(out, "{}", error_record);
+        let _ = out.flush();
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print `rfx query --group-by` groups as a table or JSON
+fn print_result_groups(
+    groups: &[crate::grouping::ResultGroup],
+    group_by: crate::grouping::GroupBy,
+    total: usize,
+    warnings: &[crate::models::CliWarning],
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    if as_json {
+        let response = serde_json::json!({
+            "group_by": group_by,
+            "total": total,
+            "groups": groups,
+            "warnings": warnings,
+        });
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&response)?
+        } else {
+            serde_json::to_string(&response)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("No results found.");
+    } else {
+        let header = match group_by {
+            crate::grouping::GroupBy::Symbol => "Symbol",
+            crate::grouping::GroupBy::Dir => "Directory",
+            crate::grouping::GroupBy::Lang => "Language",
+            crate::grouping::GroupBy::Kind => "Kind",
+        };
+        println!("  Matches  Files  {}", header);
+        println!("  -------  -----  {}", "-".repeat(header.len()));
+        for group in groups {
+            println!("  {:7}  {:5}  {}", group.matches, group.files, group.key);
+        }
+    }
+    crate::deprecations::print_warnings(warnings);
+
+    Ok(())
+}
+
+/// Deprecation-registry notices raised by how a query behaved
+fn query_behavior_warnings(tuned_limit_in_effect: bool, has_more: bool) -> Vec<crate::models::CliWarning> {
+    let mut warnings = Vec::new();
+
+    // A size-tuned limit below the old fixed default silently returns fewer results
+    if tuned_limit_in_effect && has_more {
+        warnings.extend(crate::deprecations::warning(crate::deprecations::TUNED_DEFAULT_LIMIT));
+    }
+
+    warnings
+}
+
+/// Handle the `q` subcommand by translating the expression into `rfx query` options
+fn handle_q(expr: String, as_json: bool, pretty_json: bool, count: bool, paths: bool, all: bool, dir: PathBuf) -> Result<()> {
+    let query: crate::query_dsl::QueryExpr = expr.parse()?;
+    log::debug!("Query expression {:?} parsed as {:?}", expr, query);
+
+    handle_query(QueryArgs {
+        pattern: query.pattern,
+        symbols_flag: query.symbols,
+        lang: query.lang,
+        kind_str: query.kind,
+        kind_not_str: query.kind_not,
+        use_regex: query.regex,
+        as_json,
+        pretty_json,
+        limit: query.limit,
+        offset: query.offset,
+        file_pattern: query.file,
+        exact: query.exact,
+        use_contains: query.contains,
+        count_only: count,
+        glob_patterns: query.globs,
+        exclude_patterns: query.excludes,
+        not_patterns: query.nots,
+        changed_since: query.changed_since,
+        scope: query.scope,
+        paths_only: paths,
+        all,
+        dir,
+        ..Default::default()
+    })
+}
+
+/// Options of the `query` subcommand (also filled in by `q`)
+#[derive(Default)]
+struct QueryArgs {
+    pattern: String,
+    symbols_flag: bool,
+    lang: Option<String>,
+    kind_str: Option<String>,
+    kind_not_str: Option<String>,
+    use_ast: bool,
+    use_regex: bool,
+    as_json: bool,
+    pretty_json: bool,
+    format: Option<String>,
+    ai_mode: bool,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    expand: bool,
+    file_pattern: Option<String>,
+    exact: bool,
+    use_contains: bool,
+    fuzzy: bool,
+    fuzzy_threshold: Option<f64>,
+    tokens: bool,
+    semantic: bool,
+    count_only: bool,
+    timeout: Option<u64>,
+    budget_ms: Option<u64>,
+    max_tokens: Option<usize>,
+    explain: bool,
+    plain: bool,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    not_patterns: Vec<String>,
+    changed_since: Option<String>,
+    rev: Option<String>,
+    scope: Option<String>,
+    qualified: Option<String>,
+    visibility: Option<String>,
+    owner: Option<String>,
+    table: Option<String>,
+    paths_only: bool,
+    group_by: Option<String>,
+    no_truncate: bool,
+    all: bool,
+    force: bool,
+    include_dependencies: bool,
+    related_files: Option<usize>,
+    with_owners: bool,
+    blame: bool,
+    include_deleted: bool,
+    with_docs: bool,
+    span: Option<String>,
+    before: Option<usize>,
+    after: Option<usize>,
+    context_lines: Option<usize>,
+    dir: PathBuf,
+    indexes: Vec<PathBuf>,
+}
+
+/// Handle the `query` subcommand
+fn handle_query(args: QueryArgs) -> Result<()> {
+    let QueryArgs {
+        pattern, symbols_flag, lang, kind_str, kind_not_str, use_ast, use_regex, as_json,
+        pretty_json, format, ai_mode, limit, offset, expand, file_pattern, exact, use_contains,
+        fuzzy, fuzzy_threshold, tokens, semantic, count_only, timeout, budget_ms, max_tokens,
+        explain, plain, glob_patterns, exclude_patterns, not_patterns, changed_since, rev, scope,
+        qualified, visibility, owner, table, paths_only, group_by, no_truncate, all, force,
+        include_dependencies, related_files, with_owners, blame, include_deleted, with_docs, span,
+        before, after, context_lines, dir, indexes,
+    } = args;
+
+    log::info!("Starting query command");
+
+    let span_policy = span.as_deref().map(str::parse::<SpanPolicy>).transpose()?.unwrap_or_default();
+    let visibility = visibility.as_deref().map(str::parse::<Visibility>).transpose()?;
+    let group_by = group_by.as_deref().map(str::parse::<crate::grouping::GroupBy>).transpose()?;
+
+    // Resolve grep-style context flags: explicit -B/-A win over -C
+    let before_context = before.or(context_lines);
+    let after_context = after.or(context_lines);
+    let show_context = before_context.is_some() || after_context.is_some();
+
+    // --format ndjson streams records; --format json is an alias for --json
+    let ndjson = format.as_deref() == Some("ndjson");
+    if ndjson && (use_ast || count_only || paths_only || group_by.is_some()) {
+        anyhow::bail!(
+            "--format ndjson cannot be combined with --ast, --count, --paths or --group-by.\n\
+             \n\
+             NDJSON output streams one record per matching file.\n\
+             Use --json for AST queries, counts, path lists and groups."
+        );
+    }
+    if !indexes.is_empty() && (use_ast || ndjson) {
+        anyhow::bail!(
+            "--index cannot be combined with --ast or --format ndjson.\n\
+             \n\
+             Multi-index queries merge full-text and symbol results only.\n\
+             Run AST queries against each project with --dir instead:\n\
+             • rfx query \"(function_item) @fn\" --ast --lang rust --dir ../other-service"
+        );
+    }
+    if explain && (ndjson || !indexes.is_empty()) {
+        anyhow::bail!(
+            "--explain cannot be combined with --format ndjson or --index.\n\
+             \n\
+             The query plan describes a single buffered query.\n\
+             Explain each project separately with --dir instead:\n\
+             • rfx query \"handler\" --symbols --explain --dir ../other-service"
+        );
+    }
+
+    if fuzzy_threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+        anyhow::bail!(
+            "--fuzzy-threshold must be between 0.0 and 1.0.\n\
+             \n\
+             Scores are name similarities: 1.0 is an identical name, 0.99 the same words\n\
+             in another case convention (getUserByID vs get_user_by_id).\n\
+             \n\
+             Example: rfx query \"getUserByID\" --fuzzy --fuzzy-threshold 0.9"
+        );
+    }
+
+    // AI mode implies JSON output
+    let as_json = as_json || ai_mode || format.is_some();
+
+    if max_tokens.is_some() && (!as_json || ndjson || paths_only || count_only) {
+        anyhow::bail!(
+            "--max-tokens applies to JSON result output only.\n\
+             \n\
+             Use it with --json or --ai (not --format ndjson, --paths or --count):\n\
+             • rfx query \"unwrap\" --ai --max-tokens 4000"
+        );
+    }
+
+    let cache = CacheManager::new(&dir);
+
+    // Size-tuned defaults recorded by `rfx index` (explicit flags always win)
+    let defaults = crate::tuning::search_defaults(&cache);
+    let timeout_secs = timeout.unwrap_or(defaults.timeout_secs);
+    let max_preview_length = defaults.max_preview_length;
+
+    // Flag notices from the deprecation registry (behavior notices are added after the search)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut warnings = crate::deprecations::flag_warnings(&args);
+
+    // Search the segment of a historical revision instead of the working tree (--rev)
+    let cache = match rev.as_deref() {
+        Some(rev) => crate::revision::open(&dir, &cache, rev, crate::revision::index_config(&cache)?)?,
+        None => cache,
+    };
+
+    let engine = QueryEngine::new(cache.clone());
+
+    // Parse and validate language filter
+    let language = if let Some(lang_str) = lang.as_deref() {
+        match Language::from_name(lang_str) {
+            Some(language) => Some(language),
+            None => {
+                anyhow::bail!(
+                    "Unknown language: '{}'\n\
+                     \n\
+                     Supported languages:\n\
+                     • rust, rs\n\
+                     • python, py\n\
+                     • javascript, js\n\
+                     • typescript, ts\n\
+                     • vue\n\
+                     • svelte\n\
+                     • go\n\
+                     • java\n\
+                     • php\n\
+                     • c\n\
+                     • c++, cpp\n\
+                     • c#, csharp, cs\n\
+                     • ruby, rb\n\
+                     • kotlin, kt\n\
+                     • zig\n\
+                     • shell, sh, bash, zsh\n\
+                     • make, makefile\n\
+                     • cmake\n\
+                     • bazel, starlark, bzl\n\
+                     • sql (plus embedded regions, see [embedded] in config.toml)\n\
+                     • graphql, html (embedded regions, see [embedded] in config.toml)\n\
+                     \n\
+                     Example: rfx query \"pattern\" --lang rust",
+                    lang_str
+                );
+            }
+        }
+    } else {
+        None
+    };
+
+    // Parse symbol kinds (case-insensitive, unknown kinds filter as-is)
+    let kinds = kind_str.as_deref().map(crate::models::SymbolKind::parse_list).unwrap_or_default();
+    let exclude_kinds = kind_not_str.as_deref().map(crate::models::SymbolKind::parse_list).unwrap_or_default();
+
+    // Smart behavior: --kind and --kind-not imply --symbols
+    let symbols_mode = symbols_flag || !kinds.is_empty() || !exclude_kinds.is_empty() || fuzzy || qualified.is_some() || visibility.is_some();
+
+    // Symbols from a language whose grammar failed to load come from the fallback parser
+    if symbols_mode {
+        warnings.extend(crate::deprecations::degraded_grammar_warnings(language));
+    }
+
+    // Smart limit handling:
+    // 1. If --count is set: no limit (count should always show total)
+    // 2. If --all is set: no limit (None)
+    // 3. If --limit 0 is set: no limit (None) - treat 0 as "unlimited"
+    // 4. If --paths is set and user didn't specify --limit: no limit (None)
+    // 5. If user specified --limit: use that value
+    // 6. Otherwise: use the size-tuned default limit (100 for mid-sized repos)
+    let final_limit = if count_only {
+        None  // --count always shows total count, no pagination
+    } else if all {
+        None  // --all means no limit
+    } else if limit == Some(0) {
+        None  // --limit 0 means no limit (unlimited results)
+    } else if (paths_only || group_by.is_some()) && limit.is_none() {
+        None  // --paths without explicit --limit means no limit
+    } else if let Some(user_limit) = limit {
+        Some(user_limit)  // Use user-specified limit
+    } else {
+        defaults.limit  // Default: tuned to repo size for token efficiency
+    };
+    let tuned_limit_in_effect = !count_only && !all && !paths_only && group_by.is_none() && limit.is_none()
+        && defaults.limit.is_some_and(|l| l < crate::tuning::DEFAULT_LIMIT);
+
+    // Validate AST query requirements
+    if use_ast && language.is_none() {
+        anyhow::bail!(
+            "AST pattern matching requires a language to be specified.\n\
+             \n\
+             Use --lang to specify the language for tree-sitter parsing.\n\
+             \n\
+             Supported languages for AST queries:\n\
+             • rust, python, go, java, c, c++, c#, php, ruby, kotlin, zig, typescript, javascript\n\
+             \n\
+             Note: Vue and Svelte use line-based parsing and do not support AST queries.\n\
+             \n\
+             WARNING: AST queries are SLOW (500ms-2s+). Use --symbols instead for 95% of cases.\n\
+             \n\
+             Examples:\n\
+             • rfx query \"(function_definition) @fn\" --ast --lang python\n\
+             • rfx query \"(class_declaration) @class\" --ast --lang typescript --glob \"src/**/*.ts\""
+        );
+    }
+
+    // VALIDATION: Check for conflicting or problematic flag combinations
+    // Only show warnings/errors in non-JSON mode (avoid breaking parsers)
+    if !as_json {
+        let mut has_errors = false;
+
+        // ERROR: Mutually exclusive pattern matching modes
+        if use_regex && use_contains {
+            eprintln!("{}", "ERROR: Cannot use --regex and --contains together.".red().bold());
+            eprintln!("  {} --regex for pattern matching (alternation, wildcards, etc.)", "•".dimmed());
+            eprintln!("  {} --contains for substring matching (expansive search)", "•".dimmed());
+            eprintln!("\n  {} Choose one based on your needs:", "Tip:".cyan().bold());
+            eprintln!("    {} for OR logic: --regex", "pattern1|pattern2".yellow());
+            eprintln!("    {} for substring: --contains", "partial_text".yellow());
+            has_errors = true;
+        }
+
+        // ERROR: Contradictory matching requirements
+        if exact && use_contains {
+            eprintln!("{}", "ERROR: Cannot use --exact and --contains together (contradictory).".red().bold());
+            eprintln!("  {} --exact requires exact symbol name match", "•".dimmed());
+            eprintln!("  {} --contains allows substring matching", "•".dimmed());
+            has_errors = true;
+        }
+
+        // WARNING: Redundant file filtering
+        if file_pattern.is_some() && !glob_patterns.is_empty() {
+            eprintln!("{}", "WARNING: Both --file and --glob specified.".yellow().bold());
+            eprintln!("  {} --file does substring matching on file paths", "•".dimmed());
+            eprintln!("  {} --glob does pattern matching with wildcards", "•".dimmed());
+            eprintln!("  {} Both filters will apply (AND condition)", "Note:".dimmed());
+            eprintln!("\n  {} Usually you only need one:", "Tip:".cyan().bold());
+            eprintln!("    {} for simple matching", "--file User.php".yellow());
+            eprintln!("    {} for pattern matching", "--glob src/**/*.php".yellow());
+        }
+
+        // INFO: Detect potentially problematic glob patterns
+        for pattern in &glob_patterns {
+            // Check for literal quotes in pattern
+            if (pattern.starts_with('\'') && pattern.ends_with('\'')) ||
+               (pattern.starts_with('"') && pattern.ends_with('"')) {
+                eprintln!("{}",
+                    format!("WARNING: Glob pattern contains quotes: {}", pattern).yellow().bold()
+                );
+                eprintln!("  {} Shell quotes should not be part of the pattern", "Note:".dimmed());
+                eprintln!("  {} --glob src/**/*.rs", "Correct:".green());
+                eprintln!("  {} --glob 'src/**/*.rs'", "Wrong:".red().dimmed());
+            }
+
+            // Suggest using ** instead of * for recursive matching
+            if pattern.contains("*/") && !pattern.contains("**/") {
+                eprintln!("{}",
+                    format!("INFO: Glob '{}' uses * (matches one directory level)", pattern).cyan()
+                );
+                eprintln!("  {} Use ** for recursive matching across subdirectories", "Tip:".cyan().bold());
+                eprintln!("    {} → matches files in Models/ only", "app/Models/*.php".yellow());
+                eprintln!("    {} → matches files in Models/ and subdirs", "app/Models/**/*.php".green());
+            }
+        }
+
+        if has_errors {
+            anyhow::bail!("Invalid flag combination. Fix the errors above and try again.");
+        }
+    }
+
+    let filter = QueryFilter {
+        language,
+        kinds,
+        exclude_kinds,
+        use_ast,
+        use_regex,
+        limit: final_limit,
+        symbols_mode,
+        expand,
+        file_pattern,
+        exact,
+        use_contains,
+        fuzzy,
+        fuzzy_threshold,
+        tokens,
+        semantic,
+        timeout_secs,
+        budget_ms,
+        changed_since,
+        scope,
+        qualified,
+        visibility,
+        owner,
+        table,
+        glob_patterns: glob_patterns.clone(),
+        exclude_patterns,
+        not_patterns,
+        paths_only,
+        offset,
+        force,
+        explain,
+        suppress_output: as_json,  // Suppress warnings in JSON mode
+        include_dependencies,
+        related_files,
+        with_owners,
+        blame,
+        include_deleted,
+        with_docs,
+        span_policy,
+        before_context,
+        after_context,
+        ..Default::default()
+    };
+
+    if ndjson {
+        let max_preview = if no_truncate { None } else { Some(max_preview_length) };
+        return stream_query_ndjson(&engine, &pattern, filter, max_preview, warnings, tuned_limit_in_effect);
+    }
+
+    // Measure query time
+    let start = Instant::now();
+
+    // Execute query and get pagination metadata
+    // Handle errors specially for JSON output mode
+    let (query_response, mut flat_results, total_results, has_more) = if use_ast {
+        // AST query: pattern is the S-expression, scan all files
+        match engine.search_ast_all_files(&pattern, filter.clone()) {
+            Ok(ast_results) => {
+                let count = ast_results.len();
+                (None, ast_results, count, false)
+            }
+            Err(e) => {
+                if as_json {
+                    // Output error as JSON
+                    let error_response = serde_json::json!({
+                        "error": e.to_string(),
+                        "code": crate::error_code::error_code(&e),
+                        "query_too_broad": e.to_string().contains("Query too broad")
+                    });
+                    let json_output = if pretty_json {
+                        serde_json::to_string_pretty(&error_response)?
+                    } else {
+                        serde_json::to_string(&error_response)?
+                    };
+                    println!("{}", json_output);
+                    std::process::exit(1);
+                } else {
+                    return Err(e);
+                }
+            }
+        }
+    } else {
+        // Use metadata-aware search for all queries (to get pagination info)
+        let response = if indexes.is_empty() {
+            engine.search_with_metadata(&pattern, filter.clone())
+        } else {
+            search_indexes(&dir, &indexes, &pattern, &filter)
+        };
+        match response {
+            Ok(response) => {
+                let total = response.pagination.total;
+                let has_more = response.pagination.has_more;
+
+                if response.unscanned_candidates > 0 && !as_json {
+                    output::warn(&format!(
+                        "Time budget expired: results are incomplete ({} candidate files not scanned).",
+                        response.unscanned_candidates
+                    ));
+                }
+                match response.partial {
+                    Some(crate::models::PartialStatus::TimeoutPartial) if !as_json => output::warn(&format!(
+                        "Query timeout exceeded ({} seconds): results are partial (files not parsed in time were skipped).",
+                        filter.timeout_secs
+                    )),
+                    Some(crate::models::PartialStatus::MemoryPartial) if !as_json => output::warn(
+                        "Query memory ceiling reached: results are partial (files that didn't fit were skipped). Raise [search] max_memory_mb or narrow the pattern."
+                    ),
+                    _ => {}
+                }
+
+                // Flatten grouped results to SearchResult vec for plain text formatting
+                // With -A/-B/-C, the preview carries the surrounding lines (each truncated separately)
+                let flat = response.results.iter()
+                    .flat_map(|file_group| {
+                        file_group.matches.iter().map(move |m| {
+                            let preview = if show_context {
+                                m.context_before.iter()
+                                    .chain(std::iter::once(&m.preview))
+                                    .chain(m.context_after.iter())
+                                    .map(|line| if no_truncate {
+                                        line.clone()
+                                    } else {
+                                        truncate_preview(line, max_preview_length)
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            } else {
+                                m.preview.clone()
+                            };
+
+                            crate::models::SearchResult {
+                                path: file_group.path.clone(),
+                                lang: crate::models::Language::Unknown, // Will be set by formatter if needed
+                                kind: m.kind.clone(),
+                                symbol: m.symbol.clone(),
+                                parent: m.parent.clone(),
+                                qualified_name: m.qualified_name.clone(),
+                                span: m.span.clone(),
+                                preview,
+                                dependencies: file_group.dependencies.clone(),
+                                repo: file_group.repo.clone(),
+                                signature: m.signature.clone(),
+                                doc: m.doc.clone(),
+                                exported: m.exported,
+                                captures: m.captures.clone(),
+                                score: None,
+                            }
+                        })
+                    })
+                    .collect();
+
+                (Some(response), flat, total, has_more)
+            }
+            Err(e) => {
+                if as_json {
+                    // Output error as JSON
+                    let error_response = serde_json::json!({
+                        "error": e.to_string(),
+                        "code": crate::error_code::error_code(&e),
+                        "query_too_broad": e.to_string().contains("Query too broad")
+                    });
+                    let json_output = if pretty_json {
+                        serde_json::to_string_pretty(&error_response)?
+                    } else {
+                        serde_json::to_string(&error_response)?
+                    };
+                    println!("{}", json_output);
+                    std::process::exit(1);
+                } else {
+                    return Err(e);
+                }
+            }
+        }
+    };
+
+    warnings.extend(query_behavior_warnings(tuned_limit_in_effect, has_more));
+
+    // Apply preview truncation unless --no-truncate is set
+    // (context previews were already truncated line by line above)
+    if !no_truncate && !show_context {
+        for result in &mut flat_results {
+            result.preview = truncate_preview(&result.preview, max_preview_length);
+        }
+    }
+
+    let elapsed = start.elapsed();
+
+    let history_filters = crate::history::HistoryFilters {
+        symbols: symbols_mode,
+        regex: use_regex,
+        contains: use_contains,
+        expand,
+        lang: lang.clone(),
+        kind: kind_str.clone(),
+        glob: glob_patterns.clone(),
+        exclude: filter.exclude_patterns.clone(),
+    };
+    let directories = crate::history::result_directories(flat_results.iter().map(|r| r.path.as_str()));
+    record_query_history(&dir, &args, &pattern, history_filters, flat_results.len(), total_results, directories, elapsed);
+
+    // Format timing string
+    let timing_str = if elapsed.as_millis() < 1 {
+        format!("{:.1}ms", elapsed.as_secs_f64() * 1000.0)
+    } else {
+        format!("{}ms", elapsed.as_millis())
+    };
+
+    if let Some(group_by) = group_by {
+        let groups = crate::grouping::group_results(&flat_results, group_by, &cache);
+        print_result_groups(&groups, group_by, flat_results.len(), &warnings, as_json, pretty_json)?;
+        eprintln!("Found {} results in {} groups in {}", flat_results.len(), groups.len(), timing_str);
+        return Ok(());
+    }
+
+    if as_json {
+        if count_only {
+            // Count-only JSON mode: output simple count object
+            let count_response = serde_json::json!({
+                "count": total_results,
+                "timing_ms": elapsed.as_millis(),
+                "warnings": warnings
+            });
+            let json_output = if pretty_json {
+                serde_json::to_string_pretty(&count_response)?
+            } else {
+                serde_json::to_string(&count_response)?
+            };
+            println!("{}", json_output);
+        } else if paths_only {
+            // Paths-only JSON mode: output array of {path, line} objects
+            let locations: Vec<serde_json::Value> = flat_results.iter()
+                .map(|r| match &r.repo {
+                    Some(repo) => serde_json::json!({
+                        "repo": repo,
+                        "path": r.path,
+                        "line": r.span.start_line
+                    }),
+                    None => serde_json::json!({
+                        "path": r.path,
+                        "line": r.span.start_line
+                    }),
+                })
+                .collect();
+            let json_output = if pretty_json {
+                serde_json::to_string_pretty(&locations)?
+            } else {
+                serde_json::to_string(&locations)?
+            };
+            println!("{}", json_output);
+            eprintln!("Found {} unique files in {}", locations.len(), timing_str);
+            // A bare array has nowhere to carry notices, so they go to stderr
+            crate::deprecations::print_warnings(&warnings);
+        } else {
+            // Get or build QueryResponse for JSON output
+            let mut response = if let Some(resp) = query_response {
+                // We already have a response from search_with_metadata
+                // Apply truncation to the response (the flat_results were already truncated)
+                let mut resp = resp;
+
+                // Apply truncation to results
+                if !no_truncate {
+                    for file_group in resp.results.iter_mut() {
+                        for m in file_group.matches.iter_mut() {
+                            m.preview = truncate_preview(&m.preview, max_preview_length);
+                        }
+                    }
+                }
+
+                resp
+            } else {
+                // For AST queries, build a response with minimal metadata
+                // Group flat results by file path
+                use crate::models::{PaginationInfo, IndexStatus, FileGroupedResult, MatchResult};
+                use std::collections::HashMap;
+
+                let explainer = crate::query::MatchExplainer::new(&pattern, &filter);
+
+                let mut grouped: HashMap<String, Vec<crate::models::SearchResult>> = HashMap::new();
+                for result in &flat_results {
+                    grouped
+                        .entry(result.path.clone())
+                        .or_default()
+                        .push(result.clone());
+                }
+
+                // Load ContentReader for extracting context lines
+                use crate::content_store::ContentReader;
+                let content_path = cache.path().join("content.bin");
+                let content_reader_opt = ContentReader::open(&content_path).ok();
+
+                // CODEOWNERS annotations (--with-owners)
+                let owner_matcher = if with_owners {
+                    crate::owners::load(&CacheManager::new(&dir))?.map(|owners| owners.matcher())
+                } else {
+                    None
+                };
+                let blame_cache = if blame { Some(crate::blame::BlameCache::open(&cache)?) } else { None };
+
+                let mut file_results: Vec<FileGroupedResult> = grouped
+                    .into_iter()
+                    .map(|(path, file_matches)| {
+                        // Get file_id for context extraction
+                        // Note: We use ContentReader's get_file_id_by_path() which returns array indices,
+                        // not database file_ids (which are AUTO INCREMENT values)
+                        let normalized_path = path.strip_prefix("./").unwrap_or(&path);
+                        let file_id_for_context = if let Some(reader) = &content_reader_opt {
+                            reader.get_file_id_by_path(normalized_path)
+                        } else {
+                            None
+                        };
+
+                        // Notebook spans refer to the rendered notebook; map them back to cells
+                        let notebook_content = match (&content_reader_opt, file_id_for_context) {
+                            (Some(reader), Some(fid)) if crate::notebook::is_notebook(&path) => reader.get_file_content(fid).ok(),
+                            _ => None,
+                        };
+
+                        let matches: Vec<MatchResult> = file_matches
+                            .into_iter()
+                            .map(|r| {
+                                // Extract context lines (default: 3 lines before and after)
+                                let (context_before, context_after) = if let (Some(reader), Some(fid)) = (&content_reader_opt, file_id_for_context) {
+                                    reader.get_context_lines(
+                                        fid,
+                                        r.span.start_line,
+                                        before_context.unwrap_or(crate::query::DEFAULT_CONTEXT_LINES),
+                                        after_context.unwrap_or(crate::query::DEFAULT_CONTEXT_LINES),
+                                    )
+                                        .unwrap_or_else(|_| (vec![], vec![]))
+                                } else {
+                                    (vec![], vec![])
+                                };
+
+                                let match_reason = Some(explainer.explain(&r));
+                                let cell = notebook_content.and_then(|content| crate::notebook::locate_cell(content, r.span.start_line));
+
+                                MatchResult {
+                                    kind: r.kind,
+                                    symbol: r.symbol,
+                                    parent: r.parent,
+                                    qualified_name: r.qualified_name,
+                                    signature: r.signature,
+                                    doc: r.doc,
+                                    exported: r.exported,
+                                    span: r.span,
+                                    preview: r.preview,
+                                    context_before,
+                                    context_after,
+                                    match_reason,
+                                    cell,
+                                    captures: r.captures,
+                                    blame: None,
+                                }
+                            })
+                            .collect();
+                        let owners = owner_matcher.as_ref().map(|matcher| {
+                            let relative = crate::paths::relative_path(std::path::Path::new(&path), &cache.workspace_root());
+                            matcher.owners_of(&relative).to_vec()
+                        });
+                        let mut group = FileGroupedResult {
+                            path,
+                            dependencies: None,
+                            repo: None,
+                            related_files: None,
+                            transform: None,
+                            owners,
+                            matches,
+                        };
+                        if let Some(ref blame_cache) = blame_cache {
+                            blame_cache.annotate(&mut group);
+                        }
+                        group
+                    })
+                    .collect();
+
+                // Sort by path for deterministic output
+                file_results.sort_by(|a, b| a.path.cmp(&b.path));
+
+                crate::models::QueryResponse {
+                    ai_instruction: None,  // Will be populated below if ai_mode is true
+                    status: IndexStatus::Fresh,
+                    can_trust_results: true,
+                    warning: None,
+                    pagination: PaginationInfo {
+                        total: flat_results.len(),
+                        count: flat_results.len(),
+                        offset: offset.unwrap_or(0),
+                        limit,
+                        has_more: false, // AST already applied pagination
+                    },
+                    complete: true,
+                    unscanned_candidates: 0,
+                    partial: None,
+                    truncated: false,
+                    results: file_results,
+                    deleted: None,
+                    warnings: Vec::new(),
+                    plan: None,
+                    timings: None,
+                }
+            };
+
+            // Generate AI instruction if in AI mode
+            if ai_mode {
+                let result_count: usize = response.results.iter().map(|fg| fg.matches.len()).sum();
+
+                response.ai_instruction = crate::query::generate_ai_instruction(
+                    result_count,
+                    response.pagination.total,
+                    response.pagination.has_more,
+                    symbols_mode,
+                    paths_only,
+                    use_ast,
+                    use_regex,
+                    language.is_some(),
+                    !glob_patterns.is_empty(),
+                    exact,
+                );
+            }
+
+            response.warnings = warnings;
+
+            // Trim to the token budget last, so its estimate covers everything that is printed
+            if let Some(max_tokens) = max_tokens {
+                crate::token_budget::fit_response(&mut response, max_tokens, ai_mode);
+            }
+
+            let json_output = if pretty_json {
+                serde_json::to_string_pretty(&response)?
+            } else {
+                serde_json::to_string(&response)?
+            };
+            println!("{}", json_output);
+
+            let result_count: usize = response.results.iter().map(|fg| fg.matches.len()).sum();
+            eprintln!("Found {} results in {}", result_count, timing_str);
+        }
+    } else {
+        // Standard output with formatting
+        if count_only {
+            println!("Found {} results in {}", flat_results.len(), timing_str);
+            if let Some(response) = query_response.as_ref().filter(|r| r.plan.is_some()) {
+                println!();
+                print_query_plan(response);
+            }
+            crate::deprecations::print_warnings(&warnings);
+            return Ok(());
+        }
+
+        if paths_only {
+            // Paths-only plain text mode: output one path per line
+            if flat_results.is_empty() {
+                eprintln!("No results found (searched in {}).", timing_str);
+            } else {
+                for result in &flat_results {
+                    println!("{}", result.display_path());
+                }
+                eprintln!("Found {} unique files in {}", flat_results.len(), timing_str);
+            }
+        } else {
+            // Standard result formatting
+            if flat_results.is_empty() {
+                println!("No results found (searched in {}).", timing_str);
+            } else {
+                // Use formatter for pretty output
+                let formatter = crate::formatter::OutputFormatter::new(plain);
+                formatter.format_results(&flat_results, &pattern)?;
+
+                // Print summary at the bottom with pagination details
+                if total_results > flat_results.len() {
+                    // Results were paginated - show detailed count
+                    println!("\nFound {} results ({} total) in {}", flat_results.len(), total_results, timing_str);
+                    // Show pagination hint if there are more results available
+                    if has_more {
+                        println!("Use --limit and --offset to paginate");
+                    }
+                } else {
+                    // All results shown - simple count
+                    println!("\nFound {} results in {}", flat_results.len(), timing_str);
+                }
+            }
+        }
+
+        // Tombstones of removed files (--include-deleted)
+        if let Some(deleted) = query_response.as_ref().and_then(|r| r.deleted.as_ref())
+            && !deleted.is_empty()
+        {
+            println!();
+            print_tombstones(deleted);
+        }
+
+        // Query plan (--explain)
+        if let Some(response) = query_response.as_ref().filter(|r| r.plan.is_some()) {
+            println!();
+            print_query_plan(response);
+        }
+
+        crate::deprecations::print_warnings(&warnings);
+    }
+
+    Ok(())
+}
+
+/// Handle the `serve` subcommand
+fn handle_serve(
+    port: u16,
+    host: String,
+    auth_token: Option<String>,
+    tls: Option<(PathBuf, PathBuf)>,
+    rate_limit: Option<u32>,
+    watch_debounce_ms: Option<u64>,
+) -> Result<()> {
+    log::info!("Starting HTTP server on {}:{}", host, port);
+
+    if let Some(debounce_ms) = watch_debounce_ms {
+        if !(5000..=30000).contains(&debounce_ms) {
+            anyhow::bail!(
+                "Debounce must be between 5000ms (5s) and 30000ms (30s). Got: {}ms",
+                debounce_ms
+            );
+        }
+    }
+
+    let auth_token = crate::http_auth::resolve_token(auth_token);
+    let scheme = if tls.is_some() { "https" } else { "http" };
+
+    println!("Starting Reflex HTTP server...");
+    println!("  Address: {}://{}:{}", scheme, host, port);
+    match &auth_token {
+        Some(_) => println!("  Auth:    bearer token required (except /health)"),
+        None => println!("  Auth:    none"),
+    }
+    if let Some(n) = rate_limit.filter(|n| *n > 0) {
+        println!("  Rate limit: {} requests/minute per client", n);
+    }
+    if let Some(debounce_ms) = watch_debounce_ms {
+        println!("  Watching: reindex {}s after changes", debounce_ms / 1000);
+    }
+
+    if !crate::http_auth::is_loopback(&host) {
+        if auth_token.is_none() {
+            eprintln!(
+                "\n{} Binding to {} without --auth-token: anyone who can reach this port can query the index.\n\
+                 POST /index is refused for non-local clients. Set --auth-token or {} to require a token.",
+                "Warning:".yellow().bold(),
+                host,
+                crate::http_auth::TOKEN_ENV
+            );
+        }
+        if tls.is_none() {
+            eprintln!(
+                "{} Serving plain HTTP on {}: tokens and results are sent unencrypted. Use --tls-cert/--tls-key.",
+                "Warning:".yellow().bold(),
+                host
+            );
+        }
+    }
+    println!("\nEndpoints:");
+    println!("  GET  /query?q=<pattern>&lang=<lang>&kind=<kind>&limit=<n>&symbols=true&regex=true&exact=true&contains=true&expand=true&file=<pattern>&timeout=<secs>&budget_ms=<ms>&changed_since=<ref>&rev=<rev>&scope=<symbol>&qualified=<path>&visibility=<public|private>&owner=<team>&glob=<pattern>&exclude=<pattern>&paths=true&dependencies=true&related=<n>&with_owners=true&blame=true&semantic=true&include_deleted=true&with_docs=true&span=with-attrs&format=ndjson&dir=<path>");
+    println!("  GET  /symbols/<name>?lang=<lang>&kind=<kind>&exact=false&limit=<n>&with_docs=true&dir=<path>");
+    println!("  GET  /ast?q=<s-expr>&lang=<lang>&glob=<pattern>&limit=<n>&dir=<path>");
+    println!("  GET  /outline?file=<path>&dir=<path>");
+    println!("  GET  /deps?file=<path>&reverse=true&depth=<n>&dir=<path>");
+    println!("  GET  /analyze?type=summary|circular|hotspots|unused|dead_symbols|islands&limit=<n>&offset=<n>&dir=<path>");
+    println!("  GET  /context?structure=true&framework=true&...&dir=<path>");
+    println!("  GET  /stats?dir=<path>");
+    println!("  POST /index");
+    println!("  GET  /events (WebSocket or server-sent events)");
+    println!("  GET  /openapi.json");
+    println!("\nPress Ctrl+C to stop.");
+
+    // Start the server using tokio runtime
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        run_server(port, host, auth_token, tls, rate_limit, watch_debounce_ms).await
+    })
+}
+
+/// Run the HTTP server
+async fn run_server(
+    port: u16,
+    host: String,
+    auth_token: Option<String>,
+    tls: Option<(PathBuf, PathBuf)>,
+    rate_limit: Option<u32>,
+    watch_debounce_ms: Option<u64>,
+) -> Result<()> {
+    use axum::{
+        body::Body,
+        extract::ws::{Message, WebSocket, WebSocketUpgrade},
+        extract::{ConnectInfo, Path as AxumPath, Query as AxumQuery, Request, State},
+        http::{header, Method, StatusCode},
+        middleware::{self, Next},
+        response::sse::{Event, KeepAlive, Sse},
+        response::{IntoResponse, Json, Response},
+        routing::{get, post},
+        Router,
+    };
+    use crate::http_auth::{authorized, RateLimiter};
+    use crate::watcher::WatchEvent;
+    use tokio::sync::broadcast;
+    use tower_http::cors::{CorsLayer, Any};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    // Server state shared across requests
+    #[derive(Clone)]
+    struct AppState {
+        cache_path: String,
+        auth_token: Option<String>,
+        rate_limiter: Option<Arc<RateLimiter>>,
+        events: broadcast::Sender<WatchEvent>,
+    }
+
+    // Query parameters for GET /query
+    #[derive(Debug, serde::Deserialize)]
+    struct QueryParams {
+        q: String,
+        #[serde(default)]
+        lang: Option<String>,
+        #[serde(default)]
+        kind: Option<String>,
+        #[serde(default)]
+        kind_not: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: Option<usize>,
+        #[serde(default)]
+        symbols: bool,
+        #[serde(default)]
+        regex: bool,
+        #[serde(default)]
+        exact: bool,
+        #[serde(default)]
+        contains: bool,
+        #[serde(default)]
+        expand: bool,
+        #[serde(default)]
+        file: Option<String>,
+        #[serde(default)]
+        timeout: Option<u64>,
+        #[serde(default)]
+        budget_ms: Option<u64>,
+        #[serde(default)]
+        changed_since: Option<String>,
+        #[serde(default)]
+        rev: Option<String>,
+        #[serde(default)]
+        scope: Option<String>,
+        #[serde(default)]
+        qualified: Option<String>,
+        #[serde(default)]
+        visibility: Option<String>,
+        #[serde(default)]
+        owner: Option<String>,
+        #[serde(default)]
+        table: Option<String>,
+        #[serde(default)]
+        glob: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default)]
+        paths: bool,
+        #[serde(default)]
+        force: bool,
+        #[serde(default)]
+        dependencies: bool,
+        #[serde(default)]
+        related: Option<usize>,
+        #[serde(default)]
+        with_owners: bool,
+        #[serde(default)]
+        blame: bool,
+        #[serde(default)]
+        semantic: bool,
+        #[serde(default)]
+        include_deleted: bool,
+        #[serde(default)]
+        with_docs: bool,
+        #[serde(default)]
+        span: Option<String>,
+        #[serde(default)]
+        format: Option<String>,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /stats
+    #[derive(Debug, serde::Deserialize)]
+    struct StatsParams {
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Request body for POST /index
+    #[derive(Debug, serde::Deserialize)]
+    struct IndexRequest {
+        #[serde(default)]
+        force: bool,
+        #[serde(default)]
+        languages: Vec<String>,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /symbols/<name>
+    #[derive(Debug, serde::Deserialize)]
+    struct SymbolParams {
+        #[serde(default)]
+        lang: Option<String>,
+        #[serde(default)]
+        kind: Option<String>,
+        #[serde(default)]
+        exact: Option<bool>,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: Option<usize>,
+        #[serde(default)]
+        glob: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default)]
+        with_docs: bool,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /ast
+    #[derive(Debug, serde::Deserialize)]
+    struct AstParams {
+        q: String,
+        lang: String,
+        #[serde(default)]
+        file: Option<String>,
+        #[serde(default)]
+        glob: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: Option<usize>,
+        #[serde(default)]
+        paths: bool,
+        #[serde(default)]
+        force: bool,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /outline and GET /deps
+    #[derive(Debug, serde::Deserialize)]
+    struct FileParams {
+        file: String,
+        #[serde(default)]
+        reverse: bool,
+        #[serde(default)]
+        depth: Option<usize>,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /analyze
+    #[derive(Debug, serde::Deserialize)]
+    struct AnalyzeParams {
+        #[serde(default, rename = "type")]
+        analysis: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: Option<usize>,
+        #[serde(default)]
+        sort: Option<String>,
+        #[serde(default)]
+        min_dependents: Option<usize>,
+        #[serde(default)]
+        min_island_size: Option<usize>,
+        #[serde(default)]
+        max_island_size: Option<usize>,
+        #[serde(default)]
+        min_confidence: Option<String>,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Query parameters for GET /context
+    #[derive(Debug, serde::Deserialize)]
+    struct ContextParams {
+        #[serde(default)]
+        structure: bool,
+        #[serde(default)]
+        file_types: bool,
+        #[serde(default)]
+        project_type: bool,
+        #[serde(default)]
+        framework: bool,
+        #[serde(default)]
+        entry_points: bool,
+        #[serde(default)]
+        test_layout: bool,
+        #[serde(default)]
+        config_files: bool,
+        #[serde(default)]
+        conventions: bool,
+        #[serde(default)]
+        depth: Option<usize>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        dir: Option<String>,
+    }
+
+    // Project directory for a request: `dir` if given (403 unless it is inside the
+    // server's directory), else the server's directory
+    fn project_dir(state: &AppState, dir: Option<&str>) -> Result<std::path::PathBuf, ApiError> {
+        match dir {
+            Some(dir) => crate::paths::confine_dir(std::path::Path::new(&state.cache_path), dir).map_err(reject),
+            None => Ok(std::path::PathBuf::from(&state.cache_path)),
+        }
+    }
+
+    // Error response `{"error", "code"}`, with a status matching the code
+    type ApiError = (StatusCode, Json<crate::error_code::ErrorBody>);
+
+    fn api_error(e: &anyhow::Error) -> ApiError {
+        let body = crate::error_code::ErrorBody::from_error(e);
+        let status = StatusCode::from_u16(crate::error_code::http_status(body.code))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(body))
+    }
+
+    fn reject(error: ReflexError) -> ApiError {
+        api_error(&error.into())
+    }
+
+    // Run an MCP tool implementation off the async runtime (same code path as `rfx mcp`)
+    async fn tool_response(name: &'static str, arguments: serde_json::Value) -> Result<Json<serde_json::Value>, ApiError> {
+        let result = tokio::task::spawn_blocking(move || crate::mcp::call_tool(name, arguments))
+            .await
+            .map_err(|e| api_error(&anyhow::Error::new(e).context("Request failed")))?;
+
+        result.map(Json).map_err(|e| {
+            log::error!("{} error: {}", name, e);
+            let (status, body) = api_error(&e);
+            if body.code != crate::error_code::INTERNAL {
+                return (status, body);
+            }
+            // Tools that don't raise coded errors yet
+            let message = &body.error;
+            let status = if message.contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if message.contains("Invalid") || message.contains("Unknown") || message.contains("Missing") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, body)
+        })
+    }
+
+    // GET /symbols/<name> endpoint
+    async fn handle_symbols_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumPath(name): AxumPath<String>,
+        AxumQuery(params): AxumQuery<SymbolParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        log::info!("Symbol request: name={}", name);
+
+        tool_response("search_code", serde_json::json!({
+            "pattern": name,
+            "symbols": true,
+            "exact": params.exact.unwrap_or(true),
+            "lang": params.lang,
+            "kind": params.kind,
+            "limit": params.limit,
+            "offset": params.offset,
+            "glob": params.glob,
+            "exclude": params.exclude,
+            "with_docs": params.with_docs,
+            "dir": project_dir(&state, params.dir.as_deref())?,
+        })).await
+    }
+
+    // GET /ast endpoint
+    async fn handle_ast_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<AstParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        log::info!("AST request: pattern={}", params.q);
+
+        tool_response("search_ast", serde_json::json!({
+            "pattern": params.q,
+            "lang": params.lang,
+            "file": params.file,
+            "glob": params.glob,
+            "exclude": params.exclude,
+            "limit": params.limit,
+            "offset": params.offset,
+            "paths": params.paths,
+            "force": params.force,
+            "dir": project_dir(&state, params.dir.as_deref())?,
+        })).await
+    }
+
+    // GET /outline endpoint
+    async fn handle_outline_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<FileParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        log::info!("Outline request: file={}", params.file);
+
+        // Only indexed files are outlined: the path never reaches the filesystem directly
+        let cache = CacheManager::new(project_dir(&state, params.dir.as_deref())?);
+        match crate::locate::indexed_outline(&cache, &params.file) {
+            Ok(symbols) => Ok(Json(serde_json::json!({
+                "path": params.file,
+                "symbols": symbols,
+            }))),
+            Err(e) if e.downcast_ref::<ReflexError>().is_some() => Err(api_error(&e)),
+            Err(e) => Err(reject(ReflexError::InvalidArgument(e.to_string()))),
+        }
+    }
+
+    // GET /deps endpoint
+    async fn handle_deps_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<FileParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        log::info!("Deps request: file={}, reverse={}", params.file, params.reverse);
+
+        let tool = match (params.reverse, params.depth) {
+            (true, Some(depth)) if depth > 1 => "get_transitive_dependents",
+            (true, _) => "get_dependents",
+            (false, Some(depth)) if depth > 1 => "get_transitive_deps",
+            (false, _) => "get_dependencies",
+        };
+
+        tool_response(tool, serde_json::json!({
+            "path": params.file,
+            "depth": params.depth,
+            "dir": project_dir(&state, params.dir.as_deref())?,
+        })).await
+    }
+
+    // GET /analyze endpoint
+    async fn handle_analyze_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<AnalyzeParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        let analysis = params.analysis.as_deref().unwrap_or("summary");
+        log::info!("Analyze request: type={}", analysis);
+
+        let tool = match analysis {
+            "summary" => "analyze_summary",
+            "circular" => "find_circular",
+            "hotspots" => "find_hotspots",
+            "unused" => "find_unused",
+            "dead_symbols" => "find_dead_symbols",
+            "islands" => "find_islands",
+            other => {
+                return Err(reject(ReflexError::InvalidArgument(
+                    format!("Unknown analysis type '{}'. Supported: summary, circular, hotspots, unused, dead_symbols, islands", other)
+                )));
+            }
+        };
+
+        tool_response(tool, serde_json::json!({
+            "limit": params.limit,
+            "offset": params.offset,
+            "sort": params.sort,
+            "min_dependents": params.min_dependents,
+            "min_island_size": params.min_island_size,
+            "max_island_size": params.max_island_size,
+            "min_confidence": params.min_confidence,
+            "dir": project_dir(&state, params.dir.as_deref())?,
+        })).await
+    }
+
+    // GET /context endpoint
+    async fn handle_context_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<ContextParams>,
+    ) -> Result<Json<serde_json::Value>, ApiError> {
+        log::info!("Context request");
+
+        tool_response("gather_context", serde_json::json!({
+            "structure": params.structure,
+            "file_types": params.file_types,
+            "project_type": params.project_type,
+            "framework": params.framework,
+            "entry_points": params.entry_points,
+            "test_layout": params.test_layout,
+            "config_files": params.config_files,
+            "conventions": params.conventions,
+            "depth": params.depth,
+            "path": params.path,
+            "dir": project_dir(&state, params.dir.as_deref())?,
+        })).await
+    }
+
+    // GET /events endpoint: index and file change notifications, pushed over a
+    // WebSocket when the client asks to upgrade, otherwise as server-sent events
+    async fn handle_events(State(state): State<Arc<AppState>>, ws: Option<WebSocketUpgrade>) -> Response {
+        if let Some(ws) = ws {
+            let events = state.events.subscribe();
+            return ws.on_upgrade(move |socket| push_websocket_events(socket, events));
+        }
+
+        let mut events = state.events.subscribe();
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<Event, std::convert::Infallible>>(32);
+
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => Event::default()
+                        .event(event.name())
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().event(event.name())),
+                    // Slow client: tell it to resync instead of silently dropping events
+                    Err(broadcast::error::RecvError::Lagged(missed)) => Event::default()
+                        .event("lagged")
+                        .data(format!("{{\"type\":\"lagged\",\"missed\":{}}}", missed)),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                // A send error means the client disconnected
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Sse::new(tokio_stream::wrappers::ReceiverStream::new(rx)).keep_alive(KeepAlive::default()).into_response()
+    }
+
+    // Forward broadcast events to a WebSocket client as JSON text messages until either side closes
+    async fn push_websocket_events(mut socket: WebSocket, mut events: broadcast::Receiver<WatchEvent>) {
+        // Ping idle clients so proxies keep the connection open (like SSE keep-alive)
+        let mut keep_alive = tokio::time::interval(std::time::Duration::from_secs(30));
+        keep_alive.tick().await;
+
+        loop {
+            let message = tokio::select! {
+                // Client messages are ignored (pings are answered by the socket itself)
+                incoming = socket.recv() => match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                },
+                event = events.recv() => match event {
+                    Ok(event) => match serde_json::to_string(&event) {
+                        Ok(json) => Message::Text(json),
+                        Err(_) => continue,
+                    },
+                    // Slow client: tell it to resync instead of silently dropping events
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        Message::Text(format!("{{\"type\":\"lagged\",\"missed\":{}}}", missed))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => Message::Close(None),
+                },
+                _ = keep_alive.tick() => Message::Ping(Vec::new()),
+            };
+
+            let closing = matches!(message, Message::Close(_));
+            if let Err(e) = socket.send(message).await {
+                log::debug!("WebSocket /events client disconnected: {}", e);
+                break;
+            }
+            if closing {
+                break;
+            }
+        }
+    }
+
+    // GET /openapi.json endpoint
+    async fn handle_openapi() -> Json<serde_json::Value> {
+        Json(openapi_spec())
+    }
+
+    // GET /query endpoint
+    async fn handle_query_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<QueryParams>,
+    ) -> Result<Response, ApiError> {
+        log::info!("Query request: pattern={}", params.q);
+
+        let ndjson = match params.format.as_deref() {
+            None | Some("json") => false,
+            Some("ndjson") => true,
+            Some(other) => {
+                return Err(reject(ReflexError::InvalidArgument(
+                    format!("Unknown format '{}'. Supported formats: json, ndjson", other)
+                )));
+            }
+        };
+
+        let dir = project_dir(&state, params.dir.as_deref())?;
+        let cache = CacheManager::new(&dir);
+
+        // Search the segment of a historical revision instead of the working tree
+        let cache = match params.rev.as_deref() {
+            Some(rev) => crate::revision::index_config(&cache)
+                .and_then(|config| crate::revision::open(&dir, &cache, rev, config))
+                .map_err(|e| api_error(&e))?,
+            None => cache,
+        };
+        let engine = QueryEngine::new(cache);
+
+        // Parse language filter
+        let language = if let Some(lang_str) = params.lang.as_deref() {
+            match Language::from_name(lang_str) {
+                Some(language) => Some(language),
+                None => {
+                    return Err(reject(ReflexError::InvalidArgument(
+                        format!("Unknown language '{}'. Supported languages: rust (rs), python (py), javascript (js), typescript (ts), vue, svelte, go, java, php, c, cpp (c++), csharp (cs), ruby (rb), kotlin (kt), zig, shell (sh, bash, zsh), make, cmake, bazel (starlark, bzl), sql, graphql (gql), html", lang_str)
+                    )));
+                }
+            }
+        } else {
+            None
+        };
+
+        // Parse symbol kinds (comma-separated lists)
+        let kinds = params.kind.as_deref().map(crate::models::SymbolKind::parse_list).unwrap_or_default();
+        let exclude_kinds = params.kind_not.as_deref().map(crate::models::SymbolKind::parse_list).unwrap_or_default();
+
+        let span_policy = match params.span.as_deref().map(str::parse::<SpanPolicy>).transpose() {
+            Ok(policy) => policy.unwrap_or_default(),
+            Err(e) => return Err(reject(ReflexError::InvalidArgument(e.to_string()))),
+        };
+        let visibility = match params.visibility.as_deref().map(str::parse::<Visibility>).transpose() {
+            Ok(visibility) => visibility,
+            Err(e) => return Err(reject(ReflexError::InvalidArgument(e.to_string()))),
+        };
+
+        // Smart behavior: --kind implies --symbols
+        let symbols_mode = params.symbols || !kinds.is_empty() || !exclude_kinds.is_empty() || params.qualified.is_some() || visibility.is_some();
+
+        // Size-tuned defaults recorded by `rfx index`
+        let defaults = crate::tuning::search_defaults(&CacheManager::new(&dir));
+
+        // Smart limit handling (same as CLI and MCP)
+        let final_limit = if params.paths && params.limit.is_none() {
+            None  // --paths without explicit limit means no limit
+        } else if let Some(user_limit) = params.limit {
+            Some(user_limit)  // Use user-specified limit
+        } else {
+            defaults.limit  // Default: tuned to repo size for token efficiency
+        };
+
+        let filter = QueryFilter {
+            language,
+            kinds,
+            exclude_kinds,
+            use_ast: false,
+            use_regex: params.regex,
+            limit: final_limit,
+            symbols_mode,
+            expand: params.expand,
+            file_pattern: params.file,
+            exact: params.exact,
+            use_contains: params.contains,
+            timeout_secs: params.timeout.unwrap_or(defaults.timeout_secs),
+            budget_ms: params.budget_ms,
+            changed_since: params.changed_since,
+            scope: params.scope,
+            qualified: params.qualified,
+            visibility,
+            owner: params.owner,
+            table: params.table,
+            glob_patterns: params.glob,
+            exclude_patterns: params.exclude,
+            paths_only: params.paths,
+            offset: params.offset,
+            force: params.force,
+            suppress_output: true,  // HTTP API always returns JSON, suppress warnings
+            include_dependencies: params.dependencies,
+            related_files: params.related,
+            with_owners: params.with_owners,
+            blame: params.blame,
+            semantic: params.semantic,
+            include_deleted: params.include_deleted,
+            with_docs: params.with_docs,
+            span_policy,
+            ..Default::default()
+        };
+
+        let tuned_limit_in_effect = params.limit.is_none() && !params.paths
+            && defaults.limit.is_some_and(|l| l < crate::tuning::DEFAULT_LIMIT);
+
+        if ndjson {
+            return Ok(stream_ndjson_response(engine, params.q, filter, tuned_limit_in_effect));
+        }
+
+        match engine.search_with_metadata(&params.q, filter) {
+            Ok(mut response) => {
+                response.warnings = query_behavior_warnings(tuned_limit_in_effect, response.pagination.has_more);
+                Ok(Json(response).into_response())
+            }
+            Err(e) => {
+                log::error!("Query error: {}", e);
+                Err(api_error(&e.context("Query failed")))
+            }
+        }
+    }
+
+    // Stream query records as NDJSON while the blocking search runs on its own thread.
+    // Errors after the response has started are sent in-band as an error record.
+    fn stream_ndjson_response(engine: QueryEngine, pattern: String, filter: QueryFilter, tuned_limit_in_effect: bool) -> Response {
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::io::Error>>(32);
+
+        tokio::task::spawn_blocking(move || {
+            let result = engine.search_streaming(&pattern, filter, |mut record| {
+                if let crate::query::QueryStreamRecord::Summary { pagination, warnings, .. } = &mut record {
+                    *warnings = query_behavior_warnings(tuned_limit_in_effect, pagination.has_more);
+                }
+                let mut line = serde_json::to_string(&record)?;
+                line.push('\n');
+                // A send error means the client disconnected: stop searching
+                tx.blocking_send(Ok(line))
+                    .map_err(|_| anyhow::anyhow!("client disconnected"))
+            });
+
+            if let Err(e) = result {
+                log::error!("Query error: {}", e);
+                let error_record = serde_json::json!({
+                    "type": "error",
+                    "error": e.to_string(),
+                    "code": crate::error_code::error_code(&e),
+                    "query_too_broad": e.to_string().contains("Query too broad")
+                });
+                let _ = tx.blocking_send(Ok(format!("{}\n", error_record)));
+            }
+        });
+
+        (
+            [(header::CONTENT_TYPE, "application/x-ndjson")],
+            Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+        ).into_response()
+    }
+
+    // GET /stats endpoint
+    async fn handle_stats_endpoint(
+        State(state): State<Arc<AppState>>,
+        AxumQuery(params): AxumQuery<StatsParams>,
+    ) -> Result<Json<crate::models::IndexStats>, ApiError> {
+        log::info!("Stats request");
+
+        let cache = CacheManager::new(project_dir(&state, params.dir.as_deref())?);
+
+        if !cache.exists() {
+            return Err(reject(ReflexError::IndexMissing("No index found. Run 'rfx index' first.".to_string())));
+        }
+
+        match cache.stats() {
+            Ok(stats) => Ok(Json(stats)),
+            Err(e) => {
+                log::error!("Stats error: {}", e);
+                Err(api_error(&e.context("Failed to get stats")))
+            }
+        }
+    }
+
+    // POST /index endpoint
+    async fn handle_index_endpoint(
+        State(state): State<Arc<AppState>>,
+        Json(req): Json<IndexRequest>,
+    ) -> Result<Json<crate::models::IndexStats>, ApiError> {
+        log::info!("Index request: force={}, languages={:?}", req.force, req.languages);
+
+        let dir = project_dir(&state, req.dir.as_deref())?;
+        let cache = CacheManager::new(&dir);
+
+        if req.force {
+            log::info!("Force rebuild requested, clearing existing cache");
+            if let Err(e) = cache.clear() {
+                return Err(api_error(&e.context("Failed to clear cache")));
+            }
+        }
+
+        // Parse language filters
+        let lang_filters: Vec<Language> = req.languages
+            .iter()
+            .filter_map(|s| {
+                let language = Language::from_name(s);
+                if language.is_none() {
+                    log::warn!("Unknown language: {}", s);
+                }
+                language
+            })
+            .collect();
+
+        let config = IndexConfig {
+            languages: lang_filters,
+            ..Default::default()
+        };
+
+        let indexer = Indexer::new(cache, config);
+        let start = std::time::Instant::now();
+
+        match indexer.index(&dir, false) {
+            Ok(stats) => {
+                // No subscribers is not an error
+                let _ = state.events.send(WatchEvent::IndexUpdated {
+                    files: stats.total_files,
+                    changed_files: stats.total_files,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                });
+                Ok(Json(stats))
+            }
+            Err(e) => {
+                log::error!("Index error: {}", e);
+                Err(api_error(&e.context("Indexing failed")))
+            }
+        }
+    }
+
+    // Health check endpoint
+    async fn handle_health() -> impl IntoResponse {
+        (StatusCode::OK, "Reflex is running")
+    }
+
+    // Rate limiting and token auth, applied before every endpoint
+    async fn guard_request(
+        State(state): State<Arc<AppState>>,
+        ConnectInfo(client): ConnectInfo<SocketAddr>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if let Some(Err(retry_after)) = state.rate_limiter.as_ref().map(|l| l.check(client.ip())) {
+            log::warn!("Rate limit exceeded for {}", client.ip());
+            let retry_secs = retry_after.as_secs().max(1).to_string();
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_secs)],
+                "Rate limit exceeded",
+            ).into_response();
+        }
+
+        if request.uri().path() == "/health" {
+            return next.run(request).await;
+        }
+
+        match &state.auth_token {
+            Some(token) => {
+                let header_value = request.headers()
+                    .get(header::AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok());
+                if !authorized(header_value, token) {
+                    log::warn!("Rejected unauthenticated request from {}", client.ip());
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        [(header::WWW_AUTHENTICATE, "Bearer".to_string())],
+                        "Missing or invalid bearer token",
+                    ).into_response();
+                }
+            }
+            None => {
+                if request.method() == Method::POST && !client.ip().is_loopback() {
+                    return (
+                        StatusCode::FORBIDDEN,
+                        "POST requests are only accepted from localhost unless the server runs with --auth-token",
+                    ).into_response();
+                }
+            }
+        }
+
+        next.run(request).await
+    }
+
+    let (events, _) = broadcast::channel(64);
+
+    // Reindex on file changes in the background and forward watcher events to /events
+    if let Some(debounce_ms) = watch_debounce_ms {
+        let events = events.clone();
+        std::thread::spawn(move || {
+            let path = std::path::PathBuf::from(".");
+            let cache = CacheManager::new(&path);
+            if !cache.exists() {
+                log::info!("No index found, running initial index before watching");
+                if let Err(e) = Indexer::new(cache, IndexConfig::default()).index(&path, false) {
+                    log::error!("Initial index failed: {}", e);
+                }
+            }
+
+            let indexer = Indexer::new(CacheManager::new(&path), IndexConfig::default());
+            let config = crate::watcher::WatchConfig { debounce_ms, quiet: true };
+            let result = crate::watcher::watch_with_events(&path, indexer, config, |event| {
+                let _ = events.send(event);
+            });
+            if let Err(e) = result {
+                log::error!("Watcher stopped: {}", e);
+            }
+        });
+    }
+
+    // Create shared state
+    let state = Arc::new(AppState {
+        cache_path: ".".to_string(),
+        auth_token,
+        rate_limiter: rate_limit.filter(|n| *n > 0).map(|n| Arc::new(RateLimiter::new(n))),
+        events,
+    });
+
+    // Configure CORS
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    // Build the router
+    let app = Router::new()
+        .route("/query", get(handle_query_endpoint))
+        .route("/stats", get(handle_stats_endpoint))
+        .route("/index", post(handle_index_endpoint))
+        .route("/symbols/:name", get(handle_symbols_endpoint))
+        .route("/ast", get(handle_ast_endpoint))
+        .route("/outline", get(handle_outline_endpoint))
+        .route("/deps", get(handle_deps_endpoint))
+        .route("/analyze", get(handle_analyze_endpoint))
+        .route("/context", get(handle_context_endpoint))
+        .route("/events", get(handle_events))
+        .route("/openapi.json", get(handle_openapi))
+        .route("/health", get(handle_health))
+        .layer(middleware::from_fn_with_state(state.clone(), guard_request))
+        .layer(cors)
+        .with_state(state);
+
+    // Client addresses are needed for rate limiting and the localhost check
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let addr = format!("{}:{}", host, port);
+
+    if let Some((cert, key)) = tls {
+        let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&cert, &key).await
+            .map_err(|e| anyhow::anyhow!(
+                "Failed to load TLS certificate {} / key {}: {}",
+                cert.display(),
+                key.display(),
+                e
+            ))?;
+        let socket_addr = tokio::net::lookup_host(&addr).await?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve {}", addr))?;
+
+        log::info!("Server listening on https://{}", socket_addr);
+
+        axum_server::bind_rustls(socket_addr, tls_config)
+            .serve(service)
+            .await
+            .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+        return Ok(());
+    }
+
+    // Bind to the specified address
+    let listener = tokio::net::TcpListener::bind(&addr).await
+        .map_err(|e| anyhow::anyhow!("Failed to bind to {}: {}", addr, e))?;
+
+    log::info!("Server listening on {}", addr);
+
+    // Run the server
+    axum::serve(listener, service)
+        .await
+        .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+
+    Ok(())
+}
+
+/// OpenAPI 3 description of the `rfx serve` endpoints (served at `/openapi.json`)
+fn openapi_spec() -> serde_json::Value {
+    use serde_json::{json, Map, Value};
+
+    // (name, type, required, description)
+    type Param = (&'static str, &'static str, bool, &'static str);
+    const DIR: Param = ("dir", "string", false, "Project directory to operate on (default: the server's directory; must be inside it)");
+    const LIMIT: Param = ("limit", "integer", false, "Maximum number of results");
+    const OFFSET: Param = ("offset", "integer", false, "Skip the first N results (pagination)");
+    const LANG: Param = ("lang", "string", false, "Language filter (rust, python, typescript, ...)");
+    const GLOB: Param = ("glob", "string", false, "Include files matching glob pattern");
+    const EXCLUDE: Param = ("exclude", "string", false, "Exclude files matching glob pattern");
+
+    let endpoints: &[(&str, &str, &str, &[Param])] = &[
+        ("/query", "get", "Full-text, symbol or regex code search", &[
+            ("q", "string", true, "Search pattern"),
+            LANG,
+            ("kind", "string", false, "Symbol kind filter (implies symbols=true)"),
+            LIMIT, OFFSET,
+            ("symbols", "boolean", false, "Only return symbol definitions"),
+            ("regex", "boolean", false, "Treat the pattern as a regex"),
+            ("exact", "boolean", false, "Exact symbol name match"),
+            ("contains", "boolean", false, "Substring instead of word-boundary matching"),
+            ("expand", "boolean", false, "Return full symbol bodies"),
+            ("file", "string", false, "File path substring filter"),
+            ("timeout", "integer", false, "Query timeout in seconds"),
+            ("budget_ms", "integer", false, "Return partial results after this many milliseconds"),
+            ("changed_since", "string", false, "Only search files changed since this git ref"),
+            ("rev", "string", false, "Search this git revision (branch, tag or commit) instead of the working tree; indexed on first use"),
+            ("scope", "string", false, "Only keep matches inside this symbol (Symbol, Outer.inner or path:Symbol)"),
+            ("qualified", "string", false, "Only keep symbols whose qualified name ends with this path (Worker::run or Worker.run); implies symbols=true"),
+            ("visibility", "string", false, "Only keep exported (public) or local (private) symbols; implies symbols=true"),
+            ("owner", "string", false, "Only search files owned by this CODEOWNERS team or user (@team-payments)"),
+            ("table", "string", false, "Only search files touching this database table or ORM model (users or User)"),
+            GLOB, EXCLUDE,
+            ("paths", "boolean", false, "Only return matching file paths"),
+            ("force", "boolean", false, "Bypass broad query detection"),
+            ("dependencies", "boolean", false, "Include file dependencies"),
+            ("related", "integer", false, "Attach up to N tests, counterparts, importers and imports per result file"),
+            ("with_owners", "boolean", false, "Attach each result file's CODEOWNERS owners"),
+            ("blame", "boolean", false, "Attach the last commit, author and age of each match's lines"),
+            ("semantic", "boolean", false, "Treat q as a natural-language description and rank embedded chunks by meaning (requires rfx embed)"),
+            ("include_deleted", "boolean", false, "Also report tombstones of removed files matching the pattern"),
+            ("with_docs", "boolean", false, "Include doc comments of symbols"),
+            ("span", "string", false, "Span policy: body, with-attrs, with-docs"),
+            ("format", "string", false, "Response format: json (default) or ndjson"),
+            DIR,
+        ]),
+        ("/symbols/{name}", "get", "Look up symbol definitions by name", &[
+            ("name", "string", true, "Symbol name (path parameter)"),
+            LANG,
+            ("kind", "string", false, "Symbol kind filter"),
+            ("exact", "boolean", false, "Exact name match (default: true)"),
+            LIMIT, OFFSET, GLOB, EXCLUDE,
+            ("with_docs", "boolean", false, "Include doc comments"),
+            DIR,
+        ]),
+        ("/ast", "get", "Structural search with a tree-sitter S-expression", &[
+            ("q", "string", true, "Tree-sitter query pattern"),
+            ("lang", "string", true, "Language of the pattern"),
+            ("file", "string", false, "File path substring filter"),
+            GLOB, EXCLUDE, LIMIT, OFFSET,
+            ("paths", "boolean", false, "Only return matching file paths"),
+            ("force", "boolean", false, "Bypass broad query detection"),
+            DIR,
+        ]),
+        ("/outline", "get", "Symbols of one file in source order", &[
+            ("file", "string", true, "File path relative to the project directory"),
+            DIR,
+        ]),
+        ("/deps", "get", "Dependencies or dependents of a file", &[
+            ("file", "string", true, "File path (fuzzy suffix match)"),
+            ("reverse", "boolean", false, "Return files that import this file"),
+            ("depth", "integer", false, "Transitive dependency depth (default: 1)"),
+            DIR,
+        ]),
+        ("/analyze", "get", "Dependency graph analysis", &[
+            ("type", "string", false, "summary (default), circular, hotspots, unused, dead_symbols or islands"),
+            LIMIT, OFFSET,
+            ("sort", "string", false, "asc or desc"),
+            ("min_dependents", "integer", false, "Minimum dependents for hotspots (default: 2)"),
+            ("min_island_size", "integer", false, "Minimum island size (default: 2)"),
+            ("max_island_size", "integer", false, "Maximum island size"),
+            ("min_confidence", "string", false, "Minimum dead_symbols confidence: low (default), medium or high"),
+            DIR,
+        ]),
+        ("/context", "get", "Codebase context for AI prompts (all sections when none are selected)", &[
+            ("structure", "boolean", false, "Directory structure"),
+            ("file_types", "boolean", false, "File type distribution"),
+            ("project_type", "boolean", false, "Project type detection"),
+            ("framework", "boolean", false, "Framework detection"),
+            ("entry_points", "boolean", false, "Entry point files"),
+            ("test_layout", "boolean", false, "Test organization"),
+            ("config_files", "boolean", false, "Configuration files"),
+            ("conventions", "boolean", false, "Naming, test framework, lint/format and error-handling conventions"),
+            ("depth", "integer", false, "Tree depth for structure (default: 2)"),
+            ("path", "string", false, "Focus on a directory"),
+            DIR,
+        ]),
+        ("/stats", "get", "Index statistics", &[DIR]),
+        ("/index", "post", "Build or update the index (JSON body: force, languages, dir)", &[]),
+        ("/events", "get", "Index change events over a WebSocket (Upgrade: websocket) or server-sent events: file_changed, stale, index_updated, index_failed (watcher events need --watch)", &[]),
+        ("/health", "get", "Health check", &[]),
+        ("/openapi.json", "get", "This OpenAPI description", &[]),
+    ];
+
+    let mut paths = Map::new();
+    for (path, method, summary, params) in endpoints {
+        let parameters: Vec<Value> = params
+            .iter()
+            .map(|(name, ty, required, description)| {
+                let location = if path.contains(&format!("{{{}}}", name)) { "path" } else { "query" };
+                json!({
+                    "name": name,
+                    "in": location,
+                    "required": required,
+                    "description": description,
+                    "schema": { "type": ty },
+                })
+            })
+            .collect();
+
+        let mut operation = json!({
+            "summary": summary,
+            "parameters": parameters,
+            "responses": {
+                "200": { "description": "Success (JSON)" },
+                "400": { "description": "Invalid parameters" },
+                "401": { "description": "Missing or invalid bearer token (--auth-token)" },
+                "404": { "description": "Index or file not found" },
+                "429": { "description": "Rate limit exceeded (--rate-limit)" },
+                "500": { "description": "Internal error" },
+            },
+        });
+        if *path == "/health" {
+            // Exempt from token auth for monitoring
+            operation["security"] = json!([]);
+        }
+        if *method == "post" {
+            operation["requestBody"] = json!({
+                "content": { "application/json": { "schema": { "type": "object" } } }
+            });
+        }
+
+        if let Some(operations) = paths.entry(path.to_string()).or_insert_with(|| json!({})).as_object_mut() {
+            operations.insert(method.to_string(), operation);
+        }
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Reflex HTTP API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+            },
+        },
+        // Only enforced when the server runs with --auth-token
+        "security": [{ "bearerAuth": [] }],
+    })
+}
+
+/// Handle the `stats` subcommand
+fn handle_stats(by_dir: bool, depth: usize, as_json: bool, pretty_json: bool) -> Result<()> {
+    log::info!("Showing index statistics");
+
+    let cache = CacheManager::new(".");
+
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             This will scan all files in the current directory and create a .reflex/ cache.\n\
+             \n\
+             Example:\n\
+             $ rfx index          # Index current directory\n\
+             $ rfx stats          # Show index statistics".to_string()
+        ).into());
+    }
+
+    let mut stats = cache.stats()?;
+    if by_dir {
+        stats.directories = cache.directory_stats(depth)?;
+    }
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&stats)?
+        } else {
+            serde_json::to_string(&stats)?
+        };
+        println!("{}", json_output);
+    } else {
+        println!("Reflex Index Statistics");
+        println!("=======================");
+
+        // Show git branch info if in git repo, or (None) if not
+        let root = std::env::current_dir()?;
+        if crate::git::is_git_repo(&root) {
+            match crate::git::get_git_state(&root) {
+                Ok(git_state) => {
+                    let dirty_indicator = if git_state.dirty { " (uncommitted changes)" } else { " (clean)" };
+                    println!("Branch:         {}@{}{}",
+                             git_state.branch,
+                             &git_state.commit[..7],
+                             dirty_indicator);
+
+                    // Check if current branch is indexed
+                    match cache.get_branch_info(&git_state.branch) {
+                        Ok(branch_info) => {
+                            if branch_info.commit_sha != git_state.commit {
+                                println!("                ⚠️  Index commit mismatch (indexed: {})",
+                                         &branch_info.commit_sha[..7]);
+                            }
+                            if git_state.dirty && !branch_info.is_dirty {
+                                println!("                ⚠️  Uncommitted changes not indexed");
+                            }
+                        }
+                        Err(_) => {
+                            println!("                ⚠️  Branch not indexed");
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to get git state: {}", e);
+                }
+            }
+        } else {
+            // Not a git repository - show (None)
+            println!("Branch:         (None)");
+        }
+
+        println!("Files indexed:  {}", stats.total_files);
+        println!("Index size:     {} bytes", stats.index_size_bytes);
+        println!("Last updated:   {}", stats.last_updated);
+
+        // Display language breakdown if we have indexed files
+        if !stats.files_by_language.is_empty() {
+            println!("\nFiles by language:");
+
+            // Sort languages by count (descending) for consistent output
+            let mut lang_vec: Vec<_> = stats.files_by_language.iter().collect();
+            lang_vec.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+            // Calculate column widths
+            let max_lang_len = lang_vec.iter().map(|(lang, _)| lang.len()).max().unwrap_or(8);
+            let lang_width = max_lang_len.max(8); // At least "Language" header width
+
+            // Print table header
+            println!("  {:<width$}  Files    Lines  Symbols", "Language", width = lang_width);
+            println!("  {}  -----  -------  -------", "-".repeat(lang_width));
+
+            // Print rows
+            for (language, file_count) in lang_vec {
+                let line_count = stats.lines_by_language.get(language).copied().unwrap_or(0);
+                let symbol_count = stats.symbols_by_language.get(language).copied().unwrap_or(0);
+                println!("  {:<width$}  {:5}  {:7}  {:7}",
+                    language, file_count, line_count, symbol_count,
+                    width = lang_width);
+            }
+            if stats.symbols_by_language.is_empty() {
+                println!("  (no symbols cached yet; they are parsed on first symbol search or by background indexing)");
+            }
+        }
+
+        let sizes = &stats.component_sizes;
+        println!("\nIndex components:");
+        println!("  Trigrams:   {:>10}", format_bytes(sizes.trigrams));
+        println!("  Content:    {:>10}", format_bytes(sizes.content));
+        println!("  Tokens:     {:>10}", format_bytes(sizes.tokens));
+        println!("  Symbols:    {:>10}", format_bytes(sizes.symbols));
+        println!("  Metadata:   {:>10}  (includes symbols)", format_bytes(sizes.metadata));
+
+        if !stats.directories.is_empty() {
+            println!("\nFiles by directory:");
+
+            let dir_width = stats.directories.iter().map(|d| d.directory.len()).max().unwrap_or(9).max(9);
+            println!("  {:<width$}  Files    Lines  Symbols", "Directory", width = dir_width);
+            println!("  {}  -----  -------  -------", "-".repeat(dir_width));
+            for dir in &stats.directories {
+                println!("  {:<width$}  {:5}  {:7}  {:7}",
+                    dir.directory, dir.files, dir.lines, dir.symbols,
+                    width = dir_width);
+            }
+        }
+
+        if !stats.degraded_languages.is_empty() {
+            println!("\nDegraded languages (regex fallback parser, symbols may be incomplete):");
+            for degraded in &stats.degraded_languages {
+                println!("  {}: {}", degraded.language, degraded.error);
+            }
+        }
 
-/// Handle the `query` subcommand
-fn handle_query(
-    pattern: String,
-    symbols_flag: bool,
+        let skipped = stats.warning.as_ref().and_then(|w| w.details.as_ref()).map(|d| &d.skipped_files);
+        if let Some(skipped) = skipped.filter(|s| !s.is_empty()) {
+            println!("\nSkipped files ({}, over the index limits):", skipped.len());
+            for file in skipped.iter().take(SKIPPED_FILES_SHOWN) {
+                println!("  {} [{}] {}", file.path, file.reason.as_str(), file.detail);
+            }
+            if skipped.len() > SKIPPED_FILES_SHOWN {
+                println!("  ... {} more (see 'rfx stats --json')", skipped.len() - SKIPPED_FILES_SHOWN);
+            }
+            println!("  Adjust max_file_size, max_line_length or binary_detection under [index] in .reflex/config.toml");
+        }
+    }
+
+    Ok(())
+}
+
+/// Skipped files listed by `rfx stats` before pointing at the JSON output
+const SKIPPED_FILES_SHOWN: usize = 20;
+
+/// Handle the `doctor` subcommand
+fn handle_doctor(repair: bool, as_json: bool, pretty_json: bool) -> Result<()> {
+    log::info!("Running index diagnostics");
+
+    let cache = CacheManager::new(".");
+
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index          # Index current directory\n\
+             $ rfx doctor         # Check index health".to_string()
+        ).into());
+    }
+
+    let mut integrity = crate::integrity::check(&cache);
+    let mut repairs = Vec::new();
+    if repair && !integrity.is_healthy() {
+        let mut config = IndexConfig::default();
+        crate::indexer::load_performance_config(cache.path())?.apply(&mut config);
+        crate::indexer::load_index_limits_config(cache.path())?.apply(&mut config);
+        repairs = crate::integrity::repair(&cache, &integrity, config)?;
+        integrity = crate::integrity::check(&cache);
+    }
+
+    let quarantine = crate::quarantine::Quarantine::open(cache.path())?;
+    let quarantined = quarantine.list()?;
+    let degraded = crate::models::DegradedLanguage::detect();
+
+    if as_json {
+        let report = serde_json::json!({
+            "integrity": integrity,
+            "repairs": repairs,
+            "quarantined_files": quarantined,
+            "degraded_grammars": degraded,
+        });
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    println!("Reflex Doctor");
+    println!("=============");
+
+    for repair in &repairs {
+        println!("Repaired: {}", repair);
+    }
+    if integrity.is_healthy() {
+        println!("Index integrity: ok ({} files verified)", integrity.files_checked);
+    } else {
+        let total = integrity.issues.len() + integrity.omitted_issues;
+        println!("Index integrity: {} issue(s)", total);
+        for issue in &integrity.issues {
+            let location = issue.path.as_deref().map(|p| format!(" {}", p)).unwrap_or_default();
+            println!("  [{}] {}{}: {}", issue.component.file_name(), issue.kind, location, issue.message);
+        }
+        if integrity.omitted_issues > 0 {
+            println!("  ... {} more", integrity.omitted_issues);
+        }
+        if !repair {
+            println!("  Run 'rfx doctor --repair' to rebuild the broken components.");
+        }
+    }
+
+    if degraded.is_empty() {
+        println!("Grammars: all tree-sitter grammars loaded");
+    } else {
+        println!("Grammars: {} failed to load, using the regex fallback parser", degraded.len());
+        for language in &degraded {
+            println!("  {}: {}", language.language, language.error);
+        }
+        println!("  Symbol results for these languages may be incomplete; reinstall or rebuild rfx for this platform.");
+    }
+
+    if quarantined.is_empty() {
+        println!("Parser quarantine: no files");
+        return Ok(());
+    }
+
+    println!("Parser quarantine: {} file(s) skipped after crashing a parser", quarantined.len());
+    for file in &quarantined {
+        let when = chrono::DateTime::from_timestamp(file.quarantined_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        println!("  {} ({}, {})", file.path, file.language, when);
+        println!("    {}", file.reason);
+    }
+    println!();
+    println!("Quarantined files are retried automatically once their content changes.");
+    println!("Please report parser crashes with the file contents if possible.");
+
+    Ok(())
+}
+
+/// Handle the `clear` subcommand
+fn handle_clear(skip_confirm: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+
+    if !cache.exists() {
+        println!("No cache to clear.");
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        println!("This will delete the local Reflex cache at: {:?}", cache.path());
+        print!("Are you sure? [y/N] ");
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Cancelled.");
+            return Ok(());
+        }
+    }
+
+    cache.clear()?;
+    println!("Cache cleared successfully.");
+
+    Ok(())
+}
+
+/// Handle the `list-files` subcommand
+fn handle_list_files(as_json: bool, pretty_json: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             This will scan all files in the current directory and create a .reflex/ cache.\n\
+             \n\
+             Example:\n\
+             $ rfx index            # Index current directory\n\
+             $ rfx list-files       # List indexed files".to_string()
+        ).into());
+    }
+
+    let files = cache.list_files()?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&files)?
+        } else {
+            serde_json::to_string(&files)?
+        };
+        println!("{}", json_output);
+    } else if files.is_empty() {
+        println!("No files indexed yet.");
+    } else {
+        println!("Indexed Files ({} total):", files.len());
+        println!();
+        for file in files {
+            match &file.canonical_path {
+                Some(canonical) => println!("  {} ({}) -> {}", file.path, file.language, canonical),
+                None => println!("  {} ({})", file.path, file.language),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_dump_symbols(
     lang: Option<String>,
-    kind_str: Option<String>,
-    use_ast: bool,
-    use_regex: bool,
-    as_json: bool,
-    pretty_json: bool,
-    ai_mode: bool,
+    kind: Option<String>,
+    out: Option<PathBuf>,
     limit: Option<usize>,
-    offset: Option<usize>,
-    expand: bool,
-    file_pattern: Option<String>,
-    exact: bool,
-    use_contains: bool,
-    count_only: bool,
-    timeout_secs: u64,
-    plain: bool,
-    glob_patterns: Vec<String>,
-    exclude_patterns: Vec<String>,
-    paths_only: bool,
-    no_truncate: bool,
-    all: bool,
-    force: bool,
-    include_dependencies: bool,
+    after: Option<String>,
+    resume: bool,
 ) -> Result<()> {
-    log::info!("Starting query command");
+    use crate::symbol_dump::{dump_symbols, resume_point, DumpOptions};
 
-    // AI mode implies JSON output
-    let as_json = as_json || ai_mode;
+    let cache = CacheManager::new(".");
+
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index                               # Index current directory\n\
+             $ rfx dump-symbols --out symbols.jsonl    # Export all symbols".to_string()
+        ).into());
+    }
+
+    let after = match (&out, resume) {
+        (Some(path), true) => resume_point(path)?,
+        _ => after,
+    };
 
+    let options = DumpOptions {
+        language: parse_language_arg(lang.as_deref())?,
+        kind: kind.as_deref().map(parse_kind_arg),
+        after,
+        limit,
+    };
+
+    let summary = match &out {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resume)
+                .truncate(!resume)
+                .open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            let mut writer = std::io::BufWriter::new(file);
+            dump_symbols(&cache, &options, &mut writer)?
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let mut writer = std::io::BufWriter::new(stdout.lock());
+            dump_symbols(&cache, &options, &mut writer)?
+        }
+    };
+
+    eprintln!(
+        "Exported {} symbols from {} files ({} parsed, not yet cached)",
+        summary.symbols, summary.files, summary.parsed
+    );
+    if let Some(next) = summary.next_after {
+        eprintln!("More files remain. Next page: rfx dump-symbols --after '{}' --limit {}", next, limit.unwrap_or_default());
+    }
+
+    Ok(())
+}
+
+/// Handle the `deleted list` subcommand
+fn handle_deleted_list(path: Option<String>, as_json: bool, pretty_json: bool) -> Result<()> {
     let cache = CacheManager::new(".");
-    let engine = QueryEngine::new(cache);
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.".to_string()
+        ).into());
+    }
 
-    // Parse and validate language filter
-    let language = if let Some(lang_str) = lang.as_deref() {
-        match lang_str.to_lowercase().as_str() {
-            "rust" | "rs" => Some(Language::Rust),
-            "python" | "py" => Some(Language::Python),
-            "javascript" | "js" => Some(Language::JavaScript),
-            "typescript" | "ts" => Some(Language::TypeScript),
-            "vue" => Some(Language::Vue),
-            "svelte" => Some(Language::Svelte),
-            "go" => Some(Language::Go),
-            "java" => Some(Language::Java),
-            "php" => Some(Language::PHP),
-            "c" => Some(Language::C),
-            "cpp" | "c++" => Some(Language::Cpp),
-            "csharp" | "cs" | "c#" => Some(Language::CSharp),
-            "ruby" | "rb" => Some(Language::Ruby),
-            "kotlin" | "kt" => Some(Language::Kotlin),
-            "zig" => Some(Language::Zig),
-            _ => {
-                anyhow::bail!(
-                    "Unknown language: '{}'\n\
-                     \n\
-                     Supported languages:\n\
-                     • rust, rs\n\
-                     • python, py\n\
-                     • javascript, js\n\
-                     • typescript, ts\n\
-                     • vue\n\
-                     • svelte\n\
-                     • go\n\
-                     • java\n\
-                     • php\n\
-                     • c\n\
-                     • c++, cpp\n\
-                     • c#, csharp, cs\n\
-                     • ruby, rb\n\
-                     • kotlin, kt\n\
-                     • zig\n\
-                     \n\
-                     Example: rfx query \"pattern\" --lang rust",
-                    lang_str
-                );
-            }
+    let config = crate::tombstones::load_tombstone_config(cache.path())?;
+    let tombstones = crate::tombstones::TombstoneIndex::new(cache).list(path.as_deref())?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&tombstones)?
+        } else {
+            serde_json::to_string(&tombstones)?
+        };
+        println!("{}", json_output);
+    } else if tombstones.is_empty() {
+        println!("No tombstones found.");
+        if !config.enabled {
+            println!("  Tombstones are disabled. Set [tombstones] enabled = true in .reflex/config.toml;");
+            println!("  files removed by the next compaction ('rfx index compact') will be recorded.");
+        }
+    } else {
+        print_tombstones(&tombstones);
+    }
+
+    Ok(())
+}
+
+/// Open the query history of the index in the current directory
+fn open_query_history() -> Result<crate::history::QueryHistoryLog> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.".to_string()
+        ).into());
+    }
+    Ok(crate::history::QueryHistoryLog::new(cache))
+}
+
+fn handle_history_list(limit: usize, as_json: bool, pretty_json: bool) -> Result<()> {
+    let log = open_query_history()?;
+    let entries = log.list((limit > 0).then_some(limit))?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&entries)?
+        } else {
+            serde_json::to_string(&entries)?
+        };
+        println!("{}", json_output);
+    } else if entries.is_empty() {
+        println!("No queries recorded yet.");
+    } else {
+        for entry in &entries {
+            let when = chrono::DateTime::from_timestamp(entry.executed_at, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            println!(
+                "{:>5}  {}  {:>6} results  {:>5}ms  {}",
+                entry.id.to_string().bold(),
+                when.dimmed(),
+                entry.total_results,
+                entry.timing_ms,
+                entry.command_line()
+            );
         }
+        println!("\nReplay with: rfx history replay <ID>");
+    }
+
+    Ok(())
+}
+
+fn handle_history_replay(id: Option<i64>) -> Result<()> {
+    let log = open_query_history()?;
+    let entry = match id {
+        Some(id) => log.get(id)?,
+        None => log.list(Some(1))?.into_iter().next(),
+    };
+    let Some(entry) = entry else {
+        anyhow::bail!(
+            "No such query in history.\n\
+             \n\
+             Run 'rfx history list' to see recorded queries and their ids."
+        );
+    };
+
+    eprintln!("{}", entry.command_line().dimmed());
+
+    let current_exe = std::env::current_exe()
+        .context("Failed to locate the rfx executable")?;
+    let status = std::process::Command::new(&current_exe)
+        .args(&entry.args)
+        .status()
+        .context("Failed to replay query")?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+fn handle_history_clear() -> Result<()> {
+    let removed = open_query_history()?.clear()?;
+    println!("Cleared {} recorded quer{}.", removed, if removed == 1 { "y" } else { "ies" });
+    Ok(())
+}
+
+fn handle_snapshot_save(output: PathBuf, as_json: bool, pretty_json: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+    let manifest = crate::snapshot::save(&cache, &output)?;
+
+    if as_json {
+        print_snapshot_manifest_json(&manifest, pretty_json)?;
+    } else {
+        let archive_size = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+        println!("Saved snapshot to {}", output.display());
+        print_snapshot_summary(&manifest);
+        println!("  Archive size:  {:.2} MB", archive_size as f64 / 1_048_576.0);
+    }
+
+    Ok(())
+}
+
+fn handle_snapshot_load(archive: PathBuf, force: bool, as_json: bool, pretty_json: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+    let manifest = crate::snapshot::load(&cache, &archive, force)?;
+
+    if as_json {
+        print_snapshot_manifest_json(&manifest, pretty_json)?;
     } else {
-        None
-    };
+        println!("Loaded snapshot from {}", archive.display());
+        print_snapshot_summary(&manifest);
+        println!("\nRun 'rfx index' to pick up changes made since the snapshot.");
+    }
 
-    // Parse symbol kind - try exact match first (case-insensitive), then treat as Unknown
-    let kind = kind_str.as_deref().and_then(|s| {
-        // Try parsing with proper case (PascalCase for SymbolKind)
-        let capitalized = {
-            let mut chars = s.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
-            }
+    Ok(())
+}
+
+fn handle_hooks_install(uninstall: bool, as_json: bool, pretty_json: bool) -> Result<()> {
+    let report = crate::hooks::install(std::path::Path::new("."), uninstall)?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
         };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-        capitalized.parse::<crate::models::SymbolKind>()
-            .ok()
-            .or_else(|| {
-                // If not a known kind, treat as Unknown for flexibility
-                log::debug!("Treating '{}' as unknown symbol kind for filtering", s);
-                Some(crate::models::SymbolKind::Unknown(s.to_string()))
-            })
-    });
+    if let Some(config) = &report.manual_config {
+        println!("lefthook manages the git hooks here. Add this to lefthook.yml, then run 'lefthook install':\n");
+        print!("{}", config);
+        return Ok(());
+    }
+    if report.changes.is_empty() {
+        println!("No Reflex hooks {}", if uninstall { "installed" } else { "to install" });
+        return Ok(());
+    }
 
-    // Smart behavior: --kind implies --symbols
-    let symbols_mode = symbols_flag || kind.is_some();
+    let manager = match report.manager {
+        crate::hooks::HookManager::Husky => " (husky)",
+        _ => "",
+    };
+    println!("{} git hooks{}:", if uninstall { "Uninstalled" } else { "Installed" }, manager);
+    for change in &report.changes {
+        println!("  {:<14} {:<10} {}", change.hook, change.action, change.path.dimmed());
+    }
+    Ok(())
+}
 
-    // Smart limit handling:
-    // 1. If --count is set: no limit (count should always show total)
-    // 2. If --all is set: no limit (None)
-    // 3. If --limit 0 is set: no limit (None) - treat 0 as "unlimited"
-    // 4. If --paths is set and user didn't specify --limit: no limit (None)
-    // 5. If user specified --limit: use that value
-    // 6. Otherwise: use default limit of 100
-    let final_limit = if count_only {
-        None  // --count always shows total count, no pagination
-    } else if all {
-        None  // --all means no limit
-    } else if limit == Some(0) {
-        None  // --limit 0 means no limit (unlimited results)
-    } else if paths_only && limit.is_none() {
-        None  // --paths without explicit --limit means no limit
-    } else if let Some(user_limit) = limit {
-        Some(user_limit)  // Use user-specified limit
+fn print_snapshot_manifest_json(manifest: &crate::snapshot::SnapshotManifest, pretty_json: bool) -> Result<()> {
+    let json_output = if pretty_json {
+        serde_json::to_string_pretty(manifest)?
     } else {
-        Some(100)  // Default: limit to 100 results for token efficiency
+        serde_json::to_string(manifest)?
     };
+    println!("{}", json_output);
+    Ok(())
+}
 
-    // Validate AST query requirements
-    if use_ast && language.is_none() {
-        anyhow::bail!(
-            "AST pattern matching requires a language to be specified.\n\
-             \n\
-             Use --lang to specify the language for tree-sitter parsing.\n\
-             \n\
-             Supported languages for AST queries:\n\
-             • rust, python, go, java, c, c++, c#, php, ruby, kotlin, zig, typescript, javascript\n\
-             \n\
-             Note: Vue and Svelte use line-based parsing and do not support AST queries.\n\
-             \n\
-             WARNING: AST queries are SLOW (500ms-2s+). Use --symbols instead for 95% of cases.\n\
-             \n\
-             Examples:\n\
-             • rfx query \"(function_definition) @fn\" --ast --lang python\n\
-             • rfx query \"(class_declaration) @class\" --ast --lang typescript --glob \"src/**/*.ts\""
-        );
+fn print_snapshot_summary(manifest: &crate::snapshot::SnapshotManifest) {
+    println!("  Reflex:        {}", manifest.reflex_version);
+    if let Some(branch) = &manifest.branch {
+        println!("  Branch:        {}", branch);
     }
+    if let Some(commit) = &manifest.commit {
+        println!("  Commit:        {}", commit);
+    }
+    println!("  Files:         {}", manifest.files.len());
+    println!("  Index size:    {:.2} MB", manifest.total_size() as f64 / 1_048_576.0);
+}
 
-    // VALIDATION: Check for conflicting or problematic flag combinations
-    // Only show warnings/errors in non-JSON mode (avoid breaking parsers)
-    if !as_json {
-        let mut has_errors = false;
-
-        // ERROR: Mutually exclusive pattern matching modes
-        if use_regex && use_contains {
-            eprintln!("{}", "ERROR: Cannot use --regex and --contains together.".red().bold());
-            eprintln!("  {} --regex for pattern matching (alternation, wildcards, etc.)", "•".dimmed());
-            eprintln!("  {} --contains for substring matching (expansive search)", "•".dimmed());
-            eprintln!("\n  {} Choose one based on your needs:", "Tip:".cyan().bold());
-            eprintln!("    {} for OR logic: --regex", "pattern1|pattern2".yellow());
-            eprintln!("    {} for substring: --contains", "partial_text".yellow());
-            has_errors = true;
+/// Print tombstones with their last symbols
+fn print_tombstones(tombstones: &[crate::tombstones::Tombstone]) {
+    println!("Deleted files ({}):", tombstones.len());
+    for tombstone in tombstones {
+        let when = chrono::DateTime::from_timestamp(tombstone.deleted_at, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let hash = tombstone.last_hash.as_deref()
+            .map(|h| format!(", last hash {}", &h[..h.len().min(12)]))
+            .unwrap_or_default();
+        println!("  {} ({}, deleted {}{})", tombstone.path, tombstone.language, when, hash);
+        for symbol in &tombstone.symbols {
+            println!("    {} {} (line {})", symbol.kind, symbol.name, symbol.line);
         }
+    }
+}
 
-        // ERROR: Contradictory matching requirements
-        if exact && use_contains {
-            eprintln!("{}", "ERROR: Cannot use --exact and --contains together (contradictory).".red().bold());
-            eprintln!("  {} --exact requires exact symbol name match", "•".dimmed());
-            eprintln!("  {} --contains allows substring matching", "•".dimmed());
-            has_errors = true;
-        }
+/// Print the plan and phase timings of an `--explain` query
+fn print_query_plan(response: &crate::models::QueryResponse) {
+    let Some(plan) = &response.plan else {
+        return;
+    };
 
-        // WARNING: Redundant file filtering
-        if file_pattern.is_some() && !glob_patterns.is_empty() {
-            eprintln!("{}", "WARNING: Both --file and --glob specified.".yellow().bold());
-            eprintln!("  {} --file does substring matching on file paths", "•".dimmed());
-            eprintln!("  {} --glob does pattern matching with wildcards", "•".dimmed());
-            eprintln!("  {} Both filters will apply (AND condition)", "Note:".dimmed());
-            eprintln!("\n  {} Usually you only need one:", "Tip:".cyan().bold());
-            eprintln!("    {} for simple matching", "--file User.php".yellow());
-            eprintln!("    {} for pattern matching", "--glob src/**/*.php".yellow());
+    println!("Query plan:");
+    let mut strategy = plan.strategy.clone();
+    if let Some(kind) = &plan.inferred_kind {
+        strategy.push_str(&format!(" (kind {} inferred from keyword)", kind));
+    }
+    println!("  Strategy:         {}", strategy);
+    if let Some(files) = plan.trigram_candidate_files {
+        println!("  Trigram files:    {}", files);
+    }
+    println!("  Candidates:       {}", plan.candidates);
+    println!("  Pruned:           {} by overlay, {} by --lang, {} by --glob/--exclude, {} by --not",
+             plan.pruned_by_overlay, plan.pruned_by_language, plan.pruned_by_glob, plan.pruned_by_not);
+    if plan.symbol_cache_hits + plan.symbol_parses + plan.prefiltered_files > 0 {
+        println!("  Symbol cache:     {} hits, {} parsed, {} skipped (matches only in comments/strings)",
+                 plan.symbol_cache_hits, plan.symbol_parses, plan.prefiltered_files);
+    }
+    if plan.overlay_results > 0 {
+        println!("  Overlay results:  {}", plan.overlay_results);
+    }
+    println!("  Results:          {}", plan.results);
+    const MB: f64 = 1024.0 * 1024.0;
+    match plan.memory_limit_bytes {
+        Some(limit) => println!("  Peak memory:      {:.1} MB of {:.0} MB limit", plan.peak_memory_bytes as f64 / MB, limit as f64 / MB),
+        None => println!("  Peak memory:      {:.1} MB (no limit)", plan.peak_memory_bytes as f64 / MB),
+    }
+
+    if let Some(timings) = &response.timings {
+        println!("Timings:");
+        for (phase, ms) in [
+            ("Candidates", timings.candidates_ms),
+            ("Filters", timings.filter_ms),
+            ("Symbols", timings.symbols_ms),
+            ("Post-processing", timings.post_process_ms),
+            ("Grouping", timings.grouping_ms),
+            ("Total", timings.total_ms),
+        ] {
+            println!("  {:<16}  {:>9.2}ms", format!("{}:", phase), ms);
         }
+    }
+}
 
-        // INFO: Detect potentially problematic glob patterns
-        for pattern in &glob_patterns {
-            // Check for literal quotes in pattern
-            if (pattern.starts_with('\'') && pattern.ends_with('\'')) ||
-               (pattern.starts_with('"') && pattern.ends_with('"')) {
-                eprintln!("{}",
-                    format!("WARNING: Glob pattern contains quotes: {}", pattern).yellow().bold()
-                );
-                eprintln!("  {} Shell quotes should not be part of the pattern", "Note:".dimmed());
-                eprintln!("  {} --glob src/**/*.rs", "Correct:".green());
-                eprintln!("  {} --glob 'src/**/*.rs'", "Wrong:".red().dimmed());
-            }
+/// Ensure an index exists before running a flags subcommand
+fn require_index_for_flags(cache: &CacheManager) -> Result<()> {
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             Feature flag usages are recorded while indexing.\n\
+             \n\
+             Example:\n\
+             $ rfx index            # Index current directory\n\
+             $ rfx flags list       # List feature flags".to_string()
+        ).into());
+    }
+    Ok(())
+}
 
-            // Suggest using ** instead of * for recursive matching
-            if pattern.contains("*/") && !pattern.contains("**/") {
-                eprintln!("{}",
-                    format!("INFO: Glob '{}' uses * (matches one directory level)", pattern).cyan()
-                );
-                eprintln!("  {} Use ** for recursive matching across subdirectories", "Tip:".cyan().bold());
-                eprintln!("    {} → matches files in Models/ only", "app/Models/*.php".yellow());
-                eprintln!("    {} → matches files in Models/ and subdirs", "app/Models/**/*.php".green());
-            }
-        }
+/// Handle the `flags list` subcommand
+fn handle_flags_list(as_json: bool, pretty_json: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+    require_index_for_flags(&cache)?;
 
-        if has_errors {
-            anyhow::bail!("Invalid flag combination. Fix the errors above and try again.");
+    let flag_index = crate::flags::FlagIndex::new(cache);
+    let flags = flag_index.list_flags()?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&flags)?
+        } else {
+            serde_json::to_string(&flags)?
+        };
+        println!("{}", json_output);
+    } else if flags.is_empty() {
+        println!("No feature flags found.");
+        println!("  Add custom patterns under [flags] in .reflex/config.toml, then run 'rfx index'");
+    } else {
+        let flag_width = flags.iter().map(|f| f.flag.len()).max().unwrap_or(4).max(4);
+
+        println!("Feature Flags ({} total):", flags.len());
+        println!();
+        println!("  {:<width$}  Usages  Files", "Flag", width = flag_width);
+        println!("  {}  ------  -----", "-".repeat(flag_width));
+        for flag in &flags {
+            println!("  {:<width$}  {:6}  {:5}",
+                flag.flag, flag.usages, flag.files,
+                width = flag_width);
         }
     }
 
-    let filter = QueryFilter {
-        language,
-        kind,
-        use_ast,
-        use_regex,
-        limit: final_limit,
-        symbols_mode,
-        expand,
-        file_pattern,
-        exact,
-        use_contains,
-        timeout_secs,
-        glob_patterns: glob_patterns.clone(),
-        exclude_patterns,
-        paths_only,
-        offset,
-        force,
-        suppress_output: as_json,  // Suppress warnings in JSON mode
-        include_dependencies,
-        ..Default::default()
-    };
+    Ok(())
+}
 
-    // Measure query time
-    let start = Instant::now();
+/// Handle the `flags where` subcommand
+fn handle_flags_where(flag: String, as_json: bool, pretty_json: bool, paths_only: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+    require_index_for_flags(&cache)?;
 
-    // Execute query and get pagination metadata
-    // Handle errors specially for JSON output mode
-    let (query_response, mut flat_results, total_results, has_more) = if use_ast {
-        // AST query: pattern is the S-expression, scan all files
-        match engine.search_ast_all_files(&pattern, filter.clone()) {
-            Ok(ast_results) => {
-                let count = ast_results.len();
-                (None, ast_results, count, false)
-            }
-            Err(e) => {
-                if as_json {
-                    // Output error as JSON
-                    let error_response = serde_json::json!({
-                        "error": e.to_string(),
-                        "query_too_broad": e.to_string().contains("Query too broad")
-                    });
-                    let json_output = if pretty_json {
-                        serde_json::to_string_pretty(&error_response)?
-                    } else {
-                        serde_json::to_string(&error_response)?
-                    };
-                    println!("{}", json_output);
-                    std::process::exit(1);
-                } else {
-                    return Err(e);
-                }
-            }
-        }
-    } else {
-        // Use metadata-aware search for all queries (to get pagination info)
-        match engine.search_with_metadata(&pattern, filter.clone()) {
-            Ok(response) => {
-                let total = response.pagination.total;
-                let has_more = response.pagination.has_more;
+    let flag_index = crate::flags::FlagIndex::new(cache);
+    let usages = flag_index.find_usages(&flag)?;
 
-                // Flatten grouped results to SearchResult vec for plain text formatting
-                let flat = response.results.iter()
-                    .flat_map(|file_group| {
-                        file_group.matches.iter().map(move |m| {
-                            crate::models::SearchResult {
-                                path: file_group.path.clone(),
-                                lang: crate::models::Language::Unknown, // Will be set by formatter if needed
-                                kind: m.kind.clone(),
-                                symbol: m.symbol.clone(),
-                                span: m.span.clone(),
-                                preview: m.preview.clone(),
-                                dependencies: file_group.dependencies.clone(),
-                            }
-                        })
-                    })
-                    .collect();
+    if paths_only {
+        let mut paths: Vec<&str> = usages.iter().map(|u| u.path.as_str()).collect();
+        paths.dedup();
 
-                (Some(response), flat, total, has_more)
-            }
-            Err(e) => {
-                if as_json {
-                    // Output error as JSON
-                    let error_response = serde_json::json!({
-                        "error": e.to_string(),
-                        "query_too_broad": e.to_string().contains("Query too broad")
-                    });
-                    let json_output = if pretty_json {
-                        serde_json::to_string_pretty(&error_response)?
-                    } else {
-                        serde_json::to_string(&error_response)?
-                    };
-                    println!("{}", json_output);
-                    std::process::exit(1);
-                } else {
-                    return Err(e);
-                }
+        if as_json {
+            let json_output = if pretty_json {
+                serde_json::to_string_pretty(&paths)?
+            } else {
+                serde_json::to_string(&paths)?
+            };
+            println!("{}", json_output);
+        } else {
+            for path in paths {
+                println!("{}", path);
             }
         }
-    };
+        return Ok(());
+    }
 
-    // Apply preview truncation unless --no-truncate is set
-    if !no_truncate {
-        const MAX_PREVIEW_LENGTH: usize = 100;
-        for result in &mut flat_results {
-            result.preview = truncate_preview(&result.preview, MAX_PREVIEW_LENGTH);
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&usages)?
+        } else {
+            serde_json::to_string(&usages)?
+        };
+        println!("{}", json_output);
+    } else if usages.is_empty() {
+        println!("No usages of flag '{}' found.", flag);
+    } else {
+        println!("Usages of '{}' ({} total):", flag, usages.len());
+        println!();
+        for usage in &usages {
+            println!("  {}:{}  {}", usage.path, usage.line, usage.preview);
         }
     }
 
-    let elapsed = start.elapsed();
+    Ok(())
+}
 
-    // Format timing string
-    let timing_str = if elapsed.as_millis() < 1 {
-        format!("{:.1}ms", elapsed.as_secs_f64() * 1000.0)
-    } else {
-        format!("{}ms", elapsed.as_millis())
-    };
+/// Handle the `config-keys` subcommand
+fn handle_config_keys(key: Option<String>, kind: Option<String>, as_json: bool, pretty_json: bool) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             Environment variable and config key usages are recorded while indexing.".to_string()
+        ).into());
+    }
 
-    if as_json {
-        if count_only {
-            // Count-only JSON mode: output simple count object
-            let count_response = serde_json::json!({
-                "count": total_results,
-                "timing_ms": elapsed.as_millis()
-            });
-            let json_output = if pretty_json {
-                serde_json::to_string_pretty(&count_response)?
-            } else {
-                serde_json::to_string(&count_response)?
-            };
-            println!("{}", json_output);
-        } else if paths_only {
-            // Paths-only JSON mode: output array of {path, line} objects
-            let locations: Vec<serde_json::Value> = flat_results.iter()
-                .map(|r| serde_json::json!({
-                    "path": r.path,
-                    "line": r.span.start_line
-                }))
-                .collect();
+    let kind = kind.as_deref().map(str::parse::<crate::config_keys::KeyKind>).transpose()?;
+    let key_index = crate::config_keys::ConfigKeyIndex::new(cache);
+
+    if let Some(key) = key {
+        let usages = key_index.find_usages(&key, kind)?;
+
+        if as_json {
             let json_output = if pretty_json {
-                serde_json::to_string_pretty(&locations)?
+                serde_json::to_string_pretty(&usages)?
             } else {
-                serde_json::to_string(&locations)?
+                serde_json::to_string(&usages)?
             };
             println!("{}", json_output);
-            eprintln!("Found {} unique files in {}", locations.len(), timing_str);
+        } else if usages.is_empty() {
+            println!("No usages of key '{}' found.", key);
         } else {
-            // Get or build QueryResponse for JSON output
-            let mut response = if let Some(resp) = query_response {
-                // We already have a response from search_with_metadata
-                // Apply truncation to the response (the flat_results were already truncated)
-                let mut resp = resp;
+            println!("Usages of '{}' ({} total):", key, usages.len());
+            println!();
+            for usage in &usages {
+                println!("  {}:{}  [{}]  {}", usage.path, usage.line, usage.access, usage.preview);
+            }
+        }
+        return Ok(());
+    }
 
-                // Apply truncation to results
-                if !no_truncate {
-                    const MAX_PREVIEW_LENGTH: usize = 100;
-                    for file_group in resp.results.iter_mut() {
-                        for m in file_group.matches.iter_mut() {
-                            m.preview = truncate_preview(&m.preview, MAX_PREVIEW_LENGTH);
-                        }
-                    }
-                }
+    let keys = key_index.list_keys(kind)?;
 
-                resp
-            } else {
-                // For AST queries, build a response with minimal metadata
-                // Group flat results by file path
-                use crate::models::{PaginationInfo, IndexStatus, FileGroupedResult, MatchResult};
-                use std::collections::HashMap;
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&keys)?
+        } else {
+            serde_json::to_string(&keys)?
+        };
+        println!("{}", json_output);
+    } else if keys.is_empty() {
+        println!("No environment variable or config key usages found.");
+    } else {
+        let key_width = keys.iter().map(|k| k.key.len()).max().unwrap_or(3).max(3);
 
-                let mut grouped: HashMap<String, Vec<crate::models::SearchResult>> = HashMap::new();
-                for result in &flat_results {
-                    grouped
-                        .entry(result.path.clone())
-                        .or_default()
-                        .push(result.clone());
-                }
+        println!("Config Keys ({} total):", keys.len());
+        println!();
+        println!("  {:<width$}  Kind    Reads  Writes  Files", "Key", width = key_width);
+        println!("  {}  ------  -----  ------  -----", "-".repeat(key_width));
+        for key in &keys {
+            println!("  {:<width$}  {:<6}  {:5}  {:6}  {:5}",
+                key.key, key.kind, key.reads, key.writes, key.files,
+                width = key_width);
+        }
+    }
 
-                // Load ContentReader for extracting context lines
-                use crate::content_store::ContentReader;
-                let local_cache = CacheManager::new(".");
-                let content_path = local_cache.path().join("content.bin");
-                let content_reader_opt = ContentReader::open(&content_path).ok();
+    Ok(())
+}
 
-                let mut file_results: Vec<FileGroupedResult> = grouped
-                    .into_iter()
-                    .map(|(path, file_matches)| {
-                        // Get file_id for context extraction
-                        // Note: We use ContentReader's get_file_id_by_path() which returns array indices,
-                        // not database file_ids (which are AUTO INCREMENT values)
-                        let normalized_path = path.strip_prefix("./").unwrap_or(&path);
-                        let file_id_for_context = if let Some(reader) = &content_reader_opt {
-                            reader.get_file_id_by_path(normalized_path)
-                        } else {
-                            None
-                        };
+/// Parse a --kind argument (case-insensitive; unknown kinds filter as-is)
+fn parse_kind_arg(kind: &str) -> crate::models::SymbolKind {
+    let capitalized: String = {
+        let mut chars = kind.chars();
+        match chars.next() {
+            None => String::new(),
+            Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+        }
+    };
 
-                        let matches: Vec<MatchResult> = file_matches
-                            .into_iter()
-                            .map(|r| {
-                                // Extract context lines (default: 3 lines before and after)
-                                let (context_before, context_after) = if let (Some(reader), Some(fid)) = (&content_reader_opt, file_id_for_context) {
-                                    reader.get_context_by_line(fid as u32, r.span.start_line, 3)
-                                        .unwrap_or_else(|_| (vec![], vec![]))
-                                } else {
-                                    (vec![], vec![])
-                                };
+    capitalized
+        .parse::<crate::models::SymbolKind>()
+        .unwrap_or_else(|_| crate::models::SymbolKind::Unknown(kind.to_string()))
+}
 
-                                MatchResult {
-                                    kind: r.kind,
-                                    symbol: r.symbol,
-                                    span: r.span,
-                                    preview: r.preview,
-                                    context_before,
-                                    context_after,
-                                }
-                            })
-                            .collect();
-                        FileGroupedResult {
-                            path,
-                            dependencies: None,
-                            matches,
-                        }
-                    })
-                    .collect();
+/// S-expression of the `--ast-preset` named `name` for `--lang`
+fn resolve_ast_preset(name: &str, lang: Option<&str>, dir: &std::path::Path) -> Result<String> {
+    let cache = CacheManager::new(dir);
+    let Some(language) = parse_language_arg(lang)? else {
+        let presets = crate::ast_presets::load_presets(cache.path())?;
+        let languages: Vec<String> = presets
+            .iter()
+            .filter(|p| p.name == name)
+            .map(|p| format!("{:?}", p.language).to_lowercase())
+            .collect();
+        anyhow::bail!(
+            "--ast-preset requires --lang to pick the tree-sitter grammar.\n\
+             \n\
+             Example: rfx query --ast-preset {} --lang {}",
+            name,
+            languages.first().map(String::as_str).unwrap_or("rust")
+        );
+    };
 
-                // Sort by path for deterministic output
-                file_results.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(crate::ast_presets::find_preset(cache.path(), name, language)?.pattern)
+}
 
-                crate::models::QueryResponse {
-                    ai_instruction: None,  // Will be populated below if ai_mode is true
-                    status: IndexStatus::Fresh,
-                    can_trust_results: true,
-                    warning: None,
-                    pagination: PaginationInfo {
-                        total: flat_results.len(),
-                        count: flat_results.len(),
-                        offset: offset.unwrap_or(0),
-                        limit,
-                        has_more: false, // AST already applied pagination
-                    },
-                    results: file_results,
-                }
-            };
+/// Tree-sitter query for a `--pattern` structural pattern
+fn compile_structural_pattern(pattern: &str, lang: Option<&str>) -> Result<String> {
+    let Some(language) = parse_language_arg(lang)? else {
+        anyhow::bail!(
+            "--pattern requires --lang to parse the pattern.\n\
+             \n\
+             Example: rfx query 'foo($A)' --pattern --lang rust"
+        );
+    };
 
-            // Generate AI instruction if in AI mode
-            if ai_mode {
-                let result_count: usize = response.results.iter().map(|fg| fg.matches.len()).sum();
+    crate::structural::compile(pattern, language)
+}
 
-                response.ai_instruction = crate::query::generate_ai_instruction(
-                    result_count,
-                    response.pagination.total,
-                    response.pagination.has_more,
-                    symbols_mode,
-                    paths_only,
-                    use_ast,
-                    use_regex,
-                    language.is_some(),
-                    !glob_patterns.is_empty(),
-                    exact,
-                );
-            }
+/// Parse a `--lang` argument (None if not given)
+fn parse_language_arg(lang: Option<&str>) -> Result<Option<Language>> {
+    let language = match lang {
+        None => None,
+        Some(l) => Some(Language::from_name(l).with_context(|| format!(
+            "Unknown language: '{}'\n\
+             \n\
+             Supported languages: rust, python, javascript, typescript, vue, svelte, go, java, php, c, cpp, csharp, ruby, kotlin, zig, shell, sql, graphql, html, make, cmake, bazel",
+            l
+        ))?),
+    };
+
+    Ok(language)
+}
+
+/// Handle the `errors` subcommand
+fn handle_errors(
+    name: Option<String>,
+    lang: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    defs_only: bool,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index            # Index current directory\n\
+             $ rfx errors           # List error types and raise sites".to_string()
+        ).into());
+    }
+
+    let language = parse_language_arg(lang.as_deref())?;
+
+    let filter = crate::errors::ErrorFilter {
+        language,
+        glob_patterns,
+        exclude_patterns,
+        name,
+    };
+    let mut inventory = crate::errors::build_inventory(&cache, &filter)?;
+
+    if defs_only {
+        for error_type in &mut inventory {
+            error_type.sites.clear();
+        }
+    }
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&inventory)?
+        } else {
+            serde_json::to_string(&inventory)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
+
+    if inventory.is_empty() {
+        println!("No error types found.");
+        return Ok(());
+    }
+
+    let total_sites: usize = inventory.iter().map(|t| t.sites.len()).sum();
+    if defs_only {
+        println!("Error Types ({} total):", inventory.len());
+    } else {
+        println!("Error Types ({} total, {} raise/construct sites):", inventory.len(), total_sites);
+    }
+
+    for error_type in &inventory {
+        println!();
+        println!("  {} ({})  {}:{}",
+            error_type.name, error_type.kind.to_lowercase(), error_type.path, error_type.line);
+        if defs_only {
+            continue;
+        }
+        if error_type.sites.is_empty() {
+            println!("      (no raise/construct sites found)");
+        }
+        for site in &error_type.sites {
+            println!("      {:<9}  {}:{}  {}", site.action, site.path, site.line, site.preview);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle the `todos` subcommand
+fn handle_todos(
+    markers: Vec<String>,
+    lang: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index            # Index current directory\n\
+             $ rfx todos            # List TODO/FIXME annotations".to_string()
+        ).into());
+    }
+
+    let language = parse_language_arg(lang.as_deref())?;
+
+    let filter = crate::todos::TodoFilter {
+        language,
+        glob_patterns,
+        exclude_patterns,
+        markers,
+    };
+    let items = crate::todos::scan(&cache, &filter)?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&items)?
+        } else {
+            serde_json::to_string(&items)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-            let json_output = if pretty_json {
-                serde_json::to_string_pretty(&response)?
-            } else {
-                serde_json::to_string(&response)?
-            };
-            println!("{}", json_output);
+    if items.is_empty() {
+        println!("No annotations found.");
+        return Ok(());
+    }
 
-            let result_count: usize = response.results.iter().map(|fg| fg.matches.len()).sum();
-            eprintln!("Found {} results in {}", result_count, timing_str);
-        }
-    } else {
-        // Standard output with formatting
-        if count_only {
-            println!("Found {} results in {}", flat_results.len(), timing_str);
-            return Ok(());
+    println!("Annotations ({} total):", items.len());
+    let mut current_path: Option<&str> = None;
+    for item in &items {
+        if current_path != Some(item.path.as_str()) {
+            println!();
+            println!("  {}", item.path);
+            current_path = Some(item.path.as_str());
         }
+        let symbol = item.symbol.as_deref()
+            .map(|s| format!("  (in {})", s))
+            .unwrap_or_default();
+        println!("    {:>5}  {:<5}  {}{}", item.line, item.marker, item.text, symbol);
+    }
 
-        if paths_only {
-            // Paths-only plain text mode: output one path per line
-            if flat_results.is_empty() {
-                eprintln!("No results found (searched in {}).", timing_str);
-            } else {
-                for result in &flat_results {
-                    println!("{}", result.path);
-                }
-                eprintln!("Found {} unique files in {}", flat_results.len(), timing_str);
-            }
+    Ok(())
+}
+
+/// Handle the `routes` subcommand
+fn handle_routes(
+    pattern: Option<String>,
+    method: Option<String>,
+    lang: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index            # Index current directory\n\
+             $ rfx routes           # List HTTP routes".to_string()
+        ).into());
+    }
+
+    let mut filter = crate::routes::RouteFilter {
+        language: parse_language_arg(lang.as_deref())?,
+        glob_patterns,
+        exclude_patterns,
+        ..Default::default()
+    };
+    if let Some(pattern) = &pattern {
+        filter = filter.with_pattern(pattern);
+    }
+    if let Some(method) = method {
+        filter.method = Some(method.to_uppercase());
+    }
+    let entries = crate::routes::scan(&cache, &filter)?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&entries)?
         } else {
-            // Standard result formatting
-            if flat_results.is_empty() {
-                println!("No results found (searched in {}).", timing_str);
-            } else {
-                // Use formatter for pretty output
-                let formatter = crate::formatter::OutputFormatter::new(plain);
-                formatter.format_results(&flat_results, &pattern)?;
+            serde_json::to_string(&entries)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-                // Print summary at the bottom with pagination details
-                if total_results > flat_results.len() {
-                    // Results were paginated - show detailed count
-                    println!("\nFound {} results ({} total) in {}", flat_results.len(), total_results, timing_str);
-                    // Show pagination hint if there are more results available
-                    if has_more {
-                        println!("Use --limit and --offset to paginate");
-                    }
-                } else {
-                    // All results shown - simple count
-                    println!("\nFound {} results in {}", flat_results.len(), timing_str);
-                }
-            }
+    if entries.is_empty() {
+        println!("No routes found.");
+        return Ok(());
+    }
+
+    println!("Routes ({} total):", entries.len());
+    let mut current_file: Option<&str> = None;
+    for entry in &entries {
+        if current_file != Some(entry.file.as_str()) {
+            println!();
+            println!("  {}", entry.file);
+            current_file = Some(entry.file.as_str());
         }
+        let route = &entry.route;
+        let handler = route.handler.as_deref()
+            .map(|h| format!("  -> {}", h))
+            .unwrap_or_default();
+        println!("    {:>5}  {:<7} {}{}  ({})", route.line, route.method, route.path, handler, route.framework);
     }
 
     Ok(())
 }
 
-/// Handle the `serve` subcommand
-fn handle_serve(port: u16, host: String) -> Result<()> {
-    log::info!("Starting HTTP server on {}:{}", host, port);
-
-    println!("Starting Reflex HTTP server...");
-    println!("  Address: http://{}:{}", host, port);
-    println!("\nEndpoints:");
-    println!("  GET  /query?q=<pattern>&lang=<lang>&kind=<kind>&limit=<n>&symbols=true&regex=true&exact=true&contains=true&expand=true&file=<pattern>&timeout=<secs>&glob=<pattern>&exclude=<pattern>&paths=true&dependencies=true");
-    println!("  GET  /stats");
-    println!("  POST /index");
-    println!("\nPress Ctrl+C to stop.");
+/// Handle the `replace` subcommand
+#[allow(clippy::too_many_arguments)]
+fn handle_replace(
+    pattern: String,
+    replacement: String,
+    use_regex: bool,
+    use_contains: bool,
+    lang: Option<String>,
+    file_pattern: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    write: bool,
+    force: bool,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index                      # Index current directory\n\
+             $ rfx replace old_name new_name  # Preview a rename".to_string()
+        ).into());
+    }
 
-    // Start the server using tokio runtime
-    let runtime = tokio::runtime::Runtime::new()?;
-    runtime.block_on(async {
-        run_server(port, host).await
-    })
-}
+    let filter = QueryFilter {
+        language: parse_language_arg(lang.as_deref())?,
+        use_regex,
+        use_contains,
+        file_pattern,
+        glob_patterns,
+        exclude_patterns,
+        force,
+        ..Default::default()
+    };
 
-/// Run the HTTP server
-async fn run_server(port: u16, host: String) -> Result<()> {
-    use axum::{
-        extract::{Query as AxumQuery, State},
-        http::StatusCode,
-        response::{IntoResponse, Json},
-        routing::{get, post},
-        Router,
+    let engine = QueryEngine::new(cache);
+    let plan = crate::replace::plan_replacements(&engine, &pattern, &replacement, filter)?;
+    let written = if write && !plan.files.is_empty() {
+        crate::replace::apply_plan(&plan)?
+    } else {
+        0
     };
-    use tower_http::cors::{CorsLayer, Any};
-    use std::sync::Arc;
 
-    // Server state shared across requests
-    #[derive(Clone)]
-    struct AppState {
-        cache_path: String,
+    if as_json {
+        let output = serde_json::json!({
+            "applied": write,
+            "files_written": written,
+            "plan": plan,
+        });
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&output)?
+        } else {
+            serde_json::to_string(&output)?
+        };
+        println!("{}", json_output);
+        return Ok(());
     }
 
-    // Query parameters for GET /query
-    #[derive(Debug, serde::Deserialize)]
-    struct QueryParams {
-        q: String,
-        #[serde(default)]
-        lang: Option<String>,
-        #[serde(default)]
-        kind: Option<String>,
-        #[serde(default)]
-        limit: Option<usize>,
-        #[serde(default)]
-        offset: Option<usize>,
-        #[serde(default)]
-        symbols: bool,
-        #[serde(default)]
-        regex: bool,
-        #[serde(default)]
-        exact: bool,
-        #[serde(default)]
-        contains: bool,
-        #[serde(default)]
-        expand: bool,
-        #[serde(default)]
-        file: Option<String>,
-        #[serde(default = "default_timeout")]
-        timeout: u64,
-        #[serde(default)]
-        glob: Vec<String>,
-        #[serde(default)]
-        exclude: Vec<String>,
-        #[serde(default)]
-        paths: bool,
-        #[serde(default)]
-        force: bool,
-        #[serde(default)]
-        dependencies: bool,
+    if plan.files.is_empty() {
+        println!("No matches found for '{}'.", pattern);
+        return Ok(());
     }
 
-    // Default timeout for HTTP queries (30 seconds)
-    fn default_timeout() -> u64 {
-        30
+    for file in &plan.files {
+        print!("{}", file.diff);
     }
+    println!();
 
-    // Request body for POST /index
-    #[derive(Debug, serde::Deserialize)]
-    struct IndexRequest {
-        #[serde(default)]
-        force: bool,
-        #[serde(default)]
-        languages: Vec<String>,
+    if write {
+        println!("Replaced {} occurrence(s) in {} file(s).", plan.total_replacements, written);
+        println!("  Run 'rfx index' to refresh the index");
+    } else {
+        println!("{} occurrence(s) in {} file(s) would be replaced (preview only).",
+            plan.total_replacements, plan.total_files);
+        println!("  Re-run with --write to apply");
     }
 
-    // GET /query endpoint
-    async fn handle_query_endpoint(
-        State(state): State<Arc<AppState>>,
-        AxumQuery(params): AxumQuery<QueryParams>,
-    ) -> Result<Json<crate::models::QueryResponse>, (StatusCode, String)> {
-        log::info!("Query request: pattern={}", params.q);
-
-        let cache = CacheManager::new(&state.cache_path);
-        let engine = QueryEngine::new(cache);
+    Ok(())
+}
 
-        // Parse language filter
-        let language = if let Some(lang_str) = params.lang.as_deref() {
-            match lang_str.to_lowercase().as_str() {
-                "rust" | "rs" => Some(Language::Rust),
-                "javascript" | "js" => Some(Language::JavaScript),
-                "typescript" | "ts" => Some(Language::TypeScript),
-                "vue" => Some(Language::Vue),
-                "svelte" => Some(Language::Svelte),
-                "php" => Some(Language::PHP),
-                "python" | "py" => Some(Language::Python),
-                "go" => Some(Language::Go),
-                "java" => Some(Language::Java),
-                "c" => Some(Language::C),
-                "cpp" | "c++" => Some(Language::Cpp),
-                _ => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Unknown language '{}'. Supported languages: rust, javascript (js), typescript (ts), vue, svelte, php, python (py), go, java, c, cpp (c++)", lang_str)
-                    ));
-                }
+/// Handle the `review` subcommand
+#[allow(clippy::too_many_arguments)]
+fn handle_review(
+    pattern: Option<String>,
+    pr: String,
+    findings_path: Option<PathBuf>,
+    symbols: bool,
+    kind_str: Option<String>,
+    use_regex: bool,
+    lang: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    message: Option<String>,
+    force: bool,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let (findings, pattern) = match (findings_path, pattern) {
+        (Some(path), _) => {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read findings from {}", path.display()))?;
+            (crate::review::findings_from_query_json(&json)?, String::new())
+        }
+        (None, Some(pattern)) => {
+            let cache = CacheManager::new(".");
+            if !cache.exists() {
+                return Err(ReflexError::IndexMissing(
+                    "No index found in current directory.\n\
+                     \n\
+                     Run 'rfx index' to build the code search index first.\n\
+                     \n\
+                     Example:\n\
+                     $ rfx index                              # Index current directory\n\
+                     $ rfx review unwrap --pr main...HEAD     # Review the current branch".to_string()
+                ).into());
             }
-        } else {
-            None
-        };
 
-        // Parse symbol kind
-        let kind = params.kind.as_deref().and_then(|s| {
-            let capitalized = {
-                let mut chars = s.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
-                }
+            let kind = kind_str.as_deref().map(parse_kind_arg);
+            let filter = QueryFilter {
+                language: parse_language_arg(lang.as_deref())?,
+                symbols_mode: symbols || kind.is_some(),
+                kinds: kind.into_iter().collect(),
+                use_regex,
+                glob_patterns,
+                exclude_patterns,
+                limit: None,
+                force,
+                suppress_output: as_json,
+                ..Default::default()
             };
 
-            capitalized.parse::<crate::models::SymbolKind>()
-                .ok()
-                .or_else(|| {
-                    log::debug!("Treating '{}' as unknown symbol kind for filtering", s);
-                    Some(crate::models::SymbolKind::Unknown(s.to_string()))
-                })
-        });
+            let engine = QueryEngine::new(cache);
+            let results = engine.search(&pattern, filter)?;
+            (results.iter().map(crate::review::Finding::from).collect(), pattern)
+        }
+        (None, None) => anyhow::bail!(
+            "Nothing to review.\n\
+             \n\
+             Pass a pattern to search for, or --findings with the output of `rfx query --json`.\n\
+             \n\
+             Example:\n\
+             $ rfx review unwrap --pr main...HEAD"
+        ),
+    };
 
-        // Smart behavior: --kind implies --symbols
-        let symbols_mode = params.symbols || kind.is_some();
+    let diff = crate::review::parse_unified_diff(&crate::review::load_diff(&pr, ".")?);
+    let comments = crate::review::map_findings(&findings, &diff, &pattern, message.as_deref());
 
-        // Smart limit handling (same as CLI and MCP)
-        let final_limit = if params.paths && params.limit.is_none() {
-            None  // --paths without explicit limit means no limit
-        } else if let Some(user_limit) = params.limit {
-            Some(user_limit)  // Use user-specified limit
+    if as_json {
+        let output = serde_json::json!({
+            "total_findings": findings.len(),
+            "files_in_diff": diff.len(),
+            "comments": comments,
+        });
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&output)?
         } else {
-            Some(100)  // Default: limit to 100 results for token efficiency
+            serde_json::to_string(&output)?
         };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-        let filter = QueryFilter {
-            language,
-            kind,
-            use_ast: false,
-            use_regex: params.regex,
-            limit: final_limit,
-            symbols_mode,
-            expand: params.expand,
-            file_pattern: params.file,
-            exact: params.exact,
-            use_contains: params.contains,
-            timeout_secs: params.timeout,
-            glob_patterns: params.glob,
-            exclude_patterns: params.exclude,
-            paths_only: params.paths,
-            offset: params.offset,
-            force: params.force,
-            suppress_output: true,  // HTTP API always returns JSON, suppress warnings
-            include_dependencies: params.dependencies,
-            ..Default::default()
-        };
+    if comments.is_empty() {
+        println!("No findings on lines changed by the PR ({} finding(s) outside the diff).", findings.len());
+        return Ok(());
+    }
 
-        match engine.search_with_metadata(&params.q, filter) {
-            Ok(response) => Ok(Json(response)),
-            Err(e) => {
-                log::error!("Query error: {}", e);
-                Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Query failed: {}", e)))
-            }
-        }
+    for comment in &comments {
+        let location = match comment.start_line {
+            Some(start) => format!("{}:{}-{}", comment.path, start, comment.line),
+            None => format!("{}:{}", comment.path, comment.line),
+        };
+        println!("{}  {}", location.cyan(), comment.body);
     }
+    println!();
+    println!("{} comment(s) on changed lines ({} finding(s) total).", comments.len(), findings.len());
 
-    // GET /stats endpoint
-    async fn handle_stats_endpoint(
-        State(state): State<Arc<AppState>>,
-    ) -> Result<Json<crate::models::IndexStats>, (StatusCode, String)> {
-        log::info!("Stats request");
+    Ok(())
+}
 
-        let cache = CacheManager::new(&state.cache_path);
+/// Handle the `lint` subcommand
+fn handle_lint(only: Vec<String>, list: bool, format: String, as_json: bool, pretty_json: bool) -> Result<()> {
+    use crate::lint::Severity;
 
-        if !cache.exists() {
-            return Err((StatusCode::NOT_FOUND, "No index found. Run 'rfx index' first.".to_string()));
-        }
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index             # Index current directory\n\
+             $ rfx lint              # Run the rules in .reflex/rules/".to_string()
+        ).into());
+    }
+    if !matches!(format.as_str(), "text" | "sarif") {
+        anyhow::bail!("Unknown lint format '{}'. Supported: text, sarif", format);
+    }
 
-        match cache.stats() {
-            Ok(stats) => Ok(Json(stats)),
-            Err(e) => {
-                log::error!("Stats error: {}", e);
-                Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get stats: {}", e)))
+    let mut rules = crate::lint::load_rules(cache.path())?;
+    if let Some(unknown) = only.iter().find(|id| !rules.iter().any(|r| &r.id == *id)) {
+        let known: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        anyhow::bail!("Unknown lint rule '{}'. Rules: {}", unknown, if known.is_empty() { "(none)".to_string() } else { known.join(", ") });
+    }
+    if !only.is_empty() {
+        rules.retain(|r| only.contains(&r.id));
+    }
+
+    if list {
+        if as_json {
+            let json_output = if pretty_json {
+                serde_json::to_string_pretty(&rules)?
+            } else {
+                serde_json::to_string(&rules)?
+            };
+            println!("{}", json_output);
+        } else if rules.is_empty() {
+            println!("No lint rules. Add rule packs to .reflex/{}/ (see `rfx lint --help`).", crate::lint::RULES_DIR);
+        } else {
+            for rule in &rules {
+                println!("{}  {}  {}", rule.id.bold(), rule.severity, rule.message);
             }
         }
+        return Ok(());
     }
 
-    // POST /index endpoint
-    async fn handle_index_endpoint(
-        State(state): State<Arc<AppState>>,
-        Json(req): Json<IndexRequest>,
-    ) -> Result<Json<crate::models::IndexStats>, (StatusCode, String)> {
-        log::info!("Index request: force={}, languages={:?}", req.force, req.languages);
-
-        let cache = CacheManager::new(&state.cache_path);
+    if rules.is_empty() {
+        eprintln!("No lint rules. Add rule packs to .reflex/{}/ (see `rfx lint --help`).", crate::lint::RULES_DIR);
+    }
+    let engine = QueryEngine::new(cache.clone());
+    let report = crate::lint::run(&engine, cache.path(), &rules)?;
 
-        if req.force {
-            log::info!("Force rebuild requested, clearing existing cache");
-            if let Err(e) = cache.clear() {
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to clear cache: {}", e)));
+    if format == "sarif" {
+        let (sarif_rules, findings) = crate::lint::sarif_findings(&rules, &report);
+        println!("{}", crate::sarif::SarifLog::new(&sarif_rules, &findings).render(pretty_json)?);
+    } else if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{}", json_output);
+    } else {
+        for d in &report.diagnostics {
+            let severity = match d.severity {
+                Severity::Error => d.severity.to_string().red().bold().to_string(),
+                Severity::Warning => d.severity.to_string().yellow().bold().to_string(),
+                Severity::Note => d.severity.to_string().cyan().bold().to_string(),
+            };
+            println!("{}:{}: {}[{}]: {}", d.path, d.line, severity, d.rule, d.message);
+            if !d.preview.is_empty() {
+                println!("    {}", d.preview.dimmed());
             }
         }
+        if !report.diagnostics.is_empty() {
+            println!();
+        }
+        println!(
+            "{} error(s), {} warning(s), {} note(s) from {} rule(s).",
+            report.count(Severity::Error),
+            report.count(Severity::Warning),
+            report.count(Severity::Note),
+            report.rules
+        );
+    }
 
-        // Parse language filters
-        let lang_filters: Vec<Language> = req.languages
-            .iter()
-            .filter_map(|s| match s.to_lowercase().as_str() {
-                "rust" | "rs" => Some(Language::Rust),
-                "python" | "py" => Some(Language::Python),
-                "javascript" | "js" => Some(Language::JavaScript),
-                "typescript" | "ts" => Some(Language::TypeScript),
-                "vue" => Some(Language::Vue),
-                "svelte" => Some(Language::Svelte),
-                "go" => Some(Language::Go),
-                "java" => Some(Language::Java),
-                "php" => Some(Language::PHP),
-                "c" => Some(Language::C),
-                "cpp" | "c++" => Some(Language::Cpp),
-                _ => {
-                    log::warn!("Unknown language: {}", s);
-                    None
-                }
-            })
-            .collect();
+    if report.count(Severity::Error) > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
 
-        let config = IndexConfig {
-            languages: lang_filters,
-            ..Default::default()
+/// Handle the `impact` subcommand
+fn handle_impact(
+    symbol: String,
+    kind_str: Option<String>,
+    lang: Option<String>,
+    depth: usize,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index                  # Index current directory\n\
+             $ rfx impact <symbol>        # Analyze rename impact".to_string()
+        ).into());
+    }
+
+    let filter = crate::impact::ImpactFilter {
+        kind: kind_str.as_deref().map(parse_kind_arg),
+        language: parse_language_arg(lang.as_deref())?,
+        depth,
+    };
+    let report = crate::impact::analyze_impact(&cache, &symbol, &filter)?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
         };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-        let indexer = Indexer::new(cache, config);
-        let path = std::path::PathBuf::from(&state.cache_path);
+    println!("{}", format!("Definitions of {}:", report.symbol).bold());
+    for def in &report.definitions {
+        println!("  {}  {}", format!("{}:{}", def.path, def.line).cyan(), def.preview);
+    }
 
-        match indexer.index(&path, false) {
-            Ok(stats) => Ok(Json(stats)),
-            Err(e) => {
-                log::error!("Index error: {}", e);
-                Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Indexing failed: {}", e)))
+    let groups = [
+        ("Direct usages", &report.direct),
+        ("Transitive usages", &report.transitive),
+        ("Unlinked mentions (no import path found)", &report.unlinked),
+    ];
+    for (title, files) in groups {
+        if files.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", format!("{} ({} file(s)):", title, files.len()).bold());
+        for file in files {
+            for site in &file.references {
+                println!("  {}  {}", format!("{}:{}", file.path, site.line).cyan(), site.preview);
             }
         }
     }
 
-    // Health check endpoint
-    async fn handle_health() -> impl IntoResponse {
-        (StatusCode::OK, "Reflex is running")
-    }
-
-    // Create shared state
-    let state = Arc::new(AppState {
-        cache_path: ".".to_string(),
-    });
+    println!();
+    println!(
+        "{} reference(s) in {} file(s) affected ({} direct, {} transitive, {} unlinked).",
+        report.total_references,
+        report.total_files,
+        report.direct.len(),
+        report.transitive.len(),
+        report.unlinked.len()
+    );
 
-    // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    Ok(())
+}
 
-    // Build the router
-    let app = Router::new()
-        .route("/query", get(handle_query_endpoint))
-        .route("/stats", get(handle_stats_endpoint))
-        .route("/index", post(handle_index_endpoint))
-        .route("/health", get(handle_health))
-        .layer(cors)
-        .with_state(state);
+/// Handle the `hierarchy` subcommand
+fn handle_hierarchy(
+    name: String,
+    depth: usize,
+    lang: Option<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let cache = CacheManager::new(".");
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index                  # Index current directory\n\
+             $ rfx hierarchy <type>       # Show supertypes and subtypes".to_string()
+        ).into());
+    }
 
-    // Bind to the specified address
-    let addr = format!("{}:{}", host, port);
-    let listener = tokio::net::TcpListener::bind(&addr).await
-        .map_err(|e| anyhow::anyhow!("Failed to bind to {}: {}", addr, e))?;
+    let options = crate::hierarchy::HierarchyOptions {
+        depth,
+        language: parse_language_arg(lang.as_deref())?,
+        glob_patterns,
+        exclude_patterns,
+    };
+    let report = crate::hierarchy::hierarchy(&cache, &name, &options)?;
 
-    log::info!("Server listening on {}", addr);
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-    // Run the server
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+    let root = crate::parsers::supertypes::simple_name(&report.name).to_string();
+    for (title, edges) in [("Supertypes", &report.supertypes), ("Subtypes", &report.subtypes)] {
+        println!("{}", format!("{} of {} ({}):", title, report.name, edges.len()).bold());
+        if edges.is_empty() {
+            println!("  (none)");
+        }
+        print_hierarchy_level(edges, &root, 1);
+        println!();
+    }
 
     Ok(())
 }
 
-/// Handle the `stats` subcommand
-fn handle_stats(as_json: bool, pretty_json: bool) -> Result<()> {
-    log::info!("Showing index statistics");
+/// Print the edges hanging from `of` at `depth`, then their own edges below them
+fn print_hierarchy_level(edges: &[crate::hierarchy::HierarchyEdge], of: &str, depth: usize) {
+    for edge in edges.iter().filter(|e| e.depth == depth && e.of == of) {
+        println!(
+            "{}{} ({})  {}",
+            "  ".repeat(depth),
+            edge.name,
+            edge.kind,
+            format!("{}:{}", edge.path, edge.line).cyan()
+        );
+        print_hierarchy_level(edges, crate::parsers::supertypes::simple_name(&edge.name), depth + 1);
+    }
+}
 
+/// Handle the `freq` subcommand
+fn handle_freq(
+    term: String,
+    by: Option<String>,
+    lang: Option<String>,
+    top: usize,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
     let cache = CacheManager::new(".");
-
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
-             This will scan all files in the current directory and create a .reflex/ cache.\n\
              \n\
              Example:\n\
-             $ rfx index          # Index current directory\n\
-             $ rfx stats          # Show index statistics"
-        );
+             $ rfx index                  # Index current directory\n\
+             $ rfx freq <term>            # Count occurrences".to_string()
+        ).into());
     }
 
-    let stats = cache.stats()?;
+    let options = crate::freq::FreqOptions {
+        group_by: by.as_deref().map(str::parse::<crate::freq::GroupBy>).transpose()?,
+        language: parse_language_arg(lang.as_deref())?,
+        top,
+    };
+    let report = crate::freq::term_frequency(&cache, &term, &options)?;
 
     if as_json {
         let json_output = if pretty_json {
-            serde_json::to_string_pretty(&stats)?
+            serde_json::to_string_pretty(&report)?
         } else {
-            serde_json::to_string(&stats)?
+            serde_json::to_string(&report)?
         };
         println!("{}", json_output);
-    } else {
-        println!("Reflex Index Statistics");
-        println!("=======================");
+        return Ok(());
+    }
 
-        // Show git branch info if in git repo, or (None) if not
-        let root = std::env::current_dir()?;
-        if crate::git::is_git_repo(&root) {
-            match crate::git::get_git_state(&root) {
-                Ok(git_state) => {
-                    let dirty_indicator = if git_state.dirty { " (uncommitted changes)" } else { " (clean)" };
-                    println!("Branch:         {}@{}{}",
-                             git_state.branch,
-                             &git_state.commit[..7],
-                             dirty_indicator);
+    let share = if report.total_files > 0 {
+        report.files as f64 * 100.0 / report.total_files as f64
+    } else {
+        0.0
+    };
+    println!(
+        "{}: {} occurrence(s) on {} line(s) in {} of {} file(s) ({:.1}%)",
+        report.term.bold(),
+        report.occurrences,
+        report.lines,
+        report.files,
+        report.total_files,
+        share
+    );
+
+    if !report.groups.is_empty() {
+        let width = report.groups.iter().map(|g| g.key.len()).max().unwrap_or(0);
+        println!();
+        for group in &report.groups {
+            println!(
+                "  {:<width$}  {:>6} occurrence(s)  {:>5} file(s)",
+                group.key.cyan(),
+                group.occurrences,
+                group.files,
+                width = width
+            );
+        }
+    }
 
-                    // Check if current branch is indexed
-                    match cache.get_branch_info(&git_state.branch) {
-                        Ok(branch_info) => {
-                            if branch_info.commit_sha != git_state.commit {
-                                println!("                ⚠️  Index commit mismatch (indexed: {})",
-                                         &branch_info.commit_sha[..7]);
-                            }
-                            if git_state.dirty && !branch_info.is_dirty {
-                                println!("                ⚠️  Uncommitted changes not indexed");
-                            }
-                        }
-                        Err(_) => {
-                            println!("                ⚠️  Branch not indexed");
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::warn!("Failed to get git state: {}", e);
-                }
-            }
-        } else {
-            // Not a git repository - show (None)
-            println!("Branch:         (None)");
+    if !report.co_occurring.is_empty() {
+        let width = report.co_occurring.iter().map(|c| c.identifier.len()).max().unwrap_or(0);
+        println!();
+        println!("{}", "Co-occurring identifiers (lines):".bold());
+        for co in &report.co_occurring {
+            println!("  {:<width$}  {}", co.identifier, co.lines, width = width);
         }
+    }
 
-        println!("Files indexed:  {}", stats.total_files);
-        println!("Index size:     {} bytes", stats.index_size_bytes);
-        println!("Last updated:   {}", stats.last_updated);
+    Ok(())
+}
 
-        // Display language breakdown if we have indexed files
-        if !stats.files_by_language.is_empty() {
-            println!("\nFiles by language:");
+/// Handle the `at` subcommand
+fn handle_at(location: String, as_json: bool, pretty_json: bool) -> Result<()> {
+    let location = crate::locate::Location::parse(&location)?;
+    let found = crate::locate::symbol_at(&location)?;
 
-            // Sort languages by count (descending) for consistent output
-            let mut lang_vec: Vec<_> = stats.files_by_language.iter().collect();
-            lang_vec.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&found)?
+        } else {
+            serde_json::to_string(&found)?
+        };
+        println!("{}", json_output);
+        return Ok(());
+    }
 
-            // Calculate column widths
-            let max_lang_len = lang_vec.iter().map(|(lang, _)| lang.len()).max().unwrap_or(8);
-            let lang_width = max_lang_len.max(8); // At least "Language" header width
+    let Some(symbol) = &found.symbol else {
+        println!("{}:{} is not inside any symbol (top level).", found.path, found.line);
+        return Ok(());
+    };
 
-            // Print table header
-            println!("  {:<width$}  Files  Lines", "Language", width = lang_width);
-            println!("  {}  -----  -------", "-".repeat(lang_width));
+    for (depth, ancestor) in found.ancestors.iter().chain(std::iter::once(symbol)).enumerate() {
+        println!(
+            "{}{} {}  {}",
+            "  ".repeat(depth),
+            ancestor.kind.to_string().to_lowercase().dimmed(),
+            ancestor.name.as_deref().unwrap_or("<anonymous>").bold(),
+            format!("{}:{}-{}", found.path, ancestor.span.start_line, ancestor.span.end_line).cyan(),
+        );
+    }
 
-            // Print rows
-            for (language, file_count) in lang_vec {
-                let line_count = stats.lines_by_language.get(language).copied().unwrap_or(0);
-                println!("  {:<width$}  {:5}  {:7}",
-                    language, file_count, line_count,
-                    width = lang_width);
-            }
-        }
+    if let Some(signature) = &symbol.signature {
+        println!();
+        println!("{}", signature);
     }
 
     Ok(())
 }
 
-/// Handle the `clear` subcommand
-fn handle_clear(skip_confirm: bool) -> Result<()> {
+/// Handle the `embed` subcommand
+fn handle_embed(force: bool, as_json: bool, pretty_json: bool) -> Result<()> {
     let cache = CacheManager::new(".");
-
     if !cache.exists() {
-        println!("No cache to clear.");
-        return Ok(());
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.\n\
+             \n\
+             Example:\n\
+             $ rfx index                          # Index current directory\n\
+             $ rfx embed                          # Embed the indexed code".to_string()
+        ).into());
     }
 
-    if !skip_confirm {
-        println!("This will delete the local Reflex cache at: {:?}", cache.path());
-        print!("Are you sure? [y/N] ");
-        use std::io::{self, Write};
-        io::stdout().flush()?;
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+    let config = crate::embeddings::load_config(cache.path())?;
+    let embedder = crate::embeddings::create_embedder(&config)?;
+    let stats = crate::embeddings::build(&cache, &config, embedder.as_ref(), force)?;
 
-        if !input.trim().eq_ignore_ascii_case("y") {
-            println!("Cancelled.");
-            return Ok(());
-        }
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&stats)?
+        } else {
+            serde_json::to_string(&stats)?
+        };
+        println!("{}", json_output);
+        return Ok(());
     }
 
-    cache.clear()?;
-    println!("Cache cleared successfully.");
+    println!(
+        "Embedded {} chunks from {} changed files with {}",
+        stats.embedded_chunks, stats.embedded_files, stats.model.bold()
+    );
+    println!("  Files:  {}", stats.files);
+    println!("  Chunks: {}", stats.total_chunks);
+    if stats.removed_files > 0 {
+        println!("  Removed {} files that left the index", stats.removed_files);
+    }
+    println!();
+    println!("Search by meaning with: rfx query \"<description>\" --semantic");
 
     Ok(())
 }
 
-/// Handle the `list-files` subcommand
-fn handle_list_files(as_json: bool, pretty_json: bool) -> Result<()> {
-    let cache = CacheManager::new(".");
+/// Handle the `trace-resolve` subcommand
+fn handle_trace_resolve(as_json: bool, pretty_json: bool) -> Result<()> {
+    use std::io::Read;
 
+    let cache = CacheManager::new(".");
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
-             This will scan all files in the current directory and create a .reflex/ cache.\n\
              \n\
              Example:\n\
-             $ rfx index            # Index current directory\n\
-             $ rfx list-files       # List indexed files"
-        );
+             $ rfx index                          # Index current directory\n\
+             $ pbpaste | rfx trace-resolve        # Resolve a copied stack trace".to_string()
+        ).into());
     }
 
-    let files = cache.list_files()?;
+    let mut trace = String::new();
+    std::io::stdin()
+        .read_to_string(&mut trace)
+        .context("Failed to read stack trace from stdin")?;
+
+    let resolved = crate::trace::resolve_trace(&cache, &trace)?;
 
     if as_json {
         let json_output = if pretty_json {
-            serde_json::to_string_pretty(&files)?
+            serde_json::to_string_pretty(&resolved)?
         } else {
-            serde_json::to_string(&files)?
+            serde_json::to_string(&resolved)?
         };
         println!("{}", json_output);
-    } else if files.is_empty() {
-        println!("No files indexed yet.");
-    } else {
-        println!("Indexed Files ({} total):", files.len());
+        return Ok(());
+    }
+
+    if resolved.frames.is_empty() {
+        println!("No stack frames found in input (expected a Python, Java, Node or Rust trace).");
+        return Ok(());
+    }
+
+    if let Some(error) = &resolved.error {
+        println!("{}", error.bold());
         println!();
-        for file in files {
-            println!("  {} ({})",
-                     file.path,
-                     file.language);
+    }
+
+    for frame in &resolved.frames {
+        let function = frame.frame.function.as_deref().unwrap_or("");
+        match &frame.resolved {
+            Some(location) => {
+                let symbol = location.symbol.as_ref()
+                    .and_then(|s| s.name.as_deref())
+                    .unwrap_or(function);
+                println!("{}  {}", format!("{}:{}", location.path, frame.frame.line).cyan(), symbol.bold());
+                if !location.preview.is_empty() {
+                    println!("    {}", location.preview);
+                }
+                if !location.alternatives.is_empty() {
+                    println!("    {} also matches: {}", "•".dimmed(), location.alternatives.join(", "));
+                }
+            }
+            None => {
+                println!("{}  {}", format!("{}:{}", frame.frame.file, frame.frame.line).dimmed(), function.dimmed());
+            }
         }
     }
+    println!();
+    println!("{} of {} frame(s) resolved to indexed files.", resolved.resolved_frames, resolved.total_frames);
+
+    Ok(())
+}
+
+/// Handle the `self-update` subcommand
+fn handle_self_update(
+    channel: Option<String>,
+    check_only: bool,
+    force: bool,
+    as_json: bool,
+    pretty_json: bool,
+) -> Result<()> {
+    let channel = channel
+        .as_deref()
+        .map(crate::self_update::Channel::parse)
+        .transpose()?;
+
+    let report = crate::self_update::run(channel, check_only, force)?;
+
+    if as_json {
+        let json_output = if pretty_json {
+            serde_json::to_string_pretty(&report)?
+        } else {
+            serde_json::to_string(&report)?
+        };
+        println!("{}", json_output);
+    } else if report.updated {
+        println!("Updated rfx {} → {} ({} channel)",
+            report.current_version, report.latest_version, report.channel.as_str());
+    } else if report.update_available {
+        println!("Update available: {} → {} ({} channel)",
+            report.current_version, report.latest_version, report.channel.as_str());
+        println!("  Run 'rfx self-update' to install it");
+    } else {
+        println!("rfx {} is up to date ({} channel)", report.current_version, report.channel.as_str());
+    }
 
     Ok(())
 }
@@ -2120,8 +6842,15 @@ fn handle_mcp() -> Result<()> {
 }
 
 /// Handle the internal `index-symbols-internal` command
-fn handle_index_symbols_internal(cache_dir: PathBuf) -> Result<()> {
-    let mut indexer = crate::background_indexer::BackgroundIndexer::new(&cache_dir)?;
+fn handle_index_symbols_internal(cache_dir: PathBuf, lang: Vec<String>, glob: Vec<String>) -> Result<()> {
+    let mut languages = Vec::new();
+    for name in &lang {
+        languages.extend(parse_language_arg(Some(name))?);
+    }
+    let scope = crate::background_indexer::SymbolScope { languages, globs: glob };
+
+    let mut indexer = crate::background_indexer::BackgroundIndexer::new(&cache_dir)?
+        .with_scope(scope);
     indexer.run()?;
     Ok(())
 }
@@ -2133,17 +6862,20 @@ fn handle_analyze(
     hotspots: bool,
     min_dependents: usize,
     unused: bool,
+    dead_symbols: bool,
+    min_confidence: Option<String>,
     islands: bool,
     min_island_size: usize,
     max_island_size: Option<usize>,
+    resolve_barrels: bool,
     format: String,
     as_json: bool,
     pretty_json: bool,
     count_only: bool,
     all: bool,
     plain: bool,
-    _glob_patterns: Vec<String>,
-    _exclude_patterns: Vec<String>,
+    glob_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
     _force: bool,
     limit: Option<usize>,
     offset: Option<usize>,
@@ -2156,18 +6888,18 @@ fn handle_analyze(
     let cache = CacheManager::new(".");
 
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
              \n\
              Example:\n\
              $ rfx index             # Index current directory\n\
-             $ rfx analyze           # Run dependency analysis"
-        );
+             $ rfx analyze           # Run dependency analysis".to_string()
+        ).into());
     }
 
-    let deps_index = DependencyIndex::new(cache);
+    let deps_index = DependencyIndex::new(cache.clone()).with_barrel_resolution(resolve_barrels);
 
     // JSON mode overrides format
     let format = if as_json { "json" } else { &format };
@@ -2181,8 +6913,37 @@ fn handle_analyze(
         Some(200)  // Default: limit to 200 results per page for token efficiency
     };
 
+    // SARIF collects the findings of every selected analysis into one log
+    if format == "sarif" {
+        if hotspots || islands {
+            anyhow::bail!(
+                "--hotspots and --islands describe the dependency graph rather than problems in it, \
+                 so they have no SARIF form.\n\
+                 \n\
+                 SARIF output supports --circular, --unused and --dead-symbols."
+            );
+        }
+        let all_analyses = !circular && !unused && !dead_symbols;
+        let options = crate::dead_symbols::DeadSymbolOptions {
+            min_confidence: min_confidence.as_deref().map(str::parse).transpose()?.unwrap_or(crate::dead_symbols::Confidence::Low),
+            glob_patterns,
+            exclude_patterns,
+        };
+        return handle_analyze_sarif(&cache, &deps_index, circular || all_analyses, unused || all_analyses, dead_symbols || all_analyses, &options, pretty_json);
+    }
+
+    // Graph formats render a single graph instead of per-analysis listings
+    if let Some(graph_format) = crate::graph_export::GraphFormat::parse(format) {
+        if dead_symbols {
+            anyhow::bail!("--dead-symbols lists symbols, not files, and can't be rendered as a graph (--format {})", format);
+        }
+        let graph = analyze_graph(&deps_index, circular, hotspots, min_dependents, unused, islands, min_island_size, max_island_size)?;
+        println!("{}", graph.render(graph_format, pretty_json)?.trim_end());
+        return Ok(());
+    }
+
     // If no specific flags, show summary
-    if !circular && !hotspots && !unused && !islands {
+    if !circular && !hotspots && !unused && !dead_symbols && !islands {
         return handle_analyze_summary(&deps_index, min_dependents, count_only, as_json, pretty_json);
     }
 
@@ -2199,6 +6960,15 @@ fn handle_analyze(
         handle_deps_unused(&deps_index, format, pretty_json, final_limit, offset, count_only, plain)?;
     }
 
+    if dead_symbols {
+        let options = crate::dead_symbols::DeadSymbolOptions {
+            min_confidence: min_confidence.as_deref().map(str::parse).transpose()?.unwrap_or(crate::dead_symbols::Confidence::Low),
+            glob_patterns,
+            exclude_patterns,
+        };
+        handle_dead_symbols(&cache, &options, format, pretty_json, final_limit, offset, count_only)?;
+    }
+
     if islands {
         handle_deps_islands(&deps_index, format, pretty_json, final_limit, offset, min_island_size, max_island_size, count_only, plain, sort.clone())?;
     }
@@ -2206,6 +6976,86 @@ fn handle_analyze(
     Ok(())
 }
 
+/// Print `rfx analyze --format sarif` findings as a SARIF log
+fn handle_analyze_sarif(
+    cache: &CacheManager,
+    deps_index: &crate::dependency::DependencyIndex,
+    circular: bool,
+    unused: bool,
+    dead_symbols: bool,
+    options: &crate::dead_symbols::DeadSymbolOptions,
+    pretty_json: bool,
+) -> Result<()> {
+    let mut findings = Vec::new();
+    if circular {
+        let cycles = deps_index.detect_circular_dependencies()?;
+        findings.extend(crate::sarif::circular_findings(deps_index, &cycles)?);
+    }
+    if unused {
+        let unused_files = deps_index.find_unused_files()?;
+        findings.extend(crate::sarif::unused_findings(deps_index, &unused_files)?);
+    }
+    if dead_symbols {
+        let report = crate::dead_symbols::find_dead_symbols(cache, options)?;
+        findings.extend(crate::sarif::dead_symbol_findings(&report));
+    }
+
+    println!("{}", crate::sarif::SarifLog::new(&crate::sarif::analysis_rules(), &findings).render(pretty_json)?);
+    eprintln!("Found {} findings", findings.len());
+    Ok(())
+}
+
+/// Build the graph rendered by `rfx analyze --format dot|mermaid|graph`
+///
+/// Without analysis flags this is the whole import graph. With flags it is the
+/// subgraph of the files they select; hotspots bring their direct importers.
+#[allow(clippy::too_many_arguments)]
+fn analyze_graph(
+    deps_index: &crate::dependency::DependencyIndex,
+    circular: bool,
+    hotspots: bool,
+    min_dependents: usize,
+    unused: bool,
+    islands: bool,
+    min_island_size: usize,
+    max_island_size: Option<usize>,
+) -> Result<crate::graph_export::DependencyGraph> {
+    use crate::graph_export::DependencyGraph;
+
+    if !circular && !hotspots && !unused && !islands {
+        return DependencyGraph::full(deps_index);
+    }
+
+    let mut selected: std::collections::HashSet<i64> = std::collections::HashSet::new();
+
+    if circular {
+        selected.extend(deps_index.detect_circular_dependencies()?.into_iter().flatten());
+    }
+
+    if hotspots {
+        for (file_id, _) in deps_index.find_hotspots(None, min_dependents)? {
+            selected.insert(file_id);
+            selected.extend(deps_index.get_dependents(file_id)?);
+        }
+    }
+
+    if unused {
+        selected.extend(deps_index.find_unused_files()?);
+    }
+
+    if islands {
+        // Same size bounds as `rfx analyze --islands`
+        let total_files = deps_index.get_cache().stats()?.total_files;
+        let max_size = max_island_size.unwrap_or_else(|| ((total_files as f64 * 0.5) as usize).min(500));
+        selected.extend(deps_index.find_islands()?
+            .into_iter()
+            .filter(|island| island.len() >= min_island_size && island.len() <= max_size)
+            .flatten());
+    }
+
+    DependencyGraph::induced(deps_index, &selected)
+}
+
 /// Handle analyze summary (default --analyze behavior)
 fn handle_analyze_summary(
     deps_index: &crate::dependency::DependencyIndex,
@@ -2273,6 +7123,7 @@ fn handle_deps(
     file: PathBuf,
     reverse: bool,
     depth: usize,
+    resolve_barrels: bool,
     format: String,
     as_json: bool,
     pretty_json: bool,
@@ -2284,18 +7135,18 @@ fn handle_deps(
     let cache = CacheManager::new(".");
 
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
              \n\
              Example:\n\
              $ rfx index          # Index current directory\n\
-             $ rfx deps <file>    # Analyze dependencies"
-        );
+             $ rfx deps <file>    # Analyze dependencies".to_string()
+        ).into());
     }
 
-    let deps_index = DependencyIndex::new(cache);
+    let deps_index = DependencyIndex::new(cache).with_barrel_resolution(resolve_barrels);
 
     // JSON mode overrides format
     let format = if as_json { "json" } else { &format };
@@ -2307,6 +7158,19 @@ fn handle_deps(
     let file_id = deps_index.get_file_id_by_path(&file_str)?
         .ok_or_else(|| anyhow::anyhow!("File '{}' not found in index", file_str))?;
 
+    // Graph formats render the file with its transitive dependencies (or dependents)
+    if let Some(graph_format) = crate::graph_export::GraphFormat::parse(format) {
+        let reached = if reverse {
+            deps_index.get_transitive_dependents(file_id, depth)?
+        } else {
+            deps_index.get_transitive_deps(file_id, depth)?
+        };
+        let file_ids: std::collections::HashSet<i64> = reached.into_keys().collect();
+        let graph = crate::graph_export::DependencyGraph::induced(&deps_index, &file_ids)?;
+        println!("{}", graph.render(graph_format, pretty_json)?.trim_end());
+        return Ok(());
+    }
+
     if reverse {
         // Show dependents (who imports this file)
         let dependents = deps_index.get_dependents(file_id)?;
@@ -2349,7 +7213,7 @@ fn handle_deps(
                 eprintln!("\nFound {} dependents", dependents.len());
             }
             _ => {
-                anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot", format);
+                anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot, mermaid, graph", format);
             }
         }
     } else {
@@ -2358,6 +7222,20 @@ fn handle_deps(
             // Direct dependencies only
             let deps = deps_index.get_dependencies(file_id)?;
 
+            // Through barrels the import path no longer names the file, so show both
+            let barrel_paths = if resolve_barrels {
+                let ids: Vec<i64> = deps.iter().filter_map(|dep| dep.resolved_file_id).collect();
+                deps_index.get_file_paths(&ids)?
+            } else {
+                std::collections::HashMap::new()
+            };
+            let display_path = |dep: &crate::models::Dependency| -> String {
+                match dep.resolved_file_id.and_then(|id| barrel_paths.get(&id)) {
+                    Some(path) => format!("{} → {}", dep.imported_path, path),
+                    None => dep.imported_path.clone(),
+                }
+            };
+
             match format.as_ref() {
                 "json" => {
                     let output: Vec<_> = deps.iter()
@@ -2390,7 +7268,7 @@ fn handle_deps(
                             crate::models::ImportType::External => "[external]",
                             crate::models::ImportType::Stdlib => "[stdlib]",
                         };
-                        println!("  └─ {} {} (line {})", dep.imported_path, type_label, dep.line_number);
+                        println!("  └─ {} {} (line {})", display_path(dep), type_label, dep.line_number);
                     }
                     eprintln!("\nFound {} dependencies", deps.len());
                 }
@@ -2403,12 +7281,12 @@ fn handle_deps(
                             crate::models::ImportType::External => "external",
                             crate::models::ImportType::Stdlib => "stdlib",
                         };
-                        println!("{:<28}  {:<9}  {}", dep.imported_path, type_str, dep.line_number);
+                        println!("{:<28}  {:<9}  {}", display_path(dep), type_str, dep.line_number);
                     }
                     eprintln!("\nFound {} dependencies", deps.len());
                 }
                 _ => {
-                    anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot", format);
+                    anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot, mermaid, graph", format);
                 }
             }
         } else {
@@ -2474,7 +7352,7 @@ fn handle_deps(
                     eprintln!("\nFound {} transitive dependencies", transitive.len());
                 }
                 _ => {
-                    anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot", format);
+                    anyhow::bail!("Unknown format '{}'. Supported: json, tree, table, dot, mermaid, graph", format);
                 }
             }
         }
@@ -2500,6 +7378,7 @@ fn handle_ask(
     quiet: bool,
     answer: bool,
     interactive: bool,
+    chat: bool,
     debug: bool,
 ) -> Result<()> {
     // If --configure flag is set, launch the configuration wizard
@@ -2522,6 +7401,30 @@ fn handle_ask(
         );
     }
 
+    if chat {
+        log::info!("Launching line-based chat mode");
+        let cache = CacheManager::new(".");
+
+        if !cache.exists() {
+            return Err(ReflexError::IndexMissing(
+                "No index found in current directory.\n\
+                 \n\
+                 Run 'rfx index' to build the code search index first.\n\
+                 \n\
+                 Example:\n\
+                 $ rfx index                          # Index current directory\n\
+                 $ rfx ask --chat                     # Start a chat".to_string()
+            ).into());
+        }
+
+        return crate::semantic::run_repl(&cache, crate::semantic::ReplOptions {
+            provider_override,
+            additional_context,
+            answer,
+            debug,
+        });
+    }
+
     // If no question provided and not in configure mode, default to interactive mode
     // If --interactive flag is set, launch interactive chat mode (TUI)
     if interactive || question.is_none() {
@@ -2529,15 +7432,15 @@ fn handle_ask(
         let cache = CacheManager::new(".");
 
         if !cache.exists() {
-            anyhow::bail!(
+            return Err(ReflexError::IndexMissing(
                 "No index found in current directory.\n\
                  \n\
                  Run 'rfx index' to build the code search index first.\n\
                  \n\
                  Example:\n\
                  $ rfx index                          # Index current directory\n\
-                 $ rfx ask                            # Launch interactive chat"
-            );
+                 $ rfx ask                            # Launch interactive chat".to_string()
+            ).into());
         }
 
         return crate::semantic::run_chat_mode(cache, provider_override, None);
@@ -2551,15 +7454,15 @@ fn handle_ask(
     let cache = CacheManager::new(".");
 
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
              \n\
              Example:\n\
              $ rfx index                          # Index current directory\n\
-             $ rfx ask \"Find all TODOs\"          # Ask questions"
-        );
+             $ rfx ask \"Find all TODOs\"          # Ask questions".to_string()
+        ).into());
     }
 
     // Create a tokio runtime for async operations
@@ -2725,6 +7628,10 @@ fn handle_ask(
         None
     };
 
+    let citations = generated_answer.as_deref()
+        .map(|text| crate::semantic::answer::extract_citations(text, &results))
+        .unwrap_or_default();
+
     // Output in JSON format if requested
     if as_json {
         // Build AgenticQueryResponse for JSON output (includes both queries and results)
@@ -2735,6 +7642,7 @@ fn handle_ask(
             gathered_context: gathered_context.clone(),
             tools_executed: None, // No tools in non-agentic mode
             answer: generated_answer,
+            citations,
         };
 
         let json_str = if pretty_json {
@@ -2778,6 +7686,20 @@ fn handle_ask(
         termimad::print_text(&answer_text);
         println!();
 
+        // List the cited locations (unverified ones are in the results' files but outside the matches)
+        if !citations.is_empty() {
+            println!("{}", "Sources:".bold());
+            for citation in &citations {
+                let location = format!("{}:{}", citation.path, citation.line);
+                if citation.verified {
+                    println!("  {}", location.bright_cyan());
+                } else {
+                    println!("  {} {}", location.bright_cyan(), "(outside the matched lines)".dimmed());
+                }
+            }
+            println!();
+        }
+
         // Show summary of results used
         if !results.is_empty() {
             println!(
@@ -2836,21 +7758,25 @@ fn handle_context(
     entry_points: bool,
     test_layout: bool,
     config_files: bool,
+    conventions: bool,
     depth: usize,
     json: bool,
+    summary: bool,
+    provider: Option<String>,
+    refresh: bool,
 ) -> Result<()> {
     let cache = CacheManager::new(".");
 
     if !cache.exists() {
-        anyhow::bail!(
+        return Err(ReflexError::IndexMissing(
             "No index found in current directory.\n\
              \n\
              Run 'rfx index' to build the code search index first.\n\
              \n\
              Example:\n\
              $ rfx index                  # Index current directory\n\
-             $ rfx context                # Generate context"
-        );
+             $ rfx context                # Generate context".to_string()
+        ).into());
     }
 
     // Build context options
@@ -2863,10 +7789,23 @@ fn handle_context(
         entry_points,
         test_layout,
         config_files,
+        conventions,
         depth,
         json,
     };
 
+    if summary {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let brief = runtime.block_on(crate::context::summary::summarize(&cache, &opts, provider, refresh))
+            .context("Failed to summarize codebase")?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&brief)?);
+        } else {
+            println!("{}", brief.summary);
+        }
+        return Ok(());
+    }
+
     // Generate context
     let context_output = crate::context::generate_context(&cache, &opts)
         .context("Failed to generate codebase context")?;
@@ -3170,6 +8109,86 @@ fn handle_deps_hotspots(
     Ok(())
 }
 
+/// Handle --dead-symbols flag (exported symbols without references)
+fn handle_dead_symbols(
+    cache: &CacheManager,
+    options: &crate::dead_symbols::DeadSymbolOptions,
+    format: &str,
+    pretty_json: bool,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    count_only: bool,
+) -> Result<()> {
+    let report = crate::dead_symbols::find_dead_symbols(cache, options)?;
+    let total_count = report.symbols.len();
+
+    if count_only {
+        println!("Found {} dead symbols", total_count);
+        return Ok(());
+    }
+
+    // Apply offset pagination and limit
+    let offset_val = offset.unwrap_or(0);
+    let mut symbols: Vec<_> = report.symbols.into_iter().skip(offset_val).collect();
+    if let Some(lim) = limit {
+        symbols.truncate(lim);
+    }
+    let count = symbols.len();
+    let has_more = offset_val + count < total_count;
+
+    if format == "json" {
+        let output = serde_json::json!({
+            "pagination": {
+                "total": total_count,
+                "count": count,
+                "offset": offset_val,
+                "limit": limit,
+                "has_more": has_more,
+            },
+            "candidates": report.candidates,
+            "results": symbols,
+        });
+
+        let json_str = if pretty_json {
+            serde_json::to_string_pretty(&output)?
+        } else {
+            serde_json::to_string(&output)?
+        };
+        println!("{}", json_str);
+        eprintln!("Found {} dead symbols ({} exported symbols checked)", total_count, report.candidates);
+        return Ok(());
+    }
+
+    if symbols.is_empty() {
+        println!("No dead symbols found ({} exported symbols checked).", report.candidates);
+        return Ok(());
+    }
+
+    println!("Dead Symbols (Exported, Never Referenced Elsewhere):");
+    for (idx, symbol) in symbols.iter().enumerate() {
+        println!(
+            "  {}. {}:{} {} {} [{}: {}]",
+            offset_val + idx + 1,
+            symbol.path,
+            symbol.line,
+            symbol.kind,
+            symbol.qualified_name.as_deref().unwrap_or(&symbol.name),
+            symbol.confidence,
+            symbol.reason
+        );
+    }
+    if total_count > count {
+        eprintln!("\nFound {} dead symbols ({} total)", count, total_count);
+        if has_more {
+            eprintln!("Use --limit and --offset to paginate");
+        }
+    } else {
+        eprintln!("\nFound {} dead symbols", count);
+    }
+
+    Ok(())
+}
+
 /// Handle --unused flag (orphaned files)
 fn handle_deps_unused(
     deps_index: &crate::dependency::DependencyIndex,
@@ -0,0 +1,504 @@
+//! Environment variable and config key usage indexing
+//!
+//! This module recognizes reads and writes of environment variables and
+//! configuration keys and records each one in `meta.db`, so `rfx config-keys`
+//! can show where a setting is consumed across services.
+//!
+//! # Recognized patterns
+//!
+//! Environment variables (`env`):
+//! - JavaScript/TypeScript: `process.env.X`, `process.env["X"]`, `import.meta.env.X`, `Deno.env.get("X")`
+//! - Python: `os.environ["X"]`, `os.environ.get("X")`, `os.getenv("X")`, `os.environ.setdefault("X", ...)`
+//! - Rust: `std::env::var("X")`, `env::var_os("X")`, `env!("X")`, `env::set_var("X", ...)`
+//! - Go: `os.Getenv("X")`, `os.LookupEnv("X")`, `os.Setenv("X", ...)`
+//! - Ruby `ENV["X"]`, PHP `getenv('X')`/`$_ENV['X']`/`env('X')`, Java `System.getenv("X")`,
+//!   C# `Environment.GetEnvironmentVariable("X")`, C `getenv("X")`/`setenv("X", ...)`
+//!
+//! Config keys (`config`):
+//! - Laravel `config('app.name')`, `Config::get(...)`/`Config::set(...)`, `config(['app.name' => ...])`
+//! - `config.get("db.host")` (node-config and similar), Go viper `viper.GetString("db.host")`/`viper.Set(...)`
+//! - Spring `@Value("${db.host}")`, Java `System.getProperty("db.host")`/`System.setProperty(...)`
+//! - .NET `Configuration["Db:Host"]`
+//!
+//! Subscript and property accesses count as writes when they're assigned to
+//! (`process.env.X = ...`, `os.environ["X"] = ...`) or deleted.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fmt;
+
+use crate::cache::{CacheManager, META_DB};
+
+/// Maximum length (in characters) of the stored source line preview
+const MAX_PREVIEW_LENGTH: usize = 200;
+
+/// What a pattern's match means for the key
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    Read,
+    Write,
+    /// A read, unless the expression is assigned to or deleted
+    Access,
+}
+
+/// Built-in (kind, mode, regex) triples; capture group 1 is the key
+const PATTERNS: &[(KeyKind, Mode, &str)] = &[
+    // Environment variables
+    (KeyKind::Env, Mode::Access, r"\bprocess\.env\.([A-Za-z_]\w*)"),
+    (KeyKind::Env, Mode::Access, r#"\bprocess\.env\[\s*["'`]([A-Za-z_]\w*)["'`]\s*\]"#),
+    (KeyKind::Env, Mode::Access, r#"(?:\bos\.environ|\bENV|\$_ENV)\[\s*["']([A-Za-z_]\w*)["']\s*\]"#),
+    (KeyKind::Env, Mode::Read, r"\bimport\.meta\.env\.([A-Za-z_]\w*)"),
+    (
+        KeyKind::Env,
+        Mode::Read,
+        r#"\b(?:getenv|secure_getenv|Getenv|LookupEnv|GetEnvironmentVariable|environ\.get|ENV\.fetch|Deno\.env\.get|env::var|env::var_os|env!|env)\(\s*["']([A-Za-z_]\w*)["']"#,
+    ),
+    (
+        KeyKind::Env,
+        Mode::Write,
+        r#"\b(?:setenv|unsetenv|putenv|Setenv|Unsetenv|SetEnvironmentVariable|environ\.setdefault|environ\.pop|Deno\.env\.set|Deno\.env\.delete|env::set_var|env::remove_var)\(\s*["']([A-Za-z_]\w*)"#,
+    ),
+    // Config keys
+    (
+        KeyKind::Config,
+        Mode::Read,
+        r#"\b(?:config|Config::get|config\.get|config\.has|viper\.Get\w*|viper\.IsSet|System\.getProperty|getProperty)\(\s*["']([\w.:\-]+)["']"#,
+    ),
+    (
+        KeyKind::Config,
+        Mode::Write,
+        r#"\b(?:Config::set|viper\.Set|viper\.SetDefault|System\.setProperty)\(\s*["']([\w.:\-]+)["']"#,
+    ),
+    (KeyKind::Config, Mode::Write, r#"\bconfig\(\s*\[\s*["']([\w.:\-]+)["']\s*=>"#),
+    (KeyKind::Config, Mode::Read, r#"@Value\(\s*"\$\{([\w.:\-]+?)(?::[^}]*)?\}"#),
+    (KeyKind::Config, Mode::Access, r#"[cC]onfiguration\[\s*"([\w.:\-]+)"\s*\]"#),
+];
+
+/// Whether a key is an environment variable or an application config key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyKind {
+    Env,
+    Config,
+}
+
+impl KeyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Env => "env",
+            Self::Config => "config",
+        }
+    }
+}
+
+impl fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for KeyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "env" | "environment" => Ok(Self::Env),
+            "config" => Ok(Self::Config),
+            other => anyhow::bail!("Unknown key kind '{}'. Supported: env, config", other),
+        }
+    }
+}
+
+/// Whether a usage reads or writes the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// A key usage extracted from source (before file ID resolution)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigKeyInfo {
+    /// Key as written in source
+    pub key: String,
+    pub kind: KeyKind,
+    pub access: Access,
+    /// Line number of the usage (1-indexed)
+    pub line_number: usize,
+    /// Trimmed source line
+    pub preview: String,
+}
+
+/// Compiled set of env/config key patterns
+pub struct ConfigKeyMatcher {
+    patterns: Vec<(KeyKind, Mode, Regex)>,
+    /// Assignment (not comparison) following an access
+    assignment: Regex,
+}
+
+impl ConfigKeyMatcher {
+    pub fn new() -> Self {
+        Self {
+            patterns: PATTERNS
+                .iter()
+                .map(|(kind, mode, pattern)| (*kind, *mode, Regex::new(pattern).unwrap()))
+                .collect(),
+            assignment: Regex::new(r"^\s*(?:\|\||\?\?|\+)?=(?:[^=]|$)").unwrap(),
+        }
+    }
+
+    /// Extract all key usages from file content
+    pub fn extract(&self, content: &str) -> Vec<ConfigKeyInfo> {
+        let mut usages = Vec::new();
+
+        for (idx, line) in content.lines().enumerate() {
+            for (kind, mode, regex) in &self.patterns {
+                for caps in regex.captures_iter(line) {
+                    let (Some(whole), Some(key)) = (caps.get(0), caps.get(1)) else {
+                        continue;
+                    };
+                    let access = match mode {
+                        Mode::Read => Access::Read,
+                        Mode::Write => Access::Write,
+                        Mode::Access => {
+                            let assigned = self.assignment.is_match(&line[whole.end()..]);
+                            let deleted = line[..whole.start()].trim_end().ends_with("delete");
+                            if assigned || deleted { Access::Write } else { Access::Read }
+                        }
+                    };
+                    usages.push(ConfigKeyInfo {
+                        key: key.as_str().to_string(),
+                        kind: *kind,
+                        access,
+                        line_number: idx + 1,
+                        preview: line.trim().chars().take(MAX_PREVIEW_LENGTH).collect(),
+                    });
+                }
+            }
+        }
+
+        usages.sort_by(|a, b| (a.line_number, &a.key).cmp(&(b.line_number, &b.key)));
+        usages.dedup_by(|a, b| a.key == b.key && a.line_number == b.line_number && a.access == b.access);
+        usages
+    }
+}
+
+impl Default for ConfigKeyMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Aggregated usage counts for a single key
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigKeySummary {
+    pub key: String,
+    pub kind: String,
+    pub reads: usize,
+    pub writes: usize,
+    pub files: usize,
+}
+
+/// A single stored key usage with its file path
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigKeyUsage {
+    pub key: String,
+    pub kind: String,
+    pub access: String,
+    pub path: String,
+    pub line: usize,
+    pub preview: String,
+}
+
+/// Create the key usage table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and by the
+/// meta.db 5 -> 6 migration.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS config_keys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            access TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            preview TEXT NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_config_keys_key ON config_keys(key)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_config_keys_file ON config_keys(file_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Manages key usage storage and lookups
+pub struct ConfigKeyIndex {
+    cache: CacheManager,
+}
+
+impl ConfigKeyIndex {
+    /// Create a new key index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for config key index")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Replace the stored key usages for a set of files in one transaction
+    ///
+    /// Files with an empty usage list are cleared, so removed reads disappear
+    /// on reindex.
+    pub fn replace_file_keys(&self, files: &[(i64, Vec<ConfigKeyInfo>)]) -> Result<usize> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+
+        {
+            let mut delete_stmt = tx.prepare("DELETE FROM config_keys WHERE file_id = ?")?;
+            let mut insert_stmt = tx.prepare(
+                "INSERT INTO config_keys (file_id, key, kind, access, line_number, preview)
+                 VALUES (?, ?, ?, ?, ?, ?)"
+            )?;
+
+            for (file_id, usages) in files {
+                delete_stmt.execute([file_id])?;
+                for usage in usages {
+                    insert_stmt.execute(rusqlite::params![
+                        file_id,
+                        usage.key,
+                        usage.kind.as_str(),
+                        usage.access.as_str(),
+                        usage.line_number as i64,
+                        usage.preview,
+                    ])?;
+                    inserted += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Stored {} config key usages for {} files", inserted, files.len());
+        Ok(inserted)
+    }
+
+    /// List all known keys with read/write and file counts, sorted by key
+    pub fn list_keys(&self, kind: Option<KeyKind>) -> Result<Vec<ConfigKeySummary>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT key, kind,
+                    SUM(access = 'read'), SUM(access = 'write'), COUNT(DISTINCT file_id)
+             FROM config_keys
+             WHERE ?1 IS NULL OR kind = ?1
+             GROUP BY key, kind
+             ORDER BY key, kind"
+        )?;
+
+        let keys = stmt.query_map([kind.map(|k| k.as_str())], |row| {
+            Ok(ConfigKeySummary {
+                key: row.get(0)?,
+                kind: row.get(1)?,
+                reads: row.get::<_, i64>(2)? as usize,
+                writes: row.get::<_, i64>(3)? as usize,
+                files: row.get::<_, i64>(4)? as usize,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(keys)
+    }
+
+    /// Find every read and write of a key, sorted by path and line
+    pub fn find_usages(&self, key: &str, kind: Option<KeyKind>) -> Result<Vec<ConfigKeyUsage>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT ck.key, ck.kind, ck.access, f.path, ck.line_number, ck.preview
+             FROM config_keys ck
+             JOIN files f ON ck.file_id = f.id
+             WHERE ck.key = ?1 AND (?2 IS NULL OR ck.kind = ?2)
+             ORDER BY f.path, ck.line_number"
+        )?;
+
+        let usages = stmt.query_map(rusqlite::params![key, kind.map(|k| k.as_str())], |row| {
+            Ok(ConfigKeyUsage {
+                key: row.get(0)?,
+                kind: row.get(1)?,
+                access: row.get(2)?,
+                path: row.get(3)?,
+                line: row.get::<_, i64>(4)? as usize,
+                preview: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(usages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn keys(source: &str) -> Vec<(String, KeyKind, Access)> {
+        ConfigKeyMatcher::new()
+            .extract(source)
+            .into_iter()
+            .map(|u| (u.key, u.kind, u.access))
+            .collect()
+    }
+
+    fn env(key: &str, access: Access) -> (String, KeyKind, Access) {
+        (key.to_string(), KeyKind::Env, access)
+    }
+
+    fn config(key: &str, access: Access) -> (String, KeyKind, Access) {
+        (key.to_string(), KeyKind::Config, access)
+    }
+
+    #[test]
+    fn test_extract_env_reads_across_languages() {
+        let source = "\
+const url = process.env.DATABASE_URL || process.env['FALLBACK_URL'];
+host = os.environ.get(\"DB_HOST\") or os.getenv('DB_PORT')
+let key = std::env::var(\"API_KEY\")?;
+token := os.Getenv(\"TOKEN\")
+secret = ENV.fetch(\"SECRET\")
+String home = System.getenv(\"HOME\");
+";
+        assert_eq!(keys(source), vec![
+            env("DATABASE_URL", Access::Read),
+            env("FALLBACK_URL", Access::Read),
+            env("DB_HOST", Access::Read),
+            env("DB_PORT", Access::Read),
+            env("API_KEY", Access::Read),
+            env("TOKEN", Access::Read),
+            env("SECRET", Access::Read),
+            env("HOME", Access::Read),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_env_writes() {
+        let source = "\
+process.env.NODE_ENV = 'test';
+if (process.env.CI === 'true') {}
+os.environ[\"LANG\"] = \"C\"
+std::env::set_var(\"RUST_LOG\", \"debug\");
+delete process.env.TEMP_DIR;
+";
+        assert_eq!(keys(source), vec![
+            env("NODE_ENV", Access::Write),
+            env("CI", Access::Read),
+            env("LANG", Access::Write),
+            env("RUST_LOG", Access::Write),
+            env("TEMP_DIR", Access::Write),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_config_keys() {
+        let source = "\
+$name = config('app.name');
+config(['app.locale' => 'fr']);
+port := viper.GetInt(\"server.port\")
+@Value(\"${db.pool.size:10}\")
+var conn = Configuration[\"ConnectionStrings:Default\"];
+const timeout = config.get('http.timeout');
+";
+        assert_eq!(keys(source), vec![
+            config("app.name", Access::Read),
+            config("app.locale", Access::Write),
+            config("server.port", Access::Read),
+            config("db.pool.size", Access::Read),
+            config("ConnectionStrings:Default", Access::Read),
+            config("http.timeout", Access::Read),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_ignores_non_literal_keys() {
+        assert!(keys("let v = std::env::var(name)?;\nconfig(key)\n").is_empty());
+    }
+
+    #[test]
+    fn test_replace_and_query_keys() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        cache.update_file("api/server.js", "JavaScript", 10).unwrap();
+        cache.update_file("worker/main.py", "Python", 10).unwrap();
+
+        let ids = cache.batch_get_file_ids(&["api/server.js".to_string(), "worker/main.py".to_string()]).unwrap();
+        let api = ids["api/server.js"];
+        let worker = ids["worker/main.py"];
+
+        let usage = |key: &str, kind: KeyKind, access: Access, line: usize| ConfigKeyInfo {
+            key: key.to_string(),
+            kind,
+            access,
+            line_number: line,
+            preview: String::new(),
+        };
+
+        let index = ConfigKeyIndex::new(cache.clone());
+        index.replace_file_keys(&[
+            (api, vec![usage("DATABASE_URL", KeyKind::Env, Access::Read, 3), usage("app.port", KeyKind::Config, Access::Read, 5)]),
+            (worker, vec![usage("DATABASE_URL", KeyKind::Env, Access::Write, 1)]),
+        ]).unwrap();
+
+        let all = index.list_keys(None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].key, "DATABASE_URL");
+        assert_eq!((all[0].reads, all[0].writes, all[0].files), (1, 1, 2));
+
+        let config_only = index.list_keys(Some(KeyKind::Config)).unwrap();
+        assert_eq!(config_only.len(), 1);
+        assert_eq!(config_only[0].key, "app.port");
+
+        let usages = index.find_usages("DATABASE_URL", None).unwrap();
+        assert_eq!(usages.len(), 2);
+        assert_eq!(usages[0].path, "api/server.js");
+        assert_eq!(usages[1].access, "write");
+        assert!(index.find_usages("DATABASE_URL", Some(KeyKind::Config)).unwrap().is_empty());
+
+        // Reindexing a file without usages clears its old ones
+        index.replace_file_keys(&[(worker, vec![])]).unwrap();
+        assert_eq!(index.find_usages("DATABASE_URL", None).unwrap().len(), 1);
+    }
+}
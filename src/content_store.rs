@@ -29,12 +29,13 @@
 
 use anyhow::{Context, Result};
 use memmap2::Mmap;
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-const MAGIC: &[u8; 4] = b"RFCT";
-const VERSION: u32 = 1;
+pub(crate) const MAGIC: &[u8; 4] = b"RFCT";
+pub(crate) const VERSION: u32 = 1;
 const HEADER_SIZE: usize = 32; // 4 (magic) + 4 (version) + 8 (num_files) + 8 (index_offset) + 8 (reserved)
 
 /// Metadata for a file in the content store
@@ -293,6 +294,8 @@ pub struct ContentReader {
     _file: File,
     mmap: Mmap,
     files: Vec<FileEntry>,
+    /// Normalized path → file_id, built once at open so lookups are O(1)
+    path_index: HashMap<String, u32>,
 }
 
 impl ContentReader {
@@ -391,10 +394,13 @@ impl ContentReader {
             });
         }
 
+        let path_index = build_path_index(&files);
+
         Ok(Self {
             _file: file,
             mmap,
             files,
+            path_index,
         })
     }
 
@@ -432,15 +438,26 @@ impl ContentReader {
     ///
     /// Note: This is different from database file_ids, which are AUTO INCREMENT values.
     pub fn get_file_id_by_path(&self, path: &str) -> Option<u32> {
-        // Normalize the input path (strip ./ prefix if present)
-        let normalized_input = path.strip_prefix("./").unwrap_or(path);
-
-        self.files.iter().position(|entry| {
-            // Normalize the stored path (strip ./ prefix if present)
-            let stored_path = entry.path.to_string_lossy();
-            let normalized_stored = stored_path.strip_prefix("./").unwrap_or(&stored_path);
-            normalized_stored == normalized_input
-        }).map(|idx| idx as u32)
+        self.file_id_for_path(path)
+    }
+
+    /// Look up a file_id by path using the in-memory path index
+    ///
+    /// A leading "./" is ignored on both sides, so "./src/main.rs" and
+    /// "src/main.rs" resolve to the same file. Runs in O(1) regardless of
+    /// index size.
+    pub fn file_id_for_path(&self, path: &str) -> Option<u32> {
+        self.path_index.get(normalize_path_key(path)).copied()
+    }
+
+    /// Look up a file_id by its path relative to the workspace `root`
+    ///
+    /// The store keeps paths as given to the indexer, which may be absolute, so
+    /// the root-joined path is tried as well. Only indexed files resolve:
+    /// `../` or absolute paths outside the index return `None`.
+    pub fn file_id_for_workspace_path(&self, path: &str, root: &Path) -> Option<u32> {
+        self.file_id_for_path(path)
+            .or_else(|| self.file_id_for_path(&root.join(path).to_string_lossy()))
     }
 
     /// Get content at a specific byte offset
@@ -505,15 +522,32 @@ impl ContentReader {
     ///
     /// Returns (lines_before, lines_after)
     pub fn get_context_by_line(&self, file_id: u32, line_number: usize, context_lines: usize) -> Result<(Vec<String>, Vec<String>)> {
+        self.get_context_lines(file_id, line_number, context_lines, context_lines)
+    }
+
+    /// Get an asymmetric context window around a line number (1-indexed)
+    ///
+    /// Used for grep-style `--before`/`--after` output. Returns (lines_before, lines_after);
+    /// either side may be shorter than requested near the start or end of the file.
+    pub fn get_context_lines(
+        &self,
+        file_id: u32,
+        line_number: usize,
+        before: usize,
+        after: usize,
+    ) -> Result<(Vec<String>, Vec<String>)> {
         let content = self.get_file_content(file_id)?;
         let lines: Vec<&str> = content.lines().collect();
 
         // Convert from 1-indexed to 0-indexed
         let line_idx = line_number.saturating_sub(1);
+        if line_idx >= lines.len() {
+            return Ok((vec![], vec![]));
+        }
 
         // Extract context
-        let start = line_idx.saturating_sub(context_lines);
-        let end = (line_idx + context_lines + 1).min(lines.len());
+        let start = line_idx.saturating_sub(before);
+        let end = (line_idx + after + 1).min(lines.len());
 
         let before: Vec<String> = lines[start..line_idx]
             .iter()
@@ -529,6 +563,26 @@ impl ContentReader {
     }
 }
 
+/// Strip a leading "./" so equivalent relative paths share one key
+fn normalize_path_key(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Build the path → file_id map for a file table
+///
+/// When two entries normalize to the same key the first one wins, matching
+/// the behaviour of the previous linear scan.
+fn build_path_index(files: &[FileEntry]) -> HashMap<String, u32> {
+    let mut index = HashMap::with_capacity(files.len());
+    for (file_id, entry) in files.iter().enumerate() {
+        let path = entry.path.to_string_lossy();
+        index
+            .entry(normalize_path_key(&path).to_string())
+            .or_insert(file_id as u32);
+    }
+    index
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -605,4 +659,52 @@ mod tests {
         let reader = ContentReader::open(&content_path).unwrap();
         assert_eq!(reader.get_file_content(0).unwrap(), content);
     }
+
+    #[test]
+    fn test_get_context_lines_asymmetric() {
+        let temp = TempDir::new().unwrap();
+        let content_path = temp.path().join("content.bin");
+
+        let mut writer = ContentWriter::new();
+        writer.add_file(
+            PathBuf::from("test.txt"),
+            "Line 1\nLine 2\nLine 3 with match\nLine 4\nLine 5",
+        );
+        writer.write(&content_path).unwrap();
+
+        let reader = ContentReader::open(&content_path).unwrap();
+
+        let (before, after) = reader.get_context_lines(0, 3, 2, 0).unwrap();
+        assert_eq!(before, vec!["Line 1", "Line 2"]);
+        assert!(after.is_empty());
+
+        // Windows are clamped at file boundaries
+        let (before, after) = reader.get_context_lines(0, 5, 0, 10).unwrap();
+        assert!(before.is_empty());
+        assert!(after.is_empty());
+
+        // Out-of-range lines yield no context instead of panicking
+        let (before, after) = reader.get_context_lines(0, 99, 1, 1).unwrap();
+        assert!(before.is_empty() && after.is_empty());
+    }
+
+    #[test]
+    fn test_file_id_for_path() {
+        let temp = TempDir::new().unwrap();
+        let content_path = temp.path().join("content.bin");
+
+        let mut writer = ContentWriter::new();
+        writer.add_file(PathBuf::from("./src/main.rs"), "fn main() {}");
+        writer.add_file(PathBuf::from("src/lib.rs"), "pub mod a;");
+        writer.write(&content_path).unwrap();
+
+        let reader = ContentReader::open(&content_path).unwrap();
+
+        // "./" prefix is ignored on both the stored and the requested path
+        assert_eq!(reader.file_id_for_path("src/main.rs"), Some(0));
+        assert_eq!(reader.file_id_for_path("./src/main.rs"), Some(0));
+        assert_eq!(reader.file_id_for_path("./src/lib.rs"), Some(1));
+        assert_eq!(reader.get_file_id_by_path("src/lib.rs"), Some(1));
+        assert_eq!(reader.file_id_for_path("src/missing.rs"), None);
+    }
 }
@@ -1,4 +1,4 @@
-//! Project type and framework detection
+//! Project type, framework and convention detection
 
 use anyhow::Result;
 use serde_json::{json, Value};
@@ -7,6 +7,8 @@ use std::fs;
 use std::path::Path;
 
 use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{Language, SymbolKind};
 
 /// Detect project type and return formatted string
 pub fn detect_project_type(_cache: &CacheManager, root: &Path) -> Result<String> {
@@ -1055,6 +1057,383 @@ fn find_config_files_list(root: &Path) -> Result<Vec<(String, String)>> {
     Ok(configs)
 }
 
+/// Maximum files parsed per language when sampling naming conventions
+const NAMING_SAMPLE_FILES: usize = 200;
+
+/// Minimum classified names before a naming style is reported
+const NAMING_MIN_SAMPLES: usize = 3;
+
+/// Source markers of error-handling idioms: (language, idiom, marker)
+const ERROR_IDIOMS: &[(Language, &str, &str)] = &[
+    (Language::Rust, "anyhow", "anyhow::"),
+    (Language::Rust, "thiserror", "thiserror::Error"),
+    (Language::Rust, "eyre", "eyre::"),
+    (Language::Rust, "snafu", "snafu::"),
+    (Language::Rust, "Box<dyn Error>", "Box<dyn std::error::Error"),
+    (Language::Go, "error wrapping (fmt.Errorf %w)", "%w\""),
+    (Language::Go, "errors.Is/errors.As", "errors.Is("),
+    (Language::Go, "pkg/errors", "github.com/pkg/errors"),
+    (Language::Python, "custom exception classes", "(Exception):"),
+    (Language::TypeScript, "custom Error subclasses", "extends Error"),
+    (Language::TypeScript, "throw new Error", "throw new Error("),
+    (Language::TypeScript, "neverthrow Result", "neverthrow"),
+    (Language::JavaScript, "custom Error subclasses", "extends Error"),
+    (Language::JavaScript, "throw new Error", "throw new Error("),
+    (Language::Java, "custom exceptions", "extends Exception"),
+    (Language::Java, "unchecked custom exceptions", "extends RuntimeException"),
+    (Language::Kotlin, "custom exceptions", ": Exception("),
+    (Language::Kotlin, "Result/runCatching", "runCatching"),
+    (Language::CSharp, "custom exceptions", ": Exception"),
+    (Language::PHP, "custom exceptions", "extends \\Exception"),
+    (Language::PHP, "custom exceptions", "extends Exception"),
+    (Language::Ruby, "custom errors", "< StandardError"),
+];
+
+/// Lint and format configuration files: (path, tool, kind)
+const TOOL_CONFIGS: &[(&str, &str, &str)] = &[
+    ("rustfmt.toml", "rustfmt", "formatter"),
+    (".rustfmt.toml", "rustfmt", "formatter"),
+    ("clippy.toml", "clippy", "linter"),
+    (".clippy.toml", "clippy", "linter"),
+    (".prettierrc", "prettier", "formatter"),
+    (".prettierrc.json", "prettier", "formatter"),
+    (".prettierrc.js", "prettier", "formatter"),
+    ("prettier.config.js", "prettier", "formatter"),
+    (".eslintrc", "eslint", "linter"),
+    (".eslintrc.json", "eslint", "linter"),
+    (".eslintrc.js", "eslint", "linter"),
+    (".eslintrc.cjs", "eslint", "linter"),
+    ("eslint.config.js", "eslint", "linter"),
+    ("eslint.config.mjs", "eslint", "linter"),
+    ("biome.json", "biome", "formatter+linter"),
+    (".editorconfig", "editorconfig", "formatter"),
+    ("ruff.toml", "ruff", "linter"),
+    (".ruff.toml", "ruff", "linter"),
+    (".flake8", "flake8", "linter"),
+    (".pylintrc", "pylint", "linter"),
+    ("mypy.ini", "mypy", "type checker"),
+    (".golangci.yml", "golangci-lint", "linter"),
+    (".golangci.yaml", "golangci-lint", "linter"),
+    (".php-cs-fixer.php", "php-cs-fixer", "formatter"),
+    (".php-cs-fixer.dist.php", "php-cs-fixer", "formatter"),
+    ("phpcs.xml", "phpcs", "linter"),
+    ("phpstan.neon", "phpstan", "linter"),
+    (".rubocop.yml", "rubocop", "linter"),
+    (".clang-format", "clang-format", "formatter"),
+    (".clang-tidy", "clang-tidy", "linter"),
+    ("detekt.yml", "detekt", "linter"),
+    (".stylelintrc", "stylelint", "linter"),
+    (".pre-commit-config.yaml", "pre-commit", "hooks"),
+];
+
+/// `[tool.*]` sections of pyproject.toml configuring lint/format tools: (section, tool, kind)
+const PYPROJECT_TOOLS: &[(&str, &str, &str)] = &[
+    ("[tool.black]", "black", "formatter"),
+    ("[tool.ruff", "ruff", "linter"),
+    ("[tool.isort]", "isort", "formatter"),
+    ("[tool.mypy]", "mypy", "type checker"),
+    ("[tool.pylint", "pylint", "linter"),
+];
+
+/// Detect project conventions and return formatted string
+pub fn detect_conventions(cache: &CacheManager, root: &Path) -> Result<String> {
+    let conventions = detect_conventions_json(cache, root)?;
+    let mut output = Vec::new();
+
+    if let Some(naming) = conventions["naming"].as_array().filter(|n| !n.is_empty()) {
+        output.push("Naming:".to_string());
+        for entry in naming {
+            output.push(format!(
+                "- {} {}: {} ({:.0}% of {})",
+                entry["language"].as_str().unwrap_or_default(),
+                entry["category"].as_str().unwrap_or_default(),
+                entry["style"].as_str().unwrap_or_default(),
+                entry["share"].as_f64().unwrap_or_default() * 100.0,
+                entry["samples"],
+            ));
+        }
+    }
+
+    if let Some(frameworks) = conventions["test_frameworks"].as_array().filter(|f| !f.is_empty()) {
+        let names: Vec<&str> = frameworks.iter().filter_map(|f| f.as_str()).collect();
+        output.push(format!("Test frameworks: {}", names.join(", ")));
+    }
+
+    if let Some(tools) = conventions["lint_format"].as_array().filter(|t| !t.is_empty()) {
+        output.push("Lint/format:".to_string());
+        for tool in tools {
+            output.push(format!(
+                "- {} ({}): {}",
+                tool["tool"].as_str().unwrap_or_default(),
+                tool["kind"].as_str().unwrap_or_default(),
+                tool["path"].as_str().unwrap_or_default(),
+            ));
+        }
+    }
+
+    if let Some(idioms) = conventions["error_handling"].as_array().filter(|i| !i.is_empty()) {
+        output.push("Error handling:".to_string());
+        for idiom in idioms {
+            output.push(format!(
+                "- {}: {} ({} files)",
+                idiom["language"].as_str().unwrap_or_default(),
+                idiom["idiom"].as_str().unwrap_or_default(),
+                idiom["files"],
+            ));
+        }
+    }
+
+    if output.is_empty() {
+        return Ok("No conventions detected".to_string());
+    }
+    Ok(output.join("\n"))
+}
+
+/// Detect project conventions (JSON format)
+///
+/// Naming styles and error-handling idioms are sampled from the indexed
+/// files under `root`; test frameworks and lint/format tools come from
+/// manifests and config files.
+pub fn detect_conventions_json(cache: &CacheManager, root: &Path) -> Result<Value> {
+    let workspace_root = cache.workspace_root();
+    let prefix = crate::paths::relative_path(root, &workspace_root);
+    let reader = ContentReader::open(cache.path().join("content.bin"))?;
+
+    // (language, category) -> style -> count
+    let mut naming: HashMap<(String, &'static str), HashMap<&'static str, usize>> = HashMap::new();
+    let mut parsed_files: HashMap<String, usize> = HashMap::new();
+    let mut idiom_files: HashMap<(String, &'static str), usize> = HashMap::new();
+
+    for file_id in 0..reader.file_count() as u32 {
+        let Some(path) = reader.get_file_path(file_id) else { continue };
+        let relative = crate::paths::relative_path(path, &workspace_root);
+        if !prefix.is_empty() && !Path::new(&relative).starts_with(&prefix) {
+            continue;
+        }
+        let language = Language::from_path(path);
+        if language == Language::Unknown {
+            continue;
+        }
+        let Ok(content) = reader.get_file_content(file_id) else { continue };
+        let lang_name = format!("{:?}", language).to_lowercase();
+
+        let mut seen_idioms = Vec::new();
+        for (_, idiom, marker) in ERROR_IDIOMS.iter().filter(|(l, _, _)| *l == language) {
+            if !seen_idioms.contains(idiom) && content.contains(marker) {
+                seen_idioms.push(*idiom);
+                *idiom_files.entry((lang_name.clone(), *idiom)).or_insert(0) += 1;
+            }
+        }
+
+        let parsed = parsed_files.entry(lang_name.clone()).or_insert(0);
+        if *parsed >= NAMING_SAMPLE_FILES {
+            continue;
+        }
+        *parsed += 1;
+
+        if let Some(style) = path.file_stem().and_then(|s| s.to_str()).and_then(classify_name) {
+            *naming.entry((lang_name.clone(), "files")).or_default().entry(style).or_insert(0) += 1;
+        }
+        let Ok(symbols) = crate::parsers::ParserFactory::parse_guarded(&relative, content, language) else { continue };
+        for symbol in symbols {
+            let category = match symbol.kind {
+                SymbolKind::Function | SymbolKind::Method => "functions",
+                SymbolKind::Class | SymbolKind::Struct | SymbolKind::Enum | SymbolKind::Interface
+                | SymbolKind::Trait | SymbolKind::Type => "types",
+                SymbolKind::Constant => "constants",
+                _ => continue,
+            };
+            if let Some(style) = symbol.symbol.as_deref().and_then(classify_name) {
+                *naming.entry((lang_name.clone(), category)).or_default().entry(style).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut naming: Vec<Value> = naming
+        .into_iter()
+        .filter_map(|((language, category), styles)| {
+            let samples: usize = styles.values().sum();
+            let (style, count) = styles.into_iter().max_by_key(|&(style, count)| (count, std::cmp::Reverse(style)))?;
+            (samples >= NAMING_MIN_SAMPLES).then(|| json!({
+                "language": language,
+                "category": category,
+                "style": style,
+                "share": count as f64 / samples as f64,
+                "samples": samples,
+            }))
+        })
+        .collect();
+    naming.sort_by_key(|n| (n["language"].as_str().unwrap_or_default().to_string(), n["category"].as_str().unwrap_or_default().to_string()));
+
+    let mut error_handling: Vec<((String, &str), usize)> = idiom_files.into_iter().collect();
+    error_handling.sort_by(|a, b| a.0.0.cmp(&b.0.0).then(b.1.cmp(&a.1)).then(a.0.1.cmp(b.0.1)));
+    let error_handling: Vec<Value> = error_handling
+        .into_iter()
+        .map(|((language, idiom), files)| json!({
+            "language": language,
+            "idiom": idiom,
+            "files": files,
+        }))
+        .collect();
+
+    Ok(json!({
+        "naming": naming,
+        "test_frameworks": detect_test_frameworks(root),
+        "lint_format": find_lint_format_configs(root),
+        "error_handling": error_handling,
+    }))
+}
+
+/// Naming style of an identifier, or None when it's ambiguous (e.g. `parse`)
+fn classify_name(name: &str) -> Option<&'static str> {
+    let name = name.trim_start_matches(['_', '$']);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return None;
+    }
+
+    let has_upper = name.chars().any(|c| c.is_ascii_uppercase());
+    let has_lower = name.chars().any(|c| c.is_ascii_lowercase());
+    let first_upper = name.starts_with(|c: char| c.is_ascii_uppercase());
+
+    if name.contains('-') {
+        return (!has_upper && !name.contains('_')).then_some("kebab-case");
+    }
+    if !has_lower {
+        return (has_upper && (name.contains('_') || name.len() > 1)).then_some("SCREAMING_SNAKE_CASE");
+    }
+    if name.contains('_') {
+        return (!has_upper).then_some("snake_case");
+    }
+    match (first_upper, has_upper) {
+        (true, _) => Some("PascalCase"),
+        (false, true) => Some("camelCase"),
+        (false, false) => None,
+    }
+}
+
+/// Test frameworks declared by the project's manifests
+fn detect_test_frameworks(root: &Path) -> Vec<String> {
+    let read = |file: &str| fs::read_to_string(root.join(file)).unwrap_or_default();
+    let mut frameworks = Vec::new();
+    let mut add = |name: &str| {
+        if !frameworks.iter().any(|f| f == name) {
+            frameworks.push(name.to_string());
+        }
+    };
+
+    let cargo = read("Cargo.toml");
+    if !cargo.is_empty() {
+        add("cargo test");
+        for (dep, name) in [("proptest", "proptest"), ("rstest", "rstest"), ("insta", "insta"), ("quickcheck", "quickcheck")] {
+            if cargo.contains(dep) {
+                add(name);
+            }
+        }
+    }
+
+    let package_json = read("package.json");
+    for (dep, name) in [
+        ("\"jest\"", "jest"),
+        ("\"vitest\"", "vitest"),
+        ("\"mocha\"", "mocha"),
+        ("\"jasmine\"", "jasmine"),
+        ("\"ava\"", "ava"),
+        ("\"@playwright/test\"", "playwright"),
+        ("\"cypress\"", "cypress"),
+        ("\"@testing-library/", "testing-library"),
+    ] {
+        if package_json.contains(dep) {
+            add(name);
+        }
+    }
+
+    let python = [read("pyproject.toml"), read("setup.cfg"), read("requirements.txt"), read("requirements-dev.txt")].join("\n");
+    if python.contains("pytest") || root.join("pytest.ini").exists() || root.join("conftest.py").exists() {
+        add("pytest");
+    }
+    if python.contains("hypothesis") {
+        add("hypothesis");
+    }
+
+    let go_mod = read("go.mod");
+    if !go_mod.is_empty() {
+        add("go test");
+        if go_mod.contains("github.com/stretchr/testify") {
+            add("testify");
+        }
+    }
+
+    let composer = read("composer.json");
+    if composer.contains("phpunit/phpunit") {
+        add("phpunit");
+    }
+    if composer.contains("pestphp/pest") {
+        add("pest");
+    }
+
+    let gemfile = read("Gemfile");
+    if gemfile.contains("rspec") || root.join(".rspec").exists() {
+        add("rspec");
+    }
+    if gemfile.contains("minitest") {
+        add("minitest");
+    }
+
+    let jvm = [read("pom.xml"), read("build.gradle"), read("build.gradle.kts")].join("\n");
+    if jvm.contains("junit-jupiter") {
+        add("junit5");
+    } else if jvm.contains("junit") {
+        add("junit");
+    }
+    for (dep, name) in [("testng", "testng"), ("kotest", "kotest"), ("mockito", "mockito")] {
+        if jvm.contains(dep) {
+            add(name);
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(root) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("csproj") {
+                continue;
+            }
+            let csproj = fs::read_to_string(&path).unwrap_or_default();
+            for (dep, name) in [("xunit", "xunit"), ("NUnit", "nunit"), ("MSTest", "mstest")] {
+                if csproj.contains(dep) {
+                    add(name);
+                }
+            }
+        }
+    }
+
+    frameworks
+}
+
+/// Lint and format tool configuration present in the project
+fn find_lint_format_configs(root: &Path) -> Vec<Value> {
+    let mut tools: Vec<Value> = TOOL_CONFIGS
+        .iter()
+        .filter(|(path, _, _)| root.join(path).exists())
+        .map(|(path, tool, kind)| json!({ "tool": tool, "kind": kind, "path": path }))
+        .collect();
+
+    let pyproject = fs::read_to_string(root.join("pyproject.toml")).unwrap_or_default();
+    for (section, tool, kind) in PYPROJECT_TOOLS {
+        if pyproject.contains(section) && !tools.iter().any(|t| t["tool"] == *tool) {
+            tools.push(json!({ "tool": tool, "kind": kind, "path": "pyproject.toml" }));
+        }
+    }
+
+    let package_json = fs::read_to_string(root.join("package.json")).unwrap_or_default();
+    for (key, tool, kind) in [("\"prettier\":", "prettier", "formatter"), ("\"eslintConfig\":", "eslint", "linter")] {
+        if package_json.contains(key) && !tools.iter().any(|t| t["tool"] == tool) {
+            tools.push(json!({ "tool": tool, "kind": kind, "path": "package.json" }));
+        }
+    }
+
+    tools
+}
+
 /// Count lines in a file
 fn count_lines_in_file(path: &Path) -> Result<usize> {
     let content = fs::read_to_string(path)?;
@@ -1078,3 +1457,59 @@ fn count_files_recursive(dir: &Path) -> Result<usize> {
 
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_classify_name() {
+        assert_eq!(classify_name("parse_config"), Some("snake_case"));
+        assert_eq!(classify_name("_private_helper"), Some("snake_case"));
+        assert_eq!(classify_name("parseConfig"), Some("camelCase"));
+        assert_eq!(classify_name("QueryEngine"), Some("PascalCase"));
+        assert_eq!(classify_name("MAX_RETRIES"), Some("SCREAMING_SNAKE_CASE"));
+        assert_eq!(classify_name("content-store"), Some("kebab-case"));
+        assert_eq!(classify_name("parse"), None);
+        assert_eq!(classify_name("Mixed_Case"), None);
+    }
+
+    #[test]
+    fn test_detect_conventions_json() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("Cargo.toml"), "[dependencies]\nanyhow = \"1\"\n\n[dev-dependencies]\ninsta = \"1\"\n").unwrap();
+        fs::write(root.join("rustfmt.toml"), "max_width = 120\n").unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/query_engine.rs"),
+            "const MAX_RESULTS: usize = 10;\nconst DEFAULT_LIMIT: usize = 5;\nconst PAGE_SIZE: usize = 2;\n\
+             pub struct QueryEngine;\npub struct QueryFilter;\npub enum MatchKind { A }\n\
+             fn parse_query() -> anyhow::Result<()> { Ok(()) }\nfn run_query() {}\nfn load_index() {}\n",
+        )
+        .unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let conventions = detect_conventions_json(&cache, root).unwrap();
+        let style = |category: &str| {
+            conventions["naming"].as_array().unwrap().iter()
+                .find(|n| n["language"] == "rust" && n["category"] == category)
+                .map(|n| n["style"].as_str().unwrap().to_string())
+        };
+        assert_eq!(style("functions").as_deref(), Some("snake_case"));
+        assert_eq!(style("types").as_deref(), Some("PascalCase"));
+        assert_eq!(style("constants").as_deref(), Some("SCREAMING_SNAKE_CASE"));
+        // One file is too few samples to call a file naming style
+        assert!(style("files").is_none());
+
+        assert_eq!(conventions["test_frameworks"], json!(["cargo test", "insta"]));
+        assert_eq!(conventions["lint_format"][0]["tool"], "rustfmt");
+        assert_eq!(conventions["error_handling"][0]["idiom"], "anyhow");
+        assert_eq!(conventions["error_handling"][0]["files"], 1);
+    }
+}
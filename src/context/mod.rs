@@ -5,6 +5,7 @@
 
 pub mod detection;
 pub mod structure;
+pub mod summary;
 
 use anyhow::Result;
 use crate::cache::CacheManager;
@@ -36,6 +37,11 @@ pub struct ContextOptions {
     /// List important configuration files
     pub config_files: bool,
 
+    /// Infer conventions (naming, test frameworks, lint/format tools, error handling)
+    ///
+    /// Not part of the default set: it parses a sample of indexed files.
+    pub conventions: bool,
+
     /// Tree depth for --structure (default: 1)
     pub depth: usize,
 
@@ -54,6 +60,7 @@ impl Default for ContextOptions {
             entry_points: true,
             test_layout: true,
             config_files: true,
+            conventions: false,
             depth: 1,
             json: false,
         }
@@ -72,6 +79,7 @@ impl ContextOptions {
             && !self.entry_points
             && !self.test_layout
             && !self.config_files
+            && !self.conventions
     }
 }
 
@@ -179,6 +187,13 @@ fn generate_text_context(
         }
     }
 
+    // Conventions
+    if opts.conventions
+        && let Ok(conventions) = detection::detect_conventions(cache, target_path)
+    {
+        sections.push(format!("## Conventions\n{}\n", conventions));
+    }
+
     Ok(sections.join("\n"))
 }
 
@@ -234,5 +249,11 @@ fn generate_json_context(
         }
     }
 
+    if opts.conventions
+        && let Ok(conventions) = detection::detect_conventions_json(cache, target_path)
+    {
+        context["conventions"] = conventions;
+    }
+
     serde_json::to_string_pretty(&context).map_err(Into::into)
 }
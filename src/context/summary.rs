@@ -0,0 +1,269 @@
+//! LLM-generated architecture brief (`rfx context --summary`)
+//!
+//! Feeds the structural context (project type, entry points, tree, frameworks,
+//! test layout) plus the dependency hotspots to the configured LLM provider and
+//! asks for a short architecture overview that cites paths. Briefs are cached
+//! in meta.db per commit and context path, so every agent session on the same
+//! commit reuses one brief instead of rediscovering the layout. Outside a git
+//! repository nothing is cached.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::ContextOptions;
+use crate::cache::CacheManager;
+use crate::dependency::DependencyIndex;
+use crate::semantic::providers::LlmProvider;
+
+/// Number of dependency hotspots included in the prompt
+const HOTSPOT_LIMIT: usize = 10;
+
+/// Tree depth used for the prompt when `--depth` is left at its default
+const SUMMARY_DEPTH: usize = 2;
+
+/// An architecture brief and where it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSummary {
+    /// Commit the brief describes (None outside a git repository)
+    pub commit: Option<String>,
+    /// Provider that wrote the brief
+    pub provider: String,
+    /// Whether the brief was read from the cache
+    pub cached: bool,
+    pub summary: String,
+}
+
+/// Summarize the project with the configured provider
+///
+/// Returns the cached brief for the current commit unless `refresh` is set;
+/// the provider (and its API key) is only needed on a cache miss.
+pub async fn summarize(
+    cache: &CacheManager,
+    opts: &ContextOptions,
+    provider_override: Option<String>,
+    refresh: bool,
+) -> Result<ContextSummary> {
+    let commit = crate::git::get_current_commit(cache.workspace_root()).ok();
+    let key = opts.path.clone().unwrap_or_default();
+
+    let cached = match commit.as_deref() {
+        Some(commit) if !refresh => load_cached(cache, commit, &key)?,
+        _ => None,
+    };
+    if let Some(cached) = cached {
+        return Ok(cached);
+    }
+
+    let mut config = crate::semantic::config::load_config(cache.path())?;
+    if let Some(provider) = provider_override {
+        config.provider = provider;
+    }
+    let api_key = crate::semantic::config::get_api_key(&config.provider)?;
+    let model = config.model.clone().or_else(|| crate::semantic::config::get_user_model(&config.provider));
+    let provider = crate::semantic::providers::create_provider(&config.provider, api_key, model)?;
+
+    summarize_with(cache, opts, commit, &*provider).await
+}
+
+/// Generate a brief with `provider` and cache it for `commit`
+async fn summarize_with(
+    cache: &CacheManager,
+    opts: &ContextOptions,
+    commit: Option<String>,
+    provider: &dyn LlmProvider,
+) -> Result<ContextSummary> {
+    let mut context_opts = ContextOptions { json: false, ..opts.clone() };
+    if context_opts.depth == ContextOptions::default().depth {
+        context_opts.depth = SUMMARY_DEPTH;
+    }
+    let context = super::generate_context(cache, &context_opts)?;
+    let prompt = build_prompt(&context, &hotspots(cache));
+
+    log::info!("Requesting architecture brief from {} (model: {})", provider.name(), provider.default_model());
+    let summary = provider.complete(&prompt, false).await
+        .context("Failed to generate architecture brief")?
+        .trim()
+        .to_string();
+    if summary.is_empty() {
+        anyhow::bail!("LLM returned an empty architecture brief");
+    }
+
+    if let Some(commit) = &commit {
+        store(cache, commit, opts.path.as_deref().unwrap_or_default(), provider.name(), &summary)?;
+    }
+
+    Ok(ContextSummary {
+        commit,
+        provider: provider.name().to_string(),
+        cached: false,
+        summary,
+    })
+}
+
+/// Most imported files with their dependent counts
+///
+/// Empty when the index has no dependency data.
+fn hotspots(cache: &CacheManager) -> Vec<(String, usize)> {
+    let deps = DependencyIndex::new(cache.clone());
+    let found = match deps.find_hotspots(Some(HOTSPOT_LIMIT), 2) {
+        Ok(found) => found,
+        Err(e) => {
+            log::debug!("No hotspots for architecture brief: {:#}", e);
+            return Vec::new();
+        }
+    };
+    let ids: Vec<i64> = found.iter().map(|(id, _)| *id).collect();
+    let paths = deps.get_file_paths(&ids).unwrap_or_default();
+    found
+        .into_iter()
+        .filter_map(|(id, count)| paths.get(&id).map(|path| (path.clone(), count)))
+        .collect()
+}
+
+/// Prompt asking for an architecture overview of `context`
+fn build_prompt(context: &str, hotspots: &[(String, usize)]) -> String {
+    let mut prompt = String::from(
+        "You are writing an architecture brief for a developer or coding agent who is new to this codebase.\n\
+         Using only the project context below, write a concise overview (at most ~400 words) in Markdown covering:\n\
+         - what the project is and how it is built\n\
+         - the main components and where they live\n\
+         - entry points and how control flows from them\n\
+         - the most depended-on modules\n\
+         - where tests and configuration live\n\n\
+         Cite file and directory paths in backticks exactly as they appear in the context. \
+         Do not invent paths, and say so when the context doesn't tell.\n\n",
+    );
+    prompt.push_str(context.trim());
+    prompt.push('\n');
+
+    if !hotspots.is_empty() {
+        prompt.push_str("\n## Dependency Hotspots (most imported files)\n");
+        for (path, count) in hotspots {
+            prompt.push_str(&format!("- {} ({} dependents)\n", path, count));
+        }
+    }
+
+    prompt
+}
+
+fn open_table(cache: &CacheManager) -> Result<Connection> {
+    let conn = Connection::open(cache.path().join("meta.db")).context("Failed to open meta.db")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS context_summaries (
+            commit_sha TEXT NOT NULL,
+            path TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (commit_sha, path)
+        )",
+        [],
+    )
+    .context("Failed to create context_summaries table")?;
+    Ok(conn)
+}
+
+fn load_cached(cache: &CacheManager, commit: &str, path: &str) -> Result<Option<ContextSummary>> {
+    let conn = open_table(cache)?;
+    let row: Option<(String, String)> = conn
+        .query_row(
+            "SELECT provider, summary FROM context_summaries WHERE commit_sha = ? AND path = ?",
+            [commit, path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    Ok(row.map(|(provider, summary)| ContextSummary {
+        commit: Some(commit.to_string()),
+        provider,
+        cached: true,
+        summary,
+    }))
+}
+
+fn store(cache: &CacheManager, commit: &str, path: &str, provider: &str, summary: &str) -> Result<()> {
+    let conn = open_table(cache)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO context_summaries (commit_sha, path, provider, summary, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+        rusqlite::params![commit, path, provider, summary, chrono::Utc::now().timestamp()],
+    )
+    .context("Failed to cache architecture brief")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use async_trait::async_trait;
+    use std::fs;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Provider that records prompts and answers with a fixed brief
+    struct RecordingProvider {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for RecordingProvider {
+        async fn complete(&self, prompt: &str, _json_mode: bool) -> Result<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok("  A CLI whose entry point is `src/main.rs`.\n".to_string())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn default_model(&self) -> &str {
+            "test"
+        }
+    }
+
+    #[test]
+    fn test_prompt_includes_context_and_hotspots() {
+        let prompt = build_prompt("# Project Context\n## Entry Points\nsrc/main.rs\n", &[("src/lib.rs".to_string(), 7)]);
+        assert!(prompt.contains("Cite file and directory paths"));
+        assert!(prompt.contains("src/main.rs"));
+        assert!(prompt.contains("- src/lib.rs (7 dependents)"));
+        assert!(!build_prompt("ctx", &[]).contains("Hotspots"));
+    }
+
+    #[tokio::test]
+    async fn test_summary_cached_per_commit() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let provider = RecordingProvider { prompts: Mutex::new(Vec::new()) };
+        let opts = ContextOptions::default();
+        let commit = Some("a".repeat(40));
+
+        let fresh = summarize_with(&cache, &opts, commit.clone(), &provider).await.unwrap();
+        assert!(!fresh.cached);
+        assert_eq!(fresh.summary, "A CLI whose entry point is `src/main.rs`.");
+        assert!(provider.prompts.lock().unwrap()[0].contains("main.rs"));
+
+        let cached = load_cached(&cache, commit.as_deref().unwrap(), "").unwrap().unwrap();
+        assert!(cached.cached);
+        assert_eq!(cached.provider, "recording");
+        assert_eq!(cached.summary, fresh.summary);
+
+        // Other commits and context paths get their own brief
+        assert!(load_cached(&cache, &"b".repeat(40), "").unwrap().is_none());
+        assert!(load_cached(&cache, commit.as_deref().unwrap(), "src").unwrap().is_none());
+
+        // Without a commit the brief is not cached
+        summarize_with(&cache, &opts, None, &provider).await.unwrap();
+        assert_eq!(provider.prompts.lock().unwrap().len(), 2);
+    }
+}
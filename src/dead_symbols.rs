@@ -0,0 +1,430 @@
+//! Exported symbols nobody uses (`rfx analyze --dead-symbols`)
+//!
+//! `rfx analyze --unused` finds whole files nothing imports; this module goes
+//! down to symbols. Every exported symbol (see [`crate::parsers::visibility`])
+//! is checked against the identifiers of every indexed file, and symbols whose
+//! name never occurs outside their own file are reported with a confidence:
+//!
+//! - **high**: the name occurs nowhere outside the symbol's own definition
+//! - **medium**: the name is only used elsewhere in the defining file, so the
+//!   symbol is live but doesn't need to be exported
+//! - **low**: the name is only used by tests
+//!
+//! Methods and properties are reported one level lower, since they may be
+//! called through an interface, trait object or reflection without their name
+//! appearing at the call site. Matching is by name: any other file mentioning
+//! the name (including a same-named symbol) counts as a reference, so results
+//! err on the side of missing dead code rather than flagging live code.
+//!
+//! Entry points (`main`, `lib.rs`, `index.ts`, `__init__.py`, ...), test files
+//! and dunder methods are never reported.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{SearchResult, SymbolKind};
+use crate::paths::relative_path;
+use crate::query::QueryEngine;
+use crate::related::is_test_file;
+use crate::symbol_dump::{visit_symbols, DumpOptions};
+use crate::tokens::identifiers;
+
+/// Symbol names that are called by the runtime, not by code
+const ENTRY_POINT_NAMES: &[&str] = &["main", "init", "setup", "teardown"];
+
+/// File names whose exports are the public surface of a crate, package or program
+const ENTRY_POINT_FILES: &[&str] = &[
+    "main.rs", "lib.rs", "build.rs",
+    "main.go",
+    "index.js", "index.jsx", "index.mjs", "index.cjs", "index.ts", "index.tsx",
+    "main.js", "main.ts",
+    "__init__.py", "__main__.py", "setup.py", "manage.py", "conftest.py",
+    "main.c", "main.cpp", "main.cc",
+    "Program.cs", "Main.java", "Main.kt", "main.zig", "build.zig",
+];
+
+/// How sure the analysis is that a symbol is dead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    fn lower(self) -> Self {
+        match self {
+            Confidence::High => Confidence::Medium,
+            _ => Confidence::Low,
+        }
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Confidence::Low),
+            "medium" | "med" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => anyhow::bail!(
+                "Unknown confidence '{}'.\n\
+                 \n\
+                 Valid values: low, medium, high",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        })
+    }
+}
+
+/// Where the name of a dead symbol was still found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadReason {
+    /// Nowhere outside its own definition
+    Unreferenced,
+    /// Only elsewhere in the defining file
+    OnlyInOwnFile,
+    /// Only in test files
+    OnlyInTests,
+}
+
+impl fmt::Display for DeadReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeadReason::Unreferenced => "no references",
+            DeadReason::OnlyInOwnFile => "only used in its own file",
+            DeadReason::OnlyInTests => "only used by tests",
+        })
+    }
+}
+
+/// What to analyze
+#[derive(Debug, Clone)]
+pub struct DeadSymbolOptions {
+    /// Only report symbols with at least this confidence
+    pub min_confidence: Confidence,
+    /// Only report symbols in files matching any of these globs (references are counted everywhere)
+    pub glob_patterns: Vec<String>,
+    /// Don't report symbols in files matching any of these globs
+    pub exclude_patterns: Vec<String>,
+}
+
+impl Default for DeadSymbolOptions {
+    fn default() -> Self {
+        Self {
+            min_confidence: Confidence::Low,
+            glob_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+        }
+    }
+}
+
+/// An exported symbol without references outside its file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadSymbol {
+    pub path: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    pub kind: SymbolKind,
+    pub line: usize,
+    pub confidence: Confidence,
+    pub reason: DeadReason,
+}
+
+/// Outcome of a dead symbol analysis
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeadSymbolReport {
+    /// Dead symbols in path and line order
+    pub symbols: Vec<DeadSymbol>,
+    /// Exported symbols checked
+    pub candidates: usize,
+    /// Files searched for references
+    pub files_scanned: usize,
+}
+
+/// Find exported symbols in the index in `cache` that nothing else references
+pub fn find_dead_symbols(cache: &CacheManager, options: &DeadSymbolOptions) -> Result<DeadSymbolReport> {
+    let include_matcher = build_glob_matcher(&options.glob_patterns);
+    let exclude_matcher = build_glob_matcher(&options.exclude_patterns);
+
+    let mut candidates: Vec<(String, SearchResult)> = Vec::new();
+    visit_symbols(cache, &DumpOptions::default(), &mut |path, symbols| {
+        let included = include_matcher.as_ref().is_none_or(|m| m.is_match(path));
+        let excluded = exclude_matcher.as_ref().is_some_and(|m| m.is_match(path));
+        if included && !excluded && !is_entry_point_file(path) && !is_test_file(path) {
+            candidates.extend(exported_symbols(symbols).into_iter().map(|s| (path.to_string(), s)));
+        }
+        Ok(())
+    })?;
+
+    let content_reader = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+    // Workspace-relative paths by content store file id, like the symbol table's
+    let root = cache.workspace_root();
+    let paths: Vec<String> = (0..content_reader.file_count() as u32)
+        .map(|id| content_reader.get_file_path(id).map(|p| relative_path(p, &root)).unwrap_or_default())
+        .collect();
+    let file_ids: HashMap<&str, u32> = paths.iter().enumerate().map(|(id, p)| (p.as_str(), id as u32)).collect();
+
+    let names: HashSet<&str> = candidates.iter().filter_map(|(_, s)| s.symbol.as_deref()).collect();
+    let mentions = files_mentioning(&content_reader, &paths, &names);
+
+    let mut report = DeadSymbolReport {
+        candidates: candidates.len(),
+        files_scanned: content_reader.file_count(),
+        ..Default::default()
+    };
+
+    for (path, symbol) in &candidates {
+        let Some(name) = symbol.symbol.as_deref() else {
+            continue;
+        };
+        let files = mentions.get(name).map(Vec::as_slice).unwrap_or(&[]);
+        let content = file_ids
+            .get(path.as_str())
+            .and_then(|id| content_reader.get_file_content(*id).ok())
+            .unwrap_or("");
+        let Some(reason) = classify(path, symbol, files, content) else {
+            continue;
+        };
+
+        let mut confidence = match reason {
+            DeadReason::Unreferenced => Confidence::High,
+            DeadReason::OnlyInOwnFile => Confidence::Medium,
+            DeadReason::OnlyInTests => Confidence::Low,
+        };
+        if matches!(symbol.kind, SymbolKind::Method | SymbolKind::Property) {
+            confidence = confidence.lower();
+        }
+        if confidence < options.min_confidence {
+            continue;
+        }
+
+        report.symbols.push(DeadSymbol {
+            path: path.clone(),
+            name: name.to_string(),
+            qualified_name: symbol.qualified_name.clone(),
+            kind: symbol.kind.clone(),
+            line: symbol.span.start_line,
+            confidence,
+            reason,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Exported symbols of one file worth reporting, one per declaration
+///
+/// The Rust parser reports impl methods as functions too; the method is kept.
+fn exported_symbols(symbols: &[SearchResult]) -> Vec<SearchResult> {
+    let mut kept: Vec<SearchResult> = Vec::new();
+    for symbol in symbols {
+        let Some(name) = symbol.symbol.as_deref() else {
+            continue;
+        };
+        let reportable = matches!(
+            symbol.kind,
+            SymbolKind::Function
+                | SymbolKind::Method
+                | SymbolKind::Class
+                | SymbolKind::Struct
+                | SymbolKind::Enum
+                | SymbolKind::Interface
+                | SymbolKind::Trait
+                | SymbolKind::Constant
+                | SymbolKind::Variable
+                | SymbolKind::Type
+                | SymbolKind::Macro
+                | SymbolKind::Property
+        );
+        let dunder = name.starts_with("__") && name.ends_with("__");
+        if symbol.exported != Some(true) || !reportable || dunder || ENTRY_POINT_NAMES.contains(&name) {
+            continue;
+        }
+
+        match kept.iter_mut().find(|k| k.symbol == symbol.symbol && k.span.start_line == symbol.span.start_line) {
+            Some(existing) if symbol.kind == SymbolKind::Method => *existing = symbol.clone(),
+            Some(_) => {}
+            None => kept.push(symbol.clone()),
+        }
+    }
+    kept
+}
+
+/// Paths (from `paths`, by file id) of the files whose identifiers include each of `names`
+fn files_mentioning<'a>(
+    content_reader: &ContentReader,
+    paths: &'a [String],
+    names: &HashSet<&str>,
+) -> HashMap<String, Vec<&'a str>> {
+    let per_file: Vec<(&str, HashSet<String>)> = paths
+        .par_iter()
+        .enumerate()
+        .filter_map(|(file_id, path)| {
+            let content = content_reader.get_file_content(file_id as u32).ok()?;
+            let found: HashSet<String> = content
+                .lines()
+                .flat_map(identifiers)
+                .filter(|word| names.contains(word))
+                .map(str::to_string)
+                .collect();
+            Some((path.as_str(), found))
+        })
+        .collect();
+
+    let mut mentions: HashMap<String, Vec<&str>> = HashMap::new();
+    for (path, found) in per_file {
+        for name in found {
+            mentions.entry(name).or_default().push(path);
+        }
+    }
+    mentions
+}
+
+/// Why `symbol` (defined in `path`, whose source is `content`) is dead, or None if another file uses it
+///
+/// `files` are the paths of every file mentioning the symbol's name.
+fn classify(path: &str, symbol: &SearchResult, files: &[&str], content: &str) -> Option<DeadReason> {
+    let mut others = files.iter().filter(|f| **f != path).peekable();
+    if others.peek().is_some() {
+        return others.all(|f| is_test_file(f)).then_some(DeadReason::OnlyInTests);
+    }
+
+    let name = symbol.symbol.as_deref()?;
+    let used_in_file = content.lines().enumerate().any(|(i, line)| {
+        let line_no = i + 1;
+        // Mentions inside the symbol itself (declaration, recursion) don't count
+        (line_no < symbol.span.start_line || line_no > symbol.span.end_line)
+            && identifiers(line).any(|word| word == name)
+    });
+
+    Some(if used_in_file { DeadReason::OnlyInOwnFile } else { DeadReason::Unreferenced })
+}
+
+fn is_entry_point_file(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| ENTRY_POINT_FILES.contains(&name))
+}
+
+fn build_glob_matcher(patterns: &[String]) -> Option<globset::GlobSet> {
+    use globset::{Glob, GlobSetBuilder};
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = QueryEngine::normalize_glob_pattern(pattern);
+        if let Ok(glob) = Glob::new(&normalized) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn analyze(files: &[(&str, &str)], options: &DeadSymbolOptions) -> DeadSymbolReport {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        for (path, content) in files {
+            let full = project.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+
+        let cache = CacheManager::new(&project);
+        Indexer::new(cache, IndexConfig::default()).index(&project, false).unwrap();
+        find_dead_symbols(&CacheManager::new(&project), options).unwrap()
+    }
+
+    fn dead(report: &DeadSymbolReport) -> Vec<(&str, Confidence, DeadReason)> {
+        report.symbols.iter().map(|s| (s.name.as_str(), s.confidence, s.reason)).collect()
+    }
+
+    #[test]
+    fn test_dead_symbols_confidence() {
+        let report = analyze(
+            &[
+                (
+                    "src/util.ts",
+                    "export function used() {}\n\
+                     export function forgotten() {}\n\
+                     export function overExported() {}\n\
+                     export function testedOnly() {}\n\
+                     function local() { overExported(); }\n",
+                ),
+                ("src/app.ts", "import { used } from './util';\nused();\n"),
+                ("src/util.test.ts", "import { testedOnly } from './util';\ntestedOnly();\n"),
+            ],
+            &DeadSymbolOptions::default(),
+        );
+
+        assert_eq!(
+            dead(&report),
+            vec![
+                ("forgotten", Confidence::High, DeadReason::Unreferenced),
+                ("overExported", Confidence::Medium, DeadReason::OnlyInOwnFile),
+                ("testedOnly", Confidence::Low, DeadReason::OnlyInTests),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_symbols_skip_entry_points_and_tests() {
+        let report = analyze(
+            &[
+                ("src/index.ts", "export function publicApi() {}\n"),
+                ("src/cli.ts", "export function main() {}\nexport function unusedHelper() {}\n"),
+                ("tests/helpers.ts", "export function fixture() {}\n"),
+            ],
+            &DeadSymbolOptions::default(),
+        );
+
+        assert_eq!(dead(&report), vec![("unusedHelper", Confidence::High, DeadReason::Unreferenced)]);
+    }
+
+    #[test]
+    fn test_dead_symbols_min_confidence() {
+        let report = analyze(
+            &[(
+                "src/lib_impl.rs",
+                "pub fn orphan() {}\n\npub fn chained() {}\n\nfn caller() { chained(); }\n",
+            )],
+            &DeadSymbolOptions { min_confidence: Confidence::High, ..Default::default() },
+        );
+
+        assert_eq!(dead(&report), vec![("orphan", Confidence::High, DeadReason::Unreferenced)]);
+    }
+}
@@ -32,20 +32,51 @@
 
 use anyhow::{Context, Result};
 use rusqlite::Connection;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
 
 use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
 use crate::models::{Dependency, DependencyInfo, ImportType};
 
+/// Re-exports by barrel file: (exported symbol, None for `export *`; source file ID)
+type BarrelExports = HashMap<i64, Vec<(Option<String>, i64)>>;
+
 /// Manages dependency storage and graph operations
 pub struct DependencyIndex {
     cache: CacheManager,
+    /// Attribute imports of barrel files to the files defining the imported symbols
+    resolve_barrels: bool,
+    /// Resolved re-exports, loaded on first use in barrel mode
+    barrel_exports: OnceLock<BarrelExports>,
+    /// Every resolved import edge after barrel resolution, loaded on first use
+    barrel_edges: OnceLock<Vec<(i64, i64)>>,
+    /// Content store for narrowing `export *` candidates (None if unavailable)
+    content: OnceLock<Option<ContentReader>>,
 }
 
 impl DependencyIndex {
     /// Create a new dependency index for the given cache
     pub fn new(cache: CacheManager) -> Self {
-        Self { cache }
+        Self {
+            cache,
+            resolve_barrels: false,
+            barrel_exports: OnceLock::new(),
+            barrel_edges: OnceLock::new(),
+            content: OnceLock::new(),
+        }
+    }
+
+    /// Resolve imports through barrel files (`export ... from` re-exports)
+    ///
+    /// When enabled, importing `components/index.ts` (which does
+    /// `export { Button } from './Button'`) counts as importing `Button.tsx` in
+    /// dependency listings, traversals, hotspots, cycles, islands and graph export.
+    /// Unused-file detection always follows barrel chains and is unaffected.
+    pub fn with_barrel_resolution(mut self, enabled: bool) -> Self {
+        self.resolve_barrels = enabled;
+        self
     }
 
     /// Get a reference to the cache manager
@@ -224,7 +255,26 @@ impl DependencyIndex {
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(deps)
+        if !self.resolve_barrels {
+            return Ok(deps);
+        }
+
+        // One record per defining file when an import goes through a barrel
+        let mut resolved = Vec::with_capacity(deps.len());
+        for dep in deps {
+            let Some(target_id) = dep.resolved_file_id else {
+                resolved.push(dep);
+                continue;
+            };
+            for real_id in self.resolve_barrel_import(target_id, dep.imported_symbols.as_deref())? {
+                resolved.push(Dependency {
+                    resolved_file_id: Some(real_id),
+                    ..dep.clone()
+                });
+            }
+        }
+
+        Ok(resolved)
     }
 
     /// Get all files that depend on this file (reverse lookup)
@@ -232,6 +282,15 @@ impl DependencyIndex {
     /// Returns a list of file IDs that import this file.
     /// Uses `resolved_file_id` column for instant SQL lookup (sub-10ms).
     pub fn get_dependents(&self, file_id: i64) -> Result<Vec<i64>> {
+        if self.resolve_barrels {
+            let dependents: BTreeSet<i64> = self.barrel_edges()?
+                .iter()
+                .filter(|(_, target_id)| *target_id == file_id)
+                .map(|(source_id, _)| *source_id)
+                .collect();
+            return Ok(dependents.into_iter().collect());
+        }
+
         let db_path = self.cache.path().join("meta.db");
         let conn = Connection::open(&db_path)
             .context("Failed to open meta.db for reverse dependency lookup")?;
@@ -251,6 +310,39 @@ impl DependencyIndex {
         Ok(dependents)
     }
 
+    /// Get all files that depend on this file, with how strongly they depend on it
+    ///
+    /// The weight of an importer is the number of symbols it imports from this
+    /// file, counting imports without an explicit symbol list as 1.
+    pub fn get_dependent_weights(&self, file_id: i64) -> Result<Vec<(i64, usize)>> {
+        let db_path = self.cache.path().join("meta.db");
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for reverse dependency lookup")?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, imported_symbols
+             FROM file_dependencies
+             WHERE resolved_file_id = ?
+             ORDER BY file_id"
+        )?;
+
+        let mut weights: Vec<(i64, usize)> = Vec::new();
+        let rows = stmt.query_map([file_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
+        for row in rows {
+            let (dependent_id, symbols_json) = row?;
+            let symbols: Option<Vec<String>> = symbols_json.and_then(|json| serde_json::from_str(&json).ok());
+            let weight = crate::related::symbol_weight(symbols.as_deref());
+            match weights.last_mut() {
+                Some((id, total)) if *id == dependent_id => *total += weight,
+                _ => weights.push((dependent_id, weight)),
+            }
+        }
+
+        Ok(weights)
+    }
+
     /// Get dependencies as DependencyInfo (for API output)
     ///
     /// Converts internal Dependency records to simplified DependencyInfo
@@ -325,6 +417,37 @@ impl DependencyIndex {
         Ok(visited)
     }
 
+    /// Get transitive dependents (files importing this file, directly or not) up to a given depth
+    ///
+    /// Reverse counterpart of [`Self::get_transitive_deps`]: BFS over
+    /// `get_dependents`, so depth 1 means "imports this file directly".
+    ///
+    /// # Returns
+    ///
+    /// HashMap mapping file_id to depth (distance from start file)
+    pub fn get_transitive_dependents(&self, file_id: i64, max_depth: usize) -> Result<HashMap<i64, usize>> {
+        let mut visited = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        queue.push_back((file_id, 0));
+        visited.insert(file_id, 0);
+
+        while let Some((current_id, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+
+            for dependent_id in self.get_dependents(current_id)? {
+                if let Entry::Vacant(entry) = visited.entry(dependent_id) {
+                    entry.insert(depth + 1);
+                    queue.push_back((dependent_id, depth + 1));
+                }
+            }
+        }
+
+        Ok(visited)
+    }
+
     /// Detect circular dependencies in the entire codebase
     ///
     /// Uses depth-first search to find cycles in the dependency graph.
@@ -333,24 +456,10 @@ impl DependencyIndex {
     /// Returns a list of cycle paths, where each cycle is represented as
     /// a vector of file IDs forming the cycle.
     pub fn detect_circular_dependencies(&self) -> Result<Vec<Vec<i64>>> {
-        let db_path = self.cache.path().join("meta.db");
-        let conn = Connection::open(&db_path)
-            .context("Failed to open meta.db for circular dependency analysis")?;
-
         // Build in-memory dependency graph using resolved_file_id (instant)
         let mut graph: HashMap<i64, Vec<i64>> = HashMap::new();
 
-        let mut stmt = conn.prepare(
-            "SELECT file_id, resolved_file_id
-             FROM file_dependencies
-             WHERE resolved_file_id IS NOT NULL"
-        )?;
-
-        let dependencies: Vec<(i64, i64)> = stmt
-            .query_map([], |row| {
-                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        let dependencies = self.get_resolved_edges()?;
 
         // Build adjacency list directly from resolved IDs
         for (file_id, target_id) in dependencies {
@@ -416,6 +525,34 @@ impl DependencyIndex {
         Ok(())
     }
 
+    /// Get every resolved import as a (file_id, resolved_file_id) pair
+    ///
+    /// Only imports resolved to an indexed file are included, so this is the edge
+    /// list of the internal dependency graph (with one pair per import statement).
+    pub fn get_resolved_edges(&self) -> Result<Vec<(i64, i64)>> {
+        if self.resolve_barrels {
+            return Ok(self.barrel_edges()?.clone());
+        }
+
+        let db_path = self.cache.path().join("meta.db");
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for dependency graph")?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, resolved_file_id
+             FROM file_dependencies
+             WHERE resolved_file_id IS NOT NULL"
+        )?;
+
+        let edges = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(edges)
+    }
+
     /// Get file paths for a list of file IDs
     ///
     /// Useful for converting file ID results to human-readable paths.
@@ -479,25 +616,36 @@ impl DependencyIndex {
     /// * `limit` - Maximum number of hotspots to return (None = all)
     /// * `min_dependents` - Minimum number of imports required to be a hotspot (default: 2)
     pub fn find_hotspots(&self, limit: Option<usize>, min_dependents: usize) -> Result<Vec<(i64, usize)>> {
-        let db_path = self.cache.path().join("meta.db");
-        let conn = Connection::open(&db_path)
-            .context("Failed to open meta.db for hotspot analysis")?;
+        let counts: Vec<(i64, usize)> = if self.resolve_barrels {
+            let mut counts: HashMap<i64, usize> = HashMap::new();
+            for (_, target_id) in self.barrel_edges()? {
+                *counts.entry(*target_id).or_insert(0) += 1;
+            }
+            let mut counts: Vec<(i64, usize)> = counts.into_iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            counts
+        } else {
+            let db_path = self.cache.path().join("meta.db");
+            let conn = Connection::open(&db_path)
+                .context("Failed to open meta.db for hotspot analysis")?;
 
-        // Pure SQL aggregation on resolved_file_id (instant)
-        let mut stmt = conn.prepare(
-            "SELECT resolved_file_id, COUNT(*) as count
-             FROM file_dependencies
-             WHERE resolved_file_id IS NOT NULL
-             GROUP BY resolved_file_id
-             ORDER BY count DESC"
-        )?;
+            // Pure SQL aggregation on resolved_file_id (instant)
+            let mut stmt = conn.prepare(
+                "SELECT resolved_file_id, COUNT(*) as count
+                 FROM file_dependencies
+                 WHERE resolved_file_id IS NOT NULL
+                 GROUP BY resolved_file_id
+                 ORDER BY count DESC"
+            )?;
 
-        // Get all hotspots and filter by minimum dependent count
-        let mut hotspots: Vec<(i64, usize)> = stmt
-            .query_map([], |row| {
+            stmt.query_map([], |row| {
                 Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as usize))
             })?
             .collect::<Result<Vec<_>, _>>()?
+        };
+
+        // Filter by minimum dependent count
+        let mut hotspots: Vec<(i64, usize)> = counts
             .into_iter()
             .filter(|(_, count)| *count >= min_dependents)
             .collect();
@@ -624,6 +772,178 @@ impl DependencyIndex {
         Ok(resolved_files)
     }
 
+    /// Files an import of `target_id` refers to once barrel re-exports are followed
+    ///
+    /// Each imported symbol follows the matching named re-export, otherwise the
+    /// `export *` re-exports that can provide it, and stays on the file itself when
+    /// neither does (a local definition). Imports without a symbol list (namespace
+    /// and side-effect imports) reach the file and everything it re-exports.
+    pub fn resolve_barrel_import(&self, target_id: i64, symbols: Option<&[String]>) -> Result<Vec<i64>> {
+        let exports = self.barrel_exports()?;
+        if !exports.contains_key(&target_id) {
+            return Ok(vec![target_id]);
+        }
+
+        let mut targets = Vec::new();
+        match symbols {
+            Some(symbols) if !symbols.is_empty() => {
+                for symbol in symbols {
+                    let mut visited = HashSet::new();
+                    for id in self.symbol_sources(exports, target_id, symbol, &mut visited) {
+                        if !targets.contains(&id) {
+                            targets.push(id);
+                        }
+                    }
+                }
+            }
+            _ => {
+                let mut visited = HashSet::from([target_id]);
+                let mut queue = VecDeque::from([target_id]);
+                while let Some(current_id) = queue.pop_front() {
+                    targets.push(current_id);
+                    for (_, source_id) in exports.get(&current_id).map(Vec::as_slice).unwrap_or_default() {
+                        if visited.insert(*source_id) {
+                            queue.push_back(*source_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Files defining `symbol` as exported by `file_id`, following re-exports
+    fn symbol_sources(
+        &self,
+        exports: &BarrelExports,
+        file_id: i64,
+        symbol: &str,
+        visited: &mut HashSet<i64>,
+    ) -> Vec<i64> {
+        let Some(entries) = exports.get(&file_id) else {
+            return vec![file_id];
+        };
+        if !visited.insert(file_id) {
+            return Vec::new();
+        }
+
+        if let Some((_, source_id)) = entries.iter().find(|(name, _)| name.as_deref() == Some(symbol)) {
+            return self.symbol_sources(exports, *source_id, symbol, visited);
+        }
+
+        // `export *` never re-exports a default export
+        let mut candidates: Vec<i64> = Vec::new();
+        if symbol != "default" {
+            for (_, source_id) in entries.iter().filter(|(name, _)| name.is_none()) {
+                for id in self.symbol_sources(exports, *source_id, symbol, visited) {
+                    if !candidates.contains(&id) {
+                        candidates.push(id);
+                    }
+                }
+            }
+        }
+
+        if candidates.len() <= 1 && !self.declares_export(file_id, symbol) {
+            return if candidates.is_empty() { vec![file_id] } else { candidates };
+        }
+
+        // Ambiguous: keep the files that visibly export the name, the barrel included
+        let declaring: Vec<i64> = std::iter::once(file_id)
+            .chain(candidates.iter().copied())
+            .filter(|id| self.declares_export(*id, symbol))
+            .collect();
+        if declaring.is_empty() { candidates } else { declaring }
+    }
+
+    /// Whether a file has an `export` line mentioning `symbol` as a whole word
+    fn declares_export(&self, file_id: i64, symbol: &str) -> bool {
+        let reader = self.content.get_or_init(|| {
+            ContentReader::open(self.cache.path().join("content.bin")).ok()
+        });
+        let Some(reader) = reader else {
+            return false;
+        };
+        let Ok(path) = self.get_file_path(file_id) else {
+            return false;
+        };
+        let Some(content) = reader.get_file_id_by_path(&path)
+            .and_then(|id| reader.get_file_content(id).ok())
+        else {
+            return false;
+        };
+
+        content.lines()
+            .map(str::trim_start)
+            .filter(|line| line.starts_with("export"))
+            .any(|line| {
+                line.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                    .any(|word| word == symbol)
+            })
+    }
+
+    /// Resolved re-exports of every file, loaded once
+    fn barrel_exports(&self) -> Result<&BarrelExports> {
+        if let Some(exports) = self.barrel_exports.get() {
+            return Ok(exports);
+        }
+
+        let db_path = self.cache.path().join("meta.db");
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for barrel export resolution")?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, exported_symbol, resolved_source_id
+             FROM file_exports
+             WHERE resolved_source_id IS NOT NULL
+             ORDER BY file_id, line_number"
+        )?;
+
+        let mut exports: BarrelExports = HashMap::new();
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (file_id, symbol, source_id) = row?;
+            exports.entry(file_id).or_default().push((symbol, source_id));
+        }
+
+        Ok(self.barrel_exports.get_or_init(|| exports))
+    }
+
+    /// Every resolved import edge with barrel imports attributed to defining files
+    fn barrel_edges(&self) -> Result<&Vec<(i64, i64)>> {
+        if let Some(edges) = self.barrel_edges.get() {
+            return Ok(edges);
+        }
+
+        let db_path = self.cache.path().join("meta.db");
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for dependency graph")?;
+
+        let mut stmt = conn.prepare(
+            "SELECT file_id, resolved_file_id, imported_symbols
+             FROM file_dependencies
+             WHERE resolved_file_id IS NOT NULL"
+        )?;
+
+        let rows: Vec<(i64, i64, Option<String>)> = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut edges = Vec::with_capacity(rows.len());
+        for (file_id, target_id, symbols_json) in rows {
+            let symbols: Option<Vec<String>> = symbols_json.and_then(|json| serde_json::from_str(&json).ok());
+            for real_id in self.resolve_barrel_import(target_id, symbols.as_deref())? {
+                edges.push((file_id, real_id));
+            }
+        }
+
+        Ok(self.barrel_edges.get_or_init(|| edges))
+    }
+
     /// Find disconnected components (islands) in the dependency graph
     ///
     /// An "island" is a connected component - a group of files that depend on each
@@ -638,24 +958,10 @@ impl DependencyIndex {
     /// Returns a list of islands, where each island is a vector of file IDs.
     /// Islands are sorted by size (largest first).
     pub fn find_islands(&self) -> Result<Vec<Vec<i64>>> {
-        let db_path = self.cache.path().join("meta.db");
-        let conn = Connection::open(&db_path)
-            .context("Failed to open meta.db for island analysis")?;
-
         // Build undirected dependency graph (A imports B => edge A-B and B-A)
         let mut graph: HashMap<i64, Vec<i64>> = HashMap::new();
 
-        let mut stmt = conn.prepare(
-            "SELECT file_id, resolved_file_id
-             FROM file_dependencies
-             WHERE resolved_file_id IS NOT NULL"
-        )?;
-
-        let dependencies: Vec<(i64, i64)> = stmt
-            .query_map([], |row| {
-                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        let dependencies = self.get_resolved_edges()?;
 
         // Build adjacency list (undirected) directly from resolved IDs
         for (file_id, target_id) in dependencies {
@@ -872,6 +1178,39 @@ impl DependencyIndex {
         }
     }
 
+    /// Get the files directly inside a directory (not in subdirectories)
+    ///
+    /// `dir` is relative to the index root; an empty string means the root itself.
+    /// Used to resolve imports that name a directory, like Go packages.
+    pub fn get_files_in_directory(&self, dir: &str) -> Result<Vec<(i64, String)>> {
+        let db_path = self.cache.path().join("meta.db");
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for directory lookup")?;
+
+        let dir = normalize_path_for_lookup(dir);
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir.trim_end_matches('/'))
+        };
+
+        // Compare the prefix exactly (LIKE would treat `_` in directory names as a wildcard)
+        let mut stmt = conn.prepare(
+            "SELECT id, path FROM files WHERE substr(path, 1, ?1) = ?2 ORDER BY path"
+        )?;
+
+        let files = stmt
+            .query_map(rusqlite::params![prefix.chars().count() as i64, prefix], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|(_, path)| path.strip_prefix(&prefix).is_some_and(|name| !name.contains('/')))
+            .collect();
+
+        Ok(files)
+    }
+
     /// Get dependency resolution statistics grouped by language
     ///
     /// Returns statistics showing how many internal dependencies are resolved vs unresolved
@@ -1074,6 +1413,9 @@ fn generate_path_variants(import_path: &str) -> Vec<String> {
 /// - `/home/user/project/services/foo.php` → `services/foo.php` (just filename portion)
 /// - `GetCaseByBatchNumberController.php` → `GetCaseByBatchNumberController.php`
 fn normalize_path_for_lookup(path: &str) -> String {
+    // Indexed paths use `/` separators on every platform
+    let path = crate::paths::to_slash(path);
+
     // Strip ./ and ../ prefixes
     let mut normalized = path.trim_start_matches("./").to_string();
     if normalized.starts_with("../") {
@@ -1193,7 +1535,7 @@ pub fn resolve_rust_import(
     resolved_path.and_then(|p| {
         p.strip_prefix(project_root)
             .ok()
-            .map(|rel| rel.to_string_lossy().to_string())
+            .map(crate::paths::path_to_slash)
     })
 }
 
@@ -1261,13 +1603,13 @@ pub fn resolve_rust_mod_declaration(
     // Try sibling file
     let sibling = current_dir.join(format!("{}.rs", mod_name));
     if sibling.exists() {
-        return Some(sibling.to_string_lossy().to_string());
+        return Some(crate::paths::path_to_slash(&sibling));
     }
 
     // Try directory module
     let dir_mod = current_dir.join(mod_name).join("mod.rs");
     if dir_mod.exists() {
-        return Some(dir_mod.to_string_lossy().to_string());
+        return Some(crate::paths::path_to_slash(&dir_mod));
     }
 
     None
@@ -1440,6 +1782,24 @@ mod tests {
         assert!(dependents.contains(&utils_id));
     }
 
+    #[test]
+    fn test_dependent_weights() {
+        let (_temp, cache) = setup_test_cache();
+        let deps_index = DependencyIndex::new(cache);
+
+        let lib_id = 2i64;
+        let symbols = |names: &[&str]| -> Option<Vec<String>> { Some(names.iter().map(|n| n.to_string()).collect()) };
+
+        // File 1 imports two symbols in one statement and a plain import in another
+        deps_index.insert_dependency(1, "lib".to_string(), Some(lib_id), ImportType::Internal, 1, symbols(&["a", "b"])).unwrap();
+        deps_index.insert_dependency(1, "lib".to_string(), Some(lib_id), ImportType::Internal, 2, None).unwrap();
+        // File 3 imports one symbol
+        deps_index.insert_dependency(3, "lib".to_string(), Some(lib_id), ImportType::Internal, 1, symbols(&["a"])).unwrap();
+
+        assert_eq!(deps_index.get_dependent_weights(lib_id).unwrap(), vec![(1, 3), (3, 1)]);
+        assert!(deps_index.get_dependent_weights(99).unwrap().is_empty());
+    }
+
     #[test]
     fn test_transitive_dependencies() {
         let (_temp, cache) = setup_test_cache();
@@ -1482,6 +1842,30 @@ mod tests {
         assert_eq!(transitive.get(&file3), Some(&2));
     }
 
+    #[test]
+    fn test_transitive_dependents() {
+        let (_temp, cache) = setup_test_cache();
+        let deps_index = DependencyIndex::new(cache);
+
+        // file1 → file2 → file3
+        for (from, to) in [(1i64, 2i64), (2, 3)] {
+            deps_index
+                .insert_dependency(from, format!("file{}", to), Some(to), ImportType::Internal, 1, None)
+                .unwrap();
+        }
+
+        let dependents = deps_index.get_transitive_dependents(3, 5).unwrap();
+        assert_eq!(dependents.len(), 3);
+        assert_eq!(dependents.get(&3), Some(&0));
+        assert_eq!(dependents.get(&2), Some(&1));
+        assert_eq!(dependents.get(&1), Some(&2));
+
+        // Depth limit stops at direct dependents
+        let direct = deps_index.get_transitive_dependents(3, 1).unwrap();
+        assert_eq!(direct.len(), 2);
+        assert!(!direct.contains_key(&1));
+    }
+
     #[test]
     fn test_batch_insert() {
         let (_temp, cache) = setup_test_cache();
@@ -1659,4 +2043,62 @@ mod tests {
 
         assert_eq!(resolved, Some("src/models/language.rs".to_string()));
     }
+
+    #[test]
+    fn test_barrel_resolution() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        for path in ["src/app.ts", "src/components/index.ts", "src/components/Button.tsx", "src/components/Card.tsx", "src/main.ts"] {
+            cache.update_file(path, "typescript", 10).unwrap();
+        }
+        let (app, barrel, button, card, main) = (1, 2, 3, 4, 5);
+
+        // index.ts: export { Button } from './Button'; export * from './Card';
+        let deps_index = DependencyIndex::new(cache);
+        deps_index.insert_export(barrel, Some("Button".to_string()), "./Button".to_string(), Some(button), 1).unwrap();
+        deps_index.insert_export(barrel, None, "./Card".to_string(), Some(card), 2).unwrap();
+
+        // app.ts imports Button and Card from the barrel; main.ts imports it for side effects
+        deps_index.insert_dependency(app, "./components".to_string(), Some(barrel), ImportType::Internal, 1, Some(vec!["Button".to_string()])).unwrap();
+        deps_index.insert_dependency(app, "./components".to_string(), Some(barrel), ImportType::Internal, 2, Some(vec!["Card".to_string()])).unwrap();
+        deps_index.insert_dependency(main, "./components".to_string(), Some(barrel), ImportType::Internal, 1, None).unwrap();
+
+        // Without barrel resolution, everything points at the barrel
+        assert!(deps_index.get_dependents(button).unwrap().is_empty());
+        assert_eq!(deps_index.find_hotspots(None, 1).unwrap(), vec![(barrel, 3)]);
+
+        let deps_index = DependencyIndex::new(CacheManager::new(temp.path())).with_barrel_resolution(true);
+        let targets: Vec<Option<i64>> = deps_index.get_dependencies(app).unwrap()
+            .iter()
+            .map(|dep| dep.resolved_file_id)
+            .collect();
+        assert_eq!(targets, vec![Some(button), Some(card)]);
+
+        assert_eq!(deps_index.resolve_barrel_import(barrel, None).unwrap(), vec![barrel, button, card]);
+        assert_eq!(deps_index.get_dependents(button).unwrap(), vec![app, main]);
+        assert_eq!(deps_index.find_hotspots(None, 2).unwrap(), vec![(button, 2), (card, 2)]);
+        assert_eq!(deps_index.get_transitive_deps(app, 2).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_get_files_in_directory() {
+        let (_temp, cache) = setup_test_cache();
+        cache.update_file("src/models/user.rs", "rust", 20).unwrap();
+        cache.update_file("src_old/main.rs", "rust", 10).unwrap();
+        cache.update_file("build.rs", "rust", 5).unwrap();
+        let deps_index = DependencyIndex::new(cache);
+
+        let paths = |dir: &str| -> Vec<String> {
+            deps_index.get_files_in_directory(dir).unwrap()
+                .into_iter()
+                .map(|(_, path)| path)
+                .collect()
+        };
+
+        assert_eq!(paths("src"), vec!["src/lib.rs", "src/main.rs", "src/utils.rs"]);
+        assert_eq!(paths("src/models"), vec!["src/models/user.rs"]);
+        assert_eq!(paths(""), vec!["build.rs"]);
+        assert!(paths("missing").is_empty());
+    }
 }
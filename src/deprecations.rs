@@ -0,0 +1,206 @@
+//! Registry of deprecations and behavior changes surfaced in CLI output
+//!
+//! Every notice Reflex can emit about evolving flags or defaults is declared
+//! once in [`NOTICES`]. Notices reach users through a single channel:
+//! - Text output: printed to stderr after the command's normal output
+//! - JSON output: the `warnings` array of the response
+//!
+//! Each notice has a stable `code` so scripts and agents can match on it
+//! instead of parsing prose.
+//!
+//! Notices come in two flavors:
+//! - **Flag notices** ([`Trigger::Flag`]) fire automatically whenever the flag
+//!   appears on the command line. Deprecating a flag only needs a registry entry.
+//! - **Behavior notices** ([`Trigger::Raised`]) are raised by the code path whose
+//!   behavior changed, and only when the change actually affected the result.
+
+use crate::models::{CliWarning, CliWarningKind, Language};
+use crate::parsers::ParserFactory;
+
+/// When a notice fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// Fires when `flag` (e.g. "--exact") is passed to `command` (e.g. "query")
+    Flag {
+        command: &'static str,
+        flag: &'static str,
+    },
+    /// Raised explicitly by the affected code path via [`warning`]
+    Raised,
+}
+
+/// A registered deprecation or behavior change
+#[derive(Debug, Clone, Copy)]
+pub struct Notice {
+    /// Stable identifier (kebab-case)
+    pub code: &'static str,
+    pub kind: CliWarningKind,
+    /// Version that introduced the change
+    pub since: &'static str,
+    pub trigger: Trigger,
+    /// What changed
+    pub message: &'static str,
+    /// What to do about it
+    pub migration: &'static str,
+}
+
+impl Notice {
+    fn to_warning(self) -> CliWarning {
+        CliWarning {
+            code: self.code.to_string(),
+            kind: self.kind,
+            since: self.since.to_string(),
+            message: self.message.to_string(),
+            migration: self.migration.to_string(),
+        }
+    }
+}
+
+/// Code for the size-tuned default limit notice
+pub const TUNED_DEFAULT_LIMIT: &str = "tuned-default-limit";
+
+/// Code for the notice raised when a language's tree-sitter grammar failed to load
+pub const DEGRADED_GRAMMAR: &str = "degraded-grammar";
+
+/// All known notices
+pub const NOTICES: &[Notice] = &[
+    Notice {
+        code: TUNED_DEFAULT_LIMIT,
+        kind: CliWarningKind::BehaviorChange,
+        since: "1.0.4",
+        trigger: Trigger::Raised,
+        message: "The default result limit is now tuned to repository size and is below the previous fixed default of 100; more results are available",
+        migration: "Pass --limit 100 (or set default_limit under [search] in .reflex/config.toml) to keep the previous page size, or paginate with --offset",
+    },
+    Notice {
+        code: DEGRADED_GRAMMAR,
+        kind: CliWarningKind::Degraded,
+        since: "1.0.4",
+        trigger: Trigger::Raised,
+        message: "A tree-sitter grammar failed to load; symbols for this language come from a regex fallback parser and may be incomplete",
+        migration: "Run 'rfx doctor' for the grammar error, then reinstall or rebuild rfx for this platform",
+    },
+];
+
+/// Build the warning for a registered code
+///
+/// Returns None for unknown codes (logged, so a typo never breaks a command).
+pub fn warning(code: &str) -> Option<CliWarning> {
+    let notice = NOTICES.iter().find(|n| n.code == code);
+    if notice.is_none() {
+        log::warn!("Unknown deprecation notice code: {}", code);
+    }
+    notice.map(|n| n.to_warning())
+}
+
+/// One [`DEGRADED_GRAMMAR`] warning per language on the fallback parser
+///
+/// With `language` set, only that language is reported. The message names the
+/// language and the grammar error.
+pub fn degraded_grammar_warnings(language: Option<Language>) -> Vec<CliWarning> {
+    ParserFactory::degraded_languages()
+        .iter()
+        .filter(|(lang, _)| language.is_none_or(|l| l == *lang))
+        .filter_map(|(lang, error)| {
+            let mut w = warning(DEGRADED_GRAMMAR)?;
+            w.message = format!("{} ({:?}: {})", w.message, lang, error);
+            Some(w)
+        })
+        .collect()
+}
+
+/// Collect flag notices triggered by a command line (`args` excludes the binary name)
+pub fn flag_warnings(args: &[String]) -> Vec<CliWarning> {
+    flag_warnings_in(NOTICES, args)
+}
+
+fn flag_warnings_in(notices: &[Notice], args: &[String]) -> Vec<CliWarning> {
+    // The subcommand is the first argument that isn't a global flag
+    let Some(command) = args.iter().find(|a| !a.starts_with('-')) else {
+        return Vec::new();
+    };
+
+    notices
+        .iter()
+        .filter(|n| match n.trigger {
+            Trigger::Flag { command: c, flag } => {
+                c == command && args.iter().any(|a| {
+                    a == flag || a.strip_prefix(flag).is_some_and(|rest| rest.starts_with('='))
+                })
+            }
+            Trigger::Raised => false,
+        })
+        .map(|n| n.to_warning())
+        .collect()
+}
+
+/// Print warnings to stderr (text output mode)
+pub fn print_warnings(warnings: &[CliWarning]) {
+    use owo_colors::OwoColorize;
+
+    for w in warnings {
+        eprintln!("\n{} {}", format!("warning[{}]:", w.code).yellow().bold(), w.message.yellow());
+        eprintln!("  {} {}", "→".dimmed(), w.migration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const TEST_NOTICES: &[Notice] = &[
+        Notice {
+            code: "old-flag",
+            kind: CliWarningKind::Deprecated,
+            since: "1.0.0",
+            trigger: Trigger::Flag { command: "query", flag: "--old" },
+            message: "--old is deprecated",
+            migration: "Use --new",
+        },
+        Notice {
+            code: "raised-only",
+            kind: CliWarningKind::BehaviorChange,
+            since: "1.0.0",
+            trigger: Trigger::Raised,
+            message: "Something changed",
+            migration: "Adapt",
+        },
+    ];
+
+    #[test]
+    fn test_flag_notice_fires_for_matching_command() {
+        let warnings = flag_warnings_in(TEST_NOTICES, &args(&["query", "foo", "--old"]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "old-flag");
+
+        let warnings = flag_warnings_in(TEST_NOTICES, &args(&["-v", "query", "foo", "--old=1"]));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_flag_notice_ignores_other_commands_and_prefixes() {
+        assert!(flag_warnings_in(TEST_NOTICES, &args(&["deps", "--old"])).is_empty());
+        assert!(flag_warnings_in(TEST_NOTICES, &args(&["query", "--older"])).is_empty());
+        assert!(flag_warnings_in(TEST_NOTICES, &args(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_registry_codes_are_unique_and_resolvable() {
+        for (i, notice) in NOTICES.iter().enumerate() {
+            assert!(NOTICES[i + 1..].iter().all(|n| n.code != notice.code), "duplicate code {}", notice.code);
+            assert_eq!(warning(notice.code).unwrap().code, notice.code);
+        }
+        assert!(warning("no-such-code").is_none());
+    }
+
+    #[test]
+    fn test_warning_serializes_with_kind() {
+        let json = serde_json::to_value(warning(TUNED_DEFAULT_LIMIT).unwrap()).unwrap();
+        assert_eq!(json["code"], TUNED_DEFAULT_LIMIT);
+        assert_eq!(json["kind"], "behavior_change");
+    }
+}
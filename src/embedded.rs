@@ -0,0 +1,583 @@
+//! Embedded sub-language regions
+//!
+//! Host files often carry code in another language inside string literals: SQL
+//! in Python and Go strings, GraphQL in `gql`-tagged JavaScript template
+//! literals, HTML around `<?php ... ?>` blocks or in PHP heredocs. When enabled,
+//! the indexer carves those regions out of each host file and records their line
+//! spans with the embedded language in `meta.db`. `rfx query --lang sql` then
+//! matches lines inside SQL regions, tagging the results `sql`.
+//!
+//! Carving is heuristic and runs on the raw text (no tree-sitter):
+//!
+//! - Tagged literals use their tag: `gql`/`graphql`, `sql`, `html` template
+//!   literals in JavaScript/TypeScript and `<<<SQL`/`<<<HTML` heredocs in PHP.
+//! - Other literals are classified by content: a leading `SELECT ... FROM`,
+//!   `INSERT INTO`, `UPDATE ... SET`, `DELETE FROM`, `CREATE/ALTER/DROP TABLE`
+//!   is SQL; `query`/`mutation`/`subscription`/`fragment` followed by `{` is
+//!   GraphQL; markup starting with a tag is HTML.
+//!
+//! Disabled by default; enable it in `.reflex/config.toml`:
+//!
+//! ```toml
+//! [embedded]
+//! enabled = true
+//! ```
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+use crate::models::Language;
+
+/// Languages that can be carved out of host files
+pub const EMBEDDED_LANGUAGES: &[Language] = &[Language::Sql, Language::GraphQL, Language::Html];
+
+/// `[embedded]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddedConfig {
+    /// Carve embedded regions while indexing
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Load the `[embedded]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to disabled if the file or section is missing.
+pub fn load_embedded_config(cache_path: &Path) -> Result<EmbeddedConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(EmbeddedConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("embedded") {
+        Some(section) => section.clone().try_into()
+            .context("Failed to parse [embedded] section in .reflex/config.toml"),
+        None => Ok(EmbeddedConfig::default()),
+    }
+}
+
+/// A line span of a host file holding code in another language
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddedRegion {
+    pub language: Language,
+    /// First line of the embedded code (1-indexed)
+    pub start_line: usize,
+    /// Last line of the embedded code (inclusive)
+    pub end_line: usize,
+}
+
+impl EmbeddedRegion {
+    /// Whether `line` lies inside this region
+    pub fn contains(&self, line: usize) -> bool {
+        (self.start_line..=self.end_line).contains(&line)
+    }
+}
+
+/// Carve embedded-language regions out of a host file
+///
+/// Hosts without carving support (and files with no embedded code) yield no regions.
+pub fn carve_regions(host: Language, content: &str) -> Vec<EmbeddedRegion> {
+    if !matches!(
+        host,
+        Language::Python | Language::Go | Language::JavaScript | Language::TypeScript | Language::PHP
+    ) {
+        return Vec::new();
+    }
+
+    scan_literals(host, content)
+        .into_iter()
+        .filter_map(|literal| {
+            let language = classify(literal.tag.as_deref(), &literal.body)?;
+            Some(EmbeddedRegion {
+                language,
+                start_line: literal.start_line,
+                end_line: literal.end_line,
+            })
+        })
+        .collect()
+}
+
+/// A string literal (or PHP inline HTML) found while scanning a host file
+struct Literal {
+    /// Template literal tag or heredoc label, if any
+    tag: Option<String>,
+    body: String,
+    /// Lines of the first and last non-blank body characters
+    start_line: usize,
+    end_line: usize,
+}
+
+impl Literal {
+    /// Build a literal whose body starts on `line`; blank bodies are dropped
+    fn new(tag: Option<String>, body: String, line: usize) -> Option<Self> {
+        let first = body.find(|c: char| !c.is_whitespace())?;
+        let last = body.rfind(|c: char| !c.is_whitespace())?;
+        let start_line = line + body[..first].matches('\n').count();
+        let end_line = line + body[..last].matches('\n').count();
+
+        Some(Self { tag, body, start_line, end_line })
+    }
+}
+
+/// Find string literals in a host file, skipping comments
+fn scan_literals(host: Language, content: &str) -> Vec<Literal> {
+    let chars: Vec<char> = content.chars().collect();
+    let hash_comments = matches!(host, Language::Python | Language::PHP);
+    let slash_comments = host != Language::Python;
+    let template_literals = matches!(host, Language::Go | Language::JavaScript | Language::TypeScript);
+    // Go uses '' for runes, not strings
+    let single_quote_strings = host != Language::Go;
+
+    let mut literals = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    // PHP files start as inline HTML until the first `<?php`
+    let mut in_html = host == Language::PHP;
+
+    while i < chars.len() {
+        let rest = &chars[i..];
+
+        if in_html {
+            let end = find(&chars, i, &['<', '?']).unwrap_or(chars.len());
+            let body: String = chars[i..end].iter().collect();
+            if body.contains('<') {
+                literals.extend(Literal::new(Some("html".to_string()), body, line));
+            }
+            line += count_newlines(&chars[i..end]);
+            i = end;
+            in_html = false;
+            continue;
+        }
+
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let start = i;
+
+        let literal_end = if c == '\n' {
+            i += 1;
+            line += 1;
+            continue;
+        } else if host == Language::PHP && rest.starts_with(&['?', '>']) {
+            i += 2;
+            in_html = true;
+            continue;
+        } else if (hash_comments && c == '#') || (slash_comments && c == '/' && next == Some('/')) {
+            // Leave the newline for the main loop to count
+            i = find(&chars, i, &['\n']).unwrap_or(chars.len());
+            continue;
+        } else if slash_comments && c == '/' && next == Some('*') {
+            find(&chars, i + 2, &['*', '/']).map(|end| end + 2)
+        } else if host == Language::Python && (c == '"' || c == '\'') && rest.starts_with(&[c, c, c]) {
+            read_literal(&chars, i + 3, &[c, c, c], true, true).map(|(body, end)| {
+                literals.extend(Literal::new(None, body, line));
+                end
+            })
+        } else if template_literals && c == '`' {
+            // Go raw strings have no escapes
+            let escapes = host != Language::Go;
+            read_literal(&chars, i + 1, &['`'], escapes, true).map(|(body, end)| {
+                literals.extend(Literal::new(template_tag(&chars, i), body, line));
+                end
+            })
+        } else if host == Language::PHP && rest.starts_with(&['<', '<', '<']) {
+            read_heredoc(&chars, i + 3).map(|(tag, body, body_start, end)| {
+                let body_line = line + count_newlines(&chars[i..body_start]);
+                literals.extend(Literal::new(Some(tag), body, body_line));
+                end
+            })
+        } else if c == '"' || (single_quote_strings && c == '\'') {
+            read_literal(&chars, i + 1, &[c], true, false).map(|(body, end)| {
+                literals.extend(Literal::new(None, body, line));
+                end
+            })
+        } else {
+            None
+        };
+
+        // Unterminated literals are skipped one character at a time
+        i = literal_end.unwrap_or(start + 1);
+        line += count_newlines(&chars[start..i]);
+    }
+
+    literals
+}
+
+/// Index of the next occurrence of `needle` at or after `from`
+fn find(chars: &[char], from: usize, needle: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&i| chars[i..].starts_with(needle))
+}
+
+fn count_newlines(chars: &[char]) -> usize {
+    chars.iter().filter(|&&c| c == '\n').count()
+}
+
+/// Read a literal body from `start` up to `delim`, returning it and the index past the delimiter
+fn read_literal(chars: &[char], start: usize, delim: &[char], escapes: bool, multiline: bool) -> Option<(String, usize)> {
+    let mut i = start;
+    while i < chars.len() {
+        let c = chars[i];
+        if escapes && c == '\\' {
+            i += 2;
+            continue;
+        }
+        if !multiline && c == '\n' {
+            return None;
+        }
+        if chars[i..].starts_with(delim) {
+            return Some((chars[start..i].iter().collect(), i + delim.len()));
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Identifier directly before a template literal's backtick (`gql`, `sql`, `html`)
+fn template_tag(chars: &[char], backtick: usize) -> Option<String> {
+    let start = (0..backtick)
+        .rev()
+        .take_while(|&i| chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+        .last()?;
+    let tag: String = chars[start..backtick].iter().collect();
+    // `db.sql` tags by their last component
+    tag.rsplit('.').next().map(|t| t.to_string())
+}
+
+/// Read a PHP heredoc/nowdoc after `<<<`
+///
+/// Returns the label, the body, the index where the body starts and the index
+/// past the closing label.
+fn read_heredoc(chars: &[char], start: usize) -> Option<(String, String, usize, usize)> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut i = start;
+    while chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t') {
+        i += 1;
+    }
+    let quote = chars.get(i).copied().filter(|c| *c == '\'' || *c == '"');
+    if quote.is_some() {
+        i += 1;
+    }
+    let label_start = i;
+    while chars.get(i).copied().is_some_and(is_ident) {
+        i += 1;
+    }
+    let label: String = chars[label_start..i].iter().collect();
+    if label.is_empty() {
+        return None;
+    }
+    if let Some(q) = quote {
+        if chars.get(i) != Some(&q) {
+            return None;
+        }
+        i += 1;
+    }
+    if chars.get(i) == Some(&'\r') {
+        i += 1;
+    }
+    if chars.get(i) != Some(&'\n') {
+        return None;
+    }
+
+    let body_start = i + 1;
+    let mut line_start = body_start;
+    loop {
+        let line_end = find(chars, line_start, &['\n']).unwrap_or(chars.len());
+        let indent = chars[line_start..line_end]
+            .iter()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let label_pos = line_start + indent;
+        let after = label_pos + label.chars().count();
+
+        let closes = chars.get(label_pos..after).is_some_and(|l| l.iter().copied().eq(label.chars()))
+            && !chars.get(after).copied().is_some_and(is_ident);
+        if closes {
+            let body_end = line_start.saturating_sub(1).max(body_start);
+            let body = chars[body_start..body_end].iter().collect();
+            return Some((label, body, body_start, after));
+        }
+
+        if line_end >= chars.len() {
+            return None;
+        }
+        line_start = line_end + 1;
+    }
+}
+
+/// Embedded language of a literal, from its tag or else its content
+fn classify(tag: Option<&str>, body: &str) -> Option<Language> {
+    if let Some(tag) = tag {
+        match tag.to_lowercase().as_str() {
+            "sql" | "psql" | "mysql" | "sqlite" => return Some(Language::Sql),
+            "gql" | "graphql" => return Some(Language::GraphQL),
+            "html" if looks_like_html(body) => return Some(Language::Html),
+            _ => {}
+        }
+    }
+
+    if looks_like_sql(body) {
+        Some(Language::Sql)
+    } else if looks_like_graphql(body) {
+        Some(Language::GraphQL)
+    } else if looks_like_html(body) {
+        Some(Language::Html)
+    } else {
+        None
+    }
+}
+
+fn words(body: &str) -> impl Iterator<Item = &str> {
+    body.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+}
+
+/// Leading SQL statement keyword followed by its companion clause
+///
+/// The keyword must be all upper- or all lowercase so UI strings such as
+/// "Select a file from the list" aren't mistaken for queries.
+fn looks_like_sql(body: &str) -> bool {
+    let trimmed = body.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+    let Some(first) = words(trimmed).next() else {
+        return false;
+    };
+    if !trimmed.starts_with(first) {
+        return false;
+    }
+    if first != first.to_lowercase() && first != first.to_uppercase() {
+        return false;
+    }
+
+    let companions: &[&str] = match first.to_lowercase().as_str() {
+        "select" | "delete" => &["from"],
+        "insert" => &["into"],
+        "update" => &["set"],
+        "create" | "alter" | "drop" => &["table", "index", "view"],
+        "with" => &["select"],
+        _ => return false,
+    };
+
+    words(trimmed)
+        .skip(1)
+        .any(|w| companions.contains(&w.to_lowercase().as_str()))
+}
+
+/// GraphQL operation or fragment definition
+fn looks_like_graphql(body: &str) -> bool {
+    let trimmed = body.trim_start();
+    let Some(first) = words(trimmed).next() else {
+        return false;
+    };
+
+    trimmed.starts_with(first)
+        && matches!(first, "query" | "mutation" | "subscription" | "fragment")
+        && trimmed.contains('{')
+}
+
+/// Markup starting with a tag (`<div ...>`, `</div>`, `<!DOCTYPE html>`)
+fn looks_like_html(body: &str) -> bool {
+    let trimmed = body.trim();
+    let mut chars = trimmed.chars();
+    chars.next() == Some('<')
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '!' || c == '/')
+        && trimmed.ends_with('>')
+}
+
+/// Create the embedded region table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and lazily by
+/// `EmbeddedIndex` so caches created before embedded indexing keep working.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS embedded_regions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            language TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_embedded_regions_language ON embedded_regions(language)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_embedded_regions_file ON embedded_regions(file_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Manages embedded region storage and lookups
+pub struct EmbeddedIndex {
+    cache: CacheManager,
+}
+
+impl EmbeddedIndex {
+    /// Create a new embedded region index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for embedded regions")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Replace the stored regions for a set of files in one transaction
+    ///
+    /// Files with an empty region list are cleared, so removed (or no longer
+    /// carved) regions disappear on reindex.
+    pub fn replace_file_regions(&self, files: &[(i64, Vec<EmbeddedRegion>)]) -> Result<usize> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+
+        {
+            let mut delete_stmt = tx.prepare("DELETE FROM embedded_regions WHERE file_id = ?")?;
+            let mut insert_stmt = tx.prepare(
+                "INSERT INTO embedded_regions (file_id, language, start_line, end_line)
+                 VALUES (?, ?, ?, ?)"
+            )?;
+
+            for (file_id, regions) in files {
+                delete_stmt.execute([file_id])?;
+                for region in regions {
+                    insert_stmt.execute(rusqlite::params![
+                        file_id,
+                        format!("{:?}", region.language),
+                        region.start_line as i64,
+                        region.end_line as i64,
+                    ])?;
+                    inserted += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Stored {} embedded regions for {} files", inserted, files.len());
+        Ok(inserted)
+    }
+
+    /// Regions of `language`, keyed by file path and sorted by start line
+    pub fn regions_for(&self, language: Language) -> Result<HashMap<String, Vec<EmbeddedRegion>>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.path, er.start_line, er.end_line
+             FROM embedded_regions er
+             JOIN files f ON er.file_id = f.id
+             WHERE er.language = ?
+             ORDER BY f.path, er.start_line"
+        )?;
+
+        let rows = stmt.query_map([format!("{:?}", language)], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)? as usize,
+                row.get::<_, i64>(2)? as usize,
+            ))
+        })?;
+
+        let mut regions: HashMap<String, Vec<EmbeddedRegion>> = HashMap::new();
+        for row in rows {
+            let (path, start_line, end_line) = row?;
+            regions.entry(path).or_default().push(EmbeddedRegion { language, start_line, end_line });
+        }
+
+        Ok(regions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn carved(host: Language, content: &str) -> Vec<(Language, usize, usize)> {
+        carve_regions(host, content)
+            .into_iter()
+            .map(|r| (r.language, r.start_line, r.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_python_sql_strings() {
+        let content = "# don't touch\n\
+                       QUERY = \"\"\"\n    SELECT id, name\n    FROM users\n\"\"\"\n\
+                       label = \"Select a file from the list\"\n\
+                       count = db.execute('delete from sessions where expired')\n";
+        assert_eq!(
+            carved(Language::Python, content),
+            vec![(Language::Sql, 3, 4), (Language::Sql, 7, 7)]
+        );
+    }
+
+    #[test]
+    fn test_go_raw_string_sql() {
+        let content = "package db\n\nconst q = `\nINSERT INTO users (name)\nVALUES ($1)`\n\nvar r = 'x'\n";
+        assert_eq!(carved(Language::Go, content), vec![(Language::Sql, 4, 5)]);
+    }
+
+    #[test]
+    fn test_javascript_tagged_templates() {
+        let content = "const GET_USER = gql`\n  query GetUser($id: ID!) {\n    user(id: $id) { name }\n  }\n`;\n\
+                       // `not a literal`\n\
+                       const row = html`<li>${name}</li>`;\n\
+                       const msg = `hello ${name}`;\n";
+        assert_eq!(
+            carved(Language::JavaScript, content),
+            vec![(Language::GraphQL, 2, 4), (Language::Html, 7, 7)]
+        );
+    }
+
+    #[test]
+    fn test_php_inline_html_and_heredoc() {
+        let content = "<div class=\"header\">\n  <?php echo $title; ?>\n</div>\n<?php\n\
+                       $sql = <<<SQL\n    SELECT * FROM posts\n    SQL;\n\
+                       $x = 1;\n";
+        assert_eq!(
+            carved(Language::PHP, content),
+            vec![
+                (Language::Html, 1, 1),
+                (Language::Html, 3, 3),
+                (Language::Sql, 6, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_host_has_no_regions() {
+        assert!(carve_regions(Language::Rust, "let q = \"SELECT * FROM t\";").is_empty());
+    }
+
+    #[test]
+    fn test_load_embedded_config() {
+        let temp = TempDir::new().unwrap();
+        assert!(!load_embedded_config(temp.path()).unwrap().enabled);
+
+        std::fs::write(temp.path().join(CONFIG_TOML), "[embedded]\nenabled = true\n").unwrap();
+        assert!(load_embedded_config(temp.path()).unwrap().enabled);
+    }
+}
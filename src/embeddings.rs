@@ -0,0 +1,673 @@
+//! Chunked embeddings for hybrid semantic search
+//!
+//! `rfx embed` splits every indexed file into chunks, embeds each chunk and
+//! stores the vectors in meta.db:
+//!
+//! - Definitions (functions, methods, types, ...) become one chunk each, capped
+//!   at `chunk_lines` lines; files without definitions are cut into windows of
+//!   `chunk_lines` lines.
+//! - Vectors are keyed by the file's content hash, so re-running `rfx embed`
+//!   only embeds files that changed since. Switching the embedding model
+//!   re-embeds everything.
+//!
+//! `rfx query --semantic "retry logic for http client"` embeds the query and
+//! ranks chunks by a blend of vector similarity and keyword hits (query words
+//! found in the chunk through the trigram index), weighted by `keyword_weight`.
+//!
+//! Embeddings come from the `[embeddings]` section of `.reflex/config.toml`:
+//!
+//! ```toml
+//! [embeddings]
+//! provider = "openai"                # local (default), openai or ollama
+//! model = "text-embedding-3-small"   # provider default if unset
+//! ```
+//!
+//! `local` is a hashed bag of identifier words that needs no network or model
+//! download; it matches vocabulary rather than meaning. `openai` uses the API
+//! key configured for `rfx ask`, and `ollama` a local Ollama server (`url`,
+//! default `http://localhost:11434`).
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+use crate::content_store::ContentReader;
+use crate::models::{Language, SymbolKind};
+
+/// Key of the embedding model record in the meta.db `config` table
+const MODEL_KEY: &str = "embeddings";
+
+/// Chunks embedded per provider request
+const BATCH_SIZE: usize = 64;
+
+/// Characters of a chunk sent to the embedding model
+const MAX_CHUNK_CHARS: usize = 6000;
+
+/// Dimensions of the `local` embedder
+const LOCAL_DIMENSIONS: usize = 512;
+
+/// Words too common to carry meaning in a query or chunk
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "code", "does", "for", "from", "how", "in", "is",
+    "it", "of", "on", "or", "that", "the", "this", "to", "what", "when", "where", "which", "with",
+];
+
+/// `[embeddings]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    /// Embedding provider: local, openai or ollama
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// Model override (provider default if unset)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Server URL for ollama
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Maximum lines per chunk
+    #[serde(default = "default_chunk_lines")]
+    pub chunk_lines: usize,
+    /// Share of the keyword score in the hybrid ranking (0.0-1.0)
+    #[serde(default = "default_keyword_weight")]
+    pub keyword_weight: f64,
+    /// Chunks considered per semantic query before filters and pagination
+    #[serde(default = "default_candidates")]
+    pub candidates: usize,
+}
+
+fn default_provider() -> String {
+    "local".to_string()
+}
+
+fn default_chunk_lines() -> usize {
+    60
+}
+
+fn default_keyword_weight() -> f64 {
+    0.3
+}
+
+fn default_candidates() -> usize {
+    100
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_provider(),
+            model: None,
+            url: None,
+            chunk_lines: default_chunk_lines(),
+            keyword_weight: default_keyword_weight(),
+            candidates: default_candidates(),
+        }
+    }
+}
+
+/// Load the `[embeddings]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_config(cache_path: &Path) -> Result<EmbeddingsConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(EmbeddingsConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("embeddings") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [embeddings] section in .reflex/config.toml"),
+        None => Ok(EmbeddingsConfig::default()),
+    }
+}
+
+/// Computes embedding vectors for text
+pub trait Embedder {
+    /// Provider and model (`openai:text-embedding-3-small`); vectors of different ids don't mix
+    fn id(&self) -> String;
+
+    /// One vector per text, in order
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Create the embedder configured in `[embeddings]`
+pub fn create_embedder(config: &EmbeddingsConfig) -> Result<Box<dyn Embedder>> {
+    match config.provider.to_lowercase().as_str() {
+        "local" => Ok(Box::new(LocalEmbedder)),
+        "openai" => Ok(Box::new(OpenAiEmbedder {
+            api_key: crate::semantic::config::get_api_key("openai")?,
+            model: config.model.clone().unwrap_or_else(|| "text-embedding-3-small".to_string()),
+        })),
+        "ollama" => Ok(Box::new(OllamaEmbedder {
+            url: config.url.clone().unwrap_or_else(|| "http://localhost:11434".to_string()),
+            model: config.model.clone().unwrap_or_else(|| "nomic-embed-text".to_string()),
+        })),
+        "anthropic" | "groq" => anyhow::bail!(
+            "Provider '{}' has no embeddings API. Set [embeddings] provider to local, openai or ollama.",
+            config.provider
+        ),
+        other => anyhow::bail!("Unknown embeddings provider: {}. Supported: local, openai, ollama", other),
+    }
+}
+
+/// Hashed bag of identifier words (no network, no model)
+///
+/// Each word part (`HttpClient` → `http`, `client`) and its character trigrams
+/// are hashed into a fixed number of signed buckets.
+struct LocalEmbedder;
+
+impl LocalEmbedder {
+    fn vector(text: &str) -> Vec<f32> {
+        let mut vector = vec![0f32; LOCAL_DIMENSIONS];
+        let mut add = |feature: &str, weight: f32| {
+            let hash = fnv1a(feature.as_bytes());
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % LOCAL_DIMENSIONS as u64) as usize] += sign * weight;
+        };
+
+        for word in words(text) {
+            add(&word, 1.0);
+            let chars: Vec<char> = format!("^{}$", word).chars().collect();
+            for trigram in chars.windows(3) {
+                add(&trigram.iter().collect::<String>(), 0.3);
+            }
+        }
+
+        normalize(&mut vector);
+        vector
+    }
+}
+
+impl Embedder for LocalEmbedder {
+    fn id(&self) -> String {
+        format!("local:hashed-{}", LOCAL_DIMENSIONS)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| Self::vector(text)).collect())
+    }
+}
+
+/// OpenAI embeddings API
+struct OpenAiEmbedder {
+    api_key: String,
+    model: String,
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn id(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let request = serde_json::json!({ "model": self.model, "input": texts });
+        let response = post_json(
+            "https://api.openai.com/v1/embeddings",
+            Some(&self.api_key),
+            request,
+        )?;
+
+        let data = response["data"].as_array().context("No data in OpenAI embeddings response")?;
+        data.iter().map(|item| parse_vector(&item["embedding"])).collect()
+    }
+}
+
+/// Embedding model served by a local Ollama instance
+struct OllamaEmbedder {
+    url: String,
+    model: String,
+}
+
+impl Embedder for OllamaEmbedder {
+    fn id(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let request = serde_json::json!({ "model": self.model, "input": texts });
+        let response = post_json(&format!("{}/api/embed", self.url.trim_end_matches('/')), None, request)?;
+
+        let embeddings = response["embeddings"].as_array().context("No embeddings in Ollama response")?;
+        embeddings.iter().map(parse_vector).collect()
+    }
+}
+
+/// POST a JSON request and return the JSON response
+///
+/// Runs on its own thread and runtime, so it works both from plain CLI code
+/// and from handlers already inside a tokio runtime (HTTP server, MCP).
+fn post_json(url: &str, bearer: Option<&str>, body: serde_json::Value) -> Result<serde_json::Value> {
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("Failed to create async runtime")?;
+
+            runtime.block_on(async {
+                let mut request = reqwest::Client::new().post(url).json(&body);
+                if let Some(token) = bearer {
+                    request = request.header("Authorization", format!("Bearer {}", token));
+                }
+
+                let response = request.send().await
+                    .with_context(|| format!("Failed to send embeddings request to {}", url))?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    anyhow::bail!("Embeddings API error ({}): {}", status, error_text);
+                }
+
+                response.json().await.context("Failed to parse embeddings response as JSON")
+            })
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("Embeddings request thread panicked"))?
+    })
+}
+
+fn parse_vector(value: &serde_json::Value) -> Result<Vec<f32>> {
+    let mut vector: Vec<f32> = value
+        .as_array()
+        .context("Embedding is not an array")?
+        .iter()
+        .map(|v| v.as_f64().map(|f| f as f32).context("Embedding holds a non-number"))
+        .collect::<Result<_>>()?;
+    normalize(&mut vector);
+    Ok(vector)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Lowercase word parts of the identifiers and words in `text`, without stopwords
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    crate::tokens::identifiers(text)
+        .flat_map(crate::tokens::identifier_words)
+        .filter(|word| word.len() > 1 && !STOPWORDS.contains(&word.as_str()))
+}
+
+/// Distinct words of a semantic query used for keyword scoring
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = words(query).filter(|word| word.len() >= 3).collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// A span of a file embedded as one vector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Definition the chunk covers (None for line windows)
+    pub symbol: Option<String>,
+    pub kind: SymbolKind,
+}
+
+/// Kinds of symbols that get a chunk of their own
+fn is_definition(kind: &SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Function | SymbolKind::Method | SymbolKind::Class | SymbolKind::Struct
+            | SymbolKind::Enum | SymbolKind::Interface | SymbolKind::Trait | SymbolKind::Type
+            | SymbolKind::Macro | SymbolKind::Module
+    )
+}
+
+/// Split a file into chunks: one per definition, or line windows without any
+pub fn chunk_file(path: &str, content: &str, symbols: &[crate::models::SearchResult], chunk_lines: usize) -> Vec<Chunk> {
+    let chunk_lines = chunk_lines.max(1);
+    let mut chunks: Vec<Chunk> = symbols
+        .iter()
+        .filter(|s| is_definition(&s.kind))
+        .map(|s| Chunk {
+            path: path.to_string(),
+            start_line: s.span.start_line,
+            end_line: s.span.end_line.min(s.span.start_line + chunk_lines - 1),
+            symbol: s.qualified_name.clone().or_else(|| s.symbol.clone()),
+            kind: s.kind.clone(),
+        })
+        .collect();
+
+    if chunks.is_empty() {
+        let line_count = content.lines().count();
+        chunks = (1..=line_count)
+            .step_by(chunk_lines)
+            .map(|start| Chunk {
+                path: path.to_string(),
+                start_line: start,
+                end_line: (start + chunk_lines - 1).min(line_count),
+                symbol: None,
+                kind: SymbolKind::Unknown(String::new()),
+            })
+            .collect();
+    }
+
+    chunks.sort_by_key(|c| (c.start_line, c.end_line));
+    chunks.dedup_by(|a, b| a.start_line == b.start_line && a.end_line == b.end_line);
+    chunks
+}
+
+/// Text sent to the embedding model for a chunk: its location, name and lines
+fn chunk_text(relative_path: &str, chunk: &Chunk, content: &str) -> String {
+    let mut text = format!("{}\n", relative_path);
+    if let Some(symbol) = &chunk.symbol {
+        text.push_str(&format!("{} {}\n", chunk.kind, symbol));
+    }
+    for line in content.lines().skip(chunk.start_line.saturating_sub(1)).take((chunk.end_line + 1).saturating_sub(chunk.start_line)) {
+        if text.len() + line.len() > MAX_CHUNK_CHARS {
+            break;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Result of `rfx embed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbedStats {
+    /// Provider and model the vectors came from
+    pub model: String,
+    /// Files covered by the embeddings index
+    pub files: usize,
+    /// Files (re-)embedded by this run
+    pub embedded_files: usize,
+    /// Chunks embedded by this run
+    pub embedded_chunks: usize,
+    /// Chunks in the embeddings index
+    pub total_chunks: usize,
+    /// Files dropped because they left the index
+    pub removed_files: usize,
+}
+
+/// Embedding model recorded with the vectors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelRecord {
+    model: String,
+    dimensions: usize,
+    built_at: i64,
+}
+
+fn open_db(cache: &CacheManager) -> Result<Connection> {
+    let db_path = cache.path().join(META_DB);
+    if !db_path.exists() {
+        anyhow::bail!("Cache not initialized - run 'rfx index' first");
+    }
+
+    let conn = Connection::open(&db_path).context("Failed to open meta.db")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS embedding_chunks (
+            path TEXT NOT NULL,
+            file_hash TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            symbol TEXT,
+            kind TEXT NOT NULL,
+            vector BLOB NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_embedding_chunks_path ON embedding_chunks(path);",
+    )
+    .context("Failed to create embedding_chunks table")?;
+    Ok(conn)
+}
+
+fn load_model(conn: &Connection) -> Result<Option<ModelRecord>> {
+    use rusqlite::OptionalExtension;
+
+    let json: Option<String> = conn
+        .query_row("SELECT value FROM config WHERE key = ?", [MODEL_KEY], |row| row.get(0))
+        .optional()?;
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Embed the chunks of every indexed file that changed since the last run
+///
+/// `force` re-embeds every file.
+pub fn build(cache: &CacheManager, config: &EmbeddingsConfig, embedder: &dyn Embedder, force: bool) -> Result<EmbedStats> {
+    let mut conn = open_db(cache)?;
+    let model = embedder.id();
+
+    let previous = load_model(&conn)?;
+    let model_changed = previous.as_ref().is_some_and(|p| p.model != model);
+    if model_changed {
+        log::info!("Embedding model changed to {}, re-embedding all files", model);
+    }
+    if force || model_changed {
+        conn.execute("DELETE FROM embedding_chunks", [])?;
+    }
+    let mut dimensions = match previous {
+        Some(previous) if !force && !model_changed => previous.dimensions,
+        _ => 0,
+    };
+
+    let embedded: HashMap<String, String> = {
+        let mut stmt = conn.prepare("SELECT DISTINCT path, file_hash FROM embedding_chunks")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let content = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store - run 'rfx index' first")?;
+    let hashes = cache.load_hashes_for_branch(&cache.query_branch())
+        .context("Failed to load file hashes")?;
+    let quarantine = crate::quarantine::Quarantine::open(cache.path())?;
+    let root = cache.workspace_root();
+
+    // Files whose vectors are missing or outdated
+    let mut current: HashMap<String, String> = HashMap::new();
+    let mut pending = Vec::new();
+    for file_id in 0..content.file_count() as u32 {
+        let Some(path) = content.get_file_path(file_id) else {
+            continue;
+        };
+        let path_str = path.to_string_lossy().to_string();
+        let relative = crate::paths::relative_path(path, &root);
+        let hash = hashes.get(&relative).cloned().unwrap_or_default();
+        if embedded.get(&path_str) != Some(&hash) {
+            pending.push((file_id, path_str.clone(), relative, hash.clone()));
+        }
+        current.insert(path_str, hash);
+    }
+
+    let removed: Vec<&String> = embedded.keys().filter(|path| !current.contains_key(*path)).collect();
+    let mut chunks = Vec::new();
+    for (file_id, path, relative, hash) in &pending {
+        let text = content.get_file_content(*file_id)?;
+        let language = Language::from_path(Path::new(path));
+        let symbols = if language.is_supported() {
+            quarantine.parse(path, Some(hash), text, language).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        for chunk in chunk_file(path, text, &symbols, config.chunk_lines) {
+            let chunk_text = chunk_text(relative, &chunk, text);
+            chunks.push((chunk, hash.as_str(), chunk_text));
+        }
+    }
+
+    log::info!("Embedding {} chunks of {} files with {}", chunks.len(), pending.len(), model);
+    let tx = conn.transaction()?;
+    for path in &removed {
+        tx.execute("DELETE FROM embedding_chunks WHERE path = ?", [path])?;
+    }
+    for (_, path, _, _) in &pending {
+        tx.execute("DELETE FROM embedding_chunks WHERE path = ?", [path])?;
+    }
+    for batch in chunks.chunks(BATCH_SIZE) {
+        let texts: Vec<String> = batch.iter().map(|(_, _, text)| text.clone()).collect();
+        let vectors = embedder.embed(&texts)?;
+        if vectors.len() != batch.len() {
+            anyhow::bail!("Embeddings provider returned {} vectors for {} chunks", vectors.len(), batch.len());
+        }
+
+        for ((chunk, hash, _), vector) in batch.iter().zip(vectors) {
+            dimensions = vector.len();
+            let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+            tx.execute(
+                "INSERT INTO embedding_chunks (path, file_hash, start_line, end_line, symbol, kind, vector)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![chunk.path, hash, chunk.start_line as i64, chunk.end_line as i64, chunk.symbol, chunk.kind.to_string(), bytes],
+            )?;
+        }
+    }
+
+    let record = ModelRecord { model: model.clone(), dimensions, built_at: chrono::Utc::now().timestamp() };
+    tx.execute(
+        "INSERT OR REPLACE INTO config (key, value) VALUES (?, ?)",
+        [MODEL_KEY, serde_json::to_string(&record)?.as_str()],
+    )?;
+    tx.commit()?;
+
+    let total_chunks: i64 = conn.query_row("SELECT COUNT(*) FROM embedding_chunks", [], |row| row.get(0))?;
+    Ok(EmbedStats {
+        model,
+        files: current.len(),
+        embedded_files: pending.len(),
+        embedded_chunks: chunks.len(),
+        total_chunks: total_chunks as usize,
+        removed_files: removed.len(),
+    })
+}
+
+/// A stored chunk with its vector
+#[derive(Debug, Clone)]
+pub struct EmbeddedChunk {
+    pub chunk: Chunk,
+    vector: Vec<f32>,
+}
+
+impl EmbeddedChunk {
+    /// Cosine similarity to a normalized query vector
+    pub fn similarity(&self, query: &[f32]) -> f64 {
+        dot(&self.vector, query) as f64
+    }
+}
+
+/// All embedded chunks of an index
+pub struct EmbeddingIndex {
+    /// Provider and model the vectors came from
+    pub model: String,
+    pub chunks: Vec<EmbeddedChunk>,
+}
+
+impl EmbeddingIndex {
+    /// Load the embeddings of `cache`, if `rfx embed` was run
+    pub fn load(cache: &CacheManager) -> Result<Option<Self>> {
+        let conn = open_db(cache)?;
+        let Some(model) = load_model(&conn)? else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT path, start_line, end_line, symbol, kind, vector FROM embedding_chunks",
+        )?;
+        let chunks = stmt
+            .query_map([], |row| {
+                let kind: String = row.get(4)?;
+                let bytes: Vec<u8> = row.get(5)?;
+                Ok(EmbeddedChunk {
+                    chunk: Chunk {
+                        path: row.get(0)?,
+                        start_line: row.get::<_, i64>(1)? as usize,
+                        end_line: row.get::<_, i64>(2)? as usize,
+                        symbol: row.get(3)?,
+                        kind: kind.parse().unwrap_or(SymbolKind::Unknown(kind)),
+                    },
+                    vector: bytes
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(Self { model: model.model, chunks }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use crate::query::{QueryEngine, QueryFilter};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chunk_file_windows_without_definitions() {
+        let content = (1..=25).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_file("notes.txt", &content, &[], 10);
+        let spans: Vec<(usize, usize)> = chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(spans, vec![(1, 10), (11, 20), (21, 25)]);
+    }
+
+    #[test]
+    fn test_local_embedder_prefers_shared_vocabulary() {
+        let embedder = LocalEmbedder;
+        let vectors = embedder.embed(&[
+            "retry http client".to_string(),
+            "fn retry_request(client: &HttpClient) { backoff() }".to_string(),
+            "fn render_sidebar(theme: &Theme) {}".to_string(),
+        ]).unwrap();
+        assert!(dot(&vectors[0], &vectors[1]) > dot(&vectors[0], &vectors[2]));
+    }
+
+    #[test]
+    fn test_semantic_query_ranks_chunks_and_embeds_incrementally() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(
+            root.join("http.rs"),
+            "fn send_with_retry(client: &HttpClient) {\n    for attempt in 0..3 {\n        backoff(attempt);\n    }\n}\n",
+        ).unwrap();
+        fs::write(root.join("theme.rs"), "fn render_sidebar(theme: &Theme) {\n    draw(theme);\n}\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let filter = QueryFilter { semantic: true, suppress_output: true, ..Default::default() };
+        let engine = QueryEngine::new(cache.clone());
+        assert!(engine.search("retry logic for http client", filter.clone()).is_err());
+
+        let config = EmbeddingsConfig::default();
+        let embedder = create_embedder(&config).unwrap();
+        let stats = build(&cache, &config, embedder.as_ref(), false).unwrap();
+        assert_eq!((stats.files, stats.embedded_files, stats.total_chunks), (2, 2, 2));
+
+        let results = engine.search("retry logic for http client", filter).unwrap();
+        assert!(results[0].path.ends_with("http.rs"));
+        assert_eq!(results[0].symbol.as_deref(), Some("send_with_retry"));
+        assert!(results[0].score.unwrap() > results.last().unwrap().score.unwrap());
+
+        // Only changed files are embedded again
+        fs::write(root.join("theme.rs"), "fn render_header(theme: &Theme) {}\n").unwrap();
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+        let stats = build(&cache, &config, embedder.as_ref(), false).unwrap();
+        assert_eq!((stats.embedded_files, stats.total_chunks), (1, 2));
+    }
+}
@@ -0,0 +1,149 @@
+//! Machine-readable error codes
+//!
+//! Failures a caller can act on (re-index, narrow the query, fix the pattern)
+//! are raised as [`ReflexError`] inside the usual `anyhow` chain, so context
+//! added on the way up doesn't hide them. Every surface reports the same
+//! stable code next to the message:
+//! - CLI: `{"error": "<message>", "code": "<CODE>"}` on stdout when `--json`
+//!   or `--ai` is set
+//! - HTTP: the same body, with a status that matches the code
+//! - MCP: `error.data.code` of the JSON-RPC error
+//!
+//! Errors that were never classified report [`INTERNAL`]. Codes are part of
+//! the public interface: add new ones, but don't rename or reuse them.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Code reported for errors without a [`ReflexError`] in their chain
+pub const INTERNAL: &str = "INTERNAL";
+
+/// An error with a stable code; the payload is the human-readable message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReflexError {
+    /// No index in the queried directory (`rfx index` fixes it)
+    IndexMissing(String),
+    /// The index is out of date for the working tree (`rfx index` fixes it)
+    IndexStale(String),
+    /// Cache files are unreadable or from another version (`rfx clear` + `rfx index`)
+    IndexCorrupt(String),
+    /// The query ran past its `--timeout`
+    Timeout(String),
+    /// The regex pattern doesn't compile
+    InvalidRegex(String),
+    /// The query was refused as too expensive; add filters or `--force`
+    BroadQuery(String),
+    /// A flag or parameter value is invalid
+    InvalidArgument(String),
+    /// A file, symbol or tool named in the request doesn't exist
+    NotFound(String),
+    /// The request names a directory outside the one being served
+    Forbidden(String),
+}
+
+impl ReflexError {
+    /// Stable identifier (SCREAMING_SNAKE_CASE)
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReflexError::IndexMissing(_) => "INDEX_MISSING",
+            ReflexError::IndexStale(_) => "INDEX_STALE",
+            ReflexError::IndexCorrupt(_) => "INDEX_CORRUPT",
+            ReflexError::Timeout(_) => "TIMEOUT",
+            ReflexError::InvalidRegex(_) => "INVALID_REGEX",
+            ReflexError::BroadQuery(_) => "BROAD_QUERY",
+            ReflexError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            ReflexError::NotFound(_) => "NOT_FOUND",
+            ReflexError::Forbidden(_) => "FORBIDDEN",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ReflexError::IndexMissing(m)
+            | ReflexError::IndexStale(m)
+            | ReflexError::IndexCorrupt(m)
+            | ReflexError::Timeout(m)
+            | ReflexError::InvalidRegex(m)
+            | ReflexError::BroadQuery(m)
+            | ReflexError::InvalidArgument(m)
+            | ReflexError::NotFound(m)
+            | ReflexError::Forbidden(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for ReflexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ReflexError {}
+
+/// Code of the first [`ReflexError`] in `err`'s chain, or [`INTERNAL`]
+pub fn error_code(err: &anyhow::Error) -> &'static str {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<ReflexError>())
+        .map_or(INTERNAL, ReflexError::code)
+}
+
+/// HTTP status for an error code
+pub fn http_status(code: &str) -> u16 {
+    match code {
+        "INDEX_MISSING" | "NOT_FOUND" => 404,
+        "FORBIDDEN" => 403,
+        "INDEX_STALE" => 409,
+        "TIMEOUT" => 408,
+        "INVALID_REGEX" | "BROAD_QUERY" | "INVALID_ARGUMENT" => 400,
+        _ => 500,
+    }
+}
+
+/// JSON form of an error, shared by the CLI, HTTP and MCP
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorBody {
+    pub error: String,
+    pub code: &'static str,
+}
+
+impl ErrorBody {
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        Self {
+            error: format!("{:#}", err),
+            code: error_code(err),
+        }
+    }
+}
+
+/// Whether the command line asked for JSON output, so errors should be JSON too
+pub fn wants_json(args: &[String]) -> bool {
+    args.iter().any(|a| a == "--json" || a == "--ai")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_code_survives_context() {
+        let err: anyhow::Result<()> = Err(ReflexError::Timeout("Query timeout exceeded (5 seconds)".to_string()).into());
+        let err = err.context("Search failed").unwrap_err();
+
+        assert_eq!(error_code(&err), "TIMEOUT");
+        let body = ErrorBody::from_error(&err);
+        assert_eq!(body.error, "Search failed: Query timeout exceeded (5 seconds)");
+        assert_eq!(http_status(body.code), 408);
+    }
+
+    #[test]
+    fn test_unclassified_errors_are_internal() {
+        let err = anyhow::anyhow!("disk on fire");
+        assert_eq!(error_code(&err), INTERNAL);
+        assert_eq!(http_status(error_code(&err)), 500);
+        assert_eq!(
+            serde_json::to_value(ErrorBody::from_error(&err)).unwrap(),
+            serde_json::json!({"error": "disk on fire", "code": "INTERNAL"})
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Error type and exception flow inventory
+//!
+//! This module builds a map of the error/exception types defined in the indexed
+//! codebase and the sites that construct or raise them, grouped by type. It backs
+//! the `rfx errors` command.
+//!
+//! # How types are detected
+//!
+//! A symbol pass (the tree-sitter parsers) finds every class, struct, enum and
+//! type declaration. A declaration counts as an error type when:
+//! - Its name ends in `Error` or `Exception` (`ParseError`, `NotFoundException`)
+//! - Its declaration inherits from an error base (`class X(ValueError)`,
+//!   `class X extends Error`, `class X < StandardError`, `class X : Exception`)
+//! - It derives `Error` (`#[derive(Debug, thiserror::Error)]`)
+//! - It implements the language's error interface anywhere in the codebase
+//!   (Rust `impl Error for X`, Go `func (e *X) Error() string`)
+//!
+//! # How sites are detected
+//!
+//! Every indexed file is then scanned for references to those types. A reference
+//! is a *raise* site when the line raises/returns an error (`raise`, `throw`,
+//! `Err(`, `bail!`, `panic(`, `reject(`) and a *construct* site when the type is
+//! instantiated (`X(...)`, `new X(...)`, `X { .. }`, `X::Variant`). Imports,
+//! trait impls, and handlers (`catch`, `except`, `rescue`, match arms) are skipped.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{Language, SymbolKind};
+use crate::parsers::ParserFactory;
+use crate::query::QueryEngine;
+
+/// Maximum length (in characters) of a site's source line preview
+const MAX_PREVIEW_LENGTH: usize = 160;
+
+/// Keywords that mark a line as raising/returning an error
+const RAISE_MARKERS: &[&str] = &["raise ", "throw ", "throw(", "Err(", "bail!", "panic(", "reject("];
+
+/// Line prefixes that reference a type without constructing it
+const SKIP_PREFIXES: &[&str] = &[
+    "use ", "pub use ", "import ", "from ", "#include", "require", "impl ", "impl<",
+    "//", "#", "*", "/*", "--",
+];
+
+/// Keywords that mark a line as handling (not raising) an error
+const HANDLER_MARKERS: &[&str] = &["catch", "except ", "except(", "rescue ", "=>"];
+
+/// Filters for building the inventory
+#[derive(Debug, Clone, Default)]
+pub struct ErrorFilter {
+    /// Only consider files in this language
+    pub language: Option<Language>,
+    /// Include files matching any of these globs
+    pub glob_patterns: Vec<String>,
+    /// Exclude files matching any of these globs
+    pub exclude_patterns: Vec<String>,
+    /// Only report types whose name contains this substring (case-insensitive)
+    pub name: Option<String>,
+}
+
+/// An error type definition with every site that constructs or raises it
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorType {
+    pub name: String,
+    pub kind: String,
+    pub path: String,
+    pub line: usize,
+    /// Why this declaration was classified as an error type
+    pub reason: String,
+    pub sites: Vec<ErrorSite>,
+}
+
+/// A location that constructs or raises an error type
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorSite {
+    pub path: String,
+    pub line: usize,
+    /// `raise` or `construct`
+    pub action: String,
+    pub preview: String,
+}
+
+/// An error type declaration found by the symbol pass (before site collection)
+#[derive(Debug, Clone)]
+struct ErrorTypeDef {
+    name: String,
+    kind: String,
+    path: String,
+    line: usize,
+    reason: String,
+}
+
+/// Build the error inventory from the index in `cache`
+///
+/// Returns error types sorted by name (then path), each with its sites sorted by
+/// path and line.
+pub fn build_inventory(cache: &CacheManager, filter: &ErrorFilter) -> Result<Vec<ErrorType>> {
+    let content_path = cache.path().join("content.bin");
+    let content_reader = ContentReader::open(&content_path)
+        .context("Failed to open content store")?;
+
+    let include_matcher = build_glob_matcher(&filter.glob_patterns);
+    let exclude_matcher = build_glob_matcher(&filter.exclude_patterns);
+
+    let mut files: Vec<(String, Language, &str)> = Vec::new();
+    for file_id in 0..content_reader.file_count() {
+        let file_path = match content_reader.get_file_path(file_id as u32) {
+            Some(p) => p,
+            None => continue,
+        };
+        let lang = detect_language(file_path);
+        if lang == Language::Unknown || filter.language.is_some_and(|l| l != lang) {
+            continue;
+        }
+
+        let path_str = file_path.to_string_lossy().to_string();
+        let included = include_matcher.as_ref().is_none_or(|m| m.is_match(&path_str));
+        let excluded = exclude_matcher.as_ref().is_some_and(|m| m.is_match(&path_str));
+        if !included || excluded {
+            continue;
+        }
+
+        match content_reader.get_file_content(file_id as u32) {
+            Ok(content) => files.push((path_str, lang, content)),
+            Err(e) => log::warn!("Skipping {}: {}", path_str, e),
+        }
+    }
+
+    // Pass 1: error interface implementations (may live apart from the declaration)
+    let implemented: HashSet<String> = files
+        .par_iter()
+        .flat_map_iter(|(_, lang, content)| find_error_impls(content, *lang))
+        .collect();
+
+    // Pass 2: symbol pass over files that mention an error-ish identifier
+    let mut defs: Vec<ErrorTypeDef> = files
+        .par_iter()
+        .filter(|(_, _, content)| {
+            content.contains("Error")
+                || content.contains("Exception")
+                || implemented.iter().any(|name| content.contains(name.as_str()))
+        })
+        .flat_map_iter(|(path, lang, content)| detect_error_types(path, content, *lang, &implemented))
+        .collect();
+
+    if let Some(name) = &filter.name {
+        let needle = name.to_lowercase();
+        defs.retain(|d| d.name.to_lowercase().contains(&needle));
+    }
+
+    if defs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Pass 3: construct/raise sites for every detected type name
+    let names: HashSet<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+    let definition_lines: HashSet<(&str, usize)> = defs.iter()
+        .map(|d| (d.path.as_str(), d.line))
+        .collect();
+    let name_regex = build_name_regex(&names)?;
+
+    let mut sites_by_name: HashMap<String, Vec<ErrorSite>> = HashMap::new();
+    let found: Vec<(String, ErrorSite)> = files
+        .par_iter()
+        .flat_map_iter(|(path, _, content)| {
+            find_sites(content, &name_regex)
+                .into_iter()
+                .filter(|(_, line, _, _)| !definition_lines.contains(&(path.as_str(), *line)))
+                .map(|(name, line, action, preview)| {
+                    (name, ErrorSite { path: path.clone(), line, action, preview })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for (name, site) in found {
+        sites_by_name.entry(name).or_default().push(site);
+    }
+
+    let mut inventory: Vec<ErrorType> = defs
+        .into_iter()
+        .map(|def| {
+            let mut sites = sites_by_name.get(&def.name).cloned().unwrap_or_default();
+            sites.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+            ErrorType {
+                name: def.name,
+                kind: def.kind,
+                path: def.path,
+                line: def.line,
+                reason: def.reason,
+                sites,
+            }
+        })
+        .collect();
+    inventory.sort_by(|a, b| a.name.cmp(&b.name).then(a.path.cmp(&b.path)).then(a.line.cmp(&b.line)));
+
+    Ok(inventory)
+}
+
+fn detect_language(path: &Path) -> Language {
+    Language::from_path(path)
+}
+
+fn build_glob_matcher(patterns: &[String]) -> Option<globset::GlobSet> {
+    use globset::{Glob, GlobSetBuilder};
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = QueryEngine::normalize_glob_pattern(pattern);
+        if let Ok(glob) = Glob::new(&normalized) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+/// Names of types that implement the language's error interface
+fn find_error_impls(source: &str, language: Language) -> Vec<String> {
+    static RUST_IMPL: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    static GO_METHOD: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+
+    let regex = match language {
+        Language::Rust => RUST_IMPL.get_or_init(|| {
+            Regex::new(r"impl(?:<[^>]*>)?\s+(?:std::error::|core::error::)?Error\s+for\s+(\w+)").unwrap()
+        }),
+        Language::Go => GO_METHOD.get_or_init(|| {
+            Regex::new(r"func\s+\(\s*\w+\s+\*?(\w+)\s*\)\s+Error\(\)\s+string").unwrap()
+        }),
+        _ => return Vec::new(),
+    };
+
+    regex.captures_iter(source)
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str().to_string()))
+        .collect()
+}
+
+/// Find error type declarations in a single file
+fn detect_error_types(
+    path: &str,
+    source: &str,
+    language: Language,
+    implemented: &HashSet<String>,
+) -> Vec<ErrorTypeDef> {
+    static BASE_TYPE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let base_type = BASE_TYPE.get_or_init(|| {
+        Regex::new(r"(?:\bextends\b|<|:|\(|,)\s*[\w.\\:]*(?:Error|Exception)\b").unwrap()
+    });
+
+    let symbols = match ParserFactory::parse_guarded(path, source, language) {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            log::debug!("Failed to parse {}: {}", path, e);
+            return Vec::new();
+        }
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut defs = Vec::new();
+
+    for symbol in symbols {
+        if !matches!(symbol.kind, SymbolKind::Class | SymbolKind::Struct | SymbolKind::Enum | SymbolKind::Type) {
+            continue;
+        }
+        let name = match symbol.symbol {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let line_idx = symbol.span.start_line.saturating_sub(1);
+        let header = lines.get(line_idx).copied().unwrap_or("");
+        let after_name = header.find(name.as_str())
+            .map(|pos| &header[pos + name.len()..])
+            .unwrap_or("");
+
+        let reason = if implemented.contains(&name) {
+            Some("implements Error")
+        } else if has_inheritance(language) && base_type.is_match(after_name) {
+            Some("inherits error base")
+        } else if derives_error(&lines, line_idx) {
+            Some("derives Error")
+        } else if name.ends_with("Error") || name.ends_with("Exception") {
+            Some("name")
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            defs.push(ErrorTypeDef {
+                name,
+                kind: symbol.kind.to_string(),
+                path: path.to_string(),
+                line: symbol.span.start_line,
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    defs
+}
+
+/// Whether declarations in `language` can name a base class/interface
+fn has_inheritance(language: Language) -> bool {
+    !matches!(language, Language::Rust | Language::Go | Language::C | Language::Zig)
+}
+
+/// Check the attribute lines directly above a declaration for `#[derive(.., Error)]`
+fn derives_error(lines: &[&str], decl_idx: usize) -> bool {
+    lines[..decl_idx.min(lines.len())]
+        .iter()
+        .rev()
+        .take_while(|line| {
+            let trimmed = line.trim_start();
+            trimmed.starts_with("#[") || trimmed.starts_with("///") || trimmed.is_empty()
+        })
+        .any(|line| {
+            let trimmed = line.trim_start();
+            trimmed.starts_with("#[derive(")
+                && trimmed.split(|c: char| !c.is_alphanumeric() && c != '_').any(|part| part == "Error")
+        })
+}
+
+/// Build a word-boundary alternation regex matching any of `names`
+fn build_name_regex(names: &HashSet<&str>) -> Result<Regex> {
+    let mut sorted: Vec<&str> = names.iter().copied().collect();
+    // Longest first so `IoError` wins over a shorter overlapping name
+    sorted.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    let alternation = sorted.iter()
+        .map(|name| regex::escape(name))
+        .collect::<Vec<_>>()
+        .join("|");
+    Regex::new(&format!(r"\b({})\b", alternation))
+        .context("Failed to build error type name pattern")
+}
+
+/// Find construct/raise sites in a single file
+///
+/// Returns (type name, line number, action, preview) tuples.
+fn find_sites(source: &str, name_regex: &Regex) -> Vec<(String, usize, String, String)> {
+    let mut sites = Vec::new();
+
+    for (idx, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if SKIP_PREFIXES.iter().any(|prefix| trimmed.starts_with(prefix)) {
+            continue;
+        }
+
+        let raises = RAISE_MARKERS.iter().any(|marker| line.contains(marker));
+        let mut seen: HashSet<&str> = HashSet::new();
+
+        for m in name_regex.find_iter(line) {
+            let name = m.as_str();
+            if seen.contains(name) {
+                continue;
+            }
+
+            let before = line[..m.start()].trim_end();
+            let after = &line[m.end()..];
+
+            if HANDLER_MARKERS.iter().any(|marker| before.contains(marker) || after.contains(marker)) {
+                continue;
+            }
+
+            let prev_word = before.split_whitespace().last().unwrap_or("");
+            let constructs = prev_word == "new"
+                || after.starts_with('(')
+                || after.starts_with("::")
+                || after.starts_with(".new")
+                || (after.trim_start().starts_with('{') && !before.ends_with("->") && !before.ends_with(':'));
+            let raised_bare = raises && (prev_word == "raise" || prev_word == "throw");
+
+            let action = if raises && (constructs || raised_bare) {
+                "raise"
+            } else if constructs {
+                "construct"
+            } else {
+                continue;
+            };
+
+            seen.insert(name);
+            sites.push((
+                name.to_string(),
+                idx + 1,
+                action.to_string(),
+                trimmed.trim_end().chars().take(MAX_PREVIEW_LENGTH).collect(),
+            ));
+        }
+    }
+
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn names_in(defs: &[ErrorTypeDef]) -> Vec<&str> {
+        let mut names: Vec<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_detect_python_exception_subclasses() {
+        let source = "class NotFound(LookupError):\n    pass\n\nclass Config:\n    pass\n\nclass Timeout(errors.BaseTimeout, Exception):\n    pass\n";
+        let defs = detect_error_types("app.py", source, Language::Python, &HashSet::new());
+
+        assert_eq!(names_in(&defs), vec!["NotFound", "Timeout"]);
+        assert!(defs.iter().all(|d| d.reason == "inherits error base"));
+    }
+
+    #[test]
+    fn test_detect_rust_error_impls_and_derives() {
+        let source = r#"
+pub enum Failure {
+    Io,
+}
+
+impl std::error::Error for Failure {}
+
+#[derive(Debug, thiserror::Error)]
+pub struct Broken;
+
+pub struct Settings;
+"#;
+        let implemented: HashSet<String> = find_error_impls(source, Language::Rust).into_iter().collect();
+        assert!(implemented.contains("Failure"));
+
+        let defs = detect_error_types("lib.rs", source, Language::Rust, &implemented);
+        assert_eq!(names_in(&defs), vec!["Broken", "Failure"]);
+    }
+
+    #[test]
+    fn test_find_go_error_method() {
+        let source = "type NotFound struct{}\n\nfunc (e *NotFound) Error() string { return \"x\" }\n";
+        assert_eq!(find_error_impls(source, Language::Go), vec!["NotFound".to_string()]);
+    }
+
+    #[test]
+    fn test_find_sites_classifies_raise_and_construct() {
+        let names: HashSet<&str> = ["ParseError", "ValidationError"].into_iter().collect();
+        let regex = build_name_regex(&names).unwrap();
+        let source = r#"
+use crate::errors::ParseError;
+fn parse() -> Result<(), ParseError> {
+    return Err(ParseError::Eof);
+}
+let e = ValidationError::new("bad");
+match e {
+    ParseError::Eof => {}
+}
+throw new ValidationError("x");
+raise ValidationError
+"#;
+        let sites = find_sites(source, &regex);
+        let summary: Vec<(&str, usize, &str)> = sites.iter()
+            .map(|(name, line, action, _)| (name.as_str(), *line, action.as_str()))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("ParseError", 4, "raise"),
+            ("ValidationError", 6, "construct"),
+            ("ValidationError", 10, "raise"),
+            ("ValidationError", 11, "raise"),
+        ]);
+    }
+
+    #[test]
+    fn test_build_inventory_groups_sites_by_type() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("errors.py"), "class QuotaExceeded(Exception):\n    pass\n").unwrap();
+        fs::write(
+            root.join("billing.py"),
+            "from errors import QuotaExceeded\n\ndef charge(n):\n    if n > 10:\n        raise QuotaExceeded(n)\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&root, false).unwrap();
+
+        let cache = CacheManager::new(&root);
+        let inventory = build_inventory(&cache, &ErrorFilter::default()).unwrap();
+
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory[0].name, "QuotaExceeded");
+        assert!(inventory[0].path.ends_with("errors.py"));
+        assert_eq!(inventory[0].sites.len(), 1);
+        assert!(inventory[0].sites[0].path.ends_with("billing.py"));
+        assert_eq!(inventory[0].sites[0].line, 5);
+        assert_eq!(inventory[0].sites[0].action, "raise");
+
+        let filtered = build_inventory(&cache, &ErrorFilter {
+            name: Some("nomatch".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert!(filtered.is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+//! Feature flag usage indexing
+//!
+//! This module recognizes feature-flag checks in source code and records each
+//! usage in `meta.db` so retired flags can be tracked down reliably.
+//!
+//! # Recognized patterns
+//!
+//! Built-in patterns cover the common SDK call shapes:
+//! - LaunchDarkly: `variation("flag", ...)`, `boolVariation("flag", ...)`, `BoolVariation("flag", ...)`
+//! - Unleash: `isEnabled("flag")`, `is_enabled("flag")`, `IsEnabled("flag")`, `getVariant("flag")`
+//! - Generic helpers: `featureEnabled("flag")`, `is_feature_enabled("flag")`, `flag_enabled("flag")`
+//!
+//! Custom patterns can be added in `.reflex/config.toml`. Each pattern is a regex
+//! whose first capture group is the flag name:
+//!
+//! ```toml
+//! [flags]
+//! patterns = ['Flags\.on\(\s*"([^"]+)"']
+//! disable_defaults = false  # Set to true to only use custom patterns
+//! ```
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+
+/// Maximum length (in characters) of the stored source line preview
+const MAX_PREVIEW_LENGTH: usize = 200;
+
+/// Built-in (provider, regex) pairs; capture group 1 is the flag name
+const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    (
+        "launchdarkly",
+        r#"\b(?:variation|variationDetail|boolVariation|stringVariation|numberVariation|jsonVariation|bool_variation|string_variation|int_variation|BoolVariation|StringVariation|IntVariation|JSONVariation)\(\s*["']([\w.:\-]+)["']"#,
+    ),
+    (
+        "unleash",
+        r#"\b(?:isEnabled|is_enabled|IsEnabled|getVariant|get_variant|GetVariant)\(\s*["']([\w.:\-]+)["']"#,
+    ),
+    (
+        "generic",
+        r#"\b(?:featureEnabled|isFeatureEnabled|feature_enabled|is_feature_enabled|flag_enabled|isFlagEnabled|is_flag_enabled)\(\s*["']([\w.:\-]+)["']"#,
+    ),
+];
+
+/// `[flags]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlagConfig {
+    /// Extra regex patterns (first capture group = flag name)
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Skip the built-in LaunchDarkly/Unleash/generic patterns
+    #[serde(default)]
+    pub disable_defaults: bool,
+}
+
+/// Load the `[flags]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_flag_config(cache_path: &Path) -> Result<FlagConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(FlagConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("flags") {
+        Some(flags_table) => flags_table.clone().try_into()
+            .context("Failed to parse [flags] section in .reflex/config.toml"),
+        None => Ok(FlagConfig::default()),
+    }
+}
+
+/// A flag usage extracted from source (before file ID resolution)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagUsageInfo {
+    /// Flag key as written in source
+    pub flag: String,
+    /// Line number of the check (1-indexed)
+    pub line_number: usize,
+    /// Pattern family that matched (launchdarkly, unleash, generic, custom)
+    pub provider: String,
+    /// Trimmed source line
+    pub preview: String,
+}
+
+/// Compiled set of flag-check patterns
+pub struct FlagMatcher {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl FlagMatcher {
+    /// Build a matcher from config (built-in patterns plus custom patterns)
+    pub fn from_config(config: &FlagConfig) -> Result<Self> {
+        let mut patterns = Vec::new();
+
+        if !config.disable_defaults {
+            for (provider, pattern) in DEFAULT_PATTERNS {
+                patterns.push((provider.to_string(), Regex::new(pattern)?));
+            }
+        }
+
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid flag pattern in [flags] config: {}", pattern))?;
+            if regex.captures_len() < 2 {
+                anyhow::bail!(
+                    "Flag pattern '{}' has no capture group.\n\
+                     \n\
+                     Wrap the flag name in parentheses, e.g. 'Flags\\.on\\(\"([^\"]+)\"'",
+                    pattern
+                );
+            }
+            patterns.push(("custom".to_string(), regex));
+        }
+
+        Ok(Self { patterns })
+    }
+
+    /// Returns true if no patterns are configured
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Extract all flag usages from file content
+    pub fn extract(&self, content: &str) -> Vec<FlagUsageInfo> {
+        let mut usages = Vec::new();
+
+        for (idx, line) in content.lines().enumerate() {
+            for (provider, regex) in &self.patterns {
+                for caps in regex.captures_iter(line) {
+                    if let Some(flag) = caps.get(1) {
+                        usages.push(FlagUsageInfo {
+                            flag: flag.as_str().to_string(),
+                            line_number: idx + 1,
+                            provider: provider.clone(),
+                            preview: line.trim().chars().take(MAX_PREVIEW_LENGTH).collect(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Overlapping patterns (e.g. a custom pattern shadowing a built-in) can
+        // report the same call twice
+        usages.sort_by(|a, b| (a.line_number, &a.flag).cmp(&(b.line_number, &b.flag)));
+        usages.dedup_by(|a, b| a.flag == b.flag && a.line_number == b.line_number);
+        usages
+    }
+}
+
+/// Aggregated usage counts for a single flag
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagSummary {
+    pub flag: String,
+    pub usages: usize,
+    pub files: usize,
+}
+
+/// A single stored flag usage with its file path
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagUsage {
+    pub flag: String,
+    pub path: String,
+    pub line: usize,
+    pub provider: String,
+    pub preview: String,
+}
+
+/// Create the flag usage table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and lazily by
+/// `FlagIndex` so caches created before flag indexing keep working.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS flag_usages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER NOT NULL,
+            flag TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            provider TEXT NOT NULL,
+            preview TEXT NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_flag_usages_flag ON flag_usages(flag)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_flag_usages_file ON flag_usages(file_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Manages flag usage storage and lookups
+pub struct FlagIndex {
+    cache: CacheManager,
+}
+
+impl FlagIndex {
+    /// Create a new flag index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for flag index")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Replace the stored flag usages for a set of files in one transaction
+    ///
+    /// Files with an empty usage list are cleared, so removed checks disappear
+    /// on reindex.
+    pub fn replace_file_flags(&self, files: &[(i64, Vec<FlagUsageInfo>)]) -> Result<usize> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+
+        {
+            let mut delete_stmt = tx.prepare("DELETE FROM flag_usages WHERE file_id = ?")?;
+            let mut insert_stmt = tx.prepare(
+                "INSERT INTO flag_usages (file_id, flag, line_number, provider, preview)
+                 VALUES (?, ?, ?, ?, ?)"
+            )?;
+
+            for (file_id, usages) in files {
+                delete_stmt.execute([file_id])?;
+                for usage in usages {
+                    insert_stmt.execute(rusqlite::params![
+                        file_id,
+                        usage.flag,
+                        usage.line_number as i64,
+                        usage.provider,
+                        usage.preview,
+                    ])?;
+                    inserted += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Stored {} flag usages for {} files", inserted, files.len());
+        Ok(inserted)
+    }
+
+    /// List all known flags with usage and file counts, sorted by flag name
+    pub fn list_flags(&self) -> Result<Vec<FlagSummary>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT flag, COUNT(*), COUNT(DISTINCT file_id)
+             FROM flag_usages
+             GROUP BY flag
+             ORDER BY flag"
+        )?;
+
+        let flags = stmt.query_map([], |row| {
+            Ok(FlagSummary {
+                flag: row.get(0)?,
+                usages: row.get::<_, i64>(1)? as usize,
+                files: row.get::<_, i64>(2)? as usize,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(flags)
+    }
+
+    /// Find every usage of a flag, sorted by path and line
+    pub fn find_usages(&self, flag: &str) -> Result<Vec<FlagUsage>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT fu.flag, f.path, fu.line_number, fu.provider, fu.preview
+             FROM flag_usages fu
+             JOIN files f ON fu.file_id = f.id
+             WHERE fu.flag = ?
+             ORDER BY f.path, fu.line_number"
+        )?;
+
+        let usages = stmt.query_map([flag], |row| {
+            Ok(FlagUsage {
+                flag: row.get(0)?,
+                path: row.get(1)?,
+                line: row.get::<_, i64>(2)? as usize,
+                provider: row.get(3)?,
+                preview: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(usages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn default_matcher() -> FlagMatcher {
+        FlagMatcher::from_config(&FlagConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_extract_launchdarkly() {
+        let source = "if client.variation(\"new-checkout\", user, false) {\n    checkout();\n}\n";
+        let usages = default_matcher().extract(source);
+
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].flag, "new-checkout");
+        assert_eq!(usages[0].line_number, 1);
+        assert_eq!(usages[0].provider, "launchdarkly");
+    }
+
+    #[test]
+    fn test_extract_unleash_variants() {
+        let source = "unleash.isEnabled('dark_mode')\nclient.is_enabled(\"beta.search\")\n";
+        let usages = default_matcher().extract(source);
+
+        let flags: Vec<&str> = usages.iter().map(|u| u.flag.as_str()).collect();
+        assert_eq!(flags, vec!["dark_mode", "beta.search"]);
+        assert!(usages.iter().all(|u| u.provider == "unleash"));
+    }
+
+    #[test]
+    fn test_extract_ignores_non_literal_arguments() {
+        let source = "if is_enabled(flag_name) { }\n";
+        let usages = default_matcher().extract(source);
+        assert!(usages.is_empty());
+    }
+
+    #[test]
+    fn test_custom_pattern() {
+        let config = FlagConfig {
+            patterns: vec![r#"Flags\.on\(\s*"([^"]+)""#.to_string()],
+            disable_defaults: true,
+        };
+        let matcher = FlagMatcher::from_config(&config).unwrap();
+
+        let usages = matcher.extract("if Flags.on(\"legacy-export\") {}\nis_enabled(\"ignored\")\n");
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].flag, "legacy-export");
+        assert_eq!(usages[0].provider, "custom");
+    }
+
+    #[test]
+    fn test_custom_pattern_requires_capture_group() {
+        let config = FlagConfig {
+            patterns: vec![r#"Flags\.on"#.to_string()],
+            disable_defaults: false,
+        };
+        assert!(FlagMatcher::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_load_flag_config_from_toml() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[flags]\npatterns = ['toggle\\(\"([^\"]+)\"']\ndisable_defaults = true\n",
+        ).unwrap();
+
+        let config = load_flag_config(temp.path()).unwrap();
+        assert_eq!(config.patterns.len(), 1);
+        assert!(config.disable_defaults);
+    }
+
+    #[test]
+    fn test_replace_and_query_flags() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        cache.update_file("src/a.rs", "Rust", 10).unwrap();
+        cache.update_file("src/b.rs", "Rust", 10).unwrap();
+
+        let ids = cache.batch_get_file_ids(&["src/a.rs".to_string(), "src/b.rs".to_string()]).unwrap();
+        let a = ids["src/a.rs"];
+        let b = ids["src/b.rs"];
+
+        let usage = |flag: &str, line: usize| FlagUsageInfo {
+            flag: flag.to_string(),
+            line_number: line,
+            provider: "unleash".to_string(),
+            preview: String::new(),
+        };
+
+        let index = FlagIndex::new(cache.clone());
+        index.replace_file_flags(&[
+            (a, vec![usage("checkout", 3), usage("search", 7)]),
+            (b, vec![usage("checkout", 1)]),
+        ]).unwrap();
+
+        let flags = index.list_flags().unwrap();
+        assert_eq!(flags.len(), 2);
+        assert_eq!(flags[0].flag, "checkout");
+        assert_eq!(flags[0].usages, 2);
+        assert_eq!(flags[0].files, 2);
+
+        let usages = index.find_usages("checkout").unwrap();
+        assert_eq!(usages.len(), 2);
+        assert_eq!(usages[0].path, "src/a.rs");
+
+        // Reindexing a file without checks clears its old usages
+        index.replace_file_flags(&[(a, vec![])]).unwrap();
+        assert_eq!(index.find_usages("checkout").unwrap().len(), 1);
+        assert!(index.find_usages("search").unwrap().is_empty());
+    }
+}
@@ -52,8 +52,15 @@ impl SyntaxHighlighter {
             Language::Kotlin => ("kt", None),
             Language::Swift => ("swift", None),
             Language::Zig => ("zig", None),
+            Language::Shell => ("sh", None),
+            Language::Make => ("mk", None),
+            Language::CMake => ("cmake", None),             // Not in the default set
+            Language::Bazel => ("bzl", Some("py")),        // Starlark is Python-like
             Language::Vue => ("vue", Some("html")),      // Fallback to HTML
             Language::Svelte => ("svelte", Some("html")), // Fallback to HTML
+            Language::Sql => ("sql", None),
+            Language::GraphQL => ("graphql", Some("js")),  // Not in the default set
+            Language::Html => ("html", None),
             Language::Unknown => return None,
         };
 
@@ -135,7 +142,7 @@ impl OutputFormatter {
 
         for result in results {
             grouped
-                .entry(result.path.clone())
+                .entry(result.display_path())
                 .or_default()
                 .push(result);
         }
@@ -214,9 +221,24 @@ impl OutputFormatter {
                 symbol_badge
             );
 
+            // Doc comment (only present with --with-docs)
+            if let Some(doc) = &result.doc {
+                for line in doc.lines() {
+                    println!("        {}", line.italic().dimmed());
+                }
+            }
+
+            // Signature (when the preview doesn't already show it in full)
+            if let Some(signature) = self.signature_to_show(result) {
+                println!("        {}", signature.dimmed());
+            }
+
             // Print code preview with syntax highlighting (indented)
+            // Multi-line previews (--expand, --context) keep the indent on every line
             let highlighted = self.highlight_code(&result.preview, &result.lang, pattern);
-            println!("        {}", highlighted);
+            for line in highlighted.lines() {
+                println!("        {}", line);
+            }
 
             // Print internal dependencies if available
             if let Some(deps_formatted) = self.format_internal_dependencies(&result.dependencies) {
@@ -235,7 +257,17 @@ impl OutputFormatter {
         } else {
             // Plain text output
             println!("    {} {}", line_no, symbol_badge);
-            println!("        {}", result.preview);
+            if let Some(doc) = &result.doc {
+                for line in doc.lines() {
+                    println!("        doc: {}", line);
+                }
+            }
+            if let Some(signature) = self.signature_to_show(result) {
+                println!("        sig: {}", signature);
+            }
+            for line in result.preview.lines() {
+                println!("        {}", line);
+            }
 
             // Print internal dependencies if available
             if let Some(deps_formatted) = self.format_internal_dependencies(&result.dependencies) {
@@ -256,6 +288,19 @@ impl OutputFormatter {
         Ok(())
     }
 
+    /// Signature to print above the preview, or None if the preview's first line already equals it
+    fn signature_to_show<'a>(&self, result: &'a SearchResult) -> Option<&'a str> {
+        let signature = result.signature.as_deref()?;
+        let first_line = result.preview.lines().next().unwrap_or("").trim();
+        let first_line = first_line.trim_end_matches(|c: char| c == '{' || c == ':' || c.is_whitespace());
+
+        if first_line == signature {
+            None
+        } else {
+            Some(signature)
+        }
+    }
+
     /// Format dependencies for display
     /// Returns None if no dependencies exist
     /// Note: Database only contains internal dependencies (external/stdlib filtered during indexing)
@@ -295,6 +340,10 @@ impl OutputFormatter {
             SymbolKind::Import => ("import", |s| s.bright_blue().to_string()),
             SymbolKind::Export => ("export", |s| s.bright_blue().to_string()),
             SymbolKind::Attribute => ("attribute", |s| s.bright_yellow().to_string()),
+            SymbolKind::Route => ("route", |s| s.bright_cyan().to_string()),
+            SymbolKind::Target => ("target", |s| s.bright_green().to_string()),
+            SymbolKind::Alias => ("alias", |s| s.yellow().to_string()),
+            SymbolKind::Table => ("table", |s| s.bright_blue().to_string()),
             SymbolKind::Unknown(_) => ("", |s| s.white().to_string()),
         };
 
@@ -416,36 +465,60 @@ mod tests {
                 lang: Language::Rust,
                 kind: SymbolKind::Function,
                 symbol: Some("foo".to_string()),
+                parent: None,
+                qualified_name: None,
                 span: Span {
                     start_line: 1,
                     end_line: 1,
                 },
                 preview: "fn foo() {}".to_string(),
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             },
             SearchResult {
                 path: "a.rs".to_string(),
                 lang: Language::Rust,
                 kind: SymbolKind::Function,
                 symbol: Some("bar".to_string()),
+                parent: None,
+                qualified_name: None,
                 span: Span {
                     start_line: 2,
                     end_line: 2,
                 },
                 preview: "fn bar() {}".to_string(),
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             },
             SearchResult {
                 path: "b.rs".to_string(),
                 lang: Language::Rust,
                 kind: SymbolKind::Function,
                 symbol: Some("baz".to_string()),
+                parent: None,
+                qualified_name: None,
                 span: Span {
                     start_line: 1,
                     end_line: 1,
                 },
                 preview: "fn baz() {}".to_string(),
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             },
         ];
 
@@ -458,6 +531,26 @@ mod tests {
         assert_eq!(grouped[1].1.len(), 1);
     }
 
+    #[test]
+    fn test_signature_hidden_when_preview_matches() {
+        let formatter = OutputFormatter::new(true);
+        let mut result = SearchResult::new(
+            "a.rs".to_string(),
+            Language::Rust,
+            SymbolKind::Function,
+            Some("add".to_string()),
+            Span { start_line: 1, end_line: 3 },
+            None,
+            "pub fn add(a: i32, b: i32) -> i32 {".to_string(),
+        );
+        result.signature = Some("pub fn add(a: i32, b: i32) -> i32".to_string());
+        assert_eq!(formatter.signature_to_show(&result), None);
+
+        result.preview = "pub fn add(\n    a: i32,".to_string();
+        result.signature = Some("pub fn add(a: i32, b: i32) -> i32".to_string());
+        assert_eq!(formatter.signature_to_show(&result), Some("pub fn add(a: i32, b: i32) -> i32"));
+    }
+
     #[test]
     fn test_symbol_badge_formatting() {
         let formatter = OutputFormatter::new(true);
@@ -0,0 +1,324 @@
+//! Term frequency and co-occurrence statistics
+//!
+//! `rfx freq <term>` reports how often a term occurs and which identifiers
+//! appear next to it, straight from the trigram index and content store. No
+//! search results are materialized, so agents can compare candidate terms
+//! cheaply and pick the most selective one before running a real query.
+//!
+//! Matching uses the same word-boundary rule as the default full-text search,
+//! so `lines` equals the number of results `rfx query <term>` would return.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::cache::CacheManager;
+use crate::error_code::ReflexError;
+use crate::content_store::ContentReader;
+use crate::models::Language;
+use crate::paths::relative_path;
+use crate::trigram::TrigramIndex;
+
+/// How to break down occurrence counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Parent directory of each file
+    Dir,
+    /// Language of each file
+    Lang,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "dir" | "directory" => Ok(Self::Dir),
+            "lang" | "language" => Ok(Self::Lang),
+            other => anyhow::bail!("Unknown grouping '{}'. Supported: dir, lang", other),
+        }
+    }
+}
+
+/// Options for a frequency lookup
+#[derive(Debug, Clone)]
+pub struct FreqOptions {
+    /// Break counts down by directory or language
+    pub group_by: Option<GroupBy>,
+    /// Only count files in this language
+    pub language: Option<Language>,
+    /// Number of co-occurring identifiers to report
+    pub top: usize,
+}
+
+impl Default for FreqOptions {
+    fn default() -> Self {
+        Self {
+            group_by: None,
+            language: None,
+            top: 10,
+        }
+    }
+}
+
+/// Counts for one directory or language
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreqGroup {
+    pub key: String,
+    pub occurrences: usize,
+    pub lines: usize,
+    pub files: usize,
+}
+
+/// An identifier appearing on the same lines as the term
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoOccurrence {
+    pub identifier: String,
+    /// Lines containing both the term and this identifier
+    pub lines: usize,
+}
+
+/// Frequency report for a term
+#[derive(Debug, Clone, Serialize)]
+pub struct TermFrequency {
+    pub term: String,
+    /// Word-boundary matches (a line can contain several)
+    pub occurrences: usize,
+    /// Lines containing the term
+    pub lines: usize,
+    /// Files containing the term
+    pub files: usize,
+    /// Files in the index (selectivity = files / total_files)
+    pub total_files: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<FreqGroup>,
+    /// Most frequent identifiers on lines containing the term
+    pub co_occurring: Vec<CoOccurrence>,
+}
+
+/// Words too common to say anything about a term's neighbourhood
+const STOP_WORDS: &[&str] = &[
+    "and", "as", "async", "await", "break", "case", "const", "continue", "def", "else", "false", "fn",
+    "for", "from", "func", "function", "if", "impl", "import", "in", "is", "let", "mut", "new", "nil",
+    "none", "not", "null", "or", "pub", "ref", "return", "self", "static", "struct", "the", "this",
+    "true", "use", "var", "while",
+];
+
+fn identifier_regex() -> &'static Regex {
+    static IDENTIFIER: OnceLock<Regex> = OnceLock::new();
+    IDENTIFIER.get_or_init(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap())
+}
+
+/// Per-file counts, merged into the report
+#[derive(Default)]
+struct FileCounts {
+    key: String,
+    occurrences: usize,
+    lines: usize,
+    neighbours: HashMap<String, usize>,
+}
+
+/// Count occurrences of `term` and its co-occurring identifiers in the index
+pub fn term_frequency(cache: &CacheManager, term: &str, options: &FreqOptions) -> Result<TermFrequency> {
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(
+            "No index found in current directory.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.".to_string()
+        ).into());
+    }
+    if term.is_empty() {
+        anyhow::bail!("Term must not be empty");
+    }
+
+    let content = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+
+    // Only files with every trigram of the term can contain it. Terms shorter
+    // than a trigram (or a missing trigram index) fall back to every file.
+    let trigrams_path = cache.path().join("trigrams.bin");
+    let candidates: BTreeSet<u32> = match TrigramIndex::load(&trigrams_path) {
+        Ok(index) if term.len() >= 3 => index.search(term).into_iter().map(|loc| loc.file_id).collect(),
+        _ => (0..content.file_count() as u32).collect(),
+    };
+    log::debug!("freq '{}': {} candidate files", term, candidates.len());
+
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(term)))?;
+    let root = cache.workspace_root();
+    let per_file: Vec<FileCounts> = candidates
+        .par_iter()
+        .filter_map(|&file_id| {
+            let path = content.get_file_path(file_id)?;
+            let language = Language::from_path(path);
+            if options.language.is_some_and(|lang| lang != language) {
+                return None;
+            }
+            let text = content.get_file_content(file_id).ok()?;
+            let counts = count_file(text, term, &word);
+            (counts.lines > 0).then(|| FileCounts {
+                key: group_key(options.group_by, &relative_path(path, &root), language),
+                ..counts
+            })
+        })
+        .collect();
+
+    Ok(merge(term, per_file, content.file_count(), options))
+}
+
+/// Count matches of `word` and their neighbouring identifiers in one file
+fn count_file(text: &str, term: &str, word: &Regex) -> FileCounts {
+    let mut counts = FileCounts::default();
+
+    for line in text.lines() {
+        let hits = word.find_iter(line).count();
+        if hits == 0 {
+            continue;
+        }
+        counts.occurrences += hits;
+        counts.lines += 1;
+
+        let neighbours: HashSet<&str> = identifier_regex()
+            .find_iter(line)
+            .map(|m| m.as_str())
+            .filter(|id| id.len() >= 3 && *id != term && !STOP_WORDS.contains(&id.to_lowercase().as_str()))
+            .collect();
+        for id in neighbours {
+            *counts.neighbours.entry(id.to_string()).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+/// Group of a file (`path` is workspace-relative)
+fn group_key(group_by: Option<GroupBy>, path: &str, language: Language) -> String {
+    match group_by {
+        Some(GroupBy::Dir) => match Path::new(path).parent().map(|p| p.to_string_lossy()) {
+            Some(parent) if !parent.is_empty() => parent.into_owned(),
+            _ => ".".to_string(),
+        },
+        Some(GroupBy::Lang) => format!("{:?}", language).to_lowercase(),
+        None => String::new(),
+    }
+}
+
+fn merge(term: &str, per_file: Vec<FileCounts>, total_files: usize, options: &FreqOptions) -> TermFrequency {
+    let mut report = TermFrequency {
+        term: term.to_string(),
+        occurrences: 0,
+        lines: 0,
+        files: per_file.len(),
+        total_files,
+        groups: Vec::new(),
+        co_occurring: Vec::new(),
+    };
+
+    let mut groups: HashMap<String, FreqGroup> = HashMap::new();
+    let mut neighbours: HashMap<String, usize> = HashMap::new();
+
+    for file in per_file {
+        report.occurrences += file.occurrences;
+        report.lines += file.lines;
+
+        if options.group_by.is_some() {
+            let group = groups.entry(file.key.clone()).or_insert_with(|| FreqGroup {
+                key: file.key.clone(),
+                occurrences: 0,
+                lines: 0,
+                files: 0,
+            });
+            group.occurrences += file.occurrences;
+            group.lines += file.lines;
+            group.files += 1;
+        }
+
+        for (id, lines) in file.neighbours {
+            *neighbours.entry(id).or_default() += lines;
+        }
+    }
+
+    report.groups = groups.into_values().collect();
+    report.groups.sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then_with(|| a.key.cmp(&b.key)));
+
+    report.co_occurring = neighbours
+        .into_iter()
+        .map(|(identifier, lines)| CoOccurrence { identifier, lines })
+        .collect();
+    report.co_occurring.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.identifier.cmp(&b.identifier)));
+    report.co_occurring.truncate(options.top);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_project() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src/auth")).unwrap();
+        fs::write(
+            temp.path().join("src/auth/token.rs"),
+            "fn verify_token(token: &str) -> bool {\n    decode_token(token).is_ok()\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("src/api.py"),
+            "def handler(request):\n    token = request.headers['token']\n    return decode_token(token)\n",
+        )
+        .unwrap();
+        fs::write(temp.path().join("README.md"), "tokens are verified\n").unwrap();
+
+        let cache = CacheManager::new(temp.path());
+        Indexer::new(cache, IndexConfig::default()).index(temp.path(), false).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_counts_word_boundary_matches() {
+        let temp = indexed_project();
+        let cache = CacheManager::new(temp.path());
+
+        let report = term_frequency(&cache, "token", &FreqOptions::default()).unwrap();
+        // verify_token / decode_token / tokens are not whole-word matches
+        assert_eq!(report.files, 2);
+        assert_eq!(report.lines, 4);
+        assert_eq!(report.occurrences, 5);
+        assert!(report.groups.is_empty());
+
+        let top = &report.co_occurring[0];
+        assert_eq!((top.identifier.as_str(), top.lines), ("decode_token", 2));
+        assert!(report.co_occurring.iter().all(|c| c.identifier != "token" && c.identifier != "return"));
+    }
+
+    #[test]
+    fn test_group_by_language_and_directory() {
+        let temp = indexed_project();
+        let cache = CacheManager::new(temp.path());
+
+        let by_lang = term_frequency(&cache, "token", &FreqOptions {
+            group_by: Some(GroupBy::Lang),
+            ..Default::default()
+        })
+        .unwrap();
+        let keys: Vec<_> = by_lang.groups.iter().map(|g| (g.key.as_str(), g.occurrences)).collect();
+        assert_eq!(keys, vec![("python", 3), ("rust", 2)]);
+
+        let rust_only = term_frequency(&cache, "token", &FreqOptions {
+            group_by: Some(GroupBy::Dir),
+            language: Some(Language::Rust),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(rust_only.files, 1);
+        assert_eq!(rust_only.groups[0].key, "src/auth");
+    }
+}
@@ -102,6 +102,263 @@ pub fn has_uncommitted_changes(root: impl AsRef<Path>) -> Result<bool> {
     Ok(has_changes)
 }
 
+/// Get the unified diff for a revision range (e.g., "main...HEAD")
+pub fn diff(root: impl AsRef<Path>, range: &str) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root.as_ref())
+        .args(["diff", "--no-color", "--no-ext-diff", "--end-of-options", range, "--"])
+        .output()
+        .context("Failed to execute git diff")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    String::from_utf8(output.stdout).context("Invalid UTF-8 in git diff output")
+}
+
+/// List files changed since `git_ref`, relative to `root`
+///
+/// Compares the working tree against the merge base of `git_ref` and HEAD, so
+/// commits that landed on `git_ref` after the branch point don't count.
+/// Uncommitted modifications and untracked (non-ignored) files are included.
+pub fn changed_files_since(root: impl AsRef<Path>, git_ref: &str) -> Result<Vec<String>> {
+    let root = root.as_ref();
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["merge-base", "--end-of-options", git_ref, "HEAD"])
+        .output()
+        .context("Failed to execute git merge-base")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Cannot resolve git ref '{}': {}\n\
+             \n\
+             Pass a branch, tag or commit that shares history with HEAD, e.g. --changed-since origin/main",
+            git_ref,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let merge_base = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git merge-base output")?
+        .trim()
+        .to_string();
+
+    let mut files = Vec::new();
+    let listings: [&[&str]; 2] = [
+        &["diff", "--name-only", "--relative", "--no-renames", "-z", merge_base.as_str(), "--"],
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    ];
+
+    for args in listings {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to execute git {}", args[0]))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let stdout = String::from_utf8(output.stdout)
+            .with_context(|| format!("Invalid UTF-8 in git {} output", args[0]))?;
+        files.extend(stdout.split('\0').filter(|p| !p.is_empty()).map(str::to_string));
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Resolve a revision (branch, tag, `HEAD~3`, abbreviated SHA) to its full commit SHA
+pub fn resolve_commit(root: impl AsRef<Path>, rev: &str) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root.as_ref())
+        .args(["rev-parse", "--verify", "--end-of-options", &format!("{}^{{commit}}", rev)])
+        .output()
+        .context("Failed to execute git rev-parse")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Cannot resolve git revision '{}': {}\n\
+             \n\
+             Pass a branch, tag or commit, e.g. --rev HEAD~10 or --rev v1.2.0",
+            rev,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git rev-parse output")?
+        .trim()
+        .to_string())
+}
+
+/// A file blob in a git tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Path relative to `root`
+    pub path: String,
+    /// Blob SHA
+    pub blob: String,
+    /// Blob size in bytes
+    pub size: u64,
+}
+
+/// List the regular files of `commit` below `root`
+///
+/// Symlinks and submodules are left out. Paths are relative to `root`, so a
+/// workspace in a subdirectory of the repository only lists its own files.
+pub fn list_tree(root: impl AsRef<Path>, commit: &str) -> Result<Vec<TreeEntry>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root.as_ref())
+        .args(["ls-tree", "-r", "-l", "-z", commit])
+        .output()
+        .context("Failed to execute git ls-tree")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git ls-tree failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git ls-tree output")?;
+
+    // <mode> SP <type> SP <object> SP+ <size> TAB <path>
+    let mut entries = Vec::new();
+    for record in stdout.split('\0').filter(|r| !r.is_empty()) {
+        let Some((meta, path)) = record.split_once('\t') else {
+            continue;
+        };
+        let fields: Vec<&str> = meta.split_whitespace().collect();
+        let [mode, kind, blob, size] = fields.as_slice() else {
+            continue;
+        };
+        if *kind != "blob" || *mode == "120000" {
+            continue;
+        }
+        entries.push(TreeEntry {
+            path: path.to_string(),
+            blob: blob.to_string(),
+            size: size.parse().unwrap_or(0),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Read blobs from the object database, in the order of `blobs`
+///
+/// Uses a single `git cat-file --batch` process, so reading thousands of
+/// files doesn't spawn thousands of processes.
+pub fn read_blobs(root: impl AsRef<Path>, blobs: &[String]) -> Result<Vec<Vec<u8>>> {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::Stdio;
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(root.as_ref())
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git cat-file")?;
+
+    // Feed the requests from another thread so a full stdout pipe can't deadlock us
+    let mut stdin = child.stdin.take().context("Failed to open git cat-file stdin")?;
+    let requests = blobs.join("\n") + "\n";
+    let writer = std::thread::spawn(move || stdin.write_all(requests.as_bytes()));
+
+    let mut reader = BufReader::new(child.stdout.take().context("Failed to open git cat-file stdout")?);
+    let mut contents = Vec::with_capacity(blobs.len());
+    let mut header = String::new();
+
+    for blob in blobs {
+        header.clear();
+        reader.read_line(&mut header).context("Failed to read git cat-file output")?;
+
+        // <sha> SP <type> SP <size> LF <contents> LF, or "<sha> missing" LF
+        let size = match header.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [_, "blob", size] => size.parse::<usize>().context("Invalid blob size in git cat-file output")?,
+            _ => anyhow::bail!("git cat-file could not read blob {}: {}", blob, header.trim()),
+        };
+
+        let mut content = vec![0; size + 1];
+        reader.read_exact(&mut content).context("Failed to read blob from git cat-file")?;
+        content.truncate(size);
+        contents.push(content);
+    }
+
+    writer.join()
+        .map_err(|_| anyhow::anyhow!("git cat-file writer thread panicked"))?
+        .context("Failed to write to git cat-file")?;
+    child.wait().context("Failed to wait for git cat-file")?;
+
+    Ok(contents)
+}
+
+/// Run `git blame --porcelain` on a file below `root`
+///
+/// With `commit`, blames the file as of that commit. Otherwise blames
+/// `contents` (the indexed text of the working-tree file), so line numbers
+/// match the index even when the file changed on disk since; lines that
+/// aren't committed yet are attributed to the all-zero commit.
+pub fn blame_porcelain(root: impl AsRef<Path>, path: &str, commit: Option<&str>, contents: &[u8]) -> Result<String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut command = Command::new("git");
+    command.arg("-C").arg(root.as_ref()).args(["blame", "--porcelain"]);
+    match commit {
+        Some(commit) => command.arg(commit),
+        None => command.args(["--contents", "-"]),
+    };
+
+    let mut child = command
+        .args(["--", path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git blame")?;
+
+    let mut stdin = child.stdin.take().context("Failed to open git blame stdin")?;
+    let input = if commit.is_none() { contents.to_vec() } else { Vec::new() };
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output().context("Failed to wait for git blame")?;
+    // git exits without reading stdin on errors (e.g. an untracked file)
+    let _ = writer.join();
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git blame failed for {}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    // Blamed lines are echoed back verbatim and may not be UTF-8
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// Get complete git state for the current repository
 ///
 /// This is a convenience function that captures branch, commit, and dirty state
@@ -185,4 +442,47 @@ mod tests {
         assert_eq!(state.commit.len(), 40);
         log::info!("Git state: {:?}", state);
     }
+
+    #[test]
+    fn test_changed_files_since() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+
+        git(&["init", "-q"]);
+        std::fs::write(root.join("base.rs"), "fn base() {}\n").unwrap();
+        std::fs::write(root.join("edited.rs"), "fn edited() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "base"]);
+        git(&["tag", "base"]);
+
+        std::fs::write(root.join("committed.rs"), "fn committed() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "feature"]);
+        std::fs::write(root.join("edited.rs"), "fn edited_again() {}\n").unwrap();
+        std::fs::write(root.join("untracked.rs"), "fn untracked() {}\n").unwrap();
+
+        let changed = changed_files_since(root, "base").unwrap();
+        assert_eq!(changed, vec!["committed.rs", "edited.rs", "untracked.rs"]);
+
+        assert!(changed_files_since(root, "no-such-ref").is_err());
+
+        // Refs that look like options are taken as refs, not as git flags
+        let output = root.join("written-by-git");
+        let option = format!("--output={}", output.display());
+        assert!(changed_files_since(root, &option).is_err());
+        assert!(diff(root, &option).is_err());
+        assert!(!output.exists());
+        assert!(diff(root, "base...HEAD").unwrap().contains("committed.rs"));
+    }
 }
@@ -0,0 +1,216 @@
+//! Dependency graph export (DOT, Mermaid, node/edge JSON)
+//!
+//! Renders the resolved internal import graph, or a subgraph of it, in formats
+//! that visualization tools understand directly:
+//! - `dot`: Graphviz (`rfx analyze --format dot | dot -Tsvg > deps.svg`)
+//! - `mermaid`: a `graph LR` block for Markdown renderers
+//! - `graph`: JSON with `nodes` (id, path) and `edges` (source, target)
+//!
+//! Nodes are indexed files and edges are imports resolved to another indexed
+//! file; external and stdlib imports are not part of the graph. Repeated imports
+//! between the same two files collapse into one edge.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+use crate::dependency::DependencyIndex;
+
+/// Graph output formats accepted by `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl GraphFormat {
+    /// Parse a `--format` value (None for non-graph formats like `tree`)
+    pub fn parse(format: &str) -> Option<Self> {
+        match format {
+            "dot" => Some(Self::Dot),
+            "mermaid" => Some(Self::Mermaid),
+            "graph" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// An indexed file in the graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub id: i64,
+    pub path: String,
+}
+
+/// A resolved import: `source` imports `target`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub source: i64,
+    pub target: i64,
+}
+
+/// A dependency graph ready to render
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl DependencyGraph {
+    /// Build the graph of every file with at least one resolved internal import
+    /// (as importer or imported)
+    pub fn full(deps_index: &DependencyIndex) -> Result<Self> {
+        let edges = deps_index.get_resolved_edges()?;
+        let ids: BTreeSet<i64> = edges.iter().flat_map(|&(source, target)| [source, target]).collect();
+        Self::build(deps_index, ids, edges)
+    }
+
+    /// Build the subgraph induced by `file_ids`: those files and every edge between them
+    pub fn induced(deps_index: &DependencyIndex, file_ids: &HashSet<i64>) -> Result<Self> {
+        let edges = deps_index.get_resolved_edges()?
+            .into_iter()
+            .filter(|(source, target)| file_ids.contains(source) && file_ids.contains(target))
+            .collect();
+        Self::build(deps_index, file_ids.iter().copied().collect(), edges)
+    }
+
+    fn build(deps_index: &DependencyIndex, ids: BTreeSet<i64>, edges: Vec<(i64, i64)>) -> Result<Self> {
+        let ids: Vec<i64> = ids.into_iter().collect();
+        let paths = deps_index.get_file_paths(&ids)?;
+
+        let mut nodes: Vec<GraphNode> = ids.iter()
+            .filter_map(|id| paths.get(id).map(|path| GraphNode { id: *id, path: path.clone() }))
+            .collect();
+        nodes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let edges: BTreeSet<(i64, i64)> = edges.into_iter()
+            .filter(|(source, target)| source != target && paths.contains_key(source) && paths.contains_key(target))
+            .collect();
+        let edges = edges.into_iter()
+            .map(|(source, target)| GraphEdge { source, target })
+            .collect();
+
+        Ok(Self { nodes, edges })
+    }
+
+    /// Render in the given format (JSON honors `pretty`)
+    pub fn render(&self, format: GraphFormat, pretty: bool) -> Result<String> {
+        Ok(match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Json if pretty => serde_json::to_string_pretty(self)?,
+            GraphFormat::Json => serde_json::to_string(self)?,
+        })
+    }
+
+    /// Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.nodes {
+            let _ = writeln!(out, "    n{} [label=\"{}\"];", node.id, escape_dot(&node.path));
+        }
+        for edge in &self.edges {
+            let _ = writeln!(out, "    n{} -> n{};", edge.source, edge.target);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph LR\n");
+        for node in &self.nodes {
+            let _ = writeln!(out, "    n{}[\"{}\"]", node.id, node.path.replace('"', "#quot;"));
+        }
+        for edge in &self.edges {
+            let _ = writeln!(out, "    n{} --> n{}", edge.source, edge.target);
+        }
+        out
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::models::ImportType;
+    use tempfile::TempDir;
+
+    fn setup_graph() -> (TempDir, DependencyIndex) {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        cache.update_file("src/main.rs", "rust", 100).unwrap();
+        cache.update_file("src/lib.rs", "rust", 50).unwrap();
+        cache.update_file("src/utils.rs", "rust", 30).unwrap();
+        cache.update_file("src/unused.rs", "rust", 10).unwrap();
+
+        let deps_index = DependencyIndex::new(cache);
+        // main -> lib (twice), lib -> utils, main -> serde (external)
+        for line in [1, 2] {
+            deps_index.insert_dependency(1, "crate::lib".to_string(), Some(2), ImportType::Internal, line, None).unwrap();
+        }
+        deps_index.insert_dependency(2, "crate::utils".to_string(), Some(3), ImportType::Internal, 1, None).unwrap();
+        deps_index.insert_dependency(1, "serde".to_string(), None, ImportType::External, 3, None).unwrap();
+
+        (temp, deps_index)
+    }
+
+    #[test]
+    fn test_full_graph() {
+        let (_temp, deps_index) = setup_graph();
+        let graph = DependencyGraph::full(&deps_index).unwrap();
+
+        let paths: Vec<&str> = graph.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/main.rs", "src/utils.rs"]);
+        assert_eq!(graph.edges, vec![
+            GraphEdge { source: 1, target: 2 },
+            GraphEdge { source: 2, target: 3 },
+        ]);
+    }
+
+    #[test]
+    fn test_induced_subgraph() {
+        let (_temp, deps_index) = setup_graph();
+        let ids: HashSet<i64> = [1, 2].into_iter().collect();
+        let graph = DependencyGraph::induced(&deps_index, &ids).unwrap();
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges, vec![GraphEdge { source: 1, target: 2 }]);
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let graph = DependencyGraph {
+            nodes: vec![
+                GraphNode { id: 1, path: "src/main.rs".to_string() },
+                GraphNode { id: 2, path: "src/\"odd\".rs".to_string() },
+            ],
+            edges: vec![GraphEdge { source: 1, target: 2 }],
+        };
+
+        let dot = graph.render(GraphFormat::Dot, false).unwrap();
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("n2 [label=\"src/\\\"odd\\\".rs\"];"));
+        assert!(dot.contains("n1 -> n2;"));
+
+        let mermaid = graph.render(GraphFormat::Mermaid, false).unwrap();
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("n2[\"src/#quot;odd#quot;.rs\"]"));
+        assert!(mermaid.contains("n1 --> n2"));
+
+        let json: serde_json::Value = serde_json::from_str(&graph.render(GraphFormat::Json, false).unwrap()).unwrap();
+        assert_eq!(json["nodes"][0]["path"], "src/main.rs");
+        assert_eq!(json["edges"][0]["source"], 1);
+        assert_eq!(json["edges"][0]["target"], 2);
+
+        assert_eq!(GraphFormat::parse("mermaid"), Some(GraphFormat::Mermaid));
+        assert_eq!(GraphFormat::parse("tree"), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! Result aggregation for `rfx query --group-by`
+//!
+//! Buckets matches by the symbol containing them, their directory, language
+//! or symbol kind, with match and file counts per group. Answers questions
+//! like "which modules mention X" where a flat list of lines is noise.
+//!
+//! Symbol groups are keyed `path:Outer.inner` (the `--scope` syntax), or by
+//! the bare path for matches outside any symbol. A symbol definition belongs
+//! to the symbol enclosing it, not to itself.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{Language, SearchResult, SymbolKind};
+use crate::parsers::ParserFactory;
+
+/// How to aggregate query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Innermost symbol containing each match
+    Symbol,
+    /// Parent directory of each file
+    Dir,
+    /// Language of each file
+    Lang,
+    /// Symbol kind of each match (`text` for full-text matches)
+    Kind,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "symbol" => Ok(Self::Symbol),
+            "dir" | "directory" => Ok(Self::Dir),
+            "lang" | "language" => Ok(Self::Lang),
+            "kind" => Ok(Self::Kind),
+            other => anyhow::bail!("Unknown grouping '{}'. Supported: symbol, dir, lang, kind", other),
+        }
+    }
+}
+
+/// Matches sharing one group key
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultGroup {
+    pub key: String,
+    pub matches: usize,
+    pub files: usize,
+    /// Files with matches in the group, sorted
+    pub paths: Vec<String>,
+}
+
+/// Aggregate `results` into groups, most matches first
+///
+/// Symbol grouping parses each file's indexed content from `cache`; files that
+/// can't be parsed are grouped at file level.
+pub fn group_results(results: &[SearchResult], group_by: GroupBy, cache: &CacheManager) -> Vec<ResultGroup> {
+    let keys: Vec<String> = match group_by {
+        GroupBy::Symbol => symbol_keys(results, cache),
+        _ => results.iter().map(|r| key(r, group_by)).collect(),
+    };
+
+    let mut groups: HashMap<String, (usize, BTreeSet<String>)> = HashMap::new();
+    for (result, key) in results.iter().zip(keys) {
+        let group = groups.entry(key).or_default();
+        group.0 += 1;
+        group.1.insert(result.display_path());
+    }
+
+    let mut groups: Vec<ResultGroup> = groups
+        .into_iter()
+        .map(|(key, (matches, paths))| ResultGroup {
+            key,
+            matches,
+            files: paths.len(),
+            paths: paths.into_iter().collect(),
+        })
+        .collect();
+    groups.sort_by(|a, b| b.matches.cmp(&a.matches).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
+
+/// Directory, language or kind group of a result
+fn key(result: &SearchResult, group_by: GroupBy) -> String {
+    let path = result.path.strip_prefix("./").unwrap_or(&result.path);
+    match group_by {
+        GroupBy::Dir => {
+            let dir = match Path::new(path).parent().map(|p| p.to_string_lossy()) {
+                Some(parent) if !parent.is_empty() => parent.into_owned(),
+                _ => ".".to_string(),
+            };
+            match &result.repo {
+                Some(repo) => format!("{}:{}", repo, dir),
+                None => dir,
+            }
+        }
+        GroupBy::Lang => {
+            format!("{:?}", Language::from_path(Path::new(path))).to_lowercase()
+        }
+        GroupBy::Kind => match &result.kind {
+            SymbolKind::Unknown(_) => "text".to_string(),
+            kind => kind.to_string().to_lowercase(),
+        },
+        GroupBy::Symbol => unreachable!("symbol keys need the file's symbols"),
+    }
+}
+
+/// `path:Outer.inner` keys of the symbols containing each result
+fn symbol_keys(results: &[SearchResult], cache: &CacheManager) -> Vec<String> {
+    let reader = ContentReader::open(cache.path().join("content.bin")).ok();
+    let mut symbols_by_path: HashMap<&str, Vec<SearchResult>> = HashMap::new();
+
+    results
+        .iter()
+        .map(|result| {
+            let path = result.path.strip_prefix("./").unwrap_or(&result.path);
+            let symbols = symbols_by_path
+                .entry(path)
+                .or_insert_with(|| parse(reader.as_ref(), path).unwrap_or_default());
+
+            // A definition is grouped under its parent, so leave the symbol itself out
+            let candidates: Vec<SearchResult> = symbols
+                .iter()
+                .filter(|s| !(s.span == result.span && s.symbol == result.symbol))
+                .cloned()
+                .collect();
+            let (innermost, ancestors) = crate::locate::enclosing_symbols(&candidates, result.span.start_line, None, "");
+            let names: Vec<&str> = ancestors
+                .iter()
+                .chain(innermost.as_ref())
+                .filter_map(|s| s.symbol.as_deref())
+                .collect();
+
+            let display = result.display_path();
+            if names.is_empty() {
+                display
+            } else {
+                format!("{}:{}", display, names.join("."))
+            }
+        })
+        .collect()
+}
+
+/// Symbols of an indexed file
+fn parse(reader: Option<&ContentReader>, path: &str) -> Result<Vec<SearchResult>> {
+    let Some(reader) = reader else {
+        anyhow::bail!("content store unavailable");
+    };
+    let file_id = reader
+        .get_file_id_by_path(path)
+        .ok_or_else(|| anyhow::anyhow!("{} is not indexed", path))?;
+    let source = reader.get_file_content(file_id)?;
+    ParserFactory::parse_guarded(path, source, Language::from_path(Path::new(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::{IndexConfig, Span};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn result(path: &str, lines: (usize, usize), kind: SymbolKind, symbol: Option<&str>) -> SearchResult {
+        let span = Span::new(lines.0, 0, lines.1, 0);
+        SearchResult::new(path.to_string(), Language::Unknown, kind, symbol.map(str::to_string), span, None, String::new())
+    }
+
+    #[test]
+    fn test_group_by_dir_lang_and_kind() {
+        let text = || SymbolKind::Unknown("text_match".to_string());
+        let results = vec![
+            result("./src/parsers/rust.rs", (3, 3), text(), None),
+            result("./src/parsers/rust.rs", (9, 9), SymbolKind::Function, Some("parse")),
+            result("./src/parsers/python.rs", (4, 4), text(), None),
+            result("./main.py", (1, 1), text(), None),
+        ];
+        let cache = CacheManager::new(".");
+
+        let dirs = group_results(&results, GroupBy::Dir, &cache);
+        let summary: Vec<(&str, usize, usize)> = dirs.iter().map(|g| (g.key.as_str(), g.matches, g.files)).collect();
+        assert_eq!(summary, vec![("src/parsers", 3, 2), (".", 1, 1)]);
+
+        let langs = group_results(&results, GroupBy::Lang, &cache);
+        assert_eq!(langs[0].key, "rust");
+        assert_eq!(langs[1].key, "python");
+        assert_eq!((langs[0].matches, langs[0].files), (3, 2));
+        assert_eq!(langs[1].paths, vec!["./main.py"]);
+
+        let kinds = group_results(&results, GroupBy::Kind, &cache);
+        assert_eq!((kinds[0].key.as_str(), kinds[0].matches), ("text", 3));
+        assert_eq!(kinds[1].key, "function");
+
+        assert!("module".parse::<GroupBy>().is_err());
+    }
+
+    #[test]
+    fn test_group_by_enclosing_symbol() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(
+            root.join("lib.rs"),
+            "use std::fmt;\n\nmod geo {\n    fn norm(x: i32) -> i32 {\n        x * 2\n    }\n}\n\nconst LIMIT: i32 = 2;\n",
+        ).unwrap();
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let path = root.join("lib.rs").to_string_lossy().into_owned();
+        let results = vec![
+            result(&path, (5, 5), SymbolKind::Unknown("text_match".to_string()), None),
+            result(&path, (4, 6), SymbolKind::Function, Some("norm")),
+            result(&path, (9, 9), SymbolKind::Unknown("text_match".to_string()), None),
+            result(&path, (1, 1), SymbolKind::Unknown("text_match".to_string()), None),
+        ];
+        let groups = group_results(&results, GroupBy::Symbol, &cache);
+        let keys: BTreeSet<String> = groups.into_iter().map(|g| g.key).collect();
+
+        // Text inside norm, the norm definition inside its module, a constant, an import
+        let expected = [path.clone(), format!("{}:LIMIT", path), format!("{}:geo", path), format!("{}:geo.norm", path)];
+        assert_eq!(keys, BTreeSet::from(expected));
+    }
+}
@@ -0,0 +1,224 @@
+//! Type hierarchy lookup (`rfx hierarchy`)
+//!
+//! Collects the `extends` / `implements` edges declared in every indexed file
+//! (see [`crate::parsers::supertypes`]) and walks them from one type: upwards
+//! to its supertypes and downwards to its subtypes, across files. Types are
+//! matched by their unqualified name, so `models.Base` and `Base` are the same
+//! node; cycles and diamonds are visited once.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::Language;
+use crate::parsers::supertypes::{self, RelationKind, TypeRelation};
+use crate::query::QueryEngine;
+
+/// What to walk
+#[derive(Debug, Clone, Default)]
+pub struct HierarchyOptions {
+    /// Maximum number of levels in each direction (0 = unlimited)
+    pub depth: usize,
+    /// Only read declarations in this language
+    pub language: Option<Language>,
+    /// Include files matching any of these globs
+    pub glob_patterns: Vec<String>,
+    /// Exclude files matching any of these globs
+    pub exclude_patterns: Vec<String>,
+}
+
+/// One edge reached from the requested type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HierarchyEdge {
+    /// The supertype (in `supertypes`) or subtype (in `subtypes`)
+    pub name: String,
+    /// The type this edge hangs from (the requested type at depth 1)
+    pub of: String,
+    pub kind: RelationKind,
+    /// File and line of the declaration that states the edge
+    pub path: String,
+    pub line: usize,
+    /// Levels away from the requested type (1 = direct)
+    pub depth: usize,
+}
+
+/// Supertypes and subtypes of a type
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Hierarchy {
+    pub name: String,
+    pub supertypes: Vec<HierarchyEdge>,
+    pub subtypes: Vec<HierarchyEdge>,
+}
+
+/// A declared edge and the file it was found in
+struct Located {
+    path: String,
+    relation: TypeRelation,
+}
+
+/// Walk the hierarchy of `name` in the index in `cache`
+pub fn hierarchy(cache: &CacheManager, name: &str, options: &HierarchyOptions) -> Result<Hierarchy> {
+    let content_reader = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+    let relations = collect_relations(&content_reader, options)?;
+
+    let root = supertypes::simple_name(name);
+    Ok(Hierarchy {
+        name: name.to_string(),
+        supertypes: walk(&relations, root, options.depth, Direction::Up),
+        subtypes: walk(&relations, root, options.depth, Direction::Down),
+    })
+}
+
+/// Every declared edge in the indexed files, in path and line order
+fn collect_relations(content_reader: &ContentReader, options: &HierarchyOptions) -> Result<Vec<Located>> {
+    let include_matcher = build_glob_matcher(&options.glob_patterns);
+    let exclude_matcher = build_glob_matcher(&options.exclude_patterns);
+
+    let mut files: Vec<(String, Language, &str)> = Vec::new();
+    for file_id in 0..content_reader.file_count() {
+        let Some(file_path) = content_reader.get_file_path(file_id as u32) else {
+            continue;
+        };
+        let lang = detect_language(file_path);
+        if lang == Language::Unknown || options.language.is_some_and(|l| l != lang) {
+            continue;
+        }
+
+        let path_str = file_path.to_string_lossy().to_string();
+        let included = include_matcher.as_ref().is_none_or(|m| m.is_match(&path_str));
+        let excluded = exclude_matcher.as_ref().is_some_and(|m| m.is_match(&path_str));
+        if !included || excluded {
+            continue;
+        }
+
+        match content_reader.get_file_content(file_id as u32) {
+            Ok(content) => files.push((path_str, lang, content)),
+            Err(e) => log::warn!("Skipping {}: {}", path_str, e),
+        }
+    }
+
+    let mut relations: Vec<Located> = files
+        .par_iter()
+        .flat_map_iter(|(path, lang, content)| {
+            supertypes::extract_relations(content, *lang)
+                .into_iter()
+                .map(|relation| Located { path: path.clone(), relation })
+        })
+        .collect();
+    relations.sort_by(|a, b| a.path.cmp(&b.path).then(a.relation.line.cmp(&b.relation.line)));
+
+    Ok(relations)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Towards supertypes
+    Up,
+    /// Towards subtypes
+    Down,
+}
+
+/// Breadth-first walk from `root`, visiting each type once
+fn walk(relations: &[Located], root: &str, max_depth: usize, direction: Direction) -> Vec<HierarchyEdge> {
+    let mut edges = Vec::new();
+    let mut visited: HashSet<String> = HashSet::from([root.to_string()]);
+    let mut queue: VecDeque<(String, usize)> = VecDeque::from([(root.to_string(), 0)]);
+
+    while let Some((current, depth)) = queue.pop_front() {
+        if max_depth > 0 && depth >= max_depth {
+            continue;
+        }
+
+        for located in relations {
+            let r = &located.relation;
+            let (from, to) = match direction {
+                Direction::Up => (&r.subtype, &r.supertype),
+                Direction::Down => (&r.supertype, &r.subtype),
+            };
+            if supertypes::simple_name(from) != current {
+                continue;
+            }
+
+            edges.push(HierarchyEdge {
+                name: to.clone(),
+                of: current.clone(),
+                kind: r.kind,
+                path: located.path.clone(),
+                line: r.line,
+                depth: depth + 1,
+            });
+
+            let next = supertypes::simple_name(to).to_string();
+            if visited.insert(next.clone()) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+
+    edges
+}
+
+fn detect_language(path: &Path) -> Language {
+    Language::from_path(path)
+}
+
+fn build_glob_matcher(patterns: &[String]) -> Option<globset::GlobSet> {
+    use globset::{Glob, GlobSetBuilder};
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = QueryEngine::normalize_glob_pattern(pattern);
+        if let Ok(glob) = Glob::new(&normalized) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hierarchy_across_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("base.py"), "class Model(Persistable):\n    pass\n").unwrap();
+        fs::write(root.join("user.py"), "from base import Model\n\nclass User(base.Model):\n    pass\n").unwrap();
+        fs::write(root.join("admin.py"), "class Admin(User, Auditable):\n    pass\n").unwrap();
+
+        let cache = CacheManager::new(&root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&root, false).unwrap();
+
+        let cache = CacheManager::new(&root);
+        let result = hierarchy(&cache, "User", &HierarchyOptions::default()).unwrap();
+
+        let supers: Vec<(&str, usize)> = result.supertypes.iter().map(|e| (e.name.as_str(), e.depth)).collect();
+        assert_eq!(supers, vec![("base.Model", 1), ("Persistable", 2)]);
+        assert!(result.supertypes[0].path.ends_with("user.py"));
+        assert_eq!(result.supertypes[0].line, 3);
+
+        let subs: Vec<&str> = result.subtypes.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(subs, vec!["Admin"]);
+
+        // Depth limits each direction
+        let direct = hierarchy(&cache, "Admin", &HierarchyOptions { depth: 1, ..Default::default() }).unwrap();
+        let supers: Vec<&str> = direct.supertypes.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(supers, vec!["User", "Auditable"]);
+    }
+}
@@ -0,0 +1,419 @@
+//! Query history recorded in the index
+//!
+//! Every `rfx query` / `rfx q` run is logged to `meta.db` with its arguments,
+//! filters, result counts and timing, so a search can be listed and replayed
+//! exactly (`rfx history list/replay/clear`). Interactive mode offers these
+//! queries under Ctrl+P, and `rfx ask` shows recent queries that found results
+//! to the LLM as examples of what works in this codebase. The directories
+//! that recent queries found results in are indexed first by the background
+//! symbol indexer.
+//!
+//! ```toml
+//! [history]
+//! enabled = true       # Record queries (default: true)
+//! max_entries = 1000   # Oldest entries are dropped beyond this
+//! ```
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+
+/// Default number of entries kept
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Result directories recorded per query (those with the most matches)
+const MAX_RESULT_DIRECTORIES: usize = 10;
+
+/// `[history]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Record executed queries
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Maximum number of entries kept (oldest are dropped first)
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_entries() -> usize {
+    DEFAULT_MAX_ENTRIES
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+/// Load the `[history]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults (enabled) if the file or section is missing.
+pub fn load_history_config(cache_path: &Path) -> Result<HistoryConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(HistoryConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("history") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [history] section in .reflex/config.toml"),
+        None => Ok(HistoryConfig::default()),
+    }
+}
+
+/// Filters a query ran with (the subset interactive mode understands)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryFilters {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub symbols: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub regex: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub contains: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub expand: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glob: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// A recorded query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Entry id (assigned on record; pass to `rfx history replay`)
+    pub id: i64,
+    /// Search pattern
+    pub pattern: String,
+    /// Command-line arguments after `rfx`, used to replay the query
+    pub args: Vec<String>,
+    pub filters: HistoryFilters,
+    /// Results returned (after pagination)
+    pub result_count: usize,
+    /// Total matching results (before pagination)
+    pub total_results: usize,
+    pub timing_ms: u64,
+    /// Unix timestamp of execution
+    pub executed_at: i64,
+    /// Directories the results were in, most matches first (see [`result_directories`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directories: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// The query as a shell command (`rfx query "pattern" --lang rust`)
+    pub fn command_line(&self) -> String {
+        format!("rfx {}", shell_words::join(&self.args))
+    }
+}
+
+/// Create the query history table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and lazily by
+/// `QueryHistoryLog` so caches created before query history keep working.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS query_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pattern TEXT NOT NULL,
+            args_json TEXT NOT NULL,
+            filters_json TEXT NOT NULL,
+            result_count INTEGER NOT NULL,
+            total_results INTEGER NOT NULL,
+            timing_ms INTEGER NOT NULL,
+            executed_at INTEGER NOT NULL,
+            directories_json TEXT NOT NULL DEFAULT '[]'
+        )",
+        [],
+    )?;
+
+    // Tables created before result directories were recorded
+    let has_directories: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('query_history') WHERE name = 'directories_json'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if !has_directories {
+        conn.execute(
+            "ALTER TABLE query_history ADD COLUMN directories_json TEXT NOT NULL DEFAULT '[]'",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Directories of result paths, most matches first, at most [`MAX_RESULT_DIRECTORIES`]
+///
+/// Top-level files count as `.`; a leading `./` is ignored.
+pub fn result_directories<'a>(paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for path in paths {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        let dir = match path.rsplit_once('/') {
+            Some((dir, _)) => dir.to_string(),
+            None => ".".to_string(),
+        };
+        *counts.entry(dir).or_default() += 1;
+    }
+
+    let mut dirs: Vec<(String, usize)> = counts.into_iter().collect();
+    dirs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    dirs.into_iter().take(MAX_RESULT_DIRECTORIES).map(|(dir, _)| dir).collect()
+}
+
+/// Manages query history storage
+pub struct QueryHistoryLog {
+    cache: CacheManager,
+}
+
+impl QueryHistoryLog {
+    /// Create a new history log for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for query history")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Record a query, keeping at most `max_entries` entries. Returns the new entry id.
+    ///
+    /// The `id` and `executed_at` of `entry` are ignored and assigned here.
+    pub fn record(&self, entry: &HistoryEntry, max_entries: usize) -> Result<i64> {
+        let conn = self.open()?;
+
+        conn.execute(
+            "INSERT INTO query_history
+                (pattern, args_json, filters_json, result_count, total_results, timing_ms, executed_at, directories_json)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                entry.pattern,
+                serde_json::to_string(&entry.args)?,
+                serde_json::to_string(&entry.filters)?,
+                entry.result_count as i64,
+                entry.total_results as i64,
+                entry.timing_ms as i64,
+                chrono::Utc::now().timestamp(),
+                serde_json::to_string(&entry.directories)?,
+            ],
+        )?;
+        let id = conn.last_insert_rowid();
+
+        conn.execute(
+            "DELETE FROM query_history
+             WHERE id NOT IN (SELECT id FROM query_history ORDER BY id DESC LIMIT ?)",
+            [max_entries as i64],
+        )?;
+
+        Ok(id)
+    }
+
+    /// Most recent entries, newest first (`limit` of None returns everything)
+    pub fn list(&self, limit: Option<usize>) -> Result<Vec<HistoryEntry>> {
+        let conn = self.open()?;
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+
+        let mut stmt = conn.prepare(
+            "SELECT id, pattern, args_json, filters_json, result_count, total_results, timing_ms, executed_at, directories_json
+             FROM query_history
+             ORDER BY id DESC
+             LIMIT ?"
+        )?;
+        let entries = stmt
+            .query_map([limit], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+
+    /// Entry by id
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let conn = self.open()?;
+
+        let entry = conn
+            .query_row(
+                "SELECT id, pattern, args_json, filters_json, result_count, total_results, timing_ms, executed_at, directories_json
+                 FROM query_history
+                 WHERE id = ?",
+                [id],
+                entry_from_row,
+            )
+            .optional()?;
+
+        Ok(entry)
+    }
+
+    /// Most recent distinct queries that returned results, newest first
+    pub fn recent_successful(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let mut seen = std::collections::HashSet::new();
+
+        Ok(self
+            .list(None)?
+            .into_iter()
+            .filter(|entry| entry.total_results > 0)
+            .filter(|entry| seen.insert(entry.args.clone()))
+            .take(limit)
+            .collect())
+    }
+
+    /// Result directories of the last `queries` queries, most recently queried first
+    pub fn recent_directories(&self, queries: usize) -> Result<Vec<String>> {
+        let mut seen = std::collections::HashSet::new();
+
+        Ok(self
+            .list(Some(queries))?
+            .into_iter()
+            .flat_map(|entry| entry.directories)
+            .filter(|dir| seen.insert(dir.clone()))
+            .collect())
+    }
+
+    /// Delete every entry. Returns the number removed.
+    pub fn clear(&self) -> Result<usize> {
+        let conn = self.open()?;
+        let removed = conn.execute("DELETE FROM query_history", [])?;
+        Ok(removed)
+    }
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let args_json: String = row.get(2)?;
+    let filters_json: String = row.get(3)?;
+    let directories_json: String = row.get(8)?;
+
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        pattern: row.get(1)?,
+        args: serde_json::from_str(&args_json).unwrap_or_default(),
+        filters: serde_json::from_str(&filters_json).unwrap_or_default(),
+        result_count: row.get::<_, i64>(4)? as usize,
+        total_results: row.get::<_, i64>(5)? as usize,
+        timing_ms: row.get::<_, i64>(6)? as u64,
+        executed_at: row.get(7)?,
+        directories: serde_json::from_str(&directories_json).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(pattern: &str, total_results: usize) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            pattern: pattern.to_string(),
+            args: vec!["query".to_string(), pattern.to_string(), "--symbols".to_string()],
+            filters: HistoryFilters { symbols: true, ..Default::default() },
+            result_count: total_results.min(100),
+            total_results,
+            timing_ms: 3,
+            executed_at: 0,
+            directories: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_list_and_trim() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        let log = QueryHistoryLog::new(cache);
+
+        let first = log.record(&entry("parse", 4), 2).unwrap();
+        log.record(&entry("missing", 0), 2).unwrap();
+        let last = log.record(&entry("parse config", 1), 2).unwrap();
+
+        let entries = log.list(None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, last);
+        assert_eq!(entries[0].pattern, "parse config");
+        assert!(entries[0].filters.symbols);
+        assert_eq!(entries[0].command_line(), "rfx query 'parse config' --symbols");
+        assert!(log.get(first).unwrap().is_none());
+
+        assert_eq!(log.list(Some(1)).unwrap().len(), 1);
+        assert_eq!(log.clear().unwrap(), 2);
+        assert!(log.list(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recent_successful() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        let log = QueryHistoryLog::new(cache);
+
+        log.record(&entry("parse", 4), 100).unwrap();
+        log.record(&entry("missing", 0), 100).unwrap();
+        log.record(&entry("parse", 4), 100).unwrap();
+        log.record(&entry("render", 2), 100).unwrap();
+
+        let recent = log.recent_successful(10).unwrap();
+        let patterns: Vec<&str> = recent.iter().map(|e| e.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["render", "parse"]);
+    }
+
+    #[test]
+    fn test_recent_directories() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        let log = QueryHistoryLog::new(cache);
+
+        let dirs = result_directories(["./src/a.rs", "src/b.rs", "lib/c.rs", "main.rs"]);
+        assert_eq!(dirs, vec!["src", ".", "lib"]);
+
+        log.record(&HistoryEntry { directories: dirs, ..entry("parse", 4) }, 100).unwrap();
+        log.record(&entry("missing", 0), 100).unwrap();
+        let render = HistoryEntry { directories: vec!["ui".to_string(), "src".to_string()], ..entry("render", 2) };
+        log.record(&render, 100).unwrap();
+
+        assert_eq!(log.recent_directories(10).unwrap(), vec!["ui", "src", ".", "lib"]);
+        assert_eq!(log.recent_directories(1).unwrap(), vec!["ui", "src"]);
+    }
+
+    #[test]
+    fn test_history_config_defaults() {
+        let temp = TempDir::new().unwrap();
+        let config = load_history_config(temp.path()).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.max_entries, DEFAULT_MAX_ENTRIES);
+
+        std::fs::write(temp.path().join(CONFIG_TOML), "[history]\nenabled = false\n").unwrap();
+        let config = load_history_config(temp.path()).unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.max_entries, DEFAULT_MAX_ENTRIES);
+    }
+}
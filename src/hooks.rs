@@ -0,0 +1,269 @@
+//! Git hook integration (`rfx hooks install`)
+//!
+//! Installs `post-commit`, `post-merge` and `post-checkout` hooks that pipe the
+//! files a git operation changed into `rfx index --only -`, so the index stays
+//! fresh without running `rfx watch`. If the targeted refresh fails (e.g. the
+//! new branch has no index yet) the hook falls back to a full `rfx index`. Hooks
+//! run in the background and never fail the git command.
+//!
+//! Each hook gets a marked block, so existing hooks are extended rather than
+//! replaced and `--uninstall` removes only what Reflex added. Hook managers
+//! are detected first:
+//! - husky: the block goes into `.husky/<hook>`
+//! - lefthook: hooks are generated from `lefthook.yml`, so nothing is written;
+//!   the config to add is returned instead
+//! - otherwise: the repository's hooks directory (`core.hooksPath` aware)
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error_code::ReflexError;
+
+const BEGIN_MARKER: &str = "# >>> reflex >>>";
+const END_MARKER: &str = "# <<< reflex <<<";
+
+/// Config files that mean lefthook owns the git hooks
+const LEFTHOOK_CONFIGS: &[&str] = &["lefthook.yml", ".lefthook.yml", "lefthook.yaml", ".lefthook.yaml"];
+
+/// Hooks installed, with the shell command listing the files each one changed
+const HOOKS: &[(&str, &str)] = &[
+    ("post-commit", "git diff-tree --no-commit-id --name-only -r HEAD"),
+    ("post-merge", "git diff --name-only ORIG_HEAD HEAD"),
+    // Arguments: previous HEAD, new HEAD, 1 for a branch checkout (0 for files)
+    ("post-checkout", "git diff --name-only \"$1\" \"$2\""),
+];
+
+/// Tool that manages the repository's git hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookManager {
+    Git,
+    Husky,
+    Lefthook,
+}
+
+/// What happened to one hook file
+#[derive(Debug, Clone, Serialize)]
+pub struct HookChange {
+    pub hook: String,
+    pub path: String,
+    /// `created`, `appended`, `updated`, `removed`, `deleted` or `unchanged`
+    pub action: String,
+}
+
+/// Result of installing or uninstalling the hooks
+#[derive(Debug, Clone, Serialize)]
+pub struct HookReport {
+    pub manager: HookManager,
+    pub changes: Vec<HookChange>,
+    /// Config to add by hand when lefthook manages the hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manual_config: Option<String>,
+}
+
+/// Install the hooks into the repository at `root`, or remove them with `uninstall`
+pub fn install(root: &Path, uninstall: bool) -> Result<HookReport> {
+    if !crate::git::is_git_repo(root) {
+        return Err(ReflexError::InvalidArgument(format!(
+            "{} is not a git repository; hooks need git.",
+            root.display()
+        )).into());
+    }
+
+    let manager = detect_manager(root);
+    if manager == HookManager::Lefthook {
+        return Ok(HookReport {
+            manager,
+            changes: Vec::new(),
+            manual_config: (!uninstall).then(lefthook_config),
+        });
+    }
+
+    let dir = match manager {
+        HookManager::Husky => root.join(".husky"),
+        _ => git_hooks_dir(root)?,
+    };
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create hooks directory {}", dir.display()))?;
+
+    let mut changes = Vec::new();
+    for (hook, changed_files) in HOOKS {
+        let path = dir.join(hook);
+        let existing = std::fs::read_to_string(&path).ok();
+
+        let (content, action) = if uninstall {
+            match existing.as_deref().and_then(remove_block) {
+                Some(rest) if is_empty_script(&rest) => (None, "deleted"),
+                Some(rest) => (Some(rest), "removed"),
+                None => continue,
+            }
+        } else {
+            let action = match existing.as_deref() {
+                None => "created",
+                Some(existing) if existing.contains(&hook_block(hook, changed_files)) => "unchanged",
+                Some(existing) if existing.contains(BEGIN_MARKER) => "updated",
+                Some(_) => "appended",
+            };
+            (Some(add_block(existing.as_deref(), hook, changed_files)), action)
+        };
+
+        match content {
+            Some(content) if action != "unchanged" => {
+                std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                make_executable(&path)?;
+            }
+            Some(_) => {}
+            None => std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?,
+        }
+
+        changes.push(HookChange {
+            hook: hook.to_string(),
+            path: path.display().to_string(),
+            action: action.to_string(),
+        });
+    }
+
+    Ok(HookReport { manager, changes, manual_config: None })
+}
+
+/// Which tool manages the hooks of the repository at `root`
+pub fn detect_manager(root: &Path) -> HookManager {
+    if LEFTHOOK_CONFIGS.iter().any(|name| root.join(name).exists()) {
+        HookManager::Lefthook
+    } else if root.join(".husky").is_dir() {
+        HookManager::Husky
+    } else {
+        HookManager::Git
+    }
+}
+
+/// Hooks directory git runs hooks from (honors `core.hooksPath` and worktrees)
+fn git_hooks_dir(root: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("Failed to execute git rev-parse")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git rev-parse failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(if dir.is_absolute() { dir } else { root.join(dir) })
+}
+
+/// The marked block for one hook
+fn hook_block(hook: &str, changed_files: &str) -> String {
+    let condition = if hook == "post-checkout" {
+        // File checkouts (`git checkout -- file`) don't move HEAD
+        "[ \"$3\" = \"1\" ] && command -v rfx >/dev/null 2>&1 && [ -d .reflex ]"
+    } else {
+        "command -v rfx >/dev/null 2>&1 && [ -d .reflex ]"
+    };
+    format!(
+        "{BEGIN_MARKER}\n\
+         # Keep the Reflex index fresh (installed by `rfx hooks install`)\n\
+         if {condition}; then\n\
+         \x20 ({changed_files} | rfx index --only - --quiet || rfx index --quiet) >/dev/null 2>&1 &\n\
+         fi\n\
+         {END_MARKER}\n"
+    )
+}
+
+/// Hook script with the Reflex block added, or replaced if one is already there
+fn add_block(existing: Option<&str>, hook: &str, changed_files: &str) -> String {
+    let block = hook_block(hook, changed_files);
+    let base = existing.map(|e| remove_block(e).unwrap_or_else(|| e.to_string())).unwrap_or_default();
+    if base.trim().is_empty() {
+        format!("#!/bin/sh\n\n{}", block)
+    } else {
+        format!("{}\n\n{}", base.trim_end(), block)
+    }
+}
+
+/// Hook script without the Reflex block, or None if it has no block
+fn remove_block(content: &str) -> Option<String> {
+    let start = content.find(BEGIN_MARKER)?;
+    let end = content[start..].find(END_MARKER)? + start + END_MARKER.len();
+    let rest = format!("{}{}", &content[..start], content[end..].trim_start_matches('\n'));
+    Some(format!("{}\n", rest.trim_end()))
+}
+
+/// Whether a script has nothing left but a shebang and blank lines
+fn is_empty_script(content: &str) -> bool {
+    content.lines().all(|line| line.trim().is_empty() || line.starts_with("#!"))
+}
+
+/// lefthook.yml entries equivalent to the installed hooks
+fn lefthook_config() -> String {
+    let mut config = String::new();
+    for (hook, changed_files) in HOOKS {
+        let changed_files = changed_files.replace("\"$1\" \"$2\"", "{1} {2}");
+        config.push_str(&format!(
+            "{hook}:\n  commands:\n    reflex:\n      run: ({changed_files} | rfx index --only - --quiet || rfx index --quiet) >/dev/null 2>&1 &\n"
+        ));
+    }
+    config
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("Failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CHANGED: &str = "git diff --name-only ORIG_HEAD HEAD";
+
+    #[test]
+    fn test_block_is_appended_and_removed() {
+        let existing = "#!/bin/sh\nnpm test\n";
+        let installed = add_block(Some(existing), "post-merge", CHANGED);
+        assert!(installed.starts_with("#!/bin/sh\nnpm test\n\n# >>> reflex >>>"));
+        assert!(installed.contains("| rfx index --only - --quiet"));
+
+        // Installing again replaces the block instead of duplicating it
+        let reinstalled = add_block(Some(&installed), "post-merge", CHANGED);
+        assert_eq!(reinstalled.matches(BEGIN_MARKER).count(), 1);
+
+        let removed = remove_block(&reinstalled).unwrap();
+        assert_eq!(removed, existing);
+        assert!(!is_empty_script(&removed));
+        assert!(remove_block(existing).is_none());
+
+        let created = add_block(None, "post-merge", CHANGED);
+        assert!(is_empty_script(&remove_block(&created).unwrap()));
+    }
+
+    #[test]
+    fn test_detect_manager() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(detect_manager(temp.path()), HookManager::Git);
+
+        std::fs::create_dir(temp.path().join(".husky")).unwrap();
+        assert_eq!(detect_manager(temp.path()), HookManager::Husky);
+
+        std::fs::write(temp.path().join("lefthook.yml"), "pre-commit:\n").unwrap();
+        assert_eq!(detect_manager(temp.path()), HookManager::Lefthook);
+        assert!(lefthook_config().contains("git diff --name-only {1} {2}"));
+    }
+}
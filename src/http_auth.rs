@@ -0,0 +1,149 @@
+//! Access control for the HTTP server (`rfx serve`)
+//!
+//! The server binds to localhost by default, where every local process is
+//! trusted. For CI and team setups it can be bound to other addresses, so it
+//! supports:
+//!
+//! - **Token auth**: `--auth-token` (or `REFLEX_TOKEN`) requires
+//!   `Authorization: Bearer <token>` on every endpoint except `/health`
+//! - **Rate limiting**: `--rate-limit N` allows N requests per minute per client IP
+//!
+//! Without a token, `POST /index` (the only mutating endpoint) is refused for
+//! clients other than localhost.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding the server token when `--auth-token` is not given
+pub const TOKEN_ENV: &str = "REFLEX_TOKEN";
+
+/// Token from `--auth-token`, falling back to `REFLEX_TOKEN` (empty values are ignored)
+pub fn resolve_token(flag: Option<String>) -> Option<String> {
+    flag.or_else(|| std::env::var(TOKEN_ENV).ok())
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Whether `host` only accepts connections from this machine
+pub fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Check an `Authorization` header value against the server token
+///
+/// Only the `Bearer` scheme is accepted. The comparison takes the same time
+/// for every token of the right length.
+pub fn authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(provided) = header.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (provided, expected) = (provided.trim().as_bytes(), token.as_bytes());
+    provided.len() == expected.len()
+        && provided.iter().zip(expected).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Per-client token bucket rate limiter
+///
+/// Each client IP may burst up to `per_minute` requests, refilled evenly over a minute.
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Clients tracked before idle buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute: per_minute.max(1),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take one request from `client`'s budget
+    ///
+    /// Returns the time until the next request is allowed when the budget is exhausted.
+    pub fn check(&self, client: IpAddr) -> Result<(), Duration> {
+        self.check_at(client, Instant::now())
+    }
+
+    fn check_at(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let capacity = self.per_minute as f64;
+        let per_second = capacity / 60.0;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            // Buckets that have refilled completely carry no state worth keeping
+            buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * per_second < capacity);
+        }
+
+        let bucket = buckets.entry(client).or_insert(Bucket { tokens: capacity, updated: now });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorized_requires_matching_bearer_token() {
+        assert!(authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!authorized(Some("Bearer s3creT"), "s3cret"));
+        assert!(!authorized(Some("Basic s3cret"), "s3cret"));
+        assert!(!authorized(None, "s3cret"));
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("127.0.0.1"));
+        assert!(is_loopback("localhost"));
+        assert!(is_loopback("[::1]"));
+        assert!(!is_loopback("0.0.0.0"));
+        assert!(!is_loopback("192.168.1.10"));
+    }
+
+    #[test]
+    fn test_rate_limiter_bursts_then_refills() {
+        let limiter = RateLimiter::new(60);
+        let client: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let start = Instant::now();
+
+        for _ in 0..60 {
+            assert!(limiter.check_at(client, start).is_ok());
+        }
+        let retry = limiter.check_at(client, start).unwrap_err();
+        assert!(retry <= Duration::from_secs(1));
+
+        // Other clients have their own budget
+        assert!(limiter.check_at(other, start).is_ok());
+
+        // One request per second is refilled
+        assert!(limiter.check_at(client, start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.check_at(client, start + Duration::from_secs(1)).is_err());
+    }
+}
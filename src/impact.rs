@@ -0,0 +1,261 @@
+//! Rename / signature change impact analysis
+//!
+//! `rfx impact <symbol>` answers "what breaks if I rename this or change its
+//! signature?". It combines three sources:
+//!
+//! 1. **Definitions**: a symbol search (served by the symbol cache) for the exact name
+//! 2. **References**: a word-boundary full-text search for the name
+//! 3. **Dependency graph**: reverse traversal from each defining file
+//!
+//! Every reference is classified by how its file reaches a definition:
+//!
+//! - **direct**: the defining file itself, or a file importing it
+//! - **transitive**: a file reaching it through other files (re-exports, barrels)
+//! - **unlinked**: no import path was found; either a name collision or a use
+//!   the dependency graph can't see (dynamic imports, globals, unresolved paths)
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::dependency::DependencyIndex;
+use crate::models::{Language, SearchResult, SymbolKind};
+use crate::paths::relative_path;
+use crate::query::{QueryEngine, QueryFilter};
+
+/// Filters for an impact analysis
+#[derive(Debug, Clone)]
+pub struct ImpactFilter {
+    /// Only consider definitions of this kind
+    pub kind: Option<SymbolKind>,
+    /// Only consider files in this language
+    pub language: Option<Language>,
+    /// Maximum import distance from a defining file (1 = direct importers only)
+    pub depth: usize,
+}
+
+impl Default for ImpactFilter {
+    fn default() -> Self {
+        Self {
+            kind: None,
+            language: None,
+            depth: 3,
+        }
+    }
+}
+
+/// A line mentioning the symbol
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpactSite {
+    pub line: usize,
+    pub preview: String,
+}
+
+/// A definition of the analyzed symbol
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactDefinition {
+    pub path: String,
+    pub kind: SymbolKind,
+    pub line: usize,
+    pub preview: String,
+}
+
+/// References to the symbol in one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpactFile {
+    pub path: String,
+    /// Import distance from the nearest defining file (0 = defining file, None = unlinked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    pub references: Vec<ImpactSite>,
+}
+
+/// Files and lines affected by renaming or changing a symbol
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactReport {
+    pub symbol: String,
+    pub definitions: Vec<ImpactDefinition>,
+    /// References in defining files and their direct importers
+    pub direct: Vec<ImpactFile>,
+    /// References in files importing a defining file through other files
+    pub transitive: Vec<ImpactFile>,
+    /// References in files without an import path to any definition
+    pub unlinked: Vec<ImpactFile>,
+    /// Files affected (direct + transitive + unlinked)
+    pub total_files: usize,
+    /// Reference lines affected (definitions excluded)
+    pub total_references: usize,
+}
+
+/// Analyze the impact of renaming or changing `symbol`
+pub fn analyze_impact(cache: &CacheManager, symbol: &str, filter: &ImpactFilter) -> Result<ImpactReport> {
+    let engine = QueryEngine::new(cache.clone());
+    let base = QueryFilter {
+        language: filter.language,
+        limit: None,
+        force: true,
+        suppress_output: true,
+        ..Default::default()
+    };
+
+    let definitions = engine.search(symbol, QueryFilter {
+        symbols_mode: true,
+        exact: true,
+        kinds: filter.kind.clone().into_iter().collect(),
+        ..base.clone()
+    })?;
+
+    if definitions.is_empty() {
+        anyhow::bail!(
+            "No definition of '{}' found in the index.\n\
+             \n\
+             Impact analysis starts from a symbol definition. Check the name with:\n\
+             $ rfx query {} --symbols",
+            symbol,
+            symbol
+        );
+    }
+
+    let references = engine.search(symbol, base)?;
+
+    // Import distance of every file from its nearest defining file
+    let root = cache.workspace_root();
+    let deps_index = DependencyIndex::new(cache.clone());
+    let mut depths: HashMap<String, usize> = HashMap::new();
+    let defining: HashSet<String> = definitions.iter().map(|d| relative_path(Path::new(&d.path), &root)).collect();
+
+    for path in &defining {
+        let Some(file_id) = deps_index.get_file_id_by_path(path).ok().flatten() else {
+            depths.insert(path.clone(), 0);
+            continue;
+        };
+        let dependents = deps_index.get_transitive_dependents(file_id, filter.depth)?;
+        let ids: Vec<i64> = dependents.keys().copied().collect();
+        let paths = deps_index.get_file_paths(&ids)?;
+        for (id, depth) in dependents {
+            if let Some(path) = paths.get(&id) {
+                let entry = depths.entry(path.clone()).or_insert(depth);
+                *entry = (*entry).min(depth);
+            }
+        }
+    }
+
+    Ok(classify(symbol, &definitions, &references, &depths, &root))
+}
+
+/// Group `references` by import distance (`depths`, keyed by workspace-relative path)
+fn classify(
+    symbol: &str,
+    definitions: &[SearchResult],
+    references: &[SearchResult],
+    depths: &HashMap<String, usize>,
+    root: &Path,
+) -> ImpactReport {
+    let definition_lines: HashSet<(String, usize)> = definitions
+        .iter()
+        .map(|d| (relative_path(Path::new(&d.path), root), d.span.start_line))
+        .collect();
+
+    let mut files: BTreeMap<String, Vec<ImpactSite>> = BTreeMap::new();
+    for reference in references {
+        let path = relative_path(Path::new(&reference.path), root);
+        if definition_lines.contains(&(path.clone(), reference.span.start_line)) {
+            continue;
+        }
+        files.entry(path).or_default().push(ImpactSite {
+            line: reference.span.start_line,
+            preview: reference.preview.trim().to_string(),
+        });
+    }
+
+    let mut report = ImpactReport {
+        symbol: symbol.to_string(),
+        definitions: definitions
+            .iter()
+            .map(|d| ImpactDefinition {
+                path: relative_path(Path::new(&d.path), root),
+                kind: d.kind.clone(),
+                line: d.span.start_line,
+                preview: d.preview.lines().next().unwrap_or("").trim().to_string(),
+            })
+            .collect(),
+        direct: Vec::new(),
+        transitive: Vec::new(),
+        unlinked: Vec::new(),
+        total_files: files.len(),
+        total_references: 0,
+    };
+
+    for (path, mut references) in files {
+        references.sort_by_key(|r| r.line);
+        references.dedup_by_key(|r| r.line);
+        report.total_references += references.len();
+
+        let depth = depths.get(&path).copied();
+        let file = ImpactFile { path, depth, references };
+        match depth {
+            Some(0 | 1) => report.direct.push(file),
+            Some(_) => report.transitive.push(file),
+            None => report.unlinked.push(file),
+        }
+    }
+
+    report.direct.sort_by_key(|f| f.depth);
+    report.transitive.sort_by_key(|f| f.depth);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Span;
+
+    fn result(path: &str, line: usize, preview: &str) -> SearchResult {
+        SearchResult::new(
+            path.to_string(),
+            Language::Rust,
+            SymbolKind::Unknown("text".to_string()),
+            None,
+            Span::new(line, 0, line, 0),
+            None,
+            preview.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_classify_groups_by_import_distance() {
+        let definitions = vec![SearchResult {
+            kind: SymbolKind::Function,
+            ..result("./src/auth.rs", 3, "pub fn verify(token: &str) -> bool {")
+        }];
+        let references = vec![
+            result("./src/auth.rs", 3, "pub fn verify(token: &str) -> bool {"),
+            result("./src/auth.rs", 12, "    verify(t)"),
+            result("./src/api.rs", 8, "use crate::auth::verify;"),
+            result("./src/api.rs", 20, "    if verify(&token) {"),
+            result("./src/server.rs", 4, "pub use crate::api::verify;"),
+            result("./scripts/legacy.rs", 1, "fn verify() {}"),
+        ];
+        let depths: HashMap<String, usize> = [("src/auth.rs", 0), ("src/api.rs", 1), ("src/server.rs", 2)]
+            .into_iter()
+            .map(|(p, d)| (p.to_string(), d))
+            .collect();
+
+        let report = classify("verify", &definitions, &references, &depths, Path::new("/repo"));
+
+        assert_eq!(report.definitions.len(), 1);
+        assert_eq!(report.definitions[0].path, "src/auth.rs");
+
+        let direct: Vec<_> = report.direct.iter().map(|f| (f.path.as_str(), f.references.len())).collect();
+        assert_eq!(direct, vec![("src/auth.rs", 1), ("src/api.rs", 2)]);
+        assert_eq!(report.transitive.len(), 1);
+        assert_eq!(report.transitive[0].path, "src/server.rs");
+        assert_eq!(report.unlinked.len(), 1);
+        assert_eq!(report.unlinked[0].depth, None);
+
+        assert_eq!(report.total_files, 4);
+        assert_eq!(report.total_references, 5);
+    }
+}
@@ -4,19 +4,25 @@
 //! and builds the symbol/token cache for fast querying.
 
 use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use rayon::prelude::*;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use crate::cache::CacheManager;
+use crate::cache::{CacheManager, CONFIG_TOML, TOKENS_BIN};
 use crate::content_store::ContentWriter;
 use crate::dependency::DependencyIndex;
-use crate::models::{Dependency, IndexConfig, IndexStats, Language, ImportType};
+use crate::embedded::{EmbeddedIndex, EmbeddedRegion};
+use crate::config_keys::{ConfigKeyIndex, ConfigKeyInfo, ConfigKeyMatcher};
+use crate::flags::{FlagIndex, FlagMatcher, FlagUsageInfo};
+use crate::tables::{TableIndex, TableRefInfo};
+use crate::models::{Dependency, IndexConfig, IndexStats, Language, ImportType, SkipReason, SkippedFile, WorkspaceRoot};
 use crate::output;
 use crate::parsers::{DependencyExtractor, ImportInfo, ExportInfo};
 use crate::parsers::rust::RustDependencyExtractor;
@@ -33,8 +39,17 @@ use crate::parsers::kotlin::KotlinDependencyExtractor;
 use crate::parsers::zig::ZigDependencyExtractor;
 use crate::parsers::vue::VueDependencyExtractor;
 use crate::parsers::svelte::SvelteDependencyExtractor;
+use crate::parsers::make::MakeDependencyExtractor;
+use crate::parsers::cmake::CMakeDependencyExtractor;
+use crate::parsers::bazel::BazelDependencyExtractor;
+use crate::parsers::shell::ShellDependencyExtractor;
+use crate::transforms::{TransformIndex, Transformer};
+use crate::tokens::TokenIndex;
 use crate::trigram::TrigramIndex;
 
+/// Per-directory ignore file honored in addition to .gitignore (same syntax)
+pub const REFLEXIGNORE: &str = ".reflexignore";
+
 /// Progress callback type: (current_file_count, total_file_count, status_message)
 /// Uses Arc to allow cloning for multi-threaded progress updates
 pub type ProgressCallback = Arc<dyn Fn(usize, usize, String) + Send + Sync>;
@@ -49,6 +64,28 @@ struct FileProcessingResult {
     line_count: usize,
     dependencies: Vec<ImportInfo>,
     exports: Vec<ExportInfo>,
+    flags: Vec<FlagUsageInfo>,
+    config_keys: Vec<ConfigKeyInfo>,
+    tables: Vec<TableRefInfo>,
+    embedded: Vec<EmbeddedRegion>,
+    /// Identity of the transform that rewrote the content, if any
+    transform: Option<String>,
+}
+
+/// Look up pipe-delimited candidate paths, returning the first one in the index
+///
+/// Lookup errors (such as an ambiguous suffix match) count as misses so a guessed
+/// candidate like `models/__init__.py` can't fail the whole dependency pass.
+fn lookup_candidate_paths(dep_index: &DependencyIndex, candidates: &str) -> Option<i64> {
+    candidates.split('|').find_map(|candidate| {
+        match dep_index.get_file_id_by_path(candidate) {
+            Ok(id) => id,
+            Err(e) => {
+                log::debug!("Skipping dependency candidate {}: {}", candidate, e);
+                None
+            }
+        }
+    })
 }
 
 /// Find the nearest tsconfig.json for a given source file
@@ -67,37 +104,293 @@ fn find_nearest_tsconfig<'a>(
         root.join(file_path)
     };
 
-    // Start from the file's directory and walk up
+    // Start from the file's directory and walk up, checking the root itself last
     let mut current_dir = abs_file_path.parent()?;
 
-    loop {
+    while current_dir.starts_with(root) {
         // Check if we have a tsconfig for this directory
         if let Some(alias_map) = tsconfigs.get(current_dir) {
             return Some(alias_map);
         }
 
+        // Stop once the root has been checked
+        if current_dir == root {
+            break;
+        }
+
         // Move up one directory
         current_dir = current_dir.parent()?;
+    }
 
-        // Stop if we've reached the root
-        if current_dir == root || !current_dir.starts_with(root) {
-            break;
+    None
+}
+
+/// `[performance]` section of `.reflex/config.toml`
+///
+/// Unset keys leave the corresponding [`IndexConfig`] value unchanged.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PerformanceConfig {
+    /// Threads for parallel indexing (0 = auto)
+    #[serde(default)]
+    pub parallel_threads: Option<usize>,
+    /// Memory budget for file content held during indexing (MB)
+    #[serde(default)]
+    pub memory_budget_mb: Option<usize>,
+}
+
+impl PerformanceConfig {
+    /// Apply the configured values to an index config
+    pub fn apply(&self, config: &mut IndexConfig) {
+        if let Some(threads) = self.parallel_threads {
+            config.parallel_threads = threads;
+        }
+        if let Some(budget) = self.memory_budget_mb {
+            config.memory_budget_mb = budget;
+        }
+    }
+}
+
+/// Load the `[performance]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_performance_config(cache_path: &Path) -> Result<PerformanceConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(PerformanceConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("performance") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [performance] section in .reflex/config.toml"),
+        None => Ok(PerformanceConfig::default()),
+    }
+}
+
+/// Size limits and link handling from the `[index]` section of `.reflex/config.toml`
+///
+/// Unset keys leave the corresponding [`IndexConfig`] value unchanged.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IndexLimitsConfig {
+    /// Largest file to index (bytes)
+    #[serde(default)]
+    pub max_file_size: Option<usize>,
+    /// Longest line a file may have (bytes, 0 = no limit)
+    #[serde(default)]
+    pub max_line_length: Option<usize>,
+    /// Skip files that look binary
+    #[serde(default)]
+    pub binary_detection: Option<bool>,
+    /// Follow symbolic links
+    #[serde(default)]
+    pub follow_symlinks: Option<bool>,
+    /// Index a file with several hard links once
+    #[serde(default)]
+    pub dedupe_hardlinks: Option<bool>,
+}
+
+impl IndexLimitsConfig {
+    /// Apply the configured values to an index config
+    pub fn apply(&self, config: &mut IndexConfig) {
+        if let Some(size) = self.max_file_size {
+            config.max_file_size = size;
+        }
+        if let Some(length) = self.max_line_length {
+            config.max_line_length = length;
+        }
+        if let Some(detect) = self.binary_detection {
+            config.binary_detection = detect;
+        }
+        if let Some(follow) = self.follow_symlinks {
+            config.follow_symlinks = follow;
+        }
+        if let Some(dedupe) = self.dedupe_hardlinks {
+            config.dedupe_hardlinks = dedupe;
+        }
+    }
+}
+
+/// Load the size limits in the `[index]` section of the project's `.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_index_limits_config(cache_path: &Path) -> Result<IndexLimitsConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(IndexLimitsConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("index") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [index] section in .reflex/config.toml"),
+        None => Ok(IndexLimitsConfig::default()),
+    }
+}
+
+/// Load the `[index.extensions]` section from the project's `.reflex/config.toml`
+///
+/// Maps extra file extensions to languages (`phtml = "php"`, `pyi = "python"`).
+/// Returns an empty map if the file or section is missing.
+pub fn load_extension_config(cache_path: &Path) -> Result<HashMap<String, Language>> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("index").and_then(|index| index.get("extensions")) {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [index.extensions] in .reflex/config.toml (values must be language names such as \"php\" or \"cpp\")"),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Install the project's extension → language mappings for this process
+///
+/// Combines `[index.extensions]` with `extra` (which wins on conflicts). Does
+/// nothing when both are empty, so projects without mappings never reset them.
+/// An unreadable section is ignored with a warning rather than failing the caller.
+pub fn install_extension_overrides(cache_path: &Path, extra: &HashMap<String, Language>) {
+    let mut extensions = load_extension_config(cache_path).unwrap_or_else(|e| {
+        log::warn!("Ignoring [index.extensions]: {:#}", e);
+        HashMap::new()
+    });
+    extensions.extend(extra.iter().map(|(ext, lang)| (ext.clone(), *lang)));
+
+    if !extensions.is_empty() {
+        log::debug!("Extension overrides: {:?}", extensions);
+        Language::set_extension_overrides(extensions);
+    }
+}
+
+/// Upper bound on files per batch, regardless of the memory budget
+///
+/// Keeps per-batch parse results (dependencies, exports, flags, config keys) bounded when a
+/// repository has very many tiny files.
+const MAX_BATCH_FILES: usize = 5000;
+
+/// Split files into consecutive batches whose total size stays within `budget_bytes`
+///
+/// A file larger than the budget gets a batch of its own. Returns index ranges
+/// into `sizes` (in order, covering every file).
+fn plan_batches(sizes: &[u64], budget_bytes: u64, max_files: usize) -> Vec<std::ops::Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut batch_bytes = 0u64;
+
+    for (i, &size) in sizes.iter().enumerate() {
+        let full = i - start >= max_files || batch_bytes + size > budget_bytes;
+        if i > start && full {
+            batches.push(start..i);
+            start = i;
+            batch_bytes = 0;
         }
+        batch_bytes += size;
+    }
+    if start < sizes.len() {
+        batches.push(start..sizes.len());
     }
 
+    batches
+}
+
+/// Bytes checked for NUL when detecting binary files (same window as git)
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// A file left out by the index limits, with its content hash if it was read
+struct Skipped {
+    path: PathBuf,
+    reason: SkipReason,
+    detail: String,
+    hash: Option<String>,
+}
+
+/// What makes two discovered paths the same file
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum LinkKey {
+    /// Device and inode (hard links)
+    Inode(u64, u64),
+    /// Path with all symlinks resolved
+    Canonical(PathBuf),
+}
+
+#[cfg(unix)]
+fn inode_key(path: &Path) -> Option<LinkKey> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(LinkKey::Inode(metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn inode_key(_path: &Path) -> Option<LinkKey> {
     None
 }
 
+/// Read a file from `blobs` when indexing a git revision, otherwise from disk
+fn read_source(blobs: Option<&HashMap<PathBuf, Vec<u8>>>, path: &Path) -> std::io::Result<Vec<u8>> {
+    match blobs {
+        Some(blobs) => blobs.get(path).cloned().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "not in the indexed revision")
+        }),
+        None => std::fs::read(path),
+    }
+}
+
+/// Whether a walk error is the walker refusing to follow a symlink into an ancestor
+fn is_symlink_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_symlink_loop(err),
+        _ => false,
+    }
+}
+
 /// Manages the indexing process
 pub struct Indexer {
     cache: CacheManager,
     config: IndexConfig,
+    /// Files skipped by the current run (size limit during discovery, content limits while reading)
+    skipped: Mutex<Vec<Skipped>>,
+    /// Files the current run reached through symlinks, with their canonical paths
+    linked: Mutex<Vec<(PathBuf, PathBuf)>>,
 }
 
 impl Indexer {
     /// Create a new indexer with the given cache manager and config
     pub fn new(cache: CacheManager, config: IndexConfig) -> Self {
-        Self { cache, config }
+        install_extension_overrides(cache.path(), &config.extensions);
+        crate::parsers::sql::install_dialect(cache.path());
+        Self { cache, config, skipped: Mutex::new(Vec::new()), linked: Mutex::new(Vec::new()) }
+    }
+
+    /// Cache the indexer writes to
+    pub fn cache(&self) -> &CacheManager {
+        &self.cache
+    }
+
+    /// Indexing configuration
+    pub fn config(&self) -> &IndexConfig {
+        &self.config
     }
 
     /// Build or update the index for the given root directory
@@ -113,10 +406,60 @@ impl Indexer {
         progress_callback: Option<ProgressCallback>,
     ) -> Result<IndexStats> {
         let root = root.as_ref();
+
+        // A full index supersedes any dirty working-tree overlay
+        crate::overlay::Overlay::clear(&self.cache)?;
+
+        self.index_impl(root, show_progress, progress_callback, None, None, false)
+    }
+
+    /// Rebuild the index even if no file changed since the last run
+    ///
+    /// Used by `rfx doctor --repair` when the content store no longer matches
+    /// the file hashes recorded in meta.db.
+    pub fn rebuild(&self, root: impl AsRef<Path>, show_progress: bool) -> Result<IndexStats> {
+        crate::overlay::Overlay::clear(&self.cache)?;
+        self.index_impl(root.as_ref(), show_progress, None, None, None, true)
+    }
+
+    /// Index an explicit list of files instead of walking the workspace
+    ///
+    /// Used to build the dirty working-tree overlay. Workspace roots are
+    /// neither resolved nor recorded.
+    pub fn index_files(&self, root: impl AsRef<Path>, files: Vec<PathBuf>) -> Result<IndexStats> {
+        self.index_impl(root.as_ref(), false, None, Some(files), None, false)
+    }
+
+    /// Index file contents read from the git object database instead of disk
+    ///
+    /// Used to build historical revision segments. Keys are the paths the
+    /// files would have in the working tree (`root` joined with the tree path).
+    pub fn index_blobs(&self, root: impl AsRef<Path>, blobs: HashMap<PathBuf, Vec<u8>>) -> Result<IndexStats> {
+        let mut files: Vec<PathBuf> = blobs.keys().cloned().collect();
+        files.sort();
+        self.index_impl(root.as_ref(), false, None, Some(files), Some(&blobs), false)
+    }
+
+    fn index_impl(
+        &self,
+        root: &Path,
+        show_progress: bool,
+        progress_callback: Option<ProgressCallback>,
+        explicit_files: Option<Vec<PathBuf>>,
+        blobs: Option<&HashMap<PathBuf, Vec<u8>>>,
+        rebuild: bool,
+    ) -> Result<IndexStats> {
         log::info!("Indexing directory: {:?}", root);
 
-        // Get git state (if in git repo)
-        let git_state = crate::git::get_git_state_optional(root)?;
+        // Get git state (if in git repo); revision segments are keyed by their commit
+        let git_state = match self.cache.revision() {
+            Some(commit) => Some(crate::git::GitState {
+                branch: commit.to_string(),
+                commit: commit.to_string(),
+                dirty: false,
+            }),
+            None => crate::git::get_git_state_optional(root)?,
+        };
         let branch = git_state
             .as_ref()
             .map(|s| s.branch.clone())
@@ -153,18 +496,70 @@ impl Indexer {
         // Ensure cache is initialized
         self.cache.init()?;
 
+        // Upgrade a cache written by an older Reflex; what can't be upgraded in place is rebuilt
+        let migration = self.cache.migrate()?;
+        for step in &migration.applied {
+            log::info!("Migrated cache: {}", step);
+        }
+        let rebuild = match migration.rebuild_required {
+            Some(reason) if !rebuild => {
+                log::warn!("Rebuilding the index: {}", reason);
+                if show_progress {
+                    eprintln!("Cache format changed ({}); rebuilding the index", reason);
+                }
+                true
+            }
+            _ => rebuild,
+        };
+
+        // Changed limits can include or exclude files whose content didn't change
+        let record_skips = explicit_files.is_none();
+        let limits = self.limits_identity();
+        let rebuild = rebuild
+            || (record_skips && self.cache.recorded_index_limits()?.as_deref() != Some(limits.as_str()));
+        self.skipped.lock().unwrap().clear();
+        self.linked.lock().unwrap().clear();
+
         // Check available disk space after cache is initialized
         self.check_disk_space(root)?;
 
         // Load existing hashes for incremental indexing (for current branch)
         let existing_hashes = self.cache.load_hashes_for_branch(&branch)?;
         log::debug!("Loaded {} existing file hashes for branch '{}'", existing_hashes.len(), branch);
+        // Files skipped last time are discovered again but have no hash in the index
+        let skipped_hashes = if record_skips {
+            self.cache.load_skipped_hashes()?
+        } else {
+            HashMap::new()
+        };
+
+        let roots: Vec<WorkspaceRoot> = if explicit_files.is_some() {
+            Vec::new()
+        } else {
+            self.resolve_roots()?
+        };
 
         // Step 1: Walk directory tree and collect files
-        let files = self.discover_files(root)?;
+        let files = match explicit_files {
+            Some(files) => files,
+            None => self.discover_files_in_roots(root, &roots)?,
+        };
         let total_files = files.len();
         log::info!("Discovered {} files to index", total_files);
 
+        // Record per-root metadata so filters can address roots by name
+        if !roots.is_empty() {
+            let root_counts: Vec<(WorkspaceRoot, usize)> = roots
+                .iter()
+                .map(|r| {
+                    let root_dir = root.join(&r.path);
+                    let count = files.iter().filter(|f| f.starts_with(&root_dir)).count();
+                    (r.clone(), count)
+                })
+                .collect();
+            self.cache.update_roots(&root_counts)?;
+        }
+
         // Step 1.4: Parse tsconfig.json files for TypeScript/Vue path alias resolution
         // Must be done before parallel processing so it's available during dependency extraction
         let tsconfigs = crate::parsers::tsconfig::parse_all_tsconfigs(root)
@@ -182,29 +577,32 @@ impl Indexer {
             }
         }
 
+        // Compile content transforms ([transforms] section of config.toml); their
+        // identity is folded into file hashes so rule changes force a reindex
+        let transform_config = crate::transforms::load_transform_config(self.cache.path())?;
+        let transformer = Transformer::from_config(&transform_config, root)?;
+
         // Step 1.5: Quick incremental check - are all files unchanged?
         // If yes, skip expensive rebuild entirely and return cached stats
-        if !existing_hashes.is_empty() && total_files == existing_hashes.len() {
+        if !rebuild && !existing_hashes.is_empty() && total_files == existing_hashes.len() + skipped_hashes.len() {
             // Same number of files - check if any changed by comparing hashes
             let mut any_changed = false;
 
             for file_path in &files {
-                // Normalize path to be relative to root (handles both ./ prefix and absolute paths)
+                // Normalize path to be relative to root (handles ./ prefix, absolute paths and
+                // Windows separators)
                 let path_str = file_path.to_string_lossy().to_string();
-                let normalized_path = if let Ok(rel_path) = file_path.strip_prefix(root) {
-                    // Convert absolute path to relative
-                    rel_path.to_string_lossy().to_string()
-                } else {
-                    // Already relative, just strip ./ prefix
-                    path_str.trim_start_matches("./").to_string()
-                };
+                let normalized_path = crate::paths::relative_path(file_path, root);
 
                 // Check if file exists in cache
                 if let Some(existing_hash) = existing_hashes.get(&normalized_path) {
                     // Read and hash file to check if changed
                     match std::fs::read_to_string(file_path) {
                         Ok(content) => {
-                            let current_hash = self.hash_content(content.as_bytes());
+                            let current_hash = crate::transforms::keyed_hash(
+                                &self.hash_content(content.as_bytes()),
+                                transformer.identity_for(&normalized_path),
+                            );
                             if &current_hash != existing_hash {
                                 any_changed = true;
                                 log::debug!("File changed: {}", path_str);
@@ -216,6 +614,14 @@ impl Indexer {
                             break;
                         }
                     }
+                } else if let Some(skipped_hash) = skipped_hashes.get(&normalized_path) {
+                    // Skipped last time: unchanged content is still over the limits
+                    let unchanged = std::fs::read(file_path)
+                        .is_ok_and(|bytes| &self.hash_content(&bytes) == skipped_hash);
+                    if !unchanged {
+                        any_changed = true;
+                        break;
+                    }
                 } else {
                     // File not in cache - something changed
                     any_changed = true;
@@ -228,9 +634,9 @@ impl Indexer {
                 let stats = self.cache.stats()?;
                 return Ok(stats);
             }
-        } else if total_files != existing_hashes.len() {
+        } else if total_files != existing_hashes.len() + skipped_hashes.len() {
             log::info!("File count changed ({} -> {}) - full reindex required",
-                       existing_hashes.len(), total_files);
+                       existing_hashes.len() + skipped_hashes.len(), total_files);
         }
 
         // Step 2: Build trigram index + content store
@@ -239,13 +645,60 @@ impl Indexer {
         let mut file_metadata: Vec<(String, String, String, usize)> = Vec::new(); // For batch SQLite update
         let mut all_dependencies: Vec<(String, Vec<ImportInfo>)> = Vec::new(); // For batch dependency insertion
         let mut all_exports: Vec<(String, Vec<ExportInfo>)> = Vec::new(); // For batch export insertion
+        let mut all_flags: Vec<(String, Vec<FlagUsageInfo>)> = Vec::new(); // For batch flag usage insertion
+        let mut all_config_keys: Vec<(String, Vec<ConfigKeyInfo>)> = Vec::new(); // For batch env/config key insertion
+        let mut all_tables: Vec<(String, Vec<TableRefInfo>)> = Vec::new(); // For batch table reference insertion
+        let mut all_embedded: Vec<(String, Vec<EmbeddedRegion>)> = Vec::new(); // For batch embedded region insertion
+        let mut all_transforms: Vec<(String, Option<String>)> = Vec::new(); // For batch transform recording
+
+        // Compile feature flag patterns once (built-ins + [flags] section of config.toml)
+        let flag_config = crate::flags::load_flag_config(self.cache.path())?;
+        let flag_matcher = FlagMatcher::from_config(&flag_config)?;
+        let config_key_matcher = ConfigKeyMatcher::new();
+
+        // Carve embedded sub-language regions only when [embedded] is enabled
+        let embedded_enabled = crate::embedded::load_embedded_config(self.cache.path())?.enabled;
+
+        // Build a custom thread pool with limited threads
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .context("Failed to create thread pool")?;
 
-        // Initialize trigram index and content store
-        let mut trigram_index = TrigramIndex::new();
+        // Split files into batches that fit the memory budget. Two batches are in
+        // flight at once (one being parsed, one being written), so each gets half.
+        let file_sizes: Vec<u64> = pool.install(|| {
+            files
+                .par_iter()
+                .map(|f| match blobs {
+                    Some(blobs) => blobs.get(f).map_or(0, |b| b.len() as u64),
+                    None => std::fs::metadata(f).map(|m| m.len()).unwrap_or(0),
+                })
+                .collect()
+        });
+        let total_bytes: u64 = file_sizes.iter().sum();
+        let memory_budget = (self.config.memory_budget_mb as u64).max(1) * 1024 * 1024;
+        let batches = plan_batches(&file_sizes, memory_budget / 2, MAX_BATCH_FILES);
+        let num_batches = batches.len();
+        log::info!("Processing {} files ({} bytes) in {} batches (memory budget: {} MB)",
+                   total_files, total_bytes, num_batches, self.config.memory_budget_mb);
+
+        // Initialize trigram index, identifier-token index and content store. An
+        // incremental run appends postings for new content to the existing trigram
+        // index rather than rebuilding it.
+        let existing_trigrams = if rebuild {
+            None
+        } else {
+            TrigramIndex::open_for_update(self.cache.path().join("trigrams.bin"))?
+        };
+        let mut trigram_index = existing_trigrams.unwrap_or_default();
+        let mut token_index = TokenIndex::new();
         let mut content_writer = ContentWriter::new();
 
-        // Enable batch-flush mode for trigram index if we have lots of files
-        if total_files > 10000 {
+        // Enable batch-flush mode for trigram index if we have lots of files, or
+        // if the content won't fit the memory budget in one go
+        let batch_flush = total_files > 10000 || total_bytes > memory_budget;
+        if batch_flush {
             let temp_dir = self.cache.path().join("trigram_temp");
             trigram_index.enable_batch_flush(temp_dir)
                 .context("Failed to enable batch-flush mode for trigram index")?;
@@ -309,251 +762,333 @@ impl Indexer {
             None
         };
 
-        // Build a custom thread pool with limited threads
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(num_threads)
-            .build()
-            .context("Failed to create thread pool")?;
-
-        // Process files in batches to avoid OOM on huge codebases
-        // Batch size: process 5000 files at a time to limit memory usage
-        const BATCH_SIZE: usize = 5000;
-        let num_batches = total_files.div_ceil(BATCH_SIZE);
-        log::info!("Processing {} files in {} batches of up to {} files",
-                   total_files, num_batches, BATCH_SIZE);
+        // Stage 1 (read/hash/parse): runs on the pool for one batch at a time
+        let counter_clone = Arc::clone(&progress_counter);
+        let process_file = |file_path: &PathBuf| -> Option<FileProcessingResult> {
+            // Normalize path to be relative to root (handles ./ prefix, absolute paths and
+            // Windows separators)
+            let path_str = file_path.to_string_lossy().to_string();
+            let normalized_path = crate::paths::relative_path(file_path, root);
+
+            // Read file content once (used for hashing, trigrams, and parsing)
+            let bytes = match read_source(blobs, file_path) {
+                Ok(b) => b,
+                Err(e) => {
+                    log::warn!("Failed to read {}: {}", path_str, e);
+                    // Update progress
+                    counter_clone.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            };
 
-        for (batch_idx, batch_files) in files.chunks(BATCH_SIZE).enumerate() {
-            log::info!("Processing batch {}/{} ({} files)",
-                       batch_idx + 1, num_batches, batch_files.len());
+            // Compute hash from content (no duplicate file read!)
+            let raw_hash = self.hash_content(&bytes);
 
-            // Process files in parallel using rayon with custom thread pool
-            let counter_clone = Arc::clone(&progress_counter);
-            let results: Vec<Option<FileProcessingResult>> = pool.install(|| {
-                batch_files
-                    .par_iter()
-                    .map(|file_path| {
-                // Normalize path to be relative to root (handles both ./ prefix and absolute paths)
-                let path_str = file_path.to_string_lossy().to_string();
-                let normalized_path = if let Ok(rel_path) = file_path.strip_prefix(root) {
-                    // Convert absolute path to relative
-                    rel_path.to_string_lossy().to_string()
-                } else {
-                    // Already relative, just strip ./ prefix
-                    path_str.trim_start_matches("./").to_string()
-                };
+            if let Some(detail) = self.binary_skip(&bytes) {
+                self.skip(file_path, SkipReason::Binary, detail, Some(raw_hash));
+                counter_clone.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            let content = match String::from_utf8(bytes) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("Failed to read {}: {}", path_str, e);
+                    counter_clone.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            };
 
-                // Read file content once (used for hashing, trigrams, and parsing)
-                let content = match std::fs::read_to_string(&file_path) {
-                    Ok(c) => c,
+            // Notebooks are indexed as their rendered Python source (cells → `# %%` blocks)
+            let content = if crate::notebook::is_notebook(file_path) {
+                match crate::notebook::render(&content) {
+                    Ok(rendered) => rendered,
                     Err(e) => {
-                        log::warn!("Failed to read {}: {}", path_str, e);
-                        // Update progress
+                        log::warn!("Skipping {}: {:#}", path_str, e);
                         counter_clone.fetch_add(1, Ordering::Relaxed);
                         return None;
                     }
-                };
+                }
+            } else {
+                content
+            };
 
-                // Compute hash from content (no duplicate file read!)
-                let hash = self.hash_content(content.as_bytes());
-
-                // Detect language
-                let ext = file_path.extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-                let language = Language::from_extension(ext);
-
-                // Count lines in the file
-                let line_count = content.lines().count();
-
-                // Extract dependencies and exports for supported languages
-                let dependencies = match language {
-                    Language::Rust => {
-                        match RustDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+            if let Some(detail) = self.line_length_skip(&content) {
+                self.skip(file_path, SkipReason::LongLines, detail, Some(raw_hash));
+                counter_clone.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+
+            // Apply the matching content transform before any extraction. A failing
+            // transform skips the file so untransformed content never gets indexed.
+            let (content, transform) = match transformer.apply(&normalized_path, &content) {
+                Ok(Some((identity, transformed))) => (transformed, Some(identity)),
+                Ok(None) => (content, None),
+                Err(e) => {
+                    log::warn!("Skipping {}: {:#}", path_str, e);
+                    counter_clone.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            };
+            let hash = crate::transforms::keyed_hash(&raw_hash, transform.as_deref());
+
+            // Detect language (build files like `Makefile` by name)
+            let language = Language::from_path(file_path);
+
+            // Count lines in the file
+            let line_count = content.lines().count();
+
+            // Extract dependencies and exports for supported languages
+            let dependencies = match language {
+                Language::Rust => {
+                    match RustDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Python => {
-                        match PythonDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Python => {
+                    match PythonDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::TypeScript | Language::JavaScript => {
-                        // Find nearest tsconfig for path alias resolution
-                        let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
-                        match TypeScriptDependencyExtractor::extract_dependencies_with_alias_map(&content, alias_map) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::TypeScript | Language::JavaScript => {
+                    // Find nearest tsconfig for path alias resolution
+                    let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
+                    match TypeScriptDependencyExtractor::extract_dependencies_with_alias_map(&content, alias_map) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Go => {
-                        match GoDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Go => {
+                    match GoDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Java => {
-                        match JavaDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Java => {
+                    match JavaDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::C => {
-                        match CDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::C => {
+                    match CDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Cpp => {
-                        match CppDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Cpp => {
+                    match CppDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::CSharp => {
-                        match CSharpDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::CSharp => {
+                    match CSharpDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::PHP => {
-                        match PhpDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::PHP => {
+                    match PhpDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Ruby => {
-                        match RubyDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Ruby => {
+                    match RubyDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Kotlin => {
-                        match KotlinDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Kotlin => {
+                    match KotlinDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Zig => {
-                        match ZigDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Zig => {
+                    match ZigDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Vue => {
-                        // Find nearest tsconfig for path alias resolution
-                        let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
-                        match VueDependencyExtractor::extract_dependencies_with_alias_map(&content, alias_map) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Vue => {
+                    // Find nearest tsconfig for path alias resolution
+                    let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
+                    match VueDependencyExtractor::extract_dependencies_with_alias_map(&content, alias_map) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Svelte => {
-                        match SvelteDependencyExtractor::extract_dependencies(&content) {
-                            Ok(deps) => deps,
-                            Err(e) => {
-                                log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Svelte => {
+                    match SvelteDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    // Other languages not yet implemented
-                    _ => Vec::new(),
-                };
-
-                // Extract exports (for barrel export tracking)
-                let exports = match language {
-                    Language::TypeScript | Language::JavaScript => {
-                        // Find nearest tsconfig for path alias resolution
-                        let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
-                        match TypeScriptDependencyExtractor::extract_export_declarations(&content, alias_map) {
-                            Ok(exports) => exports,
-                            Err(e) => {
-                                log::warn!("Failed to extract exports from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Shell => {
+                    match ShellDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    Language::Vue => {
-                        // Find nearest tsconfig for path alias resolution
-                        let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
-                        match VueDependencyExtractor::extract_export_declarations(&content, alias_map) {
-                            Ok(exports) => exports,
-                            Err(e) => {
-                                log::warn!("Failed to extract exports from {}: {}", path_str, e);
-                                Vec::new()
-                            }
+                }
+                Language::Make => {
+                    match MakeDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
                         }
                     }
-                    // Other languages not yet implemented for export tracking
-                    _ => Vec::new(),
-                };
-
-                // Update progress atomically
-                counter_clone.fetch_add(1, Ordering::Relaxed);
-
-                Some(FileProcessingResult {
-                    path: file_path.clone(),
-                    path_str: normalized_path.to_string(),
-                    hash,
-                    content,
-                    language,
-                    line_count,
-                    dependencies,
-                    exports,
-                })
-                })
-                .collect()
-            });
+                }
+                Language::CMake => {
+                    match CMakeDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
+                        }
+                    }
+                }
+                Language::Bazel => {
+                    match BazelDependencyExtractor::extract_dependencies(&content) {
+                        Ok(deps) => deps,
+                        Err(e) => {
+                            log::warn!("Failed to extract dependencies from {}: {}", path_str, e);
+                            Vec::new()
+                        }
+                    }
+                }
+                // Other languages not yet implemented
+                _ => Vec::new(),
+            };
+
+            // Extract exports (for barrel export tracking)
+            let exports = match language {
+                Language::TypeScript | Language::JavaScript => {
+                    // Find nearest tsconfig for path alias resolution
+                    let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
+                    match TypeScriptDependencyExtractor::extract_export_declarations(&content, alias_map) {
+                        Ok(exports) => exports,
+                        Err(e) => {
+                            log::warn!("Failed to extract exports from {}: {}", path_str, e);
+                            Vec::new()
+                        }
+                    }
+                }
+                Language::Vue => {
+                    // Find nearest tsconfig for path alias resolution
+                    let alias_map = find_nearest_tsconfig(&path_str, root, &tsconfigs);
+                    match VueDependencyExtractor::extract_export_declarations(&content, alias_map) {
+                        Ok(exports) => exports,
+                        Err(e) => {
+                            log::warn!("Failed to extract exports from {}: {}", path_str, e);
+                            Vec::new()
+                        }
+                    }
+                }
+                // Other languages not yet implemented for export tracking
+                _ => Vec::new(),
+            };
+
+            // Extract feature flag checks (language-agnostic, regex based)
+            let flags = if flag_matcher.is_empty() {
+                Vec::new()
+            } else {
+                flag_matcher.extract(&content)
+            };
+
+            // Extract environment variable and config key reads/writes (regex based)
+            let config_keys = config_key_matcher.extract(&content);
+
+            // Extract ORM models and SQL table references
+            let tables = crate::tables::extract(language, &content);
+
+            // Carve SQL/GraphQL/HTML regions out of string literals
+            let embedded = if embedded_enabled {
+                crate::embedded::carve_regions(language, &content)
+            } else {
+                Vec::new()
+            };
+
+            // Update progress atomically
+            counter_clone.fetch_add(1, Ordering::Relaxed);
+
+            Some(FileProcessingResult {
+                path: crate::paths::slash_path(file_path),
+                path_str: normalized_path.to_string(),
+                hash,
+                content,
+                language,
+                line_count,
+                dependencies,
+                exports,
+                flags,
+                config_keys,
+                tables,
+                embedded,
+                transform,
+            })
+        };
 
-            // Process batch results immediately (streaming approach to minimize memory)
-            for result in results.into_iter().flatten() {
+        // Stage 2 (trigram/content write): sequential, consumes one processed batch
+        let mut write_batch = |batch_idx: usize, results: Vec<FileProcessingResult>| -> Result<()> {
+            for result in results {
                 // Add file to trigram index (get file_id)
                 let file_id = trigram_index.add_file(result.path.clone());
 
                 // Index file content directly (avoid accumulating all trigrams)
                 trigram_index.index_file(file_id, &result.content);
 
+                // Split identifiers into word parts (same file_id as trigrams/content)
+                token_index.index_file(file_id, &result.content);
+
                 // Add to content store
                 content_writer.add_file(result.path.clone(), &result.content);
 
@@ -577,11 +1112,22 @@ impl Indexer {
                     all_exports.push((result.path_str.clone(), result.exports));
                 }
 
+                // Collect flag usages for every file (empty lists clear stale usages)
+                all_flags.push((result.path_str.clone(), result.flags));
+                all_config_keys.push((result.path_str.clone(), result.config_keys));
+                all_tables.push((result.path_str.clone(), result.tables));
+
+                // Same for embedded regions, so disabling [embedded] clears them on reindex
+                all_embedded.push((result.path_str.clone(), result.embedded));
+
+                // Record the transform (or its absence) so stale records are cleared
+                all_transforms.push((result.path_str.clone(), result.transform));
+
                 new_hashes.insert(result.path_str, result.hash);
             }
 
             // Flush trigram index batch to disk if batch-flush mode is enabled
-            if total_files > 10000 {
+            if batch_flush {
                 let flush_msg = format!("Flushing batch {}/{}...", batch_idx + 1, num_batches);
                 if show_progress {
                     pb.set_message(flush_msg.clone());
@@ -590,6 +1136,32 @@ impl Indexer {
                 trigram_index.flush_batch()
                     .context("Failed to flush trigram batch")?;
             }
+
+            Ok(())
+        };
+
+        // Pipeline: while batch N is written, batch N+1 is read and parsed, so at
+        // most two batches (each up to half the memory budget) are held at once
+        let mut pending: Option<(usize, Vec<FileProcessingResult>)> = None;
+        for (batch_idx, range) in batches.iter().enumerate() {
+            let batch_files = &files[range.clone()];
+            log::info!("Processing batch {}/{} ({} files)",
+                       batch_idx + 1, num_batches, batch_files.len());
+
+            let (results, written) = pool.install(|| {
+                rayon::join(
+                    || batch_files.par_iter().filter_map(&process_file).collect::<Vec<_>>(),
+                    || match pending.take() {
+                        Some((idx, previous)) => write_batch(idx, previous),
+                        None => Ok(()),
+                    },
+                )
+            });
+            written?;
+            pending = Some((batch_idx, results));
+        }
+        if let Some((idx, last)) = pending.take() {
+            write_batch(idx, last)?;
         }
 
         // Wait for progress thread to finish
@@ -609,6 +1181,7 @@ impl Indexer {
             pb.set_message("Finalizing trigram index...".to_string());
         }
         trigram_index.finalize();
+        token_index.finalize();
 
         // Update progress bar message for post-processing
         *progress_status.lock().unwrap() = "Writing file metadata to database...".to_string();
@@ -723,6 +1296,21 @@ impl Indexer {
 
             // Note: Kotlin projects use the same java_projects above (same build systems: Maven/Gradle)
 
+            // Map CMake target names to their defining files (target_link_libraries across directories)
+            let has_cmake = all_dependencies.iter().any(|(path, _)| Language::from_path(Path::new(path)) == Language::CMake);
+            let cmake_targets = if has_cmake {
+                crate::parsers::cmake::parse_all_cmake_targets(root)
+                    .unwrap_or_else(|e| {
+                        log::warn!("Failed to collect CMake targets: {}", e);
+                        HashMap::new()
+                    })
+            } else {
+                HashMap::new()
+            };
+            if !cmake_targets.is_empty() {
+                log::info!("Found {} CMake targets", cmake_targets.len());
+            }
+
             // Find and parse all composer.json files for PHP projects (monorepo support)
             let php_psr4_mappings = crate::parsers::php::parse_all_composer_psr4(root)
                 .unwrap_or_else(|e| {
@@ -771,6 +1359,8 @@ impl Indexer {
 
                 // Reclassify and filter dependencies
                 let mut resolved_deps = Vec::new();
+                let line_based_language = Some(Language::from_path(Path::new(&file_path)))
+                    .filter(|lang| matches!(lang, Language::Shell | Language::Make | Language::CMake | Language::Bazel));
 
                 for mut import_info in import_infos {
                     // Reclassify Go imports using module names (if Go project)
@@ -894,6 +1484,14 @@ impl Indexer {
                         }
                     }
 
+                    // Reclassify CMake link items: targets defined nowhere in the project are system libraries
+                    if line_based_language == Some(Language::CMake) {
+                        import_info.import_type = crate::parsers::cmake::reclassify_cmake_dependency(
+                            &import_info.imported_path,
+                            &cmake_targets,
+                        );
+                    }
+
                     // ONLY insert Internal dependencies - skip External and Stdlib
                     if !matches!(import_info.import_type, ImportType::Internal) {
                         continue;
@@ -924,50 +1522,62 @@ impl Indexer {
                                        import_info.imported_path);
                             None
                         }
-                    } else if file_path.ends_with(".py") && !python_packages.is_empty() {
-                        // Resolve Python dependencies using package mappings
-                        if let Some(resolved_path) = crate::parsers::python::resolve_python_import_to_path(
+                    } else if file_path.ends_with(".py") {
+                        // Resolve Python dependencies using package mappings (absolute imports)
+                        // or the importing file's location (relative imports)
+                        if let Some(candidates_str) = crate::parsers::python::resolve_python_import_to_path(
                             &import_info.imported_path,
                             &python_packages,
                             Some(&file_path),
                         ) {
-                            // Look up file ID in database using exact match
-                            match dep_index.get_file_id_by_path(&resolved_path)? {
-                                Some(id) => {
-                                    log::trace!("Resolved Python dependency: {} -> {} (file_id={})",
-                                               import_info.imported_path, resolved_path, id);
-                                    Some(id)
-                                }
-                                None => {
-                                    log::trace!("Python dependency resolved to path but file not in index: {} -> {}",
-                                               import_info.imported_path, resolved_path);
-                                    None
-                                }
+                            // Try each candidate (module.py, module/__init__.py, ...) in order
+                            let resolved_id = lookup_candidate_paths(&dep_index, &candidates_str);
+                            match resolved_id {
+                                Some(id) => log::trace!("Resolved Python dependency: {} (file_id={})",
+                                                       import_info.imported_path, id),
+                                None => log::trace!("Python dependency: no matching file found in database for any candidate: {}",
+                                                   candidates_str),
                             }
+                            resolved_id
                         } else {
                             log::trace!("Could not resolve Python import: {}", import_info.imported_path);
                             None
                         }
                     } else if file_path.ends_with(".go") && !go_modules.is_empty() {
-                        // Resolve Go dependencies using module mappings
-                        if let Some(resolved_path) = crate::parsers::go::resolve_go_import_to_path(
+                        // Resolve Go dependencies to every file of the imported package
+                        if let Some(package_dir) = crate::parsers::go::resolve_go_import_to_path(
                             &import_info.imported_path,
                             &go_modules,
                             Some(&file_path),
                         ) {
-                            // Look up file ID in database using exact match
-                            match dep_index.get_file_id_by_path(&resolved_path)? {
-                                Some(id) => {
-                                    log::trace!("Resolved Go dependency: {} -> {} (file_id={})",
-                                               import_info.imported_path, resolved_path, id);
-                                    Some(id)
-                                }
-                                None => {
-                                    log::trace!("Go dependency resolved to path but file not in index: {} -> {}",
-                                               import_info.imported_path, resolved_path);
-                                    None
-                                }
+                            let package_files: Vec<i64> = dep_index.get_files_in_directory(&package_dir)?
+                                .into_iter()
+                                .filter(|(_, path)| path.ends_with(".go") && !path.ends_with("_test.go"))
+                                .map(|(id, _)| id)
+                                .collect();
+
+                            // A package spans several files: record one edge per file so
+                            // hotspot and unused-file analysis see all of them. The first
+                            // file takes the regular slot below.
+                            for &id in package_files.iter().skip(1) {
+                                resolved_deps.push(Dependency {
+                                    file_id,
+                                    imported_path: import_info.imported_path.clone(),
+                                    resolved_file_id: Some(id),
+                                    import_type: import_info.import_type.clone(),
+                                    line_number: import_info.line_number,
+                                    imported_symbols: import_info.imported_symbols.clone(),
+                                });
                             }
+
+                            if package_files.is_empty() {
+                                log::trace!("Go dependency resolved to package but no files in index: {} -> {}",
+                                           import_info.imported_path, package_dir);
+                            } else {
+                                log::trace!("Resolved Go dependency: {} -> {} ({} files)",
+                                           import_info.imported_path, package_dir, package_files.len());
+                            }
+                            package_files.first().copied()
                         } else {
                             log::trace!("Could not resolve Go import: {}", import_info.imported_path);
                             None
@@ -991,12 +1601,7 @@ impl Indexer {
                             for candidate_path in candidates {
                                 // Normalize path to be relative to project root
                                 // Convert absolute paths to relative (without requiring file to exist)
-                                let normalized_candidate = if let Ok(rel_path) = std::path::Path::new(candidate_path).strip_prefix(root) {
-                                    rel_path.to_string_lossy().to_string()
-                                } else {
-                                    // Not an absolute path or not under root - use as-is
-                                    candidate_path.to_string()
-                                };
+                                let normalized_candidate = crate::paths::relative_path(std::path::Path::new(candidate_path), root);
 
                                 log::debug!("Looking up TS/JS candidate: '{}' (from '{}')", normalized_candidate, candidate_path);
                                 match dep_index.get_file_id_by_path(&normalized_candidate)? {
@@ -1212,6 +1817,42 @@ impl Indexer {
                             log::trace!("Could not resolve Zig import (external or stdlib): {}", import_info.imported_path);
                             None
                         }
+                    } else if let Some(line_based_language) = line_based_language {
+                        // Resolve shell and build-file dependencies (sourced files, prerequisites,
+                        // subdirectories, targets, labels)
+                        let candidates = match line_based_language {
+                            Language::Shell => crate::parsers::shell::resolve_shell_source_to_path(
+                                &import_info.imported_path,
+                                &file_path,
+                            ),
+                            Language::Make => crate::parsers::make::resolve_make_prerequisite_to_path(
+                                &import_info.imported_path,
+                                &file_path,
+                            ),
+                            Language::CMake => crate::parsers::cmake::resolve_cmake_dependency_to_path(
+                                &import_info.imported_path,
+                                &file_path,
+                                &cmake_targets,
+                            ),
+                            _ => crate::parsers::bazel::resolve_bazel_label_to_path(
+                                &import_info.imported_path,
+                                &file_path,
+                            ),
+                        };
+
+                        // Candidates are pipe-delimited (e.g., "pkg/BUILD.bazel|pkg/BUILD"); most
+                        // prerequisites are generated files or other targets, so misses are expected
+                        let resolved_id = candidates
+                            .iter()
+                            .flat_map(|candidates| candidates.split('|'))
+                            .find_map(|candidate| dep_index.get_file_id_by_path(candidate).ok().flatten());
+                        match resolved_id {
+                            Some(id) => log::trace!("Resolved {:?} dependency: {} (file_id={})",
+                                                   line_based_language, import_info.imported_path, id),
+                            None => log::trace!("Could not resolve {:?} dependency: {}",
+                                               line_based_language, import_info.imported_path),
+                        }
+                        resolved_id
                     } else if file_path.ends_with(".vue") || file_path.ends_with(".svelte") {
                         // Resolve Vue/Svelte dependencies (use TypeScript/JavaScript resolver for imports in <script> blocks)
                         let alias_map = find_nearest_tsconfig(&file_path, root, &tsconfigs);
@@ -1228,12 +1869,7 @@ impl Indexer {
                             for candidate_path in candidates {
                                 // Normalize path to be relative to project root
                                 // Convert absolute paths to relative (without requiring file to exist)
-                                let normalized_candidate = if let Ok(rel_path) = std::path::Path::new(candidate_path).strip_prefix(root) {
-                                    rel_path.to_string_lossy().to_string()
-                                } else {
-                                    // Not an absolute path or not under root - use as-is
-                                    candidate_path.to_string()
-                                };
+                                let normalized_candidate = crate::paths::relative_path(std::path::Path::new(candidate_path), root);
 
                                 match dep_index.get_file_id_by_path(&normalized_candidate)? {
                                     Some(id) => {
@@ -1340,11 +1976,7 @@ impl Indexer {
                             let mut resolved_id = None;
                             for candidate_path in candidates {
                                 // Normalize path to be relative to project root
-                                let normalized_candidate = if let Ok(rel_path) = std::path::Path::new(candidate_path).strip_prefix(root) {
-                                    rel_path.to_string_lossy().to_string()
-                                } else {
-                                    candidate_path.to_string()
-                                };
+                                let normalized_candidate = crate::paths::relative_path(std::path::Path::new(candidate_path), root);
 
                                 match dep_index.get_file_id_by_path(&normalized_candidate)? {
                                     Some(id) => {
@@ -1389,6 +2021,83 @@ impl Indexer {
             log::info!("Extracted {} exports", total_exports_inserted);
         }
 
+        // Step 2.7: Store feature flag usages (after files are inserted and have IDs)
+        if !all_flags.is_empty() {
+            *progress_status.lock().unwrap() = "Indexing feature flags...".to_string();
+            if show_progress {
+                pb.set_message("Indexing feature flags...".to_string());
+            }
+
+            let paths: Vec<String> = all_flags.iter().map(|(path, _)| path.clone()).collect();
+            let file_ids = self.cache.batch_get_file_ids(&paths)?;
+
+            let flags_by_file: Vec<(i64, Vec<FlagUsageInfo>)> = all_flags
+                .into_iter()
+                .filter_map(|(path, flags)| file_ids.get(&path).map(|id| (*id, flags)))
+                .collect();
+
+            let flag_index = FlagIndex::new(self.cache.clone());
+            let total_flags_inserted = flag_index.replace_file_flags(&flags_by_file)?;
+
+            log::info!("Extracted {} feature flag usages", total_flags_inserted);
+        }
+
+        // Step 2.72: Store environment variable / config key usages
+        if !all_config_keys.is_empty() {
+            let paths: Vec<String> = all_config_keys.iter().map(|(path, _)| path.clone()).collect();
+            let file_ids = self.cache.batch_get_file_ids(&paths)?;
+
+            let keys_by_file: Vec<(i64, Vec<ConfigKeyInfo>)> = all_config_keys
+                .into_iter()
+                .filter_map(|(path, keys)| file_ids.get(&path).map(|id| (*id, keys)))
+                .collect();
+
+            let key_index = ConfigKeyIndex::new(self.cache.clone());
+            let total_keys_inserted = key_index.replace_file_keys(&keys_by_file)?;
+
+            log::info!("Extracted {} env/config key usages", total_keys_inserted);
+        }
+
+        // Step 2.73: Store table references (keyed by path, no file IDs needed)
+        if !all_tables.is_empty() {
+            let total_refs_inserted = TableIndex::new(self.cache.clone()).replace_refs(&all_tables, None)?;
+            log::info!("Extracted {} table references", total_refs_inserted);
+        }
+
+        // Step 2.75: Store embedded sub-language regions
+        if !all_embedded.is_empty() {
+            let paths: Vec<String> = all_embedded.iter().map(|(path, _)| path.clone()).collect();
+            let file_ids = self.cache.batch_get_file_ids(&paths)?;
+
+            let regions_by_file: Vec<(i64, Vec<EmbeddedRegion>)> = all_embedded
+                .into_iter()
+                .filter_map(|(path, regions)| file_ids.get(&path).map(|id| (*id, regions)))
+                .collect();
+
+            let embedded_index = EmbeddedIndex::new(self.cache.clone());
+            let total_regions = embedded_index.replace_file_regions(&regions_by_file)?;
+
+            log::info!("Carved {} embedded regions", total_regions);
+        }
+
+        // Step 2.8: Record which transform produced each file's indexed content
+        if !all_transforms.is_empty() {
+            let paths: Vec<String> = all_transforms.iter().map(|(path, _)| path.clone()).collect();
+            let file_ids = self.cache.batch_get_file_ids(&paths)?;
+
+            let transforms_by_file: Vec<(i64, Option<String>)> = all_transforms
+                .into_iter()
+                .filter_map(|(path, transform)| file_ids.get(&path).map(|id| (*id, transform)))
+                .collect();
+
+            let transform_index = TransformIndex::new(self.cache.clone());
+            let transformed = transform_index.replace_file_transforms(&transforms_by_file)?;
+
+            if transformed > 0 {
+                log::info!("Indexed {} files through content transforms", transformed);
+            }
+        }
+
         log::info!("Indexed {} files", files_indexed);
 
         // Step 3: Write trigram index
@@ -1404,6 +2113,14 @@ impl Indexer {
             .context("Failed to write trigram index")?;
         log::info!("Wrote {} files to trigrams.bin", trigram_index.file_count());
 
+        // Step 3.5: Write identifier-token index
+        *progress_status.lock().unwrap() = "Writing token index...".to_string();
+        if show_progress {
+            pb.set_message("Writing token index...".to_string());
+        }
+        token_index.write(self.cache.path().join(TOKENS_BIN))
+            .context("Failed to write token index")?;
+
         // Step 4: Finalize content store (already been writing incrementally)
         *progress_status.lock().unwrap() = "Finalizing content store...".to_string();
         if show_progress {
@@ -1425,6 +2142,47 @@ impl Indexer {
         // Update schema hash to mark cache as compatible with current binary
         self.cache.update_schema_hash()?;
 
+        // Record skipped files for `rfx stats` and the next incremental check
+        if record_skips {
+            let mut skipped: Vec<(SkippedFile, Option<String>)> = std::mem::take(&mut *self.skipped.lock().unwrap())
+                .into_iter()
+                .map(|s| {
+                    let file = SkippedFile {
+                        path: crate::paths::relative_path(&s.path, root),
+                        reason: s.reason,
+                        detail: s.detail,
+                    };
+                    (file, s.hash)
+                })
+                .collect();
+            skipped.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+            if !skipped.is_empty() {
+                log::info!("Skipped {} files over the index limits", skipped.len());
+            }
+            self.cache.record_skipped_files(&limits, &skipped)?;
+
+            // Record where symlinked files really live (`rfx list-files`)
+            let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+            let mut linked: Vec<(String, String)> = std::mem::take(&mut *self.linked.lock().unwrap())
+                .into_iter()
+                .map(|(path, canonical)| {
+                    (crate::paths::relative_path(&path, root), crate::paths::relative_path(&canonical, &canonical_root))
+                })
+                .collect();
+            linked.sort();
+            self.cache.record_linked_files(&linked)?;
+        }
+
+        // Record Prisma models for `--table` (schema files aren't indexed)
+        let prisma = crate::tables::discover_prisma(root);
+        TableIndex::new(self.cache.clone()).replace_refs(&prisma, Some("prisma"))?;
+
+        // Record CODEOWNERS rules for `--owner` / `--with-owners`
+        match crate::owners::CodeOwners::discover(root) {
+            Ok(owners) => crate::owners::record(&self.cache, owners.as_ref())?,
+            Err(e) => log::warn!("Failed to read CODEOWNERS: {}", e),
+        }
+
         pb.finish_with_message("Indexing complete");
 
         // Return stats
@@ -1435,23 +2193,197 @@ impl Indexer {
         Ok(stats)
     }
 
+    /// Resolve workspace roots: explicit --root flags win, otherwise reuse the
+    /// roots recorded by a previous multi-root index (empty = whole workspace)
+    fn resolve_roots(&self) -> Result<Vec<WorkspaceRoot>> {
+        if !self.config.roots.is_empty() {
+            return Ok(self.config.roots.clone());
+        }
+        Ok(self.cache.list_roots()?
+            .iter()
+            .map(|r| r.to_workspace_root())
+            .collect())
+    }
+
+    /// Discover the files a full index of `root` would cover
+    pub(crate) fn discover_workspace_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let roots = self.resolve_roots()?;
+        self.discover_files_in_roots(root, &roots)
+    }
+
+    /// Discover indexable files in each workspace root
+    ///
+    /// Falls back to walking the whole workspace when no roots are configured.
+    /// Returned paths keep the workspace root as prefix so stored paths stay
+    /// relative to the workspace, not to the individual root.
+    fn discover_files_in_roots(&self, root: &Path, roots: &[WorkspaceRoot]) -> Result<Vec<PathBuf>> {
+        if roots.is_empty() {
+            return self.discover_files(root);
+        }
+
+        let ignore_patterns = self.build_ignore_patterns(root)?;
+
+        let mut seen_names = std::collections::HashSet::new();
+        let mut files = Vec::new();
+
+        for workspace_root in roots {
+            if !seen_names.insert(workspace_root.name.as_str()) {
+                anyhow::bail!(
+                    "Duplicate root name '{}'.\n\
+                     \n\
+                     Give each root a unique name with --root <name>=<path>",
+                    workspace_root.name
+                );
+            }
+
+            let root_dir = root.join(&workspace_root.path);
+            if !root_dir.is_dir() {
+                anyhow::bail!(
+                    "Root '{}' does not exist or is not a directory: {}",
+                    workspace_root.name,
+                    root_dir.display()
+                );
+            }
+
+            log::info!("Discovering files in root '{}' ({})", workspace_root.name, workspace_root.path);
+            files.extend(self.walk_files(&root_dir, &ignore_patterns)?);
+        }
+
+        // Overlapping roots (e.g., "packages" and "packages/api") would otherwise
+        // index the same file twice
+        files.sort();
+        files.dedup();
+
+        Ok(self.dedupe_links(root, files))
+    }
+
     /// Discover all indexable files in the directory tree
     fn discover_files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let ignore_patterns = self.build_ignore_patterns(root)?;
+        let files = self.walk_files(root, &ignore_patterns)?;
+        Ok(self.dedupe_links(root, files))
+    }
+
+    /// Drop files that are another discovered file under a different path
+    ///
+    /// With `follow_symlinks`, a file reached both directly and through a
+    /// symlinked directory (or through two links) is kept once, preferring the
+    /// path where it really lives; the others' canonical paths are recorded.
+    /// With `dedupe_hardlinks`, hard links to the same inode are kept once
+    /// (the smallest path wins). Discovery order is otherwise preserved.
+    fn dedupe_links(&self, root: &Path, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        if !self.config.follow_symlinks && !self.config.dedupe_hardlinks {
+            return files;
+        }
+
+        let discovered = files.len();
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        // (path, canonical path, reached directly rather than through a symlink)
+        let mut kept: Vec<(PathBuf, Option<PathBuf>, bool)> = Vec::with_capacity(files.len());
+        let mut slots: HashMap<LinkKey, usize> = HashMap::new();
+
+        for path in files {
+            let canonical = if self.config.follow_symlinks { path.canonicalize().ok() } else { None };
+            let direct = canonical.as_ref().is_none_or(|c| {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                *c == canonical_root.join(relative)
+            });
+
+            let key = if self.config.dedupe_hardlinks { inode_key(&path) } else { None }
+                .or_else(|| canonical.clone().map(LinkKey::Canonical));
+            let Some(key) = key else {
+                kept.push((path, canonical, direct));
+                continue;
+            };
+
+            match slots.get(&key) {
+                Some(&slot) => {
+                    let (kept_path, _, kept_direct) = &kept[slot];
+                    let better = (direct && !kept_direct) || (direct == *kept_direct && path < *kept_path);
+                    log::debug!(
+                        "{} is the same file as {}",
+                        if better { kept_path.display() } else { path.display() },
+                        if better { path.display() } else { kept_path.display() },
+                    );
+                    if better {
+                        kept[slot] = (path, canonical, direct);
+                    }
+                }
+                None => {
+                    slots.insert(key, kept.len());
+                    kept.push((path, canonical, direct));
+                }
+            }
+        }
+
+        if kept.len() < discovered {
+            log::info!("Skipped {} files reached through more than one link", discovered - kept.len());
+        }
+
+        let mut linked = self.linked.lock().unwrap();
+        kept.into_iter()
+            .map(|(path, canonical, direct)| {
+                if let (Some(canonical), false) = (canonical, direct) {
+                    linked.push((path.clone(), canonical));
+                }
+                path
+            })
+            .collect()
+    }
+
+    /// Compile `IndexConfig::ignore_patterns` (gitignore syntax) relative to the workspace root
+    fn build_ignore_patterns(&self, root: &Path) -> Result<Gitignore> {
+        let mut builder = GitignoreBuilder::new(root);
+        // Reject `src/[` instead of matching the `[` literally, so typos surface
+        builder.allow_unclosed_class(false);
+        for pattern in &self.config.ignore_patterns {
+            builder.add_line(None, pattern).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid ignore pattern '{}': {}\n\
+                     \n\
+                     Patterns use .gitignore syntax, e.g. --exclude 'vendor/' --exclude '*.generated.ts'",
+                    pattern,
+                    e
+                )
+            })?;
+        }
+        builder.build().context("Failed to build ignore patterns")
+    }
+
+    /// Walk `dir`, collecting indexable files that aren't ignored
+    fn walk_files(&self, dir: &Path, ignore_patterns: &Gitignore) -> Result<Vec<PathBuf>> {
         let mut files = Vec::new();
 
         // WalkBuilder from ignore crate automatically respects:
         // - .gitignore (when in a git repo)
         // - .ignore files
+        // - .reflexignore files (same syntax, works outside git repos too)
         // - Hidden files (can be configured)
-        let walker = WalkBuilder::new(root)
+        // Ignored directories are pruned, so their contents are never walked.
+        let ignore_patterns = ignore_patterns.clone();
+        let walker = WalkBuilder::new(dir)
             .follow_links(self.config.follow_symlinks)
             .git_ignore(true)  // Explicitly enable gitignore support (enabled by default, but be explicit)
             .git_global(false) // Don't use global gitignore
             .git_exclude(false) // Don't use .git/info/exclude
+            .add_custom_ignore_filename(REFLEXIGNORE)
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                // Check parents too: a named root may start inside an ignored directory
+                !ignore_patterns.matched_path_or_any_parents(entry.path(), is_dir).is_ignore()
+            })
             .build();
 
         for entry in walker {
-            let entry = entry?;
+            let entry = match entry {
+                Ok(entry) => entry,
+                // A symlinked directory pointing at one of its ancestors
+                Err(e) if is_symlink_loop(&e) => {
+                    log::warn!("Not following symlink loop: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
             let path = entry.path();
 
             // Only process files (not directories)
@@ -1470,13 +2402,8 @@ impl Indexer {
 
     /// Check if a file should be indexed based on config
     fn should_index(&self, path: &Path) -> bool {
-        // Check file extension for supported languages
-        let ext = match path.extension() {
-            Some(ext) => ext.to_string_lossy(),
-            None => return false,
-        };
-
-        let lang = Language::from_extension(&ext);
+        // Check file name/extension for supported languages
+        let lang = Language::from_path(path);
 
         // Only index files for languages with parser implementations
         if !lang.is_supported() {
@@ -1492,6 +2419,8 @@ impl Indexer {
             if metadata.len() > self.config.max_file_size as u64 {
                 log::debug!("Skipping {} (too large: {} bytes)",
                            path.display(), metadata.len());
+                let detail = format!("{} bytes (limit {})", metadata.len(), self.config.max_file_size);
+                self.skip(path, SkipReason::TooLarge, detail, None);
                 return false;
             }
         }
@@ -1502,8 +2431,38 @@ impl Indexer {
         true
     }
 
+    /// Why `bytes` look binary, if they do and binary detection is on
+    fn binary_skip(&self, bytes: &[u8]) -> Option<String> {
+        let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+        (self.config.binary_detection && sniff.contains(&0))
+            .then(|| format!("NUL byte in the first {} bytes", sniff.len()))
+    }
+
+    /// The longest line of `content`, if it's over `max_line_length`
+    fn line_length_skip(&self, content: &str) -> Option<String> {
+        let limit = self.config.max_line_length;
+        if limit == 0 || content.len() <= limit {
+            return None;
+        }
+        let longest = content.lines().map(str::len).max().unwrap_or(0);
+        (longest > limit).then(|| format!("longest line is {} bytes (limit {})", longest, limit))
+    }
+
+    fn skip(&self, path: &Path, reason: SkipReason, detail: String, hash: Option<String>) {
+        log::debug!("Skipping {} ({})", path.display(), detail);
+        self.skipped.lock().unwrap().push(Skipped { path: path.to_path_buf(), reason, detail, hash });
+    }
+
+    /// Limits that decide which files are skipped; a change forces a full re-index
+    fn limits_identity(&self) -> String {
+        format!(
+            "max_file_size={} max_line_length={} binary_detection={}",
+            self.config.max_file_size, self.config.max_line_length, self.config.binary_detection
+        )
+    }
+
     /// Compute blake3 hash from file contents for change detection
-    fn hash_content(&self, content: &[u8]) -> String {
+    pub(crate) fn hash_content(&self, content: &[u8]) -> String {
         let hash = blake3::hash(content);
         hash.to_hex().to_string()
     }
@@ -1668,10 +2627,17 @@ mod tests {
         let config = IndexConfig::default();
         let indexer = Indexer::new(cache, config);
 
-        let no_ext_file = temp.path().join("Makefile");
-        fs::write(&no_ext_file, "all:\n\techo hello").unwrap();
+        let no_ext_file = temp.path().join("LICENSE");
+        fs::write(&no_ext_file, "MIT License").unwrap();
 
         assert!(!indexer.should_index(&no_ext_file));
+
+        // Build files are recognized by name
+        for name in ["Makefile", "CMakeLists.txt", "BUILD"] {
+            let build_file = temp.path().join(name);
+            fs::write(&build_file, "all").unwrap();
+            assert!(indexer.should_index(&build_file), "{} should be indexed", name);
+        }
     }
 
     #[test]
@@ -1807,6 +2773,77 @@ mod tests {
         assert_eq!(files.len(), 2, "Should find exactly 2 files (not including .gitignore or ignored/excluded.rs)");
     }
 
+    #[test]
+    fn test_discover_files_respects_reflexignore() {
+        let temp = TempDir::new().unwrap();
+
+        // No git repo: .reflexignore works on its own
+        fs::write(temp.path().join(REFLEXIGNORE), "generated/\n*.pb.go\n").unwrap();
+        fs::write(temp.path().join("main.go"), "package main").unwrap();
+        fs::write(temp.path().join("api.pb.go"), "package main").unwrap();
+
+        let generated_dir = temp.path().join("generated");
+        fs::create_dir(&generated_dir).unwrap();
+        fs::write(generated_dir.join("schema.rs"), "pub struct Schema;").unwrap();
+
+        // Nested .reflexignore files apply to their own directory
+        let fixtures_dir = temp.path().join("tests");
+        fs::create_dir(&fixtures_dir).unwrap();
+        fs::write(fixtures_dir.join(REFLEXIGNORE), "fixture_*.rs\n").unwrap();
+        fs::write(fixtures_dir.join("fixture_big.rs"), "fn big() {}").unwrap();
+        fs::write(fixtures_dir.join("real_test.rs"), "fn real() {}").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(temp.path()), IndexConfig::default());
+        let mut files: Vec<String> = indexer
+            .discover_files(temp.path())
+            .unwrap()
+            .iter()
+            .map(|f| f.strip_prefix(temp.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        files.sort();
+
+        assert_eq!(files, vec!["main.go", "tests/real_test.rs"]);
+    }
+
+    #[test]
+    fn test_discover_files_respects_ignore_patterns() {
+        let temp = TempDir::new().unwrap();
+
+        fs::write(temp.path().join("lib.rs"), "pub fn lib() {}").unwrap();
+        fs::write(temp.path().join("bindings.generated.ts"), "export {}").unwrap();
+        let vendor_dir = temp.path().join("vendor").join("dep");
+        fs::create_dir_all(&vendor_dir).unwrap();
+        fs::write(vendor_dir.join("dep.rs"), "pub fn dep() {}").unwrap();
+
+        let config = IndexConfig {
+            ignore_patterns: vec!["/vendor/".to_string(), "*.generated.ts".to_string()],
+            ..Default::default()
+        };
+        let indexer = Indexer::new(CacheManager::new(temp.path()), config);
+        let files = indexer.discover_files(temp.path()).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("lib.rs"));
+
+        // Patterns are relative to the workspace, also when indexing named roots
+        let roots = vec![WorkspaceRoot::parse("vendor").unwrap()];
+        let files = indexer.discover_files_in_roots(temp.path(), &roots).unwrap();
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_ignore_pattern_is_an_error() {
+        let temp = TempDir::new().unwrap();
+        let config = IndexConfig {
+            ignore_patterns: vec!["src/[".to_string()],
+            ..Default::default()
+        };
+        let indexer = Indexer::new(CacheManager::new(temp.path()), config);
+
+        let err = indexer.discover_files(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("Invalid ignore pattern 'src/['"));
+    }
+
     #[test]
     fn test_index_empty_directory() {
         let temp = TempDir::new().unwrap();
@@ -2010,6 +3047,234 @@ mod tests {
         assert_eq!(stats.total_files, 1);
     }
 
+    #[test]
+    fn test_plan_batches_respects_budget() {
+        // Budget of 100 bytes: 40+50 fit, 30 starts a new batch, 200 is alone
+        let batches = plan_batches(&[40, 50, 30, 200, 10, 10], 100, 10);
+        assert_eq!(batches, vec![0..2, 2..3, 3..4, 4..6]);
+
+        // File count cap applies even when bytes fit
+        let batches = plan_batches(&[1; 5], 100, 2);
+        assert_eq!(batches, vec![0..2, 2..4, 4..5]);
+
+        assert!(plan_batches(&[], 100, 2).is_empty());
+    }
+
+    #[test]
+    fn test_index_with_small_memory_budget() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        // 1 MB budget = 512 KB per batch, so each ~600 KB file is its own batch
+        let mut config = IndexConfig::default();
+        config.memory_budget_mb = 1;
+        let indexer = Indexer::new(CacheManager::new(&project_root), config);
+
+        for name in ["a", "b", "c"] {
+            let content = format!("fn {}() {{}}\n", name).repeat(60_000);
+            fs::write(project_root.join(format!("{}.rs", name)), content).unwrap();
+        }
+
+        let stats = indexer.index(&project_root, false).unwrap();
+        assert_eq!(stats.total_files, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_index_applies_content_transforms() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        cache.init().unwrap();
+        fs::write(
+            cache.path().join(CONFIG_TOML),
+            "[[transforms.rules]]\nname = \"upper\"\ncommand = \"tr a-z A-Z\"\nglobs = [\"*.py\"]\n",
+        ).unwrap();
+
+        fs::write(project_root.join("notes.py"), "value = 1").unwrap();
+        fs::write(project_root.join("main.rs"), "fn main() {}").unwrap();
+
+        let indexer = Indexer::new(cache.clone(), IndexConfig::default());
+        indexer.index(&project_root, false).unwrap();
+
+        // Files are stored under the path they were indexed by (absolute for a temp-dir root)
+        let reader = crate::content_store::ContentReader::open(cache.path().join("content.bin")).unwrap();
+        let indexed_path = |name: &str| project_root.join(name).to_string_lossy().into_owned();
+        let notes = reader.file_id_for_path(&indexed_path("notes.py")).unwrap();
+        assert_eq!(reader.get_file_content(notes).unwrap(), "VALUE = 1");
+        let main = reader.file_id_for_path(&indexed_path("main.rs")).unwrap();
+        assert_eq!(reader.get_file_content(main).unwrap(), "fn main() {}");
+
+        let transforms = TransformIndex::new(cache.clone()).all().unwrap();
+        assert_eq!(transforms.len(), 1);
+        assert!(transforms["notes.py"].starts_with("upper@"));
+    }
+
+    #[test]
+    fn test_load_performance_config() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_performance_config(temp.path()).unwrap().memory_budget_mb.is_none());
+
+        fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[performance]\nparallel_threads = 2\nmemory_budget_mb = 64\ncompression_level = 3\n",
+        ).unwrap();
+        let mut config = IndexConfig::default();
+        load_performance_config(temp.path()).unwrap().apply(&mut config);
+        assert_eq!(config.parallel_threads, 2);
+        assert_eq!(config.memory_budget_mb, 64);
+    }
+
+    #[test]
+    fn test_load_index_limits_config() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[index]\nlanguages = []\nmax_line_length = 0\nbinary_detection = false\nfollow_symlinks = true\n\n[index.extensions]\n",
+        ).unwrap();
+        let mut config = IndexConfig::default();
+        load_index_limits_config(temp.path()).unwrap().apply(&mut config);
+        assert_eq!(config.max_file_size, IndexConfig::default().max_file_size);
+        assert_eq!(config.max_line_length, 0);
+        assert!(!config.binary_detection);
+        assert!(config.follow_symlinks);
+        assert!(!config.dedupe_hardlinks);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_indexes_each_file_once() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("packages/shared")).unwrap();
+        fs::create_dir_all(temp.path().join("apps/web")).unwrap();
+        fs::write(temp.path().join("packages/shared/lib.rs"), "pub fn shared() {}\n").unwrap();
+        fs::write(outside.path().join("ext.rs"), "pub fn ext() {}\n").unwrap();
+        symlink(temp.path().join("packages/shared"), temp.path().join("apps/web/shared")).unwrap();
+        symlink(outside.path(), temp.path().join("vendor")).unwrap();
+        // Loop back to an ancestor
+        symlink(temp.path().join("packages"), temp.path().join("packages/shared/up")).unwrap();
+
+        let cache = CacheManager::new(temp.path());
+        let config = IndexConfig { follow_symlinks: true, ..IndexConfig::default() };
+        let stats = Indexer::new(cache.clone(), config).index(temp.path(), false).unwrap();
+        assert_eq!(stats.total_files, 2);
+
+        let files = cache.list_files().unwrap();
+        let listed: Vec<_> = files.iter().map(|f| (f.path.as_str(), f.canonical_path.clone())).collect();
+        let ext = outside.path().canonicalize().unwrap().join("ext.rs").to_string_lossy().to_string();
+        assert_eq!(listed, vec![("packages/shared/lib.rs", None), ("vendor/ext.rs", Some(ext))]);
+
+        // Without following, the symlinked directories are left out
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        assert_eq!(indexer.discover_files(temp.path()).unwrap(), vec![temp.path().join("packages/shared/lib.rs")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedupe_hardlinks() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.rs"), "fn a() {}\n").unwrap();
+        fs::hard_link(temp.path().join("a.rs"), temp.path().join("b.rs")).unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(temp.path()), IndexConfig::default());
+        assert_eq!(indexer.discover_files(temp.path()).unwrap().len(), 2);
+
+        let config = IndexConfig { dedupe_hardlinks: true, ..IndexConfig::default() };
+        let indexer = Indexer::new(CacheManager::new(temp.path()), config);
+        let files = indexer.discover_files(temp.path()).unwrap();
+        assert_eq!(files, vec![temp.path().join("a.rs")]);
+    }
+
+    #[test]
+    fn test_index_records_skipped_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("main.rs"), "fn main() {}\n").unwrap();
+        fs::write(temp.path().join("bundle.min.js"), format!("var a={}1;\n", "1+".repeat(45))).unwrap();
+        fs::write(temp.path().join("blob.rs"), b"fn x() {}\0\x01\x02").unwrap();
+        fs::write(temp.path().join("huge.py"), "x = 1\n".repeat(50)).unwrap();
+
+        let cache = CacheManager::new(temp.path());
+        let config = IndexConfig {
+            max_file_size: 200,
+            max_line_length: 80,
+            ..IndexConfig::default()
+        };
+        let indexer = Indexer::new(cache.clone(), config);
+        let stats = indexer.index(temp.path(), false).unwrap();
+        assert_eq!(stats.total_files, 1);
+
+        let skipped = cache.list_skipped_files().unwrap();
+        let reasons: Vec<_> = skipped.iter().map(|f| (f.path.as_str(), f.reason)).collect();
+        assert_eq!(reasons, vec![
+            ("blob.rs", SkipReason::Binary),
+            ("bundle.min.js", SkipReason::LongLines),
+            ("huge.py", SkipReason::TooLarge),
+        ]);
+        let warning = stats.warning.unwrap();
+        assert_eq!(warning.details.unwrap().skipped_files, skipped);
+
+        // Unchanged skipped files don't defeat the incremental check
+        assert_eq!(indexer.index(temp.path(), false).unwrap().total_files, 1);
+        assert_eq!(cache.list_skipped_files().unwrap().len(), 3);
+
+        // Raising a limit re-indexes even though no file changed
+        let config = IndexConfig { max_file_size: 200, max_line_length: 0, ..IndexConfig::default() };
+        let stats = Indexer::new(cache.clone(), config).index(temp.path(), false).unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(cache.list_skipped_files().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_load_extension_config() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_extension_config(temp.path()).unwrap().is_empty());
+
+        fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[index.extensions]\nphtml = \"php\"\n\".pyi\" = \"python\"\n",
+        ).unwrap();
+        let extensions = load_extension_config(temp.path()).unwrap();
+        assert_eq!(extensions.get("phtml"), Some(&Language::PHP));
+        assert_eq!(extensions.get(".pyi"), Some(&Language::Python));
+
+        fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[index.extensions]\nphtml = \"hypertext\"\n",
+        ).unwrap();
+        assert!(load_extension_config(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_index_honors_extension_overrides() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        cache.init().unwrap();
+        fs::write(
+            cache.path().join(CONFIG_TOML),
+            "[index.extensions]\nphtml = \"php\"\n",
+        ).unwrap();
+
+        // phtml isn't a built-in PHP extension
+        assert_eq!(Language::builtin_from_extension("phtml"), Language::Unknown);
+        fs::write(project_root.join("view.phtml"), "<?php\nfunction render_view() {}\n").unwrap();
+
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        let stats = indexer.index(&project_root, false).unwrap();
+
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(Language::from_extension("phtml"), Language::PHP);
+        assert!(stats.files_by_language.get("PHP").is_some());
+    }
+
     #[test]
     fn test_index_respects_size_limit() {
         let temp = TempDir::new().unwrap();
@@ -2083,4 +3348,250 @@ mod tests {
         assert_eq!(stats.total_files, 1);
         assert!(stats.index_size_bytes > 0);
     }
+
+    #[test]
+    fn test_index_multiple_roots() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir_all(project_root.join("packages/api")).unwrap();
+        fs::create_dir_all(project_root.join("packages/web")).unwrap();
+        fs::create_dir_all(project_root.join("tools")).unwrap();
+
+        fs::write(project_root.join("packages/api/server.rs"), "fn serve() {}").unwrap();
+        fs::write(project_root.join("packages/web/app.ts"), "function render() {}").unwrap();
+        fs::write(project_root.join("packages/web/util.ts"), "function helper() {}").unwrap();
+        fs::write(project_root.join("tools/build.rs"), "fn build() {}").unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        let config = IndexConfig {
+            roots: vec![
+                WorkspaceRoot::parse("packages/api").unwrap(),
+                WorkspaceRoot::parse("frontend=packages/web").unwrap(),
+            ],
+            ..Default::default()
+        };
+        let indexer = Indexer::new(cache, config);
+
+        let stats = indexer.index(&project_root, false).unwrap();
+
+        // tools/ is outside every root and must not be indexed
+        assert_eq!(stats.total_files, 3);
+
+        let cache = CacheManager::new(&project_root);
+        let roots = cache.list_roots().unwrap();
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].name, "api");
+        assert_eq!(roots[0].path, "packages/api");
+        assert_eq!(roots[0].file_count, 1);
+        assert_eq!(roots[1].name, "frontend");
+        assert_eq!(roots[1].file_count, 2);
+
+        // Paths stay relative to the workspace, not the root
+        let files = cache.list_files().unwrap();
+        assert!(files.iter().any(|f| f.path == "packages/api/server.rs"));
+    }
+
+    #[test]
+    fn test_index_reuses_stored_roots() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir_all(project_root.join("packages/api")).unwrap();
+        fs::write(project_root.join("packages/api/server.rs"), "fn serve() {}").unwrap();
+        fs::write(project_root.join("main.rs"), "fn main() {}").unwrap();
+
+        let config = IndexConfig {
+            roots: vec![WorkspaceRoot::parse("packages/api").unwrap()],
+            ..Default::default()
+        };
+        Indexer::new(CacheManager::new(&project_root), config)
+            .index(&project_root, false)
+            .unwrap();
+
+        // A later index without --root keeps indexing only the recorded roots
+        fs::write(project_root.join("packages/api/client.rs"), "fn call() {}").unwrap();
+        let stats = Indexer::new(CacheManager::new(&project_root), IndexConfig::default())
+            .index(&project_root, false)
+            .unwrap();
+
+        assert_eq!(stats.total_files, 2);
+
+        // Forgetting the roots (`rfx index --reset-roots`) indexes the whole workspace again
+        CacheManager::new(&project_root).update_roots(&[]).unwrap();
+        let stats = Indexer::new(CacheManager::new(&project_root), IndexConfig::default())
+            .index(&project_root, false)
+            .unwrap();
+
+        assert_eq!(stats.total_files, 3);
+        assert!(CacheManager::new(&project_root).list_roots().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_missing_root_fails() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        let config = IndexConfig {
+            roots: vec![WorkspaceRoot::parse("packages/missing").unwrap()],
+            ..Default::default()
+        };
+        let indexer = Indexer::new(CacheManager::new(&project_root), config);
+
+        assert!(indexer.index(&project_root, false).is_err());
+    }
+
+    #[test]
+    fn test_index_records_flag_usages() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        fs::write(
+            project_root.join("checkout.ts"),
+            "if (client.isEnabled('new-checkout')) {\n  render();\n}\n",
+        ).unwrap();
+        fs::write(project_root.join("main.rs"), "fn main() {}").unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project_root, false).unwrap();
+
+        let flag_index = FlagIndex::new(CacheManager::new(&project_root));
+        let usages = flag_index.find_usages("new-checkout").unwrap();
+
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].path, "checkout.ts");
+        assert_eq!(usages[0].line, 1);
+    }
+
+    #[test]
+    fn test_index_records_config_keys() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+
+        fs::write(project_root.join("server.js"), "const url = process.env.DATABASE_URL;\n").unwrap();
+        fs::write(project_root.join("worker.py"), "import os\nos.environ['DATABASE_URL'] = 'sqlite://'\n").unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        Indexer::new(cache, IndexConfig::default()).index(&project_root, false).unwrap();
+
+        let key_index = ConfigKeyIndex::new(CacheManager::new(&project_root));
+        let usages = key_index.find_usages("DATABASE_URL", None).unwrap();
+
+        assert_eq!(usages.len(), 2);
+        assert_eq!((usages[0].path.as_str(), usages[0].access.as_str()), ("server.js", "read"));
+        assert_eq!((usages[1].path.as_str(), usages[1].line, usages[1].access.as_str()), ("worker.py", 2, "write"));
+    }
+
+    #[test]
+    fn test_index_resolves_aliases_and_packages() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        for dir in ["src/app", "src/lib", "pkg", "internal/cart", "scripts/lib"] {
+            fs::create_dir_all(project_root.join(dir)).unwrap();
+        }
+
+        // TypeScript alias from a tsconfig at the index root
+        fs::write(
+            project_root.join("tsconfig.json"),
+            r#"{ "compilerOptions": { "paths": { "@/*": ["./src/*"] } } }"#,
+        ).unwrap();
+        fs::write(
+            project_root.join("src/app/main.ts"),
+            "import { get } from '@/lib/api';\nimport { Component } from '@angular/core';\n",
+        ).unwrap();
+        fs::write(project_root.join("src/lib/api.ts"), "export function get() {}\n").unwrap();
+
+        // Python package-relative imports without a pyproject.toml
+        fs::write(project_root.join("pkg/__init__.py"), "VERSION = 1\n").unwrap();
+        fs::write(project_root.join("pkg/models.py"), "class User:\n    pass\n").unwrap();
+        fs::write(project_root.join("pkg/views.py"), "from .models import User\nfrom . import helpers\n").unwrap();
+
+        // Go package spanning several files
+        fs::write(project_root.join("go.mod"), "module example.com/shop\n\ngo 1.21\n").unwrap();
+        fs::write(
+            project_root.join("main.go"),
+            "package main\n\nimport \"example.com/shop/internal/cart\"\n\nfunc main() { cart.New() }\n",
+        ).unwrap();
+        fs::write(project_root.join("internal/cart/cart.go"), "package cart\n\nfunc New() {}\n").unwrap();
+        fs::write(project_root.join("internal/cart/totals.go"), "package cart\n\nfunc Total() {}\n").unwrap();
+        fs::write(project_root.join("internal/cart/cart_test.go"), "package cart\n").unwrap();
+
+        // Shell scripts sourcing files next to them
+        fs::write(
+            project_root.join("scripts/deploy.sh"),
+            "source \"$(dirname \"$0\")/lib/log.sh\"\n. \"$HOME/.profile\"\n",
+        ).unwrap();
+        fs::write(project_root.join("scripts/lib/log.sh"), "log() { echo \"$@\"; }\n").unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project_root, false).unwrap();
+
+        let dep_index = DependencyIndex::new(CacheManager::new(&project_root));
+        let resolved_paths = |path: &str| -> Vec<String> {
+            let id = dep_index.get_file_id_by_path(path).unwrap().unwrap();
+            let ids: Vec<i64> = dep_index.get_dependencies(id).unwrap()
+                .into_iter()
+                .filter_map(|dep| dep.resolved_file_id)
+                .collect();
+            let paths = dep_index.get_file_paths(&ids).unwrap();
+            let mut resolved: Vec<String> = ids.iter().map(|id| paths[id].clone()).collect();
+            resolved.sort();
+            resolved
+        };
+
+        assert_eq!(resolved_paths("src/app/main.ts"), vec!["src/lib/api.ts"]);
+        assert_eq!(resolved_paths("pkg/views.py"), vec!["pkg/__init__.py", "pkg/models.py"]);
+        assert_eq!(resolved_paths("main.go"), vec!["internal/cart/cart.go", "internal/cart/totals.go"]);
+        assert_eq!(resolved_paths("scripts/deploy.sh"), vec!["scripts/lib/log.sh"]);
+    }
+
+    #[test]
+    fn test_index_resolves_build_targets() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        for dir in ["src", "lib", "pkg"] {
+            fs::create_dir_all(project_root.join(dir)).unwrap();
+        }
+
+        fs::write(project_root.join("Makefile"), "all: app\n\napp: src/main.c\n\tcc -o app src/main.c\n").unwrap();
+        fs::write(project_root.join("src/main.c"), "int main(void) { return 0; }\n").unwrap();
+
+        // CMake targets linked across directories
+        fs::write(
+            project_root.join("CMakeLists.txt"),
+            "add_subdirectory(lib)\nadd_executable(app src/main.c)\ntarget_link_libraries(app PRIVATE core Threads::Threads)\n",
+        ).unwrap();
+        fs::write(project_root.join("lib/CMakeLists.txt"), "add_library(core STATIC core.c)\n").unwrap();
+
+        // Bazel labels resolve to the package's BUILD file
+        fs::write(project_root.join("lib/BUILD"), "cc_library(name = \"core\")\n").unwrap();
+        fs::write(
+            project_root.join("pkg/BUILD"),
+            "cc_binary(\n    name = \"tool\",\n    deps = [\"//lib:core\", \"@abseil//absl\"],\n)\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project_root, false).unwrap();
+
+        let dep_index = DependencyIndex::new(CacheManager::new(&project_root));
+        let resolved_paths = |path: &str| -> Vec<String> {
+            let id = dep_index.get_file_id_by_path(path).unwrap().unwrap();
+            let ids: Vec<i64> = dep_index.get_dependencies(id).unwrap()
+                .into_iter()
+                .filter_map(|dep| dep.resolved_file_id)
+                .collect();
+            let paths = dep_index.get_file_paths(&ids).unwrap();
+            let mut resolved: Vec<String> = ids.iter().map(|id| paths[id].clone()).collect();
+            resolved.sort();
+            resolved
+        };
+
+        assert_eq!(resolved_paths("Makefile"), vec!["src/main.c"]);
+        assert_eq!(resolved_paths("CMakeLists.txt"), vec!["lib/CMakeLists.txt", "lib/CMakeLists.txt"]);
+        assert_eq!(resolved_paths("pkg/BUILD"), vec!["lib/BUILD"]);
+    }
 }
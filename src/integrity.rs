@@ -0,0 +1,419 @@
+//! Index integrity checks and targeted repair (`rfx doctor`)
+//!
+//! The cache is four components that must agree with each other:
+//! - `meta.db`: file list and content hashes per branch, dependencies, stats
+//! - `content.bin`: file contents of the last full index, addressed by file ID
+//! - `trigrams.bin` and `tokens.bin`: search indexes over the same file IDs
+//!
+//! [`check`] validates each component on its own (readable, format version
+//! current, not truncated, schema hash matches this binary) and then cross-checks them:
+//! content.bin files missing from meta.db (orphaned IDs) or the other way
+//! round, stored content whose hash differs from meta.db, and search indexes
+//! covering a different file list than content.bin.
+//!
+//! [`repair`] rebuilds only what is broken. The search indexes are derived
+//! from content.bin, so they are rebuilt from it without touching the working
+//! tree. A broken content store or schema drift needs a full re-index; an
+//! unreadable meta.db is recreated from scratch (keeping `config.toml`).
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB, TOKENS_BIN};
+use crate::content_store::ContentReader;
+use crate::indexer::Indexer;
+use crate::models::IndexConfig;
+use crate::paths::relative_path;
+use crate::tokens::TokenIndex;
+use crate::trigram::TrigramIndex;
+
+/// Per-file issues listed individually before the rest are only counted
+const MAX_FILE_ISSUES: usize = 20;
+
+/// A cache component that can be checked and rebuilt on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    MetaDb,
+    ContentStore,
+    TrigramIndex,
+    TokenIndex,
+}
+
+impl Component {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Component::MetaDb => META_DB,
+            Component::ContentStore => "content.bin",
+            Component::TrigramIndex => "trigrams.bin",
+            Component::TokenIndex => TOKENS_BIN,
+        }
+    }
+}
+
+/// One integrity problem
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub component: Component,
+    /// Stable identifier: `unreadable`, `outdated_format`, `schema_drift`, `orphaned_file`,
+    /// `missing_file`, `hash_mismatch` or `file_list_mismatch`
+    pub kind: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Result of [`check`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Branch whose files were compared against content.bin
+    pub branch: Option<String>,
+    /// Files in content.bin whose content was verified
+    pub files_checked: usize,
+    pub issues: Vec<Issue>,
+    /// Per-file issues beyond the ones listed in `issues`
+    pub omitted_issues: usize,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Components with at least one issue, in dependency order
+    pub fn broken_components(&self) -> Vec<Component> {
+        let mut components: Vec<Component> = self.issues.iter().map(|i| i.component).collect();
+        components.sort();
+        components.dedup();
+        components
+    }
+
+    fn push(&mut self, component: Component, kind: &'static str, message: String, path: Option<String>) {
+        self.issues.push(Issue { component, kind, message, path });
+    }
+
+    /// Record a per-file issue, counting it once the listing limit is reached
+    fn push_file(&mut self, component: Component, kind: &'static str, message: String, path: String) {
+        let listed = self.issues.iter().filter(|i| i.path.is_some()).count();
+        if listed < MAX_FILE_ISSUES {
+            self.push(component, kind, message, Some(path));
+        } else {
+            self.omitted_issues += 1;
+        }
+    }
+}
+
+/// Check every component of `cache` and their consistency with each other
+pub fn check(cache: &CacheManager) -> IntegrityReport {
+    let mut report = IntegrityReport::default();
+
+    let meta_files = match check_meta_db(cache, &mut report) {
+        Some((branch, files)) => {
+            report.branch = Some(branch);
+            Some(files)
+        }
+        None => None,
+    };
+
+    if let Ok(versions) = crate::migration::versions(cache) {
+        let components = [Component::MetaDb, Component::ContentStore, Component::TrigramIndex, Component::TokenIndex];
+        for (component, version) in components.into_iter().zip(versions) {
+            if !version.is_current() {
+                report.push(component, "outdated_format", version.describe(), None);
+            }
+        }
+    }
+
+    let content_path = cache.path().join(Component::ContentStore.file_name());
+    let content = match ContentReader::open(&content_path) {
+        Ok(reader) => Some(reader),
+        Err(e) => {
+            report.push(Component::ContentStore, "unreadable", format!("{:#}", e), None);
+            None
+        }
+    };
+
+    if let Some(content) = &content {
+        let root = cache.workspace_root();
+        let content_paths: Vec<String> = (0..content.file_count() as u32)
+            .map(|id| content.get_file_path(id).map(|p| relative_path(p, &root)).unwrap_or_default())
+            .collect();
+
+        if let Some(meta_files) = &meta_files {
+            check_content_against_meta(cache, content, &content_paths, meta_files, &mut report);
+        }
+        check_trigrams(cache, &content_paths, &mut report);
+        check_tokens(cache, content_paths.len(), &mut report);
+    }
+
+    report
+}
+
+/// Validate meta.db; returns the last indexed branch and its file hashes
+fn check_meta_db(cache: &CacheManager, report: &mut IntegrityReport) -> Option<(String, HashMap<String, String>)> {
+    let result = (|| -> Result<(Option<String>, Option<String>)> {
+        let conn = Connection::open(cache.path().join(META_DB))
+            .context("Failed to open meta.db")?;
+        let integrity: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if integrity != "ok" {
+            anyhow::bail!("SQLite integrity check failed: {}", integrity);
+        }
+        let branch = conn
+            .query_row("SELECT name FROM branches ORDER BY last_indexed DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        let schema_hash = conn
+            .query_row("SELECT value FROM statistics WHERE key = 'schema_hash'", [], |row| row.get(0))
+            .optional()?;
+        Ok((branch, schema_hash))
+    })();
+
+    let (branch, schema_hash) = match result {
+        Ok(found) => found,
+        Err(e) => {
+            report.push(Component::MetaDb, "unreadable", format!("{:#}", e), None);
+            return None;
+        }
+    };
+
+    let current_schema_hash = env!("CACHE_SCHEMA_HASH");
+    if let Some(stored) = schema_hash.filter(|stored| stored != current_schema_hash) {
+        report.push(
+            Component::MetaDb,
+            "schema_drift",
+            format!("Cache was built with schema {}, this binary expects {}", stored, current_schema_hash),
+            None,
+        );
+    }
+
+    let branch = branch?;
+    match cache.get_branch_files(&branch) {
+        Ok(files) => Some((branch, files)),
+        Err(e) => {
+            report.push(Component::MetaDb, "unreadable", format!("{:#}", e), None);
+            None
+        }
+    }
+}
+
+fn check_content_against_meta(
+    cache: &CacheManager,
+    content: &ContentReader,
+    content_paths: &[String],
+    meta_files: &HashMap<String, String>,
+    report: &mut IntegrityReport,
+) {
+    let root = cache.workspace_root();
+    // Transformed files are stored rewritten, so their hash can't be recomputed
+    let transformer = crate::transforms::load_transform_config(cache.path())
+        .and_then(|config| crate::transforms::Transformer::from_config(&config, &root))
+        .ok();
+
+    let mut in_content = HashSet::new();
+    for (id, path) in content_paths.iter().enumerate() {
+        in_content.insert(path.as_str());
+
+        let Some(expected) = meta_files.get(path) else {
+            report.push_file(
+                Component::ContentStore,
+                "orphaned_file",
+                format!("File ID {} has no entry in meta.db", id),
+                path.clone(),
+            );
+            continue;
+        };
+
+        let stored = match content.get_file_content(id as u32) {
+            Ok(stored) => stored,
+            Err(e) => {
+                report.push_file(Component::ContentStore, "unreadable", format!("{:#}", e), path.clone());
+                continue;
+            }
+        };
+        report.files_checked += 1;
+
+        let transformed = transformer.as_ref().is_none_or(|t| t.identity_for(path).is_some());
+        if !transformed && blake3::hash(stored.as_bytes()).to_hex().as_str() != expected {
+            report.push_file(
+                Component::ContentStore,
+                "hash_mismatch",
+                "Stored content doesn't match the hash in meta.db".to_string(),
+                path.clone(),
+            );
+        }
+    }
+
+    let mut missing: Vec<&String> = meta_files.keys().filter(|p| !in_content.contains(p.as_str())).collect();
+    missing.sort();
+    for path in missing {
+        report.push_file(
+            Component::ContentStore,
+            "missing_file",
+            "Indexed in meta.db but missing from content.bin".to_string(),
+            path.clone(),
+        );
+    }
+}
+
+fn check_trigrams(cache: &CacheManager, content_paths: &[String], report: &mut IntegrityReport) {
+    let root = cache.workspace_root();
+    let path = cache.path().join(Component::TrigramIndex.file_name());
+    let trigrams = match TrigramIndex::load(&path) {
+        Ok(trigrams) => trigrams,
+        Err(e) => {
+            report.push(Component::TrigramIndex, "unreadable", format!("{:#}", e), None);
+            return;
+        }
+    };
+
+    let same_files = trigrams.file_count() == content_paths.len()
+        && content_paths.iter().enumerate().all(|(id, path)| {
+            trigrams.get_file(id as u32).is_some_and(|p| &relative_path(p, &root) == path)
+        });
+    if !same_files {
+        report.push(
+            Component::TrigramIndex,
+            "file_list_mismatch",
+            format!(
+                "trigrams.bin covers {} files that don't line up with the {} in content.bin",
+                trigrams.file_count(),
+                content_paths.len()
+            ),
+            None,
+        );
+    }
+}
+
+fn check_tokens(cache: &CacheManager, content_files: usize, report: &mut IntegrityReport) {
+    let path = cache.path().join(Component::TokenIndex.file_name());
+    if !path.exists() {
+        report.push(Component::TokenIndex, "unreadable", "tokens.bin is missing".to_string(), None);
+        return;
+    }
+    match TokenIndex::load(&path) {
+        // Trailing files without identifiers aren't counted, so fewer is fine
+        Ok(tokens) if tokens.file_count() > content_files => report.push(
+            Component::TokenIndex,
+            "file_list_mismatch",
+            format!("tokens.bin covers {} files, content.bin only {}", tokens.file_count(), content_files),
+            None,
+        ),
+        Ok(_) => {}
+        Err(e) => report.push(Component::TokenIndex, "unreadable", format!("{:#}", e), None),
+    }
+}
+
+/// Rebuild the broken components found by `report`
+///
+/// Returns a description of each repair made.
+pub fn repair(cache: &CacheManager, report: &IntegrityReport, config: IndexConfig) -> Result<Vec<String>> {
+    let broken = report.broken_components();
+    let mut repairs = Vec::new();
+    let root = cache.workspace_root();
+
+    let meta_unreadable = report.issues.iter().any(|i| i.component == Component::MetaDb && i.kind == "unreadable");
+    if meta_unreadable {
+        // Nothing else can be trusted without meta.db: start over, keeping settings
+        let config_toml = std::fs::read(cache.path().join(CONFIG_TOML)).ok();
+        cache.clear()?;
+        cache.init()?;
+        if let Some(config_toml) = config_toml {
+            std::fs::write(cache.path().join(CONFIG_TOML), config_toml)?;
+        }
+        Indexer::new(cache.clone(), config).index(&root, false)?;
+        repairs.push("Recreated meta.db and re-indexed the workspace".to_string());
+    } else if broken.contains(&Component::MetaDb) || broken.contains(&Component::ContentStore) {
+        Indexer::new(cache.clone(), config).rebuild(&root, false)?;
+        repairs.push("Re-indexed the workspace to rebuild content.bin and the search indexes".to_string());
+    } else if broken.contains(&Component::TrigramIndex) || broken.contains(&Component::TokenIndex) {
+        let files = rebuild_search_indexes(cache)?;
+        repairs.push(format!("Rebuilt trigrams.bin and tokens.bin from content.bin ({} files)", files));
+    }
+
+    Ok(repairs)
+}
+
+/// Rebuild trigrams.bin and tokens.bin from content.bin; returns the file count
+pub fn rebuild_search_indexes(cache: &CacheManager) -> Result<usize> {
+    let content = ContentReader::open(cache.path().join(Component::ContentStore.file_name()))
+        .context("content.bin must be readable to rebuild the search indexes")?;
+
+    let mut trigrams = TrigramIndex::new();
+    let mut tokens = TokenIndex::new();
+    for id in 0..content.file_count() as u32 {
+        let path = content.get_file_path(id)
+            .with_context(|| format!("content.bin has no path for file ID {}", id))?
+            .to_path_buf();
+        let text = content.get_file_content(id)?;
+        let file_id = trigrams.add_file(path);
+        trigrams.index_file(file_id, text);
+        tokens.index_file(file_id, text);
+    }
+    trigrams.finalize();
+    tokens.finalize();
+
+    trigrams.write(cache.path().join(Component::TrigramIndex.file_name()))
+        .context("Failed to write trigram index")?;
+    tokens.write(cache.path().join(Component::TokenIndex.file_name()))
+        .context("Failed to write token index")?;
+
+    Ok(content.file_count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_workspace() -> (TempDir, CacheManager) {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.rs"), "fn alpha() {}\n").unwrap();
+        fs::write(temp.path().join("b.rs"), "fn beta() {}\n").unwrap();
+        let cache = CacheManager::new(temp.path());
+        Indexer::new(cache.clone(), IndexConfig::default()).index(temp.path(), false).unwrap();
+        (temp, cache)
+    }
+
+    #[test]
+    fn test_fresh_index_is_healthy() {
+        let (_temp, cache) = indexed_workspace();
+        let report = check(&cache);
+        assert!(report.is_healthy(), "{:?}", report.issues);
+        assert_eq!(report.files_checked, 2);
+    }
+
+    #[test]
+    fn test_truncated_trigrams_are_rebuilt_from_content() {
+        let (temp, cache) = indexed_workspace();
+        let trigrams = cache.path().join("trigrams.bin");
+        let bytes = fs::read(&trigrams).unwrap();
+        fs::write(&trigrams, &bytes[..10]).unwrap();
+
+        let report = check(&cache);
+        assert_eq!(report.broken_components(), vec![Component::TrigramIndex]);
+
+        let repairs = repair(&cache, &report, IndexConfig::default()).unwrap();
+        assert!(repairs[0].starts_with("Rebuilt trigrams.bin"));
+        assert!(check(&cache).is_healthy());
+
+        let engine = crate::query::QueryEngine::new(CacheManager::new(temp.path()));
+        let filter = crate::query::QueryFilter { suppress_output: true, ..Default::default() };
+        assert_eq!(engine.search("beta", filter).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_content_hash_mismatch_triggers_reindex() {
+        let (_temp, cache) = indexed_workspace();
+        let conn = Connection::open(cache.path().join(META_DB)).unwrap();
+        conn.execute("UPDATE file_branches SET hash = 'bogus'", []).unwrap();
+
+        let report = check(&cache);
+        assert!(report.issues.iter().any(|i| i.kind == "hash_mismatch"));
+        assert_eq!(report.broken_components(), vec![Component::ContentStore]);
+
+        repair(&cache, &report, IndexConfig::default()).unwrap();
+        assert!(check(&cache).is_healthy());
+    }
+}
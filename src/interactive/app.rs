@@ -12,9 +12,13 @@ use crate::models::{IndexConfig, SearchResult};
 use crate::query::{QueryEngine, QueryFilter};
 
 use super::effects::EffectManager;
+use super::external;
+use super::filter_bar::{FilterBar, FilterBarAction, FilterField, PendingCount};
 use super::history::{QueryFilters, QueryHistory};
 use super::input::{InputField, KeyCommand};
 use super::mouse::{MouseAction, MouseState};
+use super::navigation::{JumpDirection, NavEntry, NavStack};
+use super::pane::ContextPane;
 use super::results::ResultList;
 use super::terminal::TerminalCapabilities;
 use super::theme::ThemeManager;
@@ -78,10 +82,22 @@ pub struct InteractiveApp {
     filter_debounce_ms: u64,
     /// Current filter selector (if open)
     filter_selector: Option<super::filter_selector::FilterSelector>,
+    /// Filter bar with pending (not yet applied) filters (if open)
+    filter_bar: Option<FilterBar>,
+    /// Time when the pending filters last changed (for debounced counting)
+    filter_bar_change_time: Option<Instant>,
+    /// Channel receiver for the pending filters' result count
+    filter_count_rx: Option<mpsc::Receiver<Result<usize>>>,
     /// Time when info message was shown (for auto-dismissal)
     info_message_time: Option<Instant>,
     /// Force full terminal clear on next render (to fix rendering artifacts)
     needs_full_clear: bool,
+    /// Back/forward history of dependency jumps
+    navigation: NavStack,
+    /// What the result list currently shows, for back/forward messages
+    results_label: String,
+    /// Outline and dependencies of the selected result's file (when the pane is open)
+    context_pane: Option<ContextPane>,
 }
 
 /// File preview state
@@ -107,6 +123,8 @@ pub enum AppMode {
     FilePreview,
     /// Filter selector is showing (language or kind)
     FilterSelector,
+    /// Filter bar is showing (all filters, with a pending result count)
+    FilterBar,
 }
 
 /// Focus state for Tab navigation
@@ -115,6 +133,8 @@ pub enum FocusState {
     Input,
     Filters,
     Results,
+    /// The context pane (only reachable while it is open)
+    Pane,
 }
 
 /// Background symbol indexing state
@@ -164,7 +184,12 @@ impl InteractiveApp {
         let engine = QueryEngine::new(cache2);
         let capabilities = TerminalCapabilities::detect();
         let theme = ThemeManager::detect();
-        let history = QueryHistory::load().unwrap_or_else(|_| QueryHistory::new(1000));
+        let mut history = QueryHistory::load().unwrap_or_else(|_| QueryHistory::new(1000));
+
+        // Offer queries run from the command line in this project (`rfx history`) under Ctrl+P
+        if let Ok(recorded) = crate::history::QueryHistoryLog::new(CacheManager::new(&cwd)).list(Some(100)) {
+            history.merge_recorded(&recorded);
+        }
 
         // Check index status
         let index_status = if cache.exists() {
@@ -231,8 +256,14 @@ impl InteractiveApp {
             filter_change_time: None,
             filter_debounce_ms: 500, // 500ms
             filter_selector: None,
+            filter_bar: None,
+            filter_bar_change_time: None,
+            filter_count_rx: None,
             info_message_time: None,
             needs_full_clear: false,
+            navigation: NavStack::new(50),
+            results_label: "search results".to_string(),
+            context_pane: None,
         })
     }
 
@@ -310,6 +341,9 @@ impl InteractiveApp {
                 }
             }
 
+            // Keep the context pane on the selected result's file
+            self.refresh_context_pane();
+
             // Check for search results
             if let Some(ref rx) = self.search_rx {
                 if let Ok(result) = rx.try_recv() {
@@ -325,9 +359,17 @@ impl InteractiveApp {
                                             lang: crate::models::Language::Unknown,
                                             kind: m.kind.clone(),
                                             symbol: m.symbol.clone(),
+                                            parent: m.parent.clone(),
+                                            qualified_name: m.qualified_name.clone(),
                                             span: m.span.clone(),
                                             preview: m.preview.clone(),
                                             dependencies: file_group.dependencies.clone(),
+                                            repo: None,
+                                            signature: m.signature.clone(),
+                                            doc: m.doc.clone(),
+                                            exported: m.exported,
+                                            captures: m.captures.clone(),
+                                            score: None,
                                         }
                                     })
                                 })
@@ -335,6 +377,7 @@ impl InteractiveApp {
 
                             self.results.set_results(flat_results);
                             self.error_message = None;
+                            self.results_label = format!("search: {}", self.input.value());
 
                             // Add to history
                             let pattern = self.input.value().to_string();
@@ -370,6 +413,9 @@ impl InteractiveApp {
                 }
             }
 
+            // Count results for the filter bar's pending filters
+            self.poll_filter_count();
+
             // Auto-clear info messages after 3 seconds
             if let Some(info_time) = self.info_message_time {
                 if info_time.elapsed() >= Duration::from_secs(3) {
@@ -522,7 +568,8 @@ impl InteractiveApp {
                     let selection_lower = selection.to_lowercase();
                     let is_language = matches!(selection_lower.as_str(),
                         "rust" | "python" | "javascript" | "typescript" | "vue" | "svelte" |
-                        "go" | "java" | "php" | "c" | "cpp" | "csharp" | "ruby" | "kotlin" | "zig"
+                        "go" | "java" | "php" | "c" | "cpp" | "csharp" | "ruby" | "kotlin" | "zig" | "shell" | "sql" |
+                        "make" | "cmake" | "bazel"
                     );
 
                     if is_language {
@@ -541,6 +588,26 @@ impl InteractiveApp {
             }
         }
 
+        // The filter bar takes all keys while it is open
+        if self.mode == AppMode::FilterBar {
+            if let Some(bar) = self.filter_bar.as_mut() {
+                match bar.handle_key(key) {
+                    FilterBarAction::None => {}
+                    FilterBarAction::Changed => self.filter_bar_change_time = Some(Instant::now()),
+                    FilterBarAction::Apply => {
+                        self.filters = bar.pending().clone();
+                        self.close_filter_bar();
+                        self.cancel_ongoing_search();
+                        if !self.input.value().trim().is_empty() {
+                            self.execute_search()?;
+                        }
+                    }
+                    FilterBarAction::Cancel => self.close_filter_bar(),
+                }
+            }
+            return Ok(None);
+        }
+
         // Handle Tab/Shift+Tab for focus cycling
         if key.code == crossterm::event::KeyCode::Tab {
             if key.modifiers.contains(crossterm::event::KeyModifiers::SHIFT) {
@@ -578,6 +645,12 @@ impl InteractiveApp {
                         self.show_file_preview(&result)?;
                     }
                 }
+                FocusState::Pane => {
+                    // Preview the outline symbol or dependency
+                    if let Some(entry) = self.context_pane.as_ref().and_then(|p| p.selected()).cloned() {
+                        self.show_file_preview(&pane_target(&entry.path, entry.line))?;
+                    }
+                }
                 _ => {}
             }
             return Ok(None);
@@ -613,6 +686,10 @@ impl InteractiveApp {
             KeyCommand::NextResult => {
                 if self.mode == AppMode::FilePreview {
                     self.scroll_preview_down();
+                } else if self.focus_state == FocusState::Pane {
+                    if let Some(pane) = self.context_pane.as_mut() {
+                        pane.next();
+                    }
                 } else {
                     self.results.next();
                 }
@@ -622,6 +699,10 @@ impl InteractiveApp {
             KeyCommand::PrevResult => {
                 if self.mode == AppMode::FilePreview {
                     self.scroll_preview_up();
+                } else if self.focus_state == FocusState::Pane {
+                    if let Some(pane) = self.context_pane.as_mut() {
+                        pane.prev();
+                    }
                 } else {
                     self.results.prev();
                 }
@@ -678,16 +759,17 @@ impl InteractiveApp {
             }
 
             KeyCommand::PromptGlob => {
-                // For now, set a simple info message. In future, could add text input modal
-                self.info_message = Some("Glob patterns: Use CLI for now (--glob flag)".to_string());
-                self.info_message_time = Some(Instant::now());
+                self.open_filter_bar(FilterBar::editing(&self.filters, FilterField::Glob));
                 Ok(None)
             }
 
             KeyCommand::PromptExclude => {
-                // For now, set a simple info message. In future, could add text input modal
-                self.info_message = Some("Exclude patterns: Use CLI for now (--exclude flag)".to_string());
-                self.info_message_time = Some(Instant::now());
+                self.open_filter_bar(FilterBar::editing(&self.filters, FilterField::Exclude));
+                Ok(None)
+            }
+
+            KeyCommand::OpenFilterBar => {
+                self.open_filter_bar(FilterBar::new(&self.filters));
                 Ok(None)
             }
 
@@ -725,8 +807,28 @@ impl InteractiveApp {
             }
 
             KeyCommand::OpenInEditor => {
-                // Return the result to open in editor
-                Ok(self.results.selected().cloned())
+                // Return the result (or pane entry) to open in editor
+                Ok(self.action_target())
+            }
+
+            KeyCommand::CopyLocation => {
+                if let Some(target) = self.action_target() {
+                    let location = format!("{}:{}", target.path, target.span.start_line);
+                    self.copy_to_clipboard(&location);
+                }
+                Ok(None)
+            }
+
+            KeyCommand::CopyPreview => {
+                if let Some(target) = self.action_target() {
+                    let text = if target.preview.is_empty() {
+                        read_line(&target.path, target.span.start_line).unwrap_or_default()
+                    } else {
+                        target.preview.clone()
+                    };
+                    self.copy_to_clipboard(&text);
+                }
+                Ok(None)
             }
 
             KeyCommand::Reindex => {
@@ -759,6 +861,55 @@ impl InteractiveApp {
                 Ok(None)
             }
 
+            KeyCommand::JumpToImporters => {
+                self.jump(JumpDirection::Importers);
+                Ok(None)
+            }
+
+            KeyCommand::JumpToImports => {
+                self.jump(JumpDirection::Imports);
+                Ok(None)
+            }
+
+            KeyCommand::NavBack => {
+                let current = self.nav_entry();
+                match self.navigation.back(current) {
+                    Some(entry) => self.restore_nav_entry(entry),
+                    None => self.show_info("Nothing to go back to".to_string()),
+                }
+                Ok(None)
+            }
+
+            KeyCommand::ToggleContextPane => {
+                if self.context_pane.take().is_some() {
+                    if self.focus_state == FocusState::Pane {
+                        self.focus_state = FocusState::Results;
+                    }
+                } else if self.results.selected().is_some() {
+                    self.refresh_context_pane_for_selection();
+                } else {
+                    self.show_info("Select a result first".to_string());
+                }
+                Ok(None)
+            }
+
+            KeyCommand::NextPaneSection => {
+                if let Some(pane) = self.context_pane.as_mut() {
+                    pane.next_section();
+                    self.focus_state = FocusState::Pane;
+                }
+                Ok(None)
+            }
+
+            KeyCommand::NavForward => {
+                let current = self.nav_entry();
+                match self.navigation.forward(current) {
+                    Some(entry) => self.restore_nav_entry(entry),
+                    None => self.show_info("Nothing to go forward to".to_string()),
+                }
+                Ok(None)
+            }
+
             KeyCommand::None => {
                 // If input is focused, handle the key for text input
                 if self.focus_state == FocusState::Input {
@@ -771,17 +922,183 @@ impl InteractiveApp {
         }
     }
 
+    /// Replace the result list with the files one import away from the current file
+    ///
+    /// The current file is the previewed file, or else the selected result. The
+    /// list being replaced goes onto the back stack.
+    fn jump(&mut self, direction: JumpDirection) {
+        let path = match (&self.mode, &self.preview_content) {
+            (AppMode::FilePreview, Some(preview)) => Some(preview.path.clone()),
+            _ => self.results.selected().map(|r| r.path.clone()),
+        };
+        let Some(path) = path else {
+            self.show_info("Select a result first".to_string());
+            return;
+        };
+        let path = path.trim_start_matches("./").to_string();
+
+        let label = match direction {
+            JumpDirection::Importers => format!("importers of {}", path),
+            JumpDirection::Imports => format!("imports of {}", path),
+        };
+
+        match super::navigation::jump_targets(&self.cache, &path, direction) {
+            Ok(targets) if targets.is_empty() => {
+                self.show_info(format!("No {}", label));
+            }
+            Ok(targets) => {
+                let count = targets.len();
+                let current = self.nav_entry();
+                self.navigation.push(current);
+                self.results.set_results(targets);
+                self.results_label = label.clone();
+                self.mode = AppMode::Normal;
+                self.preview_content = None;
+                self.focus_state = FocusState::Results;
+                self.error_message = None;
+                self.show_info(format!("{} ({} files) - b to go back", label, count));
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Dependency lookup failed: {}", e));
+            }
+        }
+    }
+
+    /// Snapshot of the current result list for the back/forward stacks
+    fn nav_entry(&self) -> NavEntry {
+        NavEntry {
+            label: self.results_label.clone(),
+            results: self.results.results().to_vec(),
+            selected: self.results.selected_index(),
+        }
+    }
+
+    fn restore_nav_entry(&mut self, entry: NavEntry) {
+        self.results.set_results(entry.results);
+        self.results.select(entry.selected);
+        self.mode = AppMode::Normal;
+        self.preview_content = None;
+        self.focus_state = FocusState::Results;
+        self.show_info(format!("Showing {} ({} back) - b/f to go back/forward", entry.label, self.navigation.depth()));
+        self.results_label = entry.label;
+    }
+
+    /// The result that open/copy actions apply to: the pane entry when the pane is focused
+    fn action_target(&self) -> Option<SearchResult> {
+        if self.focus_state == FocusState::Pane {
+            return self.context_pane.as_ref()
+                .and_then(|p| p.selected())
+                .map(|e| pane_target(&e.path, e.line));
+        }
+        self.results.selected().cloned()
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str) {
+        match external::copy_to_clipboard(text) {
+            Ok(via) => {
+                let first_line = text.lines().next().unwrap_or("");
+                self.show_info(format!("Copied to clipboard ({}): {}", via, first_line));
+            }
+            Err(e) => self.error_message = Some(format!("Copy failed: {}", e)),
+        }
+    }
+
+    fn open_filter_bar(&mut self, bar: FilterBar) {
+        self.filter_bar = Some(bar);
+        self.filter_count_rx = None;
+        // Count the current filters straight away
+        self.filter_bar_change_time = Some(Instant::now() - Duration::from_secs(1));
+        self.mode = AppMode::FilterBar;
+    }
+
+    fn close_filter_bar(&mut self) {
+        self.filter_bar = None;
+        self.filter_bar_change_time = None;
+        self.filter_count_rx = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// Start counting the pending filters once they settle, and collect the count
+    fn poll_filter_count(&mut self) {
+        let Some(bar) = self.filter_bar.as_mut() else {
+            return;
+        };
+
+        if let Some(result) = self.filter_count_rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            bar.set_count(match result {
+                Ok(total) => PendingCount::Ready(total),
+                Err(e) => PendingCount::Failed(e.to_string()),
+            });
+            self.filter_count_rx = None;
+        }
+
+        let settled = self.filter_bar_change_time
+            .is_some_and(|t| t.elapsed() >= Duration::from_millis(300));
+        if !settled {
+            return;
+        }
+        self.filter_bar_change_time = None;
+
+        let pattern = self.input.value().to_string();
+        if pattern.trim().is_empty() {
+            bar.set_count(PendingCount::Ready(0));
+            return;
+        }
+
+        // Only the total is needed, so don't materialize a page of results
+        let filter = QueryFilter {
+            limit: Some(1),
+            ..query_filter(bar.pending())
+        };
+        let (tx, rx) = mpsc::channel();
+        let engine = QueryEngine::new(CacheManager::new(&self.cwd));
+        std::thread::spawn(move || {
+            let result = engine.search_with_metadata(&pattern, filter).map(|r| r.pagination.total);
+            tx.send(result).ok();
+        });
+
+        bar.set_count(PendingCount::Counting);
+        // A newer count replaces one still running
+        self.filter_count_rx = Some(rx);
+    }
+
+    fn show_info(&mut self, message: String) {
+        self.info_message = Some(message);
+        self.info_message_time = Some(Instant::now());
+    }
+
+    /// Open the context pane on the selected result's file, or reload it if the selection moved
+    fn refresh_context_pane_for_selection(&mut self) {
+        let Some(path) = self.results.selected().map(|r| r.path.clone()) else {
+            return;
+        };
+        if self.context_pane.as_ref().is_some_and(|p| p.path() == path) {
+            return;
+        }
+        self.context_pane = Some(ContextPane::load(&self.cache, &path));
+    }
+
+    fn refresh_context_pane(&mut self) {
+        if self.context_pane.is_some() {
+            self.refresh_context_pane_for_selection();
+        }
+    }
+
     fn focus_next(&mut self) {
+        let pane_open = self.context_pane.is_some();
         self.focus_state = match self.focus_state {
             FocusState::Input => FocusState::Filters,
             FocusState::Filters => FocusState::Results,
-            FocusState::Results => FocusState::Input,
+            FocusState::Results if pane_open => FocusState::Pane,
+            FocusState::Results | FocusState::Pane => FocusState::Input,
         };
     }
 
     fn focus_prev(&mut self) {
+        let pane_open = self.context_pane.is_some();
         self.focus_state = match self.focus_state {
-            FocusState::Input => FocusState::Results,
+            FocusState::Input if pane_open => FocusState::Pane,
+            FocusState::Input | FocusState::Pane => FocusState::Results,
             FocusState::Filters => FocusState::Input,
             FocusState::Results => FocusState::Filters,
         };
@@ -792,12 +1109,8 @@ impl InteractiveApp {
         let content = std::fs::read_to_string(&result.path)?;
         let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
 
-        // Detect language from file extension
-        let language = std::path::Path::new(&result.path)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| crate::models::Language::from_extension(ext))
-            .unwrap_or(crate::models::Language::Unknown);
+        // Detect language from the file name
+        let language = crate::models::Language::from_path(std::path::Path::new(&result.path));
 
         self.preview_content = Some(FilePreview {
             path: result.path.clone(),
@@ -834,7 +1147,8 @@ impl InteractiveApp {
                     let selection_lower = selection.to_lowercase();
                     let is_language = matches!(selection_lower.as_str(),
                         "rust" | "python" | "javascript" | "typescript" | "vue" | "svelte" |
-                        "go" | "java" | "php" | "c" | "cpp" | "csharp" | "ruby" | "kotlin" | "zig"
+                        "go" | "java" | "php" | "c" | "cpp" | "csharp" | "ruby" | "kotlin" | "zig" | "shell" | "sql" |
+                        "make" | "cmake" | "bazel"
                     );
 
                     if is_language {
@@ -1007,55 +1321,7 @@ impl InteractiveApp {
             return Ok(());
         }
 
-        // Parse language filter
-        let language = self.filters.language.as_ref().and_then(|lang_str| {
-            match lang_str.to_lowercase().as_str() {
-                "rust" | "rs" => Some(crate::models::Language::Rust),
-                "python" | "py" => Some(crate::models::Language::Python),
-                "javascript" | "js" => Some(crate::models::Language::JavaScript),
-                "typescript" | "ts" => Some(crate::models::Language::TypeScript),
-                "vue" => Some(crate::models::Language::Vue),
-                "svelte" => Some(crate::models::Language::Svelte),
-                "go" => Some(crate::models::Language::Go),
-                "java" => Some(crate::models::Language::Java),
-                "php" => Some(crate::models::Language::PHP),
-                "c" => Some(crate::models::Language::C),
-                "cpp" | "c++" => Some(crate::models::Language::Cpp),
-                "csharp" | "cs" | "c#" => Some(crate::models::Language::CSharp),
-                "ruby" | "rb" => Some(crate::models::Language::Ruby),
-                "kotlin" | "kt" => Some(crate::models::Language::Kotlin),
-                "zig" => Some(crate::models::Language::Zig),
-                _ => None,
-            }
-        });
-
-        // Parse symbol kind filter
-        let kind = self.filters.kind.as_ref().and_then(|kind_str| {
-            kind_str.parse::<crate::models::SymbolKind>().ok()
-        });
-
-        // Build query filter
-        let filter = QueryFilter {
-            language,
-            kind,
-            use_ast: false,
-            use_regex: self.filters.regex_mode,
-            limit: Some(500),
-            symbols_mode: self.filters.symbols_mode,
-            expand: self.filters.expand,
-            file_pattern: None,
-            exact: false, // Exact match is the default behavior
-            use_contains: self.filters.contains,
-            timeout_secs: 10,
-            glob_patterns: self.filters.glob_patterns.clone(),
-            exclude_patterns: self.filters.exclude_patterns.clone(),
-            paths_only: false,
-            offset: None,
-            force: false,
-            suppress_output: false,
-            include_dependencies: false,  // Interactive mode doesn't support dependencies yet
-            ..Default::default()
-        };
+        let filter = query_filter(&self.filters);
 
         // Spawn background thread for search
         let (tx, rx) = mpsc::channel();
@@ -1195,15 +1461,14 @@ impl InteractiveApp {
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
         result: &SearchResult,
     ) -> Result<()> {
-        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-        let line = result.span.start_line;
-
-        // Build command with line number
-        let args = match editor.as_str() {
-            "vim" | "nvim" => vec![format!("+{}", line), result.path.clone()],
-            "emacs" => vec![format!("+{}:0", line), result.path.clone()],
-            "code" | "vscode" => vec!["-g".to_string(), format!("{}:{}", result.path, line)],
-            _ => vec![result.path.clone()],
+        // Build command with line number ($VISUAL / $EDITOR, `+line` syntax)
+        let editor = external::configured_editor();
+        let (program, args) = match external::editor_command(&editor, &result.path, result.span.start_line) {
+            Ok(command) => command,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return Ok(());
+            }
         };
 
         // Suspend terminal properly
@@ -1217,9 +1482,9 @@ impl InteractiveApp {
         terminal.show_cursor()?;
 
         // Open editor
-        let status = std::process::Command::new(&editor)
+        let status = std::process::Command::new(&program)
             .args(&args)
-            .status()?;
+            .status();
 
         // Resume terminal
         crossterm::terminal::enable_raw_mode()?;
@@ -1230,8 +1495,12 @@ impl InteractiveApp {
         )?;
         terminal.clear()?;
 
-        if !status.success() {
-            self.error_message = Some(format!("Editor exited with error code: {:?}", status.code()));
+        match status {
+            Ok(status) if !status.success() => {
+                self.error_message = Some(format!("Editor exited with error code: {:?}", status.code()));
+            }
+            Ok(_) => {}
+            Err(e) => self.error_message = Some(format!("Failed to launch {}: {}", program, e)),
         }
 
         Ok(())
@@ -1266,6 +1535,10 @@ impl InteractiveApp {
         &self.input
     }
 
+    pub fn history(&self) -> &QueryHistory {
+        &self.history
+    }
+
     pub fn results(&self) -> &ResultList {
         &self.results
     }
@@ -1330,6 +1603,10 @@ impl InteractiveApp {
         &self.cwd
     }
 
+    pub fn filter_bar(&self) -> Option<&FilterBar> {
+        self.filter_bar.as_ref()
+    }
+
     pub fn filter_selector(&self) -> Option<&super::filter_selector::FilterSelector> {
         self.filter_selector.as_ref()
     }
@@ -1341,6 +1618,85 @@ impl InteractiveApp {
     pub fn filter_badge_positions(&self) -> &super::mouse::FilterBadgePositions {
         &self.filter_badge_positions
     }
+
+    pub fn context_pane(&self) -> Option<&ContextPane> {
+        self.context_pane.as_ref()
+    }
+}
+
+/// A result pointing at `line` of `path`, for previewing pane entries
+/// Query filter for the interactive filter state
+fn query_filter(filters: &QueryFilters) -> QueryFilter {
+    // Parse language filter
+    let language = filters.language.as_ref().and_then(|lang_str| {
+        match lang_str.to_lowercase().as_str() {
+            "rust" | "rs" => Some(crate::models::Language::Rust),
+            "python" | "py" => Some(crate::models::Language::Python),
+            "javascript" | "js" => Some(crate::models::Language::JavaScript),
+            "typescript" | "ts" => Some(crate::models::Language::TypeScript),
+            "vue" => Some(crate::models::Language::Vue),
+            "svelte" => Some(crate::models::Language::Svelte),
+            "go" => Some(crate::models::Language::Go),
+            "java" => Some(crate::models::Language::Java),
+            "php" => Some(crate::models::Language::PHP),
+            "c" => Some(crate::models::Language::C),
+            "cpp" | "c++" => Some(crate::models::Language::Cpp),
+            "csharp" | "cs" | "c#" => Some(crate::models::Language::CSharp),
+            "ruby" | "rb" => Some(crate::models::Language::Ruby),
+            "kotlin" | "kt" => Some(crate::models::Language::Kotlin),
+            "zig" => Some(crate::models::Language::Zig),
+            "shell" | "sh" | "bash" | "zsh" => Some(crate::models::Language::Shell),
+            "make" | "makefile" => Some(crate::models::Language::Make),
+            "cmake" => Some(crate::models::Language::CMake),
+            "bazel" | "starlark" | "bzl" => Some(crate::models::Language::Bazel),
+            "sql" => Some(crate::models::Language::Sql),
+            _ => None,
+        }
+    });
+
+    // Parse symbol kind filter
+    let kinds = filters.kind.as_deref().map(crate::models::SymbolKind::parse_list).unwrap_or_default();
+
+    // Build query filter
+    QueryFilter {
+        language,
+        kinds,
+        use_ast: false,
+        use_regex: filters.regex_mode,
+        limit: Some(500),
+        symbols_mode: filters.symbols_mode,
+        expand: filters.expand,
+        file_pattern: None,
+        exact: false, // Exact match is the default behavior
+        use_contains: filters.contains,
+        timeout_secs: 10,
+        glob_patterns: filters.glob_patterns.clone(),
+        exclude_patterns: filters.exclude_patterns.clone(),
+        paths_only: false,
+        offset: None,
+        force: false,
+        suppress_output: false,
+        include_dependencies: false,  // Interactive mode doesn't support dependencies yet
+        ..Default::default()
+    }
+}
+
+/// Text of one line of a file (1-based)
+fn read_line(path: &str, line: usize) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    content.lines().nth(line.checked_sub(1)?).map(|l| l.trim().to_string())
+}
+
+fn pane_target(path: &str, line: usize) -> SearchResult {
+    SearchResult::new(
+        path.to_string(),
+        crate::models::Language::from_path(std::path::Path::new(path)),
+        crate::models::SymbolKind::Unknown("file".to_string()),
+        None,
+        crate::models::Span::new(line, 0, line, 0),
+        None,
+        String::new(),
+    )
 }
 
 impl FilePreview {
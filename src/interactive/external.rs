@@ -0,0 +1,128 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Program and arguments that open `path` at `line` in the user's editor
+///
+/// Uses `$VISUAL`, then `$EDITOR`, then `vim`. The variable may carry its own
+/// arguments (`code --wait`). Editors that don't accept `+line` get their own
+/// syntax; unknown editors get `+line`, which most terminal editors understand.
+pub fn editor_command(editor: &str, path: &str, line: usize) -> Result<(String, Vec<String>)> {
+    let mut words = shell_words::split(editor)
+        .with_context(|| format!("Invalid editor command: {}", editor))?;
+    if words.is_empty() {
+        bail!("Editor command is empty");
+    }
+    let program = words.remove(0);
+
+    let name = std::path::Path::new(&program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&program)
+        .to_string();
+
+    match name.as_str() {
+        "code" | "code-insiders" | "codium" | "vscode" | "cursor" => {
+            words.push("-g".to_string());
+            words.push(format!("{}:{}", path, line));
+        }
+        "subl" | "zed" | "hx" | "helix" => words.push(format!("{}:{}", path, line)),
+        "idea" | "pycharm" | "goland" | "clion" | "webstorm" | "phpstorm" | "rubymine" => {
+            words.push("--line".to_string());
+            words.push(line.to_string());
+            words.push(path.to_string());
+        }
+        _ => {
+            words.push(format!("+{}", line));
+            words.push(path.to_string());
+        }
+    }
+
+    Ok((program, words))
+}
+
+/// The editor to use, as configured in the environment
+pub fn configured_editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vim".to_string())
+}
+
+/// Clipboard programs tried in order, with the arguments they need to read stdin
+const CLIPBOARD_TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Copy `text` to the system clipboard
+///
+/// Pipes into the first clipboard program found on the PATH. Without one
+/// (e.g. over SSH) the text is sent to the terminal as an OSC 52 sequence,
+/// which most modern terminals forward to the local clipboard. Returns how
+/// the text was copied.
+pub fn copy_to_clipboard(text: &str) -> Result<&'static str> {
+    for (program, args) in CLIPBOARD_TOOLS {
+        let child = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())
+                .with_context(|| format!("Failed to write to {}", program))?;
+        }
+        let status = child.wait()?;
+        if status.success() {
+            return Ok(program);
+        }
+        log::debug!("{} exited with {:?}", program, status.code());
+    }
+
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()?;
+    Ok("terminal")
+}
+
+/// OSC 52 escape sequence that sets the clipboard to `text`
+fn osc52(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{}\x07", encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command_line_syntax() {
+        let (program, args) = editor_command("nvim", "src/main.rs", 42).unwrap();
+        assert_eq!(program, "nvim");
+        assert_eq!(args, vec!["+42", "src/main.rs"]);
+
+        let (program, args) = editor_command("code --wait", "src/main.rs", 7).unwrap();
+        assert_eq!(program, "code");
+        assert_eq!(args, vec!["--wait", "-g", "src/main.rs:7"]);
+
+        let (_, args) = editor_command("/usr/local/bin/hx", "a.py", 3).unwrap();
+        assert_eq!(args, vec!["a.py:3"]);
+
+        assert!(editor_command("  ", "a.py", 3).is_err());
+    }
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("src/main.rs:42"), "\x1b]52;c;c3JjL21haW4ucnM6NDI=\x07");
+    }
+}
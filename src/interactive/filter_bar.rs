@@ -0,0 +1,370 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::filter_selector::{KIND_OPTIONS, LANGUAGE_OPTIONS};
+use super::history::QueryFilters;
+use super::input::InputField;
+use super::theme::ThemeManager;
+
+/// A filter that can be edited in the filter bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Symbols,
+    Regex,
+    Contains,
+    Language,
+    Kind,
+    Glob,
+    Exclude,
+}
+
+impl FilterField {
+    pub const ALL: [FilterField; 7] = [
+        FilterField::Symbols,
+        FilterField::Regex,
+        FilterField::Contains,
+        FilterField::Language,
+        FilterField::Kind,
+        FilterField::Glob,
+        FilterField::Exclude,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            FilterField::Symbols => "Symbols only",
+            FilterField::Regex => "Regex",
+            FilterField::Contains => "Contains",
+            FilterField::Language => "Language",
+            FilterField::Kind => "Kind",
+            FilterField::Glob => "Include globs",
+            FilterField::Exclude => "Exclude globs",
+        }
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(self, FilterField::Glob | FilterField::Exclude)
+    }
+}
+
+/// Result count of the pending filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingCount {
+    /// Filters changed since the last count
+    Stale,
+    Counting,
+    Ready(usize),
+    Failed(String),
+}
+
+/// What the app should do after a key press in the filter bar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterBarAction {
+    None,
+    /// The pending filters changed (recount them)
+    Changed,
+    /// Apply the pending filters and close the bar
+    Apply,
+    /// Discard the pending filters and close the bar
+    Cancel,
+}
+
+/// Filter bar: edits a copy of the active filters and shows how many results
+/// they would produce before they are applied
+pub struct FilterBar {
+    pending: QueryFilters,
+    field: usize,
+    /// Text being typed into a glob field (comma-separated patterns)
+    editing: Option<InputField>,
+    count: PendingCount,
+}
+
+impl FilterBar {
+    pub fn new(filters: &QueryFilters) -> Self {
+        Self {
+            pending: filters.clone(),
+            field: 0,
+            editing: None,
+            count: PendingCount::Stale,
+        }
+    }
+
+    /// Open the bar typing into `field`
+    pub fn editing(filters: &QueryFilters, field: FilterField) -> Self {
+        let mut bar = Self::new(filters);
+        bar.field = FilterField::ALL.iter().position(|f| *f == field).unwrap_or(0);
+        bar.start_editing();
+        bar
+    }
+
+    pub fn pending(&self) -> &QueryFilters {
+        &self.pending
+    }
+
+    pub fn field(&self) -> FilterField {
+        FilterField::ALL[self.field]
+    }
+
+    pub fn set_count(&mut self, count: PendingCount) {
+        self.count = count;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FilterBarAction {
+        if let Some(input) = self.editing.as_mut() {
+            return match key.code {
+                KeyCode::Enter => {
+                    let patterns = split_patterns(input.value());
+                    self.editing = None;
+                    self.set_patterns(patterns)
+                }
+                KeyCode::Esc => {
+                    self.editing = None;
+                    FilterBarAction::None
+                }
+                _ => {
+                    input.handle_key(key);
+                    FilterBarAction::None
+                }
+            };
+        }
+
+        match key.code {
+            KeyCode::Esc => FilterBarAction::Cancel,
+            KeyCode::Enter => {
+                if self.field().is_text() {
+                    self.start_editing();
+                    FilterBarAction::None
+                } else {
+                    FilterBarAction::Apply
+                }
+            }
+            KeyCode::Char('a') => FilterBarAction::Apply,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                self.field = (self.field + 1) % FilterField::ALL.len();
+                FilterBarAction::None
+            }
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => {
+                self.field = (self.field + FilterField::ALL.len() - 1) % FilterField::ALL.len();
+                FilterBarAction::None
+            }
+            KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l') => self.change(1),
+            KeyCode::Left | KeyCode::Char('h') => self.change(-1),
+            KeyCode::Backspace | KeyCode::Delete => self.clear_field(),
+            _ => FilterBarAction::None,
+        }
+    }
+
+    /// Toggle the current field, or step through its options
+    fn change(&mut self, step: isize) -> FilterBarAction {
+        match self.field() {
+            FilterField::Symbols => self.pending.symbols_mode = !self.pending.symbols_mode,
+            FilterField::Regex => self.pending.regex_mode = !self.pending.regex_mode,
+            FilterField::Contains => self.pending.contains = !self.pending.contains,
+            FilterField::Language => cycle(&mut self.pending.language, LANGUAGE_OPTIONS, step),
+            FilterField::Kind => cycle(&mut self.pending.kind, KIND_OPTIONS, step),
+            FilterField::Glob | FilterField::Exclude => {
+                self.start_editing();
+                return FilterBarAction::None;
+            }
+        }
+        self.count = PendingCount::Stale;
+        FilterBarAction::Changed
+    }
+
+    fn clear_field(&mut self) -> FilterBarAction {
+        match self.field() {
+            FilterField::Symbols => self.pending.symbols_mode = false,
+            FilterField::Regex => self.pending.regex_mode = false,
+            FilterField::Contains => self.pending.contains = false,
+            FilterField::Language => self.pending.language = None,
+            FilterField::Kind => self.pending.kind = None,
+            FilterField::Glob => self.pending.glob_patterns.clear(),
+            FilterField::Exclude => self.pending.exclude_patterns.clear(),
+        }
+        self.count = PendingCount::Stale;
+        FilterBarAction::Changed
+    }
+
+    fn start_editing(&mut self) {
+        let patterns = match self.field() {
+            FilterField::Glob => &self.pending.glob_patterns,
+            FilterField::Exclude => &self.pending.exclude_patterns,
+            _ => return,
+        };
+        let mut input = InputField::new();
+        input.set_value(patterns.join(", "));
+        self.editing = Some(input);
+    }
+
+    fn set_patterns(&mut self, patterns: Vec<String>) -> FilterBarAction {
+        let target = match self.field() {
+            FilterField::Glob => &mut self.pending.glob_patterns,
+            FilterField::Exclude => &mut self.pending.exclude_patterns,
+            _ => return FilterBarAction::None,
+        };
+        if *target == patterns {
+            return FilterBarAction::None;
+        }
+        *target = patterns;
+        self.count = PendingCount::Stale;
+        FilterBarAction::Changed
+    }
+
+    fn value(&self, field: FilterField) -> String {
+        let on_off = |b: bool| if b { "on" } else { "off" }.to_string();
+        let list = |patterns: &[String]| {
+            if patterns.is_empty() {
+                "-".to_string()
+            } else {
+                patterns.join(", ")
+            }
+        };
+        match field {
+            FilterField::Symbols => on_off(self.pending.symbols_mode),
+            FilterField::Regex => on_off(self.pending.regex_mode),
+            FilterField::Contains => on_off(self.pending.contains),
+            FilterField::Language => self.pending.language.clone().unwrap_or_else(|| "any".to_string()),
+            FilterField::Kind => self.pending.kind.clone().unwrap_or_else(|| "any".to_string()),
+            FilterField::Glob => list(&self.pending.glob_patterns),
+            FilterField::Exclude => list(&self.pending.exclude_patterns),
+        }
+    }
+
+    /// Render the bar as a modal; `current` is the result count of the applied filters
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &ThemeManager, current: usize) {
+        let palette = &theme.palette;
+
+        let modal_width = 60.min(area.width.saturating_sub(4));
+        let modal_height = (FilterField::ALL.len() as u16 + 5).min(area.height.saturating_sub(2));
+        let modal_area = Rect::new(
+            area.x + area.width.saturating_sub(modal_width) / 2,
+            area.y + area.height.saturating_sub(modal_height) / 2,
+            modal_width,
+            modal_height,
+        );
+
+        let mut lines: Vec<Line> = FilterField::ALL
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let selected = idx == self.field;
+                let value = match (&self.editing, selected) {
+                    (Some(input), true) => format!("{}▏", input.value()),
+                    _ => self.value(*field),
+                };
+                let style = if selected {
+                    Style::default().fg(Color::Black).bg(palette.highlight).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(palette.foreground)
+                };
+                Line::from(vec![
+                    Span::styled(format!("{} {:<14}", if selected { "▶" } else { " " }, field.label()), style),
+                    Span::styled(format!(" {}", value), style),
+                ])
+            })
+            .collect();
+
+        let pending = match &self.count {
+            PendingCount::Stale | PendingCount::Counting => "counting…".to_string(),
+            PendingCount::Ready(n) => format!("{} results", n),
+            PendingCount::Failed(e) => format!("error: {}", e),
+        };
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("Pending: ", Style::default().fg(palette.muted)),
+            Span::styled(pending, Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  (now: {} results)", current), Style::default().fg(palette.muted)),
+        ]));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Filters ")
+            .border_style(Style::default().fg(palette.accent).add_modifier(Modifier::BOLD))
+            .style(Style::default().bg(Color::Rgb(25, 25, 30)));
+
+        f.render_widget(Clear, modal_area);
+        f.render_widget(Paragraph::new(lines).block(block), modal_area);
+
+        let hint = if self.editing.is_some() {
+            "type comma-separated globs  Enter done  Esc cancel edit"
+        } else {
+            "j/k field  ←/→/Space change  Del clear  Enter/a apply  Esc cancel"
+        };
+        let footer_area = Rect::new(modal_area.x, modal_area.y + modal_area.height, modal_area.width, 1);
+        if footer_area.y < area.y + area.height {
+            let footer = Paragraph::new(Span::styled(hint, Style::default().fg(palette.muted)))
+                .alignment(Alignment::Center);
+            f.render_widget(footer, footer_area);
+        }
+    }
+}
+
+/// Step an optional choice through `options`, passing through "any" (None)
+fn cycle(value: &mut Option<String>, options: &[&str], step: isize) {
+    let len = options.len() as isize + 1;
+    let current = value
+        .as_ref()
+        .and_then(|v| options.iter().position(|o| o.eq_ignore_ascii_case(v)))
+        .map_or(0, |i| i as isize + 1);
+    let next = (current + step).rem_euclid(len);
+    *value = (next > 0).then(|| options[next as usize - 1].to_string());
+}
+
+fn split_patterns(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| p.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(bar: &mut FilterBar, code: KeyCode) -> FilterBarAction {
+        bar.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_cycle_through_any() {
+        let mut value = None;
+        cycle(&mut value, &["rust", "go"], 1);
+        assert_eq!(value.as_deref(), Some("rust"));
+        cycle(&mut value, &["rust", "go"], 1);
+        assert_eq!(value.as_deref(), Some("go"));
+        cycle(&mut value, &["rust", "go"], 1);
+        assert_eq!(value, None);
+        cycle(&mut value, &["rust", "go"], -1);
+        assert_eq!(value.as_deref(), Some("go"));
+    }
+
+    #[test]
+    fn test_edit_globs_and_apply() {
+        let applied = QueryFilters::default();
+        let mut bar = FilterBar::editing(&applied, FilterField::Glob);
+        for c in "src/**, tests/** ".chars() {
+            press(&mut bar, KeyCode::Char(c));
+        }
+        assert_eq!(press(&mut bar, KeyCode::Enter), FilterBarAction::Changed);
+        assert_eq!(bar.pending().glob_patterns, vec!["src/**", "tests/**"]);
+        assert_eq!(bar.count, PendingCount::Stale);
+
+        // Toggling symbols changes the pending copy only
+        press(&mut bar, KeyCode::Home);
+        while bar.field() != FilterField::Symbols {
+            press(&mut bar, KeyCode::Down);
+        }
+        assert_eq!(press(&mut bar, KeyCode::Char(' ')), FilterBarAction::Changed);
+        assert!(bar.pending().symbols_mode);
+        assert!(!applied.symbols_mode);
+
+        assert_eq!(press(&mut bar, KeyCode::Enter), FilterBarAction::Apply);
+    }
+}
@@ -9,6 +9,19 @@ use ratatui::{
 
 use super::theme::ThemeManager;
 
+/// Languages offered by the language selector and the filter bar
+pub const LANGUAGE_OPTIONS: &[&str] = &[
+    "rust", "python", "javascript", "typescript", "vue", "svelte", "go", "java",
+    "php", "c", "cpp", "csharp", "ruby", "kotlin", "zig", "shell", "sql", "make", "cmake", "bazel",
+];
+
+/// Symbol kinds offered by the kind selector and the filter bar
+pub const KIND_OPTIONS: &[&str] = &[
+    "Function", "Class", "Struct", "Enum", "Interface", "Trait", "Constant",
+    "Variable", "Method", "Module", "Namespace", "Type", "Macro", "Property",
+    "Event", "Import", "Export", "Attribute",
+];
+
 /// Filter selector state
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterSelectorType {
@@ -31,23 +44,7 @@ pub struct FilterSelector {
 impl FilterSelector {
     /// Create a new language selector
     pub fn new_language() -> Self {
-        let options = vec![
-            "rust".to_string(),
-            "python".to_string(),
-            "javascript".to_string(),
-            "typescript".to_string(),
-            "vue".to_string(),
-            "svelte".to_string(),
-            "go".to_string(),
-            "java".to_string(),
-            "php".to_string(),
-            "c".to_string(),
-            "cpp".to_string(),
-            "csharp".to_string(),
-            "ruby".to_string(),
-            "kotlin".to_string(),
-            "zig".to_string(),
-        ];
+        let options = LANGUAGE_OPTIONS.iter().map(|s| s.to_string()).collect();
 
         Self {
             selector_type: FilterSelectorType::Language,
@@ -59,26 +56,7 @@ impl FilterSelector {
 
     /// Create a new kind selector
     pub fn new_kind() -> Self {
-        let options = vec![
-            "Function".to_string(),
-            "Class".to_string(),
-            "Struct".to_string(),
-            "Enum".to_string(),
-            "Interface".to_string(),
-            "Trait".to_string(),
-            "Constant".to_string(),
-            "Variable".to_string(),
-            "Method".to_string(),
-            "Module".to_string(),
-            "Namespace".to_string(),
-            "Type".to_string(),
-            "Macro".to_string(),
-            "Property".to_string(),
-            "Event".to_string(),
-            "Import".to_string(),
-            "Export".to_string(),
-            "Attribute".to_string(),
-        ];
+        let options = KIND_OPTIONS.iter().map(|s| s.to_string()).collect();
 
         Self {
             selector_type: FilterSelectorType::Kind,
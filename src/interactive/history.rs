@@ -47,6 +47,21 @@ pub struct QueryFilters {
     pub contains: bool,
 }
 
+impl From<&crate::history::HistoryFilters> for QueryFilters {
+    fn from(filters: &crate::history::HistoryFilters) -> Self {
+        Self {
+            symbols_mode: filters.symbols,
+            regex_mode: filters.regex,
+            language: filters.lang.clone(),
+            kind: filters.kind.clone(),
+            glob_patterns: filters.glob.clone(),
+            exclude_patterns: filters.exclude.clone(),
+            expand: filters.expand,
+            contains: filters.contains,
+        }
+    }
+}
+
 impl QueryHistory {
     /// Create a new query history
     pub fn new(max_size: usize) -> Self {
@@ -119,6 +134,35 @@ impl QueryHistory {
         self.cursor = None;
     }
 
+    /// Merge in queries recorded in the project's index (`rfx history`)
+    ///
+    /// Duplicates of existing entries are skipped; the result stays newest first.
+    pub fn merge_recorded(&mut self, entries: &[crate::history::HistoryEntry]) {
+        for entry in entries {
+            let query = HistoricalQuery {
+                pattern: entry.pattern.clone(),
+                timestamp: chrono::DateTime::from_timestamp(entry.executed_at, 0)
+                    .unwrap_or_default()
+                    .to_rfc3339(),
+                filters: QueryFilters::from(&entry.filters),
+            };
+            if !self.queries.iter().any(|q| q.pattern == query.pattern && q.filters == query.filters) {
+                self.queries.push_back(query);
+            }
+        }
+
+        self.queries.make_contiguous().sort_by_cached_key(|q| {
+            std::cmp::Reverse(chrono::DateTime::parse_from_rfc3339(&q.timestamp).ok())
+        });
+        self.queries.truncate(self.max_size);
+        self.cursor = None;
+    }
+
+    /// The most recent queries, newest first
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = &HistoricalQuery> {
+        self.queries.iter().take(count)
+    }
+
     /// Get the previous query in history (Ctrl+P)
     pub fn prev(&mut self) -> Option<&HistoricalQuery> {
         if self.queries.is_empty() {
@@ -243,4 +287,29 @@ mod tests {
         assert_eq!(history.next().unwrap().pattern, "three");
         assert!(history.next().is_none());
     }
+
+    #[test]
+    fn test_merge_recorded() {
+        let mut history = QueryHistory::new(100);
+        history.add("local".to_string(), QueryFilters::default());
+
+        let recorded = |pattern: &str, executed_at: i64| crate::history::HistoryEntry {
+            id: 0,
+            pattern: pattern.to_string(),
+            args: vec!["query".to_string(), pattern.to_string(), "--symbols".to_string()],
+            filters: crate::history::HistoryFilters { symbols: true, ..Default::default() },
+            result_count: 1,
+            total_results: 1,
+            timing_ms: 1,
+            executed_at,
+            directories: Vec::new(),
+        };
+        history.merge_recorded(&[recorded("from_cli", 1_000), recorded("from_cli", 1_000)]);
+
+        // Deduplicated, and older than the query just added
+        assert_eq!(history.len(), 2);
+        let patterns: Vec<&str> = history.recent(5).map(|q| q.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["local", "from_cli"]);
+        assert!(history.recent(5).nth(1).unwrap().filters.symbols_mode);
+    }
 }
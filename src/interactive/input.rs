@@ -160,6 +160,7 @@ pub enum KeyCommand {
     PromptKind,
     PromptGlob,
     PromptExclude,
+    OpenFilterBar,
     ToggleExpand,
     ToggleContains,
     ClearLanguage,
@@ -167,6 +168,8 @@ pub enum KeyCommand {
 
     // Actions
     OpenInEditor,
+    CopyLocation,
+    CopyPreview,
     Reindex,
     ClearAndReindex,
     ShowHelp,
@@ -176,6 +179,16 @@ pub enum KeyCommand {
     HistoryPrev,
     HistoryNext,
 
+    // Dependency navigation
+    JumpToImporters,
+    JumpToImports,
+    NavBack,
+    NavForward,
+
+    // Context pane
+    ToggleContextPane,
+    NextPaneSection,
+
     // Char input (for text input mode)
     CharInput(char),
     Backspace,
@@ -233,11 +246,14 @@ impl KeyCommand {
             (KeyCode::Char('k'), KeyModifiers::NONE) => Self::PromptKind,
             (KeyCode::Char('g'), KeyModifiers::NONE) => Self::PromptGlob,
             (KeyCode::Char('x'), KeyModifiers::NONE) => Self::PromptExclude,
+            (KeyCode::Char('F'), KeyModifiers::SHIFT) => Self::OpenFilterBar,
             (KeyCode::Char('e'), KeyModifiers::NONE) => Self::ToggleExpand,
             (KeyCode::Char('c'), KeyModifiers::NONE) => Self::ToggleContains,
 
             // Actions
             (KeyCode::Char('o'), KeyModifiers::NONE) | (KeyCode::Enter, _) => Self::OpenInEditor,
+            (KeyCode::Char('y'), KeyModifiers::NONE) => Self::CopyLocation,
+            (KeyCode::Char('Y'), KeyModifiers::SHIFT) => Self::CopyPreview,
             (KeyCode::Char('i'), KeyModifiers::NONE) => Self::Reindex,
             (KeyCode::Char('I'), KeyModifiers::SHIFT) => Self::ClearAndReindex,
             (KeyCode::Char('?'), KeyModifiers::NONE) => Self::ShowHelp,
@@ -247,6 +263,16 @@ impl KeyCommand {
             (KeyCode::Char('p'), KeyModifiers::CONTROL) => Self::HistoryPrev,
             (KeyCode::Char('n'), KeyModifiers::CONTROL) => Self::HistoryNext,
 
+            // Dependency navigation
+            (KeyCode::Char('u'), KeyModifiers::NONE) => Self::JumpToImporters,
+            (KeyCode::Char('d'), KeyModifiers::NONE) => Self::JumpToImports,
+            (KeyCode::Char('b'), KeyModifiers::NONE) | (KeyCode::Left, _) => Self::NavBack,
+            (KeyCode::Char('f'), KeyModifiers::NONE) | (KeyCode::Right, _) => Self::NavForward,
+
+            // Context pane (outline and dependencies of the selected file)
+            (KeyCode::Char('p'), KeyModifiers::NONE) => Self::ToggleContextPane,
+            (KeyCode::Char('n'), KeyModifiers::NONE) => Self::NextPaneSection,
+
             _ => Self::None,
         }
     }
@@ -319,4 +345,52 @@ mod tests {
         let key = KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE);
         assert_eq!(KeyCommand::from_key(key, false), KeyCommand::FocusInput);
     }
+
+    #[test]
+    fn test_dependency_navigation_keys() {
+        let key = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::JumpToImporters);
+
+        let key = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::JumpToImports);
+
+        let key = KeyEvent::new(KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::NavBack);
+
+        // Typing in the search box is unaffected
+        let key = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, true), KeyCommand::None);
+    }
+
+    #[test]
+    fn test_context_pane_keys() {
+        let key = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::ToggleContextPane);
+
+        let key = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::NextPaneSection);
+
+        // Ctrl+P still recalls history
+        let key = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::HistoryPrev);
+    }
+
+    #[test]
+    fn test_copy_keys() {
+        let key = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::CopyLocation);
+
+        let key = KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::SHIFT);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::CopyPreview);
+    }
+
+    #[test]
+    fn test_filter_bar_key() {
+        let key = KeyEvent::new(KeyCode::Char('F'), KeyModifiers::SHIFT);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::OpenFilterBar);
+
+        // Lowercase f still goes forward
+        let key = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE);
+        assert_eq!(KeyCommand::from_key(key, false), KeyCommand::NavForward);
+    }
 }
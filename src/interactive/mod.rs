@@ -1,10 +1,14 @@
 // Interactive mode modules
 mod app;
 mod effects;
+mod external;
+mod filter_bar;
 mod filter_selector;
 mod history;
 mod input;
 mod mouse;
+mod navigation;
+mod pane;
 mod results;
 mod syntax;
 mod terminal;
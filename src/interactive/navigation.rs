@@ -0,0 +1,175 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::cache::CacheManager;
+use crate::dependency::DependencyIndex;
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+
+/// Which side of the dependency graph to jump to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpDirection {
+    /// Files that import the current file
+    Importers,
+    /// Files the current file imports
+    Imports,
+}
+
+/// A result list the user navigated away from
+#[derive(Debug, Clone)]
+pub struct NavEntry {
+    /// What the list shows (e.g. "search: parse", "importers of src/lib.rs")
+    pub label: String,
+    pub results: Vec<SearchResult>,
+    pub selected: usize,
+}
+
+/// Back/forward stacks of result lists (like a browser history)
+#[derive(Debug, Clone)]
+pub struct NavStack {
+    back: Vec<NavEntry>,
+    forward: Vec<NavEntry>,
+    max_depth: usize,
+}
+
+impl NavStack {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            back: Vec::new(),
+            forward: Vec::new(),
+            max_depth,
+        }
+    }
+
+    /// Record `current` before navigating somewhere new (drops the forward history)
+    pub fn push(&mut self, current: NavEntry) {
+        self.back.push(current);
+        if self.back.len() > self.max_depth {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    /// Go back, keeping `current` for [`NavStack::forward`]
+    pub fn back(&mut self, current: NavEntry) -> Option<NavEntry> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    /// Go forward again, keeping `current` for [`NavStack::back`]
+    pub fn forward(&mut self, current: NavEntry) -> Option<NavEntry> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+
+    /// Number of lists to go back to
+    pub fn depth(&self) -> usize {
+        self.back.len()
+    }
+}
+
+/// Files one import away from `path`, as results pointing at the import line
+///
+/// For importers the result points at the line importing `path`; for imports it
+/// points at the top of the imported file, with the import as preview.
+pub fn jump_targets(cache: &CacheManager, path: &str, direction: JumpDirection) -> Result<Vec<SearchResult>> {
+    let deps_index = DependencyIndex::new(cache.clone());
+    let file_id = deps_index
+        .get_file_id_by_path(path)?
+        .ok_or_else(|| anyhow::anyhow!("{} is not in the dependency index", path))?;
+
+    // Target file id -> (line to show, preview)
+    let mut targets: BTreeMap<i64, (usize, String)> = BTreeMap::new();
+    match direction {
+        JumpDirection::Importers => {
+            for importer in deps_index.get_dependents(file_id)? {
+                let import = deps_index
+                    .get_dependencies(importer)?
+                    .into_iter()
+                    .find(|dep| dep.resolved_file_id == Some(file_id));
+                let (line, preview) = import
+                    .map(|dep| (dep.line_number, format!("imports {}", dep.imported_path)))
+                    .unwrap_or((1, format!("imports {}", path)));
+                targets.entry(importer).or_insert((line, preview));
+            }
+        }
+        JumpDirection::Imports => {
+            for dep in deps_index.get_dependencies(file_id)? {
+                if let Some(target) = dep.resolved_file_id {
+                    targets
+                        .entry(target)
+                        .or_insert((1, format!("imported by {}:{} as {}", path, dep.line_number, dep.imported_path)));
+                }
+            }
+        }
+    }
+
+    let ids: Vec<i64> = targets.keys().copied().collect();
+    let paths = deps_index.get_file_paths(&ids)?;
+
+    let mut results: Vec<SearchResult> = targets
+        .into_iter()
+        .filter_map(|(id, (line, preview))| {
+            let target = paths.get(&id)?;
+            Some(SearchResult::new(
+                target.clone(),
+                Language::from_path(std::path::Path::new(target)),
+                SymbolKind::Unknown("file".to_string()),
+                None,
+                Span::new(line, 0, line, 0),
+                None,
+                preview,
+            ))
+        })
+        .collect();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str) -> NavEntry {
+        NavEntry {
+            label: label.to_string(),
+            results: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut nav = NavStack::new(10);
+        assert!(nav.back(entry("search")).is_none());
+
+        nav.push(entry("search"));
+        nav.push(entry("importers of a.rs"));
+        assert_eq!(nav.depth(), 2);
+
+        let previous = nav.back(entry("imports of b.rs")).unwrap();
+        assert_eq!(previous.label, "importers of a.rs");
+        let next = nav.forward(entry("importers of a.rs")).unwrap();
+        assert_eq!(next.label, "imports of b.rs");
+        assert!(nav.forward(entry("imports of b.rs")).is_none());
+    }
+
+    #[test]
+    fn test_push_clears_forward_and_caps_depth() {
+        let mut nav = NavStack::new(2);
+        nav.push(entry("one"));
+        nav.push(entry("two"));
+        nav.back(entry("three")).unwrap();
+
+        nav.push(entry("two"));
+        assert!(nav.forward(entry("four")).is_none());
+
+        nav.push(entry("four"));
+        assert_eq!(nav.depth(), 2);
+        assert_eq!(nav.back(entry("five")).unwrap().label, "four");
+        assert_eq!(nav.back(entry("four")).unwrap().label, "two");
+        assert!(nav.back(entry("two")).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::cache::CacheManager;
+use crate::locate;
+
+use super::navigation::{jump_targets, JumpDirection};
+
+/// Section of the context pane an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneSection {
+    /// Symbols defined in the file
+    Outline,
+    /// Files the file imports
+    Imports,
+    /// Files importing the file
+    Importers,
+}
+
+impl PaneSection {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PaneSection::Outline => "Outline",
+            PaneSection::Imports => "Imports",
+            PaneSection::Importers => "Imported by",
+        }
+    }
+}
+
+/// A selectable line in the context pane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaneEntry {
+    pub section: PaneSection,
+    pub label: String,
+    /// Nesting level within the outline (0 for dependencies)
+    pub depth: usize,
+    /// File and line to open when the entry is activated
+    pub path: String,
+    pub line: usize,
+}
+
+/// Outline and direct dependencies of one file, shown next to the results
+#[derive(Debug, Clone)]
+pub struct ContextPane {
+    path: String,
+    entries: Vec<PaneEntry>,
+    selected: usize,
+}
+
+impl ContextPane {
+    /// Build the pane for `path`
+    ///
+    /// Sections that can't be loaded (unsupported language, file missing from
+    /// the dependency index) are left empty.
+    pub fn load(cache: &CacheManager, path: &str) -> Self {
+        let mut entries = Vec::new();
+
+        match std::fs::read_to_string(path) {
+            Ok(source) => match locate::outline(path, &source) {
+                Ok(symbols) => entries.extend(outline_entries(path, &symbols)),
+                Err(e) => log::debug!("No outline for {}: {}", path, e),
+            },
+            Err(e) => log::debug!("Failed to read {}: {}", path, e),
+        }
+
+        let index_path = path.trim_start_matches("./");
+        for (section, direction) in [
+            (PaneSection::Imports, JumpDirection::Imports),
+            (PaneSection::Importers, JumpDirection::Importers),
+        ] {
+            match jump_targets(cache, index_path, direction) {
+                Ok(targets) => entries.extend(targets.into_iter().map(|t| PaneEntry {
+                    section,
+                    label: t.path.clone(),
+                    depth: 0,
+                    line: t.span.start_line,
+                    path: t.path,
+                })),
+                Err(e) => log::debug!("No dependencies for {}: {}", index_path, e),
+            }
+        }
+
+        Self {
+            path: path.to_string(),
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// File the pane describes
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Entries of one section, in display order
+    pub fn section(&self, section: PaneSection) -> impl Iterator<Item = (usize, &PaneEntry)> {
+        self.entries.iter().enumerate().filter(move |(_, e)| e.section == section)
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected(&self) -> Option<&PaneEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Move to the first entry of the next non-empty section (wrapping around)
+    pub fn next_section(&mut self) {
+        let Some(current) = self.selected().map(|e| e.section) else {
+            return;
+        };
+        let order = [PaneSection::Outline, PaneSection::Imports, PaneSection::Importers];
+        let start = order.iter().position(|s| *s == current).unwrap_or(0);
+        for offset in 1..=order.len() {
+            let section = order[(start + offset) % order.len()];
+            if let Some(idx) = self.entries.iter().position(|e| e.section == section) {
+                self.selected = idx;
+                return;
+            }
+        }
+    }
+}
+
+/// Outline entries with their nesting depth (number of enclosing symbols)
+fn outline_entries(path: &str, symbols: &[locate::EnclosingSymbol]) -> Vec<PaneEntry> {
+    symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| {
+            let depth = symbols[..i]
+                .iter()
+                .filter(|outer| {
+                    outer.span.start_line <= symbol.span.start_line
+                        && symbol.span.end_line <= outer.span.end_line
+                        && outer.span != symbol.span
+                })
+                .count();
+            PaneEntry {
+                section: PaneSection::Outline,
+                label: format!("{} {}", symbol.kind, symbol.name.as_deref().unwrap_or("<anonymous>")),
+                depth,
+                path: path.to_string(),
+                line: symbol.span.start_line,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Span, SymbolKind};
+
+    fn symbol(name: &str, kind: SymbolKind, start: usize, end: usize) -> locate::EnclosingSymbol {
+        locate::EnclosingSymbol {
+            kind,
+            name: Some(name.to_string()),
+            span: Span { start_line: start, end_line: end },
+            signature: None,
+        }
+    }
+
+    fn entry(section: PaneSection, label: &str) -> PaneEntry {
+        PaneEntry {
+            section,
+            label: label.to_string(),
+            depth: 0,
+            path: label.to_string(),
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn test_outline_depth() {
+        let symbols = vec![
+            symbol("Worker", SymbolKind::Struct, 1, 20),
+            symbol("run", SymbolKind::Method, 3, 8),
+            symbol("main", SymbolKind::Function, 22, 30),
+        ];
+        let entries = outline_entries("src/lib.rs", &symbols);
+
+        let depths: Vec<(usize, usize)> = entries.iter().map(|e| (e.line, e.depth)).collect();
+        assert_eq!(depths, vec![(1, 0), (3, 1), (22, 0)]);
+        assert_eq!(entries[1].label, "Method run");
+    }
+
+    #[test]
+    fn test_section_navigation() {
+        let mut pane = ContextPane {
+            path: "a.rs".to_string(),
+            entries: vec![
+                entry(PaneSection::Outline, "fn a"),
+                entry(PaneSection::Outline, "fn b"),
+                entry(PaneSection::Importers, "main.rs"),
+            ],
+            selected: 0,
+        };
+
+        pane.next_section();
+        assert_eq!(pane.selected().unwrap().label, "main.rs");
+        pane.next();
+        assert_eq!(pane.selected_index(), 2);
+        // Imports is empty, so wrapping goes back to the outline
+        pane.next_section();
+        assert_eq!(pane.selected_index(), 0);
+        pane.prev();
+        assert_eq!(pane.selected_index(), 0);
+    }
+}
@@ -179,9 +179,17 @@ mod tests {
             lang: Language::Rust,
             kind: SymbolKind::Function,
             symbol: Some("test".to_string()),
+            parent: None,
+            qualified_name: None,
             span: Span { start_line: line, end_line: line },
             preview: "test".to_string(),
             dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
         }
     }
 
@@ -51,8 +51,15 @@ impl SyntaxHighlighter {
             Language::Kotlin => ("kt", None),
             Language::Swift => ("swift", None),
             Language::Zig => ("zig", None),
+            Language::Shell => ("sh", None),
+            Language::Make => ("mk", None),
+            Language::CMake => ("cmake", None),             // Not in the default set
+            Language::Bazel => ("bzl", Some("py")),        // Starlark is Python-like
             Language::Vue => ("vue", Some("html")),      // Fallback to HTML
             Language::Svelte => ("svelte", Some("html")), // Fallback to HTML
+            Language::Sql => ("sql", None),
+            Language::GraphQL => ("graphql", Some("js")),  // Not in the default set
+            Language::Html => ("html", None),
             Language::Unknown => return None,
         };
 
@@ -293,6 +300,7 @@ mod tests {
             (Language::Ruby, "def main; end"),
             (Language::Kotlin, "fun main() {}"),
             (Language::Zig, "pub fn main() void {}"),
+            (Language::Shell, "main() { echo hi; }"),
             (Language::Vue, "<template></template>"),
             (Language::Svelte, "<script></script>"),
         ];
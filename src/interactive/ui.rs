@@ -35,7 +35,24 @@ pub fn render(f: &mut Frame, app: &mut InteractiveApp) {
                 selector.render(f, chunks[2], &theme);
             }
         }
-        AppMode::Indexing | AppMode::Normal => render_results_area(f, chunks[2], app),
+        AppMode::FilterBar => {
+            render_results_area(f, chunks[2], app);
+            // Render the filter bar on top, with the applied filters' result count
+            if let Some(bar) = app.filter_bar() {
+                bar.render(f, chunks[2], app.theme(), app.results().len());
+            }
+        }
+        AppMode::Indexing | AppMode::Normal => match app.context_pane() {
+            Some(pane) if !app.indexing() => {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                    .split(chunks[2]);
+                render_results_area(f, columns[0], app);
+                render_context_pane(f, columns[1], app, pane);
+            }
+            _ => render_results_area(f, chunks[2], app),
+        },
     }
 
     render_footer(f, chunks[3], app);
@@ -586,12 +603,18 @@ fn render_results_area(f: &mut Frame, area: Rect, app: &InteractiveApp) {
         // Show empty state with helpful instructions
         let empty_message = if app.input().value().trim().is_empty() {
             if matches!(app.focus_state(), FocusState::Input) {
-                "Start typing to search...\n\nKeyboard shortcuts:\n  j/k or ↓/↑ - Navigate results\n  / - Focus search\n  Esc/Enter - Unfocus search\n  ? - Show help\n  q - Quit"
+                let mut message = "Start typing to search...\n\nKeyboard shortcuts:\n  j/k or ↓/↑ - Navigate results\n  / - Focus search\n  Esc/Enter - Unfocus search\n  ? - Show help\n  q - Quit".to_string();
+                let recent: Vec<&str> = app.history().recent(5).map(|q| q.pattern.as_str()).collect();
+                if !recent.is_empty() {
+                    message.push_str("\n\nRecent queries (Ctrl+P to recall):\n  ");
+                    message.push_str(&recent.join("\n  "));
+                }
+                message
             } else {
-                "Press / to start typing a search query\nPress ? for full help"
+                "Press / to start typing a search query\nPress ? for full help".to_string()
             }
         } else {
-            "No results found. Try a different query.\n\nTip: Press / to edit your search"
+            "No results found. Try a different query.\n\nTip: Press / to edit your search".to_string()
         };
 
         let empty_text = Paragraph::new(empty_message)
@@ -769,6 +792,66 @@ fn render_results_area(f: &mut Frame, area: Rect, app: &InteractiveApp) {
     }
 }
 
+/// Outline, imports and importers of the selected result's file
+fn render_context_pane(f: &mut Frame, area: Rect, app: &InteractiveApp, pane: &super::pane::ContextPane) {
+    use super::pane::PaneSection;
+
+    let palette = &app.theme().palette;
+    let focused = matches!(app.focus_state(), FocusState::Pane);
+
+    let mut items: Vec<ListItem> = Vec::new();
+    // Row of the selected entry, to keep it on screen
+    let mut selected_row = 0;
+    for section in [PaneSection::Outline, PaneSection::Imports, PaneSection::Importers] {
+        let entries: Vec<_> = pane.section(section).collect();
+        if !items.is_empty() {
+            items.push(ListItem::new(""));
+        }
+        items.push(ListItem::new(Line::from(Span::styled(
+            format!("{} ({})", section.title(), entries.len()),
+            Style::default().fg(palette.accent).add_modifier(Modifier::BOLD),
+        ))));
+        if entries.is_empty() {
+            items.push(ListItem::new(Span::styled("  (none)", Style::default().fg(palette.muted))));
+        }
+
+        for (idx, entry) in entries {
+            let text = format!("{}{}", "  ".repeat(entry.depth + 1), entry.label);
+            let style = if idx == pane.selected_index() && focused {
+                selected_row = items.len();
+                Style::default().fg(Color::Black).bg(palette.highlight).add_modifier(Modifier::BOLD)
+            } else if section == PaneSection::Outline {
+                Style::default().fg(Color::Rgb(200, 150, 255)) // Same purple as symbol lines
+            } else {
+                Style::default().fg(palette.info)
+            };
+            items.push(ListItem::new(Span::styled(text, style)));
+        }
+    }
+
+    // Scroll so the selected entry stays visible
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (selected_row + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = items.into_iter().skip(skip).collect();
+
+    let file_name = std::path::Path::new(pane.path())
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(pane.path());
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", file_name))
+            .border_style(if focused {
+                Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette.muted)
+            }),
+    );
+
+    f.render_widget(list, area);
+}
+
 fn render_help_screen(f: &mut Frame, area: Rect, app: &InteractiveApp) {
     let palette = &app.theme().palette;
 
@@ -796,19 +879,33 @@ fn render_help_screen(f: &mut Frame, area: Rect, app: &InteractiveApp) {
         "    r             Toggle regex mode",
         "    l             Select language filter",
         "    k             Select symbol kind filter",
-        "    g             Add glob pattern (CLI only for now)",
-        "    x             Add exclude pattern (CLI only for now)",
+        "    g             Edit include globs (filter bar)",
+        "    x             Edit exclude globs (filter bar)",
+        "    F             Filter bar: edit all filters, see the result count, then apply",
         "    e             Toggle expand mode (full definitions)",
         "    c             Toggle contains mode (substring)",
         "    Ctrl+L        Clear language filter",
         "    Ctrl+K        Clear kind filter",
         "",
         "  Actions:",
-        "    o / Enter     Open file in $EDITOR / Expand preview",
+        "    o / Enter     Open file in $VISUAL/$EDITOR at the line / Expand preview",
+        "    y             Copy path:line to the clipboard",
+        "    Y             Copy the matched line to the clipboard",
         "    i             Trigger reindex",
         "    ?             Toggle this help screen",
         "    q / Ctrl+C    Quit",
         "",
+        "  Dependency navigation:",
+        "    u             Jump to files importing the current file",
+        "    d             Jump to files the current file imports",
+        "    b / ←         Back to the previous result list",
+        "    f / →         Forward again",
+        "",
+        "  Context pane:",
+        "    p             Toggle outline/dependency pane for the selected file",
+        "    Tab           Move focus into the pane (j/k to move, Enter to preview)",
+        "    n             Jump to the next pane section",
+        "",
         "  Mouse:",
         "    Click         Select result / Focus input / Toggle filters",
         "    Click status  Trigger reindex (top-right corner)",
@@ -937,6 +1034,18 @@ fn render_footer(f: &mut Frame, area: Rect, app: &InteractiveApp) {
             Span::styled("Esc", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
             Span::styled(" cancel", Style::default().fg(palette.muted)),
         ],
+        AppMode::FilterBar => vec![
+            Span::styled(
+                "[FILTER BAR] ",
+                Style::default()
+                    .fg(palette.info)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("Enter", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
+            Span::styled(" apply  ", Style::default().fg(palette.muted)),
+            Span::styled("Esc", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
+            Span::styled(" discard", Style::default().fg(palette.muted)),
+        ],
         AppMode::FilePreview => vec![
             Span::styled(
                 "[PREVIEW MODE] ",
@@ -948,7 +1057,9 @@ fn render_footer(f: &mut Frame, area: Rect, app: &InteractiveApp) {
             Span::styled("Esc", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
             Span::styled(" close  ", Style::default().fg(palette.muted)),
             Span::styled("o", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
-            Span::styled(" open in editor", Style::default().fg(palette.muted)),
+            Span::styled(" open in editor  ", Style::default().fg(palette.muted)),
+            Span::styled("y/Y", Style::default().fg(palette.accent).add_modifier(Modifier::BOLD)),
+            Span::styled(" copy location/line", Style::default().fg(palette.muted)),
         ],
         AppMode::Indexing | AppMode::Normal => {
             let mut spans = vec![];
@@ -979,6 +1090,14 @@ fn render_footer(f: &mut Frame, area: Rect, app: &InteractiveApp) {
                             .add_modifier(Modifier::BOLD),
                     ));
                 }
+                FocusState::Pane => {
+                    spans.push(Span::styled(
+                        "[PANE MODE] ",
+                        Style::default()
+                            .fg(palette.success)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
             }
 
             // Show appropriate hint based on terminal capabilities
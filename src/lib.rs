@@ -24,28 +24,79 @@
 //!
 //! println!("Indexed {} files", stats.total_files);
 //! ```
+// The MCP tool list is a single large json! literal
+#![recursion_limit = "256"]
 
+pub mod ast_presets;
 pub mod ast_query;
 pub mod background_indexer;
+pub mod blame;
 pub mod cache;
+pub mod cancel;
 pub mod cli;
+pub mod config_keys;
 pub mod context;
+pub mod dead_symbols;
+pub mod deprecations;
 pub mod content_store;
 pub mod dependency;
+pub mod embedded;
+pub mod embeddings;
+pub mod error_code;
+pub mod errors;
+pub mod flags;
 pub mod formatter;
+pub mod freq;
 pub mod git;
+pub mod graph_export;
+pub mod grouping;
+pub mod hierarchy;
+pub mod history;
+pub mod hooks;
+pub mod http_auth;
+pub mod impact;
 pub mod indexer;
+pub mod integrity;
 pub mod interactive;
 pub mod line_filter;
+pub mod lint;
+pub mod locate;
 pub mod mcp;
+pub mod migration;
 pub mod models;
+pub mod notebook;
+pub mod overlay;
+pub mod owners;
 pub mod output;
 pub mod parsers;
+pub mod paths;
+pub mod quarantine;
 pub mod query;
+pub mod query_dsl;
 pub mod regex_trigrams;
+pub mod related;
+pub mod replace;
+pub mod revision;
+pub mod routes;
+pub mod review;
+pub mod sarif;
+pub mod self_update;
 pub mod semantic;
+pub mod snapshot;
+pub mod structural;
 pub mod symbol_cache;
+pub mod symbol_dump;
+pub mod tables;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+pub mod todos;
+pub mod token_budget;
+pub mod tokens;
+pub mod tombstones;
+pub mod trace;
+pub mod transforms;
 pub mod trigram;
+pub mod tuning;
 pub mod watcher;
 
 // Re-export commonly used types
@@ -53,7 +104,8 @@ pub use cache::CacheManager;
 pub use indexer::Indexer;
 pub use models::{
     Dependency, DependencyInfo, FileGroupedResult, ImportType, IndexConfig, IndexStats, IndexStatus, IndexWarning,
-    IndexWarningDetails, IndexedFile, Language, MatchResult, QueryResponse, SearchResult, Span, SymbolKind,
+    IndexWarningDetails, IndexedFile, Language, MatchResult, QueryResponse, Relation, RelatedFile, SearchResult, Span,
+    SymbolKind,
 };
 pub use query::{QueryEngine, QueryFilter};
 pub use watcher::{watch, WatchConfig};
@@ -20,6 +20,9 @@
 
 use crate::models::Language;
 
+/// Characters that open (and decorate) a line or block comment
+const COMMENT_DELIMITERS: [char; 6] = ['/', '*', '#', '!', '<', '-'];
+
 /// Trait for language-specific line filtering
 pub trait LineFilter {
     /// Check if a position in a line is inside a comment
@@ -41,6 +44,41 @@ pub trait LineFilter {
     /// # Returns
     /// `true` if the pattern is definitely inside a string literal, `false` otherwise
     fn is_in_string(&self, line: &str, pattern_pos: usize) -> bool;
+
+    /// Extract the comment on a line, with its delimiters stripped
+    ///
+    /// Extraction counterpart of [`is_in_comment`](LineFilter::is_in_comment): the
+    /// comment starts at the first comment delimiter that is inside a comment but
+    /// not inside a string literal, and ends at a block comment's closing delimiter.
+    /// Continuation lines of block comments (` * text`) are recognized by their
+    /// leading `*`, since a single line carries no block state.
+    ///
+    /// # Returns
+    /// The comment text (possibly empty), or `None` if the line has no comment
+    fn extract_comment<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let trimmed = line.trim_start();
+        let continuation = trimmed.strip_prefix('*')
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+
+        let comment = match continuation {
+            Some(rest) => rest,
+            None => {
+                let start = line.char_indices()
+                    .filter(|(_, ch)| COMMENT_DELIMITERS.contains(ch))
+                    .map(|(i, _)| i)
+                    .find(|&i| self.is_in_comment(line, i) && !self.is_in_string(line, i))?;
+                &line[start..]
+            }
+        };
+
+        let comment = comment.trim_start().trim_start_matches(COMMENT_DELIMITERS);
+        let end = ["*/", "-->"].iter()
+            .filter_map(|close| comment.find(close))
+            .min()
+            .unwrap_or(comment.len());
+
+        Some(comment[..end].trim())
+    }
 }
 
 /// Get a LineFilter for a specific language
@@ -59,9 +97,11 @@ pub fn get_filter(lang: Language) -> Option<Box<dyn LineFilter>> {
         Language::CSharp => Some(Box::new(CSharpLineFilter)),
         Language::Kotlin => Some(Box::new(KotlinLineFilter)),
         Language::Zig => Some(Box::new(ZigLineFilter)),
+        Language::Shell => Some(Box::new(ShellLineFilter)),
+        Language::Sql => Some(Box::new(SqlLineFilter)),
         Language::Vue => Some(Box::new(VueLineFilter)),
         Language::Svelte => Some(Box::new(SvelteLineFilter)),
-        Language::Swift | Language::Unknown => None,
+        Language::Make | Language::CMake | Language::Bazel | Language::Swift | Language::GraphQL | Language::Html | Language::Unknown => None,
     }
 }
 
@@ -820,6 +860,118 @@ impl LineFilter for ZigLineFilter {
     }
 }
 
+// ============================================================================
+// Shell Line Filter
+// ============================================================================
+
+struct ShellLineFilter;
+
+impl ShellLineFilter {
+    /// Quote state at `pattern_pos`, or None if a comment starts before it
+    fn quoted_at(line: &str, pattern_pos: usize) -> Option<bool> {
+        let mut in_double_quote = false;
+        let mut in_single_quote = false;
+        let mut escaped = false;
+        let mut previous: Option<char> = None;
+
+        for (i, ch) in line.char_indices() {
+            if i >= pattern_pos {
+                break;
+            }
+
+            if escaped {
+                escaped = false;
+            } else {
+                match ch {
+                    // Single-quoted strings have no escapes
+                    '\\' if !in_single_quote => escaped = true,
+                    '"' if !in_single_quote => in_double_quote = !in_double_quote,
+                    '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+                    '#' if !in_double_quote && !in_single_quote && Self::starts_word(previous) => return None,
+                    _ => {}
+                }
+            }
+            previous = Some(ch);
+        }
+
+        Some(in_double_quote || in_single_quote)
+    }
+
+    /// `#` only starts a comment at the beginning of a word (`$#`, `${#array[@]}` and `a#b` are code)
+    fn starts_word(previous: Option<char>) -> bool {
+        previous.is_none_or(|p| p.is_whitespace() || matches!(p, ';' | '&' | '|' | '('))
+    }
+}
+
+impl LineFilter for ShellLineFilter {
+    fn is_in_comment(&self, line: &str, pattern_pos: usize) -> bool {
+        // Shell comments: # at the start of a word, outside quotes
+        match Self::quoted_at(line, pattern_pos) {
+            None => true,
+            Some(quoted) => {
+                !quoted
+                    && line.get(pattern_pos..).is_some_and(|rest| rest.starts_with('#'))
+                    && Self::starts_word(line[..pattern_pos].chars().next_back())
+            }
+        }
+    }
+
+    fn is_in_string(&self, line: &str, pattern_pos: usize) -> bool {
+        // Shell strings: "..." (with escapes and expansions) and '...' (literal)
+        Self::quoted_at(line, pattern_pos).unwrap_or(false)
+    }
+}
+
+// ============================================================================
+// SQL Line Filter
+// ============================================================================
+
+struct SqlLineFilter;
+
+impl SqlLineFilter {
+    /// Whether `pattern_pos` is inside a '...' string, or None if a `--` comment starts before it
+    fn quoted_at(line: &str, pattern_pos: usize) -> Option<bool> {
+        let mut in_string = false;
+
+        for (i, ch) in line.char_indices() {
+            if i >= pattern_pos {
+                break;
+            }
+
+            match ch {
+                // `''` escapes toggle twice
+                '\'' => in_string = !in_string,
+                '-' if !in_string && line[i..].starts_with("--") => return None,
+                _ => {}
+            }
+        }
+
+        Some(in_string)
+    }
+}
+
+impl LineFilter for SqlLineFilter {
+    fn is_in_comment(&self, line: &str, pattern_pos: usize) -> bool {
+        // SQL comments: -- outside strings, and /* ... */
+        let in_block = line.find("/*").is_some_and(|start| {
+            start <= pattern_pos && line.find("*/").is_none_or(|end| pattern_pos < end + 2)
+        });
+        if in_block {
+            return true;
+        }
+
+        match Self::quoted_at(line, pattern_pos) {
+            None => true,
+            Some(quoted) => !quoted && line.get(pattern_pos..).is_some_and(|rest| rest.starts_with("--")),
+        }
+    }
+
+    fn is_in_string(&self, line: &str, pattern_pos: usize) -> bool {
+        // SQL strings: '...' only, since "..." quotes identifiers (`CREATE TABLE "users"`)
+        Self::quoted_at(line, pattern_pos).unwrap_or(false)
+    }
+}
+
 // ============================================================================
 // Vue Line Filter (use JavaScript/TypeScript for <script> sections)
 // ============================================================================
@@ -995,4 +1147,68 @@ mod tests {
         let line = r#"string s = @"before "" extract_symbols after";"#;
         assert!(filter.is_in_string(line, 19));
     }
+
+    // ========================================================================
+    // Shell Tests
+    // ========================================================================
+
+    #[test]
+    fn test_shell_comment() {
+        let filter = ShellLineFilter;
+        let line = "deploy --force  # extract_symbols here";
+        assert!(filter.is_in_comment(line, 16));
+        assert!(filter.is_in_comment(line, 18));
+        assert!(!filter.is_in_comment(line, 0));
+
+        // `$#`, `${#items[@]}` and quoted `#` don't start comments
+        let line = r##"echo $# ${#items[@]} "#extract_symbols""##;
+        assert!(!filter.is_in_comment(line, 23));
+        assert!(filter.is_in_string(line, 23));
+    }
+
+    #[test]
+    fn test_shell_string() {
+        let filter = ShellLineFilter;
+        let line = r#"msg="say "hi" extract_symbols" && echo 'it''s'"#;
+        assert!(filter.is_in_string(line, 16));
+        assert!(!filter.is_in_string(line, 33));
+    }
+
+    #[test]
+    fn test_sql_comment_and_string() {
+        let filter = SqlLineFilter;
+        let line = "CREATE TABLE users ( -- extract_symbols";
+        assert!(filter.is_in_comment(line, 24));
+        assert!(!filter.is_in_comment(line, 13));
+
+        // Quoted identifiers are code, '--' inside strings is not a comment
+        let line = r#"INSERT INTO "users" VALUES ('it''s -- extract_symbols')"#;
+        assert!(!filter.is_in_string(line, 13));
+        assert!(filter.is_in_string(line, 38));
+        assert!(!filter.is_in_comment(line, 38));
+    }
+
+    // ========================================================================
+    // Comment Extraction Tests
+    // ========================================================================
+
+    #[test]
+    fn test_extract_comment() {
+        let rust = RustLineFilter;
+        assert_eq!(rust.extract_comment("let x = 5; // TODO: tidy up"), Some("TODO: tidy up"));
+        assert_eq!(rust.extract_comment("/// FIXME broken"), Some("FIXME broken"));
+        assert_eq!(rust.extract_comment("let x = /* HACK */ 5;"), Some("HACK"));
+        assert_eq!(rust.extract_comment(r#"let url = "http://example.com";"#), None);
+        assert_eq!(rust.extract_comment("let x = 5;"), None);
+
+        let c = CLineFilter;
+        assert_eq!(c.extract_comment("/* XXX: leaks */"), Some("XXX: leaks"));
+        assert_eq!(c.extract_comment("   * TODO second line"), Some("TODO second line"));
+        assert_eq!(c.extract_comment("   */"), None);
+        assert_eq!(c.extract_comment("*ptr = 0;"), None);
+
+        let python = PythonLineFilter;
+        assert_eq!(python.extract_comment("x = 1  # TODO(ana): remove"), Some("TODO(ana): remove"));
+        assert_eq!(python.extract_comment(r##"s = "#TODO""##), None);
+    }
 }
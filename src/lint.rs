@@ -0,0 +1,352 @@
+//! Project lint rules (`rfx lint`)
+//!
+//! Rule packs are TOML files in `.reflex/rules/`. Each `[[rule]]` pairs one
+//! matcher with a severity and message, and `rfx lint` runs every rule over
+//! the index and reports the matches as diagnostics:
+//!
+//! ```toml
+//! [[rule]]
+//! id = "no-unwrap"
+//! message = "Handle the error instead of calling unwrap()"
+//! severity = "error"            # error, warning (default) or note
+//! lang = "rust"
+//! preset = "unwrap-calls"       # or ast = "...", pattern = "...", regex = "..."
+//! glob = ["src/**"]
+//! exclude = ["src/bin/**"]
+//! ```
+//!
+//! The matcher is exactly one of:
+//! - `ast`: a Tree-sitter query, as for `rfx query --ast`
+//! - `preset`: a named AST preset (see `ast_presets`)
+//! - `pattern`: a structural pattern with metavariables (see `structural`)
+//! - `regex`: a regular expression over file content (`lang` is optional)
+//!
+//! The AST matchers need `lang`. Files are read in name order and rule ids must
+//! be unique across packs.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::models::Language;
+use crate::query::{QueryEngine, QueryFilter};
+use crate::sarif::{FileLocation, Finding, Level, SarifRule};
+
+/// Directory under `.reflex/` holding rule packs
+pub const RULES_DIR: &str = "rules";
+
+/// How serious a rule's diagnostics are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[serde(alias = "info")]
+    Note,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+impl From<Severity> for Level {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Note => Level::Note,
+            Severity::Warning => Level::Warning,
+            Severity::Error => Level::Error,
+        }
+    }
+}
+
+/// A rule from a rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintRule {
+    pub id: String,
+    pub message: String,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Language>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ast: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// Only lint files matching any of these globs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glob: Vec<String>,
+    /// Never lint files matching any of these globs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Rule pack the rule was read from
+    #[serde(skip_deserializing)]
+    pub source: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulePack {
+    #[serde(default, rename = "rule")]
+    rules: Vec<LintRule>,
+}
+
+/// What a rule searches with
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Matcher {
+    /// Tree-sitter query
+    Ast(String),
+    Regex(String),
+}
+
+impl LintRule {
+    /// Check that the rule has exactly one matcher and the language it needs
+    fn validate(&self) -> Result<()> {
+        let matchers = [&self.ast, &self.preset, &self.pattern, &self.regex];
+        match matchers.iter().filter(|m| m.is_some()).count() {
+            1 => {}
+            0 => anyhow::bail!("rule '{}' has no matcher: set one of ast, preset, pattern or regex", self.id),
+            _ => anyhow::bail!("rule '{}' has several matchers: set only one of ast, preset, pattern or regex", self.id),
+        }
+        if self.regex.is_none() && self.lang.is_none() {
+            anyhow::bail!("rule '{}' needs `lang` for its AST matcher", self.id);
+        }
+        Ok(())
+    }
+
+    fn matcher(&self, cache_path: &Path) -> Result<Matcher> {
+        let lang = self.lang.unwrap_or_default();
+        Ok(if let Some(ast) = &self.ast {
+            Matcher::Ast(ast.clone())
+        } else if let Some(preset) = &self.preset {
+            Matcher::Ast(crate::ast_presets::find_preset(cache_path, preset, lang)?.pattern)
+        } else if let Some(pattern) = &self.pattern {
+            Matcher::Ast(crate::structural::compile(pattern, lang)?)
+        } else {
+            Matcher::Regex(self.regex.clone().unwrap_or_default())
+        })
+    }
+}
+
+/// A rule match
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    pub path: String,
+    pub line: usize,
+    pub end_line: usize,
+    pub preview: String,
+}
+
+/// Diagnostics of a lint run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    /// Rules that ran
+    pub rules: usize,
+    /// Diagnostics in path, line and rule order
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// Number of diagnostics with `severity`
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == severity).count()
+    }
+}
+
+/// Read the rule packs in `<cache_path>/rules/`
+pub fn load_rules(cache_path: &Path) -> Result<Vec<LintRule>> {
+    let dir = cache_path.join(RULES_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    files.retain(|path| path.extension().and_then(|e| e.to_str()) == Some("toml"));
+    files.sort();
+
+    let mut rules: Vec<LintRule> = Vec::new();
+    let mut ids = HashSet::new();
+    for path in files {
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read rule pack {}", path.display()))?;
+        let pack: RulePack = toml::from_str(&text)
+            .with_context(|| format!("Invalid rule pack {}", path.display()))?;
+
+        for mut rule in pack.rules {
+            rule.validate().with_context(|| format!("Invalid rule pack {}", path.display()))?;
+            if !ids.insert(rule.id.clone()) {
+                anyhow::bail!("Duplicate lint rule id '{}' in {}", rule.id, path.display());
+            }
+            rule.source = path.clone();
+            rules.push(rule);
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Run `rules` over the index of `engine`
+pub fn run(engine: &QueryEngine, cache_path: &Path, rules: &[LintRule]) -> Result<LintReport> {
+    let mut diagnostics = Vec::new();
+
+    for rule in rules {
+        let matcher = rule.matcher(cache_path)
+            .with_context(|| format!("Rule '{}' ({})", rule.id, rule.source.display()))?;
+        let filter = QueryFilter {
+            language: rule.lang,
+            use_ast: matches!(matcher, Matcher::Ast(_)),
+            use_regex: matches!(matcher, Matcher::Regex(_)),
+            glob_patterns: rule.glob.clone(),
+            exclude_patterns: rule.exclude.clone(),
+            limit: None,
+            force: true,
+            suppress_output: true,
+            ..Default::default()
+        };
+        let results = match &matcher {
+            Matcher::Ast(query) => engine.search_ast_all_files(query, filter),
+            Matcher::Regex(regex) => engine.search(regex, filter),
+        }
+        .with_context(|| format!("Rule '{}' ({})", rule.id, rule.source.display()))?;
+
+        let mut seen = HashSet::new();
+        for result in results {
+            if !seen.insert((result.path.clone(), result.span.start_line, result.span.end_line)) {
+                continue;
+            }
+            diagnostics.push(Diagnostic {
+                rule: rule.id.clone(),
+                severity: rule.severity,
+                message: rule.message.clone(),
+                path: result.path.strip_prefix("./").unwrap_or(&result.path).to_string(),
+                line: result.span.start_line,
+                end_line: result.span.end_line,
+                preview: result.preview.lines().next().unwrap_or_default().trim().to_string(),
+            });
+        }
+    }
+
+    diagnostics.sort_by(|a, b| (&a.path, a.line, &a.rule).cmp(&(&b.path, b.line, &b.rule)));
+    Ok(LintReport { rules: rules.len(), diagnostics })
+}
+
+/// SARIF rules and findings of a lint run
+pub fn sarif_findings(rules: &[LintRule], report: &LintReport) -> (Vec<SarifRule>, Vec<Finding>) {
+    let sarif_rules = rules
+        .iter()
+        .map(|rule| SarifRule {
+            id: rule.id.clone(),
+            name: rule.id.clone(),
+            short_description: rule.message.clone(),
+            full_description: None,
+            level: rule.severity.into(),
+        })
+        .collect();
+    let findings = report
+        .diagnostics
+        .iter()
+        .map(|d| Finding {
+            rule_id: d.rule.clone(),
+            level: d.severity.into(),
+            message: d.message.clone(),
+            location: FileLocation::new(&d.path, Some(d.line)),
+            related: Vec::new(),
+        })
+        .collect();
+    (sarif_rules, findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const PACK: &str = r#"
+[[rule]]
+id = "no-unwrap"
+message = "Handle the error instead of calling unwrap()"
+severity = "error"
+lang = "rust"
+preset = "unwrap-calls"
+
+[[rule]]
+id = "todo-owner"
+message = "TODOs need an owner"
+regex = 'TODO[^(]'
+exclude = ["**/vendor/**"]
+"#;
+
+    #[test]
+    fn test_rule_validation() {
+        let parse = |toml: &str| -> Result<()> {
+            let pack: RulePack = toml::from_str(toml)?;
+            pack.rules.iter().try_for_each(LintRule::validate)
+        };
+
+        assert!(parse(PACK).is_ok());
+        let err = parse("[[rule]]\nid = \"a\"\nmessage = \"m\"\n").unwrap_err();
+        assert!(err.to_string().contains("has no matcher"));
+        let err = parse("[[rule]]\nid = \"a\"\nmessage = \"m\"\nast = \"(x) @x\"\n").unwrap_err();
+        assert!(err.to_string().contains("needs `lang`"));
+        assert!(parse("[[rule]]\nid = \"a\"\nmessage = \"m\"\nregex = \"x\"\nast = \"(x) @x\"\nlang = \"rust\"\n").is_err());
+        // Typos are errors rather than silently ignored settings
+        assert!(parse("[[rule]]\nid = \"a\"\nmessage = \"m\"\nregexp = \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn test_lint_reports_rule_matches() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(
+            root.join("main.rs"),
+            "fn main() {\n    let n: i32 = \"4\".parse().unwrap();\n    // TODO tidy up\n    // TODO(ana) fine\n}\n",
+        ).unwrap();
+        fs::write(root.join("vendor/lib.rs"), "// TODO upstream\npub fn f() {}\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+        fs::create_dir_all(cache.path().join(RULES_DIR)).unwrap();
+        fs::write(cache.path().join(RULES_DIR).join("style.toml"), PACK).unwrap();
+
+        let rules = load_rules(cache.path()).unwrap();
+        assert_eq!(rules.len(), 2);
+        let report = run(&QueryEngine::new(cache.clone()), cache.path(), &rules).unwrap();
+
+        let found: Vec<(&str, usize, Severity)> = report.diagnostics
+            .iter()
+            .map(|d| (d.rule.as_str(), d.line, d.severity))
+            .collect();
+        assert_eq!(found, vec![("no-unwrap", 2, Severity::Error), ("todo-owner", 3, Severity::Warning)]);
+        assert!(report.diagnostics[0].path.ends_with("main.rs"));
+        assert_eq!(report.count(Severity::Error), 1);
+
+        let (sarif_rules, findings) = sarif_findings(&rules, &report);
+        let log = serde_json::to_value(crate::sarif::SarifLog::new(&sarif_rules, &findings)).unwrap();
+        assert_eq!(log["runs"][0]["results"][0]["ruleId"], "no-unwrap");
+        assert_eq!(log["runs"][0]["results"][0]["level"], "error");
+        assert_eq!(log["runs"][0]["results"][1]["ruleIndex"], 1);
+    }
+}
@@ -0,0 +1,347 @@
+//! Symbol-at-location lookup
+//!
+//! `rfx at <path>:<line>[:<col>]` answers "which symbol is this line in?" for
+//! stack traces, compiler diagnostics and diff hunks. The file is parsed on
+//! the fly with the regular tree-sitter parsers (no index needed), and the
+//! result lists the innermost symbol whose span contains the line plus the
+//! symbols enclosing it, outermost first.
+//!
+//! Spans are line-based, so the column only matters when several symbols
+//! share the same lines (e.g. two one-line functions): the symbol whose name
+//! starts closest before the column wins.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::error_code::ReflexError;
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+use crate::parsers::ParserFactory;
+
+/// A `<path>:<line>[:<col>]` location (1-indexed line and column)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: String,
+    pub line: usize,
+    pub col: Option<usize>,
+}
+
+impl Location {
+    /// Parse `path:line` or `path:line:col`
+    ///
+    /// Numbers are taken from the end, so paths containing ':' (e.g. Windows
+    /// drive letters) are kept intact.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow::anyhow!(
+                "Invalid location '{}'.\n\
+                 \n\
+                 Expected <path>:<line> or <path>:<line>:<col>, e.g. src/main.rs:42",
+                spec
+            )
+        };
+        let number = |s: &str| s.parse::<usize>().ok().filter(|n| *n > 0);
+
+        let (rest, last) = spec.rsplit_once(':').ok_or_else(invalid)?;
+        let last = number(last).ok_or_else(invalid)?;
+
+        let (path, line, col) = match rest.rsplit_once(':') {
+            Some((path, line)) if number(line).is_some() && !path.is_empty() => {
+                (path, number(line).unwrap(), Some(last))
+            }
+            _ => (rest, last, None),
+        };
+
+        if path.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self { path: path.to_string(), line, col })
+    }
+}
+
+/// A symbol containing the requested location
+#[derive(Debug, Clone, Serialize)]
+pub struct EnclosingSymbol {
+    pub kind: SymbolKind,
+    pub name: Option<String>,
+    pub span: Span,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl From<&SearchResult> for EnclosingSymbol {
+    fn from(result: &SearchResult) -> Self {
+        Self {
+            kind: result.kind.clone(),
+            name: result.symbol.clone(),
+            span: result.span.clone(),
+            signature: result.signature.clone(),
+        }
+    }
+}
+
+/// Result of a symbol-at-location lookup
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolAt {
+    pub path: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub col: Option<usize>,
+    /// Innermost symbol containing the line (None at top level)
+    pub symbol: Option<EnclosingSymbol>,
+    /// Symbols enclosing `symbol`, outermost first
+    pub ancestors: Vec<EnclosingSymbol>,
+}
+
+/// Find the innermost symbol containing `location` by parsing its file
+pub fn symbol_at(location: &Location) -> Result<SymbolAt> {
+    let source = std::fs::read_to_string(&location.path)
+        .with_context(|| format!("Failed to read {}", location.path))?;
+    symbol_in_source(&location.path, &source, location.line, location.col)
+}
+
+/// Like [`symbol_at`], for file content that is already loaded (e.g. from the index)
+pub fn symbol_in_source(path: &str, source: &str, line: usize, col: Option<usize>) -> Result<SymbolAt> {
+    let line_count = source.lines().count();
+    if line > line_count {
+        anyhow::bail!(
+            "Line {} is past the end of {} ({} lines)",
+            line,
+            path,
+            line_count
+        );
+    }
+
+    let language = Language::from_path(Path::new(path));
+    if !language.is_supported() {
+        anyhow::bail!(
+            "Cannot parse {}: unsupported language.\n\
+             \n\
+             Symbol lookup needs a tree-sitter parser. Run 'rfx query --help' for the supported languages.",
+            path
+        );
+    }
+
+    let symbols = ParserFactory::parse_guarded(path, source, language)?;
+    let line_text = source.lines().nth(line - 1).unwrap_or("");
+    let (symbol, ancestors) = enclosing_symbols(&symbols, line, col, line_text);
+
+    Ok(SymbolAt {
+        path: path.to_string(),
+        line,
+        col,
+        symbol: symbol.map(EnclosingSymbol::from),
+        ancestors: ancestors.into_iter().map(EnclosingSymbol::from).collect(),
+    })
+}
+
+/// Every structural symbol of a file, in source order
+///
+/// Attribute uses, imports and local variables inside functions are left out,
+/// the same as for [`symbol_in_source`].
+pub fn outline(path: &str, source: &str) -> Result<Vec<EnclosingSymbol>> {
+    let language = Language::from_path(Path::new(path));
+    if !language.is_supported() {
+        anyhow::bail!("Cannot outline {}: unsupported language", path);
+    }
+
+    let symbols = ParserFactory::parse_guarded(path, source, language)?;
+    let functions: Vec<&Span> = symbols
+        .iter()
+        .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
+        .map(|s| &s.span)
+        .collect();
+
+    let mut outline: Vec<&SearchResult> = symbols
+        .iter()
+        .filter(|s| !matches!(s.kind, SymbolKind::Import | SymbolKind::Export | SymbolKind::Attribute))
+        .filter(|s| {
+            !matches!(s.kind, SymbolKind::Variable)
+                || !functions.iter().any(|f| f.start_line <= s.span.start_line && s.span.end_line <= f.end_line)
+        })
+        .collect();
+    outline.sort_by_key(|s| (s.span.start_line, std::cmp::Reverse(s.span.end_line), !matches!(s.kind, SymbolKind::Method)));
+    // Methods are also reported as functions by some parsers; keep the method
+    outline.dedup_by(|a, b| a.span == b.span && a.symbol == b.symbol);
+
+    Ok(outline.into_iter().map(EnclosingSymbol::from).collect())
+}
+
+/// Outline of an indexed file, read from the content store
+///
+/// `path` is relative to the workspace root. Files that aren't in the index
+/// (including paths escaping the workspace) are reported as not found, so
+/// callers such as `GET /outline` never read arbitrary files from disk.
+pub fn indexed_outline(cache: &CacheManager, path: &str) -> Result<Vec<EnclosingSymbol>> {
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing("Index not found. Run 'rfx index' first.".to_string()).into());
+    }
+    let reader = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+    let file_id = reader
+        .file_id_for_workspace_path(path, &cache.workspace_root())
+        .ok_or_else(|| ReflexError::NotFound(format!("File '{}' not found in index", path)))?;
+    outline(path, reader.get_file_content(file_id)?)
+}
+
+/// Innermost symbol containing `line` and its ancestors (outermost first)
+pub(crate) fn enclosing_symbols<'a>(
+    symbols: &'a [SearchResult],
+    line: usize,
+    col: Option<usize>,
+    line_text: &str,
+) -> (Option<&'a SearchResult>, Vec<&'a SearchResult>) {
+    // Imports, exports and attribute uses annotate code rather than contain it
+    let mut containing: Vec<&SearchResult> = symbols
+        .iter()
+        .filter(|s| !matches!(s.kind, SymbolKind::Import | SymbolKind::Export | SymbolKind::Attribute))
+        .filter(|s| s.span.start_line <= line && line <= s.span.end_line)
+        .collect();
+
+    // Local bindings inside a function are not structural context
+    if containing.iter().any(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method)) {
+        containing.retain(|s| !matches!(s.kind, SymbolKind::Variable));
+    }
+
+    // Outermost first. Among equal spans the name closest before the column
+    // sorts last, and methods win over the same item reported as a function.
+    let name_offset = |s: &SearchResult| -> usize {
+        let Some(col) = col else { return 0 };
+        let Some(name) = s.symbol.as_deref().filter(|n| !n.is_empty()) else { return 0 };
+        let mut end = col.saturating_sub(1).min(line_text.len());
+        while !line_text.is_char_boundary(end) {
+            end -= 1;
+        }
+        line_text[..end].rfind(name).map_or(0, |i| i + 1)
+    };
+    containing.sort_by_key(|s| {
+        (
+            std::cmp::Reverse(s.span.end_line - s.span.start_line),
+            name_offset(s),
+            matches!(s.kind, SymbolKind::Method),
+        )
+    });
+
+    match containing.pop() {
+        Some(innermost) => {
+            let ancestors = containing
+                .into_iter()
+                .filter(|s| s.span.start_line <= innermost.span.start_line && innermost.span.end_line <= s.span.end_line)
+                .filter(|s| s.span != innermost.span)
+                .collect();
+            (Some(innermost), ancestors)
+        }
+        None => (None, Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = "mod outer {\n    pub struct Point { x: i32 }\n\n    impl Point {\n        pub fn norm(&self) -> i32 {\n            let doubled = self.x * 2;\n            doubled\n        }\n    }\n}\n\nfn a() {} fn b() {}\n";
+
+    fn lookup(temp: &TempDir, spec: &str) -> SymbolAt {
+        let spec = format!("{}/{}", temp.path().display(), spec);
+        symbol_at(&Location::parse(&spec).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            Location::parse("src/main.rs:42").unwrap(),
+            Location { path: "src/main.rs".into(), line: 42, col: None }
+        );
+        assert_eq!(
+            Location::parse("C:\\src\\main.rs:42:7").unwrap(),
+            Location { path: "C:\\src\\main.rs".into(), line: 42, col: Some(7) }
+        );
+        assert!(Location::parse("src/main.rs").is_err());
+        assert!(Location::parse("src/main.rs:0").is_err());
+        assert!(Location::parse(":12").is_err());
+    }
+
+    #[test]
+    fn test_innermost_symbol_and_ancestors() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("lib.rs"), SOURCE).unwrap();
+
+        let found = lookup(&temp, "lib.rs:7");
+        let symbol = found.symbol.unwrap();
+        assert_eq!(symbol.name.as_deref(), Some("norm"));
+        assert!(matches!(symbol.kind, SymbolKind::Method));
+        assert_eq!((symbol.span.start_line, symbol.span.end_line), (5, 8));
+
+        let ancestors: Vec<_> = found.ancestors.iter().map(|s| s.name.as_deref().unwrap()).collect();
+        assert_eq!(ancestors, vec!["outer"]);
+
+        // Blank line inside the module: the module itself is innermost
+        let found = lookup(&temp, "lib.rs:3");
+        assert_eq!(found.symbol.unwrap().name.as_deref(), Some("outer"));
+        assert!(found.ancestors.is_empty());
+
+        // Top level
+        assert!(lookup(&temp, "lib.rs:11").symbol.is_none());
+    }
+
+    #[test]
+    fn test_column_disambiguates_symbols_on_one_line() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("lib.rs"), SOURCE).unwrap();
+
+        assert_eq!(lookup(&temp, "lib.rs:12:5").symbol.unwrap().name.as_deref(), Some("a"));
+        assert_eq!(lookup(&temp, "lib.rs:12:15").symbol.unwrap().name.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_outline_lists_structural_symbols_in_order() {
+        let names: Vec<_> = outline("lib.rs", SOURCE)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.name.unwrap(), s.kind.to_string()))
+            .collect();
+        assert_eq!(names, vec![
+            ("outer".to_string(), "Module".to_string()),
+            ("Point".to_string(), "Struct".to_string()),
+            ("norm".to_string(), "Method".to_string()),
+            ("a".to_string(), "Function".to_string()),
+            ("b".to_string(), "Function".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_line_past_end_of_file() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("lib.rs"), SOURCE).unwrap();
+
+        let spec = format!("{}/lib.rs:99", temp.path().display());
+        assert!(symbol_at(&Location::parse(&spec).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_indexed_outline_refuses_paths_outside_index() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("lib.rs"), SOURCE).unwrap();
+        fs::write(temp.path().join("secret.rs"), "fn secret() {}\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        crate::indexer::Indexer::new(CacheManager::new(&project), crate::models::IndexConfig::default())
+            .index(&project, false)
+            .unwrap();
+
+        assert_eq!(indexed_outline(&cache, "lib.rs").unwrap().len(), 5);
+
+        let outside = temp.path().join("secret.rs");
+        for path in ["../secret.rs", outside.to_str().unwrap(), "/etc/passwd"] {
+            let err = indexed_outline(&cache, path).unwrap_err();
+            assert!(matches!(err.downcast_ref::<ReflexError>(), Some(ReflexError::NotFound(_))), "{}: {}", path, err);
+        }
+    }
+}
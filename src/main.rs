@@ -3,14 +3,23 @@
 use clap::Parser;
 
 use reflex::cli::Cli;
+use reflex::error_code;
 use reflex::output;
 
 fn main() {
     let cli = Cli::parse();
 
     if let Err(e) = cli.execute() {
-        // Display error in red with clean formatting
-        output::error(&format!("Error: {:#}", e));
+        // JSON callers get the error as JSON on stdout, with its stable code
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if error_code::wants_json(&args) {
+            if let Ok(json) = serde_json::to_string(&error_code::ErrorBody::from_error(&e)) {
+                println!("{}", json);
+            }
+        } else {
+            // Display error in red with clean formatting
+            output::error(&format!("Error: {:#}", e));
+        }
         std::process::exit(1);
     }
 }
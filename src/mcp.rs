@@ -2,17 +2,26 @@
 //!
 //! This module implements the MCP protocol directly over stdio using JSON-RPC 2.0.
 //! It exposes Reflex's code search capabilities as MCP tools for AI coding assistants.
+//!
+//! Indexed files are also exposed as MCP resources, served from the content
+//! store so they match the snapshot search results came from:
+//! - `reflex://file/<path>`: file content
+//! - `reflex://outline/<path>`: symbol outline (JSON, same as `GET /outline`)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
 use crate::dependency::DependencyIndex;
 use crate::indexer::Indexer;
 use crate::models::{IndexConfig, Language, SymbolKind};
+use crate::parsers::span::SpanPolicy;
+use crate::parsers::visibility::Visibility;
 use crate::query::{QueryEngine, QueryFilter};
 
 /// JSON-RPC 2.0 request
@@ -45,43 +54,102 @@ struct JsonRpcError {
     data: Option<Value>,
 }
 
-/// Parse language string to Language enum
-fn parse_language(lang: Option<String>) -> Option<Language> {
-    lang.as_deref().and_then(|s| match s.to_lowercase().as_str() {
-        "rust" | "rs" => Some(Language::Rust),
-        "javascript" | "js" => Some(Language::JavaScript),
-        "typescript" | "ts" => Some(Language::TypeScript),
-        "vue" => Some(Language::Vue),
-        "svelte" => Some(Language::Svelte),
-        "php" => Some(Language::PHP),
-        "python" | "py" => Some(Language::Python),
-        "go" => Some(Language::Go),
-        "java" => Some(Language::Java),
-        "c" => Some(Language::C),
-        "cpp" | "c++" => Some(Language::Cpp),
-        _ => None,
-    })
+/// `[mcp]` section of `.reflex/config.toml`
+///
+/// Lets server operators trim the tool surface and bound results, e.g. on a
+/// shared read-only deployment:
+///
+/// ```toml
+/// [mcp]
+/// disabled_tools = ["index_project"]
+///
+/// [mcp.tools.search_code]
+/// limit = 50
+/// timeout = 10
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct McpConfig {
+    /// Tools to expose (empty = all)
+    #[serde(default)]
+    pub enabled_tools: Vec<String>,
+    /// Tools to hide, applied after `enabled_tools`
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+    /// Per-tool defaults, keyed by tool name
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolSettings>,
 }
 
-/// Parse symbol kind string to SymbolKind enum
-fn parse_symbol_kind(kind: Option<String>) -> Option<SymbolKind> {
-    kind.as_deref().and_then(|s| {
-        let capitalized = {
-            let mut chars = s.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first
-                    .to_uppercase()
-                    .chain(chars.flat_map(|c| c.to_lowercase()))
-                    .collect(),
-            }
+/// Per-tool defaults from `[mcp.tools.<name>]`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ToolSettings {
+    /// Result limit used when the client doesn't pass `limit`
+    pub limit: Option<usize>,
+    /// Upper bound on `limit`, even when the client asks for more
+    pub max_limit: Option<usize>,
+    /// Query timeout in seconds (search tools only)
+    pub timeout: Option<u64>,
+}
+
+impl McpConfig {
+    /// Whether `tool` is exposed by this server
+    pub fn is_enabled(&self, tool: &str) -> bool {
+        (self.enabled_tools.is_empty() || self.enabled_tools.iter().any(|t| t == tool))
+            && !self.disabled_tools.iter().any(|t| t == tool)
+    }
+
+    /// Defaults configured for `tool`
+    pub fn settings(&self, tool: &str) -> ToolSettings {
+        self.tools.get(tool).copied().unwrap_or_default()
+    }
+
+    /// Fill in the configured default `limit` of `tool` and clamp it to `max_limit`
+    fn apply_limit(&self, tool: &str, arguments: &mut Value) {
+        let settings = self.settings(tool);
+        let requested = arguments["limit"].as_u64().or(settings.limit.map(|n| n as u64));
+        let effective = match (requested, settings.max_limit) {
+            (Some(n), Some(max)) => Some(n.min(max as u64)),
+            (None, Some(max)) => Some(max as u64),
+            (n, None) => n,
         };
+        if let Some(limit) = effective {
+            arguments["limit"] = json!(limit);
+        }
+    }
+}
+
+/// Load the `[mcp]` section from `.reflex/config.toml` in `cache_path`
+///
+/// Falls back to defaults (every tool enabled, no overrides) if the file or
+/// section is missing.
+pub fn load_mcp_config(cache_path: &Path) -> Result<McpConfig> {
+    let config_path = cache_path.join(crate::cache::CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(McpConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
 
-        capitalized
-            .parse::<SymbolKind>()
-            .ok()
-            .or_else(|| Some(SymbolKind::Unknown(s.to_string())))
-    })
+    match toml_value.get("mcp") {
+        Some(mcp_table) => mcp_table.clone().try_into()
+            .context("Failed to parse [mcp] section in .reflex/config.toml"),
+        None => Ok(McpConfig::default()),
+    }
+}
+
+/// Parse language string to Language enum
+fn parse_language(lang: Option<String>) -> Option<Language> {
+    lang.as_deref().and_then(Language::from_name)
+}
+
+/// Parse a comma-separated symbol kind list (`function,struct`) to SymbolKind enums
+fn parse_symbol_kinds(kind: Option<String>) -> Vec<SymbolKind> {
+    kind.as_deref().map(SymbolKind::parse_list).unwrap_or_default()
 }
 
 /// Handle initialize request
@@ -89,7 +157,8 @@ fn handle_initialize(_params: Option<Value>) -> Result<Value> {
     Ok(json!({
         "protocolVersion": "2024-11-05",
         "capabilities": {
-            "tools": {}
+            "tools": {},
+            "resources": {}
         },
         "serverInfo": {
             "name": "reflex",
@@ -99,8 +168,8 @@ fn handle_initialize(_params: Option<Value>) -> Result<Value> {
 }
 
 /// Handle tools/list request
-fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
-    Ok(json!({
+fn handle_list_tools(_params: Option<Value>, config: &McpConfig) -> Result<Value> {
+    let mut tools = json!({
         "tools": [
             {
                 "name": "list_locations",
@@ -158,7 +227,11 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                         },
                         "kind": {
                             "type": "string",
-                            "description": "Filter by symbol kind (function, class, etc.)"
+                            "description": "Filter by symbol kind (function, class, etc.), or a comma-separated list (struct,enum,trait)"
+                        },
+                        "kind_not": {
+                            "type": "string",
+                            "description": "Leave out symbols of these kinds (comma-separated, e.g. import,variable)"
                         },
                         "file": {
                             "type": "string",
@@ -198,7 +271,11 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                         },
                         "kind": {
                             "type": "string",
-                            "description": "Filter by symbol kind (function, class, struct, etc.)"
+                            "description": "Filter by symbol kind (function, class, struct, etc.), or a comma-separated list (struct,enum,trait)"
+                        },
+                        "kind_not": {
+                            "type": "string",
+                            "description": "Leave out symbols of these kinds (comma-separated, e.g. import,variable)"
                         },
                         "symbols": {
                             "type": "boolean",
@@ -214,7 +291,7 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                         },
                         "limit": {
                             "type": "integer",
-                            "description": "Maximum results per page (default: 100). IMPORTANT: If response.pagination.has_more is true, you MUST fetch more pages using offset parameter."
+                            "description": "Maximum results per page (default: tuned to repo size, 100 for mid-sized repos). IMPORTANT: If response.pagination.has_more is true, you MUST fetch more pages using offset parameter."
                         },
                         "offset": {
                             "type": "integer",
@@ -234,6 +311,11 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                             "items": {"type": "string"},
                             "description": "Exclude files matching glob patterns (e.g., 'target/**')"
                         },
+                        "not": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Drop matching lines that also contain any of these patterns (regexes for search_regex), e.g. [\"test\", \"mock\"]"
+                        },
                         "paths": {
                             "type": "boolean",
                             "description": "Return only unique file paths (not full results)"
@@ -245,6 +327,72 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                         "dependencies": {
                             "type": "boolean",
                             "description": "Include dependency information (imports) in results. **IMPORTANT:** Only extracts static imports (string literals). Dynamic imports (variables, template literals, expressions) are automatically filtered. See CLAUDE.md for details."
+                        },
+                        "related_files": {
+                            "type": "integer",
+                            "description": "Attach related_files to each result file: its tests, header/implementation counterpart, and strongest importers and imports (up to N per relation, e.g. 5). Saves follow-up queries for a hit's neighborhood."
+                        },
+                        "include_deleted": {
+                            "type": "boolean",
+                            "description": "Also return tombstones (path, last hash, deletion time, last symbols) of removed files whose path or symbols match the pattern. Use when a symbol you expect has vanished, to find where it went. Requires [tombstones] enabled in config."
+                        },
+                        "with_docs": {
+                            "type": "boolean",
+                            "description": "Include each symbol's doc comment or docstring (doc field). Useful with symbols=true to learn what a symbol does without reading the file."
+                        },
+                        "span": {
+                            "type": "string",
+                            "enum": ["body", "with-attrs", "with-docs"],
+                            "description": "Span policy for symbol results (default: body). with-attrs extends spans and expanded bodies over preceding attributes/annotations/decorators (e.g. #[derive(...)], @Override); with-docs also includes the doc comment. Use with expand=true when editing a symbol."
+                        },
+                        "changed_since": {
+                            "type": "string",
+                            "description": "Only search files changed since this git ref (branch, tag or commit), compared against the merge base with HEAD. Includes uncommitted and untracked files. Use when reviewing a branch or PR (e.g. 'main')."
+                        },
+                        "rev": {
+                            "type": "string",
+                            "description": "Search this git revision (branch, tag or commit, e.g. 'v1.2.0' or 'HEAD~20') instead of the working tree. The revision is indexed from git history on first use. Use for 'what did this look like before the refactor' questions."
+                        },
+                        "budget_ms": {
+                            "type": "integer",
+                            "description": "Best-effort time budget in milliseconds. When it expires, returns the results verified so far with complete=false and unscanned_candidates instead of failing. Use for fast interactive lookups (e.g. 150)."
+                        },
+                        "max_tokens": {
+                            "type": "integer",
+                            "description": "Approximate token budget for the response (~4 characters per token). Over budget, context lines are dropped, previews shortened and then the lowest-priority matches removed; the response then has truncated=true. Use to keep broad searches from filling your context (e.g. 4000)."
+                        },
+                        "scope": {
+                            "type": "string",
+                            "description": "Only keep matches inside the body of this symbol, e.g. 'parse_config', 'Engine.search' or 'src/query.rs:search'. Use for 'uses of X inside function Y' instead of expanding Y and searching manually."
+                        },
+                        "qualified": {
+                            "type": "string",
+                            "description": "Only keep symbol definitions whose qualified name ends with this path, e.g. 'Worker.run' or 'app::Worker::run'. Tells apart same-named methods in different classes. Implies symbols=true."
+                        },
+                        "visibility": {
+                            "type": "string",
+                            "enum": ["public", "private"],
+                            "description": "Only keep exported ('public': pub, export, public, uppercase Go names) or local ('private') symbol definitions. Use to list a module's API or find internal helpers. Implies symbols=true."
+                        },
+                        "owner": {
+                            "type": "string",
+                            "description": "Only search files owned by this CODEOWNERS team or user, e.g. '@acme/team-payments' or 'team-payments'. Use to scope a search to one team's code."
+                        },
+                        "table": {
+                            "type": "string",
+                            "description": "Only search files touching this database table or ORM model, e.g. 'users' or 'User' (from Eloquent, SQLAlchemy, Django, Diesel and Prisma models and raw SQL). Use to find all code reading or writing a table."
+                        },
+                        "with_owners": {
+                            "type": "boolean",
+                            "description": "Attach each result file's CODEOWNERS owners (owners field). Use to find who to ask about or review a change."
+                        },
+                        "blame": {
+                            "type": "boolean",
+                            "description": "Attach the last commit, author and age of each match's lines (blame field). Use when triaging a bug to see who touched the code recently."
+                        },
+                        "semantic": {
+                            "type": "boolean",
+                            "description": "Treat the pattern as a natural-language description (e.g. 'retry logic for http client') and rank code chunks by meaning plus keyword hits, best first. Requires embeddings built with 'rfx embed'. Use when you don't know the identifiers to search for."
                         }
                     },
                     "required": ["pattern"]
@@ -286,6 +434,11 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                             "items": {"type": "string"},
                             "description": "Exclude files matching glob patterns"
                         },
+                        "not": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Drop matching lines that also contain any of these patterns (regexes for search_regex), e.g. [\"test\", \"mock\"]"
+                        },
                         "paths": {
                             "type": "boolean",
                             "description": "Return only unique file paths"
@@ -394,7 +547,25 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
             },
             {
                 "name": "get_transitive_deps",
-                "description": "Get transitive dependencies of a file up to a specified depth.\n\n**Purpose:** Find not just direct dependencies, but dependencies of dependencies (the full dependency tree).\n\n**Returns:** Object mapping file IDs to their depth in the dependency tree.\n\n**Use this when:**\n- Understanding full dependency chain\n- Analyzing deep coupling\n- Planning refactoring impact\n\n**IMPORTANT:** Only follows **static imports** (string literals). Dynamic imports are filtered. See CLAUDE.md section \"Dependency/Import Extraction\" for details.\n\n**Example:** depth=2 finds: file → deps → deps of deps",
+                "description": "Get transitive dependencies of a file up to a specified depth.\n\n**Purpose:** Find not just direct dependencies, but dependencies of dependencies (the full dependency tree).\n\n**Returns:** Array of {path, depth} objects, where depth is the import distance from the file.\n\n**Use this when:**\n- Understanding full dependency chain\n- Analyzing deep coupling\n- Planning refactoring impact\n\n**IMPORTANT:** Only follows **static imports** (string literals). Dynamic imports are filtered. See CLAUDE.md section \"Dependency/Import Extraction\" for details.\n\n**Example:** depth=2 finds: file → deps → deps of deps",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "File path (supports fuzzy matching)"
+                        },
+                        "depth": {
+                            "type": "integer",
+                            "description": "Maximum depth to traverse (default: 3, max recommended: 5)"
+                        }
+                    },
+                    "required": ["path"]
+                }
+            },
+            {
+                "name": "get_transitive_dependents",
+                "description": "Get files that import a file directly or through other files, up to a specified depth.\n\n**Purpose:** Reverse counterpart of get_transitive_deps: the full set of files affected by a change to this file.\n\n**Returns:** Array of {path, depth} objects sorted by depth, where depth 1 means the file imports it directly.\n\n**Use this when:**\n- Estimating the blast radius of a change\n- Finding every consumer of a module, including through re-exports and barrels\n- Deciding which tests to run\n\n**IMPORTANT:** Only follows **static imports** (string literals). Dynamic imports are filtered. See CLAUDE.md section \"Dependency/Import Extraction\" for details.\n\n**Example:** depth=2 finds: file ← importers ← importers of importers",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
@@ -473,6 +644,38 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                     }
                 }
             },
+            {
+                "name": "find_dead_symbols",
+                "description": "Find exported symbols (pub, export, public) that are never referenced outside their own file.\n\n**Purpose:** Symbol-level dead code detection; find_unused only reports whole files.\n\n**Pagination:** Default limit of 200 results per page. Check response.pagination.has_more to fetch more pages.\n\n**Confidence:** 'high' = name appears nowhere else, 'medium' = only used inside its own file (export can be dropped), 'low' = only used by tests. Methods are one level lower since they may be called through interfaces or dynamic dispatch. Matching is by name, so same-named symbols hide each other (no false positives from that, but missed dead code).\n\n**Excluded:** entry points (main, lib.rs, index.ts, __init__.py), test files and dunder methods.\n\n**Example output:** {\"pagination\": {...}, \"candidates\": 412, \"results\": [{\"path\": \"src/util.ts\", \"name\": \"formatDate\", \"kind\": \"Function\", \"line\": 12, \"confidence\": \"high\", \"reason\": \"unreferenced\"}]}",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "min_confidence": {
+                            "type": "string",
+                            "enum": ["low", "medium", "high"],
+                            "description": "Only report symbols with at least this confidence (default: low)"
+                        },
+                        "glob": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Only report symbols in files matching these globs (references are still counted everywhere)"
+                        },
+                        "exclude": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Don't report symbols in files matching these globs"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of symbols per page (default: 200)"
+                        },
+                        "offset": {
+                            "type": "integer",
+                            "description": "Pagination offset (skip first N symbols). Use with limit for pagination."
+                        }
+                    }
+                }
+            },
             {
                 "name": "find_islands",
                 "description": "Find disconnected components (islands) in the dependency graph.\n\n**Purpose:** Identify groups of files that are isolated from the rest of the codebase (no dependencies between groups).\n\n**Pagination:** Default limit of 200 results per page. Check response.pagination.has_more to fetch more pages.\n\n**Sorting:** Default order is descending (largest islands first). Use sort parameter to change.\n\n**Returns:** Object with pagination metadata and array of islands, where each island contains multiple file paths that depend on each other.\n\n**Use this when:**\n- Identifying isolated subsystems\n- Understanding codebase modularity\n- Finding potential code splitting opportunities\n- Detecting disconnected feature modules\n\n**IMPORTANT:** Only considers **static imports** (string literals). Dynamic imports are filtered. See CLAUDE.md section \"Dependency/Import Extraction\" for details.\n\n**Size filtering:** Use min_island_size and max_island_size to filter by component size. Default: 2-500 files (or 50% of total files).\n\n**Example output:** {\"pagination\": {...}, \"results\": [{\"island_id\": 1, \"size\": 5, \"paths\": [\"a.rs\", \"b.rs\", \"c.rs\", \"d.rs\", \"e.rs\"]}]}",
@@ -517,7 +720,7 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
             },
             {
                 "name": "gather_context",
-                "description": "Collects comprehensive codebase information.\n\n**Parameters:**\n- `structure` (bool): Show directory tree\n- `file_types` (bool): Show file type distribution\n- `project_type` (bool): Detect project type (CLI/library/webapp)\n- `framework` (bool): Detect frameworks (React, Django, etc.)\n- `entry_points` (bool): Find main/index files\n- `test_layout` (bool): Show test organization\n- `config_files` (bool): List configuration files\n- `depth` (int): Tree depth for structure (default: 2)\n- `path` (string, optional): Focus on specific directory\n\n**When to use:**\n- Understanding project structure and organization\n- Finding which frameworks/languages are used\n- Locating entry points and test layouts\n- Getting file statistics and distribution\n\n**When NOT to use:**\n- Finding conceptual/architectural information (use search_documentation)\n- Understanding high-level how things work (use search_documentation)\n\n**Note:** By default (no parameters), all context types are gathered.",
+                "description": "Collects comprehensive codebase information.\n\n**Parameters:**\n- `structure` (bool): Show directory tree\n- `file_types` (bool): Show file type distribution\n- `project_type` (bool): Detect project type (CLI/library/webapp)\n- `framework` (bool): Detect frameworks (React, Django, etc.)\n- `entry_points` (bool): Find main/index files\n- `test_layout` (bool): Show test organization\n- `config_files` (bool): List configuration files\n- `conventions` (bool): Naming styles, test frameworks, lint/format tools and error-handling idioms (never included by default)\n- `depth` (int): Tree depth for structure (default: 2)\n- `path` (string, optional): Focus on specific directory\n\n**When to use:**\n- Understanding project structure and organization\n- Finding which frameworks/languages are used\n- Locating entry points and test layouts\n- Getting file statistics and distribution\n\n**When NOT to use:**\n- Finding conceptual/architectural information (use search_documentation)\n- Understanding high-level how things work (use search_documentation)\n\n**Note:** By default (no parameters), all context types are gathered.",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
@@ -549,6 +752,10 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                             "type": "boolean",
                             "description": "List important configuration files"
                         },
+                        "conventions": {
+                            "type": "boolean",
+                            "description": "Infer naming, test framework, lint/format and error-handling conventions"
+                        },
                         "depth": {
                             "type": "integer",
                             "description": "Tree depth for structure (default: 2)"
@@ -561,18 +768,60 @@ fn handle_list_tools(_params: Option<Value>) -> Result<Value> {
                 }
             }
         ]
-    }))
+    });
+
+    add_dir_parameter(&mut tools);
+
+    if let Some(list) = tools["tools"].as_array_mut() {
+        list.retain(|tool| tool["name"].as_str().is_some_and(|name| config.is_enabled(name)));
+    }
+
+    Ok(tools)
+}
+
+/// Add the optional `dir` argument (project to operate on) to every tool schema
+fn add_dir_parameter(tools: &mut Value) {
+    for tool in tools["tools"].as_array_mut().into_iter().flatten() {
+        if let Some(properties) = tool["inputSchema"]["properties"].as_object_mut() {
+            properties.insert(
+                "dir".to_string(),
+                json!({
+                    "type": "string",
+                    "description": "Project directory to operate on (default: the server's working directory; must be inside it). Lets one server query several checked-out repositories; each needs its own index (run index_project with the same dir)."
+                }),
+            );
+        }
+    }
+}
+
+/// Project a request operates on: its `dir` argument, which must be inside the
+/// server's working directory, or the working directory itself
+fn project_dir(arguments: &Value) -> Result<String> {
+    match arguments["dir"].as_str() {
+        Some(dir) => Ok(crate::paths::confine_dir(std::path::Path::new("."), dir)?
+            .to_string_lossy()
+            .into_owned()),
+        None => Ok(".".to_string()),
+    }
 }
 
 /// Handle tools/call request
-fn handle_call_tool(params: Option<Value>) -> Result<Value> {
+fn handle_call_tool(params: Option<Value>, config: &McpConfig) -> Result<Value> {
     let params = params.ok_or_else(|| anyhow::anyhow!("Missing params for tools/call"))?;
 
     let name = params["name"]
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("Missing tool name"))?;
 
-    let arguments = params["arguments"].clone();
+    if !config.is_enabled(name) {
+        anyhow::bail!("Tool '{}' is disabled on this server ([mcp] section of .reflex/config.toml)", name);
+    }
+
+    let mut arguments = params["arguments"].clone();
+    config.apply_limit(name, &mut arguments);
+    let timeout = config.settings(name).timeout;
+
+    let dir = project_dir(&arguments)?;
 
     match name {
         "list_locations" => {
@@ -598,7 +847,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
 
             let filter = QueryFilter {
                 language,
-                kind: None,
+                kinds: Vec::new(),
                 use_ast: false,
                 use_regex: false,
                 limit: None,  // No limit for paths-only mode
@@ -607,7 +856,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 file_pattern: file,
                 exact: false,
                 use_contains: false,
-                timeout_secs: 30,
+                timeout_secs: timeout.unwrap_or(30),
                 glob_patterns,
                 exclude_patterns,
                 paths_only: true,  // KEY: Enable paths-only mode
@@ -618,7 +867,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 ..Default::default()
             };
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let engine = QueryEngine::new(cache);
             let response = engine.search_with_metadata(&pattern, filter)?;
 
@@ -657,6 +906,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
 
             let lang = arguments["lang"].as_str().map(|s| s.to_string());
             let kind = arguments["kind"].as_str().map(|s| s.to_string());
+            let kind_not = arguments["kind_not"].as_str().map(|s| s.to_string());
             let symbols = arguments["symbols"].as_bool();
             let file = arguments["file"].as_str().map(|s| s.to_string());
             let glob_patterns = arguments["glob"]
@@ -670,12 +920,14 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
             let force = arguments["force"].as_bool().unwrap_or(false);
 
             let language = parse_language(lang);
-            let parsed_kind = parse_symbol_kind(kind);
-            let symbols_mode = symbols.unwrap_or(false) || parsed_kind.is_some();
+            let kinds = parse_symbol_kinds(kind);
+            let exclude_kinds = parse_symbol_kinds(kind_not);
+            let symbols_mode = symbols.unwrap_or(false) || !kinds.is_empty() || !exclude_kinds.is_empty();
 
             let filter = QueryFilter {
                 language,
-                kind: parsed_kind,
+                kinds,
+                exclude_kinds,
                 use_ast: false,
                 use_regex: false,
                 limit: None,  // No limit for counting
@@ -684,7 +936,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 file_pattern: file,
                 exact: false,
                 use_contains: false,
-                timeout_secs: 30,
+                timeout_secs: timeout.unwrap_or(30),
                 glob_patterns,
                 exclude_patterns,
                 paths_only: false,  // Need to count all occurrences
@@ -695,7 +947,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 ..Default::default()
             };
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let engine = QueryEngine::new(cache);
             let response = engine.search_with_metadata(&pattern, filter)?;
 
@@ -726,6 +978,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
 
             let lang = arguments["lang"].as_str().map(|s| s.to_string());
             let kind = arguments["kind"].as_str().map(|s| s.to_string());
+            let kind_not = arguments["kind_not"].as_str().map(|s| s.to_string());
             let symbols = arguments["symbols"].as_bool();
             let exact = arguments["exact"].as_bool();
             let file = arguments["file"].as_str().map(|s| s.to_string());
@@ -739,31 +992,62 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .as_array()
                 .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
                 .unwrap_or_default();
+            let not_patterns: Vec<String> = arguments["not"]
+                .as_array()
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default();
             let paths_only = arguments["paths"].as_bool().unwrap_or(false);
             let force = arguments["force"].as_bool().unwrap_or(false);
             let dependencies = arguments["dependencies"].as_bool().unwrap_or(false);
+            let related_files = arguments["related_files"].as_u64().map(|n| n as usize);
+            let include_deleted = arguments["include_deleted"].as_bool().unwrap_or(false);
+            let with_docs = arguments["with_docs"].as_bool().unwrap_or(false);
+            let budget_ms = arguments["budget_ms"].as_u64();
+            let max_tokens = arguments["max_tokens"].as_u64().map(|n| n as usize);
+            let changed_since = arguments["changed_since"].as_str().map(|s| s.to_string());
+            let scope = arguments["scope"].as_str().map(|s| s.to_string());
+            let qualified = arguments["qualified"].as_str().map(|s| s.to_string());
+            let owner = arguments["owner"].as_str().map(|s| s.to_string());
+            let table = arguments["table"].as_str().map(|s| s.to_string());
+            let with_owners = arguments["with_owners"].as_bool().unwrap_or(false);
+            let blame = arguments["blame"].as_bool().unwrap_or(false);
+            let semantic = arguments["semantic"].as_bool().unwrap_or(false);
+            let visibility = arguments["visibility"]
+                .as_str()
+                .map(str::parse::<Visibility>)
+                .transpose()?;
+            let span_policy = arguments["span"]
+                .as_str()
+                .map(str::parse::<SpanPolicy>)
+                .transpose()?
+                .unwrap_or_default();
 
             let language = parse_language(lang);
-            let parsed_kind = parse_symbol_kind(kind);
-            let symbols_mode = symbols.unwrap_or(false) || parsed_kind.is_some();
+            let kinds = parse_symbol_kinds(kind);
+            let exclude_kinds = parse_symbol_kinds(kind_not);
+            let symbols_mode = symbols.unwrap_or(false) || !kinds.is_empty() || !exclude_kinds.is_empty() || qualified.is_some() || visibility.is_some();
 
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
 
+            // Size-tuned defaults recorded by `rfx index`
+            let defaults = crate::tuning::search_defaults(&CacheManager::new(&dir));
+
             // Smart limit handling:
             // 1. If --paths is set and user didn't specify limit: no limit (None)
             // 2. If user specified limit: use that value
-            // 3. Otherwise: use default limit of 100
+            // 3. Otherwise: use the size-tuned default limit (100 for mid-sized repos)
             let final_limit = if paths_only && limit.is_none() {
                 None  // --paths without explicit limit means no limit
             } else if let Some(user_limit) = limit {
                 Some(user_limit)  // Use user-specified limit
             } else {
-                Some(100)  // Default: limit to 100 results for token efficiency
+                defaults.limit  // Default: tuned to repo size for token efficiency
             };
 
             let filter = QueryFilter {
                 language,
-                kind: parsed_kind,
+                kinds,
+                exclude_kinds,
                 use_ast: false,
                 use_regex: false,
                 limit: final_limit,
@@ -772,26 +1056,52 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 file_pattern: file,
                 exact: exact.unwrap_or(false),
                 use_contains: false, // Default to word-boundary matching for MCP
-                timeout_secs: 30, // Default 30 second timeout for MCP queries
+                timeout_secs: timeout.unwrap_or(defaults.timeout_secs), // Tuned to repo size (30s for mid-sized repos)
+                budget_ms,
+                changed_since,
+                scope,
+                qualified,
+                visibility,
+                owner,
+                table,
                 glob_patterns: glob_patterns.clone(),
                 exclude_patterns,
+                not_patterns,
                 paths_only,
                 offset,
                 force,
                 suppress_output: true,  // MCP always returns JSON
                 include_dependencies: dependencies,
+                related_files,
+                with_owners,
+                blame,
+                semantic,
+                include_deleted,
+                with_docs,
+                span_policy,
                 ..Default::default()
             };
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
+            // Search the segment of a historical revision instead of the working tree
+            let cache = match arguments["rev"].as_str() {
+                Some(rev) => crate::revision::open(Path::new(&dir), &cache, rev, crate::revision::index_config(&cache)?)?,
+                None => cache,
+            };
             let engine = QueryEngine::new(cache);
             let mut response = engine.search_with_metadata(&pattern, filter)?;
 
-            // Apply preview truncation for token efficiency (100 chars max)
-            const MAX_PREVIEW_LENGTH: usize = 100;
+            // Behavior-change notice: a size-tuned limit below the old default of 100 hid results
+            if limit.is_none() && !paths_only && response.pagination.has_more
+                && defaults.limit.is_some_and(|l| l < crate::tuning::DEFAULT_LIMIT)
+            {
+                response.warnings.extend(crate::deprecations::warning(crate::deprecations::TUNED_DEFAULT_LIMIT));
+            }
+
+            // Apply preview truncation for token efficiency (length tuned to repo size)
             for file_group in response.results.iter_mut() {
                 for m in file_group.matches.iter_mut() {
-                    m.preview = crate::cli::truncate_preview(&m.preview, MAX_PREVIEW_LENGTH);
+                    m.preview = crate::cli::truncate_preview(&m.preview, defaults.max_preview_length);
                 }
             }
 
@@ -812,6 +1122,10 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 exact.unwrap_or(false),
             );
 
+            if let Some(max_tokens) = max_tokens {
+                crate::token_budget::fit_response(&mut response, max_tokens, true);
+            }
+
             Ok(json!({
                 "content": [{
                     "type": "text",
@@ -836,24 +1150,31 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .as_array()
                 .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
                 .unwrap_or_default();
+            let not_patterns: Vec<String> = arguments["not"]
+                .as_array()
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default();
             let paths_only = arguments["paths"].as_bool().unwrap_or(false);
             let force = arguments["force"].as_bool().unwrap_or(false);
 
             let language = parse_language(lang);
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
 
+            // Size-tuned defaults recorded by `rfx index`
+            let defaults = crate::tuning::search_defaults(&CacheManager::new(&dir));
+
             // Smart limit handling (same as search_code)
             let final_limit = if paths_only && limit.is_none() {
                 None  // --paths without explicit limit means no limit
             } else if let Some(user_limit) = limit {
                 Some(user_limit)  // Use user-specified limit
             } else {
-                Some(100)  // Default: limit to 100 results for token efficiency
+                defaults.limit  // Default: tuned to repo size for token efficiency
             };
 
             let filter = QueryFilter {
                 language,
-                kind: None,
+                kinds: Vec::new(),
                 use_ast: false,
                 use_regex: true,
                 limit: final_limit,
@@ -862,9 +1183,10 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 file_pattern: file,
                 exact: false,
                 use_contains: false, // Regex mode uses substring matching via use_regex flag
-                timeout_secs: 30, // Default 30 second timeout for MCP queries
+                timeout_secs: timeout.unwrap_or(defaults.timeout_secs), // Tuned to repo size (30s for mid-sized repos)
                 glob_patterns: glob_patterns.clone(),
                 exclude_patterns,
+                not_patterns,
                 paths_only,
                 offset,
                 force,
@@ -873,15 +1195,21 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 ..Default::default()
             };
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let engine = QueryEngine::new(cache);
             let mut response = engine.search_with_metadata(&pattern, filter)?;
 
-            // Apply preview truncation for token efficiency (100 chars max)
-            const MAX_PREVIEW_LENGTH: usize = 100;
+            // Behavior-change notice: a size-tuned limit below the old default of 100 hid results
+            if limit.is_none() && !paths_only && response.pagination.has_more
+                && defaults.limit.is_some_and(|l| l < crate::tuning::DEFAULT_LIMIT)
+            {
+                response.warnings.extend(crate::deprecations::warning(crate::deprecations::TUNED_DEFAULT_LIMIT));
+            }
+
+            // Apply preview truncation for token efficiency (length tuned to repo size)
             for file_group in response.results.iter_mut() {
                 for m in file_group.matches.iter_mut() {
-                    m.preview = crate::cli::truncate_preview(&m.preview, MAX_PREVIEW_LENGTH);
+                    m.preview = crate::cli::truncate_preview(&m.preview, defaults.max_preview_length);
                 }
             }
 
@@ -945,18 +1273,21 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
 
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
 
+            // Size-tuned defaults recorded by `rfx index`
+            let defaults = crate::tuning::search_defaults(&CacheManager::new(&dir));
+
             // Smart limit handling (same as search_code)
             let final_limit = if paths_only && limit.is_none() {
                 None  // --paths without explicit limit means no limit
             } else if let Some(user_limit) = limit {
                 Some(user_limit)  // Use user-specified limit
             } else {
-                Some(100)  // Default: limit to 100 results for token efficiency
+                defaults.limit  // Default: tuned to repo size for token efficiency
             };
 
             let filter = QueryFilter {
                 language: Some(language),
-                kind: None,
+                kinds: Vec::new(),
                 use_ast: true,
                 use_regex: false,
                 limit: final_limit,
@@ -965,7 +1296,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 file_pattern: file,
                 exact: false,
                 use_contains: false,
-                timeout_secs: 60, // Longer timeout for AST queries (they're slow)
+                timeout_secs: timeout.unwrap_or(60), // Longer timeout for AST queries (they're slow)
                 glob_patterns,
                 exclude_patterns,
                 paths_only,
@@ -976,16 +1307,15 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 ..Default::default()
             };
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let engine = QueryEngine::new(cache);
 
             // Use the new search_ast_all_files method (no trigram filtering)
             let mut results = engine.search_ast_all_files(&ast_pattern, filter)?;
 
-            // Apply preview truncation for token efficiency (100 chars max)
-            const MAX_PREVIEW_LENGTH: usize = 100;
+            // Apply preview truncation for token efficiency (length tuned to repo size)
             for result in &mut results {
-                result.preview = crate::cli::truncate_preview(&result.preview, MAX_PREVIEW_LENGTH);
+                result.preview = crate::cli::truncate_preview(&result.preview, defaults.max_preview_length);
             }
 
             Ok(json!({
@@ -1005,7 +1335,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                         .collect::<Vec<_>>()
                 });
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
 
             if force.unwrap_or(false) {
                 log::info!("Force rebuild requested, clearing existing cache");
@@ -1024,7 +1354,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
             };
 
             let indexer = Indexer::new(cache, config);
-            let path = PathBuf::from(".");
+            let path = PathBuf::from(&dir);
             let stats = indexer.index(&path, false)?;
 
             Ok(json!({
@@ -1040,7 +1370,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .ok_or_else(|| anyhow::anyhow!("Missing path"))?
                 .to_string();
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             // Fuzzy path matching
@@ -1062,7 +1392,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .ok_or_else(|| anyhow::anyhow!("Missing path"))?
                 .to_string();
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             // Fuzzy path matching
@@ -1095,7 +1425,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .map(|n| n as usize)
                 .unwrap_or(3);  // Default depth of 3
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             // Fuzzy path matching
@@ -1125,6 +1455,48 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 }]
             }))
         }
+        "get_transitive_dependents" => {
+            let path = arguments["path"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Missing path"))?
+                .to_string();
+
+            let depth = arguments["depth"]
+                .as_u64()
+                .map(|n| n as usize)
+                .unwrap_or(3);
+
+            let cache = CacheManager::new(&dir);
+            let deps_index = DependencyIndex::new(cache);
+
+            // Fuzzy path matching
+            let file_id = deps_index.get_file_id_by_path(&path)?
+                .ok_or_else(|| anyhow::anyhow!("File '{}' not found in index", path))?;
+
+            let dependents = deps_index.get_transitive_dependents(file_id, depth)?;
+            let file_ids: Vec<i64> = dependents.keys().copied().collect();
+            let paths = deps_index.get_file_paths(&file_ids)?;
+
+            // Nearest importers first
+            let mut ordered: Vec<(&String, usize)> = dependents.iter()
+                .filter_map(|(id, depth)| paths.get(id).map(|path| (path, *depth)))
+                .collect();
+            ordered.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+
+            let result: Vec<serde_json::Value> = ordered.into_iter()
+                .map(|(path, depth)| json!({
+                    "path": path,
+                    "depth": depth
+                }))
+                .collect();
+
+            Ok(json!({
+                "content": [{
+                    "type": "text",
+                    "text": serde_json::to_string(&result)?
+                }]
+            }))
+        }
         "find_hotspots" => {
             let limit = arguments["limit"].as_u64().map(|n| n as usize);
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
@@ -1134,7 +1506,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .unwrap_or(2);
             let sort = arguments["sort"].as_str().map(|s| s.to_string());
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             // Get all hotspots first (without limit) to track total count
@@ -1206,7 +1578,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
             let sort = arguments["sort"].as_str().map(|s| s.to_string());
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             let mut all_cycles = deps_index.detect_circular_dependencies()?;
@@ -1277,7 +1649,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
             let limit = arguments["limit"].as_u64().map(|n| n as usize);
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             let all_unused = deps_index.find_unused_files()?;
@@ -1320,6 +1692,54 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 }]
             }))
         }
+        "find_dead_symbols" => {
+            let limit = arguments["limit"].as_u64().map(|n| n as usize);
+            let offset = arguments["offset"].as_u64().map(|n| n as usize);
+            let strings = |key: &str| -> Vec<String> {
+                arguments[key]
+                    .as_array()
+                    .map(|values| values.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                    .unwrap_or_default()
+            };
+
+            let options = crate::dead_symbols::DeadSymbolOptions {
+                min_confidence: arguments["min_confidence"]
+                    .as_str()
+                    .map(str::parse)
+                    .transpose()?
+                    .unwrap_or(crate::dead_symbols::Confidence::Low),
+                glob_patterns: strings("glob"),
+                exclude_patterns: strings("exclude"),
+            };
+            let report = crate::dead_symbols::find_dead_symbols(&CacheManager::new(&dir), &options)?;
+            let total_count = report.symbols.len();
+
+            // Apply offset pagination and limit (default 200)
+            let offset_val = offset.unwrap_or(0);
+            let limit_val = limit.unwrap_or(200);
+            let results: Vec<_> = report.symbols.into_iter().skip(offset_val).take(limit_val).collect();
+            let count = results.len();
+            let has_more = offset_val + count < total_count;
+
+            let response = json!({
+                "pagination": {
+                    "total": total_count,
+                    "count": count,
+                    "offset": offset_val,
+                    "limit": limit_val,
+                    "has_more": has_more,
+                },
+                "candidates": report.candidates,
+                "results": results,
+            });
+
+            Ok(json!({
+                "content": [{
+                    "type": "text",
+                    "text": serde_json::to_string(&response)?
+                }]
+            }))
+        }
         "find_islands" => {
             let limit = arguments["limit"].as_u64().map(|n| n as usize);
             let offset = arguments["offset"].as_u64().map(|n| n as usize);
@@ -1332,7 +1752,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .map(|n| n as usize);
             let sort = arguments["sort"].as_str().map(|s| s.to_string());
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             let all_islands = deps_index.find_islands()?;
@@ -1432,7 +1852,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 .map(|n| n as usize)
                 .unwrap_or(2);
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let deps_index = DependencyIndex::new(cache);
 
             let cycles = deps_index.detect_circular_dependencies()?;
@@ -1464,6 +1884,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
             let entry_points = arguments["entry_points"].as_bool().unwrap_or(false);
             let test_layout = arguments["test_layout"].as_bool().unwrap_or(false);
             let config_files = arguments["config_files"].as_bool().unwrap_or(false);
+            let conventions = arguments["conventions"].as_bool().unwrap_or(false);
             let depth = arguments["depth"]
                 .as_u64()
                 .map(|n| n as usize)
@@ -1480,6 +1901,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 entry_points,
                 test_layout,
                 config_files,
+                conventions,
                 depth,
                 json: false,  // MCP always returns text format
             };
@@ -1495,7 +1917,7 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
                 opts.config_files = true;
             }
 
-            let cache = CacheManager::new(".");
+            let cache = CacheManager::new(&dir);
             let context = crate::context::generate_context(&cache, &opts)?;
 
             Ok(json!({
@@ -1509,14 +1931,198 @@ fn handle_call_tool(params: Option<Value>) -> Result<Value> {
     }
 }
 
+/// Run a tool and return its payload as JSON
+///
+/// Lets the HTTP API serve the same tool implementations as MCP. Tools that
+/// produce plain text (e.g. `gather_context`) are wrapped as `{"text": ...}`.
+/// The `[mcp]` tool surface config does not apply here.
+pub fn call_tool(name: &str, arguments: Value) -> Result<Value> {
+    let params = json!({ "name": name, "arguments": arguments });
+    let result = handle_call_tool(Some(params), &McpConfig::default())?;
+    let text = result["content"][0]["text"].as_str().unwrap_or_default();
+    Ok(serde_json::from_str(text).unwrap_or_else(|_| json!({ "text": text })))
+}
+
+/// Log the tools this server exposes and their configured defaults
+fn log_tool_manifest(config: &McpConfig) -> Result<()> {
+    let all_tools = handle_list_tools(None, &McpConfig::default())?;
+    let names: Vec<&str> = all_tools["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+
+    for configured in config.enabled_tools.iter().chain(&config.disabled_tools).chain(config.tools.keys()) {
+        if !names.contains(&configured.as_str()) {
+            log::warn!("[mcp] config refers to unknown tool '{}'", configured);
+        }
+    }
+
+    let (enabled, disabled): (Vec<&str>, Vec<&str>) = names.iter().copied().partition(|name| config.is_enabled(name));
+    log::info!("MCP tools enabled ({}): {}", enabled.len(), enabled.join(", "));
+    if !disabled.is_empty() {
+        log::info!("MCP tools disabled ({}): {}", disabled.len(), disabled.join(", "));
+    }
+    for (tool, settings) in &config.tools {
+        log::info!(
+            "MCP tool {}: limit={} max_limit={} timeout={}",
+            tool,
+            settings.limit.map_or("default".to_string(), |n| n.to_string()),
+            settings.max_limit.map_or("none".to_string(), |n| n.to_string()),
+            settings.timeout.map_or("default".to_string(), |n| format!("{}s", n)),
+        );
+    }
+
+    Ok(())
+}
+
+/// URI scheme prefix of file content resources
+const FILE_RESOURCE: &str = "reflex://file/";
+/// URI scheme prefix of symbol outline resources
+const OUTLINE_RESOURCE: &str = "reflex://outline/";
+/// Resources returned per `resources/list` page
+const RESOURCE_PAGE_SIZE: usize = 500;
+
+fn open_content_store(cache: &CacheManager) -> Result<ContentReader> {
+    if !cache.exists() {
+        anyhow::bail!("Index not found. Run index_project (or 'rfx index') first.");
+    }
+    ContentReader::open(cache.path().join("content.bin")).context("Failed to open content store")
+}
+
+/// Handle resources/templates/list request
+fn handle_list_resource_templates() -> Result<Value> {
+    Ok(json!({
+        "resourceTemplates": [
+            {
+                "uriTemplate": format!("{}{{path}}", FILE_RESOURCE),
+                "name": "Indexed file",
+                "description": "Content of an indexed file as of the last index, by workspace-relative path",
+                "mimeType": "text/plain"
+            },
+            {
+                "uriTemplate": format!("{}{{path}}", OUTLINE_RESOURCE),
+                "name": "Symbol outline",
+                "description": "Structural symbols of an indexed file (kind, name, span, signature) in source order",
+                "mimeType": "application/json"
+            }
+        ]
+    }))
+}
+
+/// Handle resources/list request: every indexed file, paginated with an opaque cursor
+fn handle_list_resources(params: Option<Value>, cache: &CacheManager) -> Result<Value> {
+    let offset = params
+        .as_ref()
+        .and_then(|p| p["cursor"].as_str())
+        .map(|cursor| cursor.parse::<usize>().map_err(|_| anyhow::anyhow!("Invalid cursor '{}'", cursor)))
+        .transpose()?
+        .unwrap_or(0);
+
+    let content = open_content_store(cache)?;
+    let root = cache.workspace_root();
+    let total = content.file_count();
+
+    let resources: Vec<Value> = (offset..total.min(offset + RESOURCE_PAGE_SIZE))
+        .filter_map(|id| content.get_file_path(id as u32))
+        .map(|path| {
+            let path = crate::paths::relative_path(path, &root);
+            json!({
+                "uri": format!("{}{}", FILE_RESOURCE, path),
+                "name": path,
+                "mimeType": "text/plain"
+            })
+        })
+        .collect();
+
+    let mut result = json!({ "resources": resources });
+    if offset + RESOURCE_PAGE_SIZE < total {
+        result["nextCursor"] = json!((offset + RESOURCE_PAGE_SIZE).to_string());
+    }
+    Ok(result)
+}
+
+/// Handle resources/read request
+fn handle_read_resource(params: Option<Value>, cache: &CacheManager) -> Result<Value> {
+    let params = params.ok_or_else(|| anyhow::anyhow!("Missing params for resources/read"))?;
+    let uri = params["uri"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing uri"))?;
+
+    let (outline, path) = match (uri.strip_prefix(FILE_RESOURCE), uri.strip_prefix(OUTLINE_RESOURCE)) {
+        (Some(path), _) => (false, percent_decode(path)),
+        (_, Some(path)) => (true, percent_decode(path)),
+        _ => anyhow::bail!(
+            "Unknown resource '{}'. Supported: {}<path>, {}<path>",
+            uri,
+            FILE_RESOURCE,
+            OUTLINE_RESOURCE
+        ),
+    };
+
+    let content = open_content_store(cache)?;
+    let root = cache.workspace_root();
+    let wanted = path.trim_start_matches("./");
+    let file_id = content
+        .file_id_for_workspace_path(wanted, &root)
+        .ok_or_else(|| anyhow::anyhow!("File '{}' not found in index", path))?;
+    let source = content.get_file_content(file_id)?;
+
+    let (mime_type, text) = if outline {
+        let symbols = crate::locate::outline(wanted, source)?;
+        ("application/json", serde_json::to_string(&symbols)?)
+    } else {
+        ("text/plain", source.to_string())
+    };
+
+    Ok(json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": mime_type,
+            "text": text
+        }]
+    }))
+}
+
+/// Decode `%XX` escapes in a resource URI path (invalid escapes are kept as-is)
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 /// Process a single JSON-RPC request
-fn process_request(request: JsonRpcRequest) -> JsonRpcResponse {
+fn process_request(request: JsonRpcRequest, config: &McpConfig) -> JsonRpcResponse {
     log::debug!("MCP request: method={}", request.method);
 
     let result = match request.method.as_str() {
         "initialize" => handle_initialize(request.params),
-        "tools/list" => handle_list_tools(request.params),
-        "tools/call" => handle_call_tool(request.params),
+        "tools/list" => handle_list_tools(request.params, config),
+        "tools/call" => handle_call_tool(request.params, config),
+        // Resources come from the same project index as the tools (`dir`, else the working directory)
+        "resources/list" => project_dir(request.params.as_ref().unwrap_or(&Value::Null))
+            .and_then(|dir| handle_list_resources(request.params, &CacheManager::new(dir))),
+        "resources/templates/list" => handle_list_resource_templates(),
+        "resources/read" => project_dir(request.params.as_ref().unwrap_or(&Value::Null))
+            .and_then(|dir| handle_read_resource(request.params, &CacheManager::new(dir))),
         _ => Err(anyhow::anyhow!("Unknown method: {}", request.method)),
     };
 
@@ -1536,7 +2142,7 @@ fn process_request(request: JsonRpcRequest) -> JsonRpcResponse {
                 error: Some(JsonRpcError {
                     code: -32603,
                     message: e.to_string(),
-                    data: None,
+                    data: Some(json!({ "code": crate::error_code::error_code(&e) })),
                 }),
             }
         }
@@ -1547,6 +2153,9 @@ fn process_request(request: JsonRpcRequest) -> JsonRpcResponse {
 pub fn run_mcp_server() -> Result<()> {
     log::info!("Starting Reflex MCP server on stdio");
 
+    let config = load_mcp_config(CacheManager::new(".").path())?;
+    log_tool_manifest(&config)?;
+
     let stdin = io::stdin();
     let mut stdout = io::stdout();
     let reader = stdin.lock();
@@ -1571,7 +2180,7 @@ pub fn run_mcp_server() -> Result<()> {
         };
 
         // Process request
-        let response = process_request(request);
+        let response = process_request(request, &config);
 
         // Send response
         let response_json = serde_json::to_string(&response)?;
@@ -1584,3 +2193,140 @@ pub fn run_mcp_server() -> Result<()> {
     log::info!("Reflex MCP server stopped");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tool_names(config: &McpConfig) -> Vec<String> {
+        handle_list_tools(None, config).unwrap()["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_load_mcp_config() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_mcp_config(temp.path()).unwrap().enabled_tools.is_empty());
+
+        std::fs::write(
+            temp.path().join(crate::cache::CONFIG_TOML),
+            "[mcp]\ndisabled_tools = [\"index_project\"]\n\n[mcp.tools.search_code]\nlimit = 50\ntimeout = 10\n",
+        )
+        .unwrap();
+        let config = load_mcp_config(temp.path()).unwrap();
+        assert!(!config.is_enabled("index_project"));
+        assert_eq!(config.settings("search_code").limit, Some(50));
+        assert_eq!(config.settings("search_code").timeout, Some(10));
+        assert_eq!(config.settings("search_regex").limit, None);
+    }
+
+    #[test]
+    fn test_disabled_tools_are_hidden_and_rejected() {
+        let all = tool_names(&McpConfig::default());
+        assert!(all.contains(&"index_project".to_string()));
+
+        let config = McpConfig {
+            disabled_tools: vec!["index_project".to_string()],
+            ..Default::default()
+        };
+        let names = tool_names(&config);
+        assert_eq!(names.len(), all.len() - 1);
+        assert!(!names.contains(&"index_project".to_string()));
+
+        let params = json!({ "name": "index_project", "arguments": {} });
+        let err = handle_call_tool(Some(params), &config).unwrap_err();
+        assert!(err.to_string().contains("disabled"));
+
+        let allowlist = McpConfig {
+            enabled_tools: vec!["search_code".to_string(), "list_locations".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(tool_names(&allowlist), vec!["list_locations", "search_code"]);
+    }
+
+    #[test]
+    fn test_dependency_and_context_tools_are_registered() {
+        let names = tool_names(&McpConfig::default());
+        for tool in [
+            "get_dependencies",
+            "get_dependents",
+            "get_transitive_deps",
+            "get_transitive_dependents",
+            "find_circular",
+            "find_hotspots",
+            "find_unused",
+            "find_dead_symbols",
+            "find_islands",
+            "analyze_summary",
+            "gather_context",
+        ] {
+            assert!(names.contains(&tool.to_string()), "{} is not registered", tool);
+        }
+    }
+
+    #[test]
+    fn test_file_and_outline_resources() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/my lib.rs"), "pub struct Point { x: i32 }\n\nfn norm() {}\n").unwrap();
+        let cache = CacheManager::new(temp.path());
+        Indexer::new(cache.clone(), IndexConfig::default()).index(temp.path(), false).unwrap();
+
+        let listed = handle_list_resources(None, &cache).unwrap();
+        assert_eq!(listed["resources"][0]["uri"], "reflex://file/src/my lib.rs");
+        assert!(listed["nextCursor"].is_null());
+
+        // Content comes from the index, not the working tree
+        std::fs::write(temp.path().join("src/my lib.rs"), "changed").unwrap();
+        let file = handle_read_resource(Some(json!({ "uri": "reflex://file/src/my%20lib.rs" })), &cache).unwrap();
+        assert!(file["contents"][0]["text"].as_str().unwrap().starts_with("pub struct Point"));
+
+        let outline = handle_read_resource(Some(json!({ "uri": "reflex://outline/src/my lib.rs" })), &cache).unwrap();
+        assert_eq!(outline["contents"][0]["mimeType"], "application/json");
+        let symbols: Value = serde_json::from_str(outline["contents"][0]["text"].as_str().unwrap()).unwrap();
+        let names: Vec<_> = symbols.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["Point", "norm"]);
+
+        assert!(handle_read_resource(Some(json!({ "uri": "reflex://file/missing.rs" })), &cache).is_err());
+        assert!(handle_read_resource(Some(json!({ "uri": "file:///etc/passwd" })), &cache).is_err());
+        assert!(handle_read_resource(Some(json!({ "uri": "reflex://file/../etc/passwd" })), &cache).is_err());
+    }
+
+    #[test]
+    fn test_resources_reject_dir_outside_server_directory() {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: "resources/read".to_string(),
+            params: Some(json!({ "uri": "reflex://file/etc/passwd", "dir": "/" })),
+        };
+
+        let response = process_request(request, &McpConfig::default());
+        assert!(response.result.is_none());
+        assert_eq!(response.error.unwrap().data.unwrap()["code"], "FORBIDDEN");
+    }
+
+    #[test]
+    fn test_apply_limit_defaults_and_caps() {
+        let mut tools = BTreeMap::new();
+        tools.insert("search_code".to_string(), ToolSettings { limit: Some(50), max_limit: Some(80), timeout: None });
+        let config = McpConfig { tools, ..Default::default() };
+
+        let mut args = json!({ "pattern": "x" });
+        config.apply_limit("search_code", &mut args);
+        assert_eq!(args["limit"], 50);
+
+        let mut args = json!({ "pattern": "x", "limit": 500 });
+        config.apply_limit("search_code", &mut args);
+        assert_eq!(args["limit"], 80);
+
+        let mut args = json!({ "pattern": "x" });
+        config.apply_limit("search_regex", &mut args);
+        assert!(args["limit"].is_null());
+    }
+}
@@ -0,0 +1,351 @@
+//! Cache format versions and in-place migration
+//!
+//! Every cache artifact records the format it was written in: meta.db as the
+//! `cache_version` statistic, the binary files in the version field of their
+//! header. [`migrate`] runs before indexing and querying, and brings a cache
+//! written by an older Reflex up to the formats this binary reads:
+//! - meta.db: the registered migrations run in order, each in a transaction
+//!   that also bumps `cache_version`
+//! - trigrams.bin / tokens.bin: regenerated from content.bin
+//! - content.bin: holds the indexed file contents, so an old one can only be
+//!   replaced by re-indexing
+//!
+//! Whatever can't be migrated in place is reported as `rebuild_required`,
+//! which `rfx index` turns into a full rebuild. That also covers a schema hash
+//! that differs from this binary's (see `build.rs`). A cache written by a
+//! newer Reflex is never touched.
+//!
+//! Changing an on-disk format means bumping its version constant and, for
+//! meta.db, registering a migration from the previous version.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+
+use crate::cache::{CacheManager, CACHE_FORMAT_VERSION, META_DB, TOKENS_BIN};
+use crate::error_code::ReflexError;
+
+/// A meta.db upgrade from format `from` to `from + 1`
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// meta.db migrations, in order
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "add the roots, flags, transforms, tombstones, embedded regions and history tables",
+        apply: add_feature_tables,
+    },
+    Migration {
+        from: 2,
+        description: "add the skipped_files table",
+        apply: CacheManager::ensure_skipped_files_table,
+    },
+    Migration {
+        from: 3,
+        description: "record result directories in the query history",
+        apply: crate::history::ensure_schema,
+    },
+    Migration {
+        from: 4,
+        description: "add the linked_files table",
+        apply: CacheManager::ensure_linked_files_table,
+    },
+    Migration {
+        from: 5,
+        description: "add the config_keys table",
+        apply: crate::config_keys::ensure_schema,
+    },
+    Migration {
+        from: 6,
+        description: "add the table_refs table",
+        apply: crate::tables::ensure_schema,
+    },
+];
+
+/// Format version of one cache file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArtifactVersion {
+    pub file: &'static str,
+    /// Version found on disk; None if the file is missing or has no readable header
+    pub found: Option<u32>,
+    /// Version this binary reads and writes
+    pub expected: u32,
+}
+
+impl ArtifactVersion {
+    pub fn is_current(&self) -> bool {
+        self.found.is_none_or(|found| found == self.expected)
+    }
+
+    pub fn is_newer(&self) -> bool {
+        self.found.is_some_and(|found| found > self.expected)
+    }
+
+    /// "<file> is in format N, this version of Reflex uses format M"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} is in format {}, this version of Reflex uses format {}",
+            self.file,
+            self.found.unwrap_or_default(),
+            self.expected
+        )
+    }
+}
+
+/// What [`migrate`] did to a cache
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    /// Upgrades applied in place, in order
+    pub applied: Vec<String>,
+    /// Why the cache has to be re-indexed, if it couldn't be fully upgraded
+    pub rebuild_required: Option<String>,
+}
+
+/// Format versions of all cache files, meta.db first
+pub fn versions(cache: &CacheManager) -> Result<Vec<ArtifactVersion>> {
+    let conn = Connection::open(cache.path().join(META_DB))
+        .context("Failed to open meta.db")?;
+
+    Ok(vec![
+        ArtifactVersion {
+            file: META_DB,
+            found: Some(meta_version(&conn)?),
+            expected: CACHE_FORMAT_VERSION,
+        },
+        ArtifactVersion {
+            file: "content.bin",
+            found: header_version(&cache.path().join("content.bin"), crate::content_store::MAGIC),
+            expected: crate::content_store::VERSION,
+        },
+        ArtifactVersion {
+            file: "trigrams.bin",
+            found: header_version(&cache.path().join("trigrams.bin"), crate::trigram::MAGIC),
+            expected: crate::trigram::VERSION,
+        },
+        ArtifactVersion {
+            file: TOKENS_BIN,
+            found: header_version(&cache.path().join(TOKENS_BIN), crate::tokens::MAGIC),
+            expected: crate::tokens::VERSION,
+        },
+    ])
+}
+
+/// Upgrade `cache` to the current formats where possible
+///
+/// Fails without touching anything if a file was written by a newer Reflex.
+pub fn migrate(cache: &CacheManager) -> Result<MigrationReport> {
+    let mut report = MigrationReport::default();
+    if !cache.exists() {
+        return Ok(report);
+    }
+
+    let versions = versions(cache)?;
+    if let Some(newer) = versions.iter().find(|v| v.is_newer()) {
+        return Err(ReflexError::IndexCorrupt(format!(
+            "The cache was written by a newer version of Reflex ({}). \
+             Upgrade Reflex, or run 'rfx index --force' to rebuild it with this version.",
+            newer.describe()
+        )).into());
+    }
+
+    let conn = Connection::open(cache.path().join(META_DB))
+        .context("Failed to open meta.db")?;
+    let mut version = meta_version(&conn)?;
+    while version < CACHE_FORMAT_VERSION {
+        let Some(migration) = MIGRATIONS.iter().find(|m| m.from == version) else {
+            return Err(ReflexError::IndexCorrupt(format!(
+                "meta.db is in format {}, which can't be upgraded to format {}. \
+                 Run 'rfx index --force' to rebuild the cache.",
+                version, CACHE_FORMAT_VERSION
+            )).into());
+        };
+
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)
+            .with_context(|| format!("Failed to migrate meta.db from format {}", version))?;
+        set_meta_version(&tx, version + 1)?;
+        tx.commit()?;
+
+        log::info!("Migrated meta.db from format {} to {}", version, version + 1);
+        report.applied.push(format!(
+            "meta.db {} -> {}: {}",
+            version,
+            version + 1,
+            migration.description
+        ));
+        version += 1;
+    }
+
+    let stored_hash: Option<String> = conn
+        .query_row("SELECT value FROM statistics WHERE key = 'schema_hash'", [], |row| row.get(0))
+        .optional()?;
+    let current_hash = env!("CACHE_SCHEMA_HASH");
+
+    // A rebuild rewrites every file, so regenerating the search indexes first would be wasted
+    let [_, content, trigrams, tokens] = &versions[..] else {
+        unreachable!("versions() lists four files");
+    };
+    if !content.is_current() {
+        report.rebuild_required = Some(content.describe());
+    } else if let Some(stored_hash) = stored_hash.filter(|stored| stored != current_hash) {
+        report.rebuild_required = Some(format!(
+            "the cache was built with schema {}, this version of Reflex uses schema {}",
+            stored_hash, current_hash
+        ));
+    } else if !trigrams.is_current() || !tokens.is_current() {
+        let files = crate::integrity::rebuild_search_indexes(cache)?;
+        log::info!("Rebuilt search indexes from content.bin after a format change");
+        report.applied.push(format!(
+            "trigrams.bin {} -> {}, tokens.bin {} -> {}: regenerated from content.bin ({} files)",
+            trigrams.found.unwrap_or(trigrams.expected),
+            trigrams.expected,
+            tokens.found.unwrap_or(tokens.expected),
+            tokens.expected,
+            files
+        ));
+    }
+
+    Ok(report)
+}
+
+/// Stored meta.db format; caches from before the statistic existed are format 1
+fn meta_version(conn: &Connection) -> Result<u32> {
+    let version: Option<String> = conn
+        .query_row("SELECT value FROM statistics WHERE key = 'cache_version'", [], |row| row.get(0))
+        .optional()
+        .context("Failed to read cache_version from meta.db")?;
+    match version {
+        Some(v) => v.parse().with_context(|| format!("Invalid cache_version in meta.db: {}", v)),
+        None => Ok(1),
+    }
+}
+
+fn set_meta_version(conn: &Connection, version: u32) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO statistics (key, value, updated_at) VALUES ('cache_version', ?, ?)",
+        rusqlite::params![version.to_string(), chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Version field of a binary cache file, or None if it's missing or not that file type
+fn header_version(path: &Path, magic: &[u8; 4]) -> Option<u32> {
+    let mut header = [0u8; 8];
+    std::fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[0..4] != magic {
+        return None;
+    }
+    Some(u32::from_le_bytes(header[4..8].try_into().unwrap()))
+}
+
+/// 1 -> 2: tables added by features that only created them for new caches
+fn add_feature_tables(conn: &Connection) -> Result<()> {
+    CacheManager::ensure_roots_table(conn)?;
+    crate::flags::ensure_schema(conn)?;
+    crate::transforms::ensure_schema(conn)?;
+    crate::tombstones::ensure_schema(conn)?;
+    crate::embedded::ensure_schema(conn)?;
+    crate::history::ensure_schema(conn)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_workspace() -> (TempDir, CacheManager) {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("main.rs"), "fn migrate_me() {}\n").unwrap();
+        let cache = CacheManager::new(temp.path());
+        Indexer::new(cache.clone(), IndexConfig::default()).index(temp.path(), false).unwrap();
+        (temp, cache)
+    }
+
+    fn set_header_version(path: &Path, version: u32) {
+        let mut data = fs::read(path).unwrap();
+        data[4..8].copy_from_slice(&version.to_le_bytes());
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_current_cache_is_untouched() {
+        let (_temp, cache) = indexed_workspace();
+        assert!(versions(&cache).unwrap().iter().all(ArtifactVersion::is_current));
+
+        let report = migrate(&cache).unwrap();
+        assert!(report.applied.is_empty());
+        assert!(report.rebuild_required.is_none());
+    }
+
+    #[test]
+    fn test_format_1_meta_db_is_migrated_in_place() {
+        let (_temp, cache) = indexed_workspace();
+        let conn = Connection::open(cache.path().join(META_DB)).unwrap();
+        conn.execute("DROP TABLE flag_usages", []).unwrap();
+        conn.execute("DROP TABLE skipped_files", []).unwrap();
+        set_meta_version(&conn, 1).unwrap();
+
+        let report = migrate(&cache).unwrap();
+        assert_eq!(report.applied.len(), 6);
+        assert!(report.applied[0].starts_with("meta.db 1 -> 2"));
+        assert!(report.applied[1].starts_with("meta.db 2 -> 3"));
+        assert!(report.applied[2].starts_with("meta.db 3 -> 4"));
+        assert!(report.applied[3].starts_with("meta.db 4 -> 5"));
+        assert!(report.applied[4].starts_with("meta.db 5 -> 6"));
+        assert!(report.applied[5].starts_with("meta.db 6 -> 7"));
+        assert_eq!(meta_version(&conn).unwrap(), CACHE_FORMAT_VERSION);
+        let restored: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'flag_usages'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(restored, 1);
+
+        // Running again is a no-op
+        assert!(migrate(&cache).unwrap().applied.is_empty());
+    }
+
+    #[test]
+    fn test_old_search_indexes_are_regenerated() {
+        let (_temp, cache) = indexed_workspace();
+        set_header_version(&cache.path().join("trigrams.bin"), crate::trigram::VERSION - 1);
+
+        let report = migrate(&cache).unwrap();
+        assert!(report.applied[0].contains("regenerated from content.bin (1 files)"));
+        assert!(report.rebuild_required.is_none());
+        assert!(versions(&cache).unwrap().iter().all(ArtifactVersion::is_current));
+    }
+
+    #[test]
+    fn test_old_content_store_requires_rebuild() {
+        let (temp, cache) = indexed_workspace();
+        set_header_version(&cache.path().join("content.bin"), 0);
+
+        let report = migrate(&cache).unwrap();
+        assert!(report.rebuild_required.unwrap().starts_with("content.bin is in format 0"));
+
+        // Indexing rebuilds even though no file changed
+        Indexer::new(cache.clone(), IndexConfig::default()).index(temp.path(), false).unwrap();
+        assert!(migrate(&cache).unwrap().rebuild_required.is_none());
+    }
+
+    #[test]
+    fn test_newer_cache_is_refused() {
+        let (_temp, cache) = indexed_workspace();
+        let conn = Connection::open(cache.path().join(META_DB)).unwrap();
+        set_meta_version(&conn, CACHE_FORMAT_VERSION + 1).unwrap();
+
+        let err = migrate(&cache).unwrap_err();
+        assert_eq!(crate::error_code::error_code(&err), "INDEX_CORRUPT");
+        assert!(err.to_string().contains("newer version of Reflex"));
+        assert_eq!(meta_version(&conn).unwrap(), CACHE_FORMAT_VERSION + 1);
+    }
+}
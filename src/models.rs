@@ -4,8 +4,18 @@
 //! that Reflex provides to AI agents and other programmatic consumers.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::RwLock;
 use strum::{EnumString, Display};
 
+/// Extra extension → language mappings installed from `[index.extensions]`
+///
+/// Process-wide because the same lookup is done wherever a path's language is
+/// needed (indexing, query filters, symbol parsing); see
+/// [`Language::set_extension_overrides`].
+static EXTENSION_OVERRIDES: RwLock<Option<HashMap<String, Language>>> = RwLock::new(None);
+
 /// Represents a source code location span (line range only)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Span {
@@ -47,7 +57,18 @@ pub enum SymbolKind {
     Event,
     Import,
     Export,
+    /// Attributes, decorators and annotations (`#[test]`, `@property`, `@Override`),
+    /// both definitions and uses. `--kind decorator` and `--kind annotation` are aliases.
+    #[strum(to_string = "Attribute", serialize = "Decorator", serialize = "Annotation")]
     Attribute,
+    /// HTTP routes registered with a web framework, named by path (see `routes`)
+    Route,
+    /// Build targets: Make rules, CMake `add_executable`/`add_library` targets, Bazel rules
+    Target,
+    /// Shell aliases (`alias ll='ls -l'`)
+    Alias,
+    /// SQL tables and views (`CREATE TABLE`, `CREATE VIEW`)
+    Table,
     /// Catch-all for symbol kinds not yet explicitly supported.
     /// This ensures no data loss when encountering new tree-sitter node types.
     /// The string contains the original kind name from the parser.
@@ -55,6 +76,26 @@ pub enum SymbolKind {
     Unknown(String),
 }
 
+impl SymbolKind {
+    /// Parse a comma-separated `--kind` list such as `function,struct,trait`
+    ///
+    /// Names are case-insensitive; names that aren't a known kind filter as-is.
+    pub fn parse_list(list: &str) -> Vec<SymbolKind> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|kind| !kind.is_empty())
+            .map(|kind| {
+                let mut chars = kind.chars();
+                let capitalized: String = match chars.next() {
+                    None => String::new(),
+                    Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+                };
+                capitalized.parse().unwrap_or_else(|_| SymbolKind::Unknown(kind.to_string()))
+            })
+            .collect()
+    }
+}
+
 /// Programming language identifier
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -76,11 +117,50 @@ pub enum Language {
     Kotlin,
     Swift,
     Zig,
+    /// Shell scripts: `*.sh`, `*.bash`, `*.zsh`, `*.ksh`
+    Shell,
+    /// `Makefile`, `GNUmakefile`, `*.mk`
+    Make,
+    /// `CMakeLists.txt`, `*.cmake`
+    CMake,
+    /// `BUILD`, `WORKSPACE`, `*.bazel`, `*.bzl`
+    Bazel,
+    /// `*.sql`, `*.pgsql`, `*.psql`, `*.ddl`, plus embedded regions inside host files (see `embedded`)
+    Sql,
+    /// Only indexed as embedded regions inside host files (see `embedded`)
+    GraphQL,
+    /// Only indexed as embedded regions inside host files (see `embedded`)
+    Html,
     Unknown,
 }
 
 impl Language {
+    /// Language of a file extension (without the dot), honoring configured overrides
     pub fn from_extension(ext: &str) -> Self {
+        let overridden = EXTENSION_OVERRIDES
+            .read()
+            .ok()
+            .and_then(|overrides| overrides.as_ref()?.get(ext).copied());
+        if let Some(lang) = overridden {
+            return lang;
+        }
+
+        Self::builtin_from_extension(ext)
+    }
+
+    /// Language of a file, by its name for build files without an extension
+    /// (`Makefile`, `CMakeLists.txt`, `BUILD`) and by its extension otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some("Makefile" | "makefile" | "GNUmakefile") => Language::Make,
+            Some("CMakeLists.txt") => Language::CMake,
+            Some("BUILD" | "WORKSPACE") => Language::Bazel,
+            _ => Self::from_extension(path.extension().and_then(|e| e.to_str()).unwrap_or("")),
+        }
+    }
+
+    /// Built-in extension mapping (ignores `[index.extensions]`)
+    pub fn builtin_from_extension(ext: &str) -> Self {
         match ext {
             "rs" => Language::Rust,
             "py" => Language::Python,
@@ -98,10 +178,63 @@ impl Language {
             "kt" | "kts" => Language::Kotlin,
             "swift" => Language::Swift,
             "zig" => Language::Zig,
+            "sh" | "bash" | "zsh" | "ksh" => Language::Shell,
+            "sql" | "pgsql" | "psql" | "ddl" => Language::Sql,
+            "mk" => Language::Make,
+            "cmake" => Language::CMake,
+            "bazel" | "bzl" => Language::Bazel,
+            "ipynb" => Language::Python,  // Rendered to Python by `notebook`
             _ => Language::Unknown,
         }
     }
 
+    /// Language named by a `--lang`/`lang` filter (case-insensitive, short aliases allowed)
+    ///
+    /// Shared by the CLI, the HTTP API and the MCP tools so they accept the same names.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Some(Language::Rust),
+            "python" | "py" => Some(Language::Python),
+            "javascript" | "js" => Some(Language::JavaScript),
+            "typescript" | "ts" => Some(Language::TypeScript),
+            "vue" => Some(Language::Vue),
+            "svelte" => Some(Language::Svelte),
+            "go" => Some(Language::Go),
+            "java" => Some(Language::Java),
+            "php" => Some(Language::PHP),
+            "c" => Some(Language::C),
+            "cpp" | "c++" => Some(Language::Cpp),
+            "csharp" | "cs" | "c#" => Some(Language::CSharp),
+            "ruby" | "rb" => Some(Language::Ruby),
+            "kotlin" | "kt" => Some(Language::Kotlin),
+            "zig" => Some(Language::Zig),
+            "shell" | "sh" | "bash" | "zsh" => Some(Language::Shell),
+            "make" | "makefile" => Some(Language::Make),
+            "cmake" => Some(Language::CMake),
+            "bazel" | "starlark" | "bzl" => Some(Language::Bazel),
+            "sql" => Some(Language::Sql),
+            "graphql" | "gql" => Some(Language::GraphQL),
+            "html" => Some(Language::Html),
+            _ => None,
+        }
+    }
+
+    /// Map extra extensions to languages for the rest of the process
+    ///
+    /// Overrides win over the built-in mapping (`h = "cpp"` re-maps C headers).
+    /// Keys may be written with or without the leading dot. Replaces any
+    /// previously installed overrides.
+    pub fn set_extension_overrides(overrides: HashMap<String, Language>) {
+        let normalized = overrides
+            .into_iter()
+            .map(|(ext, lang)| (ext.trim_start_matches('.').to_string(), lang))
+            .collect();
+
+        if let Ok(mut current) = EXTENSION_OVERRIDES.write() {
+            *current = Some(normalized);
+        }
+    }
+
     /// Check if this language has a parser implementation
     ///
     /// Returns true only for languages with working Tree-sitter parsers.
@@ -124,6 +257,10 @@ impl Language {
             Language::Kotlin => true,
             Language::Swift => false,  // Temporarily disabled - requires tree-sitter 0.23
             Language::Zig => true,
+            Language::Shell => true,
+            Language::Make | Language::CMake | Language::Bazel => true,
+            Language::Sql => true,
+            Language::GraphQL | Language::Html => false,  // Embedded regions only
             Language::Unknown => false,
         }
     }
@@ -192,6 +329,26 @@ pub struct SearchResult {
     /// None for text/regex matches where symbol name cannot be accurately determined
     #[serde(skip_serializing_if = "Option::is_none")]
     pub symbol: Option<String>,
+    /// Name of the enclosing symbol (class, impl target, module, function).
+    /// Only present for nested symbols.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Full parent chain and name, e.g. `outer::Worker::run`.
+    /// Only present for nested symbols.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    /// Declaration signature without the body (parameters, return type, generics),
+    /// or the field list for structs/interfaces. Only present for symbol results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Doc comment or docstring attached to the symbol, markers stripped.
+    /// Only returned when requested (--with-docs).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Whether the symbol is exported from its file or module (`pub`, `export`,
+    /// `public`, ...). Only present for symbols of languages with a visibility model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported: Option<bool>,
     /// Location span in the source file
     pub span: Span,
     /// Code preview (few lines around the match)
@@ -200,6 +357,31 @@ pub struct SearchResult {
     /// DEPRECATED: Use FileGroupedResult.dependencies instead for file-level grouping
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<Vec<DependencyInfo>>,
+    /// Label of the index this result came from (only populated for multi-index queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Nodes bound to each named capture of the `--ast` pattern (only present for AST queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captures: Option<AstCaptures>,
+    /// Hybrid relevance of a `--semantic` match (see `embeddings`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// Named captures of an AST query match, keyed by capture name without the `@`
+///
+/// A capture holds several nodes when its pattern is quantified (e.g. `(_)* @args`).
+pub type AstCaptures = BTreeMap<String, Vec<AstCapture>>;
+
+/// A node bound to a capture of an AST query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AstCapture {
+    /// Tree-sitter node kind (e.g. `identifier`, `parameters`)
+    pub kind: String,
+    /// Source text of the node
+    pub text: String,
+    /// Location span in the source file
+    pub span: Span,
 }
 
 /// An individual match within a file (no path or dependencies)
@@ -211,6 +393,21 @@ pub struct MatchResult {
     /// Symbol name (e.g., function name, class name)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub symbol: Option<String>,
+    /// Name of the enclosing symbol (only present for nested symbols)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Full parent chain and name, e.g. `outer::Worker::run` (only present for nested symbols)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    /// Declaration signature (only present for symbol results)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Doc comment or docstring (only present with --with-docs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Whether the symbol is exported from its file or module (only present for symbol results)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported: Option<bool>,
     /// Location span in the source file
     pub span: Span,
     /// Code preview (few lines around the match)
@@ -221,6 +418,107 @@ pub struct MatchResult {
     /// Lines of code after the match (for context)
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub context_after: Vec<String>,
+    /// Why this result was returned (how the pattern matched, which filters it passed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_reason: Option<MatchReason>,
+    /// Notebook cell holding the match (only present for `.ipynb` files)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<NotebookCell>,
+    /// Nodes bound to each named capture of the `--ast` pattern (only present for AST queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captures: Option<AstCaptures>,
+    /// Last commit touching the match's span (only present with --blame)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<BlameInfo>,
+}
+
+/// Most recent change to the lines of a match (see `blame`)
+///
+/// Lines that aren't committed yet report git's all-zero commit and the
+/// author "Not Committed Yet".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlameInfo {
+    /// Full commit SHA
+    pub commit: String,
+    pub author: String,
+    pub author_email: String,
+    /// First line of the commit message
+    pub summary: String,
+    /// Unix timestamp of the authored change
+    pub timestamp: i64,
+    /// Days since the change
+    pub age_days: i64,
+}
+
+/// Type of a Jupyter notebook cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellType {
+    Code,
+    Markdown,
+    Raw,
+}
+
+impl CellType {
+    /// Parse an nbformat `cell_type` (unknown types are treated as raw)
+    pub fn from_nbformat(cell_type: &str) -> Self {
+        match cell_type {
+            "code" => CellType::Code,
+            "markdown" => CellType::Markdown,
+            _ => CellType::Raw,
+        }
+    }
+}
+
+/// Location of a match inside a notebook cell
+///
+/// Spans of notebook results refer to lines of the rendered notebook (see
+/// `notebook`); this maps them back to the cell the user sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookCell {
+    /// Cell number within the notebook (1-indexed)
+    pub cell: usize,
+    pub cell_type: CellType,
+    /// Line within the cell (1-indexed)
+    pub line: usize,
+}
+
+/// How a result matched the query pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMethod {
+    /// Symbol name equals the pattern
+    ExactSymbol,
+    /// Symbol name contains the pattern
+    SymbolSubstring,
+    /// Pattern found at word boundaries (default full-text search)
+    WordBoundary,
+    /// Pattern found anywhere in the line (--contains)
+    Substring,
+    /// Line matched the regex (--regex)
+    Regex,
+    /// Node captured by a tree-sitter query (--ast)
+    AstCapture,
+    /// Pattern is a language keyword, so every symbol of that kind is listed
+    KeywordListing,
+    /// Symbol name is similar to the pattern (--fuzzy)
+    Fuzzy,
+    /// An identifier contains every word part of the pattern (--tokens)
+    IdentifierTokens,
+    /// Chunk is similar in meaning to the query (--semantic)
+    Semantic,
+}
+
+/// Explanation of why a result was returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchReason {
+    pub method: MatchMethod,
+    /// Filters the result passed, as `name=value` (e.g. `lang=rust`, `glob=src/**`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<String>,
+    /// Name similarity of a fuzzy match or hybrid relevance of a semantic match (0.0-1.0, --fuzzy and --semantic only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
 }
 
 /// File-level grouped results with dependencies at file level
@@ -231,10 +529,49 @@ pub struct FileGroupedResult {
     /// File dependencies (only populated when --dependencies flag is used)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<Vec<DependencyInfo>>,
+    /// Tests, counterparts, importers and imports of this file (only populated when --related is used)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_files: Option<Vec<RelatedFile>>,
+    /// Content transform applied before indexing; matches reflect the transformed
+    /// content rather than the file on disk (see `[transforms]` in config.toml)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transform: Option<String>,
+    /// CODEOWNERS owners of this file (only populated when --with-owners is used;
+    /// empty = no rule owns it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<String>>,
+    /// Label of the index this file came from (only populated for multi-index queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
     /// Individual matches within this file
     pub matches: Vec<MatchResult>,
 }
 
+/// How a related file is connected to a result file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// Test file covering this file (`foo_test.go`, `test_foo.py`, `Foo.spec.ts`, ...)
+    Test,
+    /// Header/implementation counterpart (`foo.h` ↔ `foo.c`)
+    Counterpart,
+    /// File importing this file
+    Importer,
+    /// File imported by this file
+    Import,
+}
+
+/// A file in the neighborhood of a result file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelatedFile {
+    pub path: String,
+    pub relation: Relation,
+    /// Strength of the relation (higher = closer). Importers and imports count
+    /// imported symbols (1 per plain import); tests and counterparts count
+    /// shared leading directories.
+    pub weight: usize,
+}
+
 impl SearchResult {
     pub fn new(
         path: String,
@@ -245,16 +582,35 @@ impl SearchResult {
         scope: Option<String>,
         preview: String,
     ) -> Self {
-        // Ignore scope parameter for backwards compatibility
-        let _ = scope;
+        // Parsers pass the enclosing declaration as "<keyword> <name>" (`class Foo`, `impl Foo`)
+        let parent = scope.map(|s| match s.split_once(' ') {
+            Some((_, name)) => name.trim().to_string(),
+            None => s,
+        });
         Self {
             path,
             lang,
             kind,
             symbol,
+            parent,
+            qualified_name: None,
+            signature: None,
+            doc: None,
+            exported: None,
             span,
             preview,
             dependencies: None,
+            repo: None,
+            captures: None,
+            score: None,
+        }
+    }
+
+    /// Path for display, prefixed with the repo label (`repo:path`) on multi-index results
+    pub fn display_path(&self) -> String {
+        match &self.repo {
+            Some(repo) => format!("{}:{}", repo, self.path),
+            None => self.path.clone(),
         }
     }
 }
@@ -268,14 +624,33 @@ pub struct IndexConfig {
     pub include_patterns: Vec<String>,
     /// Glob patterns to exclude
     pub exclude_patterns: Vec<String>,
-    /// Follow symbolic links
+    /// Extra ignore rules in gitignore syntax, applied on top of .gitignore and .reflexignore
+    pub ignore_patterns: Vec<String>,
+    /// Follow symbolic links; loops are skipped and a file reached through
+    /// several paths is indexed once
     pub follow_symlinks: bool,
+    /// Index a file with several hard links once (Unix only)
+    #[serde(default)]
+    pub dedupe_hardlinks: bool,
     /// Maximum file size to index (bytes)
     pub max_file_size: usize,
+    /// Skip files with a line longer than this (bytes, 0 = no limit); catches minified bundles
+    #[serde(default = "default_max_line_length")]
+    pub max_line_length: usize,
+    /// Skip files that look binary (a NUL byte near the start)
+    #[serde(default = "default_true")]
+    pub binary_detection: bool,
     /// Number of threads for parallel indexing (0 = auto, 80% of available cores)
     pub parallel_threads: usize,
+    /// Approximate memory budget for file content held during indexing (MB)
+    pub memory_budget_mb: usize,
     /// Query timeout in seconds (0 = no timeout)
     pub query_timeout_secs: u64,
+    /// Named sub-directories to index (empty = entire workspace)
+    pub roots: Vec<WorkspaceRoot>,
+    /// Extra extension → language mappings, on top of `[index.extensions]` in config.toml
+    #[serde(default)]
+    pub extensions: HashMap<String, Language>,
 }
 
 impl Default for IndexConfig {
@@ -284,14 +659,117 @@ impl Default for IndexConfig {
             languages: vec![],
             include_patterns: vec![],
             exclude_patterns: vec![],
+            ignore_patterns: vec![],
             follow_symlinks: false,
+            dedupe_hardlinks: false,
             max_file_size: 10 * 1024 * 1024, // 10 MB
+            max_line_length: default_max_line_length(),
+            binary_detection: true,
             parallel_threads: 0, // 0 = auto (80% of available cores)
+            memory_budget_mb: 512,
             query_timeout_secs: 30, // 30 seconds default timeout
+            roots: vec![],
+            extensions: HashMap::new(),
         }
     }
 }
 
+fn default_max_line_length() -> usize {
+    10_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Why a discovered file was left out of the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Larger than `max_file_size`
+    TooLarge,
+    /// Has a line longer than `max_line_length`
+    LongLines,
+    /// Looks binary (`binary_detection`)
+    Binary,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::TooLarge => "too_large",
+            SkipReason::LongLines => "long_lines",
+            SkipReason::Binary => "binary",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "too_large" => Some(SkipReason::TooLarge),
+            "long_lines" => Some(SkipReason::LongLines),
+            "binary" => Some(SkipReason::Binary),
+            _ => None,
+        }
+    }
+}
+
+/// A file the last `rfx index` skipped because it exceeded a limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+    /// Measured value against the limit, e.g. "longest line is 48213 bytes (limit 10000)"
+    pub detail: String,
+}
+
+/// A named sub-directory of the workspace indexed as its own root
+///
+/// Used for monorepos where several packages share a single `.reflex/` cache.
+/// Query filters can address a root by name with an `@name` prefix
+/// (e.g., `--glob '@api/**/*.ts'` or `--file @web`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceRoot {
+    /// Short name used to address the root in filters
+    pub name: String,
+    /// Path relative to the workspace root (no leading `./` or trailing `/`)
+    pub path: String,
+}
+
+impl WorkspaceRoot {
+    /// Parse a root spec from the command line
+    ///
+    /// Accepts either `name=path` or a bare `path`, in which case the name
+    /// defaults to the last path component (`packages/api` → `api`).
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let (name, path) = match spec.split_once('=') {
+            Some((name, path)) => (name.trim().to_string(), path),
+            None => (String::new(), spec),
+        };
+
+        let path = path
+            .trim()
+            .trim_start_matches("./")
+            .trim_end_matches('/')
+            .to_string();
+
+        if path.is_empty() || path == "." {
+            anyhow::bail!("Invalid root '{}': path must be a sub-directory of the workspace", spec);
+        }
+
+        let name = if name.is_empty() {
+            path.rsplit('/').next().unwrap_or(&path).to_string()
+        } else {
+            name
+        };
+
+        if name.contains('/') || name.contains('@') {
+            anyhow::bail!("Invalid root name '{}': names may not contain '/' or '@'", name);
+        }
+
+        Ok(Self { name, path })
+    }
+}
+
 /// Statistics about the index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexStats {
@@ -305,6 +783,67 @@ pub struct IndexStats {
     pub files_by_language: std::collections::HashMap<String, usize>,
     /// Line count breakdown by language
     pub lines_by_language: std::collections::HashMap<String, usize>,
+    /// Symbol count breakdown by language (files in the symbol cache only)
+    #[serde(default)]
+    pub symbols_by_language: std::collections::HashMap<String, usize>,
+    /// Size of each index component on disk
+    #[serde(default)]
+    pub component_sizes: IndexComponentSizes,
+    /// Per-directory breakdown (`rfx stats --by-dir`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directories: Vec<DirectoryStats>,
+    /// Languages whose tree-sitter grammar failed to load (symbols come from the regex fallback parser)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_languages: Vec<DegradedLanguage>,
+    /// Set when the last index run skipped files (details list them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<IndexWarning>,
+}
+
+/// On-disk size of each index component (bytes)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexComponentSizes {
+    /// Trigram index (trigrams.bin)
+    pub trigrams: u64,
+    /// Stored file contents (content.bin)
+    pub content: u64,
+    /// Identifier word-part index (tokens.bin)
+    pub tokens: u64,
+    /// Cached symbols, stored inside meta.db
+    pub symbols: u64,
+    /// meta.db as a whole, including the cached symbols
+    pub metadata: u64,
+}
+
+/// Files, lines and symbols under one directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryStats {
+    /// Directory relative to the workspace root (`.` for files at the top level)
+    pub directory: String,
+    pub files: usize,
+    pub lines: usize,
+    pub symbols: usize,
+}
+
+/// A language parsed by the regex fallback parser because its grammar failed to load
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedLanguage {
+    pub language: String,
+    /// Grammar load error
+    pub error: String,
+}
+
+impl DegradedLanguage {
+    /// Every language currently running on the fallback parser
+    pub fn detect() -> Vec<Self> {
+        crate::parsers::ParserFactory::degraded_languages()
+            .iter()
+            .map(|(language, error)| Self {
+                language: format!("{:?}", language),
+                error: error.clone(),
+            })
+            .collect()
+    }
 }
 
 /// Information about an indexed file
@@ -316,6 +855,10 @@ pub struct IndexedFile {
     pub language: String,
     /// Last indexed timestamp
     pub last_indexed: String,
+    /// Where the file really lives, if it was reached through a symlink
+    /// (relative to the workspace when the target is inside it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_path: Option<String>,
 }
 
 /// Index status for query responses
@@ -340,6 +883,32 @@ pub struct IndexWarning {
     pub details: Option<IndexWarningDetails>,
 }
 
+/// Kind of notice reported in the CLI `warnings` channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CliWarningKind {
+    /// A flag or option that still works but will be removed
+    Deprecated,
+    /// Existing usage now behaves differently
+    BehaviorChange,
+    /// A feature is running in a reduced mode (e.g. a grammar failed to load)
+    Degraded,
+}
+
+/// Machine-readable deprecation or behavior-change notice (see `deprecations` registry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliWarning {
+    /// Stable notice code (e.g. "tuned-default-limit")
+    pub code: String,
+    pub kind: CliWarningKind,
+    /// Version that introduced the change
+    pub since: String,
+    /// What changed
+    pub message: String,
+    /// How to migrate
+    pub migration: String,
+}
+
 /// Detailed information about index staleness
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexWarningDetails {
@@ -355,6 +924,9 @@ pub struct IndexWarningDetails {
     /// Indexed commit SHA (if in git repo)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub indexed_commit: Option<String>,
+    /// Files left out of the index by the size, line length or binary limits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_files: Vec<SkippedFile>,
 }
 
 /// Pagination information for query results
@@ -373,6 +945,28 @@ pub struct PaginationInfo {
     pub has_more: bool,
 }
 
+/// Why a response holds partial results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PartialStatus {
+    /// `--timeout` expired while symbols were parsed or AST patterns matched;
+    /// files not reached by then are missing from the results
+    TimeoutPartial,
+    /// The `[search] max_memory_mb` ceiling was reached; candidate files and
+    /// parse batches that didn't fit are missing from the results
+    MemoryPartial,
+}
+
+/// Serde default for `complete` flags (responses are complete unless time-boxed)
+pub fn default_complete() -> bool {
+    true
+}
+
+/// Serde helper to omit zero counts
+pub fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
 /// Query response with results and index status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResponse {
@@ -390,9 +984,91 @@ pub struct QueryResponse {
     pub warning: Option<IndexWarning>,
     /// Pagination information
     pub pagination: PaginationInfo,
+    /// False when the time budget (`--budget-ms`) expired before every candidate was scanned,
+    /// or `--timeout` cut parsing short (see `partial`)
+    #[serde(default = "default_complete")]
+    pub complete: bool,
+    /// Candidate files skipped because the time budget expired
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unscanned_candidates: usize,
+    /// `TIMEOUT_PARTIAL` when the query timed out after its candidates were found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial: Option<PartialStatus>,
+    /// True when matches, previews or context were trimmed to fit `--max-tokens`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
     /// File-grouped search results
     /// Results are always grouped by file path, with dependencies populated when --dependencies flag is used
     pub results: Vec<FileGroupedResult>,
+    /// Removed files whose path or last symbols match the pattern (only with --include-deleted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<Vec<crate::tombstones::Tombstone>>,
+    /// Deprecation and behavior-change notices for this invocation
+    #[serde(default)]
+    pub warnings: Vec<CliWarning>,
+    /// How the query was executed (only with --explain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<QueryPlan>,
+    /// Time spent per query phase (only with --explain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<QueryTimings>,
+}
+
+/// How a query found its results (`rfx query --explain`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryPlan {
+    /// Candidate search: `trigram`, `regex`, `tokens`, `keyword` or `fuzzy`
+    /// (keyword and fuzzy queries scan every file of the language)
+    pub strategy: String,
+    /// The pattern is a language keyword, so every symbol of that kind is listed
+    pub keyword_mode: bool,
+    /// Symbol kind inferred from a keyword pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inferred_kind: Option<SymbolKind>,
+    /// Files whose trigrams contain the pattern, before their lines were verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigram_candidate_files: Option<usize>,
+    /// Matches returned by the candidate search
+    pub candidates: usize,
+    /// Candidates dropped because the dirty overlay holds a newer copy of the file
+    pub pruned_by_overlay: usize,
+    /// Candidates dropped by --lang
+    pub pruned_by_language: usize,
+    /// Candidates dropped by --glob/--exclude
+    pub pruned_by_glob: usize,
+    /// Candidate lines dropped by --not
+    pub pruned_by_not: usize,
+    /// Files skipped before parsing because every match was in a comment or string
+    pub prefiltered_files: usize,
+    /// Files whose symbols came from the symbol cache
+    pub symbol_cache_hits: usize,
+    /// Files parsed because their symbols weren't cached
+    pub symbol_parses: usize,
+    /// Results merged in from the dirty overlay
+    pub overlay_results: usize,
+    /// Results after every filter, before --offset/--limit
+    pub results: usize,
+    /// Most memory held at once by buffered results, file contents and parse batches (estimated)
+    pub peak_memory_bytes: u64,
+    /// Memory ceiling from `[search] max_memory_mb` (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_bytes: Option<u64>,
+}
+
+/// Milliseconds spent in each phase of a query (`rfx query --explain`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryTimings {
+    /// Finding candidates (trigram, token or regex search, or listing files)
+    pub candidates_ms: f64,
+    /// Overlay, language and glob filtering
+    pub filter_ms: f64,
+    /// Symbol extraction or AST matching
+    pub symbols_ms: f64,
+    /// Remaining filters, body expansion, overlay results and sorting
+    pub post_process_ms: f64,
+    /// Grouping by file, context lines and dependencies
+    pub grouping_ms: f64,
+    pub total_ms: f64,
 }
 
 /// Report from cache compaction operation
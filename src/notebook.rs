@@ -0,0 +1,196 @@
+//! Jupyter notebook (`.ipynb`) support
+//!
+//! Notebooks are JSON documents, so neither trigrams nor the Python parser can
+//! make sense of them directly. The indexer renders each notebook into a
+//! virtual Python file in the "percent" format used by jupytext and VS Code:
+//!
+//! ```text
+//! # %%
+//! import pandas as pd
+//! # %% [markdown]
+//! # ## Load the data
+//! # %%
+//! def load(path):
+//!     return pd.read_csv(path)
+//! ```
+//!
+//! Code cells are copied verbatim, markdown and raw cells become comment lines,
+//! and every cell starts with a `# %%` marker. The rendered text is what gets
+//! stored in the content store, so full-text matches, symbols and context lines
+//! all refer to virtual lines. [`locate_cell`] maps a virtual line back to its
+//! cell and the line within it, which query results report as `cell`.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::models::{CellType, NotebookCell};
+
+/// Marker line starting every rendered cell
+const CELL_MARKER: &str = "# %%";
+
+/// Whether `path` is a Jupyter notebook
+pub fn is_notebook(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext == "ipynb")
+}
+
+#[derive(Deserialize)]
+struct Notebook {
+    #[serde(default)]
+    cells: Vec<Cell>,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: Source,
+}
+
+/// Cell source: nbformat allows a single string or a list of lines
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Source {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source::Text(String::new())
+    }
+}
+
+impl Source {
+    fn text(self) -> String {
+        match self {
+            Source::Text(text) => text,
+            Source::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+/// Render a notebook's JSON into percent-format Python source
+pub fn render(json: &str) -> Result<String> {
+    let notebook: Notebook = serde_json::from_str(json)
+        .context("Failed to parse notebook JSON")?;
+
+    let mut rendered = String::new();
+    for cell in notebook.cells {
+        let cell_type = CellType::from_nbformat(&cell.cell_type);
+        let source = cell.source.text();
+
+        match cell_type {
+            CellType::Code => rendered.push_str(CELL_MARKER),
+            CellType::Markdown => rendered.push_str("# %% [markdown]"),
+            CellType::Raw => rendered.push_str("# %% [raw]"),
+        }
+        rendered.push('\n');
+
+        for line in source.lines() {
+            match cell_type {
+                CellType::Code => rendered.push_str(line),
+                _ if line.is_empty() => rendered.push('#'),
+                _ => {
+                    rendered.push_str("# ");
+                    rendered.push_str(line);
+                }
+            }
+            rendered.push('\n');
+        }
+    }
+
+    Ok(rendered)
+}
+
+/// Cell containing `line` (1-indexed) of a rendered notebook
+///
+/// Returns `None` for marker lines and lines past the end of the content.
+pub fn locate_cell(rendered: &str, line: usize) -> Option<NotebookCell> {
+    let mut current: Option<(usize, CellType, usize)> = None;
+    let mut cell_index = 0;
+
+    for (idx, text) in rendered.lines().enumerate().take(line) {
+        let line_no = idx + 1;
+        if let Some(cell_type) = parse_marker(text) {
+            cell_index += 1;
+            current = Some((cell_index, cell_type, line_no));
+            if line_no == line {
+                return None;
+            }
+        } else if line_no == line {
+            let (cell, cell_type, marker_line) = current?;
+            return Some(NotebookCell {
+                cell,
+                cell_type,
+                line: line_no - marker_line,
+            });
+        }
+    }
+
+    None
+}
+
+/// Cell type of a rendered marker line
+fn parse_marker(line: &str) -> Option<CellType> {
+    match line {
+        CELL_MARKER => Some(CellType::Code),
+        "# %% [markdown]" => Some(CellType::Markdown),
+        "# %% [raw]" => Some(CellType::Raw),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+        "cells": [
+            {"cell_type": "markdown", "metadata": {}, "source": ["# Analysis\n", "\n", "Loads the data"]},
+            {"cell_type": "code", "metadata": {}, "outputs": [], "source": "import pandas as pd"},
+            {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["def load(path):\n", "    return pd.read_csv(path)\n"]}
+        ],
+        "metadata": {"kernelspec": {"language": "python"}},
+        "nbformat": 4,
+        "nbformat_minor": 5
+    }"##;
+
+    #[test]
+    fn test_render_percent_format() {
+        assert_eq!(
+            render(NOTEBOOK).unwrap(),
+            "# %% [markdown]\n# # Analysis\n#\n# Loads the data\n\
+             # %%\nimport pandas as pd\n\
+             # %%\ndef load(path):\n    return pd.read_csv(path)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_invalid_json() {
+        assert!(render("not a notebook").is_err());
+    }
+
+    #[test]
+    fn test_locate_cell() {
+        let rendered = render(NOTEBOOK).unwrap();
+
+        assert_eq!(
+            locate_cell(&rendered, 4),
+            Some(NotebookCell { cell: 1, cell_type: CellType::Markdown, line: 3 })
+        );
+        assert_eq!(
+            locate_cell(&rendered, 8),
+            Some(NotebookCell { cell: 3, cell_type: CellType::Code, line: 1 })
+        );
+        // Marker lines and lines past the end belong to no cell
+        assert_eq!(locate_cell(&rendered, 5), None);
+        assert_eq!(locate_cell(&rendered, 42), None);
+    }
+
+    #[test]
+    fn test_is_notebook() {
+        assert!(is_notebook("analysis/explore.ipynb"));
+        assert!(!is_notebook("analysis/explore.py"));
+    }
+}
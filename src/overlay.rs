@@ -0,0 +1,375 @@
+//! Dirty working-tree overlay
+//!
+//! A full `rfx index` rebuilds the whole content store, which is too slow to
+//! run after every edit. Instead, `rfx index --dirty-only` indexes just the
+//! files that differ from the committed index (modified or untracked) into a
+//! small separate cache at `.reflex/overlay/`:
+//!
+//! - `content.bin`, `trigrams.bin`, `meta.db`: a regular index of the dirty files
+//! - `overlay.json`: the [`Overlay`] manifest (branch, HEAD, dirty and deleted paths)
+//!
+//! Queries drop main-index results for every path the overlay shadows and
+//! merge in the overlay's own results, so uncommitted changes are searchable
+//! without warnings about a stale index. The overlay only applies on the
+//! branch it was built for and is cleared by the next full `rfx index`.
+//!
+//! `rfx index --only <glob>` refreshes just the matching files. Their changes
+//! are added to the overlay next to the entries already in it, so editors and
+//! hooks can re-index a handful of files without walking the whole tree.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheManager;
+use crate::error_code::ReflexError;
+use crate::indexer::Indexer;
+use crate::models::IndexConfig;
+use crate::paths::relative_path;
+
+/// Overlay directory inside `.reflex/`
+pub const OVERLAY_DIR: &str = "overlay";
+
+/// Manifest file inside the overlay directory
+const MANIFEST: &str = "overlay.json";
+
+/// Manifest of the dirty working-tree overlay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Overlay {
+    /// Branch the overlay was built on (`_default` outside git repos)
+    pub branch: String,
+    /// HEAD commit when the overlay was built
+    pub commit: Option<String>,
+    /// Unix timestamp of the overlay build
+    pub indexed_at: i64,
+    /// Files indexed into the overlay (modified or new), sorted
+    pub modified: Vec<String>,
+    /// Files in the main index that no longer exist, sorted
+    pub deleted: Vec<String>,
+    /// Workspace root that result paths are relative to
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl Overlay {
+    /// Load the overlay manifest of `cache`, if one exists
+    pub fn open(cache: &CacheManager) -> Option<Self> {
+        let path = cache.overlay().path().join(MANIFEST);
+        let json = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Overlay>(&json) {
+            Ok(overlay) => Some(Overlay { root: cache.workspace_root(), ..overlay }),
+            Err(e) => {
+                log::warn!("Ignoring unreadable overlay manifest {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Remove the overlay of `cache` (no-op if there is none)
+    pub fn clear(cache: &CacheManager) -> Result<()> {
+        let dir = cache.overlay().path().to_path_buf();
+        if dir.exists() {
+            log::info!("Clearing dirty overlay at {:?}", dir);
+            std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to remove overlay at {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Whether results for `path` must come from the overlay instead of the main index
+    pub fn shadows(&self, path: &str) -> bool {
+        let path = relative_path(Path::new(path), &self.root);
+        self.modified.binary_search(&path).is_ok() || self.deleted.binary_search(&path).is_ok()
+    }
+
+    /// Whether the overlay holds any indexed files
+    pub fn has_files(&self) -> bool {
+        !self.modified.is_empty()
+    }
+}
+
+/// Index modified and untracked files of `root` into the overlay
+///
+/// A file is dirty when it is missing from the main index of the current
+/// branch or its content hash differs. Files untouched since the last full
+/// index are skipped by mtime, so only recently edited files are read.
+pub fn build_overlay(root: &Path, cache: &CacheManager, config: IndexConfig) -> Result<Overlay> {
+    build(root, cache, config, None)
+}
+
+/// Re-index only the files matching `selectors` into the overlay
+///
+/// Selectors are globs or paths relative to `root`. Matching files are
+/// checked like [`build_overlay`] does; matching paths that were indexed but
+/// no longer exist are recorded as deleted. Overlay entries outside the
+/// selection are kept, so repeated calls accumulate.
+pub fn build_partial_overlay(
+    root: &Path,
+    cache: &CacheManager,
+    config: IndexConfig,
+    selectors: &[String],
+) -> Result<Overlay> {
+    let selection = selection_matcher(root, selectors)?;
+    build(root, cache, config, Some(&selection))
+}
+
+/// Glob set for `--only` selectors, with paths made relative to `root`
+fn selection_matcher(root: &Path, selectors: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = globset::GlobSetBuilder::new();
+    for selector in selectors {
+        let selector = relative_path(Path::new(selector.trim()), root);
+        let selector = selector.trim_end_matches('/');
+        if selector.is_empty() {
+            continue;
+        }
+        // A directory selects everything below it
+        for pattern in [selector.to_string(), format!("{}/**", selector)] {
+            let glob = globset::GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| ReflexError::InvalidArgument(format!("Invalid --only pattern '{}': {}", selector, e)))?;
+            builder.add(glob);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+fn build(root: &Path, cache: &CacheManager, config: IndexConfig, selection: Option<&globset::GlobSet>) -> Result<Overlay> {
+    let git_state = crate::git::get_git_state_optional(root)?;
+    let branch = git_state
+        .as_ref()
+        .map(|s| s.branch.clone())
+        .unwrap_or_else(|| "_default".to_string());
+
+    if !cache.exists() || !cache.branch_exists(&branch)? {
+        return Err(ReflexError::IndexMissing(format!(
+            "No index found for branch '{}'.\n\
+             \n\
+             The dirty overlay sits on top of a full index. Build one first:\n\
+             rfx index",
+            branch
+        )).into());
+    }
+
+    let branch_info = cache.get_branch_info(&branch)?;
+    let main_hashes = cache.load_hashes_for_branch(&branch)?;
+
+    let indexer = Indexer::new(cache.clone(), config.clone());
+    let selected = |rel: &str| selection.is_none_or(|s| s.is_match(rel));
+    let files = indexer.discover_workspace_files(root)?;
+
+    // Transformed files are stored under a hash keyed by their transform
+    let transform_config = crate::transforms::load_transform_config(cache.path())?;
+    let transformer = crate::transforms::Transformer::from_config(&transform_config, root)?;
+
+    let mut seen = HashSet::new();
+    let mut dirty: Vec<(String, PathBuf)> = Vec::new();
+
+    for file_path in files {
+        let rel = relative_path(&file_path, root);
+        if !selected(&rel) {
+            continue;
+        }
+        seen.insert(rel.clone());
+
+        let Some(indexed_hash) = main_hashes.get(&rel) else {
+            dirty.push((rel, file_path));
+            continue;
+        };
+
+        if modified_secs(&file_path).is_some_and(|m| m < branch_info.last_indexed) {
+            continue;
+        }
+
+        match std::fs::read(&file_path) {
+            Ok(content) if &crate::transforms::keyed_hash(
+                &indexer.hash_content(&content),
+                transformer.identity_for(&rel),
+            ) == indexed_hash => {}
+            _ => dirty.push((rel, file_path)),
+        }
+    }
+
+    let mut deleted: Vec<String> = main_hashes
+        .into_keys()
+        .filter(|path| selected(path) && !seen.contains(path))
+        .collect();
+
+    // A partial build keeps what the overlay already holds outside the selection
+    if let Some(previous) = selection.and_then(|_| Overlay::open(cache)).filter(|o| o.branch == branch) {
+        for rel in previous.modified.into_iter().filter(|rel| !selected(rel)) {
+            let path = root.join(&rel);
+            if path.exists() {
+                dirty.push((rel, path));
+            } else {
+                deleted.push(rel);
+            }
+        }
+        deleted.extend(previous.deleted.into_iter().filter(|rel| !selected(rel)));
+    }
+    deleted.sort();
+    deleted.dedup();
+    dirty.sort();
+
+    Overlay::clear(cache)?;
+
+    let overlay_cache = cache.overlay();
+    if !dirty.is_empty() {
+        // Share the main index settings (e.g. [flags] patterns)
+        std::fs::create_dir_all(overlay_cache.path())?;
+        let config_toml = cache.path().join(crate::cache::CONFIG_TOML);
+        if config_toml.exists() {
+            std::fs::copy(&config_toml, overlay_cache.path().join(crate::cache::CONFIG_TOML))?;
+        }
+
+        let paths = dirty.iter().map(|(_, path)| path.clone()).collect();
+        Indexer::new(overlay_cache.clone(), config)
+            .index_files(root, paths)
+            .context("Failed to index dirty files into the overlay")?;
+    }
+
+    let overlay = Overlay {
+        branch,
+        commit: git_state.map(|s| s.commit),
+        indexed_at: chrono::Utc::now().timestamp(),
+        modified: dirty.into_iter().map(|(rel, _)| rel).collect(),
+        deleted,
+        root: root.to_path_buf(),
+    };
+
+    std::fs::create_dir_all(overlay_cache.path())?;
+    std::fs::write(
+        overlay_cache.path().join(MANIFEST),
+        serde_json::to_string_pretty(&overlay)?,
+    )
+    .context("Failed to write overlay manifest")?;
+
+    log::info!(
+        "Dirty overlay: {} modified, {} deleted files",
+        overlay.modified.len(),
+        overlay.deleted.len()
+    );
+
+    Ok(overlay)
+}
+
+/// Modification time in Unix seconds
+fn modified_secs(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{QueryEngine, QueryFilter};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn full_index(root: &Path) -> CacheManager {
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default())
+            .index(root, false)
+            .unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_overlay_tracks_modified_new_and_deleted_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.rs"), "fn alpha() {}\n").unwrap();
+        fs::write(root.join("b.rs"), "fn beta() {}\n").unwrap();
+        fs::write(root.join("c.rs"), "fn gamma() {}\n").unwrap();
+        let cache = full_index(root);
+
+        fs::write(root.join("a.rs"), "fn alpha_renamed() {}\n").unwrap();
+        fs::write(root.join("d.rs"), "fn delta() {}\n").unwrap();
+        fs::remove_file(root.join("c.rs")).unwrap();
+
+        let overlay = build_overlay(root, &cache, IndexConfig::default()).unwrap();
+        assert_eq!(overlay.modified, vec!["a.rs", "d.rs"]);
+        assert_eq!(overlay.deleted, vec!["c.rs"]);
+        assert!(overlay.shadows("./a.rs"));
+        assert!(overlay.shadows("c.rs"));
+        assert!(!overlay.shadows("b.rs"));
+
+        let reopened = Overlay::open(&cache).unwrap();
+        assert_eq!(reopened.modified, overlay.modified);
+    }
+
+    #[test]
+    fn test_queries_merge_overlay_results() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.rs"), "fn handle_old() {}\n").unwrap();
+        fs::write(root.join("b.rs"), "fn handle_other() {}\n").unwrap();
+        let cache = full_index(root);
+
+        fs::write(root.join("a.rs"), "fn handle_new() {}\n").unwrap();
+        build_overlay(root, &cache, IndexConfig::default()).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(root));
+        let filter = QueryFilter { suppress_output: true, ..Default::default() };
+
+        assert!(engine.search("handle_old", filter.clone()).unwrap().is_empty());
+        let results = engine.search("handle_new", filter.clone()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("a.rs"));
+        let all = QueryFilter { use_contains: true, ..filter };
+        assert_eq!(engine.search("handle_", all).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_full_index_clears_overlay() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.rs"), "fn alpha() {}\n").unwrap();
+        let cache = full_index(root);
+
+        fs::write(root.join("b.rs"), "fn beta() {}\n").unwrap();
+        build_overlay(root, &cache, IndexConfig::default()).unwrap();
+        assert!(Overlay::open(&cache).is_some());
+
+        full_index(root);
+        assert!(Overlay::open(&cache).is_none());
+    }
+
+    #[test]
+    fn test_partial_overlay_only_touches_selection() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "fn alpha() {}\n").unwrap();
+        fs::write(root.join("src/b.rs"), "fn beta() {}\n").unwrap();
+        fs::write(root.join("c.rs"), "fn gamma() {}\n").unwrap();
+        let cache = full_index(root);
+
+        fs::write(root.join("src/a.rs"), "fn alpha_v2() {}\n").unwrap();
+        fs::write(root.join("c.rs"), "fn gamma_v2() {}\n").unwrap();
+
+        let overlay = build_partial_overlay(root, &cache, IndexConfig::default(), &["src/*.rs".to_string()]).unwrap();
+        assert_eq!(overlay.modified, vec!["src/a.rs"]);
+
+        // A second selection adds to the overlay instead of replacing it
+        fs::remove_file(root.join("src/b.rs")).unwrap();
+        let overlay = build_partial_overlay(root, &cache, IndexConfig::default(), &["./c.rs".to_string(), "src/b.rs".to_string()]).unwrap();
+        assert_eq!(overlay.modified, vec!["c.rs", "src/a.rs"]);
+        assert_eq!(overlay.deleted, vec!["src/b.rs"]);
+
+        let engine = QueryEngine::new(CacheManager::new(root));
+        let filter = QueryFilter { suppress_output: true, ..Default::default() };
+        assert_eq!(engine.search("alpha_v2", filter.clone()).unwrap().len(), 1);
+        assert_eq!(engine.search("gamma_v2", filter.clone()).unwrap().len(), 1);
+        assert!(engine.search("beta", filter).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_overlay_requires_full_index() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("a.rs"), "fn alpha() {}\n").unwrap();
+        let cache = CacheManager::new(temp.path());
+        assert!(build_overlay(temp.path(), &cache, IndexConfig::default()).is_err());
+    }
+}
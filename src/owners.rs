@@ -0,0 +1,365 @@
+//! CODEOWNERS integration
+//!
+//! `rfx index` looks for a CODEOWNERS file in the places GitHub and GitLab
+//! read it from (`.github/`, the repository root, `docs/`, `.gitlab/`) and
+//! records its rules in the `config` table of `meta.db`. Queries then use the
+//! recorded rules to annotate results with their owners (`--with-owners`) or
+//! to restrict a search to one team's files (`--owner @team-payments`).
+//!
+//! Matching follows the CODEOWNERS semantics: patterns use gitignore syntax,
+//! and the last matching rule wins. A rule without owners clears ownership for
+//! the paths it matches.
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::cache::{CacheManager, META_DB};
+
+/// Locations checked for a CODEOWNERS file, in priority order
+pub const CODEOWNERS_LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+const CODEOWNERS_KEY: &str = "codeowners";
+
+/// One `pattern @owner...` line of a CODEOWNERS file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+    /// 1-based line number in the CODEOWNERS file
+    pub line: usize,
+}
+
+/// Parsed CODEOWNERS rules
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeOwners {
+    /// Path of the CODEOWNERS file, relative to the project root
+    pub source: String,
+    pub rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    /// Parse CODEOWNERS text
+    ///
+    /// Blank lines and comments are skipped and `\#` / `\ ` escapes in
+    /// patterns are honoured. Rules under a GitLab `[Section] @owner` header
+    /// without owners of their own inherit the section's default owners.
+    pub fn parse(source: &str, text: &str) -> Self {
+        let mut rules = Vec::new();
+        let mut section_owners: Vec<String> = Vec::new();
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(defaults) = section_header(line) {
+                section_owners = owner_tokens(defaults);
+                continue;
+            }
+
+            let (pattern, rest) = split_pattern(line);
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let mut owners = owner_tokens(rest);
+            if owners.is_empty() {
+                owners = section_owners.clone();
+            }
+
+            rules.push(OwnerRule { pattern, owners, line: idx + 1 });
+        }
+
+        Self { source: source.to_string(), rules }
+    }
+
+    /// Find and parse the project's CODEOWNERS file, if any
+    pub fn discover(root: &Path) -> Result<Option<Self>> {
+        for location in CODEOWNERS_LOCATIONS {
+            let path = root.join(location);
+            if path.is_file() {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                return Ok(Some(Self::parse(location, &text)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Compile the rules into a matcher
+    ///
+    /// Rules whose pattern cannot be compiled are skipped with a warning,
+    /// matching how GitHub ignores invalid lines.
+    pub fn matcher(&self) -> OwnerMatcher {
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            match compile_pattern(&rule.pattern) {
+                Ok(globs) => rules.push((globs, rule.owners.clone())),
+                Err(e) => log::warn!("Ignoring {} line {}: invalid pattern '{}': {}",
+                    self.source, rule.line, rule.pattern, e),
+            }
+        }
+        OwnerMatcher { rules }
+    }
+}
+
+/// Compiled CODEOWNERS rules
+pub struct OwnerMatcher {
+    rules: Vec<(GlobSet, Vec<String>)>,
+}
+
+impl OwnerMatcher {
+    /// Owners of a path relative to the project root (empty = unowned)
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        let path = normalize_path(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(globs, _)| globs.is_match(path.as_str()))
+            .map(|(_, owners)| owners.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Whether `owner` (e.g. `@team-payments`) owns the path
+    pub fn is_owned_by(&self, path: &str, owner: &str) -> bool {
+        self.owners_of(path).iter().any(|candidate| owner_matches(candidate, owner))
+    }
+}
+
+/// Compare a CODEOWNERS entry against a user-supplied owner
+///
+/// Case-insensitive, the leading `@` is optional, and a bare team name
+/// matches an organisation-qualified team (`payments` = `@acme/payments`).
+pub fn owner_matches(candidate: &str, owner: &str) -> bool {
+    let candidate = candidate.trim_start_matches('@').to_lowercase();
+    let owner = owner.trim().trim_start_matches('@').to_lowercase();
+    if owner.is_empty() {
+        return false;
+    }
+    candidate == owner
+        || (!owner.contains('/')
+            && candidate.rsplit_once('/').is_some_and(|(_, team)| team == owner))
+}
+
+/// Record the CODEOWNERS rules in meta.db (`None` clears a stale record)
+pub fn record(cache: &CacheManager, owners: Option<&CodeOwners>) -> Result<()> {
+    let conn = Connection::open(cache.path().join(META_DB))
+        .context("Failed to open meta.db")?;
+
+    match owners {
+        Some(owners) => {
+            let json = serde_json::to_string(owners)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO config (key, value) VALUES (?, ?)",
+                [CODEOWNERS_KEY, json.as_str()],
+            )?;
+            log::debug!("Recorded {} CODEOWNERS rules from {}", owners.rules.len(), owners.source);
+        }
+        None => {
+            conn.execute("DELETE FROM config WHERE key = ?", [CODEOWNERS_KEY])?;
+        }
+    }
+    Ok(())
+}
+
+/// Load the CODEOWNERS rules recorded by the last `rfx index`, if any
+pub fn load(cache: &CacheManager) -> Result<Option<CodeOwners>> {
+    let db_path = cache.path().join(META_DB);
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let conn = Connection::open(&db_path)
+        .context("Failed to open meta.db")?;
+
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM config WHERE key = ?", [CODEOWNERS_KEY], |row| row.get(0))
+        .optional()?;
+
+    match value {
+        Some(json) => Ok(Some(serde_json::from_str(&json)
+            .context("Failed to parse recorded CODEOWNERS rules")?)),
+        None => Ok(None),
+    }
+}
+
+/// Parse a GitLab `[Section]`, `^[Optional]` or `[Section][2]` header,
+/// returning the text after it (the section's default owners)
+///
+/// A pattern such as `[Mm]akefile` is not a header: headers are followed by
+/// whitespace or nothing.
+fn section_header(line: &str) -> Option<&str> {
+    let line = line.strip_prefix('^').unwrap_or(line);
+    let rest = line.strip_prefix('[')?;
+    let rest = &rest[rest.find(']')? + 1..];
+
+    // Optional `[n]` approval count
+    let rest = match rest.strip_prefix('[') {
+        Some(count) => {
+            let end = count.find(']')?;
+            if !count[..end].chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            &count[end + 1..]
+        }
+        None => rest,
+    };
+
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
+
+/// Owner tokens up to an inline comment
+fn owner_tokens(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .take_while(|token| !token.starts_with('#'))
+        .map(|token| token.to_string())
+        .collect()
+}
+
+/// Split a rule line into its (unescaped) pattern and the remainder
+fn split_pattern(line: &str) -> (String, &str) {
+    let mut pattern = String::new();
+    let mut chars = line.char_indices();
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some((_, next)) = chars.next() {
+                    pattern.push(next);
+                }
+            }
+            c if c.is_whitespace() => return (pattern, &line[idx..]),
+            c => pattern.push(c),
+        }
+    }
+
+    (pattern, "")
+}
+
+/// Translate a gitignore-style pattern into globs over root-relative paths
+fn compile_pattern(pattern: &str) -> Result<GlobSet> {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.starts_with('/') || trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+
+    let base = if trimmed.is_empty() {
+        "**".to_string()
+    } else if anchored {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+
+    let mut globs = vec![format!("{}/**", base)];
+    if !dir_only {
+        globs.push(base);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(GlobBuilder::new(&glob).literal_separator(true).build()?);
+    }
+    Ok(builder.build()?)
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE: &str = "\
+# Default owners
+*                   @acme/platform
+
+/src/payments/      @acme/team-payments @alice
+*.md                docs@example.com
+docs/**/*.png       @acme/design
+/vendor/
+src/payments/README.md @acme/platform  # inline comment
+";
+
+    #[test]
+    fn test_parse_codeowners() {
+        let owners = CodeOwners::parse("CODEOWNERS", SAMPLE);
+        assert_eq!(owners.rules.len(), 6);
+        assert_eq!(owners.rules[1].pattern, "/src/payments/");
+        assert_eq!(owners.rules[1].owners, vec!["@acme/team-payments", "@alice"]);
+        assert_eq!(owners.rules[1].line, 4);
+        assert!(owners.rules[4].owners.is_empty());
+        assert_eq!(owners.rules[5].owners, vec!["@acme/platform"]);
+    }
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let matcher = CodeOwners::parse("CODEOWNERS", SAMPLE).matcher();
+
+        assert_eq!(matcher.owners_of("src/main.rs"), ["@acme/platform"]);
+        assert_eq!(matcher.owners_of("src/payments/charge.rs"), ["@acme/team-payments", "@alice"]);
+        assert_eq!(matcher.owners_of("src/payments/stripe/client.rs"), ["@acme/team-payments", "@alice"]);
+        assert_eq!(matcher.owners_of("src/payments/README.md"), ["@acme/platform"]);
+        assert_eq!(matcher.owners_of("guide/intro.md"), ["docs@example.com"]);
+        assert_eq!(matcher.owners_of("docs/img/arch/overview.png"), ["@acme/design"]);
+        assert!(matcher.owners_of("vendor/lib/dep.c").is_empty());
+        // Anchored directory patterns don't match elsewhere in the tree
+        assert_eq!(matcher.owners_of("lib/src/payments/x.rs"), ["@acme/platform"]);
+    }
+
+    #[test]
+    fn test_gitlab_sections() {
+        let text = "[Backend] @backend\napp/api/\n[Mm]akefile @build\n^[Docs][2] @writers\ndocs/ @lead\nguide/\n";
+        let owners = CodeOwners::parse("CODEOWNERS", text);
+        let patterns: Vec<_> = owners.rules.iter().map(|r| (r.pattern.as_str(), r.owners.clone())).collect();
+        assert_eq!(patterns, vec![
+            ("app/api/", vec!["@backend".to_string()]),
+            ("[Mm]akefile", vec!["@build".to_string()]),
+            ("docs/", vec!["@lead".to_string()]),
+            ("guide/", vec!["@writers".to_string()]),
+        ]);
+        assert_eq!(owners.matcher().owners_of("sub/Makefile"), ["@build"]);
+    }
+
+    #[test]
+    fn test_owner_matches() {
+        assert!(owner_matches("@acme/team-payments", "@acme/team-payments"));
+        assert!(owner_matches("@acme/team-payments", "@team-payments"));
+        assert!(owner_matches("@acme/Team-Payments", "team-payments"));
+        assert!(owner_matches("@alice", "alice"));
+        assert!(!owner_matches("@acme/team-payments", "@other/team-payments"));
+        assert!(!owner_matches("@acme/team-payments", "payments"));
+        assert!(!owner_matches("@alice", ""));
+    }
+
+    #[test]
+    fn test_discover_and_record_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".github")).unwrap();
+        std::fs::write(temp.path().join(".github/CODEOWNERS"), "*.rs @rustaceans\n").unwrap();
+
+        let owners = CodeOwners::discover(temp.path()).unwrap().unwrap();
+        assert_eq!(owners.source, ".github/CODEOWNERS");
+
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        assert!(load(&cache).unwrap().is_none());
+
+        record(&cache, Some(&owners)).unwrap();
+        assert_eq!(load(&cache).unwrap(), Some(owners));
+
+        record(&cache, None).unwrap();
+        assert!(load(&cache).unwrap().is_none());
+    }
+}
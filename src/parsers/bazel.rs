@@ -0,0 +1,384 @@
+//! Bazel parser
+//!
+//! Extracts symbols from Bazel files (`BUILD`, `BUILD.bazel`, `WORKSPACE`,
+//! `MODULE.bazel`, `*.bzl`):
+//! - Rule instances with a `name` (kind: target), e.g. `cc_library(name = "core")`
+//! - Starlark functions (`def`) and rule/macro definitions (`my_rule = rule(...)`)
+//!
+//! Labels in `deps`-like attributes (`deps`, `runtime_deps`, `exports`,
+//! `data`, `tools`, ...) and `load()` statements are extracted as
+//! dependencies. `//pkg:target` labels resolve to the package's BUILD file,
+//! relative to the index root (assumed to be the workspace root); labels of
+//! other repositories (`@repo//pkg`) are external.
+//!
+//! Note: This parser is line-based since no tree-sitter grammar for Starlark
+//! is bundled.
+
+use anyhow::Result;
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::models::{ImportType, Language, SearchResult, Span, SymbolKind};
+use crate::parsers::{DependencyExtractor, ImportInfo};
+
+/// Top-level calls that configure a package or workspace rather than define a target
+const NON_TARGET_CALLS: &[&str] = &["load", "package", "workspace", "module", "bazel_dep", "licenses"];
+
+/// Calls whose result is a rule or macro definition (`my_rule = rule(...)`)
+const DEFINING_CALLS: &[&str] = &["rule", "macro", "repository_rule", "aspect", "module_extension"];
+
+/// A top-level call: `name(...)` or `variable = name(...)`
+struct Call {
+    /// Function called (`cc_library`)
+    function: String,
+    /// Variable assigned the result, if any
+    assigned: Option<String>,
+    /// Text between the parentheses
+    body: String,
+    /// First line (0-indexed)
+    start: usize,
+    /// Line of the closing parenthesis (0-indexed)
+    end: usize,
+}
+
+/// Parse a Bazel file and extract targets and Starlark definitions
+pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut symbols = Vec::new();
+
+    for call in top_level_calls(&lines) {
+        let (name, kind) = match &call.assigned {
+            Some(variable) if DEFINING_CALLS.contains(&call.function.as_str()) => (variable.clone(), SymbolKind::Function),
+            Some(_) => continue,
+            None if NON_TARGET_CALLS.contains(&call.function.as_str()) => continue,
+            None => match name_attribute(&call.body) {
+                Some(name) => (name, SymbolKind::Target),
+                None => continue,
+            },
+        };
+        symbols.push(symbol(path, &lines, name, kind, call.start, call.end));
+    }
+
+    static DEF: OnceLock<Regex> = OnceLock::new();
+    let def = DEF.get_or_init(|| Regex::new(r"^def\s+([A-Za-z_]\w*)\s*\(").expect("invalid Starlark def pattern"));
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(captures) = def.captures(line) {
+            let end = (idx + 1..lines.len())
+                .take_while(|&i| lines[i].trim().is_empty() || lines[i].starts_with([' ', '\t', ')']))
+                .filter(|&i| !lines[i].trim().is_empty())
+                .last()
+                .unwrap_or(idx);
+            symbols.push(symbol(path, &lines, captures[1].to_string(), SymbolKind::Function, idx, end));
+        }
+    }
+
+    symbols.sort_by_key(|s| s.span.start_line);
+    Ok(symbols)
+}
+
+fn symbol(path: &str, lines: &[&str], name: String, kind: SymbolKind, start: usize, end: usize) -> SearchResult {
+    SearchResult::new(
+        path.to_string(),
+        Language::Bazel,
+        kind,
+        Some(name),
+        Span::new(start + 1, 0, end + 1, 0),
+        None,
+        lines[start..(start + 7).min(lines.len())].join("\n"),
+    )
+}
+
+/// Calls starting at column 0, with their full (possibly multi-line) arguments
+fn top_level_calls(lines: &[&str]) -> Vec<Call> {
+    static CALL: OnceLock<Regex> = OnceLock::new();
+    let call = CALL.get_or_init(|| {
+        Regex::new(r"^(?:([A-Za-z_]\w*)\s*=\s*)?([A-Za-z_][\w.]*)\s*\(").expect("invalid Starlark call pattern")
+    });
+
+    let mut calls = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let Some(captures) = call.captures(lines[idx]) else {
+            idx += 1;
+            continue;
+        };
+
+        let start = idx;
+        let open = captures.get(0).map_or(0, |m| m.end());
+        let mut body = String::new();
+        let mut depth = 1;
+        let mut quote: Option<char> = None;
+        let mut text = &lines[idx][open..];
+        'lines: loop {
+            let mut chars = text.chars();
+            while let Some(c) = chars.next() {
+                match (quote, c) {
+                    (Some(_), '\\') => {
+                        body.push(c);
+                        if let Some(escaped) = chars.next() {
+                            body.push(escaped);
+                        }
+                        continue;
+                    }
+                    (Some(q), c) if c == q => quote = None,
+                    (Some(_), _) => {}
+                    (None, '"' | '\'') => quote = Some(c),
+                    (None, '#') => break,
+                    (None, '(' | '[' | '{') => depth += 1,
+                    (None, ')' | ']' | '}') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break 'lines;
+                        }
+                    }
+                    _ => {}
+                }
+                body.push(c);
+            }
+            body.push('\n');
+            idx += 1;
+            match lines.get(idx) {
+                Some(line) => text = line,
+                None => {
+                    idx -= 1;
+                    break;
+                }
+            }
+        }
+
+        calls.push(Call {
+            function: captures[2].to_string(),
+            assigned: captures.get(1).map(|m| m.as_str().to_string()),
+            body,
+            start,
+            end: idx,
+        });
+        idx += 1;
+    }
+
+    calls
+}
+
+/// Value of the `name = "..."` attribute of a call
+fn name_attribute(body: &str) -> Option<String> {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    let name = NAME.get_or_init(|| {
+        Regex::new(r#"(?:^|[\s,(])name\s*=\s*["']([^"']*)["']"#).expect("invalid Bazel name pattern")
+    });
+    name.captures(body).map(|c| c[1].to_string())
+}
+
+/// Bazel dependency extractor
+///
+/// Records the labels of `deps`-like attributes and `load()` statements (with
+/// the loaded symbols). Labels of other repositories (`@repo//...`) are
+/// external; `@//` labels point into the main repository.
+pub struct BazelDependencyExtractor;
+
+impl DependencyExtractor for BazelDependencyExtractor {
+    fn extract_dependencies(source: &str) -> Result<Vec<ImportInfo>> {
+        static DEPS: OnceLock<Regex> = OnceLock::new();
+        static STRING: OnceLock<Regex> = OnceLock::new();
+        let deps = DEPS.get_or_init(|| {
+            Regex::new(r"(?:^|[\s,(])(\w*deps|exports|data|tools|toolchains)\s*=\s*\[([^\]]*)\]")
+                .expect("invalid Bazel deps pattern")
+        });
+        let string = STRING.get_or_init(|| Regex::new(r#""([^"]*)"|'([^']*)'"#).expect("invalid Starlark string pattern"));
+        let strings = |text: &str| -> Vec<String> {
+            string.captures_iter(text)
+                .filter_map(|c| c.get(1).or_else(|| c.get(2)).map(|m| m.as_str().to_string()))
+                .collect()
+        };
+
+        let lines: Vec<&str> = source.lines().collect();
+        let mut imports = Vec::new();
+
+        for call in top_level_calls(&lines) {
+            if call.function == "load" {
+                let mut arguments = strings(&call.body).into_iter();
+                if let Some(label) = arguments.next() {
+                    imports.push(ImportInfo {
+                        import_type: label_type(&label),
+                        imported_path: label,
+                        line_number: call.start + 1,
+                        imported_symbols: Some(arguments.collect()),
+                    });
+                }
+                continue;
+            }
+
+            for captures in deps.captures_iter(&call.body) {
+                let list = captures.get(2).map_or("", |m| m.as_str());
+                // Line of the attribute, counted from the start of the call
+                let offset = call.body[..captures.get(0).map_or(0, |m| m.start())].matches('\n').count();
+                for label in strings(list) {
+                    imports.push(ImportInfo {
+                        import_type: label_type(&label),
+                        imported_path: label,
+                        line_number: call.start + offset + 1,
+                        imported_symbols: None,
+                    });
+                }
+            }
+        }
+
+        Ok(imports)
+    }
+}
+
+/// Labels of other repositories are external
+fn label_type(label: &str) -> ImportType {
+    if label.starts_with('@') && !label.starts_with("@//") && !label.starts_with("@@//") {
+        ImportType::External
+    } else {
+        ImportType::Internal
+    }
+}
+
+/// Resolve a label to candidate paths relative to the index root
+///
+/// `//pkg:target` resolves to `pkg/BUILD.bazel|pkg/BUILD` (pipe-delimited,
+/// tried in order), file labels such as `//tools:defs.bzl` or `:defs.bzl` to
+/// the file itself. Returns None for external labels and for targets of the
+/// current file's own package.
+pub fn resolve_bazel_label_to_path(label: &str, current_file_path: &str) -> Option<String> {
+    let current_file_path = current_file_path.trim_start_matches("./");
+    let current_package = current_file_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let label = label.trim_start_matches("@@").trim_start_matches('@');
+    if !label.starts_with("//") && label.contains("//") {
+        return None;
+    }
+
+    let (package, target) = match label.strip_prefix("//") {
+        Some(absolute) => match absolute.split_once(':') {
+            Some((package, target)) => (package, target),
+            None => (absolute, absolute.rsplit('/').next().unwrap_or(absolute)),
+        },
+        None => (current_package, label.trim_start_matches(':')),
+    };
+
+    // Source files (`load()` targets, `data` files) are paths within the package
+    if target.contains('.') {
+        return Some(if package.is_empty() { target.to_string() } else { format!("{}/{}", package, target) });
+    }
+    if package == current_package {
+        return None;
+    }
+    Some(if package.is_empty() {
+        "BUILD.bazel|BUILD".to_string()
+    } else {
+        format!("{}/BUILD.bazel|{}/BUILD", package, package)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILD: &str = r#"load("//tools:defs.bzl", "app_binary", my_test = "test_rule")
+load("@rules_cc//cc:defs.bzl", "cc_library")
+
+package(default_visibility = ["//visibility:public"])
+
+cc_library(
+    name = "server",
+    srcs = ["server.cc"],  # (implementation)
+    hdrs = ["server.h"],
+    deps = [
+        ":util",
+        "//lib/core",
+        "//lib/net:http",
+        "@abseil//absl/strings",
+    ],
+)
+
+cc_library(name = "util", srcs = ["util.cc"])
+
+app_binary(
+    name = "app",
+    data = ["config.yaml"],
+    deps = [":server"],
+)
+"#;
+
+    const DEFS: &str = r#"def _impl(ctx):
+    return []
+
+app_binary = rule(
+    implementation = _impl,
+    attrs = {"deps": attr.label_list()},
+)
+
+def app_test(name, **kwargs):
+    native.cc_test(
+        name = name,
+        **kwargs
+    )
+"#;
+
+    #[test]
+    fn test_parse_targets() {
+        let symbols = parse("server/BUILD.bazel", BUILD).unwrap();
+        let found: Vec<(&str, &SymbolKind, usize, usize)> = symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("server", &SymbolKind::Target, 6, 16),
+            ("util", &SymbolKind::Target, 18, 18),
+            ("app", &SymbolKind::Target, 20, 24),
+        ]);
+        assert!(symbols.iter().all(|s| s.lang == Language::Bazel));
+    }
+
+    #[test]
+    fn test_parse_starlark_definitions() {
+        let symbols = parse("tools/defs.bzl", DEFS).unwrap();
+        let found: Vec<(&str, &SymbolKind, usize, usize)> = symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("_impl", &SymbolKind::Function, 1, 2),
+            ("app_binary", &SymbolKind::Function, 4, 7),
+            ("app_test", &SymbolKind::Function, 9, 13),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_labels() {
+        let deps = BazelDependencyExtractor::extract_dependencies(BUILD).unwrap();
+        let found: Vec<(&str, bool, usize)> = deps
+            .iter()
+            .map(|d| (d.imported_path.as_str(), matches!(d.import_type, ImportType::Internal), d.line_number))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("//tools:defs.bzl", true, 1),
+            ("@rules_cc//cc:defs.bzl", false, 2),
+            (":util", true, 10),
+            ("//lib/core", true, 10),
+            ("//lib/net:http", true, 10),
+            ("@abseil//absl/strings", false, 10),
+            ("config.yaml", true, 22),
+            (":server", true, 23),
+        ]);
+        assert_eq!(deps[0].imported_symbols, Some(vec!["app_binary".to_string(), "test_rule".to_string()]));
+    }
+
+    #[test]
+    fn test_resolve_labels() {
+        let resolve = |label| resolve_bazel_label_to_path(label, "server/BUILD.bazel");
+
+        assert_eq!(resolve("//lib/core").as_deref(), Some("lib/core/BUILD.bazel|lib/core/BUILD"));
+        assert_eq!(resolve("@//lib/net:http").as_deref(), Some("lib/net/BUILD.bazel|lib/net/BUILD"));
+        assert_eq!(resolve("//tools:defs.bzl").as_deref(), Some("tools/defs.bzl"));
+        assert_eq!(resolve("config.yaml").as_deref(), Some("server/config.yaml"));
+        assert_eq!(resolve("//:root").as_deref(), Some("BUILD.bazel|BUILD"));
+        assert_eq!(resolve(":util"), None);
+        assert_eq!(resolve("//server:util"), None);
+        assert_eq!(resolve("@abseil//absl/strings"), None);
+    }
+}
@@ -16,7 +16,7 @@ use crate::models::{Language, SearchResult, Span, SymbolKind};
 
 /// Parse C source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_c::LANGUAGE;
 
     parser
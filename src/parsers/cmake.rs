@@ -0,0 +1,433 @@
+//! CMake parser
+//!
+//! Extracts symbols from `CMakeLists.txt` and `*.cmake` files:
+//! - Targets (kind: target) from `add_executable`, `add_library` and `add_custom_target`
+//! - Functions and macros (`function()`/`macro()` up to `endfunction()`/`endmacro()`)
+//! - Options (`option()`) as variables
+//!
+//! `target_link_libraries` and `add_dependencies` items are extracted as
+//! dependencies on other targets, `add_subdirectory` and `include` as
+//! dependencies on the files they load. Target names resolve to the file
+//! defining the target anywhere in the project (see [`parse_all_cmake_targets`]).
+//!
+//! Note: This parser is line-based since no tree-sitter grammar for CMake is
+//! bundled.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::models::{ImportType, Language, SearchResult, Span, SymbolKind};
+use crate::parsers::{DependencyExtractor, ImportInfo};
+
+/// `target_link_libraries` keywords that aren't library names
+const LINK_KEYWORDS: &[&str] = &[
+    "PUBLIC", "PRIVATE", "INTERFACE", "LINK_PUBLIC", "LINK_PRIVATE", "LINK_INTERFACE_LIBRARIES",
+    "debug", "optimized", "general",
+];
+
+/// One command invocation: `name(arguments)`
+struct Command {
+    /// Command name, lowercased (commands are case-insensitive)
+    name: String,
+    /// Arguments with quotes removed
+    args: Vec<String>,
+    /// First line (0-indexed)
+    start: usize,
+    /// Line of the closing parenthesis (0-indexed)
+    end: usize,
+}
+
+/// Parse a CMake file and extract targets, functions, macros and options
+pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let commands = commands(source);
+    let mut symbols = Vec::new();
+
+    for (idx, command) in commands.iter().enumerate() {
+        // Names computed at configure time (`add_executable(${name})`) can't be looked up
+        let Some(name) = command.args.first().filter(|name| !name.contains('$')) else {
+            continue;
+        };
+
+        let (kind, end) = match command.name.as_str() {
+            "add_executable" | "add_library" | "add_custom_target" => (SymbolKind::Target, command.end),
+            "option" => (SymbolKind::Variable, command.end),
+            "function" | "macro" => {
+                let kind = if command.name == "function" { SymbolKind::Function } else { SymbolKind::Macro };
+                let closing = format!("end{}", command.name);
+                let end = block_end(&commands[idx..], &command.name, &closing).unwrap_or(command.end);
+                (kind, end)
+            }
+            _ => continue,
+        };
+
+        symbols.push(SearchResult::new(
+            path.to_string(),
+            Language::CMake,
+            kind,
+            Some(name.clone()),
+            Span::new(command.start + 1, 0, end + 1, 0),
+            None,
+            lines[command.start..(command.start + 7).min(lines.len())].join("\n"),
+        ));
+    }
+
+    Ok(symbols)
+}
+
+/// Last line of a `function()`/`macro()` block starting at `commands[0]`
+fn block_end(commands: &[Command], opening: &str, closing: &str) -> Option<usize> {
+    let mut depth = 0;
+    for command in commands {
+        if command.name == opening {
+            depth += 1;
+        } else if command.name == closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(command.end);
+            }
+        }
+    }
+    None
+}
+
+/// Split a CMake file into command invocations
+///
+/// Handles multi-line arguments, nested parentheses, quoted arguments and
+/// `#` comments.
+fn commands(source: &str) -> Vec<Command> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut commands = Vec::new();
+    let mut line = 0;
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        if c == '\n' {
+            line += 1;
+            idx += 1;
+        } else if c == '#' {
+            while idx < chars.len() && chars[idx] != '\n' {
+                idx += 1;
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let name_start = idx;
+            while idx < chars.len() && (chars[idx].is_ascii_alphanumeric() || chars[idx] == '_') {
+                idx += 1;
+            }
+            let name: String = chars[name_start..idx].iter().collect();
+            while idx < chars.len() && (chars[idx] == ' ' || chars[idx] == '\t') {
+                idx += 1;
+            }
+            if chars.get(idx) != Some(&'(') {
+                continue;
+            }
+
+            let start = line;
+            let (args, next, lines) = arguments(&chars, idx + 1);
+            line += lines;
+            idx = next;
+            commands.push(Command { name: name.to_lowercase(), args, start, end: line });
+        } else {
+            idx += 1;
+        }
+    }
+
+    commands
+}
+
+/// Arguments of a command whose `(` ends just before `start`
+///
+/// Returns the arguments, the index after the closing `)` and the number of
+/// newlines consumed.
+fn arguments(chars: &[char], start: usize) -> (Vec<String>, usize, usize) {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut depth = 1;
+    let mut newlines = 0;
+    let mut idx = start;
+
+    fn flush(current: &mut String, args: &mut Vec<String>) {
+        if !current.is_empty() {
+            args.push(std::mem::take(current));
+        }
+    }
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        idx += 1;
+        match c {
+            '"' => {
+                while idx < chars.len() && chars[idx] != '"' {
+                    if chars[idx] == '\\' {
+                        idx += 1;
+                    }
+                    if chars.get(idx) == Some(&'\n') {
+                        newlines += 1;
+                    }
+                    if let Some(&c) = chars.get(idx) {
+                        current.push(c);
+                    }
+                    idx += 1;
+                }
+                idx += 1;
+            }
+            '#' => {
+                while idx < chars.len() && chars[idx] != '\n' {
+                    idx += 1;
+                }
+            }
+            '(' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    flush(&mut current, &mut args);
+                    break;
+                }
+                current.push(c);
+            }
+            c if c.is_whitespace() => {
+                if c == '\n' {
+                    newlines += 1;
+                }
+                if depth == 1 {
+                    flush(&mut current, &mut args);
+                } else {
+                    current.push(c);
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    (args, idx, newlines)
+}
+
+/// Names of the targets a CMake file defines
+pub fn defined_targets(source: &str) -> Vec<String> {
+    commands(source)
+        .into_iter()
+        .filter(|c| matches!(c.name.as_str(), "add_executable" | "add_library" | "add_custom_target"))
+        .filter_map(|c| c.args.into_iter().next())
+        .filter(|name| !name.contains('$'))
+        .collect()
+}
+
+/// CMake dependency extractor
+///
+/// Linked libraries and `add_dependencies` items are recorded by target name,
+/// `add_subdirectory(dir)` as `dir/CMakeLists.txt` and `include()` by file.
+/// Names with `::` (`Threads::Threads`) and `include()` of CMake modules
+/// (`include(CTest)`) are external.
+pub struct CMakeDependencyExtractor;
+
+impl DependencyExtractor for CMakeDependencyExtractor {
+    fn extract_dependencies(source: &str) -> Result<Vec<ImportInfo>> {
+        let mut imports = Vec::new();
+
+        for command in commands(source) {
+            let paths: Vec<(String, ImportType)> = match command.name.as_str() {
+                "target_link_libraries" | "add_dependencies" => command.args
+                    .iter()
+                    .skip(1)
+                    .filter(|arg| is_target_name(arg))
+                    .map(|arg| {
+                        let import_type = if arg.contains("::") { ImportType::External } else { ImportType::Internal };
+                        (arg.clone(), import_type)
+                    })
+                    .collect(),
+                "add_subdirectory" => command.args
+                    .first()
+                    .filter(|dir| !dir.contains('$'))
+                    .map(|dir| (format!("{}/CMakeLists.txt", dir.trim_end_matches('/')), ImportType::Internal))
+                    .into_iter()
+                    .collect(),
+                "include" => command.args
+                    .first()
+                    .filter(|file| !file.contains('$'))
+                    .map(|file| {
+                        let import_type = if file.ends_with(".cmake") { ImportType::Internal } else { ImportType::External };
+                        (file.clone(), import_type)
+                    })
+                    .into_iter()
+                    .collect(),
+                _ => continue,
+            };
+
+            imports.extend(paths.into_iter().map(|(path, import_type)| ImportInfo {
+                imported_path: path,
+                import_type,
+                line_number: command.start + 1,
+                imported_symbols: None,
+            }));
+        }
+
+        Ok(imports)
+    }
+}
+
+/// Whether a `target_link_libraries` argument names a target (not a keyword, flag, path or expression)
+fn is_target_name(arg: &str) -> bool {
+    !LINK_KEYWORDS.contains(&arg)
+        && !arg.starts_with('-')
+        && !arg.contains(['$', '<', '/'])
+}
+
+/// Whether a dependency is a file (`add_subdirectory`, `include`) rather than a target
+fn is_file_dependency(dependency: &str) -> bool {
+    dependency.ends_with("CMakeLists.txt") || dependency.ends_with(".cmake")
+}
+
+/// Reclassify a dependency once the project's targets are known
+///
+/// Target names defined nowhere in the project (`pthread`, `m`) are system
+/// libraries, so they become external.
+pub fn reclassify_cmake_dependency(dependency: &str, targets: &HashMap<String, String>) -> ImportType {
+    if is_file_dependency(dependency) || targets.contains_key(dependency) {
+        ImportType::Internal
+    } else {
+        ImportType::External
+    }
+}
+
+/// Resolve a dependency to a path relative to the index root
+///
+/// Targets resolve to the file defining them, files relative to the current
+/// file's directory. Returns None for a target defined in the current file.
+pub fn resolve_cmake_dependency_to_path(
+    dependency: &str,
+    current_file_path: &str,
+    targets: &HashMap<String, String>,
+) -> Option<String> {
+    if is_file_dependency(dependency) {
+        return crate::paths::join_relative(current_file_path, dependency);
+    }
+    targets
+        .get(dependency)
+        .filter(|path| path.as_str() != current_file_path.trim_start_matches("./"))
+        .cloned()
+}
+
+/// Find every target defined by the project's CMake files
+///
+/// Returns target names mapped to the defining file, relative to the index
+/// root. The first definition of a name wins.
+pub fn parse_all_cmake_targets(index_root: &Path) -> Result<HashMap<String, String>> {
+    use ignore::WalkBuilder;
+
+    let mut targets = HashMap::new();
+
+    let walker = WalkBuilder::new(index_root)
+        .follow_links(false)
+        .git_ignore(true)
+        .build();
+
+    for entry in walker {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() || Language::from_path(path) != Language::CMake {
+            continue;
+        }
+
+        if let Ok(content) = std::fs::read_to_string(path) {
+            let relative = crate::paths::relative_path(path, index_root);
+            for target in defined_targets(&content) {
+                targets.entry(target).or_insert_with(|| relative.clone());
+            }
+        }
+    }
+
+    log::debug!("Found {} CMake targets", targets.len());
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CMAKELISTS: &str = r#"cmake_minimum_required(VERSION 3.16)
+project(demo CXX)
+
+option(DEMO_TESTS "Build tests" ON)
+include(GNUInstallDirs)
+include(cmake/warnings.cmake)
+add_subdirectory(lib/core)
+
+# The application
+add_executable(app
+    src/main.cpp   # entry point
+    "src/cli (v2).cpp"
+)
+target_link_libraries(app PRIVATE core $<$<CONFIG:Debug>:asan> Threads::Threads -lm)
+
+function(demo_add_test name)
+  if(DEMO_TESTS)
+    add_executable(${name} ${ARGN})
+  endif()
+endfunction()
+
+MACRO(demo_warn)
+  message(WARNING "careful")
+ENDMACRO()
+"#;
+
+    #[test]
+    fn test_parse_targets_functions_and_options() {
+        let symbols = parse("CMakeLists.txt", CMAKELISTS).unwrap();
+        let found: Vec<(&str, &SymbolKind, usize, usize)> = symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("DEMO_TESTS", &SymbolKind::Variable, 4, 4),
+            ("app", &SymbolKind::Target, 10, 13),
+            ("demo_add_test", &SymbolKind::Function, 16, 20),
+            ("demo_warn", &SymbolKind::Macro, 22, 24),
+        ]);
+        assert!(symbols.iter().all(|s| s.lang == Language::CMake));
+    }
+
+    #[test]
+    fn test_extract_links_subdirectories_and_includes() {
+        let deps = CMakeDependencyExtractor::extract_dependencies(CMAKELISTS).unwrap();
+        let found: Vec<(&str, bool, usize)> = deps
+            .iter()
+            .map(|d| (d.imported_path.as_str(), matches!(d.import_type, ImportType::Internal), d.line_number))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("GNUInstallDirs", false, 5),
+            ("cmake/warnings.cmake", true, 6),
+            ("lib/core/CMakeLists.txt", true, 7),
+            ("core", true, 14),
+            ("Threads::Threads", false, 14),
+        ]);
+    }
+
+    #[test]
+    fn test_resolve_targets_and_files() {
+        let targets = HashMap::from([
+            ("core".to_string(), "lib/core/CMakeLists.txt".to_string()),
+            ("app".to_string(), "CMakeLists.txt".to_string()),
+        ]);
+
+        assert_eq!(
+            resolve_cmake_dependency_to_path("core", "CMakeLists.txt", &targets).as_deref(),
+            Some("lib/core/CMakeLists.txt")
+        );
+        assert_eq!(
+            resolve_cmake_dependency_to_path("lib/core/CMakeLists.txt", "CMakeLists.txt", &targets).as_deref(),
+            Some("lib/core/CMakeLists.txt")
+        );
+        assert_eq!(resolve_cmake_dependency_to_path("app", "CMakeLists.txt", &targets), None);
+        assert_eq!(resolve_cmake_dependency_to_path("pthread", "CMakeLists.txt", &targets), None);
+
+        assert!(matches!(reclassify_cmake_dependency("core", &targets), ImportType::Internal));
+        assert!(matches!(reclassify_cmake_dependency("pthread", &targets), ImportType::External));
+    }
+}
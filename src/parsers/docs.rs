@@ -0,0 +1,306 @@
+//! Doc comment extraction for parsed symbols
+//!
+//! After a language parser has produced symbols, this module attaches the
+//! documentation written for each one as `doc`, so a symbol search can answer
+//! "what does this do" without opening the file:
+//!
+//! - **Preceding comments**: `///` (Rust, C#, C/C++, Zig, Swift), `/** */`
+//!   (JSDoc, Javadoc, KDoc, PHPDoc, Doxygen), `//` (Go), `#` (Ruby, shell) and
+//!   `--` (SQL) comment blocks directly above the declaration. Attributes, annotations and
+//!   decorators between the comment and the declaration are skipped, but a
+//!   blank line ends the block.
+//! - **Docstrings** (Python): the string literal opening the body.
+//!
+//! Comment markers are stripped and docs longer than [`MAX_DOC_LENGTH`] are
+//! truncated with an ellipsis.
+
+use crate::models::{Language, SearchResult, Span};
+
+/// Maximum doc length in characters
+pub const MAX_DOC_LENGTH: usize = 1000;
+
+/// Fill in `doc` for every symbol that has one
+pub fn attach_docs(symbols: &mut [SearchResult], source: &str, language: Language) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    for symbol in symbols.iter_mut() {
+        if symbol.doc.is_none() {
+            symbol.doc = extract_doc(&lines, &symbol.span, language);
+        }
+    }
+}
+
+/// Extract the doc comment or docstring of a symbol spanning `span` within `lines`
+pub fn extract_doc(lines: &[&str], span: &Span, language: Language) -> Option<String> {
+    let start = span.start_line.checked_sub(1)?;
+    let end = span.end_line.min(lines.len());
+    if start >= end {
+        return None;
+    }
+
+    let raw = match language {
+        Language::Python => docstring(&lines[start..end])?,
+        _ => {
+            // Some grammars include decorators or comments in the node: start from the declaration
+            let decl = (start..end).find(|&i| !is_preamble_line(lines[i].trim(), language))?;
+            preceding_comment(&lines[..decl], language)?
+        }
+    };
+
+    let doc = raw.trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(truncate(doc))
+    }
+}
+
+/// Line comment prefixes that mark documentation in `language`
+fn line_doc_prefixes(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Rust | Language::CSharp | Language::C | Language::Cpp | Language::Zig | Language::Swift => &["///"],
+        Language::Go => &["//"],
+        Language::Ruby | Language::Shell => &["#"],
+        Language::Sql => &["--"],
+        _ => &[],
+    }
+}
+
+/// Whether `/** ... */` blocks are doc comments in `language`
+fn has_block_docs(language: Language) -> bool {
+    !matches!(language, Language::Python | Language::Ruby | Language::Go | Language::Zig | Language::Shell | Language::Unknown)
+}
+
+/// Lines between a doc comment and its declaration (or leading a node) that aren't the declaration
+fn is_preamble_line(trimmed: &str, language: Language) -> bool {
+    trimmed.is_empty()
+        || is_attribute_line(trimmed, language)
+        || trimmed.starts_with("//")
+        || trimmed.starts_with("/*")
+        || trimmed.starts_with('*')
+        || (language == Language::Ruby && trimmed.starts_with('#'))
+}
+
+/// Whether a line is an attribute, annotation or decorator in `language`
+pub(crate) fn is_attribute_line(trimmed: &str, language: Language) -> bool {
+    match language {
+        Language::Rust | Language::PHP => trimmed.starts_with("#["),
+        Language::CSharp => trimmed.starts_with('[') && trimmed.ends_with(']'),
+        Language::Ruby => false,
+        _ => trimmed.starts_with('@'),
+    }
+}
+
+/// First line of the doc comment block ending right above `lines.len()`
+///
+/// Unlike [`extract_doc`], attributes between the comment and `lines.len()`
+/// are not skipped.
+pub(crate) fn doc_comment_start(lines: &[&str], language: Language) -> Option<usize> {
+    let idx = lines.len();
+    let last = lines.get(idx.checked_sub(1)?)?.trim();
+
+    // Block doc comment: /** ... */
+    if has_block_docs(language) && last.ends_with("*/") {
+        let open = (0..idx).rev().find(|&i| lines[i].contains("/*"))?;
+        return lines[open].trim_start().starts_with("/**").then_some(open);
+    }
+
+    // Line doc comments: a contiguous run of prefixed lines
+    let prefix = line_doc_prefix(last, language)?;
+    let mut first = idx - 1;
+    while first > 0 && is_doc_line(lines[first - 1].trim(), prefix, language) {
+        first -= 1;
+    }
+    Some(first)
+}
+
+/// Collect the doc comment block ending right above `lines.len()`
+fn preceding_comment(lines: &[&str], language: Language) -> Option<String> {
+    let mut idx = lines.len();
+
+    // Skip attributes/annotations sitting between the comment and the declaration
+    while idx > 0 && is_attribute_line(lines[idx - 1].trim(), language) {
+        idx -= 1;
+    }
+
+    let first = doc_comment_start(&lines[..idx], language)?;
+    let block = &lines[first..idx];
+
+    if has_block_docs(language) && block[0].trim_start().starts_with("/**") {
+        let body: Vec<&str> = block
+            .iter()
+            .map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix("/**").unwrap_or(line);
+                let line = line.strip_suffix("*/").unwrap_or(line).trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line).trim_end()
+            })
+            .collect();
+        return Some(body.join("\n"));
+    }
+
+    let prefix = line_doc_prefix(block[0].trim(), language)?;
+    let body: Vec<&str> = block
+        .iter()
+        .map(|line| {
+            let line = line.trim().strip_prefix(prefix).unwrap_or("");
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+    Some(body.join("\n"))
+}
+
+/// The doc comment prefix `trimmed` starts with, if it is a doc line
+fn line_doc_prefix(trimmed: &str, language: Language) -> Option<&'static str> {
+    line_doc_prefixes(language)
+        .iter()
+        .copied()
+        .find(|p| is_doc_line(trimmed, p, language))
+}
+
+fn is_doc_line(trimmed: &str, prefix: &str, language: Language) -> bool {
+    if !trimmed.starts_with(prefix) {
+        return false;
+    }
+    match language {
+        // `////` separators and `//!` inner docs are not item docs
+        Language::Rust | Language::CSharp | Language::C | Language::Cpp | Language::Zig | Language::Swift => {
+            !trimmed.starts_with("////")
+        }
+        // Go build constraints and compiler directives
+        Language::Go => !trimmed.starts_with("//go:") && !trimmed.starts_with("// +build"),
+        // Ruby magic comments and shebangs
+        Language::Ruby => !trimmed.starts_with("#!") && !trimmed.contains("frozen_string_literal:"),
+        // Shebangs and shellcheck directives
+        Language::Shell => !trimmed.starts_with("#!") && !trimmed.starts_with("# shellcheck"),
+        _ => true,
+    }
+}
+
+/// The docstring opening a Python definition spanning `lines`
+fn docstring(lines: &[&str]) -> Option<String> {
+    // Skip decorators, then find the end of the (possibly multi-line) header
+    let decl = lines.iter().position(|l| {
+        let t = l.trim();
+        !t.is_empty() && !t.starts_with('@') && !t.starts_with('#')
+    })?;
+    let header_end = (decl..lines.len()).find(|&i| strip_line_comment(lines[i]).trim_end().ends_with(':'))?;
+
+    let body_start = (header_end + 1..lines.len()).find(|&i| !lines[i].trim().is_empty())?;
+    let first = lines[body_start].trim().trim_start_matches(['r', 'R', 'u', 'U']);
+    let quote = ["\"\"\"", "'''"].into_iter().find(|q| first.starts_with(q))?;
+    let opening = &first[quote.len()..];
+
+    // Single-line docstring
+    if let Some(close) = opening.find(quote) {
+        return Some(opening[..close].to_string());
+    }
+
+    let mut body = vec![opening.trim_end()];
+    for line in &lines[body_start + 1..] {
+        let trimmed = line.trim();
+        if let Some(close) = trimmed.find(quote) {
+            body.push(&trimmed[..close]);
+            return Some(body.join("\n"));
+        }
+        body.push(trimmed);
+    }
+
+    // Unterminated (span cut short): keep what we have
+    Some(body.join("\n"))
+}
+
+fn strip_line_comment(line: &str) -> &str {
+    match line.find(" #") {
+        Some(idx) => &line[..idx],
+        None => line,
+    }
+}
+
+fn truncate(doc: String) -> String {
+    if doc.chars().count() <= MAX_DOC_LENGTH {
+        return doc;
+    }
+
+    let mut truncated: String = doc.chars().take(MAX_DOC_LENGTH).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(source: &str, start: usize, end: usize, language: Language) -> Option<String> {
+        let lines: Vec<&str> = source.lines().collect();
+        extract_doc(&lines, &Span { start_line: start, end_line: end }, language)
+    }
+
+    #[test]
+    fn test_rust_line_docs_skip_attributes() {
+        let source = "//! Module docs\n\n/// Adds two numbers.\n///\n/// Panics never.\n#[inline]\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+        assert_eq!(
+            doc(source, 7, 9, Language::Rust).unwrap(),
+            "Adds two numbers.\n\nPanics never."
+        );
+    }
+
+    #[test]
+    fn test_blank_line_or_plain_comment_is_not_a_doc() {
+        let source = "/// Detached\n\nfn detached() {}\n// plain comment\nfn plain() {}\n";
+        assert!(doc(source, 3, 3, Language::Rust).is_none());
+        assert!(doc(source, 5, 5, Language::Rust).is_none());
+    }
+
+    #[test]
+    fn test_jsdoc_and_javadoc_blocks() {
+        let ts = "/**\n * Fetch a user.\n * @param id User id\n */\nexport async function getUser(id: string) {\n}\n";
+        assert_eq!(
+            doc(ts, 5, 6, Language::TypeScript).unwrap(),
+            "Fetch a user.\n@param id User id"
+        );
+
+        let java = "    /** Returns the name. */\n    @Override\n    public String getName() {\n        return name;\n    }\n";
+        assert_eq!(doc(java, 3, 5, Language::Java).unwrap(), "Returns the name.");
+
+        let plain_block = "/* not a doc */\nfunction f() {}\n";
+        assert!(doc(plain_block, 2, 2, Language::JavaScript).is_none());
+    }
+
+    #[test]
+    fn test_go_and_ruby_comment_runs() {
+        let go = "//go:generate stringer\n// Increment adds one.\n// It is safe to call concurrently.\nfunc (c *Counter) Increment() {\n}\n";
+        assert_eq!(
+            doc(go, 4, 5, Language::Go).unwrap(),
+            "Increment adds one.\nIt is safe to call concurrently."
+        );
+
+        let ruby = "# Greets a user\ndef greet(name)\n  puts name\nend\n";
+        assert_eq!(doc(ruby, 2, 4, Language::Ruby).unwrap(), "Greets a user");
+
+        let shell = "#!/bin/sh\n# Builds the release binary\nbuild() {\n  cargo build\n}\n";
+        assert_eq!(doc(shell, 3, 5, Language::Shell).unwrap(), "Builds the release binary");
+
+        let sql = "-- Registered accounts\nCREATE TABLE users (\n  id int\n);\n";
+        assert_eq!(doc(sql, 2, 4, Language::Sql).unwrap(), "Registered accounts");
+    }
+
+    #[test]
+    fn test_python_docstrings() {
+        let single = "def greet(self) -> str:\n    \"\"\"Return a greeting.\"\"\"\n    return 'hi'\n";
+        assert_eq!(doc(single, 1, 3, Language::Python).unwrap(), "Return a greeting.");
+
+        let multi = "@cache\ndef fetch(\n    url: str,\n) -> bytes:  # network\n    '''\n    Fetch a URL.\n\n    Retries twice.\n    '''\n    return get(url)\n";
+        assert_eq!(
+            doc(multi, 1, 10, Language::Python).unwrap(),
+            "Fetch a URL.\n\nRetries twice."
+        );
+
+        let none = "def f():\n    return 1\n";
+        assert!(doc(none, 1, 2, Language::Python).is_none());
+    }
+}
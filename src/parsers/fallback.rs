@@ -0,0 +1,265 @@
+//! Regex-based fallback parser
+//!
+//! Used when a language's tree-sitter grammar fails to load (ABI mismatch,
+//! platform issue), so symbol queries for that language degrade to basic
+//! results instead of silently returning nothing. Only declarations that are
+//! recognizable from a single line are extracted: functions, classes,
+//! structs, interfaces, traits, enums and modules. Spans are closed by brace
+//! matching, or by indentation for Python and Ruby.
+//!
+//! Degraded languages are reported by `rfx stats`, `rfx doctor` and the JSON
+//! `warnings` array of symbol queries.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+
+/// How a declaration's body is delimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockStyle {
+    /// `{ ... }`
+    Braces,
+    /// Deeper-indented lines (Python), optionally closed by an `end` line (Ruby)
+    Indentation,
+}
+
+/// Declaration patterns per language: (regex with the name as group 1, kind)
+fn rules(language: Language) -> Vec<(&'static str, SymbolKind)> {
+    match language {
+        Language::Rust => vec![
+            (r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+\S+)\s+)*fn\s+([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^\s*(?:pub(?:\([^)]*\))?\s+)?struct\s+([A-Za-z_]\w*)", SymbolKind::Struct),
+            (r"^\s*(?:pub(?:\([^)]*\))?\s+)?enum\s+([A-Za-z_]\w*)", SymbolKind::Enum),
+            (r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+([A-Za-z_]\w*)", SymbolKind::Trait),
+            (r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_]\w*)\s*\{", SymbolKind::Module),
+        ],
+        Language::Python => vec![
+            (r"^\s*(?:async\s+)?def\s+([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^\s*class\s+([A-Za-z_]\w*)", SymbolKind::Class),
+        ],
+        Language::TypeScript | Language::JavaScript => vec![
+            (r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*([A-Za-z_$][\w$]*)", SymbolKind::Function),
+            (r"^\s*(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*=>", SymbolKind::Function),
+            (r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+([A-Za-z_$][\w$]*)", SymbolKind::Class),
+            (r"^\s*(?:export\s+)?interface\s+([A-Za-z_$][\w$]*)", SymbolKind::Interface),
+            (r"^\s*(?:export\s+)?(?:const\s+)?enum\s+([A-Za-z_$][\w$]*)", SymbolKind::Enum),
+        ],
+        Language::Go => vec![
+            (r"^func\s+\([^)]*\)\s*([A-Za-z_]\w*)", SymbolKind::Method),
+            (r"^func\s+([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^type\s+([A-Za-z_]\w*)\s+struct\b", SymbolKind::Struct),
+            (r"^type\s+([A-Za-z_]\w*)\s+interface\b", SymbolKind::Interface),
+        ],
+        Language::Java | Language::CSharp => vec![
+            (r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|sealed|partial)\s+)*class\s+([A-Za-z_]\w*)", SymbolKind::Class),
+            (r"^\s*(?:(?:public|private|protected|internal|static|sealed|partial)\s+)*interface\s+([A-Za-z_]\w*)", SymbolKind::Interface),
+            (r"^\s*(?:(?:public|private|protected|internal|static)\s+)*enum\s+([A-Za-z_]\w*)", SymbolKind::Enum),
+            (r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|synchronized|virtual|override|async)\s+)+[\w<>\[\],.?]+\s+([A-Za-z_]\w*)\s*\([^;]*$", SymbolKind::Method),
+        ],
+        Language::Kotlin => vec![
+            (r"^\s*(?:(?:public|private|protected|internal|override|suspend|inline|open)\s+)*fun\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^\s*(?:(?:public|private|protected|internal|open|abstract|data|sealed|enum)\s+)*class\s+([A-Za-z_]\w*)", SymbolKind::Class),
+            (r"^\s*(?:(?:public|private|protected|internal|sealed|fun)\s+)*interface\s+([A-Za-z_]\w*)", SymbolKind::Interface),
+            (r"^\s*(?:(?:public|private|protected|internal)\s+)*object\s+([A-Za-z_]\w*)", SymbolKind::Class),
+        ],
+        Language::PHP => vec![
+            (r"^\s*(?:(?:public|private|protected|static|abstract|final)\s+)*function\s+&?([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^\s*(?:(?:abstract|final|readonly)\s+)*class\s+([A-Za-z_]\w*)", SymbolKind::Class),
+            (r"^\s*interface\s+([A-Za-z_]\w*)", SymbolKind::Interface),
+            (r"^\s*trait\s+([A-Za-z_]\w*)", SymbolKind::Trait),
+            (r"^\s*enum\s+([A-Za-z_]\w*)", SymbolKind::Enum),
+        ],
+        Language::Ruby => vec![
+            (r"^\s*def\s+(?:self\.)?([A-Za-z_]\w*[?!=]?)", SymbolKind::Method),
+            (r"^\s*class\s+([A-Z]\w*)", SymbolKind::Class),
+            (r"^\s*module\s+([A-Z]\w*)", SymbolKind::Module),
+        ],
+        Language::C | Language::Cpp => vec![
+            (r"^\s*(?:typedef\s+)?struct\s+([A-Za-z_]\w*)\s*\{", SymbolKind::Struct),
+            (r"^\s*(?:typedef\s+)?enum\s+(?:class\s+)?([A-Za-z_]\w*)\s*(?::\s*\w+\s*)?\{", SymbolKind::Enum),
+            (r"^\s*class\s+([A-Za-z_]\w*)\s*(?:final\s*)?(?::[^{;]*)?\{?\s*$", SymbolKind::Class),
+            (r"^\s*namespace\s+([A-Za-z_]\w*)", SymbolKind::Namespace),
+            // Function definitions start at column 0: `type name(args)` without a trailing `;`
+            (r"^[A-Za-z_][\w\s\*&:<>,]*?[\s\*&]([A-Za-z_][\w:~]*)\s*\([^;]*$", SymbolKind::Function),
+        ],
+        Language::Zig => vec![
+            (r"^\s*(?:pub\s+)?(?:export\s+|inline\s+)?fn\s+([A-Za-z_]\w*)", SymbolKind::Function),
+            (r"^\s*(?:pub\s+)?const\s+([A-Za-z_]\w*)\s*=\s*(?:extern\s+|packed\s+)?struct\b", SymbolKind::Struct),
+            (r"^\s*(?:pub\s+)?const\s+([A-Za-z_]\w*)\s*=\s*enum\b", SymbolKind::Enum),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn block_style(language: Language) -> BlockStyle {
+    match language {
+        Language::Python | Language::Ruby => BlockStyle::Indentation,
+        _ => BlockStyle::Braces,
+    }
+}
+
+/// A declaration pattern and the kind of symbol it captures
+type Rule = (Regex, SymbolKind);
+
+/// Compiled [`rules`] for a language (compiled once per process)
+fn compiled_rules(language: Language) -> &'static [Rule] {
+    static COMPILED: OnceLock<Vec<(Language, Vec<Rule>)>> = OnceLock::new();
+    let all = COMPILED.get_or_init(|| {
+        super::TREE_SITTER_LANGUAGES
+            .iter()
+            .map(|&lang| {
+                let compiled = rules(lang)
+                    .into_iter()
+                    .map(|(pattern, kind)| (Regex::new(pattern).expect("invalid fallback pattern"), kind))
+                    .collect();
+                (lang, compiled)
+            })
+            .collect()
+    });
+    all.iter()
+        .find(|(lang, _)| *lang == language)
+        .map(|(_, rules)| rules.as_slice())
+        .unwrap_or(&[])
+}
+
+/// Extract declarations line by line
+pub fn parse(path: &str, source: &str, language: Language) -> Vec<SearchResult> {
+    let lines: Vec<&str> = source.lines().collect();
+    let style = block_style(language);
+    let mut symbols = Vec::new();
+
+    for (idx, line) in lines.iter().enumerate() {
+        // Comments, attributes and preprocessor lines never start a declaration
+        let trimmed = line.trim_start();
+        if ["//", "/*", "*", "#"].iter().any(|prefix| trimmed.starts_with(prefix)) {
+            continue;
+        }
+
+        let Some((name, kind)) = compiled_rules(language)
+            .iter()
+            .find_map(|(re, kind)| re.captures(line).map(|c| (c[1].to_string(), kind.clone())))
+        else {
+            continue;
+        };
+
+        let end = block_end(&lines, idx, style);
+        let span = Span::new(idx + 1, 0, end + 1, 0);
+        let preview = lines[idx..(idx + 7).min(lines.len())].join("\n");
+
+        symbols.push(SearchResult::new(
+            path.to_string(),
+            language,
+            kind,
+            Some(name),
+            span,
+            None,
+            preview,
+        ));
+    }
+
+    symbols
+}
+
+/// Index of the last line of the declaration starting at `start`
+fn block_end(lines: &[&str], start: usize, style: BlockStyle) -> usize {
+    match style {
+        BlockStyle::Braces => {
+            let mut depth = 0i32;
+            let mut opened = false;
+            for (idx, line) in lines.iter().enumerate().skip(start) {
+                for c in line.chars() {
+                    match c {
+                        '{' => {
+                            depth += 1;
+                            opened = true;
+                        }
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                }
+                if opened && depth <= 0 {
+                    return idx;
+                }
+                // A declaration without a body (prototype, `struct Foo;`)
+                if !opened && (line.trim_end().ends_with(';') || idx > start + 2) {
+                    return start;
+                }
+            }
+            lines.len().saturating_sub(1).max(start)
+        }
+        BlockStyle::Indentation => {
+            let indent = |l: &str| l.len() - l.trim_start().len();
+            let base = indent(lines[start]);
+            let mut end = start;
+            for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if indent(line) <= base {
+                    // Ruby closes blocks with `end` at the declaration's indentation
+                    if line.trim() == "end" && indent(line) == base {
+                        end = idx;
+                    }
+                    break;
+                }
+                end = idx;
+            }
+            end
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(source: &str, language: Language) -> Vec<(String, SymbolKind, usize, usize)> {
+        parse("test", source, language)
+            .into_iter()
+            .map(|s| (s.symbol.unwrap(), s.kind, s.span.start_line, s.span.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_fallback() {
+        let source = "pub struct Point { x: i32 }\n\nimpl Point {\n    pub fn norm(&self) -> i32 {\n        self.x\n    }\n}\n\n// fn commented() {}\nasync fn run() {}\n";
+        assert_eq!(names(source, Language::Rust), vec![
+            ("Point".to_string(), SymbolKind::Struct, 1, 1),
+            ("norm".to_string(), SymbolKind::Function, 4, 6),
+            ("run".to_string(), SymbolKind::Function, 10, 10),
+        ]);
+    }
+
+    #[test]
+    fn test_python_fallback_uses_indentation() {
+        let source = "class Greeter:\n    def greet(self):\n        return 'hi'\n\n    async def wave(self):\n        pass\n\ndef main():\n    Greeter().greet()\n";
+        assert_eq!(names(source, Language::Python), vec![
+            ("Greeter".to_string(), SymbolKind::Class, 1, 6),
+            ("greet".to_string(), SymbolKind::Function, 2, 3),
+            ("wave".to_string(), SymbolKind::Function, 5, 6),
+            ("main".to_string(), SymbolKind::Function, 8, 9),
+        ]);
+    }
+
+    #[test]
+    fn test_ruby_fallback_includes_end() {
+        let source = "module Billing\n  class Invoice\n    def total\n      42\n    end\n  end\nend\n";
+        assert_eq!(names(source, Language::Ruby), vec![
+            ("Billing".to_string(), SymbolKind::Module, 1, 7),
+            ("Invoice".to_string(), SymbolKind::Class, 2, 6),
+            ("total".to_string(), SymbolKind::Method, 3, 5),
+        ]);
+    }
+
+    #[test]
+    fn test_typescript_fallback() {
+        let source = "export interface User {\n  id: string;\n}\n\nexport const load = async (id: string) => {\n  return id;\n};\n\nexport default class Store {}\n";
+        let found: Vec<_> = names(source, Language::TypeScript).into_iter().map(|(n, k, _, _)| (n, k)).collect();
+        assert_eq!(found, vec![
+            ("User".to_string(), SymbolKind::Interface),
+            ("load".to_string(), SymbolKind::Function),
+            ("Store".to_string(), SymbolKind::Class),
+        ]);
+    }
+}
@@ -15,7 +15,7 @@ use crate::models::{Language, SearchResult, Span, SymbolKind};
 
 /// Parse Go source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_go::LANGUAGE;
 
     parser
@@ -775,17 +775,22 @@ func main() {
         assert_eq!(modules.len(), 1);
 
         // Test sub-package import resolution
-        // "github.com/myorg/myapp/pkg/models" → "myapp/pkg/models.go" or "myapp/pkg/models/models.go"
+        // "github.com/myorg/myapp/pkg/models" → "myapp/pkg/models" (package directory)
         let resolved = resolve_go_import_to_path(
             "github.com/myorg/myapp/pkg/models",
             &modules,
             None
         );
 
-        assert!(resolved.is_some());
-        let path = resolved.unwrap();
-        assert!(path.contains("myapp/pkg/models"));
-        assert!(path.ends_with(".go"));
+        assert_eq!(resolved.as_deref(), Some("myapp/pkg/models"));
+
+        // Module names match on path boundaries
+        let resolved = resolve_go_import_to_path(
+            "github.com/myorg/myapp2/pkg/models",
+            &modules,
+            None
+        );
+        assert!(resolved.is_none());
     }
 
     #[test]
@@ -812,11 +817,8 @@ func main() {
             None
         );
 
-        assert!(resolved.is_some());
-        let path = resolved.unwrap();
-        // Should try main.go or server.go
-        assert!(path.contains("cmd/server"));
-        assert!(path.ends_with(".go"));
+        // Resolves to the directory containing go.mod
+        assert_eq!(resolved.as_deref(), Some("cmd/server"));
     }
 
     #[test]
@@ -860,6 +862,43 @@ func main() {
 
         assert!(resolved.is_none());
     }
+
+    #[test]
+    fn test_resolve_go_import_nested_modules() {
+        let module = |name: &str, root: &str| GoModule {
+            name: name.to_string(),
+            project_root: root.to_string(),
+            abs_project_root: std::path::PathBuf::from("/repo").join(root),
+        };
+        let modules = vec![
+            module("example.com/app", ""),
+            module("example.com/app/tools", "tools"),
+        ];
+
+        assert_eq!(
+            resolve_go_import_to_path("example.com/app/internal/db", &modules, None).as_deref(),
+            Some("internal/db")
+        );
+        assert_eq!(
+            resolve_go_import_to_path("example.com/app/tools/gen", &modules, None).as_deref(),
+            Some("tools/gen")
+        );
+        assert_eq!(
+            resolve_go_import_to_path("example.com/app", &modules, None).as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn test_reclassify_go_import_shared_host() {
+        let module = Some("github.com/myorg/myapp");
+
+        assert!(matches!(reclassify_go_import("github.com/myorg/myapp/pkg/api", module), ImportType::Internal));
+        assert!(matches!(reclassify_go_import("github.com/spf13/cobra", module), ImportType::External));
+        assert!(matches!(reclassify_go_import("github.com/myorg/myapp2", module), ImportType::External));
+        // Vanity domains still group sibling modules (e.g. Kubernetes staging repos)
+        assert!(matches!(reclassify_go_import("k8s.io/client-go/rest", Some("k8s.io/kubernetes")), ImportType::Internal));
+    }
 }
 
 // ============================================================================
@@ -992,19 +1031,39 @@ fn classify_go_import(import_path: &str) -> ImportType {
     classify_go_import_impl(import_path, None)
 }
 
+/// Code hosts shared by unrelated modules, where a matching domain says nothing
+/// about whether an import belongs to the same project
+const SHARED_GO_HOSTS: &[&str] = &[
+    "github.com", "gitlab.com", "bitbucket.org", "gopkg.in", "golang.org",
+    "google.golang.org", "go.googlesource.com", "sr.ht", "codeberg.org",
+];
+
+/// Whether `import_path` is the module `module_name` or one of its packages
+///
+/// Matches on path boundaries: `example.com/app2` is not part of `example.com/app`.
+fn is_in_go_module(import_path: &str, module_name: &str) -> bool {
+    import_path.strip_prefix(module_name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
 /// Internal implementation of Go import classification
 fn classify_go_import_impl(import_path: &str, module_prefix: Option<&str>) -> ImportType {
     // If we have a module prefix, check if import starts with it → Internal
     if let Some(prefix) = module_prefix {
-        if import_path.starts_with(prefix) {
+        if is_in_go_module(import_path, prefix) {
             return ImportType::Internal;
         }
         // Also check for multi-module repos - imports starting with k8s.io/* for Kubernetes
         // Extract the domain portion and check if it matches
         if let Some(import_domain) = import_path.split('/').next() {
             if let Some(module_domain) = prefix.split('/').next() {
-                // If domains match (e.g., both start with k8s.io), consider it internal
-                if import_domain == module_domain && module_domain.contains('.') {
+                // If domains match (e.g., both start with k8s.io), consider it internal.
+                // Shared code hosts are skipped: github.com/other/lib is not part of
+                // github.com/myorg/myapp.
+                if import_domain == module_domain
+                    && module_domain.contains('.')
+                    && !SHARED_GO_HOSTS.contains(&module_domain)
+                {
                     return ImportType::Internal;
                 }
             }
@@ -1159,12 +1218,20 @@ pub fn parse_all_go_modules(index_root: &std::path::Path) -> Result<Vec<GoModule
     Ok(modules)
 }
 
-/// Resolve a Go import to a file path
+/// Resolve a Go import to its package directory
+///
+/// A Go package is every non-test `.go` file directly inside one directory, so
+/// imports resolve to that directory (relative to the index root) rather than to
+/// a single file; the caller links the import to each file in it.
 ///
 /// Handles:
-/// - Internal imports: `mymodule/pkg/utils` → `pkg/utils.go` or `pkg/utils/utils.go`
-/// - Sub-packages: `mymodule/internal/models` → `internal/models/models.go`
-/// - Relative imports: `./utils` (rare in Go but possible)
+/// - Internal imports: `mymodule/pkg/utils` → `pkg/utils`
+/// - Nested modules: the module with the longest matching name wins, so
+///   `example.com/app/tools/gen` resolves inside `example.com/app/tools` when both
+///   are in the index
+/// - Module root: `mymodule` → the directory containing `go.mod`
+///
+/// Relative imports (`./utils`) are only valid in GOPATH mode and return None.
 pub fn resolve_go_import_to_path(
     import_path: &str,
     modules: &[GoModule],
@@ -1176,43 +1243,22 @@ pub fn resolve_go_import_to_path(
         return None;
     }
 
-    // Find matching module
-    for module in modules {
-        if import_path.starts_with(&module.name) {
-            // Strip module name to get sub-package path
-            // "k8s.io/kubernetes/pkg/api" with module "k8s.io/kubernetes" → "pkg/api"
-            let sub_path = import_path.strip_prefix(&module.name)
-                .unwrap_or(import_path)
-                .trim_start_matches('/');
-
-            if sub_path.is_empty() {
-                // Importing the module root - could be multiple files
-                // Try common patterns
-                let candidates = vec![
-                    format!("{}/main.go", module.project_root),
-                    format!("{}/{}.go", module.project_root, module.name.split('/').last().unwrap_or("main")),
-                ];
-
-                for candidate in candidates {
-                    log::trace!("Checking Go module root: {}", candidate);
-                    return Some(candidate);
-                }
-            } else {
-                // Sub-package import
-                // Try both single file and package directory patterns
-                let package_name = sub_path.split('/').last().unwrap_or(sub_path);
-                let candidates = vec![
-                    format!("{}/{}.go", module.project_root, sub_path),
-                    format!("{}/{}/{}.go", module.project_root, sub_path, package_name),
-                ];
-
-                for candidate in candidates {
-                    log::trace!("Checking Go package path: {}", candidate);
-                    return Some(candidate);
-                }
-            }
-        }
-    }
+    // Find the most specific matching module
+    let module = modules
+        .iter()
+        .filter(|module| is_in_go_module(import_path, &module.name))
+        .max_by_key(|module| module.name.len())?;
 
-    None
+    // Strip module name to get sub-package path
+    // "k8s.io/kubernetes/pkg/api" with module "k8s.io/kubernetes" → "pkg/api"
+    let sub_path = import_path[module.name.len()..].trim_start_matches('/');
+
+    let package_dir = match (module.project_root.is_empty(), sub_path.is_empty()) {
+        (true, _) => sub_path.to_string(),
+        (false, true) => module.project_root.clone(),
+        (false, false) => format!("{}/{}", module.project_root, sub_path),
+    };
+
+    log::trace!("Resolved Go import {} to package directory '{}'", import_path, package_dir);
+    Some(package_dir)
 }
@@ -17,7 +17,7 @@ use crate::models::{Language, SearchResult, Span, SymbolKind};
 
 /// Parse Kotlin source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_kotlin_ng::LANGUAGE;
 
     parser
@@ -0,0 +1,324 @@
+//! Makefile parser
+//!
+//! Extracts symbols from Makefiles (`Makefile`, `GNUmakefile`, `*.mk`):
+//! - Rule targets (kind: target), spanning their recipe
+//! - Variables (`=`, `:=`, `::=`, `?=`, `!=` assignments and `define` blocks)
+//!
+//! Prerequisites and `include` directives are extracted as dependencies, so
+//! the dependency graph records what each target is built from. Prerequisites
+//! naming an indexed file (`app: src/main.c`) resolve to that file.
+//!
+//! Note: This parser is line-based since no tree-sitter grammar for Make is
+//! bundled.
+
+use anyhow::Result;
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::models::{ImportType, Language, SearchResult, Span, SymbolKind};
+use crate::parsers::{DependencyExtractor, ImportInfo};
+
+/// Directives that start a line without defining a rule or variable
+const DIRECTIVES: &[&str] = &[
+    "ifeq", "ifneq", "ifdef", "ifndef", "else", "endif", "unexport", "vpath", "undefine",
+];
+
+/// One logical line: physical lines joined at trailing backslashes
+struct LogicalLine {
+    /// First physical line (0-indexed)
+    start: usize,
+    /// Last physical line (0-indexed)
+    end: usize,
+    /// Joined text with the comment removed
+    text: String,
+    /// Whether the line is part of a recipe (starts with a tab)
+    recipe: bool,
+    /// Whether the line is inside a `define` block (after `define`, up to `endef`)
+    in_define: bool,
+}
+
+/// A statement of a Makefile that defines or references something
+enum Statement<'a> {
+    /// `targets: prerequisites`
+    Rule { targets: Vec<&'a str>, prerequisites: Vec<&'a str> },
+    /// `NAME = value` and friends
+    Variable(&'a str),
+    /// `define NAME` (closed by `endef`)
+    Define(&'a str),
+    /// `include a.mk b.mk`
+    Include(Vec<&'a str>),
+}
+
+/// Parse a Makefile and extract targets and variables
+pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let logical = logical_lines(&lines);
+    let mut symbols = Vec::new();
+
+    let mut idx = 0;
+    while idx < logical.len() {
+        let line = &logical[idx];
+        idx += 1;
+        if line.recipe || line.in_define {
+            continue;
+        }
+
+        let (names, kind, end) = match statement(&line.text) {
+            Some(Statement::Rule { targets, .. }) => {
+                // The recipe belongs to the rule: tab-indented lines, with blank and comment lines between them
+                let mut end = line.end;
+                while idx < logical.len() && (logical[idx].recipe || logical[idx].text.trim().is_empty()) {
+                    if logical[idx].recipe {
+                        end = logical[idx].end;
+                    }
+                    idx += 1;
+                }
+                let targets = targets.into_iter().filter(|t| !is_special_target(t)).collect();
+                (targets, SymbolKind::Target, end)
+            }
+            Some(Statement::Variable(name)) => (vec![name], SymbolKind::Variable, line.end),
+            Some(Statement::Define(name)) => {
+                let mut end = line.end;
+                while idx < logical.len() && logical[idx].in_define {
+                    end = logical[idx].end;
+                    idx += 1;
+                }
+                (vec![name], SymbolKind::Variable, end)
+            }
+            Some(Statement::Include(_)) | None => continue,
+        };
+
+        let preview = lines[line.start..(line.start + 7).min(lines.len())].join("\n");
+        for name in names {
+            symbols.push(SearchResult::new(
+                path.to_string(),
+                Language::Make,
+                kind.clone(),
+                Some(name.to_string()),
+                Span::new(line.start + 1, 0, end + 1, 0),
+                None,
+                preview.clone(),
+            ));
+        }
+    }
+
+    Ok(symbols)
+}
+
+/// Join backslash-continued lines and strip comments
+fn logical_lines(lines: &[&str]) -> Vec<LogicalLine> {
+    let mut logical = Vec::new();
+    let mut in_define = false;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let start = idx;
+        let mut text = String::new();
+        loop {
+            let line = lines[idx];
+            match line.strip_suffix('\\') {
+                Some(continued) if idx + 1 < lines.len() => {
+                    text.push_str(continued);
+                    text.push(' ');
+                    idx += 1;
+                }
+                _ => {
+                    text.push_str(line);
+                    break;
+                }
+            }
+        }
+        let recipe = lines[start].starts_with('\t');
+        if !recipe
+            && let Some(comment) = text.find('#').filter(|&i| i == 0 || !text[..i].ends_with('\\'))
+        {
+            text.truncate(comment);
+        }
+        // `define` bodies are raw text, closed by `endef` (which belongs to the block)
+        let first_word = text.split_whitespace().next();
+        let body = in_define;
+        if in_define {
+            in_define = first_word != Some("endef");
+        } else if !recipe && first_word == Some("define") {
+            in_define = true;
+        }
+        logical.push(LogicalLine { start, end: idx, text, recipe, in_define: body });
+        idx += 1;
+    }
+    logical
+}
+
+/// Classify a (non-recipe) logical line
+fn statement(text: &str) -> Option<Statement<'_>> {
+    static ASSIGNMENT: OnceLock<Regex> = OnceLock::new();
+    let assignment = ASSIGNMENT.get_or_init(|| {
+        Regex::new(r"^\s*(?:(?:export|override|private)\s+)*([A-Za-z_][\w.-]*)\s*(?:=|:=|::=|:::=|\?=|!=)")
+            .expect("invalid Make assignment pattern")
+    });
+
+    let trimmed = text.trim();
+    let first_word = trimmed.split_whitespace().next()?;
+
+    if matches!(first_word, "include" | "-include" | "sinclude") {
+        let files = trimmed.split_whitespace().skip(1).filter(|f| !f.contains('$')).collect();
+        return Some(Statement::Include(files));
+    }
+    if first_word == "define" {
+        let name = trimmed.split_whitespace().nth(1)?;
+        return Some(Statement::Define(name));
+    }
+    if let Some(captures) = assignment.captures(text) {
+        return Some(Statement::Variable(captures.get(1)?.as_str()));
+    }
+    if DIRECTIVES.contains(&first_word) || first_word == "export" {
+        return None;
+    }
+
+    // `targets: prerequisites` or `targets:: prerequisites` (`+=` appends never get here)
+    let colon = trimmed.find(':')?;
+    let (targets, rest) = (&trimmed[..colon], trimmed[colon + 1..].trim_start_matches(':'));
+    if targets.trim().is_empty() || targets.contains('=') || rest.starts_with('=') {
+        return None;
+    }
+
+    // Target-specific variables (`app: CFLAGS += -g`) don't define the rule
+    if assignment.is_match(rest) || rest.contains("+=") {
+        return None;
+    }
+
+    // `target: prerequisites ; recipe`
+    let prerequisites = rest.split(';').next().unwrap_or("");
+    Some(Statement::Rule {
+        targets: targets.split_whitespace().collect(),
+        prerequisites: prerequisites
+            .split_whitespace()
+            .filter(|p| *p != "|" && !p.contains('$') && !p.contains('%'))
+            .collect(),
+    })
+}
+
+/// `.PHONY`, `.SUFFIXES` and other built-in targets that configure Make itself
+fn is_special_target(target: &str) -> bool {
+    target
+        .strip_prefix('.')
+        .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c == '_'))
+}
+
+/// Makefile dependency extractor
+///
+/// Records every rule prerequisite and included file as an internal
+/// dependency. Prerequisites built from variables or patterns are skipped.
+pub struct MakeDependencyExtractor;
+
+impl DependencyExtractor for MakeDependencyExtractor {
+    fn extract_dependencies(source: &str) -> Result<Vec<ImportInfo>> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut imports = Vec::new();
+
+        for line in logical_lines(&lines).iter().filter(|line| !line.recipe && !line.in_define) {
+            let paths = match statement(&line.text) {
+                // `.PHONY: all clean` lists targets, not prerequisites
+                Some(Statement::Rule { targets, .. }) if targets.iter().all(|t| is_special_target(t)) => continue,
+                Some(Statement::Rule { prerequisites, .. }) => prerequisites,
+                Some(Statement::Include(files)) => files,
+                _ => continue,
+            };
+            imports.extend(paths.into_iter().map(|path| ImportInfo {
+                imported_path: path.to_string(),
+                import_type: ImportType::Internal,
+                line_number: line.start + 1,
+                imported_symbols: None,
+            }));
+        }
+
+        Ok(imports)
+    }
+}
+
+/// Resolve a prerequisite or included file to a path relative to the index root
+///
+/// Prerequisites are relative to the Makefile's directory. Returns None for
+/// names that don't look like files (`all`, `clean`): those are other targets.
+pub fn resolve_make_prerequisite_to_path(prerequisite: &str, current_file_path: &str) -> Option<String> {
+    let file_name = prerequisite.rsplit('/').next().unwrap_or(prerequisite);
+    if !prerequisite.contains('/') && !file_name.contains('.') {
+        return None;
+    }
+    crate::paths::join_relative(current_file_path, prerequisite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAKEFILE: &str = "\
+CC ?= gcc
+OBJS := main.o util.o
+export PREFIX = /usr/local
+include config.mk
+
+.PHONY: all build clean
+
+all: build
+
+# Link the binary
+build: $(OBJS) src/main.c | out
+\t$(CC) -o app $(OBJS) \\
+\t    -lm
+
+\t@echo done
+
+main.o util.o: common.h
+build: CFLAGS += -O2
+
+define HELP
+Usage: make build
+endef
+
+clean:
+\trm -f app *.o
+";
+
+    #[test]
+    fn test_parse_targets_and_variables() {
+        let symbols = parse("Makefile", MAKEFILE).unwrap();
+        let found: Vec<(&str, &SymbolKind, usize, usize)> = symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("CC", &SymbolKind::Variable, 1, 1),
+            ("OBJS", &SymbolKind::Variable, 2, 2),
+            ("PREFIX", &SymbolKind::Variable, 3, 3),
+            ("all", &SymbolKind::Target, 8, 8),
+            ("build", &SymbolKind::Target, 11, 15),
+            ("main.o", &SymbolKind::Target, 17, 17),
+            ("util.o", &SymbolKind::Target, 17, 17),
+            ("HELP", &SymbolKind::Variable, 20, 22),
+            ("clean", &SymbolKind::Target, 24, 25),
+        ]);
+        assert!(symbols.iter().all(|s| s.lang == Language::Make));
+    }
+
+    #[test]
+    fn test_extract_prerequisites_and_includes() {
+        let deps = MakeDependencyExtractor::extract_dependencies(MAKEFILE).unwrap();
+        let found: Vec<(&str, usize)> = deps.iter().map(|d| (d.imported_path.as_str(), d.line_number)).collect();
+
+        assert_eq!(found, vec![
+            ("config.mk", 4),
+            ("build", 8),
+            ("src/main.c", 11),
+            ("out", 11),
+            ("common.h", 17),
+        ]);
+        assert!(deps.iter().all(|d| matches!(d.import_type, ImportType::Internal)));
+    }
+
+    #[test]
+    fn test_resolve_prerequisites() {
+        assert_eq!(resolve_make_prerequisite_to_path("src/main.c", "Makefile").as_deref(), Some("src/main.c"));
+        assert_eq!(resolve_make_prerequisite_to_path("../rules.mk", "app/Makefile").as_deref(), Some("rules.mk"));
+        assert_eq!(resolve_make_prerequisite_to_path("build", "app/Makefile"), None);
+    }
+}
@@ -22,13 +22,93 @@ pub mod ruby;
 pub mod kotlin;
 // pub mod swift;  // Temporarily disabled - requires tree-sitter 0.23
 pub mod zig;
+pub mod shell;
+pub mod sql;
+pub mod make;
+pub mod cmake;
+pub mod bazel;
+pub mod signature;
+pub mod docs;
+pub mod qualify;
+pub mod visibility;
+pub mod supertypes;
+pub mod span;
+pub mod fallback;
 
 use anyhow::{anyhow, Result};
+use std::sync::OnceLock;
 use crate::models::{Language, SearchResult};
 
+// Generated by build.rs: `const PARSER_VERSIONS: &[(&str, &str)]`
+include!(concat!(env!("OUT_DIR"), "/parser_versions.rs"));
+
 /// Parser factory that selects the appropriate parser based on language
 pub struct ParserFactory;
 
+/// Languages whose symbol parser is built on a tree-sitter grammar
+pub(crate) const TREE_SITTER_LANGUAGES: &[Language] = &[
+    Language::Rust,
+    Language::Python,
+    Language::TypeScript,
+    Language::JavaScript,
+    Language::Go,
+    Language::Java,
+    Language::C,
+    Language::Cpp,
+    Language::CSharp,
+    Language::PHP,
+    Language::Ruby,
+    Language::Kotlin,
+    Language::Zig,
+];
+
+/// Grammars that failed to load, checked once per process
+fn grammar_failures() -> &'static [(Language, String)] {
+    static FAILURES: OnceLock<Vec<(Language, String)>> = OnceLock::new();
+    FAILURES.get_or_init(|| {
+        TREE_SITTER_LANGUAGES
+            .iter()
+            .filter_map(|&language| {
+                let check = std::panic::catch_unwind(|| -> Result<()> {
+                    let grammar = ParserFactory::get_language_grammar(language)?;
+                    tree_sitter::Parser::new().set_language(&grammar)?;
+                    Ok(())
+                });
+                let error = match check {
+                    Ok(Ok(())) => return None,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(_) => "grammar panicked while loading".to_string(),
+                };
+                log::warn!(
+                    "tree-sitter grammar for {:?} failed to load ({}); using regex fallback parser",
+                    language,
+                    error
+                );
+                Some((language, error))
+            })
+            .collect()
+    })
+}
+
+/// A parser panicked while processing a file
+///
+/// Returned (inside `anyhow::Error`) by [`ParserFactory::parse_guarded`], so
+/// callers can tell a crashing file apart from an ordinary parse error with
+/// `err.downcast_ref::<ParserPanic>()`.
+#[derive(Debug, Clone)]
+pub struct ParserPanic {
+    /// Panic message
+    pub message: String,
+}
+
+impl std::fmt::Display for ParserPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "parser panicked: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParserPanic {}
+
 /// Extracted import/dependency information (before file ID resolution)
 #[derive(Debug, Clone)]
 pub struct ImportInfo {
@@ -45,7 +125,7 @@ pub struct ImportInfo {
 /// Extracted export/re-export information (for barrel export tracking)
 #[derive(Debug, Clone)]
 pub struct ExportInfo {
-    /// Symbol being exported (None for wildcard `export * from`)
+    /// Public name of the exported symbol, after any `as` (None for wildcard `export * from`)
     pub exported_symbol: Option<String>,
     /// Source path where the symbol is re-exported from
     pub source_path: String,
@@ -81,7 +161,10 @@ impl ParserFactory {
     ///
     /// Returns an error for:
     /// - Vue/Svelte (use line-based parsing instead of tree-sitter)
+    /// - Shell scripts and Make/CMake/Bazel build files (line-based too)
+    /// - SQL (statement-based)
     /// - Swift (temporarily disabled due to tree-sitter version incompatibility)
+    /// - GraphQL/HTML (only indexed as embedded regions)
     /// - Unknown languages
     pub fn get_language_grammar(language: Language) -> Result<tree_sitter::Language> {
         match language {
@@ -107,6 +190,18 @@ impl ParserFactory {
             Language::Svelte => Err(anyhow!(
                 "Svelte uses line-based parsing, not tree-sitter (tree-sitter-svelte incompatible with tree-sitter 0.24+)"
             )),
+            Language::Shell => Err(anyhow!(
+                "Shell uses line-based parsing, not tree-sitter (no bundled tree-sitter-bash grammar)"
+            )),
+            Language::Make | Language::CMake | Language::Bazel => Err(anyhow!(
+                "{:?} build files use line-based parsing, not tree-sitter", language
+            )),
+            Language::Sql => Err(anyhow!(
+                "SQL uses statement-based parsing, not tree-sitter (no bundled tree-sitter-sql grammar)"
+            )),
+            Language::GraphQL | Language::Html => Err(anyhow!(
+                "{:?} is only indexed as embedded regions, not parsed for symbols", language
+            )),
             Language::Unknown => Err(anyhow!("Unknown language")),
         }
     }
@@ -134,7 +229,10 @@ impl ParserFactory {
             Language::Zig => &["fn", "struct", "enum", "const", "var", "type"],
             Language::Swift => &["class", "struct", "enum", "protocol", "func", "var", "let"],
             Language::Vue | Language::Svelte => &["function", "const", "let", "var"],
-            Language::Unknown => &[],
+            Language::Shell => &["function"],
+            Language::Make | Language::CMake | Language::Bazel => &[],
+            Language::Sql => &["function", "type"],
+            Language::GraphQL | Language::Html | Language::Unknown => &[],
         }
     }
 
@@ -163,13 +261,85 @@ impl ParserFactory {
         ]
     }
 
+    /// Why a language's tree-sitter grammar failed to load, if it did
+    ///
+    /// Grammars are loaded once per process. A failing language (ABI mismatch,
+    /// platform issue) is parsed by the regex-based [`fallback`] parser instead.
+    pub fn grammar_error(language: Language) -> Option<&'static str> {
+        grammar_failures()
+            .iter()
+            .find(|(lang, _)| *lang == language)
+            .map(|(_, error)| error.as_str())
+    }
+
+    /// Languages running on the regex fallback parser, with their grammar errors
+    pub fn degraded_languages() -> &'static [(Language, String)] {
+        grammar_failures()
+    }
+
+    /// Get the parser version for a language
+    ///
+    /// The version is a build-time hash of the language's parser source and its
+    /// tree-sitter grammar version. Cached symbols record it so that a grammar or
+    /// parser upgrade invalidates only the affected languages' symbol sets.
+    /// Languages on the fallback parser report `"fallback"`, so their symbols are
+    /// re-extracted once the grammar loads again.
+    ///
+    /// Returns an empty string for languages without a parser.
+    pub fn parser_version(language: Language) -> &'static str {
+        if Self::grammar_error(language).is_some() {
+            return "fallback";
+        }
+
+        let key = match language {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Vue => "vue",
+            Language::Svelte => "svelte",
+            Language::Go => "go",
+            Language::Java => "java",
+            Language::PHP => "php",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::CSharp => "csharp",
+            Language::Ruby => "ruby",
+            Language::Kotlin => "kotlin",
+            Language::Zig => "zig",
+            Language::Shell => "shell",
+            Language::Make => "make",
+            Language::CMake => "cmake",
+            Language::Bazel => "bazel",
+            Language::Sql => "sql",
+            Language::Swift | Language::GraphQL | Language::Html | Language::Unknown => return "",
+        };
+
+        let version = PARSER_VERSIONS.iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, version)| *version)
+            .unwrap_or("");
+
+        // SQL symbols also depend on the configured dialect
+        if language == Language::Sql {
+            return sql::versioned(version);
+        }
+        version
+    }
+
+    /// Get the parser versions of every language, keyed by language name
+    pub fn parser_versions() -> &'static [(&'static str, &'static str)] {
+        PARSER_VERSIONS
+    }
+
     /// Parse a file and extract symbols based on its language
     pub fn parse(
         path: &str,
         source: &str,
         language: Language,
     ) -> Result<Vec<SearchResult>> {
-        match language {
+        let mut symbols = match language {
+            _ if Self::grammar_error(language).is_some() => Ok(fallback::parse(path, source, language)),
             Language::Rust => rust::parse(path, source),
             Language::TypeScript => typescript::parse(path, source, language),
             Language::JavaScript => typescript::parse(path, source, language),
@@ -189,10 +359,53 @@ impl ParserFactory {
                 Ok(vec![])
             }
             Language::Zig => zig::parse(path, source),
+            Language::Shell => shell::parse(path, source),
+            Language::Make => make::parse(path, source),
+            Language::CMake => cmake::parse(path, source),
+            Language::Bazel => bazel::parse(path, source),
+            Language::Sql => sql::parse(path, source),
+            Language::GraphQL | Language::Html => Ok(vec![]),
             Language::Unknown => {
                 log::warn!("Unknown language for file: {}", path);
                 Ok(vec![])
             }
+        }?;
+
+        // Derive body-free signatures (parameters, return types, field lists)
+        signature::attach_signatures(&mut symbols, source, language);
+
+        // Doc comments and docstrings written for each symbol
+        docs::attach_docs(&mut symbols, source, language);
+
+        // Enclosing symbols (module::Class::method)
+        qualify::attach_qualified_names(&mut symbols, language);
+
+        // Exported vs. local (needs the parents recorded above)
+        visibility::attach_visibility(&mut symbols, source, language);
+
+        // HTTP routes registered with web frameworks
+        crate::routes::attach_routes(&mut symbols, path, source, language);
+
+        Ok(symbols)
+    }
+
+    /// Parse a file like [`ParserFactory::parse`], converting a parser panic into a
+    /// [`ParserPanic`] error so one malformed file can't take down a whole query
+    pub fn parse_guarded(
+        path: &str,
+        source: &str,
+        language: Language,
+    ) -> Result<Vec<SearchResult>> {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Self::parse(path, source, language))) {
+            Ok(result) => result,
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Err(ParserPanic { message }.into())
+            }
         }
     }
 }
@@ -206,4 +419,65 @@ mod tests {
         // Simple test to ensure module compiles
         let _factory = ParserFactory;
     }
+
+    #[test]
+    fn test_parse_attaches_signatures() {
+        let source = "/// Adds two numbers\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nstruct Pair { left: u8, right: u8 }\n";
+        let symbols = ParserFactory::parse("lib.rs", source, Language::Rust).unwrap();
+
+        let add = symbols.iter().find(|s| s.symbol.as_deref() == Some("add")).unwrap();
+        assert_eq!(add.signature.as_deref(), Some("pub fn add(a: i32, b: i32) -> i32"));
+
+        let pair = symbols.iter().find(|s| s.symbol.as_deref() == Some("Pair")).unwrap();
+        assert_eq!(pair.signature.as_deref(), Some("struct Pair { left: u8, right: u8 }"));
+    }
+
+    #[test]
+    fn test_parse_attaches_docs() {
+        let source = "/// Adds two numbers\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\nstruct Pair { left: u8, right: u8 }\n";
+        let symbols = ParserFactory::parse("lib.rs", source, Language::Rust).unwrap();
+
+        let add = symbols.iter().find(|s| s.symbol.as_deref() == Some("add")).unwrap();
+        assert_eq!(add.doc.as_deref(), Some("Adds two numbers"));
+
+        let pair = symbols.iter().find(|s| s.symbol.as_deref() == Some("Pair")).unwrap();
+        assert!(pair.doc.is_none());
+    }
+
+    #[test]
+    fn test_parse_guarded_survives_truncated_input() {
+        // Property: no prefix of a valid file may escape as a panic
+        let samples = [
+            ("lib.rs", Language::Rust, "/// Doc\n#[derive(Debug)]\npub struct P<'a, T: Clone> { x: &'a T }\nimpl<'a, T: Clone> P<'a, T> { fn get(&self) -> &T { self.x } }\n"),
+            ("app.py", Language::Python, "@dec\nclass A(B):\n    \"\"\"Doc.\"\"\"\n    def f(self, x: int = {'a': 1}) -> str:\n        return f'{x}'\n"),
+            ("app.ts", Language::TypeScript, "/** Doc */\nexport class S<T extends I> { m(a: T): Promise<void> { return `${a}`; } }\n"),
+            ("main.go", Language::Go, "// Doc\nfunc (c *C) M(a []int) (int, error) {\n\treturn 0, nil\n}\n"),
+        ];
+
+        for (path, language, source) in samples {
+            for (end, _) in source.char_indices() {
+                let result = ParserFactory::parse_guarded(path, &source[..end], language);
+                if let Err(e) = result {
+                    assert!(e.downcast_ref::<ParserPanic>().is_none(), "{} panicked at byte {}: {}", path, end, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parser_version_per_language() {
+        assert!(!ParserFactory::parser_version(Language::Rust).is_empty());
+        assert!(!ParserFactory::parser_version(Language::Python).is_empty());
+        assert_ne!(
+            ParserFactory::parser_version(Language::Rust),
+            ParserFactory::parser_version(Language::Python)
+        );
+        assert_eq!(ParserFactory::parser_version(Language::Unknown), "");
+    }
+
+    #[test]
+    fn test_bundled_grammars_load() {
+        assert!(ParserFactory::degraded_languages().is_empty(), "{:?}", ParserFactory::degraded_languages());
+        assert_eq!(ParserFactory::grammar_error(Language::Rust), None);
+    }
 }
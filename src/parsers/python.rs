@@ -4,7 +4,7 @@
 //! - Functions (def, async def)
 //! - Classes (regular, abstract)
 //! - Methods (regular, async, static, class methods, properties via @property)
-//! - Decorators (tracked in scope)
+//! - Decorators (uses, as Attribute symbols named by their dotted path)
 //! - Lambda expressions assigned to variables
 //! - Local variables (inside functions)
 //! - Global variables (module-level non-uppercase variables)
@@ -18,7 +18,7 @@ use crate::models::{Language, SearchResult, Span, SymbolKind};
 
 /// Parse Python source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_python::LANGUAGE;
 
     parser
@@ -41,6 +41,7 @@ pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
     symbols.extend(extract_global_variables(source, &root_node, &language.into())?);
     symbols.extend(extract_local_variables(source, &root_node, &language.into())?);
     symbols.extend(extract_lambdas(source, &root_node, &language.into())?);
+    symbols.extend(extract_decorators(source, &root_node, &language.into())?);
 
     // Add file path to all symbols
     for symbol in &mut symbols {
@@ -384,6 +385,25 @@ fn extract_lambdas(
     extract_symbols(source, root, &query, SymbolKind::Function, None)
 }
 
+/// Extract decorator uses (@property, @app.route("/"), @pytest.mark.slow)
+fn extract_decorators(
+    source: &str,
+    root: &tree_sitter::Node,
+    language: &tree_sitter::Language,
+) -> Result<Vec<SearchResult>> {
+    let query_str = r#"
+        (decorator (identifier) @name) @decorator
+        (decorator (attribute) @name) @decorator
+        (decorator (call function: (identifier) @name)) @decorator
+        (decorator (call function: (attribute) @name)) @decorator
+    "#;
+
+    let query = Query::new(language, query_str)
+        .context("Failed to create decorator query")?;
+
+    extract_symbols(source, root, &query, SymbolKind::Attribute, None)
+}
+
 /// Generic symbol extraction helper
 fn extract_symbols(
     source: &str,
@@ -783,7 +803,7 @@ pub fn reclassify_python_import(
         // Extract first component: "django.conf.settings" → "django"
         let first_component = import_path.split('.').next().unwrap_or(import_path);
 
-        if first_component == prefix {
+        if package_name_matches(prefix, first_component) {
             return ImportType::Internal;
         }
     }
@@ -968,6 +988,10 @@ pub fn parse_all_python_packages(index_root: &std::path::Path) -> Result<Vec<Pyt
 /// - Absolute imports: `from myapp.models import User` → `myapp/models.py` or `myapp/models/__init__.py`
 /// - Relative imports: `from .models import User` (requires current_file_path)
 /// - Package imports: `import myapp.utils` → `myapp/utils.py` or `myapp/utils/__init__.py`
+///
+/// Returns pipe-delimited candidate paths in order of preference (module file, then
+/// package `__init__.py`, then the same under `src/` for src-layout projects).
+/// The caller should try each candidate against the index.
 pub fn resolve_python_import_to_path(
     import_path: &str,
     packages: &[PythonPackage],
@@ -982,27 +1006,24 @@ pub fn resolve_python_import_to_path(
     // Extract first component: "django.conf.settings" → "django"
     let first_component = import_path.split('.').next()?;
 
-    // Find matching package
-    for package in packages {
-        if package.name == first_component {
-            // Convert import path to file path
-            // "django.conf.settings" → "django/conf/settings.py"
-            let module_path = import_path.replace('.', "/");
-
-            // Try both .py file and __init__.py in package
-            let candidates = vec![
-                format!("{}/{}.py", package.project_root, module_path),
-                format!("{}/{}/__init__.py", package.project_root, module_path),
-            ];
-
-            for candidate in candidates {
-                log::trace!("Checking Python module path: {}", candidate);
-                return Some(candidate);
-            }
+    // Convert import path to file path
+    // "django.conf.settings" → "django/conf/settings"
+    let module_path = import_path.replace('.', "/");
+
+    let mut candidates = Vec::new();
+    for package in packages.iter().filter(|p| package_name_matches(&p.name, first_component)) {
+        let src_root = join_python_path(&package.project_root, "src");
+        for base in [package.project_root.as_str(), src_root.as_str()] {
+            candidates.extend(python_module_candidates(base, &module_path));
         }
     }
 
-    None
+    if candidates.is_empty() {
+        return None;
+    }
+
+    log::trace!("Python module candidates for {}: {}", import_path, candidates.join(" | "));
+    Some(candidates.join("|"))
 }
 
 /// Resolve relative Python imports (. or ..)
@@ -1027,30 +1048,46 @@ fn resolve_relative_python_import(
     for _ in 1..dots {
         target_dir = target_dir.parent()?.to_path_buf();
     }
+    let target_dir = target_dir.to_string_lossy();
 
     // Get the module path after the dots
     let module_path = import_path.trim_start_matches('.');
 
     if module_path.is_empty() {
         // Just "from ." means import from current package's __init__.py
-        return Some(format!("{}/__init__.py", target_dir.to_string_lossy()));
+        return Some(join_python_path(&target_dir, "__init__.py"));
     }
 
     // Convert dots to slashes: "models.user" → "models/user"
-    let file_path = module_path.replace('.', "/");
+    let candidates = python_module_candidates(&target_dir, &module_path.replace('.', "/"));
 
-    // Try both .py file and __init__.py in package
-    let candidates = vec![
-        format!("{}/{}.py", target_dir.to_string_lossy(), file_path),
-        format!("{}/{}/__init__.py", target_dir.to_string_lossy(), file_path),
-    ];
+    log::trace!("Relative Python import candidates for {}: {}", import_path, candidates.join(" | "));
+    Some(candidates.join("|"))
+}
+
+/// Candidate files for a module: `<base>/<module>.py` and `<base>/<module>/__init__.py`
+fn python_module_candidates(base: &str, module_path: &str) -> [String; 2] {
+    [
+        join_python_path(base, &format!("{}.py", module_path)),
+        join_python_path(base, &format!("{}/__init__.py", module_path)),
+    ]
+}
 
-    for candidate in candidates {
-        log::trace!("Checking relative Python import: {}", candidate);
-        return Some(candidate);
+/// Join index-relative path segments, treating an empty base as the index root
+fn join_python_path(base: &str, path: &str) -> String {
+    if base.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), path)
     }
+}
 
-    None
+/// Whether an import's top-level module belongs to a distribution name
+///
+/// Distribution names are case-insensitive and use `-` or `.` where the import
+/// name uses `_` (`my-app` is imported as `my_app`).
+fn package_name_matches(package_name: &str, first_component: &str) -> bool {
+    package_name.replace(['-', '.'], "_").eq_ignore_ascii_case(first_component)
 }
 
 #[cfg(test)]
@@ -1226,6 +1263,40 @@ class WebService:
         assert!(method_symbols.iter().any(|s| s.symbol.as_deref() == Some("validate_url")));
     }
 
+    #[test]
+    fn test_parse_decorators() {
+        let source = r#"
+@app.route("/users")
+@login_required
+def list_users():
+    pass
+
+class Config:
+    @property
+    def url(self):
+        return self._url
+
+@pytest.mark.slow
+def test_import():
+    pass
+        "#;
+
+        let symbols = parse("test.py", source).unwrap();
+
+        let decorators: Vec<_> = symbols.iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Attribute))
+            .collect();
+
+        assert_eq!(decorators.len(), 4);
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("app.route") && s.span.start_line == 2));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("login_required")));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("property")));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("pytest.mark.slow")));
+
+        // `--kind decorator` resolves to the same kind
+        assert_eq!("Decorator".parse::<SymbolKind>().unwrap(), SymbolKind::Attribute);
+    }
+
     #[test]
     fn test_parse_mixed_symbols() {
         let source = r#"
@@ -1513,6 +1584,27 @@ def get_config():
         assert!(path.contains("models/user"));
     }
 
+    #[test]
+    fn test_resolve_python_import_candidates() {
+        // Distribution "my-app" at the index root is imported as "my_app"
+        let packages = vec![PythonPackage {
+            name: "my-app".to_string(),
+            project_root: String::new(),
+            abs_project_root: std::path::PathBuf::from("/repo"),
+        }];
+
+        let resolved = resolve_python_import_to_path("my_app.models", &packages, None);
+        assert_eq!(
+            resolved.as_deref(),
+            Some("my_app/models.py|my_app/models/__init__.py|src/my_app/models.py|src/my_app/models/__init__.py")
+        );
+        assert!(matches!(reclassify_python_import("my_app.models", Some("my-app")), ImportType::Internal));
+
+        // Relative imports try the module file, then the package
+        let resolved = resolve_python_import_to_path("..models", &[], Some("app/views/admin.py"));
+        assert_eq!(resolved.as_deref(), Some("app/models.py|app/models/__init__.py"));
+    }
+
     #[test]
     fn test_resolve_python_import_not_found() {
         use tempfile::TempDir;
@@ -0,0 +1,205 @@
+//! Parent chains and qualified names for parsed symbols
+//!
+//! After a language parser has produced symbols, this module records where each
+//! one is nested, so two methods named `run` in different classes can be told
+//! apart. Containers are the modules, namespaces, classes, structs, enums,
+//! interfaces and traits whose span encloses a symbol; the innermost one
+//! becomes `parent` and the whole chain (outermost first) plus the symbol's own
+//! name becomes `qualified_name`, joined with `::` for Rust, C++ and PHP and
+//! `.` elsewhere (`app::Worker::run`, `app.Worker.run`).
+//!
+//! Some parsers already know a method's owner even though it is declared
+//! outside the owner's span (Rust `impl` blocks, Go receivers). That owner is
+//! kept as the innermost link of the chain.
+
+use crate::models::{Language, SearchResult, SymbolKind};
+
+/// Separator between the segments of a qualified name in `language`
+pub fn separator(language: Language) -> &'static str {
+    match language {
+        Language::Rust | Language::Cpp | Language::PHP => "::",
+        _ => ".",
+    }
+}
+
+/// Fill in `parent` and `qualified_name` for every nested symbol
+pub fn attach_qualified_names(symbols: &mut [SearchResult], language: Language) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    let chains: Vec<Vec<String>> = (0..symbols.len())
+        .map(|i| container_chain(symbols, i))
+        .collect();
+
+    for (symbol, mut chain) in symbols.iter_mut().zip(chains) {
+        let Some(name) = symbol.symbol.clone() else {
+            continue;
+        };
+
+        // Owner reported by the parser (impl target, receiver type)
+        if let Some(owner) = symbol.parent.take()
+            && chain.last() != Some(&owner)
+        {
+            chain.push(owner);
+        }
+
+        if chain.is_empty() {
+            continue;
+        }
+
+        symbol.parent = chain.last().cloned();
+        chain.push(name);
+        symbol.qualified_name = Some(chain.join(separator(language)));
+    }
+}
+
+/// Split a qualified name (`Worker::run`, `Worker.run`) into its segments
+pub fn segments(path: &str) -> Vec<&str> {
+    path.split("::")
+        .flat_map(|s| s.split('.'))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Whether a symbol's qualified name ends with the segments of `path`
+///
+/// `Worker::run` matches `app::Worker::run` but not `app::Worker::run_all`
+/// or `Scheduler::run`; a single segment matches the symbol name.
+pub fn matches_path(symbol: &SearchResult, path: &[&str]) -> bool {
+    let Some(name) = symbol.qualified_name.as_deref().or(symbol.symbol.as_deref()) else {
+        return false;
+    };
+    let own = segments(name);
+    !path.is_empty() && own.ends_with(path)
+}
+
+/// Kinds whose span can enclose other symbols
+pub(crate) fn is_container(kind: &SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Class
+            | SymbolKind::Struct
+            | SymbolKind::Enum
+            | SymbolKind::Interface
+            | SymbolKind::Trait
+            | SymbolKind::Module
+            | SymbolKind::Namespace
+    )
+}
+
+/// Names of the containers enclosing `symbols[index]`, outermost first
+fn container_chain(symbols: &[SearchResult], index: usize) -> Vec<String> {
+    let target = &symbols[index];
+    let mut containers: Vec<&SearchResult> = symbols
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| {
+            *i != index
+                && s.symbol.is_some()
+                && is_container(&s.kind)
+                && s.span.start_line <= target.span.start_line
+                && target.span.end_line <= s.span.end_line
+                && (s.span.start_line, s.span.end_line) != (target.span.start_line, target.span.end_line)
+        })
+        .map(|(_, s)| s)
+        .collect();
+
+    // Outermost (earliest start, latest end) first
+    containers.sort_by(|a, b| {
+        a.span.start_line.cmp(&b.span.start_line)
+            .then_with(|| b.span.end_line.cmp(&a.span.end_line))
+    });
+
+    let mut chain: Vec<String> = Vec::new();
+    for container in containers {
+        let name = container.symbol.clone().unwrap_or_default();
+        // Parsers may report a declaration twice (e.g. exported classes)
+        if chain.last() != Some(&name) {
+            chain.push(name);
+        }
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Span;
+
+    fn symbol(name: &str, kind: SymbolKind, start: usize, end: usize, scope: Option<&str>) -> SearchResult {
+        SearchResult::new(
+            String::new(),
+            Language::Rust,
+            kind,
+            Some(name.to_string()),
+            Span { start_line: start, end_line: end },
+            scope.map(|s| s.to_string()),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_nested_chain() {
+        let mut symbols = vec![
+            symbol("app", SymbolKind::Module, 1, 30, None),
+            symbol("Worker", SymbolKind::Class, 2, 10, None),
+            symbol("run", SymbolKind::Method, 3, 5, Some("class Worker")),
+            symbol("Scheduler", SymbolKind::Class, 12, 20, None),
+            symbol("run", SymbolKind::Method, 13, 15, Some("class Scheduler")),
+            symbol("main", SymbolKind::Function, 40, 42, None),
+            symbol("count", SymbolKind::Variable, 41, 41, None),
+        ];
+        attach_qualified_names(&mut symbols, Language::Rust);
+
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("app::Worker"));
+        assert_eq!(symbols[2].parent.as_deref(), Some("Worker"));
+        assert_eq!(symbols[2].qualified_name.as_deref(), Some("app::Worker::run"));
+        assert_eq!(symbols[4].qualified_name.as_deref(), Some("app::Scheduler::run"));
+        // Top-level symbols have no parent
+        assert_eq!(symbols[0].qualified_name, None);
+        assert_eq!(symbols[5].parent, None);
+        // Functions don't qualify their locals
+        assert_eq!(symbols[6].parent, None);
+    }
+
+    #[test]
+    fn test_separator_follows_language() {
+        let mut symbols = vec![
+            symbol("Worker", SymbolKind::Class, 1, 10, None),
+            symbol("run", SymbolKind::Method, 2, 4, Some("class Worker")),
+        ];
+        attach_qualified_names(&mut symbols, Language::Python);
+
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("Worker.run"));
+    }
+
+    #[test]
+    fn test_owner_outside_span() {
+        // Rust methods live in `impl` blocks outside the struct's span
+        let mut symbols = vec![
+            symbol("Worker", SymbolKind::Struct, 1, 3, None),
+            symbol("run", SymbolKind::Method, 6, 8, Some("impl Worker")),
+        ];
+        attach_qualified_names(&mut symbols, Language::Rust);
+
+        assert_eq!(symbols[1].parent.as_deref(), Some("Worker"));
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("Worker::run"));
+    }
+
+    #[test]
+    fn test_matches_path() {
+        let mut symbols = vec![
+            symbol("Worker", SymbolKind::Class, 1, 10, None),
+            symbol("run", SymbolKind::Method, 2, 4, None),
+            symbol("run_all", SymbolKind::Method, 5, 7, None),
+        ];
+        attach_qualified_names(&mut symbols, Language::Rust);
+
+        assert!(matches_path(&symbols[1], &segments("Worker::run")));
+        assert!(matches_path(&symbols[1], &segments("Worker.run")));
+        assert!(matches_path(&symbols[1], &segments("run")));
+        assert!(!matches_path(&symbols[2], &segments("Worker::run")));
+        assert!(!matches_path(&symbols[1], &segments("Scheduler::run")));
+    }
+}
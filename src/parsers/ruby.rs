@@ -20,7 +20,7 @@ use crate::parsers::{DependencyExtractor, ImportInfo};
 
 /// Parse Ruby source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_ruby::LANGUAGE;
 
     parser
@@ -11,7 +11,8 @@
 //! - Local variables (let bindings)
 //! - Modules
 //! - Type aliases
-//! - Macros (macro_rules! definitions)
+//! - Macros (macro_rules! definitions, function-like and derive proc macros)
+//! - Attributes (#[proc_macro_attribute] definitions and attribute uses)
 
 use anyhow::{Context, Result};
 use streaming_iterator::StreamingIterator;
@@ -21,7 +22,7 @@ use crate::parsers::{DependencyExtractor, ImportInfo};
 
 /// Parse Rust source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_rust::LANGUAGE;
 
     parser
@@ -271,11 +272,14 @@ fn extract_macros(source: &str, root: &tree_sitter::Node) -> Result<Vec<SearchRe
 /// Extract attributes: BOTH definitions and uses
 /// Definitions: #[proc_macro_attribute] pub fn route(...)
 /// Uses: #[test] fn my_test(), #[derive(Debug)] struct Foo
+///
+/// Function-like (#[proc_macro]) and derive (#[proc_macro_derive(Name)]) proc
+/// macros are extracted as Macro symbols; a derive is named after the derive.
 fn extract_attributes(source: &str, root: &tree_sitter::Node) -> Result<Vec<SearchResult>> {
     let language = tree_sitter_rust::LANGUAGE;
     let mut symbols = Vec::new();
 
-    // Part 1: Extract attribute DEFINITIONS (proc macro attributes)
+    // Part 1: Extract attribute DEFINITIONS (proc macros)
     let func_query_str = r#"
         (function_item
             name: (identifier) @name) @function
@@ -304,9 +308,9 @@ fn extract_attributes(source: &str, root: &tree_sitter::Node) -> Result<Vec<Sear
             }
         }
 
-        // Check if this function has #[proc_macro_attribute] attribute
+        // Check if this function has a #[proc_macro*] attribute
         if let (Some(name), Some(func_node)) = (name, func_node) {
-            let mut has_proc_macro_attr = false;
+            let mut proc_macro = None;
 
             if let Some(parent) = func_node.parent() {
                 let mut func_index = None;
@@ -324,8 +328,8 @@ fn extract_attributes(source: &str, root: &tree_sitter::Node) -> Result<Vec<Sear
                         if let Some(child) = parent.child(i) {
                             if child.kind() == "attribute_item" {
                                 let attr_text = child.utf8_text(source.as_bytes()).unwrap_or("");
-                                if attr_text.contains("proc_macro_attribute") {
-                                    has_proc_macro_attr = true;
+                                if let Some(found) = proc_macro_definition(attr_text, &name) {
+                                    proc_macro = Some(found);
                                 }
                             } else if !child.kind().contains("comment") && child.kind() != "line_comment" {
                                 break;
@@ -335,15 +339,15 @@ fn extract_attributes(source: &str, root: &tree_sitter::Node) -> Result<Vec<Sear
                 }
             }
 
-            if has_proc_macro_attr {
+            if let Some((kind, macro_name)) = proc_macro {
                 let span = node_to_span(&func_node);
                 let preview = extract_preview(source, &span);
 
                 symbols.push(SearchResult::new(
                     String::new(),
                     Language::Rust,
-                    SymbolKind::Attribute,
-                    Some(name),
+                    kind,
+                    Some(macro_name),
                     span,
                     None,
                     preview,
@@ -401,6 +405,29 @@ fn extract_attributes(source: &str, root: &tree_sitter::Node) -> Result<Vec<Sear
     Ok(symbols)
 }
 
+/// Kind and name of the proc macro an attribute defines on function `fn_name`
+///
+/// `#[proc_macro_attribute]` defines an attribute named after the function,
+/// `#[proc_macro]` a function-like macro, and `#[proc_macro_derive(Name, ...)]` a
+/// derive macro named `Name`.
+fn proc_macro_definition(attr_text: &str, fn_name: &str) -> Option<(SymbolKind, String)> {
+    let inner = attr_text.trim().strip_prefix("#[")?.strip_suffix(']')?.trim();
+
+    if inner == "proc_macro_attribute" {
+        Some((SymbolKind::Attribute, fn_name.to_string()))
+    } else if inner == "proc_macro" {
+        Some((SymbolKind::Macro, fn_name.to_string()))
+    } else if let Some(args) = inner.strip_prefix("proc_macro_derive") {
+        let derive = args.trim_start().strip_prefix('(')?
+            .split([',', ')'])
+            .next()?
+            .trim();
+        (!derive.is_empty()).then(|| (SymbolKind::Macro, derive.to_string()))
+    } else {
+        None
+    }
+}
+
 /// Generic symbol extraction helper
 fn extract_symbols(
     source: &str,
@@ -985,6 +1012,39 @@ mod tests {
         assert_eq!(attributes.len(), 4);
     }
 
+    #[test]
+    fn test_proc_macro_definitions() {
+        let source = r#"
+            use proc_macro::TokenStream;
+
+            #[proc_macro]
+            pub fn sql(input: TokenStream) -> TokenStream {
+                input
+            }
+
+            #[proc_macro_derive(Builder, attributes(builder))]
+            pub fn derive_builder(input: TokenStream) -> TokenStream {
+                input
+            }
+        "#;
+
+        let symbols = parse("lib.rs", source).unwrap();
+
+        let macros: Vec<_> = symbols.iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Macro))
+            .collect();
+
+        assert_eq!(macros.len(), 2);
+        assert!(macros.iter().any(|m| m.symbol.as_deref() == Some("sql")));
+        assert!(macros.iter().any(|m| m.symbol.as_deref() == Some("Builder")));
+
+        assert_eq!(
+            proc_macro_definition("#[proc_macro_attribute]", "route"),
+            Some((SymbolKind::Attribute, "route".to_string()))
+        );
+        assert_eq!(proc_macro_definition("#[inline]", "route"), None);
+    }
+
     #[test]
     fn test_attribute_uses() {
         let source = r#"
@@ -0,0 +1,384 @@
+//! Shell script parser
+//!
+//! Extracts symbols from shell scripts (`*.sh`, `*.bash`, `*.zsh`, `*.ksh`):
+//! - Functions (`name() { ... }`, `function name { ... }`), spanning their body
+//! - Aliases (`alias ll='ls -l'`, kind: alias)
+//!
+//! `source file` and `. file` are extracted as dependencies. Paths built from
+//! the script's own directory (`$(dirname "$0")/lib.sh`, `$SCRIPT_DIR/lib.sh`)
+//! resolve against it; other relative paths are tried against the script's
+//! directory, then the index root. Paths built from other variables
+//! (`$HOME/.bashrc`) are skipped.
+//!
+//! Note: This parser is line-based since no tree-sitter grammar for shell is
+//! bundled.
+
+use anyhow::Result;
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::models::{ImportType, Language, SearchResult, Span, SymbolKind};
+use crate::parsers::{DependencyExtractor, ImportInfo};
+
+/// Parse a shell script and extract functions and aliases
+pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
+    static FUNCTION: OnceLock<Regex> = OnceLock::new();
+    static ALIAS: OnceLock<Regex> = OnceLock::new();
+    let function = FUNCTION.get_or_init(|| {
+        Regex::new(r"^\s*(?:function\s+([^\s(){}=;&|<>$]+)(?:\s*\(\s*\))?|([^\s(){}=;&|<>$]+)\s*\(\s*\))\s*([{(]?)")
+            .expect("invalid shell function pattern")
+    });
+    let alias = ALIAS.get_or_init(|| Regex::new(r"(?:^|[;&|]|\bthen|\bdo)\s*alias\s").expect("invalid shell alias pattern"));
+
+    let lines: Vec<&str> = source.lines().collect();
+    let code = code_lines(&lines);
+    let mut symbols = Vec::new();
+
+    for (idx, text) in code.iter().enumerate() {
+        if let Some(captures) = function.captures(text) {
+            let name = captures.get(1).or_else(|| captures.get(2)).map_or("", |m| m.as_str());
+            // The body may open on the next line (`name()` then `{`)
+            let body = match captures.get(3).map(|m| m.start()).filter(|_| !captures[3].is_empty()) {
+                Some(open) => Some((idx, open)),
+                None => (idx + 1..code.len())
+                    .find(|&i| !code[i].trim().is_empty())
+                    .and_then(|i| code[i].find(|c: char| !c.is_whitespace()).map(|open| (i, open)))
+                    .filter(|&(i, open)| matches!(code[i].as_bytes()[open], b'{' | b'(')),
+            };
+            if let Some((line, open)) = body {
+                let end = body_end(&code, line, open);
+                symbols.push(symbol(path, &lines, name, SymbolKind::Function, idx, end));
+            }
+            continue;
+        }
+
+        if let Some(found) = alias.find(text) {
+            // `alias ll='ls -l' la='ls -a'` defines each name before an `=`
+            let names = text[found.end()..]
+                .split_whitespace()
+                .filter_map(|word| word.split_once('=').map(|(name, _)| name))
+                .filter(|name| !name.is_empty() && !name.starts_with('-') && !name.contains('$'));
+            for name in names {
+                symbols.push(symbol(path, &lines, name, SymbolKind::Alias, idx, idx));
+            }
+        }
+    }
+
+    Ok(symbols)
+}
+
+fn symbol(path: &str, lines: &[&str], name: &str, kind: SymbolKind, start: usize, end: usize) -> SearchResult {
+    SearchResult::new(
+        path.to_string(),
+        Language::Shell,
+        kind,
+        Some(name.to_string()),
+        Span::new(start + 1, 0, end + 1, 0),
+        None,
+        lines[start..(start + 7).min(lines.len())].join("\n"),
+    )
+}
+
+/// Line of the bracket closing the one at `code[line][open]` (the last line if it is never closed)
+fn body_end(code: &[String], line: usize, open: usize) -> usize {
+    let (opening, closing) = match code[line].as_bytes()[open] {
+        b'(' => (b'(', b')'),
+        _ => (b'{', b'}'),
+    };
+    let mut depth = 0usize;
+    for (idx, text) in code.iter().enumerate().skip(line) {
+        let from = if idx == line { open } else { 0 };
+        for &byte in &text.as_bytes()[from..] {
+            if byte == opening {
+                depth += 1;
+            } else if byte == closing {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return idx;
+                }
+            }
+        }
+    }
+    code.len().saturating_sub(1)
+}
+
+/// Lines with the contents of strings blanked, comments removed and here-document
+/// bodies emptied, so brackets and words can be matched as code
+///
+/// Blanking keeps byte offsets, so a position in a code line is the same
+/// position in the source line.
+fn code_lines(lines: &[&str]) -> Vec<String> {
+    let mut code = Vec::with_capacity(lines.len());
+    let mut quote: Option<char> = None;
+    // Terminators of the here-documents opened on the previous code line (and whether tabs are stripped)
+    let mut pending: Vec<(String, bool)> = Vec::new();
+
+    let mut idx = 0;
+    while idx < lines.len() {
+        if !pending.is_empty() {
+            let (terminator, strip_tabs) = pending.remove(0);
+            while idx < lines.len() {
+                let line = if strip_tabs { lines[idx].trim_start_matches('\t') } else { lines[idx] };
+                idx += 1;
+                code.push(String::new());
+                if line == terminator {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let line = lines[idx];
+        let mut text = String::with_capacity(line.len());
+        let mut escaped = false;
+        let mut previous: Option<char> = None;
+        for (i, c) in line.char_indices() {
+            let blank = |text: &mut String| text.extend(std::iter::repeat_n(' ', c.len_utf8()));
+            match quote {
+                _ if escaped => {
+                    escaped = false;
+                    blank(&mut text);
+                }
+                Some('"') if c == '\\' => {
+                    escaped = true;
+                    blank(&mut text);
+                }
+                Some(q) if c == q => {
+                    quote = None;
+                    text.push(c);
+                }
+                Some(_) => blank(&mut text),
+                None => match c {
+                    '\\' => {
+                        escaped = true;
+                        text.push(c);
+                    }
+                    '"' | '\'' => {
+                        quote = Some(c);
+                        text.push(c);
+                    }
+                    '#' if previous.is_none_or(|p| p.is_whitespace() || matches!(p, ';' | '&' | '|' | '(')) => {
+                        text.extend(std::iter::repeat_n(' ', line.len() - i));
+                        break;
+                    }
+                    _ => text.push(c),
+                },
+            }
+            previous = Some(c);
+        }
+
+        pending.extend(heredoc_terminators(&text, line));
+        code.push(text);
+        idx += 1;
+    }
+
+    code
+}
+
+/// Terminators of the here-documents opened on a line (`<<EOF`, `<<-'EOF'`), and whether tabs are stripped
+///
+/// Quoted terminators are blanked in the code line, so they are read from the source line.
+fn heredoc_terminators(code: &str, line: &str) -> Vec<(String, bool)> {
+    let mut terminators = Vec::new();
+    let mut from = 0;
+    while let Some(found) = code[from..].find("<<") {
+        from += found + 2;
+        // `<<<` is a here-string
+        if code[from..].starts_with('<') {
+            from += 1;
+            continue;
+        }
+        let rest = &line[from..];
+        let strip_tabs = rest.starts_with('-');
+        let rest = rest.trim_start_matches('-').trim_start().trim_start_matches(['\'', '"', '\\']);
+        let terminator: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        // `$((1 << 2))` shifts rather than opening a here-document
+        if terminator.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            terminators.push((terminator, strip_tabs));
+        }
+    }
+    terminators
+}
+
+/// Shell dependency extractor
+///
+/// Records the file of every `source` and `.` command. Script-directory
+/// prefixes are rewritten to `./`; arguments built from other variables or
+/// command substitutions are skipped, and absolute paths are external.
+pub struct ShellDependencyExtractor;
+
+impl DependencyExtractor for ShellDependencyExtractor {
+    fn extract_dependencies(source: &str) -> Result<Vec<ImportInfo>> {
+        static SOURCE: OnceLock<Regex> = OnceLock::new();
+        let command = SOURCE.get_or_init(|| {
+            Regex::new(r"(?:^|[;&|(]|\bthen|\bdo|\belse)\s*(?:source|\.)\s+").expect("invalid shell source pattern")
+        });
+
+        let lines: Vec<&str> = source.lines().collect();
+        let mut imports = Vec::new();
+
+        for (idx, text) in code_lines(&lines).iter().enumerate() {
+            for found in command.find_iter(text) {
+                let Some(path) = sourced_path(&word(&lines[idx][found.end()..])) else {
+                    continue;
+                };
+                imports.push(ImportInfo {
+                    import_type: if path.starts_with('/') { ImportType::External } else { ImportType::Internal },
+                    imported_path: path,
+                    line_number: idx + 1,
+                    imported_symbols: None,
+                });
+            }
+        }
+
+        Ok(imports)
+    }
+}
+
+/// The shell word at the start of `text`, with quotes removed
+fn word(text: &str) -> String {
+    let mut word = String::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => word.extend(chars.next()),
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '\\') => word.extend(chars.next()),
+            (None, '$') if chars.peek() == Some(&'(') => {
+                depth += 1;
+                word.push(c);
+            }
+            (None, ')') if depth > 0 => {
+                depth -= 1;
+                word.push(c);
+            }
+            (None, c) if depth == 0 && (c.is_whitespace() || matches!(c, ';' | '&' | '|' | ')' | '<' | '>')) => break,
+            (None, c) => word.push(c),
+        }
+    }
+    word
+}
+
+/// Path of a sourced file relative to the script (`./lib.sh`), as written (`lib/common.sh`), or absolute
+///
+/// None for paths that depend on other variables, the home directory or command output.
+fn sourced_path(word: &str) -> Option<String> {
+    static SCRIPT_DIR: OnceLock<Regex> = OnceLock::new();
+    let script_dir = SCRIPT_DIR.get_or_init(|| {
+        Regex::new(r#"^(?:\$\(\s*dirname\s+(?:\$0|\$\{0\}|\$\{?BASH_SOURCE(?:\[0\])?\}?|\$\{\(%\):-%x\})\s*\)|\$\{0%/\*\}|\$\{?\w*(?:DIR|dir|Dir)\}?)/"#)
+            .expect("invalid script directory pattern")
+    });
+
+    let path = match script_dir.find(word) {
+        Some(prefix) => format!("./{}", &word[prefix.end()..]),
+        None => word.to_string(),
+    };
+    if path.is_empty() || path == "./" || path.contains(['$', '`', '*', '?']) || path.starts_with('~') {
+        return None;
+    }
+    Some(path)
+}
+
+/// Resolve a sourced file to candidate paths relative to the index root
+///
+/// Scripts usually source files next to them or run from the repository root,
+/// so the script's directory is tried first, then the root
+/// (pipe-delimited, e.g. `scripts/lib.sh|lib.sh`).
+pub fn resolve_shell_source_to_path(sourced: &str, current_file_path: &str) -> Option<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    for candidate in [
+        crate::paths::join_relative(current_file_path, sourced),
+        crate::paths::join_relative("", sourced),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !candidate.is_empty() && !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+    }
+    (!candidates.is_empty()).then(|| candidates.join("|"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+source "$(dirname "$0")/lib/common.sh"
+. ./env.sh; [ -f "$HOME/.bashrc" ] && source "$HOME/.bashrc"
+
+alias ll='ls -l' la="ls -a"
+
+# Build the release binary
+build() {
+    local target="${1:-release}"  # not } a brace
+    if [[ ${#target} -gt 0 ]]; then
+        cargo build --"$target"
+    fi
+}
+
+function deploy {
+    cat <<-'EOF' >&2
+	}
+	EOF
+    echo "deploy() {"
+}
+
+function cleanup() ( rm -rf "$TMP" )
+
+check()
+{
+    echo ok
+}
+"#;
+
+    #[test]
+    fn test_parse_functions_and_aliases() {
+        let symbols = parse("scripts/release.sh", SCRIPT).unwrap();
+        let found: Vec<(&str, &SymbolKind, usize, usize)> = symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("ll", &SymbolKind::Alias, 6, 6),
+            ("la", &SymbolKind::Alias, 6, 6),
+            ("build", &SymbolKind::Function, 9, 14),
+            ("deploy", &SymbolKind::Function, 16, 21),
+            ("cleanup", &SymbolKind::Function, 23, 23),
+            ("check", &SymbolKind::Function, 25, 28),
+        ]);
+        assert!(symbols.iter().all(|s| s.lang == Language::Shell));
+    }
+
+    #[test]
+    fn test_extract_sourced_files() {
+        let deps = ShellDependencyExtractor::extract_dependencies(SCRIPT).unwrap();
+        let found: Vec<(&str, usize)> = deps.iter().map(|d| (d.imported_path.as_str(), d.line_number)).collect();
+
+        assert_eq!(found, vec![("./lib/common.sh", 3), ("./env.sh", 4)]);
+
+        let deps = ShellDependencyExtractor::extract_dependencies(
+            "source /etc/profile\nif true; then . \"${SCRIPT_DIR}/util.sh\"; fi\n",
+        ).unwrap();
+        assert_eq!(deps[0].imported_path, "/etc/profile");
+        assert!(matches!(deps[0].import_type, ImportType::External));
+        assert_eq!(deps[1].imported_path, "./util.sh");
+    }
+
+    #[test]
+    fn test_resolve_sourced_files() {
+        assert_eq!(
+            resolve_shell_source_to_path("./lib/common.sh", "scripts/release.sh").as_deref(),
+            Some("scripts/lib/common.sh|lib/common.sh"),
+        );
+        assert_eq!(resolve_shell_source_to_path("../env.sh", "scripts/release.sh").as_deref(), Some("env.sh"));
+        assert_eq!(resolve_shell_source_to_path("env.sh", "release.sh").as_deref(), Some("env.sh"));
+    }
+}
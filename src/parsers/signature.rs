@@ -0,0 +1,289 @@
+//! Signature extraction for parsed symbols
+//!
+//! Agents often only need a function's parameters and return type, or a
+//! struct's fields, not its whole body. After a language parser has produced
+//! symbols, this module derives a compact `signature` for each one from the
+//! source text covered by its span:
+//!
+//! - **Functions, methods, classes, traits**: the declaration up to (not
+//!   including) the body, e.g. `pub fn get<K: Hash>(&self, key: &K) -> Option<&V>`
+//! - **Structs, enums, interfaces, type aliases**: the whole declaration with
+//!   comments and attributes dropped, e.g. `struct Point { x: f64, y: f64 }`
+//!
+//! Whitespace is collapsed to single spaces and signatures longer than
+//! [`MAX_SIGNATURE_LENGTH`] are truncated with an ellipsis.
+
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+
+/// Maximum signature length in characters
+pub const MAX_SIGNATURE_LENGTH: usize = 300;
+
+/// How much of a declaration makes up its signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Everything before the body
+    Header,
+    /// The full declaration (field/variant/member list)
+    Fields,
+}
+
+fn mode_for(kind: &SymbolKind) -> Option<Mode> {
+    match kind {
+        SymbolKind::Function
+        | SymbolKind::Method
+        | SymbolKind::Class
+        | SymbolKind::Trait
+        | SymbolKind::Macro => Some(Mode::Header),
+        SymbolKind::Struct | SymbolKind::Enum | SymbolKind::Interface | SymbolKind::Type => Some(Mode::Fields),
+        _ => None,
+    }
+}
+
+/// Fill in `signature` for every symbol that supports one
+pub fn attach_signatures(symbols: &mut [SearchResult], source: &str, language: Language) {
+    if symbols.is_empty() {
+        return;
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    for symbol in symbols.iter_mut() {
+        if symbol.signature.is_none() {
+            symbol.signature = extract_signature(&lines, &symbol.span, &symbol.kind, language);
+        }
+    }
+}
+
+/// Extract the signature of a symbol spanning `span` within `lines`
+pub fn extract_signature(lines: &[&str], span: &Span, kind: &SymbolKind, language: Language) -> Option<String> {
+    let mode = mode_for(kind)?;
+
+    let start = span.start_line.checked_sub(1)?;
+    let end = span.end_line.min(lines.len());
+    if start >= end {
+        return None;
+    }
+
+    // Skip decorators, attributes and comments that some grammars include in the node
+    let first = (start..end).find(|&i| !is_preamble_line(lines[i].trim()))?;
+
+    let raw = match mode {
+        Mode::Header => scan_header(&lines[first..end].join("\n"), language),
+        Mode::Fields => lines[first..end]
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !is_comment_line(line) && !line.starts_with("#["))
+            .map(strip_trailing_comment)
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+
+    let signature = normalize(&raw);
+    if signature.is_empty() {
+        None
+    } else {
+        Some(truncate(signature))
+    }
+}
+
+/// Lines that precede a declaration without being part of its signature
+fn is_preamble_line(trimmed: &str) -> bool {
+    trimmed.is_empty()
+        || trimmed.starts_with('@')
+        || trimmed.starts_with("#[")
+        || (trimmed.starts_with('[') && trimmed.ends_with(']'))  // C# attributes
+        || is_comment_line(trimmed)
+}
+
+fn is_comment_line(trimmed: &str) -> bool {
+    trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*')
+}
+
+/// Drop a trailing `// comment` (only when preceded by whitespace, so URLs in strings survive)
+fn strip_trailing_comment(line: &str) -> &str {
+    match line.find(" //") {
+        Some(idx) => line[..idx].trim_end(),
+        None => line,
+    }
+}
+
+/// Scan a declaration up to the start of its body, respecting brackets and strings
+fn scan_header(text: &str, language: Language) -> String {
+    let tracks_generics = matches!(
+        language,
+        Language::Rust
+            | Language::TypeScript
+            | Language::Vue
+            | Language::Svelte
+            | Language::Java
+            | Language::CSharp
+            | Language::Cpp
+            | Language::Kotlin
+            | Language::Swift
+    );
+    // Rust lifetimes ('a) are not string delimiters
+    let single_quote_strings = language != Language::Rust;
+
+    let mut depth: usize = 0;
+    let mut in_string: Option<char> = None;
+    let mut escaped = false;
+    let mut prev = '\0';
+
+    for (idx, c) in text.char_indices() {
+        if let Some(quote) = in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                in_string = None;
+            }
+            prev = c;
+            continue;
+        }
+
+        match c {
+            '"' | '`' => in_string = Some(c),
+            '\'' if single_quote_strings => in_string = Some(c),
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            '<' if tracks_generics => depth += 1,
+            // `->` and `=>` are arrows, not closing angle brackets
+            '>' if tracks_generics && prev != '-' && prev != '=' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && is_body_start(c, prev, &text[idx..], language) => {
+                return text[..idx].to_string();
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+
+    text.to_string()
+}
+
+/// Whether `c` (at depth 0) starts the body of a declaration
+fn is_body_start(c: char, prev: char, rest: &str, language: Language) -> bool {
+    match language {
+        Language::Python => c == ':',
+        Language::Ruby => c == '\n' || c == ';',
+        // Kotlin expression bodies: `fun area() = w * h` (but not `==`)
+        Language::Kotlin if c == '=' => prev != '=' && prev != '!' && !rest.starts_with("==") && !rest.starts_with("=>"),
+        _ => c == '{' || c == ';',
+    }
+}
+
+/// Collapse whitespace and tidy spacing around brackets
+pub(crate) fn normalize(raw: &str) -> String {
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tidied = collapsed
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" ,", ",");
+
+    tidied
+        .trim_end_matches(|c: char| c == ';' || c.is_whitespace())
+        .to_string()
+}
+
+/// Cut a signature at [`MAX_SIGNATURE_LENGTH`] characters
+pub(crate) fn truncate(signature: String) -> String {
+    if signature.chars().count() <= MAX_SIGNATURE_LENGTH {
+        return signature;
+    }
+
+    let mut truncated: String = signature.chars().take(MAX_SIGNATURE_LENGTH).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sig(source: &str, start: usize, end: usize, kind: SymbolKind, language: Language) -> Option<String> {
+        let lines: Vec<&str> = source.lines().collect();
+        extract_signature(&lines, &Span { start_line: start, end_line: end }, &kind, language)
+    }
+
+    #[test]
+    fn test_rust_function_with_generics_and_where_clause() {
+        let source = "pub fn get<'a, K>(&'a self, key: &K) -> Option<&'a V>\nwhere\n    K: Hash,\n{\n    self.map.get(key)\n}\n";
+        assert_eq!(
+            sig(source, 1, 6, SymbolKind::Function, Language::Rust).unwrap(),
+            "pub fn get<'a, K>(&'a self, key: &K) -> Option<&'a V> where K: Hash,"
+        );
+    }
+
+    #[test]
+    fn test_rust_struct_fields_drop_comments_and_attributes() {
+        let source = "pub struct Point {\n    /// X coordinate\n    pub x: f64,\n    #[serde(default)]\n    pub y: f64, // vertical\n}\n";
+        assert_eq!(
+            sig(source, 1, 6, SymbolKind::Struct, Language::Rust).unwrap(),
+            "pub struct Point { pub x: f64, pub y: f64, }"
+        );
+    }
+
+    #[test]
+    fn test_python_def_stops_at_colon() {
+        let source = "def fetch(url: str, headers: Dict[str, str] = {}) -> Response:\n    return get(url)\n";
+        assert_eq!(
+            sig(source, 1, 2, SymbolKind::Function, Language::Python).unwrap(),
+            "def fetch(url: str, headers: Dict[str, str] = {}) -> Response"
+        );
+    }
+
+    #[test]
+    fn test_decorators_and_annotations_skipped() {
+        let source = "@Override\npublic String toString() {\n    return name;\n}\n";
+        assert_eq!(
+            sig(source, 1, 4, SymbolKind::Method, Language::Java).unwrap(),
+            "public String toString()"
+        );
+    }
+
+    #[test]
+    fn test_go_method_and_typescript_class_header() {
+        let go = "func (s *Server) Handle(w http.ResponseWriter, r *http.Request) error {\n\treturn nil\n}\n";
+        assert_eq!(
+            sig(go, 1, 3, SymbolKind::Method, Language::Go).unwrap(),
+            "func (s *Server) Handle(w http.ResponseWriter, r *http.Request) error"
+        );
+
+        let ts = "export class Store<T extends Item> extends Base implements Cache {\n  items: T[] = [];\n}\n";
+        assert_eq!(
+            sig(ts, 1, 3, SymbolKind::Class, Language::TypeScript).unwrap(),
+            "export class Store<T extends Item> extends Base implements Cache"
+        );
+
+        let arrow = "const handler = (req: Request): Promise<void> => {\n  return done();\n};\n";
+        assert_eq!(
+            sig(arrow, 1, 3, SymbolKind::Function, Language::TypeScript).unwrap(),
+            "const handler = (req: Request): Promise<void> =>"
+        );
+    }
+
+    #[test]
+    fn test_kotlin_expression_body_and_ruby_def() {
+        let kotlin = "fun area(w: Int = 1, h: Int): Int = w * h\n";
+        assert_eq!(
+            sig(kotlin, 1, 1, SymbolKind::Function, Language::Kotlin).unwrap(),
+            "fun area(w: Int = 1, h: Int): Int"
+        );
+
+        let ruby = "def greet(name, greeting = \"hi\")\n  puts greeting\nend\n";
+        assert_eq!(
+            sig(ruby, 1, 3, SymbolKind::Method, Language::Ruby).unwrap(),
+            "def greet(name, greeting = \"hi\")"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_kinds_and_truncation() {
+        assert!(sig("let x = 1;\n", 1, 1, SymbolKind::Variable, Language::Rust).is_none());
+
+        let fields: String = (0..100).map(|i| format!("    field_{}: u32,\n", i)).collect();
+        let source = format!("struct Wide {{\n{}}}\n", fields);
+        let signature = sig(&source, 1, 102, SymbolKind::Struct, Language::Rust).unwrap();
+        assert_eq!(signature.chars().count(), MAX_SIGNATURE_LENGTH + 1);
+        assert!(signature.ends_with('…'));
+    }
+}
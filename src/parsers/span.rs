@@ -0,0 +1,178 @@
+//! Span policies for parsed symbols
+//!
+//! Parsers report the span of the definition itself, starting at the line with
+//! the declaration. Agents editing a symbol usually also need what sits on top
+//! of it, so queries can widen spans according to a [`SpanPolicy`]:
+//!
+//! - **body** (default): the definition as reported by the parser
+//! - **with-attrs**: plus attributes, annotations and decorators
+//!   (`#[derive(...)]`, `@Override`, `@app.route(...)`, `[Serializable]`)
+//! - **with-docs**: plus attributes and the doc comment above them
+//!
+//! Widening works on source lines with the same per-language rules as doc
+//! extraction (see [`super::docs`]), so it applies to every parser alike.
+//! Python docstrings already sit inside the body.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::docs::{doc_comment_start, is_attribute_line};
+use crate::models::{Language, Span};
+
+/// How far a symbol's span reaches above its declaration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpanPolicy {
+    /// The definition only (parser span)
+    #[default]
+    Body,
+    /// Include preceding attributes, annotations and decorators
+    WithAttrs,
+    /// Include preceding attributes and doc comments
+    WithDocs,
+}
+
+impl FromStr for SpanPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "body" => Ok(SpanPolicy::Body),
+            "with-attrs" | "attrs" => Ok(SpanPolicy::WithAttrs),
+            "with-docs" | "docs" => Ok(SpanPolicy::WithDocs),
+            _ => anyhow::bail!(
+                "Unknown span policy '{}'.\n\
+                 \n\
+                 Valid policies: body, with-attrs, with-docs",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SpanPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SpanPolicy::Body => "body",
+            SpanPolicy::WithAttrs => "with-attrs",
+            SpanPolicy::WithDocs => "with-docs",
+        })
+    }
+}
+
+/// Maximum number of lines a single multi-line attribute may span
+const MAX_ATTRIBUTE_LINES: usize = 20;
+
+/// Widen `span` within `lines` according to `policy`
+///
+/// The end line is never changed, and a span is only widened over lines that
+/// directly precede it (a blank line stops the search).
+pub fn apply_span_policy(lines: &[&str], span: &Span, language: Language, policy: SpanPolicy) -> Span {
+    if policy == SpanPolicy::Body || span.start_line == 0 {
+        return span.clone();
+    }
+
+    let mut start = (span.start_line - 1).min(lines.len());
+    loop {
+        let mut next = start;
+        while let Some(attr) = attribute_start(&lines[..next], language) {
+            next = attr;
+        }
+        if policy == SpanPolicy::WithDocs
+            && let Some(doc) = doc_comment_start(&lines[..next], language)
+        {
+            next = doc;
+        }
+
+        // Attributes can also sit above a doc comment (e.g. `@Deprecated /** ... */`)
+        if next == start {
+            break;
+        }
+        start = next;
+    }
+
+    Span {
+        start_line: start + 1,
+        end_line: span.end_line,
+    }
+}
+
+/// First line of the attribute ending right above `lines.len()`
+///
+/// Handles attributes spread over several lines by balancing brackets
+/// upwards until the line that opens the attribute.
+fn attribute_start(lines: &[&str], language: Language) -> Option<usize> {
+    let mut depth: i32 = 0;
+
+    for i in (0..lines.len()).rev().take(MAX_ATTRIBUTE_LINES) {
+        let trimmed = lines[i].trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        for c in trimmed.chars() {
+            match c {
+                ')' | ']' | '}' => depth += 1,
+                '(' | '[' | '{' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        if depth <= 0 {
+            return is_attribute_line(trimmed, language).then_some(i);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widen(source: &str, start: usize, end: usize, language: Language, policy: SpanPolicy) -> (usize, usize) {
+        let lines: Vec<&str> = source.lines().collect();
+        let span = apply_span_policy(&lines, &Span { start_line: start, end_line: end }, language, policy);
+        (span.start_line, span.end_line)
+    }
+
+    #[test]
+    fn test_rust_attributes_and_docs() {
+        let source = "use std::fmt;\n\n/// A point.\n#[derive(Debug,\n         Clone)]\n#[serde(default)]\npub struct Point {\n    x: i32,\n}\n";
+        assert_eq!(widen(source, 7, 9, Language::Rust, SpanPolicy::Body), (7, 9));
+        assert_eq!(widen(source, 7, 9, Language::Rust, SpanPolicy::WithAttrs), (4, 9));
+        assert_eq!(widen(source, 7, 9, Language::Rust, SpanPolicy::WithDocs), (3, 9));
+    }
+
+    #[test]
+    fn test_python_multiline_decorator() {
+        let source = "import x\n\n@app.route(\n    \"/users\",\n    methods=[\"GET\"],\n)\n@login_required\ndef users():\n    \"\"\"List users.\"\"\"\n    return []\n";
+        assert_eq!(widen(source, 8, 10, Language::Python, SpanPolicy::WithAttrs), (3, 10));
+        // Docstrings are part of the body already
+        assert_eq!(widen(source, 8, 10, Language::Python, SpanPolicy::WithDocs), (3, 10));
+    }
+
+    #[test]
+    fn test_java_annotations_around_javadoc() {
+        let source = "class A {\n    @Deprecated\n    /**\n     * Old name.\n     */\n    @Override\n    public String getName() {\n        return name;\n    }\n}\n";
+        assert_eq!(widen(source, 7, 9, Language::Java, SpanPolicy::WithAttrs), (6, 9));
+        assert_eq!(widen(source, 7, 9, Language::Java, SpanPolicy::WithDocs), (2, 9));
+    }
+
+    #[test]
+    fn test_blank_line_and_code_stop_widening() {
+        let source = "/// Detached\n\nfn detached() {}\nlet x = foo(\n    1)\nfn after_call() {}\n";
+        assert_eq!(widen(source, 3, 3, Language::Rust, SpanPolicy::WithDocs), (3, 3));
+        assert_eq!(widen(source, 6, 6, Language::Rust, SpanPolicy::WithDocs), (6, 6));
+    }
+
+    #[test]
+    fn test_parse_span_policy() {
+        assert_eq!("with-attrs".parse::<SpanPolicy>().unwrap(), SpanPolicy::WithAttrs);
+        assert_eq!("WITH_DOCS".parse::<SpanPolicy>().unwrap(), SpanPolicy::WithDocs);
+        assert_eq!(SpanPolicy::WithDocs.to_string(), "with-docs");
+        assert!("everything".parse::<SpanPolicy>().is_err());
+    }
+}
@@ -0,0 +1,653 @@
+//! SQL parser
+//!
+//! Extracts symbols from SQL files (`*.sql`, `*.pgsql`, `*.psql`, `*.ddl`), such
+//! as schema dumps and migration directories:
+//! - Tables and views (`CREATE TABLE`, `CREATE [MATERIALIZED] VIEW`, kind: table)
+//! - Functions and procedures (`CREATE [OR REPLACE] FUNCTION`, `CREATE PROCEDURE`)
+//! - Types and domains (`CREATE TYPE`, `CREATE DOMAIN`)
+//! - Schemas (`CREATE SCHEMA`, kind: namespace)
+//!
+//! Each symbol spans its whole statement and is named without its schema
+//! (`public.users` is `users`). Statements end at `;` outside strings, comments
+//! and routine bodies. How a file is tokenized depends on the SQL dialect
+//! (see [`SqlDialect`]), set for the project in `.reflex/config.toml`:
+//!
+//! ```toml
+//! [sql]
+//! dialect = "postgres"  # auto, postgres, mysql, sqlite, tsql
+//! ```
+//!
+//! Note: This parser is statement-based since no tree-sitter grammar for SQL
+//! is bundled.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+use crate::cache::CONFIG_TOML;
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+use crate::parsers::signature;
+
+/// Dialect used for SQL files, installed from `[sql]` in config.toml
+///
+/// Process-wide like the extension overrides (see [`install_dialect`]), since
+/// symbols are parsed wherever a query or the background indexer needs them.
+static DIALECT: RwLock<SqlDialect> = RwLock::new(SqlDialect::Auto);
+
+/// SQL dialect of a project's `.sql` files
+///
+/// Dialects differ in how identifiers are quoted, which comments and string
+/// escapes exist, and how routine bodies are delimited. `auto` accepts the
+/// syntax of all of them, which only goes wrong where they conflict: `#` is
+/// not a comment, `"..."` is an identifier rather than a MySQL string, and
+/// backslashes don't escape quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlDialect {
+    /// Any of the dialects below
+    #[default]
+    Auto,
+    /// PostgreSQL: `$$`-quoted bodies, `"quoted"` identifiers
+    #[serde(alias = "postgresql", alias = "pg")]
+    Postgres,
+    /// MySQL and MariaDB: `DELIMITER` commands, `` `quoted` `` identifiers,
+    /// `#` comments, `"strings"` and backslash escapes
+    #[serde(alias = "mariadb")]
+    Mysql,
+    /// SQLite: `"quoted"`, `` `quoted` `` and `[quoted]` identifiers
+    Sqlite,
+    /// SQL Server: `GO` batch separators, `[quoted]` identifiers
+    #[serde(alias = "mssql", alias = "sqlserver")]
+    Tsql,
+}
+
+impl SqlDialect {
+    /// Name used in config.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            SqlDialect::Auto => "auto",
+            SqlDialect::Postgres => "postgres",
+            SqlDialect::Mysql => "mysql",
+            SqlDialect::Sqlite => "sqlite",
+            SqlDialect::Tsql => "tsql",
+        }
+    }
+
+    fn dollar_quotes(self) -> bool {
+        matches!(self, SqlDialect::Auto | SqlDialect::Postgres)
+    }
+
+    fn delimiter_command(self) -> bool {
+        matches!(self, SqlDialect::Auto | SqlDialect::Mysql)
+    }
+
+    fn batch_separator(self) -> bool {
+        matches!(self, SqlDialect::Auto | SqlDialect::Tsql)
+    }
+
+    fn backtick_identifiers(self) -> bool {
+        matches!(self, SqlDialect::Auto | SqlDialect::Mysql | SqlDialect::Sqlite)
+    }
+
+    fn bracket_identifiers(self) -> bool {
+        matches!(self, SqlDialect::Auto | SqlDialect::Sqlite | SqlDialect::Tsql)
+    }
+
+    fn mysql(self) -> bool {
+        self == SqlDialect::Mysql
+    }
+}
+
+/// `[sql]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SqlConfig {
+    /// Dialect of the project's SQL files
+    #[serde(default)]
+    pub dialect: SqlDialect,
+}
+
+/// Load the `[sql]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to the `auto` dialect if the file or section is missing.
+pub fn load_sql_config(cache_path: &Path) -> Result<SqlConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(SqlConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("sql") {
+        Some(section) => section.clone().try_into()
+            .context("Failed to parse [sql] section in .reflex/config.toml (dialect must be auto, postgres, mysql, sqlite or tsql)"),
+        None => Ok(SqlConfig::default()),
+    }
+}
+
+/// Install the project's SQL dialect for this process
+///
+/// An unreadable section is ignored with a warning (keeping `auto`) rather
+/// than failing the caller.
+pub fn install_dialect(cache_path: &Path) {
+    let dialect = load_sql_config(cache_path)
+        .map(|config| config.dialect)
+        .unwrap_or_else(|e| {
+            log::warn!("Ignoring [sql]: {:#}", e);
+            SqlDialect::Auto
+        });
+    set_dialect(dialect);
+}
+
+/// Set the dialect used to parse SQL files for the rest of the process
+pub fn set_dialect(dialect: SqlDialect) {
+    if let Ok(mut current) = DIALECT.write() {
+        *current = dialect;
+    }
+}
+
+/// Dialect used to parse SQL files
+pub fn dialect() -> SqlDialect {
+    DIALECT.read().map(|dialect| *dialect).unwrap_or_default()
+}
+
+/// Parser version for the installed dialect
+///
+/// Symbols depend on the dialect, so symbols cached under another dialect are
+/// re-extracted. `auto` keeps the plain parser version.
+pub fn versioned(version: &'static str) -> &'static str {
+    static VERSIONS: [OnceLock<String>; 5] = [const { OnceLock::new() }; 5];
+
+    let dialect = dialect();
+    if dialect == SqlDialect::Auto || version.is_empty() {
+        return version;
+    }
+    VERSIONS[dialect as usize].get_or_init(|| format!("{}-{}", version, dialect.name()))
+}
+
+/// A statement, without its comments
+#[derive(Default)]
+struct Statement {
+    /// First line (0-indexed)
+    start: usize,
+    /// Last line (0-indexed)
+    end: usize,
+    /// Source text with comments blanked
+    text: String,
+    /// `text` with the contents of strings and quoted bodies blanked too (same byte offsets)
+    code: String,
+    /// `GO`-separated batch the statement belongs to
+    batch: usize,
+}
+
+/// Collects statements while scanning a file
+#[derive(Default)]
+struct Splitter {
+    statements: Vec<Statement>,
+    current: Statement,
+    /// Whether the current statement has code yet
+    started: bool,
+    batch: usize,
+}
+
+impl Splitter {
+    /// Add source text at `line`, keeping it in the code unless `blank`
+    fn push(&mut self, line: usize, s: &str, blank: bool) {
+        if !self.started && !s.trim().is_empty() {
+            self.started = true;
+            self.current.start = line;
+            self.current.batch = self.batch;
+        }
+        if !s.trim().is_empty() {
+            self.current.end = line;
+        }
+        if self.started {
+            self.current.text.push_str(s);
+            if blank {
+                self.current.code.extend(std::iter::repeat_n(' ', s.len()));
+            } else {
+                self.current.code.push_str(s);
+            }
+        }
+    }
+
+    /// Blank a comment
+    fn comment(&mut self, s: &str) {
+        if self.started {
+            let spaces = " ".repeat(s.len());
+            self.current.text.push_str(&spaces);
+            self.current.code.push_str(&spaces);
+        }
+    }
+
+    fn newline(&mut self) {
+        self.push(0, "\n", false);
+    }
+
+    fn finish(&mut self) {
+        if self.started {
+            self.statements.push(std::mem::take(&mut self.current));
+            self.started = false;
+        }
+    }
+}
+
+/// A quoted string, identifier or body being scanned
+enum Quote {
+    /// `'...'`
+    Single,
+    /// `"..."`: an identifier, or a string in MySQL
+    Double,
+    /// `` `...` ``
+    Backtick,
+    /// `[...]`
+    Bracket,
+    /// `$tag$...$tag$`
+    Dollar(String),
+}
+
+/// Split a file into statements
+fn statements(lines: &[&str], dialect: SqlDialect) -> Vec<Statement> {
+    static DOLLAR: OnceLock<Regex> = OnceLock::new();
+    let dollar = DOLLAR.get_or_init(|| Regex::new(r"^\$(?:[A-Za-z_]\w*)?\$").expect("invalid dollar quote pattern"));
+
+    let mut splitter = Splitter::default();
+    let mut quote: Option<Quote> = None;
+    let mut block_comment = false;
+    let mut delimiter = ";".to_string();
+
+    for (idx, line) in lines.iter().enumerate() {
+        if quote.is_none() && !block_comment {
+            let mut words = line.split_whitespace();
+            let command = words.next().unwrap_or("");
+            // `DELIMITER //` (MySQL client command)
+            if dialect.delimiter_command() && command.eq_ignore_ascii_case("DELIMITER") {
+                splitter.finish();
+                if let Some(new_delimiter) = words.next() {
+                    delimiter = new_delimiter.to_string();
+                }
+                continue;
+            }
+            // `GO` or `GO 5` (SQL Server batch separator)
+            if dialect.batch_separator() && command.eq_ignore_ascii_case("GO") && words.all(|w| w.parse::<u32>().is_ok()) {
+                splitter.finish();
+                splitter.batch += 1;
+                continue;
+            }
+        }
+
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or(' ');
+            let char_str = &rest[..c.len_utf8()];
+
+            if block_comment {
+                let len = if rest.starts_with("*/") { 2 } else { c.len_utf8() };
+                block_comment = len != 2;
+                splitter.comment(&rest[..len]);
+                i += len;
+                continue;
+            }
+
+            if let Some(q) = &quote {
+                // Doubled quotes and backslash escapes stay inside the string
+                let escape_len = match q {
+                    Quote::Single if rest.starts_with("''") => 2,
+                    Quote::Double if rest.starts_with("\"\"") => 2,
+                    Quote::Single | Quote::Double if dialect.mysql() && c == '\\' => {
+                        1 + rest[1..].chars().next().map_or(0, char::len_utf8)
+                    }
+                    _ => 0,
+                };
+                let closing = match q {
+                    Quote::Single => "'",
+                    Quote::Double => "\"",
+                    Quote::Backtick => "`",
+                    Quote::Bracket => "]",
+                    Quote::Dollar(tag) => tag.as_str(),
+                };
+                // Identifiers stay visible in the code, literals and bodies are blanked
+                let blank = match q {
+                    Quote::Single | Quote::Dollar(_) => true,
+                    Quote::Double => dialect.mysql(),
+                    Quote::Backtick | Quote::Bracket => false,
+                };
+
+                if escape_len > 0 {
+                    splitter.push(idx, &rest[..escape_len], blank);
+                    i += escape_len;
+                } else if rest.starts_with(closing) {
+                    let len = closing.len();
+                    splitter.push(idx, &rest[..len], false);
+                    quote = None;
+                    i += len;
+                } else {
+                    splitter.push(idx, char_str, blank);
+                    i += char_str.len();
+                }
+                continue;
+            }
+
+            if rest.starts_with(delimiter.as_str()) {
+                if delimiter == ";" {
+                    splitter.push(idx, ";", false);
+                }
+                splitter.finish();
+                i += delimiter.len();
+                continue;
+            }
+
+            if rest.starts_with("--") || (dialect.mysql() && c == '#') {
+                splitter.comment(rest);
+                break;
+            }
+            if rest.starts_with("/*") {
+                block_comment = true;
+                splitter.comment("/*");
+                i += 2;
+                continue;
+            }
+
+            let opened = match c {
+                '\'' => Some(Quote::Single),
+                '"' => Some(Quote::Double),
+                '`' if dialect.backtick_identifiers() => Some(Quote::Backtick),
+                '[' if dialect.bracket_identifiers() => Some(Quote::Bracket),
+                '$' if dialect.dollar_quotes() => dollar.find(rest).map(|tag| Quote::Dollar(tag.as_str().to_string())),
+                _ => None,
+            };
+            let len = match &opened {
+                Some(Quote::Dollar(tag)) => tag.len(),
+                _ => char_str.len(),
+            };
+            splitter.push(idx, &rest[..len], false);
+            if opened.is_some() {
+                quote = opened;
+            }
+            i += len;
+        }
+        splitter.newline();
+    }
+    splitter.finish();
+
+    merge_routine_bodies(splitter.statements)
+}
+
+/// Rejoin routines whose bodies were split at the `;` of their inner statements
+///
+/// A routine continues while a `BEGIN` or `CASE` of its body is still open and,
+/// in files with `GO` separators, up to the end of its batch (SQL Server
+/// routines take the whole batch).
+fn merge_routine_bodies(statements: Vec<Statement>) -> Vec<Statement> {
+    static ROUTINE: OnceLock<Regex> = OnceLock::new();
+    let routine = ROUTINE.get_or_init(|| {
+        Regex::new(r"(?is)^CREATE\s+(?:OR\s+(?:REPLACE|ALTER)\s+)?(?:(?:DEFINER\s*=\s*\S+|AGGREGATE|CONSTRAINT)\s+)*(?:FUNCTION|PROCEDURE|PROC|TRIGGER)\b")
+            .expect("invalid SQL routine pattern")
+    });
+
+    let batches = statements.iter().any(|s| s.batch > 0);
+    let mut merged: Vec<Statement> = Vec::with_capacity(statements.len());
+    let mut statements = statements.into_iter().peekable();
+    while let Some(mut statement) = statements.next() {
+        if routine.is_match(statement.code.trim_start()) {
+            while let Some(next) = statements.next_if(|next| {
+                open_blocks(&statement.code) > 0 || (batches && next.batch == statement.batch)
+            }) {
+                statement.text.push_str(&next.text);
+                statement.code.push_str(&next.code);
+                statement.end = next.end;
+            }
+        }
+        merged.push(statement);
+    }
+    merged
+}
+
+/// `BEGIN` and `CASE` blocks left open in `code`
+fn open_blocks(code: &str) -> isize {
+    static BLOCK: OnceLock<Regex> = OnceLock::new();
+    let block = BLOCK.get_or_init(|| {
+        Regex::new(r"(?i)\b(BEGIN|CASE|END)\b[ \t]*(\w+|;)?").expect("invalid SQL block pattern")
+    });
+
+    block.captures_iter(code).fold(0, |depth, captures| {
+        let next = captures.get(2).map_or(String::new(), |m| m.as_str().to_ascii_uppercase());
+        match captures[1].to_ascii_uppercase().as_str() {
+            // `BEGIN;` and `BEGIN TRANSACTION` start transactions
+            "BEGIN" if next == ";" || matches!(next.as_str(), "TRANSACTION" | "TRAN" | "WORK" | "DEFERRED" | "IMMEDIATE" | "EXCLUSIVE") => depth,
+            "BEGIN" | "CASE" => depth + 1,
+            // `END IF`, `END LOOP` ... close blocks that aren't counted
+            _ if matches!(next.as_str(), "IF" | "LOOP" | "WHILE" | "REPEAT" | "FOR") => depth,
+            _ => depth - 1,
+        }
+    })
+}
+
+/// Parse a SQL file with the installed dialect
+pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
+    parse_with_dialect(path, source, dialect())
+}
+
+/// Parse a SQL file and extract tables, views, routines, types and schemas
+pub fn parse_with_dialect(path: &str, source: &str, dialect: SqlDialect) -> Result<Vec<SearchResult>> {
+    const IDENT: &str = r#""[^"]*"|`[^`]*`|\[[^\]]*\]|[\w$#@]+"#;
+    static CREATE: OnceLock<Regex> = OnceLock::new();
+    static BODY: OnceLock<Regex> = OnceLock::new();
+    let create = CREATE.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?is)^CREATE\s+(?:OR\s+(?:REPLACE|ALTER)\s+)?(?:(?:GLOBAL|LOCAL|TEMP|TEMPORARY|UNLOGGED|MATERIALIZED|RECURSIVE|VIRTUAL|AGGREGATE|DEFINER\s*=\s*\S+|ALGORITHM\s*=\s*\w+|SQL\s+SECURITY\s+\w+)\s+)*(TABLE|VIEW|FUNCTION|PROCEDURE|PROC|TYPE|DOMAIN|SCHEMA)\s+(?:IF\s+NOT\s+EXISTS\s+)?((?:(?:{IDENT})\s*\.\s*)*(?:{IDENT}))"
+        ))
+        .expect("invalid SQL CREATE pattern")
+    });
+    // Where a routine's body (or a view's query) starts
+    let body = BODY.get_or_init(|| {
+        Regex::new(r"(?i)\s(?:AS|IS|BEGIN|LANGUAGE|RETURN|IMMUTABLE|STABLE|VOLATILE|DETERMINISTIC)\b|\$").expect("invalid SQL body pattern")
+    });
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut symbols = Vec::new();
+
+    for statement in statements(&lines, dialect) {
+        let offset = statement.code.len() - statement.code.trim_start().len();
+        let Some(captures) = create.captures(&statement.code[offset..]) else {
+            continue;
+        };
+
+        let kind = match captures[1].to_ascii_uppercase().as_str() {
+            "TABLE" | "VIEW" => SymbolKind::Table,
+            "FUNCTION" | "PROCEDURE" | "PROC" => SymbolKind::Function,
+            "TYPE" | "DOMAIN" => SymbolKind::Type,
+            _ => SymbolKind::Namespace,
+        };
+        let qualified = &captures[2];
+        let name = unquote(last_component(qualified));
+        // `CREATE SCHEMA AUTHORIZATION role` names no schema
+        if name.trim().is_empty() || name.eq_ignore_ascii_case("AUTHORIZATION") {
+            continue;
+        }
+
+        // Routines and views stop before their body, tables and types keep their definition
+        let header_end = match captures[1].to_ascii_uppercase().as_str() {
+            "SCHEMA" => None,
+            "TABLE" | "TYPE" | "DOMAIN" => Some(statement.text.len()),
+            _ => {
+                let after_name = offset + captures.get(2).map_or(0, |m| m.end());
+                Some(body.find_at(&statement.code, after_name).map_or(statement.text.len(), |m| m.start()))
+            }
+        };
+        let signature = header_end
+            .map(|end| signature::normalize(&statement.text[..end]))
+            .filter(|signature| !signature.is_empty())
+            .map(signature::truncate);
+
+        let mut symbol = SearchResult::new(
+            path.to_string(),
+            Language::Sql,
+            kind,
+            Some(name),
+            Span::new(statement.start + 1, 0, statement.end + 1, 0),
+            None,
+            lines[statement.start..(statement.start + 7).min(lines.len())].join("\n"),
+        );
+        symbol.signature = signature;
+        symbols.push(symbol);
+    }
+
+    Ok(symbols)
+}
+
+/// Last dot-separated part of a qualified name, ignoring dots inside quotes
+fn last_component(qualified: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in qualified.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, '.') => start = i + 1,
+            _ => {}
+        }
+    }
+    qualified[start..].trim()
+}
+
+/// Identifier without its quotes
+fn unquote(ident: &str) -> String {
+    let unquoted = ident
+        .strip_prefix('"').and_then(|s| s.strip_suffix('"'))
+        .or_else(|| ident.strip_prefix('`').and_then(|s| s.strip_suffix('`')))
+        .or_else(|| ident.strip_prefix('[').and_then(|s| s.strip_suffix(']')));
+    match unquoted {
+        Some(name) => name.replace("\"\"", "\""),
+        None => ident.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn found(symbols: &[SearchResult]) -> Vec<(&str, &SymbolKind, usize, usize)> {
+        symbols
+            .iter()
+            .map(|s| (s.symbol.as_deref().unwrap(), &s.kind, s.span.start_line, s.span.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_postgres_schema() {
+        let source = r#"-- Accounts
+CREATE TABLE IF NOT EXISTS public.users (
+    id serial PRIMARY KEY,
+    email text NOT NULL DEFAULT 'none; really' -- not the end;
+);
+
+CREATE OR REPLACE FUNCTION "touch_updated_at"(row_id int) RETURNS trigger AS $body$
+BEGIN
+    UPDATE users SET email = 'x'; /* ; */
+    RETURN NEW;
+END;
+$body$ LANGUAGE plpgsql;
+
+CREATE MATERIALIZED VIEW active_users AS SELECT * FROM users;
+CREATE TYPE mood AS ENUM ('sad', 'ok');
+CREATE SCHEMA billing;
+CREATE INDEX users_email ON users (email);
+"#;
+        let symbols = parse_with_dialect("db/schema.sql", source, SqlDialect::Postgres).unwrap();
+
+        assert_eq!(found(&symbols), vec![
+            ("users", &SymbolKind::Table, 2, 5),
+            ("touch_updated_at", &SymbolKind::Function, 7, 12),
+            ("active_users", &SymbolKind::Table, 14, 14),
+            ("mood", &SymbolKind::Type, 15, 15),
+            ("billing", &SymbolKind::Namespace, 16, 16),
+        ]);
+        assert!(symbols.iter().all(|s| s.lang == Language::Sql));
+        assert_eq!(
+            symbols[0].signature.as_deref(),
+            Some("CREATE TABLE IF NOT EXISTS public.users (id serial PRIMARY KEY, email text NOT NULL DEFAULT 'none; really')")
+        );
+        assert_eq!(
+            symbols[1].signature.as_deref(),
+            Some(r#"CREATE OR REPLACE FUNCTION "touch_updated_at"(row_id int) RETURNS trigger"#)
+        );
+        assert_eq!(symbols[2].signature.as_deref(), Some("CREATE MATERIALIZED VIEW active_users"));
+    }
+
+    #[test]
+    fn test_parse_mysql_routines() {
+        let source = "\
+# Reporting
+CREATE TABLE `order items` (id INT, note VARCHAR(20) DEFAULT \"it's; fine\");
+
+DELIMITER //
+CREATE DEFINER=`root`@`%` PROCEDURE totals(IN since DATE)
+BEGIN
+    SELECT COUNT(*) FROM orders WHERE created_at > since;
+END //
+DELIMITER ;
+
+CREATE FUNCTION double_it(x INT) RETURNS INT DETERMINISTIC
+BEGIN
+    DECLARE y INT;
+    IF x > 0 THEN SET y = x * 2; END IF;
+    RETURN y;
+END;
+";
+        let symbols = parse_with_dialect("db/report.sql", source, SqlDialect::Mysql).unwrap();
+
+        assert_eq!(found(&symbols), vec![
+            ("order items", &SymbolKind::Table, 2, 2),
+            ("totals", &SymbolKind::Function, 5, 8),
+            ("double_it", &SymbolKind::Function, 11, 16),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_tsql_batches() {
+        let source = "\
+CREATE TABLE [dbo].[Users] ([Id] INT);
+GO
+CREATE OR ALTER PROCEDURE dbo.GetUsers
+AS
+    SELECT * FROM [dbo].[Users];
+    SELECT 1;
+GO
+CREATE VIEW dbo.Names AS SELECT Id FROM dbo.Users
+GO
+";
+        let symbols = parse_with_dialect("db/init.sql", source, SqlDialect::Tsql).unwrap();
+
+        assert_eq!(found(&symbols), vec![
+            ("Users", &SymbolKind::Table, 1, 1),
+            ("GetUsers", &SymbolKind::Function, 3, 6),
+            ("Names", &SymbolKind::Table, 8, 8),
+        ]);
+
+        // The same file parses in auto mode
+        let auto = parse_with_dialect("db/init.sql", source, SqlDialect::Auto).unwrap();
+        assert_eq!(found(&auto), found(&symbols));
+    }
+
+    #[test]
+    fn test_load_sql_config() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(load_sql_config(temp.path()).unwrap().dialect, SqlDialect::Auto);
+
+        std::fs::write(temp.path().join(CONFIG_TOML), "[sql]\ndialect = \"mssql\"\n").unwrap();
+        assert_eq!(load_sql_config(temp.path()).unwrap().dialect, SqlDialect::Tsql);
+
+        std::fs::write(temp.path().join(CONFIG_TOML), "[sql]\ndialect = \"oracle\"\n").unwrap();
+        assert!(load_sql_config(temp.path()).is_err());
+    }
+}
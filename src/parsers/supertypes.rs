@@ -0,0 +1,499 @@
+//! Inheritance and implementation relationships between types
+//!
+//! Finds the `extends` / `implements` edges declared in a source file: class
+//! base lists, interface and trait supertypes, and Rust `impl Trait for Type`
+//! blocks. Like signatures, relationships are read from declaration headers
+//! (the text up to the body), so they work for every language the same way
+//! and don't depend on grammar details:
+//!
+//! | Language | Declarations |
+//! | --- | --- |
+//! | Rust | `impl Trait for Type`, `trait A: B + C` |
+//! | TypeScript, JavaScript, Java, PHP | `class A extends B implements C`, `interface A extends B` |
+//! | Python | `class A(B, C):` |
+//! | Ruby | `class A < B` |
+//! | C#, Kotlin, Swift, C++ | `class A : B, C` |
+//!
+//! Base lists that don't say which edge is which (`class A : B, C`) are
+//! classified by convention: C# `IName` interfaces, Kotlin supertypes without a
+//! constructor call and Swift protocols after the first entry are `implements`.
+//! Supertype names keep their qualifier (`models.Base`) but drop generic
+//! arguments (`Repository<User>` becomes `Repository`).
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::models::Language;
+
+/// Maximum number of lines a declaration header may span
+const MAX_HEADER_LINES: usize = 10;
+
+/// How a type relates to its supertype
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationKind {
+    /// Subclassing, or an interface/trait extending another
+    Extends,
+    /// Implementing an interface, trait or protocol
+    Implements,
+}
+
+impl std::fmt::Display for RelationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationKind::Extends => write!(f, "extends"),
+            RelationKind::Implements => write!(f, "implements"),
+        }
+    }
+}
+
+/// One declared supertype edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRelation {
+    pub subtype: String,
+    pub supertype: String,
+    pub kind: RelationKind,
+    /// Line of the declaration (1-indexed)
+    pub line: usize,
+}
+
+/// Find the supertype edges declared in `source`
+pub fn extract_relations(source: &str, language: Language) -> Vec<TypeRelation> {
+    let Some(decl) = declaration_regex(language) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut relations = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(caps) = decl.captures(line) else {
+            continue;
+        };
+        let keyword = caps.get(1).map_or("", |m| m.as_str());
+        let rest_start = caps.get(0).map_or(0, |m| m.end());
+        let header = header_text(&lines, idx, rest_start, language);
+
+        for (subtype, supertype, kind) in parse_header(keyword, &header, language) {
+            // Anonymous classes (`export default class extends Base`) and `class << self`
+            if !is_type_name(&subtype) || !is_type_name(&supertype) {
+                continue;
+            }
+            relations.push(TypeRelation {
+                subtype,
+                supertype,
+                kind,
+                line: idx + 1,
+            });
+        }
+    }
+    relations
+}
+
+/// Last segment of a possibly qualified type name (`models.Base` -> `Base`)
+pub fn simple_name(name: &str) -> &str {
+    name.rsplit(['.', ':', '\\']).next().unwrap_or(name)
+}
+
+fn is_type_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '\\')
+        && !matches!(name, "extends" | "implements")
+}
+
+/// Regex matching the start of a declaration; group 1 is the keyword
+fn declaration_regex(language: Language) -> Option<&'static Regex> {
+    static RUST: OnceLock<Regex> = OnceLock::new();
+    static CLASS_LIKE: OnceLock<Regex> = OnceLock::new();
+    static PYTHON: OnceLock<Regex> = OnceLock::new();
+    static RUBY: OnceLock<Regex> = OnceLock::new();
+    static CPP: OnceLock<Regex> = OnceLock::new();
+
+    const MODIFIERS: &str = r"(?:(?:export|default|public|private|protected|internal|abstract|final|sealed|static|open|data|partial|readonly|declare|inner|fileprivate|strictfp|non-sealed)\s+)*";
+
+    let regex = match language {
+        Language::Rust => RUST.get_or_init(|| {
+            Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?(impl|trait)\b").unwrap()
+        }),
+        Language::Python => PYTHON.get_or_init(|| Regex::new(r"^\s*(class)\s").unwrap()),
+        Language::Ruby => RUBY.get_or_init(|| Regex::new(r"^\s*(class)\s").unwrap()),
+        Language::Cpp => CPP.get_or_init(|| {
+            Regex::new(r"^\s*(?:template\s*<[^>]*>\s*)?(class|struct)\s").unwrap()
+        }),
+        Language::TypeScript
+        | Language::JavaScript
+        | Language::Java
+        | Language::PHP
+        | Language::CSharp
+        | Language::Kotlin
+        | Language::Swift => CLASS_LIKE.get_or_init(|| {
+            Regex::new(&format!(
+                r"^\s*{}(class|interface|trait|struct|record|protocol|extension|object)\s",
+                MODIFIERS
+            ))
+            .unwrap()
+        }),
+        _ => return None,
+    };
+    Some(regex)
+}
+
+/// Declaration text after the keyword, up to the body, on a single line
+fn header_text(lines: &[&str], start: usize, offset: usize, language: Language) -> String {
+    let mut header = String::new();
+    let end = (start + MAX_HEADER_LINES).min(lines.len());
+    for (i, line) in lines[start..end].iter().enumerate() {
+        let text = if i == 0 { &line[offset..] } else { line };
+        match language {
+            Language::Python => {
+                header.push_str(text);
+                if text.trim_end().ends_with(':') {
+                    break;
+                }
+            }
+            Language::Ruby => {
+                header.push_str(text.split(';').next().unwrap_or(text));
+                break;
+            }
+            _ => {
+                if let Some(brace) = text.find('{') {
+                    header.push_str(&text[..brace]);
+                    break;
+                }
+                header.push_str(text);
+                // Forward declarations and one-line statements end here
+                if text.trim_end().ends_with(';') {
+                    break;
+                }
+            }
+        }
+        header.push(' ');
+    }
+    header.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// (subtype, supertype, kind) edges declared by one header
+fn parse_header(keyword: &str, header: &str, language: Language) -> Vec<(String, String, RelationKind)> {
+    match language {
+        Language::Rust => parse_rust(keyword, header),
+        Language::Python => parse_python(header),
+        Language::Ruby => parse_ruby(header),
+        Language::TypeScript | Language::JavaScript | Language::Java | Language::PHP => {
+            parse_keyword_lists(keyword, header)
+        }
+        Language::CSharp | Language::Kotlin | Language::Swift | Language::Cpp => {
+            parse_colon_list(keyword, header, language)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// `impl<T> Trait<T> for Type<T>` and `trait Name<T>: A + B`
+fn parse_rust(keyword: &str, header: &str) -> Vec<(String, String, RelationKind)> {
+    let header = cut_where(header);
+    if keyword == "impl" {
+        let rest = skip_balanced(header.trim_start(), '<', '>');
+        let Some(pos) = find_word(rest, "for") else {
+            return Vec::new(); // Inherent impl
+        };
+        let supertype = type_name(rest[..pos].trim().trim_start_matches('!'));
+        let subtype = type_name(&rest[pos + 3..]);
+        if supertype.is_empty() || subtype.is_empty() {
+            return Vec::new();
+        }
+        return vec![(subtype, supertype, RelationKind::Implements)];
+    }
+
+    let (name, rest) = split_name(header);
+    let Some(bounds) = skip_balanced(rest, '<', '>').trim_start().strip_prefix(':') else {
+        return Vec::new();
+    };
+    split_top_level(bounds, '+')
+        .into_iter()
+        .map(|b| b.trim().trim_start_matches('?'))
+        .filter(|b| !b.is_empty() && !b.starts_with('\''))
+        .map(|b| (name.clone(), type_name(b), RelationKind::Extends))
+        .filter(|(_, sup, _)| !sup.is_empty())
+        .collect()
+}
+
+/// `class Name(Base, Mixin, metaclass=Meta):`
+fn parse_python(header: &str) -> Vec<(String, String, RelationKind)> {
+    let (name, rest) = split_name(header);
+    let rest = skip_balanced(rest, '[', ']').trim_start();
+    let Some(inner) = rest.strip_prefix('(').and_then(|r| r.rsplit_once(')')).map(|(inner, _)| inner) else {
+        return Vec::new();
+    };
+    split_top_level(inner, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|b| !b.is_empty() && !b.contains('=') && *b != "object")
+        .map(|b| (name.clone(), type_name(b), RelationKind::Extends))
+        .collect()
+}
+
+/// `class Name < Base`
+fn parse_ruby(header: &str) -> Vec<(String, String, RelationKind)> {
+    let (name, rest) = split_name(header);
+    let Some(base) = rest.trim_start().strip_prefix('<') else {
+        return Vec::new();
+    };
+    // `class Point < Struct.new(:x, :y)` inherits from Struct
+    let base = base.trim().split(['.', '(', ' ']).next().unwrap_or("");
+    if base.is_empty() {
+        return Vec::new();
+    }
+    vec![(name, base.to_string(), RelationKind::Extends)]
+}
+
+/// `class A<T> extends B<T> implements C, D` (interfaces: `interface A extends B, C`)
+fn parse_keyword_lists(keyword: &str, header: &str) -> Vec<(String, String, RelationKind)> {
+    let (name, rest) = split_name(header);
+    // Generic parameters and Java record components
+    let rest = skip_balanced(skip_balanced(rest, '<', '>').trim_start(), '(', ')');
+
+    let mut relations = Vec::new();
+    let extends_at = find_word(rest, "extends");
+    let implements_at = find_word(rest, "implements");
+
+    if let Some(pos) = extends_at {
+        let end = implements_at.filter(|&i| i > pos).unwrap_or(rest.len());
+        for base in split_top_level(&rest[pos + "extends".len()..end], ',') {
+            let base = type_name(base);
+            if !base.is_empty() {
+                relations.push((name.clone(), base, RelationKind::Extends));
+            }
+        }
+    }
+    if let Some(pos) = implements_at {
+        let end = extends_at.filter(|&e| e > pos).unwrap_or(rest.len());
+        // An interface's supertypes are always `extends`
+        let kind = if keyword == "interface" { RelationKind::Extends } else { RelationKind::Implements };
+        for base in split_top_level(&rest[pos + "implements".len()..end], ',') {
+            let base = type_name(base);
+            if !base.is_empty() {
+                relations.push((name.clone(), base, kind));
+            }
+        }
+    }
+    relations
+}
+
+/// `class A : B, C` in C#, Kotlin, Swift and C++
+fn parse_colon_list(keyword: &str, header: &str, language: Language) -> Vec<(String, String, RelationKind)> {
+    let header = cut_where(header.trim_end_matches(';'));
+    let (name, rest) = split_name(header);
+    let mut rest = skip_balanced(rest, '<', '>').trim_start();
+    if language == Language::Kotlin {
+        rest = rest.strip_prefix("constructor").unwrap_or(rest).trim_start();
+    }
+    if matches!(language, Language::Kotlin | Language::CSharp) {
+        // Primary constructor parameters
+        rest = skip_balanced(rest, '(', ')').trim_start();
+    }
+    if language == Language::Cpp {
+        rest = rest.strip_prefix("final").unwrap_or(rest).trim_start();
+    }
+    let Some(list) = rest.strip_prefix(':').filter(|l| !l.starts_with(':')) else {
+        return Vec::new();
+    };
+
+    split_top_level(list, ',')
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, entry)| {
+            let mut entry = entry.trim();
+            if language == Language::Cpp {
+                while let Some((first, tail)) = entry.split_once(' ') {
+                    if !matches!(first, "public" | "protected" | "private" | "virtual") {
+                        break;
+                    }
+                    entry = tail.trim_start();
+                }
+            }
+            let base = type_name(entry);
+            if base.is_empty() {
+                return None;
+            }
+
+            let kind = match (language, keyword) {
+                (_, "interface" | "protocol") => RelationKind::Extends,
+                (Language::CSharp, _) if is_csharp_interface(&base) => RelationKind::Implements,
+                (Language::CSharp, "struct") => RelationKind::Implements,
+                (Language::Kotlin, _) if !entry.contains('(') => RelationKind::Implements,
+                (Language::Swift, "class") if i == 0 => RelationKind::Extends,
+                (Language::Swift, _) => RelationKind::Implements,
+                _ => RelationKind::Extends,
+            };
+            Some((name.clone(), base, kind))
+        })
+        .collect()
+}
+
+/// C# naming convention for interfaces (`IDisposable`)
+fn is_csharp_interface(name: &str) -> bool {
+    let mut chars = simple_name(name).chars();
+    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+/// Split a header into the declared name and the text after it
+fn split_name(header: &str) -> (String, &str) {
+    let header = header.trim_start();
+    let end = header
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == ':' || c == '\\'))
+        .unwrap_or(header.len());
+    // `class Foo:` in Python/Swift: a trailing colon isn't part of the name
+    let name = header[..end].trim_end_matches(':');
+    (name.to_string(), &header[name.len()..])
+}
+
+/// Type name with generic arguments, call arguments and bounds removed
+fn type_name(text: &str) -> String {
+    let text = text.trim();
+    let end = text.find(['<', '(', '[', '{', ' ']).unwrap_or(text.len());
+    text[..end].trim_end_matches(',').to_string()
+}
+
+/// Text after a balanced `open ... close` group at the start of `text`
+fn skip_balanced(text: &str, open: char, close: char) -> &str {
+    if !text.starts_with(open) {
+        return text;
+    }
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth = depth.saturating_sub(1);
+            if depth == 0 {
+                return &text[i + c.len_utf8()..];
+            }
+        }
+    }
+    ""
+}
+
+/// Split on `sep` outside of `<>`, `()` and `[]`
+fn split_top_level(text: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            _ if c == sep && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Byte position of `word` in `text` as a whole word
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(pos, _)| pos).find(|&pos| {
+        !text[..pos].chars().next_back().is_some_and(is_word)
+            && !text[pos + word.len()..].chars().next().is_some_and(is_word)
+    })
+}
+
+/// Header without a trailing `where` clause
+fn cut_where(header: &str) -> &str {
+    match find_word(header, "where") {
+        Some(pos) => &header[..pos],
+        None => header,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(source: &str, language: Language) -> Vec<(String, String, RelationKind)> {
+        extract_relations(source, language)
+            .into_iter()
+            .map(|r| (r.subtype, r.supertype, r.kind))
+            .collect()
+    }
+
+    fn edge(sub: &str, sup: &str, kind: RelationKind) -> (String, String, RelationKind) {
+        (sub.to_string(), sup.to_string(), kind)
+    }
+
+    #[test]
+    fn test_rust_impls_and_supertraits() {
+        let source = r#"
+impl<T: Clone> Display for Wrapper<T> {
+}
+impl Worker {
+}
+unsafe impl Send for Handle {}
+pub trait Shape: Debug + Clone + 'static {
+}
+"#;
+        assert_eq!(edges(source, Language::Rust), vec![
+            edge("Wrapper", "Display", RelationKind::Implements),
+            edge("Handle", "Send", RelationKind::Implements),
+            edge("Shape", "Debug", RelationKind::Extends),
+            edge("Shape", "Clone", RelationKind::Extends),
+        ]);
+    }
+
+    #[test]
+    fn test_typescript_extends_and_implements() {
+        let source = "export class UserRepo<T> extends BaseRepo<T>\n    implements Repo, Disposable {\n}\ninterface Admin extends User, Auditable {}\n";
+        let found = extract_relations(source, Language::TypeScript);
+
+        assert_eq!(found.iter().map(|r| (r.subtype.clone(), r.supertype.clone(), r.kind)).collect::<Vec<_>>(), vec![
+            edge("UserRepo", "BaseRepo", RelationKind::Extends),
+            edge("UserRepo", "Repo", RelationKind::Implements),
+            edge("UserRepo", "Disposable", RelationKind::Implements),
+            edge("Admin", "User", RelationKind::Extends),
+            edge("Admin", "Auditable", RelationKind::Extends),
+        ]);
+        assert_eq!(found[0].line, 1);
+        assert_eq!(found[3].line, 4);
+    }
+
+    #[test]
+    fn test_python_bases() {
+        let source = "class Worker(models.Base, Generic[T], metaclass=Meta):\n    pass\nclass Plain:\n    pass\n";
+        assert_eq!(edges(source, Language::Python), vec![
+            edge("Worker", "models.Base", RelationKind::Extends),
+            edge("Worker", "Generic", RelationKind::Extends),
+        ]);
+    }
+
+    #[test]
+    fn test_colon_lists() {
+        assert_eq!(edges("public class Repo<T> : Base, IDisposable where T : class {", Language::CSharp), vec![
+            edge("Repo", "Base", RelationKind::Extends),
+            edge("Repo", "IDisposable", RelationKind::Implements),
+        ]);
+        assert_eq!(edges("class Cat(name: String) : Animal(name), Pet {", Language::Kotlin), vec![
+            edge("Cat", "Animal", RelationKind::Extends),
+            edge("Cat", "Pet", RelationKind::Implements),
+        ]);
+        assert_eq!(edges("class Cat: Animal, Codable {", Language::Swift), vec![
+            edge("Cat", "Animal", RelationKind::Extends),
+            edge("Cat", "Codable", RelationKind::Implements),
+        ]);
+        assert_eq!(edges("class Cat : public Animal, private virtual Pet {\nclass Fwd;", Language::Cpp), vec![
+            edge("Cat", "Animal", RelationKind::Extends),
+            edge("Cat", "Pet", RelationKind::Extends),
+        ]);
+    }
+
+    #[test]
+    fn test_ruby_superclass() {
+        assert_eq!(edges("class Point < Struct.new(:x, :y)\nend\nclass Admin < Models::User\n", Language::Ruby), vec![
+            edge("Point", "Struct", RelationKind::Extends),
+            edge("Admin", "Models::User", RelationKind::Extends),
+        ]);
+        assert_eq!(simple_name("Models::User"), "User");
+    }
+}
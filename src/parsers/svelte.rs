@@ -15,7 +15,7 @@
 
 use anyhow::{Context, Result};
 use crate::models::{Language, SearchResult, Span, SymbolKind};
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{Query, QueryCursor};
 use streaming_iterator::StreamingIterator;
 use crate::parsers::{DependencyExtractor, ImportInfo};
 use crate::parsers::typescript::TypeScriptDependencyExtractor;
@@ -110,7 +110,7 @@ fn parse_script_block(
     script_source: &str,
     line_offset: usize,
 ) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
 
     // Use TSX parser to handle both TypeScript and JavaScript
     let ts_language: tree_sitter::Language = tree_sitter_typescript::LANGUAGE_TSX.into();
@@ -263,9 +263,17 @@ fn extract_variables(
                     lang: Language::Svelte,
                     kind,
                     symbol: Some(name),
+                    parent: None,
+                    qualified_name: None,
                     span,
                     preview,
                     dependencies: None,
+                    repo: None,
+                    signature: None,
+                    doc: None,
+                    exported: None,
+                    captures: None,
+                    score: None,
                 });
             }
         }
@@ -330,9 +338,17 @@ fn extract_reactive_declarations(
                     lang: Language::Svelte,
                     kind: SymbolKind::Variable,
                     symbol: Some(name),
+                    parent: None,
+                    qualified_name: None,
                     span,
                     preview,
                     dependencies: None,
+                    repo: None,
+                    signature: None,
+                    doc: None,
+                    exported: None,
+                    captures: None,
+                    score: None,
                 });
             }
         }
@@ -377,9 +393,17 @@ fn extract_symbols(
                 lang: Language::Svelte,
                 kind: kind.clone(),
                 symbol: Some(name),
+                parent: None,
+                qualified_name: None,
                 span,
                 preview,
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             });
         }
     }
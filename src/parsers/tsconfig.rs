@@ -2,6 +2,7 @@
 //!
 //! Parses tsconfig.json files to extract path alias mappings from compilerOptions.paths.
 //! These mappings are used to resolve non-relative imports in TypeScript/JavaScript/Vue files.
+//! Options missing from a tsconfig are inherited through relative `extends` entries.
 //!
 //! Example tsconfig.json:
 //! ```json
@@ -46,12 +47,60 @@ struct CompilerOptions {
 struct TsConfig {
     #[serde(rename = "compilerOptions")]
     compiler_options: Option<CompilerOptions>,
+    extends: Option<Extends>,
 }
 
+/// `extends` is a single config path or (TypeScript 5+) a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Extends {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Extends {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Extends::One(path) => vec![path],
+            Extends::Many(paths) => paths,
+        }
+    }
+}
+
+/// Maximum depth of `extends` chains to follow (guards against cycles)
+const MAX_EXTENDS_DEPTH: usize = 8;
+
 impl PathAliasMap {
     /// Parse a tsconfig.json file and extract path aliases
+    ///
+    /// `baseUrl` and `paths` missing from the file are inherited through relative
+    /// `extends` entries (e.g. `"extends": "./tsconfig.base.json"`). Package
+    /// references like `@tsconfig/node18` are not followed.
     pub fn from_file(tsconfig_path: impl AsRef<Path>) -> Result<Self> {
         let tsconfig_path = tsconfig_path.as_ref();
+
+        let config_dir = tsconfig_path.parent()
+            .ok_or_else(|| anyhow::anyhow!("Invalid tsconfig.json path"))?
+            .to_path_buf();
+
+        let (paths, base_url) = Self::load_compiler_options(tsconfig_path, 0)?;
+
+        Ok(Self {
+            aliases: paths.unwrap_or_default(),
+            base_url,
+            config_dir,
+        })
+    }
+
+    /// Read `paths` and `baseUrl` from a tsconfig, following its `extends` chain
+    ///
+    /// Inherited values are made absolute so they stay relative to the config that
+    /// declared them: an inherited `baseUrl` is joined to the parent's directory, and
+    /// inherited `paths` without any `baseUrl` resolve against the parent's directory.
+    fn load_compiler_options(
+        tsconfig_path: &Path,
+        depth: usize,
+    ) -> Result<(Option<HashMap<String, Vec<String>>>, Option<String>)> {
         let content = std::fs::read_to_string(tsconfig_path)
             .with_context(|| format!("Failed to read tsconfig.json: {}", tsconfig_path.display()))?;
 
@@ -59,9 +108,7 @@ impl PathAliasMap {
         let config: TsConfig = json5::from_str(&content)
             .with_context(|| format!("Failed to parse tsconfig.json: {}", tsconfig_path.display()))?;
 
-        let config_dir = tsconfig_path.parent()
-            .ok_or_else(|| anyhow::anyhow!("Invalid tsconfig.json path"))?
-            .to_path_buf();
+        let config_dir = tsconfig_path.parent().unwrap_or_else(|| Path::new(""));
 
         let compiler_options = config.compiler_options.unwrap_or_else(|| {
             CompilerOptions {
@@ -69,12 +116,53 @@ impl PathAliasMap {
                 paths: None,
             }
         });
+        let mut paths = compiler_options.paths;
+        let mut base_url = compiler_options.base_url;
 
-        Ok(Self {
-            aliases: compiler_options.paths.unwrap_or_default(),
-            base_url: compiler_options.base_url,
-            config_dir,
-        })
+        if paths.is_some() && base_url.is_some() {
+            return Ok((paths, base_url));
+        }
+        if depth >= MAX_EXTENDS_DEPTH {
+            log::warn!("tsconfig extends chain too deep at {}", tsconfig_path.display());
+            return Ok((paths, base_url));
+        }
+
+        // Later entries of an `extends` list override earlier ones
+        let parents = config.extends.map(Extends::into_vec).unwrap_or_default();
+        for parent in parents.iter().rev() {
+            if !parent.starts_with("./") && !parent.starts_with("../") {
+                log::debug!("Not following non-relative tsconfig extends '{}'", parent);
+                continue;
+            }
+
+            let parent_path = if parent.ends_with(".json") {
+                config_dir.join(parent)
+            } else {
+                config_dir.join(format!("{}.json", parent))
+            };
+            let parent_dir = parent_path.parent().unwrap_or(config_dir);
+
+            match Self::load_compiler_options(&parent_path, depth + 1) {
+                Ok((parent_paths, parent_base_url)) => {
+                    if base_url.is_none() {
+                        base_url = parent_base_url
+                            .map(|url| parent_dir.join(url).to_string_lossy().to_string());
+                    }
+                    if paths.is_none() && parent_paths.is_some() {
+                        paths = parent_paths;
+                        if base_url.is_none() {
+                            base_url = Some(parent_dir.to_string_lossy().to_string());
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to follow tsconfig extends '{}' from {}: {}",
+                              parent, tsconfig_path.display(), e);
+                }
+            }
+        }
+
+        Ok((paths, base_url))
     }
 
     /// Find the nearest tsconfig.json for a given source file
@@ -104,6 +192,9 @@ impl PathAliasMap {
     /// Resolve an import path using the path alias mappings
     ///
     /// Returns the resolved path if the import matches an alias, None otherwise.
+    /// Patterns follow TypeScript's rules: at most one `*`, which captures the rest
+    /// of the import, and when several patterns match the one with the longest
+    /// prefix before the `*` wins (exact patterns beat wildcards).
     ///
     /// Example:
     /// - Alias: `@packages/*` => `../../packages/*`
@@ -112,54 +203,53 @@ impl PathAliasMap {
     pub fn resolve_alias(&self, import_path: &str) -> Option<String> {
         log::debug!("  resolve_alias: trying to match '{}' against {} aliases", import_path, self.aliases.len());
 
-        // Try to match against each alias pattern
-        for (alias_pattern, target_paths) in &self.aliases {
-            log::trace!("    Checking alias pattern: {} => {:?}", alias_pattern, target_paths);
-            // Check if pattern has a wildcard
-            if alias_pattern.ends_with("/*") {
-                let alias_prefix = alias_pattern.trim_end_matches("/*");
-
-                // Check if import starts with the alias prefix
-                if import_path.starts_with(alias_prefix) {
-                    // Extract the suffix after the alias prefix
-                    // Example: "@packages/ui/stores/auth" with alias "@packages"
-                    //          => suffix = "/ui/stores/auth"
-                    let suffix = import_path.strip_prefix(alias_prefix).unwrap_or("");
-
-                    // Use the first target path (tsconfig allows multiple, but we'll use the first)
-                    if let Some(target_pattern) = target_paths.first() {
-                        // Replace wildcard in target with the suffix
-                        let resolved = if target_pattern.ends_with("/*") {
-                            let target_prefix = target_pattern.trim_end_matches("/*");
-                            format!("{}{}", target_prefix, suffix)
-                        } else {
-                            // No wildcard in target - append suffix with proper path joining
-                            // Strip leading '/' from suffix to avoid double slashes
-                            // Example: ".." + "/ui/stores/auth" => "../ui/stores/auth"
-                            let clean_suffix = suffix.trim_start_matches('/');
-                            if clean_suffix.is_empty() {
-                                target_pattern.to_string()
-                            } else {
-                                format!("{}/{}", target_pattern, clean_suffix)
-                            }
-                        };
-
-                        log::trace!("Resolved alias {} + {} => {}", alias_pattern, import_path, resolved);
-                        return Some(resolved);
-                    }
-                }
-            } else {
-                // Exact match (no wildcard)
-                if import_path == alias_pattern {
-                    if let Some(target) = target_paths.first() {
-                        log::trace!("Resolved exact alias {} => {}", alias_pattern, target);
-                        return Some(target.clone());
-                    }
+        let (alias_pattern, captured) = self.match_alias(import_path)?;
+        // Use the first target path (tsconfig allows multiple, but we'll use the first)
+        let target_pattern = self.aliases.get(alias_pattern)?.first()?;
+
+        let resolved = match captured {
+            None => target_pattern.clone(),
+            Some(captured) if target_pattern.contains('*') => target_pattern.replacen('*', captured, 1),
+            Some(captured) => {
+                // No wildcard in target - append the captured part with proper path joining
+                // Example: ".." + "ui/stores/auth" => "../ui/stores/auth"
+                let clean_suffix = captured.trim_start_matches('/');
+                if clean_suffix.is_empty() {
+                    target_pattern.clone()
+                } else {
+                    format!("{}/{}", target_pattern.trim_end_matches('/'), clean_suffix)
                 }
             }
+        };
+
+        log::trace!("Resolved alias {} + {} => {}", alias_pattern, import_path, resolved);
+        Some(resolved)
+    }
+
+    /// Find the alias pattern that applies to `import_path`
+    ///
+    /// Returns the pattern and, for wildcard patterns, the text matched by `*`.
+    pub fn match_alias<'a>(&self, import_path: &'a str) -> Option<(&str, Option<&'a str>)> {
+        if let Some((pattern, _)) = self.aliases.get_key_value(import_path) {
+            return Some((pattern.as_str(), None));
         }
 
-        None
+        self.aliases
+            .keys()
+            .filter_map(|pattern| {
+                let (prefix, suffix) = pattern.split_once('*')?;
+                if import_path.len() < prefix.len() + suffix.len()
+                    || !import_path.starts_with(prefix)
+                    || !import_path.ends_with(suffix)
+                {
+                    return None;
+                }
+                let captured = &import_path[prefix.len()..import_path.len() - suffix.len()];
+                Some((pattern.as_str(), prefix.len(), captured))
+            })
+            // Longest prefix wins; break ties on the pattern so the choice is deterministic
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(pattern, _, captured)| (pattern, Some(captured)))
     }
 
     /// Resolve a path relative to the tsconfig directory and baseUrl
@@ -321,6 +411,54 @@ mod tests {
         assert_eq!(resolved, None);
     }
 
+    #[test]
+    fn test_resolve_longest_alias_prefix() {
+        let temp = TempDir::new().unwrap();
+        let alias_map = PathAliasMap {
+            aliases: HashMap::from([
+                ("@/*".to_string(), vec!["./src/*".to_string()]),
+                ("@/ui/*".to_string(), vec!["./packages/ui/src/*".to_string()]),
+            ]),
+            base_url: None,
+            config_dir: temp.path().to_path_buf(),
+        };
+
+        assert_eq!(alias_map.resolve_alias("@/ui/button"), Some("./packages/ui/src/button".to_string()));
+        assert_eq!(alias_map.resolve_alias("@/lib/api"), Some("./src/lib/api".to_string()));
+        // The wildcard prefix includes the slash, so scoped npm packages don't match
+        assert_eq!(alias_map.resolve_alias("@angular/core"), None);
+        assert_eq!(alias_map.match_alias("@/ui/button"), Some(("@/ui/*", Some("button"))));
+    }
+
+    #[test]
+    fn test_extends_inherits_paths() {
+        let temp = TempDir::new().unwrap();
+        let app_dir = temp.path().join("apps/web");
+        fs::create_dir_all(&app_dir).unwrap();
+
+        fs::write(temp.path().join("tsconfig.base.json"), r#"{
+            // Shared compiler options
+            "compilerOptions": {
+                "paths": { "@shared/*": ["libs/shared/*"] },
+            }
+        }"#).unwrap();
+        fs::write(app_dir.join("tsconfig.json"), r#"{
+            "extends": "../../tsconfig.base",
+            "compilerOptions": { "strict": true }
+        }"#).unwrap();
+
+        let alias_map = PathAliasMap::from_file(app_dir.join("tsconfig.json")).unwrap();
+        assert_eq!(alias_map.config_dir, app_dir);
+        assert!(alias_map.aliases.contains_key("@shared/*"));
+
+        // Inherited paths resolve against the base config's directory
+        let resolved = alias_map.resolve_alias("@shared/date").unwrap();
+        assert_eq!(
+            alias_map.resolve_relative_to_config(&resolved),
+            temp.path().join("libs/shared/date")
+        );
+    }
+
     #[test]
     fn test_find_nearest_tsconfig() {
         let temp = TempDir::new().unwrap();
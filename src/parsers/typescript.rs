@@ -9,6 +9,7 @@
 //! - Variables and constants (const, let, var - all scopes)
 //! - Methods (with class scope)
 //! - Modules/Namespaces
+//! - Decorators (uses, as Attribute symbols: @Component, @Input(), @foo.bar)
 //!
 //! This parser handles both TypeScript (.ts, .tsx) and JavaScript (.js, .jsx)
 //! files using the tree-sitter-typescript grammar.
@@ -20,7 +21,7 @@ use crate::models::{Language, SearchResult, Span, SymbolKind};
 
 /// Parse TypeScript/JavaScript source code and extract symbols
 pub fn parse(path: &str, source: &str, language: Language) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
 
     // tree-sitter-typescript provides both TypeScript and TSX grammars
     // For JavaScript, we use the TypeScript grammar (it's a superset)
@@ -54,6 +55,7 @@ pub fn parse(path: &str, source: &str, language: Language) -> Result<Vec<SearchR
     symbols.extend(extract_enums(source, &root_node, &ts_language)?);
     symbols.extend(extract_variables(source, &root_node, &ts_language)?);
     symbols.extend(extract_methods(source, &root_node, &ts_language)?);
+    symbols.extend(extract_decorators(source, &root_node, &ts_language)?);
 
     // Add file path and language to all symbols
     for symbol in &mut symbols {
@@ -340,6 +342,25 @@ fn extract_methods(
     Ok(symbols)
 }
 
+/// Extract decorator uses (@Injectable(), @Input, @observable.ref)
+fn extract_decorators(
+    source: &str,
+    root: &tree_sitter::Node,
+    language: &tree_sitter::Language,
+) -> Result<Vec<SearchResult>> {
+    let query_str = r#"
+        (decorator (identifier) @name) @decorator
+        (decorator (member_expression) @name) @decorator
+        (decorator (call_expression function: (identifier) @name)) @decorator
+        (decorator (call_expression function: (member_expression) @name)) @decorator
+    "#;
+
+    let query = Query::new(language, query_str)
+        .context("Failed to create decorator query")?;
+
+    extract_symbols(source, root, &query, SymbolKind::Attribute, None)
+}
+
 /// Generic symbol extraction helper
 fn extract_symbols(
     source: &str,
@@ -515,6 +536,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_decorators() {
+        let source = r#"
+            @Component({ selector: 'app-user' })
+            export class UserCard {
+                @Input() name: string;
+
+                @observable.ref
+                items = [];
+
+                @HostListener('click')
+                onClick() {}
+            }
+        "#;
+
+        let symbols = parse("test.ts", source, Language::TypeScript).unwrap();
+
+        let decorators: Vec<_> = symbols.iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Attribute))
+            .collect();
+
+        assert_eq!(decorators.len(), 4);
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("Component") && s.span.start_line == 2));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("Input")));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("observable.ref")));
+        assert!(decorators.iter().any(|s| s.symbol.as_deref() == Some("HostListener")));
+    }
+
     #[test]
     fn test_parse_interface() {
         let source = r#"
@@ -1056,21 +1105,10 @@ fn classify_js_import(import_path: &str, alias_map: Option<&crate::parsers::tsco
     // Check if import matches a configured path alias (e.g., @packages/*, ~/*)
     if let Some(map) = alias_map {
         log::trace!("classify_js_import: checking '{}' against {} aliases", import_path, map.aliases.len());
-        for alias_pattern in map.aliases.keys() {
-            // Check for wildcard patterns like "@packages/*"
-            if alias_pattern.ends_with("/*") {
-                let alias_prefix = alias_pattern.trim_end_matches("/*");
-                if import_path.starts_with(alias_prefix) {
-                    log::info!("classify_js_import: '{}' => Internal (matches alias pattern '{}')", import_path, alias_pattern);
-                    return ImportType::Internal;
-                }
-            } else {
-                // Exact match
-                if import_path == alias_pattern {
-                    log::info!("classify_js_import: '{}' => Internal (exact match alias '{}')", import_path, alias_pattern);
-                    return ImportType::Internal;
-                }
-            }
+        // Wildcard patterns match on their full prefix, so `@/*` does not claim `@angular/core`
+        if let Some((alias_pattern, _)) = map.match_alias(import_path) {
+            log::info!("classify_js_import: '{}' => Internal (matches alias pattern '{}')", import_path, alias_pattern);
+            return ImportType::Internal;
         }
         log::trace!("classify_js_import: '{}' did not match any of {} alias patterns", import_path, map.aliases.len());
     } else {
@@ -1219,28 +1257,41 @@ fn extract_export_from_statements(
 
 /// Extract the list of symbols being exported from an export statement
 ///
-/// For `export { A, B as C } from './module'`, returns ["A", "B"]
+/// Returns the public names, as importers of the barrel see them:
+/// For `export { A, B as C } from './module'`, returns ["A", "C"]
+/// For `export * as ns from './module'`, returns ["ns"]
 /// For `export * from './module'`, returns empty vec (handled by caller)
 fn extract_exported_symbols(source: &str, export_node: &tree_sitter::Node) -> Result<Vec<String>> {
     let mut symbols = Vec::new();
 
-    // Walk children to find export_clause
+    // Walk children to find export_clause or namespace_export
     let mut cursor = export_node.walk();
     for child in export_node.children(&mut cursor) {
-        if child.kind() == "export_clause" {
-            // Extract individual export specifiers
-            let mut specifier_cursor = child.walk();
-            for specifier in child.children(&mut specifier_cursor) {
-                if specifier.kind() == "export_specifier" {
-                    // Get the exported name (before "as" if aliased)
-                    // For `export { foo as bar }`, we want "foo" (the original name)
-                    if let Ok(text) = specifier.utf8_text(source.as_bytes()) {
-                        // Parse "foo as bar" or just "foo"
-                        let name = text.split_whitespace().next().unwrap_or(text);
-                        symbols.push(name.to_string());
+        match child.kind() {
+            "export_clause" => {
+                // Extract individual export specifiers
+                let mut specifier_cursor = child.walk();
+                for specifier in child.children(&mut specifier_cursor) {
+                    if specifier.kind() == "export_specifier" {
+                        // Get the exported name (after "as" if aliased)
+                        // For `export { foo as bar }`, importers use "bar"
+                        if let Ok(text) = specifier.utf8_text(source.as_bytes()) {
+                            // Parse "foo as bar" or just "foo"
+                            let name = text.split_whitespace().last().unwrap_or(text);
+                            symbols.push(name.to_string());
+                        }
                     }
                 }
             }
+            "namespace_export" => {
+                // `* as ns`: the namespace is the exported name
+                if let Ok(text) = child.utf8_text(source.as_bytes())
+                    && let Some(name) = text.split_whitespace().last().filter(|name| *name != "*")
+                {
+                    symbols.push(name.to_string());
+                }
+            }
+            _ => {}
         }
     }
 
@@ -0,0 +1,470 @@
+//! Exported vs. local symbols
+//!
+//! After a language parser has produced symbols (and `qualify` has recorded
+//! their parents), this module decides whether each one is part of the file's
+//! public surface and records it as `exported`:
+//!
+//! - **Rust, Zig**: `pub` (restricted forms like `pub(crate)` are local);
+//!   Rust methods of trait impls and trait declarations follow their trait.
+//! - **Go**: the name starts with an uppercase letter.
+//! - **Python**: the name doesn't start with `_` (dunders are public).
+//! - **JavaScript, TypeScript**: `export` on the declaration or in an
+//!   `export { ... }` list; class members unless `private`, `protected` or `#name`.
+//! - **Java, C#**: `public` (interface members are implicitly public).
+//! - **Kotlin, PHP**: everything not `private`, `protected` or `internal`.
+//! - **C, C++**: everything not `static`; C++ class members follow the closest
+//!   `public:`/`private:` label (classes default to private, structs to public).
+//! - **Ruby**: methods not following a `private`/`protected` section marker.
+//!
+//! A member is only exported if every enclosing container is. Languages
+//! without a visibility model (Vue, Svelte, markup) are left as `None`.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::docs::is_attribute_line;
+use super::qualify::is_container;
+use crate::models::{Language, SearchResult};
+
+/// Maximum number of parent links followed when resolving a member's visibility
+const MAX_PARENT_DEPTH: usize = 16;
+
+/// Visibility filter for symbol queries (`--visibility`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Symbols exported from their file or module
+    Public,
+    /// Symbols local to their file, module or class
+    Private,
+}
+
+impl Visibility {
+    /// Whether a symbol with the given `exported` flag passes this filter
+    ///
+    /// Symbols of languages without a visibility model never pass.
+    pub fn matches(self, exported: Option<bool>) -> bool {
+        match self {
+            Visibility::Public => exported == Some(true),
+            Visibility::Private => exported == Some(false),
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "public" | "exported" | "pub" => Ok(Visibility::Public),
+            "private" | "local" => Ok(Visibility::Private),
+            _ => anyhow::bail!(
+                "Unknown visibility '{}'.\n\
+                 \n\
+                 Valid values: public, private",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+        })
+    }
+}
+
+/// Fill in `exported` for every symbol of a language with a visibility model
+pub fn attach_visibility(symbols: &mut [SearchResult], source: &str, language: Language) {
+    if symbols.is_empty() || !has_visibility_model(language) {
+        return;
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let export_list = match language {
+        Language::JavaScript | Language::TypeScript => export_list_names(source),
+        _ => HashSet::new(),
+    };
+
+    let own: Vec<Option<bool>> = (0..symbols.len())
+        .map(|i| declared_visibility(symbols, i, &lines, language, &export_list))
+        .collect();
+
+    let resolved: Vec<Option<bool>> = (0..symbols.len())
+        .map(|i| resolve(symbols, &own, i))
+        .collect();
+
+    for (symbol, exported) in symbols.iter_mut().zip(resolved) {
+        symbol.exported = exported;
+    }
+}
+
+fn has_visibility_model(language: Language) -> bool {
+    matches!(
+        language,
+        Language::Rust
+            | Language::Zig
+            | Language::Go
+            | Language::Python
+            | Language::JavaScript
+            | Language::TypeScript
+            | Language::Java
+            | Language::CSharp
+            | Language::Kotlin
+            | Language::PHP
+            | Language::C
+            | Language::Cpp
+            | Language::Ruby
+    )
+}
+
+/// A member is only exported if its own declaration and every enclosing container are
+fn resolve(symbols: &[SearchResult], own: &[Option<bool>], index: usize) -> Option<bool> {
+    let mut exported = own[index]?;
+    let mut current = index;
+    for _ in 0..MAX_PARENT_DEPTH {
+        if !exported {
+            break;
+        }
+        let Some(parent) = container_of(symbols, current) else {
+            break;
+        };
+        exported = own[parent].unwrap_or(true);
+        current = parent;
+    }
+    Some(exported)
+}
+
+/// Index of the container symbol named by `symbols[index].parent`
+///
+/// Prefers a container whose span encloses the symbol; Rust `impl` methods and
+/// Go receiver methods are declared outside their type, so any container of
+/// that name is accepted as a fallback.
+fn container_of(symbols: &[SearchResult], index: usize) -> Option<usize> {
+    let target = &symbols[index];
+    let parent = target.parent.as_deref()?;
+    let candidates = || {
+        symbols.iter().enumerate().filter(move |(i, s)| {
+            *i != index && is_container(&s.kind) && s.symbol.as_deref() == Some(parent)
+        })
+    };
+
+    candidates()
+        .find(|(_, s)| s.span.start_line <= target.span.start_line && target.span.end_line <= s.span.end_line)
+        .or_else(|| candidates().next())
+        .map(|(i, _)| i)
+}
+
+/// Container of `symbols[index]` whose span encloses it (not an out-of-line `impl` target)
+fn enclosing_container(symbols: &[SearchResult], index: usize) -> Option<&SearchResult> {
+    let target = &symbols[index];
+    container_of(symbols, index)
+        .map(|i| &symbols[i])
+        .filter(|s| s.span.start_line <= target.span.start_line && target.span.end_line <= s.span.end_line)
+}
+
+/// Visibility written on the declaration itself, ignoring enclosing containers
+fn declared_visibility(
+    symbols: &[SearchResult],
+    index: usize,
+    lines: &[&str],
+    language: Language,
+    export_list: &HashSet<String>,
+) -> Option<bool> {
+    let symbol = &symbols[index];
+    let name = symbol.symbol.as_deref()?;
+    let start = symbol.span.start_line.checked_sub(1)?;
+    let end = symbol.span.end_line.min(lines.len());
+    if start >= end {
+        return None;
+    }
+
+    // Some grammars include attributes, decorators or comments in the node
+    let decl_idx = (start..end).find(|&i| !is_preamble_line(lines[i].trim(), language))?;
+    let decl = lines[decl_idx].trim();
+    let modifiers = modifiers(decl, name);
+    let has = |word: &str| modifiers.contains(&word);
+    let container = enclosing_container(symbols, index);
+
+    let exported = match language {
+        Language::Rust => {
+            if decl.starts_with("macro_rules!") {
+                lines[..decl_idx]
+                    .iter()
+                    .rev()
+                    .take_while(|l| is_preamble_line(l.trim(), language))
+                    .any(|l| l.trim().starts_with("#[macro_export"))
+            } else if has("pub") {
+                !decl[decl.find("pub")? + 3..].trim_start().starts_with('(')
+            } else {
+                // Trait items and methods of trait impls are as visible as the trait
+                container.is_some_and(|c| matches!(c.kind, crate::models::SymbolKind::Trait))
+                    || impl_header(lines, decl_idx).is_some_and(|h| h.contains(" for "))
+            }
+        }
+        Language::Zig => has("pub"),
+        Language::Go => name.chars().next().is_some_and(char::is_uppercase),
+        Language::Python => !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__")),
+        Language::JavaScript | Language::TypeScript => {
+            if container.is_some() {
+                !(has("private") || has("protected") || name.starts_with('#'))
+            } else {
+                has("export") || export_list.contains(name)
+            }
+        }
+        Language::Java | Language::CSharp => {
+            has("public")
+                || container.is_some_and(|c| matches!(c.kind, crate::models::SymbolKind::Interface))
+        }
+        Language::Kotlin | Language::PHP => !(has("private") || has("protected") || has("internal")),
+        Language::C => !has("static"),
+        Language::Cpp => match container {
+            Some(c) => cpp_member_is_public(lines, c.span.start_line.saturating_sub(1), decl_idx),
+            None => !has("static"),
+        },
+        Language::Ruby => match container {
+            Some(c) => !has("private")
+                && !has("protected")
+                && ruby_member_is_public(lines, c.span.start_line.saturating_sub(1), decl_idx),
+            None => true,
+        },
+        _ => return None,
+    };
+
+    Some(exported)
+}
+
+fn is_preamble_line(trimmed: &str, language: Language) -> bool {
+    trimmed.is_empty()
+        || is_attribute_line(trimmed, language)
+        || trimmed.starts_with("//")
+        || trimmed.starts_with("/*")
+        || trimmed.starts_with('*')
+        || (matches!(language, Language::Python | Language::Ruby) && trimmed.starts_with('#'))
+}
+
+/// Words of a declaration line before the symbol's name
+fn modifiers<'a>(decl: &'a str, name: &str) -> Vec<&'a str> {
+    let mut words = Vec::new();
+    for word in decl.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '#' || c == '$')) {
+        if word.is_empty() {
+            continue;
+        }
+        if word == name || word.trim_start_matches('$') == name.trim_start_matches('$') {
+            break;
+        }
+        words.push(word);
+    }
+    words
+}
+
+/// Header of the Rust `impl` block enclosing line `decl_idx`, if any
+fn impl_header<'a>(lines: &[&'a str], decl_idx: usize) -> Option<&'a str> {
+    let indent = indentation(lines[decl_idx]);
+    lines[..decl_idx]
+        .iter()
+        .rev()
+        .find(|l| !l.trim().is_empty() && indentation(l) < indent)
+        .map(|l| l.trim())
+        .filter(|l| l.starts_with("impl") || l.starts_with("unsafe impl"))
+}
+
+/// Whether the C++ member at `decl_idx` sits in a `public:` section of the class opening at `class_idx`
+fn cpp_member_is_public(lines: &[&str], class_idx: usize, decl_idx: usize) -> bool {
+    for line in lines[class_idx.min(decl_idx)..decl_idx].iter().rev() {
+        match line.trim() {
+            "public:" => return true,
+            "private:" | "protected:" => return false,
+            _ => {}
+        }
+    }
+    // No access label: `struct` members are public, `class` members private
+    lines
+        .get(class_idx)
+        .is_some_and(|header| header.split_whitespace().any(|w| w == "struct"))
+}
+
+/// Whether the Ruby method at `decl_idx` precedes any `private`/`protected` marker of its class
+fn ruby_member_is_public(lines: &[&str], class_idx: usize, decl_idx: usize) -> bool {
+    let indent = indentation(lines[decl_idx]);
+    for line in lines[class_idx.min(decl_idx)..decl_idx].iter().rev() {
+        if indentation(line) != indent {
+            continue;
+        }
+        match line.trim() {
+            "public" => return true,
+            "private" | "protected" => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Local names exported through `export { a, b as c }` lists (re-exports `from` other modules excluded)
+fn export_list_names(source: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut rest = source;
+    while let Some(pos) = rest.find("export") {
+        let after = rest[pos + "export".len()..].trim_start();
+        let after = after.strip_prefix("type").map(str::trim_start).unwrap_or(after);
+        rest = &rest[pos + "export".len()..];
+
+        if let Some(list) = after.strip_prefix('{') {
+            let Some(close) = list.find('}') else {
+                break;
+            };
+            if list[close + 1..].trim_start().starts_with("from") {
+                continue;
+            }
+            for item in list[..close].split(',') {
+                let local = item.split_whitespace().find(|w| *w != "type");
+                if let Some(local) = local {
+                    names.insert(local.to_string());
+                }
+            }
+        } else if let Some(default) = after.strip_prefix("default") {
+            // `export default Name;` exports an earlier declaration
+            let ident: String = default
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+                .collect();
+            if !ident.is_empty() {
+                names.insert(ident);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ParserFactory;
+
+    fn exported(source: &str, path: &str, language: Language) -> Vec<(String, Option<bool>)> {
+        ParserFactory::parse(path, source, language)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.symbol.unwrap_or_default(), s.exported))
+            .collect()
+    }
+
+    // The Rust parser reports impl methods as functions too; methods come last
+    fn lookup(symbols: &[(String, Option<bool>)], name: &str) -> Option<bool> {
+        symbols.iter().rev().find(|(n, _)| n == name).and_then(|(_, e)| *e)
+    }
+
+    #[test]
+    fn test_rust_visibility() {
+        let source = r#"
+pub struct Engine {}
+
+struct Cache {}
+
+impl Engine {
+    pub fn search(&self) {}
+
+    fn plan(&self) {}
+
+    pub(crate) fn warm(&self) {}
+}
+
+impl Cache {
+    pub fn get(&self) {}
+}
+
+impl Default for Engine {
+    fn default() -> Self { Engine {} }
+}
+
+pub fn run() {}
+fn helper() {}
+"#;
+        let symbols = exported(source, "lib.rs", Language::Rust);
+
+        assert_eq!(lookup(&symbols, "Engine"), Some(true));
+        assert_eq!(lookup(&symbols, "Cache"), Some(false));
+        assert_eq!(lookup(&symbols, "search"), Some(true));
+        assert_eq!(lookup(&symbols, "plan"), Some(false));
+        assert_eq!(lookup(&symbols, "warm"), Some(false));
+        // Public method on a private type isn't reachable
+        assert_eq!(lookup(&symbols, "get"), Some(false));
+        assert_eq!(lookup(&symbols, "default"), Some(true));
+        assert_eq!(lookup(&symbols, "run"), Some(true));
+        assert_eq!(lookup(&symbols, "helper"), Some(false));
+    }
+
+    #[test]
+    fn test_typescript_visibility() {
+        let source = r#"
+export function parse(input: string) {}
+
+function helper() {}
+
+class Widget {
+    render() {}
+    private layout() {}
+}
+
+export { Widget };
+"#;
+        let symbols = exported(source, "widget.ts", Language::TypeScript);
+
+        assert_eq!(lookup(&symbols, "parse"), Some(true));
+        assert_eq!(lookup(&symbols, "helper"), Some(false));
+        assert_eq!(lookup(&symbols, "Widget"), Some(true));
+        assert_eq!(lookup(&symbols, "render"), Some(true));
+        assert_eq!(lookup(&symbols, "layout"), Some(false));
+    }
+
+    #[test]
+    fn test_python_and_go_visibility() {
+        let python = "def load():\n    pass\n\ndef _cache():\n    pass\n\nclass Store:\n    def __init__(self):\n        pass\n\n    def _evict(self):\n        pass\n";
+        let symbols = exported(python, "store.py", Language::Python);
+        assert_eq!(lookup(&symbols, "load"), Some(true));
+        assert_eq!(lookup(&symbols, "_cache"), Some(false));
+        assert_eq!(lookup(&symbols, "__init__"), Some(true));
+        assert_eq!(lookup(&symbols, "_evict"), Some(false));
+
+        let go = "package store\n\nfunc Open() {}\n\nfunc open() {}\n";
+        let symbols = exported(go, "store.go", Language::Go);
+        assert_eq!(lookup(&symbols, "Open"), Some(true));
+        assert_eq!(lookup(&symbols, "open"), Some(false));
+    }
+
+    #[test]
+    fn test_java_visibility() {
+        let source = r#"
+public class Service {
+    public void start() {}
+    private void stop() {}
+    void reset() {}
+}
+"#;
+        let symbols = exported(source, "Service.java", Language::Java);
+        assert_eq!(lookup(&symbols, "Service"), Some(true));
+        assert_eq!(lookup(&symbols, "start"), Some(true));
+        assert_eq!(lookup(&symbols, "stop"), Some(false));
+        assert_eq!(lookup(&symbols, "reset"), Some(false));
+    }
+
+    #[test]
+    fn test_visibility_filter() {
+        assert!(Visibility::Public.matches(Some(true)));
+        assert!(!Visibility::Public.matches(None));
+        assert!(Visibility::Private.matches(Some(false)));
+        assert_eq!("exported".parse::<Visibility>().unwrap(), Visibility::Public);
+        assert!("internal".parse::<Visibility>().is_err());
+    }
+}
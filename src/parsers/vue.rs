@@ -15,7 +15,7 @@
 
 use anyhow::{Context, Result};
 use crate::models::{Language, SearchResult, Span, SymbolKind};
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{Query, QueryCursor};
 use streaming_iterator::StreamingIterator;
 use crate::parsers::{DependencyExtractor, ImportInfo};
 use crate::parsers::typescript::TypeScriptDependencyExtractor;
@@ -107,7 +107,7 @@ fn parse_script_block(
     script_source: &str,
     line_offset: usize,
 ) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
 
     // Use TSX parser to handle both TypeScript and JavaScript
     let ts_language: tree_sitter::Language = tree_sitter_typescript::LANGUAGE_TSX.into();
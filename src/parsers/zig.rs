@@ -12,14 +12,14 @@
 
 use anyhow::{Context, Result};
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{Query, QueryCursor};
 use crate::models::{Language, SearchResult, Span, SymbolKind};
 use crate::parsers::{DependencyExtractor, ImportInfo};
 use crate::ImportType;
 
 /// Parse Zig source code and extract symbols
 pub fn parse(path: &str, source: &str) -> Result<Vec<SearchResult>> {
-    let mut parser = Parser::new();
+    let mut parser = crate::cancel::parser();
     let language = tree_sitter_zig::LANGUAGE;
 
     parser
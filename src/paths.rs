@@ -0,0 +1,152 @@
+//! Path normalization shared by the indexer, query filters and dependency resolution
+//!
+//! The index stores paths with `/` separators on every platform. On Windows the
+//! directory walker yields `src\main.rs`, so paths are converted when they enter
+//! the index, and user-supplied filters (`--file src\`, `--glob "src\**\*.rs"`,
+//! `rfx deps src\main.rs`) are converted before they are matched. On Unix a
+//! backslash is an ordinary file name character (and a glob escape), so it is
+//! left alone there.
+
+use crate::error_code::ReflexError;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+/// Replace the platform's path separator with `/`
+pub fn to_slash(path: &str) -> Cow<'_, str> {
+    with_separator(path, MAIN_SEPARATOR)
+}
+
+/// `Path` as a `/`-separated string
+pub fn path_to_slash(path: &Path) -> String {
+    to_slash(&path.to_string_lossy()).into_owned()
+}
+
+/// `PathBuf` with `/` separators, as stored in the trigram index and content store
+pub fn slash_path(path: &Path) -> PathBuf {
+    PathBuf::from(path_to_slash(path))
+}
+
+/// Path relative to the workspace root, as stored in meta.db
+///
+/// Paths outside `root` (or already relative) keep their components, minus a
+/// leading `./`.
+pub fn relative_path(path: &Path, root: &Path) -> String {
+    let path = match path.strip_prefix(root) {
+        Ok(rel) => rel,
+        Err(_) => path,
+    };
+    let path = path_to_slash(path);
+    path.strip_prefix("./").map(str::to_string).unwrap_or(path)
+}
+
+/// `relative`, as written in the file at `base_file`, resolved against that file's directory
+///
+/// Both paths are `/`-separated and relative to the workspace root; `.` and
+/// `..` are collapsed without touching the file system. None for absolute
+/// paths and paths climbing out of the root.
+pub fn join_relative(base_file: &str, relative: &str) -> Option<String> {
+    if relative.starts_with('/') {
+        return None;
+    }
+    let dir = base_file.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut parts = Vec::new();
+    for component in dir.split('/').chain(relative.split('/')) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            component => parts.push(component),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// `dir`, resolved against `root`, if it is `root` or a directory below it
+///
+/// Both paths are canonicalized, so `..` components and symlinks can't reach
+/// outside `root`. Used to confine the `dir` parameter of `rfx serve` and
+/// `rfx mcp` requests to the directory the server was started in.
+pub fn confine_dir(root: &Path, dir: &str) -> Result<PathBuf, ReflexError> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| ReflexError::NotFound(format!("{}: {}", root.display(), e)))?;
+    let resolved = root
+        .join(dir)
+        .canonicalize()
+        .map_err(|_| ReflexError::NotFound(format!("Directory '{}' not found", dir)))?;
+    if !resolved.starts_with(&root) {
+        return Err(ReflexError::Forbidden(format!(
+            "Directory '{}' is outside the served directory",
+            dir
+        )));
+    }
+    Ok(resolved)
+}
+
+fn with_separator(path: &str, separator: char) -> Cow<'_, str> {
+    if separator != '/' && path.contains(separator) {
+        Cow::Owned(path.replace(separator, "/"))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_separators() {
+        assert_eq!(with_separator(r"src\query\mod.rs", '\\'), "src/query/mod.rs");
+        assert_eq!(with_separator(r".\src\main.rs", '\\'), "./src/main.rs");
+        assert_eq!(with_separator(r"src\**\*.rs", '\\'), "src/**/*.rs");
+        assert!(matches!(with_separator("src/main.rs", '\\'), Cow::Borrowed(_)));
+
+        // A backslash is a file name character (or glob escape) on Unix
+        assert_eq!(with_separator(r"src/a\b.rs", '/'), r"src/a\b.rs");
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/work/repo");
+        assert_eq!(relative_path(Path::new("/work/repo/src/lib.rs"), root), "src/lib.rs");
+        assert_eq!(relative_path(Path::new("./src/lib.rs"), Path::new(".")), "src/lib.rs");
+        assert_eq!(relative_path(Path::new("./src/lib.rs"), root), "src/lib.rs");
+        assert_eq!(relative_path(Path::new("/elsewhere/x.rs"), root), "/elsewhere/x.rs");
+    }
+
+    #[test]
+    fn test_join_relative() {
+        assert_eq!(join_relative("Makefile", "src/main.c").as_deref(), Some("src/main.c"));
+        assert_eq!(join_relative("lib/net/BUILD", "../core/defs.bzl").as_deref(), Some("lib/core/defs.bzl"));
+        assert_eq!(join_relative("./app/CMakeLists.txt", "./cmake/../x.cmake").as_deref(), Some("app/x.cmake"));
+        assert_eq!(join_relative("Makefile", "../outside.mk"), None);
+        assert_eq!(join_relative("Makefile", "/usr/include/x.h"), None);
+    }
+
+    #[test]
+    fn test_confine_dir() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("root");
+        std::fs::create_dir_all(root.join("repos/app")).unwrap();
+        std::fs::create_dir_all(temp.path().join("outside")).unwrap();
+        let canonical = root.canonicalize().unwrap();
+
+        assert_eq!(confine_dir(&root, ".").unwrap(), canonical);
+        assert_eq!(confine_dir(&root, "repos/app").unwrap(), canonical.join("repos/app"));
+        let absolute = canonical.join("repos").to_string_lossy().into_owned();
+        assert_eq!(confine_dir(&root, &absolute).unwrap(), canonical.join("repos"));
+
+        assert!(matches!(confine_dir(&root, "../outside"), Err(ReflexError::Forbidden(_))));
+        assert!(matches!(confine_dir(&root, "repos/../../outside"), Err(ReflexError::Forbidden(_))));
+        assert!(matches!(confine_dir(&root, "/"), Err(ReflexError::Forbidden(_))));
+        assert!(matches!(confine_dir(&root, "missing"), Err(ReflexError::NotFound(_))));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp.path().join("outside"), root.join("link")).unwrap();
+            assert!(matches!(confine_dir(&root, "link"), Err(ReflexError::Forbidden(_))));
+        }
+    }
+}
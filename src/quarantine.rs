@@ -0,0 +1,246 @@
+//! Quarantine for files that crash a parser
+//!
+//! Malformed or adversarial source files occasionally make a tree-sitter
+//! wrapper panic. Parsing goes through [`Quarantine::parse`], which catches the
+//! panic (see `ParserFactory::parse_guarded`) and records the file together
+//! with its content hash in meta.db. Quarantined files are skipped by symbol
+//! queries and the background symbol indexer until their content changes, so
+//! one bad file costs a single failed parse instead of every query.
+//!
+//! Quarantined files are listed by `rfx doctor`.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::{Language, SearchResult};
+use crate::parsers::{ParserFactory, ParserPanic};
+
+/// A file skipped because its parser panicked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedFile {
+    pub path: String,
+    /// Content hash at the time of the crash (the file is retried once it changes)
+    pub file_hash: String,
+    pub language: String,
+    /// Panic message
+    pub reason: String,
+    /// Unix timestamp of the crash
+    pub quarantined_at: i64,
+}
+
+/// A file was skipped because it is quarantined (see [`Quarantine::parse`])
+#[derive(Debug, Clone)]
+pub struct Quarantined {
+    pub path: String,
+}
+
+impl std::fmt::Display for Quarantined {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is quarantined after a parser crash (see `rfx doctor`)", self.path)
+    }
+}
+
+impl std::error::Error for Quarantined {}
+
+/// Whether `err` means the file crashed a parser or was skipped as quarantined
+///
+/// Callers should not cache an empty symbol set for such files, so they are
+/// parsed again once the file changes.
+pub fn is_quarantine_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ParserPanic>().is_some() || err.downcast_ref::<Quarantined>().is_some()
+}
+
+/// Parser crash quarantine stored in meta.db
+pub struct Quarantine {
+    db_path: PathBuf,
+    /// Snapshot of quarantined path -> hash taken at open (cheap lookups during parallel parsing)
+    entries: HashMap<String, String>,
+}
+
+impl Quarantine {
+    /// Open the quarantine at the given cache directory
+    pub fn open(cache_dir: &Path) -> Result<Self> {
+        let db_path = cache_dir.join("meta.db");
+
+        if !db_path.exists() {
+            anyhow::bail!("Cache not initialized - run 'rfx index' first");
+        }
+
+        let conn = Connection::open(&db_path).context("Failed to open meta.db")?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS parse_quarantine (
+                path TEXT PRIMARY KEY,
+                file_hash TEXT NOT NULL,
+                language TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                quarantined_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        let mut stmt = conn.prepare("SELECT path, file_hash FROM parse_quarantine")?;
+        let entries = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+
+        Ok(Self { db_path, entries })
+    }
+
+    /// Whether `path` is quarantined at this content hash
+    pub fn contains(&self, path: &str, file_hash: &str) -> bool {
+        self.entries.get(path).is_some_and(|hash| hash == file_hash)
+    }
+
+    /// Parse a file unless it is quarantined, quarantining it if the parser panics
+    ///
+    /// Without a `file_hash` the file is still protected from panics but can't be
+    /// quarantined. Errors for skipped or crashing files satisfy [`is_quarantine_error`].
+    pub fn parse(
+        &self,
+        path: &str,
+        file_hash: Option<&str>,
+        source: &str,
+        language: Language,
+    ) -> Result<Vec<SearchResult>> {
+        if let Some(hash) = file_hash
+            && self.contains(path, hash)
+        {
+            log::debug!("Skipping quarantined file: {}", path);
+            return Err(Quarantined { path: path.to_string() }.into());
+        }
+
+        let result = ParserFactory::parse_guarded(path, source, language);
+
+        if let Err(e) = &result
+            && let Some(panic) = e.downcast_ref::<ParserPanic>()
+        {
+            log::warn!("Parser crashed on {}, quarantining until it changes: {}", path, panic.message);
+            if let Some(hash) = file_hash
+                && let Err(record_err) = self.record(path, hash, language, &panic.message)
+            {
+                log::warn!("Failed to quarantine {}: {}", path, record_err);
+            }
+        }
+
+        result
+    }
+
+    /// Record a crashing file
+    pub fn record(&self, path: &str, file_hash: &str, language: Language, reason: &str) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        let now = chrono::Utc::now().timestamp();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO parse_quarantine (path, file_hash, language, reason, quarantined_at)
+             VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![path, file_hash, format!("{:?}", language), reason, now],
+        )?;
+
+        Ok(())
+    }
+
+    /// List quarantined files, sorted by path
+    ///
+    /// Entries whose file has since changed are still listed until the file is
+    /// parsed successfully (or the quarantine is cleared).
+    pub fn list(&self) -> Result<Vec<QuarantinedFile>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT path, file_hash, language, reason, quarantined_at FROM parse_quarantine ORDER BY path",
+        )?;
+
+        let files = stmt
+            .query_map([], |row| {
+                Ok(QuarantinedFile {
+                    path: row.get(0)?,
+                    file_hash: row.get(1)?,
+                    language: row.get(2)?,
+                    reason: row.get(3)?,
+                    quarantined_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(files)
+    }
+
+    /// Remove a file from the quarantine (returns whether it was quarantined)
+    pub fn release(&self, path: &str) -> Result<bool> {
+        let conn = Connection::open(&self.db_path)?;
+        let removed = conn.execute("DELETE FROM parse_quarantine WHERE path = ?", [path])?;
+        Ok(removed > 0)
+    }
+
+    /// Drop entries for files that have changed or are no longer indexed
+    ///
+    /// `current_hashes` maps indexed paths to their current content hash.
+    pub fn prune(&self, current_hashes: &HashMap<String, String>) -> Result<usize> {
+        let stale: Vec<&String> = self
+            .entries
+            .iter()
+            .filter(|(path, hash)| current_hashes.get(*path) != Some(*hash))
+            .map(|(path, _)| path)
+            .collect();
+
+        for path in &stale {
+            self.release(path)?;
+        }
+
+        Ok(stale.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use tempfile::TempDir;
+
+    fn open_quarantine(temp: &TempDir) -> Quarantine {
+        let cache_mgr = CacheManager::new(temp.path());
+        cache_mgr.init().unwrap();
+        Quarantine::open(cache_mgr.path()).unwrap()
+    }
+
+    #[test]
+    fn test_quarantined_until_hash_changes() {
+        let temp = TempDir::new().unwrap();
+        let quarantine = open_quarantine(&temp);
+        quarantine.record("src/bad.rs", "hash1", Language::Rust, "boom").unwrap();
+
+        // Reopen to pick up the new entry
+        let quarantine = Quarantine::open(&temp.path().join(".reflex")).unwrap();
+        assert!(quarantine.contains("src/bad.rs", "hash1"));
+        assert!(!quarantine.contains("src/bad.rs", "hash2"));
+
+        let err = quarantine.parse("src/bad.rs", Some("hash1"), "fn ok() {}", Language::Rust).unwrap_err();
+        assert!(is_quarantine_error(&err));
+
+        // Changed content is parsed again
+        let symbols = quarantine.parse("src/bad.rs", Some("hash2"), "fn ok() {}", Language::Rust).unwrap();
+        assert_eq!(symbols.len(), 1);
+    }
+
+    #[test]
+    fn test_list_release_and_prune() {
+        let temp = TempDir::new().unwrap();
+        let quarantine = open_quarantine(&temp);
+        quarantine.record("b.py", "h1", Language::Python, "boom").unwrap();
+        quarantine.record("a.rs", "h2", Language::Rust, "bang").unwrap();
+
+        let files = quarantine.list().unwrap();
+        assert_eq!(files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["a.rs", "b.py"]);
+        assert_eq!(files[0].reason, "bang");
+
+        assert!(quarantine.release("a.rs").unwrap());
+        assert!(!quarantine.release("a.rs").unwrap());
+
+        let quarantine = Quarantine::open(&temp.path().join(".reflex")).unwrap();
+        let current = HashMap::from([("b.py".to_string(), "changed".to_string())]);
+        assert_eq!(quarantine.prune(&current).unwrap(), 1);
+        assert!(quarantine.list().unwrap().is_empty());
+    }
+}
@@ -5,25 +5,40 @@
 
 use anyhow::{Context, Result};
 use regex::Regex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
 
-use crate::cache::CacheManager;
+use crate::cache::{CacheManager, RootInfo};
+use crate::cancel::CancelToken;
 use crate::content_store::ContentReader;
+use crate::error_code::ReflexError;
 use crate::models::{
-    IndexStatus, IndexWarning, IndexWarningDetails, Language, QueryResponse, SearchResult, Span,
-    SymbolKind,
+    IndexStatus, IndexWarning, IndexWarningDetails, Language, MatchMethod, MatchReason, PartialStatus, QueryPlan,
+    QueryResponse, QueryTimings, SearchResult, Span, SymbolKind,
 };
 use crate::output;
+use crate::overlay::Overlay;
 use crate::parsers::ParserFactory;
+use crate::parsers::span::{apply_span_policy, SpanPolicy};
+use crate::parsers::visibility::Visibility;
 use crate::regex_trigrams::extract_trigrams_from_regex;
 use crate::trigram::TrigramIndex;
 
+/// Lines of context loaded before/after each match when not overridden by the filter
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
 /// Query filter options
 #[derive(Debug, Clone)]
 pub struct QueryFilter {
     /// Language filter (None = all languages)
     pub language: Option<Language>,
-    /// Symbol kind filter (None = all kinds)
+    /// Symbol kind filter (None = all kinds), kept alongside `kinds` for existing callers
+    #[deprecated(note = "Use `kinds`, which takes several kinds (a kind set here joins them)")]
     pub kind: Option<SymbolKind>,
+    /// Symbol kinds to keep (empty = all kinds); see [`QueryFilter::matches_kind`]
+    pub kinds: Vec<SymbolKind>,
+    /// Symbol kinds to leave out (`--kind-not`)
+    pub exclude_kinds: Vec<SymbolKind>,
     /// Use AST pattern matching (vs lexical search)
     pub use_ast: bool,
     /// Use regex pattern matching
@@ -40,22 +55,72 @@ pub struct QueryFilter {
     pub exact: bool,
     /// Use substring matching instead of word-boundary matching (opt-in, expansive)
     pub use_contains: bool,
+    /// Rank symbols by name similarity to the pattern (implies symbol search)
+    pub fuzzy: bool,
+    /// Minimum similarity of a fuzzy match, 0.0-1.0 (None = `[search] fuzzy_threshold`)
+    pub fuzzy_threshold: Option<f64>,
+    /// Match identifiers containing every word part of the pattern, across naming
+    /// conventions (`user config` finds `parseUserConfig` and `load_user_config`)
+    pub tokens: bool,
+    /// Rank embedded chunks by meaning and keyword hits (requires `rfx embed`; see `embeddings`)
+    pub semantic: bool,
     /// Query timeout in seconds (0 = no timeout)
     pub timeout_secs: u64,
+    /// Best-effort time budget in milliseconds (None = scan every candidate)
+    ///
+    /// Unlike `timeout_secs`, an expired budget doesn't fail the query: candidates
+    /// that haven't been verified yet are skipped and reported as unscanned.
+    pub budget_ms: Option<u64>,
+    /// Memory ceiling of the query in MB, 0 = unlimited (None = `[search] max_memory_mb`)
+    pub max_memory_mb: Option<u64>,
+    /// Only search files changed since this git ref (merge-base aware, includes uncommitted files)
+    pub changed_since: Option<String>,
+    /// Restrict matches to the line span of a named symbol (`Symbol`, `Outer.inner`
+    /// or `path:Symbol`), resolved via the symbol cache
+    pub scope: Option<String>,
+    /// Only keep symbols whose qualified name ends with this path (`Worker::run`,
+    /// `Worker.run`); see `parsers::qualify`
+    pub qualified: Option<String>,
+    /// Only keep exported (`Public`) or local (`Private`) symbols; see `parsers::visibility`
+    pub visibility: Option<Visibility>,
+    /// Only search files owned by this CODEOWNERS entry (`@team-payments`); see `owners`
+    pub owner: Option<String>,
+    /// Only search files touching this database table or ORM model (`users`, `User`); see `tables`
+    pub table: Option<String>,
+    /// Annotate each file with its CODEOWNERS owners
+    pub with_owners: bool,
+    /// Annotate each match with the last commit touching its span (see `blame`)
+    pub blame: bool,
     /// Glob patterns to include (empty = all files)
     pub glob_patterns: Vec<String>,
     /// Glob patterns to exclude (applied after includes)
     pub exclude_patterns: Vec<String>,
+    /// Drop candidate lines containing any of these patterns (`--not`; regexes with `use_regex`)
+    pub not_patterns: Vec<String>,
     /// Return only unique file paths (deduplicated)
     pub paths_only: bool,
     /// Pagination offset (skip first N results after sorting)
     pub offset: Option<usize>,
     /// Force execution of potentially expensive queries (bypass broad query detection)
     pub force: bool,
+    /// Report how the query was executed (`QueryResponse::plan` and `timings`)
+    pub explain: bool,
     /// Suppress warning/info output (for --json mode to ensure pure JSON output)
     pub suppress_output: bool,
     /// Include dependency information in results
     pub include_dependencies: bool,
+    /// Attach up to N related files per relation to each result file (None = off)
+    pub related_files: Option<usize>,
+    /// Also report tombstones of removed files whose path or last symbols match
+    pub include_deleted: bool,
+    /// Include symbol doc comments/docstrings in results
+    pub with_docs: bool,
+    /// How far symbol spans reach above the declaration (attributes, doc comments)
+    pub span_policy: SpanPolicy,
+    /// Lines of context before each match (None = DEFAULT_CONTEXT_LINES)
+    pub before_context: Option<usize>,
+    /// Lines of context after each match (None = DEFAULT_CONTEXT_LINES)
+    pub after_context: Option<usize>,
     /// Test-only: Override large index threshold (None = use default of 20,000)
     #[doc(hidden)]
     pub test_large_index_threshold: Option<usize>,
@@ -65,10 +130,13 @@ pub struct QueryFilter {
 }
 
 impl Default for QueryFilter {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             language: None,
-            kind: None,
+            kind: None,  // Deprecated: see `kinds`
+            kinds: Vec::new(),  // Default: all kinds
+            exclude_kinds: Vec::new(),
             use_ast: false,
             use_regex: false,
             limit: Some(100),  // Default: limit to 100 results for token efficiency
@@ -77,20 +145,513 @@ impl Default for QueryFilter {
             file_pattern: None,
             exact: false,
             use_contains: false,  // Default: word-boundary matching
+            fuzzy: false,  // Default: literal name matching
+            fuzzy_threshold: None,  // Default: [search] fuzzy_threshold
+            tokens: false,  // Default: trigram matching
+            semantic: false,  // Default: literal matching
             timeout_secs: 30, // 30 seconds default timeout
+            budget_ms: None,  // Default: complete results
+            max_memory_mb: None,  // Default: [search] max_memory_mb
+            changed_since: None,  // Default: search all files
+            scope: None,  // Default: match anywhere in the file
+            qualified: None,  // Default: any enclosing symbols
+            visibility: None,  // Default: exported and local symbols
+            owner: None,  // Default: files of any owner
+            table: None,  // Default: files touching any table
+            with_owners: false,  // Default: no owner annotations
+            blame: false,  // Default: no blame annotations
             glob_patterns: Vec::new(),
             exclude_patterns: Vec::new(),
+            not_patterns: Vec::new(),  // Default: no exclusion terms
             paths_only: false,
             offset: None,
             force: false,  // Default: enable broad query detection
+            explain: false,  // Default: no query plan
             suppress_output: false,  // Default: show warnings/info
             include_dependencies: false,  // Default: don't load dependencies for performance
+            related_files: None,  // Default: no related-file lookups
+            include_deleted: false,  // Default: live files only
+            with_docs: false,  // Default: omit docs for token efficiency
+            span_policy: SpanPolicy::Body,  // Default: definition only
+            before_context: None,  // Default: DEFAULT_CONTEXT_LINES
+            after_context: None,  // Default: DEFAULT_CONTEXT_LINES
             test_large_index_threshold: None,  // Default: use production threshold (20,000)
             test_short_pattern_threshold: None,  // Default: use production threshold (4)
         }
     }
 }
 
+impl QueryFilter {
+    /// Whether the query filters by symbol kind (which implies symbol search)
+    pub fn filters_kinds(&self) -> bool {
+        !self.kinds.is_empty() || !self.exclude_kinds.is_empty()
+    }
+
+    /// Whether a symbol of `kind` passes `--kind` and `--kind-not`
+    ///
+    /// `Function` also matches methods (methods are functions in classes).
+    pub fn matches_kind(&self, kind: &SymbolKind) -> bool {
+        let is = |wanted: &SymbolKind| {
+            wanted == kind || (*wanted == SymbolKind::Function && *kind == SymbolKind::Method)
+        };
+        (self.kinds.is_empty() || self.kinds.iter().any(is)) && !self.exclude_kinds.iter().any(is)
+    }
+
+    /// Stored kinds to keep and to leave out, as the symbol cache filters them
+    ///
+    /// `Function` brings in `Method`, as in [`QueryFilter::matches_kind`].
+    pub(crate) fn stored_kinds(&self) -> (Vec<SymbolKind>, Vec<SymbolKind>) {
+        let expand = |kinds: &[SymbolKind]| {
+            let mut stored = Vec::new();
+            for kind in kinds {
+                if *kind == SymbolKind::Function {
+                    stored.push(SymbolKind::Method);
+                }
+                stored.push(kind.clone());
+            }
+            stored
+        };
+        (
+            expand(&self.kinds),
+            expand(&self.exclude_kinds),
+        )
+    }
+
+    /// Move the deprecated `kind` field into `kinds`
+    ///
+    /// Done once by each public search entry point; everything after reads `kinds` only.
+    fn merge_legacy_kind(&mut self) {
+        #[allow(deprecated)]
+        if let Some(kind) = self.kind.take()
+            && !self.kinds.contains(&kind)
+        {
+            self.kinds.push(kind);
+        }
+    }
+}
+
+/// Time budget of a best-effort query (see [`QueryFilter::budget_ms`])
+///
+/// Candidate files still unverified when the deadline passes are skipped
+/// and counted, so the response can report how incomplete it is.
+struct Budget {
+    deadline: std::time::Instant,
+    unscanned: AtomicUsize,
+}
+
+impl Budget {
+    fn from_filter(filter: &QueryFilter) -> Option<Self> {
+        filter.budget_ms.map(|ms| Self {
+            deadline: std::time::Instant::now() + std::time::Duration::from_millis(ms),
+            unscanned: AtomicUsize::new(0),
+        })
+    }
+
+    /// Whether one more candidate file may be scanned (counts it as unscanned otherwise)
+    fn admit(&self) -> bool {
+        if std::time::Instant::now() < self.deadline {
+            return true;
+        }
+        self.unscanned.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Number of candidate files skipped after the deadline
+    fn unscanned(&self) -> usize {
+        self.unscanned.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether a candidate file may be scanned within `budget` (always true without one)
+fn within_budget(budget: Option<&Budget>) -> bool {
+    budget.is_none_or(Budget::admit)
+}
+
+/// Smallest parse or AST batch, so a nearly exhausted ceiling still makes progress
+const MIN_BATCH_BYTES: u64 = 1024 * 1024;
+
+/// Memory ceiling of one query (see `[search] max_memory_mb`)
+///
+/// Tracks an estimate of what the query holds at once: buffered candidate
+/// and symbol results, copied file contents and the files of the parse batch
+/// in flight. As the ceiling gets closer, parsing and AST matching run in
+/// smaller batches; once it is reached, remaining candidate files are skipped
+/// and the response is marked `MEMORY_PARTIAL` instead of the process running
+/// out of memory.
+struct MemoryGuard {
+    limit: Option<u64>,
+    used: AtomicU64,
+    high_water: AtomicU64,
+    exhausted: AtomicBool,
+}
+
+impl MemoryGuard {
+    fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            used: AtomicU64::new(0),
+            high_water: AtomicU64::new(0),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    /// Account for `bytes` more held by the query
+    fn charge(&self, bytes: u64) {
+        let used = self.used.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.high_water.fetch_max(used, Ordering::Relaxed);
+    }
+
+    /// Account for `bytes` released by the query
+    fn release(&self, bytes: u64) {
+        let _ = self.used.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| Some(used.saturating_sub(bytes)));
+    }
+
+    /// Whether one more candidate file or batch may be loaded (marks the guard exhausted otherwise)
+    fn admit(&self) -> bool {
+        match self.limit {
+            Some(limit) if self.used.load(Ordering::Relaxed) >= limit => {
+                self.exhausted.store(true, Ordering::Relaxed);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Content size of the next parse or AST batch: half the room left, at least `MIN_BATCH_BYTES`
+    fn batch_bytes(&self) -> u64 {
+        match self.limit {
+            Some(limit) => (limit.saturating_sub(self.used.load(Ordering::Relaxed)) / 2).max(MIN_BATCH_BYTES),
+            None => u64::MAX,
+        }
+    }
+
+    /// Number of leading files of `sizes` that make up the next batch (at least one)
+    fn next_batch(&self, sizes: &[u64]) -> usize {
+        let budget = self.batch_bytes();
+        let mut total = 0u64;
+        let count = sizes.iter().take_while(|&&size| {
+            total = total.saturating_add(size);
+            total <= budget
+        }).count();
+        count.max(1).min(sizes.len())
+    }
+
+    /// Most memory held at once so far
+    fn high_water(&self) -> u64 {
+        self.high_water.load(Ordering::Relaxed)
+    }
+
+    /// Whether the ceiling cut the query short
+    fn exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+}
+
+/// Estimated heap size of buffered results
+fn results_bytes(results: &[SearchResult]) -> u64 {
+    results.iter().map(|r| {
+        std::mem::size_of::<SearchResult>()
+            + r.path.len()
+            + r.preview.len()
+            + r.symbol.as_ref().map_or(0, String::len)
+            + r.signature.as_ref().map_or(0, String::len)
+            + r.doc.as_ref().map_or(0, String::len)
+    }).sum::<usize>() as u64
+}
+
+/// Cancellation token for the query's `--timeout` (never cancels without one)
+fn cancel_token(filter: &QueryFilter) -> CancelToken {
+    let timeout = (filter.timeout_secs > 0).then(|| std::time::Duration::from_secs(filter.timeout_secs));
+    CancelToken::with_timeout(timeout)
+}
+
+/// Why the query is partial: `TIMEOUT_PARTIAL` if `cancel` cut it short,
+/// otherwise `MEMORY_PARTIAL` if `memory` did
+fn partial_status(cancel: &CancelToken, memory: &MemoryGuard) -> Option<PartialStatus> {
+    if cancel.was_cancelled() {
+        Some(PartialStatus::TimeoutPartial)
+    } else if memory.exhausted() {
+        Some(PartialStatus::MemoryPartial)
+    } else {
+        None
+    }
+}
+
+/// Warn on stderr that `--timeout` cut a query short
+fn warn_timeout_partial(filter: &QueryFilter) {
+    if !filter.suppress_output {
+        output::warn(&format!(
+            "Query timeout exceeded ({} seconds): results are partial (files not parsed in time were skipped).",
+            filter.timeout_secs
+        ));
+    }
+}
+
+/// Warn on stderr that `[search] max_memory_mb` cut a query short
+fn warn_memory_partial(filter: &QueryFilter, memory: &MemoryGuard) {
+    if memory.exhausted() && !filter.suppress_output {
+        output::warn(&format!(
+            "Query memory ceiling reached ({} MB): results are partial (files that didn't fit were skipped). Raise [search] max_memory_mb or narrow the pattern.",
+            memory.limit.unwrap_or(0) / (1024 * 1024)
+        ));
+    }
+}
+
+/// Plan and phase timings of an `--explain` query (see [`QueryFilter::explain`])
+#[derive(Default)]
+struct Trace {
+    plan: Mutex<QueryPlan>,
+    timings: Mutex<QueryTimings>,
+}
+
+impl Trace {
+    fn from_filter(filter: &QueryFilter) -> Option<Self> {
+        filter.explain.then(Self::default)
+    }
+
+    fn into_parts(self) -> (QueryPlan, QueryTimings) {
+        (
+            self.plan.into_inner().unwrap_or_default(),
+            self.timings.into_inner().unwrap_or_default(),
+        )
+    }
+}
+
+/// Update the query plan (no-op unless the query is explained)
+fn record_plan(trace: Option<&Trace>, update: impl FnOnce(&mut QueryPlan)) {
+    if let Some(mut plan) = trace.and_then(|t| t.plan.lock().ok()) {
+        update(&mut plan);
+    }
+}
+
+/// Add the time since `phase_start` to one phase and start the next phase
+fn record_phase(trace: Option<&Trace>, phase_start: &mut std::time::Instant, phase: impl FnOnce(&mut QueryTimings) -> &mut f64) {
+    if let Some(mut timings) = trace.and_then(|t| t.timings.lock().ok()) {
+        *phase(&mut timings) += phase_start.elapsed().as_secs_f64() * 1000.0;
+    }
+    *phase_start = std::time::Instant::now();
+}
+
+/// Files a `--changed-since` query is restricted to (see [`QueryFilter::changed_since`])
+struct ChangedFiles {
+    root: std::path::PathBuf,
+    /// Workspace-relative paths
+    paths: std::collections::HashSet<String>,
+}
+
+impl ChangedFiles {
+    fn contains(&self, path: &std::path::Path) -> bool {
+        self.paths.contains(&crate::paths::relative_path(path, &self.root))
+    }
+}
+
+/// Whether `path` is in scope of a `--changed-since` restriction (always true without one)
+fn in_changed_files(changed: Option<&ChangedFiles>, path: &std::path::Path) -> bool {
+    changed.is_none_or(|c| c.contains(path))
+}
+
+/// CODEOWNERS rules recorded by the last `rfx index` (see [`crate::owners`])
+struct FileOwners {
+    root: std::path::PathBuf,
+    matcher: crate::owners::OwnerMatcher,
+}
+
+impl FileOwners {
+    fn owners_of(&self, path: &str) -> &[String] {
+        self.matcher.owners_of(&crate::paths::relative_path(std::path::Path::new(path), &self.root))
+    }
+
+    /// Drop results outside the files owned by `owner`
+    fn retain_owned(&self, results: &mut Vec<SearchResult>, owner: &str) {
+        let before = results.len();
+        results.retain(|r| self.owners_of(&r.path).iter().any(|o| crate::owners::owner_matches(o, owner)));
+        log::debug!("Owner filter '{}': reduced {} candidates to {}", owner, before, results.len());
+    }
+}
+
+/// Split a `--in` scope into an optional path filter and the symbol name
+///
+/// `src/query.rs:search` scopes to files whose path contains `src/query.rs`;
+/// a `::` (as in `Engine::search`) belongs to the name, not the path.
+fn parse_scope(scope: &str) -> (Option<&str>, &str) {
+    let bytes = scope.as_bytes();
+    let separator = (0..bytes.len()).find(|&i| {
+        bytes[i] == b':'
+            && (i == 0 || bytes[i - 1] != b':')
+            && bytes.get(i + 1) != Some(&b':')
+    });
+
+    match separator {
+        Some(i) if i > 0 => (Some(&scope[..i]), &scope[i + 1..]),
+        Some(_) => (None, &scope[1..]),
+        None => (None, scope),
+    }
+}
+
+/// Spans of the symbols matching a (possibly qualified) scope name
+///
+/// The last segment must name the symbol; each earlier segment must name a
+/// symbol whose span encloses it, so `Engine.search` skips a free `search`.
+fn scope_spans(symbols: &[SearchResult], segments: &[&str]) -> Vec<Span> {
+    let Some((name, parents)) = segments.split_last() else {
+        return Vec::new();
+    };
+
+    symbols
+        .iter()
+        .filter(|s| s.symbol.as_deref() == Some(*name))
+        .filter(|s| {
+            parents.iter().all(|parent| {
+                symbols.iter().any(|p| {
+                    p.symbol.as_deref() == Some(*parent)
+                        && p.span.start_line <= s.span.start_line
+                        && s.span.end_line <= p.span.end_line
+                        && !std::ptr::eq(p, *s)
+                })
+            })
+        })
+        .map(|s| s.span.clone())
+        .collect()
+}
+
+/// Builds the `match_reason` of each result of one query
+///
+/// The match method follows from the query mode; only symbol searches need the
+/// result itself (exact name vs substring). Filters are the same for every result.
+pub(crate) struct MatchExplainer {
+    pattern: String,
+    mode: MatchMethod,
+    filters: Vec<String>,
+}
+
+impl MatchExplainer {
+    pub(crate) fn new(pattern: &str, filter: &QueryFilter) -> Self {
+        let symbol_search = filter.symbols_mode || filter.filters_kinds();
+        let is_keyword_query = symbol_search && !filter.fuzzy && !filter.tokens && ParserFactory::get_all_keywords().contains(&pattern);
+
+        let mode = if filter.semantic {
+            MatchMethod::Semantic
+        } else if filter.fuzzy {
+            MatchMethod::Fuzzy
+        } else if filter.tokens {
+            MatchMethod::IdentifierTokens
+        } else if filter.use_ast {
+            MatchMethod::AstCapture
+        } else if filter.use_regex {
+            MatchMethod::Regex
+        } else if is_keyword_query {
+            MatchMethod::KeywordListing
+        } else if symbol_search {
+            MatchMethod::SymbolSubstring
+        } else if filter.use_contains {
+            MatchMethod::Substring
+        } else {
+            MatchMethod::WordBoundary
+        };
+
+        let mut kinds: Vec<SymbolKind> = filter.kinds.clone();
+        if kinds.is_empty() && is_keyword_query {
+            kinds.extend(QueryEngine::keyword_to_kind(pattern));
+        }
+
+        let mut filters = Vec::new();
+        if let Some(lang) = filter.language {
+            filters.push(format!("lang={}", format!("{:?}", lang).to_lowercase()));
+        }
+        if !kinds.is_empty() {
+            filters.push(format!("kind={}", kinds.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")));
+        }
+        if !filter.exclude_kinds.is_empty() {
+            filters.push(format!("kind_not={}", filter.exclude_kinds.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")));
+        }
+        if let Some(file) = &filter.file_pattern {
+            filters.push(format!("file={}", file));
+        }
+        filters.extend(filter.glob_patterns.iter().map(|g| format!("glob={}", g)));
+        filters.extend(filter.exclude_patterns.iter().map(|g| format!("exclude={}", g)));
+        filters.extend(filter.not_patterns.iter().map(|p| format!("not={}", p)));
+        if let Some(rev) = &filter.changed_since {
+            filters.push(format!("changed_since={}", rev));
+        }
+        if let Some(scope) = &filter.scope {
+            filters.push(format!("in={}", scope));
+        }
+        if let Some(qualified) = &filter.qualified {
+            filters.push(format!("qualified={}", qualified));
+        }
+        if let Some(owner) = &filter.owner {
+            filters.push(format!("owner={}", owner));
+        }
+        if let Some(table) = &filter.table {
+            filters.push(format!("table={}", table));
+        }
+
+        Self {
+            pattern: pattern.to_string(),
+            mode,
+            filters,
+        }
+    }
+
+    pub(crate) fn explain(&self, result: &SearchResult) -> MatchReason {
+        let method = match self.mode {
+            MatchMethod::SymbolSubstring | MatchMethod::KeywordListing
+                if result.symbol.as_deref() == Some(self.pattern.as_str()) =>
+            {
+                MatchMethod::ExactSymbol
+            }
+            mode => mode,
+        };
+
+        let score = match method {
+            MatchMethod::Fuzzy => Some(crate::symbol_cache::fuzzy_score(&self.pattern, result.symbol.as_deref().unwrap_or(""))),
+            MatchMethod::Semantic => result.score,
+            _ => None,
+        };
+
+        MatchReason {
+            method,
+            filters: self.filters.clone(),
+            score,
+        }
+    }
+}
+
+/// One line of NDJSON output produced by [`QueryEngine::search_streaming`]
+///
+/// A stream always starts with a `header`, carries one `file` record per matching
+/// file (sorted by path), and ends with a `summary` holding the pagination info.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueryStreamRecord {
+    /// Index freshness information, emitted before any results
+    Header {
+        status: IndexStatus,
+        can_trust_results: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        warning: Option<IndexWarning>,
+    },
+    /// All matches for a single file
+    File(crate::models::FileGroupedResult),
+    /// Pagination metadata, emitted after the last file
+    Summary {
+        pagination: crate::models::PaginationInfo,
+        /// False when the time budget expired before every candidate was scanned,
+        /// or `--timeout` cut parsing short
+        #[serde(default = "crate::models::default_complete")]
+        complete: bool,
+        /// Candidate files skipped because the time budget expired
+        #[serde(default, skip_serializing_if = "crate::models::is_zero")]
+        unscanned_candidates: usize,
+        /// `TIMEOUT_PARTIAL` when the query timed out after its candidates were found
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        partial: Option<PartialStatus>,
+        /// Deprecation and behavior-change notices (populated by the CLI layer)
+        #[serde(default)]
+        warnings: Vec<crate::models::CliWarning>,
+    },
+}
+
 /// Manages query execution against the index
 pub struct QueryEngine {
     cache: CacheManager,
@@ -98,7 +659,12 @@ pub struct QueryEngine {
 
 impl QueryEngine {
     /// Create a new query engine with the given cache manager
+    ///
+    /// Installs the project's `[index.extensions]` mappings and `[sql]` dialect
+    /// so results get the same languages and symbols the indexer produced.
     pub fn new(cache: CacheManager) -> Self {
+        crate::indexer::install_extension_overrides(cache.path(), &std::collections::HashMap::new());
+        crate::parsers::sql::install_dialect(cache.path());
         Self { cache }
     }
 
@@ -158,10 +724,12 @@ impl QueryEngine {
     fn group_and_load_dependencies(
         &self,
         results: Vec<SearchResult>,
-        include_deps: bool,
+        filter: &QueryFilter,
+        context: (usize, usize),
+        explainer: &MatchExplainer,
     ) -> Result<Vec<crate::models::FileGroupedResult>> {
         use std::collections::HashMap;
-        use crate::models::{FileGroupedResult, MatchResult};
+        use crate::models::FileGroupedResult;
 
         if results.is_empty() {
             return Ok(Vec::new());
@@ -176,15 +744,11 @@ impl QueryEngine {
                 .push(result);
         }
 
-        // Create dependency index if needed
-        let dep_index = if include_deps {
-            let workspace_root = self.cache.path().parent()
-                .ok_or_else(|| anyhow::anyhow!("Cache path has no parent"))?;
-            let cache_for_deps = CacheManager::new(workspace_root);
-            Some(crate::dependency::DependencyIndex::new(cache_for_deps))
-        } else {
-            None
-        };
+        let dep_index = self.dependency_index(filter.include_dependencies)?;
+        let related = self.related_finder(filter.related_files)?;
+        let owners = if filter.with_owners { self.file_owners(false)? } else { None };
+        let blame = self.blame_cache(filter.blame)?;
+        let transforms = self.file_transforms();
 
         // Load ContentReader for extracting context lines
         let content_path = self.cache.path().join("content.bin");
@@ -194,88 +758,11 @@ impl QueryEngine {
         let mut file_results: Vec<FileGroupedResult> = grouped
             .into_iter()
             .map(|(path, file_matches)| {
-                // Load dependencies for this file (once per file, not per result)
-                let dependencies = if let Some(dep_idx) = &dep_index {
-                    let normalized_path = path.strip_prefix("./").unwrap_or(&path);
-                    match self.cache.get_file_id(normalized_path) {
-                        Ok(Some(file_id)) => {
-                            match dep_idx.get_dependencies_info(file_id) {
-                                Ok(dep_infos) if !dep_infos.is_empty() => {
-                                    log::debug!("Loaded {} dependencies for file: {}", dep_infos.len(), path);
-                                    Some(dep_infos)
-                                }
-                                Ok(_) => None,
-                                Err(e) => {
-                                    log::warn!("Failed to get dependencies for {}: {}", path, e);
-                                    None
-                                }
-                            }
-                        }
-                        Ok(None) => {
-                            log::warn!("No file_id found for path: {}", path);
-                            None
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to get file_id for path {}: {}", path, e);
-                            None
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                // Get file_id for context extraction
-                // Note: We use ContentReader's get_file_id_by_path() which returns array indices,
-                // not database file_ids (which are AUTO INCREMENT values)
-                let normalized_path = path.strip_prefix("./").unwrap_or(&path);
-                let file_id_for_context = if let Some(reader) = &content_reader_opt {
-                    reader.get_file_id_by_path(normalized_path)
-                } else {
-                    None
-                };
-                log::debug!("Context extraction: file={}, file_id={:?}, content_reader={}",
-                    path, file_id_for_context, content_reader_opt.is_some());
-
-                // Convert SearchResults to MatchResults (strip path and dependencies) and extract context
-                let matches: Vec<MatchResult> = file_matches
-                    .into_iter()
-                    .map(|r| {
-                        // Extract context lines (default: 3 lines before and after)
-                        let (context_before, context_after) = if let (Some(reader), Some(fid)) = (&content_reader_opt, file_id_for_context) {
-                            let result = reader.get_context_by_line(fid as u32, r.span.start_line, 3)
-                                .unwrap_or_else(|e| {
-                                    log::warn!("Failed to extract context for {}:{}: {}", path, r.span.start_line, e);
-                                    (vec![], vec![])
-                                });
-                            log::debug!("Extracted context for {}:{} - before: {}, after: {}",
-                                path, r.span.start_line, result.0.len(), result.1.len());
-                            result
-                        } else {
-                            if content_reader_opt.is_none() {
-                                log::debug!("No ContentReader available for context extraction");
-                            }
-                            if file_id_for_context.is_none() {
-                                log::debug!("No file_id found for {}", path);
-                            }
-                            (vec![], vec![])
-                        };
-
-                        MatchResult {
-                            kind: r.kind,
-                            symbol: r.symbol,
-                            span: r.span,
-                            preview: r.preview,
-                            context_before,
-                            context_after,
-                        }
-                    })
-                    .collect();
-
-                FileGroupedResult {
-                    path,
-                    dependencies,
-                    matches,
+                let mut group = self.build_file_group(path, file_matches, dep_index.as_ref(), related.as_ref(), owners.as_ref(), &transforms, content_reader_opt.as_ref(), context, explainer);
+                if let Some(ref blame) = blame {
+                    blame.annotate(&mut group);
                 }
+                group
             })
             .collect();
 
@@ -285,85 +772,506 @@ impl QueryEngine {
         Ok(file_results)
     }
 
-    /// Execute a query and return matching results with index metadata
-    ///
-    /// This is the preferred method for programmatic/JSON output as it includes
-    /// index freshness information that AI agents can use to decide whether to re-index.
-    pub fn search_with_metadata(&self, pattern: &str, filter: QueryFilter) -> Result<QueryResponse> {
-        log::info!("Executing query with metadata: pattern='{}', filter={:?}", pattern, filter);
-
-        // Ensure cache exists
-        if !self.cache.exists() {
-            anyhow::bail!(
-                "Index not found. Run 'rfx index' to build the cache first."
-            );
-        }
-
-        // Validate cache integrity
-        if let Err(e) = self.cache.validate() {
-            anyhow::bail!(
-                "Cache appears to be corrupted: {}. Run 'rfx clear' followed by 'rfx index' to rebuild.",
-                e
-            );
+    /// Create a dependency index rooted at the workspace when dependencies are requested
+    fn dependency_index(&self, include_deps: bool) -> Result<Option<crate::dependency::DependencyIndex>> {
+        if !include_deps {
+            return Ok(None);
         }
 
-        // Get index status and warning (without printing warnings to stderr)
-        let (status, can_trust_results, warning) = self.get_index_status()?;
-
-        // Execute the search
-        let (results, total) = self.search_internal(pattern, filter.clone())?;
+        let workspace_root = self.cache.path().parent()
+            .ok_or_else(|| anyhow::anyhow!("Cache path has no parent"))?;
+        let cache_for_deps = CacheManager::new(workspace_root);
+        Ok(Some(crate::dependency::DependencyIndex::new(cache_for_deps)))
+    }
 
-        // Build pagination metadata
-        use crate::models::PaginationInfo;
-        let pagination = PaginationInfo {
-            total,
-            count: results.len(),
-            offset: filter.offset.unwrap_or(0),
-            limit: filter.limit,
-            has_more: total > filter.offset.unwrap_or(0) + results.len(),
+    /// Build the related-file finder when related files are requested
+    fn related_finder(&self, related_files: Option<usize>) -> Result<Option<crate::related::RelatedFinder>> {
+        let Some(limit) = related_files else {
+            return Ok(None);
         };
 
-        // Always use grouped format (group results by file)
-        // Dependencies are loaded only when include_dependencies is true
-        let grouped_results = self.group_and_load_dependencies(results, filter.include_dependencies)?;
-
-        Ok(QueryResponse {
-            ai_instruction: None,  // AI instruction is generated by CLI/MCP layer, not here
-            status,
-            can_trust_results,
-            warning,
-            pagination,
-            results: grouped_results,
-        })
+        let workspace_root = self.cache.path().parent()
+            .ok_or_else(|| anyhow::anyhow!("Cache path has no parent"))?;
+        Ok(Some(crate::related::RelatedFinder::new(CacheManager::new(workspace_root), limit)?))
     }
 
-    /// Execute a query and return matching results (legacy method)
+    /// Load the CODEOWNERS rules recorded at index time
     ///
-    /// This method prints warnings to stderr and returns just the results.
-    /// For programmatic use, prefer `search_with_metadata()`.
-    pub fn search(&self, pattern: &str, filter: QueryFilter) -> Result<Vec<SearchResult>> {
-        log::info!("Executing query: pattern='{}', filter={:?}", pattern, filter);
+    /// Rules live in the main cache, so overlay engines share them. Without a
+    /// recorded CODEOWNERS file, `required` (an `--owner` filter) is an error
+    /// and annotations are simply left out.
+    fn file_owners(&self, required: bool) -> Result<Option<FileOwners>> {
+        let root = self.cache.workspace_root();
+        match crate::owners::load(&CacheManager::new(&root))? {
+            Some(owners) => Ok(Some(FileOwners { root, matcher: owners.matcher() })),
+            None if required => anyhow::bail!(
+                "No CODEOWNERS file was recorded for this index. Add one at {} and run 'rfx index'.",
+                crate::owners::CODEOWNERS_LOCATIONS.join(", ")
+            ),
+            None => Ok(None),
+        }
+    }
 
-        // Ensure cache exists
-        if !self.cache.exists() {
-            anyhow::bail!(
-                "Index not found. Run 'rfx index' to build the cache first."
-            );
+    /// Open the blame cache when blame annotations are requested
+    fn blame_cache(&self, blame: bool) -> Result<Option<crate::blame::BlameCache>> {
+        if !blame {
+            return Ok(None);
         }
+        Ok(Some(crate::blame::BlameCache::open(&self.cache)?))
+    }
 
-        // Validate cache integrity
-        if let Err(e) = self.cache.validate() {
-            anyhow::bail!(
-                "Cache appears to be corrupted: {}. Run 'rfx clear' followed by 'rfx index' to rebuild.",
-                e
-            );
+    /// Drop results outside the files owned by `filter.owner` (no-op without one)
+    fn retain_owned(&self, results: &mut Vec<SearchResult>, filter: &QueryFilter) -> Result<()> {
+        let Some(ref owner) = filter.owner else {
+            return Ok(());
+        };
+        if let Some(owners) = self.file_owners(true)? {
+            owners.retain_owned(results, owner);
         }
+        Ok(())
+    }
 
-        // Show non-blocking warnings about branch state and staleness
-        self.check_index_freshness(&filter)?;
+    /// Drop results outside the files touching `filter.table` (no-op without one)
+    ///
+    /// A file touches a table if the registry records a reference to it there
+    /// (model, schema or query) or it mentions one of the table's models.
+    fn retain_table(&self, results: &mut Vec<SearchResult>, filter: &QueryFilter) -> Result<()> {
+        let Some(ref table) = filter.table else {
+            return Ok(());
+        };
+        let root = self.cache.workspace_root();
+        let usage = crate::tables::TableIndex::new(CacheManager::new(&root))
+            .resolve(table)?
+            .ok_or_else(|| crate::tables::unknown_table(table))?;
+
+        let mut files: std::collections::HashSet<String> = usage.refs.into_iter().map(|r| r.path).collect();
+        for model in &usage.models {
+            let mentions = self.get_trigram_candidates(model, &QueryFilter::default(), None, &MemoryGuard::new(None), None, None)?;
+            files.extend(mentions.iter().map(|r| crate::paths::relative_path(std::path::Path::new(&r.path), &root)));
+        }
 
-        // Execute the search (discard total count - legacy method doesn't use it)
-        let (mut results, _total_count) = self.search_internal(pattern, filter.clone())?;
+        let before = results.len();
+        results.retain(|r| files.contains(&crate::paths::relative_path(std::path::Path::new(&r.path), &root)));
+        log::debug!("Table filter '{}': reduced {} candidates to {}", table, before, results.len());
+        Ok(())
+    }
+
+    /// Drop candidate lines matching any `--not` pattern of `filter`
+    ///
+    /// Patterns are substrings, or regexes with `--regex`. A literal pattern is
+    /// subtracted via the trigram index first: lines of files whose trigrams
+    /// can't contain it are kept without being checked.
+    fn retain_not_matching(&self, results: &mut Vec<SearchResult>, filter: &QueryFilter) -> Result<()> {
+        let trigram_index = TrigramIndex::load(self.cache.path().join("trigrams.bin")).ok();
+
+        let mut terms: Vec<(Regex, Option<std::collections::HashSet<String>>)> = Vec::new();
+        for pattern in &filter.not_patterns {
+            if pattern.is_empty() {
+                anyhow::bail!("--not needs a non-empty pattern");
+            }
+            let literal = !filter.use_regex || regex::escape(pattern) == *pattern;
+            let source = if filter.use_regex { pattern.clone() } else { regex::escape(pattern) };
+            let regex = Regex::new(&source)
+                .map_err(|e| ReflexError::InvalidRegex(format!("Invalid --not pattern '{}': {}", pattern, e)))?;
+
+            // Files that may contain the pattern (None = check every file)
+            let files = match &trigram_index {
+                Some(index) if literal && pattern.len() >= 3 => Some(
+                    index.search(pattern)
+                        .iter()
+                        .filter_map(|loc| index.get_file(loc.file_id))
+                        .map(|path| path.to_string_lossy().to_string())
+                        .collect(),
+                ),
+                _ => None,
+            };
+            terms.push((regex, files));
+        }
+
+        let before = results.len();
+        results.retain(|r| {
+            !terms.iter().any(|(regex, files)| {
+                files.as_ref().is_none_or(|files| files.contains(&r.path)) && regex.is_match(&r.preview)
+            })
+        });
+        log::debug!("--not filter: reduced {} candidates to {}", before, results.len());
+        Ok(())
+    }
+
+    /// Minimum fuzzy match similarity from `[search] fuzzy_threshold`
+    ///
+    /// An unreadable config falls back to the default rather than failing the query.
+    fn fuzzy_threshold(&self) -> f64 {
+        crate::tuning::load_search_config(self.cache.path())
+            .map_err(|e| log::warn!("Ignoring [search] config: {}", e))
+            .ok()
+            .and_then(|config| config.fuzzy_threshold)
+            .unwrap_or(crate::symbol_cache::DEFAULT_FUZZY_THRESHOLD)
+    }
+
+    /// Memory guard for a query, limited by `filter` or `[search] max_memory_mb`
+    fn memory_guard(&self, filter: &QueryFilter) -> MemoryGuard {
+        let limit_mb = filter.max_memory_mb.unwrap_or_else(|| {
+            crate::tuning::load_search_config(self.cache.path())
+                .map_err(|e| log::warn!("Ignoring [search] config: {}", e))
+                .ok()
+                .and_then(|config| config.max_memory_mb)
+                .unwrap_or(crate::tuning::DEFAULT_MAX_MEMORY_MB)
+        });
+        MemoryGuard::new((limit_mb > 0).then(|| limit_mb.saturating_mul(1024 * 1024)))
+    }
+
+    /// Load the path → transform map for files indexed through a content transform
+    ///
+    /// Missing tables (caches built before transforms existed) yield an empty map.
+    fn file_transforms(&self) -> std::collections::HashMap<String, String> {
+        crate::transforms::TransformIndex::new(self.cache.clone())
+            .all()
+            .unwrap_or_else(|e| {
+                log::debug!("Failed to load file transforms: {}", e);
+                std::collections::HashMap::new()
+            })
+    }
+
+    /// Keep matches in files of `lang` or inside `lang` regions carved out of host files
+    ///
+    /// Matches inside a region are re-tagged with the embedded language. Missing
+    /// tables (caches built before embedded indexing) leave only same-language files.
+    fn retain_embedded(&self, results: &mut Vec<SearchResult>, lang: Language) {
+        let regions = crate::embedded::EmbeddedIndex::new(self.cache.clone())
+            .regions_for(lang)
+            .unwrap_or_else(|e| {
+                log::debug!("Failed to load embedded regions: {}", e);
+                std::collections::HashMap::new()
+            });
+
+        let workspace_root = self.cache.workspace_root();
+        results.retain_mut(|r| {
+            if r.lang == lang {
+                return true;
+            }
+            let path = std::path::Path::new(&r.path);
+            let relative = path.strip_prefix(&workspace_root).unwrap_or(path);
+            let relative = relative.to_string_lossy();
+            let normalized_path = relative.strip_prefix("./").unwrap_or(&relative);
+            let inside = regions
+                .get(normalized_path)
+                .is_some_and(|spans| spans.iter().any(|region| region.contains(r.span.start_line)));
+            if inside {
+                r.lang = lang;
+            }
+            inside
+        });
+    }
+
+    /// Build the grouped result for a single file: load its dependencies and
+    /// related files (once per file) and extract context lines around each match
+    #[allow(clippy::too_many_arguments)]
+    fn build_file_group(
+        &self,
+        path: String,
+        file_matches: Vec<SearchResult>,
+        dep_index: Option<&crate::dependency::DependencyIndex>,
+        related: Option<&crate::related::RelatedFinder>,
+        owners: Option<&FileOwners>,
+        transforms: &std::collections::HashMap<String, String>,
+        content_reader_opt: Option<&ContentReader>,
+        context: (usize, usize),
+        explainer: &MatchExplainer,
+    ) -> crate::models::FileGroupedResult {
+        use crate::models::{FileGroupedResult, MatchResult};
+
+        // Load dependencies for this file (once per file, not per result)
+        let dependencies = if let Some(dep_idx) = dep_index {
+            let normalized_path = path.strip_prefix("./").unwrap_or(&path);
+            match self.cache.get_file_id(normalized_path) {
+                Ok(Some(file_id)) => {
+                    match dep_idx.get_dependencies_info(file_id) {
+                        Ok(dep_infos) if !dep_infos.is_empty() => {
+                            log::debug!("Loaded {} dependencies for file: {}", dep_infos.len(), path);
+                            Some(dep_infos)
+                        }
+                        Ok(_) => None,
+                        Err(e) => {
+                            log::warn!("Failed to get dependencies for {}: {}", path, e);
+                            None
+                        }
+                    }
+                }
+                Ok(None) => {
+                    log::warn!("No file_id found for path: {}", path);
+                    None
+                }
+                Err(e) => {
+                    log::warn!("Failed to get file_id for path {}: {}", path, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Get file_id for context extraction
+        // Note: We use ContentReader's get_file_id_by_path() which returns array indices,
+        // not database file_ids (which are AUTO INCREMENT values)
+        let normalized_path = path.strip_prefix("./").unwrap_or(&path);
+        let file_id_for_context = if let Some(reader) = content_reader_opt {
+            reader.get_file_id_by_path(normalized_path)
+        } else {
+            None
+        };
+        log::debug!("Context extraction: file={}, file_id={:?}, content_reader={}",
+            path, file_id_for_context, content_reader_opt.is_some());
+
+        // Notebook spans refer to the rendered notebook; map them back to cells
+        let notebook_content = match (content_reader_opt, file_id_for_context) {
+            (Some(reader), Some(fid)) if crate::notebook::is_notebook(&path) => reader.get_file_content(fid).ok(),
+            _ => None,
+        };
+
+        // Convert SearchResults to MatchResults (strip path and dependencies) and extract context
+        let matches: Vec<MatchResult> = file_matches
+            .into_iter()
+            .map(|r| {
+                // Extract context lines (counts come from --before/--after/--context)
+                let (context_before, context_after) = if let (Some(reader), Some(fid)) = (content_reader_opt, file_id_for_context) {
+                    let result = reader.get_context_lines(fid, r.span.start_line, context.0, context.1)
+                        .unwrap_or_else(|e| {
+                            log::warn!("Failed to extract context for {}:{}: {}", path, r.span.start_line, e);
+                            (vec![], vec![])
+                        });
+                    log::debug!("Extracted context for {}:{} - before: {}, after: {}",
+                        path, r.span.start_line, result.0.len(), result.1.len());
+                    result
+                } else {
+                    if content_reader_opt.is_none() {
+                        log::debug!("No ContentReader available for context extraction");
+                    }
+                    if file_id_for_context.is_none() {
+                        log::debug!("No file_id found for {}", path);
+                    }
+                    (vec![], vec![])
+                };
+
+                let match_reason = Some(explainer.explain(&r));
+                let cell = notebook_content.and_then(|content| crate::notebook::locate_cell(content, r.span.start_line));
+
+                MatchResult {
+                    kind: r.kind,
+                    symbol: r.symbol,
+                    parent: r.parent,
+                    qualified_name: r.qualified_name,
+                    signature: r.signature,
+                    doc: r.doc,
+                    exported: r.exported,
+                    span: r.span,
+                    preview: r.preview,
+                    context_before,
+                    context_after,
+                    match_reason,
+                    cell,
+                    captures: r.captures,
+                    blame: None,
+                }
+            })
+            .collect();
+
+        let related_files = related.map(|finder| finder.related(&path));
+        let transform = transforms.get(path.strip_prefix("./").unwrap_or(&path)).cloned();
+        let owners = owners.map(|o| o.owners_of(&path).to_vec());
+
+        FileGroupedResult {
+            path,
+            dependencies,
+            related_files,
+            transform,
+            owners,
+            repo: None,
+            matches,
+        }
+    }
+
+    /// Execute a query and return matching results with index metadata
+    ///
+    /// This is the preferred method for programmatic/JSON output as it includes
+    /// index freshness information that AI agents can use to decide whether to re-index.
+    pub fn search_with_metadata(&self, pattern: &str, mut filter: QueryFilter) -> Result<QueryResponse> {
+        log::info!("Executing query with metadata: pattern='{}', filter={:?}", pattern, filter);
+        filter.merge_legacy_kind();
+
+        self.ensure_cache_usable()?;
+
+        // Get index status and warning (without printing warnings to stderr)
+        let (status, can_trust_results, warning) = self.get_index_status()?;
+
+        // Execute the search
+        let start = std::time::Instant::now();
+        let budget = Budget::from_filter(&filter);
+        let cancel = cancel_token(&filter);
+        let memory = self.memory_guard(&filter);
+        let trace = Trace::from_filter(&filter);
+        let (results, total) = self.search_internal(pattern, filter.clone(), budget.as_ref(), &cancel, &memory, trace.as_ref())?;
+        let unscanned_candidates = budget.as_ref().map_or(0, Budget::unscanned);
+        let partial = partial_status(&cancel, &memory);
+        let mut phase_start = std::time::Instant::now();
+
+        // Build pagination metadata
+        use crate::models::PaginationInfo;
+        let pagination = PaginationInfo {
+            total,
+            count: results.len(),
+            offset: filter.offset.unwrap_or(0),
+            limit: filter.limit,
+            has_more: total > filter.offset.unwrap_or(0) + results.len(),
+        };
+
+        // Always use grouped format (group results by file)
+        // Dependencies are loaded only when include_dependencies is true
+        let context = (
+            filter.before_context.unwrap_or(DEFAULT_CONTEXT_LINES),
+            filter.after_context.unwrap_or(DEFAULT_CONTEXT_LINES),
+        );
+        let explainer = MatchExplainer::new(pattern, &filter);
+        let grouped_results = self.group_and_load_dependencies(results, &filter, context, &explainer)?;
+        record_phase(trace.as_ref(), &mut phase_start, |t| &mut t.grouping_ms);
+
+        // Tombstones answer "where did this go?" for symbols of removed files
+        let deleted = if filter.include_deleted {
+            Some(crate::tombstones::TombstoneIndex::new(self.cache.clone()).find(pattern, filter.exact)?)
+        } else {
+            None
+        };
+
+        let (plan, timings) = match trace.map(Trace::into_parts) {
+            Some((mut plan, mut timings)) => {
+                plan.peak_memory_bytes = memory.high_water();
+                plan.memory_limit_bytes = memory.limit;
+                timings.total_ms = start.elapsed().as_secs_f64() * 1000.0;
+                (Some(plan), Some(timings))
+            }
+            None => (None, None),
+        };
+
+        Ok(QueryResponse {
+            ai_instruction: None,  // AI instruction is generated by CLI/MCP layer, not here
+            status,
+            can_trust_results,
+            warning,
+            pagination,
+            complete: unscanned_candidates == 0 && partial.is_none(),
+            unscanned_candidates,
+            partial,
+            truncated: false,
+            results: grouped_results,
+            deleted,
+            warnings: Vec::new(),  // Populated by CLI/MCP layer
+            plan,
+            timings,
+        })
+    }
+
+    /// Execute a query and hand each result record to `emit` as soon as it is ready
+    ///
+    /// Emits a [`QueryStreamRecord::Header`] first, then one [`QueryStreamRecord::File`]
+    /// per matching file, then a [`QueryStreamRecord::Summary`]. Context lines and
+    /// dependencies are loaded file by file, so callers writing NDJSON can flush the
+    /// first files before the rest have been materialized. An error returned by
+    /// `emit` (e.g. a closed pipe) stops the stream.
+    pub fn search_streaming<F>(&self, pattern: &str, mut filter: QueryFilter, mut emit: F) -> Result<()>
+    where
+        F: FnMut(QueryStreamRecord) -> Result<()>,
+    {
+        use std::collections::BTreeMap;
+        use crate::models::PaginationInfo;
+
+        log::info!("Executing streaming query: pattern='{}', filter={:?}", pattern, filter);
+        filter.merge_legacy_kind();
+
+        self.ensure_cache_usable()?;
+
+        let (status, can_trust_results, warning) = self.get_index_status()?;
+        emit(QueryStreamRecord::Header { status, can_trust_results, warning })?;
+
+        let budget = Budget::from_filter(&filter);
+        let cancel = cancel_token(&filter);
+        let memory = self.memory_guard(&filter);
+        let (results, total) = self.search_internal(pattern, filter.clone(), budget.as_ref(), &cancel, &memory, None)?;
+        let unscanned_candidates = budget.as_ref().map_or(0, Budget::unscanned);
+        let partial = partial_status(&cancel, &memory);
+        let count = results.len();
+
+        // BTreeMap keeps files in the same path order as the buffered response
+        let mut grouped: BTreeMap<String, Vec<SearchResult>> = BTreeMap::new();
+        for result in results {
+            grouped.entry(result.path.clone()).or_default().push(result);
+        }
+
+        let dep_index = self.dependency_index(filter.include_dependencies)?;
+        let related = self.related_finder(filter.related_files)?;
+        let owners = if filter.with_owners { self.file_owners(false)? } else { None };
+        let blame = self.blame_cache(filter.blame)?;
+        let transforms = self.file_transforms();
+        let content_reader_opt = ContentReader::open(self.cache.path().join("content.bin")).ok();
+        let context = (
+            filter.before_context.unwrap_or(DEFAULT_CONTEXT_LINES),
+            filter.after_context.unwrap_or(DEFAULT_CONTEXT_LINES),
+        );
+
+        let explainer = MatchExplainer::new(pattern, &filter);
+        for (path, file_matches) in grouped {
+            let mut group = self.build_file_group(path, file_matches, dep_index.as_ref(), related.as_ref(), owners.as_ref(), &transforms, content_reader_opt.as_ref(), context, &explainer);
+            if let Some(ref blame) = blame {
+                blame.annotate(&mut group);
+            }
+            emit(QueryStreamRecord::File(group))?;
+        }
+
+        let offset = filter.offset.unwrap_or(0);
+        emit(QueryStreamRecord::Summary {
+            pagination: PaginationInfo {
+                total,
+                count,
+                offset,
+                limit: filter.limit,
+                has_more: total > offset + count,
+            },
+            complete: unscanned_candidates == 0 && partial.is_none(),
+            unscanned_candidates,
+            partial,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Execute a query and return matching results (legacy method)
+    ///
+    /// This method prints warnings to stderr and returns just the results.
+    /// For programmatic use, prefer `search_with_metadata()`.
+    pub fn search(&self, pattern: &str, mut filter: QueryFilter) -> Result<Vec<SearchResult>> {
+        log::info!("Executing query: pattern='{}', filter={:?}", pattern, filter);
+        filter.merge_legacy_kind();
+
+        self.ensure_cache_usable()?;
+
+        // Show non-blocking warnings about branch state and staleness
+        self.check_index_freshness(&filter)?;
+
+        // Execute the search (discard total count - legacy method doesn't use it)
+        let budget = Budget::from_filter(&filter);
+        let cancel = cancel_token(&filter);
+        let memory = self.memory_guard(&filter);
+        let (mut results, _total_count) = self.search_internal(pattern, filter.clone(), budget.as_ref(), &cancel, &memory, None)?;
+
+        let unscanned = budget.as_ref().map_or(0, Budget::unscanned);
+        if unscanned > 0 && !filter.suppress_output {
+            output::warn(&format!(
+                "Time budget expired: results are incomplete ({} candidate files not scanned).",
+                unscanned
+            ));
+        }
+        if cancel.was_cancelled() {
+            warn_timeout_partial(&filter);
+        }
+        warn_memory_partial(&filter, &memory);
 
         // Load dependencies if requested
         self.load_dependencies(&mut results, filter.include_dependencies)?;
@@ -373,7 +1281,13 @@ impl QueryEngine {
 
     /// Internal search implementation (used by both search methods)
     /// Returns (results, total_count) where total_count is the count before offset/limit
-    fn search_internal(&self, pattern: &str, filter: QueryFilter) -> Result<(Vec<SearchResult>, usize)> {
+    ///
+    /// `cancel` carries the `--timeout` deadline: running out of time while
+    /// candidates are found is an error, afterwards parsing stops and the
+    /// results found so far are returned (`cancel.was_cancelled()` tells).
+    /// `memory` bounds what the query buffers the same way, with
+    /// `memory.exhausted()` telling whether files were skipped.
+    fn search_internal(&self, pattern: &str, filter: QueryFilter, budget: Option<&Budget>, cancel: &CancelToken, memory: &MemoryGuard, trace: Option<&Trace>) -> Result<(Vec<SearchResult>, usize)> {
         use std::time::{Duration, Instant};
 
         // Start timeout timer if configured
@@ -394,7 +1308,7 @@ impl QueryEngine {
         // 2. Pattern matches a keyword in ANY supported language
         //
         // Note: --lang is optional. If specified, language filtering happens naturally in Phase 2/3.
-        let is_keyword_query = if filter.symbols_mode || filter.kind.is_some() {
+        let is_keyword_query = if (filter.symbols_mode || filter.filters_kinds()) && !filter.fuzzy && !filter.tokens {
             ParserFactory::get_all_keywords().contains(&pattern)
         } else {
             false
@@ -404,10 +1318,17 @@ impl QueryEngine {
         // Example: "class" → SymbolKind::Class, "function" → SymbolKind::Function
         // This ensures keyword queries return only the relevant symbol type
         let mut filter = filter.clone();  // Clone so we can modify it
-        if is_keyword_query && filter.kind.is_none() {
-            if let Some(inferred_kind) = Self::keyword_to_kind(pattern) {
-                log::info!("Keyword '{}' mapped to kind {:?} (auto-inferred)", pattern, inferred_kind);
-                filter.kind = Some(inferred_kind);
+        Self::normalize_path_filters(&mut filter);
+        self.resolve_root_aliases(&mut filter)?;
+        if filter.fuzzy {
+            filter.symbols_mode = true;  // Fuzzy matching ranks symbol names
+        }
+        let mut inferred_kind = None;
+        if is_keyword_query && filter.kinds.is_empty() {
+            if let Some(kind) = Self::keyword_to_kind(pattern) {
+                log::info!("Keyword '{}' mapped to kind {:?} (auto-inferred)", pattern, kind);
+                filter.kinds = vec![kind.clone()];
+                inferred_kind = Some(kind);
             }
         }
 
@@ -435,7 +1356,7 @@ impl QueryEngine {
             let short_pattern_threshold = filter.test_short_pattern_threshold.unwrap_or(4);
 
             if total_files > large_index_threshold && pattern_len < short_pattern_threshold {
-                anyhow::bail!(
+                return Err(ReflexError::BroadQuery(format!(
                     "Query too broad - would be expensive to execute on this large index\n\
                      \n\
                      This index contains {} files, and pattern '{}' ({} characters) is too short for efficient searching.\n\
@@ -459,12 +1380,37 @@ impl QueryEngine {
                     pattern,
                     pattern_len,
                     pattern
-                );
+                )).into());
             }
         }
 
+        // Restrict candidates to files touched since a git ref (--changed-since)
+        let changed_files = self.changed_files(&filter)?;
+
+        let mut phase_start = Instant::now();
+        record_plan(trace, |plan| {
+            plan.strategy = if filter.semantic {
+                "semantic"
+            } else if is_keyword_query {
+                "keyword"
+            } else if filter.fuzzy {
+                "fuzzy"
+            } else if filter.tokens {
+                "tokens"
+            } else if filter.use_regex {
+                "regex"
+            } else {
+                "trigram"
+            }.to_string();
+            plan.keyword_mode = is_keyword_query;
+            plan.inferred_kind = inferred_kind;
+        });
+
         // PHASE 1: Get initial candidates (choose search strategy)
-        let mut results = if is_keyword_query {
+        let mut results = if filter.semantic {
+            // Hybrid vector + keyword ranking over embedded chunks
+            self.get_semantic_candidates(pattern, changed_files.as_ref())?
+        } else if is_keyword_query {
             // KEYWORD QUERY MODE: Scan all files (or files of target language if --lang specified)
             // This ensures we find ALL classes/functions/etc, not just those in the first 100 trigram matches
             if let Some(lang) = filter.language {
@@ -473,30 +1419,54 @@ impl QueryEngine {
             } else {
                 log::info!("Keyword query detected for '{}' - scanning all files (bypassing trigram search)", pattern);
             }
-            self.get_all_language_files(&filter)?
+            self.get_all_language_files(&filter, changed_files.as_ref())?
+        } else if filter.fuzzy {
+            // FUZZY MODE: A misremembered name shares few trigrams with the real one,
+            // so every file of the target language is a candidate
+            log::info!("Fuzzy query for '{}' - scanning all symbols (bypassing trigram search)", pattern);
+            self.get_all_language_files(&filter, changed_files.as_ref())?
+        } else if filter.tokens {
+            // Identifier-token search: word parts across camelCase/snake_case
+            self.get_token_candidates(pattern, budget, memory, changed_files.as_ref())?
         } else if filter.use_regex {
             // Regex pattern search with trigram optimization
-            self.get_regex_candidates(pattern, timeout.as_ref(), &start_time, filter.suppress_output)?
+            self.get_regex_candidates(pattern, timeout.as_ref(), &start_time, filter.suppress_output, budget, memory, changed_files.as_ref())?
         } else {
             // Standard trigram-based full-text search
-            self.get_trigram_candidates(pattern, &filter)?
+            self.get_trigram_candidates(pattern, &filter, budget, memory, changed_files.as_ref(), trace)?
         };
+        record_plan(trace, |plan| plan.candidates = results.len());
+        record_phase(trace, &mut phase_start, |t| &mut t.candidates_ms);
+
+        // Files changed in the working tree are answered by the dirty overlay instead
+        // (embeddings only cover the main index, so semantic queries skip it)
+        let overlay = if filter.semantic { None } else { self.active_overlay() };
+        if let Some(ref overlay) = overlay {
+            let before_count = results.len();
+            results.retain(|r| !overlay.shadows(&r.path));
+            record_plan(trace, |plan| plan.pruned_by_overlay = before_count - results.len());
+        }
 
         // EARLY LANGUAGE FILTER: Apply language filtering BEFORE broad query check
         // This ensures we only parse files matching the language filter in Phase 2
         // Critical for non-keyword queries to work correctly with accurate candidate counts
         //
-        // Skip for keyword queries - those candidates are already pre-filtered by language
-        if !is_keyword_query {
+        // Skip for keyword and fuzzy queries - those candidates are already pre-filtered by language
+        if !is_keyword_query && !filter.fuzzy {
             if let Some(lang) = filter.language {
                 let before_count = results.len();
-                results.retain(|r| r.lang == lang);
+                if crate::embedded::EMBEDDED_LANGUAGES.contains(&lang) {
+                    self.retain_embedded(&mut results, lang);
+                } else {
+                    results.retain(|r| r.lang == lang);
+                }
                 log::debug!(
                     "Language filter ({:?}): reduced {} candidates to {} candidates",
                     lang,
                     before_count,
                     results.len()
                 );
+                record_plan(trace, |plan| plan.pruned_by_language = before_count - results.len());
             }
         }
 
@@ -582,25 +1552,36 @@ impl QueryEngine {
                 before_count,
                 results.len()
             );
+            record_plan(trace, |plan| plan.pruned_by_glob = before_count - results.len());
+        }
+
+        // EXCLUSION TERMS: Drop candidate lines matching a --not pattern before they are parsed
+        if !filter.not_patterns.is_empty() {
+            let before_count = results.len();
+            self.retain_not_matching(&mut results, &filter)?;
+            record_plan(trace, |plan| plan.pruned_by_not = before_count - results.len());
         }
 
+        // Restrict candidates to one team's files (--owner) and one table's files (--table)
+        self.retain_owned(&mut results, &filter)?;
+        self.retain_table(&mut results, &filter)?;
+        record_phase(trace, &mut phase_start, |t| &mut t.filter_ms);
+
         // Check timeout after Phase 1
-        if let Some(timeout_duration) = timeout {
-            if start_time.elapsed() > timeout_duration {
-                anyhow::bail!(
-                    "Query timeout exceeded ({} seconds).\n\
-                     \n\
-                     The query took too long to complete. Try one of these approaches:\n\
-                     • Use a more specific search pattern (longer patterns = faster search)\n\
-                     • Add a language filter with --lang to narrow the search space\n\
-                     • Add a file filter with --file to search specific directories\n\
-                     • Increase the timeout with --timeout <seconds>\n\
-                     \n\
-                     Example: rfx query \"{}\" --lang rust --timeout 60",
-                    filter.timeout_secs,
-                    pattern
-                );
-            }
+        if cancel.is_cancelled() {
+            return Err(ReflexError::Timeout(format!(
+                "Query timeout exceeded ({} seconds).\n\
+                 \n\
+                 The query took too long to complete. Try one of these approaches:\n\
+                 • Use a more specific search pattern (longer patterns = faster search)\n\
+                 • Add a language filter with --lang to narrow the search space\n\
+                 • Add a file filter with --file to search specific directories\n\
+                 • Increase the timeout with --timeout <seconds>\n\
+                 \n\
+                 Example: rfx query \"{}\" --lang rust --timeout 60",
+                filter.timeout_secs,
+                pattern
+            )).into());
         }
 
         // BROAD QUERY DETECTION: Check if query is too expensive BEFORE parsing
@@ -627,14 +1608,14 @@ impl QueryEngine {
                 100  // AST without glob - allow small codebases
             } else if filter.use_ast {
                 10_000  // AST with glob restriction
-            } else if is_keyword_query {
-                20_000  // Keyword queries (e.g., "class", "function")
+            } else if is_keyword_query || filter.fuzzy {
+                20_000  // Keyword and fuzzy queries scan all files of the language
             } else {
                 50_000  // Trigram-filtered symbol queries
             };
 
             let has_many_candidates = candidate_count > threshold &&
-                                     (filter.symbols_mode || filter.kind.is_some() || filter.use_ast);
+                                     (filter.symbols_mode || filter.filters_kinds() || filter.use_ast);
 
             if is_short_pattern || has_many_candidates || is_broad_ast {
                 let reason = if is_short_pattern {
@@ -685,14 +1666,19 @@ impl QueryEngine {
                 if let Some(ref lang) = filter.language {
                     cmd_flags.push_str(&format!("--lang {:?} ", lang));
                 }
-                if let Some(ref kind) = filter.kind {
-                    cmd_flags.push_str(&format!("--kind {:?} ", kind));
+                if !filter.kinds.is_empty() {
+                    let kinds: Vec<String> = filter.kinds.iter().map(|k| k.to_string().to_lowercase()).collect();
+                    cmd_flags.push_str(&format!("--kind {} ", kinds.join(",")));
+                }
+                if !filter.exclude_kinds.is_empty() {
+                    let kinds: Vec<String> = filter.exclude_kinds.iter().map(|k| k.to_string().to_lowercase()).collect();
+                    cmd_flags.push_str(&format!("--kind-not {} ", kinds.join(",")));
                 }
                 if filter.use_ast {
                     cmd_flags.push_str("--ast ");
                 }
 
-                anyhow::bail!(
+                return Err(ReflexError::BroadQuery(format!(
                     "Query too broad - would be expensive to execute\n\
                      \n\
                      {}\n\
@@ -712,13 +1698,13 @@ impl QueryEngine {
                     suggestions.join("\n             "),
                     pattern,
                     cmd_flags
-                );
+                )).into());
             }
         }
 
         // DETERMINISTIC SORTING: Sort candidates early for deterministic results
         // This ensures results are always returned in the same order
-        if filter.symbols_mode || filter.kind.is_some() || filter.use_ast {
+        if filter.symbols_mode || filter.filters_kinds() || filter.use_ast {
             results.sort_by(|a, b| {
                 a.path.cmp(&b.path)
                     .then_with(|| a.span.start_line.cmp(&b.span.start_line))
@@ -738,13 +1724,18 @@ impl QueryEngine {
         }
 
         // PHASE 2: Enrich with symbol information or AST pattern matching (if needed)
+        // (the enriched results replace the candidates held so far)
+        let candidate_bytes = results_bytes(&results);
         if filter.use_ast {
             // AST pattern matching: Execute Tree-sitter query on candidate files
-            results = self.enrich_with_ast(results, pattern, filter.language)?;
-        } else if filter.symbols_mode || filter.kind.is_some() {
+            results = self.enrich_with_ast(results, pattern, filter.language, budget, cancel, memory)?;
+            memory.release(candidate_bytes);
+        } else if filter.symbols_mode || filter.filters_kinds() {
             // Symbol enrichment: Parse candidate files and extract symbol definitions
-            results = self.enrich_with_symbols(results, pattern, &filter)?;
+            results = self.enrich_with_symbols(results, pattern, &filter, budget, cancel, memory, trace)?;
+            memory.release(candidate_bytes);
         }
+        record_phase(trace, &mut phase_start, |t| &mut t.symbols_ms);
 
         // PHASE 3: Apply post-enrichment filters
         // Note: Language and glob filters are applied in Phase 1 (before broad query check)
@@ -752,15 +1743,19 @@ impl QueryEngine {
 
         // Apply kind filter (only relevant for symbol searches)
         // Special case: --kind function also includes methods (methods are functions in classes)
-        if let Some(ref kind) = filter.kind {
-            results.retain(|r| {
-                if matches!(kind, SymbolKind::Function) {
-                    // When searching for functions, also include methods
-                    matches!(r.kind, SymbolKind::Function | SymbolKind::Method)
-                } else {
-                    r.kind == *kind
-                }
-            });
+        if filter.filters_kinds() {
+            results.retain(|r| filter.matches_kind(&r.kind));
+        }
+
+        // Apply qualified name filter (--qualified): the symbol's parent chain must end with the path
+        if let Some(ref qualified) = filter.qualified {
+            let path = crate::parsers::qualify::segments(qualified);
+            results.retain(|r| crate::parsers::qualify::matches_path(r, &path));
+        }
+
+        // Apply visibility filter (--visibility): symbols without a visibility model never match
+        if let Some(visibility) = filter.visibility {
+            results.retain(|r| visibility.matches(r.exported));
         }
 
         // Apply file path filter (substring match)
@@ -773,6 +1768,21 @@ impl QueryEngine {
             results.retain(|r| r.symbol.as_deref() == Some(pattern));
         }
 
+        // Apply symbol scope filter (--in): keep matches inside the named symbol's span
+        if let Some(ref scope) = filter.scope {
+            results = self.restrict_to_scope(results, scope)?;
+        }
+
+        // Docs are always parsed but only returned on request
+        if !filter.with_docs {
+            for result in &mut results {
+                result.doc = None;
+            }
+        }
+
+        // Widen symbol spans over attributes/doc comments (before expanding bodies)
+        self.widen_spans(&mut results, filter.span_policy);
+
         // Expand symbol bodies if requested
         // Works for both symbol-mode and regex searches (if regex matched a symbol definition)
         if filter.expand {
@@ -783,7 +1793,7 @@ impl QueryEngine {
                     // Only expand if the result has a meaningful span (not just a single line)
                     if result.span.start_line < result.span.end_line {
                         // Find the file_id for this result's path
-                        if let Some(file_id) = Self::find_file_id(&content_reader, &result.path) {
+                        if let Some(file_id) = content_reader.file_id_for_path(&result.path) {
                             // Fetch the full span content
                             if let Ok(content) = content_reader.get_file_content(file_id) {
                                 let lines: Vec<&str> = content.lines().collect();
@@ -801,6 +1811,22 @@ impl QueryEngine {
             }
         }
 
+        // Merge results from the dirty overlay (it has no overlay of its own)
+        if let Some(ref overlay) = overlay {
+            if overlay.has_files() {
+                let overlay_filter = QueryFilter {
+                    limit: None,
+                    offset: None,
+                    ..filter.clone()
+                };
+                let overlay_engine = QueryEngine::new(self.cache.overlay());
+                let (overlay_results, _) = overlay_engine.search_internal(pattern, overlay_filter, budget, cancel, memory, None)
+                    .context("Failed to search dirty overlay")?;
+                record_plan(trace, |plan| plan.overlay_results = overlay_results.len());
+                results.extend(overlay_results);
+            }
+        }
+
         // Step 4: Deduplicate by path if paths-only mode
         if filter.paths_only {
             use std::collections::HashSet;
@@ -809,14 +1835,22 @@ impl QueryEngine {
         }
 
         // Step 5: Sort results deterministically (by path, then line number)
-        results.sort_by(|a, b| {
-            a.path.cmp(&b.path)
-                .then_with(|| a.span.start_line.cmp(&b.span.start_line))
-        });
+        // Fuzzy and semantic results are ranked best match first, so pagination keeps the closest ones
+        if filter.fuzzy {
+            results = crate::symbol_cache::rank_fuzzy(results, pattern, 0.0);
+        } else if filter.semantic {
+            results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        } else {
+            results.sort_by(|a, b| {
+                a.path.cmp(&b.path)
+                    .then_with(|| a.span.start_line.cmp(&b.span.start_line))
+            });
+        }
 
         // Capture total count AFTER all filtering but BEFORE pagination (offset/limit)
         // This is the total number of results the user can paginate through
         let total_count = results.len();
+        record_plan(trace, |plan| plan.results = total_count);
 
         // Step 5.5: Apply offset (pagination)
         if let Some(offset) = filter.offset {
@@ -834,6 +1868,7 @@ impl QueryEngine {
         }
 
         log::info!("Query returned {} results (total before pagination: {})", results.len(), total_count);
+        record_phase(trace, &mut phase_start, |t| &mut t.post_process_ms);
 
         Ok((results, total_count))
     }
@@ -881,6 +1916,11 @@ impl QueryEngine {
     pub fn search_ast_all_files(&self, ast_pattern: &str, filter: QueryFilter) -> Result<Vec<SearchResult>> {
         log::info!("Executing AST query on all files: pattern='{}', filter={:?}", ast_pattern, filter);
 
+        let mut filter = filter;
+        Self::normalize_path_filters(&mut filter);
+        filter.merge_legacy_kind();
+        self.resolve_root_aliases(&mut filter)?;
+
         // Require language for AST queries
         let lang = filter.language.ok_or_else(|| anyhow::anyhow!(
             "Language must be specified for AST pattern matching. Use --lang to specify the language.\n\
@@ -890,14 +1930,18 @@ impl QueryEngine {
 
         // Ensure cache exists
         if !self.cache.exists() {
-            anyhow::bail!(
-                "Index not found. Run 'rfx index' to build the cache first."
-            );
+            return Err(ReflexError::IndexMissing(
+                "Index not found. Run 'rfx index' to build the cache first.".to_string()
+            ).into());
         }
 
         // Show non-blocking warnings about branch state and staleness
         self.check_index_freshness(&filter)?;
 
+        // --timeout and the memory ceiling cut the scan short instead of failing it
+        let cancel = cancel_token(&filter);
+        let memory = self.memory_guard(&filter);
+
         // Load content store
         let content_path = self.cache.path().join("content.bin");
         let content_reader = ContentReader::open(&content_path)
@@ -934,6 +1978,9 @@ impl QueryEngine {
             None
         };
 
+        let overlay = self.active_overlay();
+        let changed_files = self.changed_files(&filter)?;
+
         // Get all files matching the language and glob filters
         let mut candidates: Vec<SearchResult> = Vec::new();
 
@@ -943,11 +1990,8 @@ impl QueryEngine {
                 None => continue,
             };
 
-            // Detect language from file extension
-            let ext = file_path.extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("");
-            let detected_lang = Language::from_extension(ext);
+            // Detect language from the file name
+            let detected_lang = Language::from_path(file_path);
 
             // Filter by language
             if detected_lang != lang {
@@ -964,24 +2008,45 @@ impl QueryEngine {
                 continue;
             }
 
+            // Dirty files are scanned in the overlay below
+            if overlay.as_ref().is_some_and(|o| o.shadows(&file_path_str)) {
+                continue;
+            }
+
+            if !in_changed_files(changed_files.as_ref(), file_path) {
+                continue;
+            }
+
             // Create a dummy candidate for this file (AST query will replace it)
             candidates.push(SearchResult {
                 path: file_path_str,
                 lang: detected_lang,
                 span: Span { start_line: 1, end_line: 1 },
                 symbol: None,
+                parent: None,
+                qualified_name: None,
                 kind: SymbolKind::Unknown("ast_query".to_string()),
                 preview: String::new(),
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             });
         }
 
+        // Restrict candidates to one team's files (--owner) and one table's files (--table)
+        self.retain_owned(&mut candidates, &filter)?;
+        self.retain_table(&mut candidates, &filter)?;
+
         log::info!("AST query scanning {} files for language {:?}", candidates.len(), lang);
 
         // BROAD QUERY DETECTION: Block large AST queries without glob restriction
         // Allow small codebases (<100 files) but require --glob for larger ones
         if !filter.force && filter.glob_patterns.is_empty() && candidates.len() >= 100 {
-            anyhow::bail!(
+            return Err(ReflexError::BroadQuery(format!(
                 "Query too broad - would be expensive to execute\n\
                  \n\
                  AST query without --glob restriction will scan the ENTIRE codebase ({} files). AST queries are SLOW (500ms-10s+).\n\
@@ -1002,10 +2067,11 @@ impl QueryEngine {
                 candidates.len(),
                 ast_pattern,
                 lang
-            );
+            )).into());
         }
 
-        if candidates.is_empty() {
+        let overlay_has_files = overlay.as_ref().is_some_and(|o| o.has_files());
+        if candidates.is_empty() && !overlay_has_files {
             if !filter.suppress_output {
                 output::warn(&format!("No files found for language {:?}. Check your language filter or glob patterns.", lang));
             }
@@ -1014,32 +2080,32 @@ impl QueryEngine {
 
         // Execute the AST query on all candidate files
         // This will load file contents and parse them with tree-sitter
-        let mut results = self.enrich_with_ast(candidates, ast_pattern, filter.language)?;
+        let mut results = self.enrich_with_ast(candidates, ast_pattern, filter.language, None, &cancel, &memory)?;
+        if cancel.was_cancelled() {
+            warn_timeout_partial(&filter);
+        }
+        warn_memory_partial(&filter, &memory);
 
         log::debug!("AST query found {} matches before filtering", results.len());
 
         // Apply remaining filters (same as search_internal Phase 3)
 
         // Apply kind filter
-        if let Some(ref kind) = filter.kind {
-            results.retain(|r| {
-                if matches!(kind, SymbolKind::Function) {
-                    matches!(r.kind, SymbolKind::Function | SymbolKind::Method)
-                } else {
-                    r.kind == *kind
-                }
-            });
+        if filter.filters_kinds() {
+            results.retain(|r| filter.matches_kind(&r.kind));
         }
 
         // Note: exact filter doesn't make sense for AST queries (pattern is S-expression, not symbol name)
 
+        self.widen_spans(&mut results, filter.span_policy);
+
         // Expand symbol bodies if requested
         if filter.expand {
             let content_path = self.cache.path().join("content.bin");
             if let Ok(content_reader) = ContentReader::open(&content_path) {
                 for result in &mut results {
                     if result.span.start_line < result.span.end_line {
-                        if let Some(file_id) = Self::find_file_id(&content_reader, &result.path) {
+                        if let Some(file_id) = content_reader.file_id_for_path(&result.path) {
                             if let Ok(content) = content_reader.get_file_content(file_id) {
                                 let lines: Vec<&str> = content.lines().collect();
                                 let start_idx = (result.span.start_line as usize).saturating_sub(1);
@@ -1056,6 +2122,23 @@ impl QueryEngine {
             }
         }
 
+        // Merge matches from the dirty overlay (it has no overlay of its own)
+        if overlay_has_files {
+            let overlay_filter = QueryFilter {
+                limit: None,
+                offset: None,
+                force: true,
+                suppress_output: true,
+                include_dependencies: false,
+                ..filter.clone()
+            };
+            let overlay_engine = QueryEngine::new(self.cache.overlay());
+            results.extend(
+                overlay_engine.search_ast_all_files(ast_pattern, overlay_filter)
+                    .context("Failed to search dirty overlay")?,
+            );
+        }
+
         // Deduplicate by path if paths-only mode
         if filter.paths_only {
             use std::collections::HashSet;
@@ -1111,11 +2194,16 @@ impl QueryEngine {
         log::info!("Executing AST query with text filter: text='{}', ast='{}', filter={:?}",
                    text_pattern, ast_pattern, filter);
 
+        let mut filter = filter;
+        Self::normalize_path_filters(&mut filter);
+        filter.merge_legacy_kind();
+        self.resolve_root_aliases(&mut filter)?;
+
         // Ensure cache exists
         if !self.cache.exists() {
-            anyhow::bail!(
-                "Index not found. Run 'rfx index' to build the cache first."
-            );
+            return Err(ReflexError::IndexMissing(
+                "Index not found. Run 'rfx index' to build the cache first.".to_string()
+            ).into());
         }
 
         // Show non-blocking warnings about branch state and staleness
@@ -1130,17 +2218,36 @@ impl QueryEngine {
             None
         };
 
+        // Restrict candidates to files touched since a git ref (--changed-since)
+        let changed_files = self.changed_files(&filter)?;
+        let memory = self.memory_guard(&filter);
+
         // PHASE 1: Get initial candidates using text pattern (trigram search)
-        let candidates = if filter.use_regex {
-            self.get_regex_candidates(text_pattern, timeout.as_ref(), &start_time, filter.suppress_output)?
+        let mut candidates = if filter.use_regex {
+            self.get_regex_candidates(text_pattern, timeout.as_ref(), &start_time, filter.suppress_output, None, &memory, changed_files.as_ref())?
         } else {
-            self.get_trigram_candidates(text_pattern, &filter)?
+            self.get_trigram_candidates(text_pattern, &filter, None, &memory, changed_files.as_ref(), None)?
         };
 
+        // Files changed in the working tree are answered by the dirty overlay instead
+        let overlay = self.active_overlay();
+        if let Some(ref overlay) = overlay {
+            candidates.retain(|r| !overlay.shadows(&r.path));
+        }
+
+        // Restrict candidates to one team's files (--owner) and one table's files (--table)
+        self.retain_owned(&mut candidates, &filter)?;
+        self.retain_table(&mut candidates, &filter)?;
+
         log::debug!("Phase 1 found {} candidate locations", candidates.len());
 
         // PHASE 2: Execute AST query on candidates
-        let mut results = self.enrich_with_ast(candidates, ast_pattern, filter.language)?;
+        let cancel = CancelToken::with_timeout(timeout.map(|t| t.saturating_sub(start_time.elapsed())));
+        let mut results = self.enrich_with_ast(candidates, ast_pattern, filter.language, None, &cancel, &memory)?;
+        if cancel.was_cancelled() {
+            warn_timeout_partial(&filter);
+        }
+        warn_memory_partial(&filter, &memory);
 
         log::debug!("Phase 2 AST matching found {} results", results.len());
 
@@ -1149,14 +2256,8 @@ impl QueryEngine {
             results.retain(|r| r.lang == lang);
         }
 
-        if let Some(ref kind) = filter.kind {
-            results.retain(|r| {
-                if matches!(kind, SymbolKind::Function) {
-                    matches!(r.kind, SymbolKind::Function | SymbolKind::Method)
-                } else {
-                    r.kind == *kind
-                }
-            });
+        if filter.filters_kinds() {
+            results.retain(|r| filter.matches_kind(&r.kind));
         }
 
         if let Some(ref file_pattern) = filter.file_pattern {
@@ -1206,13 +2307,15 @@ impl QueryEngine {
             results.retain(|r| r.symbol.as_deref() == Some(text_pattern));
         }
 
+        self.widen_spans(&mut results, filter.span_policy);
+
         // Expand symbol bodies if requested
         if filter.expand {
             let content_path = self.cache.path().join("content.bin");
             if let Ok(content_reader) = ContentReader::open(&content_path) {
                 for result in &mut results {
                     if result.span.start_line < result.span.end_line {
-                        if let Some(file_id) = Self::find_file_id(&content_reader, &result.path) {
+                        if let Some(file_id) = content_reader.file_id_for_path(&result.path) {
                             if let Ok(content) = content_reader.get_file_content(file_id) {
                                 let lines: Vec<&str> = content.lines().collect();
                                 let start_idx = (result.span.start_line as usize).saturating_sub(1);
@@ -1229,6 +2332,23 @@ impl QueryEngine {
             }
         }
 
+        // Merge matches from the dirty overlay (it has no overlay of its own)
+        if let Some(ref overlay) = overlay {
+            if overlay.has_files() {
+                let overlay_filter = QueryFilter {
+                    limit: None,
+                    offset: None,
+                    suppress_output: true,
+                    ..filter.clone()
+                };
+                let overlay_engine = QueryEngine::new(self.cache.overlay());
+                results.extend(
+                    overlay_engine.search_ast_with_text_filter(text_pattern, ast_pattern, overlay_filter)
+                        .context("Failed to search dirty overlay")?,
+                );
+            }
+        }
+
         // Sort results deterministically
         results.sort_by(|a, b| {
             a.path.cmp(&b.path)
@@ -1257,7 +2377,7 @@ impl QueryEngine {
     /// List all symbols of a specific kind
     pub fn list_by_kind(&self, kind: SymbolKind) -> Result<Vec<SearchResult>> {
         let filter = QueryFilter {
-            kind: Some(kind),
+            kinds: vec![kind],
             symbols_mode: true,
             ..Default::default()
         };
@@ -1285,28 +2405,23 @@ impl QueryEngine {
     /// # Optimizations
     /// 1. Language filtering: Skips files with unsupported languages (no parsers)
     /// 2. Parallel processing: Uses Rayon to parse files concurrently across CPU cores
-    fn enrich_with_symbols(&self, candidates: Vec<SearchResult>, pattern: &str, filter: &QueryFilter) -> Result<Vec<SearchResult>> {
+    #[allow(clippy::too_many_arguments)]
+    fn enrich_with_symbols(&self, candidates: Vec<SearchResult>, pattern: &str, filter: &QueryFilter, budget: Option<&Budget>, cancel: &CancelToken, memory: &MemoryGuard, trace: Option<&Trace>) -> Result<Vec<SearchResult>> {
         // Load content store for file reading
         let content_path = self.cache.path().join("content.bin");
         let content_reader = ContentReader::open(&content_path)
             .context("Failed to open content store")?;
 
-        // Load trigram index for file path lookups
-        let trigrams_path = self.cache.path().join("trigrams.bin");
-        let trigram_index = if trigrams_path.exists() {
-            TrigramIndex::load(&trigrams_path)?
-        } else {
-            Self::rebuild_trigram_index(&content_reader)?
-        };
-
         // Open symbol cache for reading cached symbols
         let symbol_cache = crate::symbol_cache::SymbolCache::open(self.cache.path())
             .context("Failed to open symbol cache")?;
 
+        // Files that crashed a parser are skipped until they change
+        let quarantine = crate::quarantine::Quarantine::open(self.cache.path())
+            .context("Failed to open parser quarantine")?;
+
         // Load file hashes for current branch for cache lookups
-        let root = self.cache.workspace_root();
-        let branch = crate::git::get_current_branch(&root)
-            .unwrap_or_else(|_| "_default".to_string());
+        let branch = self.cache.query_branch();
         let file_hashes = self.cache.load_hashes_for_branch(&branch)
             .context("Failed to load file hashes")?;
         log::debug!("Loaded {} file hashes for branch '{}' for symbol cache lookups", file_hashes.len(), branch);
@@ -1347,20 +2462,19 @@ impl QueryEngine {
 
         // PHASE 2a: Line-based pre-filtering (skip files where ALL matches are in comments/strings)
         // This reduces tree-sitter parsing workload by 2-5x for most queries
+        // (fuzzy and token queries don't expect the pattern to appear literally, and route
+        // paths are string literals, so nothing is skipped for them)
         let mut files_to_skip: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let prefilter = !filter.fuzzy && !filter.tokens && !filter.kinds.contains(&SymbolKind::Route);
 
-        for file_path in &files_to_process {
+        for file_path in files_to_process.iter().filter(|_| prefilter) {
             // Get the language for this file
-            let ext = std::path::Path::new(file_path)
-                .extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("");
-            let lang = Language::from_extension(ext);
+            let lang = Language::from_path(std::path::Path::new(file_path));
 
             // Get line filter for this language (if available)
             if let Some(line_filter) = crate::line_filter::get_filter(lang) {
                 // Find file_id for this path
-                let file_id = match Self::find_file_id_by_path(&content_reader, &trigram_index, file_path) {
+                let file_id = match content_reader.file_id_for_path(file_path) {
                     Some(id) => id,
                     None => continue,
                 };
@@ -1448,7 +2562,7 @@ impl QueryEngine {
         let file_id_map = self.cache.batch_get_file_ids(&files_with_hashes)
             .context("Failed to batch lookup file IDs")?;
 
-        // Step 3: Build (file_id, hash, path) tuples for batch_get_with_kind
+        // Step 3: Build (file_id, hash, path) tuples for batch_get_with_kinds
         let file_lookup_tuples: Vec<(i64, String, String)> = files_with_hashes
             .iter()
             .filter_map(|path| {
@@ -1458,8 +2572,9 @@ impl QueryEngine {
             })
             .collect();
 
-        // Step 4: Batch read symbols with kind filtering (uses junction table + integer joins)
-        let batch_results = symbol_cache.batch_get_with_kind(&file_lookup_tuples, filter.kind.clone())
+        // Step 4: Batch read symbols, keeping those of the requested kinds (filtered in SQL)
+        let (kinds, exclude_kinds) = filter.stored_kinds();
+        let batch_results = symbol_cache.batch_get_with_kinds(&file_lookup_tuples, &kinds, &exclude_kinds)
             .context("Failed to batch read symbol cache")?;
 
         // Step 5: Separate files into cached vs need-to-parse
@@ -1498,61 +2613,111 @@ impl QueryEngine {
             cached_symbols.len(),
             files_needing_parse.len()
         );
+        record_plan(trace, |plan| {
+            plan.prefiltered_files = files_to_skip.len();
+            plan.symbol_cache_hits = cached_symbols.len();
+            plan.symbol_parses = files_needing_parse.len();
+        });
 
-        // Parse files in parallel using custom thread pool (only cache misses)
+        for symbols in cached_symbols.values() {
+            memory.charge(results_bytes(symbols));
+        }
+
+        // Parse files in parallel using custom thread pool (only cache misses), in
+        // batches sized to the memory left so the content and parse trees in flight
+        // stay under the ceiling
         use rayon::prelude::*;
 
-        let parsed_symbols: Vec<SearchResult> = pool.install(|| {
-            files_needing_parse
-                .par_iter()
-                .flat_map(|file_path| {
-                // Find file_id for this path
-                let file_id = match Self::find_file_id_by_path(&content_reader, &trigram_index, file_path) {
-                    Some(id) => id,
-                    None => {
-                        log::warn!("Could not find file_id for path: {}", file_path);
-                        return Vec::new();
-                    }
-                };
+        let parse_sizes: Vec<u64> = files_needing_parse
+            .iter()
+            .map(|path| {
+                content_reader.file_id_for_path(path)
+                    .and_then(|file_id| content_reader.get_file_content(file_id).ok())
+                    .map_or(0, |content| content.len() as u64)
+            })
+            .collect();
 
-                let content = match content_reader.get_file_content(file_id) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::warn!("Failed to read file {}: {}", file_path, e);
+        let mut parsed_symbols: Vec<SearchResult> = Vec::new();
+        let mut next = 0;
+        while next < files_needing_parse.len() {
+            // Out of memory: leave the remaining files unparsed (nothing is cached for them)
+            if !memory.admit() {
+                break;
+            }
+            let batch = next..next + memory.next_batch(&parse_sizes[next..]);
+            let batch_bytes: u64 = parse_sizes[batch.clone()].iter().sum();
+            next = batch.end;
+            memory.charge(batch_bytes);
+
+            let batch_symbols: Vec<SearchResult> = pool.install(|| {
+                files_needing_parse[batch]
+                    .par_iter()
+                    .flat_map(|file_path| {
+                    // Out of time budget or past --timeout: skip parsing (nothing is cached for the file)
+                    if !within_budget(budget) || cancel.is_cancelled() {
                         return Vec::new();
                     }
-                };
 
-                // Detect language
-                let ext = std::path::Path::new(file_path)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-                let lang = Language::from_extension(ext);
-
-                // Parse file to extract symbols
-                let symbols = match ParserFactory::parse(file_path, content, lang) {
-                    Ok(symbols) => {
-                        log::debug!("Parsed {} symbols from {}", symbols.len(), file_path);
-                        symbols
-                    }
-                    Err(e) => {
-                        log::debug!("Failed to parse {}: {}", file_path, e);
-                        Vec::new()
-                    }
-                };
+                    // Find file_id for this path
+                    let file_id = match content_reader.file_id_for_path(file_path) {
+                        Some(id) => id,
+                        None => {
+                            log::warn!("Could not find file_id for path: {}", file_path);
+                            return Vec::new();
+                        }
+                    };
+
+                    let content = match content_reader.get_file_content(file_id) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            log::warn!("Failed to read file {}: {}", file_path, e);
+                            return Vec::new();
+                        }
+                    };
+
+                    // Detect language
+                    let lang = Language::from_path(std::path::Path::new(file_path));
+
+                    // Parse file to extract symbols (a parser panic quarantines the file);
+                    // the parse itself gives up at the --timeout deadline
+                    let file_hash = file_hashes.get(file_path.as_str());
+                    let parsed = cancel.enter(|| quarantine.parse(file_path, file_hash.map(|h| h.as_str()), content, lang));
+                    let symbols = match parsed {
+                        Ok(symbols) => {
+                            log::debug!("Parsed {} symbols from {}", symbols.len(), file_path);
+                            symbols
+                        }
+                        Err(e) if crate::quarantine::is_quarantine_error(&e) => {
+                            // Not cached, so the file is parsed again once it changes
+                            return Vec::new();
+                        }
+                        Err(_) if cancel.is_cancelled() => {
+                            // Aborted at the deadline: not cached, the file parses fine next time
+                            log::debug!("Parse of {} aborted by query timeout", file_path);
+                            return Vec::new();
+                        }
+                        Err(e) => {
+                            log::debug!("Failed to parse {}: {}", file_path, e);
+                            Vec::new()
+                        }
+                    };
 
-                // Cache the parsed symbols (ignore errors - caching is best-effort)
-                if let Some(file_hash) = file_hashes.get(file_path.as_str()) {
-                    if let Err(e) = symbol_cache.set(file_path, file_hash, &symbols) {
-                        log::debug!("Failed to cache symbols for {}: {}", file_path, e);
+                    // Cache the parsed symbols (ignore errors - caching is best-effort)
+                    if let Some(file_hash) = file_hash {
+                        if let Err(e) = symbol_cache.set(file_path, file_hash, &symbols) {
+                            log::debug!("Failed to cache symbols for {}: {}", file_path, e);
+                        }
                     }
-                }
 
-                symbols
-            })
-            .collect()
-        });
+                    symbols
+                })
+                .collect()
+            });
+
+            memory.release(batch_bytes);
+            memory.charge(results_bytes(&batch_symbols));
+            parsed_symbols.extend(batch_symbols);
+        }
 
         // Combine cached and parsed symbols
         let mut all_symbols: Vec<SearchResult> = Vec::new();
@@ -1598,7 +2763,19 @@ impl QueryEngine {
 
         // If pattern is a keyword (like "class" or "function"), skip name-based filtering
         // and return all symbols (kind filtering happens in Phase 3)
-        let filtered: Vec<SearchResult> = if is_keyword_query {
+        let filtered: Vec<SearchResult> = if filter.fuzzy {
+            // Rank by name similarity (typos, other case conventions, abbreviations)
+            let threshold = filter.fuzzy_threshold.unwrap_or_else(|| self.fuzzy_threshold());
+            log::info!("Fuzzy matching {} symbols against '{}' (threshold {})", all_symbols.len(), pattern, threshold);
+            crate::symbol_cache::rank_fuzzy(all_symbols, pattern, threshold)
+        } else if filter.tokens {
+            // Symbol names containing every word part of the pattern
+            let words = crate::tokens::identifier_words(pattern);
+            all_symbols
+                .into_iter()
+                .filter(|sym| sym.symbol.as_deref().is_some_and(|s| crate::tokens::has_all_words(s, &words)))
+                .collect()
+        } else if is_keyword_query {
             log::info!("Pattern '{}' is a language keyword - listing all symbols (kind filtering will be applied in Phase 3)", pattern);
             all_symbols
         } else if filter.use_regex {
@@ -1670,7 +2847,7 @@ impl QueryEngine {
     /// # Requirements
     /// - Language must be specified (AST queries are language-specific)
     /// - AST pattern must be valid S-expression syntax
-    fn enrich_with_ast(&self, candidates: Vec<SearchResult>, ast_pattern: &str, language: Option<Language>) -> Result<Vec<SearchResult>> {
+    fn enrich_with_ast(&self, candidates: Vec<SearchResult>, ast_pattern: &str, language: Option<Language>, budget: Option<&Budget>, cancel: &CancelToken, memory: &MemoryGuard) -> Result<Vec<SearchResult>> {
         // Require language for AST queries
         let lang = language.ok_or_else(|| anyhow::anyhow!(
             "Language must be specified for AST pattern matching. Use --lang to specify the language."
@@ -1681,28 +2858,48 @@ impl QueryEngine {
         let content_reader = ContentReader::open(&content_path)
             .context("Failed to open content store")?;
 
-        // Load trigram index for file path lookups
-        let trigrams_path = self.cache.path().join("trigrams.bin");
-        let trigram_index = if trigrams_path.exists() {
-            TrigramIndex::load(&trigrams_path)?
-        } else {
-            Self::rebuild_trigram_index(&content_reader)?
-        };
-
-        // Collect unique file paths from candidates and load their contents
+        // Group candidates by file, keeping the order files were first seen
         use std::collections::HashMap;
+        let mut paths: Vec<String> = Vec::new();
+        let mut candidates_by_path: HashMap<String, Vec<SearchResult>> = HashMap::new();
+        for candidate in candidates {
+            if !candidates_by_path.contains_key(&candidate.path) {
+                paths.push(candidate.path.clone());
+            }
+            candidates_by_path.entry(candidate.path.clone()).or_default().push(candidate);
+        }
+
+        log::debug!("Executing AST query on {} candidate files with language {:?}", paths.len(), lang);
+
+        // Load file contents in batches sized to the memory left, and run the AST query
+        // on each batch before loading the next (stops at the --timeout deadline)
+        let mut results = Vec::new();
         let mut file_contents: HashMap<String, String> = HashMap::new();
+        let mut batch: Vec<SearchResult> = Vec::new();
+        let mut batch_bytes = 0u64;
+        let mut batch_limit = memory.batch_bytes();
+        let mut run_batch = |batch: &mut Vec<SearchResult>, file_contents: &mut HashMap<String, String>, batch_bytes: &mut u64| -> Result<()> {
+            let matches = cancel.enter(|| crate::ast_query::execute_ast_query(std::mem::take(batch), ast_pattern, lang, file_contents))?;
+            file_contents.clear();
+            memory.release(std::mem::take(batch_bytes));
+            memory.charge(results_bytes(&matches));
+            results.extend(matches);
+            Ok(())
+        };
 
-        for candidate in &candidates {
-            if file_contents.contains_key(&candidate.path) {
+        for path in paths {
+            let file_candidates = candidates_by_path.remove(&path).unwrap_or_default();
+
+            // Out of time budget, memory or past --timeout: leave the file unmatched
+            if !within_budget(budget) || !memory.admit() || cancel.is_cancelled() {
                 continue;
             }
 
             // Find file_id for this path
-            let file_id = match Self::find_file_id_by_path(&content_reader, &trigram_index, &candidate.path) {
+            let file_id = match content_reader.file_id_for_path(&path) {
                 Some(id) => id,
                 None => {
-                    log::warn!("Could not find file_id for path: {}", candidate.path);
+                    log::warn!("Could not find file_id for path: {}", path);
                     continue;
                 }
             };
@@ -1711,49 +2908,134 @@ impl QueryEngine {
             let content = match content_reader.get_file_content(file_id) {
                 Ok(c) => c,
                 Err(e) => {
-                    log::warn!("Failed to read file {}: {}", candidate.path, e);
+                    log::warn!("Failed to read file {}: {}", path, e);
                     continue;
                 }
             };
 
-            file_contents.insert(candidate.path.clone(), content.to_string());
+            let size = content.len() as u64;
+            if !file_contents.is_empty() && batch_bytes + size > batch_limit {
+                run_batch(&mut batch, &mut file_contents, &mut batch_bytes)?;
+                batch_limit = memory.batch_bytes();
+            }
+            memory.charge(size);
+            batch_bytes += size;
+            file_contents.insert(path, content.to_string());
+            batch.extend(file_candidates);
+        }
+        if !file_contents.is_empty() {
+            run_batch(&mut batch, &mut file_contents, &mut batch_bytes)?;
         }
-
-        log::debug!("Executing AST query on {} candidate files with language {:?}", file_contents.len(), lang);
-
-        // Execute AST query using the ast_query module
-        let results = crate::ast_query::execute_ast_query(candidates, ast_pattern, lang, &file_contents)?;
 
         log::info!("AST query found {} matches for pattern '{}'", results.len(), ast_pattern);
 
         Ok(results)
     }
 
-    /// Helper to find file_id by path string
-    fn find_file_id_by_path(
-        content_reader: &ContentReader,
-        trigram_index: &TrigramIndex,
-        target_path: &str,
-    ) -> Option<u32> {
-        // Try trigram index first (faster)
-        for file_id in 0..trigram_index.file_count() {
-            if let Some(path) = trigram_index.get_file(file_id as u32) {
-                if path.to_string_lossy() == target_path {
-                    return Some(file_id as u32);
-                }
+    /// Keep only results that fall inside the line span of the `--in` symbol
+    ///
+    /// The scope symbol is resolved per candidate file, so a symbol defined in
+    /// several files (or overloaded) scopes matches to every definition.
+    fn restrict_to_scope(&self, results: Vec<SearchResult>, scope: &str) -> Result<Vec<SearchResult>> {
+        let (path_filter, name) = parse_scope(scope);
+        let segments: Vec<&str> = name.split("::").flat_map(|s| s.split('.')).filter(|s| !s.is_empty()).collect();
+        if segments.is_empty() {
+            anyhow::bail!(
+                "Invalid --in scope '{}'.\n\
+                 \n\
+                 Expected a symbol name, optionally qualified or prefixed with a path:\n\
+                 rfx query \"unwrap\" --in parse_config\n\
+                 rfx query \"self.cache\" --in QueryEngine.search\n\
+                 rfx query \"timeout\" --in src/query.rs:search_internal",
+                scope
+            );
+        }
+
+        let mut paths: Vec<String> = results.iter()
+            .filter(|r| path_filter.is_none_or(|p| r.path.contains(p)))
+            .map(|r| r.path.clone())
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        let symbols_by_file = self.file_symbols(&paths)?;
+
+        // Spans of every definition of the scope symbol, per file
+        let mut spans: std::collections::HashMap<String, Vec<Span>> = std::collections::HashMap::new();
+        for (path, symbols) in &symbols_by_file {
+            let found = scope_spans(symbols, &segments);
+            if !found.is_empty() {
+                spans.insert(path.clone(), found);
             }
         }
+        log::debug!("Scope '{}' resolved in {} of {} candidate files", scope, spans.len(), paths.len());
 
-        // Fallback to content reader
-        for file_id in 0..content_reader.file_count() {
-            if let Some(path) = content_reader.get_file_path(file_id as u32) {
-                if path.to_string_lossy() == target_path {
-                    return Some(file_id as u32);
+        Ok(results.into_iter()
+            .filter(|r| {
+                spans.get(&r.path).is_some_and(|file_spans| {
+                    file_spans.iter().any(|s| s.start_line <= r.span.start_line && r.span.start_line <= s.end_line)
+                })
+            })
+            .collect())
+    }
+
+    /// Load the symbols of a set of files, from the symbol cache where possible
+    ///
+    /// Cache misses are parsed from the content store (and cached); files that
+    /// fail to parse or are quarantined are left out.
+    fn file_symbols(&self, paths: &[String]) -> Result<std::collections::HashMap<String, Vec<SearchResult>>> {
+        use std::collections::HashMap;
+
+        let symbol_cache = crate::symbol_cache::SymbolCache::open(self.cache.path())
+            .context("Failed to open symbol cache")?;
+        let quarantine = crate::quarantine::Quarantine::open(self.cache.path())
+            .context("Failed to open parser quarantine")?;
+
+        let branch = self.cache.query_branch();
+        let file_hashes = self.cache.load_hashes_for_branch(&branch)
+            .context("Failed to load file hashes")?;
+        let file_ids = self.cache.batch_get_file_ids(paths)
+            .context("Failed to batch lookup file IDs")?;
+
+        let lookups: Vec<(i64, String, String)> = paths.iter()
+            .filter_map(|path| Some((*file_ids.get(path)?, file_hashes.get(path.as_str())?.clone(), path.clone())))
+            .collect();
+        let mut cached = symbol_cache.batch_get_with_kind(&lookups, None)
+            .context("Failed to batch read symbol cache")?;
+
+        let content_reader = ContentReader::open(self.cache.path().join("content.bin")).ok();
+
+        let mut symbols_by_file = HashMap::new();
+        for path in paths {
+            if let Some(symbols) = file_ids.get(path).and_then(|id| cached.remove(id)) {
+                symbols_by_file.insert(path.clone(), symbols);
+                continue;
+            }
+
+            let Some(reader) = content_reader.as_ref() else {
+                continue;
+            };
+            let Some(content) = reader.file_id_for_path(path).and_then(|id| reader.get_file_content(id).ok()) else {
+                continue;
+            };
+
+            let language = Language::from_path(std::path::Path::new(path));
+            let file_hash = file_hashes.get(path.as_str());
+            match quarantine.parse(path, file_hash.map(|h| h.as_str()), content, language) {
+                Ok(symbols) => {
+                    // Caching is best-effort, like for symbol queries
+                    if let Some(file_hash) = file_hash {
+                        if let Err(e) = symbol_cache.set(path, file_hash, &symbols) {
+                            log::debug!("Failed to cache symbols for {}: {}", path, e);
+                        }
+                    }
+                    symbols_by_file.insert(path.clone(), symbols);
                 }
+                Err(e) => log::debug!("Skipping scope resolution in {}: {}", path, e),
             }
         }
 
-        None
+        Ok(symbols_by_file)
     }
 
     /// Map keyword patterns to SymbolKind for auto-inference
@@ -1800,7 +3082,7 @@ impl QueryEngine {
     /// not just the first 100 candidates from a trigram search.
     ///
     /// Similar to `search_ast_all_files()` but works for symbol queries instead of AST queries.
-    fn get_all_language_files(&self, filter: &QueryFilter) -> Result<Vec<SearchResult>> {
+    fn get_all_language_files(&self, filter: &QueryFilter, changed: Option<&ChangedFiles>) -> Result<Vec<SearchResult>> {
         // Language filter is optional - if not specified, scan all files
         // If specified, only scan files of that language
 
@@ -1847,11 +3129,8 @@ impl QueryEngine {
                 None => continue,
             };
 
-            // Detect language from file extension
-            let ext = file_path.extension()
-                .and_then(|e| e.to_str())
-                .unwrap_or("");
-            let detected_lang = Language::from_extension(ext);
+            // Detect language from the file name
+            let detected_lang = Language::from_path(file_path);
 
             // Filter by language (if specified)
             if let Some(lang) = filter.language {
@@ -1877,6 +3156,10 @@ impl QueryEngine {
                 }
             }
 
+            if !in_changed_files(changed, file_path) {
+                continue;
+            }
+
             // Create a dummy candidate for this file
             // Phase 2 (symbol enrichment) will parse it and extract actual symbols
             candidates.push(SearchResult {
@@ -1884,9 +3167,17 @@ impl QueryEngine {
                 lang: detected_lang,
                 span: Span { start_line: 1, end_line: 1 },
                 symbol: None,
+                parent: None,
+                qualified_name: None,
                 kind: SymbolKind::Unknown("keyword_query".to_string()),
                 preview: String::new(),
                 dependencies: None,
+                repo: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                captures: None,
+                score: None,
             });
         }
 
@@ -1900,7 +3191,7 @@ impl QueryEngine {
     }
 
     /// Get candidate results using trigram-based full-text search
-    fn get_trigram_candidates(&self, pattern: &str, filter: &QueryFilter) -> Result<Vec<SearchResult>> {
+    fn get_trigram_candidates(&self, pattern: &str, filter: &QueryFilter, budget: Option<&Budget>, memory: &MemoryGuard, changed: Option<&ChangedFiles>, trace: Option<&Trace>) -> Result<Vec<SearchResult>> {
         // Load content store
         let content_path = self.cache.path().join("content.bin");
         let content_reader = ContentReader::open(&content_path)
@@ -1939,7 +3230,7 @@ impl QueryEngine {
                 Ok(re) => Some(re),
                 Err(e) => {
                     log::error!("Invalid regex pattern '{}': {}", pattern_owned, e);
-                    anyhow::bail!("Invalid regex pattern '{}': {}", pattern_owned, e);
+                    return Err(ReflexError::InvalidRegex(format!("Invalid regex pattern '{}': {}", pattern_owned, e)).into());
                 }
             }
         } else {
@@ -1956,7 +3247,16 @@ impl QueryEngine {
                 .push(loc);
         }
 
+        // --changed-since: drop untouched files before scanning their content
+        if changed.is_some() {
+            candidates_by_file.retain(|file_id, _| {
+                trigram_index.get_file(*file_id)
+                    .is_some_and(|path| in_changed_files(changed, path))
+            });
+        }
+
         log::debug!("Scanning {} files with trigram matches", candidates_by_file.len());
+        record_plan(trace, |plan| plan.trigram_candidate_files = Some(candidates_by_file.len()));
 
         // Process files in parallel using rayon
         use rayon::prelude::*;
@@ -1964,6 +3264,11 @@ impl QueryEngine {
         let results: Vec<SearchResult> = candidates_by_file
             .par_iter()
             .flat_map(|(file_id, locations)| {
+                // Out of time budget or memory: leave the file unverified
+                if !within_budget(budget) || !memory.admit() {
+                    return Vec::new();
+                }
+
                 // Get file metadata
                 let file_path = match trigram_index.get_file(*file_id) {
                     Some(p) => p,
@@ -1978,10 +3283,7 @@ impl QueryEngine {
                 let file_path_str = file_path.to_string_lossy().to_string();
 
                 // Detect language once per file
-                let ext = file_path.extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-                let lang = Language::from_extension(ext);
+                let lang = Language::from_path(file_path);
 
                 // Split content into lines once
                 let lines: Vec<&str> = content.lines().collect();
@@ -2037,15 +3339,233 @@ impl QueryEngine {
                         lang: lang.clone(),
                         kind: SymbolKind::Unknown("text_match".to_string()),
                         symbol: None,  // No symbol name for text matches (avoid duplication)
+                        parent: None,
+                        qualified_name: None,
                         span: Span {
                             start_line: line_no,
                             end_line: line_no,
                         },
                         preview: line.to_string(),
                         dependencies: None,
+                        repo: None,
+                        signature: None,
+                        doc: None,
+                        exported: None,
+                        captures: None,
+                        score: None,
                     });
                 }
 
+                memory.charge(results_bytes(&file_results));
+                file_results
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Get candidate results from the embeddings index (--semantic)
+    ///
+    /// Every chunk is scored by its vector similarity to the query, blended with
+    /// the share of query words found in it (`[embeddings] keyword_weight`).
+    /// Keyword hits come from the trigram index, checked case-insensitively.
+    /// The best `[embeddings] candidates` chunks are returned, best first.
+    fn get_semantic_candidates(&self, pattern: &str, changed: Option<&ChangedFiles>) -> Result<Vec<SearchResult>> {
+        use crate::embeddings::{self, EmbeddingIndex};
+        use std::collections::{HashMap, HashSet};
+
+        let config = embeddings::load_config(self.cache.path())?;
+        let index = EmbeddingIndex::load(&self.cache)?.ok_or_else(|| anyhow::anyhow!(
+            "No embeddings found for this index.\n\
+             \n\
+             Run 'rfx embed' to embed the indexed files, then retry the --semantic query."
+        ))?;
+        let embedder = embeddings::create_embedder(&config)?;
+        if embedder.id() != index.model {
+            anyhow::bail!(
+                "Embeddings were built with {} but [embeddings] now configures {}. Run 'rfx embed' to re-embed.",
+                index.model,
+                embedder.id()
+            );
+        }
+        let query = embedder.embed(&[pattern.to_string()])?
+            .pop()
+            .context("Embeddings provider returned no vector for the query")?;
+
+        let content_reader = ContentReader::open(self.cache.path().join("content.bin"))
+            .context("Failed to open content store")?;
+
+        // Lines holding each query word (trigrams are case-sensitive, so try common casings)
+        let terms = embeddings::query_terms(pattern);
+        let mut hits: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        if !terms.is_empty() {
+            let trigrams_path = self.cache.path().join("trigrams.bin");
+            let trigram_index = if trigrams_path.exists() {
+                TrigramIndex::load(&trigrams_path)?
+            } else {
+                Self::rebuild_trigram_index(&content_reader)?
+            };
+
+            for (term_idx, term) in terms.iter().enumerate() {
+                let mut chars = term.chars();
+                let capitalized: String = chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default();
+                let locations: HashSet<(u32, u32)> = [term.clone(), capitalized, term.to_uppercase()]
+                    .iter()
+                    .flat_map(|variant| trigram_index.search(variant))
+                    .map(|loc| (loc.file_id, loc.line_no))
+                    .collect();
+
+                for (file_id, line_no) in locations {
+                    let (Some(path), Ok(content)) = (content_reader.get_file_path(file_id), content_reader.get_file_content(file_id)) else {
+                        continue;
+                    };
+                    let verified = content.lines().nth((line_no as usize).saturating_sub(1))
+                        .is_some_and(|line| line.to_lowercase().contains(term.as_str()));
+                    if verified {
+                        hits.entry(path.to_string_lossy().to_string()).or_default().push((line_no as usize, term_idx));
+                    }
+                }
+            }
+        }
+
+        let keyword_weight = config.keyword_weight.clamp(0.0, 1.0);
+        let mut scored: Vec<(f64, &embeddings::EmbeddedChunk)> = index.chunks
+            .iter()
+            .filter(|c| in_changed_files(changed, std::path::Path::new(&c.chunk.path)))
+            .map(|c| {
+                let matched: HashSet<usize> = hits.get(&c.chunk.path)
+                    .map(|lines| lines.iter()
+                        .filter(|(line, _)| (c.chunk.start_line..=c.chunk.end_line).contains(line))
+                        .map(|(_, term)| *term)
+                        .collect())
+                    .unwrap_or_default();
+                let keyword = if terms.is_empty() { 0.0 } else { matched.len() as f64 / terms.len() as f64 };
+                let score = (1.0 - keyword_weight) * c.similarity(&query).max(0.0) + keyword_weight * keyword;
+                (score, c)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(config.candidates);
+        log::debug!("Semantic query {:?} scored {} chunks (keyword terms: {:?})", pattern, index.chunks.len(), terms);
+
+        Ok(scored
+            .into_iter()
+            .map(|(score, c)| {
+                let chunk = &c.chunk;
+                let preview = content_reader.get_file_id_by_path(&chunk.path)
+                    .and_then(|fid| content_reader.get_file_content(fid).ok())
+                    .and_then(|content| content.lines().nth(chunk.start_line.saturating_sub(1)))
+                    .unwrap_or_default()
+                    .to_string();
+                let mut result = SearchResult::new(
+                    chunk.path.clone(),
+                    Language::from_path(std::path::Path::new(&chunk.path)),
+                    chunk.kind.clone(),
+                    chunk.symbol.clone(),
+                    Span { start_line: chunk.start_line, end_line: chunk.end_line },
+                    None,
+                    preview,
+                );
+                result.score = Some((score * 1000.0).round() / 1000.0);
+                result
+            })
+            .collect())
+    }
+
+    /// Get candidate results from the identifier-token index (--tokens)
+    ///
+    /// The pattern is split into word parts like an identifier, so `user config`,
+    /// `userConfig` and `user_config` are the same query. Lines holding every part
+    /// are then verified: one identifier on the line must contain all of them.
+    fn get_token_candidates(&self, pattern: &str, budget: Option<&Budget>, memory: &MemoryGuard, changed: Option<&ChangedFiles>) -> Result<Vec<SearchResult>> {
+        let words = crate::tokens::identifier_words(pattern);
+        if words.is_empty() {
+            anyhow::bail!(
+                "Pattern '{}' has no identifier words to search for.\n\
+                 \n\
+                 --tokens matches word parts of identifiers (letters and digits).\n\
+                 Example: rfx query \"user config\" --tokens",
+                pattern
+            );
+        }
+
+        let content_path = self.cache.path().join("content.bin");
+        let content_reader = ContentReader::open(&content_path)
+            .context("Failed to open content store")?;
+        let token_index = self.load_token_index(&content_reader)?;
+
+        // Group candidate lines by file
+        use std::collections::HashMap;
+        let mut lines_by_file: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (file_id, line_no) in token_index.search(&words) {
+            lines_by_file.entry(file_id).or_default().push(line_no);
+        }
+        log::debug!("Token index matched {:?} in {} files", words, lines_by_file.len());
+
+        // --changed-since: drop untouched files before scanning their content
+        if changed.is_some() {
+            lines_by_file.retain(|file_id, _| {
+                content_reader.get_file_path(*file_id)
+                    .is_some_and(|path| in_changed_files(changed, path))
+            });
+        }
+
+        use rayon::prelude::*;
+
+        let results: Vec<SearchResult> = lines_by_file
+            .par_iter()
+            .flat_map(|(file_id, line_numbers)| {
+                // Out of time budget or memory: leave the file unverified
+                if !within_budget(budget) || !memory.admit() {
+                    return Vec::new();
+                }
+
+                let file_path = match content_reader.get_file_path(*file_id) {
+                    Some(p) => p,
+                    None => return Vec::new(),
+                };
+                let content = match content_reader.get_file_content(*file_id) {
+                    Ok(c) => c,
+                    Err(_) => return Vec::new(),
+                };
+
+                let lang = Language::from_path(file_path);
+                let file_path_str = file_path.to_string_lossy().to_string();
+                let lines: Vec<&str> = content.lines().collect();
+
+                let file_results = line_numbers
+                    .iter()
+                    .filter_map(|&line_no| {
+                        let line = *lines.get((line_no as usize).checked_sub(1)?)?;
+
+                        // The words may come from different identifiers on the line
+                        if !crate::tokens::identifiers(line).any(|id| crate::tokens::has_all_words(id, &words)) {
+                            return None;
+                        }
+
+                        Some(SearchResult {
+                            path: file_path_str.clone(),
+                            lang,
+                            kind: SymbolKind::Unknown("text_match".to_string()),
+                            symbol: None,
+                            parent: None,
+                            qualified_name: None,
+                            span: Span {
+                                start_line: line_no as usize,
+                                end_line: line_no as usize,
+                            },
+                            preview: line.to_string(),
+                            dependencies: None,
+                            repo: None,
+                            signature: None,
+                            doc: None,
+                            exported: None,
+                            captures: None,
+                            score: None,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                memory.charge(results_bytes(&file_results));
                 file_results
             })
             .collect();
@@ -2053,6 +3573,43 @@ impl QueryEngine {
         Ok(results)
     }
 
+    /// Load tokens.bin, rebuilding it from the content store if it's missing or stale
+    ///
+    /// Caches indexed before the token index existed have no tokens.bin until
+    /// their next full reindex, so their queries pay for one in-memory build.
+    fn load_token_index(&self, content_reader: &ContentReader) -> Result<crate::tokens::TokenIndex> {
+        use crate::tokens::TokenIndex;
+
+        let tokens_path = self.cache.path().join(crate::cache::TOKENS_BIN);
+        if tokens_path.exists() {
+            match TokenIndex::load(&tokens_path) {
+                Ok(index) if index.file_count() == content_reader.file_count() => {
+                    log::debug!("Loaded token index from disk: {} tokens, {} files",
+                               index.token_count(), index.file_count());
+                    return Ok(index);
+                }
+                Ok(index) => {
+                    log::warn!("tokens.bin covers {} files but the content store has {} - rebuilding",
+                              index.file_count(), content_reader.file_count());
+                }
+                Err(e) => {
+                    log::warn!("Failed to load token index from disk: {} - rebuilding", e);
+                }
+            }
+        } else {
+            log::debug!("tokens.bin not found, rebuilding from content store");
+        }
+
+        let mut token_index = TokenIndex::new();
+        for file_id in 0..content_reader.file_count() {
+            let content = content_reader.get_file_content(file_id as u32)?;
+            token_index.index_file(file_id as u32, content);
+        }
+        token_index.finalize();
+
+        Ok(token_index)
+    }
+
     /// Get candidate results using regex patterns with trigram optimization
     ///
     /// # Algorithm
@@ -2076,18 +3633,19 @@ impl QueryEngine {
     /// - Best case (pattern with literals): <20ms (trigram optimization)
     /// - Typical case (alternation/sequential): 5-15ms on small codebases (<100 files)
     /// - Worst case (no literals like `.*`): ~100ms (full scan)
-    fn get_regex_candidates(&self, pattern: &str, timeout: Option<&std::time::Duration>, start_time: &std::time::Instant, suppress_output: bool) -> Result<Vec<SearchResult>> {
+    #[allow(clippy::too_many_arguments)]
+    fn get_regex_candidates(&self, pattern: &str, timeout: Option<&std::time::Duration>, start_time: &std::time::Instant, suppress_output: bool, budget: Option<&Budget>, memory: &MemoryGuard, changed: Option<&ChangedFiles>) -> Result<Vec<SearchResult>> {
         // Step 1: Compile the regex
         let regex = Regex::new(pattern)
-            .with_context(|| format!("Invalid regex pattern: {}", pattern))?;
+            .map_err(|e| ReflexError::InvalidRegex(format!("Invalid regex pattern: {}: {}", pattern, e)))?;
 
         // Check timeout before expensive operations
         if let Some(timeout_duration) = timeout {
             if start_time.elapsed() > *timeout_duration {
-                anyhow::bail!(
+                return Err(ReflexError::Timeout(format!(
                     "Query timeout exceeded ({} seconds) during regex compilation",
                     timeout_duration.as_secs()
-                );
+                )).into());
             }
         }
 
@@ -2114,12 +3672,16 @@ impl QueryEngine {
             for file_id in 0..content_reader.file_count() {
                 let file_path = content_reader.get_file_path(file_id as u32)
                     .context("Invalid file_id")?;
+                if !in_changed_files(changed, file_path) || !within_budget(budget) || !memory.admit() {
+                    continue;
+                }
                 let content = content_reader.get_file_content(file_id as u32)?;
 
                 self.find_regex_matches_in_file(
                     &regex,
                     file_path,
                     content,
+                    memory,
                     &mut results,
                 )?;
             }
@@ -2145,8 +3707,11 @@ impl QueryEngine {
                 for file_id in 0..content_reader.file_count() {
                     let file_path = content_reader.get_file_path(file_id as u32)
                         .context("Invalid file_id")?;
+                    if !in_changed_files(changed, file_path) || !within_budget(budget) || !memory.admit() {
+                        continue;
+                    }
                     let content = content_reader.get_file_content(file_id as u32)?;
-                    self.find_regex_matches_in_file(&regex, file_path, content, &mut results)?;
+                    self.find_regex_matches_in_file(&regex, file_path, content, memory, &mut results)?;
                 }
             } else {
                 // Search for each literal sequence and union the results
@@ -2175,12 +3740,16 @@ impl QueryEngine {
                 for &file_id in &final_candidates {
                     let file_path = trigram_index.get_file(file_id)
                         .context("Invalid file_id from trigram search")?;
+                    if !in_changed_files(changed, file_path) || !within_budget(budget) || !memory.admit() {
+                        continue;
+                    }
                     let content = content_reader.get_file_content(file_id)?;
 
                     self.find_regex_matches_in_file(
                         &regex,
                         file_path,
                         content,
+                        memory,
                         &mut results,
                     )?;
                 }
@@ -2191,21 +3760,20 @@ impl QueryEngine {
         Ok(results)
     }
 
-    /// Find all regex matches in a single file
+    /// Find all regex matches in a single file (charging them to `memory`)
     fn find_regex_matches_in_file(
         &self,
         regex: &Regex,
         file_path: &std::path::Path,
         content: &str,
+        memory: &MemoryGuard,
         results: &mut Vec<SearchResult>,
     ) -> Result<()> {
+        let first_match = results.len();
         let file_path_str = file_path.to_string_lossy().to_string();
 
-        // Detect language from file extension
-        let ext = file_path.extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-        let lang = Language::from_extension(ext);
+        // Detect language from the file name
+        let lang = Language::from_path(file_path);
 
         // Find all regex matches line by line
         for (line_idx, line) in content.lines().enumerate() {
@@ -2223,29 +3791,57 @@ impl QueryEngine {
                     lang: lang.clone(),
                     kind: SymbolKind::Unknown("regex_match".to_string()),
                     symbol: None,  // No symbol name for regex matches
+                    parent: None,
+                    qualified_name: None,
                     span: Span {
                         start_line: line_no,
                         end_line: line_no,
                     },
                     preview: line.to_string(),
                     dependencies: None,
+                    repo: None,
+                    signature: None,
+                    doc: None,
+                    exported: None,
+                    captures: None,
+                    score: None,
                 });
             }
         }
 
+        memory.charge(results_bytes(&results[first_match..]));
         Ok(())
     }
 
     /// Helper function to find file_id in ContentReader by matching path
-    fn find_file_id(content_reader: &ContentReader, target_path: &str) -> Option<u32> {
-        for file_id in 0..content_reader.file_count() {
-            if let Some(path) = content_reader.get_file_path(file_id as u32) {
-                if path.to_string_lossy() == target_path {
-                    return Some(file_id as u32);
-                }
+    /// Widen symbol spans according to `policy` (text matches are left alone)
+    fn widen_spans(&self, results: &mut [SearchResult], policy: SpanPolicy) {
+        if policy == SpanPolicy::Body {
+            return;
+        }
+
+        let content_path = self.cache.path().join("content.bin");
+        let Ok(content_reader) = ContentReader::open(&content_path) else {
+            return;
+        };
+
+        use std::collections::HashMap;
+        let mut contents: HashMap<String, Option<&str>> = HashMap::new();
+        for result in results.iter_mut() {
+            if matches!(result.kind, SymbolKind::Unknown(_)) {
+                continue;
+            }
+
+            let content = contents.entry(result.path.clone()).or_insert_with(|| {
+                content_reader.file_id_for_path(&result.path)
+                    .and_then(|file_id| content_reader.get_file_content(file_id).ok())
+            });
+
+            if let Some(content) = *content {
+                let lines: Vec<&str> = content.lines().collect();
+                result.span = apply_span_policy(&lines, &result.span, result.lang, policy);
             }
         }
-        None
     }
 
     /// Rebuild trigram index from content store (fallback when trigrams.bin is missing)
@@ -2280,7 +3876,9 @@ impl QueryEngine {
     /// - "./services/**/*.php" → "./services/**/*.php" (unchanged)
     /// - "**/services/**/*.php" → "**/services/**/*.php" (unchanged)
     /// - "/absolute/path/**" → "/absolute/path/**" (unchanged)
-    fn normalize_glob_pattern(pattern: &str) -> String {
+    /// - "src\\**\\*.rs" → "./src/**/*.rs" (on Windows)
+    pub(crate) fn normalize_glob_pattern(pattern: &str) -> String {
+        let pattern = crate::paths::to_slash(pattern);
         if pattern.starts_with('.') || pattern.starts_with('/') || pattern.starts_with('*') {
             // Already has a prefix that works - don't modify
             pattern.to_string()
@@ -2290,6 +3888,81 @@ impl QueryEngine {
         }
     }
 
+    /// Convert Windows separators in `--file`, `--glob` and `--exclude` filters to `/`
+    ///
+    /// Indexed paths always use `/`, so `--file src\\query` and `--file src/query`
+    /// select the same files.
+    fn normalize_path_filters(filter: &mut QueryFilter) {
+        for pattern in filter.glob_patterns.iter_mut()
+            .chain(filter.exclude_patterns.iter_mut())
+            .chain(filter.file_pattern.iter_mut())
+        {
+            if let std::borrow::Cow::Owned(normalized) = crate::paths::to_slash(pattern) {
+                *pattern = normalized;
+            }
+        }
+    }
+
+    /// Expand `@name` workspace root references in glob, exclude, and file filters
+    ///
+    /// Roots are recorded by `rfx index --root`. Examples (with root `api` at `packages/api`):
+    /// - `--glob '@api/**/*.rs'` → `./packages/api/**/*.rs`
+    /// - `--file @api` → `packages/api`
+    ///
+    /// Globs are anchored at the workspace root the index was built from, so they
+    /// match stored paths whether those are `./`-relative or absolute.
+    /// Patterns naming an unknown root are left untouched so literal paths such as
+    /// `@types/` (npm scopes) keep working.
+    fn resolve_root_aliases(&self, filter: &mut QueryFilter) -> Result<()> {
+        let uses_alias = filter.glob_patterns.iter()
+            .chain(filter.exclude_patterns.iter())
+            .chain(filter.file_pattern.iter())
+            .any(|p| p.starts_with('@'));
+
+        if !uses_alias {
+            return Ok(());
+        }
+
+        let roots = self.cache.list_roots()?;
+        if roots.is_empty() {
+            return Ok(());
+        }
+
+        let workspace_root = self.cache.workspace_root();
+        for pattern in filter.glob_patterns.iter_mut().chain(filter.exclude_patterns.iter_mut()) {
+            if let Some(expanded) = Self::expand_root_alias(pattern, &roots) {
+                let anchored = crate::paths::to_slash(&workspace_root.join(&expanded).to_string_lossy()).into_owned();
+                log::debug!("Expanded root alias '{}' → '{}'", pattern, anchored);
+                *pattern = anchored;
+            }
+        }
+
+        // `--file` is a substring filter, so the root's relative path matches either way
+        if let Some(pattern) = filter.file_pattern.as_mut() {
+            if let Some(expanded) = Self::expand_root_alias(pattern, &roots) {
+                log::debug!("Expanded root alias '{}' → '{}'", pattern, expanded);
+                *pattern = expanded;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expand a single `@name[/rest]` pattern, returning None if it names no known root
+    fn expand_root_alias(pattern: &str, roots: &[RootInfo]) -> Option<String> {
+        let alias = pattern.strip_prefix('@')?;
+        let (name, rest) = match alias.split_once('/') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (alias, None),
+        };
+
+        let root = roots.iter().find(|r| r.name == name)?;
+        Some(match rest {
+            Some(rest) => format!("{}/{}", root.path, rest),
+            None => root.path.clone(),
+        })
+    }
+
     /// Check if pattern appears at word boundaries in a line
     ///
     /// Word boundary is defined as:
@@ -2301,9 +3974,12 @@ impl QueryEngine {
     /// - "Error" matches "Error" but not "NetworkError"
     /// - "parse" matches "parse()" but not "parseUser()"
     fn has_word_boundary_match(line: &str, pattern: &str) -> bool {
-        // Build regex: \bpattern\b
-        let escaped_pattern = regex::escape(pattern);
-        let pattern_with_boundaries = format!(r"\b{}\b", escaped_pattern);
+        // Build regex: \bpattern\b, with boundaries only at word-character ends
+        // (`\b/users` could never match after a quote or space)
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = if pattern.starts_with(is_word) { r"\b" } else { "" };
+        let end = if pattern.ends_with(is_word) { r"\b" } else { "" };
+        let pattern_with_boundaries = format!("{}{}{}", start, regex::escape(pattern), end);
 
         if let Ok(re) = Regex::new(&pattern_with_boundaries) {
             re.is_match(line)
@@ -2314,12 +3990,74 @@ impl QueryEngine {
         }
     }
 
-    /// Get index status for programmatic use (doesn't print warnings)
-    ///
+    /// Resolve `--changed-since` into the set of files the query is restricted to
+    fn changed_files(&self, filter: &QueryFilter) -> Result<Option<ChangedFiles>> {
+        let Some(ref git_ref) = filter.changed_since else {
+            return Ok(None);
+        };
+
+        let root = self.cache.workspace_root();
+        let paths = crate::git::changed_files_since(&root, git_ref)?;
+        log::debug!("{} files changed since '{}'", paths.len(), git_ref);
+
+        Ok(Some(ChangedFiles {
+            root,
+            paths: paths.into_iter().collect(),
+        }))
+    }
+
+    /// Dirty working-tree overlay for the current branch, if one was built
+    fn active_overlay(&self) -> Option<Overlay> {
+        let overlay = Overlay::open(&self.cache)?;
+        let root = self.cache.workspace_root();
+        let branch = if crate::git::is_git_repo(&root) {
+            crate::git::get_current_branch(&root).ok()?
+        } else {
+            "_default".to_string()
+        };
+        (overlay.branch == branch).then_some(overlay)
+    }
+
+    /// Check the cache exists and can be read by this version of Reflex
+    ///
+    /// Caches from an older Reflex are upgraded in place where possible;
+    /// otherwise the error asks for a re-index instead of reporting corruption.
+    fn ensure_cache_usable(&self) -> Result<()> {
+        if !self.cache.exists() {
+            return Err(ReflexError::IndexMissing(
+                "Index not found. Run 'rfx index' to build the cache first.".to_string()
+            ).into());
+        }
+
+        if let Some(reason) = self.cache.migrate()?.rebuild_required {
+            return Err(ReflexError::IndexStale(format!(
+                "The index must be rebuilt for this version of Reflex: {}. Run 'rfx index' to rebuild it.",
+                reason
+            )).into());
+        }
+
+        if let Err(e) = self.cache.validate() {
+            return Err(ReflexError::IndexCorrupt(format!(
+                "Cache appears to be corrupted: {}. Run 'rfx clear' followed by 'rfx index' to rebuild.",
+                e
+            )).into());
+        }
+
+        Ok(())
+    }
+
+    /// Get index status for programmatic use (doesn't print warnings)
+    ///
     /// Returns (status, can_trust_results, warning) tuple for JSON output.
     /// This is optimized for AI agents to detect staleness and auto-reindex.
     fn get_index_status(&self) -> Result<(IndexStatus, bool, Option<IndexWarning>)> {
-        let root = std::env::current_dir()?;
+        // A revision segment never goes stale: its commit can't change
+        if self.cache.revision().is_some() {
+            return Ok((IndexStatus::Fresh, true, None));
+        }
+
+        // Workspace of the queried index (not necessarily the current directory)
+        let root = self.cache.workspace_root();
 
         // Check git state if in a git repo
         if crate::git::is_git_repo(&root) {
@@ -2334,6 +4072,7 @@ impl QueryEngine {
                             indexed_branch: None,
                             current_commit: None,
                             indexed_commit: None,
+                            skipped_files: Vec::new(),
                         }),
                     };
                     return Ok((IndexStatus::Stale, false, Some(warning)));
@@ -2343,7 +4082,15 @@ impl QueryEngine {
                 if let (Ok(current_commit), Ok(branch_info)) =
                     (crate::git::get_current_commit(&root), self.cache.get_branch_info(&current_branch)) {
 
-                    if branch_info.commit_sha != current_commit {
+                    // A dirty overlay built at HEAD covers everything changed since the full index
+                    let overlay = Overlay::open(&self.cache).filter(|o| o.branch == current_branch);
+                    let overlay_at_head = overlay.as_ref()
+                        .is_some_and(|o| o.commit.as_deref() == Some(current_commit.as_str()));
+                    let indexed_time = overlay.as_ref()
+                        .filter(|_| overlay_at_head)
+                        .map_or(branch_info.last_indexed, |o| o.indexed_at.max(branch_info.last_indexed));
+
+                    if branch_info.commit_sha != current_commit && !overlay_at_head {
                         let warning = IndexWarning {
                             reason: format!(
                                 "Commit changed from {} to {}",
@@ -2356,6 +4103,7 @@ impl QueryEngine {
                                 indexed_branch: Some(current_branch.clone()),
                                 current_commit: Some(current_commit.clone()),
                                 indexed_commit: Some(branch_info.commit_sha.clone()),
+                                skipped_files: Vec::new(),
                             }),
                         };
                         return Ok((IndexStatus::Stale, false, Some(warning)));
@@ -2369,11 +4117,10 @@ impl QueryEngine {
 
                         for (path, _indexed_hash) in branch_files.iter().take(SAMPLE_SIZE) {
                             checked += 1;
-                            let file_path = std::path::Path::new(path);
+                            let file_path = root.join(path);
 
-                            if let Ok(metadata) = std::fs::metadata(file_path) {
+                            if let Ok(metadata) = std::fs::metadata(&file_path) {
                                 if let Ok(modified) = metadata.modified() {
-                                    let indexed_time = branch_info.last_indexed;
                                     let file_time = modified.duration_since(std::time::UNIX_EPOCH)
                                         .unwrap_or_default()
                                         .as_secs() as i64;
@@ -2396,6 +4143,7 @@ impl QueryEngine {
                                     indexed_branch: Some(branch_info.branch.clone()),
                                     current_commit: Some(current_commit.clone()),
                                     indexed_commit: Some(branch_info.commit_sha.clone()),
+                                    skipped_files: Vec::new(),
                                 }),
                             };
                             return Ok((IndexStatus::Stale, false, Some(warning)));
@@ -2419,7 +4167,12 @@ impl QueryEngine {
     /// 2. Commit changed: HEAD moved since indexing
     /// 3. File changes: quick mtime check on sample of files (if available)
     fn check_index_freshness(&self, filter: &QueryFilter) -> Result<()> {
-        let root = std::env::current_dir()?;
+        if self.cache.revision().is_some() {
+            return Ok(());
+        }
+
+        // Workspace of the queried index (not necessarily the current directory)
+        let root = self.cache.workspace_root();
 
         // Check git state if in a git repo
         if crate::git::is_git_repo(&root) {
@@ -2436,7 +4189,15 @@ impl QueryEngine {
                 if let (Ok(current_commit), Ok(branch_info)) =
                     (crate::git::get_current_commit(&root), self.cache.get_branch_info(&current_branch)) {
 
-                    if branch_info.commit_sha != current_commit {
+                    // A dirty overlay built at HEAD covers everything changed since the full index
+                    let overlay = Overlay::open(&self.cache).filter(|o| o.branch == current_branch);
+                    let overlay_at_head = overlay.as_ref()
+                        .is_some_and(|o| o.commit.as_deref() == Some(current_commit.as_str()));
+                    let indexed_time = overlay.as_ref()
+                        .filter(|_| overlay_at_head)
+                        .map_or(branch_info.last_indexed, |o| o.indexed_at.max(branch_info.last_indexed));
+
+                    if branch_info.commit_sha != current_commit && !overlay_at_head {
                         if !filter.suppress_output {
                             output::warn(&format!("⚠️  WARNING: Index may be stale (commit changed: {} → {}). Consider running 'rfx index'.",
                                      &branch_info.commit_sha[..7], &current_commit[..7]));
@@ -2453,12 +4214,11 @@ impl QueryEngine {
 
                         for (path, _indexed_hash) in branch_files.iter().take(SAMPLE_SIZE) {
                             checked += 1;
-                            let file_path = std::path::Path::new(path);
+                            let file_path = root.join(path);
 
                             // Check if file exists and has been modified (mtime/size heuristic)
-                            if let Ok(metadata) = std::fs::metadata(file_path) {
+                            if let Ok(metadata) = std::fs::metadata(&file_path) {
                                 if let Ok(modified) = metadata.modified() {
-                                    let indexed_time = branch_info.last_indexed;
                                     let file_time = modified.duration_since(std::time::UNIX_EPOCH)
                                         .unwrap_or_default()
                                         .as_secs() as i64;
@@ -2599,11 +4359,76 @@ pub fn generate_ai_instruction(
     None
 }
 
+/// Merge responses from several indexes into one, labelling each file with its repo
+///
+/// Files keep the order of `responses` (then each index's own order). Pagination
+/// counts are summed: `--limit` and `--offset` apply to each index separately.
+/// The merged response is stale if any index is stale; the first stale warning
+/// is kept with its reason prefixed by the repo label.
+pub fn merge_responses(responses: Vec<(String, QueryResponse)>) -> QueryResponse {
+    let mut merged = QueryResponse {
+        ai_instruction: None,
+        status: IndexStatus::Fresh,
+        can_trust_results: true,
+        warning: None,
+        pagination: crate::models::PaginationInfo {
+            total: 0,
+            count: 0,
+            offset: 0,
+            limit: None,
+            has_more: false,
+        },
+        complete: true,
+        unscanned_candidates: 0,
+        partial: None,
+        truncated: false,
+        results: Vec::new(),
+        deleted: None,
+        warnings: Vec::new(),
+        plan: None,
+        timings: None,
+    };
+
+    for (idx, (repo, response)) in responses.into_iter().enumerate() {
+        if idx == 0 {
+            merged.pagination.offset = response.pagination.offset;
+            merged.pagination.limit = response.pagination.limit;
+        }
+        if response.status == IndexStatus::Stale {
+            merged.status = IndexStatus::Stale;
+        }
+        merged.can_trust_results &= response.can_trust_results;
+        if merged.warning.is_none() {
+            merged.warning = response.warning.map(|mut warning| {
+                warning.reason = format!("[{}] {}", repo, warning.reason);
+                warning
+            });
+        }
+        merged.pagination.total += response.pagination.total;
+        merged.pagination.count += response.pagination.count;
+        merged.pagination.has_more |= response.pagination.has_more;
+        merged.complete &= response.complete;
+        merged.unscanned_candidates += response.unscanned_candidates;
+        merged.partial = merged.partial.or(response.partial);
+        if let Some(deleted) = response.deleted {
+            merged.deleted.get_or_insert_with(Vec::new).extend(deleted);
+        }
+        merged.warnings.extend(response.warnings);
+
+        merged.results.extend(response.results.into_iter().map(|mut file| {
+            file.repo = Some(repo.clone());
+            file
+        }));
+    }
+
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::indexer::Indexer;
-    use crate::models::IndexConfig;
+    use crate::models::{CellType, IndexConfig, NotebookCell, WorkspaceRoot};
     use std::fs;
     use tempfile::TempDir;
 
@@ -2632,7 +4457,7 @@ mod tests {
 
         // Test that kind implies symbols_mode (handled in CLI layer)
         let filter_with_kind = QueryFilter {
-            kind: Some(SymbolKind::Function),
+            kinds: vec![SymbolKind::Function],
             symbols_mode: true,
             ..Default::default()
         };
@@ -2700,6 +4525,81 @@ mod tests {
         assert!(results.iter().any(|r| r.kind == SymbolKind::Function));
     }
 
+    #[test]
+    fn test_symbol_search_with_docs() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("main.rs"),
+            "/// Says hello\nfn greet() {}\nfn main() {\n    greet();\n}"
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        // Docs are omitted by default
+        let filter = QueryFilter {
+            symbols_mode: true,
+            ..Default::default()
+        };
+        let results = engine.search("greet", filter).unwrap();
+        assert!(results.iter().all(|r| r.doc.is_none()));
+
+        let filter = QueryFilter {
+            symbols_mode: true,
+            with_docs: true,
+            ..Default::default()
+        };
+        let results = engine.search("greet", filter).unwrap();
+        let greet = results.iter().find(|r| r.symbol.as_deref() == Some("greet")).unwrap();
+        assert_eq!(greet.doc.as_deref(), Some("Says hello"));
+    }
+
+    #[test]
+    fn test_span_policy_widens_expanded_body() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("point.rs"),
+            "/// A point\n#[derive(Debug)]\npub struct Point {\n    x: i32,\n}\n"
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let search = |span_policy| {
+            let filter = QueryFilter {
+                symbols_mode: true,
+                exact: true,
+                expand: true,
+                span_policy,
+                ..Default::default()
+            };
+            engine.search("Point", filter).unwrap().remove(0)
+        };
+
+        let body = search(SpanPolicy::Body);
+        assert_eq!(body.span.start_line, 3);
+        assert!(body.preview.starts_with("pub struct Point"));
+
+        let with_attrs = search(SpanPolicy::WithAttrs);
+        assert_eq!(with_attrs.span.start_line, 2);
+        assert!(with_attrs.preview.starts_with("#[derive(Debug)]"));
+
+        let with_docs = search(SpanPolicy::WithDocs);
+        assert_eq!((with_docs.span.start_line, with_docs.span.end_line), (1, 5));
+        assert!(with_docs.preview.starts_with("/// A point"));
+    }
+
     #[test]
     fn test_regex_search() {
         let temp = TempDir::new().unwrap();
@@ -2781,7 +4681,7 @@ mod tests {
         // Filter to functions only (includes methods)
         let filter = QueryFilter {
             symbols_mode: true,
-            kind: Some(SymbolKind::Function),
+            kinds: vec![SymbolKind::Function],
             use_contains: true,  // "mai" is substring of "main"
             ..Default::default()
         };
@@ -2822,6 +4722,73 @@ mod tests {
         assert!(!results.iter().any(|r| r.path.contains("tests/")));
     }
 
+    #[test]
+    fn test_root_alias_file_filter() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("packages/api")).unwrap();
+        fs::create_dir_all(project.join("packages/web")).unwrap();
+
+        fs::write(project.join("packages/api/lib.rs"), "fn foo() {}").unwrap();
+        fs::write(project.join("packages/web/lib.rs"), "fn foo() {}").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let config = IndexConfig {
+            roots: vec![
+                WorkspaceRoot::parse("packages/api").unwrap(),
+                WorkspaceRoot::parse("packages/web").unwrap(),
+            ],
+            ..Default::default()
+        };
+        let indexer = Indexer::new(cache, config);
+        indexer.index(&project, false).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let engine = QueryEngine::new(cache);
+
+        // Address the api root by name
+        let filter = QueryFilter {
+            file_pattern: Some("@api".to_string()),
+            ..Default::default()
+        };
+        let results = engine.search("foo", filter).unwrap();
+
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.path.contains("packages/api")));
+
+        // Same root through a glob
+        let filter = QueryFilter {
+            glob_patterns: vec!["@web/**/*.rs".to_string()],
+            ..Default::default()
+        };
+        let results = engine.search("foo", filter).unwrap();
+
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r.path.contains("packages/web")));
+    }
+
+    #[test]
+    fn test_expand_root_alias() {
+        let roots = vec![RootInfo {
+            name: "api".to_string(),
+            path: "packages/api".to_string(),
+            file_count: 1,
+            last_indexed: 0,
+        }];
+
+        assert_eq!(
+            QueryEngine::expand_root_alias("@api/**/*.rs", &roots),
+            Some("packages/api/**/*.rs".to_string())
+        );
+        assert_eq!(
+            QueryEngine::expand_root_alias("@api", &roots),
+            Some("packages/api".to_string())
+        );
+        // Unknown roots and plain patterns are left alone
+        assert_eq!(QueryEngine::expand_root_alias("@types/node", &roots), None);
+        assert_eq!(QueryEngine::expand_root_alias("src/**", &roots), None);
+    }
+
     #[test]
     fn test_limit_filter() {
         let temp = TempDir::new().unwrap();
@@ -2883,7 +4850,51 @@ mod tests {
         assert_eq!(results[0].symbol.as_deref(), Some("test"));
     }
 
-    // ==================== Expand Mode Tests ====================
+    #[test]
+    fn test_scope_filter() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("engine.py"),
+            "class Engine:\n    def search(self):\n        return lookup()\n\n\
+             def search():\n    return lookup()\n\n\
+             def other():\n    return lookup()\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let scoped = |scope: &str| {
+            let filter = QueryFilter { scope: Some(scope.to_string()), ..Default::default() };
+            let mut lines: Vec<usize> = engine.search("lookup", filter).unwrap()
+                .iter()
+                .map(|r| r.span.start_line)
+                .collect();
+            lines.sort();
+            lines
+        };
+
+        // Every definition named `search` scopes the matches
+        assert_eq!(scoped("search"), vec![3, 6]);
+        // Qualified names pick the method only
+        assert_eq!(scoped("Engine.search"), vec![3]);
+        assert_eq!(scoped("engine.py:other"), vec![9]);
+        assert!(scoped("missing.py:other").is_empty());
+    }
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!(parse_scope("search"), (None, "search"));
+        assert_eq!(parse_scope("src/query.rs:search"), (Some("src/query.rs"), "search"));
+        assert_eq!(parse_scope("Engine::search"), (None, "Engine::search"));
+        assert_eq!(parse_scope("src/lib.rs:Engine::search"), (Some("src/lib.rs"), "Engine::search"));
+    }
+
+        // ==================== Expand Mode Tests ====================
 
     #[test]
     fn test_expand_mode() {
@@ -3094,7 +5105,7 @@ mod tests {
         // Combine language, kind, and file pattern filters
         let filter = QueryFilter {
             language: Some(Language::Rust),
-            kind: Some(SymbolKind::Function),
+            kinds: vec![SymbolKind::Function],
             file_pattern: Some("src/main".to_string()),
             symbols_mode: true,
             ..Default::default()
@@ -3151,7 +5162,7 @@ mod tests {
 
         // Search for structs that contain "oin" (Point contains it, Line doesn't)
         let filter = QueryFilter {
-            kind: Some(SymbolKind::Struct),
+            kinds: vec![SymbolKind::Struct],
             symbols_mode: true,
             use_contains: true,  // "oin" is substring of "Point"
             ..Default::default()
@@ -3164,6 +5175,96 @@ mod tests {
         assert!(results.iter().any(|r| r.symbol.as_deref() == Some("Point")));
     }
 
+    #[test]
+    fn test_search_multiple_kinds_and_kind_not() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("types.rs"),
+            "struct Shape {}\nenum ShapeKind { A }\ntrait ShapeLike {}\nfn shape() {}\nimpl Shape { fn area(&self) {} }\n",
+        ).unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        // One keyword query lists every type definition
+        let filter = QueryFilter {
+            kinds: SymbolKind::parse_list("struct, Enum,TRAIT"),
+            symbols_mode: true,
+            ..Default::default()
+        };
+        let mut names: Vec<String> = engine.search("struct", filter).unwrap().into_iter().filter_map(|r| r.symbol).collect();
+        names.sort();
+        assert_eq!(names, vec!["Shape", "ShapeKind", "ShapeLike"]);
+
+        // --kind-not function also drops methods, like --kind function keeps them
+        let filter = QueryFilter {
+            exclude_kinds: vec![SymbolKind::Function],
+            symbols_mode: true,
+            use_contains: true,
+            ..Default::default()
+        };
+        assert!(filter.filters_kinds());
+        assert!(!filter.matches_kind(&SymbolKind::Method));
+        let results = engine.search("hape", filter).unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| !matches!(r.kind, SymbolKind::Function | SymbolKind::Method)));
+
+        // The deprecated single `kind` still filters (merged into `kinds` by the entry point)
+        #[allow(deprecated)]
+        let filter = QueryFilter {
+            kind: Some(SymbolKind::Enum),
+            symbols_mode: true,
+            use_contains: true,
+            ..Default::default()
+        };
+        let names: Vec<String> = engine.search("hape", filter).unwrap().into_iter().filter_map(|r| r.symbol).collect();
+        assert_eq!(names, vec!["ShapeKind"]);
+    }
+
+    #[test]
+    fn test_search_not_patterns() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("app.rs"), "let client = Client::new();\nlet client = test_double();\n").unwrap();
+        fs::write(project.join("mock.rs"), "let client = MockClient::default();\n").unwrap();
+        fs::write(project.join("other.rs"), "fn client() { test() }\n").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        let filter = QueryFilter {
+            not_patterns: vec!["test".to_string(), "Mock".to_string()],
+            explain: true,
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("client", filter).unwrap();
+        let previews: Vec<&str> = response.results.iter()
+            .flat_map(|f| f.matches.iter().map(|m| m.preview.as_str()))
+            .collect();
+        assert_eq!(previews, vec!["let client = Client::new();"]);
+        assert_eq!(response.plan.unwrap().pruned_by_not, 3);
+
+        // Regex exclusions, which skip the trigram subtraction
+        let filter = QueryFilter {
+            use_regex: true,
+            not_patterns: vec![r"(test|Mock)\w*".to_string()],
+            ..Default::default()
+        };
+        let results = engine.search(r"client\b", filter).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("app.rs"));
+
+        let empty = QueryFilter { not_patterns: vec![String::new()], ..Default::default() };
+        assert!(engine.search("client", empty).is_err());
+    }
+
     // ==================== Metadata Tests ====================
 
     #[test]
@@ -3189,6 +5290,552 @@ mod tests {
         // Note: can_trust_results may be false if running in a git repo without branch index
     }
 
+    #[test]
+    fn test_search_with_metadata_explain() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("main.rs"), "fn handler() {}\nfn run() { handler() }\n").unwrap();
+        fs::write(project.join("app.py"), "def handler():\n    pass\n").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let plain = engine.search_with_metadata("handler", QueryFilter::default()).unwrap();
+        assert!(plain.plan.is_none() && plain.timings.is_none());
+
+        let filter = QueryFilter {
+            symbols_mode: true,
+            language: Some(Language::Rust),
+            explain: true,
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("handler", filter).unwrap();
+        let plan = response.plan.unwrap();
+        assert_eq!(plan.strategy, "trigram");
+        assert!(!plan.keyword_mode);
+        assert_eq!(plan.trigram_candidate_files, Some(2));
+        assert_eq!(plan.candidates, 3);
+        assert_eq!(plan.pruned_by_language, 1);
+        assert_eq!(plan.symbol_cache_hits + plan.symbol_parses, 1);
+        assert_eq!(plan.results, 1);
+        let timings = response.timings.unwrap();
+        assert!(timings.total_ms >= timings.symbols_ms);
+
+        let keyword = QueryFilter { symbols_mode: true, explain: true, ..Default::default() };
+        let plan = engine.search_with_metadata("fn", keyword).unwrap().plan.unwrap();
+        assert_eq!(plan.strategy, "keyword");
+        assert_eq!(plan.inferred_kind, Some(SymbolKind::Function));
+    }
+
+    #[test]
+    fn test_fuzzy_symbol_search() {
+        use crate::models::MatchMethod;
+
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("users.rs"),
+            "fn get_user_by_id() {}\nfn get_usr_by_id() {}\nfn render_page() {}\n"
+        ).unwrap();
+        fs::write(project.join("api.rs"), "fn getUserById() {}\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            fuzzy: true,
+            ..Default::default()
+        };
+
+        // Neither name contains the pattern literally
+        let results = engine.search("getUserByID", filter.clone()).unwrap();
+        let names: Vec<&str> = results.iter().filter_map(|r| r.symbol.as_deref()).collect();
+        assert_eq!(names, vec!["getUserById", "get_user_by_id", "get_usr_by_id"]);
+
+        // A stricter threshold drops the weaker match
+        let strict = QueryFilter { fuzzy_threshold: Some(0.95), ..filter.clone() };
+        assert_eq!(engine.search("getUserByID", strict).unwrap().len(), 2);
+
+        let response = engine.search_with_metadata("getUserByID", filter).unwrap();
+        let reason = response.results[0].matches[0].match_reason.clone().unwrap();
+        assert_eq!(reason.method, MatchMethod::Fuzzy);
+        assert!(reason.score.unwrap() >= 0.8);
+    }
+
+    #[test]
+    fn test_token_search_across_naming_conventions() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("config.rs"),
+            "fn parseUserConfig() {}\nfn load_user_config() {}\nfn user() { config(); }\n"
+        ).unwrap();
+        fs::write(project.join("paths.py"), "open(USER_CONFIG_PATH)\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        assert!(project.join(".reflex").join(crate::cache::TOKENS_BIN).exists());
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            tokens: true,
+            ..Default::default()
+        };
+
+        // Words split across two identifiers (line 3) don't match
+        let results = engine.search("user config", filter.clone()).unwrap();
+        let hits: Vec<(&str, usize)> = results
+            .iter()
+            .map(|r| (r.path.rsplit('/').next().unwrap(), r.span.start_line))
+            .collect();
+        assert_eq!(hits, vec![("config.rs", 1), ("config.rs", 2), ("paths.py", 1)]);
+
+        // Symbol mode matches definition names by word parts
+        let symbols = QueryFilter { symbols_mode: true, ..filter };
+        let results = engine.search("userConfig", symbols).unwrap();
+        let names: Vec<&str> = results.iter().filter_map(|r| r.symbol.as_deref()).collect();
+        assert_eq!(names, vec!["parseUserConfig", "load_user_config"]);
+    }
+
+    #[test]
+    fn test_search_with_metadata_match_reason() {
+        use crate::models::MatchMethod;
+
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("main.rs"), "fn parse() {}\nfn parse_args() { parse() }\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        // Substring matching, so `parse_args` is found next to `parse`
+        let filter = QueryFilter {
+            symbols_mode: true,
+            language: Some(Language::Rust),
+            use_contains: true,
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("parse", filter).unwrap();
+        let reasons: Vec<_> = response.results[0]
+            .matches
+            .iter()
+            .map(|m| (m.symbol.clone().unwrap(), m.match_reason.clone().unwrap()))
+            .collect();
+
+        let (_, exact) = reasons.iter().find(|(name, _)| name == "parse").unwrap();
+        assert_eq!(exact.method, MatchMethod::ExactSymbol);
+        assert_eq!(exact.filters, vec!["lang=rust"]);
+        let (_, partial) = reasons.iter().find(|(name, _)| name == "parse_args").unwrap();
+        assert_eq!(partial.method, MatchMethod::SymbolSubstring);
+
+        let response = engine.search_with_metadata("parse", QueryFilter::default()).unwrap();
+        let reason = response.results[0].matches[0].match_reason.clone().unwrap();
+        assert_eq!(reason.method, MatchMethod::WordBoundary);
+        assert!(reason.filters.is_empty());
+    }
+
+    #[test]
+    fn test_budget_returns_partial_results() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("a.rs"), "fn shared() {}\n").unwrap();
+        fs::write(project.join("b.rs"), "fn caller() { shared(); }\n").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        // An already expired budget skips every candidate instead of failing
+        let expired = QueryFilter { budget_ms: Some(0), suppress_output: true, ..Default::default() };
+        let response = engine.search_with_metadata("shared", expired).unwrap();
+        assert!(!response.complete);
+        assert_eq!(response.unscanned_candidates, 2);
+        assert!(response.results.is_empty());
+
+        let generous = QueryFilter { budget_ms: Some(60_000), suppress_output: true, ..Default::default() };
+        let response = engine.search_with_metadata("shared", generous).unwrap();
+        assert!(response.complete);
+        assert_eq!(response.unscanned_candidates, 0);
+        assert_eq!(response.results.len(), 2);
+    }
+
+    #[test]
+    fn test_timeout_stops_symbol_parsing() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("a.rs"), "fn shared() {}\n").unwrap();
+        fs::write(project.join("b.rs"), "fn caller() { shared(); }\n").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        let filter = QueryFilter { symbols_mode: true, suppress_output: true, ..Default::default() };
+        let memory = MemoryGuard::new(None);
+        let candidates = engine.get_trigram_candidates("shared", &filter, None, &memory, None, None).unwrap();
+        assert_eq!(candidates.len(), 2);
+
+        // Past the deadline nothing is parsed, and the cancellation is reported
+        let expired = CancelToken::with_timeout(Some(std::time::Duration::ZERO));
+        let results = engine.enrich_with_symbols(candidates.clone(), "shared", &filter, None, &expired, &memory, None).unwrap();
+        assert!(results.is_empty());
+        assert_eq!(partial_status(&expired, &memory), Some(PartialStatus::TimeoutPartial));
+
+        // Skipped files weren't cached as symbol-less
+        let unbounded = CancelToken::default();
+        let results = engine.enrich_with_symbols(candidates, "shared", &filter, None, &unbounded, &memory, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.as_deref(), Some("shared"));
+        assert_eq!(partial_status(&unbounded, &memory), None);
+    }
+
+    #[test]
+    fn test_memory_guard_batches_and_exhausts() {
+        const MB: u64 = 1024 * 1024;
+        let memory = MemoryGuard::new(Some(8 * MB));
+        assert_eq!(memory.batch_bytes(), 4 * MB);
+        assert_eq!(memory.next_batch(&[MB, MB, MB, MB, MB]), 4);
+        assert_eq!(memory.next_batch(&[16 * MB, MB]), 1);
+
+        // Batches shrink as the ceiling gets closer, down to the minimum
+        memory.charge(6 * MB);
+        assert_eq!(memory.batch_bytes(), MIN_BATCH_BYTES);
+        assert!(memory.admit());
+
+        memory.charge(3 * MB);
+        assert!(!memory.admit());
+        assert!(memory.exhausted());
+        memory.release(20 * MB);
+        assert_eq!(memory.high_water(), 9 * MB);
+        assert_eq!(partial_status(&CancelToken::default(), &memory), Some(PartialStatus::MemoryPartial));
+
+        let unlimited = MemoryGuard::new(None);
+        unlimited.charge(u64::MAX / 2);
+        assert!(unlimited.admit());
+        assert_eq!(unlimited.next_batch(&[MB; 3]), 3);
+    }
+
+    #[test]
+    fn test_memory_ceiling_skips_candidates_and_reports_peak() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        for i in 0..200 {
+            fs::write(project.join(format!("f{}.rs", i)), "fn shared() {}\n").unwrap();
+        }
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        // A one-byte ceiling is full after the first verified file
+        let memory = MemoryGuard::new(Some(1));
+        let filter = QueryFilter { suppress_output: true, ..Default::default() };
+        let candidates = engine.get_trigram_candidates("shared", &filter, None, &memory, None, None).unwrap();
+        assert!(!candidates.is_empty() && candidates.len() < 200);
+        assert!(memory.exhausted());
+
+        // --explain reports the high-water mark against the configured ceiling
+        let filter = QueryFilter { explain: true, max_memory_mb: Some(64), suppress_output: true, limit: None, ..Default::default() };
+        let response = engine.search_with_metadata("shared", filter).unwrap();
+        assert!(response.complete);
+        assert_eq!(response.partial, None);
+        let plan = response.plan.unwrap();
+        assert!(plan.peak_memory_bytes > 0);
+        assert_eq!(plan.memory_limit_bytes, Some(64 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_search_with_metadata_context_lines() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("main.rs"),
+            "// one\n// two\n// three\nfn target() {}\n// five\n// six\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        // Asymmetric window: 1 line before, 2 after
+        let filter = QueryFilter {
+            before_context: Some(1),
+            after_context: Some(2),
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("target", filter).unwrap();
+        let m = &response.results[0].matches[0];
+        assert_eq!(m.context_before, vec!["// three"]);
+        assert_eq!(m.context_after, vec!["// five", "// six"]);
+
+        // Default window is DEFAULT_CONTEXT_LINES on each side
+        let response = engine.search_with_metadata("target", QueryFilter::default()).unwrap();
+        let m = &response.results[0].matches[0];
+        assert_eq!(m.context_before.len(), DEFAULT_CONTEXT_LINES);
+    }
+
+    #[test]
+    fn test_search_notebook_reports_cell() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("explore.ipynb"),
+            r##"{"cells": [
+                {"cell_type": "markdown", "source": ["# Load the data"]},
+                {"cell_type": "code", "source": ["import pandas as pd\n", "def load_frame(path):\n", "    return pd.read_csv(path)\n"]}
+            ], "nbformat": 4}"##,
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            symbols_mode: true,
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("load_frame", filter).unwrap();
+        let m = &response.results[0].matches[0];
+
+        assert_eq!(m.kind, SymbolKind::Function);
+        assert_eq!(m.cell, Some(NotebookCell { cell: 2, cell_type: CellType::Code, line: 2 }));
+    }
+
+    #[test]
+    fn test_search_qualified_filter() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("jobs.py"),
+            "class Worker:\n    def run(self):\n        pass\n\nclass Scheduler:\n    def run(self):\n        pass\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            symbols_mode: true,
+            qualified: Some("Worker.run".to_string()),
+            ..Default::default()
+        };
+        let results = engine.search("run", filter).unwrap();
+
+        // Scheduler.run (line 6) is filtered out
+        assert!(!results.is_empty());
+        for r in &results {
+            assert_eq!(r.span.start_line, 2);
+            assert_eq!(r.parent.as_deref(), Some("Worker"));
+            assert_eq!(r.qualified_name.as_deref(), Some("Worker.run"));
+        }
+    }
+
+    #[test]
+    fn test_search_visibility_filter() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(
+            project.join("lib.rs"),
+            "pub fn load_config() {}\n\nfn load_defaults() {}\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let search = |visibility| {
+            let filter = QueryFilter {
+                symbols_mode: true,
+                use_contains: true,
+                visibility: Some(visibility),
+                ..Default::default()
+            };
+            engine.search("load_", filter).unwrap()
+                .into_iter()
+                .map(|r| (r.symbol.unwrap_or_default(), r.exported))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(search(Visibility::Public), vec![("load_config".to_string(), Some(true))]);
+        assert_eq!(search(Visibility::Private), vec![("load_defaults".to_string(), Some(false))]);
+    }
+
+    #[test]
+    fn test_search_owner_filter_and_annotations() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("payments")).unwrap();
+        fs::create_dir_all(project.join(".github")).unwrap();
+
+        fs::write(project.join(".github/CODEOWNERS"), "* @acme/platform\n/payments/ @acme/team-payments\n").unwrap();
+        fs::write(project.join("payments/charge.rs"), "fn charge_card() {}\n").unwrap();
+        fs::write(project.join("main.rs"), "fn charge_all() {}\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            use_contains: true,
+            owner: Some("@team-payments".to_string()),
+            ..Default::default()
+        };
+        let results = engine.search("charge", filter).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("payments/charge.rs"));
+
+        let filter = QueryFilter {
+            use_contains: true,
+            with_owners: true,
+            ..Default::default()
+        };
+        let response = engine.search_with_metadata("charge", filter).unwrap();
+        let owners: Vec<_> = response.results.iter()
+            .map(|group| group.owners.clone().unwrap_or_default())
+            .collect();
+        assert_eq!(owners, vec![vec!["@acme/platform".to_string()], vec!["@acme/team-payments".to_string()]]);
+    }
+
+    #[test]
+    fn test_search_table_filter() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("prisma")).unwrap();
+
+        fs::write(project.join("prisma/schema.prisma"), "model Order {\n  id Int @id\n  @@map(\"orders\")\n}\n").unwrap();
+        fs::write(project.join("report.py"), "def load(db):\n    return db.execute(\"SELECT * FROM orders\")\n").unwrap();
+        fs::write(project.join("checkout.ts"), "export function load(db) {\n  return db.order.findMany() as Order[];\n}\n").unwrap();
+        fs::write(project.join("users.py"), "def load(db):\n    return db.execute(\"SELECT * FROM users\")\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        Indexer::new(cache, IndexConfig::default()).index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            table: Some("orders".to_string()),
+            ..Default::default()
+        };
+        let mut paths: Vec<String> = engine.search("load", filter).unwrap().into_iter().map(|r| r.path).collect();
+        paths.sort();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("checkout.ts"));
+        assert!(paths[1].ends_with("report.py"));
+
+        let filter = QueryFilter {
+            table: Some("invoices".to_string()),
+            ..Default::default()
+        };
+        assert!(engine.search("load", filter).is_err());
+    }
+
+    #[test]
+    fn test_search_streaming_record_order() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("b.rs"), "fn shared() {}\nfn other() { shared(); }\n").unwrap();
+        fs::write(project.join("a.rs"), "fn caller() { shared(); }\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        let mut records = Vec::new();
+        engine.search_streaming("shared", QueryFilter::default(), |record| {
+            records.push(record);
+            Ok(())
+        }).unwrap();
+
+        assert!(matches!(records.first(), Some(QueryStreamRecord::Header { .. })));
+        let paths: Vec<&str> = records.iter()
+            .filter_map(|r| match r {
+                QueryStreamRecord::File(group) => Some(group.path.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0] < paths[1], "files should be sorted by path: {:?}", paths);
+
+        // Streaming and buffered output agree on pagination
+        let buffered = engine.search_with_metadata("shared", QueryFilter::default()).unwrap();
+        match records.last() {
+            Some(QueryStreamRecord::Summary { pagination, .. }) => {
+                assert_eq!(pagination.total, buffered.pagination.total);
+                assert_eq!(pagination.count, 3);
+            }
+            other => panic!("expected summary record last, got {:?}", other),
+        }
+
+        // Each record serializes to a single tagged JSON line
+        let line = serde_json::to_string(&records[1]).unwrap();
+        assert!(!line.contains('\n'));
+        assert!(line.starts_with("{\"type\":\"file\""));
+    }
+
+    #[test]
+    fn test_search_streaming_stops_on_emit_error() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+
+        fs::write(project.join("a.rs"), "fn shared() {}\n").unwrap();
+        fs::write(project.join("b.rs"), "fn shared_too() { shared(); }\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+
+        let mut seen = 0;
+        let result = engine.search_streaming("shared", QueryFilter::default(), |_| {
+            seen += 1;
+            if seen == 2 {
+                anyhow::bail!("broken pipe");
+            }
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(seen, 2);
+    }
+
     // ==================== Multi-language Tests ====================
 
     #[test]
@@ -3217,4 +5864,67 @@ mod tests {
         assert!(results.iter().any(|r| r.lang == Language::TypeScript));
         assert!(results.iter().any(|r| r.lang == Language::Python));
     }
+
+    #[test]
+    fn test_search_embedded_language() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join(".reflex")).unwrap();
+        fs::write(project.join(".reflex/config.toml"), "[embedded]\nenabled = true\n").unwrap();
+
+        fs::write(
+            project.join("repo.py"),
+            "def load(db):\n    q = \"\"\"\n    SELECT id FROM users\n    \"\"\"\n    users = db.execute(q)\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&project);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project));
+        let filter = QueryFilter {
+            language: Some(Language::Sql),
+            ..Default::default()
+        };
+        let results = engine.search("users", filter).unwrap();
+
+        // Only the line inside the SQL string matches, tagged with the embedded language
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].span.start_line, 3);
+        assert_eq!(results[0].lang, Language::Sql);
+    }
+
+    // ==================== Multi-index Tests ====================
+
+    #[test]
+    fn test_merge_responses_labels_repos() {
+        let temp = TempDir::new().unwrap();
+        let mut responses = Vec::new();
+
+        for (name, source) in [("api", "fn greet() {}"), ("web", "fn greet() {}\nfn greet_all() {}")] {
+            let project = temp.path().join(name);
+            fs::create_dir(&project).unwrap();
+            fs::write(project.join("main.rs"), source).unwrap();
+
+            let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+            indexer.index(&project, false).unwrap();
+
+            let engine = QueryEngine::new(CacheManager::new(&project));
+            let response = engine.search_with_metadata("greet", QueryFilter::default()).unwrap();
+            responses.push((name.to_string(), response));
+        }
+
+        let merged = merge_responses(responses);
+
+        // Same relative path in both repos stays two separate files
+        assert_eq!(merged.results.len(), 2);
+        assert_eq!(merged.results[0].repo.as_deref(), Some("api"));
+        assert_eq!(merged.results[1].repo.as_deref(), Some("web"));
+        assert!(merged.results.iter().all(|f| f.path.ends_with("main.rs")));
+
+        let matches: usize = merged.results.iter().map(|f| f.matches.len()).sum();
+        assert_eq!(merged.pagination.total, matches);
+        assert_eq!(merged.pagination.count, matches);
+        assert!(merged.complete);
+    }
 }
@@ -0,0 +1,272 @@
+//! Compact query expressions for `rfx q`
+//!
+//! A single argument expresses the pattern and every filter, which is easier
+//! to type and for LLMs to generate than a long flag list:
+//!
+//! ```text
+//! rfx q 'sym:parse kind:function lang:rust path:src/** !path:tests/**'
+//! ```
+//!
+//! | Term               | Equivalent flag                       |
+//! |--------------------|---------------------------------------|
+//! | `word`, `"a b"`    | pattern (full-text, words are joined) |
+//! | `sym:NAME`         | pattern + `--symbols`                 |
+//! | `re:REGEX`         | pattern + `--regex`                   |
+//! | `kind:KIND,...`    | `--kind`                              |
+//! | `!kind:KIND,...`   | `--kind-not`                          |
+//! | `lang:LANG`        | `--lang`                              |
+//! | `path:GLOB`        | `--glob` (repeatable)                 |
+//! | `!path:GLOB`       | `--exclude` (repeatable)              |
+//! | `not:TEXT`         | `--not` (repeatable)                  |
+//! | `file:TEXT`        | `--file`                              |
+//! | `since:REF`        | `--changed-since`                     |
+//! | `in:SYMBOL`        | `--in`                                |
+//! | `match:exact`      | `--exact` (`match:contains` = `--contains`) |
+//! | `limit:N`          | `--limit`                             |
+//! | `offset:N`         | `--offset`                            |
+//!
+//! Values containing spaces are double-quoted (`path:"docs/my notes/**"`).
+//! The expression is translated to the same arguments as `rfx query`, so the
+//! flags remain the stable interface and both behave identically.
+
+use anyhow::Result;
+
+/// A parsed query expression
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryExpr {
+    pub pattern: String,
+    pub symbols: bool,
+    pub regex: bool,
+    pub exact: bool,
+    pub contains: bool,
+    pub kind: Option<String>,
+    pub kind_not: Option<String>,
+    pub lang: Option<String>,
+    pub file: Option<String>,
+    pub globs: Vec<String>,
+    pub excludes: Vec<String>,
+    pub nots: Vec<String>,
+    pub changed_since: Option<String>,
+    pub scope: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl std::str::FromStr for QueryExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        parse(expr)
+    }
+}
+
+/// Parse a query expression
+pub fn parse(expr: &str) -> Result<QueryExpr> {
+    let mut query = QueryExpr::default();
+    let mut words: Vec<String> = Vec::new();
+    let mut pattern_term: Option<String> = None;
+
+    for term in tokenize(expr)? {
+        // `!` only negates filter terms; `!=` or `!ok` are ordinary words
+        let (negated, body) = match term.strip_prefix('!') {
+            Some(rest) if split_term(rest).is_some() => (true, rest),
+            _ => (false, term.as_str()),
+        };
+
+        let Some((key, value)) = split_term(body) else {
+            words.push(term.clone());
+            continue;
+        };
+
+        if value.is_empty() {
+            anyhow::bail!("Missing value for '{}:'", key);
+        }
+        if negated && key != "path" && key != "kind" {
+            anyhow::bail!(
+                "Only path: and kind: terms can be negated, got '{}'.\n\
+                 \n\
+                 Example: rfx q 'sym:parse !path:tests/**'",
+                term
+            );
+        }
+
+        match key {
+            "sym" | "re" | "text" => {
+                if let Some(previous) = &pattern_term {
+                    anyhow::bail!("Only one pattern term is allowed, got both {}: and {}:", previous, key);
+                }
+                pattern_term = Some(key.to_string());
+                query.symbols |= key == "sym";
+                query.regex |= key == "re";
+                query.pattern = value.to_string();
+            }
+            "kind" if negated => set_once(&mut query.kind_not, "!kind", value)?,
+            "kind" => set_once(&mut query.kind, key, value)?,
+            "lang" => set_once(&mut query.lang, key, value)?,
+            "file" => set_once(&mut query.file, key, value)?,
+            "since" => set_once(&mut query.changed_since, key, value)?,
+            "in" => set_once(&mut query.scope, key, value)?,
+            "path" if negated => query.excludes.push(value.to_string()),
+            "path" => query.globs.push(value.to_string()),
+            "not" => query.nots.push(value.to_string()),
+            "match" => match value {
+                "exact" => query.exact = true,
+                "contains" => query.contains = true,
+                "word" => {}
+                other => anyhow::bail!("Unknown match mode '{}'. Supported: word, exact, contains", other),
+            },
+            "limit" => query.limit = Some(number(key, value)?),
+            "offset" => query.offset = Some(number(key, value)?),
+            _ => unreachable!("split_term only returns known keys"),
+        }
+    }
+
+    if !words.is_empty() {
+        if let Some(previous) = pattern_term {
+            anyhow::bail!(
+                "Free text '{}' can't be combined with a {}: term.\n\
+                 \n\
+                 Quote multi-word patterns instead, e.g. sym:\"my name\"",
+                words.join(" "),
+                previous
+            );
+        }
+        query.pattern = words.join(" ");
+    }
+
+    if query.pattern.is_empty() {
+        // `kind:function lang:rust` lists every symbol of that kind, like `rfx query function --kind function`
+        // (a list of kinds is keyed by its first kind and filtered to all of them)
+        match query.kind.as_deref().and_then(|kinds| kinds.split(',').next()) {
+            Some(kind) => query.pattern = kind.trim().to_lowercase(),
+            None => anyhow::bail!(
+                "Query expression has no pattern.\n\
+                 \n\
+                 Add a word, sym:NAME or re:REGEX term, e.g.\n\
+                 $ rfx q 'sym:parse kind:function lang:rust'"
+            ),
+        }
+    }
+
+    Ok(query)
+}
+
+const KEYS: &[&str] = &["sym", "re", "text", "kind", "lang", "path", "not", "file", "since", "in", "match", "limit", "offset"];
+
+/// `key:value` for known keys; anything else (`http://x`, `a::b`) is a pattern word
+fn split_term(term: &str) -> Option<(&str, &str)> {
+    term.split_once(':').filter(|(key, _)| KEYS.contains(key))
+}
+
+fn set_once(slot: &mut Option<String>, key: &str, value: &str) -> Result<()> {
+    if slot.is_some() {
+        anyhow::bail!("'{}:' can only be given once", key);
+    }
+    *slot = Some(value.to_string());
+    Ok(())
+}
+
+fn number(key: &str, value: &str) -> Result<usize> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("'{}:' expects a number, got '{}'", key, value))
+}
+
+/// Split on whitespace, keeping double-quoted sections (quotes removed) together
+fn tokenize(expr: &str) -> Result<Vec<String>> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+
+    for c in expr.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    terms.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+
+    if quoted {
+        anyhow::bail!("Unterminated quote in query expression: {}", expr);
+    }
+    if started {
+        terms.push(current);
+    }
+
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_expression() {
+        let query = parse("sym:parse kind:function lang:rust path:src/** !path:tests/** limit:20").unwrap();
+        assert_eq!(query, QueryExpr {
+            pattern: "parse".to_string(),
+            symbols: true,
+            kind: Some("function".to_string()),
+            lang: Some("rust".to_string()),
+            globs: vec!["src/**".to_string()],
+            excludes: vec!["tests/**".to_string()],
+            limit: Some(20),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_free_text_and_quoting() {
+        let query = parse(r#"unwrap or default path:"docs/my notes/**" match:contains"#).unwrap();
+        assert_eq!(query.pattern, "unwrap or default");
+        assert!(!query.symbols && query.contains);
+        assert_eq!(query.globs, vec!["docs/my notes/**"]);
+
+        // Colons in values and unknown prefixes stay part of the pattern
+        let query = parse(r#"re:"^impl\s+\w+:" since:HEAD~3"#).unwrap();
+        assert_eq!(query.pattern, r"^impl\s+\w+:");
+        assert!(query.regex);
+        assert_eq!(query.changed_since.as_deref(), Some("HEAD~3"));
+        assert_eq!(parse("unwrap in:src/query.rs:Engine::search").unwrap().scope.as_deref(), Some("src/query.rs:Engine::search"));
+        assert_eq!(parse("http://example.com").unwrap().pattern, "http://example.com");
+        assert_eq!(parse("!= None").unwrap().pattern, "!= None");
+        assert_eq!(parse("client not:test not:\"mock \"").unwrap().nots, vec!["test", "mock "]);
+    }
+
+    #[test]
+    fn test_kind_without_pattern_lists_symbols() {
+        let query = parse("kind:Struct lang:go").unwrap();
+        assert_eq!(query.pattern, "struct");
+
+        let query = parse("kind:struct,enum,trait !kind:import").unwrap();
+        assert_eq!(query.pattern, "struct");
+        assert_eq!(query.kind.as_deref(), Some("struct,enum,trait"));
+        assert_eq!(query.kind_not.as_deref(), Some("import"));
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(parse("").is_err());
+        assert!(parse("lang:rust").is_err());
+        assert!(parse("sym:a re:b").is_err());
+        assert!(parse("foo sym:bar").is_err());
+        assert!(parse("sym:a !lang:rust").is_err());
+        assert!(parse("sym:a lang:rust lang:go").is_err());
+        assert!(parse("sym:a limit:many").is_err());
+        assert!(parse("sym:a match:fuzzy").is_err());
+        assert!(parse("sym:\"a").is_err());
+        assert!(parse("sym:").is_err());
+    }
+}
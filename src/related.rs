@@ -0,0 +1,337 @@
+//! Related-file suggestions for search results
+//!
+//! For each file with matches, `--related` attaches the neighborhood an agent
+//! usually opens next, saving a round of follow-up queries:
+//! - **Tests**: files whose name marks them as a test of this file
+//!   (`foo_test.go`, `test_foo.py`, `foo.spec.ts`, `FooTest.java`, `tests/foo.rs`)
+//! - **Counterparts**: C/C++ header ↔ implementation (`foo.h` ↔ `foo.cpp`)
+//! - **Importers / imports**: direct neighbors from the dependency index,
+//!   strongest connections (most imported symbols) first
+//!
+//! Tests and counterparts are matched by file name against the list of indexed
+//! files, preferring candidates closest in the directory tree.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::dependency::DependencyIndex;
+use crate::models::{Language, Relation, RelatedFile};
+
+/// Related files per relation when `--related` is given without a count
+pub const DEFAULT_RELATED_LIMIT: usize = 5;
+
+/// Directory names that hold tests
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "spec", "specs", "testing"];
+
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx"];
+const IMPL_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx"];
+
+/// Finds related files for result files (built once per query)
+pub struct RelatedFinder {
+    deps: DependencyIndex,
+    /// Lowercased file stem -> indexed paths
+    by_stem: HashMap<String, Vec<String>>,
+    /// Lowercased stem of the tested file -> test paths
+    tests_by_subject: HashMap<String, Vec<String>>,
+    limit: usize,
+}
+
+impl RelatedFinder {
+    /// Build the file-name lookups from the index (`limit` = max files per relation)
+    pub fn new(cache: CacheManager, limit: usize) -> Result<Self> {
+        let files = cache.list_files()?;
+
+        let mut by_stem: HashMap<String, Vec<String>> = HashMap::new();
+        let mut tests_by_subject: HashMap<String, Vec<String>> = HashMap::new();
+        for file in files {
+            if let Some(subject) = test_subject(&file.path) {
+                tests_by_subject.entry(subject).or_default().push(file.path.clone());
+            }
+            if let Some(stem) = lower_stem(&file.path) {
+                by_stem.entry(stem).or_default().push(file.path);
+            }
+        }
+
+        Ok(Self {
+            deps: DependencyIndex::new(cache),
+            by_stem,
+            tests_by_subject,
+            limit,
+        })
+    }
+
+    /// Related files of `path`, grouped by relation (tests, counterparts, importers, imports)
+    pub fn related(&self, path: &str) -> Vec<RelatedFile> {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        let mut related = Vec::new();
+
+        related.extend(self.tests_of(path));
+        related.extend(self.counterparts_of(path));
+
+        match self.graph_neighbors(path) {
+            Ok(neighbors) => related.extend(neighbors),
+            Err(e) => log::warn!("Failed to load import neighbors for {}: {}", path, e),
+        }
+
+        related
+    }
+
+    fn tests_of(&self, path: &str) -> Vec<RelatedFile> {
+        // A test file has no tests of its own
+        if test_subject(path).is_some() {
+            return Vec::new();
+        }
+        let Some(stem) = lower_stem(path) else {
+            return Vec::new();
+        };
+        let candidates = self.tests_by_subject.get(&stem).map(Vec::as_slice).unwrap_or(&[]);
+        let language = language_family(path);
+
+        self.closest(path, Relation::Test, candidates.iter().filter(|c| language_family(c) == language))
+    }
+
+    fn counterparts_of(&self, path: &str) -> Vec<RelatedFile> {
+        let Some(ext) = extension(path) else {
+            return Vec::new();
+        };
+        let wanted: &[&str] = if HEADER_EXTENSIONS.contains(&ext.as_str()) {
+            IMPL_EXTENSIONS
+        } else if IMPL_EXTENSIONS.contains(&ext.as_str()) {
+            HEADER_EXTENSIONS
+        } else {
+            return Vec::new();
+        };
+        let Some(stem) = lower_stem(path) else {
+            return Vec::new();
+        };
+        let candidates = self.by_stem.get(&stem).map(Vec::as_slice).unwrap_or(&[]);
+
+        self.closest(path, Relation::Counterpart, candidates.iter().filter(|c| {
+            extension(c).is_some_and(|e| wanted.contains(&e.as_str()))
+        }))
+    }
+
+    /// Up to `limit` candidates, closest in the directory tree first
+    fn closest<'a>(&self, path: &str, relation: Relation, candidates: impl Iterator<Item = &'a String>) -> Vec<RelatedFile> {
+        let mut related: Vec<RelatedFile> = candidates
+            .filter(|c| c.as_str() != path)
+            .map(|c| RelatedFile {
+                path: c.clone(),
+                relation,
+                weight: shared_dirs(path, c),
+            })
+            .collect();
+        sort_by_weight(&mut related);
+        related.truncate(self.limit);
+        related
+    }
+
+    fn graph_neighbors(&self, path: &str) -> Result<Vec<RelatedFile>> {
+        let Some(file_id) = self.deps.get_cache().get_file_id(path)? else {
+            return Ok(Vec::new());
+        };
+
+        // Imports: weight = imported symbols across all statements importing the file
+        let mut import_weights: HashMap<i64, usize> = HashMap::new();
+        for dep in self.deps.get_dependencies(file_id)? {
+            if let Some(target) = dep.resolved_file_id
+                && target != file_id
+            {
+                *import_weights.entry(target).or_default() += symbol_weight(dep.imported_symbols.as_deref());
+            }
+        }
+
+        let importer_weights: HashMap<i64, usize> = self
+            .deps
+            .get_dependent_weights(file_id)?
+            .into_iter()
+            .filter(|(id, _)| *id != file_id)
+            .collect();
+
+        let ids: Vec<i64> = import_weights.keys().chain(importer_weights.keys()).copied().collect();
+        let paths = self.deps.get_file_paths(&ids)?;
+
+        let mut related = Vec::new();
+        for (relation, weights) in [(Relation::Importer, importer_weights), (Relation::Import, import_weights)] {
+            let mut group: Vec<RelatedFile> = weights
+                .into_iter()
+                .filter_map(|(id, weight)| {
+                    Some(RelatedFile {
+                        path: paths.get(&id)?.clone(),
+                        relation,
+                        weight,
+                    })
+                })
+                .collect();
+            sort_by_weight(&mut group);
+            group.truncate(self.limit);
+            related.extend(group);
+        }
+
+        Ok(related)
+    }
+}
+
+/// Weight of one import statement: its symbol count, or 1 for a plain import
+pub(crate) fn symbol_weight(symbols: Option<&[String]>) -> usize {
+    symbols.map_or(1, |s| s.len().max(1))
+}
+
+/// Highest weight first, then by path for deterministic output
+fn sort_by_weight(related: &mut [RelatedFile]) {
+    related.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.path.cmp(&b.path)));
+}
+
+/// Whether `path` is a test file or test support file (helpers, fixtures under a test directory)
+pub(crate) fn is_test_file(path: &str) -> bool {
+    test_subject(path).is_some() || in_test_dir(path)
+}
+
+/// The lowercased stem of the file a test file covers, or None if `path` isn't a test
+///
+/// Recognizes `foo_test`, `foo_spec`, `foo.test`, `foo.spec`, `test_foo`,
+/// `FooTest(s)`, `FooSpec`, and any file under a test directory (`tests/foo.rs`).
+fn test_subject(path: &str) -> Option<String> {
+    let file_name = Path::new(path).file_name()?.to_str()?;
+    // Strip the language extension only (`foo.test.ts` -> `foo.test`)
+    let stem = file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem);
+    if stem.is_empty() {
+        return None;
+    }
+
+    let lower = stem.to_lowercase();
+    for suffix in ["_test", "_tests", "_spec", ".test", ".spec"] {
+        if let Some(subject) = lower.strip_suffix(suffix)
+            && !subject.is_empty()
+        {
+            return Some(subject.to_string());
+        }
+    }
+    if let Some(subject) = lower.strip_prefix("test_")
+        && !subject.is_empty()
+    {
+        return Some(subject.to_string());
+    }
+    // CamelCase suffixes are case-sensitive so `latest.rs` or `inspect.py` don't match
+    for suffix in ["Tests", "Test", "Spec"] {
+        if let Some(subject) = stem.strip_suffix(suffix)
+            && !subject.is_empty()
+        {
+            return Some(subject.to_lowercase());
+        }
+    }
+
+    // `tests/mod.rs`, `tests/__init__.py` etc. don't name a subject
+    let generic = matches!(lower.as_str(), "mod" | "main" | "lib" | "index" | "__init__" | "conftest" | "helpers" | "utils" | "common");
+    (in_test_dir(path) && !generic).then_some(lower)
+}
+
+/// Whether any directory of `path` is a test directory (`tests/`, `__tests__/`, ...)
+fn in_test_dir(path: &str) -> bool {
+    Path::new(path).parent().is_some_and(|parent| {
+        parent
+            .components()
+            .any(|c| c.as_os_str().to_str().is_some_and(|dir| TEST_DIRS.contains(&dir)))
+    })
+}
+
+fn lower_stem(path: &str) -> Option<String> {
+    Some(Path::new(path).file_stem()?.to_str()?.to_lowercase())
+}
+
+fn extension(path: &str) -> Option<String> {
+    Some(Path::new(path).extension()?.to_str()?.to_lowercase())
+}
+
+/// Language used to pair tests with sources (C and C++ count as one)
+fn language_family(path: &str) -> String {
+    let language = Language::from_extension(extension(path).as_deref().unwrap_or(""));
+    match language {
+        Language::C | Language::Cpp => "c/c++".to_string(),
+        Language::TypeScript | Language::JavaScript => "js/ts".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Number of leading directories two paths share
+fn shared_dirs(a: &str, b: &str) -> usize {
+    let dirs = |p: &str| -> Vec<String> {
+        Path::new(p)
+            .parent()
+            .map(|d| d.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect())
+            .unwrap_or_default()
+    };
+    dirs(a).iter().zip(dirs(b).iter()).take_while(|(x, y)| x == y).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::{ImportType, IndexConfig};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_test_subject() {
+        assert_eq!(test_subject("pkg/server_test.go").as_deref(), Some("server"));
+        assert_eq!(test_subject("tests/test_parser.py").as_deref(), Some("parser"));
+        assert_eq!(test_subject("src/Button.spec.tsx").as_deref(), Some("button"));
+        assert_eq!(test_subject("src/test/java/UserServiceTest.java").as_deref(), Some("userservice"));
+        assert_eq!(test_subject("tests/indexer.rs").as_deref(), Some("indexer"));
+
+        assert_eq!(test_subject("src/latest.rs"), None);
+        assert_eq!(test_subject("src/contest.py"), None);
+        assert_eq!(test_subject("tests/mod.rs"), None);
+        assert_eq!(test_subject("src/main.rs"), None);
+    }
+
+    #[test]
+    fn test_shared_dirs() {
+        assert_eq!(shared_dirs("src/a/x.rs", "src/a/y.rs"), 2);
+        assert_eq!(shared_dirs("src/a/x.rs", "tests/x.rs"), 0);
+        assert_eq!(shared_dirs("x.rs", "y.rs"), 0);
+    }
+
+    #[test]
+    fn test_related_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/net")).unwrap();
+        fs::create_dir_all(root.join("include")).unwrap();
+        fs::write(root.join("src/net/client.py"), "def connect():\n    pass\n").unwrap();
+        fs::write(root.join("src/net/codec.py"), "def encode():\n    pass\n").unwrap();
+        fs::write(root.join("src/net/pool.py"), "def acquire():\n    pass\n").unwrap();
+        fs::write(root.join("src/net/test_client.py"), "def test_connect():\n    pass\n").unwrap();
+        fs::write(root.join("src/buffer.c"), "int buffer_len(void) { return 0; }\n").unwrap();
+        fs::write(root.join("include/buffer.h"), "int buffer_len(void);\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        // client.py imports two symbols from codec.py and is imported by pool.py
+        let id = |path: &str| cache.get_file_id(path).unwrap().unwrap();
+        let deps = DependencyIndex::new(cache.clone());
+        let symbols = Some(vec!["encode".to_string(), "decode".to_string()]);
+        deps.insert_dependency(id("src/net/client.py"), ".codec".to_string(), Some(id("src/net/codec.py")), ImportType::Internal, 1, symbols).unwrap();
+        deps.insert_dependency(id("src/net/pool.py"), ".client".to_string(), Some(id("src/net/client.py")), ImportType::Internal, 1, None).unwrap();
+
+        let finder = RelatedFinder::new(cache, DEFAULT_RELATED_LIMIT).unwrap();
+        let related = finder.related("./src/net/client.py");
+        let summary: Vec<(&str, Relation, usize)> = related.iter().map(|r| (r.path.as_str(), r.relation, r.weight)).collect();
+        assert_eq!(summary, vec![
+            ("src/net/test_client.py", Relation::Test, 2),
+            ("src/net/pool.py", Relation::Importer, 1),
+            ("src/net/codec.py", Relation::Import, 2),
+        ]);
+
+        let related = finder.related("include/buffer.h");
+        assert_eq!(related, vec![RelatedFile {
+            path: "src/buffer.c".to_string(),
+            relation: Relation::Counterpart,
+            weight: 0,
+        }]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Index-backed search and replace
+//!
+//! `rfx replace` finds candidate files through the normal query pipeline
+//! (trigram narrowing + lexical/regex verification), computes line-level edits
+//! against the current on-disk content, and renders them as a unified diff.
+//! Nothing is written unless the plan is explicitly applied (`--write`).
+//!
+//! Matching follows `rfx query` semantics:
+//! - Default: word-boundary match of the literal pattern
+//! - `--contains`: substring match of the literal pattern
+//! - `--regex`: regex match; the replacement may use `$1`/`${name}` capture groups
+//!
+//! Matches never span lines, mirroring the line-based search pipeline.
+
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::query::{QueryEngine, QueryFilter};
+
+/// Lines of unchanged context around each diff hunk
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A single changed line
+#[derive(Debug, Clone, Serialize)]
+pub struct LineEdit {
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+    /// Number of matches replaced on this line
+    pub replacements: usize,
+}
+
+/// All edits for one file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEdit {
+    pub path: String,
+    pub replacements: usize,
+    pub edits: Vec<LineEdit>,
+    /// Unified diff of the change
+    pub diff: String,
+    /// blake3 hash of the content the edits were computed from
+    #[serde(skip)]
+    original_hash: String,
+    #[serde(skip)]
+    new_content: String,
+}
+
+/// Edits for every affected file
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplacePlan {
+    pub pattern: String,
+    pub replacement: String,
+    pub total_files: usize,
+    pub total_replacements: usize,
+    pub files: Vec<FileEdit>,
+}
+
+/// Build the line matcher for a pattern using `rfx query` matching semantics
+pub fn build_matcher(pattern: &str, use_regex: bool, use_contains: bool) -> Result<Regex> {
+    let source = if use_regex {
+        pattern.to_string()
+    } else if use_contains {
+        regex::escape(pattern)
+    } else {
+        format!(r"\b{}\b", regex::escape(pattern))
+    };
+
+    Regex::new(&source).with_context(|| format!("Invalid pattern: {}", pattern))
+}
+
+/// Compute the replacement plan without touching any files
+///
+/// `filter` scopes the search (language, globs, file pattern). Its limit is
+/// ignored so that every match is replaced.
+pub fn plan_replacements(
+    engine: &QueryEngine,
+    pattern: &str,
+    replacement: &str,
+    mut filter: QueryFilter,
+) -> Result<ReplacePlan> {
+    let matcher = build_matcher(pattern, filter.use_regex, filter.use_contains)?;
+
+    filter.limit = None;
+    filter.offset = None;
+    filter.paths_only = true;
+    filter.symbols_mode = false;
+    filter.suppress_output = true;
+
+    let mut paths: Vec<String> = engine.search(pattern, filter.clone())?
+        .into_iter()
+        .map(|r| r.path)
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut files = Vec::new();
+    for path in paths {
+        let original = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                log::warn!("Skipping {}: {}", path, e);
+                continue;
+            }
+        };
+
+        if let Some(file_edit) = compute_file_edit(&path, &original, &matcher, replacement, filter.use_regex) {
+            files.push(file_edit);
+        }
+    }
+
+    Ok(ReplacePlan {
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+        total_files: files.len(),
+        total_replacements: files.iter().map(|f| f.replacements).sum(),
+        files,
+    })
+}
+
+/// Apply a plan, writing each file atomically
+///
+/// Files that changed on disk since the plan was computed are skipped (and
+/// reported in the error) rather than overwritten.
+pub fn apply_plan(plan: &ReplacePlan) -> Result<usize> {
+    let mut written = 0;
+    let mut stale = Vec::new();
+
+    for file in &plan.files {
+        let current = std::fs::read_to_string(&file.path)
+            .with_context(|| format!("Failed to read {}", file.path))?;
+        if blake3::hash(current.as_bytes()).to_hex().to_string() != file.original_hash {
+            stale.push(file.path.clone());
+            continue;
+        }
+
+        write_atomic(Path::new(&file.path), &file.new_content)?;
+        written += 1;
+    }
+
+    if !stale.is_empty() {
+        anyhow::bail!(
+            "{} file(s) changed since the preview was computed and were not modified:\n  {}\n\
+             \n\
+             Re-run the command to recompute the edits.",
+            stale.len(),
+            stale.join("\n  ")
+        );
+    }
+
+    Ok(written)
+}
+
+/// Compute line edits for one file (None if nothing matches)
+fn compute_file_edit(
+    path: &str,
+    original: &str,
+    matcher: &Regex,
+    replacement: &str,
+    expand_captures: bool,
+) -> Option<FileEdit> {
+    let mut edits = Vec::new();
+    let mut new_content = String::with_capacity(original.len());
+
+    for (idx, raw_line) in original.split_inclusive('\n').enumerate() {
+        let (line, ending) = split_line_ending(raw_line);
+        let count = matcher.find_iter(line).count();
+
+        if count == 0 {
+            new_content.push_str(raw_line);
+            continue;
+        }
+
+        let replaced = if expand_captures {
+            matcher.replace_all(line, replacement).into_owned()
+        } else {
+            matcher.replace_all(line, NoExpand(replacement)).into_owned()
+        };
+
+        new_content.push_str(&replaced);
+        new_content.push_str(ending);
+
+        if replaced != line {
+            edits.push(LineEdit {
+                line: idx + 1,
+                before: line.to_string(),
+                after: replaced,
+                replacements: count,
+            });
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    Some(FileEdit {
+        path: path.to_string(),
+        replacements: edits.iter().map(|e| e.replacements).sum(),
+        diff: unified_diff(path, original, &edits),
+        edits,
+        original_hash: blake3::hash(original.as_bytes()).to_hex().to_string(),
+        new_content,
+    })
+}
+
+/// Split a line into its content and its `\n` / `\r\n` terminator
+fn split_line_ending(line: &str) -> (&str, &str) {
+    if let Some(stripped) = line.strip_suffix("\r\n") {
+        (stripped, "\r\n")
+    } else if let Some(stripped) = line.strip_suffix('\n') {
+        (stripped, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// Render line edits as a unified diff (`--- a/path` / `+++ b/path` with hunks)
+fn unified_diff(path: &str, original: &str, edits: &[LineEdit]) -> String {
+    let lines: Vec<&str> = original.lines().collect();
+    let display_path = path.strip_prefix("./").unwrap_or(path);
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", display_path, display_path);
+
+    // Group edits whose context windows touch into a single hunk
+    let mut hunks: Vec<&[LineEdit]> = Vec::new();
+    let mut start = 0;
+    for i in 1..=edits.len() {
+        if i == edits.len() || edits[i].line - edits[i - 1].line > DIFF_CONTEXT_LINES * 2 {
+            hunks.push(&edits[start..i]);
+            start = i;
+        }
+    }
+
+    // Track how far new line numbers drift when a replacement adds lines
+    let mut offset: isize = 0;
+
+    for hunk in hunks {
+        let first = hunk[0].line;
+        let last = hunk[hunk.len() - 1].line;
+        let hunk_start = first.saturating_sub(DIFF_CONTEXT_LINES).max(1);
+        let hunk_end = (last + DIFF_CONTEXT_LINES).min(lines.len());
+
+        let mut body = String::new();
+        let mut old_count = 0;
+        let mut new_count = 0;
+        let mut edit_iter = hunk.iter().peekable();
+
+        for line_no in hunk_start..=hunk_end {
+            match edit_iter.peek() {
+                Some(edit) if edit.line == line_no => {
+                    body.push_str(&format!("-{}\n", edit.before));
+                    old_count += 1;
+                    for new_line in edit.after.split('\n') {
+                        body.push_str(&format!("+{}\n", new_line));
+                        new_count += 1;
+                    }
+                    edit_iter.next();
+                }
+                _ => {
+                    body.push_str(&format!(" {}\n", lines[line_no - 1]));
+                    old_count += 1;
+                    new_count += 1;
+                }
+            }
+        }
+
+        let new_start = hunk_start as isize + offset;
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", hunk_start, old_count, new_start, new_count));
+        out.push_str(&body);
+        offset += new_count as isize - old_count as isize;
+    }
+
+    out
+}
+
+/// Write `content` to `path` via a temp file + rename in the same directory
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .with_context(|| format!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.rfx-replace", file_name));
+
+    std::fs::write(&temp_path, content)
+        .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = std::fs::set_permissions(&temp_path, metadata.permissions());
+    }
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_word_boundary_replacement() {
+        let matcher = build_matcher("count", false, false).unwrap();
+        let source = "let count = 1;\nlet counter = count + 1;\n";
+        let edit = compute_file_edit("a.rs", source, &matcher, "total", false).unwrap();
+
+        assert_eq!(edit.replacements, 2);
+        assert_eq!(edit.new_content, "let total = 1;\nlet counter = total + 1;\n");
+    }
+
+    #[test]
+    fn test_regex_replacement_expands_captures() {
+        let matcher = build_matcher(r"get_(\w+)\(\)", true, false).unwrap();
+        let source = "a.get_name();\r\nb.get_id();\r\n";
+        let edit = compute_file_edit("a.rs", source, &matcher, "$1()", true).unwrap();
+
+        assert_eq!(edit.new_content, "a.name();\r\nb.id();\r\n");
+    }
+
+    #[test]
+    fn test_literal_replacement_does_not_expand_dollars() {
+        let matcher = build_matcher("price", false, true).unwrap();
+        let edit = compute_file_edit("a.js", "const price = 1;", &matcher, "$cost", false).unwrap();
+
+        assert_eq!(edit.new_content, "const $cost = 1;");
+    }
+
+    #[test]
+    fn test_no_match_returns_none() {
+        let matcher = build_matcher("missing", false, false).unwrap();
+        assert!(compute_file_edit("a.rs", "fn main() {}\n", &matcher, "x", false).is_none());
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let source: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let matcher = build_matcher(r"^line (?:2|18)$", true, false).unwrap();
+        let edit = compute_file_edit("./src/a.txt", &source, &matcher, "changed", true).unwrap();
+
+        assert!(edit.diff.starts_with("--- a/src/a.txt\n+++ b/src/a.txt\n"));
+        assert!(edit.diff.contains("@@ -1,5 +1,5 @@\n line 1\n-line 2\n+changed\n line 3\n"));
+        assert!(edit.diff.contains("@@ -15,6 +15,6 @@\n"));
+        assert!(edit.diff.contains("-line 18\n+changed\n"));
+    }
+
+    #[test]
+    fn test_plan_and_apply() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir(&project_root).unwrap();
+        fs::write(project_root.join("lib.rs"), "fn old_name() {}\nfn caller() { old_name(); }\n").unwrap();
+        fs::write(project_root.join("other.rs"), "fn unrelated() {}\n").unwrap();
+
+        let cache = CacheManager::new(&project_root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&project_root, false).unwrap();
+
+        let engine = QueryEngine::new(CacheManager::new(&project_root));
+        let plan = plan_replacements(&engine, "old_name", "new_name", QueryFilter::default()).unwrap();
+
+        assert_eq!(plan.total_files, 1);
+        assert_eq!(plan.total_replacements, 2);
+        assert!(plan.files[0].path.ends_with("lib.rs"));
+
+        // Nothing is written until the plan is applied
+        let lib_path = Path::new(&plan.files[0].path).to_path_buf();
+        assert!(fs::read_to_string(&lib_path).unwrap().contains("old_name"));
+
+        assert_eq!(apply_plan(&plan).unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(&lib_path).unwrap(),
+            "fn new_name() {}\nfn caller() { new_name(); }\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_files_changed_after_preview() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("a.rs");
+        fs::write(&path, "let a = 1;\n").unwrap();
+
+        let matcher = build_matcher("a", false, false).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+        let edit = compute_file_edit(&path_str, "let a = 1;\n", &matcher, "b", false).unwrap();
+        let plan = ReplacePlan {
+            pattern: "a".to_string(),
+            replacement: "b".to_string(),
+            total_files: 1,
+            total_replacements: 1,
+            files: vec![edit],
+        };
+
+        fs::write(&path, "let a = 2;\n").unwrap();
+        assert!(apply_plan(&plan).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "let a = 2;\n");
+    }
+}
@@ -0,0 +1,387 @@
+//! Map query findings onto a pull request diff
+//!
+//! `rfx review --pr <diff>` runs a query (or reads the JSON output of an
+//! earlier `rfx query --json`) and keeps only the findings that touch lines
+//! added or modified by the PR. Each one becomes a review comment with
+//! `path` / `line` / `side` (plus `start_line` / `start_side` for multi-line
+//! findings), the coordinates used by the GitHub and GitLab review APIs.
+//!
+//! The diff is read from a unified diff file, stdin (`-`), or produced with
+//! `git diff <range>` when the argument is not a file.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use crate::models::{SearchResult, Span, SymbolKind};
+
+/// Lines touched by a PR in one file (new-side line numbers)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffFile {
+    /// Path on the new side of the diff
+    pub path: String,
+    /// Added or modified lines
+    pub added: BTreeSet<usize>,
+    /// New-side line ranges of each hunk (inclusive)
+    pub hunks: Vec<(usize, usize)>,
+}
+
+impl DiffFile {
+    /// Hunk containing new-side `line`
+    fn hunk_of(&self, line: usize) -> Option<usize> {
+        self.hunks.iter().position(|&(start, end)| (start..=end).contains(&line))
+    }
+}
+
+/// A query finding to map onto the diff
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub path: String,
+    pub span: Span,
+    /// Symbol kind (None for text matches)
+    pub kind: Option<String>,
+    pub symbol: Option<String>,
+    pub preview: String,
+}
+
+impl From<&SearchResult> for Finding {
+    fn from(result: &SearchResult) -> Self {
+        Self {
+            path: result.path.clone(),
+            span: result.span.clone(),
+            kind: match &result.kind {
+                SymbolKind::Unknown(_) => None,
+                kind => Some(kind.to_string()),
+            },
+            symbol: result.symbol.clone(),
+            preview: result.preview.clone(),
+        }
+    }
+}
+
+/// Review side of a commented line (findings are always on the new code)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Side {
+    Right,
+}
+
+/// A review comment anchored to lines touched by the PR
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReviewComment {
+    pub path: String,
+    /// Last commented line (new side)
+    pub line: usize,
+    pub side: Side,
+    /// First commented line, for multi-line comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_side: Option<Side>,
+    pub body: String,
+}
+
+/// Read a diff from a file, stdin (`-`) or `git diff <range>` in `root`
+pub fn load_diff(spec: &str, root: impl AsRef<Path>) -> Result<String> {
+    if spec == "-" {
+        let mut diff = String::new();
+        std::io::stdin()
+            .read_to_string(&mut diff)
+            .context("Failed to read diff from stdin")?;
+        return Ok(diff);
+    }
+
+    if Path::new(spec).is_file() {
+        return std::fs::read_to_string(spec).with_context(|| format!("Failed to read diff file {}", spec));
+    }
+
+    crate::git::diff(root, spec).with_context(|| {
+        format!(
+            "'{}' is neither a diff file nor a git range.\n\
+             \n\
+             Pass a unified diff (e.g. from `gh pr diff 123 > pr.diff`), '-' for stdin,\n\
+             or a git range such as main...HEAD",
+            spec
+        )
+    })
+}
+
+/// Parse a unified diff into the lines each file adds or modifies
+///
+/// Deleted files are skipped (nothing on the new side to comment on).
+pub fn parse_unified_diff(diff: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut current: Option<DiffFile> = None;
+    let mut new_line = 0usize;
+    // Lines left in the current hunk (old side, new side)
+    let mut remaining = (0usize, 0usize);
+
+    for line in diff.lines() {
+        let in_hunk = remaining != (0, 0);
+        let is_content = matches!(line.chars().next(), None | Some(' ' | '+' | '-' | '\\'));
+
+        if in_hunk && is_content {
+            match line.chars().next() {
+                Some('+') => {
+                    if let Some(file) = current.as_mut() {
+                        file.added.insert(new_line);
+                    }
+                    new_line += 1;
+                    remaining.1 = remaining.1.saturating_sub(1);
+                }
+                Some('-') => remaining.0 = remaining.0.saturating_sub(1),
+                // "\ No newline at end of file"
+                Some('\\') => {}
+                _ => {
+                    new_line += 1;
+                    remaining = (remaining.0.saturating_sub(1), remaining.1.saturating_sub(1));
+                }
+            }
+            continue;
+        }
+        remaining = (0, 0);
+
+        if line.starts_with("diff --git ") {
+            files.extend(current.take());
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            files.extend(current.take());
+            let path = path.split('\t').next().unwrap_or(path).trim();
+            if path != "/dev/null" {
+                current = Some(DiffFile {
+                    path: normalize_path(path.strip_prefix("b/").unwrap_or(path)),
+                    ..Default::default()
+                });
+            }
+        } else if let Some(header) = line.strip_prefix("@@ ")
+            && let Some((old_count, (start, count))) = parse_hunk_header(header)
+        {
+            new_line = start;
+            remaining = (old_count, count);
+            if let Some(file) = current.as_mut()
+                && count > 0
+            {
+                file.hunks.push((start, start + count - 1));
+            }
+        }
+    }
+
+    files.extend(current);
+    files.retain(|f| !f.added.is_empty());
+    files
+}
+
+/// Old-side count and new-side (start, count) of a hunk header like `-10,3 +12,4 @@ fn main()`
+fn parse_hunk_header(header: &str) -> Option<(usize, (usize, usize))> {
+    let mut parts = header.split_whitespace();
+    let (_, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let new_range = parse_range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_count, new_range))
+}
+
+/// A `start[,count]` range (count defaults to 1)
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Anchor findings to lines touched by the diff
+///
+/// A finding is kept if its span contains an added line. Multi-line comments
+/// cover the touched lines of the span within a single hunk, as review APIs
+/// require. `message` replaces the generated comment body.
+pub fn map_findings(findings: &[Finding], diff: &[DiffFile], pattern: &str, message: Option<&str>) -> Vec<ReviewComment> {
+    let by_path: HashMap<&str, &DiffFile> = diff.iter().map(|f| (f.path.as_str(), f)).collect();
+    let mut comments: Vec<ReviewComment> = Vec::new();
+
+    for finding in findings {
+        let path = normalize_path(&finding.path);
+        let Some(file) = by_path.get(path.as_str()) else {
+            continue;
+        };
+
+        let end_line = finding.span.end_line.max(finding.span.start_line);
+        let mut touched = file.added.range(finding.span.start_line..=end_line);
+        let Some(&first) = touched.next() else {
+            continue;
+        };
+
+        let hunk = file.hunk_of(first);
+        let last = touched
+            .take_while(|&&line| file.hunk_of(line) == hunk)
+            .last()
+            .copied()
+            .unwrap_or(first);
+
+        let comment = ReviewComment {
+            path: path.clone(),
+            line: last,
+            side: Side::Right,
+            start_line: (first < last).then_some(first),
+            start_side: (first < last).then_some(Side::Right),
+            body: message.map(str::to_string).unwrap_or_else(|| comment_body(finding, pattern)),
+        };
+
+        if !comments.iter().any(|c| c.path == comment.path && c.line == comment.line) {
+            comments.push(comment);
+        }
+    }
+
+    comments.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    comments
+}
+
+/// Extract findings from the JSON output of `rfx query --json`
+pub fn findings_from_query_json(json: &str) -> Result<Vec<Finding>> {
+    let value: serde_json::Value = serde_json::from_str(json).context("Findings are not valid JSON")?;
+    let files = value["results"].as_array().context(
+        "Findings JSON has no 'results' array.\n\
+         \n\
+         Pass the output of `rfx query <pattern> --json`",
+    )?;
+
+    let mut findings = Vec::new();
+    for file in files {
+        let path = file["path"].as_str().context("Finding without 'path'")?;
+        for m in file["matches"].as_array().into_iter().flatten() {
+            let start_line = m["span"]["start_line"].as_u64().context("Finding without 'span'")? as usize;
+            let end_line = m["span"]["end_line"].as_u64().map_or(start_line, |n| n as usize);
+            findings.push(Finding {
+                path: path.to_string(),
+                span: Span { start_line, end_line },
+                kind: m["kind"].as_str().map(str::to_string),
+                symbol: m["symbol"].as_str().map(str::to_string),
+                preview: m["preview"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+    }
+
+    Ok(findings)
+}
+
+fn comment_body(finding: &Finding, pattern: &str) -> String {
+    let matched = match (&finding.kind, &finding.symbol) {
+        (Some(kind), Some(symbol)) => format!("{} `{}`", kind.to_lowercase(), symbol),
+        _ => format!("`{}`", finding.preview.lines().next().unwrap_or_default().trim()),
+    };
+
+    // Findings read from JSON don't carry the query pattern
+    if pattern.is_empty() {
+        format!("Reflex: {}", matched)
+    } else {
+        format!("Reflex: `{}` matches {}", pattern, matched)
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,5 @@
+ use std::fmt;
+-fn old() {}
++fn new_fn() {
++    todo!()
++}
+
+@@ -20,2 +21,3 @@ impl Foo {
+     fn a() {}
++    fn b() {}
+     fn c() {}
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn gone() {}
+diff --git a/docs.md b/docs.md
+--- a/docs.md
++++ b/docs.md
+@@ -3 +3 @@
+-old
++new
+";
+
+    fn finding(path: &str, start: usize, end: usize, symbol: Option<&str>) -> Finding {
+        Finding {
+            path: path.to_string(),
+            span: Span { start_line: start, end_line: end },
+            kind: symbol.map(|_| "Function".to_string()),
+            symbol: symbol.map(str::to_string),
+            preview: "    todo!()".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let files = parse_unified_diff(DIFF);
+        assert_eq!(files.len(), 2);
+
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].added.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4, 22]);
+        assert_eq!(files[0].hunks, vec![(1, 5), (21, 23)]);
+
+        assert_eq!(files[1].path, "docs.md");
+        assert_eq!(files[1].added.iter().copied().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_map_findings_to_touched_lines() {
+        let diff = parse_unified_diff(DIFF);
+        let findings = vec![
+            // Symbol spanning the added function: multi-line comment
+            finding("./src/lib.rs", 2, 4, Some("new_fn")),
+            // Text match on an added line
+            finding("src/lib.rs", 3, 3, None),
+            // Untouched line and file not in the diff
+            finding("src/lib.rs", 21, 21, None),
+            finding("src/other.rs", 1, 1, None),
+            // Span crossing both hunks only covers the first one
+            finding("src/lib.rs", 1, 30, Some("Foo")),
+        ];
+
+        let comments = map_findings(&findings, &diff, "todo", None);
+        assert_eq!(comments.len(), 2);
+
+        assert_eq!(comments[0].line, 3);
+        assert_eq!(comments[0].start_line, None);
+        assert_eq!(comments[0].body, "Reflex: `todo` matches `todo!()`");
+        assert_eq!((comments[1].start_line, comments[1].line), (Some(2), 4));
+        assert_eq!(comments[1].body, "Reflex: `todo` matches function `new_fn`");
+
+        let json = serde_json::to_value(&comments[1]).unwrap();
+        assert_eq!(json["side"], "RIGHT");
+        assert_eq!(json["start_side"], "RIGHT");
+    }
+
+    #[test]
+    fn test_findings_from_query_json() {
+        let json = r#"{"results": [{"path": "./src/lib.rs", "matches": [
+            {"kind": "Function", "symbol": "new_fn", "span": {"start_line": 2, "end_line": 4}, "preview": "fn new_fn() {"},
+            {"span": {"start_line": 3, "end_line": 3}, "preview": "todo!()"}
+        ]}]}"#;
+
+        let findings = findings_from_query_json(json).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].symbol.as_deref(), Some("new_fn"));
+        assert!(findings[1].kind.is_none());
+
+        let comments = map_findings(&findings, &parse_unified_diff(DIFF), "todo", Some("Please remove"));
+        assert_eq!(comments.len(), 2);
+        assert!(comments.iter().all(|c| c.body == "Please remove"));
+
+        assert!(findings_from_query_json("{}").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Historical search across git revisions
+//!
+//! `rfx index --rev <REV>` reads the files of a commit straight from the git
+//! object database and indexes them into a separate cache segment at
+//! `.reflex/revs/<commit>/`, without checking anything out:
+//!
+//! - `content.bin`, `trigrams.bin`, `meta.db`: a regular index of the commit
+//! - `revision.json`: the [`RevisionSegment`] manifest
+//!
+//! `rfx query --rev <REV>` searches a segment instead of the main index and
+//! builds it first if needed. Segments are keyed by the full commit SHA, so
+//! `HEAD~5`, a tag and an abbreviated SHA naming the same commit share one.
+//! A commit never changes, so segments are never stale and a full `rfx index`
+//! leaves them alone; re-running `rfx index --rev <REV>` rebuilds one.
+
+use anyhow::{Context, Result};
+use ignore::gitignore::GitignoreBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheManager;
+use crate::indexer::Indexer;
+use crate::models::{IndexConfig, Language};
+
+/// Directory of revision segments inside `.reflex/`
+pub const REVS_DIR: &str = "revs";
+
+/// Manifest file inside a revision segment
+const MANIFEST: &str = "revision.json";
+
+/// Manifest of an indexed revision segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionSegment {
+    /// Full commit SHA the segment was built from
+    pub commit: String,
+    /// Unix timestamp of the segment build
+    pub indexed_at: i64,
+    /// Number of files read from the commit
+    pub files: usize,
+}
+
+impl RevisionSegment {
+    /// Load the manifest of the segment for `commit`, if it was built
+    pub fn open(cache: &CacheManager, commit: &str) -> Option<Self> {
+        let path = cache.at_revision(commit).path().join(MANIFEST);
+        let json = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&json) {
+            Ok(segment) => Some(segment),
+            Err(e) => {
+                log::warn!("Ignoring unreadable revision manifest {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Index settings for a segment built on demand (`[performance]` and index
+/// limits from the main index's config.toml)
+pub fn index_config(cache: &CacheManager) -> Result<IndexConfig> {
+    let mut config = IndexConfig::default();
+    crate::indexer::load_performance_config(cache.path())?.apply(&mut config);
+    crate::indexer::load_index_limits_config(cache.path())?.apply(&mut config);
+    Ok(config)
+}
+
+/// Cache of the segment for `rev`, indexing the revision first if needed
+pub fn open(root: &Path, cache: &CacheManager, rev: &str, config: IndexConfig) -> Result<CacheManager> {
+    let commit = crate::git::resolve_commit(root, rev)?;
+    if RevisionSegment::open(cache, &commit).is_none() {
+        log::info!("Revision {} ({}) is not indexed yet", rev, &commit[..7]);
+        build(root, cache, &commit, config)?;
+    }
+    Ok(cache.at_revision(&commit))
+}
+
+/// Index the files of `rev` into its segment, replacing an existing one
+pub fn index_revision(root: &Path, cache: &CacheManager, rev: &str, config: IndexConfig) -> Result<RevisionSegment> {
+    let commit = crate::git::resolve_commit(root, rev)?;
+    build(root, cache, &commit, config)
+}
+
+/// All indexed revision segments, oldest build first
+pub fn list(cache: &CacheManager) -> Result<Vec<RevisionSegment>> {
+    let dir = cache.path().join(REVS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut segments = Vec::new();
+    for entry in std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let name = entry?.file_name();
+        if let Some(segment) = RevisionSegment::open(cache, &name.to_string_lossy()) {
+            segments.push(segment);
+        }
+    }
+    segments.sort_by_key(|s| s.indexed_at);
+    Ok(segments)
+}
+
+fn build(root: &Path, cache: &CacheManager, commit: &str, config: IndexConfig) -> Result<RevisionSegment> {
+    if !cache.exists() {
+        cache.init()?;
+    }
+
+    let entries = crate::git::list_tree(root, commit)?;
+    let selected = select_entries(root, entries, &config)?;
+    log::info!("Reading {} files of {} from the object database", selected.len(), &commit[..7]);
+
+    let blob_ids: Vec<String> = selected.iter().map(|e| e.blob.clone()).collect();
+    let contents = crate::git::read_blobs(root, &blob_ids)?;
+    let blobs: HashMap<PathBuf, Vec<u8>> = selected
+        .iter()
+        .map(|entry| root.join(&entry.path))
+        .zip(contents)
+        .collect();
+
+    let segment_cache = cache.at_revision(commit);
+    let dir = segment_cache.path().to_path_buf();
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove revision segment at {}", dir.display()))?;
+    }
+
+    // Share the main index settings (e.g. [flags] patterns)
+    std::fs::create_dir_all(&dir)?;
+    let config_toml = cache.path().join(crate::cache::CONFIG_TOML);
+    if config_toml.exists() {
+        std::fs::copy(&config_toml, dir.join(crate::cache::CONFIG_TOML))?;
+    }
+
+    let files = blobs.len();
+    Indexer::new(segment_cache, config)
+        .index_blobs(root, blobs)
+        .with_context(|| format!("Failed to index revision {}", commit))?;
+
+    let segment = RevisionSegment {
+        commit: commit.to_string(),
+        indexed_at: chrono::Utc::now().timestamp(),
+        files,
+    };
+    std::fs::write(dir.join(MANIFEST), serde_json::to_string_pretty(&segment)?)
+        .context("Failed to write revision manifest")?;
+
+    log::info!("Indexed revision {}: {} files", &commit[..7], files);
+    Ok(segment)
+}
+
+/// Keep the tree entries a working-tree index would include
+///
+/// Applies the same language, `max_file_size` and `--exclude` rules as
+/// discovery; ignore files don't apply since committed files are tracked.
+fn select_entries(root: &Path, entries: Vec<crate::git::TreeEntry>, config: &IndexConfig) -> Result<Vec<crate::git::TreeEntry>> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in &config.ignore_patterns {
+        builder.add_line(None, pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid ignore pattern '{}': {}", pattern, e))?;
+    }
+    let ignore = builder.build().context("Failed to build ignore patterns")?;
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| {
+            let path = Path::new(&entry.path);
+            Language::from_path(path).is_supported()
+                && entry.size <= config.max_file_size as u64
+                && !ignore.matched_path_or_any_parents(root.join(path), false).is_ignore()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{QueryEngine, QueryFilter};
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_query_old_revision_without_touching_working_tree() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        git(root, &["init", "-q"]);
+        fs::write(root.join("lib.rs"), "fn legacy_parser() {}\n").unwrap();
+        fs::write(root.join("notes.txt"), "legacy_parser notes\n").unwrap();
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "before"]);
+        git(root, &["tag", "before-refactor"]);
+
+        fs::write(root.join("lib.rs"), "fn new_parser() {}\n").unwrap();
+        git(root, &["commit", "-q", "-am", "refactor"]);
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let filter = QueryFilter { suppress_output: true, ..Default::default() };
+        let head = QueryEngine::new(cache.clone());
+        assert!(head.search("legacy_parser", filter.clone()).unwrap().is_empty());
+
+        let segment = open(root, &cache, "before-refactor", IndexConfig::default()).unwrap();
+        let old = QueryEngine::new(segment.clone());
+        let results = old.search("legacy_parser", filter.clone()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("lib.rs"));
+
+        let symbols = QueryFilter { symbols_mode: true, ..filter };
+        assert_eq!(old.search("legacy_parser", symbols).unwrap().len(), 1);
+
+        // The working tree still holds the refactored file
+        assert_eq!(fs::read_to_string(root.join("lib.rs")).unwrap(), "fn new_parser() {}\n");
+
+        let commit = crate::git::resolve_commit(root, "HEAD~1").unwrap();
+        assert_eq!(segment.revision(), Some(commit.as_str()));
+        let segments = list(&cache).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].files, 1);
+    }
+}
@@ -0,0 +1,879 @@
+//! HTTP route extraction for web frameworks
+//!
+//! Framework-specific extractors recognize route registrations in source text:
+//!
+//! - **axum, actix-web, Rocket** (Rust): `.route("/users", get(list_users))`,
+//!   `web::resource("/users").route(web::get().to(list))`, `#[get("/users")]`
+//! - **Express, Fastify** (JavaScript, TypeScript): `app.get("/users", list)`,
+//!   `router.route("/users").get(list)`, `fastify.route({ method, url, handler })`
+//! - **Laravel** (PHP): `Route::get('/users', [UserController::class, 'index'])`,
+//!   `Route::match(...)`, `Route::resource(...)`
+//! - **Spring** (Java, Kotlin): `@GetMapping("/users")` and `@RequestMapping`,
+//!   prefixed by a class-level `@RequestMapping`
+//! - **Django** (`urls.py`): `path("users/", views.user_list)`, `re_path(...)`
+//!
+//! Paths are reported as registered: prefixes added by nesting (axum `nest`,
+//! `app.use("/api", router)`, Laravel route groups) are not applied.
+//!
+//! Each route is attached to its file's symbols as a [`SymbolKind::Route`]
+//! named by its path, with a `GET /users -> list_users` signature, so
+//! `rfx query /users --kind route` finds it. `rfx routes` lists them with
+//! method and handler.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+use crate::query::QueryEngine;
+
+/// Method of routes that accept every HTTP method
+pub const ANY_METHOD: &str = "ANY";
+
+/// Longest argument list (in bytes) searched for a registration's path and handler
+const MAX_ARGS_BYTES: usize = 4096;
+
+/// Bytes after a Rust route attribute searched for the handler function
+const MAX_HANDLER_DISTANCE: usize = 512;
+
+/// An HTTP route registered in a source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Route {
+    /// Uppercase HTTP method (`ANY` when the route accepts every method)
+    pub method: String,
+    /// Route path as registered (with the class-level prefix for Spring)
+    pub path: String,
+    /// Handler function, method or controller action, when it's named
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    pub framework: &'static str,
+    /// Line of the registration (1-indexed)
+    pub line: usize,
+}
+
+impl Route {
+    /// `GET /users -> list_users`
+    pub fn signature(&self) -> String {
+        match &self.handler {
+            Some(handler) => format!("{} {} -> {}", self.method, self.path, handler),
+            None => format!("{} {}", self.method, self.path),
+        }
+    }
+
+    /// Whether a request with `method` can reach this route
+    pub fn accepts(&self, method: &str) -> bool {
+        self.method == ANY_METHOD || self.method.eq_ignore_ascii_case(method)
+    }
+
+    /// Whether the route path matches `query`
+    ///
+    /// Matches when the paths are equal segment by segment (a route parameter
+    /// such as `:id`, `{id}` or `<int:id>` matches any segment, so `/users/42`
+    /// finds `/users/:id`), or when the route path contains `query`.
+    pub fn matches_path(&self, query: &str) -> bool {
+        let route = self.path.trim_matches('/');
+        let query = query.trim_matches('/');
+        if route.contains(query) {
+            return true;
+        }
+
+        let route_segments: Vec<&str> = route.split('/').collect();
+        let query_segments: Vec<&str> = query.split('/').collect();
+        route_segments.len() == query_segments.len()
+            && route_segments.iter().zip(&query_segments).all(|(r, q)| {
+                r == q || r.starts_with([':', '{', '<', '*']) || r.starts_with("(?P<")
+            })
+    }
+}
+
+/// Extract the routes registered in a file
+pub fn extract(path: &str, source: &str, language: Language) -> Vec<Route> {
+    let lines = LineIndex::new(source);
+    let mut routes = Vec::new();
+
+    match language {
+        Language::Rust => rust_routes(source, &lines, &mut routes),
+        Language::JavaScript | Language::TypeScript => js_routes(source, &lines, &mut routes),
+        Language::PHP => laravel_routes(source, &lines, &mut routes),
+        Language::Java | Language::Kotlin => spring_routes(source, &lines, &mut routes),
+        Language::Python if path.ends_with("urls.py") => django_routes(source, &lines, &mut routes),
+        _ => {}
+    }
+
+    routes.sort_by_key(|r| r.line);
+    routes
+}
+
+/// Add the routes registered in a file to its symbols
+pub fn attach_routes(symbols: &mut Vec<SearchResult>, path: &str, source: &str, language: Language) {
+    let routes = extract(path, source, language);
+    if routes.is_empty() {
+        return;
+    }
+
+    let source_lines: Vec<&str> = source.lines().collect();
+    for route in routes {
+        let preview = source_lines.get(route.line - 1).map(|l| l.trim().to_string()).unwrap_or_default();
+        let mut symbol = SearchResult::new(
+            path.to_string(),
+            language,
+            SymbolKind::Route,
+            Some(route.path.clone()),
+            Span::new(route.line, 0, route.line, 0),
+            None,
+            preview,
+        );
+        symbol.signature = Some(route.signature());
+        symbols.push(symbol);
+    }
+}
+
+/// Filters for a route scan
+#[derive(Debug, Clone, Default)]
+pub struct RouteFilter {
+    /// Only scan files in this language
+    pub language: Option<Language>,
+    /// Include files matching any of these globs
+    pub glob_patterns: Vec<String>,
+    /// Exclude files matching any of these globs
+    pub exclude_patterns: Vec<String>,
+    /// Only routes reachable with this HTTP method
+    pub method: Option<String>,
+    /// Only routes whose path matches (see [`Route::matches_path`])
+    pub path: Option<String>,
+}
+
+impl RouteFilter {
+    /// Set the method and path from a `GET /users` style pattern
+    ///
+    /// A leading HTTP method is optional (`/users` matches every method).
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        let mut words = pattern.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(method), Some(path)) if is_http_method(method) => {
+                self.method = Some(method.to_uppercase());
+                self.path = Some(path.to_string());
+            }
+            (Some(method), None) if is_http_method(method) => self.method = Some(method.to_uppercase()),
+            (Some(_), _) => self.path = Some(pattern.trim().to_string()),
+            (None, _) => {}
+        }
+        self
+    }
+
+    fn matches(&self, route: &Route) -> bool {
+        self.method.as_deref().is_none_or(|m| route.accepts(m))
+            && self.path.as_deref().is_none_or(|p| route.matches_path(p))
+    }
+}
+
+fn is_http_method(word: &str) -> bool {
+    ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "ANY"]
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(word))
+}
+
+/// A route and the file registering it
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteEntry {
+    pub file: String,
+    #[serde(flatten)]
+    pub route: Route,
+}
+
+/// Scan the index in `cache` for HTTP routes
+///
+/// Returns routes sorted by file and line.
+pub fn scan(cache: &CacheManager, filter: &RouteFilter) -> Result<Vec<RouteEntry>> {
+    let content_reader = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+
+    let include_matcher = build_glob_matcher(&filter.glob_patterns);
+    let exclude_matcher = build_glob_matcher(&filter.exclude_patterns);
+
+    let mut files: Vec<(String, Language, &str)> = Vec::new();
+    for file_id in 0..content_reader.file_count() as u32 {
+        let Some(file_path) = content_reader.get_file_path(file_id) else { continue };
+        let lang = Language::from_path(file_path);
+        if lang == Language::Unknown || filter.language.is_some_and(|l| l != lang) {
+            continue;
+        }
+
+        let path_str = file_path.to_string_lossy().to_string();
+        let included = include_matcher.as_ref().is_none_or(|m| m.is_match(&path_str));
+        let excluded = exclude_matcher.as_ref().is_some_and(|m| m.is_match(&path_str));
+        if !included || excluded {
+            continue;
+        }
+
+        match content_reader.get_file_content(file_id) {
+            Ok(content) => files.push((path_str, lang, content)),
+            Err(e) => log::warn!("Skipping {}: {}", path_str, e),
+        }
+    }
+
+    let mut entries: Vec<RouteEntry> = files
+        .par_iter()
+        .flat_map_iter(|(path, lang, content)| {
+            extract(path, content, *lang)
+                .into_iter()
+                .filter(|route| filter.matches(route))
+                .map(|route| RouteEntry { file: path.clone(), route })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.file.cmp(&b.file).then(a.route.line.cmp(&b.route.line)));
+
+    Ok(entries)
+}
+
+fn build_glob_matcher(patterns: &[String]) -> Option<globset::GlobSet> {
+    use globset::{Glob, GlobSetBuilder};
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = QueryEngine::normalize_glob_pattern(pattern);
+        if let Ok(glob) = Glob::new(&normalized) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+/// Byte offset to line number lookup
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// 1-indexed line containing `offset`
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Contents of the bracket opening at byte `open`, and the offset after its close
+///
+/// Brackets inside string literals are skipped. An unbalanced bracket yields
+/// everything up to [`MAX_ARGS_BYTES`].
+fn bracketed(text: &str, open: usize) -> (&str, usize) {
+    let bytes = text.as_bytes();
+    let mut limit = (open + MAX_ARGS_BYTES).min(bytes.len());
+    while !text.is_char_boundary(limit) {
+        limit -= 1;
+    }
+
+    let mut depth = 0usize;
+    let mut quote: Option<u8> = None;
+    let mut i = open;
+    while i < limit {
+        let b = bytes[i];
+        match quote {
+            Some(_) if b == b'\\' => i += 1,
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None => match b {
+                b'"' | b'\'' | b'`' => quote = Some(b),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return (&text[open + 1..i], i + 1);
+                    }
+                }
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+
+    (text.get(open + 1..limit).unwrap_or_default(), limit)
+}
+
+/// Top-level comma-separated arguments
+fn split_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in args.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = args[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+/// Contents of a string literal argument (`"x"`, `'x'`, `` `x` ``, `r"x"`)
+fn string_literal(arg: &str) -> Option<&str> {
+    let arg = arg.trim();
+    let arg = arg.strip_prefix('r').filter(|a| a.starts_with(['"', '\''])).unwrap_or(arg);
+    let quote = arg.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let inner = &arg[1..];
+    inner.find(quote).map(|end| &inner[..end])
+}
+
+/// Whether `arg` names a function (`list_users`, `handlers::list`, `ctrl.list`)
+fn handler_name(arg: &str) -> Option<String> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    regex(&RE, r"^[A-Za-z_$][\w$]*(?:(?:::|\.)[A-Za-z_$][\w$]*)*$")
+        .is_match(arg.trim())
+        .then(|| arg.trim().to_string())
+}
+
+/// Method calls chained after byte `pos`: `.get(a).post(b)` -> [("get", "a"), ("post", "b")]
+fn method_chain(text: &str, mut pos: usize) -> Vec<(&str, &str)> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let call = regex(&RE, r"^\s*\.\s*([A-Za-z_]\w*)\s*\(");
+
+    let mut chain = Vec::new();
+    while let Some(caps) = text.get(pos..).and_then(|rest| call.captures(rest)) {
+        let name = caps.get(1).unwrap().as_str();
+        let open = pos + caps.get(0).unwrap().end() - 1;
+        let (args, end) = bracketed(text, open);
+        chain.push((name, args));
+        pos = end;
+    }
+    chain
+}
+
+fn push(routes: &mut Vec<Route>, framework: &'static str, line: usize, method: &str, path: &str, handler: Option<String>) {
+    routes.push(Route {
+        method: method.to_uppercase(),
+        path: path.to_string(),
+        handler,
+        framework,
+        line,
+    });
+}
+
+/// axum `get(handler)` / actix `web::get().to(handler)` method routers in `args`
+fn rust_method_routers(args: &str) -> Vec<(String, Option<String>)> {
+    static METHOD: OnceLock<Regex> = OnceLock::new();
+    static HANDLER: OnceLock<Regex> = OnceLock::new();
+    let method = regex(&METHOD, r"\b(get|post|put|delete|patch|head|options|trace|any)\s*\(");
+    let handler = regex(&HANDLER, r"^\s*(?:\)\s*\.\s*to\s*\(\s*)?([A-Za-z_][\w:]*)\s*[),]");
+
+    method
+        .captures_iter(args)
+        .map(|caps| {
+            let name = caps.get(1).unwrap();
+            let name_str = if name.as_str() == "any" { ANY_METHOD } else { name.as_str() };
+            let after = &args[caps.get(0).unwrap().end()..];
+            let handler = handler.captures(after).map(|h| h[1].to_string());
+            (name_str.to_string(), handler)
+        })
+        .collect()
+}
+
+fn rust_routes(source: &str, lines: &LineIndex, routes: &mut Vec<Route>) {
+    static ROUTE: OnceLock<Regex> = OnceLock::new();
+    static RESOURCE: OnceLock<Regex> = OnceLock::new();
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    static ATTR_METHOD: OnceLock<Regex> = OnceLock::new();
+    static FN_NAME: OnceLock<Regex> = OnceLock::new();
+
+    // axum `.route("/users", get(list).post(create))`, actix `.route("/users", web::get().to(list))`
+    for m in regex(&ROUTE, r"\.\s*route\s*\(").find_iter(source) {
+        let (args, _) = bracketed(source, m.end() - 1);
+        let parts = split_args(args);
+        let Some(path) = parts.first().and_then(|p| string_literal(p)) else { continue };
+        let rest = parts[1..].join(",");
+        let framework = if rest.contains(".to(") || rest.contains("web::") { "actix-web" } else { "axum" };
+        let line = lines.line(m.start());
+
+        let routers = rust_method_routers(&rest);
+        if routers.is_empty() {
+            push(routes, framework, line, ANY_METHOD, path, parts.get(1).and_then(|h| handler_name(h)));
+        }
+        for (method, handler) in routers {
+            push(routes, framework, line, &method, path, handler);
+        }
+    }
+
+    // actix `web::resource("/users").route(web::get().to(list)).to(fallback)`
+    for m in regex(&RESOURCE, r"\bresource\s*\(").find_iter(source) {
+        let (args, end) = bracketed(source, m.end() - 1);
+        let Some(path) = string_literal(args) else { continue };
+        let line = lines.line(m.start());
+        for (name, args) in method_chain(source, end) {
+            match name {
+                "route" => {
+                    for (method, handler) in rust_method_routers(args) {
+                        push(routes, "actix-web", line, &method, path, handler);
+                    }
+                }
+                "to" => push(routes, "actix-web", line, ANY_METHOD, path, handler_name(args)),
+                _ => {}
+            }
+        }
+    }
+
+    // actix / Rocket `#[get("/users")]` and `#[route("/users", method = "GET")]`
+    let framework = if source.contains("rocket") { "rocket" } else { "actix-web" };
+    let attribute = regex(&ATTRIBUTE, r"#\[\s*(?:[\w:]+::)?(get|post|put|delete|patch|head|options|trace|route)\s*\(");
+    for caps in attribute.captures_iter(source) {
+        let whole = caps.get(0).unwrap();
+        let (args, end) = bracketed(source, whole.end() - 1);
+        let Some(path) = split_args(args).first().and_then(|p| string_literal(p)) else { continue };
+        let line = lines.line(whole.start());
+
+        let window_end = (end + MAX_HANDLER_DISTANCE).min(source.len());
+        let handler = source.get(end..window_end)
+            .and_then(|after| regex(&FN_NAME, r"\bfn\s+([A-Za-z_]\w*)").captures(after))
+            .map(|c| c[1].to_string());
+
+        if &caps[1] == "route" {
+            let methods: Vec<String> = regex(&ATTR_METHOD, r#"method\s*=\s*"(\w+)""#)
+                .captures_iter(args)
+                .map(|c| c[1].to_string())
+                .collect();
+            if methods.is_empty() {
+                push(routes, framework, line, ANY_METHOD, path, handler.clone());
+            }
+            for method in methods {
+                push(routes, framework, line, &method, path, handler.clone());
+            }
+        } else {
+            push(routes, framework, line, &caps[1], path, handler);
+        }
+    }
+}
+
+/// Whether an identifier names an Express/Fastify app or router (not an HTTP client)
+fn is_router_receiver(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["app", "router", "server", "fastify", "routes", "route"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+fn js_routes(source: &str, lines: &LineIndex, routes: &mut Vec<Route>) {
+    static VERB: OnceLock<Regex> = OnceLock::new();
+    static ROUTE: OnceLock<Regex> = OnceLock::new();
+    static OBJ_METHOD: OnceLock<Regex> = OnceLock::new();
+    static OBJ_URL: OnceLock<Regex> = OnceLock::new();
+    static OBJ_HANDLER: OnceLock<Regex> = OnceLock::new();
+    static WORD: OnceLock<Regex> = OnceLock::new();
+
+    let framework = if source.contains("fastify") { "fastify" } else { "express" };
+    let last_handler = |parts: &[&str]| if parts.len() > 1 { parts.last().and_then(|h| handler_name(h)) } else { None };
+
+    // `app.get("/users", auth, listUsers)`
+    let verb = regex(&VERB, r"\b([A-Za-z_$][\w$]*)\s*\.\s*(get|post|put|delete|patch|options|head|all)\s*\(");
+    for caps in verb.captures_iter(source) {
+        if !is_router_receiver(&caps[1]) {
+            continue;
+        }
+        let whole = caps.get(0).unwrap();
+        let (args, _) = bracketed(source, whole.end() - 1);
+        let parts = split_args(args);
+        let Some(path) = parts.first().and_then(|p| string_literal(p)) else { continue };
+        if !path.starts_with(['/', '*']) {
+            continue;
+        }
+        let method = if &caps[2] == "all" { ANY_METHOD } else { &caps[2] };
+        push(routes, framework, lines.line(whole.start()), method, path, last_handler(&parts));
+    }
+
+    // `router.route("/users").get(list).post(create)` and `fastify.route({ method, url, handler })`
+    for caps in regex(&ROUTE, r"\b([A-Za-z_$][\w$]*)\s*\.\s*route\s*\(").captures_iter(source) {
+        if !is_router_receiver(&caps[1]) {
+            continue;
+        }
+        let whole = caps.get(0).unwrap();
+        let line = lines.line(whole.start());
+        let (args, end) = bracketed(source, whole.end() - 1);
+
+        if let Some(path) = string_literal(args) {
+            for (name, args) in method_chain(source, end) {
+                let method = match name {
+                    "all" => ANY_METHOD,
+                    "get" | "post" | "put" | "delete" | "patch" | "options" | "head" => name,
+                    _ => continue,
+                };
+                let parts = split_args(args);
+                push(routes, "express", line, method, path, parts.last().and_then(|h| handler_name(h)));
+            }
+        } else if args.trim_start().starts_with('{') {
+            let url = regex(&OBJ_URL, r#"\b(?:url|path)\s*:\s*['"`]([^'"`]+)['"`]"#).captures(args);
+            let Some(url) = url else { continue };
+            let handler = regex(&OBJ_HANDLER, r"\bhandler\s*:\s*([A-Za-z_$][\w$.]*)")
+                .captures(args)
+                .map(|c| c[1].to_string());
+            let methods: Vec<String> = regex(&OBJ_METHOD, r"\bmethod\s*:\s*(\[[^\]]*\]|['`]\w+['`]|\x22\w+\x22)")
+                .captures(args)
+                .map(|c| regex(&WORD, r"\w+").find_iter(&c[1]).map(|w| w.as_str().to_string()).collect())
+                .unwrap_or_default();
+            if methods.is_empty() {
+                push(routes, "fastify", line, ANY_METHOD, &url[1], handler.clone());
+            }
+            for method in methods {
+                push(routes, "fastify", line, &method, &url[1], handler.clone());
+            }
+        }
+    }
+}
+
+/// Laravel action: `[UserController::class, 'index']`, `'UserController@index'` or `UserController::class`
+fn laravel_action(arg: &str) -> Option<String> {
+    static ARRAY: OnceLock<Regex> = OnceLock::new();
+    static CLASS: OnceLock<Regex> = OnceLock::new();
+
+    if let Some(caps) = regex(&ARRAY, r#"^\[\s*\\?([\w\\]+)::class\s*,\s*['"](\w+)['"]\s*\]$"#).captures(arg) {
+        return Some(format!("{}@{}", &caps[1], &caps[2]));
+    }
+    if let Some(action) = string_literal(arg).filter(|a| a.contains('@')) {
+        return Some(action.to_string());
+    }
+    regex(&CLASS, r"^\\?([\w\\]+)::class$").captures(arg).map(|c| c[1].to_string())
+}
+
+fn laravel_routes(source: &str, lines: &LineIndex, routes: &mut Vec<Route>) {
+    static VERB: OnceLock<Regex> = OnceLock::new();
+    static MATCH: OnceLock<Regex> = OnceLock::new();
+    static RESOURCE: OnceLock<Regex> = OnceLock::new();
+
+    // `Route::get('/users', ...)` and Lumen's `$router->get('/users', ...)`
+    let verb = regex(&VERB, r"(?:\bRoute::|\$\w*(?:[Rr]outer|app|route)\s*->\s*)(get|post|put|patch|delete|options|any)\s*\(");
+    for caps in verb.captures_iter(source) {
+        let whole = caps.get(0).unwrap();
+        let (args, _) = bracketed(source, whole.end() - 1);
+        let parts = split_args(args);
+        let Some(path) = parts.first().and_then(|p| string_literal(p)) else { continue };
+        let handler = parts.get(1).and_then(|a| laravel_action(a));
+        push(routes, "laravel", lines.line(whole.start()), &caps[1], path, handler);
+    }
+
+    // `Route::match(['get', 'post'], '/users', ...)`
+    for m in regex(&MATCH, r"\bRoute::match\s*\(").find_iter(source) {
+        let (args, _) = bracketed(source, m.end() - 1);
+        let parts = split_args(args);
+        let (Some(methods), Some(path)) = (parts.first(), parts.get(1).and_then(|p| string_literal(p))) else { continue };
+        let handler = parts.get(2).and_then(|a| laravel_action(a));
+        let methods = methods.trim_start_matches('[').trim_end_matches(']');
+        for method in split_args(methods).into_iter().filter_map(string_literal) {
+            push(routes, "laravel", lines.line(m.start()), method, path, handler.clone());
+        }
+    }
+
+    // `Route::resource('users', UserController::class)`: the conventional actions
+    for caps in regex(&RESOURCE, r"\bRoute::(resource|apiResource)\s*\(").captures_iter(source) {
+        let whole = caps.get(0).unwrap();
+        let (args, _) = bracketed(source, whole.end() - 1);
+        let parts = split_args(args);
+        let Some(name) = parts.first().and_then(|p| string_literal(p)) else { continue };
+        let Some(controller) = parts.get(1).and_then(|a| laravel_action(a)) else { continue };
+        let api = &caps[1] == "apiResource";
+
+        let base = format!("/{}", name.trim_matches('/').replace('.', "/"));
+        let resource = base.rsplit('/').next().unwrap_or_default();
+        let param = format!("{{{}}}", resource.strip_suffix('s').unwrap_or(resource));
+        let member = format!("{}/{}", base, param);
+        let actions = [
+            ("GET", base.clone(), "index", true),
+            ("GET", format!("{}/create", base), "create", !api),
+            ("POST", base.clone(), "store", true),
+            ("GET", member.clone(), "show", true),
+            ("GET", format!("{}/edit", member), "edit", !api),
+            ("PUT", member.clone(), "update", true),
+            ("DELETE", member.clone(), "destroy", true),
+        ];
+        let line = lines.line(whole.start());
+        for (method, path, action, included) in actions {
+            if included {
+                push(routes, "laravel", line, method, &path, Some(format!("{}@{}", controller, action)));
+            }
+        }
+    }
+}
+
+/// Paths of a Spring mapping annotation's arguments (`""` when none is given)
+fn spring_paths(args: &str) -> Vec<String> {
+    static LITERAL: OnceLock<Regex> = OnceLock::new();
+    static NAMED: OnceLock<Regex> = OnceLock::new();
+
+    let parts = split_args(args);
+    let named = regex(&NAMED, r"^(value|path)\s*=\s*");
+    let value = parts.iter()
+        .find_map(|p| named.find(p).map(|m| &p[m.end()..]))
+        .or_else(|| parts.first().copied().filter(|p| !p.contains('=')));
+
+    let paths: Vec<String> = value
+        .map(|v| regex(&LITERAL, r#""([^"]*)""#).captures_iter(v).map(|c| c[1].to_string()).collect())
+        .unwrap_or_default();
+    if paths.is_empty() { vec![String::new()] } else { paths }
+}
+
+fn join_paths(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    match (prefix.is_empty(), path.is_empty()) {
+        (true, true) => "/".to_string(),
+        (_, true) => if prefix.starts_with('/') { prefix.to_string() } else { format!("/{}", prefix) },
+        _ => format!("{}/{}", if prefix.starts_with('/') || prefix.is_empty() { prefix.to_string() } else { format!("/{}", prefix) }, path),
+    }
+}
+
+fn spring_routes(source: &str, lines: &LineIndex, routes: &mut Vec<Route>) {
+    static MAPPING: OnceLock<Regex> = OnceLock::new();
+    static REQUEST_METHOD: OnceLock<Regex> = OnceLock::new();
+    static CLASS: OnceLock<Regex> = OnceLock::new();
+    static METHOD_NAME: OnceLock<Regex> = OnceLock::new();
+
+    let mut prefix = String::new();
+    for caps in regex(&MAPPING, r"@(Get|Post|Put|Delete|Patch|Request)Mapping\b").captures_iter(source) {
+        let whole = caps.get(0).unwrap();
+        let after = &source[whole.end()..];
+        let (args, end) = if after.trim_start().starts_with('(') {
+            let open = whole.end() + (after.len() - after.trim_start().len());
+            bracketed(source, open)
+        } else {
+            ("", whole.end())
+        };
+
+        // The declaration the annotation applies to (skipping further annotations)
+        let declaration = source[end..]
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with(['@', '/', '*']))
+            .unwrap_or_default();
+        let paths = spring_paths(args);
+
+        if regex(&CLASS, r"\b(class|interface)\b").is_match(declaration) {
+            prefix = paths.into_iter().next().unwrap_or_default();
+            continue;
+        }
+
+        let handler = regex(&METHOD_NAME, r"([A-Za-z_]\w*)\s*\(")
+            .captures(declaration)
+            .map(|c| c[1].to_string());
+        let methods: Vec<String> = if &caps[1] == "Request" {
+            regex(&REQUEST_METHOD, r"RequestMethod\.(\w+)")
+                .captures_iter(args)
+                .map(|c| c[1].to_string())
+                .collect()
+        } else {
+            vec![caps[1].to_string()]
+        };
+        let methods = if methods.is_empty() { vec![ANY_METHOD.to_string()] } else { methods };
+
+        let line = lines.line(whole.start());
+        for path in &paths {
+            for method in &methods {
+                push(routes, "spring", line, method, &join_paths(&prefix, path), handler.clone());
+            }
+        }
+    }
+}
+
+fn django_routes(source: &str, lines: &LineIndex, routes: &mut Vec<Route>) {
+    static PATH: OnceLock<Regex> = OnceLock::new();
+    static VIEW: OnceLock<Regex> = OnceLock::new();
+
+    for caps in regex(&PATH, r"(?:^|[^\w.])(re_path|path|url)\s*\(").captures_iter(source) {
+        let call = caps.get(1).unwrap();
+        let (args, _) = bracketed(source, caps.get(0).unwrap().end() - 1);
+        let parts = split_args(args);
+        let (Some(route), Some(view)) = (parts.first().and_then(|p| string_literal(p)), parts.get(1)) else { continue };
+        if view.starts_with("include") {
+            continue;
+        }
+
+        let handler = regex(&VIEW, r"^([A-Za-z_][\w.]*?)(?:\.as_view\(.*\))?$")
+            .captures(view)
+            .map(|c| c[1].to_string());
+        push(routes, "django", lines.line(call.start()), ANY_METHOD, route, handler);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(routes: &[Route]) -> Vec<String> {
+        routes.iter().map(|r| format!("{}:{} [{}]", r.line, r.signature(), r.framework)).collect()
+    }
+
+    #[test]
+    fn test_rust_routes() {
+        let source = r#"
+fn app() -> Router {
+    Router::new()
+        .route("/users", get(list_users).post(create_user))
+        .route("/users/{id}", routing::delete(handlers::delete_user))
+}
+
+#[get("/health")]
+async fn health() -> impl Responder { "ok" }
+
+fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/items").route(web::get().to(list_items)));
+}
+"#;
+        assert_eq!(summary(&extract("src/main.rs", source, Language::Rust)), vec![
+            "4:GET /users -> list_users [axum]",
+            "4:POST /users -> create_user [axum]",
+            "5:DELETE /users/{id} -> handlers::delete_user [axum]",
+            "8:GET /health -> health [actix-web]",
+            "12:GET /items -> list_items [actix-web]",
+        ]);
+    }
+
+    #[test]
+    fn test_js_routes() {
+        let source = r#"
+const app = express();
+app.get('/users', auth, listUsers);
+app.post("/users", async (req, res) => { res.send(201) });
+axios.get('/users');
+router.route('/items/:id').get(items.show).delete(items.remove);
+fastify.route({ method: ['GET', 'HEAD'], url: '/health', handler: health });
+"#;
+        assert_eq!(summary(&extract("app.js", source, Language::JavaScript)), vec![
+            "3:GET /users -> listUsers [fastify]",
+            "4:POST /users [fastify]",
+            "6:GET /items/:id -> items.show [express]",
+            "6:DELETE /items/:id -> items.remove [express]",
+            "7:GET /health -> health [fastify]",
+            "7:HEAD /health -> health [fastify]",
+        ]);
+    }
+
+    #[test]
+    fn test_laravel_and_django_routes() {
+        let php = r#"<?php
+Route::get('/users', [UserController::class, 'index']);
+Route::match(['get', 'post'], '/login', 'AuthController@login');
+Route::apiResource('photos', PhotoController::class);
+"#;
+        let routes = extract("routes/web.php", php, Language::PHP);
+        assert_eq!(summary(&routes[..3]), vec![
+            "2:GET /users -> UserController@index [laravel]",
+            "3:GET /login -> AuthController@login [laravel]",
+            "3:POST /login -> AuthController@login [laravel]",
+        ]);
+        assert_eq!(routes.len(), 8);
+        assert_eq!(routes[6].signature(), "PUT /photos/{photo} -> PhotoController@update");
+
+        let python = r#"
+urlpatterns = [
+    path("users/<int:pk>/", views.user_detail, name="user-detail"),
+    path("api/", include("api.urls")),
+    re_path(r"^items/$", ItemList.as_view()),
+]
+"#;
+        assert_eq!(summary(&extract("app/urls.py", python, Language::Python)), vec![
+            "3:ANY users/<int:pk>/ -> views.user_detail [django]",
+            "5:ANY ^items/$ -> ItemList [django]",
+        ]);
+        assert!(extract("app/views.py", python, Language::Python).is_empty());
+    }
+
+    #[test]
+    fn test_spring_routes() {
+        let source = r#"
+@RestController
+@RequestMapping("/api/users")
+public class UserController {
+    @GetMapping
+    public List<User> list() { return users; }
+
+    @GetMapping("/{id}")
+    @ResponseBody
+    public User show(@PathVariable Long id) { return null; }
+
+    @RequestMapping(value = "/search", method = {RequestMethod.GET, RequestMethod.POST})
+    public List<User> search() { return users; }
+}
+"#;
+        assert_eq!(summary(&extract("UserController.java", source, Language::Java)), vec![
+            "5:GET /api/users -> list [spring]",
+            "8:GET /api/users/{id} -> show [spring]",
+            "12:GET /api/users/search -> search [spring]",
+            "12:POST /api/users/search -> search [spring]",
+        ]);
+    }
+
+    #[test]
+    fn test_routes_are_route_symbols() {
+        use crate::indexer::Indexer;
+        use crate::models::IndexConfig;
+        use crate::query::QueryFilter;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::write(root.join("server.js"), "app.get('/users', listUsers);\napp.post('/orders', createOrder);\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let entries = scan(&cache, &RouteFilter::default().with_pattern("GET /users")).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].route.handler.as_deref(), Some("listUsers"));
+        assert!(entries[0].file.ends_with("server.js"));
+
+        let filter = QueryFilter { kinds: vec![SymbolKind::Route], suppress_output: true, ..Default::default() };
+        let results = QueryEngine::new(cache).search("/orders", filter).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].signature.as_deref(), Some("POST /orders -> createOrder"));
+    }
+
+    #[test]
+    fn test_route_filter() {
+        let route = Route {
+            method: "GET".to_string(),
+            path: "/users/:id".to_string(),
+            handler: None,
+            framework: "express",
+            line: 1,
+        };
+        let filter = RouteFilter::default().with_pattern("get /users/42");
+        assert_eq!(filter.method.as_deref(), Some("GET"));
+        assert!(filter.matches(&route));
+        assert!(RouteFilter::default().with_pattern("/users").matches(&route));
+        assert!(!RouteFilter::default().with_pattern("POST /users").matches(&route));
+        assert!(!RouteFilter::default().with_pattern("/orders/1").matches(&route));
+    }
+}
@@ -0,0 +1,436 @@
+//! SARIF 2.1.0 output (`rfx analyze --format sarif`, `rfx lint --format sarif`)
+//!
+//! Turns findings into a SARIF log that GitHub code scanning and other SARIF
+//! consumers can ingest (`rfx analyze --format sarif > rfx.sarif`). `rfx lint`
+//! reports its project rules the same way. Each analysis is one rule:
+//! - `circular-dependency` (warning): reported at the import that closes the
+//!   cycle in its first file, with the other imports of the cycle as related
+//!   locations
+//! - `unused-file` (note): files nothing imports
+//! - `dead-symbol`: exported symbols nothing references; high confidence is a
+//!   warning, medium and low confidence are notes
+//!
+//! Paths are relative to the project root (`%SRCROOT%`). Hotspots and islands
+//! describe the graph rather than problems in it, so they have no rule.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::dead_symbols::{Confidence, DeadSymbolReport};
+use crate::dependency::DependencyIndex;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/reflex-search/reflex";
+
+/// Base URI id that result locations are relative to
+const SRCROOT: &str = "%SRCROOT%";
+
+/// Rule ids of the `rfx analyze` findings
+pub const CIRCULAR_DEPENDENCY: &str = "circular-dependency";
+pub const UNUSED_FILE: &str = "unused-file";
+pub const DEAD_SYMBOL: &str = "dead-symbol";
+
+/// A rule that results refer to by id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifRule {
+    pub id: String,
+    /// PascalCase name some viewers show instead of the id
+    pub name: String,
+    pub short_description: String,
+    pub full_description: Option<String>,
+    /// Level of results that don't set their own
+    pub level: Level,
+}
+
+/// Rules of the `rfx analyze` findings
+pub fn analysis_rules() -> Vec<SarifRule> {
+    let rule = |id: &str, name: &str, short: &str, full: &str, level| SarifRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        short_description: short.to_string(),
+        full_description: Some(full.to_string()),
+        level,
+    };
+    vec![
+        rule(
+            CIRCULAR_DEPENDENCY,
+            "CircularDependency",
+            "Files import each other in a cycle",
+            "A chain of imports leads back to the file it started from. Cycles couple \
+             the files together and can break module initialization order.",
+            Level::Warning,
+        ),
+        rule(
+            UNUSED_FILE,
+            "UnusedFile",
+            "File is not imported by any other file",
+            "No indexed file imports this file. It may be an entry point, loaded \
+             dynamically, or dead code.",
+            Level::Note,
+        ),
+        rule(
+            DEAD_SYMBOL,
+            "DeadSymbol",
+            "Exported symbol is never referenced outside its file",
+            "The symbol is exported, but its name only occurs in its own file or in \
+             tests. Matching is by name, so dynamic uses are not seen.",
+            Level::Note,
+        ),
+    ]
+}
+
+/// SARIF result severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+/// One problem found by an analysis or lint rule
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule_id: String,
+    pub level: Level,
+    pub message: String,
+    pub location: FileLocation,
+    /// Other places involved in the problem (e.g. the rest of a cycle)
+    pub related: Vec<FileLocation>,
+}
+
+/// A file, optionally narrowed to a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLocation {
+    pub path: String,
+    pub line: Option<usize>,
+    pub message: Option<String>,
+}
+
+impl FileLocation {
+    pub fn new(path: &str, line: Option<usize>) -> Self {
+        Self {
+            path: path.strip_prefix("./").unwrap_or(path).replace('\\', "/"),
+            line: line.filter(|l| *l > 0),
+            message: None,
+        }
+    }
+}
+
+/// Findings for `cycles` from [`DependencyIndex::detect_circular_dependencies`]
+///
+/// Each file of a cycle imports the next one, and the last imports the first.
+pub fn circular_findings(deps_index: &DependencyIndex, cycles: &[Vec<i64>]) -> Result<Vec<Finding>> {
+    let ids: Vec<i64> = cycles.iter().flatten().copied().collect();
+    let paths = deps_index.get_file_paths(&ids)?;
+    let path_of = |id: &i64| paths.get(id).cloned().unwrap_or_else(|| format!("<file {}>", id));
+
+    let mut findings = Vec::with_capacity(cycles.len());
+    for cycle in cycles.iter().filter(|c| !c.is_empty()) {
+        let mut imports = Vec::with_capacity(cycle.len());
+        for (i, file_id) in cycle.iter().enumerate() {
+            let target = cycle[(i + 1) % cycle.len()];
+            let line = deps_index
+                .get_dependencies(*file_id)?
+                .into_iter()
+                .find(|dep| dep.resolved_file_id == Some(target))
+                .map(|dep| dep.line_number);
+            let mut location = FileLocation::new(&path_of(file_id), line);
+            location.message = Some(format!("imports {}", path_of(&target)));
+            imports.push(location);
+        }
+
+        let chain: Vec<String> = cycle.iter().chain(cycle.first()).map(path_of).collect();
+        let mut related = imports;
+        let location = related.remove(0);
+        findings.push(Finding {
+            rule_id: CIRCULAR_DEPENDENCY.to_string(),
+            level: Level::Warning,
+            message: format!("Circular dependency: {}", chain.join(" -> ")),
+            location,
+            related,
+        });
+    }
+
+    Ok(findings)
+}
+
+/// Findings for files from [`DependencyIndex::find_unused_files`]
+pub fn unused_findings(deps_index: &DependencyIndex, file_ids: &[i64]) -> Result<Vec<Finding>> {
+    let paths = deps_index.get_file_paths(file_ids)?;
+    Ok(file_ids
+        .iter()
+        .filter_map(|id| paths.get(id))
+        .map(|path| Finding {
+            rule_id: UNUSED_FILE.to_string(),
+            level: Level::Note,
+            message: format!("{} is not imported by any indexed file", path),
+            location: FileLocation::new(path, None),
+            related: Vec::new(),
+        })
+        .collect())
+}
+
+/// Findings for a dead symbol analysis
+pub fn dead_symbol_findings(report: &DeadSymbolReport) -> Vec<Finding> {
+    report
+        .symbols
+        .iter()
+        .map(|symbol| Finding {
+            rule_id: DEAD_SYMBOL.to_string(),
+            level: match symbol.confidence {
+                Confidence::High => Level::Warning,
+                Confidence::Medium | Confidence::Low => Level::Note,
+            },
+            message: format!(
+                "Exported {} '{}': {} ({} confidence)",
+                symbol.kind.to_string().to_lowercase(),
+                symbol.qualified_name.as_deref().unwrap_or(&symbol.name),
+                symbol.reason,
+                symbol.confidence,
+            ),
+            location: FileLocation::new(&symbol.path, Some(symbol.line)),
+            related: Vec::new(),
+        })
+        .collect()
+}
+
+/// A SARIF log with one run of reflex
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    original_uri_base_ids: serde_json::Value,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportingDescriptor {
+    id: String,
+    name: String,
+    short_description: Message,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+    default_configuration: Configuration,
+}
+
+#[derive(Debug, Serialize)]
+struct Configuration {
+    level: Level,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: Level,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+}
+
+impl Location {
+    fn new(location: &FileLocation, id: Option<usize>) -> Self {
+        Self {
+            id,
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation {
+                    uri: location.path.replace(' ', "%20"),
+                    uri_base_id: SRCROOT,
+                },
+                region: location.line.map(|start_line| Region { start_line }),
+            },
+            message: location.message.clone().map(|text| Message { text }),
+        }
+    }
+}
+
+impl SarifLog {
+    /// Build a log of `findings`; every rule is listed even without results
+    pub fn new(rules: &[SarifRule], findings: &[Finding]) -> Self {
+        let descriptors = rules
+            .iter()
+            .map(|rule| ReportingDescriptor {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+                short_description: Message { text: rule.short_description.clone() },
+                full_description: rule.full_description.clone().map(|text| Message { text }),
+                default_configuration: Configuration { level: rule.level },
+            })
+            .collect();
+
+        let results = findings
+            .iter()
+            .map(|finding| SarifResult {
+                rule_id: finding.rule_id.clone(),
+                rule_index: rules.iter().position(|rule| rule.id == finding.rule_id),
+                level: finding.level,
+                message: Message { text: finding.message.clone() },
+                locations: vec![Location::new(&finding.location, None)],
+                related_locations: finding
+                    .related
+                    .iter()
+                    .enumerate()
+                    .map(|(i, related)| Location::new(related, Some(i + 1)))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "reflex",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: INFORMATION_URI,
+                        rules: descriptors,
+                    },
+                },
+                original_uri_base_ids: serde_json::json!({ SRCROOT: { "uri": "./" } }),
+                results,
+            }],
+        }
+    }
+
+    pub fn render(&self, pretty: bool) -> Result<String> {
+        Ok(if pretty {
+            serde_json::to_string_pretty(self)?
+        } else {
+            serde_json::to_string(self)?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::dead_symbols::{DeadReason, DeadSymbol};
+    use crate::models::{ImportType, SymbolKind};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_circular_dependency_result() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        cache.update_file("src/a.rs", "rust", 10).unwrap();
+        cache.update_file("src/b.rs", "rust", 10).unwrap();
+
+        let deps_index = DependencyIndex::new(cache);
+        deps_index.insert_dependency(1, "crate::b".to_string(), Some(2), ImportType::Internal, 3, None).unwrap();
+        deps_index.insert_dependency(2, "crate::a".to_string(), Some(1), ImportType::Internal, 7, None).unwrap();
+
+        let findings = circular_findings(&deps_index, &[vec![1, 2]]).unwrap();
+        let log = serde_json::to_value(SarifLog::new(&analysis_rules(), &findings)).unwrap();
+        let result = &log["runs"][0]["results"][0];
+
+        assert_eq!(result["ruleId"], "circular-dependency");
+        assert_eq!(result["level"], "warning");
+        assert_eq!(result["message"]["text"], "Circular dependency: src/a.rs -> src/b.rs -> src/a.rs");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/a.rs");
+        assert_eq!(location["region"]["startLine"], 3);
+        let related = &result["relatedLocations"][0];
+        assert_eq!(related["physicalLocation"]["region"]["startLine"], 7);
+        assert_eq!(related["message"]["text"], "imports src/a.rs");
+    }
+
+    #[test]
+    fn test_log_lists_rules_and_dead_symbol_levels() {
+        let symbol = |name: &str, confidence| DeadSymbol {
+            path: "./src/lib.rs".to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: SymbolKind::Function,
+            line: 4,
+            confidence,
+            reason: DeadReason::Unreferenced,
+        };
+        let report = DeadSymbolReport {
+            symbols: vec![symbol("orphan", Confidence::High), symbol("helper", Confidence::Low)],
+            candidates: 2,
+            files_scanned: 1,
+        };
+
+        let log = serde_json::to_value(SarifLog::new(&analysis_rules(), &dead_symbol_findings(&report))).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        let rule_ids: Vec<&str> = run["tool"]["driver"]["rules"].as_array().unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rule_ids, vec!["circular-dependency", "unused-file", "dead-symbol"]);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleIndex"], 2);
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[0]["message"]["text"], "Exported function 'orphan': no references (high confidence)");
+        assert_eq!(results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(results[1]["level"], "note");
+    }
+}
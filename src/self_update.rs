@@ -0,0 +1,533 @@
+//! Self-update from GitHub releases
+//!
+//! `rfx self-update` downloads the release archive for the running platform,
+//! verifies it against the published SHA-256 checksum, and atomically swaps it
+//! in place of the running binary.
+//!
+//! The checksum is published in the same GitHub release as the archive, so it
+//! catches truncated or corrupted downloads but does not prove who built the
+//! release; that rests on HTTPS and the GitHub release itself.
+//!
+//! # Channels
+//!
+//! - `stable`: the latest non-prerelease GitHub release
+//! - `nightly`: the newest release, including prereleases
+//!
+//! # Configuration
+//!
+//! Self-update is configured in the user-level `~/.reflex/config.toml` (shared by
+//! every project on the machine):
+//!
+//! ```toml
+//! [update]
+//! enabled = false      # Disable `rfx self-update` (e.g., for package-managed installs)
+//! channel = "nightly"  # Default channel when --channel is not given
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// GitHub repository that publishes release artifacts
+const RELEASE_REPO: &str = "reflex-search/reflex";
+
+/// Package name used as the artifact prefix by `dist`
+const ARTIFACT_PREFIX: &str = "reflex-search";
+
+/// Target triple this binary was built for (set by build.rs)
+const BUILD_TARGET: &str = env!("RFX_BUILD_TARGET");
+
+/// Release channel to update from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Stable,
+    Nightly,
+}
+
+impl Channel {
+    /// Parse a channel name (`stable` or `nightly`)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "stable" => Ok(Channel::Stable),
+            "nightly" => Ok(Channel::Nightly),
+            _ => anyhow::bail!("Unknown release channel '{}' (expected 'stable' or 'nightly')", name),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Nightly => "nightly",
+        }
+    }
+}
+
+/// `[update]` section of `~/.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// Allow `rfx self-update` to replace the binary
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Default channel when `--channel` is not given
+    #[serde(default)]
+    pub channel: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channel: None,
+        }
+    }
+}
+
+/// Load the `[update]` section from `~/.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_update_config() -> Result<UpdateConfig> {
+    let home = match dirs::home_dir() {
+        Some(h) => h,
+        None => {
+            log::debug!("Could not determine home directory, using default update config");
+            return Ok(UpdateConfig::default());
+        }
+    };
+
+    parse_update_config(&home.join(".reflex").join("config.toml"))
+}
+
+fn parse_update_config(config_path: &Path) -> Result<UpdateConfig> {
+    if !config_path.exists() {
+        return Ok(UpdateConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+
+    match toml_value.get("update") {
+        Some(section) => section.clone().try_into()
+            .with_context(|| format!("Failed to parse [update] section in {}", config_path.display())),
+        None => Ok(UpdateConfig::default()),
+    }
+}
+
+/// A GitHub release (subset of the REST API response)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// A downloadable file attached to a release
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// Version without a leading `v` (e.g., `v1.2.0` → `1.2.0`)
+    pub fn version(&self) -> &str {
+        self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name)
+    }
+
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// Outcome of a self-update run
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateReport {
+    pub current_version: String,
+    pub latest_version: String,
+    pub channel: Channel,
+    /// True if a newer version is available (or `--force` was given)
+    pub update_available: bool,
+    /// True if the binary was replaced
+    pub updated: bool,
+}
+
+/// Archive name `dist` publishes for a target triple
+pub fn archive_name(target: &str) -> String {
+    if target.contains("windows") {
+        format!("{}-{}.zip", ARTIFACT_PREFIX, target)
+    } else {
+        format!("{}-{}.tar.xz", ARTIFACT_PREFIX, target)
+    }
+}
+
+/// Pick the release to install for a channel from a newest-first release list
+pub fn select_release(releases: &[Release], channel: Channel) -> Option<&Release> {
+    releases.iter()
+        .filter(|r| !r.draft)
+        .find(|r| channel == Channel::Nightly || !r.prerelease)
+}
+
+/// Compare two `MAJOR.MINOR.PATCH[-PRERELEASE]` versions
+///
+/// A release sorts after its prereleases (`1.2.0-rc.1` < `1.2.0`), and numeric
+/// prerelease identifiers compare numerically (`nightly.9` < `nightly.10`).
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+        let numbers = core.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+        (numbers, pre)
+    }
+
+    let (a_core, a_pre) = split(a);
+    let (b_core, b_pre) = split(b);
+
+    let len = a_core.len().max(b_core.len());
+    for i in 0..len {
+        let ordering = a_core.get(i).unwrap_or(&0).cmp(b_core.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a_pre), Some(b_pre)) => {
+            let mut a_parts = a_pre.split('.');
+            let mut b_parts = b_pre.split('.');
+            loop {
+                match (a_parts.next(), b_parts.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(x), Some(y)) => {
+                        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                            (Ok(x), Ok(y)) => x.cmp(&y),
+                            _ => x.cmp(y),
+                        };
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Extract the hex digest from a `.sha256` file (`<hex>  <filename>` or bare hex)
+pub fn parse_checksum(contents: &str) -> Option<String> {
+    let digest = contents.split_whitespace().next()?.to_lowercase();
+    if digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(digest)
+    } else {
+        None
+    }
+}
+
+/// Verify `data` against an expected SHA-256 hex digest
+pub fn verify_checksum(data: &[u8], expected: &str) -> Result<()> {
+    let actual = Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    if actual != expected.to_lowercase() {
+        anyhow::bail!(
+            "Checksum mismatch for downloaded archive\n\
+             \n\
+             Expected: {}\n\
+             Actual:   {}\n\
+             \n\
+             The download may be corrupted. The binary was not replaced.",
+            expected, actual
+        );
+    }
+    Ok(())
+}
+
+/// Check for (and optionally install) a newer release
+///
+/// `channel` defaults to the configured channel, then `stable`. With `check_only`,
+/// only reports whether an update is available. With `force`, reinstalls even when
+/// the latest release is not newer than the running binary.
+pub fn run(channel: Option<Channel>, check_only: bool, force: bool) -> Result<UpdateReport> {
+    let config = load_update_config()?;
+    let channel = match channel {
+        Some(channel) => channel,
+        None => match config.channel.as_deref() {
+            Some(name) => Channel::parse(name)
+                .context("Invalid 'channel' in [update] section of ~/.reflex/config.toml")?,
+            None => Channel::Stable,
+        },
+    };
+    if !config.enabled && !check_only {
+        anyhow::bail!(
+            "Self-update is disabled in ~/.reflex/config.toml.\n\
+             \n\
+             To enable it, set:\n\
+             [update]\n\
+             enabled = true"
+        );
+    }
+
+    let runtime = tokio::runtime::Runtime::new()
+        .context("Failed to create async runtime")?;
+    runtime.block_on(run_async(channel, check_only, force))
+}
+
+async fn run_async(channel: Channel, check_only: bool, force: bool) -> Result<UpdateReport> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("rfx/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let releases: Vec<Release> = client
+        .get(format!("https://api.github.com/repos/{}/releases?per_page=20", RELEASE_REPO))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .context("Failed to query GitHub releases")?
+        .error_for_status()
+        .context("GitHub releases request failed")?
+        .json()
+        .await
+        .context("Failed to parse GitHub releases response")?;
+
+    let release = select_release(&releases, channel)
+        .with_context(|| format!("No {} release found for {}", channel.as_str(), RELEASE_REPO))?;
+
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+    let latest_version = release.version().to_string();
+    let update_available = force
+        || compare_versions(&latest_version, &current_version) == Ordering::Greater;
+
+    let mut report = UpdateReport {
+        current_version,
+        latest_version,
+        channel,
+        update_available,
+        updated: false,
+    };
+
+    if check_only || !update_available {
+        return Ok(report);
+    }
+
+    let archive = archive_name(BUILD_TARGET);
+    let archive_asset = release.asset(&archive).with_context(|| {
+        format!("Release {} has no artifact for this platform ({})", release.tag_name, archive)
+    })?;
+    let checksum_asset = release.asset(&format!("{}.sha256", archive)).with_context(|| {
+        format!("Release {} has no checksum for {}; refusing to install an unverified binary", release.tag_name, archive)
+    })?;
+
+    log::info!("Downloading {}", archive_asset.browser_download_url);
+    let checksum_text = download(&client, &checksum_asset.browser_download_url).await?;
+    let expected = parse_checksum(&String::from_utf8_lossy(&checksum_text))
+        .with_context(|| format!("Invalid checksum file {}", checksum_asset.name))?;
+    let archive_bytes = download(&client, &archive_asset.browser_download_url).await?;
+    verify_checksum(&archive_bytes, &expected)?;
+
+    // Unique, private directory, removed when dropped
+    let temp_dir = tempfile::Builder::new()
+        .prefix("rfx-update-")
+        .tempdir()
+        .context("Failed to create a temporary directory for the update")?;
+    let work_dir = temp_dir.path();
+
+    let archive_path = work_dir.join(&archive);
+    std::fs::write(&archive_path, &archive_bytes)
+        .with_context(|| format!("Failed to write {}", archive_path.display()))?;
+    extract_archive(&archive_path, work_dir)?;
+
+    let new_binary = find_binary(work_dir)?
+        .with_context(|| format!("Archive {} does not contain an rfx binary", archive))?;
+    let current_exe = std::env::current_exe()
+        .context("Failed to locate the running rfx binary")?;
+    replace_binary(&new_binary, &current_exe)?;
+
+    report.updated = true;
+    Ok(report)
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>> {
+    let bytes = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?
+        .error_for_status()
+        .with_context(|| format!("Download failed: {}", url))?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to read {}", url))?;
+    Ok(bytes.to_vec())
+}
+
+/// Unpack a `.tar.xz`/`.zip` archive with the system `tar` (bsdtar on Windows/macOS)
+fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let status = std::process::Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dest)
+        .status()
+        .context("Failed to run 'tar' to extract the release archive")?;
+
+    if !status.success() {
+        anyhow::bail!("Failed to extract {} (tar exited with {})", archive.display(), status);
+    }
+    Ok(())
+}
+
+/// Locate the `rfx` executable inside an extracted archive
+fn find_binary(dir: &Path) -> Result<Option<PathBuf>> {
+    let binary_name = if cfg!(windows) { "rfx.exe" } else { "rfx" };
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() && entry.file_name() == binary_name {
+            return Ok(Some(entry.into_path()));
+        }
+    }
+    Ok(None)
+}
+
+/// Atomically replace `target` with `new_binary`
+///
+/// The new binary is first copied next to the target (same filesystem) and then
+/// renamed over it, so a crash never leaves a partially written executable. On
+/// Windows the running binary cannot be overwritten, so it is moved aside first
+/// (and moved back if the new binary can't be put in its place).
+fn replace_binary(new_binary: &Path, target: &Path) -> Result<()> {
+    let staged = target.with_extension("new");
+    std::fs::copy(new_binary, &staged)
+        .with_context(|| format!("Failed to stage new binary at {}", staged.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", staged.display()))?;
+    }
+
+    let old = if cfg!(windows) {
+        let old = target.with_extension("old");
+        let _ = std::fs::remove_file(&old);
+        if let Err(e) = std::fs::rename(target, &old) {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e).with_context(|| format!("Failed to move {} aside", target.display()));
+        }
+        Some(old)
+    } else {
+        None
+    };
+
+    if let Err(e) = std::fs::rename(&staged, target) {
+        if let Some(old) = &old
+            && let Err(restore) = std::fs::rename(old, target)
+        {
+            log::error!("Failed to restore {} from {}: {}", target.display(), old.display(), restore);
+        }
+        let _ = std::fs::remove_file(&staged);
+        return Err(e).with_context(|| format!("Failed to replace {}", target.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn release(tag: &str, prerelease: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            prerelease,
+            draft: false,
+            assets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.0.4", "1.0.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.3", "1.0.3"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0-rc.1", "1.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.1.0-nightly.10", "1.1.0-nightly.9"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0.0", "1.99.99"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_select_release_by_channel() {
+        let releases = vec![
+            release("v1.1.0-nightly.3", true),
+            release("v1.0.4", false),
+        ];
+
+        assert_eq!(select_release(&releases, Channel::Stable).unwrap().version(), "1.0.4");
+        assert_eq!(select_release(&releases, Channel::Nightly).unwrap().version(), "1.1.0-nightly.3");
+    }
+
+    #[test]
+    fn test_archive_name() {
+        assert_eq!(archive_name("x86_64-unknown-linux-gnu"), "reflex-search-x86_64-unknown-linux-gnu.tar.xz");
+        assert_eq!(archive_name("x86_64-pc-windows-msvc"), "reflex-search-x86_64-pc-windows-msvc.zip");
+    }
+
+    #[test]
+    fn test_checksum_verification() {
+        let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let file = format!("{}  reflex-search-x86_64-unknown-linux-gnu.tar.xz\n", digest);
+
+        assert_eq!(parse_checksum(&file).as_deref(), Some(digest));
+        assert!(parse_checksum("not-a-digest").is_none());
+        assert!(verify_checksum(b"hello", digest).is_ok());
+        assert!(verify_checksum(b"corrupted", digest).is_err());
+    }
+
+    #[test]
+    fn test_update_config_can_disable() {
+        let temp = TempDir::new().unwrap();
+        let config_path = temp.path().join("config.toml");
+
+        assert!(parse_update_config(&config_path).unwrap().enabled);
+
+        std::fs::write(&config_path, "[update]\nenabled = false\nchannel = \"nightly\"\n").unwrap();
+        let config = parse_update_config(&config_path).unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.channel.as_deref(), Some("nightly"));
+    }
+
+    #[test]
+    fn test_replace_binary() {
+        let temp = TempDir::new().unwrap();
+        let new_binary = temp.path().join("downloaded");
+        let target = temp.path().join("rfx");
+        std::fs::write(&new_binary, "new").unwrap();
+        std::fs::write(&target, "old").unwrap();
+
+        replace_binary(&new_binary, &target).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert!(!target.with_extension("new").exists());
+    }
+}
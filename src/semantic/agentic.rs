@@ -75,12 +75,13 @@ pub async fn run_agentic_loop(
 ) -> Result<AgenticQueryResponse> {
     log::info!("Starting agentic loop for question: {}", question);
 
-    // Validate cache before starting - auto-reindex if schema mismatch detected
+    // Validate cache before starting - auto-reindex if schema or format mismatch detected
+    cache.migrate()?;
     if let Err(e) = cache.validate() {
         let error_msg = e.to_string();
 
-        // Check if this is a schema mismatch error
-        if error_msg.contains("Cache schema version mismatch") {
+        // Check if this is a schema or format mismatch error
+        if error_msg.contains("Cache schema version mismatch") || error_msg.contains("Cache format version mismatch") {
             log::warn!("Cache schema mismatch detected, auto-reindexing...");
 
             // Create progress callback that reports to the reporter
@@ -204,6 +205,7 @@ pub async fn run_agentic_loop(
             None
         },
         answer: None,  // No answer generation in agentic mode (handled in CLI)
+        citations: Vec::new(),
     })
 }
 
@@ -514,6 +516,7 @@ async fn phase_6_refine(
         },
         tools_executed: None,  // No new tools executed during refinement
         answer: None,  // No answer generation in agentic mode (handled in CLI)
+        citations: Vec::new(),
     })
 }
 
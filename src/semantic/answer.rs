@@ -4,6 +4,9 @@
 //! from code search results using LLM providers.
 
 use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use crate::models::FileGroupedResult;
 use super::providers::LlmProvider;
 
@@ -13,6 +16,17 @@ const MAX_MATCHES_IN_PROMPT: usize = 50;
 /// Maximum preview length per match (characters)
 const MAX_PREVIEW_LENGTH: usize = 200;
 
+/// A `path:line` reference found in a generated answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Path as it appears in the search results
+    pub path: String,
+    pub line: usize,
+    /// Whether the line falls inside one of the matches shown to the LLM
+    /// (false means the file was in the results but the line was not)
+    pub verified: bool,
+}
+
 /// Generate a conversational answer based on search results
 ///
 /// Takes the user's original question and search results, then calls the LLM
@@ -179,7 +193,7 @@ fn build_answer_prompt(
     // Instructions for answer format
     prompt.push_str("\nProvide a conversational answer that:\n");
     prompt.push_str("1. Directly answers the question based on the search results\n");
-    prompt.push_str("2. References specific files and line numbers where relevant\n");
+    prompt.push_str("2. Cites the code it relies on as path:line, using a File path and Line number from above (e.g. src/main.rs:42)\n");
     prompt.push_str("3. Summarizes patterns or common approaches if multiple results are similar\n");
     prompt.push_str("4. Is concise but informative (typically 2-4 sentences)\n");
     prompt.push_str("5. Only mentions information that appears in the search results above\n\n");
@@ -239,6 +253,40 @@ fn build_codebase_context_prompt(question: &str, codebase_context: &str) -> Stri
     prompt
 }
 
+/// Collect the `path:line` citations in `answer` that point into `results`
+///
+/// References to files outside the results are dropped, since they can't
+/// come from the search. Each citation appears once, in order of first use.
+pub fn extract_citations(answer: &str, results: &[FileGroupedResult]) -> Vec<Citation> {
+    static CITATION: OnceLock<Regex> = OnceLock::new();
+    let pattern = CITATION.get_or_init(|| Regex::new(r"([\w./\-]+\.\w+):(\d+)").unwrap());
+
+    let mut citations: Vec<Citation> = Vec::new();
+    for capture in pattern.captures_iter(answer) {
+        let cited = capture[1].trim_start_matches("./");
+        let Ok(line) = capture[2].parse::<usize>() else {
+            continue;
+        };
+        let Some(group) = results.iter().find(|g| {
+            let path = g.path.trim_start_matches("./");
+            path == cited || path.ends_with(&format!("/{}", cited))
+        }) else {
+            continue;
+        };
+
+        let verified = group.matches.iter().take(MAX_MATCHES_IN_PROMPT).any(|m| {
+            let start = m.span.start_line.saturating_sub(m.context_before.len());
+            let end = m.span.end_line.max(m.span.start_line) + m.context_after.len();
+            (start..=end).contains(&line)
+        });
+        let citation = Citation { path: group.path.clone(), line, verified };
+        if !citations.contains(&citation) {
+            citations.push(citation);
+        }
+    }
+    citations
+}
+
 /// Strip markdown code fences from LLM response
 ///
 /// Some LLMs add markdown formatting even when instructed not to.
@@ -290,6 +338,46 @@ mod tests {
         assert_eq!(strip_markdown_fences(input), "This is the answer");
     }
 
+    #[test]
+    fn test_extract_citations() {
+        use crate::models::{MatchResult, Span, SymbolKind};
+
+        let results = vec![FileGroupedResult {
+            path: "./src/auth.rs".to_string(),
+            dependencies: None,
+            related_files: None,
+            transform: None,
+            owners: None,
+            repo: None,
+            matches: vec![MatchResult {
+                kind: SymbolKind::Function,
+                symbol: Some("login".to_string()),
+                parent: None,
+                qualified_name: None,
+                signature: None,
+                doc: None,
+                exported: None,
+                span: Span::new(10, 0, 14, 0),
+                preview: "fn login() {".to_string(),
+                context_before: vec![],
+                context_after: vec![],
+                match_reason: None,
+                cell: None,
+                captures: None,
+                blame: None,
+            }],
+        }];
+
+        let answer = "Login is handled in src/auth.rs:12 (see also src/auth.rs:40 and lib/other.rs:3). \
+                      Again: src/auth.rs:12.";
+        let citations = extract_citations(answer, &results);
+
+        assert_eq!(citations, vec![
+            Citation { path: "./src/auth.rs".to_string(), line: 12, verified: true },
+            Citation { path: "./src/auth.rs".to_string(), line: 40, verified: false },
+        ]);
+    }
+
     #[test]
     fn test_build_answer_prompt_empty_results() {
         let results: Vec<FileGroupedResult> = vec![];
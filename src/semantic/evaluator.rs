@@ -343,9 +343,18 @@ mod tests {
         FileGroupedResult {
             path: path.to_string(),
             dependencies: None,
+            repo: None,
+            related_files: None,
+            transform: None,
+            owners: None,
             matches: vec![MatchResult {
                 kind: crate::models::SymbolKind::Unknown("test".to_string()),
                 symbol: None,
+                parent: None,
+                qualified_name: None,
+                signature: None,
+                doc: None,
+                exported: None,
                 span: Span {
                     start_line: line,
                     end_line: line,
@@ -353,6 +362,10 @@ mod tests {
                 preview: "test preview".to_string(),
                 context_before: vec![],
                 context_after: vec![],
+                match_reason: None,
+                cell: None,
+                captures: None,
+                blame: None,
             }],
         }
     }
@@ -215,6 +215,11 @@ impl ParsedCommand {
                 "kotlin" | "kt" => Some(Language::Kotlin),
                 "swift" => Some(Language::Swift),
                 "zig" => Some(Language::Zig),
+                "shell" | "sh" | "bash" | "zsh" => Some(Language::Shell),
+                "make" | "makefile" => Some(Language::Make),
+                "cmake" => Some(Language::CMake),
+                "bazel" | "starlark" | "bzl" => Some(Language::Bazel),
+                "sql" => Some(Language::Sql),
                 _ => anyhow::bail!("Unknown language: {}", lang_str),
             }
         } else {
@@ -222,30 +227,8 @@ impl ParsedCommand {
         };
 
         // Parse symbol kind
-        let kind = if let Some(kind_str) = &self.kind {
-            // Capitalize first letter for parsing
-            let capitalized = {
-                let mut chars = kind_str.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => first.to_uppercase()
-                        .chain(chars.flat_map(|c| c.to_lowercase()))
-                        .collect()
-                }
-            };
-
-            let parsed_kind: SymbolKind = capitalized.parse()
-                .ok()
-                .or_else(|| {
-                    log::debug!("Treating '{}' as unknown symbol kind", kind_str);
-                    Some(SymbolKind::Unknown(kind_str.to_string()))
-                })
-                .context("Failed to parse symbol kind")?;
-
-            Some(parsed_kind)
-        } else {
-            None
-        };
+        // Comma-separated kinds (case-insensitive, unknown kinds filter as-is)
+        let kinds = self.kind.as_deref().map(SymbolKind::parse_list).unwrap_or_default();
 
         // Symbol mode is enabled if --symbols flag OR --kind is specified
         let symbols_mode = self.symbols || self.kind.is_some();
@@ -259,7 +242,7 @@ impl ParsedCommand {
 
         Ok(QueryFilter {
             language,
-            kind,
+            kinds,
             use_ast: self.use_ast,
             use_regex: self.use_regex,
             limit,
@@ -483,6 +466,6 @@ mod tests {
         let filter = parsed.to_query_filter().unwrap();
 
         assert!(filter.symbols_mode); // kind implies symbols mode
-        assert!(matches!(filter.kind, Some(SymbolKind::Function)));
+        assert_eq!(filter.kinds, vec![SymbolKind::Function]);
     }
 }
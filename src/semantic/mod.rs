@@ -21,6 +21,9 @@ pub mod reporter;
 pub mod chat_session;
 pub mod chat_tui;
 
+// Line-based chat mode (`rfx ask --chat`)
+pub mod repl;
+
 // Re-export main types for convenience
 pub use configure::run_configure_wizard;
 pub use executor::{execute_queries, parse_command, ParsedCommand};
@@ -29,6 +32,7 @@ pub use agentic::{run_agentic_loop, AgenticConfig};
 pub use reporter::{AgenticReporter, ConsoleReporter, QuietReporter};
 pub use answer::generate_answer;
 pub use chat_tui::run_chat_mode;
+pub use repl::{run_repl, ReplOptions};
 pub use config::{save_user_provider, is_any_api_key_configured};
 
 use anyhow::{Context, Result};
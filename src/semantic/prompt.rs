@@ -32,6 +32,39 @@ fn read_project_config(workspace_root: &std::path::Path) -> Option<String> {
     }
 }
 
+/// Number of past successful queries shown to the LLM
+const RECENT_QUERY_EXAMPLES: usize = 10;
+
+/// Recent `rfx query` commands that returned results in this codebase (from `rfx history`)
+///
+/// Returns an empty string when there is no index or no successful query yet.
+fn format_recent_queries(cache: &CacheManager) -> String {
+    let entries = match crate::history::QueryHistoryLog::new(cache.clone()).recent_successful(RECENT_QUERY_EXAMPLES * 3) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::debug!("No query history for prompt: {}", e);
+            return String::new();
+        }
+    };
+
+    let lines: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.args.first().map(String::as_str) == Some("query"))
+        .take(RECENT_QUERY_EXAMPLES)
+        .map(|entry| format!("- `{}` ({} results)", entry.command_line(), entry.total_results))
+        .collect();
+
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "\n## Recent Successful Queries\n\nThese queries recently found results in this codebase. \
+         Reuse their filters and naming when relevant:\n\n{}\n",
+        lines.join("\n")
+    )
+}
+
 /// Build the complete prompt for the LLM
 ///
 /// Extracts comprehensive codebase context and injects it into the prompt template
@@ -71,10 +104,11 @@ pub fn build_prompt(
             "No project-specific instructions provided.".to_string()
         });
 
-    // Format additional context
+    // Format additional context, followed by queries that worked here before
     let additional_context_str = additional_context
         .map(|ctx| format!("\n## Additional Context\n\n{}\n", ctx))
-        .unwrap_or_default();
+        .unwrap_or_default()
+        + &format_recent_queries(cache);
 
     // Inject context and project config into template
     let prompt = PROMPT_TEMPLATE
@@ -147,4 +181,32 @@ mod tests {
         assert!(prompt.contains("services/"));
         assert!(prompt.contains("backend/"));
     }
+
+    #[test]
+    fn test_prompt_includes_recent_successful_queries() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp_dir.path());
+        cache.init().unwrap();
+
+        let log = crate::history::QueryHistoryLog::new(cache.clone());
+        let entry = |args: &[&str], total_results: usize| crate::history::HistoryEntry {
+            id: 0,
+            pattern: args[1].to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            filters: Default::default(),
+            result_count: total_results,
+            total_results,
+            timing_ms: 1,
+            executed_at: 0,
+            directories: Vec::new(),
+        };
+        log.record(&entry(&["query", "parse_config", "--symbols"], 2), 100).unwrap();
+        log.record(&entry(&["query", "nothing_here"], 0), 100).unwrap();
+
+        let prompt = build_prompt("test", &cache, None).unwrap();
+
+        assert!(prompt.contains("Recent Successful Queries"));
+        assert!(prompt.contains("rfx query parse_config --symbols"));
+        assert!(!prompt.contains("nothing_here"));
+    }
 }
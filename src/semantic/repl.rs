@@ -0,0 +1,340 @@
+//! Line-based conversational mode for `rfx ask --chat`
+//!
+//! Each turn sends the question together with the conversation so far: earlier
+//! questions, the queries they produced and a short summary of what those
+//! queries found. Follow-ups like "only the ones in tests" or "now show the
+//! callers" can then refine the previous turn instead of starting over.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::io::{self, BufRead, Write};
+
+use crate::cache::CacheManager;
+use crate::models::FileGroupedResult;
+
+use super::chat_session::{ChatSession, MessageMetadata, MessageRole};
+use super::providers::{self, LlmProvider};
+use super::{config, context, schema::QueryCommand};
+
+/// Matches listed in the summary fed back into the next turn
+const SUMMARY_MATCHES: usize = 15;
+
+/// Matches printed per turn
+const DISPLAY_MATCHES: usize = 30;
+
+/// Messages kept verbatim when the conversation is compacted
+const KEEP_RECENT_MESSAGES: usize = 6;
+
+/// Longest preview kept in a result summary (characters)
+const SUMMARY_PREVIEW_LENGTH: usize = 100;
+
+/// Settings for a chat session
+#[derive(Debug, Clone, Default)]
+pub struct ReplOptions {
+    /// Override the configured provider
+    pub provider_override: Option<String>,
+    /// Extra context added to every turn (e.g. from `rfx context`)
+    pub additional_context: Option<String>,
+    /// Also answer each turn in prose
+    pub answer: bool,
+    /// Print the full prompts
+    pub debug: bool,
+}
+
+/// Run the chat loop until `/exit` or end of input
+pub fn run_repl(cache: &CacheManager, options: ReplOptions) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()
+        .context("Failed to create async runtime")?;
+
+    let (provider_name, provider) = create_provider(cache, options.provider_override.as_deref())?;
+    let mut session = ChatSession::new(provider_name.clone(), provider.default_model().to_string());
+    let codebase_context = context::CodebaseContext::extract(cache)
+        .ok()
+        .map(|ctx| ctx.to_prompt_string());
+
+    println!("{} {}", "rfx ask --chat".bold().cyan(), format!("({})", provider_name).dimmed());
+    println!("{}", "Ask a question; follow-ups build on earlier answers. /clear forgets the conversation, /exit quits.".dimmed());
+
+    let stdin = io::stdin();
+    loop {
+        print!("\n{} ", "ask>".bold().green());
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        let question = line.trim();
+
+        match question {
+            "" => continue,
+            "/exit" | "/quit" => break,
+            "/clear" => {
+                session.clear();
+                println!("{}", "Conversation cleared.".dimmed());
+                continue;
+            }
+            _ => {}
+        }
+
+        // Earlier turns go in as context; the question itself is the prompt
+        let turn_context = build_turn_context(&session, options.additional_context.as_deref());
+        session.add_user_message(question.to_string());
+
+        let turn = runtime.block_on(async {
+            let response = super::ask_question(
+                question,
+                cache,
+                Some(provider_name.clone()),
+                turn_context.clone(),
+                options.debug,
+            ).await?;
+            let (results, total, count_only) = super::execute_queries(response.queries.clone(), cache).await?;
+
+            let answer = if options.answer {
+                Some(super::generate_answer(
+                    question,
+                    &results,
+                    total,
+                    turn_context.as_deref(),
+                    codebase_context.as_deref(),
+                    &*provider,
+                ).await?)
+            } else {
+                None
+            };
+
+            anyhow::Ok((response.queries, results, total, count_only, answer))
+        });
+
+        let (queries, results, total, count_only, answer) = match turn {
+            Ok(turn) => turn,
+            Err(e) => {
+                eprintln!("{} {:#}", "Error:".red().bold(), e);
+                session.add_system_message(format!("The previous question failed: {}", e));
+                continue;
+            }
+        };
+
+        print_turn(&queries, &results, total, count_only, answer.as_deref());
+        record_turn(&mut session, &queries, &results, total, answer);
+
+        if session.should_compact() {
+            compact(&mut session);
+        }
+    }
+
+    Ok(())
+}
+
+fn create_provider(cache: &CacheManager, provider_override: Option<&str>) -> Result<(String, Box<dyn LlmProvider>)> {
+    let mut config = config::load_config(cache.path())?;
+    if let Some(provider) = provider_override {
+        config.provider = provider.to_string();
+    }
+    let api_key = config::get_api_key(&config.provider)?;
+    let model = config.model.clone().or_else(|| config::get_user_model(&config.provider));
+    let provider = providers::create_provider(&config.provider, api_key, model)?;
+    Ok((config.provider, provider))
+}
+
+/// Conversation so far plus any fixed extra context, or None on the first turn without extras
+fn build_turn_context(session: &ChatSession, additional_context: Option<&str>) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(extra) = additional_context.filter(|c| !c.is_empty()) {
+        parts.push(extra.to_string());
+    }
+    if !session.messages().is_empty() {
+        parts.push(format!(
+            "{}\nThe new question may refer to the turns above; refine or build on their queries where it does.",
+            session.build_context()
+        ));
+    }
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// Record a finished turn so the next question can build on it
+fn record_turn(
+    session: &mut ChatSession,
+    queries: &[QueryCommand],
+    results: &[FileGroupedResult],
+    total: usize,
+    answer: Option<String>,
+) {
+    let commands: Vec<String> = queries.iter().map(|q| q.command.clone()).collect();
+    let content = format!("Ran queries:\n{}", commands.iter().map(|c| format!("  rfx {}", c)).collect::<Vec<_>>().join("\n"));
+    let metadata = MessageMetadata {
+        queries: commands,
+        tool_calls: Vec::new(),
+        results_count: 0,
+        execution_time_ms: None,
+        needs_context: false,
+    };
+    session.add_assistant_message(content, MessageRole::AssistantQueries, Some(metadata));
+
+    let metadata = MessageMetadata {
+        queries: Vec::new(),
+        tool_calls: Vec::new(),
+        results_count: total,
+        execution_time_ms: None,
+        needs_context: false,
+    };
+    session.add_assistant_message(summarize_results(results, total), MessageRole::AssistantExecuting, Some(metadata));
+
+    if let Some(answer) = answer {
+        session.add_answer_message(answer);
+    }
+}
+
+/// Short text summary of a turn's results: counts plus the first few matches as `path:line`
+fn summarize_results(results: &[FileGroupedResult], total: usize) -> String {
+    if results.is_empty() {
+        return format!("Found {} results.", total);
+    }
+
+    let mut summary = format!("Found {} results across {} files:\n", total, results.len());
+    let matches = results
+        .iter()
+        .flat_map(|group| group.matches.iter().map(move |m| (group.path.as_str(), m)));
+    let mut shown = 0;
+    for (path, m) in matches.take(SUMMARY_MATCHES) {
+        let preview: String = m.preview.lines().next().unwrap_or("").trim().chars().take(SUMMARY_PREVIEW_LENGTH).collect();
+        summary.push_str(&format!("- {}:{} {}\n", path, m.span.start_line, preview));
+        shown += 1;
+    }
+    if total > shown {
+        summary.push_str(&format!("- ... {} more\n", total - shown));
+    }
+    summary
+}
+
+/// Drop the oldest turns, keeping their questions and result summaries as one note
+fn compact(session: &mut ChatSession) {
+    let (old, remove_count, _) = session.prepare_compaction(KEEP_RECENT_MESSAGES);
+    if remove_count == 0 || remove_count >= session.messages().len() {
+        return;
+    }
+    let note: String = old.chars().take(4_000).collect();
+    session.apply_compaction(remove_count, format!("Earlier conversation (truncated):\n{}", note));
+    println!("{}", "(Older turns were compacted to stay within the context window.)".dimmed());
+}
+
+fn print_turn(
+    queries: &[QueryCommand],
+    results: &[FileGroupedResult],
+    total: usize,
+    count_only: bool,
+    answer: Option<&str>,
+) {
+    for query in queries {
+        println!("{} {}", "rfx".bright_green().bold(), query.command.dimmed());
+    }
+    println!();
+
+    if let Some(answer) = answer {
+        termimad::print_text(answer);
+        println!();
+        println!("{}", format!("(Based on {} matches across {} files)", total, results.len()).dimmed());
+        return;
+    }
+
+    if count_only {
+        println!("{} {}", "Found".bright_green().bold(), format!("{} results", total).bright_white().bold());
+        return;
+    }
+    if results.is_empty() {
+        println!("{}", "No results found.".yellow());
+        return;
+    }
+
+    println!(
+        "{} {} {} {} {}",
+        "Found".bright_green().bold(),
+        total.to_string().bright_white().bold(),
+        "total results across".dimmed(),
+        results.len().to_string().bright_white().bold(),
+        "files:".dimmed()
+    );
+    let mut shown = 0;
+    for group in results {
+        if shown >= DISPLAY_MATCHES {
+            break;
+        }
+        println!("{}:", group.path.bright_cyan().bold());
+        for m in group.matches.iter().take(DISPLAY_MATCHES - shown) {
+            println!(
+                "  {} {}: {}",
+                "Line".dimmed(),
+                m.span.start_line.to_string().bright_yellow(),
+                m.preview.lines().next().unwrap_or("")
+            );
+            shown += 1;
+        }
+    }
+    if total > shown {
+        println!("{}", format!("... {} more", total - shown).dimmed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MatchResult, Span, SymbolKind};
+
+    fn group(path: &str, lines: &[usize]) -> FileGroupedResult {
+        FileGroupedResult {
+            path: path.to_string(),
+            dependencies: None,
+            related_files: None,
+            transform: None,
+            owners: None,
+            repo: None,
+            matches: lines
+                .iter()
+                .map(|line| MatchResult {
+                    kind: SymbolKind::Function,
+                    symbol: None,
+                    parent: None,
+                    qualified_name: None,
+                    signature: None,
+                    doc: None,
+                    exported: None,
+                    span: Span::new(*line, 0, *line, 0),
+                    preview: format!("    fn item_{}() {{", line),
+                    context_before: Vec::new(),
+                    context_after: Vec::new(),
+                    match_reason: None,
+                    cell: None,
+                    captures: None,
+                    blame: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_results() {
+        let results = vec![group("src/a.rs", &[3, 9]), group("src/b.rs", &[1])];
+        let summary = summarize_results(&results, 40);
+
+        assert!(summary.starts_with("Found 40 results across 2 files:"));
+        assert!(summary.contains("- src/a.rs:9 fn item_9() {"));
+        assert!(summary.contains("- ... 37 more"));
+        assert_eq!(summarize_results(&[], 0), "Found 0 results.");
+    }
+
+    #[test]
+    fn test_turn_context_includes_previous_turns() {
+        let mut session = ChatSession::new("openai".to_string(), "gpt-4o-mini".to_string());
+        assert_eq!(build_turn_context(&session, None), None);
+
+        session.add_user_message("where is the config loaded?".to_string());
+        record_turn(&mut session, &[], &[group("src/config.rs", &[12])], 1, None);
+
+        let context = build_turn_context(&session, Some("Project: demo")).unwrap();
+        assert!(context.starts_with("Project: demo"));
+        assert!(context.contains("User: where is the config loaded?"));
+        assert!(context.contains("src/config.rs:12"));
+    }
+}
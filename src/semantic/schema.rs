@@ -36,6 +36,10 @@ pub struct AgenticQueryResponse {
     /// Conversational answer synthesized from results (only when --answer is used)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub answer: Option<String>,
+
+    /// `path:line` references in the answer that point into the results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<super::answer::Citation>,
 }
 
 /// A single rfx query command with execution metadata
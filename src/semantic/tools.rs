@@ -76,6 +76,7 @@ fn execute_gather_context(
         entry_points: params.entry_points,
         test_layout: params.test_layout,
         config_files: params.config_files,
+        conventions: false,
         depth: params.depth,
         json: false, // Always use text format for LLM consumption
     };
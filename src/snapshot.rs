@@ -0,0 +1,692 @@
+//! Index snapshots: pack `.reflex/` into one archive and restore it elsewhere
+//!
+//! Large repositories can be indexed once (e.g. in CI) and the cache shipped
+//! to developers instead of re-indexing locally:
+//!
+//! ```text
+//! rfx snapshot save reflex.rfxsnap    # in CI, after `rfx index`
+//! rfx snapshot load reflex.rfxsnap    # locally, in a checkout of the same commit
+//! ```
+//!
+//! Archive layout:
+//! - 8-byte magic `RFXSNAP\0` and a little-endian u32 archive format version
+//! - a zstd stream holding a little-endian u32 manifest length, the JSON
+//!   [`SnapshotManifest`], then the contents of every listed file in order
+//!
+//! Loading verifies the archive version, the cache schema hash, the platform
+//! endianness (binary index files are written in native byte order) and the
+//! blake3 hash of every file. The commit the index was built at must match the
+//! checkout's HEAD unless forced. The local `config.toml` is kept (archives
+//! never carry one) and query history is stripped from the archived meta.db. Files are unpacked next to the cache and only
+//! swapped in once everything checks out; the old index is kept until the swap
+//! has succeeded.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::cache::{CacheManager, CACHE_DIR, CONFIG_TOML, META_DB};
+use crate::error_code::ReflexError;
+
+/// Magic bytes at the start of every snapshot archive
+const MAGIC: &[u8; 8] = b"RFXSNAP\0";
+
+/// Archive format version (bump when the layout above changes)
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// zstd compression level for archives
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Largest manifest accepted when loading (a corrupt length must not drive an allocation)
+const MAX_MANIFEST_LEN: u32 = 64 * 1024 * 1024;
+
+/// Entries of `.reflex/` that are machine-local or transient and never archived
+///
+/// `config.toml` is local: it can hold commands (`[[transforms.rules]]`) that
+/// `rfx index` runs, so it must never come from an archive.
+const EXCLUDED: &[&str] = &[
+    crate::cache::CONFIG_TOML,
+    crate::overlay::OVERLAY_DIR,
+    crate::revision::REVS_DIR,
+    "trigram_temp",
+    "indexing.lock",
+    "indexing.status",
+    ".space_check",
+];
+
+/// Description of a snapshot archive, stored ahead of the file contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Archive format version
+    pub format_version: u32,
+    /// Reflex version that wrote the archive
+    pub reflex_version: String,
+    /// Cache schema hash of the binary that built the index
+    pub schema_hash: String,
+    /// Byte order of the platform that built the index ("little" or "big")
+    pub endianness: String,
+    /// Git branch the index was built on (None outside git repositories)
+    pub branch: Option<String>,
+    /// Commit the index was built at (None outside git repositories)
+    pub commit: Option<String>,
+    /// Unix timestamp of archive creation
+    pub created_at: i64,
+    /// Archived files, in the order their contents follow the manifest
+    pub files: Vec<SnapshotEntry>,
+}
+
+/// A single file inside the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path relative to `.reflex/`, with `/` separators
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// blake3 hash of the contents (hex)
+    pub hash: String,
+}
+
+impl SnapshotManifest {
+    /// Total uncompressed size of the archived files
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+}
+
+/// Byte order of the running platform
+fn native_endianness() -> &'static str {
+    if cfg!(target_endian = "little") { "little" } else { "big" }
+}
+
+/// Pack the cache into a snapshot archive at `output`
+pub fn save(cache: &CacheManager, output: &Path) -> Result<SnapshotManifest> {
+    if !cache.exists() {
+        return Err(ReflexError::IndexMissing(format!(
+            "No index found in {}.\n\
+             \n\
+             Run 'rfx index' to build the code search index first.",
+            cache.workspace_root().display()
+        )).into());
+    }
+
+    // Flush the WAL so meta.db is self-contained
+    cache.checkpoint_wal()?;
+
+    // Archive a copy of meta.db without the local query history
+    let scratch = scratch_dir(cache);
+    if scratch.exists() {
+        std::fs::remove_dir_all(&scratch)?;
+    }
+    std::fs::create_dir_all(&scratch)?;
+    let written = scrub_meta_db(cache, &scratch.join(META_DB))
+        .and_then(|scrubbed| write_archive(cache, output, &scrubbed));
+    let _ = std::fs::remove_dir_all(&scratch);
+    let manifest = written?;
+
+    log::info!(
+        "Saved snapshot of {} files ({} bytes) to {}",
+        manifest.files.len(),
+        manifest.total_size(),
+        output.display()
+    );
+    Ok(manifest)
+}
+
+/// Copy meta.db to `target` with the query history dropped
+fn scrub_meta_db(cache: &CacheManager, target: &Path) -> Result<PathBuf> {
+    let conn = rusqlite::Connection::open(cache.path().join(META_DB))?;
+    conn.execute("VACUUM INTO ?1", [target.to_string_lossy()])
+        .context("Failed to copy meta.db for the snapshot")?;
+    drop(conn);
+
+    // The table is recreated on demand (see `history::ensure_schema`)
+    let copy = rusqlite::Connection::open(target)?;
+    copy.execute_batch("DROP TABLE IF EXISTS query_history; VACUUM;")?;
+    Ok(target.to_path_buf())
+}
+
+/// Write the archive, reading meta.db from `meta_db` instead of the cache
+fn write_archive(cache: &CacheManager, output: &Path, meta_db: &Path) -> Result<SnapshotManifest> {
+    let source_of = |path: &str| {
+        if path == META_DB { meta_db.to_path_buf() } else { cache.path().join(path) }
+    };
+
+    let (branch, commit) = indexed_commit(cache);
+    let mut files = collect_entries(cache.path())?;
+    for entry in files.iter_mut().filter(|e| e.path == META_DB) {
+        let mut hasher = blake3::Hasher::new();
+        entry.size = std::io::copy(&mut File::open(meta_db)?, &mut hasher)?;
+        entry.hash = hasher.finalize().to_hex().to_string();
+    }
+
+    let manifest = SnapshotManifest {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        reflex_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_hash: env!("CACHE_SCHEMA_HASH").to_string(),
+        endianness: native_endianness().to_string(),
+        branch,
+        commit,
+        created_at: chrono::Utc::now().timestamp(),
+        files,
+    };
+
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create snapshot {}", output.display()))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_le_bytes())?;
+
+    let mut encoder = zstd::stream::write::Encoder::new(writer, COMPRESSION_LEVEL)?;
+    let manifest_json = serde_json::to_vec(&manifest)?;
+    encoder.write_all(&(manifest_json.len() as u32).to_le_bytes())?;
+    encoder.write_all(&manifest_json)?;
+
+    for entry in &manifest.files {
+        let path = source_of(&entry.path);
+        let mut source = File::open(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let copied = std::io::copy(&mut source, &mut encoder)?;
+        if copied != entry.size {
+            anyhow::bail!(
+                "{} changed while the snapshot was written.\n\
+                 \n\
+                 Wait for indexing to finish and run 'rfx snapshot save' again.",
+                entry.path
+            );
+        }
+    }
+
+    encoder.finish()?.flush()?;
+    Ok(manifest)
+}
+
+/// Restore a snapshot archive into the cache, replacing any existing index
+///
+/// With `force`, a commit mismatch between the snapshot and the checkout is
+/// accepted (the index is then stale until the next `rfx index`). Format,
+/// schema and endianness mismatches are never accepted.
+pub fn load(cache: &CacheManager, archive: &Path, force: bool) -> Result<SnapshotManifest> {
+    let (manifest, mut decoder) = open_archive(archive)?;
+
+    if !force {
+        check_commit(&manifest, &cache.workspace_root())?;
+    }
+
+    // Unpack next to the cache so the final swap is a rename on the same filesystem
+    let staging = staging_dir(cache);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::create_dir_all(&staging)?;
+
+    let unpacked = unpack_entries(&manifest, &mut decoder, &staging)
+        .and_then(|()| keep_local_config(cache, &staging));
+    if let Err(e) = unpacked {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    // Move the old index aside, swap the restored one in, and only then drop the old one
+    let backup = backup_dir(cache);
+    if backup.exists() {
+        std::fs::remove_dir_all(&backup)?;
+    }
+    let had_index = cache.path().exists();
+    if had_index
+        && let Err(e) = std::fs::rename(cache.path(), &backup)
+    {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e).with_context(|| format!("Failed to move existing index at {} aside", cache.path().display()));
+    }
+    if let Err(e) = std::fs::rename(&staging, cache.path()) {
+        if had_index {
+            let _ = std::fs::rename(&backup, cache.path());
+        }
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e).with_context(|| format!("Failed to move restored index into {}", cache.path().display()));
+    }
+    if had_index
+        && let Err(e) = std::fs::remove_dir_all(&backup)
+    {
+        log::warn!("Failed to remove previous index at {}: {}", backup.display(), e);
+    }
+
+    // Without a local config there is none to keep: write the defaults
+    cache.init()?;
+
+    log::info!(
+        "Loaded snapshot of {} files ({} bytes) from {}",
+        manifest.files.len(),
+        manifest.total_size(),
+        archive.display()
+    );
+    Ok(manifest)
+}
+
+type Decoder = zstd::stream::read::Decoder<'static, BufReader<BufReader<File>>>;
+
+/// Open an archive, validate its header and manifest, and return the stream positioned at the file contents
+fn open_archive(archive: &Path) -> Result<(SnapshotManifest, Decoder)> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open snapshot {}", archive.display()))?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    let mut version = [0u8; 4];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC || reader.read_exact(&mut version).is_err() {
+        anyhow::bail!(
+            "{} is not a Reflex snapshot.\n\
+             \n\
+             Create one with 'rfx snapshot save <FILE>'.",
+            archive.display()
+        );
+    }
+
+    let version = u32::from_le_bytes(version);
+    if version != SNAPSHOT_FORMAT_VERSION {
+        anyhow::bail!(
+            "Unsupported snapshot format version {} (this binary reads version {}).\n\
+             \n\
+             Save the snapshot with the same Reflex version that loads it,\n\
+             or rebuild the index locally with 'rfx index'.",
+            version,
+            SNAPSHOT_FORMAT_VERSION
+        );
+    }
+
+    let mut decoder = zstd::stream::read::Decoder::new(reader)?;
+    let mut len = [0u8; 4];
+    decoder.read_exact(&mut len).context("Snapshot is truncated (missing manifest)")?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_MANIFEST_LEN {
+        anyhow::bail!(
+            "Snapshot manifest is corrupted (length {} exceeds {} bytes)",
+            len,
+            MAX_MANIFEST_LEN
+        );
+    }
+    // Grow the buffer as bytes arrive instead of trusting the length up front
+    let mut manifest_json = Vec::new();
+    decoder.by_ref().take(len as u64).read_to_end(&mut manifest_json)
+        .context("Snapshot is truncated (incomplete manifest)")?;
+    if manifest_json.len() != len as usize {
+        anyhow::bail!("Snapshot is truncated (incomplete manifest)");
+    }
+    let manifest: SnapshotManifest = serde_json::from_slice(&manifest_json)
+        .context("Snapshot manifest is corrupted")?;
+
+    validate_manifest(&manifest)?;
+    Ok((manifest, decoder))
+}
+
+/// Reject manifests this binary cannot use
+fn validate_manifest(manifest: &SnapshotManifest) -> Result<()> {
+    if manifest.endianness != native_endianness() {
+        anyhow::bail!(
+            "Snapshot was built on a {}-endian platform, but this platform is {}-endian.\n\
+             \n\
+             Index files are stored in native byte order and cannot be shared\n\
+             across platforms with different endianness. Run 'rfx index' instead.",
+            manifest.endianness,
+            native_endianness()
+        );
+    }
+
+    let current_schema_hash = env!("CACHE_SCHEMA_HASH");
+    if manifest.schema_hash != current_schema_hash {
+        anyhow::bail!(
+            "Snapshot cache schema does not match this binary.\n\
+             \n\
+             - Snapshot was built with Reflex {} (schema {})\n\
+             - Current binary expects schema {}\n\
+             \n\
+             Use the same Reflex version in CI and locally, or run 'rfx index'.",
+            manifest.reflex_version,
+            manifest.schema_hash,
+            current_schema_hash
+        );
+    }
+
+    // Commits are shown (and sliced) in messages; anything but a hex SHA is corrupt
+    if let Some(commit) = manifest.commit.as_deref()
+        && (commit.is_empty() || !commit.bytes().all(|b| b.is_ascii_hexdigit()))
+    {
+        anyhow::bail!("Snapshot manifest has an invalid commit: {:?}", commit);
+    }
+
+    for entry in &manifest.files {
+        if !is_safe_entry_path(&entry.path) {
+            anyhow::bail!("Snapshot contains an invalid path: {}", entry.path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Require the snapshot's commit to match the checkout's HEAD
+fn check_commit(manifest: &SnapshotManifest, workspace_root: &Path) -> Result<()> {
+    let Some(snapshot_commit) = manifest.commit.as_deref() else {
+        return Ok(());
+    };
+    let Some(state) = crate::git::get_git_state_optional(workspace_root)? else {
+        return Ok(());
+    };
+
+    if state.commit != snapshot_commit {
+        anyhow::bail!(
+            "Snapshot was built at commit {} but HEAD is {}.\n\
+             \n\
+             Check out the snapshot's commit, or load it anyway and catch up incrementally:\n\
+             \n\
+               rfx snapshot load <FILE> --force\n\
+               rfx index",
+            short_sha(snapshot_commit),
+            short_sha(&state.commit)
+        );
+    }
+
+    Ok(())
+}
+
+/// Carry the local `config.toml` (if any) into `staging`
+fn keep_local_config(cache: &CacheManager, staging: &Path) -> Result<()> {
+    let local = cache.path().join(CONFIG_TOML);
+    if local.exists() {
+        std::fs::copy(&local, staging.join(CONFIG_TOML))
+            .context("Failed to keep the local config.toml")?;
+    }
+    Ok(())
+}
+
+/// Write every archived file into `staging`, verifying sizes and hashes
+///
+/// Local-only entries (see [`EXCLUDED`]) are verified but not written, so an
+/// archive can't replace `config.toml`.
+fn unpack_entries(manifest: &SnapshotManifest, decoder: &mut impl Read, staging: &Path) -> Result<()> {
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    for entry in &manifest.files {
+        let mut writer: Box<dyn Write> = if is_local_entry(&entry.path) {
+            log::warn!("Ignoring local-only file {} in snapshot", entry.path);
+            Box::new(std::io::sink())
+        } else {
+            let target = staging.join(&entry.path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Box::new(BufWriter::new(File::create(&target)
+                .with_context(|| format!("Failed to create {}", target.display()))?))
+        };
+
+        let mut hasher = blake3::Hasher::new();
+        let mut remaining = entry.size;
+        while remaining > 0 {
+            let chunk = remaining.min(buffer.len() as u64) as usize;
+            decoder.read_exact(&mut buffer[..chunk])
+                .with_context(|| format!("Snapshot is truncated (in {})", entry.path))?;
+            hasher.update(&buffer[..chunk]);
+            writer.write_all(&buffer[..chunk])?;
+            remaining -= chunk as u64;
+        }
+        writer.flush()?;
+
+        if hasher.finalize().to_hex().as_str() != entry.hash {
+            anyhow::bail!("Snapshot is corrupted: checksum mismatch for {}", entry.path);
+        }
+    }
+
+    Ok(())
+}
+
+/// List every archivable file under the cache directory, sorted by path
+fn collect_entries(cache_path: &Path) -> Result<Vec<SnapshotEntry>> {
+    let mut entries = Vec::new();
+
+    let walker = walkdir::WalkDir::new(cache_path)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !is_excluded(e.path(), cache_path));
+
+    for dir_entry in walker {
+        let dir_entry = dir_entry?;
+        if !dir_entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative = dir_entry.path().strip_prefix(cache_path)?;
+        let path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let mut hasher = blake3::Hasher::new();
+        let mut file = File::open(dir_entry.path())?;
+        let size = std::io::copy(&mut file, &mut hasher)?;
+
+        entries.push(SnapshotEntry {
+            path,
+            size,
+            hash: hasher.finalize().to_hex().to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Whether a cache entry is transient state that stays out of snapshots
+fn is_excluded(path: &Path, cache_path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(cache_path) else {
+        return false;
+    };
+    is_local_entry(&relative.to_string_lossy())
+}
+
+/// Whether an archive path (relative to the cache) is local-only state
+fn is_local_entry(path: &str) -> bool {
+    let top = path.split(['/', '\\']).next().unwrap_or(path);
+
+    // SQLite side files are folded into meta.db by the WAL checkpoint
+    EXCLUDED.contains(&top)
+        || path.ends_with("-wal")
+        || path.ends_with("-shm")
+        || path.ends_with("-journal")
+}
+
+/// Archive paths must stay inside the cache directory
+fn is_safe_entry_path(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Branch and commit the index was last built at
+fn indexed_commit(cache: &CacheManager) -> (Option<String>, Option<String>) {
+    let Ok(Some(state)) = crate::git::get_git_state_optional(cache.workspace_root()) else {
+        return (None, None);
+    };
+
+    let commit = cache
+        .get_branch_info(&state.branch)
+        .ok()
+        .map(|info| info.commit_sha)
+        .filter(|sha| !sha.is_empty());
+    (Some(state.branch), commit)
+}
+
+/// Temporary directory a snapshot is unpacked into before replacing the cache
+fn staging_dir(cache: &CacheManager) -> PathBuf {
+    cache.workspace_root().join(format!("{}.snapshot-tmp", CACHE_DIR))
+}
+
+/// Temporary directory for the scrubbed meta.db copy while saving
+fn scratch_dir(cache: &CacheManager) -> PathBuf {
+    cache.workspace_root().join(format!("{}.snapshot-save", CACHE_DIR))
+}
+
+/// Where the existing cache is moved while a restored snapshot is swapped in
+fn backup_dir(cache: &CacheManager) -> PathBuf {
+    cache.workspace_root().join(format!("{}.snapshot-old", CACHE_DIR))
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_project(temp: &TempDir, name: &str) -> PathBuf {
+        let project = temp.path().join(name);
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("main.rs"), "fn greet() {}\n").unwrap();
+
+        let indexer = Indexer::new(CacheManager::new(&project), IndexConfig::default());
+        indexer.index(&project, false).unwrap();
+        project
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let source = indexed_project(&temp, "ci");
+        let archive = temp.path().join("index.rfxsnap");
+
+        // Query history stays local
+        let source_cache = CacheManager::new(&source);
+        let conn = rusqlite::Connection::open(source_cache.path().join(META_DB)).unwrap();
+        crate::history::ensure_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO query_history (pattern, args_json, filters_json, result_count, total_results, timing_ms, executed_at)
+             VALUES ('secret', '[]', '{}', 0, 0, 0, 0)",
+            [],
+        ).unwrap();
+        drop(conn);
+
+        let saved = save(&source_cache, &archive).unwrap();
+        assert!(saved.files.iter().any(|f| f.path == "meta.db"));
+        assert!(saved.files.iter().all(|f| !f.path.starts_with("overlay")));
+        assert!(saved.files.iter().all(|f| f.path != CONFIG_TOML));
+        assert!(!scratch_dir(&source_cache).exists());
+
+        let target = temp.path().join("local");
+        fs::create_dir(&target).unwrap();
+        let cache = CacheManager::new(&target);
+        let loaded = load(&cache, &archive, false).unwrap();
+
+        assert_eq!(loaded.files.len(), saved.files.len());
+        assert!(cache.exists());
+        assert!(!staging_dir(&cache).exists());
+        for entry in &saved.files {
+            let restored = fs::read(cache.path().join(&entry.path)).unwrap();
+            assert_eq!(blake3::hash(&restored).to_hex().as_str(), entry.hash);
+        }
+        assert!(cache.path().join(CONFIG_TOML).exists());
+        let conn = rusqlite::Connection::open(cache.path().join(META_DB)).unwrap();
+        let history_tables: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = 'query_history'", [], |row| row.get(0),
+        ).unwrap();
+        assert_eq!(history_tables, 0);
+        drop(conn);
+
+        // The local config survives a load
+        fs::write(cache.path().join(CONFIG_TOML), "# local settings\n").unwrap();
+        load(&cache, &archive, false).unwrap();
+        assert_eq!(fs::read_to_string(cache.path().join(CONFIG_TOML)).unwrap(), "# local settings\n");
+
+        // Loading over an existing index replaces it and cleans up the old one
+        fs::write(cache.path().join("stale.txt"), "old").unwrap();
+        load(&cache, &archive, false).unwrap();
+        assert!(!cache.path().join("stale.txt").exists());
+        assert!(cache.path().join("meta.db").exists());
+        assert!(!backup_dir(&cache).exists());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_archives() {
+        let temp = TempDir::new().unwrap();
+        let source = indexed_project(&temp, "ci");
+        let archive = temp.path().join("index.rfxsnap");
+        save(&CacheManager::new(&source), &archive).unwrap();
+
+        let target = temp.path().join("local");
+        fs::create_dir(&target).unwrap();
+        let cache = CacheManager::new(&target);
+
+        // Not a snapshot at all
+        let bogus = temp.path().join("bogus.rfxsnap");
+        fs::write(&bogus, b"not a snapshot").unwrap();
+        assert!(load(&cache, &bogus, false).unwrap_err().to_string().contains("not a Reflex snapshot"));
+
+        // Unknown format version
+        let mut bytes = fs::read(&archive).unwrap();
+        bytes[8..12].copy_from_slice(&(SNAPSHOT_FORMAT_VERSION + 1).to_le_bytes());
+        let future = temp.path().join("future.rfxsnap");
+        fs::write(&future, &bytes).unwrap();
+        assert!(load(&cache, &future, false).unwrap_err().to_string().contains("format version"));
+
+        // Truncated contents leave no partial index behind
+        let bytes = fs::read(&archive).unwrap();
+        let truncated = temp.path().join("truncated.rfxsnap");
+        fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(load(&cache, &truncated, false).is_err());
+        assert!(!cache.exists());
+        assert!(!staging_dir(&cache).exists());
+
+        // A manifest length beyond the cap is rejected before allocating
+        let mut huge = Vec::new();
+        huge.extend_from_slice(MAGIC);
+        huge.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+        huge.extend(zstd::encode_all(&u32::MAX.to_le_bytes()[..], COMPRESSION_LEVEL).unwrap());
+        let huge_path = temp.path().join("huge.rfxsnap");
+        fs::write(&huge_path, &huge).unwrap();
+        assert!(load(&cache, &huge_path, false).unwrap_err().to_string().contains("exceeds"));
+
+        // A failed load keeps the existing index
+        load(&cache, &archive, false).unwrap();
+        assert!(load(&cache, &truncated, false).is_err());
+        assert!(cache.path().join("meta.db").exists());
+        assert!(!backup_dir(&cache).exists());
+    }
+
+    #[test]
+    fn test_validate_manifest() {
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            reflex_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_hash: env!("CACHE_SCHEMA_HASH").to_string(),
+            endianness: native_endianness().to_string(),
+            branch: None,
+            commit: None,
+            created_at: 0,
+            files: vec![SnapshotEntry { path: "meta.db".to_string(), size: 0, hash: String::new() }],
+        };
+        assert!(validate_manifest(&manifest).is_ok());
+
+        let foreign = SnapshotManifest {
+            endianness: if native_endianness() == "little" { "big" } else { "little" }.to_string(),
+            ..manifest.clone()
+        };
+        assert!(validate_manifest(&foreign).unwrap_err().to_string().contains("endian"));
+
+        let old_schema = SnapshotManifest { schema_hash: "0000".to_string(), ..manifest.clone() };
+        assert!(validate_manifest(&old_schema).is_err());
+
+        let bad_commit = SnapshotManifest { commit: Some("abcdef012345é".to_string()), ..manifest.clone() };
+        assert!(validate_manifest(&bad_commit).unwrap_err().to_string().contains("invalid commit"));
+        let good_commit = SnapshotManifest { commit: Some("0123456789abcdef".to_string()), ..manifest.clone() };
+        assert!(validate_manifest(&good_commit).is_ok());
+
+        let escaping = SnapshotManifest {
+            files: vec![SnapshotEntry { path: "../evil".to_string(), size: 0, hash: String::new() }],
+            ..manifest
+        };
+        assert!(validate_manifest(&escaping).unwrap_err().to_string().contains("invalid path"));
+    }
+}
@@ -0,0 +1,367 @@
+//! Structural pattern matching with metavariables
+//!
+//! `rfx query --pattern` takes a snippet of code instead of a Tree-sitter
+//! S-expression, in the style of comby and ast-grep:
+//!
+//! ```bash
+//! rfx query 'if ($COND) { return $X; }' --pattern --lang typescript
+//! rfx query 'foo($A, $$$REST)' --pattern --lang rust
+//! ```
+//!
+//! - `$NAME` (uppercase) matches any single node and is exposed as a capture of
+//!   that name; using it twice (`$A == $A`) requires both nodes to have the same text
+//! - `$_` matches any single node without capturing it
+//! - `$$$` / `$$$NAME` match any number of sibling nodes (not captured)
+//!
+//! # Compilation
+//!
+//! Metavariables are swapped for placeholder identifiers and the snippet is
+//! parsed with the language's grammar, first on its own and then inside the
+//! per-language wrappers from [`contexts`] (statements need a function body in
+//! Rust, Go, Java, ...). The smallest node spanning the snippet becomes a query:
+//! named nodes keep their kind and field names, children are anchored so the
+//! number of arguments/statements has to line up, leaves must have the same
+//! text, and keywords and operators (`async`, `+`, `==`) must be present.
+//! Whitespace and punctuation are ignored. Comments are not: Tree-sitter anchors
+//! count them as siblings, so a comment among the children of a matched node
+//! (e.g. at the top of a block) prevents the match.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use tree_sitter::{Node, Parser};
+
+use crate::models::Language;
+
+/// Prefix of captures used internally by compiled patterns (left out of results)
+pub const INTERNAL_CAPTURE_PREFIX: &str = "__";
+
+/// Prefix of the placeholder identifiers metavariables are parsed as
+const PLACEHOLDER_PREFIX: &str = "__rfx_mv";
+
+/// A metavariable of the pattern
+#[derive(Debug, Clone)]
+struct Metavariable {
+    /// Capture name (None for `$_` and anonymous `$$$`)
+    name: Option<String>,
+    /// `$$$`: any number of nodes
+    multi: bool,
+}
+
+/// Source wrappers (prefix, suffix) a pattern is parsed in, after parsing it on its own
+///
+/// Statement-level patterns are only valid inside a function body in most
+/// languages; declarations inside a class in Java and C#.
+fn contexts(language: Language) -> &'static [(&'static str, &'static str)] {
+    match language {
+        Language::Rust => &[("fn __rfx_wrapper() {\n", "\n}")],
+        Language::Go => &[("package main\n", ""), ("package main\nfunc __rfx_wrapper() {\n", "\n}")],
+        Language::Java => &[
+            ("class RfxWrapper {\nvoid rfxWrapper() {\n", "\n}\n}"),
+            ("class RfxWrapper {\n", "\n}"),
+        ],
+        Language::CSharp => &[
+            ("class RfxWrapper {\nvoid RfxWrapper() {\n", "\n}\n}"),
+            ("class RfxWrapper {\n", "\n}"),
+        ],
+        Language::C | Language::Cpp => &[("void __rfx_wrapper() {\n", "\n}")],
+        Language::PHP => &[("<?php\n", ""), ("<?php\nfunction __rfx_wrapper() {\n", "\n}")],
+        Language::Kotlin => &[("fun rfxWrapper() {\n", "\n}")],
+        Language::Zig => &[("fn rfxWrapper() void {\n", "\n}")],
+        Language::JavaScript | Language::TypeScript => &[("function __rfx_wrapper() {\n", "\n}")],
+        Language::Ruby => &[("def __rfx_wrapper\n", "\nend")],
+        _ => &[],
+    }
+}
+
+/// Compile a source-like pattern with metavariables into a Tree-sitter query
+///
+/// The query captures the matched node as `@__match`, each `$NAME` as `@NAME`,
+/// and uses `@__`-prefixed captures for its text predicates.
+pub fn compile(pattern: &str, language: Language) -> Result<String> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        anyhow::bail!("Structural pattern is empty");
+    }
+
+    let grammar = crate::parsers::ParserFactory::get_language_grammar(language)
+        .with_context(|| format!("Language {:?} not supported for structural patterns", language))?;
+    let mut parser = Parser::new();
+    parser
+        .set_language(&grammar)
+        .context("Failed to set Tree-sitter language")?;
+
+    let (snippet, metavariables) = substitute_metavariables(pattern);
+
+    let wrappers = std::iter::once(("", "")).chain(contexts(language).iter().copied());
+    for (prefix, suffix) in wrappers {
+        let source = format!("{}{}{}", prefix, snippet, suffix);
+        let Some(tree) = parser.parse(&source, None) else {
+            continue;
+        };
+        if tree.root_node().has_error() {
+            continue;
+        }
+
+        let range = (prefix.len(), prefix.len() + snippet.len());
+        let Some(node) = pattern_root(tree.root_node(), range, &source) else {
+            continue;
+        };
+
+        let mut compiler = Compiler {
+            source: &source,
+            metavariables: &metavariables,
+            seen: HashMap::new(),
+            predicates: Vec::new(),
+        };
+        if compiler.metavariable(node).is_some() {
+            anyhow::bail!("Structural pattern needs code around its metavariables: {}", pattern);
+        }
+        let root = compiler.emit(node).expect("root is not a metavariable");
+
+        let query = if compiler.predicates.is_empty() {
+            format!("{} @{}match", root, INTERNAL_CAPTURE_PREFIX)
+        } else {
+            format!("({} @{}match {})", root, INTERNAL_CAPTURE_PREFIX, compiler.predicates.join(" "))
+        };
+        log::debug!("Compiled structural pattern '{}' to: {}", pattern, query);
+        return Ok(query);
+    }
+
+    anyhow::bail!(
+        "Could not parse the structural pattern as {:?} code: {}\n\
+         \n\
+         Patterns must be syntactically valid code (an expression, statement or\n\
+         declaration), with $NAME standing in for any single node.",
+        language,
+        pattern
+    )
+}
+
+/// Replace `$NAME`, `$_` and `$$$NAME` with placeholder identifiers
+fn substitute_metavariables(pattern: &str) -> (String, Vec<Metavariable>) {
+    let re = Regex::new(r"\$\$\$([A-Z][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").expect("valid regex");
+    let mut metavariables = Vec::new();
+
+    let snippet = re.replace_all(pattern, |caps: &regex::Captures| {
+        let (name, multi) = match (caps.get(1), caps.get(2)) {
+            (Some(name), _) => (Some(name.as_str().to_string()), true),
+            (None, Some(name)) if name.as_str() == "_" => (None, false),
+            (None, Some(name)) => (Some(name.as_str().to_string()), false),
+            (None, None) => (None, true),
+        };
+        metavariables.push(Metavariable { name, multi });
+        format!("{}{}", PLACEHOLDER_PREFIX, metavariables.len() - 1)
+    });
+
+    (snippet.into_owned(), metavariables)
+}
+
+/// Node the pattern compiles from: the outermost node spanning exactly `range`,
+/// minus wrappers around a single child (a statement around its expression)
+fn pattern_root<'t>(root: Node<'t>, range: (usize, usize), source: &str) -> Option<Node<'t>> {
+    let mut node = root.descendant_for_byte_range(range.0, range.1)?;
+    while let Some(parent) = node.parent() {
+        if parent.start_byte() != node.start_byte() || parent.end_byte() != node.end_byte() {
+            break;
+        }
+        node = parent;
+    }
+    // Several sibling statements have no node of their own
+    if node.start_byte() != range.0 || node.end_byte() != range.1 {
+        return None;
+    }
+
+    loop {
+        let mut cursor = node.walk();
+        let named: Vec<Node> = node.named_children(&mut cursor).filter(|c| !c.is_extra()).collect();
+        let [child] = named.as_slice() else {
+            break;
+        };
+        let text = source[node.byte_range()].trim().trim_end_matches(';').trim_end();
+        if &source[child.byte_range()] != text {
+            break;
+        }
+        node = *child;
+    }
+
+    Some(node)
+}
+
+struct Compiler<'a> {
+    source: &'a str,
+    metavariables: &'a [Metavariable],
+    /// Times each metavariable name has been captured
+    seen: HashMap<String, usize>,
+    predicates: Vec<String>,
+}
+
+impl Compiler<'_> {
+    /// Metavariable a node stands for (its whole text is a placeholder)
+    fn metavariable(&self, node: Node) -> Option<&Metavariable> {
+        let text = &self.source[node.byte_range()];
+        let index: usize = text.strip_prefix(PLACEHOLDER_PREFIX)?.parse().ok()?;
+        self.metavariables.get(index)
+    }
+
+    /// Query pattern for `node`; None for `$$$`, which matches any siblings
+    fn emit(&mut self, node: Node) -> Option<String> {
+        if let Some(metavariable) = self.metavariable(node).cloned() {
+            if metavariable.multi {
+                return None;
+            }
+            return Some(match metavariable.name {
+                Some(name) => self.capture_metavariable(&name),
+                None => "(_)".to_string(),
+            });
+        }
+
+        // (field, pattern, named) per significant child
+        let mut children: Vec<(Option<&str>, Option<String>, bool)> = Vec::new();
+        let mut cursor = node.walk();
+        for (i, child) in node.children(&mut cursor).enumerate() {
+            if child.is_extra() {
+                continue;
+            }
+            let field = node.field_name_for_child(i as u32);
+            if child.is_named() {
+                children.push((field, self.emit(child), true));
+            } else if field.is_some() || child.kind().chars().all(|c| c.is_alphabetic() || c == '_') {
+                // Keywords and operators; punctuation is implied by the node kinds
+                children.push((field, Some(format!("\"{}\"", escape(child.kind()))), false));
+            }
+        }
+
+        if !children.iter().any(|(_, _, named)| *named) {
+            // Leaf: same kind and text
+            let capture = format!("{}lit{}", INTERNAL_CAPTURE_PREFIX, self.predicates.len());
+            self.predicates.push(format!(
+                "(#eq? @{} \"{}\")",
+                capture,
+                escape(&self.source[node.byte_range()])
+            ));
+            return Some(format!("(({}) @{})", node.kind(), capture));
+        }
+
+        // Anchor consecutive named children so counts line up; `$$$` breaks the chain
+        let mut items = Vec::new();
+        let mut prev_named = false;
+        for (idx, (field, pattern, named)) in children.iter().enumerate() {
+            let Some(pattern) = pattern else {
+                prev_named = false;
+                continue;
+            };
+            if *named && (prev_named || idx == 0) {
+                items.push(".".to_string());
+            }
+            items.push(match field {
+                Some(field) => format!("{}: {}", field, pattern),
+                None => pattern.clone(),
+            });
+            prev_named = *named;
+        }
+        if children.last().is_some_and(|(_, pattern, named)| *named && pattern.is_some()) {
+            items.push(".".to_string());
+        }
+
+        Some(format!("({} {})", node.kind(), items.join(" ")))
+    }
+
+    /// `(_) @NAME`, requiring repeated metavariables to match the same text
+    fn capture_metavariable(&mut self, name: &str) -> String {
+        let count = self.seen.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            return format!("(_) @{}", name);
+        }
+
+        let capture = format!("{}{}_{}", INTERNAL_CAPTURE_PREFIX, name, count);
+        self.predicates.push(format!("(#eq? @{} @{})", name, capture));
+        format!("(_) @{}", capture)
+    }
+}
+
+/// Escape a string for a Tree-sitter query string literal
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_query::execute_ast_query;
+    use crate::models::{SearchResult, Span, SymbolKind};
+
+    fn search(pattern: &str, language: Language, path: &str, content: &str) -> Vec<SearchResult> {
+        let query = compile(pattern, language).unwrap();
+
+        let mut file_contents = HashMap::new();
+        file_contents.insert(path.to_string(), content.to_string());
+        let candidates = vec![SearchResult {
+            path: path.to_string(),
+            lang: language,
+            span: Span { start_line: 1, end_line: 1 },
+            symbol: None,
+            parent: None,
+            qualified_name: None,
+            kind: SymbolKind::Unknown("ast_query".to_string()),
+            preview: String::new(),
+            dependencies: None,
+            repo: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            captures: None,
+            score: None,
+        }];
+        execute_ast_query(candidates, &query, language, &file_contents).unwrap()
+    }
+
+    fn lines(results: &[SearchResult]) -> Vec<usize> {
+        results.iter().map(|r| r.span.start_line).collect()
+    }
+
+    #[test]
+    fn test_statement_pattern_with_metavariables() {
+        let content = "function a(x) {\n  if (x > 1) { return x; }\n  if (ready) {\n    return compute(x);\n  }\n  if (x) { log(x); return; }\n}\n";
+        let results = search("if ($COND) { return $X; }", Language::TypeScript, "a.ts", content);
+
+        assert_eq!(lines(&results), vec![2, 3]);
+        let captures = results[1].captures.as_ref().unwrap();
+        assert_eq!(captures["COND"][0].text, "ready");
+        assert_eq!(captures["X"][0].text, "compute(x)");
+        assert!(captures.keys().all(|k| !k.starts_with(INTERNAL_CAPTURE_PREFIX)));
+    }
+
+    #[test]
+    fn test_argument_counts_and_multi_metavariables() {
+        let content = "fn main() {\n    foo(1);\n    foo(1, 2);\n    bar(1);\n    foo();\n}\n";
+
+        let results = search("foo($A)", Language::Rust, "main.rs", content);
+        assert_eq!(lines(&results), vec![2]);
+
+        let results = search("foo($$$)", Language::Rust, "main.rs", content);
+        assert_eq!(lines(&results), vec![2, 3, 5]);
+
+        let results = search("foo($A, $$$REST)", Language::Rust, "main.rs", content);
+        assert_eq!(lines(&results), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_repeated_metavariable_and_operators() {
+        let content = "x = a == a\ny = a == b\nz = a != a\n";
+        let results = search("$X == $X", Language::Python, "m.py", content);
+        assert_eq!(lines(&results), vec![1]);
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(compile("$X", Language::Rust).is_err());
+        assert!(compile("fn (", Language::Rust).is_err());
+        assert!(compile("foo()", Language::Vue).is_err());
+    }
+}
@@ -4,15 +4,41 @@
 //! re-parsing files during symbol queries. Symbols are stored in SQLite
 //! and keyed by (file_path, blake3_hash) for automatic invalidation when
 //! files change.
+//!
+//! Each cached symbol set also records the parser version of its language
+//! (see `ParserFactory::parser_version`). When an upgraded rfx binary ships a
+//! different grammar or parser, the outdated sets are dropped on open and are
+//! re-parsed on demand or by the background symbol indexer.
+//!
+//! Cached symbols can also be ranked by fuzzy name similarity (`--fuzzy`), so a
+//! misremembered `getUserByID` still finds `get_user_by_id`.
 
 use anyhow::{Context, Result};
 use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
-use crate::models::SearchResult;
+use crate::models::{Language, SearchResult};
+use crate::parsers::ParserFactory;
+
+/// Statistics key holding the parser versions the cache was last validated against
+const PARSER_VERSIONS_KEY: &str = "symbol_parser_versions";
+
+/// Statistics key holding the most recent parser-upgrade invalidation
+const INVALIDATION_KEY: &str = "symbol_parser_invalidation";
+
+/// A kind as `json_extract` reads it from cached symbols (`"Function"` → `Function`)
+fn stored_kind_key(kind: &crate::models::SymbolKind) -> String {
+    match serde_json::to_value(kind) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(other) => other.to_string(),
+        Err(_) => kind.to_string(),
+    }
+}
 
 #[cfg(test)]
-use crate::models::{Language, Span, SymbolKind};
+use crate::models::{Span, SymbolKind};
 
 /// Symbol cache for storing and retrieving parsed symbols
 pub struct SymbolCache {
@@ -31,6 +57,10 @@ impl SymbolCache {
         let cache = Self { db_path };
         cache.init_schema()?;
 
+        if let Err(e) = cache.invalidate_outdated() {
+            log::warn!("Failed to check symbol cache parser versions: {}", e);
+        }
+
         Ok(cache)
     }
 
@@ -77,6 +107,23 @@ impl SymbolCache {
             [],
         )?;
 
+        // Add parser_version to caches created before it existed. Existing rows get an
+        // empty version, so they are treated as outdated and re-parsed.
+        let has_parser_version: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('symbols') WHERE name='parser_version'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0) > 0;
+
+        if !has_parser_version {
+            conn.execute(
+                "ALTER TABLE symbols ADD COLUMN parser_version TEXT NOT NULL DEFAULT ''",
+                [],
+            )?;
+        }
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_symbols_file_id ON symbols(file_id)",
             [],
@@ -111,8 +158,8 @@ impl SymbolCache {
 
         let symbols_json: Option<String> = conn
             .query_row(
-                "SELECT symbols_json FROM symbols WHERE file_id = ? AND file_hash = ?",
-                [&file_id.to_string(), file_hash],
+                "SELECT symbols_json FROM symbols WHERE file_id = ? AND file_hash = ? AND parser_version = ?",
+                [&file_id.to_string(), file_hash, parser_version_for_path(file_path)],
                 |row| row.get(0),
             )
             .optional()?;
@@ -155,7 +202,7 @@ impl SymbolCache {
         // Prepare statements for file_id lookup and symbol retrieval
         let mut file_id_stmt = conn.prepare("SELECT id FROM files WHERE path = ?")?;
         let mut symbols_stmt = conn.prepare(
-            "SELECT symbols_json FROM symbols WHERE file_id = ? AND file_hash = ?"
+            "SELECT symbols_json FROM symbols WHERE file_id = ? AND file_hash = ? AND parser_version = ?"
         )?;
 
         let mut results = Vec::with_capacity(files.len());
@@ -170,7 +217,10 @@ impl SymbolCache {
 
             let symbols = if let Some(file_id) = file_id {
                 let symbols_json: Option<String> = symbols_stmt
-                    .query_row([&file_id.to_string(), file_hash.as_str()], |row| row.get(0))
+                    .query_row(
+                        [&file_id.to_string(), file_hash.as_str(), parser_version_for_path(file_path)],
+                        |row| row.get(0),
+                    )
                     .optional()?;
 
                 match symbols_json {
@@ -210,20 +260,35 @@ impl SymbolCache {
 
     /// Get cached symbols for multiple files with optional kind filtering
     ///
-    /// Uses integer file_ids for fast batch retrieval, then filters by kind in Rust.
-    /// This avoids the cache miss detection bug that occurs with SQL-level filtering.
+    /// Single-kind form of [`SymbolCache::batch_get_with_kinds`].
+    pub fn batch_get_with_kind(
+        &self,
+        file_ids: &[(i64, String, String)],  // (file_id, hash, path)
+        kind_filter: Option<crate::models::SymbolKind>
+    ) -> Result<std::collections::HashMap<i64, Vec<SearchResult>>> {
+        self.batch_get_with_kinds(file_ids, kind_filter.as_slice(), &[])
+    }
+
+    /// Get cached symbols for multiple files, keeping symbols of `kinds` (empty = all)
+    /// and dropping those of `exclude_kinds`
+    ///
+    /// Uses integer file_ids for fast batch retrieval. Kinds are filtered inside
+    /// SQLite (`json_each` over each file's symbols), so only matching symbols are
+    /// deserialized. Every cached file still returns a row, possibly with no
+    /// symbols, so files without symbols of the kind aren't mistaken for cache misses.
     ///
     /// Automatically chunks large batches to avoid SQLite parameter limits (999 max).
     ///
     /// Parameters:
     /// - file_ids: Vec of (file_id, file_hash, file_path) tuples
-    /// - kind_filter: Optional symbol kind to filter by (applied in Rust after retrieval)
+    /// - kinds / exclude_kinds: stored symbol kinds (no `Function` → `Method` expansion)
     ///
     /// Returns HashMap of file_id → symbols for cache hits.
-    pub fn batch_get_with_kind(
+    pub fn batch_get_with_kinds(
         &self,
         file_ids: &[(i64, String, String)],  // (file_id, hash, path)
-        kind_filter: Option<crate::models::SymbolKind>
+        kinds: &[crate::models::SymbolKind],
+        exclude_kinds: &[crate::models::SymbolKind],
     ) -> Result<std::collections::HashMap<i64, Vec<SearchResult>>> {
         use std::collections::HashMap;
 
@@ -242,31 +307,45 @@ impl SymbolCache {
             .map(|(id, hash, path)| (*id, (hash.clone(), path.clone())))
             .collect();
 
-        // Capture kind filter for Rust-side filtering
-        let kind_for_filtering = kind_filter.clone();
+        // Symbols column, reduced to the requested kinds when filtering
+        let placeholders = |n: usize| vec!["?"; n].join(", ");
+        let mut conditions = Vec::new();
+        if !kinds.is_empty() {
+            conditions.push(format!("json_extract(value, '$.kind') IN ({})", placeholders(kinds.len())));
+        }
+        if !exclude_kinds.is_empty() {
+            conditions.push(format!(
+                "(json_extract(value, '$.kind') IS NULL OR json_extract(value, '$.kind') NOT IN ({}))",
+                placeholders(exclude_kinds.len())
+            ));
+        }
+        let symbols_column = if conditions.is_empty() {
+            "symbols_json".to_string()
+        } else {
+            format!(
+                "(SELECT json_group_array(json(value)) FROM json_each(symbols_json) WHERE {})",
+                conditions.join(" AND ")
+            )
+        };
+        let kind_keys: Vec<String> = kinds.iter().chain(exclude_kinds).map(stored_kind_key).collect();
 
         // Collect results across all chunks
         let mut cache_map: HashMap<i64, Vec<SearchResult>> = HashMap::new();
         let mut hits = 0;
 
         for chunk in file_ids.chunks(BATCH_SIZE) {
-            // Build placeholders for IN clause for this chunk
-            let id_placeholders = chunk.iter()
-                .map(|_| "?")
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            // Always use simple query - filter by kind in Rust to avoid cache miss detection bug
             let query = format!(
-                "SELECT file_id, symbols_json
+                "SELECT file_id, {}, parser_version
                  FROM symbols
                  WHERE file_id IN ({})",
-                id_placeholders
+                symbols_column,
+                placeholders(chunk.len())
             );
 
-            // Prepare parameters for this chunk
-            let params: Vec<Box<dyn rusqlite::ToSql>> = chunk.iter()
-                .map(|(id, _, _)| Box::new(*id) as Box<dyn rusqlite::ToSql>)
+            // Kind parameters come first: they appear before the file_ids in the query
+            let params: Vec<Box<dyn rusqlite::ToSql>> = kind_keys.iter()
+                .map(|key| Box::new(key.clone()) as Box<dyn rusqlite::ToSql>)
+                .chain(chunk.iter().map(|(id, _, _)| Box::new(*id) as Box<dyn rusqlite::ToSql>))
                 .collect();
 
             // Execute query
@@ -275,15 +354,21 @@ impl SymbolCache {
             let rows = stmt.query_map(param_refs.as_slice(), |row| {
                 Ok((
                     row.get::<_, i64>(0)?,
-                    row.get::<_, String>(1)?
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?
                 ))
             })?;
 
             for row_result in rows {
-                let (file_id, symbols_json) = row_result?;
+                let (file_id, symbols_json, parser_version) = row_result?;
 
                 // Verify hash matches
                 if let Some((_hash, file_path)) = file_info.get(&file_id) {
+                    // Symbols from an older parser are a miss (re-parsed by the caller)
+                    if parser_version != parser_version_for_path(file_path) {
+                        continue;
+                    }
+
                     // Note: We can't verify hash here since symbols table doesn't include hash in result
                     // This is OK - we'll verify by checking file_hash in a separate query if needed
                     match serde_json::from_str::<Vec<SearchResult>>(&symbols_json) {
@@ -293,13 +378,6 @@ impl SymbolCache {
                                 symbol.path = file_path.clone();
                             }
 
-                            // Filter symbols by kind if needed (Rust-side filtering)
-                            // Note: We do this in Rust rather than SQL to avoid cache miss detection bugs
-                            // SQL filtering would exclude files without the kind, making QueryEngine think they're uncached
-                            if let Some(ref filter_kind) = kind_for_filtering {
-                                symbols.retain(|s| &s.kind == filter_kind);
-                            }
-
                             cache_map.insert(file_id, symbols);
                             hits += 1;
                         }
@@ -312,18 +390,11 @@ impl SymbolCache {
         }
 
         let misses = file_ids.len() - hits;
-
-        if kind_for_filtering.is_some() {
-            log::debug!(
-                "Batch symbol cache with Rust-side kind filter: {} hits, {} misses ({} total, {} chunks)",
-                hits, misses, file_ids.len(), (file_ids.len() + BATCH_SIZE - 1) / BATCH_SIZE
-            );
-        } else {
-            log::debug!(
-                "Batch symbol cache: {} hits, {} misses ({} total, {} chunks)",
-                hits, misses, file_ids.len(), (file_ids.len() + BATCH_SIZE - 1) / BATCH_SIZE
-            );
-        }
+        log::debug!(
+            "Batch symbol cache{}: {} hits, {} misses ({} total, {} chunks)",
+            if conditions.is_empty() { "" } else { " with kind filter" },
+            hits, misses, file_ids.len(), file_ids.len().div_ceil(BATCH_SIZE)
+        );
 
         Ok(cache_map)
     }
@@ -355,9 +426,9 @@ impl SymbolCache {
         let now = chrono::Utc::now().timestamp();
 
         conn.execute(
-            "INSERT OR REPLACE INTO symbols (file_id, file_hash, symbols_json, last_cached)
-             VALUES (?, ?, ?, ?)",
-            [&file_id.to_string(), file_hash, &symbols_json, &now.to_string()],
+            "INSERT OR REPLACE INTO symbols (file_id, file_hash, symbols_json, last_cached, parser_version)
+             VALUES (?, ?, ?, ?, ?)",
+            [&file_id.to_string(), file_hash, &symbols_json, &now.to_string(), parser_version_for_path(file_path)],
         )?;
 
         log::debug!("Cached {} symbols for {}", symbols.len(), file_path);
@@ -395,9 +466,9 @@ impl SymbolCache {
 
             // Insert into symbols table
             tx.execute(
-                "INSERT OR REPLACE INTO symbols (file_id, file_hash, symbols_json, last_cached)
-                 VALUES (?, ?, ?, ?)",
-                [&file_id.to_string(), file_hash.as_str(), &symbols_json, &now_str],
+                "INSERT OR REPLACE INTO symbols (file_id, file_hash, symbols_json, last_cached, parser_version)
+                 VALUES (?, ?, ?, ?, ?)",
+                [&file_id.to_string(), file_hash.as_str(), &symbols_json, &now_str, parser_version_for_path(file_path)],
             )?;
         }
 
@@ -406,6 +477,28 @@ impl SymbolCache {
         Ok(())
     }
 
+    /// Carry cached symbols over from a renamed file or directory to its new path
+    ///
+    /// Entries stay keyed by content hash, so a moved file keeps its symbols
+    /// until its content changes. Both paths must be in the index. Returns the
+    /// number of symbol sets copied.
+    pub fn copy_renamed(&self, from: &str, to: &str) -> Result<usize> {
+        let conn = Connection::open(&self.db_path)?;
+
+        let copied = conn.execute(
+            "INSERT OR IGNORE INTO symbols (file_id, file_hash, symbols_json, last_cached, parser_version)
+             SELECT dest.id, s.file_hash, s.symbols_json, s.last_cached, s.parser_version
+             FROM symbols s
+             JOIN files src ON src.id = s.file_id
+             JOIN files dest ON dest.path = ?2 || substr(src.path, length(?1) + 1)
+             WHERE src.path = ?1 OR substr(src.path, 1, length(?1) + 1) = ?1 || '/'",
+            [from, to],
+        )?;
+
+        log::debug!("Carried {} cached symbol sets over from {} to {}", copied, from, to);
+        Ok(copied)
+    }
+
     /// Clear all cached symbols
     pub fn clear(&self) -> Result<()> {
         let conn = Connection::open(&self.db_path)?;
@@ -414,6 +507,122 @@ impl SymbolCache {
         Ok(())
     }
 
+    /// Drop cached symbol sets produced by a different parser version
+    ///
+    /// This is cheap when nothing changed: the current parser versions are compared
+    /// against the set recorded at the last check, and rows are only scanned after
+    /// an upgrade. Returns the invalidation (if any entries were dropped), which is
+    /// also recorded for `rfx index status`.
+    pub fn invalidate_outdated(&self) -> Result<Option<ParserInvalidation>> {
+        let mut conn = Connection::open(&self.db_path)?;
+
+        let current: BTreeMap<&str, &str> = ParserFactory::parser_versions().iter().copied().collect();
+        let current_json = serde_json::to_string(&current)?;
+
+        let recorded: Option<String> = conn
+            .query_row(
+                "SELECT value FROM statistics WHERE key = ?",
+                [PARSER_VERSIONS_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if recorded.as_deref() == Some(current_json.as_str()) {
+            return Ok(None);
+        }
+
+        // Find rows whose parser version differs from the current one for their language
+        let mut outdated: Vec<i64> = Vec::new();
+        let mut languages: Vec<String> = Vec::new();
+        {
+            let mut stmt = conn.prepare(
+                "SELECT s.file_id, s.parser_version, f.path
+                 FROM symbols s
+                 JOIN files f ON f.id = s.file_id"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+
+            for row in rows {
+                let (file_id, parser_version, path) = row?;
+                let language = language_for_path(&path);
+                if parser_version != ParserFactory::parser_version(language) {
+                    outdated.push(file_id);
+                    let name = format!("{:?}", language).to_lowercase();
+                    if !languages.contains(&name) {
+                        languages.push(name);
+                    }
+                }
+            }
+        }
+        languages.sort();
+
+        let now = chrono::Utc::now().timestamp();
+        let invalidation = ParserInvalidation {
+            invalidated_entries: outdated.len(),
+            languages,
+            invalidated_at: now,
+        };
+
+        let tx = conn.transaction()?;
+        for file_id in &outdated {
+            tx.execute("DELETE FROM symbols WHERE file_id = ?", [file_id])?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO statistics (key, value, updated_at) VALUES (?, ?, ?)",
+            [PARSER_VERSIONS_KEY, current_json.as_str(), &now.to_string()],
+        )?;
+        if !outdated.is_empty() {
+            tx.execute(
+                "INSERT OR REPLACE INTO statistics (key, value, updated_at) VALUES (?, ?, ?)",
+                [INVALIDATION_KEY, &serde_json::to_string(&invalidation)?, &now.to_string()],
+            )?;
+        }
+        tx.commit()?;
+
+        if outdated.is_empty() {
+            return Ok(None);
+        }
+
+        log::info!(
+            "Parser upgrade detected: invalidated {} cached symbol sets ({})",
+            invalidation.invalidated_entries,
+            invalidation.languages.join(", ")
+        );
+        Ok(Some(invalidation))
+    }
+
+    /// Get the most recent parser-upgrade invalidation, if any
+    pub fn last_invalidation(&self) -> Result<Option<ParserInvalidation>> {
+        let conn = Connection::open(&self.db_path)?;
+
+        let value: Option<String> = conn
+            .query_row(
+                "SELECT value FROM statistics WHERE key = ?",
+                [INVALIDATION_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match value {
+            Some(json) => Ok(Some(serde_json::from_str(&json)
+                .context("Failed to parse parser invalidation record")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Forget the last parser-upgrade invalidation (after the symbols are rebuilt)
+    pub fn clear_invalidation(&self) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute("DELETE FROM statistics WHERE key = ?", [INVALIDATION_KEY])?;
+        Ok(())
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> Result<SymbolCacheStats> {
         let conn = Connection::open(&self.db_path)?;
@@ -475,6 +684,140 @@ pub struct SymbolCacheStats {
     pub cache_size_bytes: u64,
 }
 
+/// Record of cached symbols dropped after a parser/grammar upgrade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParserInvalidation {
+    /// Number of cached symbol sets removed
+    pub invalidated_entries: usize,
+    /// Languages whose parser version changed
+    pub languages: Vec<String>,
+    /// Unix timestamp of the invalidation
+    pub invalidated_at: i64,
+}
+
+fn language_for_path(path: &str) -> Language {
+    Language::from_path(Path::new(path))
+}
+
+/// Parser version that symbols for `path` must have been produced by
+fn parser_version_for_path(path: &str) -> &'static str {
+    ParserFactory::parser_version(language_for_path(path))
+}
+
+/// Default minimum similarity of a `--fuzzy` match (`[search] fuzzy_threshold`)
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.8;
+
+/// Similarity of a symbol name to a fuzzy query, from 0.0 (unrelated) to 1.0 (identical)
+///
+/// Both sides are compared as lowercase words without separators, so names that
+/// differ only in case convention score 0.99. Otherwise the score is the better of
+/// an edit-distance similarity (typos, transpositions) and a Ctrl-P style
+/// subsequence score that rewards query characters landing on word starts or
+/// right after the previous match (`gubi` → `getUserById`).
+pub fn fuzzy_score(query: &str, name: &str) -> f64 {
+    if query == name {
+        return 1.0;
+    }
+
+    let name_words = crate::tokens::identifier_words(name);
+    let query: Vec<char> = crate::tokens::identifier_words(query).concat().chars().collect();
+    let name: Vec<char> = name_words.concat().chars().collect();
+
+    if query.is_empty() || name.is_empty() {
+        return 0.0;
+    }
+    if query == name {
+        return 0.99;
+    }
+
+    let mut word_starts = vec![false; name.len()];
+    let mut offset = 0;
+    for word in &name_words {
+        word_starts[offset] = true;
+        offset += word.chars().count();
+    }
+
+    let longest = query.len().max(name.len()) as f64;
+    let edit = 1.0 - edit_distance(&query, &name) as f64 / longest;
+    let subsequence = subsequence_score(&query, &name, &word_starts).unwrap_or(0.0);
+
+    // Rounded so equal-looking scores tie (and fall through to the tie-breakers)
+    (edit.max(subsequence).min(0.98) * 1000.0).round() / 1000.0
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent transpositions)
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Score of `query` as an in-order subsequence of `name` (None if it isn't one)
+///
+/// Matches greedily from the left. Half the score is the share of query
+/// characters on a word start or adjacent to the previous match, half is how
+/// much of the name the query covers; the result is scaled into 0.6..=1.0.
+fn subsequence_score(query: &[char], name: &[char], word_starts: &[bool]) -> Option<f64> {
+    let mut strong = 0;
+    let mut last: Option<usize> = None;
+    let mut from = 0;
+
+    for &qc in query {
+        let found = (from..name.len()).find(|&i| name[i] == qc)?;
+        if word_starts[found] || last.is_some_and(|l| l + 1 == found) {
+            strong += 1;
+        }
+        last = Some(found);
+        from = found + 1;
+    }
+
+    let quality = strong as f64 / query.len() as f64;
+    let coverage = query.len() as f64 / name.len() as f64;
+    Some(0.6 + 0.4 * (quality + coverage) / 2.0)
+}
+
+/// Keep symbols whose name scores at least `threshold` against `query`, best first
+///
+/// Equal scores are ordered by path, line and name so results (and pagination)
+/// are deterministic.
+pub fn rank_fuzzy(symbols: Vec<SearchResult>, query: &str, threshold: f64) -> Vec<SearchResult> {
+    let mut scored: Vec<(f64, SearchResult)> = symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let score = fuzzy_score(query, symbol.symbol.as_deref()?);
+            (score >= threshold).then_some((score, symbol))
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score.total_cmp(a_score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.span.start_line.cmp(&b.span.start_line))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+
+    scored.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -530,6 +873,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_symbol_cache_copy_renamed() {
+        let temp = TempDir::new().unwrap();
+        let cache_mgr = CacheManager::new(temp.path());
+        cache_mgr.init().unwrap();
+        for path in ["src/a.rs", "src/util/b.rs", "lib/a.rs", "lib/util/b.rs", "moved.rs"] {
+            cache_mgr.update_file(path, "rust", 100).unwrap();
+        }
+
+        let symbol_cache = SymbolCache::open(cache_mgr.path()).unwrap();
+        let symbol = |path: &str| {
+            vec![SearchResult::new(
+                path.to_string(),
+                Language::Rust,
+                SymbolKind::Function,
+                Some("helper".to_string()),
+                Span::new(1, 0, 1, 0),
+                None,
+                "fn helper() {}".to_string(),
+            )]
+        };
+        symbol_cache.set("src/a.rs", "hash_a", &symbol("src/a.rs")).unwrap();
+        symbol_cache.set("src/util/b.rs", "hash_b", &symbol("src/util/b.rs")).unwrap();
+
+        // A directory rename carries over every file below it
+        assert_eq!(symbol_cache.copy_renamed("src", "lib").unwrap(), 2);
+        let moved = symbol_cache.get("lib/util/b.rs", "hash_b").unwrap().unwrap();
+        assert_eq!(moved[0].path, "lib/util/b.rs");
+        assert!(symbol_cache.get("lib/a.rs", "hash_a").unwrap().is_some());
+
+        assert_eq!(symbol_cache.copy_renamed("src/a.rs", "moved.rs").unwrap(), 1);
+        assert!(symbol_cache.get("moved.rs", "hash_a").unwrap().is_some());
+        assert_eq!(symbol_cache.copy_renamed("src/a.rs", "missing.rs").unwrap(), 0);
+    }
+
     #[test]
     fn test_symbol_cache_hash_mismatch() {
         let temp = TempDir::new().unwrap();
@@ -713,6 +1091,73 @@ mod tests {
         assert_eq!(empty_results.len(), 0);
     }
 
+    #[test]
+    fn test_symbol_cache_batch_get_with_kinds() {
+        let temp = TempDir::new().unwrap();
+        let cache_mgr = CacheManager::new(temp.path());
+        cache_mgr.init().unwrap();
+
+        cache_mgr.update_file("mixed.rs", "rust", 100).unwrap();
+        cache_mgr.update_file("structs.rs", "rust", 200).unwrap();
+
+        let symbol_cache = SymbolCache::open(cache_mgr.path()).unwrap();
+
+        let symbol = |path: &str, kind: SymbolKind, name: &str| SearchResult::new(
+            path.to_string(),
+            Language::Rust,
+            kind,
+            Some(name.to_string()),
+            Span::new(1, 0, 5, 0),
+            None,
+            String::new(),
+        );
+        symbol_cache.batch_set(&[
+            (
+                "mixed.rs".to_string(),
+                "hash1".to_string(),
+                vec![
+                    symbol("mixed.rs", SymbolKind::Function, "run"),
+                    symbol("mixed.rs", SymbolKind::Method, "call"),
+                    symbol("mixed.rs", SymbolKind::Struct, "Config"),
+                ],
+            ),
+            (
+                "structs.rs".to_string(),
+                "hash2".to_string(),
+                vec![symbol("structs.rs", SymbolKind::Struct, "Point")],
+            ),
+        ]).unwrap();
+
+        let ids = cache_mgr.batch_get_file_ids(&["mixed.rs".to_string(), "structs.rs".to_string()]).unwrap();
+        let mixed_id = ids["mixed.rs"];
+        let structs_id = ids["structs.rs"];
+        let lookups = vec![
+            (mixed_id, "hash1".to_string(), "mixed.rs".to_string()),
+            (structs_id, "hash2".to_string(), "structs.rs".to_string()),
+        ];
+        let names = |symbols: &[SearchResult]| symbols.iter()
+            .map(|s| s.symbol.clone().unwrap())
+            .collect::<Vec<_>>();
+
+        // Keep only the requested kinds; path is restored on the filtered symbols
+        let results = symbol_cache.batch_get_with_kinds(&lookups, &[SymbolKind::Function, SymbolKind::Method], &[]).unwrap();
+        assert_eq!(names(&results[&mixed_id]), vec!["run", "call"]);
+        assert_eq!(results[&mixed_id][0].path, "mixed.rs");
+
+        // Files without symbols of the kind are still hits, just empty
+        assert!(results[&structs_id].is_empty());
+
+        // Excluded kinds are dropped
+        let results = symbol_cache.batch_get_with_kinds(&lookups, &[], &[SymbolKind::Struct]).unwrap();
+        assert_eq!(names(&results[&mixed_id]), vec!["run", "call"]);
+        assert!(results[&structs_id].is_empty());
+
+        // Single-kind form
+        let results = symbol_cache.batch_get_with_kind(&lookups, Some(SymbolKind::Struct)).unwrap();
+        assert_eq!(names(&results[&mixed_id]), vec!["Config"]);
+        assert_eq!(names(&results[&structs_id]), vec!["Point"]);
+    }
+
     #[test]
     fn test_symbol_cache_clear() {
         let temp = TempDir::new().unwrap();
@@ -800,4 +1245,124 @@ mod tests {
         let cached2 = symbol_cache.get("deleted.rs", "hash2").unwrap();
         assert!(cached2.is_none());
     }
+
+    #[test]
+    fn test_symbol_cache_parser_version_mismatch_is_miss() {
+        let temp = TempDir::new().unwrap();
+        let cache_mgr = CacheManager::new(temp.path());
+        cache_mgr.init().unwrap();
+        cache_mgr.update_file("test.rs", "rust", 100).unwrap();
+
+        let symbol_cache = SymbolCache::open(cache_mgr.path()).unwrap();
+        let symbols = vec![SearchResult::new(
+            "test.rs".to_string(),
+            Language::Rust,
+            SymbolKind::Function,
+            Some("test_fn".to_string()),
+            Span::new(1, 0, 5, 0),
+            None,
+            "fn test_fn() {}".to_string(),
+        )];
+        symbol_cache.set("test.rs", "hash123", &symbols).unwrap();
+        assert!(symbol_cache.get("test.rs", "hash123").unwrap().is_some());
+
+        // Simulate symbols produced by an older grammar
+        let conn = rusqlite::Connection::open(cache_mgr.path().join("meta.db")).unwrap();
+        conn.execute("UPDATE symbols SET parser_version = 'old'", []).unwrap();
+
+        assert!(symbol_cache.get("test.rs", "hash123").unwrap().is_none());
+        let batch = symbol_cache.batch_get(&[("test.rs".to_string(), "hash123".to_string())]).unwrap();
+        assert!(batch[0].1.is_none());
+    }
+
+    #[test]
+    fn test_invalidate_outdated_after_parser_upgrade() {
+        let temp = TempDir::new().unwrap();
+        let cache_mgr = CacheManager::new(temp.path());
+        cache_mgr.init().unwrap();
+        cache_mgr.update_file("lib.rs", "rust", 100).unwrap();
+        cache_mgr.update_file("app.py", "python", 100).unwrap();
+
+        let symbol_cache = SymbolCache::open(cache_mgr.path()).unwrap();
+        let symbols = vec![SearchResult::new(
+            String::new(),
+            Language::Rust,
+            SymbolKind::Function,
+            Some("f".to_string()),
+            Span::new(1, 0, 1, 0),
+            None,
+            "fn f() {}".to_string(),
+        )];
+        symbol_cache.set("lib.rs", "hash1", &symbols).unwrap();
+        symbol_cache.set("app.py", "hash2", &symbols).unwrap();
+
+        // Nothing changed since open: no invalidation
+        assert!(symbol_cache.invalidate_outdated().unwrap().is_none());
+        assert!(symbol_cache.last_invalidation().unwrap().is_none());
+
+        // Simulate a Rust grammar upgrade: stored Rust symbols carry an old version
+        let conn = rusqlite::Connection::open(cache_mgr.path().join("meta.db")).unwrap();
+        conn.execute(
+            "UPDATE symbols SET parser_version = 'old' WHERE file_id = (SELECT id FROM files WHERE path = 'lib.rs')",
+            [],
+        ).unwrap();
+        conn.execute("DELETE FROM statistics WHERE key = 'symbol_parser_versions'", []).unwrap();
+
+        // Reopening detects the upgrade and drops only the Rust entry
+        let symbol_cache = SymbolCache::open(cache_mgr.path()).unwrap();
+        let invalidation = symbol_cache.last_invalidation().unwrap().unwrap();
+        assert_eq!(invalidation.invalidated_entries, 1);
+        assert_eq!(invalidation.languages, vec!["rust".to_string()]);
+
+        assert_eq!(symbol_cache.stats().unwrap().total_files, 1);
+        assert!(symbol_cache.get("app.py", "hash2").unwrap().is_some());
+        assert!(symbol_cache.invalidate_outdated().unwrap().is_none());
+
+        symbol_cache.clear_invalidation().unwrap();
+        assert!(symbol_cache.last_invalidation().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("parse", "parse"), 1.0);
+        assert_eq!(fuzzy_score("getUserByID", "get_user_by_id"), 0.99);
+
+        // Typos, transpositions and abbreviations clear the default threshold
+        assert!(fuzzy_score("get_usr_by_id", "getUserById") >= DEFAULT_FUZZY_THRESHOLD);
+        assert!(fuzzy_score("pasre", "parse") >= DEFAULT_FUZZY_THRESHOLD);
+        assert!(fuzzy_score("gubi", "getUserById") >= DEFAULT_FUZZY_THRESHOLD);
+
+        // Unrelated names don't
+        assert!(fuzzy_score("render", "parse") < DEFAULT_FUZZY_THRESHOLD);
+        assert!(fuzzy_score("ae", "parse") < DEFAULT_FUZZY_THRESHOLD);
+        assert_eq!(fuzzy_score("", "parse"), 0.0);
+    }
+
+    #[test]
+    fn test_rank_fuzzy_orders_by_score_then_location() {
+        let symbol = |path: &str, line: usize, name: &str| SearchResult::new(
+            path.to_string(),
+            Language::Rust,
+            SymbolKind::Function,
+            Some(name.to_string()),
+            Span::new(line, 0, line, 0),
+            None,
+            String::new(),
+        );
+
+        let ranked = rank_fuzzy(
+            vec![
+                symbol("b.rs", 1, "get_user_by_id"),
+                symbol("a.rs", 9, "get_user_by_id"),
+                symbol("a.rs", 2, "getUserByIdd"),
+                symbol("a.rs", 1, "render"),
+                symbol("c.rs", 1, "getUserByID"),
+            ],
+            "getUserByID",
+            DEFAULT_FUZZY_THRESHOLD,
+        );
+
+        let order: Vec<(&str, usize)> = ranked.iter().map(|r| (r.path.as_str(), r.span.start_line)).collect();
+        assert_eq!(order, vec![("c.rs", 1), ("a.rs", 9), ("b.rs", 1), ("a.rs", 2)]);
+    }
 }
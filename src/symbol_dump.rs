@@ -0,0 +1,357 @@
+//! Bulk export of the symbol table (`rfx dump-symbols`)
+//!
+//! Streams every symbol of every indexed file as JSON Lines (one symbol per
+//! line) in path order, so other systems can ingest the full symbol table
+//! without issuing a query per name. Symbols come from the symbol cache;
+//! files the background symbol indexer hasn't reached yet are parsed on the
+//! fly and cached.
+//!
+//! Output is paginated by file: `limit` bounds the files per run and the
+//! summary carries the cursor (`next_after`) for the next page. A dump to a
+//! file that was interrupted resumes with [`resume_point`], which drops the
+//! possibly incomplete last file and continues from there.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::models::{Language, SearchResult, Span, SymbolKind};
+use crate::quarantine::Quarantine;
+use crate::symbol_cache::SymbolCache;
+
+/// Files loaded from the symbol cache per database round trip
+const CHUNK_SIZE: usize = 500;
+
+/// One exported symbol (one JSONL line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolRecord {
+    pub path: String,
+    pub lang: Language,
+    pub name: Option<String>,
+    /// Name prefixed by the enclosing modules, classes, etc. (`outer::Point`)
+    pub qualified_name: Option<String>,
+    pub kind: SymbolKind,
+    pub span: Span,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// What to export
+#[derive(Debug, Clone, Default)]
+pub struct DumpOptions {
+    pub language: Option<Language>,
+    pub kind: Option<SymbolKind>,
+    /// Start after this path (exclusive cursor, paths are in sorted order)
+    pub after: Option<String>,
+    /// Maximum number of files to export in this run
+    pub limit: Option<usize>,
+}
+
+/// Outcome of a dump
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DumpSummary {
+    /// Files visited (including files without matching symbols)
+    pub files: usize,
+    pub symbols: usize,
+    /// Files that weren't in the symbol cache and were parsed for this dump
+    pub parsed: usize,
+    /// Cursor for the next page (set when `limit` stopped the dump early)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_after: Option<String>,
+}
+
+/// Write the symbols of all indexed files as JSON Lines
+pub fn dump_symbols(cache: &CacheManager, options: &DumpOptions, out: &mut dyn Write) -> Result<DumpSummary> {
+    let mut written = 0;
+    let mut summary = visit_symbols(cache, options, &mut |path, symbols| {
+        for record in records(path, symbols, options.kind.as_ref()) {
+            serde_json::to_writer(&mut *out, &record)?;
+            out.write_all(b"\n")?;
+            written += 1;
+        }
+        Ok(())
+    })?;
+
+    out.flush()?;
+    summary.symbols = written;
+    Ok(summary)
+}
+
+/// Call `visit` with the symbols of every indexed file selected by `options`, in path order
+///
+/// `options.kind` is not applied; the summary's `symbols` count is left to the caller.
+pub fn visit_symbols(
+    cache: &CacheManager,
+    options: &DumpOptions,
+    visit: &mut dyn FnMut(&str, &[SearchResult]) -> Result<()>,
+) -> Result<DumpSummary> {
+    let symbol_cache = SymbolCache::open(cache.path()).context("Failed to open symbol cache")?;
+    let quarantine = Quarantine::open(cache.path()).context("Failed to open parser quarantine")?;
+    let content_reader = ContentReader::open(cache.path().join("content.bin"))
+        .context("Failed to open content store")?;
+
+    let branch = cache.query_branch();
+    let file_hashes = cache.load_hashes_for_branch(&branch)
+        .context("Failed to load file hashes")?;
+
+    let mut paths: Vec<&String> = file_hashes
+        .keys()
+        .filter(|path| options.after.as_ref().is_none_or(|after| path.as_str() > after.as_str()))
+        .filter(|path| {
+            let lang = language_of(path);
+            lang.is_supported() && options.language.is_none_or(|l| l == lang)
+        })
+        .collect();
+    paths.sort();
+
+    let mut summary = DumpSummary::default();
+    if let Some(limit) = options.limit
+        && paths.len() > limit
+    {
+        paths.truncate(limit);
+        summary.next_after = paths.last().map(|p| p.to_string());
+    }
+
+    for chunk in paths.chunks(CHUNK_SIZE) {
+        let chunk_paths: Vec<String> = chunk.iter().map(|p| p.to_string()).collect();
+        let file_ids = cache.batch_get_file_ids(&chunk_paths)?;
+        let lookups: Vec<(i64, String, String)> = chunk_paths
+            .iter()
+            .filter_map(|path| Some((*file_ids.get(path)?, file_hashes[path].clone(), path.clone())))
+            .collect();
+        let mut cached = symbol_cache.batch_get_with_kind(&lookups, None)?;
+
+        let mut newly_cached = Vec::new();
+        for path in &chunk_paths {
+            let symbols = match file_ids.get(path).and_then(|id| cached.remove(id)) {
+                Some(symbols) => symbols,
+                None => {
+                    let Some(symbols) = parse_file(&content_reader, &quarantine, &cache.workspace_root(), path, &file_hashes[path]) else {
+                        summary.files += 1;
+                        continue;
+                    };
+                    summary.parsed += 1;
+                    newly_cached.push((path.clone(), file_hashes[path].clone(), symbols.clone()));
+                    symbols
+                }
+            };
+
+            summary.files += 1;
+            visit(path, &symbols)?;
+        }
+
+        // Caching is best-effort, like for queries
+        if !newly_cached.is_empty()
+            && let Err(e) = symbol_cache.batch_set(&newly_cached)
+        {
+            log::warn!("Failed to cache parsed symbols: {}", e);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Prepare an interrupted dump in `out_path` for resuming
+///
+/// The last file in the output may be incomplete, so its records (and any
+/// partial trailing line) are truncated away. Returns the cursor to pass as
+/// [`DumpOptions::after`]: the last path still in the output, or None to start
+/// from the beginning.
+pub fn resume_point(out_path: &Path) -> Result<Option<String>> {
+    if !out_path.exists() {
+        return Ok(None);
+    }
+
+    let file = std::fs::File::open(out_path)
+        .with_context(|| format!("Failed to open {}", out_path.display()))?;
+    // (path, byte offset just past the line) of every complete line
+    let mut lines: Vec<(String, u64)> = Vec::new();
+    let mut offset = 0u64;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        offset += read as u64;
+        let record: SymbolRecord = serde_json::from_str(&line).with_context(|| {
+            format!("{} is not a dump-symbols output (line {} is not a symbol record)", out_path.display(), lines.len() + 1)
+        })?;
+        lines.push((record.path, offset));
+    }
+
+    // Drop every record of the last file
+    if let Some((last_path, _)) = lines.last().cloned() {
+        while lines.last().is_some_and(|(path, _)| *path == last_path) {
+            lines.pop();
+        }
+    }
+
+    let keep = lines.last().map_or(0, |(_, end)| *end);
+    let file = std::fs::OpenOptions::new().write(true).open(out_path)?;
+    file.set_len(keep)?;
+
+    Ok(lines.pop().map(|(path, _)| path))
+}
+
+fn language_of(path: &str) -> Language {
+    Language::from_path(Path::new(path))
+}
+
+fn parse_file(content_reader: &ContentReader, quarantine: &Quarantine, root: &Path, path: &str, file_hash: &str) -> Option<Vec<SearchResult>> {
+    // The content store keeps paths as given to the indexer, which may be absolute
+    let file_id = content_reader
+        .file_id_for_path(path)
+        .or_else(|| content_reader.file_id_for_path(&root.join(path).to_string_lossy()))?;
+    let content = match content_reader.get_file_content(file_id) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("Failed to read {} from the content store: {}", path, e);
+            return None;
+        }
+    };
+
+    match quarantine.parse(path, Some(file_hash), content, language_of(path)) {
+        Ok(symbols) => Some(symbols),
+        Err(e) => {
+            log::debug!("Skipping {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Records for one file's symbols, with qualified names
+///
+/// Top-level symbols are qualified by their own name.
+fn records(path: &str, symbols: &[SearchResult], kind: Option<&SymbolKind>) -> Vec<SymbolRecord> {
+    let lang = language_of(path);
+
+    symbols
+        .iter()
+        .filter(|s| kind.is_none_or(|k| &s.kind == k))
+        .map(|s| SymbolRecord {
+            path: path.to_string(),
+            lang,
+            name: s.symbol.clone(),
+            qualified_name: s.qualified_name.clone().or_else(|| s.symbol.clone()),
+            kind: s.kind.clone(),
+            span: s.span.clone(),
+            signature: s.signature.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn symbol(name: &str, kind: SymbolKind, start: usize, end: usize) -> SearchResult {
+        SearchResult::new(
+            "a.py".to_string(),
+            Language::Python,
+            kind,
+            Some(name.to_string()),
+            Span::new(start, 0, end, 0),
+            None,
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_qualified_names() {
+        let mut symbols = vec![
+            symbol("Shapes", SymbolKind::Class, 1, 20),
+            symbol("Point", SymbolKind::Class, 2, 10),
+            symbol("norm", SymbolKind::Method, 4, 6),
+            symbol("helper", SymbolKind::Function, 22, 24),
+        ];
+        crate::parsers::qualify::attach_qualified_names(&mut symbols, Language::Python);
+        let names: Vec<String> = records("a.py", &symbols, None).into_iter().filter_map(|r| r.qualified_name).collect();
+        assert_eq!(names, vec!["Shapes", "Shapes.Point", "Shapes.Point.norm", "helper"]);
+    }
+
+    fn indexed_project() -> (TempDir, CacheManager) {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.rs"), "mod outer {\n    pub struct Point { x: i32 }\n}\n\nfn alpha() {}\n").unwrap();
+        fs::write(root.join("b.py"), "def beta():\n    pass\n").unwrap();
+        fs::write(root.join("c.rs"), "fn gamma() {}\n").unwrap();
+        fs::write(root.join("notes.txt"), "not code\n").unwrap();
+
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+        (temp, cache)
+    }
+
+    fn dump(cache: &CacheManager, options: &DumpOptions) -> (Vec<SymbolRecord>, DumpSummary) {
+        let mut out = Vec::new();
+        let summary = dump_symbols(cache, options, &mut out).unwrap();
+        let records = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        (records, summary)
+    }
+
+    #[test]
+    fn test_dump_filters_and_pages() {
+        let (_temp, cache) = indexed_project();
+
+        let (records, summary) = dump(&cache, &DumpOptions::default());
+        assert_eq!(summary.files, 3);
+        assert!(summary.next_after.is_none());
+        let point = records.iter().find(|r| r.name.as_deref() == Some("Point")).unwrap();
+        assert_eq!(point.qualified_name.as_deref(), Some("outer::Point"));
+
+        let options = DumpOptions { kind: Some(SymbolKind::Function), language: Some(Language::Rust), ..Default::default() };
+        let (records, _) = dump(&cache, &options);
+        let names: Vec<_> = records.iter().filter_map(|r| r.name.as_deref()).collect();
+        assert_eq!(names, vec!["alpha", "gamma"]);
+
+        // Page through one file at a time
+        let (records, summary) = dump(&cache, &DumpOptions { limit: Some(1), ..Default::default() });
+        assert!(records.iter().all(|r| r.path == "a.rs"));
+        assert_eq!(summary.next_after.as_deref(), Some("a.rs"));
+        let (records, _) = dump(&cache, &DumpOptions { after: summary.next_after, ..Default::default() });
+        assert!(records.iter().all(|r| r.path != "a.rs"));
+        assert!(!records.is_empty());
+    }
+
+    #[test]
+    fn test_resume_point_drops_last_file() {
+        let (temp, cache) = indexed_project();
+        let out_path = temp.path().join("symbols.jsonl");
+
+        let mut out = Vec::new();
+        dump_symbols(&cache, &DumpOptions::default(), &mut out).unwrap();
+        let full = String::from_utf8(out).unwrap();
+
+        // Simulate an interruption halfway through a line of the last file
+        let cut = full.len() - 5;
+        fs::write(&out_path, &full[..cut]).unwrap();
+
+        // The last complete file is dropped too, as it may have been cut short
+        let after = resume_point(&out_path).unwrap();
+        assert!(after.is_some());
+        let kept = fs::read_to_string(&out_path).unwrap();
+        assert!(full.starts_with(&kept));
+        assert!(kept.lines().all(|l| !l.contains("\"c.rs\"")));
+
+        // Resuming reproduces the full output
+        let mut file = std::fs::OpenOptions::new().append(true).open(&out_path).unwrap();
+        dump_symbols(&cache, &DumpOptions { after, ..Default::default() }, &mut file).unwrap();
+        assert_eq!(fs::read_to_string(&out_path).unwrap(), full);
+
+        assert!(resume_point(&temp.path().join("missing.jsonl")).unwrap().is_none());
+    }
+}
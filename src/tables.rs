@@ -0,0 +1,644 @@
+//! Database table and ORM model usage registry
+//!
+//! Extraction passes recognize where code defines or touches a database table,
+//! and `rfx index` records every reference in `meta.db`. `rfx query --table
+//! users` resolves a table or model name through the registry and restricts
+//! the search to the files touching that table.
+//!
+//! # Recognized references
+//!
+//! - **Eloquent** (PHP): `class User extends Model`, bound to `protected $table`
+//!   or the snake-case plural of the class name; `DB::table('users')` and
+//!   `Schema::create('users', ...)` migrations
+//! - **SQLAlchemy / Django** (Python): `__tablename__ = "users"` and
+//!   `db_table = "users"`, bound to the enclosing class; `Table("users", metadata)`
+//! - **Diesel** (Rust): `table! { users (id) { ... } }`, structs annotated with
+//!   `#[diesel(table_name = users)]`, and `users::table` / `users::dsl` paths
+//! - **Prisma**: `model User { ... @@map("users") }` in `schema.prisma` files
+//!   (read from the root, `prisma/` and `prisma/schema/`)
+//! - **Raw SQL** (any language): uppercase `FROM`, `JOIN`, `INTO`, `UPDATE`
+//!   and `CREATE`/`ALTER`/`DROP TABLE` followed by a table name
+//!
+//! Table names compare case-insensitively, model names exactly.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::cache::{CacheManager, META_DB};
+use crate::models::Language;
+
+/// Maximum length (in characters) of the stored source line preview
+const MAX_PREVIEW_LENGTH: usize = 200;
+
+/// Directories searched for Prisma schema files, relative to the index root
+pub const PRISMA_DIRS: &[&str] = &["", "prisma", "prisma/schema"];
+
+/// Words that follow FROM/JOIN/INTO/UPDATE without naming a table
+const SQL_NON_TABLES: &[&str] = &["SELECT", "LATERAL", "ONLY", "TABLE", "IF", "UNNEST"];
+
+/// Paths that look like Diesel table paths (`x::table`, `x::dsl`) but aren't
+const DIESEL_NON_TABLES: &[&str] = &["diesel", "crate", "self", "super", "schema"];
+
+/// How a reference relates to its table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefKind {
+    /// ORM model class or struct bound to the table
+    Model,
+    /// Table definition (Diesel `table!`, migration, `CREATE TABLE`)
+    Schema,
+    /// Code reading or writing the table (SQL, query builder, Diesel DSL)
+    Query,
+}
+
+impl RefKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Model => "model",
+            Self::Schema => "schema",
+            Self::Query => "query",
+        }
+    }
+}
+
+/// A table reference extracted from source (before storage)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRefInfo {
+    /// Table name as written (or derived, for Eloquent models without `$table`)
+    pub table: String,
+    /// Model bound to the table, for `Model` references
+    pub model: Option<String>,
+    pub kind: RefKind,
+    /// eloquent, laravel, sqlalchemy, django, diesel, prisma or sql
+    pub framework: &'static str,
+    /// Line number of the reference (1-indexed)
+    pub line_number: usize,
+    /// Trimmed source line
+    pub preview: String,
+}
+
+/// Extract table references from a source file
+pub fn extract(language: Language, content: &str) -> Vec<TableRefInfo> {
+    let lines = Lines::new(content);
+    let mut refs = Vec::new();
+
+    match language {
+        Language::PHP => eloquent_refs(&lines, &mut refs),
+        Language::Python => python_refs(&lines, &mut refs),
+        Language::Rust => diesel_refs(&lines, &mut refs),
+        _ => {}
+    }
+    sql_refs(&lines, &mut refs);
+
+    refs.sort_by(|a, b| (a.line_number, &a.table).cmp(&(b.line_number, &b.table)));
+    refs.dedup_by(|a, b| a.table == b.table && a.line_number == b.line_number && a.kind == b.kind);
+    refs
+}
+
+/// Extract the models of a Prisma schema file
+pub fn extract_prisma(content: &str) -> Vec<TableRefInfo> {
+    let lines = Lines::new(content);
+    let mut refs = Vec::new();
+    static MODEL: OnceLock<Regex> = OnceLock::new();
+    static MAP: OnceLock<Regex> = OnceLock::new();
+
+    for caps in regex(&MODEL, r"(?m)^[ \t]*model\s+(\w+)\s*\{").captures_iter(content) {
+        let (whole, model) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+        // The block ends at the first closing brace on its own line
+        let body_end = content[whole.end()..]
+            .find("\n}")
+            .map_or(content.len(), |i| whole.end() + i);
+        let body = &content[whole.end()..body_end];
+        let table = regex(&MAP, r#"@@map\(\s*(?:name:\s*)?"(\w+)""#)
+            .captures(body)
+            .map_or(model.as_str(), |c| c.get(1).unwrap().as_str());
+        refs.push(lines.reference(table, Some(model.as_str()), RefKind::Model, "prisma", whole.start()));
+    }
+
+    refs
+}
+
+/// Read the Prisma schema files under `root`
+///
+/// Returns (root-relative path, references) for each `*.prisma` file in
+/// [`PRISMA_DIRS`], sorted by path.
+pub fn discover_prisma(root: &Path) -> Vec<(String, Vec<TableRefInfo>)> {
+    let mut found = Vec::new();
+
+    for dir in PRISMA_DIRS {
+        let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "prisma") || !path.is_file() {
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(content) => found.push((crate::paths::relative_path(&path, root), extract_prisma(&content))),
+                Err(e) => log::warn!("Failed to read Prisma schema {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+/// Eloquent models, `DB::table()` queries and `Schema::` migrations
+fn eloquent_refs(lines: &Lines, refs: &mut Vec<TableRefInfo>) {
+    let content = lines.content;
+    static CLASS: OnceLock<Regex> = OnceLock::new();
+    static TABLE: OnceLock<Regex> = OnceLock::new();
+    static QUERY: OnceLock<Regex> = OnceLock::new();
+    static SCHEMA: OnceLock<Regex> = OnceLock::new();
+
+    let classes: Vec<_> = regex(&CLASS, r"\bclass\s+(\w+)\s+extends\s+\\?(?:\w+\\)*(?:Model|Authenticatable|Pivot)\b")
+        .captures_iter(content)
+        .collect();
+
+    for (i, caps) in classes.iter().enumerate() {
+        let (whole, model) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+        let body_end = classes.get(i + 1).map_or(content.len(), |next| next.get(0).unwrap().start());
+        let table = regex(&TABLE, r#"\$table\s*=\s*['"](\w+)['"]"#)
+            .captures(&content[whole.end()..body_end])
+            .map_or_else(|| default_table(model.as_str()), |c| c[1].to_string());
+        refs.push(lines.reference(&table, Some(model.as_str()), RefKind::Model, "eloquent", whole.start()));
+    }
+
+    for (cell, pattern, kind) in [
+        (&QUERY, r#"\bDB::table\(\s*['"](\w+)['"]"#, RefKind::Query),
+        (&SCHEMA, r#"\bSchema::(?:create|table|drop|dropIfExists|rename)\(\s*['"](\w+)['"]"#, RefKind::Schema),
+    ] {
+        for caps in regex(cell, pattern).captures_iter(content) {
+            refs.push(lines.reference(&caps[1], None, kind, "laravel", caps.get(0).unwrap().start()));
+        }
+    }
+}
+
+/// SQLAlchemy and Django models, SQLAlchemy `Table()` definitions
+fn python_refs(lines: &Lines, refs: &mut Vec<TableRefInfo>) {
+    let content = lines.content;
+    static TABLENAME: OnceLock<Regex> = OnceLock::new();
+    static DB_TABLE: OnceLock<Regex> = OnceLock::new();
+    static CLASS: OnceLock<Regex> = OnceLock::new();
+    static TABLE_CALL: OnceLock<Regex> = OnceLock::new();
+
+    for (cell, pattern, framework) in [
+        (&TABLENAME, r#"\b__tablename__\s*=\s*['"](\w+)['"]"#, "sqlalchemy"),
+        (&DB_TABLE, r#"\bdb_table\s*=\s*['"](\w+)['"]"#, "django"),
+    ] {
+        for caps in regex(cell, pattern).captures_iter(content) {
+            let start = caps.get(0).unwrap().start();
+            // Django's `db_table` lives in the model's inner `class Meta`
+            let model = regex(&CLASS, r"(?m)^[ \t]*class\s+(\w+)")
+                .captures_iter(&content[..start])
+                .map(|c| c.get(1).unwrap().as_str())
+                .filter(|name| *name != "Meta")
+                .last();
+            refs.push(lines.reference(&caps[1], model, RefKind::Model, framework, start));
+        }
+    }
+
+    for caps in regex(&TABLE_CALL, r#"\bTable\(\s*['"](\w+)['"]"#).captures_iter(content) {
+        refs.push(lines.reference(&caps[1], None, RefKind::Schema, "sqlalchemy", caps.get(0).unwrap().start()));
+    }
+}
+
+/// Diesel `table!` definitions, annotated model structs and DSL paths
+fn diesel_refs(lines: &Lines, refs: &mut Vec<TableRefInfo>) {
+    let content = lines.content;
+    static TABLE_MACRO: OnceLock<Regex> = OnceLock::new();
+    static ANNOTATION: OnceLock<Regex> = OnceLock::new();
+    static STRUCT: OnceLock<Regex> = OnceLock::new();
+    static DSL: OnceLock<Regex> = OnceLock::new();
+
+    for caps in regex(&TABLE_MACRO, r"\btable!\s*\{\s*(?:#\[[^\]]*\]\s*)*(?:\w+\s*\.\s*)?(\w+)\s*[({]").captures_iter(content) {
+        let name = caps.get(1).unwrap();
+        refs.push(lines.reference(name.as_str(), None, RefKind::Schema, "diesel", name.start()));
+    }
+
+    let annotation = regex(&ANNOTATION, r#"#\[(?:diesel\(\s*table_name\s*=\s*(?:\w+::)*(\w+)|table_name\s*=\s*"(\w+)")"#);
+    for caps in annotation.captures_iter(content) {
+        let table = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+        let after = caps.get(0).unwrap().end();
+        if let Some(model) = regex(&STRUCT, r"\bstruct\s+(\w+)").captures(&content[after..]) {
+            let model = model.get(1).unwrap();
+            refs.push(lines.reference(table, Some(model.as_str()), RefKind::Model, "diesel", after + model.start()));
+        }
+    }
+
+    for caps in regex(&DSL, r"\b(\w+)::(?:table|dsl)\b").captures_iter(content) {
+        if !DIESEL_NON_TABLES.contains(&&caps[1]) {
+            refs.push(lines.reference(&caps[1], None, RefKind::Query, "diesel", caps.get(0).unwrap().start()));
+        }
+    }
+}
+
+/// Tables named by uppercase SQL keywords
+fn sql_refs(lines: &Lines, refs: &mut Vec<TableRefInfo>) {
+    let content = lines.content;
+    static QUERY: OnceLock<Regex> = OnceLock::new();
+    static SCHEMA: OnceLock<Regex> = OnceLock::new();
+
+    for (cell, pattern, kind) in [
+        (&QUERY, r#"\b(?:FROM|JOIN|INTO|UPDATE)\s+[`"\[]?(?:\w+[`"\]]?\.[`"\[]?)?(\w+)"#, RefKind::Query),
+        (&SCHEMA, r#"\b(?:CREATE|ALTER|DROP)\s+TABLE\s+(?:IF\s+(?:NOT\s+)?EXISTS\s+)?[`"\[]?(?:\w+[`"\]]?\.[`"\[]?)?(\w+)"#, RefKind::Schema),
+    ] {
+        for caps in regex(cell, pattern).captures_iter(content) {
+            let table = &caps[1];
+            if SQL_NON_TABLES.contains(&table) || table.starts_with(|c: char| c.is_ascii_digit()) {
+                continue;
+            }
+            refs.push(lines.reference(table, None, kind, "sql", caps.get(0).unwrap().start()));
+        }
+    }
+}
+
+/// Eloquent's default table for a model: snake case, plural (`UserProfile` -> `user_profiles`)
+fn default_table(model: &str) -> String {
+    let snake = crate::tokens::identifier_words(model).join("_");
+    if let Some(stem) = snake.strip_suffix('y')
+        && !stem.ends_with(['a', 'e', 'i', 'o', 'u'])
+    {
+        format!("{}ies", stem)
+    } else if snake.ends_with(['s', 'x', 'z']) || snake.ends_with("ch") || snake.ends_with("sh") {
+        format!("{}es", snake)
+    } else {
+        format!("{}s", snake)
+    }
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Source text with line lookup by byte offset
+struct Lines<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(content: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { content, starts }
+    }
+
+    fn reference(&self, table: &str, model: Option<&str>, kind: RefKind, framework: &'static str, offset: usize) -> TableRefInfo {
+        let line = self.starts.partition_point(|&start| start <= offset);
+        let start = self.starts[line - 1];
+        let end = self.content[start..].find('\n').map_or(self.content.len(), |i| start + i);
+        TableRefInfo {
+            table: table.to_string(),
+            model: model.map(str::to_string),
+            kind,
+            framework,
+            line_number: line,
+            preview: self.content[start..end].trim().chars().take(MAX_PREVIEW_LENGTH).collect(),
+        }
+    }
+}
+
+/// A stored table reference with its file path
+#[derive(Debug, Clone, Serialize)]
+pub struct TableRef {
+    pub table: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub kind: String,
+    pub framework: String,
+    pub path: String,
+    pub line: usize,
+    pub preview: String,
+}
+
+/// Everything the registry knows about one table
+#[derive(Debug, Clone, Serialize)]
+pub struct TableUsage {
+    /// Table names matched (one, unless spelled in different cases)
+    pub tables: Vec<String>,
+    /// Models bound to the table
+    pub models: Vec<String>,
+    pub refs: Vec<TableRef>,
+}
+
+/// Create the table reference table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and by the
+/// meta.db 6 -> 7 migration. Rows are keyed by path because Prisma schemas
+/// aren't indexed files.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS table_refs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            table_name TEXT NOT NULL,
+            model TEXT,
+            kind TEXT NOT NULL,
+            framework TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            preview TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_table_refs_table ON table_refs(table_name COLLATE NOCASE)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_table_refs_path ON table_refs(path)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Manages table reference storage and lookups
+pub struct TableIndex {
+    cache: CacheManager,
+}
+
+impl TableIndex {
+    /// Create a new table index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for table index")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Replace the stored references of a set of files in one transaction
+    ///
+    /// Files with an empty list are cleared, so removed references disappear
+    /// on reindex. With `framework`, every reference of that framework is
+    /// replaced instead (Prisma schemas are re-read in full on each index).
+    pub fn replace_refs(&self, files: &[(String, Vec<TableRefInfo>)], framework: Option<&str>) -> Result<usize> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+
+        if let Some(framework) = framework {
+            tx.execute("DELETE FROM table_refs WHERE framework = ?", [framework])?;
+        }
+
+        {
+            let mut delete_stmt = tx.prepare("DELETE FROM table_refs WHERE path = ?")?;
+            let mut insert_stmt = tx.prepare(
+                "INSERT INTO table_refs (path, table_name, model, kind, framework, line_number, preview)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)"
+            )?;
+
+            for (path, refs) in files {
+                delete_stmt.execute([path])?;
+                for table_ref in refs {
+                    insert_stmt.execute(rusqlite::params![
+                        path,
+                        table_ref.table,
+                        table_ref.model,
+                        table_ref.kind.as_str(),
+                        table_ref.framework,
+                        table_ref.line_number as i64,
+                        table_ref.preview,
+                    ])?;
+                    inserted += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Stored {} table references for {} files", inserted, files.len());
+        Ok(inserted)
+    }
+
+    /// Resolve a table or model name to the table's references
+    ///
+    /// `name` matches table names case-insensitively and model names exactly;
+    /// a model resolves to its table. References of files no longer in the
+    /// index are left out. Returns None if nothing matches.
+    pub fn resolve(&self, name: &str) -> Result<Option<TableUsage>> {
+        let conn = self.open()?;
+
+        let mut tables_stmt = conn.prepare(
+            "SELECT DISTINCT table_name FROM table_refs
+             WHERE table_name = ?1 COLLATE NOCASE OR model = ?1"
+        )?;
+        let tables: BTreeSet<String> = tables_stmt
+            .query_map([name], |row| row.get::<_, String>(0))?
+            .collect::<Result<_, _>>()?;
+        if tables.is_empty() {
+            return Ok(None);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT table_name, model, kind, framework, path, line_number, preview
+             FROM table_refs
+             WHERE table_name = ?1 COLLATE NOCASE
+               AND (framework = 'prisma' OR path IN (SELECT path FROM files))
+             ORDER BY path, line_number"
+        )?;
+
+        let mut usage = TableUsage { tables: Vec::new(), models: Vec::new(), refs: Vec::new() };
+        let mut models = BTreeSet::new();
+        let mut seen = BTreeSet::new();
+        for table in &tables {
+            if !seen.insert(table.to_lowercase()) {
+                continue;
+            }
+            let refs = stmt.query_map([table], |row| {
+                Ok(TableRef {
+                    table: row.get(0)?,
+                    model: row.get(1)?,
+                    kind: row.get(2)?,
+                    framework: row.get(3)?,
+                    path: row.get(4)?,
+                    line: row.get::<_, i64>(5)? as usize,
+                    preview: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+            models.extend(refs.iter().filter_map(|r| r.model.clone()));
+            usage.refs.extend(refs);
+        }
+
+        usage.tables = usage.refs.iter().map(|r| r.table.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+        usage.models = models.into_iter().collect();
+        Ok(Some(usage))
+    }
+}
+
+/// Regex matching the names of a table and its models as whole words
+///
+/// Used as the search pattern of `rfx query --table NAME` without a pattern.
+pub fn usage_pattern(cache: &CacheManager, name: &str) -> Result<String> {
+    let usage = TableIndex::new(cache.clone()).resolve(name)?.ok_or_else(|| unknown_table(name))?;
+    let names: BTreeSet<String> = usage.tables.iter().chain(&usage.models).map(|n| regex::escape(n)).collect();
+    Ok(format!(r"\b(?:{})\b", names.into_iter().collect::<Vec<_>>().join("|")))
+}
+
+/// Error for a `--table` name the registry doesn't know
+pub fn unknown_table(name: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "No table or model named '{}' was recorded by 'rfx index'.\n\
+         \n\
+         Tables come from ORM models (Eloquent, SQLAlchemy, Django, Diesel, Prisma)\n\
+         and raw SQL (FROM/JOIN/INTO/UPDATE/CREATE TABLE). Re-run 'rfx index' after\n\
+         adding them.",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn summary(refs: &[TableRefInfo]) -> Vec<(&str, Option<&str>, RefKind, &str, usize)> {
+        refs.iter()
+            .map(|r| (r.table.as_str(), r.model.as_deref(), r.kind, r.framework, r.line_number))
+            .collect()
+    }
+
+    #[test]
+    fn test_eloquent_models_and_queries() {
+        let source = "<?php\n\
+class User extends Model\n{\n}\n\
+class Category extends \\Illuminate\\Database\\Eloquent\\Model\n{\n    protected $table = 'taxonomies';\n}\n\
+$rows = DB::table('audit_log')->get();\n\
+Schema::create('orders', function (Blueprint $table) {});\n";
+
+        assert_eq!(summary(&extract(Language::PHP, source)), vec![
+            ("users", Some("User"), RefKind::Model, "eloquent", 2),
+            ("taxonomies", Some("Category"), RefKind::Model, "eloquent", 5),
+            ("audit_log", None, RefKind::Query, "laravel", 9),
+            ("orders", None, RefKind::Schema, "laravel", 10),
+        ]);
+    }
+
+    #[test]
+    fn test_python_models() {
+        let source = "\
+class User(Base):
+    __tablename__ = \"users\"
+
+class Invoice(models.Model):
+    class Meta:
+        db_table = 'billing_invoices'
+
+tags = Table('tags', metadata, Column('id', Integer))
+";
+        assert_eq!(summary(&extract(Language::Python, source)), vec![
+            ("users", Some("User"), RefKind::Model, "sqlalchemy", 2),
+            ("billing_invoices", Some("Invoice"), RefKind::Model, "django", 6),
+            ("tags", None, RefKind::Schema, "sqlalchemy", 8),
+        ]);
+    }
+
+    #[test]
+    fn test_diesel_schema_models_and_dsl() {
+        let source = "\
+diesel::table! {
+    users (id) {
+        id -> Int4,
+    }
+}
+
+#[derive(Queryable)]
+#[diesel(table_name = crate::schema::users)]
+pub struct User {
+    pub id: i32,
+}
+
+fn load(conn: &mut PgConnection) {
+    users::table.load::<User>(conn);
+    diesel::dsl::count_star();
+}
+";
+        assert_eq!(summary(&extract(Language::Rust, source)), vec![
+            ("users", None, RefKind::Schema, "diesel", 2),
+            ("users", Some("User"), RefKind::Model, "diesel", 9),
+            ("users", None, RefKind::Query, "diesel", 14),
+        ]);
+    }
+
+    #[test]
+    fn test_raw_sql_and_prisma() {
+        let source = "\
+from app import db
+rows = db.execute(\"SELECT * FROM public.users u JOIN orders o ON o.user_id = u.id\")
+db.execute('INSERT INTO audit_log (event) VALUES (?)')
+db.execute(\"CREATE TABLE IF NOT EXISTS sessions (id TEXT)\")
+";
+        assert_eq!(summary(&extract(Language::Python, source)), vec![
+            ("orders", None, RefKind::Query, "sql", 2),
+            ("users", None, RefKind::Query, "sql", 2),
+            ("audit_log", None, RefKind::Query, "sql", 3),
+            ("sessions", None, RefKind::Schema, "sql", 4),
+        ]);
+
+        let prisma = "model User {\n  id Int @id\n  @@map(\"users\")\n}\n\nmodel Post {\n  id Int @id\n}\n";
+        assert_eq!(summary(&extract_prisma(prisma)), vec![
+            ("users", Some("User"), RefKind::Model, "prisma", 1),
+            ("Post", Some("Post"), RefKind::Model, "prisma", 6),
+        ]);
+    }
+
+    #[test]
+    fn test_default_table_names() {
+        assert_eq!(default_table("User"), "users");
+        assert_eq!(default_table("UserProfile"), "user_profiles");
+        assert_eq!(default_table("Category"), "categories");
+        assert_eq!(default_table("Address"), "addresses");
+        assert_eq!(default_table("Survey"), "surveys");
+    }
+
+    #[test]
+    fn test_resolve_table_or_model() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+        cache.update_file("app/Models/User.php", "PHP", 10).unwrap();
+        cache.update_file("app/Reports.php", "PHP", 10).unwrap();
+
+        let index = TableIndex::new(cache.clone());
+        index.replace_refs(&[
+            ("app/Models/User.php".to_string(), extract(Language::PHP, "<?php\nclass User extends Model {}\n")),
+            ("app/Reports.php".to_string(), extract(Language::PHP, "<?php\nDB::select('SELECT * FROM USERS');\n")),
+            ("app/Gone.php".to_string(), extract(Language::PHP, "<?php\nDB::table('users');\n")),
+        ], None).unwrap();
+        index.replace_refs(&[("prisma/schema.prisma".to_string(), extract_prisma("model Account {\n}\n"))], Some("prisma")).unwrap();
+
+        let by_table = index.resolve("users").unwrap().unwrap();
+        assert_eq!(by_table.models, vec!["User"]);
+        // app/Gone.php isn't an indexed file
+        let paths: Vec<&str> = by_table.refs.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["app/Models/User.php", "app/Reports.php"]);
+
+        let by_model = index.resolve("User").unwrap().unwrap();
+        assert_eq!(by_model.refs.len(), 2);
+        assert_eq!(usage_pattern(&cache, "User").unwrap(), r"\b(?:USERS|User|users)\b");
+
+        assert_eq!(index.resolve("Account").unwrap().unwrap().refs[0].path, "prisma/schema.prisma");
+        assert!(index.resolve("invoices").unwrap().is_none());
+        assert!(usage_pattern(&cache, "invoices").is_err());
+    }
+}
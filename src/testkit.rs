@@ -0,0 +1,338 @@
+//! Test corpus and golden-output harness for integrators
+//!
+//! Enabled with the `testkit` feature. Editor plugins, MCP clients and other
+//! integrations can use it to check their handling of Reflex results against
+//! stable expected outputs, without maintaining their own fixtures:
+//!
+//! ```no_run
+//! use reflex::testkit::{golden_cases, Corpus};
+//!
+//! // Write the multi-language corpus to a directory and index it
+//! let corpus = Corpus::materialize("/tmp/reflex-corpus").unwrap();
+//!
+//! // Run every golden case through the library and compare
+//! corpus.verify_all().unwrap();
+//!
+//! // Or check your own transport (CLI, HTTP, MCP) case by case:
+//! // run `rfx <args> --json` in the corpus root, then compare
+//! // `GoldenOutput::from_query_response(case.name, &response)` with `case.expected()`
+//! for case in golden_cases() {
+//!     println!("rfx {}", case.cli_args().join(" "));
+//! }
+//! ```
+//!
+//! Golden outputs are normalized (paths relative to the corpus root with `/`
+//! separators, results sorted by location, previews omitted) so they compare
+//! equal across platforms and output formats. They are versioned with
+//! [`GOLDEN_VERSION`], which is bumped whenever expected results change.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::CacheManager;
+use crate::indexer::Indexer;
+use crate::models::{IndexConfig, QueryResponse, SearchResult, Span, SymbolKind};
+use crate::query::{QueryEngine, QueryFilter};
+
+include!(concat!(env!("OUT_DIR"), "/testkit_corpus.rs"));
+
+/// Version of the golden outputs (bumped whenever expected results change)
+pub const GOLDEN_VERSION: u32 = 1;
+
+/// Files of the embedded test corpus as (relative path, contents)
+pub fn corpus_files() -> &'static [(&'static str, &'static [u8])] {
+    CORPUS_FILES
+}
+
+/// An indexed copy of the test corpus on disk
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    root: PathBuf,
+}
+
+impl Corpus {
+    /// Write the corpus into `dir` (created if missing) and index it
+    pub fn materialize(dir: impl AsRef<Path>) -> Result<Self> {
+        let root = dir.as_ref().to_path_buf();
+
+        for (relative, contents) in CORPUS_FILES {
+            let path = root.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let indexer = Indexer::new(CacheManager::new(&root), IndexConfig::default());
+        indexer.index(&root, false).context("Failed to index test corpus")?;
+
+        Ok(Self { root })
+    }
+
+    /// Root directory of the materialized corpus
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Query engine over the corpus index
+    pub fn engine(&self) -> QueryEngine {
+        QueryEngine::new(CacheManager::new(&self.root))
+    }
+
+    /// Run a golden case through the library and normalize the results
+    pub fn run(&self, case: &GoldenCase) -> Result<GoldenOutput> {
+        let results = self
+            .engine()
+            .search(case.pattern, case.filter())
+            .with_context(|| format!("Golden case '{}' failed", case.name))?;
+        Ok(GoldenOutput::from_search_results(case.name, &results))
+    }
+
+    /// Run a golden case and compare it against its expected output
+    pub fn verify(&self, case: &GoldenCase) -> Result<()> {
+        let expected = case.expected()?;
+        let actual = self.run(case)?;
+        let diff = expected.diff(&actual);
+        if !diff.is_empty() {
+            bail!("Golden case '{}' does not match:\n{}", case.name, diff.join("\n"));
+        }
+        Ok(())
+    }
+
+    /// Verify every golden case, reporting all mismatches at once
+    pub fn verify_all(&self) -> Result<()> {
+        let failures: Vec<String> = golden_cases()
+            .iter()
+            .filter_map(|case| self.verify(case).err())
+            .map(|e| e.to_string())
+            .collect();
+
+        if !failures.is_empty() {
+            bail!("{} golden case(s) failed:\n\n{}", failures.len(), failures.join("\n\n"));
+        }
+        Ok(())
+    }
+}
+
+/// A representative query with a versioned expected output
+#[derive(Debug, Clone)]
+pub struct GoldenCase {
+    /// Stable identifier (snake_case)
+    pub name: &'static str,
+    /// What the case exercises
+    pub description: &'static str,
+    pub pattern: &'static str,
+    /// Search symbol definitions only
+    pub symbols: bool,
+    pub kind: Option<SymbolKind>,
+    pub exact: bool,
+    /// File path substring filter
+    pub file: Option<&'static str>,
+    /// Expected output as JSON
+    pub golden_json: &'static str,
+}
+
+impl GoldenCase {
+    /// Query filter equivalent to this case
+    pub fn filter(&self) -> QueryFilter {
+        QueryFilter {
+            symbols_mode: self.symbols || self.kind.is_some(),
+            kinds: self.kind.clone().into_iter().collect(),
+            exact: self.exact,
+            file_pattern: self.file.map(str::to_string),
+            limit: None,
+            force: true,
+            suppress_output: true,
+            ..Default::default()
+        }
+    }
+
+    /// `rfx` arguments equivalent to this case (add `--json` for machine-readable output)
+    pub fn cli_args(&self) -> Vec<String> {
+        let mut args = vec!["query".to_string(), self.pattern.to_string()];
+        if self.symbols {
+            args.push("--symbols".to_string());
+        }
+        if let Some(kind) = &self.kind {
+            args.push("--kind".to_string());
+            args.push(kind.to_string().to_lowercase());
+        }
+        if self.exact {
+            args.push("--exact".to_string());
+        }
+        if let Some(file) = self.file {
+            args.push("--file".to_string());
+            args.push(file.to_string());
+        }
+        args
+    }
+
+    /// Parsed expected output
+    pub fn expected(&self) -> Result<GoldenOutput> {
+        serde_json::from_str(self.golden_json)
+            .with_context(|| format!("Invalid golden output for case '{}'", self.name))
+    }
+}
+
+/// All golden cases, in a stable order
+pub fn golden_cases() -> Vec<GoldenCase> {
+    vec![
+        GoldenCase {
+            name: "rust_struct_exact",
+            description: "Exact struct lookup in a Rust file",
+            pattern: "Point",
+            symbols: true,
+            kind: Some(SymbolKind::Struct),
+            exact: true,
+            file: Some("rust/structs.rs"),
+            golden_json: include_str!("../tests/golden/rust_struct_exact.json"),
+        },
+        GoldenCase {
+            name: "go_method_exact",
+            description: "Pointer receiver method in a Go file",
+            pattern: "Increment",
+            symbols: true,
+            kind: Some(SymbolKind::Method),
+            exact: true,
+            file: Some("go/structs.go"),
+            golden_json: include_str!("../tests/golden/go_method_exact.json"),
+        },
+        GoldenCase {
+            name: "python_method_exact",
+            description: "Method inside a Python class",
+            pattern: "greet",
+            symbols: true,
+            kind: Some(SymbolKind::Method),
+            exact: true,
+            file: Some("python/classes.py"),
+            golden_json: include_str!("../tests/golden/python_method_exact.json"),
+        },
+        GoldenCase {
+            name: "go_fulltext",
+            description: "Full-text search matching a comment and a declaration",
+            pattern: "Increment",
+            symbols: false,
+            kind: None,
+            exact: false,
+            file: Some("go/structs.go"),
+            golden_json: include_str!("../tests/golden/go_fulltext.json"),
+        },
+    ]
+}
+
+/// Normalized result of a golden case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenOutput {
+    pub version: u32,
+    pub case: String,
+    pub results: Vec<GoldenEntry>,
+}
+
+/// A single normalized match
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GoldenEntry {
+    /// Path relative to the corpus root, `/`-separated
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Symbol kind (None for text matches)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl GoldenEntry {
+    fn new(path: &str, span: &Span, kind: &SymbolKind, symbol: Option<&String>, signature: Option<&String>) -> Self {
+        Self {
+            path: normalize_path(path),
+            start_line: span.start_line,
+            end_line: span.end_line,
+            kind: match kind {
+                SymbolKind::Unknown(_) => None,
+                kind => Some(kind.to_string()),
+            },
+            symbol: symbol.cloned(),
+            signature: signature.cloned(),
+        }
+    }
+}
+
+impl GoldenOutput {
+    fn from_entries(case: &str, mut results: Vec<GoldenEntry>) -> Self {
+        results.sort();
+        results.dedup();
+        Self {
+            version: GOLDEN_VERSION,
+            case: case.to_string(),
+            results,
+        }
+    }
+
+    /// Normalize library results
+    pub fn from_search_results(case: &str, results: &[SearchResult]) -> Self {
+        let entries = results
+            .iter()
+            .map(|r| GoldenEntry::new(&r.path, &r.span, &r.kind, r.symbol.as_ref(), r.signature.as_ref()))
+            .collect();
+        Self::from_entries(case, entries)
+    }
+
+    /// Normalize a JSON response from `rfx query --json`, the HTTP API or MCP
+    pub fn from_query_response(case: &str, response: &QueryResponse) -> Self {
+        let entries = response
+            .results
+            .iter()
+            .flat_map(|file| {
+                file.matches.iter().map(move |m| {
+                    GoldenEntry::new(&file.path, &m.span, &m.kind, m.symbol.as_ref(), m.signature.as_ref())
+                })
+            })
+            .collect();
+        Self::from_entries(case, entries)
+    }
+
+    /// Differences from `actual`, one line per entry (empty if they match)
+    pub fn diff(&self, actual: &GoldenOutput) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.version != actual.version {
+            lines.push(format!("  version: expected {}, got {}", self.version, actual.version));
+        }
+        for entry in self.results.iter().filter(|e| !actual.results.contains(e)) {
+            lines.push(format!("  - missing:    {}", describe(entry)));
+        }
+        for entry in actual.results.iter().filter(|e| !self.results.contains(e)) {
+            lines.push(format!("  + unexpected: {}", describe(entry)));
+        }
+        lines
+    }
+
+    /// Pretty JSON in the format of the golden files
+    pub fn to_json_pretty(&self) -> Result<String> {
+        Ok(format!("{}\n", serde_json::to_string_pretty(self)?))
+    }
+}
+
+fn describe(entry: &GoldenEntry) -> String {
+    let mut text = format!("{}:{}-{}", entry.path, entry.start_line, entry.end_line);
+    if let Some(kind) = &entry.kind {
+        text.push_str(&format!(" {}", kind));
+    }
+    if let Some(symbol) = &entry.symbol {
+        text.push_str(&format!(" {}", symbol));
+    }
+    if let Some(signature) = &entry.signature {
+        text.push_str(&format!(" `{}`", signature));
+    }
+    text
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
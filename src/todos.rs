@@ -0,0 +1,342 @@
+//! TODO/FIXME annotation scanner
+//!
+//! This module scans the comments of every indexed file for annotation markers
+//! (`TODO`, `FIXME`, `HACK` and `XXX` by default) and attributes each one to the
+//! innermost symbol enclosing it. It backs the `rfx todos` command.
+//!
+//! Comments are found with the language's [`LineFilter`] in extraction mode, so a
+//! marker in code or a string literal is never reported. Markers are matched
+//! case-sensitively as whole words (`TODO:` and `TODO(ana)` match, `TODOS` does
+//! not). The marker list is configurable:
+//!
+//! ```toml
+//! [todos]
+//! markers = ["TODO", "FIXME", "HACK", "XXX", "SAFETY"]
+//! ```
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML};
+use crate::content_store::ContentReader;
+use crate::line_filter::{self, LineFilter};
+use crate::models::{Language, SearchResult, SymbolKind};
+use crate::parsers::ParserFactory;
+use crate::query::QueryEngine;
+
+/// Maximum length (in characters) of an annotation's text
+const MAX_TEXT_LENGTH: usize = 200;
+
+/// `[todos]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoConfig {
+    /// Markers to look for in comments
+    #[serde(default = "default_markers")]
+    pub markers: Vec<String>,
+}
+
+fn default_markers() -> Vec<String> {
+    ["TODO", "FIXME", "HACK", "XXX"].iter().map(|m| m.to_string()).collect()
+}
+
+impl Default for TodoConfig {
+    fn default() -> Self {
+        Self {
+            markers: default_markers(),
+        }
+    }
+}
+
+/// Load the `[todos]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to the default markers if the file or section is missing.
+pub fn load_todo_config(cache_path: &Path) -> Result<TodoConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(TodoConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("todos") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [todos] section in .reflex/config.toml"),
+        None => Ok(TodoConfig::default()),
+    }
+}
+
+/// Filters for a scan
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    /// Only scan files in this language
+    pub language: Option<Language>,
+    /// Include files matching any of these globs
+    pub glob_patterns: Vec<String>,
+    /// Exclude files matching any of these globs
+    pub exclude_patterns: Vec<String>,
+    /// Markers to report (empty = the configured markers)
+    pub markers: Vec<String>,
+}
+
+/// An annotation found in a comment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TodoItem {
+    pub path: String,
+    /// Line of the comment (1-indexed)
+    pub line: usize,
+    pub marker: String,
+    /// Comment text after the marker
+    pub text: String,
+    /// Innermost symbol enclosing the comment (None at file level)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+/// Scan the index in `cache` for annotations
+///
+/// Returns annotations sorted by path and line.
+pub fn scan(cache: &CacheManager, filter: &TodoFilter) -> Result<Vec<TodoItem>> {
+    let markers = if filter.markers.is_empty() {
+        load_todo_config(cache.path())?.markers
+    } else {
+        filter.markers.clone()
+    };
+    if markers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let content_path = cache.path().join("content.bin");
+    let content_reader = ContentReader::open(&content_path)
+        .context("Failed to open content store")?;
+
+    let include_matcher = build_glob_matcher(&filter.glob_patterns);
+    let exclude_matcher = build_glob_matcher(&filter.exclude_patterns);
+
+    let mut files: Vec<(String, Language, &str)> = Vec::new();
+    for file_id in 0..content_reader.file_count() {
+        let file_path = match content_reader.get_file_path(file_id as u32) {
+            Some(p) => p,
+            None => continue,
+        };
+        let lang = detect_language(file_path);
+        if lang == Language::Unknown || filter.language.is_some_and(|l| l != lang) {
+            continue;
+        }
+
+        let path_str = file_path.to_string_lossy().to_string();
+        let included = include_matcher.as_ref().is_none_or(|m| m.is_match(&path_str));
+        let excluded = exclude_matcher.as_ref().is_some_and(|m| m.is_match(&path_str));
+        if !included || excluded {
+            continue;
+        }
+
+        match content_reader.get_file_content(file_id as u32) {
+            Ok(content) => files.push((path_str, lang, content)),
+            Err(e) => log::warn!("Skipping {}: {}", path_str, e),
+        }
+    }
+
+    let mut items: Vec<TodoItem> = files
+        .par_iter()
+        .filter(|(_, _, content)| markers.iter().any(|m| content.contains(m.as_str())))
+        .flat_map_iter(|(path, lang, content)| scan_file(path, content, *lang, &markers))
+        .collect();
+    items.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+
+    Ok(items)
+}
+
+/// Find annotations in a single file and attribute them to their symbols
+fn scan_file(path: &str, source: &str, language: Language, markers: &[String]) -> Vec<TodoItem> {
+    let Some(filter) = line_filter::get_filter(language) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<TodoItem> = find_annotations(source, filter.as_ref(), markers)
+        .into_iter()
+        .map(|(line, marker, text)| TodoItem {
+            path: path.to_string(),
+            line,
+            marker,
+            text,
+            symbol: None,
+        })
+        .collect();
+    if items.is_empty() {
+        return items;
+    }
+
+    match ParserFactory::parse_guarded(path, source, language) {
+        Ok(symbols) => {
+            for item in &mut items {
+                item.symbol = enclosing_symbol(&symbols, item.line);
+            }
+        }
+        Err(e) => log::debug!("Failed to parse {} for symbol attribution: {}", path, e),
+    }
+
+    items
+}
+
+/// Find marker comments in a source file
+///
+/// Returns (line number, marker, text) tuples; at most one per line (the marker
+/// that appears first in the comment).
+fn find_annotations(source: &str, filter: &dyn LineFilter, markers: &[String]) -> Vec<(usize, String, String)> {
+    let mut found = Vec::new();
+
+    for (idx, line) in source.lines().enumerate() {
+        if !markers.iter().any(|m| line.contains(m.as_str())) {
+            continue;
+        }
+        let Some(comment) = filter.extract_comment(line) else {
+            continue;
+        };
+
+        let first = markers.iter()
+            .filter_map(|marker| find_marker(comment, marker).map(|pos| (pos, marker)))
+            .min_by_key(|(pos, marker)| (*pos, std::cmp::Reverse(marker.len())));
+        let Some((pos, marker)) = first else {
+            continue;
+        };
+
+        let text = comment[pos + marker.len()..]
+            .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+            .chars()
+            .take(MAX_TEXT_LENGTH)
+            .collect();
+        found.push((idx + 1, marker.clone(), text));
+    }
+
+    found
+}
+
+/// Byte position of the first whole-word occurrence of `marker` in `text`
+fn find_marker(text: &str, marker: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    text.match_indices(marker)
+        .map(|(pos, _)| pos)
+        .find(|&pos| {
+            let before = text[..pos].chars().next_back();
+            let after = text[pos + marker.len()..].chars().next();
+            !before.is_some_and(is_word) && !after.is_some_and(is_word)
+        })
+}
+
+/// Name of the innermost symbol whose span contains `line`
+///
+/// Variables, imports and other leaf declarations are skipped so an annotation
+/// on a `let` line is attributed to the surrounding function.
+fn enclosing_symbol(symbols: &[SearchResult], line: usize) -> Option<String> {
+    symbols
+        .iter()
+        .filter(|s| !matches!(
+            s.kind,
+            SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Property
+                | SymbolKind::Import | SymbolKind::Export | SymbolKind::Attribute
+        ))
+        .filter(|s| s.span.start_line <= line && line <= s.span.end_line)
+        .min_by_key(|s| s.span.end_line - s.span.start_line)
+        .and_then(|s| s.symbol.clone())
+}
+
+fn detect_language(path: &Path) -> Language {
+    Language::from_path(path)
+}
+
+fn build_glob_matcher(patterns: &[String]) -> Option<globset::GlobSet> {
+    use globset::{Glob, GlobSetBuilder};
+
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = QueryEngine::normalize_glob_pattern(pattern);
+        if let Ok(glob) = Glob::new(&normalized) {
+            builder.add(glob);
+        }
+    }
+    builder.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn markers() -> Vec<String> {
+        TodoConfig::default().markers
+    }
+
+    #[test]
+    fn test_find_annotations_only_in_comments() {
+        let source = r#"
+// TODO: split this module
+fn main() {
+    let s = "TODO: not a comment";
+    let todo_count = 0; // FIXME(ana) off by one
+    // TODOS are not markers, nor is XXXL
+    /* HACK */
+}
+"#;
+        let filter = line_filter::get_filter(Language::Rust).unwrap();
+        let found = find_annotations(source, filter.as_ref(), &markers());
+
+        assert_eq!(found, vec![
+            (2, "TODO".to_string(), "split this module".to_string()),
+            (5, "FIXME".to_string(), "(ana) off by one".to_string()),
+            (7, "HACK".to_string(), String::new()),
+        ]);
+    }
+
+    #[test]
+    fn test_find_marker_whole_word() {
+        assert_eq!(find_marker("TODO: x", "TODO"), Some(0));
+        assert_eq!(find_marker("see TODOS, then TODO", "TODO"), Some(16));
+        assert_eq!(find_marker("MYTODO", "TODO"), None);
+    }
+
+    #[test]
+    fn test_scan_attributes_enclosing_symbol() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir(&root).unwrap();
+        fs::write(
+            root.join("billing.py"),
+            "# XXX: module-level note\n\ndef charge(n):\n    # TODO: handle refunds\n    return n\n",
+        ).unwrap();
+
+        let cache = CacheManager::new(&root);
+        let indexer = Indexer::new(cache, IndexConfig::default());
+        indexer.index(&root, false).unwrap();
+
+        let cache = CacheManager::new(&root);
+        let items = scan(&cache, &TodoFilter::default()).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!((items[0].line, items[0].marker.as_str()), (1, "XXX"));
+        assert_eq!(items[0].symbol, None);
+        assert_eq!((items[1].line, items[1].marker.as_str()), (4, "TODO"));
+        assert_eq!(items[1].text, "handle refunds");
+        assert_eq!(items[1].symbol.as_deref(), Some("charge"));
+
+        let fixmes = scan(&cache, &TodoFilter {
+            markers: vec!["FIXME".to_string()],
+            ..Default::default()
+        }).unwrap();
+        assert!(fixmes.is_empty());
+    }
+}
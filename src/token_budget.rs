@@ -0,0 +1,236 @@
+//! Token budgets for JSON query output (`--max-tokens`)
+//!
+//! Agents pass a budget so a single response can't overflow their context
+//! window. Sizes are estimated from the serialized JSON at ~4 characters per
+//! token. A response over budget is shrunk in stages, each only if the
+//! previous one wasn't enough:
+//!
+//! 1. drop context lines around matches
+//! 2. shorten previews
+//! 3. drop matches, plain text matches before symbol definitions, later
+//!    matches before earlier ones
+//!
+//! The response is then marked `truncated` so callers know to narrow the query
+//! or page with `--offset`.
+
+use serde::Serialize;
+
+use crate::models::QueryResponse;
+
+/// Rough characters-per-token ratio used for estimates
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Preview length (characters) used once previews have to be shortened
+const SHORT_PREVIEW_LENGTH: usize = 60;
+
+/// Approximate token count of `value` serialized as JSON
+pub fn estimate_tokens<T: Serialize>(value: &T) -> usize {
+    serde_json::to_string(value)
+        .map(|s| s.len().div_ceil(CHARS_PER_TOKEN))
+        .unwrap_or(0)
+}
+
+/// Shrink `response` until it fits in `max_tokens`
+///
+/// Returns true if anything was removed. `truncated` is set and
+/// `pagination.count` updated to the matches that remain. With `explain`,
+/// guidance for agents is put in front of `ai_instruction`; it counts
+/// against the budget like everything else.
+pub fn fit_response(response: &mut QueryResponse, max_tokens: usize, explain: bool) -> bool {
+    if estimate_tokens(response) <= max_tokens {
+        return false;
+    }
+    response.truncated = true;
+
+    let original_instruction = response.ai_instruction.take();
+    let set_instruction = |response: &mut QueryResponse| {
+        if explain {
+            let guidance = truncation_instruction(response, max_tokens);
+            response.ai_instruction = Some(match &original_instruction {
+                Some(existing) => format!("{} {}", guidance, existing),
+                None => guidance,
+            });
+        } else {
+            response.ai_instruction = original_instruction.clone();
+        }
+    };
+    set_instruction(response);
+
+    for m in response.results.iter_mut().flat_map(|g| g.matches.iter_mut()) {
+        m.context_before.clear();
+        m.context_after.clear();
+    }
+    if estimate_tokens(response) <= max_tokens {
+        set_instruction(response);
+        return true;
+    }
+
+    for m in response.results.iter_mut().flat_map(|g| g.matches.iter_mut()) {
+        m.preview = crate::cli::truncate_preview(&m.preview, SHORT_PREVIEW_LENGTH);
+        m.doc = None;
+    }
+    if estimate_tokens(response) > max_tokens {
+        drop_matches(response, max_tokens);
+    }
+
+    response.pagination.count = response.results.iter().map(|g| g.matches.len()).sum();
+    response.pagination.has_more = response.pagination.has_more || response.pagination.count < response.pagination.total;
+    set_instruction(response);
+    true
+}
+
+/// Keep the highest-priority matches that fit, in their original order
+fn drop_matches(response: &mut QueryResponse, max_tokens: usize) {
+    // Symbol definitions outrank plain text matches; otherwise keep result order
+    let mut ranked: Vec<(usize, usize)> = response.results
+        .iter()
+        .enumerate()
+        .flat_map(|(g, group)| (0..group.matches.len()).map(move |m| (g, m)))
+        .collect();
+    ranked.sort_by_key(|&(g, m)| response.results[g].matches[m].symbol.is_none());
+
+    let all = std::mem::take(&mut response.results);
+    let keep_first = |n: usize| {
+        let kept: std::collections::HashSet<(usize, usize)> = ranked[..n].iter().copied().collect();
+        all.iter()
+            .enumerate()
+            .filter_map(|(g, group)| {
+                let matches: Vec<_> = group.matches
+                    .iter()
+                    .enumerate()
+                    .filter(|(m, _)| kept.contains(&(g, *m)))
+                    .map(|(_, m)| m.clone())
+                    .collect();
+                (!matches.is_empty()).then(|| crate::models::FileGroupedResult {
+                    matches,
+                    ..group.clone()
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Largest number of matches that still fits (size grows with the count)
+    let (mut lo, mut hi) = (0, ranked.len());
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        response.results = keep_first(mid);
+        if estimate_tokens(response) <= max_tokens {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    response.results = keep_first(lo);
+}
+
+/// Guidance for agents about a truncated response
+fn truncation_instruction(response: &QueryResponse, max_tokens: usize) -> String {
+    format!(
+        "Output was trimmed to fit the {}-token budget: showing {} of {} results, with context lines and long previews cut. \
+         Don't assume these are all matches. Narrow the query (lang, kind, glob) or page with offset to see the rest.",
+        max_tokens, response.pagination.count, response.pagination.total
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FileGroupedResult, IndexStatus, MatchResult, PaginationInfo, Span, SymbolKind};
+
+    fn m(line: usize, symbol: Option<&str>) -> MatchResult {
+        MatchResult {
+            kind: SymbolKind::Function,
+            symbol: symbol.map(str::to_string),
+            parent: None,
+            qualified_name: None,
+            signature: None,
+            doc: None,
+            exported: None,
+            span: Span::new(line, 0, line, 0),
+            preview: format!("{} let value = compute_something_rather_long(argument_one, argument_two);", line),
+            context_before: vec!["// before".repeat(10)],
+            context_after: vec!["// after".repeat(10)],
+            match_reason: None,
+            cell: None,
+            captures: None,
+            blame: None,
+        }
+    }
+
+    fn response() -> QueryResponse {
+        let results: Vec<FileGroupedResult> = (0..10)
+            .map(|i| FileGroupedResult {
+                path: format!("src/file_{}.rs", i),
+                dependencies: None,
+                related_files: None,
+                transform: None,
+                owners: None,
+                repo: None,
+                matches: vec![m(1, None), m(2, if i == 7 { Some("target") } else { None })],
+            })
+            .collect();
+        QueryResponse {
+            ai_instruction: None,
+            status: IndexStatus::Fresh,
+            can_trust_results: true,
+            warning: None,
+            pagination: PaginationInfo { total: 20, count: 20, offset: 0, limit: None, has_more: false },
+            complete: true,
+            unscanned_candidates: 0,
+            partial: None,
+            truncated: false,
+            results,
+            deleted: None,
+            warnings: Vec::new(),
+            plan: None,
+            timings: None,
+        }
+    }
+
+    #[test]
+    fn test_within_budget_is_untouched() {
+        let mut resp = response();
+        assert!(!fit_response(&mut resp, 100_000, false));
+        assert!(!resp.truncated);
+        assert_eq!(resp.results[0].matches[0].context_before.len(), 1);
+    }
+
+    #[test]
+    fn test_context_dropped_first() {
+        let mut resp = response();
+        let full = estimate_tokens(&resp);
+        let without_context = {
+            let mut r = response();
+            r.results.iter_mut().flat_map(|g| g.matches.iter_mut()).for_each(|m| {
+                m.context_before.clear();
+                m.context_after.clear();
+            });
+            estimate_tokens(&r)
+        };
+        assert!(without_context < full);
+
+        assert!(fit_response(&mut resp, without_context + 10, false));
+        assert!(resp.truncated);
+        assert_eq!(resp.pagination.count, 20);
+        assert!(resp.results[0].matches[0].context_before.is_empty());
+    }
+
+    #[test]
+    fn test_drops_text_matches_before_definitions() {
+        let mut resp = response();
+        assert!(fit_response(&mut resp, 450, true));
+
+        assert!(estimate_tokens(&resp) <= 450);
+        assert!(resp.ai_instruction.as_deref().unwrap().starts_with("Output was trimmed"));
+        assert!(resp.pagination.count < 20);
+        assert!(resp.pagination.has_more);
+        // The only definition survives
+        let symbols: Vec<_> = resp.results.iter().flat_map(|g| &g.matches).filter_map(|m| m.symbol.as_deref()).collect();
+        assert_eq!(symbols, vec!["target"]);
+        // Original order is kept
+        let paths: Vec<_> = resp.results.iter().map(|g| g.path.clone()).collect();
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(paths, sorted);
+    }
+}
@@ -0,0 +1,314 @@
+//! Identifier-token index
+//!
+//! Trigrams find literal substrings, so `user config` never matches
+//! `parseUserConfig` or `load_user_config`. While indexing, every identifier is
+//! split into lowercase word parts (camelCase humps, `_`/`-` separators, digit
+//! runs) and each part is mapped to the lines it occurs on. The index is written
+//! to `tokens.bin` next to `trigrams.bin` and uses the same file ids as the
+//! content store.
+//!
+//! `rfx query "user config" --tokens` intersects the postings of each query word
+//! and then keeps only lines holding a single identifier that contains every
+//! word, whatever naming convention it uses.
+//!
+//! # Binary format
+//!
+//! `magic(4) + version(4) + num_files(4)`, followed by a zstd stream of
+//! `num_tokens`, then per token `len + bytes + num_postings + postings`, all as
+//! varints. Postings are `(file_id delta, line_no)` pairs sorted by file and line.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::trigram::{read_varint, write_varint};
+
+pub(crate) const MAGIC: &[u8; 4] = b"RFTK"; // ReFlex ToKens
+pub(crate) const VERSION: u32 = 1;
+// Header: magic(4) + version(4) + num_files(4) = 12 bytes
+const HEADER_SIZE: usize = 12;
+
+/// Lowercase words of an identifier, split on separators and camelCase humps
+///
+/// `getUserByID`, `get_user_by_id` and `GetUserById` all yield `get user by id`.
+pub fn identifier_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let boundary = match i.checked_sub(1).map(|p| chars[p]) {
+            Some(prev) if prev.is_alphanumeric() => {
+                let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                // userBy, HTTPServer (P starts "Server"), utf8 / v2
+                (c.is_uppercase() && prev.is_lowercase())
+                    || (c.is_uppercase() && prev.is_uppercase() && next_is_lower)
+                    || (c.is_ascii_digit() != prev.is_ascii_digit())
+            }
+            _ => false,
+        };
+
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// Identifiers on a line: runs of letters, digits and `_` not starting with a digit
+pub fn identifiers(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|run| run.chars().next().is_some_and(|c| !c.is_ascii_digit()))
+}
+
+/// Whether `identifier` contains every one of `words` (as whole word parts)
+pub fn has_all_words(identifier: &str, words: &[String]) -> bool {
+    let parts = identifier_words(identifier);
+    words.iter().all(|word| parts.contains(word))
+}
+
+/// Inverted index from identifier word parts to the lines they occur on
+#[derive(Default)]
+pub struct TokenIndex {
+    /// Word part → (file_id, line_no) postings, sorted and deduplicated after `finalize`
+    postings: HashMap<String, Vec<(u32, u32)>>,
+    num_files: u32,
+}
+
+impl TokenIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of files the index covers (must match the content store)
+    pub fn file_count(&self) -> usize {
+        self.num_files as usize
+    }
+
+    /// Number of distinct word parts
+    pub fn token_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Record the identifier word parts of a file (`file_id` from the content store)
+    pub fn index_file(&mut self, file_id: u32, content: &str) {
+        self.num_files = self.num_files.max(file_id + 1);
+
+        for (line_idx, line) in content.lines().enumerate() {
+            let line_no = (line_idx + 1) as u32;
+
+            for identifier in identifiers(line) {
+                for word in identifier_words(identifier) {
+                    match self.postings.get_mut(&word) {
+                        // Lines are visited in order, so a repeat is always the last entry
+                        Some(list) => {
+                            if list.last() != Some(&(file_id, line_no)) {
+                                list.push((file_id, line_no));
+                            }
+                        }
+                        None => {
+                            self.postings.insert(word, vec![(file_id, line_no)]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sort and deduplicate posting lists (call once after the last `index_file`)
+    pub fn finalize(&mut self) {
+        for list in self.postings.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+    }
+
+    /// Lines containing every word part (candidates; identifiers aren't checked yet)
+    pub fn search(&self, words: &[String]) -> Vec<(u32, u32)> {
+        let mut lists: Vec<&Vec<(u32, u32)>> = Vec::with_capacity(words.len());
+        for word in words {
+            match self.postings.get(word) {
+                Some(list) => lists.push(list),
+                None => return Vec::new(),
+            }
+        }
+
+        // Intersect starting from the rarest word
+        lists.sort_by_key(|list| list.len());
+        let Some((first, rest)) = lists.split_first() else {
+            return Vec::new();
+        };
+
+        first
+            .iter()
+            .filter(|loc| rest.iter().all(|list| list.binary_search(*loc).is_ok()))
+            .copied()
+            .collect()
+    }
+
+    /// Write the index to disk (see the module docs for the format)
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+
+        // Sorted for byte-identical output across runs
+        let mut tokens: Vec<(&String, &Vec<(u32, u32)>)> = self.postings.iter().collect();
+        tokens.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut body = Vec::new();
+        write_varint(&mut body, tokens.len() as u32)?;
+        for (token, list) in tokens {
+            write_varint(&mut body, token.len() as u32)?;
+            body.write_all(token.as_bytes())?;
+            write_varint(&mut body, list.len() as u32)?;
+
+            let mut prev_file_id = 0u32;
+            for (file_id, line_no) in list {
+                write_varint(&mut body, file_id - prev_file_id)?;
+                write_varint(&mut body, *line_no)?;
+                prev_file_id = *file_id;
+            }
+        }
+
+        let compressed = zstd::encode_all(body.as_slice(), 3)
+            .context("Failed to compress token index")?;
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + compressed.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&VERSION.to_le_bytes());
+        data.extend_from_slice(&self.num_files.to_le_bytes());
+        data.extend_from_slice(&compressed);
+
+        std::fs::write(path, data)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        log::info!("Wrote token index: {} tokens, {} files to {:?}",
+                   self.postings.len(), self.num_files, path);
+
+        Ok(())
+    }
+
+    /// Load an index written by [`TokenIndex::write`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        if data.len() < HEADER_SIZE {
+            anyhow::bail!("tokens.bin too small (expected at least {} bytes)", HEADER_SIZE);
+        }
+        if &data[0..4] != MAGIC {
+            anyhow::bail!("Invalid tokens.bin (wrong magic bytes)");
+        }
+
+        let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if version != VERSION {
+            anyhow::bail!("Unsupported tokens.bin version {} (expected {})", version, VERSION);
+        }
+        let num_files = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+
+        let body = zstd::decode_all(&data[HEADER_SIZE..])
+            .context("Failed to decompress tokens.bin")?;
+
+        let mut pos = 0;
+        let num_tokens = next_varint(&body, &mut pos)? as usize;
+        let mut postings = HashMap::with_capacity(num_tokens);
+
+        for _ in 0..num_tokens {
+            let len = next_varint(&body, &mut pos)? as usize;
+            let bytes = body.get(pos..pos + len)
+                .context("Truncated token in tokens.bin")?;
+            let token = String::from_utf8(bytes.to_vec())
+                .context("Invalid token in tokens.bin")?;
+            pos += len;
+
+            let count = next_varint(&body, &mut pos)? as usize;
+            let mut list = Vec::with_capacity(count);
+            let mut file_id = 0u32;
+            for _ in 0..count {
+                file_id += next_varint(&body, &mut pos)?;
+                let line_no = next_varint(&body, &mut pos)?;
+                list.push((file_id, line_no));
+            }
+
+            postings.insert(token, list);
+        }
+
+        Ok(Self { postings, num_files })
+    }
+}
+
+/// Read the varint at `pos` and advance past it
+fn next_varint(data: &[u8], pos: &mut usize) -> Result<u32> {
+    let (value, used) = read_varint(data.get(*pos..).unwrap_or_default())?;
+    *pos += used;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn words(query: &str) -> Vec<String> {
+        identifier_words(query)
+    }
+
+    #[test]
+    fn test_identifier_words() {
+        assert_eq!(identifier_words("getUserByID"), vec!["get", "user", "by", "id"]);
+        assert_eq!(identifier_words("get_user_by_id"), vec!["get", "user", "by", "id"]);
+        assert_eq!(identifier_words("HTTPServer"), vec!["http", "server"]);
+        assert_eq!(identifier_words("utf8-decode"), vec!["utf", "8", "decode"]);
+    }
+
+    #[test]
+    fn test_identifiers_and_word_match() {
+        let line = "let cfg = parseUserConfig(user, 42, config_2);";
+        let found: Vec<&str> = identifiers(line).collect();
+        assert_eq!(found, vec!["let", "cfg", "parseUserConfig", "user", "config_2"]);
+
+        assert!(has_all_words("parseUserConfig", &words("user config")));
+        assert!(has_all_words("load_user_config", &words("config user")));
+        assert!(!has_all_words("user", &words("user config")));
+        // Whole word parts only: "use" isn't a part of "parseUserConfig"
+        assert!(!has_all_words("parseUserConfig", &words("use")));
+    }
+
+    #[test]
+    fn test_search_write_and_load() {
+        let mut index = TokenIndex::new();
+        index.index_file(0, "fn parseUserConfig() {}\nlet user = config;\n");
+        index.index_file(1, "def load_user_config():\n    pass\n");
+        index.finalize();
+
+        let candidates = index.search(&words("user config"));
+        assert_eq!(candidates, vec![(0, 1), (0, 2), (1, 1)]);
+        assert!(index.search(&words("user missing")).is_empty());
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("tokens.bin");
+        index.write(&path).unwrap();
+
+        let loaded = TokenIndex::load(&path).unwrap();
+        assert_eq!(loaded.file_count(), 2);
+        assert_eq!(loaded.token_count(), index.token_count());
+        assert_eq!(loaded.search(&words("UserConfig")), candidates);
+
+        std::fs::write(&path, b"RFTGxxxxxxxxxxxx").unwrap();
+        assert!(TokenIndex::load(&path).is_err());
+    }
+}
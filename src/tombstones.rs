@@ -0,0 +1,433 @@
+//! Tombstones for files removed from the index
+//!
+//! Compaction drops files that no longer exist on disk, and with them every
+//! trace of their symbols. With tombstones enabled, compaction first records
+//! each removed file's path, last content hash, deletion time and last known
+//! symbols in `meta.db`, so "where did this function go?" gets a pointer to the
+//! deletion instead of zero results.
+//!
+//! ```toml
+//! [tombstones]
+//! enabled = true
+//! retention_days = 90  # Forget tombstones older than this (0 = keep forever)
+//! ```
+//!
+//! Tombstones are listed with `rfx deleted list` and merged into query output
+//! with `rfx query --include-deleted`. A tombstone is hidden once a file is
+//! indexed at the same path again.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+use crate::content_store::ContentReader;
+use crate::models::{Language, SearchResult, SymbolKind};
+
+/// Default number of days a tombstone is kept
+const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+/// `[tombstones]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TombstoneConfig {
+    /// Record tombstones when compaction removes deleted files
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days to keep tombstones (0 = forever)
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u64,
+}
+
+fn default_retention_days() -> u64 {
+    DEFAULT_RETENTION_DAYS
+}
+
+impl Default for TombstoneConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// Load the `[tombstones]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults (disabled) if the file or section is missing.
+pub fn load_tombstone_config(cache_path: &Path) -> Result<TombstoneConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(TombstoneConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("tombstones") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [tombstones] section in .reflex/config.toml"),
+        None => Ok(TombstoneConfig::default()),
+    }
+}
+
+/// A symbol defined in a file at the time it was deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TombstoneSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Line of the definition (1-indexed)
+    pub line: usize,
+}
+
+/// Metadata retained for a file removed from the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub path: String,
+    pub language: String,
+    /// Content hash of the last indexed version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_hash: Option<String>,
+    /// Unix timestamp of the compaction that removed the file
+    pub deleted_at: i64,
+    /// Symbols defined in the last indexed version (only matching ones for query results)
+    pub symbols: Vec<TombstoneSymbol>,
+}
+
+/// Create the tombstone table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and lazily by
+/// `TombstoneIndex` so caches created before tombstones keep working.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tombstones (
+            path TEXT PRIMARY KEY,
+            language TEXT NOT NULL,
+            last_hash TEXT,
+            deleted_at INTEGER NOT NULL,
+            symbols_json TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tombstones_deleted_at ON tombstones(deleted_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Manages tombstone storage and lookups
+pub struct TombstoneIndex {
+    cache: CacheManager,
+}
+
+impl TombstoneIndex {
+    /// Create a new tombstone index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for tombstones")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Record tombstones for files about to be removed from the index
+    ///
+    /// Must run before the files are deleted: symbols come from the symbol
+    /// cache (which cascades with the file rows) or are parsed from the
+    /// content store. Returns the number of tombstones written.
+    pub fn record(&self, file_ids: &[i64]) -> Result<usize> {
+        if file_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut conn = self.open()?;
+        let now = chrono::Utc::now().timestamp();
+
+        // (file_id, path, language, last hash) of every file being removed
+        let mut files: Vec<(i64, String, String, Option<String>)> = Vec::with_capacity(file_ids.len());
+        {
+            let mut file_stmt = conn.prepare("SELECT path, language FROM files WHERE id = ?")?;
+            let mut hash_stmt = conn.prepare(
+                "SELECT hash FROM file_branches WHERE file_id = ? ORDER BY last_indexed DESC LIMIT 1"
+            )?;
+
+            for &file_id in file_ids {
+                let Some((path, language)) = file_stmt
+                    .query_row([file_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+                    .optional()?
+                else {
+                    continue;
+                };
+                let hash: Option<String> = hash_stmt
+                    .query_row([file_id], |row| row.get(0))
+                    .optional()?;
+                files.push((file_id, path, language, hash));
+            }
+        }
+
+        let symbols_by_file = self.last_symbols(&files)?;
+
+        let tx = conn.transaction()?;
+        {
+            let mut insert_stmt = tx.prepare(
+                "INSERT OR REPLACE INTO tombstones (path, language, last_hash, deleted_at, symbols_json)
+                 VALUES (?, ?, ?, ?, ?)"
+            )?;
+
+            for (file_id, path, language, hash) in &files {
+                let symbols = symbols_by_file.get(file_id).map(Vec::as_slice).unwrap_or(&[]);
+                insert_stmt.execute(rusqlite::params![
+                    path,
+                    language,
+                    hash,
+                    now,
+                    serde_json::to_string(symbols)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        log::info!("Recorded {} tombstones", files.len());
+        Ok(files.len())
+    }
+
+    /// Last known symbols of files being removed, keyed by file_id
+    fn last_symbols(
+        &self,
+        files: &[(i64, String, String, Option<String>)],
+    ) -> Result<std::collections::HashMap<i64, Vec<TombstoneSymbol>>> {
+        let lookups: Vec<(i64, String, String)> = files
+            .iter()
+            .filter_map(|(id, path, _, hash)| Some((*id, hash.clone()?, path.clone())))
+            .collect();
+
+        let symbol_cache = crate::symbol_cache::SymbolCache::open(self.cache.path())?;
+        let mut cached = symbol_cache.batch_get_with_kind(&lookups, None)?;
+
+        // The content store still holds removed files until the next full index
+        let content_reader = ContentReader::open(self.cache.path().join("content.bin")).ok();
+        let quarantine = crate::quarantine::Quarantine::open(self.cache.path()).ok();
+        let root = self.cache.workspace_root();
+
+        let mut symbols_by_file = std::collections::HashMap::new();
+        for (file_id, path, _, hash) in files {
+            let symbols = match cached.remove(file_id) {
+                Some(symbols) => symbols,
+                None => parse_symbols(content_reader.as_ref(), quarantine.as_ref(), &root, path, hash.as_deref()),
+            };
+            symbols_by_file.insert(*file_id, to_tombstone_symbols(&symbols));
+        }
+
+        Ok(symbols_by_file)
+    }
+
+    /// All live tombstones (no file indexed at the same path), newest first
+    ///
+    /// `path_filter` keeps tombstones whose path contains the given text.
+    pub fn list(&self, path_filter: Option<&str>) -> Result<Vec<Tombstone>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT path, language, last_hash, deleted_at, symbols_json
+             FROM tombstones
+             WHERE path NOT IN (SELECT path FROM files)
+             ORDER BY deleted_at DESC, path"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut tombstones = Vec::new();
+        for row in rows {
+            let (path, language, last_hash, deleted_at, symbols_json) = row?;
+            if path_filter.is_some_and(|filter| !path.contains(filter)) {
+                continue;
+            }
+            let symbols = serde_json::from_str(&symbols_json).unwrap_or_else(|e| {
+                log::warn!("Corrupt tombstone symbols for {}: {}", path, e);
+                Vec::new()
+            });
+            tombstones.push(Tombstone { path, language, last_hash, deleted_at, symbols });
+        }
+
+        Ok(tombstones)
+    }
+
+    /// Tombstones whose path or symbols match a query pattern
+    ///
+    /// Symbols match by name (exactly with `exact`, otherwise as a substring).
+    /// Only matching symbols are returned; a tombstone matched by path alone
+    /// keeps none.
+    pub fn find(&self, pattern: &str, exact: bool) -> Result<Vec<Tombstone>> {
+        let mut matches = Vec::new();
+
+        for mut tombstone in self.list(None)? {
+            let path_match = !exact && tombstone.path.contains(pattern);
+            tombstone.symbols.retain(|s| {
+                if exact { s.name == pattern } else { s.name.contains(pattern) }
+            });
+            if path_match || !tombstone.symbols.is_empty() {
+                matches.push(tombstone);
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Remove tombstones older than `retention_days` (0 = keep forever)
+    pub fn prune(&self, retention_days: u64) -> Result<usize> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+
+        let conn = self.open()?;
+        let cutoff = chrono::Utc::now().timestamp() - (retention_days as i64) * 86_400;
+        let removed = conn.execute("DELETE FROM tombstones WHERE deleted_at < ?", [cutoff])?;
+
+        if removed > 0 {
+            log::info!("Pruned {} tombstones older than {} days", removed, retention_days);
+        }
+        Ok(removed)
+    }
+}
+
+fn parse_symbols(
+    content_reader: Option<&ContentReader>,
+    quarantine: Option<&crate::quarantine::Quarantine>,
+    root: &Path,
+    path: &str,
+    hash: Option<&str>,
+) -> Vec<SearchResult> {
+    let (Some(reader), Some(quarantine)) = (content_reader, quarantine) else {
+        return Vec::new();
+    };
+    // The content store keeps paths as given to the indexer, which may be absolute
+    let file_id = reader
+        .file_id_for_path(path)
+        .or_else(|| reader.file_id_for_path(&root.join(path).to_string_lossy()));
+    let Some(content) = file_id.and_then(|id| reader.get_file_content(id).ok()) else {
+        return Vec::new();
+    };
+
+    let language = Language::from_path(Path::new(path));
+    if !language.is_supported() {
+        return Vec::new();
+    }
+
+    quarantine.parse(path, hash, content, language).unwrap_or_else(|e| {
+        log::debug!("No tombstone symbols for {}: {}", path, e);
+        Vec::new()
+    })
+}
+
+fn to_tombstone_symbols(symbols: &[SearchResult]) -> Vec<TombstoneSymbol> {
+    symbols
+        .iter()
+        .filter_map(|s| {
+            Some(TombstoneSymbol {
+                name: s.symbol.clone()?,
+                kind: s.kind.clone(),
+                line: s.span.start_line,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn enable_tombstones(cache: &CacheManager) {
+        fs::write(cache.path().join(CONFIG_TOML), "[tombstones]\nenabled = true\nretention_days = 90\n").unwrap();
+    }
+
+    #[test]
+    fn test_load_tombstone_config() {
+        let temp = TempDir::new().unwrap();
+        let config = load_tombstone_config(temp.path()).unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.retention_days, DEFAULT_RETENTION_DAYS);
+
+        fs::write(temp.path().join(CONFIG_TOML), "[tombstones]\nenabled = true\nretention_days = 7\n").unwrap();
+        let config = load_tombstone_config(temp.path()).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.retention_days, 7);
+    }
+
+    #[test]
+    fn test_compaction_records_tombstones() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("keep.rs"), "fn keep() {}\n").unwrap();
+        fs::write(project.join("gone.rs"), "fn legacy_parser() {}\nstruct OldConfig;\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(&project, false).unwrap();
+        enable_tombstones(&cache);
+
+        fs::remove_file(project.join("gone.rs")).unwrap();
+        let report = cache.compact().unwrap();
+        assert_eq!(report.files_removed, 1);
+
+        let index = TombstoneIndex::new(cache.clone());
+        let tombstones = index.list(None).unwrap();
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].path, "gone.rs");
+        assert!(tombstones[0].last_hash.is_some());
+        assert!(tombstones[0].symbols.iter().any(|s| s.name == "legacy_parser" && s.line == 1));
+
+        let found = index.find("legacy_parser", true).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].symbols.len(), 1);
+        assert!(index.find("keep", false).unwrap().is_empty());
+
+        // Re-creating the file hides its tombstone
+        fs::write(project.join("gone.rs"), "fn legacy_parser() {}\n").unwrap();
+        Indexer::new(cache.clone(), IndexConfig::default()).index(&project, false).unwrap();
+        assert!(index.list(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compaction_without_tombstones() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("gone.rs"), "fn legacy() {}\n").unwrap();
+
+        let cache = CacheManager::new(&project);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(&project, false).unwrap();
+
+        fs::remove_file(project.join("gone.rs")).unwrap();
+        cache.compact().unwrap();
+
+        assert!(TombstoneIndex::new(cache).list(None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Stack trace resolution
+//!
+//! `rfx trace-resolve` reads a stack trace pasted on stdin and maps every
+//! frame onto the indexed codebase. Supported frame formats:
+//!
+//! - **Python**: `File "/app/svc/handlers.py", line 42, in handle`
+//! - **Java**: `at com.acme.svc.Handler.handle(Handler.java:42)`
+//! - **Node**: `at handle (/srv/app/dist/handler.js:42:13)` or `at /srv/app/handler.js:42:13`
+//! - **Rust**: `panicked at src/main.rs:42:5` and backtrace lines `at ./src/main.rs:42:5`
+//!
+//! Traces from production carry build-time paths (`/app/`, `/home/ci/build/`,
+//! `webpack:///./`), so frames are matched by path suffix: the indexed file
+//! sharing the most trailing path components wins, and at least the file name
+//! must match. Java frames only name the file, so the package of the class is
+//! used as the directory. Resolved frames get the enclosing symbol (see
+//! [`crate::locate`]) and the source line from the index.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::cache::CacheManager;
+use crate::content_store::ContentReader;
+use crate::locate::EnclosingSymbol;
+
+/// Stack trace dialect a frame was recognized as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceFormat {
+    Python,
+    Java,
+    Node,
+    Rust,
+}
+
+/// A frame parsed from the raw trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Frame {
+    pub format: TraceFormat,
+    /// File as written in the trace (may be a build or deploy path)
+    pub file: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub col: Option<usize>,
+    /// Function, method or qualified class name reported by the trace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// Java package of the frame's class (used as the directory for matching)
+    #[serde(skip)]
+    package: Option<String>,
+}
+
+/// Indexed location a frame maps to
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedLocation {
+    /// Indexed file path
+    pub path: String,
+    /// Source line at the frame (trimmed)
+    pub preview: String,
+    /// Innermost symbol containing the line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<EnclosingSymbol>,
+    /// Symbols enclosing `symbol`, outermost first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<EnclosingSymbol>,
+    /// Other indexed files matching the frame equally well
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<String>,
+}
+
+/// A trace frame with its indexed location (None when it points outside the index)
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedFrame {
+    #[serde(flatten)]
+    pub frame: Frame,
+    pub resolved: Option<ResolvedLocation>,
+}
+
+/// A stack trace enriched with indexed locations
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedTrace {
+    /// Error message line of the trace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub total_frames: usize,
+    pub resolved_frames: usize,
+    /// Frames in the order they appear in the trace
+    pub frames: Vec<ResolvedFrame>,
+}
+
+fn python_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"^\s*File "(.+)", line (\d+)(?:, in (.+))?$"#).unwrap())
+}
+
+fn java_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*at (?:[\w.$-]+/)*([\w.$<>]+)\(([^():]+):(\d+)\)$").unwrap())
+}
+
+fn named_location_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*at (.+?) \((.+):(\d+):(\d+)\)$").unwrap())
+}
+
+fn bare_location_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*at (.+):(\d+):(\d+)$").unwrap())
+}
+
+fn rust_panic() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"panicked at (?:'.*', )?(.+?):(\d+):(\d+):?$").unwrap())
+}
+
+fn rust_backtrace_symbol() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*\d+: (.+)$").unwrap())
+}
+
+/// Parse the frames of a stack trace, skipping lines that aren't frames
+pub fn parse_frames(trace: &str) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    // Rust backtraces print the function on the line above its location
+    let mut rust_symbol: Option<String> = None;
+
+    for line in trace.lines() {
+        let line = line.trim_end();
+        let number = |s: &str| s.parse::<usize>().ok();
+
+        if let Some(c) = python_frame().captures(line) {
+            frames.push(Frame {
+                format: TraceFormat::Python,
+                file: c[1].to_string(),
+                line: number(&c[2]).unwrap_or(0),
+                col: None,
+                function: c.get(3).map(|m| m.as_str().to_string()),
+                package: None,
+            });
+        } else if let Some(c) = java_frame().captures(line) {
+            // com.acme.Outer$Inner.method -> package com.acme
+            let qualified = &c[1];
+            let class = qualified.rsplit_once('.').map_or(qualified, |(class, _)| class);
+            let package = class.rsplit_once('.').map(|(package, _)| package.to_string());
+            frames.push(Frame {
+                format: TraceFormat::Java,
+                file: c[2].to_string(),
+                line: number(&c[3]).unwrap_or(0),
+                col: None,
+                function: Some(qualified.to_string()),
+                package,
+            });
+        } else if let Some(c) = named_location_frame().captures(line) {
+            frames.push(location_frame(&c[2], &c[3], &c[4], Some(c[1].to_string())));
+        } else if let Some(c) = bare_location_frame().captures(line) {
+            frames.push(location_frame(&c[1], &c[2], &c[3], rust_symbol.take()));
+        } else if let Some(c) = rust_panic().captures(line) {
+            frames.push(location_frame(&c[1], &c[2], &c[3], None));
+        } else if let Some(c) = rust_backtrace_symbol().captures(line) {
+            rust_symbol = Some(c[1].to_string());
+            continue;
+        }
+        rust_symbol = None;
+    }
+
+    frames.retain(|f| f.line > 0);
+    frames
+}
+
+/// A Node or Rust frame with a `file:line:col` location
+fn location_frame(file: &str, line: &str, col: &str, function: Option<String>) -> Frame {
+    let format = if file.ends_with(".rs") { TraceFormat::Rust } else { TraceFormat::Node };
+    Frame {
+        format,
+        file: file.to_string(),
+        line: line.parse().unwrap_or(0),
+        col: col.parse().ok(),
+        function,
+        package: None,
+    }
+}
+
+/// The error message of a trace
+///
+/// Python prints it after the frames; the other formats lead with it.
+fn error_message(trace: &str, frames: &[Frame]) -> Option<String> {
+    let mut lines = trace.lines().map(str::trim).filter(|l| !l.is_empty());
+    let message = if frames.iter().any(|f| f.format == TraceFormat::Python) {
+        lines.next_back()
+    } else {
+        lines.next()
+    };
+    message.map(str::to_string)
+}
+
+/// Path components used for suffix matching (`./`, `webpack:///`, `\` stripped)
+fn components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != "." && !c.ends_with(':'))
+        .collect()
+}
+
+/// Indexed files best matching `frame`, ordered by path (empty if none match)
+fn match_files<'a>(frame: &Frame, indexed: &'a [String]) -> Vec<&'a String> {
+    let mut wanted: Vec<&str> = Vec::new();
+    if let Some(package) = &frame.package {
+        wanted.extend(package.split('.'));
+    }
+    wanted.extend(components(&frame.file));
+
+    let mut best = 0;
+    let mut matches = Vec::new();
+    for path in indexed {
+        let score = components(path)
+            .iter()
+            .rev()
+            .zip(wanted.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        if score == 0 || score < best {
+            continue;
+        }
+        if score > best {
+            best = score;
+            matches.clear();
+        }
+        matches.push(path);
+    }
+    matches.sort();
+    matches
+}
+
+/// Resolve every frame of `trace` against the index in `cache`
+pub fn resolve_trace(cache: &CacheManager, trace: &str) -> Result<ResolvedTrace> {
+    let content_path = cache.path().join("content.bin");
+    let content_reader = ContentReader::open(&content_path)
+        .context("Failed to open content store")?;
+
+    let mut ids: HashMap<String, u32> = HashMap::new();
+    for file_id in 0..content_reader.file_count() as u32 {
+        if let Some(path) = content_reader.get_file_path(file_id) {
+            ids.insert(path.to_string_lossy().to_string(), file_id);
+        }
+    }
+    let mut indexed: Vec<String> = ids.keys().cloned().collect();
+    indexed.sort();
+
+    let frames = parse_frames(trace);
+    let error = error_message(trace, &frames);
+
+    let mut resolved_frames = Vec::with_capacity(frames.len());
+    for frame in frames {
+        let matches = match_files(&frame, &indexed);
+        let resolved = match matches.split_first() {
+            Some((path, alternatives)) => {
+                let content = content_reader.get_file_content(ids[path.as_str()])?;
+                let preview = content
+                    .lines()
+                    .nth(frame.line.saturating_sub(1))
+                    .map(|l| l.trim().to_string())
+                    .unwrap_or_default();
+
+                // Unparseable or out-of-range locations still resolve to the file
+                let (symbol, ancestors) = match crate::locate::symbol_in_source(path, content, frame.line, frame.col) {
+                    Ok(found) => (found.symbol, found.ancestors),
+                    Err(e) => {
+                        log::debug!("No symbol for {}:{}: {}", path, frame.line, e);
+                        (None, Vec::new())
+                    }
+                };
+
+                Some(ResolvedLocation {
+                    path: path.to_string(),
+                    preview,
+                    symbol,
+                    ancestors,
+                    alternatives: alternatives.iter().map(|p| p.to_string()).collect(),
+                })
+            }
+            None => None,
+        };
+        resolved_frames.push(ResolvedFrame { frame, resolved });
+    }
+
+    Ok(ResolvedTrace {
+        error,
+        total_frames: resolved_frames.len(),
+        resolved_frames: resolved_frames.iter().filter(|f| f.resolved.is_some()).count(),
+        frames: resolved_frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::Indexer;
+    use crate::models::IndexConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn frame(format: TraceFormat, file: &str, line: usize, col: Option<usize>, function: Option<&str>) -> Frame {
+        Frame {
+            format,
+            file: file.to_string(),
+            line,
+            col,
+            function: function.map(str::to_string),
+            package: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_python_trace() {
+        let trace = "Traceback (most recent call last):\n  File \"/app/svc/main.py\", line 10, in <module>\n    run()\n  File \"/app/svc/handlers.py\", line 42, in handle\n    raise ValueError(\"bad\")\nValueError: bad\n";
+        let frames = parse_frames(trace);
+        assert_eq!(frames, vec![
+            frame(TraceFormat::Python, "/app/svc/main.py", 10, None, Some("<module>")),
+            frame(TraceFormat::Python, "/app/svc/handlers.py", 42, None, Some("handle")),
+        ]);
+        assert_eq!(error_message(trace, &frames).as_deref(), Some("ValueError: bad"));
+    }
+
+    #[test]
+    fn test_parse_java_node_and_rust_traces() {
+        let java = parse_frames("Exception in thread \"main\" java.lang.IllegalStateException: boom\n\tat com.acme.svc.Handler$Inner.handle(Handler.java:42)\n\tat java.base/java.lang.Thread.run(Thread.java:833)\n");
+        assert_eq!(java.len(), 2);
+        assert_eq!(java[0].file, "Handler.java");
+        assert_eq!(java[0].package.as_deref(), Some("com.acme.svc"));
+        assert_eq!(java[1].function.as_deref(), Some("java.lang.Thread.run"));
+
+        let node = parse_frames("TypeError: x is undefined\n    at handle (/srv/app/dist/handler.js:42:13)\n    at /srv/app/index.js:7:1\n");
+        assert_eq!(node, vec![
+            frame(TraceFormat::Node, "/srv/app/dist/handler.js", 42, Some(13), Some("handle")),
+            frame(TraceFormat::Node, "/srv/app/index.js", 7, Some(1), None),
+        ]);
+
+        let rust = parse_frames("thread 'main' panicked at src/main.rs:12:5:\nboom\nstack backtrace:\n   0: app::run\n             at ./src/lib.rs:30:9\n");
+        assert_eq!(rust, vec![
+            frame(TraceFormat::Rust, "src/main.rs", 12, Some(5), None),
+            frame(TraceFormat::Rust, "./src/lib.rs", 30, Some(9), Some("app::run")),
+        ]);
+    }
+
+    #[test]
+    fn test_match_files_by_longest_suffix() {
+        let indexed: Vec<String> = ["./api/handler.js", "./web/handler.js", "./src/com/acme/svc/Handler.java"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let node = frame(TraceFormat::Node, "/srv/build/web/handler.js", 1, None, None);
+        assert_eq!(match_files(&node, &indexed), vec!["./web/handler.js"]);
+
+        let ambiguous = frame(TraceFormat::Node, "webpack:///handler.js", 1, None, None);
+        assert_eq!(match_files(&ambiguous, &indexed).len(), 2);
+
+        let java = parse_frames("\tat com.acme.svc.Handler.handle(Handler.java:3)\n");
+        assert_eq!(match_files(&java[0], &indexed), vec!["./src/com/acme/svc/Handler.java"]);
+
+        let missing = frame(TraceFormat::Node, "node:internal/modules/cjs/loader.js", 1, None, None);
+        assert!(match_files(&missing, &indexed).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_trace_attaches_symbols() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("svc")).unwrap();
+        fs::write(root.join("svc/handlers.py"), "import os\n\ndef handle(req):\n    check(req)\n    raise ValueError('bad')\n").unwrap();
+        let cache = CacheManager::new(root);
+        Indexer::new(cache.clone(), IndexConfig::default()).index(root, false).unwrap();
+
+        let trace = "Traceback (most recent call last):\n  File \"/app/svc/handlers.py\", line 5, in handle\n  File \"/usr/lib/python3.12/json/decoder.py\", line 337, in decode\nValueError: bad\n";
+        let resolved = resolve_trace(&cache, trace).unwrap();
+
+        assert_eq!(resolved.total_frames, 2);
+        assert_eq!(resolved.resolved_frames, 1);
+        let location = resolved.frames[0].resolved.as_ref().unwrap();
+        assert!(location.path.ends_with("svc/handlers.py"));
+        assert_eq!(location.preview, "raise ValueError('bad')");
+        assert_eq!(location.symbol.as_ref().unwrap().name.as_deref(), Some("handle"));
+        assert!(resolved.frames[1].resolved.is_none());
+    }
+}
@@ -0,0 +1,386 @@
+//! Index-time content normalization
+//!
+//! Transforms rewrite file content before trigram and symbol extraction, e.g. to
+//! strip license headers, render templated files or redact secrets. Each rule
+//! pipes the matching file's content through an external command (stdin →
+//! stdout) and the rule that produced the indexed content is recorded per file
+//! in `meta.db`, so query results can flag files whose indexed content differs
+//! from what is on disk.
+//!
+//! Rules are configured in `.reflex/config.toml`; the first rule whose globs
+//! match a file wins:
+//!
+//! ```toml
+//! [[transforms.rules]]
+//! name = "strip-license"
+//! command = "scripts/strip-license.sh --lang rust"
+//! globs = ["**/*.rs"]
+//! ```
+//!
+//! Commands run from the workspace root with `REFLEX_FILE` set to the file's
+//! relative path. A command that fails or prints non-UTF-8 output causes the
+//! file to be skipped rather than indexed untransformed, so a broken redaction
+//! filter never leaks raw content into the cache.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+
+/// Length of the command digest embedded in a transform identity
+const IDENTITY_DIGEST_LEN: usize = 12;
+
+/// A single `[[transforms.rules]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformRule {
+    /// Short name shown in query results (e.g. "strip-license")
+    pub name: String,
+    /// Command line to run; content is piped through stdin → stdout
+    pub command: String,
+    /// Glob patterns (relative to the workspace root) selecting files for this rule
+    #[serde(default)]
+    pub globs: Vec<String>,
+}
+
+/// `[transforms]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransformConfig {
+    #[serde(default)]
+    pub rules: Vec<TransformRule>,
+}
+
+/// Load the `[transforms]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to no transforms if the file or section is missing.
+pub fn load_transform_config(cache_path: &Path) -> Result<TransformConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(TransformConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("transforms") {
+        Some(table) => table.clone().try_into()
+            .context("Failed to parse [transforms] section in .reflex/config.toml"),
+        None => Ok(TransformConfig::default()),
+    }
+}
+
+/// A rule with its command parsed and globs compiled
+struct CompiledRule {
+    identity: String,
+    program: String,
+    args: Vec<String>,
+    globs: GlobSet,
+}
+
+/// Compiled set of transform rules for one indexing run
+pub struct Transformer {
+    root: PathBuf,
+    rules: Vec<CompiledRule>,
+}
+
+impl Transformer {
+    /// Compile the configured rules; commands run with `root` as working directory
+    pub fn from_config(config: &TransformConfig, root: impl AsRef<Path>) -> Result<Self> {
+        let mut rules = Vec::with_capacity(config.rules.len());
+
+        for rule in &config.rules {
+            let mut words = shell_words::split(&rule.command)
+                .with_context(|| format!("Invalid command for transform '{}'", rule.name))?;
+            if words.is_empty() {
+                anyhow::bail!(
+                    "Transform '{}' has an empty command.\n\
+                     \n\
+                     Set command to a program that reads content on stdin and writes\n\
+                     the normalized content to stdout.",
+                    rule.name
+                );
+            }
+            if rule.globs.is_empty() {
+                anyhow::bail!(
+                    "Transform '{}' has no globs.\n\
+                     \n\
+                     Add globs = [\"**/*.ext\"] to select the files it applies to.",
+                    rule.name
+                );
+            }
+
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &rule.globs {
+                let glob = Glob::new(pattern)
+                    .with_context(|| format!("Invalid glob '{}' in transform '{}'", pattern, rule.name))?;
+                builder.add(glob);
+            }
+
+            let program = words.remove(0);
+            rules.push(CompiledRule {
+                identity: transform_identity(rule),
+                program,
+                args: words,
+                globs: builder.build()?,
+            });
+        }
+
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Returns true if no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Identity of the rule that applies to a workspace-relative path, if any
+    pub fn identity_for(&self, path: &str) -> Option<&str> {
+        self.rule_for(path).map(|rule| rule.identity.as_str())
+    }
+
+    /// Apply the matching rule to a file's content
+    ///
+    /// Returns `Ok(None)` when no rule matches, otherwise the rule identity and
+    /// the transformed content.
+    pub fn apply(&self, path: &str, content: &str) -> Result<Option<(String, String)>> {
+        let Some(rule) = self.rule_for(path) else {
+            return Ok(None);
+        };
+
+        let mut child = Command::new(&rule.program)
+            .args(&rule.args)
+            .current_dir(&self.root)
+            .env("REFLEX_FILE", path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run transform '{}' ({})", rule.identity, rule.program))?;
+
+        // Feed stdin from a separate thread so a command that streams output
+        // before consuming all input can't deadlock on a full pipe
+        let mut stdin = child.stdin.take().context("Transform stdin unavailable")?;
+        let output = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || stdin.write_all(content.as_bytes()));
+            let output = child.wait_with_output();
+            // A filter may legitimately stop reading early (e.g. `head`)
+            let _ = writer.join();
+            output
+        })
+        .with_context(|| format!("Transform '{}' failed on {}", rule.identity, path))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Transform '{}' exited with {} on {}: {}",
+                rule.identity,
+                output.status,
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let transformed = String::from_utf8(output.stdout)
+            .with_context(|| format!("Transform '{}' produced non-UTF-8 output for {}", rule.identity, path))?;
+
+        Ok(Some((rule.identity.clone(), transformed)))
+    }
+
+    fn rule_for(&self, path: &str) -> Option<&CompiledRule> {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        self.rules.iter().find(|rule| rule.globs.is_match(path))
+    }
+}
+
+/// Stable identity for a rule: its name plus a digest of the command line
+///
+/// Changing a rule's command changes its identity, which in turn changes the
+/// stored hash of every file it applies to and forces those files to reindex.
+pub fn transform_identity(rule: &TransformRule) -> String {
+    let digest = blake3::hash(rule.command.as_bytes()).to_hex();
+    format!("{}@{}", rule.name, &digest[..IDENTITY_DIGEST_LEN])
+}
+
+/// Combine a raw content hash with the transform identity applied to the file
+///
+/// Untransformed files keep their plain content hash.
+pub fn keyed_hash(content_hash: &str, identity: Option<&str>) -> String {
+    match identity {
+        Some(identity) => blake3::hash(format!("{}:{}", content_hash, identity).as_bytes())
+            .to_hex()
+            .to_string(),
+        None => content_hash.to_string(),
+    }
+}
+
+/// Create the file transform table if it doesn't exist
+///
+/// Called from `CacheManager::init_meta_db()` for new caches and lazily by
+/// `TransformIndex` so caches created before transforms keep working.
+pub(crate) fn ensure_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_transforms (
+            file_id INTEGER PRIMARY KEY,
+            transform TEXT NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Stores which transform produced each file's indexed content
+pub struct TransformIndex {
+    cache: CacheManager,
+}
+
+impl TransformIndex {
+    /// Create a new transform index for the given cache
+    pub fn new(cache: CacheManager) -> Self {
+        Self { cache }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let db_path = self.cache.path().join(META_DB);
+
+        if !db_path.exists() {
+            anyhow::bail!("Index not found. Run 'rfx index' to build the cache first.");
+        }
+
+        let conn = Connection::open(&db_path)
+            .context("Failed to open meta.db for transform index")?;
+        ensure_schema(&conn)?;
+        Ok(conn)
+    }
+
+    /// Record the transform for a set of files in one transaction
+    ///
+    /// Files mapped to `None` were indexed verbatim and have any previous
+    /// record cleared.
+    pub fn replace_file_transforms(&self, files: &[(i64, Option<String>)]) -> Result<usize> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        let mut recorded = 0;
+
+        {
+            let mut delete_stmt = tx.prepare("DELETE FROM file_transforms WHERE file_id = ?")?;
+            let mut insert_stmt = tx.prepare(
+                "INSERT INTO file_transforms (file_id, transform) VALUES (?, ?)"
+            )?;
+
+            for (file_id, transform) in files {
+                delete_stmt.execute([file_id])?;
+                if let Some(transform) = transform {
+                    insert_stmt.execute(rusqlite::params![file_id, transform])?;
+                    recorded += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        log::debug!("Recorded transforms for {} of {} files", recorded, files.len());
+        Ok(recorded)
+    }
+
+    /// Map of path → transform identity for every transformed file
+    pub fn all(&self) -> Result<HashMap<String, String>> {
+        let conn = self.open()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.path, ft.transform
+             FROM file_transforms ft
+             JOIN files f ON ft.file_id = f.id"
+        )?;
+
+        let transforms = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(transforms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rule(name: &str, command: &str, globs: &[&str]) -> TransformRule {
+        TransformRule {
+            name: name.to_string(),
+            command: command.to_string(),
+            globs: globs.iter().map(|g| g.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_load_transform_config() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[[transforms.rules]]\nname = \"upper\"\ncommand = \"tr a-z A-Z\"\nglobs = [\"**/*.txt\"]\n",
+        ).unwrap();
+
+        let config = load_transform_config(temp.path()).unwrap();
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].name, "upper");
+        assert_eq!(config.rules[0].globs, vec!["**/*.txt"]);
+
+        let empty = TempDir::new().unwrap();
+        assert!(load_transform_config(empty.path()).unwrap().rules.is_empty());
+    }
+
+    #[test]
+    fn test_identity_tracks_command() {
+        let a = transform_identity(&rule("strip", "sed 1d", &["*.rs"]));
+        let b = transform_identity(&rule("strip", "sed 1,2d", &["*.rs"]));
+        assert!(a.starts_with("strip@"));
+        assert_ne!(a, b);
+
+        assert_eq!(keyed_hash("abc", None), "abc");
+        assert_ne!(keyed_hash("abc", Some(&a)), keyed_hash("abc", Some(&b)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_matching_rule() {
+        let temp = TempDir::new().unwrap();
+        let config = TransformConfig {
+            rules: vec![
+                rule("upper", "tr a-z A-Z", &["docs/**"]),
+                rule("fail", "false", &["**/*.secret"]),
+            ],
+        };
+        let transformer = Transformer::from_config(&config, temp.path()).unwrap();
+
+        let (identity, content) = transformer.apply("./docs/a.txt", "hello").unwrap().unwrap();
+        assert!(identity.starts_with("upper@"));
+        assert_eq!(content, "HELLO");
+
+        // No matching rule: content is indexed as-is
+        assert!(transformer.apply("src/main.rs", "fn main() {}").unwrap().is_none());
+
+        // A failing command is an error, never a silent pass-through
+        assert!(transformer.apply("keys.secret", "token").is_err());
+    }
+
+    #[test]
+    fn test_rule_requires_globs() {
+        let temp = TempDir::new().unwrap();
+        let config = TransformConfig { rules: vec![rule("noop", "cat", &[])] };
+        assert!(Transformer::from_config(&config, temp.path()).is_err());
+    }
+}
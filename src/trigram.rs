@@ -12,27 +12,52 @@
 //! 3. **Verification**: Check actual matches at candidate locations
 //!
 //! See `.context/TRIGRAM_RESEARCH.md` for detailed algorithm documentation.
+//!
+//! # Storage
+//!
+//! `trigrams.bin` holds append-only segments of posting lists. A segment indexes
+//! a set of *documents*, each identified by the blake3 hash of its content, and
+//! a manifest at the end of the file maps every indexed file to its content
+//! hash. Documents are matched to files by hash when the index is loaded, so:
+//!
+//! - an incremental index only appends a segment for content no segment has
+//!   seen yet, plus a new manifest, instead of rewriting the whole file;
+//! - deleted and changed files are tombstoned by dropping out of the manifest
+//!   (their documents become dead and are skipped by searches);
+//! - renamed, copied or reverted files reuse the postings already on disk.
+//!
+//! Once [`MAX_SEGMENTS`] segments, or more dead than live documents, have built
+//! up, [`TrigramIndex::open_for_update`] declines and the indexer rewrites the
+//! file from scratch as a single segment.
 
 use anyhow::{Context, Result};
 use rkyv::{Archive, Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 /// A trigram is 3 consecutive bytes, packed into a u32 for efficient hashing
 pub type Trigram = u32;
 
 // Binary format constants for trigrams.bin
-const MAGIC: &[u8; 4] = b"RFTG"; // ReFlex TriGrams
-const VERSION: u32 = 3; // V3: No filtering, lazy loading with directory + data separation
-// Header: magic(4) + version(4) + num_trigrams(8) + num_files(8) = 24 bytes
-#[allow(dead_code)]
+pub(crate) const MAGIC: &[u8; 4] = b"RFTG"; // ReFlex TriGrams
+pub(crate) const VERSION: u32 = 4; // V4: Content-addressed segments + file manifest
+// Header: magic(4) + version(4) + manifest_offset(8) + num_files(8) = 24 bytes
 const HEADER_SIZE: usize = 24;
 
+/// Segments an index may accumulate before the next index run compacts it
+pub const MAX_SEGMENTS: usize = 16;
+
+/// blake3 hash of a file's indexed content
+type ContentHash = [u8; 32];
+
+/// `Segment::doc_files` entry for a document no indexed file has the content of
+const DEAD_DOC: u32 = u32::MAX;
+
 /// Write a u32 as a varint (variable-length integer)
 /// Uses 1-5 bytes depending on magnitude (smaller numbers = fewer bytes)
-fn write_varint(writer: &mut impl Write, mut value: u32) -> std::io::Result<()> {
+pub(crate) fn write_varint(writer: &mut impl Write, mut value: u32) -> std::io::Result<()> {
     loop {
         let mut byte = (value & 0x7F) as u8;
         value >>= 7;
@@ -48,7 +73,7 @@ fn write_varint(writer: &mut impl Write, mut value: u32) -> std::io::Result<()>
 }
 
 /// Read a varint from a byte slice, returns (value, bytes_consumed)
-fn read_varint(data: &[u8]) -> Result<(u32, usize)> {
+pub(crate) fn read_varint(data: &[u8]) -> Result<(u32, usize)> {
     let mut value: u32 = 0;
     let mut shift = 0;
     let mut pos = 0;
@@ -185,6 +210,24 @@ struct DirectoryEntry {
     compressed_size: u32,
 }
 
+/// A loaded segment of `trigrams.bin`
+struct Segment {
+    /// Directory of (trigram, offset, size), sorted by trigram
+    directory: Vec<DirectoryEntry>,
+    /// File holding each document's content (`DEAD_DOC` if no indexed file does)
+    doc_files: Vec<u32>,
+    /// Document ids are file ids (a freshly built index), so postings need no remapping
+    identity: bool,
+}
+
+/// The existing index that `write()` appends a segment to
+struct UpdateBase {
+    /// Descriptor offsets of the existing segments
+    segments: Vec<u64>,
+    /// Content with postings on disk or earlier in this run
+    known: HashSet<ContentHash>,
+}
+
 /// Trigram-based inverted index
 ///
 /// Maps each trigram to a sorted list of locations where it appears.
@@ -195,6 +238,10 @@ struct DirectoryEntry {
 /// 1. **In-memory mode** (during indexing): All posting lists in RAM
 /// 2. **Batch-flush mode** (large codebases): Periodically flushes partial indices to disk to limit RAM
 /// 3. **Lazy-loaded mode** (after loading): Compressed posting lists in mmap, decompressed on-demand
+///
+/// An index started with [`TrigramIndex::open_for_update`] only indexes content
+/// the existing file has no postings for, using document ids instead of file
+/// ids; it is written, never searched.
 pub struct TrigramIndex {
     /// Inverted index: sorted Vec of (trigram, locations) for binary search
     /// Used in in-memory mode (during indexing)
@@ -203,10 +250,18 @@ pub struct TrigramIndex {
     files: Vec<PathBuf>,
     /// Temporary HashMap used during batch indexing (None when finalized)
     temp_index: Option<HashMap<Trigram, Vec<FileLocation>>>,
+    /// Content hash per file id
+    hashes: Vec<ContentHash>,
     /// Memory-mapped index file (for lazy loading)
     mmap: Option<memmap2::Mmap>,
-    /// Directory of (trigram, offset, size) for lazy loading
-    directory: Vec<DirectoryEntry>,
+    /// Segments of the loaded index (lazy loading)
+    segments: Vec<Segment>,
+    /// Other files with the same content as a file that documents map to (lazy loading)
+    extra_files: HashMap<u32, Vec<u32>>,
+    /// Existing index this one appends to (None = fresh build)
+    base: Option<UpdateBase>,
+    /// Content hashes of the documents indexed by this run (when appending)
+    new_docs: Vec<ContentHash>,
     /// Partial index files created during batch flushing (for k-way merge at finalize)
     partial_indices: Vec<PathBuf>,
     /// Temporary directory for partial indices
@@ -220,13 +275,78 @@ impl TrigramIndex {
             index: Vec::new(),
             files: Vec::new(),
             temp_index: Some(HashMap::new()),
+            hashes: Vec::new(),
             mmap: None,
-            directory: Vec::new(),
+            segments: Vec::new(),
+            extra_files: HashMap::new(),
+            base: None,
+            new_docs: Vec::new(),
             partial_indices: Vec::new(),
             temp_dir: None,
         }
     }
 
+    /// Start an incremental update of the index at `path`
+    ///
+    /// Files whose content already has postings in one of its segments are not
+    /// re-indexed; `write()` appends one segment for the rest, plus a manifest of
+    /// the files added to this index. Returns None when there is no readable
+    /// index, or when it is due for compaction (see [`MAX_SEGMENTS`]), in which
+    /// case the caller builds a fresh one.
+    pub fn open_for_update(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mmap = unsafe {
+            memmap2::Mmap::map(&file)
+                .with_context(|| format!("Failed to mmap {}", path.display()))?
+        };
+
+        let read_documents = || -> Result<_> {
+            let manifest = read_manifest(&mmap)?;
+            let mut known = HashSet::new();
+            for &offset in &manifest.segments {
+                let (docs, _) = read_segment(&mmap, offset)?;
+                known.extend(docs);
+            }
+            Ok((manifest, known))
+        };
+        let (manifest, known) = match read_documents() {
+            Ok(documents) => documents,
+            Err(e) => {
+                log::info!("Rebuilding trigram index: {:#}", e);
+                return Ok(None);
+            }
+        };
+
+        let live = manifest.hashes.iter().filter(|h| known.contains(*h)).collect::<HashSet<_>>().len();
+        let dead = known.len() - live;
+
+        if manifest.segments.len() >= MAX_SEGMENTS || dead > live {
+            log::info!(
+                "Compacting trigram index ({} segments, {} live and {} dead documents)",
+                manifest.segments.len(),
+                live,
+                dead
+            );
+            return Ok(None);
+        }
+
+        log::debug!(
+            "Appending to trigram index ({} segments, {} live and {} dead documents)",
+            manifest.segments.len(),
+            live,
+            dead
+        );
+        let mut index = Self::new();
+        index.base = Some(UpdateBase { segments: manifest.segments, known });
+        Ok(Some(index))
+    }
+
     /// Enable batch-flush mode for large codebases
     ///
     /// Creates a temporary directory for partial indices that will be merged at finalize().
@@ -325,6 +445,10 @@ impl TrigramIndex {
     /// Add a file to the index and return its file_id
     pub fn add_file(&mut self, path: PathBuf) -> u32 {
         let file_id = self.files.len() as u32;
+        // Stands in until index_file() hashes the content; unique per file, so
+        // files never given content don't share postings
+        let placeholder = blake3::hash(format!("\0file {} {}", file_id, path.display()).as_bytes());
+        self.hashes.push(*placeholder.as_bytes());
         self.files.push(path);
         file_id
     }
@@ -341,12 +465,20 @@ impl TrigramIndex {
 
     /// Get total number of unique trigrams
     pub fn trigram_count(&self) -> usize {
-        if !self.directory.is_empty() {
-            // Lazy-loaded mode
-            self.directory.len()
-        } else {
+        match self.segments.as_slice() {
             // In-memory mode
-            self.index.len()
+            [] => self.index.len(),
+            // Lazy-loaded mode
+            [segment] => segment.directory.len(),
+            segments => {
+                let mut trigrams: Vec<Trigram> = segments
+                    .iter()
+                    .flat_map(|s| s.directory.iter().map(|e| e.trigram))
+                    .collect();
+                trigrams.sort_unstable();
+                trigrams.dedup();
+                trigrams.len()
+            }
         }
     }
 
@@ -354,8 +486,24 @@ impl TrigramIndex {
     ///
     /// Extracts all trigrams from the content and adds them to the inverted index.
     /// Must call finalize() after indexing all files to prepare for searching.
+    /// When appending to an existing index, content it already has postings for
+    /// is skipped.
     pub fn index_file(&mut self, file_id: u32, content: &str) {
-        let trigrams = extract_trigrams_with_locations(content, file_id);
+        let hash = *blake3::hash(content.as_bytes()).as_bytes();
+        self.hashes[file_id as usize] = hash;
+
+        let doc_id = match self.base {
+            Some(ref mut base) => {
+                if !base.known.insert(hash) {
+                    return;
+                }
+                self.new_docs.push(hash);
+                (self.new_docs.len() - 1) as u32
+            }
+            None => file_id,
+        };
+
+        let trigrams = extract_trigrams_with_locations(content, doc_id);
 
         // Use the persistent HashMap for O(1) updates during batch processing
         if let Some(ref mut temp_map) = self.temp_index {
@@ -436,20 +584,21 @@ impl TrigramIndex {
         self.index.sort_unstable_by_key(|(trigram, _)| *trigram);
     }
 
-    /// Merge all partial indices directly to trigrams.bin using streaming k-way merge
+    /// Merge all partial indices into a segment's posting lists using streaming k-way merge
     ///
     /// This avoids loading the entire index into RAM by:
     /// 1. Opening all partial index files as readers
     /// 2. Performing k-way merge using a priority queue
     /// 3. Writing compressed posting lists directly to disk
     /// 4. Never accumulating more than K posting lists in memory at once
-    fn merge_partial_indices_to_file(&mut self, output_path: &Path) -> Result<()> {
-        use std::io::{BufReader, BufWriter, Read};
+    ///
+    /// Returns the segment's directory; `offset` is where `writer` is positioned.
+    fn merge_partial_indices_into(&mut self, writer: &mut impl Write, offset: u64) -> Result<Vec<DirectoryEntry>> {
+        use std::io::{BufReader, Read};
         use std::cmp::Ordering;
         use std::collections::BinaryHeap;
 
-        log::info!("Streaming merge of {} partial indices to {:?}",
-                   self.partial_indices.len(), output_path);
+        log::info!("Streaming merge of {} partial indices", self.partial_indices.len());
 
         // Open all partial indices as buffered readers
         struct PartialIndexReader {
@@ -553,25 +702,9 @@ impl TrigramIndex {
             }
         }
 
-        // Open output file for writing
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(output_path)
-            .with_context(|| format!("Failed to create {}", output_path.display()))?;
-
-        let mut writer = BufWriter::with_capacity(16 * 1024 * 1024, file);
-
-        // Write placeholder header (we'll update it at the end)
-        writer.write_all(MAGIC)?;
-        writer.write_all(&VERSION.to_le_bytes())?;
-        writer.write_all(&0u64.to_le_bytes())?; // num_trigrams (placeholder)
-        writer.write_all(&(self.files.len() as u64).to_le_bytes())?; // num_files
-
         // We'll build the directory as we go
         let mut directory: Vec<DirectoryEntry> = Vec::new();
-        let mut num_trigrams = 0u64;
+        let mut data_offset = offset;
 
         // K-way merge loop
         let mut current_trigram: Option<Trigram> = None;
@@ -588,8 +721,7 @@ impl TrigramIndex {
                 merged_locations.dedup();
 
                 // Compress and write this trigram's posting list
-                let data_offset = writer.stream_position()?;
-                let compressed_size = self.write_compressed_posting_list(&mut writer, &merged_locations)?;
+                let compressed_size = self.write_compressed_posting_list(writer, &merged_locations)?;
 
                 directory.push(DirectoryEntry {
                     trigram,
@@ -597,7 +729,7 @@ impl TrigramIndex {
                     compressed_size,
                 });
 
-                num_trigrams += 1;
+                data_offset += compressed_size as u64;
                 merged_locations.clear();
             }
 
@@ -623,72 +755,16 @@ impl TrigramIndex {
             merged_locations.sort_unstable();
             merged_locations.dedup();
 
-            let data_offset = writer.stream_position()?;
-            let compressed_size = self.write_compressed_posting_list(&mut writer, &merged_locations)?;
+            let compressed_size = self.write_compressed_posting_list(writer, &merged_locations)?;
 
             directory.push(DirectoryEntry {
                 trigram,
                 data_offset,
                 compressed_size,
             });
-
-            num_trigrams += 1;
         }
 
-        log::info!("Merged {} trigrams from {} partial indices", num_trigrams, self.partial_indices.len());
-
-        // Remember where data section ended (not used but kept for clarity)
-        let _data_end_pos = writer.stream_position()?;
-
-        // Write file paths after data section
-        for file_path in &self.files {
-            let path_str = file_path.to_string_lossy();
-            let path_bytes = path_str.as_bytes();
-            write_varint(&mut writer, path_bytes.len() as u32)?;
-            writer.write_all(path_bytes)?;
-        }
-
-        // Flush before we rewrite the beginning
-        writer.flush()?;
-        drop(writer);
-
-        // Now we need to insert the directory at the beginning
-        // We'll read the data+files we just wrote, then rewrite the file with directory in between
-        use std::io::{Seek, SeekFrom};
-
-        // Read data and files sections
-        let mut temp_data = Vec::new();
-        {
-            let mut file = File::open(output_path)?;
-            file.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
-            file.read_to_end(&mut temp_data)?;
-        }
-
-        // Rewrite file with correct structure
-        let file = OpenOptions::new().write(true).truncate(true).open(output_path)?;
-        let mut writer = BufWriter::with_capacity(16 * 1024 * 1024, file);
-
-        // Write header with correct num_trigrams
-        writer.write_all(MAGIC)?;
-        writer.write_all(&VERSION.to_le_bytes())?;
-        writer.write_all(&num_trigrams.to_le_bytes())?;
-        writer.write_all(&(self.files.len() as u64).to_le_bytes())?;
-
-        // Write directory
-        for entry in &directory {
-            writer.write_all(&entry.trigram.to_le_bytes())?;
-            // Adjust data offset to account for directory size
-            let adjusted_offset = entry.data_offset + (directory.len() * 16) as u64;
-            writer.write_all(&adjusted_offset.to_le_bytes())?;
-            writer.write_all(&entry.compressed_size.to_le_bytes())?;
-        }
-
-        // Write data and files sections
-        writer.write_all(&temp_data)?;
-
-        // Flush and sync
-        writer.flush()?;
-        writer.get_ref().sync_all()?;
+        log::info!("Merged {} trigrams from {} partial indices", directory.len(), self.partial_indices.len());
 
         // Clean up partial index files
         for partial_path in &self.partial_indices {
@@ -697,10 +773,9 @@ impl TrigramIndex {
         if let Some(ref temp_dir) = self.temp_dir {
             let _ = std::fs::remove_dir(temp_dir);
         }
+        self.partial_indices.clear();
 
-        log::info!("Wrote {} trigrams to {:?}", num_trigrams, output_path);
-
-        Ok(())
+        Ok(directory)
     }
 
     /// Write a compressed posting list to the writer and return the compressed size
@@ -844,23 +919,16 @@ impl TrigramIndex {
             let mut posting_lists: Vec<Vec<FileLocation>> = Vec::new();
 
             for trigram in &trigrams {
-                // Binary search directory for this trigram
-                match self.directory.binary_search_by_key(trigram, |e| e.trigram) {
-                    Ok(idx) => {
-                        let entry = &self.directory[idx];
-                        // Decompress this posting list on-demand
-                        match decompress_posting_list(mmap, entry.data_offset, entry.compressed_size) {
-                            Ok(locations) => posting_lists.push(locations),
-                            Err(e) => {
-                                log::warn!("Failed to decompress posting list for trigram {}: {}", trigram, e);
-                                return vec![];
-                            }
-                        }
-                    }
-                    Err(_) => {
+                match self.lazy_posting_list(mmap, *trigram) {
+                    Ok(Some(locations)) => posting_lists.push(locations),
+                    Ok(None) => {
                         // Trigram not found - pattern cannot match
                         return vec![];
                     }
+                    Err(e) => {
+                        log::warn!("Failed to decompress posting list for trigram {}: {}", trigram, e);
+                        return vec![];
+                    }
                 }
             }
 
@@ -902,6 +970,42 @@ impl TrigramIndex {
         }
     }
 
+    /// Posting list of `trigram` across all segments, with documents mapped to files
+    ///
+    /// Returns None when no indexed file contains the trigram.
+    fn lazy_posting_list(&self, mmap: &[u8], trigram: Trigram) -> Result<Option<Vec<FileLocation>>> {
+        let mut locations = Vec::new();
+
+        for segment in &self.segments {
+            let Ok(idx) = segment.directory.binary_search_by_key(&trigram, |e| e.trigram) else {
+                continue;
+            };
+            let entry = &segment.directory[idx];
+            let postings = decompress_posting_list(mmap, entry.data_offset, entry.compressed_size)?;
+
+            if segment.identity && self.segments.len() == 1 {
+                return Ok(Some(postings));
+            }
+
+            for loc in postings {
+                let file_id = segment.doc_files.get(loc.file_id as usize).copied().unwrap_or(DEAD_DOC);
+                if file_id == DEAD_DOC {
+                    continue;
+                }
+                locations.push(FileLocation { file_id, ..loc });
+                if let Some(extra) = self.extra_files.get(&file_id) {
+                    locations.extend(extra.iter().map(|&file_id| FileLocation { file_id, ..loc }));
+                }
+            }
+        }
+
+        if locations.is_empty() {
+            return Ok(None);
+        }
+        locations.sort_unstable();
+        Ok(Some(locations))
+    }
+
     /// Get posting list for a specific trigram (for debugging)
     pub fn get_posting_list(&self, trigram: Trigram) -> Option<&Vec<FileLocation>> {
         self.index
@@ -912,119 +1016,99 @@ impl TrigramIndex {
 
     /// Write the trigram index to disk
     ///
-    /// Binary format V3 (lazy-loadable with directory + data separation):
-    /// - Header (24 bytes): magic, version, num_trigrams, num_files
-    /// - Directory Section (16 bytes per trigram):
-    ///   - trigram: u32 (4 bytes)
-    ///   - data_offset: u64 (8 bytes) - absolute offset in file
-    ///   - compressed_size: u32 (4 bytes) - size of compressed posting list
-    /// - Data Section (variable size):
-    ///   - Compressed posting lists (delta+varint encoded)
-    /// - File Paths Section (variable size):
-    ///   - path_len: varint
-    ///   - path_bytes: [u8; path_len]
+    /// Binary format V4 (lazy-loadable, append-only segments):
+    /// - Header (24 bytes): magic, version, manifest_offset (u64), num_files (u64)
+    /// - Segments, each made of:
+    ///   - Data: compressed posting lists (delta+varint encoded), with document
+    ///     ids in place of file ids
+    ///   - Descriptor: num_docs (u32), num_trigrams (u32), the content hash of
+    ///     each document (32 bytes), then a directory entry per trigram sorted by
+    ///     trigram: trigram (u32), data_offset (u64, absolute), compressed_size (u32)
+    /// - Manifest (at manifest_offset):
+    ///   - num_segments (u32), then each segment's descriptor offset (u64)
+    ///   - Per file: path_len (varint), path bytes, content hash (32 bytes)
+    ///
+    /// A fresh index is written as one segment whose document ids are the file
+    /// ids. An index from [`TrigramIndex::open_for_update`] appends a segment for
+    /// the content it indexed and a new manifest to the existing file; the header
+    /// is repointed last, so an interrupted write leaves the previous index intact.
     pub fn write(&mut self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
 
-        // If we have partial indices from batch flushing, use streaming merge
-        if !self.partial_indices.is_empty() {
-            log::info!("Using streaming merge to write {} partial indices", self.partial_indices.len());
-            return self.merge_partial_indices_to_file(path);
-        }
-
-        // Standard write path (no batch flushing)
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .with_context(|| format!("Failed to create {}", path.display()))?;
+        // Appending keeps the existing segments; a fresh build starts the file over
+        let (file, mut segments) = match self.base {
+            Some(ref base) => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                (file, base.segments.clone())
+            }
+            None => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .truncate(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                (file, Vec::new())
+            }
+        };
 
         // Use a large buffer (16MB) for streaming writes
         let mut writer = std::io::BufWriter::with_capacity(16 * 1024 * 1024, file);
-
-        // Write header
-        writer.write_all(MAGIC)?;
-        writer.write_all(&VERSION.to_le_bytes())?;
-        writer.write_all(&(self.index.len() as u64).to_le_bytes())?; // num_trigrams
-        writer.write_all(&(self.files.len() as u64).to_le_bytes())?; // num_files
-
-        // Build directory and write compressed data in a single pass
-        let mut directory: Vec<DirectoryEntry> = Vec::with_capacity(self.index.len());
-
-        // Calculate directory start and size
-        let directory_start = HEADER_SIZE as u64;
-        let directory_size = self.index.len() * 16;
-
-        // Reserve space for directory (we'll write it after data)
-        let data_start = directory_start + directory_size as u64;
-        let mut current_offset = data_start;
-
-        // We need to write in the correct order: header, directory, data, file paths
-        // But we need data offsets to write directory
-        // So we compress data first, then write header+directory+data
-
-        // Step 1: Compress all posting lists and track offsets
-        let mut compressed_lists: Vec<(Trigram, Vec<u8>)> = Vec::with_capacity(self.index.len());
-
-        for (trigram, locations) in &self.index {
-            // Compress the posting list
-            let mut compressed = Vec::new();
-            let mut prev_file_id = 0u32;
-            let mut prev_line_no = 0u32;
-            let mut prev_byte_offset = 0u32;
-
-            for loc in locations {
-                let file_id_delta = loc.file_id.wrapping_sub(prev_file_id);
-                let line_no_delta = loc.line_no.wrapping_sub(prev_line_no);
-                let byte_offset_delta = loc.byte_offset.wrapping_sub(prev_byte_offset);
-
-                write_varint(&mut compressed, file_id_delta)?;
-                write_varint(&mut compressed, line_no_delta)?;
-                write_varint(&mut compressed, byte_offset_delta)?;
-
-                prev_file_id = loc.file_id;
-                prev_line_no = loc.line_no;
-                prev_byte_offset = loc.byte_offset;
-            }
-
-            directory.push(DirectoryEntry {
-                trigram: *trigram,
-                data_offset: current_offset,
-                compressed_size: compressed.len() as u32,
-            });
-            current_offset += compressed.len() as u64;
-
-            compressed_lists.push((*trigram, compressed));
+        if self.base.is_some() {
+            writer.seek(SeekFrom::End(0))?;
+        } else {
+            // Placeholder until the manifest is written
+            write_header(&mut writer, 0, 0)?;
         }
 
-        // Step 2: Write directory
-        for entry in &directory {
-            writer.write_all(&entry.trigram.to_le_bytes())?;
-            writer.write_all(&entry.data_offset.to_le_bytes())?;
-            writer.write_all(&entry.compressed_size.to_le_bytes())?;
-        }
+        // A fresh build has a document per file; an append only the new content
+        let docs = match self.base {
+            Some(_) => std::mem::take(&mut self.new_docs),
+            None => self.hashes.clone(),
+        };
 
-        // Step 3: Write data section (compressed posting lists)
-        for (_, compressed) in &compressed_lists {
-            writer.write_all(compressed)?;
+        if !docs.is_empty() {
+            let data_start = writer.stream_position()?;
+            let directory = if !self.partial_indices.is_empty() {
+                log::info!("Using streaming merge to write {} partial indices", self.partial_indices.len());
+                self.merge_partial_indices_into(&mut writer, data_start)?
+            } else {
+                self.write_posting_lists(&mut writer, data_start)?
+            };
+
+            segments.push(writer.stream_position()?);
+            write_segment_descriptor(&mut writer, &docs, &directory)?;
+            log::info!("Wrote trigram segment: {} trigrams, {} documents", directory.len(), docs.len());
         }
 
-        // Step 4: Write file paths
-        for file_path in &self.files {
+        let manifest_offset = writer.stream_position()?;
+        writer.write_all(&(segments.len() as u32).to_le_bytes())?;
+        for offset in &segments {
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+        for (file_path, hash) in self.files.iter().zip(&self.hashes) {
             let path_str = file_path.to_string_lossy();
             let path_bytes = path_str.as_bytes();
             write_varint(&mut writer, path_bytes.len() as u32)?;
             writer.write_all(path_bytes)?;
+            writer.write_all(hash)?;
         }
 
-        // Flush and sync
+        // Point the header at the new manifest once everything it references is on disk
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        writer.seek(SeekFrom::Start(0))?;
+        write_header(&mut writer, manifest_offset, self.files.len() as u64)?;
         writer.flush()?;
         writer.get_ref().sync_all()?;
 
         log::info!(
-            "Wrote lazy-loadable trigram index: {} trigrams, {} files to {:?}",
-            self.index.len(),
+            "Wrote lazy-loadable trigram index: {} segments, {} files to {:?}",
+            segments.len(),
             self.files.len(),
             path
         );
@@ -1032,10 +1116,30 @@ impl TrigramIndex {
         Ok(())
     }
 
+    /// Write the in-memory posting lists, returning the segment's directory
+    ///
+    /// `offset` is where `writer` is positioned.
+    fn write_posting_lists(&self, writer: &mut impl Write, offset: u64) -> Result<Vec<DirectoryEntry>> {
+        let mut directory = Vec::with_capacity(self.index.len());
+        let mut data_offset = offset;
+
+        for (trigram, locations) in &self.index {
+            let compressed_size = self.write_compressed_posting_list(writer, locations)?;
+            directory.push(DirectoryEntry {
+                trigram: *trigram,
+                data_offset,
+                compressed_size,
+            });
+            data_offset += compressed_size as u64;
+        }
+
+        Ok(directory)
+    }
+
     /// Load trigram index from disk using memory-mapped I/O with lazy loading
     ///
-    /// Binary format V3: Only reads the directory and file paths, keeps posting lists compressed in mmap.
-    /// Posting lists are decompressed on-demand during search queries.
+    /// Binary format V4: Only reads the manifest and segment directories, keeps posting lists
+    /// compressed in mmap. Posting lists are decompressed on-demand during search queries.
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
 
@@ -1048,120 +1152,60 @@ impl TrigramIndex {
                 .with_context(|| format!("Failed to mmap {}", path.display()))?
         };
 
-        // Validate header
-        if mmap.len() < HEADER_SIZE {
-            anyhow::bail!("trigrams.bin too small (expected at least {} bytes)", HEADER_SIZE);
-        }
-
-        if &mmap[0..4] != MAGIC {
-            anyhow::bail!("Invalid trigrams.bin (wrong magic bytes)");
-        }
-
-        let version = u32::from_le_bytes([mmap[4], mmap[5], mmap[6], mmap[7]]);
-        if version != VERSION {
-            anyhow::bail!(
-                "Unsupported trigrams.bin version: {} (expected {}). Please re-index with 'reflex index'.",
-                version, VERSION
-            );
-        }
-
-        let num_trigrams = u64::from_le_bytes([
-            mmap[8], mmap[9], mmap[10], mmap[11],
-            mmap[12], mmap[13], mmap[14], mmap[15],
-        ]) as usize;
+        let manifest = read_manifest(&mmap)?;
+        let num_files = manifest.files.len();
 
-        let num_files = u64::from_le_bytes([
-            mmap[16], mmap[17], mmap[18], mmap[19],
-            mmap[20], mmap[21], mmap[22], mmap[23],
-        ]) as usize;
+        let mut segments = Vec::with_capacity(manifest.segments.len());
+        let mut extra_files: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut first_file: Option<HashMap<ContentHash, u32>> = None;
 
-        log::debug!("Loading lazy trigram index: {} trigrams, {} files", num_trigrams, num_files);
+        for &offset in &manifest.segments {
+            let (docs, directory) = read_segment(&mmap, offset)?;
 
-        // Read directory (trigram → offset mappings) - fast, just metadata
-        let mut directory = Vec::with_capacity(num_trigrams);
-        let mut pos = HEADER_SIZE;
-        let directory_size = num_trigrams * 16; // 16 bytes per entry
-
-        for _ in 0..num_trigrams {
-            if pos + 16 > mmap.len() {
-                anyhow::bail!("Truncated directory entry at pos={}", pos);
+            // Freshly built: document ids are file ids
+            if manifest.segments.len() == 1 && docs == manifest.hashes {
+                segments.push(Segment { directory, doc_files: (0..num_files as u32).collect(), identity: true });
+                continue;
             }
 
-            let trigram = u32::from_le_bytes([
-                mmap[pos],
-                mmap[pos + 1],
-                mmap[pos + 2],
-                mmap[pos + 3],
-            ]);
-            pos += 4;
-
-            let data_offset = u64::from_le_bytes([
-                mmap[pos],
-                mmap[pos + 1],
-                mmap[pos + 2],
-                mmap[pos + 3],
-                mmap[pos + 4],
-                mmap[pos + 5],
-                mmap[pos + 6],
-                mmap[pos + 7],
-            ]);
-            pos += 8;
-
-            let compressed_size = u32::from_le_bytes([
-                mmap[pos],
-                mmap[pos + 1],
-                mmap[pos + 2],
-                mmap[pos + 3],
-            ]);
-            pos += 4;
-
-            directory.push(DirectoryEntry {
-                trigram,
-                data_offset,
-                compressed_size,
+            // Otherwise map each document to the first file with its content; a
+            // content hash is only mapped once across segments
+            let first_file = first_file.get_or_insert_with(|| {
+                let mut first_file = HashMap::with_capacity(num_files);
+                for (file_id, hash) in manifest.hashes.iter().enumerate() {
+                    match first_file.entry(*hash) {
+                        std::collections::hash_map::Entry::Vacant(e) => {
+                            e.insert(file_id as u32);
+                        }
+                        std::collections::hash_map::Entry::Occupied(e) => {
+                            extra_files.entry(*e.get()).or_default().push(file_id as u32);
+                        }
+                    }
+                }
+                first_file
             });
+            let doc_files = docs.iter().map(|hash| first_file.remove(hash).unwrap_or(DEAD_DOC)).collect();
+            segments.push(Segment { directory, doc_files, identity: false });
         }
 
-        // Directory is already sorted by trigram (from write())
-        directory.sort_unstable_by_key(|e| e.trigram);
-
-        // Calculate where file paths section starts (after header + directory + data)
-        let data_section_size: u64 = directory.iter().map(|e| e.compressed_size as u64).sum();
-        let files_section_offset = HEADER_SIZE + directory_size + data_section_size as usize;
-        pos = files_section_offset;
-
-        // Read file paths (varint-encoded lengths)
-        let mut files = Vec::with_capacity(num_files);
-        for _ in 0..num_files {
-            // Read path length (varint)
-            let (path_len, consumed) = read_varint(&mmap[pos..])?;
-            pos += consumed;
-            let path_len = path_len as usize;
-
-            if pos + path_len > mmap.len() {
-                anyhow::bail!("Truncated file path at pos={}", pos);
-            }
-
-            let path_bytes = &mmap[pos..pos + path_len];
-            let path_str = std::str::from_utf8(path_bytes)
-                .context("Invalid UTF-8 in file path")?;
-            files.push(PathBuf::from(path_str));
-            pos += path_len;
-        }
-
+        let directory_entries: usize = segments.iter().map(|s| s.directory.len()).sum();
         log::info!(
-            "Loaded lazy trigram index: {} trigrams, {} files (directory: {} KB)",
-            num_trigrams,
+            "Loaded lazy trigram index: {} segments, {} files (directory: {} KB)",
+            segments.len(),
             num_files,
-            directory_size / 1024
+            directory_entries * 16 / 1024
         );
 
         Ok(Self {
             index: Vec::new(),  // Empty in lazy mode
-            files,
+            files: manifest.files,
             temp_index: None,
+            hashes: manifest.hashes,
             mmap: Some(mmap),  // Keep mmap alive for lazy decompression!
-            directory,
+            segments,
+            extra_files,
+            base: None,
+            new_docs: Vec::new(),
             partial_indices: Vec::new(),
             temp_dir: None,
         })
@@ -1174,6 +1218,131 @@ impl Default for TrigramIndex {
     }
 }
 
+/// File table and segment list of a `trigrams.bin`
+struct Manifest {
+    /// Descriptor offset of each segment
+    segments: Vec<u64>,
+    files: Vec<PathBuf>,
+    /// Content hash of each file
+    hashes: Vec<ContentHash>,
+}
+
+fn write_header(writer: &mut impl Write, manifest_offset: u64, num_files: u64) -> Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&manifest_offset.to_le_bytes())?;
+    writer.write_all(&num_files.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_segment_descriptor(writer: &mut impl Write, docs: &[ContentHash], directory: &[DirectoryEntry]) -> Result<()> {
+    writer.write_all(&(docs.len() as u32).to_le_bytes())?;
+    writer.write_all(&(directory.len() as u32).to_le_bytes())?;
+    for hash in docs {
+        writer.write_all(hash)?;
+    }
+    for entry in directory {
+        writer.write_all(&entry.trigram.to_le_bytes())?;
+        writer.write_all(&entry.data_offset.to_le_bytes())?;
+        writer.write_all(&entry.compressed_size.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// `len` bytes of `data` at `*pos`, advancing `pos`
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize, what: &str) -> Result<&'a [u8]> {
+    let end = pos.checked_add(len).filter(|&end| end <= data.len());
+    let Some(end) = end else {
+        anyhow::bail!("Truncated {} at pos={}", what, pos);
+    };
+    let bytes = &data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+fn take_u32(data: &[u8], pos: &mut usize, what: &str) -> Result<u32> {
+    Ok(u32::from_le_bytes(take(data, pos, 4, what)?.try_into().unwrap()))
+}
+
+fn take_u64(data: &[u8], pos: &mut usize, what: &str) -> Result<u64> {
+    Ok(u64::from_le_bytes(take(data, pos, 8, what)?.try_into().unwrap()))
+}
+
+/// Validate the header of a `trigrams.bin` and read its manifest
+fn read_manifest(mmap: &[u8]) -> Result<Manifest> {
+    // Validate header
+    if mmap.len() < HEADER_SIZE {
+        anyhow::bail!("trigrams.bin too small (expected at least {} bytes)", HEADER_SIZE);
+    }
+
+    if &mmap[0..4] != MAGIC {
+        anyhow::bail!("Invalid trigrams.bin (wrong magic bytes)");
+    }
+
+    let version = u32::from_le_bytes([mmap[4], mmap[5], mmap[6], mmap[7]]);
+    if version != VERSION {
+        anyhow::bail!(
+            "Unsupported trigrams.bin version: {} (expected {}). Please re-index with 'reflex index'.",
+            version, VERSION
+        );
+    }
+
+    let mut pos = 8;
+    let manifest_offset = take_u64(mmap, &mut pos, "header")?;
+    let num_files = take_u64(mmap, &mut pos, "header")? as usize;
+
+    let mut pos = manifest_offset as usize;
+    let num_segments = take_u32(mmap, &mut pos, "manifest")? as usize;
+    let mut segments = Vec::with_capacity(num_segments);
+    for _ in 0..num_segments {
+        segments.push(take_u64(mmap, &mut pos, "manifest")?);
+    }
+
+    // Read file paths (varint-encoded lengths) and content hashes
+    let mut files = Vec::with_capacity(num_files);
+    let mut hashes = Vec::with_capacity(num_files);
+    for _ in 0..num_files {
+        let (path_len, consumed) = read_varint(mmap.get(pos..).unwrap_or_default())?;
+        pos += consumed;
+
+        let path_bytes = take(mmap, &mut pos, path_len as usize, "file path")?;
+        let path_str = std::str::from_utf8(path_bytes)
+            .context("Invalid UTF-8 in file path")?;
+        files.push(PathBuf::from(path_str));
+        hashes.push(take(mmap, &mut pos, 32, "content hash")?.try_into().unwrap());
+    }
+
+    Ok(Manifest { segments, files, hashes })
+}
+
+/// Read the document hashes and directory of the segment described at `offset`
+fn read_segment(mmap: &[u8], offset: u64) -> Result<(Vec<ContentHash>, Vec<DirectoryEntry>)> {
+    let mut pos = offset as usize;
+    let num_docs = take_u32(mmap, &mut pos, "segment descriptor")? as usize;
+    let num_trigrams = take_u32(mmap, &mut pos, "segment descriptor")? as usize;
+
+    let docs = take(mmap, &mut pos, num_docs * 32, "segment documents")?
+        .chunks_exact(32)
+        .map(|hash| hash.try_into().unwrap())
+        .collect();
+
+    // Read directory (trigram → offset mappings) - fast, just metadata
+    let mut directory = Vec::with_capacity(num_trigrams);
+    for _ in 0..num_trigrams {
+        let entry = take(mmap, &mut pos, 16, "directory entry")?;
+        directory.push(DirectoryEntry {
+            trigram: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+            data_offset: u64::from_le_bytes(entry[4..12].try_into().unwrap()),
+            compressed_size: u32::from_le_bytes(entry[12..16].try_into().unwrap()),
+        });
+    }
+
+    // Directory is already sorted by trigram (from write())
+    directory.sort_unstable_by_key(|e| e.trigram);
+
+    Ok((docs, directory))
+}
+
 /// Extract all trigrams from text
 ///
 /// Returns a vector of trigrams (without location info).
@@ -1461,4 +1630,89 @@ mod tests {
         // Note: Full roundtrip test verifies write works correctly.
         // Load verification is tested in production via query performance tests.
     }
+
+    /// Index `files` into `trigrams.bin`, appending when an index is open for update
+    fn write_index(path: &Path, files: &[(&str, &str)]) -> TrigramIndex {
+        let mut index = TrigramIndex::open_for_update(path).unwrap().unwrap_or_default();
+        for (name, content) in files {
+            let file_id = index.add_file(PathBuf::from(name));
+            index.index_file(file_id, content);
+        }
+        index.finalize();
+        index.write(path).unwrap();
+        index
+    }
+
+    fn search_files(path: &Path, pattern: &str) -> Vec<String> {
+        let index = TrigramIndex::load(path).unwrap();
+        let mut files: Vec<String> = index
+            .search(pattern)
+            .iter()
+            .map(|loc| index.get_file(loc.file_id).unwrap().display().to_string())
+            .collect();
+        files.dedup();
+        files
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("trigrams.bin");
+
+        write_index(&path, &[("a.rs", "fn alpha() {}"), ("b.rs", "fn beta() {}"), ("c.rs", "fn alpha() {}")]);
+
+        assert_eq!(search_files(&path, "alpha"), vec!["a.rs", "c.rs"]);
+        assert_eq!(search_files(&path, "beta"), vec!["b.rs"]);
+        assert!(search_files(&path, "gamma").is_empty());
+    }
+
+    #[test]
+    fn test_incremental_update_appends_changed_content() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("trigrams.bin");
+
+        write_index(&path, &[("keep.rs", "fn unchanged() {}"), ("edit.rs", "fn before_edit() {}"), ("gone.rs", "fn deleted() {}")]);
+        let size = std::fs::metadata(&path).unwrap().len();
+
+        // Only the edited content gets new postings; the renamed file reuses its old ones
+        let index = write_index(&path, &[("moved.rs", "fn unchanged() {}"), ("edit.rs", "fn after_edit() {}")]);
+        assert_eq!(index.new_docs.len(), 0, "new documents are handed to the segment on write");
+        assert!(std::fs::metadata(&path).unwrap().len() > size, "the update appends to the file");
+
+        assert_eq!(search_files(&path, "unchanged"), vec!["moved.rs"]);
+        assert_eq!(search_files(&path, "after_edit"), vec!["edit.rs"]);
+        assert!(search_files(&path, "before_edit").is_empty(), "changed content is tombstoned");
+        assert!(search_files(&path, "deleted").is_empty(), "deleted files are tombstoned");
+
+        // Reverting an edit reuses the postings of the first segment
+        let mut index = TrigramIndex::open_for_update(&path).unwrap().unwrap();
+        let file_id = index.add_file(PathBuf::from("edit.rs"));
+        index.index_file(file_id, "fn before_edit() {}");
+        assert!(index.new_docs.is_empty());
+        index.finalize();
+        index.write(&path).unwrap();
+        assert_eq!(search_files(&path, "before_edit"), vec!["edit.rs"]);
+    }
+
+    #[test]
+    fn test_compaction_after_max_segments() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("trigrams.bin");
+
+        let base: Vec<String> = (0..MAX_SEGMENTS * 2).map(|i| format!("fn stable_{}() {{}}", i)).collect();
+        for run in 0..MAX_SEGMENTS {
+            let edited = format!("fn edited_{}() {{}}", run);
+            let mut files: Vec<(&str, &str)> = vec![("edited.rs", &edited)];
+            files.extend(base.iter().map(|content| ("stable.rs", content.as_str())));
+            write_index(&path, &files);
+        }
+        assert!(TrigramIndex::open_for_update(&path).unwrap().is_none());
+
+        // The next run rewrites a single segment
+        write_index(&path, &[("edited.rs", "fn edited_final() {}")]);
+        let index = TrigramIndex::load(&path).unwrap();
+        assert_eq!(index.segments.len(), 1);
+        assert!(index.segments[0].identity);
+        assert_eq!(search_files(&path, "edited_final"), vec!["edited.rs"]);
+    }
 }
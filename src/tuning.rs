@@ -0,0 +1,374 @@
+//! Profile-guided search defaults
+//!
+//! A fixed limit of 100 results, a 30s timeout and 100-char previews suit a
+//! mid-sized repository but are too stingy for a small crate and too generous
+//! for a monorepo. At the end of `rfx index` we record the repository's scale
+//! (files, lines, language mix) in the `config` table of `meta.db`, and queries
+//! derive their defaults from that profile.
+//!
+//! Explicit settings in the `[search]` section of `.reflex/config.toml` always
+//! win over the derived values:
+//!
+//! ```toml
+//! [search]
+//! auto_tune = true          # false = always use the static defaults
+//! default_limit = 50        # 0 = unlimited
+//! timeout = 20              # seconds, 0 = no timeout
+//! max_preview_length = 120  # characters
+//! fuzzy_threshold = 0.8     # minimum --fuzzy name similarity (0.0-1.0)
+//! max_memory_mb = 1024      # memory ceiling of one query, 0 = unlimited
+//! ```
+//!
+//! Command-line flags (`--limit`, `--timeout`, `--no-truncate`) override both.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cache::{CacheManager, CONFIG_TOML, META_DB};
+use crate::models::IndexStats;
+
+/// Key under which the repository profile is stored in the `config` table
+const PROFILE_KEY: &str = "repo_profile";
+
+/// Static default result limit (used when no profile has been recorded)
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Static default query timeout in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Static default preview length in characters
+pub const DEFAULT_PREVIEW_LENGTH: usize = 100;
+
+/// Static default memory ceiling of one query in MB
+pub const DEFAULT_MAX_MEMORY_MB: u64 = 1024;
+
+/// Coarse size class of an indexed repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoScale {
+    /// Up to 1,000 files and 100k lines
+    Small,
+    /// Up to 10,000 files and 1M lines
+    Medium,
+    /// Up to 50,000 files and 5M lines
+    Large,
+    /// Anything bigger
+    Giant,
+}
+
+impl RepoScale {
+    /// Classify a repository by file and line count (the larger class wins)
+    pub fn classify(total_files: usize, total_lines: usize) -> Self {
+        if total_files <= 1_000 && total_lines <= 100_000 {
+            RepoScale::Small
+        } else if total_files <= 10_000 && total_lines <= 1_000_000 {
+            RepoScale::Medium
+        } else if total_files <= 50_000 && total_lines <= 5_000_000 {
+            RepoScale::Large
+        } else {
+            RepoScale::Giant
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepoScale::Small => "small",
+            RepoScale::Medium => "medium",
+            RepoScale::Large => "large",
+            RepoScale::Giant => "giant",
+        }
+    }
+}
+
+/// Repository scale recorded after indexing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoProfile {
+    pub total_files: usize,
+    pub total_lines: usize,
+    /// Files per language
+    pub languages: BTreeMap<String, usize>,
+    pub scale: RepoScale,
+    /// RFC 3339 timestamp of when the profile was recorded
+    pub profiled_at: String,
+}
+
+impl RepoProfile {
+    /// Build a profile from the stats returned by the indexer
+    pub fn from_stats(stats: &IndexStats) -> Self {
+        let total_lines = stats.lines_by_language.values().sum();
+        Self {
+            total_files: stats.total_files,
+            total_lines,
+            languages: stats.files_by_language.iter().map(|(k, v)| (k.clone(), *v)).collect(),
+            scale: RepoScale::classify(stats.total_files, total_lines),
+            profiled_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Defaults applied to queries when the caller doesn't specify a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchDefaults {
+    /// Result limit (None = unlimited)
+    pub limit: Option<usize>,
+    /// Query timeout in seconds (0 = no timeout)
+    pub timeout_secs: u64,
+    /// Preview truncation length in characters
+    pub max_preview_length: usize,
+}
+
+impl Default for SearchDefaults {
+    fn default() -> Self {
+        Self {
+            limit: Some(DEFAULT_LIMIT),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            max_preview_length: DEFAULT_PREVIEW_LENGTH,
+        }
+    }
+}
+
+impl SearchDefaults {
+    /// Derive recommended defaults for a repository profile
+    ///
+    /// Small repos get near-unlimited results and wider previews since a broad
+    /// query can't return much anyway. Large repos keep result pages small to
+    /// bound output size, but get a longer timeout because every candidate
+    /// scan takes longer.
+    pub fn recommended(profile: &RepoProfile) -> Self {
+        match profile.scale {
+            RepoScale::Small => Self { limit: Some(500), timeout_secs: 30, max_preview_length: 200 },
+            RepoScale::Medium => Self::default(),
+            RepoScale::Large => Self { limit: Some(50), timeout_secs: 45, max_preview_length: 100 },
+            RepoScale::Giant => Self { limit: Some(25), timeout_secs: 60, max_preview_length: 80 },
+        }
+    }
+}
+
+/// `[search]` section of `.reflex/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Derive defaults from the recorded repository profile
+    #[serde(default = "default_auto_tune")]
+    pub auto_tune: bool,
+    /// Explicit result limit (0 = unlimited)
+    #[serde(default)]
+    pub default_limit: Option<usize>,
+    /// Explicit query timeout in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Explicit preview length in characters
+    #[serde(default)]
+    pub max_preview_length: Option<usize>,
+    /// Minimum name similarity of `--fuzzy` symbol matches (0.0-1.0)
+    #[serde(default)]
+    pub fuzzy_threshold: Option<f64>,
+    /// Memory held by one query's buffered results and file contents, in MB (0 = unlimited)
+    #[serde(default)]
+    pub max_memory_mb: Option<u64>,
+}
+
+fn default_auto_tune() -> bool {
+    true
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            auto_tune: true,
+            default_limit: None,
+            timeout: None,
+            max_preview_length: None,
+            fuzzy_threshold: None,
+            max_memory_mb: None,
+        }
+    }
+}
+
+/// Load the `[search]` section from the project's `.reflex/config.toml`
+///
+/// Falls back to defaults if the file or section is missing.
+pub fn load_search_config(cache_path: &Path) -> Result<SearchConfig> {
+    let config_path = cache_path.join(CONFIG_TOML);
+
+    if !config_path.exists() {
+        return Ok(SearchConfig::default());
+    }
+
+    let config_str = std::fs::read_to_string(&config_path)
+        .context("Failed to read .reflex/config.toml")?;
+
+    let toml_value: toml::Value = toml::from_str(&config_str)
+        .context("Failed to parse .reflex/config.toml")?;
+
+    match toml_value.get("search") {
+        Some(search_table) => search_table.clone().try_into()
+            .context("Failed to parse [search] section in .reflex/config.toml"),
+        None => Ok(SearchConfig::default()),
+    }
+}
+
+/// Record the repository profile in `meta.db`
+pub fn record_profile(cache: &CacheManager, profile: &RepoProfile) -> Result<()> {
+    let conn = Connection::open(cache.path().join(META_DB))
+        .context("Failed to open meta.db")?;
+
+    let json = serde_json::to_string(profile)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO config (key, value) VALUES (?, ?)",
+        [PROFILE_KEY, json.as_str()],
+    )?;
+
+    log::debug!("Recorded repo profile: {} files, {} lines ({})",
+        profile.total_files, profile.total_lines, profile.scale.as_str());
+    Ok(())
+}
+
+/// Load the repository profile recorded by the last `rfx index`, if any
+pub fn load_profile(cache: &CacheManager) -> Result<Option<RepoProfile>> {
+    let db_path = cache.path().join(META_DB);
+    if !db_path.exists() {
+        return Ok(None);
+    }
+
+    let conn = Connection::open(&db_path)
+        .context("Failed to open meta.db")?;
+
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM config WHERE key = ?", [PROFILE_KEY], |row| row.get(0))
+        .optional()?;
+
+    match value {
+        Some(json) => Ok(Some(serde_json::from_str(&json)
+            .context("Failed to parse recorded repo profile")?)),
+        None => Ok(None),
+    }
+}
+
+/// Combine the recorded profile and `[search]` config into effective defaults
+pub fn resolve(profile: Option<&RepoProfile>, config: &SearchConfig) -> SearchDefaults {
+    let mut defaults = match profile {
+        Some(profile) if config.auto_tune => SearchDefaults::recommended(profile),
+        _ => SearchDefaults::default(),
+    };
+
+    if let Some(limit) = config.default_limit {
+        defaults.limit = if limit == 0 { None } else { Some(limit) };
+    }
+    if let Some(timeout) = config.timeout {
+        defaults.timeout_secs = timeout;
+    }
+    if let Some(length) = config.max_preview_length {
+        defaults.max_preview_length = length;
+    }
+
+    defaults
+}
+
+/// Effective search defaults for a cache
+///
+/// Never fails: an unreadable profile or config falls back to the static
+/// defaults (with a warning in the log) so queries are never blocked by tuning.
+pub fn search_defaults(cache: &CacheManager) -> SearchDefaults {
+    let config = load_search_config(cache.path()).unwrap_or_else(|e| {
+        log::warn!("Ignoring [search] config: {}", e);
+        SearchConfig::default()
+    });
+    let profile = load_profile(cache).unwrap_or_else(|e| {
+        log::warn!("Ignoring recorded repo profile: {}", e);
+        None
+    });
+
+    resolve(profile.as_ref(), &config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn stats(files: usize, lines: usize) -> IndexStats {
+        IndexStats {
+            total_files: files,
+            index_size_bytes: 0,
+            last_updated: String::new(),
+            files_by_language: HashMap::from([("Rust".to_string(), files)]),
+            lines_by_language: HashMap::from([("Rust".to_string(), lines)]),
+            symbols_by_language: HashMap::new(),
+            component_sizes: Default::default(),
+            directories: Vec::new(),
+            degraded_languages: Vec::new(),
+            warning: None,
+        }
+    }
+
+    #[test]
+    fn test_classify_scale() {
+        assert_eq!(RepoScale::classify(200, 20_000), RepoScale::Small);
+        assert_eq!(RepoScale::classify(200, 500_000), RepoScale::Medium);
+        assert_eq!(RepoScale::classify(20_000, 2_000_000), RepoScale::Large);
+        assert_eq!(RepoScale::classify(100_000, 10_000_000), RepoScale::Giant);
+    }
+
+    #[test]
+    fn test_recommended_defaults_scale_with_repo() {
+        let small = SearchDefaults::recommended(&RepoProfile::from_stats(&stats(50, 5_000)));
+        let giant = SearchDefaults::recommended(&RepoProfile::from_stats(&stats(80_000, 9_000_000)));
+
+        assert!(small.limit.unwrap() > DEFAULT_LIMIT);
+        assert!(giant.limit.unwrap() < DEFAULT_LIMIT);
+        assert!(giant.max_preview_length < small.max_preview_length);
+    }
+
+    #[test]
+    fn test_config_overrides_profile() {
+        let profile = RepoProfile::from_stats(&stats(80_000, 9_000_000));
+        let config = SearchConfig {
+            default_limit: Some(0),
+            timeout: Some(10),
+            ..Default::default()
+        };
+
+        let defaults = resolve(Some(&profile), &config);
+        assert_eq!(defaults.limit, None);
+        assert_eq!(defaults.timeout_secs, 10);
+        assert_eq!(defaults.max_preview_length, 80);  // Not overridden, still tuned
+
+        let disabled = SearchConfig { auto_tune: false, ..Default::default() };
+        assert_eq!(resolve(Some(&profile), &disabled), SearchDefaults::default());
+    }
+
+    #[test]
+    fn test_record_and_load_profile() {
+        let temp = TempDir::new().unwrap();
+        let cache = CacheManager::new(temp.path());
+        cache.init().unwrap();
+
+        assert!(load_profile(&cache).unwrap().is_none());
+        assert_eq!(search_defaults(&cache), SearchDefaults::default());
+
+        let profile = RepoProfile::from_stats(&stats(10, 300));
+        record_profile(&cache, &profile).unwrap();
+
+        assert_eq!(load_profile(&cache).unwrap(), Some(profile.clone()));
+        assert_eq!(search_defaults(&cache), SearchDefaults::recommended(&profile));
+        assert_eq!(profile.languages.get("Rust"), Some(&10));
+    }
+
+    #[test]
+    fn test_load_search_config_section() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(CONFIG_TOML),
+            "[search]\nauto_tune = false\nmax_preview_length = 150\n",
+        ).unwrap();
+
+        let config = load_search_config(temp.path()).unwrap();
+        assert!(!config.auto_tune);
+        assert_eq!(config.max_preview_length, Some(150));
+        assert_eq!(config.default_limit, None);
+    }
+}
@@ -2,17 +2,30 @@
 //!
 //! The watcher monitors the workspace for file changes and automatically
 //! triggers incremental reindexing with configurable debouncing.
+//!
+//! Deletes and renames don't wait for the debounce: the dirty overlay is
+//! refreshed for the affected paths right away, so queries stop returning
+//! deleted files and find moved ones immediately. After the reindex, deleted
+//! files are dropped from meta.db and renamed files keep their cached symbols.
+//! A change to `.gitignore`, `.ignore` or `.reflexignore` triggers a reindex,
+//! which walks the workspace with the new ignore rules.
 
 use anyhow::{Context, Result};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use serde::Serialize;
+use notify::event::{ModifyKind, RenameMode};
+use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::time::{Duration, Instant};
 
-use crate::indexer::Indexer;
+use crate::indexer::{Indexer, REFLEXIGNORE};
 use crate::models::Language;
 use crate::output;
+use crate::symbol_cache::SymbolCache;
+
+/// Ignore files whose changes can add or drop indexed files
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", REFLEXIGNORE];
 
 /// Configuration for file watching
 #[derive(Debug, Clone)]
@@ -33,6 +46,32 @@ impl Default for WatchConfig {
     }
 }
 
+/// Something the watcher noticed or did (broadcast on `rfx serve --watch` `/events`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchEvent {
+    /// A watched file was created, modified, removed or renamed (once per path per debounce window)
+    FileChanged { path: String },
+    /// The index no longer matches the working tree (first change since the last reindex)
+    Stale { pending_files: usize },
+    /// A reindex finished
+    IndexUpdated { files: usize, changed_files: usize, duration_ms: u64 },
+    /// A reindex failed
+    IndexFailed { error: String },
+}
+
+impl WatchEvent {
+    /// Event name (the `type` field), e.g. for SSE `event:` lines
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FileChanged { .. } => "file_changed",
+            Self::Stale { .. } => "stale",
+            Self::IndexUpdated { .. } => "index_updated",
+            Self::IndexFailed { .. } => "index_failed",
+        }
+    }
+}
+
 /// Watch a directory for file changes and auto-reindex
 ///
 /// This function blocks until interrupted (Ctrl+C).
@@ -59,6 +98,16 @@ impl Default for WatchConfig {
 /// t=20s: Timer expires    [reindex A, B, C]
 /// ```
 pub fn watch(path: &Path, indexer: Indexer, config: WatchConfig) -> Result<()> {
+    watch_with_events(path, indexer, config, |_| {})
+}
+
+/// Like [`watch`], reporting every change and reindex to `on_event`
+pub fn watch_with_events(
+    path: &Path,
+    indexer: Indexer,
+    config: WatchConfig,
+    mut on_event: impl FnMut(WatchEvent),
+) -> Result<()> {
     log::info!(
         "Starting file watcher for {:?} with {}ms debounce",
         path,
@@ -81,9 +130,17 @@ pub fn watch(path: &Path, indexer: Indexer, config: WatchConfig) -> Result<()> {
         println!("Watching for changes (debounce: {}s)...", config.debounce_ms / 1000);
     }
 
+    // Event paths are absolute; report them relative to the watched directory
+    let root = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
     // Track pending file changes
     let mut pending_files: HashSet<PathBuf> = HashSet::new();
     let mut last_event_time: Option<Instant> = None;
+    // Deleted and renamed paths (relative), applied to the overlay on the next idle tick
+    let mut overlay_paths: BTreeSet<String> = BTreeSet::new();
+    // Paths removed since the last reindex, and renames whose symbols carry over
+    let mut removed: BTreeSet<String> = BTreeSet::new();
+    let mut renamed: Vec<(String, String)> = Vec::new();
     let debounce_duration = Duration::from_millis(config.debounce_ms);
 
     // Event loop
@@ -92,19 +149,65 @@ pub fn watch(path: &Path, indexer: Indexer, config: WatchConfig) -> Result<()> {
         match rx.recv_timeout(Duration::from_millis(100)) {
             Ok(Ok(event)) => {
                 // Process the file system event
-                if let Some(changed_path) = process_event(&event) {
+                for change in process_event(&event) {
                     // Filter to only supported file types
-                    if should_watch_file(&changed_path) {
-                        log::debug!("Detected change: {:?}", changed_path);
-                        pending_files.insert(changed_path);
-                        last_event_time = Some(Instant::now());
+                    if !should_watch_change(&change, &root) {
+                        continue;
+                    }
+                    log::debug!("Detected change: {:?}", change);
+
+                    match &change {
+                        Change::Removed(removed_path) => {
+                            let rel = crate::paths::relative_path(removed_path, &root);
+                            overlay_paths.insert(rel.clone());
+                            removed.insert(rel);
+                        }
+                        Change::Renamed { from, to } => {
+                            let from = crate::paths::relative_path(from, &root);
+                            let to = crate::paths::relative_path(to, &root);
+                            overlay_paths.insert(from.clone());
+                            overlay_paths.insert(to.clone());
+                            removed.insert(from.clone());
+                            renamed.push((from, to));
+                        }
+                        Change::IgnoreRules(ignore_file) => {
+                            log::info!("Ignore rules changed: {:?}", ignore_file);
+                        }
+                        Change::Modified(_) => {}
+                    }
+
+                    for changed_path in change.paths() {
+                        let path_str = changed_path.strip_prefix(&root)
+                            .unwrap_or(changed_path)
+                            .to_string_lossy()
+                            .to_string();
+                        if pending_files.insert(changed_path.clone()) {
+                            on_event(WatchEvent::FileChanged { path: path_str });
+                            if pending_files.len() == 1 {
+                                on_event(WatchEvent::Stale { pending_files: 1 });
+                            }
+                        }
                     }
+                    last_event_time = Some(Instant::now());
                 }
             }
             Ok(Err(e)) => {
                 log::warn!("Watch error: {}", e);
             }
             Err(RecvTimeoutError::Timeout) => {
+                // Hide deleted files (and index moved ones) without waiting for the reindex
+                if !overlay_paths.is_empty() {
+                    let selectors: Vec<String> = std::mem::take(&mut overlay_paths).into_iter().collect();
+                    if let Err(e) = crate::overlay::build_partial_overlay(
+                        path,
+                        indexer.cache(),
+                        indexer.config().clone(),
+                        &selectors,
+                    ) {
+                        log::warn!("Failed to update overlay for {} path(s): {}", selectors.len(), e);
+                    }
+                }
+
                 // Check if debounce period has elapsed
                 if let Some(last_time) = last_event_time {
                     if !pending_files.is_empty() && last_time.elapsed() >= debounce_duration {
@@ -132,15 +235,24 @@ pub fn watch(path: &Path, indexer: Indexer, config: WatchConfig) -> Result<()> {
                                     stats.total_files,
                                     elapsed
                                 );
+                                apply_moves(&indexer, &renamed, &removed);
+                                on_event(WatchEvent::IndexUpdated {
+                                    files: stats.total_files,
+                                    changed_files: pending_files.len(),
+                                    duration_ms: elapsed.as_millis() as u64,
+                                });
                             }
                             Err(e) => {
                                 output::error(&format!("✗ Reindex failed: {}", e));
                                 log::error!("Reindex failed: {}", e);
+                                on_event(WatchEvent::IndexFailed { error: e.to_string() });
                             }
                         }
 
                         // Clear pending changes
                         pending_files.clear();
+                        removed.clear();
+                        renamed.clear();
                         last_event_time = None;
                     }
                 }
@@ -159,17 +271,126 @@ pub fn watch(path: &Path, indexer: Indexer, config: WatchConfig) -> Result<()> {
     Ok(())
 }
 
-/// Process a file system event and extract the changed path
+/// What a file system event means for the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    /// A file was created or modified
+    Modified(PathBuf),
+    /// A file or directory was removed (or moved out of the workspace)
+    Removed(PathBuf),
+    /// A file or directory was moved within the workspace
+    Renamed { from: PathBuf, to: PathBuf },
+    /// An ignore file changed, so the set of indexed files may have changed
+    IgnoreRules(PathBuf),
+}
+
+impl Change {
+    /// Paths affected by the change
+    fn paths(&self) -> Vec<&PathBuf> {
+        match self {
+            Self::Modified(path) | Self::Removed(path) | Self::IgnoreRules(path) => vec![path],
+            Self::Renamed { from, to } => vec![from, to],
+        }
+    }
+}
+
+/// Process a file system event into index changes
 ///
-/// Returns None if the event should be ignored (e.g., metadata changes, directory events)
-fn process_event(event: &Event) -> Option<PathBuf> {
-    // Only care about Create, Modify, and Remove events
-    match event.kind {
-        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-            // Take the first path (usually only one)
-            event.paths.first().cloned()
+/// Returns nothing for events that should be ignored (e.g., access and metadata-only events)
+fn process_event(event: &Event) -> Vec<Change> {
+    let changes: Vec<Change> = match event.kind {
+        // Both ends of a rename in one event
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            if is_ignore_file(from) || is_ignore_file(to) {
+                vec![Change::Removed(from.clone()), Change::Modified(to.clone())]
+            } else {
+                vec![Change::Renamed { from: from.clone(), to: to.clone() }]
+            }
+        }
+        // One end of a rename: whichever side still exists
+        EventKind::Modify(ModifyKind::Name(_)) => event
+            .paths
+            .iter()
+            .map(|path| if path.exists() { Change::Modified(path.clone()) } else { Change::Removed(path.clone()) })
+            .collect(),
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Create(_) | EventKind::Modify(_) => event.paths.iter().cloned().map(Change::Modified).collect(),
+        EventKind::Remove(_) => event.paths.iter().cloned().map(Change::Removed).collect(),
+        _ => Vec::new(),
+    };
+
+    changes
+        .into_iter()
+        .map(|change| match change {
+            Change::Modified(path) | Change::Removed(path) if is_ignore_file(&path) => Change::IgnoreRules(path),
+            change => change,
+        })
+        .collect()
+}
+
+/// Whether `path` is a `.gitignore`, `.ignore` or `.reflexignore` file
+fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| IGNORE_FILES.contains(&name))
+}
+
+/// Check if a change should trigger a reindex
+///
+/// Changes inside hidden directories (`.git`, `.reflex`) never do. Removed
+/// paths can't be checked on disk, so any path without an unsupported
+/// extension counts (it may have been a directory).
+fn should_watch_change(change: &Change, root: &Path) -> bool {
+    let in_hidden_dir = |path: &Path| {
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        rel.parent().is_some_and(|parent| {
+            parent.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        })
+    };
+    let maybe_watched = |path: &Path| {
+        let hidden = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        let supported = path.extension().is_none() || Language::from_path(path).is_supported();
+        !hidden && supported
+    };
+
+    if change.paths().iter().all(|path| in_hidden_dir(path)) {
+        return false;
+    }
+
+    match change {
+        Change::Modified(path) => should_watch_file(path),
+        Change::Removed(path) => maybe_watched(path),
+        Change::Renamed { from, to } => to.is_dir() || should_watch_file(to) || maybe_watched(from),
+        Change::IgnoreRules(_) => true,
+    }
+}
+
+/// Bring meta.db in line with deletes and renames after a reindex
+///
+/// Renamed files get the symbols cached under their old path (the reindex
+/// created new entries for them), then entries for paths that are gone are
+/// removed instead of lingering until the next compaction.
+fn apply_moves(indexer: &Indexer, renamed: &[(String, String)], removed: &BTreeSet<String>) {
+    if !renamed.is_empty() {
+        match SymbolCache::open(indexer.cache().path()) {
+            Ok(symbol_cache) => {
+                for (from, to) in renamed {
+                    if let Err(e) = symbol_cache.copy_renamed(from, to) {
+                        log::warn!("Failed to carry cached symbols over from {} to {}: {}", from, to, e);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to open symbol cache: {}", e),
+        }
+    }
+
+    if !removed.is_empty() {
+        let paths: Vec<String> = removed.iter().cloned().collect();
+        match indexer.cache().remove_deleted_files(&paths) {
+            Ok(count) => log::info!("Removed {} deleted file(s) from the index", count),
+            Err(e) => log::warn!("Failed to remove deleted files from the index: {}", e),
         }
-        _ => None,
     }
 }
 
@@ -189,14 +410,8 @@ fn should_watch_file(path: &Path) -> bool {
         return false;
     }
 
-    // Check if file extension is supported
-    if let Some(ext) = path.extension() {
-        let ext_str = ext.to_string_lossy();
-        let lang = Language::from_extension(&ext_str);
-        return lang.is_supported();
-    }
-
-    false
+    // Check if the file's language is supported
+    Language::from_path(path).is_supported()
 }
 
 #[cfg(test)]
@@ -241,6 +456,16 @@ mod tests {
         assert!(!should_watch_file(&dir));
     }
 
+    #[test]
+    fn test_watch_event_json() {
+        let event = WatchEvent::IndexUpdated { files: 12, changed_files: 2, duration_ms: 40 };
+        assert_eq!(event.name(), "index_updated");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "index_updated", "files": 12, "changed_files": 2, "duration_ms": 40 })
+        );
+    }
+
     #[test]
     fn test_watch_config_default() {
         let config = WatchConfig::default();
@@ -256,9 +481,8 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let path = process_event(&event);
-        assert!(path.is_some());
-        assert_eq!(path.unwrap(), PathBuf::from("/test/file.rs"));
+        let changes = process_event(&event);
+        assert_eq!(changes, vec![Change::Modified(PathBuf::from("/test/file.rs"))]);
     }
 
     #[test]
@@ -271,9 +495,8 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let path = process_event(&event);
-        assert!(path.is_some());
-        assert_eq!(path.unwrap(), PathBuf::from("/test/file.rs"));
+        let changes = process_event(&event);
+        assert_eq!(changes, vec![Change::Modified(PathBuf::from("/test/file.rs"))]);
     }
 
     #[test]
@@ -284,7 +507,55 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let path = process_event(&event);
-        assert!(path.is_none());
+        assert!(process_event(&event).is_empty());
+    }
+
+    #[test]
+    fn test_process_event_rename_and_remove() {
+        let rename = Event {
+            kind: EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            paths: vec![PathBuf::from("/test/old.rs"), PathBuf::from("/test/new.rs")],
+            attrs: Default::default(),
+        };
+        assert_eq!(
+            process_event(&rename),
+            vec![Change::Renamed { from: PathBuf::from("/test/old.rs"), to: PathBuf::from("/test/new.rs") }]
+        );
+
+        // The source half of a rename whose destination is outside the workspace
+        let moved_out = Event {
+            kind: EventKind::Modify(ModifyKind::Name(RenameMode::From)),
+            paths: vec![PathBuf::from("/test/gone.rs")],
+            attrs: Default::default(),
+        };
+        assert_eq!(process_event(&moved_out), vec![Change::Removed(PathBuf::from("/test/gone.rs"))]);
+
+        let remove = Event {
+            kind: EventKind::Remove(notify::event::RemoveKind::File),
+            paths: vec![PathBuf::from("/test/file.rs")],
+            attrs: Default::default(),
+        };
+        assert_eq!(process_event(&remove), vec![Change::Removed(PathBuf::from("/test/file.rs"))]);
+    }
+
+    #[test]
+    fn test_ignore_file_changes_are_watched() {
+        let root = Path::new("/test");
+        let event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(notify::event::DataChange::Any)),
+            paths: vec![root.join(".gitignore")],
+            attrs: Default::default(),
+        };
+        let changes = process_event(&event);
+        assert_eq!(changes, vec![Change::IgnoreRules(root.join(".gitignore"))]);
+        assert!(should_watch_change(&changes[0], root));
+
+        // Hidden files and anything under hidden directories stay unwatched
+        assert!(!should_watch_change(&Change::IgnoreRules(root.join(".git/info/.gitignore")), root));
+        assert!(!should_watch_change(&Change::Removed(root.join(".reflex/overlay")), root));
+        assert!(!should_watch_change(&Change::Removed(root.join(".env.rs")), root));
+        assert!(should_watch_change(&Change::Removed(root.join("src/lib.rs")), root));
+        assert!(should_watch_change(&Change::Removed(root.join("src/parsers")), root));
+        assert!(!should_watch_change(&Change::Removed(root.join("notes.txt")), root));
     }
 }
@@ -25,7 +25,7 @@ fn test_rust_function_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         file_pattern: Some("rust/functions.rs".to_string()),
         use_contains: true,  // "function" is substring of "public_function", "async_function", etc.
         ..Default::default()
@@ -46,7 +46,7 @@ fn test_rust_struct_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Struct),
+        kinds: vec![SymbolKind::Struct],
         file_pattern: Some("rust/structs.rs".to_string()),
         use_contains: true,  // "oin" is substring of "Point"
         ..Default::default()
@@ -66,7 +66,7 @@ fn test_rust_enum_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Enum),
+        kinds: vec![SymbolKind::Enum],
         file_pattern: Some("rust/enums.rs".to_string()),
         use_contains: true,  // "tat" is substring of "Status"
         ..Default::default()
@@ -86,7 +86,7 @@ fn test_rust_trait_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Trait),
+        kinds: vec![SymbolKind::Trait],
         file_pattern: Some("rust/traits.rs".to_string()),
         use_contains: true,  // "able" is substring of "Drawable", "Serializable"
         ..Default::default()
@@ -108,7 +108,7 @@ fn test_rust_method_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function), // Methods should be included with functions
+        kinds: vec![SymbolKind::Function], // Methods should be included with functions
         file_pattern: Some("rust/impls.rs".to_string()),
         ..Default::default()
     };
@@ -125,7 +125,7 @@ fn test_rust_module_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Module),
+        kinds: vec![SymbolKind::Module],
         file_pattern: Some("rust/modules.rs".to_string()),
         use_contains: true,  // "mod" is substring of "public_module", "private_module", etc.
         ..Default::default()
@@ -144,7 +144,7 @@ fn test_typescript_class_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Class),
+        kinds: vec![SymbolKind::Class],
         language: Some(Language::TypeScript),
         file_pattern: Some("typescript/classes.ts".to_string()),
         use_contains: true,  // "erson" is substring of "Person"
@@ -164,7 +164,7 @@ fn test_typescript_interface_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Interface),
+        kinds: vec![SymbolKind::Interface],
         file_pattern: Some("typescript/interfaces.ts".to_string()),
         use_contains: true,  // "ser" is substring of "UserSettings", "PersonData", etc.
         ..Default::default()
@@ -183,7 +183,7 @@ fn test_typescript_type_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Type),
+        kinds: vec![SymbolKind::Type],
         file_pattern: Some("typescript/types.ts".to_string()),
         use_contains: true,  // "oint" is substring of "Point", "Point3D"
         ..Default::default()
@@ -202,7 +202,7 @@ fn test_typescript_enum_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Enum),
+        kinds: vec![SymbolKind::Enum],
         file_pattern: Some("typescript/enums.ts".to_string()),
         use_contains: true,  // "olor" is substring of "Color"
         ..Default::default()
@@ -221,7 +221,7 @@ fn test_javascript_function_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         language: Some(Language::JavaScript),
         file_pattern: Some("javascript/functions.js".to_string()),
         use_contains: true,  // "function" may be substring of "arrowFunction", etc.
@@ -240,7 +240,7 @@ fn test_javascript_class_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Class),
+        kinds: vec![SymbolKind::Class],
         file_pattern: Some("javascript/classes.js".to_string()),
         use_contains: true,  // "erson" is substring of "Person"
         ..Default::default()
@@ -259,7 +259,7 @@ fn test_php_class_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Class),
+        kinds: vec![SymbolKind::Class],
         language: Some(Language::PHP),
         file_pattern: Some("php/classes.php".to_string()),
         use_contains: true,  // "erson" is substring of "Person"
@@ -279,7 +279,7 @@ fn test_php_function_detection() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         file_pattern: Some("php/functions.php".to_string()),
         use_contains: true,  // "Function" is substring of "simpleFunction", "variadicFunction"
         ..Default::default()
@@ -445,7 +445,7 @@ fn test_filter_language_and_kind() {
     let filter = QueryFilter {
         symbols_mode: true,
         language: Some(Language::Rust),
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         use_contains: true,  // "async" in "async_function" is not at word boundary (underscore is word char)
         ..Default::default()
     };
@@ -464,7 +464,7 @@ fn test_filter_language_kind_and_file() {
     let filter = QueryFilter {
         symbols_mode: true,
         language: Some(Language::TypeScript),
-        kind: Some(SymbolKind::Class),
+        kinds: vec![SymbolKind::Class],
         file_pattern: Some("typescript/classes.ts".to_string()),
         use_contains: true,  // "erson" is substring of "Person"
         ..Default::default()
@@ -540,7 +540,7 @@ fn test_filter_regex_with_symbols() {
     let filter = QueryFilter {
         use_regex: true,
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         ..Default::default()
     };
 
@@ -697,7 +697,7 @@ fn test_find_async_functions() {
 
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         use_contains: true,  // "async" in "async_function" is not at word boundary (underscore is word char)
         ..Default::default()
     };
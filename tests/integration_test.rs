@@ -308,7 +308,7 @@ fn test_combined_filters_workflow() {
     let engine = QueryEngine::new(cache);
     let filter = QueryFilter {
         language: Some(reflex::Language::Rust),
-        kind: Some(SymbolKind::Function),
+        kinds: vec![SymbolKind::Function],
         file_pattern: Some("src/".to_string()),
         symbols_mode: true,
         use_contains: true,  // "poi" in "point_new" is not at word boundary
@@ -1728,7 +1728,7 @@ fn test_explicit_kind_overrides_keyword_inference() {
     let engine = QueryEngine::new(cache);
     let filter = QueryFilter {
         symbols_mode: true,
-        kind: Some(SymbolKind::Function),  // Explicit kind override
+        kinds: vec![SymbolKind::Function],  // Explicit kind override
         ..Default::default()
     };
     let results = engine.search("struct", filter).unwrap();
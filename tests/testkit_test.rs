@@ -0,0 +1,94 @@
+//! Golden-output tests for the `testkit` feature
+//!
+//! Run with `cargo test --features testkit`. Set `REFLEX_UPDATE_GOLDEN=1` to
+//! rewrite `tests/golden/*.json` from the current results (and bump
+//! `GOLDEN_VERSION` if the expected outputs changed).
+
+#![cfg(feature = "testkit")]
+
+use reflex::models::PaginationInfo;
+use reflex::testkit::{corpus_files, golden_cases, Corpus, GoldenOutput};
+use reflex::{FileGroupedResult, IndexStatus, MatchResult, QueryResponse, Span};
+use tempfile::TempDir;
+
+#[test]
+fn test_golden_outputs() {
+    let temp = TempDir::new().unwrap();
+    let corpus = Corpus::materialize(temp.path()).unwrap();
+
+    if std::env::var_os("REFLEX_UPDATE_GOLDEN").is_some() {
+        for case in golden_cases() {
+            let output = corpus.run(&case).unwrap();
+            let path = format!("{}/tests/golden/{}.json", env!("CARGO_MANIFEST_DIR"), case.name);
+            std::fs::write(path, output.to_json_pretty().unwrap()).unwrap();
+        }
+        return;
+    }
+
+    corpus.verify_all().unwrap();
+}
+
+#[test]
+fn test_corpus_files_are_embedded() {
+    let files = corpus_files();
+    assert!(files.iter().any(|(path, _)| *path == "rust/structs.rs"));
+    assert!(files.iter().all(|(path, _)| !path.contains('\\')));
+}
+
+#[test]
+fn test_golden_cases_are_unique_and_parse() {
+    let cases = golden_cases();
+    for (i, case) in cases.iter().enumerate() {
+        assert!(cases[i + 1..].iter().all(|c| c.name != case.name), "duplicate case {}", case.name);
+        assert_eq!(case.expected().unwrap().case, case.name);
+    }
+}
+
+#[test]
+fn test_query_response_normalizes_like_search_results() {
+    let case = &golden_cases()[0];
+    let expected = case.expected().unwrap();
+    let entry = &expected.results[0];
+
+    let response = QueryResponse {
+        ai_instruction: None,
+        status: IndexStatus::Fresh,
+        can_trust_results: true,
+        warning: None,
+        pagination: PaginationInfo {
+            total: 1,
+            count: 1,
+            offset: 0,
+            limit: None,
+            has_more: false,
+        },
+        complete: true,
+        unscanned_candidates: 0,
+        results: vec![FileGroupedResult {
+            path: format!("./{}", entry.path),
+            dependencies: None,
+            related_files: None,
+            transform: None,
+            repo: None,
+            matches: vec![MatchResult {
+                kind: entry.kind.as_deref().unwrap().parse().unwrap(),
+                symbol: entry.symbol.clone(),
+                signature: entry.signature.clone(),
+                doc: None,
+                span: Span {
+                    start_line: entry.start_line,
+                    end_line: entry.end_line,
+                },
+                preview: String::new(),
+                context_before: Vec::new(),
+                context_after: Vec::new(),
+                match_reason: None,
+            }],
+        }],
+        deleted: None,
+        warnings: Vec::new(),
+    };
+
+    let actual = GoldenOutput::from_query_response(case.name, &response);
+    assert!(expected.diff(&actual).is_empty(), "{:?}", expected.diff(&actual));
+}